@@ -0,0 +1,95 @@
+// Draw throughput of the WASM build, driven by `webgl2 bench`.
+//
+// Reads `{ shaders: [{ name, vertex, fragment }], width, height, frames,
+// vertices }` as JSON on stdin and prints one result per shader:
+// `{ name, fill_mpixels_per_sec, vertex_mverts_per_sec, instantiate_us,
+// pooled_instantiate_us }`.
+//
+// Fill rate draws two full-screen triangles per frame, so every pixel runs
+// the fragment shader once. Vertex throughput draws zero-area triangles,
+// which run the vertex shader without producing fragments. Instantiation
+// times are medians over `frames` re-instantiations of the linked program,
+// with the shader instance pool emptied first or left warm.
+
+import { webGL2 } from '../index.js';
+
+async function readStdin() {
+  const chunks = [];
+  for await (const chunk of process.stdin) chunks.push(chunk);
+  return JSON.parse(Buffer.concat(chunks).toString('utf8'));
+}
+
+function createProgram(gl, { name, vertex, fragment }) {
+  const program = gl.createProgram();
+  for (const [type, source] of [[gl.VERTEX_SHADER, vertex], [gl.FRAGMENT_SHADER, fragment]]) {
+    const shader = gl.createShader(type);
+    gl.shaderSource(shader, source);
+    gl.compileShader(shader);
+    gl.attachShader(program, shader);
+  }
+  gl.linkProgram(program);
+  if (!gl.getProgramParameter(program, gl.LINK_STATUS)) {
+    throw new Error(`${name}: ${gl.getProgramInfoLog(program)}`);
+  }
+  return program;
+}
+
+function timeDraws(gl, count, frames) {
+  // One untimed draw so instantiation and first-touch costs are excluded
+  gl.drawArrays(gl.TRIANGLES, 0, count);
+  const start = process.hrtime.bigint();
+  for (let i = 0; i < frames; i++) {
+    gl.drawArrays(gl.TRIANGLES, 0, count);
+  }
+  return Number(process.hrtime.bigint() - start) / 1e9;
+}
+
+function timeInstantiation(gl, program, runs, fresh) {
+  const times = [];
+  for (let i = 0; i < runs; i++) {
+    if (fresh) gl.clearShaderPool();
+    const start = process.hrtime.bigint();
+    gl._instantiateProgramShaders(program);
+    times.push(Number(process.hrtime.bigint() - start) / 1e3);
+  }
+  times.sort((a, b) => a - b);
+  return times[times.length >> 1];
+}
+
+const { shaders, width, height, frames, vertices } = await readStdin();
+const gl = await webGL2({ size: { width, height } });
+try {
+  const fullScreen = gl.createBuffer();
+  gl.bindBuffer(gl.ARRAY_BUFFER, fullScreen);
+  gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 1, -1, -1, 1, 1, -1, 1, 1, -1, 1]), gl.STATIC_DRAW);
+
+  const degenerate = gl.createBuffer();
+  gl.bindBuffer(gl.ARRAY_BUFFER, degenerate);
+  gl.bufferData(gl.ARRAY_BUFFER, new Float32Array(vertices * 2), gl.STATIC_DRAW);
+  gl.enableVertexAttribArray(0);
+
+  const results = [];
+  for (const shader of shaders) {
+    const program = createProgram(gl, shader);
+    gl.useProgram(program);
+
+    gl.bindBuffer(gl.ARRAY_BUFFER, fullScreen);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    const fillSecs = timeDraws(gl, 6, frames);
+
+    gl.bindBuffer(gl.ARRAY_BUFFER, degenerate);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    const vertexSecs = timeDraws(gl, vertices, frames);
+
+    results.push({
+      name: shader.name,
+      fill_mpixels_per_sec: (width * height * frames) / fillSecs / 1e6,
+      vertex_mverts_per_sec: (vertices * frames) / vertexSecs / 1e6,
+      instantiate_us: timeInstantiation(gl, program, frames, true),
+      pooled_instantiate_us: timeInstantiation(gl, program, frames, false),
+    });
+  }
+  process.stdout.write(JSON.stringify(results));
+} finally {
+  gl.destroy();
+}
@@ -0,0 +1,43 @@
+//! Criterion suite for the shader compiler and rasterizer.
+//!
+//! Run with `cargo bench --bench pipeline`. `webgl2 bench` prints the same
+//! measurements as a compact report, plus draw throughput from the WASM build.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use webgl2::bench::{compile, link, FillTarget, CANONICAL_SHADERS};
+
+fn bench_compile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compile");
+    for shader in CANONICAL_SHADERS {
+        group.bench_with_input(BenchmarkId::from_parameter(shader.name), shader, |b, s| {
+            b.iter(|| compile(s).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_link(c: &mut Criterion) {
+    let mut group = c.benchmark_group("link");
+    for shader in CANONICAL_SHADERS {
+        group.bench_with_input(BenchmarkId::from_parameter(shader.name), shader, |b, s| {
+            b.iter(|| link(s).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_fill(c: &mut Criterion) {
+    let mut group = c.benchmark_group("raster_fill");
+    for (width, height) in [(320, 240), (640, 480)] {
+        let mut target = FillTarget::new(width, height);
+        group.throughput(Throughput::Elements(target.pixels()));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{width}x{height}")),
+            |b| b.iter(|| target.fill()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_compile, bench_link, bench_fill);
+criterion_main!(benches);
@@ -0,0 +1,342 @@
+// Spinning textured cube, rendered through the WebGL2 path (GLSL) and through
+// the WebGPU path (WGSL). Both go through the whole stack: shader
+// compilation, vertex buffers, texture upload, per-frame uniforms, draw and
+// readback.
+//
+//   node examples/spinning_cube.js [timeMs]   writes spinning-cube-webgl2.png and spinning-cube-webgpu.png
+//   examples/spinning_cube.html               shows both paths side by side in the browser
+//
+// test/examples/spinning_cube.test.js renders frames from here to catch
+// regressions that only show up when the modules are used together.
+
+const isNode = typeof process !== 'undefined' && process.versions && process.versions.node;
+
+const { webGL2, webGPU, GPUBufferUsage, GPUMapMode, GPUShaderStage, GPUTextureUsage } = await import(
+  isNode || location.hostname === 'localhost' || location.hostname === '127.0.0.1'
+    ? '../index.js'
+    : 'https://esm.run/webgl2'
+);
+
+/** One full turn every 5 seconds. */
+const PERIOD_MS = 5000;
+
+const GOLD = [255, 215, 0, 255];
+const CORNFLOWER_BLUE = [100, 149, 237, 255];
+
+// x, y, z, u, v per vertex, two triangles per face
+const CUBE_VERTICES = new Float32Array([
+  // Front
+  -0.5, -0.5, 0.5, 0, 0, 0.5, -0.5, 0.5, 1, 0, 0.5, 0.5, 0.5, 1, 1,
+  -0.5, -0.5, 0.5, 0, 0, 0.5, 0.5, 0.5, 1, 1, -0.5, 0.5, 0.5, 0, 1,
+  // Back
+  -0.5, -0.5, -0.5, 0, 0, -0.5, 0.5, -0.5, 0, 1, 0.5, 0.5, -0.5, 1, 1,
+  -0.5, -0.5, -0.5, 0, 0, 0.5, 0.5, -0.5, 1, 1, 0.5, -0.5, -0.5, 1, 0,
+  // Top
+  -0.5, 0.5, -0.5, 0, 0, -0.5, 0.5, 0.5, 0, 1, 0.5, 0.5, 0.5, 1, 1,
+  -0.5, 0.5, -0.5, 0, 0, 0.5, 0.5, 0.5, 1, 1, 0.5, 0.5, -0.5, 1, 0,
+  // Bottom
+  -0.5, -0.5, -0.5, 0, 0, 0.5, -0.5, -0.5, 1, 0, 0.5, -0.5, 0.5, 1, 1,
+  -0.5, -0.5, -0.5, 0, 0, 0.5, -0.5, 0.5, 1, 1, -0.5, -0.5, 0.5, 0, 1,
+  // Right
+  0.5, -0.5, -0.5, 0, 0, 0.5, 0.5, -0.5, 0, 1, 0.5, 0.5, 0.5, 1, 1,
+  0.5, -0.5, -0.5, 0, 0, 0.5, 0.5, 0.5, 1, 1, 0.5, -0.5, 0.5, 1, 0,
+  // Left
+  -0.5, -0.5, -0.5, 0, 0, -0.5, -0.5, 0.5, 1, 0, -0.5, 0.5, 0.5, 1, 1,
+  -0.5, -0.5, -0.5, 0, 0, -0.5, 0.5, 0.5, 1, 1, -0.5, 0.5, -0.5, 0, 1,
+]);
+
+/** 16x16 checkerboard of 4x4 gold and cornflower blue squares. */
+function checkerboard() {
+  const texels = new Uint8Array(16 * 16 * 4);
+  for (let y = 0; y < 16; y++) {
+    for (let x = 0; x < 16; x++) {
+      texels.set(((x >> 2) ^ (y >> 2)) & 1 ? GOLD : CORNFLOWER_BLUE, (y * 16 + x) * 4);
+    }
+  }
+  return texels;
+}
+
+// Column-major 4x4 matrices, as both uniform paths expect them
+function multiply(a, b) {
+  const out = new Float32Array(16);
+  for (let col = 0; col < 4; col++) {
+    for (let row = 0; row < 4; row++) {
+      let sum = 0;
+      for (let k = 0; k < 4; k++) {
+        sum += a[k * 4 + row] * b[col * 4 + k];
+      }
+      out[col * 4 + row] = sum;
+    }
+  }
+  return out;
+}
+
+/** Model-view-projection matrix of the cube `timeMs` into the animation. */
+export function cubeMvp(timeMs, aspect) {
+  const f = 1 / Math.tan(Math.PI / 8);
+  const near = 0.1;
+  const far = 100;
+  const nf = 1 / (near - far);
+  const angle = 0.8 + (timeMs / PERIOD_MS) * Math.PI * 2;
+  const [cx, sx, cy, sy] = [Math.cos(0.5), Math.sin(0.5), Math.cos(angle), Math.sin(angle)];
+
+  const projection = [f / aspect, 0, 0, 0, 0, f, 0, 0, 0, 0, (far + near) * nf, -1, 0, 0, 2 * far * near * nf, 0];
+  const view = [1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, -3, 1];
+  const rotateX = [1, 0, 0, 0, 0, cx, sx, 0, 0, -sx, cx, 0, 0, 0, 0, 1];
+  const rotateY = [cy, 0, -sy, 0, 0, 1, 0, 0, sy, 0, cy, 0, 0, 0, 0, 1];
+  return multiply(multiply(multiply(projection, view), rotateX), rotateY);
+}
+
+/**
+ * Set up the cube on the WebGL2 path. `render(timeMs)` draws one frame and
+ * returns it as top-down RGBA.
+ *
+ * @param {{ width?: number, height?: number }} [options]
+ * @returns {Promise<{ render: (timeMs: number) => Promise<Uint8Array>, destroy: () => void }>}
+ */
+export async function createWebGL2Cube({ width = 640, height = 480 } = {}) {
+  const gl = await webGL2({ size: { width, height } });
+  const compile = (type, source) => {
+    const shader = gl.createShader(type);
+    gl.shaderSource(shader, source);
+    gl.compileShader(shader);
+    if (!gl.getShaderParameter(shader, gl.COMPILE_STATUS)) {
+      throw new Error(gl.getShaderInfoLog(shader));
+    }
+    return shader;
+  };
+  const program = gl.createProgram();
+  gl.attachShader(program, compile(gl.VERTEX_SHADER, `#version 300 es
+    layout(location = 0) in vec3 position;
+    layout(location = 1) in vec2 uv;
+    uniform mat4 u_mvp;
+    out vec2 v_uv;
+    void main() {
+      v_uv = uv;
+      gl_Position = u_mvp * vec4(position, 1.0);
+    }`));
+  gl.attachShader(program, compile(gl.FRAGMENT_SHADER, `#version 300 es
+    precision highp float;
+    uniform sampler2D u_texture;
+    in vec2 v_uv;
+    out vec4 color;
+    void main() {
+      color = texture(u_texture, v_uv);
+    }`));
+  gl.linkProgram(program);
+  if (!gl.getProgramParameter(program, gl.LINK_STATUS)) {
+    throw new Error(gl.getProgramInfoLog(program));
+  }
+  gl.useProgram(program);
+
+  gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+  gl.bufferData(gl.ARRAY_BUFFER, CUBE_VERTICES, gl.STATIC_DRAW);
+  gl.enableVertexAttribArray(0);
+  gl.vertexAttribPointer(0, 3, gl.FLOAT, false, 20, 0);
+  gl.enableVertexAttribArray(1);
+  gl.vertexAttribPointer(1, 2, gl.FLOAT, false, 20, 12);
+
+  gl.bindTexture(gl.TEXTURE_2D, gl.createTexture());
+  gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, 16, 16, 0, gl.RGBA, gl.UNSIGNED_BYTE, checkerboard());
+  gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, gl.NEAREST);
+  gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MAG_FILTER, gl.NEAREST);
+  gl.uniform1i(gl.getUniformLocation(program, 'u_texture'), 0);
+
+  gl.viewport(0, 0, width, height);
+  gl.enable(gl.DEPTH_TEST);
+  gl.clearColor(0, 0, 0, 0);
+  const mvpLoc = gl.getUniformLocation(program, 'u_mvp');
+
+  const render = async (time) => {
+    gl.uniformMatrix4fv(mvpLoc, false, cubeMvp(time, width / height));
+    gl.clear(gl.COLOR_BUFFER_BIT | gl.DEPTH_BUFFER_BIT);
+    gl.drawArrays(gl.TRIANGLES, 0, 36);
+
+    const pixels = new Uint8Array(width * height * 4);
+    gl.readPixels(0, 0, width, height, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+    // readPixels is bottom-up
+    const image = new Uint8Array(pixels.length);
+    for (let y = 0; y < height; y++) {
+      image.set(pixels.subarray((height - 1 - y) * width * 4, (height - y) * width * 4), y * width * 4);
+    }
+    return image;
+  };
+  return { render, destroy: () => gl.destroy() };
+}
+
+/**
+ * Set up the cube on the WebGPU path. `render(timeMs)` draws one frame and
+ * returns it as top-down RGBA.
+ *
+ * @param {{ width?: number, height?: number }} [options]
+ * @returns {Promise<{ render: (timeMs: number) => Promise<Uint8Array>, destroy: () => void }>}
+ */
+export async function createWebGPUCube({ width = 640, height = 480 } = {}) {
+  const gpu = await webGPU();
+  const adapter = await gpu.requestAdapter();
+  const device = await adapter.requestDevice();
+  const module = device.createShaderModule({
+    code: `
+      struct Uniforms {
+        mvp: mat4x4<f32>,
+      };
+      @group(0) @binding(0) var<uniform> uniforms: Uniforms;
+      @group(0) @binding(1) var t_diffuse: texture_2d<f32>;
+      @group(0) @binding(2) var s_diffuse: sampler;
+
+      struct VertexOutput {
+        @builtin(position) clip_position: vec4<f32>,
+        @location(0) uv: vec2<f32>,
+      };
+
+      @vertex
+      fn vs_main(@location(0) position: vec3<f32>, @location(1) uv: vec2<f32>) -> VertexOutput {
+        var out: VertexOutput;
+        out.uv = uv;
+        out.clip_position = uniforms.mvp * vec4<f32>(position, 1.0);
+        return out;
+      }
+
+      @fragment
+      fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+        return textureSample(t_diffuse, s_diffuse, in.uv);
+      }
+    `,
+  });
+
+  const bindGroupLayout = device.createBindGroupLayout({
+    entries: [
+      { binding: 0, visibility: GPUShaderStage.VERTEX, buffer: {} },
+      { binding: 1, visibility: GPUShaderStage.FRAGMENT, texture: {} },
+      { binding: 2, visibility: GPUShaderStage.FRAGMENT, sampler: {} },
+    ],
+  });
+  const pipeline = device.createRenderPipeline({
+    layout: device.createPipelineLayout({ bindGroupLayouts: [bindGroupLayout] }),
+    vertex: {
+      module,
+      entryPoint: 'vs_main',
+      buffers: [{
+        arrayStride: 20,
+        attributes: [
+          { shaderLocation: 0, offset: 0, format: 'float32x3' },
+          { shaderLocation: 1, offset: 12, format: 'float32x2' },
+        ],
+      }],
+    },
+    fragment: { module, entryPoint: 'fs_main', targets: [{ format: 'rgba8unorm' }] },
+    primitive: { topology: 'triangle-list' },
+    depthStencil: { format: 'depth24plus', depthWriteEnabled: true, depthCompare: 'less' },
+  });
+
+  const vertexBuffer = device.createBuffer({
+    size: CUBE_VERTICES.byteLength,
+    usage: GPUBufferUsage.VERTEX | GPUBufferUsage.COPY_DST,
+  });
+  device.queue.writeBuffer(vertexBuffer, 0, CUBE_VERTICES);
+  const uniformBuffer = device.createBuffer({ size: 64, usage: GPUBufferUsage.UNIFORM | GPUBufferUsage.COPY_DST });
+
+  const texture = device.createTexture({
+    size: [16, 16],
+    format: 'rgba8unorm',
+    usage: GPUTextureUsage.TEXTURE_BINDING | GPUTextureUsage.COPY_DST,
+  });
+  device.queue.writeTexture({ texture }, checkerboard(), { bytesPerRow: 64 }, [16, 16]);
+  const sampler = device.createSampler({ magFilter: 'nearest', minFilter: 'nearest' });
+
+  const bindGroup = device.createBindGroup({
+    layout: bindGroupLayout,
+    entries: [
+      { binding: 0, resource: { buffer: uniformBuffer } },
+      { binding: 1, resource: texture.createView() },
+      { binding: 2, resource: sampler },
+    ],
+  });
+
+  const target = device.createTexture({
+    size: [width, height],
+    format: 'rgba8unorm',
+    usage: GPUTextureUsage.RENDER_ATTACHMENT | GPUTextureUsage.COPY_SRC,
+  });
+  const depth = device.createTexture({
+    size: [width, height],
+    format: 'depth24plus',
+    usage: GPUTextureUsage.RENDER_ATTACHMENT,
+  });
+  const bytesPerRow = Math.ceil((width * 4) / 256) * 256;
+  const readback = device.createBuffer({
+    size: bytesPerRow * height,
+    usage: GPUBufferUsage.COPY_DST | GPUBufferUsage.MAP_READ,
+  });
+
+  const render = async (time) => {
+    device.queue.writeBuffer(uniformBuffer, 0, cubeMvp(time, width / height));
+
+    const encoder = device.createCommandEncoder();
+    const pass = encoder.beginRenderPass({
+      colorAttachments: [{
+        view: target.createView(),
+        clearValue: { r: 0, g: 0, b: 0, a: 0 },
+        loadOp: 'clear',
+        storeOp: 'store',
+      }],
+      depthStencilAttachment: {
+        view: depth.createView(),
+        depthClearValue: 1,
+        depthLoadOp: 'clear',
+        depthStoreOp: 'store',
+      },
+    });
+    pass.setPipeline(pipeline);
+    pass.setBindGroup(0, bindGroup);
+    pass.setVertexBuffer(0, vertexBuffer);
+    pass.draw(36);
+    pass.end();
+    encoder.copyTextureToBuffer({ texture: target }, { buffer: readback, bytesPerRow }, [width, height]);
+    device.queue.submit([encoder.finish()]);
+
+    await readback.mapAsync(GPUMapMode.READ);
+    const rows = new Uint8Array(readback.getMappedRange());
+    const image = new Uint8Array(width * height * 4);
+    for (let y = 0; y < height; y++) {
+      image.set(rows.subarray(y * bytesPerRow, y * bytesPerRow + width * 4), y * width * 4);
+    }
+    readback.unmap();
+    return image;
+  };
+  return { render, destroy: () => device.destroy() };
+}
+
+if (isNode && import.meta.url === (await import('node:url')).pathToFileURL(process.argv[1]).href) {
+  const fs = await import('node:fs');
+  const { PNG } = await import('pngjs');
+  const time = Number(process.argv[2] ?? 0);
+  const width = 640;
+  const height = 480;
+
+  for (const [name, create] of [['webgl2', createWebGL2Cube], ['webgpu', createWebGPUCube]]) {
+    const cube = await create({ width, height });
+    const image = await cube.render(time);
+    cube.destroy();
+    const png = new PNG({ width, height });
+    png.data.set(image);
+    fs.writeFileSync(`spinning-cube-${name}.png`, PNG.sync.write(png));
+    console.log(`Saved spinning-cube-${name}.png`);
+  }
+} else if (!isNode) {
+  // Browser: animate both paths on the canvases of spinning_cube.html
+  const views = [];
+  for (const [id, create] of [['webgl2-canvas', createWebGL2Cube], ['webgpu-canvas', createWebGPUCube]]) {
+    const canvas = document.getElementById(id);
+    views.push({ canvas, cube: await create({ width: canvas.width, height: canvas.height }) });
+  }
+  const start = performance.now();
+  const frame = async () => {
+    for (const { canvas, cube } of views) {
+      const image = await cube.render(performance.now() - start);
+      canvas.getContext('2d').putImageData(new ImageData(new Uint8ClampedArray(image.buffer), canvas.width, canvas.height), 0, 0);
+    }
+    requestAnimationFrame(frame);
+  };
+  frame();
+}
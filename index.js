@@ -7,17 +7,19 @@ import {
   readErrorMessage,
   getShaderModule,
   getShaderWat,
+  getShaderIr,
   getShaderGlsl,
   decompileWasmToGlsl
 } from './src/webgl2_context.js';
 import { GPU, GPUBufferUsage, GPUMapMode, GPUTextureUsage, GPUShaderStage } from './src/webgpu_context.js';
+import { startTrace, replayTrace, readDrawFramebuffer, hashPixels } from './src/webgl2_trace.js';
 
 export const debug = {
   getLcovReport,
   resetLcovReport
 };
 
-export { ERR_OK, ERR_INVALID_HANDLE, GPUBufferUsage, GPUMapMode, GPUTextureUsage, GPUShaderStage, getShaderModule, getShaderWat, getShaderGlsl, decompileWasmToGlsl };
+export { ERR_OK, ERR_INVALID_HANDLE, GPUBufferUsage, GPUMapMode, GPUTextureUsage, GPUShaderStage, getShaderModule, getShaderWat, getShaderIr, getShaderGlsl, decompileWasmToGlsl, startTrace, replayTrace, readDrawFramebuffer, hashPixels };
 
 /**
  * Simple allocator for function table indices.
@@ -74,17 +76,53 @@ const isNode =
  * This function:
  * 1. Auto-loads webgl2.wasm (expects it next to index2.js)
  * 2. Instantiates the WASM module with memory
- * 3. Creates a Rust-owned context via wasm_create_context_with_flags(flags)
+ * 3. Creates a Rust-owned context via wasm_create_context_with_attributes(flags, attributes)
  * 4. Returns a WasmWebGL2RenderingContext JS wrapper
  *
  * @param {{
  *  debug?: boolean | 'shaders' | 'rust' | 'all',
+ *  coverage?: boolean,
+ *  profile?: boolean,
+ *  validateWasm?: boolean,
+ *  webgl1?: boolean,
+ *  glsl100Compat?: boolean,
+ *  textureValidation?: 'lenient' | 'spec' | 'strict',
+ *  depthPrecision?: 'float32' | 'fixed24',
+ *  readOrigin?: 'bottom-left' | 'top-left',
+ *  attributes?: WebGLContextAttributes,
+ *  gpuCostModel?: { drawNs?: number, vertexNs?: number, fragmentNs?: number },
+ *  limits?: { maxTextureSize?: number, maxVertexAttribs?: number, maxVaryingVectors?: number,
+ *    maxUniformVectors?: number, maxDrawBuffers?: number },
+ *  gpuClock?: 'virtual' | 'cpu',
+ *  shaderCache?: ShaderCache,
  *  size?: { width: number, height: number },
- * }} [opts] - options
+ * }} [opts] - options; `webgl1` restricts the context to the WebGL1 API and
+ *   GLSL ES 1.00 shaders; `glsl100Compat` lets a WebGL2 context compile
+ *   shaders without `#version 300 es` as GLSL ES 1.00 (`attribute`,
+ *   `varying`, `texture2D`, `gl_FragColor`), with a warning in the info log.
+ *   `attributes` configures the default framebuffer as
+ *   in `getContext('webgl2', attributes)`. `validateWasm` (default: the
+ *   WEBGL2_VALIDATE_WASM environment variable) validates every generated shader
+ *   module with wasmparser at link time and reports failures in the program
+ *   info log. `textureValidation` selects how draws
+ *   treat samplers of incomplete textures: 'lenient' samples them anyway, 'spec'
+ *   samples them as (0, 0, 0, 1) as WebGL does, 'strict' fails the draw.
+ *   `depthPrecision` selects how the default depth buffer stores depth:
+ *   'float32' (default) keeps full float precision, 'fixed24' rounds to the
+ *   24-bit grid of typical GPU depth buffers.
+ *   `readOrigin` selects the origin of `readPixels`: 'bottom-left' (default)
+ *   as in WebGL, or 'top-left', where `y` counts down from the top and rows
+ *   come back top first, as image files store them. Rendering is unaffected.
+ *   `gpuCostModel` assigns draws virtual GPU time, which timer queries measure
+ *   (see `setGpuCostModel`). `gpuClock: 'cpu'` makes timer queries measure
+ *   real CPU time instead (see `setGpuClock`). `shaderCache` installs a persistent store of
+ *   compiled shader WASM (see `setShaderCache`). `limits` lowers the
+ *   implementation limits the context reports and enforces, so code paths for
+ *   small GPUs can be tested; missing limits keep the emulator's maximums
  * @returns {Promise<WasmWebGL2RenderingContext>}
  * @throws {Error} if WASM loading or instantiation fails
  */
-export async function webGL2({ debug = (typeof process !== 'undefined' ? process?.env || {} : typeof window !== 'undefined' ? window : globalThis).WEBGL2_DEBUG === 'true', size } = {}) {
+export async function webGL2({ debug = (typeof process !== 'undefined' ? process?.env || {} : typeof window !== 'undefined' ? window : globalThis).WEBGL2_DEBUG === 'true', coverage = false, profile = false, validateWasm = (typeof process !== 'undefined' ? process?.env || {} : typeof window !== 'undefined' ? window : globalThis).WEBGL2_VALIDATE_WASM === 'true', webgl1 = false, glsl100Compat = false, textureValidation = 'lenient', depthPrecision = 'float32', readOrigin = 'bottom-left', attributes = {}, gpuCostModel, limits, gpuClock, shaderCache, size } = {}) {
   // Determine if we need the debug WASM binary (Rust symbols)
   const useDebugWasm = debug === true || debug === 'rust' || debug === 'all';
 
@@ -102,6 +140,10 @@ export async function webGL2({ debug = (typeof process !== 'undefined' ? process
   }
   const { ex, instance, sharedTable, tableAllocator, turboGlobals } = await promise;
 
+  if (shaderCache) {
+    setShaderCache(shaderCache);
+  }
+
   // Initialize coverage if available
   if (ex.wasm_init_coverage && ex.COV_MAP_PTR) {
     const mapPtr = ex.COV_MAP_PTR.value;
@@ -115,19 +157,44 @@ export async function webGL2({ debug = (typeof process !== 'undefined' ? process
   // Determine debug flags for creation
   const debugShaders = debug === true || debug === 'shaders' || debug === 'all';
   const debugRust = debug === true || debug === 'rust' || debug === 'all';
-  const flags = (debugShaders ? 1 : 0); // only shader debug encoded in flags
+  // bit0 = shader debug, bit1 = shader basic block coverage, bit2 = shader line profiling,
+  // bit3 = WebGL1 compatibility, bits4-5 = texture validation level,
+  // bit6 = shader WASM validation, bit7 = 24-bit fixed-point default depth buffer,
+  // bit8 = top-left readback origin
+  const validationLevel = { lenient: 0, spec: 1, strict: 2 }[textureValidation];
+  if (validationLevel === undefined) {
+    throw new Error(`Unknown textureValidation '${textureValidation}'`);
+  }
+  if (depthPrecision !== 'float32' && depthPrecision !== 'fixed24') {
+    throw new Error(`Unknown depthPrecision '${depthPrecision}'`);
+  }
+  if (readOrigin !== 'bottom-left' && readOrigin !== 'top-left') {
+    throw new Error(`Unknown readOrigin '${readOrigin}'`);
+  }
+  const flags = (debugShaders ? 1 : 0) | (coverage ? 2 : 0) | (profile ? 4 : 0) | (webgl1 ? 8 : 0) |
+    (validationLevel << 4) | (validateWasm ? 64 : 0) | (depthPrecision === 'fixed24' ? 128 : 0) |
+    (readOrigin === 'top-left' ? 256 : 0) | (glsl100Compat ? 512 : 0);
 
   // Default size to 640x480 if not provided
   const width = size?.width ?? 640;
   const height = size?.height ?? 480;
 
   // Create a context in WASM using the flags-aware API (mandatory)
-  const ctxHandle = ex.wasm_create_context_with_flags(flags, width, height);
+  const ctxHandle = ex.wasm_create_context_with_attributes(flags, packContextAttributes(attributes), width, height);
 
   if (ctxHandle === 0) {
     const msg = readErrorMessage(instance);
     throw new Error(`Failed to create context: ${msg}`);
   }
+  if (limits) {
+    const code = ex.wasm_ctx_set_limits(ctxHandle, limits.maxTextureSize ?? 0, limits.maxVertexAttribs ?? 0,
+      limits.maxVaryingVectors ?? 0, limits.maxUniformVectors ?? 0, limits.maxDrawBuffers ?? 0);
+    if (code !== 0) {
+      const msg = readErrorMessage(instance);
+      ex.wasm_destroy_context(ctxHandle);
+      throw new Error(`Failed to create context: ${msg}`);
+    }
+  }
 
   // Wrap and return, pass debug booleans to the JS wrapper
   const gl = new WasmWebGL2RenderingContext({
@@ -136,6 +203,7 @@ export async function webGL2({ debug = (typeof process !== 'undefined' ? process
     width,
     height,
     debugShaders: !!debugShaders,
+    webgl1: !!webgl1,
     sharedTable,
     tableAllocator,
     turboGlobals
@@ -146,9 +214,35 @@ export async function webGL2({ debug = (typeof process !== 'undefined' ? process
     gl.viewport(0, 0, size.width, size.height);
   }
 
+  if (gpuCostModel) {
+    gl.setGpuCostModel(gpuCostModel);
+  }
+  if (gpuClock) {
+    gl.setGpuClock(gpuClock);
+  }
+
   return gl;
 }
 
+/**
+ * Pack context attributes one bit each, in the layout of
+ * wasm_create_context_with_attributes. Missing attributes take the WebGL defaults.
+ *
+ * @param {WebGLContextAttributes} attributes
+ * @returns {number}
+ */
+function packContextAttributes({
+  alpha = true,
+  depth = true,
+  stencil = false,
+  antialias = true,
+  premultipliedAlpha = true,
+  preserveDrawingBuffer = false,
+}) {
+  return (alpha ? 1 : 0) | (depth ? 2 : 0) | (stencil ? 4 : 0) | (antialias ? 8 : 0) |
+    (premultipliedAlpha ? 16 : 0) | (preserveDrawingBuffer ? 32 : 0);
+}
+
 /**
  * Factory function: create a new WebGPU instance.
  *
@@ -182,6 +276,48 @@ export async function webGPU({ debug = (typeof process !== 'undefined' ? process
  */
 const wasmCache = new Map();
 
+/**
+ * A store of compiled shader WASM keyed by a hash of the shader source and
+ * program interface. `get` is called while linking, so it must answer
+ * synchronously; asynchronous backends preload their entries.
+ * @typedef {{
+ *  get(key: string): Uint8Array | undefined,
+ *  put(key: string, bytes: Uint8Array): void,
+ * }} ShaderCache
+ */
+
+/** @type {ShaderCache | null} */
+let activeShaderCache = null;
+
+/**
+ * Persist compiled shader WASM across sessions: linking looks each stage up
+ * in `cache` before compiling it and stores what it compiles. The cache is
+ * shared by all contexts; pass null to stop using it. Shaders compiled for
+ * debugging, coverage or profiling are never cached.
+ * @param {ShaderCache | null} cache
+ */
+export function setShaderCache(cache) {
+  activeShaderCache = cache || null;
+}
+
+/**
+ * A shader cache keeping one file per entry in `dir` (Node.js only). For
+ * IndexedDB in the browser, see the `IndexedDBShaderCache` harness served at
+ * /api/harness/shader-cache.ts by `webgl2 serve`.
+ * @param {string} dir
+ * @returns {Promise<ShaderCache>}
+ */
+export async function fileShaderCache(dir) {
+  const fs = await import('fs');
+  const path = await import('path');
+  fs.mkdirSync(dir, { recursive: true });
+  const file = (key) => path.join(dir, `${key}.wasm`);
+  return {
+    get: (key) => (fs.existsSync(file(key)) ? new Uint8Array(fs.readFileSync(file(key))) : undefined),
+    put: (key, bytes) => fs.writeFileSync(file(key), bytes),
+  };
+}
+
 /**
  * @param {{ debug?: boolean }} [options]
  */
@@ -293,6 +429,7 @@ async function initWASM({ debug } = {}) {
         env.gl_debug4 = instance.exports.gl_debug4;
         env.gl_inverse_mat2 = instance.exports.gl_inverse_mat2;
         env.gl_inverse_mat3 = instance.exports.gl_inverse_mat3;
+        env.webgpu_texture_sample = instance.exports.webgpu_texture_sample;
 
         const shaderInstance = new WebAssembly.Instance(shaderModule, { env });
         if (shaderInstance.exports.main) {
@@ -303,6 +440,39 @@ async function initWASM({ debug } = {}) {
       wasm_release_shader_index: (idx) => {
         tableAllocator.free(idx);
       },
+      // Copies the entry only if it fits; returns its length, or -1 on a miss
+      shader_cache_get: (keyPtr, keyLen, dstPtr, dstLen) => {
+        if (!activeShaderCache) return -1;
+        const mem = new Uint8Array(instance.exports.memory.buffer);
+        const key = new TextDecoder('utf-8').decode(mem.subarray(keyPtr, keyPtr + keyLen));
+        const bytes = activeShaderCache.get(key);
+        if (!bytes) return -1;
+        if (bytes.length <= dstLen) mem.set(bytes, dstPtr);
+        return bytes.length;
+      },
+      shader_cache_put: (keyPtr, keyLen, dataPtr, dataLen) => {
+        if (!activeShaderCache) return;
+        const mem = new Uint8Array(instance.exports.memory.buffer);
+        const key = new TextDecoder('utf-8').decode(mem.subarray(keyPtr, keyPtr + keyLen));
+        activeShaderCache.put(key, mem.slice(dataPtr, dataPtr + dataLen));
+      },
+      // Pipeline-stage hooks installed with setPipelineHooks. Varying
+      // locations start at word 8 of the buffer, after gl_Position and the
+      // other built-ins. Only `false` discards.
+      pipeline_hook_vertex: (ctx, vertexId, instanceId, varyingsPtr, varyingsLen) => {
+        const hooks = WasmWebGL2RenderingContext._contexts.get(ctx)?._pipelineHooks;
+        if (!hooks?.vertex) return 1;
+        const words = new Float32Array(instance.exports.memory.buffer, varyingsPtr, varyingsLen);
+        const vertex = { vertexId, instanceId, position: Array.from(words.subarray(0, 4)), varyings: words.slice(8) };
+        return hooks.vertex(vertex) === false ? 0 : 1;
+      },
+      pipeline_hook_fragment: (ctx, x, y, depth, frontFacing, varyingsPtr, varyingsLen) => {
+        const hooks = WasmWebGL2RenderingContext._contexts.get(ctx)?._pipelineHooks;
+        if (!hooks?.fragment) return 1;
+        const words = new Float32Array(instance.exports.memory.buffer, varyingsPtr, varyingsLen);
+        const fragment = { x, y, depth, frontFacing: frontFacing !== 0, varyings: words.slice(8) };
+        return hooks.fragment(fragment) === false ? 0 : 1;
+      },
       wasm_sync_turbo_globals: (attr, uniform, varying, private_, texture, frame_sp) => {
         try {
           turboGlobals.ACTIVE_ATTR_PTR.value = attr >>> 0;
@@ -316,17 +486,11 @@ async function initWASM({ debug } = {}) {
           console.warn('wasm_sync_turbo_globals failed to set globals', e);
         }
       },
-      dispatch_uncaptured_error: (ptr, len) => {
-        const mem = new Uint8Array(instance.exports.memory.buffer);
-        const bytes = mem.subarray(ptr, ptr + len);
-        const msg = new TextDecoder('utf-8').decode(bytes);
-        if (typeof GPU !== 'undefined' && typeof GPU.dispatchUncapturedError === 'function') {
-          GPU.dispatchUncapturedError(msg);
-        } else {
-          console.error("GPU.dispatchUncapturedError not available", msg);
-        }
+      dispatch_device_events: () => {
+        GPU.dispatchDeviceEvents(instance.exports, instance.exports.memory);
       },
-      // Required by egg crate for timing measurements
+      // Monotonic host clock in milliseconds, for egg's timing measurements
+      // and the CPU clock of the GPU timeline
       now: () => {
         return performance.now();
       }
@@ -350,8 +514,8 @@ async function initWASM({ debug } = {}) {
 
   // Verify required exports
   const ex = instance.exports;
-  if (typeof ex.wasm_create_context_with_flags !== 'function') {
-    throw new Error('WASM module missing wasm_create_context_with_flags export');
+  if (typeof ex.wasm_create_context_with_attributes !== 'function') {
+    throw new Error('WASM module missing wasm_create_context_with_attributes export');
   }
   if (!(ex.memory instanceof WebAssembly.Memory)) {
     throw new Error('WASM module missing memory export');
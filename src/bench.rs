@@ -0,0 +1,189 @@
+//! Canonical shaders and pipeline stages for performance measurement
+//!
+//! Shared by the criterion suite (`benches/pipeline.rs`) and the
+//! `webgl2 bench` command so both time exactly the same work. Compile and link
+//! run natively through the context API; drawing executes shader WASM and is
+//! measured by `bench/draw.mjs` under Node with the same shader sources.
+
+use crate::wasm_gl_emu::{GpuKernel, OwnedFramebuffer, Rasterizer};
+use crate::webgl2_context::registry::{
+    create_context_with_flags, destroy_context, get_registry, with_context,
+};
+use crate::webgl2_context::types::{GL_FRAGMENT_SHADER, GL_VERTEX_SHADER};
+use crate::webgl2_context::{
+    ctx_attach_shader, ctx_compile_shader, ctx_create_program, ctx_create_shader, ctx_link_program,
+};
+use serde::Serialize;
+
+/// A vertex/fragment pair exercising one part of the pipeline.
+///
+/// Every vertex shader takes a clip-space `vec2` at attribute location 0,
+/// so a single full-screen vertex buffer drives all of them.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CanonicalShader {
+    pub name: &'static str,
+    pub vertex: &'static str,
+    pub fragment: &'static str,
+}
+
+/// Shaders measured by `webgl2 bench`, from cheapest to most expensive.
+pub const CANONICAL_SHADERS: &[CanonicalShader] = &[
+    CanonicalShader {
+        name: "solid",
+        vertex: r#"#version 300 es
+layout(location = 0) in vec2 position;
+void main() {
+    gl_Position = vec4(position, 0.0, 1.0);
+}"#,
+        fragment: r#"#version 300 es
+precision highp float;
+out vec4 color;
+void main() {
+    color = vec4(1.0, 0.5, 0.25, 1.0);
+}"#,
+    },
+    CanonicalShader {
+        name: "gradient",
+        vertex: r#"#version 300 es
+layout(location = 0) in vec2 position;
+out vec2 uv;
+void main() {
+    uv = position * 0.5 + 0.5;
+    gl_Position = vec4(position, 0.0, 1.0);
+}"#,
+        fragment: r#"#version 300 es
+precision highp float;
+in vec2 uv;
+out vec4 color;
+void main() {
+    color = vec4(uv, 1.0 - uv.x * uv.y, 1.0);
+}"#,
+    },
+    CanonicalShader {
+        name: "procedural",
+        vertex: r#"#version 300 es
+layout(location = 0) in vec2 position;
+out vec2 uv;
+void main() {
+    uv = position;
+    gl_Position = vec4(position, 0.0, 1.0);
+}"#,
+        fragment: r#"#version 300 es
+precision highp float;
+in vec2 uv;
+out vec4 color;
+void main() {
+    vec2 z = uv;
+    float glow = 0.0;
+    for (int i = 0; i < 16; i++) {
+        z = vec2(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + uv;
+        glow += exp(-dot(z, z));
+    }
+    color = vec4(sin(glow), cos(glow), glow / 16.0, 1.0);
+}"#,
+    },
+];
+
+/// Parse and validate both stages, as `compileShader` does.
+pub fn compile(shader: &CanonicalShader) -> Result<(), String> {
+    use crate::webgl2_context::shaders::compile_glsl;
+    compile_glsl(GL_VERTEX_SHADER, shader.vertex)?;
+    compile_glsl(GL_FRAGMENT_SHADER, shader.fragment)?;
+    Ok(())
+}
+
+/// Compile and link a program in a scratch context, which generates the
+/// shader WASM. Returns the combined size of both modules in bytes.
+pub fn link(shader: &CanonicalShader) -> Result<usize, String> {
+    let ctx = create_context_with_flags(0, 1, 1);
+    let result = link_program(ctx, shader).and_then(|program| {
+        with_context(ctx, |c| {
+            let p = &c.programs[&program];
+            p.vs_wasm.as_ref().map_or(0, Vec::len) + p.fs_wasm.as_ref().map_or(0, Vec::len)
+        })
+        .ok_or_else(|| "invalid context handle".to_string())
+    });
+    destroy_context(ctx);
+    result
+}
+
+/// Compile and link a program in `ctx`, returning its handle.
+pub fn link_program(ctx: u32, shader: &CanonicalShader) -> Result<u32, String> {
+    let program = ctx_create_program(ctx);
+    for (type_, source) in [
+        (GL_VERTEX_SHADER, shader.vertex),
+        (GL_FRAGMENT_SHADER, shader.fragment),
+    ] {
+        let handle = ctx_create_shader(ctx, type_);
+        // Native pointers don't fit the u32 guest ABI of ctx_shader_source
+        if let Some(s) = get_registry()
+            .borrow_mut()
+            .contexts
+            .get_mut(&ctx)
+            .and_then(|c| c.shaders.get_mut(&handle))
+        {
+            s.source = source.to_string();
+        }
+        ctx_compile_shader(ctx, handle);
+        ctx_attach_shader(ctx, program, handle);
+    }
+    ctx_link_program(ctx, program);
+
+    with_context(ctx, |c| match c.programs.get(&program) {
+        Some(p) if p.linked => Ok(program),
+        Some(p) => Err(p.info_log.clone()),
+        None => Err("program not found".to_string()),
+    })
+    .unwrap_or_else(|| Err("invalid context handle".to_string()))
+}
+
+/// Framebuffer the rasterizer fill benchmark draws into.
+pub struct FillTarget {
+    kernel: GpuKernel,
+    framebuffer: OwnedFramebuffer,
+    rasterizer: Rasterizer,
+}
+
+impl FillTarget {
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut kernel = GpuKernel::new();
+        let framebuffer = OwnedFramebuffer::new(&mut kernel, width, height);
+        Self {
+            kernel,
+            framebuffer,
+            rasterizer: Rasterizer::default(),
+        }
+    }
+
+    /// Pixels covered by one [`fill`](Self::fill).
+    pub fn pixels(&self) -> u64 {
+        self.framebuffer.width as u64 * self.framebuffer.height as u64
+    }
+
+    /// Cover the target with two flat-colored triangles. This is the
+    /// rasterizer's coverage and write path without any shader execution.
+    pub fn fill(&mut self) {
+        let w = self.framebuffer.width as f32;
+        let h = self.framebuffer.height as f32;
+        let colors = [vec![255, 128, 64, 255]];
+        let mut fb = self.framebuffer.as_framebuffer(&mut self.kernel);
+        self.rasterizer
+            .draw_triangle(&mut fb, (0.0, 0.0), (w, 0.0), (0.0, h), &colors);
+        self.rasterizer
+            .draw_triangle(&mut fb, (w, 0.0), (w, h), (0.0, h), &colors);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_shaders_link() {
+        for shader in CANONICAL_SHADERS {
+            assert_eq!(compile(shader), Ok(()), "{}", shader.name);
+            let size = link(shader).unwrap_or_else(|e| panic!("{}: {}", shader.name, e));
+            assert!(size > 0, "{}", shader.name);
+        }
+    }
+}
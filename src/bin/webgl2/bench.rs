@@ -0,0 +1,230 @@
+//! `webgl2 bench`: performance report for the canonical shaders.
+//!
+//! Compile and link times are measured in-process with the same code as the
+//! criterion suite. Fill rate, vertex throughput and shader instantiation
+//! times need the shader WASM to execute, so they come from `bench/draw.mjs`
+//! running the WASM build under Node; those columns are left empty when Node
+//! or webgl2.wasm is missing.
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use webgl2::bench::{compile, link, FillTarget, CANONICAL_SHADERS};
+
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Timed runs per compile/link measurement (the median is reported)
+    #[arg(long, default_value_t = 20)]
+    iterations: usize,
+
+    /// Frames drawn per fill-rate and vertex measurement
+    #[arg(long, default_value_t = 10)]
+    frames: usize,
+
+    /// Render target width for fill rate
+    #[arg(long, default_value_t = 640)]
+    width: u32,
+
+    /// Render target height for fill rate
+    #[arg(long, default_value_t = 480)]
+    height: u32,
+
+    /// Vertices per draw for vertex throughput
+    #[arg(long, default_value_t = 30000)]
+    vertices: usize,
+
+    /// Package directory holding index.js, bench/draw.mjs and webgl2.wasm
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    root: PathBuf,
+
+    /// Node.js executable used for draw measurements
+    #[arg(long, default_value = "node")]
+    node: String,
+
+    /// Skip draw measurements
+    #[arg(long)]
+    no_draw: bool,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Serialize)]
+struct Report {
+    shaders: Vec<ShaderReport>,
+    /// Native rasterizer fill without shader execution
+    raster_fill_mpixels_per_sec: f64,
+}
+
+#[derive(Serialize)]
+struct ShaderReport {
+    name: String,
+    compile_ms: f64,
+    link_ms: f64,
+    wasm_bytes: usize,
+    fill_mpixels_per_sec: Option<f64>,
+    vertex_mverts_per_sec: Option<f64>,
+    /// Instantiating the program's shader modules from scratch
+    instantiate_us: Option<f64>,
+    /// The same, reusing pooled instances
+    pooled_instantiate_us: Option<f64>,
+}
+
+/// One entry of `bench/draw.mjs` output
+#[derive(Deserialize)]
+struct DrawResult {
+    name: String,
+    fill_mpixels_per_sec: f64,
+    vertex_mverts_per_sec: f64,
+    instantiate_us: f64,
+    pooled_instantiate_us: f64,
+}
+
+pub fn run(args: BenchArgs) -> Result<()> {
+    if args.iterations == 0 || args.frames == 0 {
+        bail!("--iterations and --frames must be at least 1");
+    }
+
+    let mut shaders = Vec::new();
+    for shader in CANONICAL_SHADERS {
+        tracing::debug!("Timing compile/link of {}", shader.name);
+        compile(shader).map_err(anyhow::Error::msg)?;
+        let wasm_bytes = link(shader).map_err(anyhow::Error::msg)?;
+        shaders.push(ShaderReport {
+            name: shader.name.to_string(),
+            compile_ms: median_ms(args.iterations, || {
+                let _ = compile(shader);
+            }),
+            link_ms: median_ms(args.iterations, || {
+                let _ = link(shader);
+            }),
+            wasm_bytes,
+            fill_mpixels_per_sec: None,
+            vertex_mverts_per_sec: None,
+            instantiate_us: None,
+            pooled_instantiate_us: None,
+        });
+    }
+
+    let mut target = FillTarget::new(args.width, args.height);
+    let fill_ms = median_ms(args.iterations, || target.fill());
+    let raster_fill_mpixels_per_sec = target.pixels() as f64 / fill_ms / 1e3;
+
+    if !args.no_draw {
+        match measure_draws(&args) {
+            Ok(results) => {
+                for result in results {
+                    if let Some(report) = shaders.iter_mut().find(|s| s.name == result.name) {
+                        report.fill_mpixels_per_sec = Some(result.fill_mpixels_per_sec);
+                        report.vertex_mverts_per_sec = Some(result.vertex_mverts_per_sec);
+                        report.instantiate_us = Some(result.instantiate_us);
+                        report.pooled_instantiate_us = Some(result.pooled_instantiate_us);
+                    }
+                }
+            }
+            Err(e) => tracing::warn!("Skipping draw measurements: {:#}", e),
+        }
+    }
+
+    let report = Report {
+        shaders,
+        raster_fill_mpixels_per_sec,
+    };
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_table(&report);
+    }
+    Ok(())
+}
+
+/// Median wall time of `iterations` runs of `f`, in milliseconds.
+fn median_ms(iterations: usize, mut f: impl FnMut()) -> f64 {
+    let mut times: Vec<Duration> = (0..iterations)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    times[times.len() / 2].as_secs_f64() * 1e3
+}
+
+/// Run `bench/draw.mjs` against the WASM build.
+fn measure_draws(args: &BenchArgs) -> Result<Vec<DrawResult>> {
+    let script = args.root.join("bench").join("draw.mjs");
+    if !args.root.join("webgl2.wasm").exists() {
+        bail!(
+            "webgl2.wasm not found in {:?}; run `npm run build-release`",
+            args.root
+        );
+    }
+
+    let input = serde_json::json!({
+        "shaders": CANONICAL_SHADERS,
+        "width": args.width,
+        "height": args.height,
+        "frames": args.frames,
+        // Whole triangles only
+        "vertices": args.vertices.max(3) / 3 * 3,
+    });
+
+    let mut child = Command::new(&args.node)
+        .arg(&script)
+        .current_dir(&args.root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", args.node))?;
+    child
+        .stdin
+        .take()
+        .context("node stdin unavailable")?
+        .write_all(input.to_string().as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!("{:?} exited with {}", script, output.status);
+    }
+    serde_json::from_slice(&output.stdout).context("Failed to parse draw results")
+}
+
+fn print_table(report: &Report) {
+    let optional =
+        |value: Option<f64>| value.map_or_else(|| "-".to_string(), |v| format!("{v:.2}"));
+
+    println!(
+        "{:<12} {:>11} {:>9} {:>10} {:>13} {:>13} {:>9} {:>10}",
+        "shader",
+        "compile ms",
+        "link ms",
+        "wasm B",
+        "fill Mpix/s",
+        "verts Mv/s",
+        "inst us",
+        "pooled us"
+    );
+    for s in &report.shaders {
+        println!(
+            "{:<12} {:>11.3} {:>9.3} {:>10} {:>13} {:>13} {:>9} {:>10}",
+            s.name,
+            s.compile_ms,
+            s.link_ms,
+            s.wasm_bytes,
+            optional(s.fill_mpixels_per_sec),
+            optional(s.vertex_mverts_per_sec),
+            optional(s.instantiate_us),
+            optional(s.pooled_instantiate_us)
+        );
+    }
+    println!(
+        "\nrasterizer fill (no shading): {:.2} Mpix/s",
+        report.raster_fill_mpixels_per_sec
+    );
+}
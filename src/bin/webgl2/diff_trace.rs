@@ -0,0 +1,283 @@
+//! `webgl2 diff-trace`: find where two API traces start rendering differently.
+//!
+//! Traces are recorded with `startTrace` from index.js. Replaying needs the
+//! shader WASM to execute, so each trace is replayed under Node by
+//! `tools/replay_trace.mjs`, which hashes the draw framebuffer after every
+//! draw and clear. The first draw whose hash differs is reported with the
+//! call that made it, and both framebuffers after that call are written as
+//! PNGs. Replaying the same trace with two builds (`--root-b`) bisects
+//! regressions between versions.
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+#[derive(Args)]
+pub struct DiffTraceArgs {
+    /// Baseline trace
+    a: PathBuf,
+
+    /// Trace compared against the baseline
+    b: PathBuf,
+
+    /// Package directory holding index.js, tools/replay_trace.mjs and webgl2.wasm
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    root: PathBuf,
+
+    /// Package directory of another build to replay the second trace with
+    #[arg(long, value_name = "DIR")]
+    root_b: Option<PathBuf>,
+
+    /// Directory the framebuffers of the first diverging draw are written to
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    out: PathBuf,
+
+    /// Node.js executable used for replays
+    #[arg(long, default_value = "node")]
+    node: String,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+/// One entry of `tools/replay_trace.mjs` output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Draw {
+    call: usize,
+    method: String,
+    hash: String,
+}
+
+#[derive(Deserialize)]
+struct Replay {
+    draws: Vec<Draw>,
+}
+
+#[derive(Serialize)]
+struct Side {
+    draw: Option<Draw>,
+    /// The call, with its arguments
+    call: Option<String>,
+    image: Option<PathBuf>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    draws: usize,
+    /// Index of the first draw that differs, if any
+    diverged_at: Option<usize>,
+    a: Option<Side>,
+    b: Option<Side>,
+}
+
+pub fn run(args: DiffTraceArgs) -> Result<()> {
+    let root_b = args.root_b.clone().unwrap_or_else(|| args.root.clone());
+    let a = replay(&args, &args.a, &args.root, None)?;
+    let b = replay(&args, &args.b, &root_b, None)?;
+
+    let diverged_at = first_divergence(&a, &b);
+    let mut report = Report {
+        draws: a.len().max(b.len()),
+        diverged_at,
+        a: None,
+        b: None,
+    };
+    if let Some(index) = diverged_at {
+        std::fs::create_dir_all(&args.out)
+            .with_context(|| format!("failed to create {}", args.out.display()))?;
+        report.a = Some(side(&args, "a", &args.a, &args.root, a.get(index))?);
+        report.b = Some(side(&args, "b", &args.b, &root_b, b.get(index))?);
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print_report(&report);
+    }
+    if diverged_at.is_some() {
+        bail!("traces diverge");
+    }
+    Ok(())
+}
+
+/// Index of the first draw that differs in call, method or framebuffer, or
+/// that only one trace has.
+fn first_divergence(a: &[Draw], b: &[Draw]) -> Option<usize> {
+    (0..a.len().max(b.len())).find(|&i| a.get(i) != b.get(i))
+}
+
+/// Describe one trace's side of a divergence, dumping its framebuffer.
+fn side(
+    args: &DiffTraceArgs,
+    name: &str,
+    trace: &Path,
+    root: &Path,
+    draw: Option<&Draw>,
+) -> Result<Side> {
+    let Some(draw) = draw else {
+        return Ok(Side {
+            draw: None,
+            call: None,
+            image: None,
+        });
+    };
+    let image = args.out.join(format!("{}-call{}.png", name, draw.call));
+    replay(args, trace, root, Some((draw.call, &image)))?;
+    Ok(Side {
+        draw: Some(draw.clone()),
+        call: Some(describe_call(trace, draw.call)?),
+        image: Some(image),
+    })
+}
+
+/// Replay `trace` with the build in `root`, or only up to a call whose
+/// framebuffer is written to an image.
+fn replay(
+    args: &DiffTraceArgs,
+    trace: &Path,
+    root: &Path,
+    dump: Option<(usize, &Path)>,
+) -> Result<Vec<Draw>> {
+    let script = args.root.join("tools").join("replay_trace.mjs");
+    if !root.join("webgl2.wasm").exists() {
+        bail!(
+            "webgl2.wasm not found in {:?}; run `npm run build-release`",
+            root
+        );
+    }
+    let absolute = |path: &Path| {
+        std::fs::canonicalize(path).with_context(|| format!("{} not found", path.display()))
+    };
+    let input = serde_json::json!({
+        "trace": absolute(trace)?,
+        "module": absolute(&root.join("index.js"))?,
+        "dump": dump.map(|(call, _)| call),
+        "out": dump.map(|(_, image)| image),
+    });
+
+    let mut child = Command::new(&args.node)
+        .arg(&script)
+        .current_dir(&args.root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", args.node))?;
+    child
+        .stdin
+        .take()
+        .context("node stdin unavailable")?
+        .write_all(input.to_string().as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!("replaying {:?} exited with {}", trace, output.status);
+    }
+    let replay: Replay =
+        serde_json::from_slice(&output.stdout).context("Failed to parse replay results")?;
+    Ok(replay.draws)
+}
+
+/// `method(args)` of a traced call.
+fn describe_call(trace: &Path, index: usize) -> Result<String> {
+    let text = std::fs::read_to_string(trace)
+        .with_context(|| format!("failed to read {}", trace.display()))?;
+    let trace: Value = serde_json::from_str(&text)
+        .with_context(|| format!("{} is not a trace", trace.display()))?;
+    let call = &trace["calls"][index];
+    let args: Vec<String> = call["args"]
+        .as_array()
+        .map(|args| args.iter().map(describe_arg).collect())
+        .unwrap_or_default();
+    Ok(format!(
+        "{}({})",
+        call["method"].as_str().unwrap_or("?"),
+        args.join(", ")
+    ))
+}
+
+fn describe_arg(arg: &Value) -> String {
+    match arg {
+        Value::Object(fields) => {
+            if let Some(id) = fields.get("ref") {
+                format!("#{}", id)
+            } else if let Some(typed) = fields.get("typed").and_then(Value::as_str) {
+                let bytes = fields
+                    .get("base64")
+                    .and_then(Value::as_str)
+                    .map_or(0, |data| data.trim_end_matches('=').len() * 3 / 4);
+                format!("{}({} bytes)", typed, bytes)
+            } else if fields.contains_key("undefined") {
+                "undefined".to_string()
+            } else {
+                "?".to_string()
+            }
+        }
+        Value::Array(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(describe_arg)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        other => other.to_string(),
+    }
+}
+
+fn print_report(report: &Report) {
+    let Some(index) = report.diverged_at else {
+        println!("traces match over {} draws", report.draws);
+        return;
+    };
+    println!("first divergence at draw {}", index);
+    for (name, side) in [("a", &report.a), ("b", &report.b)] {
+        match side.as_ref().and_then(|s| Some((s.draw.as_ref()?, s))) {
+            Some((draw, side)) => println!(
+                "  {}: call {} {} -> {} ({})",
+                name,
+                draw.call,
+                side.call.as_deref().unwrap_or(&draw.method),
+                draw.hash,
+                side.image
+                    .as_ref()
+                    .map_or_else(String::new, |image| image.display().to_string())
+            ),
+            None => println!("  {}: no draw (trace ended)", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(call: usize, hash: &str) -> Draw {
+        Draw {
+            call,
+            method: "drawArrays".to_string(),
+            hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let a = [draw(3, "00"), draw(5, "11"), draw(9, "22")];
+        assert_eq!(first_divergence(&a, &a), None);
+        assert_eq!(
+            first_divergence(&a, &[draw(3, "00"), draw(5, "ff"), draw(9, "22")]),
+            Some(1)
+        );
+        assert_eq!(first_divergence(&a, &a[..2]), Some(2));
+        assert_eq!(
+            describe_arg(
+                &serde_json::json!([{ "ref": 2 }, 4, { "typed": "Float32Array", "base64": "AACAPw==" }])
+            ),
+            "[#2, 4, Float32Array(4 bytes)]"
+        );
+    }
+}
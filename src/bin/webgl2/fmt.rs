@@ -0,0 +1,77 @@
+//! `webgl2 fmt`: reformat GLSL sources.
+//!
+//! Files are rewritten in place, or only checked with `--check`. A `.wasm`
+//! file is decompiled and its formatted GLSL printed instead, so a shader can
+//! be diffed against its round trip. Without files, stdin is formatted to
+//! stdout.
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use std::io::Read;
+use std::path::PathBuf;
+use webgl2::decompiler::decompile_to_glsl;
+use webgl2::glsl_format::{format_glsl, FormatOptions};
+
+#[derive(Args)]
+pub struct FmtArgs {
+    /// GLSL files to format, or .wasm shaders to decompile and print
+    files: Vec<PathBuf>,
+
+    /// Spaces per indent level
+    #[arg(long, default_value_t = 4)]
+    indent: usize,
+
+    /// Indent with tabs instead of spaces
+    #[arg(long)]
+    tabs: bool,
+
+    /// Report files that aren't formatted instead of rewriting them
+    #[arg(long)]
+    check: bool,
+}
+
+pub fn run(args: FmtArgs) -> Result<()> {
+    let options = FormatOptions {
+        indent: if args.tabs {
+            "\t".to_string()
+        } else {
+            " ".repeat(args.indent)
+        },
+    };
+
+    if args.files.is_empty() {
+        let mut source = String::new();
+        std::io::stdin().read_to_string(&mut source)?;
+        print!("{}", format_glsl(&source, &options));
+        return Ok(());
+    }
+
+    let mut unformatted = Vec::new();
+    for path in &args.files {
+        if path.extension().is_some_and(|ext| ext == "wasm") {
+            let bytes = std::fs::read(path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            let glsl = decompile_to_glsl(&bytes)
+                .with_context(|| format!("failed to decompile {}", path.display()))?;
+            print!("{}", format_glsl(&glsl, &options));
+            continue;
+        }
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let formatted = format_glsl(&source, &options);
+        if formatted == source {
+            continue;
+        }
+        if args.check {
+            unformatted.push(path.display().to_string());
+        } else {
+            std::fs::write(path, formatted)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            tracing::info!("formatted {}", path.display());
+        }
+    }
+    if !unformatted.is_empty() {
+        bail!("not formatted: {}", unformatted.join(", "));
+    }
+    Ok(())
+}
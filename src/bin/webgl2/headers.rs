@@ -0,0 +1,31 @@
+//! `webgl2 headers`: declarations of the WASM exports for embedders.
+//!
+//! Writes `webgl2.h` for C/C++ hosts (and anything binding C, like Python's
+//! ctypes or cffi) and `webgl2.wasm.d.ts` for JS hosts. Both carry the
+//! package version and a hash of the ABI.
+
+use anyhow::{Context, Result};
+use clap::Args;
+use std::path::PathBuf;
+use webgl2::js_codegen::{generate_c_header, generate_ts_declarations};
+
+#[derive(Args)]
+pub struct HeadersArgs {
+    /// Directory the files are written to
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    out: PathBuf,
+}
+
+pub fn run(args: HeadersArgs) -> Result<()> {
+    let outputs = [
+        ("webgl2.h", generate_c_header()?),
+        ("webgl2.wasm.d.ts", generate_ts_declarations()?),
+    ];
+    for (name, contents) in outputs {
+        let path = args.out.join(name);
+        std::fs::write(&path, contents)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        tracing::info!("wrote {}", path.display());
+    }
+    Ok(())
+}
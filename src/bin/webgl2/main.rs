@@ -0,0 +1,61 @@
+//! WebGL2 Developer Command Line
+//!
+//! Native tooling around the WASM build: a live-preview dev server for
+//! editing shaders, a benchmark report for the compiler and rasterizer, the
+//! C/TypeScript declarations of the module's exports, a GLSL formatter and a
+//! differ for API traces.
+
+mod bench;
+mod diff_trace;
+mod fmt;
+mod headers;
+mod serve;
+mod websocket;
+
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(name = "webgl2")]
+#[command(about = "WebGL2 shader development tools", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Enable verbose logging
+    #[arg(short, long, global = true)]
+    verbose: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve a live shader preview that reloads when shader files change
+    Serve(serve::ServeArgs),
+    /// Measure compile, link, fill-rate and vertex throughput of canonical shaders
+    Bench(bench::BenchArgs),
+    /// Write webgl2.h and webgl2.wasm.d.ts declaring the module's exports
+    Headers(headers::HeadersArgs),
+    /// Format GLSL files, or print decompiled .wasm shaders formatted
+    Fmt(fmt::FmtArgs),
+    /// Replay two API traces and report the first draw that renders differently
+    DiffTrace(diff_trace::DiffTraceArgs),
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let log_level = if cli.verbose {
+        tracing::Level::DEBUG
+    } else {
+        tracing::Level::INFO
+    };
+    tracing_subscriber::fmt().with_max_level(log_level).init();
+
+    match cli.command {
+        Command::Serve(args) => serve::run(args),
+        Command::Bench(args) => bench::run(args),
+        Command::Headers(args) => headers::run(args),
+        Command::Fmt(args) => fmt::run(args),
+        Command::DiffTrace(args) => diff_trace::run(args),
+    }
+}
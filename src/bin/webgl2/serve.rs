@@ -0,0 +1,271 @@
+//! `webgl2 serve`: live shader preview over HTTP.
+//!
+//! Serves the package (index.js, the JS wrappers and the compiled
+//! webgl2.wasm) next to a preview page that renders a `<name>.vert` /
+//! `<name>.frag` pair from the shader directory with the emulator itself.
+//! A polling watcher notices edited shader files and pushes a WebSocket
+//! message, on which the page reloads the program in place with
+//! `gl.reloadProgram`, keeping its uniform values.
+
+use super::websocket;
+use anyhow::{Context, Result};
+use clap::Args;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Preview page served at `/`
+const PREVIEW_HTML: &str = include_str!("preview.html");
+
+/// Prefix of the dev server's own endpoints, kept apart from package files
+const API_PREFIX: &str = "/__webgl2/";
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Directory containing `<name>.vert` and `<name>.frag` shader pairs
+    #[arg(value_name = "SHADER_DIR", default_value = ".")]
+    shaders: PathBuf,
+
+    /// Package directory holding index.js, src/*.js and webgl2.wasm
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    root: PathBuf,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1")]
+    host: String,
+
+    /// Port to listen on
+    #[arg(short, long, default_value_t = 8080)]
+    port: u16,
+
+    /// How often to check shader files for changes, in milliseconds
+    #[arg(long, default_value_t = 250)]
+    poll_ms: u64,
+}
+
+/// Open WebSocket connections waiting for reload notifications
+type Clients = Arc<Mutex<Vec<TcpStream>>>;
+
+pub fn run(args: ServeArgs) -> Result<()> {
+    if !args.root.join("webgl2.wasm").exists() {
+        tracing::warn!(
+            "{:?} not found; run `npm run build-release` first",
+            args.root.join("webgl2.wasm")
+        );
+    }
+
+    let listener = TcpListener::bind((args.host.as_str(), args.port))
+        .with_context(|| format!("Failed to listen on {}:{}", args.host, args.port))?;
+    tracing::info!(
+        "Serving shaders from {:?} at http://{}:{}/",
+        args.shaders,
+        args.host,
+        args.port
+    );
+
+    let clients: Clients = Arc::default();
+    {
+        let shaders = args.shaders.clone();
+        let clients = clients.clone();
+        let interval = Duration::from_millis(args.poll_ms);
+        thread::spawn(move || watch(&shaders, interval, &clients));
+    }
+
+    let root = Arc::new(args.root);
+    let shaders = Arc::new(args.shaders);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("Connection failed: {}", e);
+                continue;
+            }
+        };
+        let (root, shaders, clients) = (root.clone(), shaders.clone(), clients.clone());
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(stream, &root, &shaders, &clients) {
+                tracing::debug!("Request failed: {:#}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Answer one HTTP request, or hand a WebSocket upgrade over to `clients`.
+fn handle_connection(
+    mut stream: TcpStream,
+    root: &Path,
+    shaders: &Path,
+    clients: &Clients,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("/");
+    let path = target.split('?').next().unwrap_or("/");
+    tracing::debug!("{} {}", method, target);
+
+    if path.strip_prefix(API_PREFIX) == Some("ws") {
+        let key = headers
+            .get("sec-websocket-key")
+            .context("WebSocket upgrade without Sec-WebSocket-Key")?;
+        stream.write_all(websocket::handshake_response(key).as_bytes())?;
+        clients.lock().unwrap().push(stream);
+        return Ok(());
+    }
+
+    if method != "GET" {
+        return respond(&mut stream, "405 Method Not Allowed", "text/plain", b"");
+    }
+
+    match route(path, root, shaders) {
+        Some((content_type, body)) => respond(&mut stream, "200 OK", content_type, &body),
+        None => respond(&mut stream, "404 Not Found", "text/plain", b"not found"),
+    }
+}
+
+/// Resolve a request path to `(content type, body)`.
+fn route(path: &str, root: &Path, shaders: &Path) -> Option<(&'static str, Vec<u8>)> {
+    if path == "/" {
+        return Some(("text/html; charset=utf-8", PREVIEW_HTML.as_bytes().to_vec()));
+    }
+
+    if let Some(api) = path.strip_prefix(API_PREFIX) {
+        if api == "shaders" {
+            let names = shader_names(shaders);
+            return Some(("application/json", serde_json::to_vec(&names).ok()?));
+        }
+        if let Some(file) = api.strip_prefix("shaders/") {
+            let path = safe_join(shaders, file)?;
+            return Some(("text/plain; charset=utf-8", std::fs::read(path).ok()?));
+        }
+        if api == "harness/shader-cache.ts" {
+            let harness = webgl2::js_codegen::generate_shader_cache_harness();
+            return Some(("text/plain; charset=utf-8", harness.into_bytes()));
+        }
+        if let Some(file) = api.strip_prefix("harness/") {
+            let name = file.strip_suffix(".ts")?;
+            let source =
+                std::fs::read_to_string(safe_join(shaders, &format!("{name}.vert"))?).ok()?;
+            let manifest = webgl2::glsl_introspection::introspect_shader(&source).ok()?;
+            let harness = webgl2::js_codegen::generate_typescript(&manifest).ok()?;
+            return Some(("text/plain; charset=utf-8", harness.into_bytes()));
+        }
+        return None;
+    }
+
+    let file = safe_join(root, path.trim_start_matches('/'))?;
+    Some((content_type(&file), std::fs::read(file).ok()?))
+}
+
+/// Join a URL path onto `base`, refusing anything that would escape it.
+fn safe_join(base: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+        .then(|| base.join(relative))
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("wasm") => "application/wasm",
+        Some("json") => "application/json",
+        Some("png") => "image/png",
+        _ => "application/octet-stream",
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8]) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Names of the shader pairs in `dir` (a `.vert` with a matching `.frag`).
+fn shader_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = shader_files(dir)
+        .into_keys()
+        .filter(|path| {
+            path.extension().is_some_and(|e| e == "vert") && path.with_extension("frag").exists()
+        })
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Modification times of the `.vert` and `.frag` files in `dir`.
+fn shader_files(dir: &Path) -> HashMap<PathBuf, SystemTime> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("vert" | "frag")
+            )
+        })
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
+
+/// Poll `dir` forever, telling connected pages which shader changed.
+fn watch(dir: &Path, interval: Duration, clients: &Clients) {
+    let mut known = shader_files(dir);
+    loop {
+        thread::sleep(interval);
+        let current = shader_files(dir);
+        let mut changed: Vec<&str> = current
+            .iter()
+            .filter(|(path, modified)| known.get(*path) != Some(*modified))
+            .filter_map(|(path, _)| path.file_stem()?.to_str())
+            .collect();
+        changed.sort();
+        changed.dedup();
+
+        for name in changed {
+            tracing::info!("Shader {} changed, reloading", name);
+            let message = serde_json::json!({ "type": "reload", "shader": name }).to_string();
+            // Pages that went away fail the write and are dropped
+            clients
+                .lock()
+                .unwrap()
+                .retain_mut(|client| websocket::write_text(client, &message).is_ok());
+        }
+        known = current;
+    }
+}
@@ -11,6 +11,8 @@ pub enum ScalarType {
     Long,   // i64 -> not directly supported in GLSL ES, treat as int
     Float,  // f32 -> float
     Double, // f64 -> not directly supported in GLSL ES, treat as float
+    Bool,   // i32 holding only 0/1, recovered by type inference
+    Uint,   // i32 used as unsigned, recovered by type inference
 }
 
 impl ScalarType {
@@ -19,6 +21,8 @@ impl ScalarType {
         match self {
             ScalarType::Int | ScalarType::Long => "int",
             ScalarType::Float | ScalarType::Double => "float",
+            ScalarType::Bool => "bool",
+            ScalarType::Uint => "uint",
         }
     }
 }
@@ -76,6 +80,23 @@ impl BinOp {
             BinOp::GeS | BinOp::GeU => ">=",
         }
     }
+
+    /// Returns true for comparison operators (which produce an i32 0/1).
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            BinOp::Eq
+                | BinOp::Ne
+                | BinOp::LtS
+                | BinOp::LtU
+                | BinOp::LeS
+                | BinOp::LeU
+                | BinOp::GtS
+                | BinOp::GtU
+                | BinOp::GeS
+                | BinOp::GeU
+        )
+    }
 }
 
 /// Unary operators supported in expressions.
@@ -140,6 +161,8 @@ pub enum Expr {
     LocalGet(u32),
     /// Read a global variable
     GlobalGet(u32),
+    /// Read a hoisted temporary (see `Stmt::TempDecl`)
+    Temp(u32),
 
     /// Binary operation
     BinaryOp {
@@ -168,6 +191,14 @@ pub enum Expr {
     /// Function call
     Call { func_idx: u32, args: Vec<Expr> },
 
+    /// Indirect function call through a table slot
+    CallIndirect {
+        table_idx: u32,
+        type_idx: u32,
+        index: Box<Expr>,
+        args: Vec<Expr>,
+    },
+
     /// Select (ternary operator)
     Select {
         condition: Box<Expr>,
@@ -200,6 +231,13 @@ pub enum Stmt {
     /// Assign to a global variable
     GlobalSet { global_idx: u32, value: Expr },
 
+    /// Declare and initialize a hoisted temporary
+    TempDecl {
+        temp_idx: u32,
+        ty: ScalarType,
+        value: Expr,
+    },
+
     /// Memory store
     MemoryStore {
         ty: ScalarType,
@@ -236,6 +274,9 @@ pub enum Stmt {
     /// Drop a value from the stack (no-op in GLSL)
     Drop,
 
+    /// Source line marker recovered from the DWARF line table
+    SourceLine { line: u32 },
+
     /// Placeholder for unsupported statements
     Unknown(String),
 }
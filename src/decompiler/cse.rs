@@ -0,0 +1,423 @@
+//! Common subexpression hoisting.
+//!
+//! After simplification, the same subexpression frequently appears several
+//! times inside one statement (for example an address computation used by
+//! both sides of a store). This pass detects such repeats and hoists them
+//! into typed temporaries declared right before the statement:
+//!
+//! ```text
+//! memory[(p0 * 4 + 16) >> 2] = memory[(p0 * 4 + 16) >> 2] + 1;
+//! ```
+//!
+//! becomes
+//!
+//! ```text
+//! int t0 = p0 * 4 + 16;
+//! memory[t0 >> 2] = memory[t0 >> 2] + 1;
+//! ```
+//!
+//! Only expressions without side effects are hoisted, and repeats are only
+//! merged within a single statement, so no ordering analysis is required.
+
+use super::ast::{Expr, Function, ScalarType, Stmt};
+use super::typeinfer::TypeEnv;
+use std::collections::HashMap;
+
+/// Hoist repeated subexpressions of every statement in `func` into temporaries.
+///
+/// `global_types` provides the types of globals for type inference.
+/// Subexpressions smaller than `min_size` AST nodes are left inline;
+/// a `min_size` of 0 disables the pass.
+pub fn hoist_common_subexpressions(
+    func: &mut Function,
+    global_types: &[ScalarType],
+    min_size: usize,
+) {
+    if min_size == 0 {
+        return;
+    }
+    let local_types: Vec<ScalarType> = func
+        .param_types
+        .iter()
+        .chain(func.local_types.iter())
+        .copied()
+        .collect();
+    let mut hoister = Hoister {
+        local_types: &local_types,
+        global_types,
+        temp_types: HashMap::new(),
+        min_size,
+        next_temp: 0,
+    };
+    let body = std::mem::take(&mut func.body);
+    func.body = hoister.hoist_body(body);
+}
+
+struct Hoister<'a> {
+    /// Parameter types followed by local types
+    local_types: &'a [ScalarType],
+    global_types: &'a [ScalarType],
+    temp_types: HashMap<u32, ScalarType>,
+    min_size: usize,
+    next_temp: u32,
+}
+
+impl Hoister<'_> {
+    /// Process a statement list, recursing into nested bodies.
+    fn hoist_body(&mut self, body: Vec<Stmt>) -> Vec<Stmt> {
+        let mut out = Vec::with_capacity(body.len());
+        for stmt in body {
+            let stmt = match stmt {
+                Stmt::If {
+                    condition,
+                    then_body,
+                    else_body,
+                } => Stmt::If {
+                    condition,
+                    then_body: self.hoist_body(then_body),
+                    else_body: else_body.map(|b| self.hoist_body(b)),
+                },
+                Stmt::Block { body } => Stmt::Block {
+                    body: self.hoist_body(body),
+                },
+                Stmt::Loop { body } => Stmt::Loop {
+                    body: self.hoist_body(body),
+                },
+                other => other,
+            };
+            self.hoist_stmt(stmt, &mut out);
+        }
+        out
+    }
+
+    /// Hoist repeats out of a single statement, appending the temporaries
+    /// followed by the rewritten statement to `out`.
+    fn hoist_stmt(&mut self, mut stmt: Stmt, out: &mut Vec<Stmt>) {
+        // Temporaries created for this statement; later (smaller) ones may be
+        // used by earlier (larger) ones, so new declarations go to the front.
+        let mut decls: Vec<Stmt> = Vec::new();
+
+        while let Some((key, expr, ty)) = self.best_candidate(&stmt, &decls) {
+            let temp_idx = self.next_temp;
+            self.next_temp += 1;
+            self.temp_types.insert(temp_idx, ty);
+
+            for decl in &mut decls {
+                if let Stmt::TempDecl { value, .. } = decl {
+                    replace_expr(value, &key, temp_idx);
+                }
+            }
+            for expr in stmt_exprs_mut(&mut stmt) {
+                replace_expr(expr, &key, temp_idx);
+            }
+            decls.insert(
+                0,
+                Stmt::TempDecl {
+                    temp_idx,
+                    ty,
+                    value: expr,
+                },
+            );
+        }
+
+        out.extend(decls);
+        out.push(stmt);
+    }
+
+    /// Find the largest hoistable subexpression that occurs more than once.
+    fn best_candidate(&self, stmt: &Stmt, decls: &[Stmt]) -> Option<(String, Expr, ScalarType)> {
+        let mut counts: HashMap<String, (usize, usize, Expr)> = HashMap::new();
+        let mut roots: Vec<&Expr> = stmt_exprs(stmt);
+        for decl in decls {
+            if let Stmt::TempDecl { value, .. } = decl {
+                roots.push(value);
+            }
+        }
+        for root in roots {
+            count_subexprs(root, &mut counts);
+        }
+
+        let mut best: Option<(String, usize, Expr, ScalarType)> = None;
+        for (key, (count, size, expr)) in counts {
+            if count < 2 || size < self.min_size {
+                continue;
+            }
+            let ty = match self.infer_type(&expr) {
+                Some(ty) => ty,
+                None => continue,
+            };
+            // Prefer larger expressions; break ties by key for deterministic output
+            let better = match &best {
+                Some((best_key, best_size, _, _)) => {
+                    size > *best_size || (size == *best_size && key < *best_key)
+                }
+                None => true,
+            };
+            if better {
+                best = Some((key, size, expr, ty));
+            }
+        }
+        best.map(|(key, _, expr, ty)| (key, expr, ty))
+    }
+
+    /// Infer the scalar type of a pure expression.
+    fn infer_type(&self, expr: &Expr) -> Option<ScalarType> {
+        match expr {
+            // Temporaries are never re-hoisted; vectors have no scalar type
+            Expr::Temp(_)
+            | Expr::VecConstruct { .. }
+            | Expr::VecBinaryOp { .. }
+            | Expr::Call { .. }
+            | Expr::CallIndirect { .. }
+            | Expr::Unknown(_) => None,
+            _ => {
+                let env = TypeEnv {
+                    locals: self.local_types,
+                    globals: self.global_types,
+                    temps: &self.temp_types,
+                };
+                Some(env.expr_type(expr))
+            }
+        }
+    }
+}
+
+/// Count pure subexpressions of `expr`, returning its size in nodes, or None
+/// if it has side effects (and therefore so do all its ancestors).
+fn count_subexprs(
+    expr: &Expr,
+    counts: &mut HashMap<String, (usize, usize, Expr)>,
+) -> Option<usize> {
+    let children: Vec<&Expr> = match expr {
+        Expr::ConstI32(_)
+        | Expr::ConstI64(_)
+        | Expr::ConstF32(_)
+        | Expr::ConstF64(_)
+        | Expr::LocalGet(_)
+        | Expr::GlobalGet(_)
+        | Expr::Temp(_) => return Some(1),
+        Expr::BinaryOp { left, right, .. } | Expr::VecBinaryOp { left, right, .. } => {
+            vec![left.as_ref(), right.as_ref()]
+        }
+        Expr::UnaryOp { operand, .. } | Expr::Convert { operand, .. } => vec![operand.as_ref()],
+        Expr::MemoryLoad { addr, .. } => vec![addr.as_ref()],
+        Expr::Select {
+            condition,
+            true_val,
+            false_val,
+        } => vec![condition.as_ref(), true_val.as_ref(), false_val.as_ref()],
+        Expr::VecConstruct { components } => components.iter().collect(),
+        Expr::Call { args, .. } => {
+            for arg in args {
+                count_subexprs(arg, counts);
+            }
+            return None;
+        }
+        Expr::CallIndirect { index, args, .. } => {
+            count_subexprs(index, counts);
+            for arg in args {
+                count_subexprs(arg, counts);
+            }
+            return None;
+        }
+        Expr::Unknown(_) => return None,
+    };
+
+    let mut size = Some(1);
+    for child in children {
+        let child_size = count_subexprs(child, counts);
+        size = match (size, child_size) {
+            (Some(s), Some(c)) => Some(s + c),
+            _ => None,
+        };
+    }
+
+    if let Some(size) = size {
+        let entry = counts
+            .entry(format!("{:?}", expr))
+            .or_insert_with(|| (0, size, expr.clone()));
+        entry.0 += 1;
+    }
+    size
+}
+
+/// Replace every occurrence of the expression identified by `key` with a temporary.
+fn replace_expr(expr: &mut Expr, key: &str, temp_idx: u32) {
+    if format!("{:?}", expr) == key {
+        *expr = Expr::Temp(temp_idx);
+        return;
+    }
+    match expr {
+        Expr::BinaryOp { left, right, .. } | Expr::VecBinaryOp { left, right, .. } => {
+            replace_expr(left, key, temp_idx);
+            replace_expr(right, key, temp_idx);
+        }
+        Expr::UnaryOp { operand, .. } | Expr::Convert { operand, .. } => {
+            replace_expr(operand, key, temp_idx);
+        }
+        Expr::MemoryLoad { addr, .. } => replace_expr(addr, key, temp_idx),
+        Expr::Select {
+            condition,
+            true_val,
+            false_val,
+        } => {
+            replace_expr(condition, key, temp_idx);
+            replace_expr(true_val, key, temp_idx);
+            replace_expr(false_val, key, temp_idx);
+        }
+        Expr::VecConstruct { components } => {
+            for c in components {
+                replace_expr(c, key, temp_idx);
+            }
+        }
+        Expr::Call { args, .. } => {
+            for a in args {
+                replace_expr(a, key, temp_idx);
+            }
+        }
+        Expr::CallIndirect { index, args, .. } => {
+            replace_expr(index, key, temp_idx);
+            for a in args {
+                replace_expr(a, key, temp_idx);
+            }
+        }
+        Expr::ConstI32(_)
+        | Expr::ConstI64(_)
+        | Expr::ConstF32(_)
+        | Expr::ConstF64(_)
+        | Expr::LocalGet(_)
+        | Expr::GlobalGet(_)
+        | Expr::Temp(_)
+        | Expr::Unknown(_) => {}
+    }
+}
+
+/// The expressions evaluated directly by a statement (not its nested bodies).
+fn stmt_exprs(stmt: &Stmt) -> Vec<&Expr> {
+    match stmt {
+        Stmt::LocalSet { value, .. }
+        | Stmt::GlobalSet { value, .. }
+        | Stmt::TempDecl { value, .. }
+        | Stmt::ExprStmt(value) => vec![value],
+        Stmt::MemoryStore { addr, value, .. } => vec![addr, value],
+        Stmt::If { condition, .. } => vec![condition],
+        Stmt::Return { value } => value.iter().collect(),
+        Stmt::Block { .. }
+        | Stmt::Loop { .. }
+        | Stmt::Break { .. }
+        | Stmt::Continue { .. }
+        | Stmt::Drop
+        | Stmt::SourceLine { .. }
+        | Stmt::Unknown(_) => Vec::new(),
+    }
+}
+
+/// Mutable counterpart of [`stmt_exprs`].
+fn stmt_exprs_mut(stmt: &mut Stmt) -> Vec<&mut Expr> {
+    match stmt {
+        Stmt::LocalSet { value, .. }
+        | Stmt::GlobalSet { value, .. }
+        | Stmt::TempDecl { value, .. }
+        | Stmt::ExprStmt(value) => vec![value],
+        Stmt::MemoryStore { addr, value, .. } => vec![addr, value],
+        Stmt::If { condition, .. } => vec![condition],
+        Stmt::Return { value } => value.iter_mut().collect(),
+        Stmt::Block { .. }
+        | Stmt::Loop { .. }
+        | Stmt::Break { .. }
+        | Stmt::Continue { .. }
+        | Stmt::Drop
+        | Stmt::SourceLine { .. }
+        | Stmt::Unknown(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decompiler::ast::BinOp;
+
+    fn addr() -> Expr {
+        Expr::BinaryOp {
+            op: BinOp::Add,
+            left: Box::new(Expr::BinaryOp {
+                op: BinOp::Mul,
+                left: Box::new(Expr::LocalGet(0)),
+                right: Box::new(Expr::ConstI32(4)),
+            }),
+            right: Box::new(Expr::ConstI32(16)),
+        }
+    }
+
+    fn function(body: Vec<Stmt>) -> Function {
+        Function {
+            func_idx: 0,
+            param_count: 1,
+            param_types: vec![ScalarType::Int],
+            return_type: None,
+            local_types: vec![],
+            body,
+        }
+    }
+
+    #[test]
+    fn test_hoist_repeated_address() {
+        let mut func = function(vec![Stmt::MemoryStore {
+            ty: ScalarType::Int,
+            offset: 0,
+            addr: addr(),
+            value: Expr::MemoryLoad {
+                ty: ScalarType::Int,
+                offset: 0,
+                addr: Box::new(addr()),
+            },
+        }]);
+
+        hoist_common_subexpressions(&mut func, &[], 4);
+
+        assert_eq!(func.body.len(), 2);
+        match &func.body[0] {
+            Stmt::TempDecl { temp_idx, ty, .. } => {
+                assert_eq!(*temp_idx, 0);
+                assert_eq!(*ty, ScalarType::Int);
+            }
+            other => panic!("Expected TempDecl, got {:?}", other),
+        }
+        match &func.body[1] {
+            Stmt::MemoryStore { addr, value, .. } => {
+                assert!(matches!(addr, Expr::Temp(0)));
+                assert!(
+                    matches!(value, Expr::MemoryLoad { addr, .. } if matches!(**addr, Expr::Temp(0)))
+                );
+            }
+            other => panic!("Expected MemoryStore, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_hoist_skips_calls_and_small_exprs() {
+        let call = Expr::Call {
+            func_idx: 1,
+            args: vec![addr()],
+        };
+        let mut func = function(vec![Stmt::ExprStmt(Expr::BinaryOp {
+            op: BinOp::Add,
+            left: Box::new(call.clone()),
+            right: Box::new(call),
+        })]);
+
+        // The calls themselves must stay, but their pure argument is hoisted
+        hoist_common_subexpressions(&mut func, &[], 4);
+        assert_eq!(func.body.len(), 2);
+
+        let mut func = function(vec![Stmt::Return {
+            value: Some(Expr::BinaryOp {
+                op: BinOp::Add,
+                left: Box::new(addr()),
+                right: Box::new(addr()),
+            }),
+        }]);
+        hoist_common_subexpressions(&mut func, &[], 0);
+        assert_eq!(func.body.len(), 1, "min_size 0 disables hoisting");
+    }
+}
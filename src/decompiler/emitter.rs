@@ -3,8 +3,11 @@
 //! This module implements the code generation phase, turning our
 //! intermediate representation into readable GLSL code.
 
-use super::ast::{Expr, Function, ScalarType, Stmt, UnaryOp};
-use std::collections::HashMap;
+use super::ast::{BinOp, Expr, Function, ScalarType, Stmt, UnaryOp};
+use super::module::{DataSegment, ExportInfo, ExportKind, GlobalInfo, TableInfo};
+use super::typeinfer::TypeEnv;
+use crate::gl_constants::enum_name;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
 
 /// Configuration for the GLSL emitter.
@@ -16,6 +19,9 @@ pub struct EmitterConfig {
     pub use_es: bool,
     /// Indent string (e.g., "  " or "\t")
     pub indent: String,
+    /// Minimum size (in AST nodes) of a repeated subexpression before it is
+    /// hoisted into a named temporary; 0 disables hoisting
+    pub hoist_min_size: usize,
 }
 
 impl Default for EmitterConfig {
@@ -24,6 +30,7 @@ impl Default for EmitterConfig {
             glsl_version: 300,
             use_es: true,
             indent: "    ".to_string(),
+            hoist_min_size: 4,
         }
     }
 }
@@ -34,6 +41,22 @@ pub struct Emitter {
     output: String,
     indent_level: usize,
     function_names: HashMap<u32, String>,
+    local_names: HashMap<(u32, u32), String>,
+    memory_names: BTreeMap<u32, String>,
+    /// Index of the function being emitted, for local name lookup
+    current_func: u32,
+    /// Original source lines for annotating `SourceLine` markers
+    source_lines: Vec<String>,
+    /// Last source line annotated, to skip consecutive repeats
+    last_line: Option<u32>,
+    /// Parameter and local types of the function being emitted
+    local_types: Vec<ScalarType>,
+    /// Return type of the function being emitted
+    return_type: Option<ScalarType>,
+    /// Types of the module's globals
+    global_types: Vec<ScalarType>,
+    /// Types of the temporaries declared so far in the current function
+    temp_types: HashMap<u32, ScalarType>,
 }
 
 impl Emitter {
@@ -44,6 +67,15 @@ impl Emitter {
             output: String::new(),
             indent_level: 0,
             function_names: HashMap::new(),
+            local_names: HashMap::new(),
+            memory_names: BTreeMap::new(),
+            current_func: 0,
+            source_lines: Vec::new(),
+            last_line: None,
+            local_types: Vec::new(),
+            return_type: None,
+            global_types: Vec::new(),
+            temp_types: HashMap::new(),
         }
     }
 
@@ -57,6 +89,21 @@ impl Emitter {
         self.function_names = names;
     }
 
+    /// Set local names keyed by (function index, local index).
+    pub fn set_local_names(&mut self, names: HashMap<(u32, u32), String>) {
+        self.local_names = names;
+    }
+
+    /// Set the original source used to quote lines in `// line N:` comments.
+    pub fn set_source(&mut self, source: &str) {
+        self.source_lines = source.lines().map(|l| l.trim().to_string()).collect();
+    }
+
+    /// Set names for linear memory byte offsets.
+    pub fn set_memory_names(&mut self, names: BTreeMap<u32, String>) {
+        self.memory_names = names;
+    }
+
     /// Get the current indentation string.
     fn indent(&self) -> String {
         self.config.indent.repeat(self.indent_level)
@@ -96,8 +143,161 @@ impl Emitter {
         self.write_line("");
     }
 
+    /// Emit a comment block listing the module's exported symbols.
+    pub fn emit_exports(&mut self, exports: &[ExportInfo]) {
+        if exports.is_empty() {
+            return;
+        }
+        self.write_line("// Exports:");
+        for export in exports {
+            let target = match export.kind {
+                ExportKind::Function => self.function_name(export.index),
+                ExportKind::Table => format!("table{}", export.index),
+                ExportKind::Memory => format!("memory{}", export.index),
+                ExportKind::Global => format!("g{}", export.index),
+                ExportKind::Tag => format!("tag{}", export.index),
+            };
+            self.write_line(&format!("//   \"{}\" -> {}", export.name, target));
+        }
+        self.write_line("");
+    }
+
+    /// Emit constants for named memory addresses.
+    pub fn emit_memory_names(&mut self) {
+        if self.memory_names.is_empty() {
+            return;
+        }
+        let lines: Vec<String> = self
+            .memory_names
+            .iter()
+            .map(|(offset, name)| format!("const int {} = {};", name, offset))
+            .collect();
+        for line in lines {
+            self.write_line(&line);
+        }
+        self.write_line("");
+    }
+
+    /// Emit global declarations.
+    ///
+    /// Imported globals become uniforms, immutable globals become constants
+    /// and mutable globals become initialized module-scope variables.
+    pub fn emit_globals(&mut self, globals: &[GlobalInfo]) {
+        self.global_types = globals.iter().map(|g| g.ty).collect();
+        if globals.is_empty() {
+            return;
+        }
+        for (idx, global) in globals.iter().enumerate() {
+            let ty = global.ty.glsl_name();
+            if let Some((module, field)) = &global.import {
+                self.write_line(&format!(
+                    "uniform {} g{}; // import {}.{}",
+                    ty, idx, module, field
+                ));
+                continue;
+            }
+            let init = global
+                .init
+                .as_ref()
+                .map(|e| self.expr_to_string(e, 0))
+                .unwrap_or_else(|| "0".to_string());
+            if global.mutable {
+                self.write_line(&format!("{} g{} = {};", ty, idx, init));
+            } else {
+                self.write_line(&format!("const {} g{} = {};", ty, idx, init));
+            }
+        }
+        self.write_line("");
+    }
+
+    /// Emit data segments as constant int arrays (little-endian words).
+    pub fn emit_data_segments(&mut self, segments: &[DataSegment]) {
+        if segments.is_empty() {
+            return;
+        }
+        for (idx, segment) in segments.iter().enumerate() {
+            let placement = match segment.offset {
+                Some(offset) => format!("memory{} @ {}", segment.memory_idx, offset),
+                None => "passive".to_string(),
+            };
+            self.write_line(&format!(
+                "// data segment {}: {}, {} bytes",
+                idx,
+                placement,
+                segment.data.len()
+            ));
+            if segment.data.is_empty() {
+                continue;
+            }
+            let words: Vec<String> = segment
+                .data
+                .chunks(4)
+                .map(|chunk| {
+                    let mut bytes = [0u8; 4];
+                    bytes[..chunk.len()].copy_from_slice(chunk);
+                    format!("{}", i32::from_le_bytes(bytes))
+                })
+                .collect();
+            self.write_line(&format!(
+                "const int data{}[{}] = int[]({});",
+                idx,
+                words.len(),
+                words.join(", ")
+            ));
+        }
+        self.write_line("");
+    }
+
+    /// Emit function tables used by indirect calls.
+    ///
+    /// Each slot holds a function index (-1 when empty); the trailing
+    /// comment maps slots back to function names.
+    pub fn emit_tables(&mut self, tables: &[TableInfo]) {
+        let mut emitted = false;
+        for (idx, table) in tables.iter().enumerate() {
+            if table.entries.is_empty() {
+                continue;
+            }
+            let slots: Vec<String> = table
+                .entries
+                .iter()
+                .map(|e| e.map(|f| f.to_string()).unwrap_or_else(|| "-1".to_string()))
+                .collect();
+            let names: Vec<String> = table
+                .entries
+                .iter()
+                .map(|e| {
+                    e.map(|f| self.function_name(f))
+                        .unwrap_or_else(|| "-".to_string())
+                })
+                .collect();
+            self.write_line(&format!(
+                "const int table{}[{}] = int[]({}); // {}",
+                idx,
+                slots.len(),
+                slots.join(", "),
+                names.join(", ")
+            ));
+            emitted = true;
+        }
+        if emitted {
+            self.write_line("");
+        }
+    }
+
     /// Emit a function.
     pub fn emit_function(&mut self, func: &Function, name: &str) {
+        self.current_func = func.func_idx;
+        self.last_line = None;
+        self.local_types = func
+            .param_types
+            .iter()
+            .chain(func.local_types.iter())
+            .copied()
+            .collect();
+        self.return_type = func.return_type;
+        self.temp_types.clear();
+
         // Return type
         let return_type = func.return_type.map(|t| t.glsl_name()).unwrap_or("void");
 
@@ -106,7 +306,13 @@ impl Emitter {
             .param_types
             .iter()
             .enumerate()
-            .map(|(i, ty)| format!("{} p{}", ty.glsl_name(), i))
+            .map(|(i, ty)| {
+                format!(
+                    "{} {}",
+                    ty.glsl_name(),
+                    self.local_name(i as u32, func.param_count)
+                )
+            })
             .collect();
         let params_str = params.join(", ");
 
@@ -115,8 +321,9 @@ impl Emitter {
 
         // Local variable declarations (excluding parameters)
         for (i, ty) in func.local_types.iter().enumerate() {
-            let local_idx = func.param_count as usize + i;
-            self.write_line(&format!("{} v{};", ty.glsl_name(), local_idx));
+            let local_idx = func.param_count + i as u32;
+            let local_name = self.local_name(local_idx, func.param_count);
+            self.write_line(&format!("{} {};", ty.glsl_name(), local_name));
         }
         if !func.local_types.is_empty() {
             self.write_line("");
@@ -136,29 +343,34 @@ impl Emitter {
         match stmt {
             Stmt::LocalSet { local_idx, value } => {
                 let var_name = self.local_name(*local_idx, param_count);
-                let expr_str = self.expr_to_string(value, param_count);
+                let ty = self.type_env().expr_type(&Expr::LocalGet(*local_idx));
+                let expr_str = self.coerce(value, ty, param_count);
                 self.write_line(&format!("{} = {};", var_name, expr_str));
             }
             Stmt::GlobalSet { global_idx, value } => {
-                let expr_str = self.expr_to_string(value, param_count);
+                let ty = self.type_env().expr_type(&Expr::GlobalGet(*global_idx));
+                let expr_str = self.coerce(value, ty, param_count);
                 self.write_line(&format!("g{} = {};", global_idx, expr_str));
             }
+            Stmt::TempDecl {
+                temp_idx,
+                ty,
+                value,
+            } => {
+                let expr_str = self.coerce(value, *ty, param_count);
+                self.temp_types.insert(*temp_idx, *ty);
+                self.write_line(&format!("{} t{} = {};", ty.glsl_name(), temp_idx, expr_str));
+            }
             Stmt::MemoryStore {
                 ty,
                 offset,
                 addr,
                 value,
             } => {
-                let addr_str = self.expr_to_string(addr, param_count);
-                let value_str = self.expr_to_string(value, param_count);
-                // Assume 4-byte aligned for int/float
-                let index_expr = if *offset == 0 {
-                    format!("({}) >> 2", addr_str)
-                } else {
-                    format!("(({}) + {}) >> 2", addr_str, offset)
-                };
+                let value_str = self.coerce(value, *ty, param_count);
+                let index_expr = self.memory_index(addr, *offset, param_count);
                 match ty {
-                    ScalarType::Int | ScalarType::Long => {
+                    ScalarType::Int | ScalarType::Long | ScalarType::Bool | ScalarType::Uint => {
                         self.write_line(&format!("memory[{}] = {};", index_expr, value_str));
                     }
                     ScalarType::Float | ScalarType::Double => {
@@ -174,7 +386,7 @@ impl Emitter {
                 then_body,
                 else_body,
             } => {
-                let cond_str = self.expr_to_string(condition, param_count);
+                let cond_str = self.condition(condition, param_count);
                 self.write_line(&format!("if ({}) {{", cond_str));
                 self.indent_level += 1;
                 for s in then_body {
@@ -217,7 +429,10 @@ impl Emitter {
             }
             Stmt::Return { value } => {
                 if let Some(val) = value {
-                    let val_str = self.expr_to_string(val, param_count);
+                    let val_str = match self.return_type {
+                        Some(ty) => self.coerce(val, ty, param_count),
+                        None => self.expr_to_string(val, param_count),
+                    };
                     self.write_line(&format!("return {};", val_str));
                 } else {
                     self.write_line("return;");
@@ -230,6 +445,21 @@ impl Emitter {
             Stmt::Drop => {
                 // No-op in GLSL
             }
+            Stmt::SourceLine { line } => {
+                if self.last_line == Some(*line) {
+                    return;
+                }
+                self.last_line = Some(*line);
+                let text = (*line as usize)
+                    .checked_sub(1)
+                    .and_then(|i| self.source_lines.get(i))
+                    .filter(|text| !text.is_empty());
+                let comment = match text {
+                    Some(text) => format!("// line {}: {}", line, text),
+                    None => format!("// line {}", line),
+                };
+                self.write_line(&comment);
+            }
             Stmt::Unknown(desc) => {
                 self.write_line(&format!("/* unknown: {} */", desc));
             }
@@ -257,38 +487,41 @@ impl Emitter {
             }
             Expr::LocalGet(idx) => self.local_name(*idx, param_count),
             Expr::GlobalGet(idx) => format!("g{}", idx),
+            Expr::Temp(idx) => format!("t{}", idx),
             Expr::BinaryOp { op, left, right } => {
-                let left_str = self.expr_to_string(left, param_count);
-                let right_str = self.expr_to_string(right, param_count);
-                format!("({} {} {})", left_str, op.glsl_op(), right_str)
+                self.binary_op_to_string(*op, left, right, param_count)
             }
             Expr::UnaryOp { op, operand } => {
                 let operand_str = self.expr_to_string(operand, param_count);
+                let operand_ty = self.type_env().expr_type(operand);
                 if op.is_function() {
                     format!("{}({})", op.glsl_name(), operand_str)
                 } else if matches!(op, UnaryOp::Eqz) {
-                    format!("({} == 0)", operand_str)
+                    match operand_ty {
+                        ScalarType::Bool => format!("(!{})", operand_str),
+                        ScalarType::Uint => format!("({} == 0u)", operand_str),
+                        _ => format!("({} == 0)", operand_str),
+                    }
+                } else if operand_ty == ScalarType::Bool {
+                    format!("({}int({}))", op.glsl_name(), operand_str)
                 } else {
                     format!("({}{})", op.glsl_name(), operand_str)
                 }
             }
-            Expr::Convert {
-                from: _,
-                to,
-                operand,
-            } => {
-                let operand_str = self.expr_to_string(operand, param_count);
+            Expr::Convert { from, to, operand } => {
+                // Unsigned sources go through uint() first so the conversion
+                // sees the unsigned value
+                let operand_str = if *from == ScalarType::Uint {
+                    self.coerce(operand, ScalarType::Uint, param_count)
+                } else {
+                    self.expr_to_string(operand, param_count)
+                };
                 format!("{}({})", to.glsl_name(), operand_str)
             }
             Expr::MemoryLoad { ty, offset, addr } => {
-                let addr_str = self.expr_to_string(addr, param_count);
-                let index_expr = if *offset == 0 {
-                    format!("({}) >> 2", addr_str)
-                } else {
-                    format!("(({}) + {}) >> 2", addr_str, offset)
-                };
+                let index_expr = self.memory_index(addr, *offset, param_count);
                 match ty {
-                    ScalarType::Int | ScalarType::Long => {
+                    ScalarType::Int | ScalarType::Long | ScalarType::Bool | ScalarType::Uint => {
                         format!("memory[{}]", index_expr)
                     }
                     ScalarType::Float | ScalarType::Double => {
@@ -302,24 +535,35 @@ impl Emitter {
                     .map(|a| self.expr_to_string(a, param_count))
                     .collect();
 
-                // Look up function name, fallback to func{idx}
-                let func_name = self
-                    .function_names
-                    .get(func_idx)
-                    .cloned()
-                    .unwrap_or_else(|| format!("func{}", func_idx));
-
-                format!("{}({})", func_name, args_str.join(", "))
+                format!("{}({})", self.function_name(*func_idx), args_str.join(", "))
+            }
+            Expr::CallIndirect {
+                table_idx,
+                type_idx,
+                index,
+                args,
+            } => {
+                let mut args_str = vec![format!(
+                    "table{}[{}]",
+                    table_idx,
+                    self.expr_to_string(index, param_count)
+                )];
+                args_str.extend(args.iter().map(|a| self.expr_to_string(a, param_count)));
+                format!("call_indirect_t{}({})", type_idx, args_str.join(", "))
             }
             Expr::Select {
                 condition,
                 true_val,
                 false_val,
             } => {
-                let cond_str = self.expr_to_string(condition, param_count);
-                let true_str = self.expr_to_string(true_val, param_count);
-                let false_str = self.expr_to_string(false_val, param_count);
-                format!("({} != 0 ? {} : {})", cond_str, true_str, false_str)
+                let cond_str = match self.type_env().expr_type(condition) {
+                    ScalarType::Bool | ScalarType::Uint => self.condition(condition, param_count),
+                    _ => format!("{} != 0", self.expr_to_string(condition, param_count)),
+                };
+                let ty = self.type_env().expr_type(expr);
+                let true_str = self.coerce(true_val, ty, param_count);
+                let false_str = self.coerce(false_val, ty, param_count);
+                format!("({} ? {} : {})", cond_str, true_str, false_str)
             }
             Expr::VecConstruct { components } => {
                 let comps: Vec<String> = components
@@ -345,8 +589,165 @@ impl Emitter {
         }
     }
 
+    /// Convert a binary operation to a string, making bool and uint
+    /// operands explicit.
+    fn binary_op_to_string(
+        &self,
+        op: BinOp,
+        left: &Expr,
+        right: &Expr,
+        param_count: u32,
+    ) -> String {
+        let env = self.type_env();
+        let (lt, rt) = (env.expr_type(left), env.expr_type(right));
+
+        // Comparing a boolean against 0 or 1 is the boolean or its negation
+        if matches!(op, BinOp::Eq | BinOp::Ne) {
+            let flag = match (lt, left, rt, right) {
+                (ScalarType::Bool, b, _, Expr::ConstI32(c @ (0 | 1)))
+                | (_, Expr::ConstI32(c @ (0 | 1)), ScalarType::Bool, b) => Some((b, *c)),
+                _ => None,
+            };
+            if let Some((b, c)) = flag {
+                let b_str = self.expr_to_string(b, param_count);
+                return if (op == BinOp::Eq) == (c == 1) {
+                    b_str
+                } else {
+                    format!("(!{})", b_str)
+                };
+            }
+        }
+
+        if env.is_bool_pair(left, right) {
+            let glsl_op = match op {
+                BinOp::And => Some("&&"),
+                BinOp::Or => Some("||"),
+                BinOp::Xor => Some("^^"),
+                BinOp::Eq => Some("=="),
+                BinOp::Ne => Some("!="),
+                _ => None,
+            };
+            if let Some(glsl_op) = glsl_op {
+                return format!(
+                    "({} {} {})",
+                    self.coerce(left, ScalarType::Bool, param_count),
+                    glsl_op,
+                    self.coerce(right, ScalarType::Bool, param_count)
+                );
+            }
+        }
+
+        let operand_ty = if lt == ScalarType::Uint || rt == ScalarType::Uint {
+            Some(ScalarType::Uint)
+        } else if lt == ScalarType::Bool || rt == ScalarType::Bool {
+            Some(ScalarType::Int)
+        } else {
+            None
+        };
+        let (left_str, right_str) = match operand_ty {
+            Some(ty) => (
+                self.coerce(left, ty, param_count),
+                self.coerce(right, ty, param_count),
+            ),
+            None => (
+                self.expr_to_string(left, param_count),
+                self.expr_to_string(right, param_count),
+            ),
+        };
+        // Equality tests against a known GL enum (filter modes, formats...)
+        // name the enum so the generated sampler code stays readable
+        let right_str = match (op, right) {
+            (BinOp::Eq | BinOp::Ne, Expr::ConstI32(v)) => match enum_name(*v as u32) {
+                Some(name) => format!("{} /* {} */", right_str, name),
+                None => right_str,
+            },
+            _ => right_str,
+        };
+        format!("({} {} {})", left_str, op.glsl_op(), right_str)
+    }
+
+    /// Convert an expression to a string of type `to`.
+    ///
+    /// Only conversions involving `bool` or `uint` are spelled out; int and
+    /// float mixes keep their historical implicit form.
+    fn coerce(&self, expr: &Expr, to: ScalarType, param_count: u32) -> String {
+        let from = self.type_env().expr_type(expr);
+        if from.glsl_name() == to.glsl_name() {
+            return self.expr_to_string(expr, param_count);
+        }
+        match (expr, to) {
+            (Expr::ConstI32(v), ScalarType::Bool) => (*v != 0).to_string(),
+            (Expr::ConstI32(v), ScalarType::Uint) => format!("{}u", *v as u32),
+            (_, ScalarType::Bool) => {
+                let zero = match from {
+                    ScalarType::Uint => "0u",
+                    ScalarType::Float | ScalarType::Double => "0.0",
+                    _ => "0",
+                };
+                format!("({} != {})", self.expr_to_string(expr, param_count), zero)
+            }
+            _ if matches!(from, ScalarType::Bool | ScalarType::Uint) || to == ScalarType::Uint => {
+                format!(
+                    "{}({})",
+                    to.glsl_name(),
+                    self.expr_to_string(expr, param_count)
+                )
+            }
+            _ => self.expr_to_string(expr, param_count),
+        }
+    }
+
+    /// Convert an `if` condition to a string.
+    ///
+    /// Plain ints are left as-is to keep the existing output.
+    fn condition(&self, expr: &Expr, param_count: u32) -> String {
+        match self.type_env().expr_type(expr) {
+            ScalarType::Uint => self.coerce(expr, ScalarType::Bool, param_count),
+            _ => self.expr_to_string(expr, param_count),
+        }
+    }
+
+    /// Types visible to the function being emitted.
+    fn type_env(&self) -> TypeEnv<'_> {
+        TypeEnv {
+            locals: &self.local_types,
+            globals: &self.global_types,
+            temps: &self.temp_types,
+        }
+    }
+
+    /// Look up a function name, falling back to `func{idx}`.
+    fn function_name(&self, idx: u32) -> String {
+        self.function_names
+            .get(&idx)
+            .cloned()
+            .unwrap_or_else(|| format!("func{}", idx))
+    }
+
+    /// Build the `memory[]` word index for an access at `addr + offset`.
+    ///
+    /// Constant addresses that hit a named memory symbol use the symbol.
+    fn memory_index(&self, addr: &Expr, offset: u32, param_count: u32) -> String {
+        if let Expr::ConstI32(base) = addr {
+            let effective = (*base as u32).wrapping_add(offset);
+            if let Some(name) = self.memory_names.get(&effective) {
+                return format!("({}) >> 2", name);
+            }
+        }
+        let addr_str = self.expr_to_string(addr, param_count);
+        // Assume 4-byte aligned for int/float
+        if offset == 0 {
+            format!("({}) >> 2", addr_str)
+        } else {
+            format!("(({}) + {}) >> 2", addr_str, offset)
+        }
+    }
+
     /// Generate a local variable name.
     fn local_name(&self, idx: u32, param_count: u32) -> String {
+        if let Some(name) = self.local_names.get(&(self.current_func, idx)) {
+            return name.clone();
+        }
         if idx < param_count {
             format!("p{}", idx)
         } else {
@@ -444,4 +845,171 @@ mod tests {
         let output = function_to_glsl(&func, "red", None);
         assert!(output.contains("vec4(1.0, 0.0, 0.0, 1.0)"));
     }
+
+    #[test]
+    fn test_emitter_module_declarations() {
+        let mut emitter = Emitter::default_config();
+        emitter.set_function_names(HashMap::from([(1, "main".to_string())]));
+        emitter.emit_globals(&[
+            GlobalInfo {
+                ty: ScalarType::Int,
+                mutable: false,
+                init: None,
+                import: Some(("env".to_string(), "base".to_string())),
+            },
+            GlobalInfo {
+                ty: ScalarType::Int,
+                mutable: true,
+                init: Some(Expr::ConstI32(1024)),
+                import: None,
+            },
+        ]);
+        emitter.emit_data_segments(&[DataSegment {
+            memory_idx: 0,
+            offset: Some(16),
+            data: vec![1, 0, 0, 0, 2],
+        }]);
+        emitter.emit_tables(&[TableInfo {
+            entries: vec![None, Some(1)],
+        }]);
+
+        let output = emitter.finish();
+        assert!(output.contains("uniform int g0; // import env.base"));
+        assert!(output.contains("int g1 = 1024;"));
+        assert!(output.contains("const int data0[2] = int[](1, 2);"));
+        assert!(output.contains("const int table0[2] = int[](-1, 1); // -, main"));
+    }
+
+    #[test]
+    fn test_emitter_source_lines() {
+        let func = Function {
+            func_idx: 0,
+            param_count: 0,
+            param_types: vec![],
+            return_type: None,
+            local_types: vec![],
+            body: vec![
+                Stmt::SourceLine { line: 2 },
+                Stmt::SourceLine { line: 2 },
+                Stmt::SourceLine { line: 9 },
+                Stmt::Return { value: None },
+            ],
+        };
+
+        let mut emitter = Emitter::default_config();
+        emitter.set_source("#version 300 es\n  gl_Position = vec4(0);\n");
+        emitter.emit_function(&func, "main");
+
+        let output = emitter.finish();
+        assert_eq!(
+            output.matches("// line 2: gl_Position = vec4(0);").count(),
+            1
+        );
+        assert!(output.contains("// line 9\n"));
+    }
+
+    #[test]
+    fn test_emitter_symbol_names() {
+        let func = Function {
+            func_idx: 3,
+            param_count: 1,
+            param_types: vec![ScalarType::Float],
+            return_type: None,
+            local_types: vec![ScalarType::Float],
+            body: vec![Stmt::MemoryStore {
+                ty: ScalarType::Float,
+                offset: 4,
+                addr: Expr::ConstI32(1020),
+                value: Expr::LocalGet(1),
+            }],
+        };
+
+        let mut emitter = Emitter::default_config();
+        emitter.set_local_names(HashMap::from([
+            ((3, 0), "intensity".to_string()),
+            ((3, 1), "albedo".to_string()),
+        ]));
+        emitter.set_memory_names(BTreeMap::from([(1024, "u_color".to_string())]));
+        emitter.emit_memory_names();
+        emitter.emit_function(&func, "shade");
+
+        let output = emitter.finish();
+        assert!(output.contains("const int u_color = 1024;"));
+        assert!(output.contains("void shade(float intensity) {"));
+        assert!(output.contains("float albedo;"));
+        assert!(output.contains("memory[(u_color) >> 2] = floatBitsToInt(albedo);"));
+    }
+
+    #[test]
+    fn test_emitter_bool_and_uint_locals() {
+        let func = Function {
+            func_idx: 0,
+            param_count: 1,
+            param_types: vec![ScalarType::Int],
+            return_type: Some(ScalarType::Int),
+            local_types: vec![ScalarType::Bool, ScalarType::Uint],
+            body: vec![
+                Stmt::LocalSet {
+                    local_idx: 1,
+                    value: Expr::BinaryOp {
+                        op: BinOp::LtS,
+                        left: Box::new(Expr::LocalGet(0)),
+                        right: Box::new(Expr::ConstI32(5)),
+                    },
+                },
+                Stmt::LocalSet {
+                    local_idx: 2,
+                    value: Expr::LocalGet(0),
+                },
+                Stmt::If {
+                    condition: Expr::BinaryOp {
+                        op: BinOp::Eq,
+                        left: Box::new(Expr::LocalGet(1)),
+                        right: Box::new(Expr::ConstI32(0)),
+                    },
+                    then_body: vec![Stmt::Return {
+                        value: Some(Expr::BinaryOp {
+                            op: BinOp::GtU,
+                            left: Box::new(Expr::LocalGet(2)),
+                            right: Box::new(Expr::ConstI32(10)),
+                        }),
+                    }],
+                    else_body: None,
+                },
+                Stmt::Return {
+                    value: Some(Expr::LocalGet(2)),
+                },
+            ],
+        };
+
+        let output = function_to_glsl(&func, "f", None);
+        assert!(output.contains("bool v1;"));
+        assert!(output.contains("uint v2;"));
+        assert!(output.contains("v1 = (p0 < 5);"));
+        assert!(output.contains("v2 = uint(p0);"));
+        assert!(output.contains("if ((!v1)) {"));
+        assert!(output.contains("return int((v2 > 10u));"));
+        assert!(output.contains("return int(v2);"));
+    }
+
+    #[test]
+    fn test_emitter_names_gl_enum_comparisons() {
+        let func = Function {
+            func_idx: 0,
+            param_count: 1,
+            param_types: vec![ScalarType::Int],
+            return_type: Some(ScalarType::Int),
+            local_types: vec![],
+            body: vec![Stmt::Return {
+                value: Some(Expr::BinaryOp {
+                    op: BinOp::Eq,
+                    left: Box::new(Expr::LocalGet(0)),
+                    right: Box::new(Expr::ConstI32(0x2601)),
+                }),
+            }],
+        };
+
+        let output = function_to_glsl(&func, "f", None);
+        assert!(output.contains("(p0 == 9729 /* LINEAR */)"));
+    }
 }
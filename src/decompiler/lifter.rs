@@ -41,6 +41,8 @@ pub struct Lifter {
     _param_count: u32,
     /// Local types (including parameters)
     _local_types: Vec<ScalarType>,
+    /// Index of the imported `debug_step(line, func, result_ptr)` function, if any
+    line_marker_func: Option<u32>,
 }
 
 impl Lifter {
@@ -52,9 +54,22 @@ impl Lifter {
             current_body: Vec::new(),
             _param_count: param_count,
             _local_types: local_types,
+            line_marker_func: None,
         }
     }
 
+    /// Treat calls to `func_idx` as `debug_step` calls and drop them. They
+    /// are instrumentation: the lines they report come from the module's
+    /// line table instead (see [`mark_source_line`](Self::mark_source_line)).
+    pub fn set_line_marker_func(&mut self, func_idx: u32) {
+        self.line_marker_func = Some(func_idx);
+    }
+
+    /// Mark the start of the statement for GLSL source `line`.
+    pub fn mark_source_line(&mut self, line: u32) {
+        self.emit(Stmt::SourceLine { line });
+    }
+
     /// Push an expression onto the symbolic stack.
     fn push(&mut self, expr: Expr) {
         self.value_stack.push(expr);
@@ -192,7 +207,7 @@ impl Lifter {
             Operator::F64Sqrt => self.unary_op(UnaryOp::Sqrt),
 
             // Conversions
-            Operator::I32TruncF32S | Operator::I32TruncF32U => {
+            Operator::I32TruncF32S => {
                 let operand = self.pop();
                 self.push(Expr::Convert {
                     from: ScalarType::Float,
@@ -200,7 +215,15 @@ impl Lifter {
                     operand: Box::new(operand),
                 });
             }
-            Operator::F32ConvertI32S | Operator::F32ConvertI32U => {
+            Operator::I32TruncF32U => {
+                let operand = self.pop();
+                self.push(Expr::Convert {
+                    from: ScalarType::Float,
+                    to: ScalarType::Uint,
+                    operand: Box::new(operand),
+                });
+            }
+            Operator::F32ConvertI32S => {
                 let operand = self.pop();
                 self.push(Expr::Convert {
                     from: ScalarType::Int,
@@ -208,6 +231,14 @@ impl Lifter {
                     operand: Box::new(operand),
                 });
             }
+            Operator::F32ConvertI32U => {
+                let operand = self.pop();
+                self.push(Expr::Convert {
+                    from: ScalarType::Uint,
+                    to: ScalarType::Float,
+                    operand: Box::new(operand),
+                });
+            }
             Operator::I32ReinterpretF32 => {
                 // Bit-cast, keep as-is for now
                 let operand = self.pop();
@@ -398,6 +429,12 @@ impl Lifter {
                 self.emit(Stmt::Drop);
             }
 
+            Operator::Call { function_index } if Some(*function_index) == self.line_marker_func => {
+                // debug_step(line, func, result_ptr) returns nothing
+                self.pop();
+                self.pop();
+                self.pop();
+            }
             Operator::Call { function_index } => {
                 // For now, we don't know the arity, so we'll handle this minimally
                 self.push(Expr::Call {
@@ -405,6 +442,19 @@ impl Lifter {
                     args: Vec::new(),
                 });
             }
+            Operator::CallIndirect {
+                type_index,
+                table_index,
+            } => {
+                // The table slot is on top of the stack; arity is unknown as for direct calls
+                let index = self.pop();
+                self.push(Expr::CallIndirect {
+                    table_idx: *table_index,
+                    type_idx: *type_index,
+                    index: Box::new(index),
+                    args: Vec::new(),
+                });
+            }
 
             Operator::Nop => {}
             Operator::Unreachable => {
@@ -509,6 +559,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_lifter_line_marker() {
+        let mut lifter = Lifter::new(0, vec![]);
+        lifter.set_line_marker_func(0);
+        lifter.mark_source_line(7);
+        lifter.process_operator(&Operator::I32Const { value: 7 });
+        lifter.process_operator(&Operator::I32Const { value: -1 });
+        lifter.process_operator(&Operator::I32Const { value: 0 });
+        lifter.process_operator(&Operator::Call { function_index: 0 });
+        assert!(lifter.value_stack.is_empty());
+        assert!(matches!(
+            lifter.current_body.as_slice(),
+            [Stmt::SourceLine { line: 7 }]
+        ));
+    }
+
     #[test]
     fn test_lifter_add() {
         let mut lifter = Lifter::new(0, vec![]);
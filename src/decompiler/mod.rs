@@ -18,6 +18,10 @@
 //!
 //! 4. **Emitter** (`emitter.rs`): Generates GLSL source code from the AST.
 //!
+//! Between lifting and emission, `typeinfer.rs` recovers `bool` and `uint`
+//! locals, `simplifier.rs` folds expressions with equality saturation and
+//! `cse.rs` hoists repeated subexpressions into named temporaries.
+//!
 //! # Example
 //!
 //! ```ignore
@@ -29,11 +33,14 @@
 //! ```
 
 pub mod ast;
+pub mod cse;
 pub mod emitter;
 pub mod lifter;
 pub mod module;
 pub mod parser;
 pub mod simplifier;
+pub mod symbols;
+pub mod typeinfer;
 
 use anyhow::Result;
 use emitter::{Emitter, EmitterConfig};
@@ -43,7 +50,8 @@ use simplifier::simplify_expr;
 /// Decompile WASM bytecode to GLSL source code.
 ///
 /// This is the main entry point for the decompiler. It parses the WASM
-/// module, lifts all functions to AST form, and emits GLSL code.
+/// module, lifts all functions to AST form, and emits GLSL code preceded by
+/// the module-level declarations: exports, globals, data segments and tables.
 ///
 /// # Arguments
 ///
@@ -71,25 +79,73 @@ pub fn decompile_to_glsl(wasm_bytes: &[u8]) -> Result<String> {
 ///
 /// A string containing the decompiled GLSL source code.
 pub fn decompile_to_glsl_with_config(wasm_bytes: &[u8], config: EmitterConfig) -> Result<String> {
+    decompile_to_glsl_with_symbols(wasm_bytes, config, &SymbolMap::new())
+}
+
+/// Decompile WASM bytecode to GLSL source code using caller-supplied names.
+///
+/// Names in `symbols` override those from the module's name section, so a
+/// map saved from an earlier session keeps its names on the next run.
+///
+/// # Arguments
+///
+/// * `wasm_bytes` - The raw WASM bytecode to decompile
+/// * `config` - Emitter configuration (GLSL version, indentation, etc.)
+/// * `symbols` - Function, local and memory address names
+///
+/// # Returns
+///
+/// A string containing the decompiled GLSL source code.
+pub fn decompile_to_glsl_with_symbols(
+    wasm_bytes: &[u8],
+    config: EmitterConfig,
+    symbols: &SymbolMap,
+) -> Result<String> {
     let mut module = parse_wasm(wasm_bytes)?;
+    module.apply_symbols(symbols);
+
+    // Recover bool and uint locals while operator signedness is still known
+    let global_types: Vec<_> = module.globals.iter().map(|g| g.ty).collect();
+    for func in module.functions.values_mut() {
+        typeinfer::infer_local_types(func, &global_types);
+    }
 
     // Phase 3: Simplify all expressions in all functions using egg
     for func in module.functions.values_mut() {
         simplify_function(func);
     }
 
+    // Hoist repeated subexpressions into temporaries for readability
+    for func in module.functions.values_mut() {
+        cse::hoist_common_subexpressions(func, &global_types, config.hoist_min_size);
+    }
+
     let mut emitter = Emitter::new(config);
 
-    // Set function names map for proper call emission
+    // Set names for proper call, local and memory emission
     emitter.set_function_names(module.function_names.clone());
+    emitter.set_local_names(module.local_names.clone());
+    emitter.set_memory_names(module.memory_names.clone());
+    if let Some(source) = &module.source {
+        emitter.set_source(source);
+    }
 
     // Emit header
     emitter.emit_header();
 
+    // Note exported symbols before any code
+    emitter.emit_exports(&module.exports);
+
     // Emit memory buffer declaration if needed
     // (We could make this configurable)
     emitter.emit_memory_buffer();
 
+    // Module-level declarations referenced by function bodies
+    emitter.emit_globals(&module.globals);
+    emitter.emit_data_segments(&module.data_segments);
+    emitter.emit_tables(&module.tables);
+    emitter.emit_memory_names();
+
     // Emit all functions in WASM index order (deterministic)
     let mut indices: Vec<_> = module.functions.keys().copied().collect();
     indices.sort();
@@ -120,6 +176,9 @@ fn simplify_stmt(stmt: &mut ast::Stmt) {
         ast::Stmt::GlobalSet { value, .. } => {
             *value = simplify_expr(value);
         }
+        ast::Stmt::TempDecl { value, .. } => {
+            *value = simplify_expr(value);
+        }
         ast::Stmt::MemoryStore { addr, value, .. } => {
             *addr = simplify_expr(addr);
             *value = simplify_expr(value);
@@ -161,6 +220,7 @@ fn simplify_stmt(stmt: &mut ast::Stmt) {
         ast::Stmt::Break { .. }
         | ast::Stmt::Continue { .. }
         | ast::Stmt::Drop
+        | ast::Stmt::SourceLine { .. }
         | ast::Stmt::Unknown(_) => {}
     }
 }
@@ -179,9 +239,16 @@ pub fn decompile_function_to_glsl(wasm_bytes: &[u8], func_idx: u32) -> Result<St
     let module = parse_wasm(wasm_bytes)?;
 
     if let Some(func) = module.get_function(func_idx) {
+        let mut func = func.clone();
+        let global_types: Vec<_> = module.globals.iter().map(|g| g.ty).collect();
+        typeinfer::infer_local_types(&mut func, &global_types);
         let name = module.get_function_name(func_idx);
         let mut emitter = Emitter::new(EmitterConfig::default());
-        emitter.emit_function(func, &name);
+        emitter.set_local_names(module.local_names.clone());
+        if let Some(source) = &module.source {
+            emitter.set_source(source);
+        }
+        emitter.emit_function(&func, &name);
         Ok(emitter.finish())
     } else {
         Err(anyhow::anyhow!("Function {} not found", func_idx))
@@ -200,6 +267,7 @@ pub fn parse_module(wasm_bytes: &[u8]) -> Result<DecompiledModule> {
 pub use ast::{BinOp, Expr, Function, ScalarType, Stmt, UnaryOp};
 pub use module::DecompiledModule;
 pub use simplifier::SimplifierConfig;
+pub use symbols::SymbolMap;
 
 #[cfg(test)]
 mod tests {
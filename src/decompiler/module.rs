@@ -1,10 +1,75 @@
 //! Module container for decompiled functions.
 //!
 //! This module provides a container that holds the results of parsing
-//! a WASM module and stores decompiled functions.
+//! a WASM module and stores decompiled functions along with module-level
+//! declarations (globals, data segments, tables and exports).
 
-use super::ast::Function;
-use std::collections::HashMap;
+use super::ast::{Expr, Function, ScalarType};
+use super::symbols::SymbolMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// A global variable, either imported or defined by the module.
+#[derive(Debug, Clone)]
+pub struct GlobalInfo {
+    /// Value type of the global
+    pub ty: ScalarType,
+    /// Whether the global may be written with `global.set`
+    pub mutable: bool,
+    /// Constant initializer (None for imported globals)
+    pub init: Option<Expr>,
+    /// `(module, field)` the global is imported from
+    pub import: Option<(String, String)>,
+}
+
+/// A data segment from the data section.
+#[derive(Debug, Clone)]
+pub struct DataSegment {
+    /// Target memory index (active segments only)
+    pub memory_idx: u32,
+    /// Constant byte offset into memory; None for passive segments or
+    /// offsets that are not a plain constant
+    pub offset: Option<u32>,
+    /// Raw segment bytes
+    pub data: Vec<u8>,
+}
+
+/// A function table populated by active element segments.
+#[derive(Debug, Clone, Default)]
+pub struct TableInfo {
+    /// Function index stored in each slot (None for empty slots)
+    pub entries: Vec<Option<u32>>,
+}
+
+impl TableInfo {
+    /// Store a function index at `slot`, growing the table if needed.
+    pub fn set(&mut self, slot: usize, func_idx: u32) {
+        if slot >= self.entries.len() {
+            self.entries.resize(slot + 1, None);
+        }
+        self.entries[slot] = Some(func_idx);
+    }
+}
+
+/// The kind of item an export refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+    Function,
+    Table,
+    Memory,
+    Global,
+    Tag,
+}
+
+/// An exported symbol.
+#[derive(Debug, Clone)]
+pub struct ExportInfo {
+    /// Export name
+    pub name: String,
+    /// Kind of the exported item
+    pub kind: ExportKind,
+    /// Index within the item's index space
+    pub index: u32,
+}
 
 /// A decompiled WASM module.
 #[derive(Debug, Clone)]
@@ -13,8 +78,22 @@ pub struct DecompiledModule {
     pub functions: HashMap<u32, Function>,
     /// Function names (from export or name section)
     pub function_names: HashMap<u32, String>,
+    /// Local names keyed by (function index, local index), from the name section
+    pub local_names: HashMap<(u32, u32), String>,
+    /// Names for linear memory byte offsets (caller-supplied only)
+    pub memory_names: BTreeMap<u32, String>,
     /// Number of imported functions (offset for code section functions)
     pub import_count: u32,
+    /// Globals in global index order (imports first)
+    pub globals: Vec<GlobalInfo>,
+    /// Data segments in data section order
+    pub data_segments: Vec<DataSegment>,
+    /// Function tables in table index order (imports first)
+    pub tables: Vec<TableInfo>,
+    /// Exports in export section order
+    pub exports: Vec<ExportInfo>,
+    /// Original GLSL source, when embedded by a debug build
+    pub source: Option<String>,
 }
 
 impl DecompiledModule {
@@ -23,7 +102,14 @@ impl DecompiledModule {
         Self {
             functions: HashMap::new(),
             function_names: HashMap::new(),
+            local_names: HashMap::new(),
+            memory_names: BTreeMap::new(),
             import_count: 0,
+            globals: Vec::new(),
+            data_segments: Vec::new(),
+            tables: Vec::new(),
+            exports: Vec::new(),
+            source: None,
         }
     }
 
@@ -37,6 +123,16 @@ impl DecompiledModule {
         self.function_names.insert(idx, name);
     }
 
+    /// Merge caller-supplied symbols over the names found in the module.
+    pub fn apply_symbols(&mut self, symbols: &SymbolMap) {
+        self.function_names
+            .extend(symbols.functions.iter().map(|(k, v)| (*k, v.clone())));
+        self.local_names
+            .extend(symbols.locals.iter().map(|(k, v)| (*k, v.clone())));
+        self.memory_names
+            .extend(symbols.memory.iter().map(|(k, v)| (*k, v.clone())));
+    }
+
     /// Get a function by index.
     pub fn get_function(&self, idx: u32) -> Option<&Function> {
         self.functions.get(&idx)
@@ -83,4 +179,21 @@ mod tests {
         assert_eq!(module.get_function_name(0), "main");
         assert_eq!(module.get_function_name(1), "func1");
     }
+
+    #[test]
+    fn test_apply_symbols_overrides_names() {
+        let mut module = DecompiledModule::new();
+        module.set_function_name(0, "func_0".to_string());
+        let symbols = SymbolMap::parse("func 0 shade\nlocal 0 1 albedo").unwrap();
+        module.apply_symbols(&symbols);
+        assert_eq!(module.get_function_name(0), "shade");
+        assert_eq!(module.local_names[&(0, 1)], "albedo");
+    }
+
+    #[test]
+    fn test_table_set_grows() {
+        let mut table = TableInfo::default();
+        table.set(2, 7);
+        assert_eq!(table.entries, vec![None, None, Some(7)]);
+    }
 }
@@ -3,11 +3,16 @@
 //! This module uses wasmparser to iterate through WASM sections and
 //! extract the information needed for decompilation.
 
-use super::ast::{Function, ScalarType};
+use super::ast::{Expr, Function, ScalarType};
 use super::lifter::{valtype_to_scalar, Lifter};
-use super::module::DecompiledModule;
+use super::module::{DataSegment, DecompiledModule, ExportInfo, ExportKind, GlobalInfo, TableInfo};
+use crate::naga_wasm_backend::debug::{statement_lines, SOURCE_SECTION_NAME};
 use anyhow::Result;
-use wasmparser::{CompositeInnerType, FunctionBody, Parser, Payload, TypeRef};
+use std::collections::BTreeMap;
+use wasmparser::{
+    CompositeInnerType, ConstExpr, DataKind, ElementItems, ElementKind, ExternalKind, FunctionBody,
+    Operator, Parser, Payload, TypeRef,
+};
 
 /// Stored function type info for decompilation.
 #[derive(Clone)]
@@ -27,6 +32,11 @@ pub fn parse_wasm(data: &[u8]) -> Result<DecompiledModule> {
     let mut func_type_indices: Vec<u32> = Vec::new();
     // Track import count
     let mut import_func_count: u32 = 0;
+    // Imported `debug_step` function, whose calls are instrumentation
+    let mut line_marker_func: Option<u32> = None;
+    // Statement lines from the DWARF line table, by code section offset
+    let mut line_table = BTreeMap::new();
+    let mut code_start = 0;
 
     // First pass: collect metadata
     for payload in parser.parse_all(data) {
@@ -58,11 +68,103 @@ pub fn parse_wasm(data: &[u8]) -> Result<DecompiledModule> {
             Payload::ImportSection(reader) => {
                 for import in reader {
                     let import = import?;
-                    if matches!(import.ty, TypeRef::Func(_)) {
-                        import_func_count += 1;
+                    match import.ty {
+                        TypeRef::Func(_) => {
+                            if import.module == "env" && import.name == "debug_step" {
+                                line_marker_func = Some(import_func_count);
+                            }
+                            import_func_count += 1;
+                        }
+                        TypeRef::Global(ty) => module.globals.push(GlobalInfo {
+                            ty: valtype_to_scalar(ty.content_type),
+                            mutable: ty.mutable,
+                            init: None,
+                            import: Some((import.module.to_string(), import.name.to_string())),
+                        }),
+                        TypeRef::Table(_) => module.tables.push(TableInfo::default()),
+                        _ => {}
                     }
                 }
             }
+            Payload::TableSection(reader) => {
+                for table in reader {
+                    let table = table?;
+                    module.tables.push(TableInfo {
+                        entries: vec![None; table.ty.initial as usize],
+                    });
+                }
+            }
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    let global = global?;
+                    module.globals.push(GlobalInfo {
+                        ty: valtype_to_scalar(global.ty.content_type),
+                        mutable: global.ty.mutable,
+                        init: Some(const_expr_to_expr(&global.init_expr)),
+                        import: None,
+                    });
+                }
+            }
+            Payload::ElementSection(reader) => {
+                for element in reader {
+                    let element = element?;
+                    let (table_idx, base) = match element.kind {
+                        ElementKind::Active {
+                            table_index,
+                            offset_expr,
+                        } => match const_expr_offset(&offset_expr) {
+                            Some(base) => (table_index.unwrap_or(0), base),
+                            None => continue,
+                        },
+                        // Passive and declared segments don't populate a table
+                        _ => continue,
+                    };
+                    let mut funcs = Vec::new();
+                    match element.items {
+                        ElementItems::Functions(items) => {
+                            for func_idx in items {
+                                funcs.push(Some(func_idx?));
+                            }
+                        }
+                        ElementItems::Expressions(_, items) => {
+                            for expr in items {
+                                let mut ops = expr?.get_operators_reader();
+                                funcs.push(match ops.read()? {
+                                    Operator::RefFunc { function_index } => Some(function_index),
+                                    _ => None,
+                                });
+                            }
+                        }
+                    }
+                    while module.tables.len() <= table_idx as usize {
+                        module.tables.push(TableInfo::default());
+                    }
+                    let table = &mut module.tables[table_idx as usize];
+                    for (i, func_idx) in funcs.into_iter().enumerate() {
+                        if let Some(func_idx) = func_idx {
+                            table.set(base as usize + i, func_idx);
+                        }
+                    }
+                }
+            }
+            Payload::DataSection(reader) => {
+                for data in reader {
+                    let data = data?;
+                    let (memory_idx, offset) = match data.kind {
+                        DataKind::Active {
+                            memory_index,
+                            offset_expr,
+                        } => (memory_index, const_expr_offset(&offset_expr)),
+                        DataKind::Passive => (0, None),
+                    };
+                    module.data_segments.push(DataSegment {
+                        memory_idx,
+                        offset,
+                        data: data.data.to_vec(),
+                    });
+                }
+            }
+            Payload::CodeSectionStart { range, .. } => code_start = range.start,
             Payload::FunctionSection(reader) => {
                 for func in reader {
                     let type_idx = func?;
@@ -72,22 +174,53 @@ pub fn parse_wasm(data: &[u8]) -> Result<DecompiledModule> {
             Payload::ExportSection(reader) => {
                 for export in reader {
                     let export = export?;
-                    if let wasmparser::ExternalKind::Func = export.kind {
-                        module.set_function_name(export.index, export.name.to_string());
-                    }
+                    let kind = match export.kind {
+                        ExternalKind::Func => {
+                            module.set_function_name(export.index, export.name.to_string());
+                            ExportKind::Function
+                        }
+                        ExternalKind::Table => ExportKind::Table,
+                        ExternalKind::Memory => ExportKind::Memory,
+                        ExternalKind::Global => ExportKind::Global,
+                        ExternalKind::Tag => ExportKind::Tag,
+                    };
+                    module.exports.push(ExportInfo {
+                        name: export.name.to_string(),
+                        kind,
+                        index: export.index,
+                    });
                 }
             }
             Payload::CustomSection(reader) => {
-                if reader.name() == "name" {
+                if reader.name() == SOURCE_SECTION_NAME {
+                    module.source = Some(String::from_utf8_lossy(reader.data()).into_owned());
+                } else if reader.name() == ".debug_line" {
+                    line_table = statement_lines(reader.data()).unwrap_or_default();
+                } else if reader.name() == "name" {
                     use wasmparser::{BinaryReader, Name, NameSectionReader};
                     let binary_reader = BinaryReader::new(reader.data(), reader.data_offset());
                     let name_section = NameSectionReader::new(binary_reader);
                     for name in name_section {
-                        if let Name::Function(names) = name? {
-                            for naming in names {
-                                let naming = naming?;
-                                module.set_function_name(naming.index, naming.name.to_string());
+                        match name? {
+                            Name::Function(names) => {
+                                for naming in names {
+                                    let naming = naming?;
+                                    module.set_function_name(naming.index, naming.name.to_string());
+                                }
+                            }
+                            Name::Local(locals) => {
+                                for func in locals {
+                                    let func = func?;
+                                    for naming in func.names {
+                                        let naming = naming?;
+                                        module.local_names.insert(
+                                            (func.index, naming.index),
+                                            naming.name.to_string(),
+                                        );
+                                    }
+                                }
                             }
+                            _ => {}
                         }
                     }
                 }
@@ -96,7 +229,12 @@ pub fn parse_wasm(data: &[u8]) -> Result<DecompiledModule> {
         }
     }
 
-    // Second pass: decompile function bodies
+    // Second pass: decompile function bodies. Line table addresses are
+    // relative to the code section; operators are read at module offsets
+    let source_lines: BTreeMap<usize, u32> = line_table
+        .into_iter()
+        .map(|(address, line)| (code_start + address as usize, line))
+        .collect();
     let parser = Parser::new(0);
     let mut code_idx = 0u32;
 
@@ -111,7 +249,8 @@ pub fn parse_wasm(data: &[u8]) -> Result<DecompiledModule> {
             let func_type_info = types.get(type_idx as usize);
 
             if let Some(type_info) = func_type_info {
-                let func = decompile_function(func_idx, type_info, body)?;
+                let func =
+                    decompile_function(func_idx, type_info, body, line_marker_func, &source_lines)?;
                 module.add_function(func);
             }
             code_idx += 1;
@@ -122,11 +261,37 @@ pub fn parse_wasm(data: &[u8]) -> Result<DecompiledModule> {
     Ok(module)
 }
 
-/// Decompile a single function body.
+/// Lift a constant initializer expression (global init, segment offset).
+fn const_expr_to_expr(expr: &ConstExpr) -> Expr {
+    let mut ops = expr.get_operators_reader();
+    match ops.read() {
+        Ok(Operator::I32Const { value }) => Expr::ConstI32(value),
+        Ok(Operator::I64Const { value }) => Expr::ConstI64(value),
+        Ok(Operator::F32Const { value }) => Expr::ConstF32(f32::from_bits(value.bits())),
+        Ok(Operator::F64Const { value }) => Expr::ConstF64(f64::from_bits(value.bits())),
+        Ok(Operator::GlobalGet { global_index }) => Expr::GlobalGet(global_index),
+        Ok(op) => Expr::Unknown(format!("{:?}", op)),
+        Err(e) => Expr::Unknown(e.to_string()),
+    }
+}
+
+/// Resolve a segment offset expression to a constant, if it is one.
+fn const_expr_offset(expr: &ConstExpr) -> Option<u32> {
+    match const_expr_to_expr(expr) {
+        Expr::ConstI32(v) => Some(v as u32),
+        Expr::ConstI64(v) => Some(v as u32),
+        _ => None,
+    }
+}
+
+/// Decompile a single function body, marking the statements that start at
+/// the module offsets in `source_lines` with their GLSL line.
 fn decompile_function(
     func_idx: u32,
     type_info: &FuncTypeInfo,
     body: FunctionBody,
+    line_marker_func: Option<u32>,
+    source_lines: &BTreeMap<usize, u32>,
 ) -> Result<Function> {
     let param_types = type_info.params.clone();
     let param_count = param_types.len() as u32;
@@ -143,10 +308,16 @@ fn decompile_function(
 
     // Create lifter and process operators
     let mut lifter = Lifter::new(param_count, local_types.clone());
+    if let Some(marker) = line_marker_func {
+        lifter.set_line_marker_func(marker);
+    }
     let operators_reader = body.get_operators_reader()?;
 
-    for op_result in operators_reader {
-        let op = op_result?;
+    for op_result in operators_reader.into_iter_with_offsets() {
+        let (op, offset) = op_result?;
+        if let Some(&line) = source_lines.get(&offset) {
+            lifter.mark_source_line(line);
+        }
         lifter.process_operator(&op);
     }
 
@@ -181,6 +352,52 @@ mod tests {
         0x0A, 0x06, 0x01, 0x04, 0x00, 0x41, 0x2A, 0x0B, // func: i32.const 42, end
     ];
 
+    // Module with a table, memory, mutable global, element and data segments
+    const MODULE_WASM: &[u8] = &[
+        0x00, 0x61, 0x73, 0x6D, // magic
+        0x01, 0x00, 0x00, 0x00, // version
+        // Type section
+        0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7F, // () -> i32
+        // Function section
+        0x03, 0x02, 0x01, 0x00, // function 0 uses type 0
+        // Table section
+        0x04, 0x04, 0x01, 0x70, 0x00, 0x02, // funcref table, min 2
+        // Memory section
+        0x05, 0x03, 0x01, 0x00, 0x01, // memory, min 1 page
+        // Global section
+        0x06, 0x07, 0x01, 0x7F, 0x01, 0x41, 0x80, 0x08, 0x0B, // mut i32 = 1024
+        // Export section
+        0x07, 0x0C, 0x02, 0x04, 0x6D, 0x61, 0x69, 0x6E, 0x00, 0x00, // "main" = func 0
+        0x01, 0x67, 0x03, 0x00, // "g" = global 0
+        // Element section
+        0x09, 0x07, 0x01, 0x00, 0x41, 0x01, 0x0B, 0x01, 0x00, // table0[1] = func 0
+        // Code section
+        0x0A, 0x06, 0x01, 0x04, 0x00, 0x41, 0x2A, 0x0B, // func: i32.const 42, end
+        // Data section
+        0x0B, 0x0B, 0x01, 0x00, 0x41, 0x10, 0x0B, 0x05, 0x01, 0x00, 0x00, 0x00,
+        0x02, // memory[16..21]
+    ];
+
+    #[test]
+    fn test_parse_module_declarations() {
+        let module = parse_wasm(MODULE_WASM).unwrap();
+
+        assert_eq!(module.globals.len(), 1);
+        assert!(module.globals[0].mutable);
+        assert!(matches!(module.globals[0].init, Some(Expr::ConstI32(1024))));
+
+        assert_eq!(module.tables.len(), 1);
+        assert_eq!(module.tables[0].entries, vec![None, Some(0)]);
+
+        assert_eq!(module.data_segments.len(), 1);
+        assert_eq!(module.data_segments[0].offset, Some(16));
+        assert_eq!(module.data_segments[0].data, vec![1, 0, 0, 0, 2]);
+
+        assert_eq!(module.exports.len(), 2);
+        assert_eq!(module.exports[1].name, "g");
+        assert_eq!(module.exports[1].kind, ExportKind::Global);
+    }
+
     #[test]
     fn test_parse_minimal_wasm() {
         let result = parse_wasm(MINIMAL_WASM);
@@ -189,4 +406,56 @@ mod tests {
         assert_eq!(module.functions.len(), 1);
         assert_eq!(module.get_function_name(0), "main");
     }
+
+    #[test]
+    fn test_source_lines_come_from_the_line_table() {
+        use crate::decompiler::ast::Stmt;
+        use crate::naga_wasm_backend::debug::{append_custom_section, DwarfGenerator};
+        use wasm_encoder::{CodeSection, EntityType, Function, FunctionSection, ImportSection};
+        use wasm_encoder::{Instruction, TypeSection, ValType};
+
+        // debug_step(7, -1, 0) ahead of the statement on line 7
+        let mut types = TypeSection::new();
+        types.ty().function(vec![ValType::I32; 3], vec![]);
+        types.ty().function(vec![], vec![]);
+        let mut imports = ImportSection::new();
+        imports.import("env", "debug_step", EntityType::Function(0));
+        let mut functions = FunctionSection::new();
+        functions.function(1);
+        let mut body = Function::new(vec![]);
+        body.instruction(&Instruction::I32Const(7));
+        body.instruction(&Instruction::I32Const(-1));
+        body.instruction(&Instruction::I32Const(0));
+        body.instruction(&Instruction::Call(0));
+        body.instruction(&Instruction::Nop);
+        body.instruction(&Instruction::End);
+        let mut code = CodeSection::new();
+        code.function(&body);
+        let mut module = wasm_encoder::Module::new();
+        module
+            .section(&types)
+            .section(&imports)
+            .section(&functions)
+            .section(&code);
+        let mut wasm = module.finish();
+        let lines = |wasm: &[u8]| -> Vec<u32> {
+            let module = parse_wasm(wasm).unwrap();
+            module.functions[&1]
+                .body
+                .iter()
+                .filter_map(|stmt| match stmt {
+                    Stmt::SourceLine { line } => Some(*line),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // The debug_step call alone is dropped without a line
+        assert_eq!(lines(&wasm), Vec::<u32>::new());
+        let sections = DwarfGenerator::new("test.vert").finish(&wasm, 0).unwrap();
+        for (name, data) in &sections {
+            append_custom_section(&mut wasm, name, data);
+        }
+        assert_eq!(lines(&wasm), vec![7]);
+    }
 }
@@ -70,6 +70,8 @@ define_language! {
         // Type conversions
         "int" = ToInt([Id; 1]),
         "float" = ToFloat([Id; 1]),
+        "uint" = ToUint([Id; 1]),
+        "ufloat" = UintToFloat([Id; 1]),
 
         // Function calls
         "call" = Call(Box<[Id]>),
@@ -326,7 +328,10 @@ impl CostFunction<WasmLang> for GLSLCost {
             | WasmLang::Trunc(_) => 2,
 
             // Conversions
-            WasmLang::ToInt(_) | WasmLang::ToFloat(_) => 1,
+            WasmLang::ToInt(_)
+            | WasmLang::ToFloat(_)
+            | WasmLang::ToUint(_)
+            | WasmLang::UintToFloat(_) => 1,
 
             // Select (ternary)
             WasmLang::Select(_) => 3,
@@ -417,6 +422,10 @@ fn build_rec_expr(expr: &Expr, rec: &mut RecExpr<WasmLang>) -> Id {
             let sym = egg::Symbol::from(format!("v{}", idx));
             rec.add(WasmLang::Symbol(sym))
         }
+        Expr::Temp(idx) => {
+            let sym = egg::Symbol::from(format!("t{}", idx));
+            rec.add(WasmLang::Symbol(sym))
+        }
         Expr::GlobalGet(idx) => {
             let sym = egg::Symbol::from(format!("g{}", idx));
             rec.add(WasmLang::Symbol(sym))
@@ -463,13 +472,16 @@ fn build_rec_expr(expr: &Expr, rec: &mut RecExpr<WasmLang>) -> Id {
             };
             rec.add(node)
         }
-        Expr::Convert { to, operand, .. } => {
+        Expr::Convert { from, to, operand } => {
+            use super::ast::ScalarType;
             let o = build_rec_expr(operand, rec);
-            let node = match to {
-                super::ast::ScalarType::Int | super::ast::ScalarType::Long => WasmLang::ToInt([o]),
-                super::ast::ScalarType::Float | super::ast::ScalarType::Double => {
-                    WasmLang::ToFloat([o])
+            let node = match (from, to) {
+                (_, ScalarType::Uint) => WasmLang::ToUint([o]),
+                (ScalarType::Uint, ScalarType::Float | ScalarType::Double) => {
+                    WasmLang::UintToFloat([o])
                 }
+                (_, ScalarType::Int | ScalarType::Long | ScalarType::Bool) => WasmLang::ToInt([o]),
+                (_, ScalarType::Float | ScalarType::Double) => WasmLang::ToFloat([o]),
             };
             rec.add(node)
         }
@@ -556,6 +568,23 @@ fn build_rec_expr(expr: &Expr, rec: &mut RecExpr<WasmLang>) -> Id {
 
             rec.add(WasmLang::Call(children.into_boxed_slice()))
         }
+        Expr::CallIndirect {
+            table_idx,
+            type_idx,
+            index,
+            args,
+        } => {
+            // Encoded as a call whose first argument is the table slot
+            let func_sym = egg::Symbol::from(format!("call_indirect_{}_{}", table_idx, type_idx));
+            let func_id = rec.add(WasmLang::Symbol(func_sym));
+
+            let mut children = vec![func_id, build_rec_expr(index, rec)];
+            for arg in args {
+                children.push(build_rec_expr(arg, rec));
+            }
+
+            rec.add(WasmLang::Call(children.into_boxed_slice()))
+        }
         // For expressions we can't simplify, use a symbol placeholder
         Expr::MemoryLoad { .. } | Expr::Unknown(_) => {
             let sym = egg::Symbol::from("__unsimplified__");
@@ -597,6 +626,11 @@ fn rec_expr_node_to_expr(rec: &RecExpr<WasmLang>, id: Id) -> Expr {
                     return Expr::GlobalGet(idx);
                 }
             }
+            if let Some(stripped) = s_str.strip_prefix('t') {
+                if let Ok(idx) = stripped.parse::<u32>() {
+                    return Expr::Temp(idx);
+                }
+            }
             Expr::Unknown(s_str.to_string())
         }
         WasmLang::Add([l, r]) => Expr::BinaryOp {
@@ -717,6 +751,16 @@ fn rec_expr_node_to_expr(rec: &RecExpr<WasmLang>, id: Id) -> Expr {
             to: super::ast::ScalarType::Float,
             operand: Box::new(rec_expr_node_to_expr(rec, *o)),
         },
+        WasmLang::ToUint([o]) => Expr::Convert {
+            from: super::ast::ScalarType::Float,
+            to: super::ast::ScalarType::Uint,
+            operand: Box::new(rec_expr_node_to_expr(rec, *o)),
+        },
+        WasmLang::UintToFloat([o]) => Expr::Convert {
+            from: super::ast::ScalarType::Uint,
+            to: super::ast::ScalarType::Float,
+            operand: Box::new(rec_expr_node_to_expr(rec, *o)),
+        },
         WasmLang::Vec2([a, b]) => Expr::VecConstruct {
             components: vec![
                 rec_expr_node_to_expr(rec, *a),
@@ -770,6 +814,27 @@ fn rec_expr_node_to_expr(rec: &RecExpr<WasmLang>, id: Id) -> Expr {
 
             // First child is the function symbol
             let func_expr = rec_expr_node_to_expr(rec, children[0]);
+            if let Expr::Unknown(ref s) = func_expr {
+                if let Some(rest) = s.strip_prefix("call_indirect_") {
+                    let mut parts = rest.split('_').map(|p| p.parse::<u32>().unwrap_or(0));
+                    let table_idx = parts.next().unwrap_or(0);
+                    let type_idx = parts.next().unwrap_or(0);
+                    if children.len() < 2 {
+                        return Expr::Unknown("call_indirect without index".to_string());
+                    }
+                    let index = rec_expr_node_to_expr(rec, children[1]);
+                    let args = children[2..]
+                        .iter()
+                        .map(|id| rec_expr_node_to_expr(rec, *id))
+                        .collect();
+                    return Expr::CallIndirect {
+                        table_idx,
+                        type_idx,
+                        index: Box::new(index),
+                        args,
+                    };
+                }
+            }
             let func_idx = match func_expr {
                 Expr::Unknown(ref s) if s.starts_with("func") => s[4..].parse::<u32>().unwrap_or(0),
                 _ => 0,
@@ -0,0 +1,116 @@
+//! User-supplied symbol names for decompiled output.
+//!
+//! A `SymbolMap` names functions, locals and memory addresses. Names parsed
+//! from the module's `name` section are used by default; a caller-supplied
+//! map is merged on top so that names chosen during a reverse-engineering
+//! session override the debug names and can be persisted between runs.
+//!
+//! The text form holds one symbol per line; `#` starts a comment:
+//!
+//! ```text
+//! func 3 shade
+//! local 3 5 albedo
+//! mem 1024 u_color
+//! ```
+
+use anyhow::{anyhow, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Names for functions, locals and memory addresses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymbolMap {
+    /// Function index -> name
+    pub functions: HashMap<u32, String>,
+    /// (function index, local index) -> name; local indices include parameters
+    pub locals: HashMap<(u32, u32), String>,
+    /// Linear memory byte offset -> name
+    pub memory: BTreeMap<u32, String>,
+}
+
+impl SymbolMap {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a map from its text form.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut map = Self::new();
+        for (line_no, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let bad_line = || {
+                anyhow!(
+                    "symbol map line {}: malformed entry '{}'",
+                    line_no + 1,
+                    line
+                )
+            };
+            let num = |s: &str| s.parse::<u32>().map_err(|_| bad_line());
+            match fields.as_slice() {
+                ["func", idx, name] => {
+                    map.functions.insert(num(idx)?, name.to_string());
+                }
+                ["local", func, idx, name] => {
+                    map.locals.insert((num(func)?, num(idx)?), name.to_string());
+                }
+                ["mem", offset, name] => {
+                    map.memory.insert(num(offset)?, name.to_string());
+                }
+                _ => return Err(bad_line()),
+            }
+        }
+        Ok(map)
+    }
+
+    /// Returns true if the map holds no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.locals.is_empty() && self.memory.is_empty()
+    }
+}
+
+impl fmt::Display for SymbolMap {
+    /// Write the map in its text form, sorted for stable diffs.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut functions: Vec<_> = self.functions.iter().collect();
+        functions.sort();
+        for (idx, name) in functions {
+            writeln!(f, "func {} {}", idx, name)?;
+        }
+        let mut locals: Vec<_> = self.locals.iter().collect();
+        locals.sort();
+        for ((func, idx), name) in locals {
+            writeln!(f, "local {} {} {}", func, idx, name)?;
+        }
+        for (offset, name) in &self.memory {
+            writeln!(f, "mem {} {}", offset, name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_map_round_trip() {
+        let text = "# session names\nfunc 3 shade\nlocal 3 5 albedo\nmem 1024 u_color\n";
+        let map = SymbolMap::parse(text).unwrap();
+        assert_eq!(map.functions[&3], "shade");
+        assert_eq!(map.locals[&(3, 5)], "albedo");
+        assert_eq!(map.memory[&1024], "u_color");
+        assert_eq!(SymbolMap::parse(&map.to_string()).unwrap(), map);
+    }
+
+    #[test]
+    fn test_symbol_map_rejects_malformed_lines() {
+        assert!(SymbolMap::parse("func x y").is_err());
+        assert!(SymbolMap::parse("local 1 name").is_err());
+        assert!(SymbolMap::parse("  # only a comment\n").unwrap().is_empty());
+    }
+}
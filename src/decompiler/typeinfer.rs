@@ -0,0 +1,492 @@
+//! Type inference for decompiled locals.
+//!
+//! WASM only has `i32`, so booleans and unsigned integers arrive as plain
+//! ints. This pass looks at how each non-parameter `i32` local is defined and
+//! used and refines its type:
+//!
+//! - `bool` when every assignment is a comparison, `eqz` or the constant 0/1,
+//!   and every use is a condition or a logical operation on other booleans.
+//! - `uint` when it takes part in at least one unsigned operation (unsigned
+//!   comparison, logical shift right, unsigned conversion) and never in a
+//!   signed one.
+//!
+//! The pass runs before simplification, which does not preserve the
+//! signedness of operators. [`TypeEnv`] is shared with the emitter and the
+//! temporary hoisting pass so that all of them agree on expression types.
+
+use super::ast::{BinOp, Expr, Function, ScalarType, Stmt, UnaryOp};
+use std::collections::{HashMap, HashSet};
+
+/// Types of the names an expression can refer to.
+pub struct TypeEnv<'a> {
+    /// Parameter types followed by local types
+    pub locals: &'a [ScalarType],
+    /// Global types in global index order
+    pub globals: &'a [ScalarType],
+    /// Types of hoisted temporaries
+    pub temps: &'a HashMap<u32, ScalarType>,
+}
+
+impl TypeEnv<'_> {
+    /// Infer the scalar type of an expression.
+    ///
+    /// Call results and unknown expressions are assumed to be `int`.
+    pub fn expr_type(&self, expr: &Expr) -> ScalarType {
+        match expr {
+            Expr::ConstI32(_) => ScalarType::Int,
+            Expr::ConstI64(_) => ScalarType::Long,
+            Expr::ConstF32(_) => ScalarType::Float,
+            Expr::ConstF64(_) => ScalarType::Double,
+            Expr::LocalGet(idx) => self.local(*idx),
+            Expr::GlobalGet(idx) => self
+                .globals
+                .get(*idx as usize)
+                .copied()
+                .unwrap_or(ScalarType::Int),
+            Expr::Temp(idx) => self.temps.get(idx).copied().unwrap_or(ScalarType::Int),
+            Expr::BinaryOp { op, left, right } => {
+                if op.is_comparison() {
+                    return ScalarType::Bool;
+                }
+                if matches!(op, BinOp::And | BinOp::Or | BinOp::Xor)
+                    && self.is_bool_pair(left, right)
+                {
+                    return ScalarType::Bool;
+                }
+                let l = self.expr_type(left);
+                let r = self.expr_type(right);
+                match op {
+                    // The shift amount does not affect the result type
+                    BinOp::Shl | BinOp::ShrS | BinOp::ShrU if l == ScalarType::Bool => {
+                        ScalarType::Int
+                    }
+                    BinOp::Shl | BinOp::ShrS | BinOp::ShrU => l,
+                    _ if l == ScalarType::Uint || r == ScalarType::Uint => ScalarType::Uint,
+                    // Booleans in arithmetic are promoted to int
+                    _ if l == ScalarType::Bool && r == ScalarType::Bool => ScalarType::Int,
+                    _ if l == ScalarType::Bool => r,
+                    _ => l,
+                }
+            }
+            Expr::UnaryOp { op, operand } => match op {
+                UnaryOp::Eqz => ScalarType::Bool,
+                _ => match self.expr_type(operand) {
+                    ScalarType::Bool => ScalarType::Int,
+                    ty => ty,
+                },
+            },
+            Expr::Convert { to, .. } => *to,
+            Expr::MemoryLoad { ty, .. } => *ty,
+            Expr::Select {
+                true_val,
+                false_val,
+                ..
+            } => match self.expr_type(true_val) {
+                ScalarType::Bool => self.expr_type(false_val),
+                ty => ty,
+            },
+            Expr::VecConstruct { .. } | Expr::VecBinaryOp { .. } => ScalarType::Float,
+            Expr::Call { .. } | Expr::CallIndirect { .. } | Expr::Unknown(_) => ScalarType::Int,
+        }
+    }
+
+    /// Returns true if `expr` is a boolean or the constant 0 or 1.
+    pub fn is_boolish(&self, expr: &Expr) -> bool {
+        matches!(expr, Expr::ConstI32(0) | Expr::ConstI32(1))
+            || self.expr_type(expr) == ScalarType::Bool
+    }
+
+    /// Returns true if both operands can be treated as booleans and at
+    /// least one of them actually is one.
+    pub fn is_bool_pair(&self, left: &Expr, right: &Expr) -> bool {
+        self.is_boolish(left)
+            && self.is_boolish(right)
+            && (self.expr_type(left) == ScalarType::Bool
+                || self.expr_type(right) == ScalarType::Bool)
+    }
+
+    fn local(&self, idx: u32) -> ScalarType {
+        self.locals
+            .get(idx as usize)
+            .copied()
+            .unwrap_or(ScalarType::Int)
+    }
+}
+
+/// Refine the types of `func`'s `i32` locals to `bool` or `uint` where the
+/// way they are used allows it. Parameters keep their signature types.
+pub fn infer_local_types(func: &mut Function, global_types: &[ScalarType]) {
+    let param_count = func.param_count as usize;
+    let mut types: Vec<ScalarType> = func
+        .param_types
+        .iter()
+        .chain(func.local_types.iter())
+        .copied()
+        .collect();
+    let temps = HashMap::new();
+
+    // Booleans: start from every assigned int local and drop violators until
+    // the remaining set is consistent.
+    let mut candidates: HashSet<u32> = (param_count..types.len())
+        .filter(|&i| types[i] == ScalarType::Int)
+        .map(|i| i as u32)
+        .collect();
+    loop {
+        for idx in &candidates {
+            types[*idx as usize] = ScalarType::Bool;
+        }
+        let usage = collect_usage(&func.body, &types, global_types, &temps);
+        let rejected: Vec<u32> = candidates
+            .iter()
+            .copied()
+            .filter(|idx| {
+                !usage.assigned.contains(idx)
+                    || usage.non_bool_defs.contains(idx)
+                    || usage.uses(*idx).iter().any(|u| *u != Use::Bool)
+            })
+            .collect();
+        if rejected.is_empty() {
+            break;
+        }
+        for idx in rejected {
+            candidates.remove(&idx);
+            types[idx as usize] = ScalarType::Int;
+        }
+    }
+
+    // Unsigned integers: the remaining int locals with at least one unsigned
+    // use and no signed (or opaque) ones.
+    let mut candidates: HashSet<u32> = (param_count..types.len())
+        .filter(|&i| types[i] == ScalarType::Int)
+        .map(|i| i as u32)
+        .collect();
+    loop {
+        for idx in &candidates {
+            types[*idx as usize] = ScalarType::Uint;
+        }
+        let usage = collect_usage(&func.body, &types, global_types, &temps);
+        let rejected: Vec<u32> = candidates
+            .iter()
+            .copied()
+            .filter(|idx| {
+                let uses = usage.uses(*idx);
+                !uses.contains(&Use::Unsigned)
+                    || uses.iter().any(|u| matches!(u, Use::Signed | Use::Other))
+            })
+            .collect();
+        if rejected.is_empty() {
+            break;
+        }
+        for idx in rejected {
+            candidates.remove(&idx);
+            types[idx as usize] = ScalarType::Int;
+        }
+    }
+
+    func.local_types = types.split_off(param_count);
+}
+
+/// The context a local is read in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Use {
+    /// Condition or logical operand
+    Bool,
+    /// Operand of an unsigned operation
+    Unsigned,
+    /// Operand of a signed operation
+    Signed,
+    /// Sign-agnostic arithmetic or equality
+    Neutral,
+    /// Anything else: call arguments, addresses, stored values
+    Other,
+}
+
+#[derive(Default)]
+struct Usage {
+    uses: HashMap<u32, Vec<Use>>,
+    assigned: HashSet<u32>,
+    non_bool_defs: HashSet<u32>,
+}
+
+impl Usage {
+    fn uses(&self, idx: u32) -> &[Use] {
+        self.uses.get(&idx).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+fn collect_usage(
+    body: &[Stmt],
+    types: &[ScalarType],
+    globals: &[ScalarType],
+    temps: &HashMap<u32, ScalarType>,
+) -> Usage {
+    let mut collector = Collector {
+        env: TypeEnv {
+            locals: types,
+            globals,
+            temps,
+        },
+        usage: Usage::default(),
+    };
+    collector.body(body);
+    collector.usage
+}
+
+struct Collector<'a> {
+    env: TypeEnv<'a>,
+    usage: Usage,
+}
+
+impl Collector<'_> {
+    fn body(&mut self, body: &[Stmt]) {
+        for stmt in body {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::LocalSet { local_idx, value } => {
+                self.usage.assigned.insert(*local_idx);
+                if !self.env.is_boolish(value) {
+                    self.usage.non_bool_defs.insert(*local_idx);
+                }
+                let ctx = match self.env.local(*local_idx) {
+                    ScalarType::Bool => Use::Bool,
+                    ScalarType::Uint => Use::Neutral,
+                    _ => Use::Other,
+                };
+                self.expr(value, ctx);
+            }
+            Stmt::GlobalSet { value, .. }
+            | Stmt::TempDecl { value, .. }
+            | Stmt::ExprStmt(value) => self.expr(value, Use::Other),
+            Stmt::MemoryStore { addr, value, .. } => {
+                self.expr(addr, Use::Other);
+                self.expr(value, Use::Other);
+            }
+            Stmt::If {
+                condition,
+                then_body,
+                else_body,
+            } => {
+                self.expr(condition, Use::Bool);
+                self.body(then_body);
+                if let Some(else_body) = else_body {
+                    self.body(else_body);
+                }
+            }
+            Stmt::Block { body } | Stmt::Loop { body } => self.body(body),
+            Stmt::Return { value } => {
+                if let Some(value) = value {
+                    self.expr(value, Use::Other);
+                }
+            }
+            Stmt::Break { .. }
+            | Stmt::Continue { .. }
+            | Stmt::Drop
+            | Stmt::SourceLine { .. }
+            | Stmt::Unknown(_) => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr, ctx: Use) {
+        // Integer arithmetic passes its context through to its operands,
+        // except that an int in a condition is compared against zero.
+        let through = match ctx {
+            Use::Bool => Use::Neutral,
+            other => other,
+        };
+        match expr {
+            Expr::LocalGet(idx) => self.usage.uses.entry(*idx).or_default().push(ctx),
+            Expr::BinaryOp { op, left, right } => {
+                let (l, r) = match op {
+                    BinOp::And | BinOp::Or | BinOp::Xor | BinOp::Eq | BinOp::Ne
+                        if self.env.is_bool_pair(left, right) =>
+                    {
+                        (Use::Bool, Use::Bool)
+                    }
+                    BinOp::Eq | BinOp::Ne => (Use::Neutral, Use::Neutral),
+                    BinOp::LtU | BinOp::LeU | BinOp::GtU | BinOp::GeU => {
+                        (Use::Unsigned, Use::Unsigned)
+                    }
+                    BinOp::LtS | BinOp::LeS | BinOp::GtS | BinOp::GeS | BinOp::Div | BinOp::Rem => {
+                        (Use::Signed, Use::Signed)
+                    }
+                    BinOp::ShrU => (Use::Unsigned, Use::Neutral),
+                    BinOp::ShrS => (Use::Signed, Use::Neutral),
+                    BinOp::Shl => (through, Use::Neutral),
+                    BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::And | BinOp::Or | BinOp::Xor => {
+                        (through, through)
+                    }
+                };
+                self.expr(left, l);
+                self.expr(right, r);
+            }
+            Expr::UnaryOp { op, operand } => match op {
+                UnaryOp::Eqz if self.env.is_boolish(operand) => self.expr(operand, Use::Bool),
+                UnaryOp::Eqz => self.expr(operand, Use::Neutral),
+                UnaryOp::Neg | UnaryOp::Not => self.expr(operand, through),
+                _ => self.expr(operand, Use::Other),
+            },
+            Expr::Convert { from, operand, .. } => {
+                let ctx = match from {
+                    ScalarType::Uint => Use::Unsigned,
+                    _ => Use::Other,
+                };
+                self.expr(operand, ctx);
+            }
+            Expr::MemoryLoad { addr, .. } => self.expr(addr, Use::Other),
+            Expr::Select {
+                condition,
+                true_val,
+                false_val,
+            } => {
+                self.expr(condition, Use::Bool);
+                self.expr(true_val, ctx);
+                self.expr(false_val, ctx);
+            }
+            Expr::Call { args, .. } => {
+                for arg in args {
+                    self.expr(arg, Use::Other);
+                }
+            }
+            Expr::CallIndirect { index, args, .. } => {
+                self.expr(index, Use::Other);
+                for arg in args {
+                    self.expr(arg, Use::Other);
+                }
+            }
+            Expr::VecConstruct { components } => {
+                for c in components {
+                    self.expr(c, Use::Other);
+                }
+            }
+            Expr::VecBinaryOp { left, right, .. } => {
+                self.expr(left, Use::Other);
+                self.expr(right, Use::Other);
+            }
+            Expr::ConstI32(_)
+            | Expr::ConstI64(_)
+            | Expr::ConstF32(_)
+            | Expr::ConstF64(_)
+            | Expr::GlobalGet(_)
+            | Expr::Temp(_)
+            | Expr::Unknown(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(local_types: Vec<ScalarType>, body: Vec<Stmt>) -> Function {
+        Function {
+            func_idx: 0,
+            param_count: 1,
+            param_types: vec![ScalarType::Int],
+            return_type: None,
+            local_types,
+            body,
+        }
+    }
+
+    fn cmp(op: BinOp, left: Expr, right: Expr) -> Expr {
+        Expr::BinaryOp {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    #[test]
+    fn test_infer_bool_local() {
+        // v1 = p0 < 5; v2 = v1 & (p0 != 0); if (v2) { ... }
+        let mut func = function(
+            vec![ScalarType::Int, ScalarType::Int],
+            vec![
+                Stmt::LocalSet {
+                    local_idx: 1,
+                    value: cmp(BinOp::LtS, Expr::LocalGet(0), Expr::ConstI32(5)),
+                },
+                Stmt::LocalSet {
+                    local_idx: 2,
+                    value: cmp(
+                        BinOp::And,
+                        Expr::LocalGet(1),
+                        cmp(BinOp::Ne, Expr::LocalGet(0), Expr::ConstI32(0)),
+                    ),
+                },
+                Stmt::If {
+                    condition: Expr::LocalGet(2),
+                    then_body: vec![Stmt::Return { value: None }],
+                    else_body: None,
+                },
+            ],
+        );
+
+        infer_local_types(&mut func, &[]);
+        assert_eq!(func.local_types, vec![ScalarType::Bool, ScalarType::Bool]);
+    }
+
+    #[test]
+    fn test_infer_uint_and_int_locals() {
+        // v1 = p0; v2 = p0;
+        // if (v1 <u 10) { memory[v1 >>u 2] = v2 + 1; }
+        let mut func = function(
+            vec![ScalarType::Int, ScalarType::Int],
+            vec![
+                Stmt::LocalSet {
+                    local_idx: 1,
+                    value: Expr::LocalGet(0),
+                },
+                Stmt::LocalSet {
+                    local_idx: 2,
+                    value: Expr::LocalGet(0),
+                },
+                Stmt::If {
+                    condition: cmp(BinOp::LtU, Expr::LocalGet(1), Expr::ConstI32(10)),
+                    then_body: vec![Stmt::MemoryStore {
+                        ty: ScalarType::Int,
+                        offset: 0,
+                        addr: cmp(BinOp::ShrU, Expr::LocalGet(1), Expr::ConstI32(2)),
+                        value: cmp(BinOp::Add, Expr::LocalGet(2), Expr::ConstI32(1)),
+                    }],
+                    else_body: None,
+                },
+            ],
+        );
+
+        infer_local_types(&mut func, &[]);
+        // v2 is stored to memory, an opaque use, so it stays int
+        assert_eq!(func.local_types, vec![ScalarType::Uint, ScalarType::Int]);
+
+        // A signed comparison rules out uint
+        func.local_types = vec![ScalarType::Int, ScalarType::Int];
+        func.body.push(Stmt::If {
+            condition: cmp(BinOp::LtS, Expr::LocalGet(1), Expr::ConstI32(0)),
+            then_body: vec![],
+            else_body: None,
+        });
+        infer_local_types(&mut func, &[]);
+        assert_eq!(func.local_types, vec![ScalarType::Int, ScalarType::Int]);
+    }
+
+    #[test]
+    fn test_expr_type_promotes_bool_arithmetic() {
+        let locals = [ScalarType::Bool, ScalarType::Uint];
+        let temps = HashMap::new();
+        let env = TypeEnv {
+            locals: &locals,
+            globals: &[],
+            temps: &temps,
+        };
+        let both = cmp(BinOp::Add, Expr::LocalGet(0), Expr::LocalGet(0));
+        assert_eq!(env.expr_type(&both), ScalarType::Int);
+        let logical = cmp(BinOp::Or, Expr::LocalGet(0), Expr::ConstI32(1));
+        assert_eq!(env.expr_type(&logical), ScalarType::Bool);
+        let unsigned = cmp(BinOp::Mul, Expr::ConstI32(3), Expr::LocalGet(1));
+        assert_eq!(env.expr_type(&unsigned), ScalarType::Uint);
+    }
+}
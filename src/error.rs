@@ -1,7 +1,13 @@
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::ffi::CString;
 use std::os::raw::c_char;
 
+use crate::webgl2_context::types::{
+    ERR_COMPILATION, ERR_GL, ERR_INTERNAL, ERR_INVALID_ARGS, ERR_INVALID_ENUM, ERR_INVALID_HANDLE,
+    ERR_INVALID_OPERATION, ERR_NOT_IMPLEMENTED, ERR_OOM,
+};
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ErrorSource {
     WebGL,
@@ -17,6 +23,122 @@ pub enum WebGPUErrorFilter {
     Internal,
 }
 
+/// What kind of failure a [`Webgl2Error`] is. The discriminants are the
+/// errno values exports return and `wasm_get_last_error_code` reports, so
+/// they are part of the FFI and never change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ErrorCategory {
+    InvalidHandle = ERR_INVALID_HANDLE,
+    OutOfMemory = ERR_OOM,
+    InvalidArgs = ERR_INVALID_ARGS,
+    NotImplemented = ERR_NOT_IMPLEMENTED,
+    Gl = ERR_GL,
+    Internal = ERR_INTERNAL,
+    InvalidOperation = ERR_INVALID_OPERATION,
+    InvalidEnum = ERR_INVALID_ENUM,
+    Compilation = ERR_COMPILATION,
+}
+
+impl ErrorCategory {
+    /// The category of an errno value, or `None` for `ERR_OK` and unknown
+    /// values.
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            ERR_INVALID_HANDLE => Self::InvalidHandle,
+            ERR_OOM => Self::OutOfMemory,
+            ERR_INVALID_ARGS => Self::InvalidArgs,
+            ERR_NOT_IMPLEMENTED => Self::NotImplemented,
+            ERR_GL => Self::Gl,
+            ERR_INTERNAL => Self::Internal,
+            ERR_INVALID_OPERATION => Self::InvalidOperation,
+            ERR_INVALID_ENUM => Self::InvalidEnum,
+            ERR_COMPILATION => Self::Compilation,
+            _ => return None,
+        })
+    }
+
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+}
+
+/// Crate-level error: every module error converts into one, and
+/// [`report`] is the one path that renders errors into the last-error slot.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{message}")]
+pub struct Webgl2Error {
+    pub category: ErrorCategory,
+    pub message: String,
+}
+
+impl Webgl2Error {
+    pub fn new(category: ErrorCategory, message: impl Into<String>) -> Self {
+        Self {
+            category,
+            message: message.into(),
+        }
+    }
+
+    /// An error for a raw errno value; unknown values are internal errors.
+    pub fn from_code(code: u32, message: impl Into<String>) -> Self {
+        Self::new(
+            ErrorCategory::from_code(code).unwrap_or(ErrorCategory::Internal),
+            message,
+        )
+    }
+
+    /// The errno value for this error.
+    pub fn code(&self) -> u32 {
+        self.category.code()
+    }
+}
+
+impl From<crate::naga_wasm_backend::BackendError> for Webgl2Error {
+    fn from(error: crate::naga_wasm_backend::BackendError) -> Self {
+        use crate::naga_wasm_backend::BackendError;
+        let category = match &error {
+            BackendError::UnsupportedFeature(_) => ErrorCategory::NotImplemented,
+            BackendError::InternalError(_) => ErrorCategory::Internal,
+            BackendError::AbiMismatch { .. } => ErrorCategory::InvalidOperation,
+            _ => ErrorCategory::Compilation,
+        };
+        Self::new(category, error.to_string())
+    }
+}
+
+impl From<crate::js_codegen::CodegenError> for Webgl2Error {
+    fn from(error: crate::js_codegen::CodegenError) -> Self {
+        use crate::js_codegen::CodegenError;
+        let category = match &error {
+            CodegenError::TemplateError(_) => ErrorCategory::Internal,
+            CodegenError::TypeMappingError(_) => ErrorCategory::NotImplemented,
+            CodegenError::BlockMismatch(_) => ErrorCategory::InvalidArgs,
+        };
+        Self::new(category, error.to_string())
+    }
+}
+
+impl From<crate::glsl_introspection::ParseError> for Webgl2Error {
+    fn from(error: crate::glsl_introspection::ParseError) -> Self {
+        use crate::glsl_introspection::ParseError;
+        let category = match &error {
+            ParseError::AnnotationError(_) => ErrorCategory::InvalidArgs,
+            _ => ErrorCategory::Compilation,
+        };
+        Self::new(category, error.to_string())
+    }
+}
+
+/// Make `error` the WebGL last error and return its errno value, so
+/// exports can `return report(...)`.
+pub fn report(error: impl Into<Webgl2Error>) -> u32 {
+    let error = error.into();
+    let code = error.code();
+    set_error(ErrorSource::WebGL, code, error);
+    code
+}
+
 #[derive(Debug, Clone)]
 pub struct WasmError {
     pub code: u32,
@@ -24,6 +146,40 @@ pub struct WasmError {
     pub source: ErrorSource,
 }
 
+/// Why a WebGPU device was lost, as in `GPUDeviceLostReason`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviceLostReason {
+    Unknown = 0,
+    Destroyed = 1,
+}
+
+/// An event for the host to dispatch on a `GPUDevice`.
+#[derive(Debug, Clone)]
+pub enum DeviceEvent {
+    /// An error no error scope captured. It is not tied to a device, so the
+    /// host delivers it to every live device.
+    UncapturedError(WasmError),
+    /// A device was lost; `GPUDevice.lost` resolves with the reason.
+    Lost {
+        ctx_handle: u32,
+        device_handle: u32,
+        reason: DeviceLostReason,
+        message: String,
+    },
+}
+
+impl DeviceEvent {
+    fn kind(&self) -> u32 {
+        match self {
+            DeviceEvent::UncapturedError(_) => DEVICE_EVENT_UNCAPTURED_ERROR,
+            DeviceEvent::Lost { .. } => DEVICE_EVENT_LOST,
+        }
+    }
+}
+
+pub const DEVICE_EVENT_UNCAPTURED_ERROR: u32 = 1;
+pub const DEVICE_EVENT_LOST: u32 = 2;
+
 struct ErrorState {
     /// WebGL: Single sticky error slot.
     webgl_last_error: Option<WasmError>,
@@ -36,6 +192,13 @@ struct ErrorState {
     /// WebGPU: Temporary storage for the last popped error to be retrieved via FFI.
     webgpu_popped_error: Option<WasmError>,
 
+    /// WebGPU: Device events waiting to be drained by the host.
+    webgpu_device_events: VecDeque<DeviceEvent>,
+
+    /// WebGPU: Last popped device event and its message, kept alive for FFI.
+    webgpu_event_record: [u32; 6],
+    webgpu_event_message: String,
+
     /// FFI: Persistent buffer for the last retrieved error message to ensure safety.
     ffi_buffer: Option<CString>,
 }
@@ -45,12 +208,15 @@ thread_local! {
         webgl_last_error: None,
         webgpu_scope_stack: Vec::new(),
         webgpu_popped_error: None,
+        webgpu_device_events: VecDeque::new(),
+        webgpu_event_record: [0; 6],
+        webgpu_event_message: String::new(),
         ffi_buffer: None,
     }) };
 }
 
 pub fn set_error(source: ErrorSource, code: u32, msg: impl ToString) {
-    STATE.with(|s| {
+    let queued = STATE.with(|s| {
         let mut state = s.borrow_mut();
 
         let message = match source {
@@ -94,9 +260,12 @@ pub fn set_error(source: ErrorSource, code: u32, msg: impl ToString) {
                 }
 
                 if !captured {
-                    // Uncaptured error: Log immediately or trigger global handler
+                    // Uncaptured error: Log and queue it for the device handlers
                     crate::js_log(0, &format!("Uncaptured WebGPU Error: {}", error.message));
-                    crate::js_dispatch_uncaptured_error(&error.message);
+                    state
+                        .webgpu_device_events
+                        .push_back(DeviceEvent::UncapturedError(error));
+                    return true;
                 }
             }
             _ => {
@@ -104,7 +273,38 @@ pub fn set_error(source: ErrorSource, code: u32, msg: impl ToString) {
                 crate::js_log(0, &format!("System Error: {}", error.message));
             }
         }
+        false
     });
+
+    // Notify outside the borrow: the host drains the queue right away.
+    if queued {
+        crate::js_dispatch_device_events();
+    }
+}
+
+/// Queue a device-lost event and notify the host.
+pub fn webgpu_device_lost(
+    ctx_handle: u32,
+    device_handle: u32,
+    reason: DeviceLostReason,
+    message: impl ToString,
+) {
+    STATE.with(|s| {
+        s.borrow_mut()
+            .webgpu_device_events
+            .push_back(DeviceEvent::Lost {
+                ctx_handle,
+                device_handle,
+                reason,
+                message: message.to_string(),
+            });
+    });
+    crate::js_dispatch_device_events();
+}
+
+/// Take the oldest queued device event.
+pub fn webgpu_pop_device_event() -> Option<DeviceEvent> {
+    STATE.with(|s| s.borrow_mut().webgpu_device_events.pop_front())
 }
 
 pub fn get_last_error_message() -> Option<String> {
@@ -226,6 +426,49 @@ pub extern "C" fn wasm_get_last_error_msg_len() -> u32 {
     })
 }
 
+/// Pop the oldest device event. Returns a pointer to six u32s: event kind,
+/// context handle, device handle (both 0 for uncaptured errors), error filter
+/// or lost reason, message pointer and message length. The record stays valid
+/// until the next pop. Returns 0 once the queue is empty.
+#[no_mangle]
+pub extern "C" fn wasm_webgpu_device_pop_event() -> *const u32 {
+    let Some(event) = webgpu_pop_device_event() else {
+        return std::ptr::null();
+    };
+    let kind = event.kind();
+    let (ctx_handle, device_handle, code, message) = match event {
+        DeviceEvent::UncapturedError(error) => {
+            let filter = match error.source {
+                ErrorSource::WebGPU(WebGPUErrorFilter::OutOfMemory) => 1,
+                ErrorSource::WebGPU(WebGPUErrorFilter::Internal) => 2,
+                _ => 0,
+            };
+            (0, 0, filter, error.message)
+        }
+        DeviceEvent::Lost {
+            ctx_handle,
+            device_handle,
+            reason,
+            message,
+        } => (ctx_handle, device_handle, reason as u32, message),
+    };
+    STATE.with(|s| {
+        let mut state = s.borrow_mut();
+        state.webgpu_event_message = message;
+        let message_ptr = state.webgpu_event_message.as_ptr() as u32;
+        let message_len = state.webgpu_event_message.len() as u32;
+        state.webgpu_event_record = [
+            kind,
+            ctx_handle,
+            device_handle,
+            code,
+            message_ptr,
+            message_len,
+        ];
+        state.webgpu_event_record.as_ptr()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -300,4 +543,57 @@ mod tests {
         assert!(err_oom.is_some());
         assert_eq!(err_oom.unwrap().message, "Out of Memory Error: OOM");
     }
+
+    #[test]
+    fn test_webgpu_device_event_queue() {
+        while webgpu_pop_device_event().is_some() {}
+
+        set_error(
+            ErrorSource::WebGPU(WebGPUErrorFilter::Validation),
+            1,
+            "Fail",
+        );
+        webgpu_device_lost(1, 2, DeviceLostReason::Destroyed, "destroyed");
+
+        match webgpu_pop_device_event() {
+            Some(DeviceEvent::UncapturedError(err)) => {
+                assert_eq!(err.message, "Validation Error: Fail")
+            }
+            other => panic!("expected uncaptured error, got {:?}", other),
+        }
+        match webgpu_pop_device_event() {
+            Some(DeviceEvent::Lost {
+                ctx_handle: 1,
+                device_handle: 2,
+                reason: DeviceLostReason::Destroyed,
+                ..
+            }) => {}
+            other => panic!("expected device lost, got {:?}", other),
+        }
+        assert!(webgpu_pop_device_event().is_none());
+    }
+
+    #[test]
+    fn test_module_errors_report_through_last_error() {
+        use crate::glsl_introspection::ParseError;
+        use crate::naga_wasm_backend::BackendError;
+
+        let code = report(BackendError::UnsupportedFeature("atomics".into()));
+        assert_eq!(code, ERR_NOT_IMPLEMENTED);
+        assert_eq!(get_last_error_code(), ERR_NOT_IMPLEMENTED);
+        assert_eq!(
+            get_last_error_message().as_deref(),
+            Some("Unsupported Naga IR feature: atomics")
+        );
+
+        assert_eq!(
+            report(ParseError::GlslParseError("x".into())),
+            ERR_COMPILATION
+        );
+        for code in 1..=9 {
+            assert_eq!(Webgl2Error::from_code(code, "").code(), code);
+        }
+        assert_eq!(Webgl2Error::from_code(99, "").code(), ERR_INTERNAL);
+        clear_error();
+    }
 }
@@ -0,0 +1,768 @@
+//! Named OpenGL ES / WebGL enum values shared by the context, the emulator
+//! and the shader backend, plus helpers that turn raw enum values back into
+//! names for error messages, traces and decompiler output.
+
+// GL Error constants
+pub const GL_NO_ERROR: u32 = 0;
+pub const GL_INVALID_ENUM: u32 = 0x0500;
+pub const GL_INVALID_VALUE: u32 = 0x0501;
+pub const GL_INVALID_OPERATION: u32 = 0x0502;
+pub const GL_OUT_OF_MEMORY: u32 = 0x0505;
+pub const GL_INVALID_FRAMEBUFFER_OPERATION: u32 = 0x0506;
+
+pub const GL_ARRAY_BUFFER: u32 = 0x8892;
+pub const GL_ELEMENT_ARRAY_BUFFER: u32 = 0x8893;
+pub const GL_COPY_READ_BUFFER: u32 = 0x8F36;
+pub const GL_COPY_WRITE_BUFFER: u32 = 0x8F37;
+pub const GL_PIXEL_PACK_BUFFER: u32 = 0x88EB;
+pub const GL_PIXEL_UNPACK_BUFFER: u32 = 0x88EC;
+pub const GL_STATIC_DRAW: u32 = 0x88E4;
+
+pub const GL_COMPILE_STATUS: u32 = 0x8B81;
+pub const GL_LINK_STATUS: u32 = 0x8B82;
+pub const GL_SHADER_TYPE: u32 = 0x8B4F;
+pub const GL_DELETE_STATUS: u32 = 0x8B80;
+pub const GL_INFO_LOG_LENGTH: u32 = 0x8B84;
+pub const GL_ATTACHED_SHADERS: u32 = 0x8B85;
+pub const GL_ACTIVE_UNIFORMS: u32 = 0x8B86;
+pub const GL_ACTIVE_ATTRIBUTES: u32 = 0x8B89;
+
+pub const GL_POINTS: u32 = 0x0000;
+pub const GL_LINES: u32 = 0x0001;
+pub const GL_LINE_LOOP: u32 = 0x0002;
+pub const GL_LINE_STRIP: u32 = 0x0003;
+pub const GL_TRIANGLES: u32 = 0x0004;
+pub const GL_TRIANGLE_STRIP: u32 = 0x0005;
+pub const GL_TRIANGLE_FAN: u32 = 0x0006;
+
+pub const GL_FRONT: u32 = 0x0404;
+pub const GL_BACK: u32 = 0x0405;
+pub const GL_FRONT_AND_BACK: u32 = 0x0408;
+
+pub const GL_COLOR: u32 = 0x1800;
+pub const GL_DEPTH: u32 = 0x1801;
+pub const GL_STENCIL: u32 = 0x1802;
+pub const GL_CW: u32 = 0x0900;
+pub const GL_CCW: u32 = 0x0901;
+
+pub const GL_BYTE: u32 = 0x1400;
+pub const GL_UNSIGNED_BYTE: u32 = 0x1401;
+pub const GL_SHORT: u32 = 0x1402;
+pub const GL_UNSIGNED_SHORT: u32 = 0x1403;
+pub const GL_INT: u32 = 0x1404;
+pub const GL_UNSIGNED_INT: u32 = 0x1405;
+pub const GL_FLOAT: u32 = 0x1406;
+pub const GL_HALF_FLOAT: u32 = 0x140B;
+pub const GL_INT_2_10_10_10_REV: u32 = 0x8D9F;
+pub const GL_UNSIGNED_INT_2_10_10_10_REV: u32 = 0x8368;
+pub const GL_UNSIGNED_SHORT_4_4_4_4: u32 = 0x8033;
+pub const GL_UNSIGNED_SHORT_5_5_5_1: u32 = 0x8034;
+pub const GL_UNSIGNED_SHORT_5_6_5: u32 = 0x8363;
+pub const GL_UNSIGNED_INT_10F_11F_11F_REV: u32 = 0x8C3B;
+pub const GL_UNSIGNED_INT_5_9_9_9_REV: u32 = 0x8C3E;
+pub const GL_FLOAT_32_UNSIGNED_INT_24_8_REV: u32 = 0x8DAD;
+
+pub const GL_TEXTURE_2D: u32 = 0x0DE1;
+pub const GL_TEXTURE_3D: u32 = 0x806F;
+pub const GL_TEXTURE_2D_ARRAY: u32 = 0x8C1A;
+
+pub const GL_RGBA: u32 = 0x1908;
+pub const GL_RGB: u32 = 0x1907;
+pub const GL_RED: u32 = 0x1903;
+pub const GL_RG: u32 = 0x8227;
+pub const GL_ALPHA: u32 = 0x1906;
+pub const GL_LUMINANCE: u32 = 0x1909;
+pub const GL_LUMINANCE_ALPHA: u32 = 0x190A;
+
+pub const GL_RGBA_INTEGER: u32 = 0x8D9E;
+pub const GL_RGB_INTEGER: u32 = 0x8D98;
+pub const GL_RG_INTEGER: u32 = 0x8228;
+pub const GL_RED_INTEGER: u32 = 0x8D94;
+pub const GL_DEPTH_COMPONENT: u32 = 0x1902;
+pub const GL_UNSIGNED_INT_24_8: u32 = 0x84FA;
+pub const GL_DEPTH24_STENCIL8: u32 = 0x88F0;
+
+pub const GL_RGBA8: u32 = 0x8058;
+pub const GL_RGB8: u32 = 0x8051;
+
+pub const GL_R32F: u32 = 0x822E;
+pub const GL_RG32F: u32 = 0x8230;
+pub const GL_RGBA32F: u32 = 0x8814;
+
+pub const GL_R32UI: u32 = 0x8236;
+pub const GL_RG32UI: u32 = 0x823C;
+pub const GL_RGBA32UI: u32 = 0x8D70;
+
+pub const GL_R32I: u32 = 0x8235;
+pub const GL_RG32I: u32 = 0x823B;
+pub const GL_RGBA32I: u32 = 0x8D82;
+
+pub const GL_R8UI: u32 = 0x8232;
+pub const GL_RG8UI: u32 = 0x8238;
+pub const GL_RGB8UI: u32 = 0x8D7D;
+pub const GL_RGBA8UI: u32 = 0x8D7C;
+pub const GL_R8I: u32 = 0x8231;
+pub const GL_RG8I: u32 = 0x8237;
+pub const GL_RGB8I: u32 = 0x8D8F;
+pub const GL_RGBA8I: u32 = 0x8D8E;
+
+pub const GL_R16UI: u32 = 0x8234;
+pub const GL_RG16UI: u32 = 0x823A;
+pub const GL_RGB16UI: u32 = 0x8D77;
+pub const GL_RGBA16UI: u32 = 0x8D76;
+pub const GL_R16I: u32 = 0x8233;
+pub const GL_RG16I: u32 = 0x8239;
+pub const GL_RGB16I: u32 = 0x8D89;
+pub const GL_RGBA16I: u32 = 0x8D88;
+
+pub const GL_RGB32UI: u32 = 0x8D71;
+pub const GL_RGB32I: u32 = 0x8D83;
+
+pub const GL_R16F: u32 = 0x822D;
+pub const GL_RG16F: u32 = 0x822F;
+pub const GL_RGB16F: u32 = 0x881B;
+pub const GL_RGBA16F: u32 = 0x881A;
+pub const GL_RGB32F: u32 = 0x8815;
+
+pub const GL_RGBA4: u32 = 0x8056;
+pub const GL_RGB565: u32 = 0x8D62;
+pub const GL_RGB5_A1: u32 = 0x8057;
+
+pub const GL_DEPTH_COMPONENT16: u32 = 0x81A5;
+pub const GL_DEPTH_COMPONENT24: u32 = 0x81A6;
+pub const GL_DEPTH_COMPONENT32F: u32 = 0x8CAC;
+pub const GL_STENCIL_INDEX8: u32 = 0x8D48;
+
+pub const GL_VERTEX_ATTRIB_ARRAY_ENABLED: u32 = 0x8622;
+pub const GL_VERTEX_ATTRIB_ARRAY_SIZE: u32 = 0x8623;
+pub const GL_VERTEX_ATTRIB_ARRAY_STRIDE: u32 = 0x8624;
+pub const GL_VERTEX_ATTRIB_ARRAY_TYPE: u32 = 0x8625;
+pub const GL_VERTEX_ATTRIB_ARRAY_NORMALIZED: u32 = 0x886A;
+pub const GL_VERTEX_ATTRIB_ARRAY_POINTER: u32 = 0x8645;
+pub const GL_VERTEX_ATTRIB_ARRAY_BUFFER_BINDING: u32 = 0x889F;
+pub const GL_VERTEX_ATTRIB_ARRAY_DIVISOR: u32 = 0x88FE;
+pub const GL_VERTEX_ATTRIB_ARRAY_INTEGER: u32 = 0x88FD;
+pub const GL_CURRENT_VERTEX_ATTRIB: u32 = 0x8626;
+
+pub const GL_TEXTURE_MAG_FILTER: u32 = 0x2800;
+pub const GL_TEXTURE_MIN_FILTER: u32 = 0x2801;
+pub const GL_TEXTURE_WRAP_S: u32 = 0x2802;
+pub const GL_TEXTURE_WRAP_T: u32 = 0x2803;
+pub const GL_TEXTURE_WRAP_R: u32 = 0x8072;
+pub const GL_TEXTURE_LOD_BIAS: u32 = 0x8501;
+
+pub const GL_NEAREST: u32 = 0x2600;
+pub const GL_LINEAR: u32 = 0x2601;
+pub const GL_NEAREST_MIPMAP_NEAREST: u32 = 0x2700;
+pub const GL_LINEAR_MIPMAP_NEAREST: u32 = 0x2701;
+pub const GL_NEAREST_MIPMAP_LINEAR: u32 = 0x2702;
+pub const GL_LINEAR_MIPMAP_LINEAR: u32 = 0x2703;
+pub const GL_REPEAT: u32 = 0x2901;
+pub const GL_CLAMP_TO_EDGE: u32 = 0x812F;
+pub const GL_MIRRORED_REPEAT: u32 = 0x8370;
+
+pub const GL_COLOR_ATTACHMENT0: u32 = 0x8CE0;
+pub const GL_COLOR_ATTACHMENT1: u32 = 0x8CE1;
+pub const GL_COLOR_ATTACHMENT2: u32 = 0x8CE2;
+pub const GL_COLOR_ATTACHMENT3: u32 = 0x8CE3;
+pub const GL_COLOR_ATTACHMENT4: u32 = 0x8CE4;
+pub const GL_COLOR_ATTACHMENT5: u32 = 0x8CE5;
+pub const GL_COLOR_ATTACHMENT6: u32 = 0x8CE6;
+pub const GL_COLOR_ATTACHMENT7: u32 = 0x8CE7;
+pub const GL_DEPTH_ATTACHMENT: u32 = 0x8D00;
+pub const GL_STENCIL_ATTACHMENT: u32 = 0x8D20;
+pub const GL_DEPTH_STENCIL_ATTACHMENT: u32 = 0x821A;
+
+pub const GL_DRAW_FRAMEBUFFER: u32 = 0x8CA9;
+pub const GL_READ_FRAMEBUFFER: u32 = 0x8CA8;
+
+pub const GL_MAX_COLOR_ATTACHMENTS: u32 = 0x8CDF;
+pub const GL_MAX_DRAW_BUFFERS: u32 = 0x8824;
+pub const GL_NONE: u32 = 0;
+
+pub const GL_VERTEX_SHADER: u32 = 0x8B31;
+pub const GL_FRAGMENT_SHADER: u32 = 0x8B30;
+pub const GL_VALIDATE_STATUS: u32 = 0x8B83;
+
+pub const GL_PROGRAM_SEPARABLE: u32 = 0x8258;
+pub const GL_ACTIVE_PROGRAM: u32 = 0x8259;
+pub const GL_PROGRAM_PIPELINE_BINDING: u32 = 0x825A;
+pub const GL_VERTEX_SHADER_BIT: u32 = 0x00000001;
+pub const GL_FRAGMENT_SHADER_BIT: u32 = 0x00000002;
+pub const GL_ALL_SHADER_BITS: u32 = 0xFFFFFFFF;
+
+pub const GL_LESS: u32 = 0x0201;
+pub const GL_EQUAL: u32 = 0x0202;
+pub const GL_LEQUAL: u32 = 0x0203;
+pub const GL_GREATER: u32 = 0x0204;
+pub const GL_NOTEQUAL: u32 = 0x0205;
+pub const GL_GEQUAL: u32 = 0x0206;
+pub const GL_ALWAYS: u32 = 0x0207;
+pub const GL_NEVER: u32 = 0x0200;
+
+pub const GL_STENCIL_TEST: u32 = 0x0B90;
+pub const GL_DEPTH_TEST: u32 = 0x0B71;
+pub const GL_BLEND: u32 = 0x0BE2;
+pub const GL_DITHER: u32 = 0x0BD0;
+pub const GL_CULL_FACE: u32 = 0x0B44;
+pub const GL_SCISSOR_TEST: u32 = 0x0C11;
+pub const GL_SAMPLE_ALPHA_TO_COVERAGE: u32 = 0x809E;
+pub const GL_SAMPLE_COVERAGE: u32 = 0x80A0;
+
+pub const GL_TRANSFORM_FEEDBACK_BUFFER: u32 = 0x8C8E;
+pub const GL_TRANSFORM_FEEDBACK_BUFFER_BINDING: u32 = 0x8C8F;
+pub const GL_TRANSFORM_FEEDBACK_BUFFER_START: u32 = 0x8C84;
+pub const GL_TRANSFORM_FEEDBACK_BUFFER_SIZE: u32 = 0x8C85;
+pub const GL_TRANSFORM_FEEDBACK_BINDING: u32 = 0x8E25;
+pub const GL_TRANSFORM_FEEDBACK_BUFFER_MODE: u32 = 0x8C7F;
+pub const GL_TRANSFORM_FEEDBACK_VARYINGS: u32 = 0x8C83;
+pub const GL_TRANSFORM_FEEDBACK_VARYING_MAX_LENGTH: u32 = 0x8C76;
+pub const GL_MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS: u32 = 0x8C8B;
+pub const GL_MAX_TRANSFORM_FEEDBACK_INTERLEAVED_COMPONENTS: u32 = 0x8C8A;
+pub const GL_MAX_TRANSFORM_FEEDBACK_SEPARATE_COMPONENTS: u32 = 0x8C80;
+pub const GL_INTERLEAVED_ATTRIBS: u32 = 0x8C8C;
+pub const GL_SEPARATE_ATTRIBS: u32 = 0x8C8D;
+pub const GL_TRANSFORM_FEEDBACK_PAUSED: u32 = 0x8E23;
+pub const GL_TRANSFORM_FEEDBACK_ACTIVE: u32 = 0x8E24;
+pub const GL_TRANSFORM_FEEDBACK: u32 = 0x8E22;
+
+pub const GL_RASTERIZER_DISCARD: u32 = 0x8C89;
+
+pub const GL_UNIFORM_BUFFER: u32 = 0x8A11;
+pub const GL_UNIFORM_BUFFER_BINDING: u32 = 0x8A28;
+pub const GL_UNIFORM_BUFFER_START: u32 = 0x8A29;
+pub const GL_UNIFORM_BUFFER_SIZE: u32 = 0x8A2A;
+pub const GL_MAX_UNIFORM_BUFFER_BINDINGS: u32 = 0x8A2F;
+pub const GL_ACTIVE_UNIFORM_BLOCKS: u32 = 0x8A36;
+pub const GL_UNIFORM_BLOCK_BINDING: u32 = 0x8A3F;
+pub const GL_UNIFORM_BLOCK_DATA_SIZE: u32 = 0x8A40;
+pub const GL_UNIFORM_BLOCK_ACTIVE_UNIFORMS: u32 = 0x8A42;
+pub const GL_UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES: u32 = 0x8A43;
+pub const GL_UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER: u32 = 0x8A44;
+pub const GL_UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER: u32 = 0x8A46;
+
+pub const GL_KEEP: u32 = 0x1E00;
+pub const GL_REPLACE: u32 = 0x1E01;
+pub const GL_INCR: u32 = 0x1E02;
+pub const GL_DECR: u32 = 0x1E03;
+pub const GL_INVERT: u32 = 0x150A;
+pub const GL_INCR_WRAP: u32 = 0x8507;
+pub const GL_DECR_WRAP: u32 = 0x8508;
+
+pub const GL_FUNC_ADD: u32 = 0x8006;
+pub const GL_FUNC_SUBTRACT: u32 = 0x800A;
+pub const GL_FUNC_REVERSE_SUBTRACT: u32 = 0x800B;
+pub const GL_MIN: u32 = 0x8007;
+pub const GL_MAX: u32 = 0x8008;
+
+pub const GL_ZERO: u32 = 0;
+pub const GL_ONE: u32 = 1;
+pub const GL_SRC_COLOR: u32 = 0x0300;
+pub const GL_ONE_MINUS_SRC_COLOR: u32 = 0x0301;
+pub const GL_SRC_ALPHA: u32 = 0x0302;
+pub const GL_ONE_MINUS_SRC_ALPHA: u32 = 0x0303;
+pub const GL_DST_ALPHA: u32 = 0x0304;
+pub const GL_ONE_MINUS_DST_ALPHA: u32 = 0x0305;
+pub const GL_DST_COLOR: u32 = 0x0306;
+pub const GL_ONE_MINUS_DST_COLOR: u32 = 0x0307;
+pub const GL_SRC_ALPHA_SATURATE: u32 = 0x0308;
+pub const GL_CONSTANT_COLOR: u32 = 0x8001;
+pub const GL_ONE_MINUS_CONSTANT_COLOR: u32 = 0x8002;
+pub const GL_CONSTANT_ALPHA: u32 = 0x8003;
+pub const GL_ONE_MINUS_CONSTANT_ALPHA: u32 = 0x8004;
+
+pub const GL_BLEND_COLOR: u32 = 0x8005;
+pub const GL_BLEND_EQUATION_RGB: u32 = 0x8009;
+pub const GL_BLEND_EQUATION_ALPHA: u32 = 0x883D;
+pub const GL_BLEND_DST_RGB: u32 = 0x80C8;
+pub const GL_BLEND_SRC_RGB: u32 = 0x80C9;
+pub const GL_BLEND_DST_ALPHA: u32 = 0x80CA;
+pub const GL_BLEND_SRC_ALPHA: u32 = 0x80CB;
+
+pub const GL_VIEWPORT: u32 = 0x0BA2;
+pub const GL_SCISSOR_BOX: u32 = 0x0C10;
+pub const GL_COLOR_CLEAR_VALUE: u32 = 0x0C22;
+pub const GL_ALIASED_POINT_SIZE_RANGE: u32 = 0x846D;
+pub const GL_BUFFER_SIZE: u32 = 0x8764;
+pub const GL_COLOR_BUFFER_BIT: u32 = 0x00004000;
+pub const GL_RENDERBUFFER: u32 = 0x8D41;
+pub const GL_FRAMEBUFFER: u32 = 0x8D40;
+pub const GL_FRAMEBUFFER_COMPLETE: u32 = 0x8CD5;
+pub const GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT: u32 = 0x8CD6;
+pub const GL_FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT: u32 = 0x8CD7;
+pub const GL_FRAMEBUFFER_INCOMPLETE_DIMENSIONS: u32 = 0x8CD9;
+pub const GL_FRAMEBUFFER_UNSUPPORTED: u32 = 0x8CDD;
+
+pub const GL_DEPTH_STENCIL: u32 = 0x84F9;
+
+pub const GL_CURRENT_QUERY: u32 = 0x8865;
+pub const GL_QUERY_RESULT: u32 = 0x8866;
+pub const GL_QUERY_RESULT_AVAILABLE: u32 = 0x8867;
+pub const GL_QUERY_COUNTER_BITS_EXT: u32 = 0x8864;
+pub const GL_TIME_ELAPSED_EXT: u32 = 0x88BF;
+pub const GL_TIMESTAMP_EXT: u32 = 0x8E28;
+
+// Texture units
+pub const GL_TEXTURE0: u32 = 0x84C0;
+pub const GL_TEXTURE31: u32 = 0x84DF;
+pub const GL_ACTIVE_TEXTURE: u32 = 0x84E0;
+
+// Clear mask bits
+pub const GL_DEPTH_BUFFER_BIT: u32 = 0x00000100;
+pub const GL_STENCIL_BUFFER_BIT: u32 = 0x00000400;
+
+// Uniform and attribute types
+pub const GL_FLOAT_VEC2: u32 = 0x8B50;
+pub const GL_FLOAT_VEC3: u32 = 0x8B51;
+pub const GL_FLOAT_VEC4: u32 = 0x8B52;
+pub const GL_INT_VEC2: u32 = 0x8B53;
+pub const GL_INT_VEC3: u32 = 0x8B54;
+pub const GL_INT_VEC4: u32 = 0x8B55;
+pub const GL_BOOL: u32 = 0x8B56;
+pub const GL_BOOL_VEC2: u32 = 0x8B57;
+pub const GL_BOOL_VEC3: u32 = 0x8B58;
+pub const GL_BOOL_VEC4: u32 = 0x8B59;
+pub const GL_FLOAT_MAT2: u32 = 0x8B5A;
+pub const GL_FLOAT_MAT3: u32 = 0x8B5B;
+pub const GL_FLOAT_MAT4: u32 = 0x8B5C;
+pub const GL_SAMPLER_2D: u32 = 0x8B5E;
+pub const GL_SAMPLER_3D: u32 = 0x8B5F;
+pub const GL_SAMPLER_CUBE: u32 = 0x8B60;
+pub const GL_UNSIGNED_INT_VEC2: u32 = 0x8DC6;
+pub const GL_UNSIGNED_INT_VEC3: u32 = 0x8DC7;
+pub const GL_UNSIGNED_INT_VEC4: u32 = 0x8DC8;
+
+// Pixel storage parameters
+pub const GL_UNPACK_ROW_LENGTH: u32 = 0x0CF2;
+pub const GL_UNPACK_SKIP_ROWS: u32 = 0x0CF3;
+pub const GL_UNPACK_SKIP_PIXELS: u32 = 0x0CF4;
+pub const GL_UNPACK_ALIGNMENT: u32 = 0x0CF5;
+pub const GL_PACK_ROW_LENGTH: u32 = 0x0D02;
+pub const GL_PACK_SKIP_ROWS: u32 = 0x0D03;
+pub const GL_PACK_SKIP_PIXELS: u32 = 0x0D04;
+pub const GL_PACK_ALIGNMENT: u32 = 0x0D05;
+pub const GL_UNPACK_SKIP_IMAGES: u32 = 0x806D;
+pub const GL_UNPACK_IMAGE_HEIGHT: u32 = 0x806E;
+pub const GL_UNPACK_FLIP_Y_WEBGL: u32 = 0x9240;
+pub const GL_UNPACK_PREMULTIPLY_ALPHA_WEBGL: u32 = 0x9241;
+pub const GL_UNPACK_COLORSPACE_CONVERSION_WEBGL: u32 = 0x9243;
+
+// getParameter names
+pub const GL_VENDOR: u32 = 0x1F00;
+pub const GL_RENDERER: u32 = 0x1F01;
+pub const GL_VERSION: u32 = 0x1F02;
+pub const GL_SHADING_LANGUAGE_VERSION: u32 = 0x8B8C;
+pub const GL_COLOR_WRITEMASK: u32 = 0x0C23;
+pub const GL_DEPTH_WRITEMASK: u32 = 0x0B72;
+pub const GL_DEPTH_CLEAR_VALUE: u32 = 0x0B73;
+pub const GL_DEPTH_FUNC: u32 = 0x0B74;
+pub const GL_STENCIL_FUNC: u32 = 0x0B92;
+pub const GL_STENCIL_VALUE_MASK: u32 = 0x0B93;
+pub const GL_STENCIL_FAIL: u32 = 0x0B94;
+pub const GL_STENCIL_PASS_DEPTH_FAIL: u32 = 0x0B95;
+pub const GL_STENCIL_PASS_DEPTH_PASS: u32 = 0x0B96;
+pub const GL_STENCIL_REF: u32 = 0x0B97;
+pub const GL_STENCIL_WRITEMASK: u32 = 0x0B98;
+pub const GL_STENCIL_BACK_FUNC: u32 = 0x8800;
+pub const GL_STENCIL_BACK_FAIL: u32 = 0x8801;
+pub const GL_STENCIL_BACK_PASS_DEPTH_FAIL: u32 = 0x8802;
+pub const GL_STENCIL_BACK_PASS_DEPTH_PASS: u32 = 0x8803;
+pub const GL_STENCIL_BACK_REF: u32 = 0x8CA3;
+pub const GL_STENCIL_BACK_VALUE_MASK: u32 = 0x8CA4;
+pub const GL_STENCIL_BACK_WRITEMASK: u32 = 0x8CA5;
+pub const GL_ALPHA_BITS: u32 = 0x0D55;
+pub const GL_DEPTH_BITS: u32 = 0x0D56;
+pub const GL_STENCIL_BITS: u32 = 0x0D57;
+pub const GL_SAMPLE_BUFFERS: u32 = 0x80A8;
+pub const GL_SAMPLES: u32 = 0x80A9;
+pub const GL_SAMPLE_COVERAGE_VALUE: u32 = 0x80AA;
+pub const GL_SAMPLE_COVERAGE_INVERT: u32 = 0x80AB;
+pub const GL_DRAW_FRAMEBUFFER_BINDING: u32 = 0x8CA6;
+pub const GL_RENDERBUFFER_BINDING: u32 = 0x8CA7;
+pub const GL_READ_FRAMEBUFFER_BINDING: u32 = 0x8CAA;
+pub const GL_MAX_VERTEX_ATTRIBS: u32 = 0x8869;
+pub const GL_MAX_VARYING_COMPONENTS: u32 = 0x8B4B;
+pub const GL_MAX_VARYING_VECTORS: u32 = 0x8DFC;
+pub const GL_MAX_VERTEX_UNIFORM_VECTORS: u32 = 0x8DFB;
+pub const GL_MAX_FRAGMENT_UNIFORM_VECTORS: u32 = 0x8DFD;
+pub const GL_MAX_TEXTURE_SIZE: u32 = 0x0D33;
+pub const GL_MAX_RENDERBUFFER_SIZE: u32 = 0x84E8;
+pub const GL_DRAW_BUFFER0: u32 = 0x8825;
+pub const GL_DRAW_BUFFER7: u32 = 0x882C;
+
+/// Enum values sorted by value, used by [`enum_name`]. Values below `0x100`
+/// and bitfields are left out because they are shared by unrelated enums
+/// (`POINTS`, `ZERO`, `NONE`, `FALSE` are all 0), and for aliased values the
+/// first spelling wins.
+const ENUM_NAMES: &[(u32, &str)] = &[
+    (0x0200, "NEVER"),
+    (0x0201, "LESS"),
+    (0x0202, "EQUAL"),
+    (0x0203, "LEQUAL"),
+    (0x0204, "GREATER"),
+    (0x0205, "NOTEQUAL"),
+    (0x0206, "GEQUAL"),
+    (0x0207, "ALWAYS"),
+    (0x0300, "SRC_COLOR"),
+    (0x0301, "ONE_MINUS_SRC_COLOR"),
+    (0x0302, "SRC_ALPHA"),
+    (0x0303, "ONE_MINUS_SRC_ALPHA"),
+    (0x0304, "DST_ALPHA"),
+    (0x0305, "ONE_MINUS_DST_ALPHA"),
+    (0x0306, "DST_COLOR"),
+    (0x0307, "ONE_MINUS_DST_COLOR"),
+    (0x0308, "SRC_ALPHA_SATURATE"),
+    (0x0404, "FRONT"),
+    (0x0405, "BACK"),
+    (0x0408, "FRONT_AND_BACK"),
+    (0x0500, "INVALID_ENUM"),
+    (0x0501, "INVALID_VALUE"),
+    (0x0502, "INVALID_OPERATION"),
+    (0x0505, "OUT_OF_MEMORY"),
+    (0x0506, "INVALID_FRAMEBUFFER_OPERATION"),
+    (0x0900, "CW"),
+    (0x0901, "CCW"),
+    (0x0B44, "CULL_FACE"),
+    (0x0B71, "DEPTH_TEST"),
+    (0x0B72, "DEPTH_WRITEMASK"),
+    (0x0B73, "DEPTH_CLEAR_VALUE"),
+    (0x0B74, "DEPTH_FUNC"),
+    (0x0B90, "STENCIL_TEST"),
+    (0x0B92, "STENCIL_FUNC"),
+    (0x0B93, "STENCIL_VALUE_MASK"),
+    (0x0B94, "STENCIL_FAIL"),
+    (0x0B95, "STENCIL_PASS_DEPTH_FAIL"),
+    (0x0B96, "STENCIL_PASS_DEPTH_PASS"),
+    (0x0B97, "STENCIL_REF"),
+    (0x0B98, "STENCIL_WRITEMASK"),
+    (0x0BA2, "VIEWPORT"),
+    (0x0BD0, "DITHER"),
+    (0x0BE2, "BLEND"),
+    (0x0C10, "SCISSOR_BOX"),
+    (0x0C11, "SCISSOR_TEST"),
+    (0x0C22, "COLOR_CLEAR_VALUE"),
+    (0x0C23, "COLOR_WRITEMASK"),
+    (0x0CF2, "UNPACK_ROW_LENGTH"),
+    (0x0CF3, "UNPACK_SKIP_ROWS"),
+    (0x0CF4, "UNPACK_SKIP_PIXELS"),
+    (0x0CF5, "UNPACK_ALIGNMENT"),
+    (0x0D02, "PACK_ROW_LENGTH"),
+    (0x0D03, "PACK_SKIP_ROWS"),
+    (0x0D04, "PACK_SKIP_PIXELS"),
+    (0x0D05, "PACK_ALIGNMENT"),
+    (0x0D33, "MAX_TEXTURE_SIZE"),
+    (0x0D55, "ALPHA_BITS"),
+    (0x0D56, "DEPTH_BITS"),
+    (0x0D57, "STENCIL_BITS"),
+    (0x0DE1, "TEXTURE_2D"),
+    (0x1400, "BYTE"),
+    (0x1401, "UNSIGNED_BYTE"),
+    (0x1402, "SHORT"),
+    (0x1403, "UNSIGNED_SHORT"),
+    (0x1404, "INT"),
+    (0x1405, "UNSIGNED_INT"),
+    (0x1406, "FLOAT"),
+    (0x140B, "HALF_FLOAT"),
+    (0x150A, "INVERT"),
+    (0x1800, "COLOR"),
+    (0x1801, "DEPTH"),
+    (0x1802, "STENCIL"),
+    (0x1902, "DEPTH_COMPONENT"),
+    (0x1903, "RED"),
+    (0x1907, "RGB"),
+    (0x1908, "RGBA"),
+    (0x1E00, "KEEP"),
+    (0x1E01, "REPLACE"),
+    (0x1E02, "INCR"),
+    (0x1E03, "DECR"),
+    (0x1F00, "VENDOR"),
+    (0x1F01, "RENDERER"),
+    (0x1F02, "VERSION"),
+    (0x2600, "NEAREST"),
+    (0x2601, "LINEAR"),
+    (0x2700, "NEAREST_MIPMAP_NEAREST"),
+    (0x2701, "LINEAR_MIPMAP_NEAREST"),
+    (0x2702, "NEAREST_MIPMAP_LINEAR"),
+    (0x2703, "LINEAR_MIPMAP_LINEAR"),
+    (0x2800, "TEXTURE_MAG_FILTER"),
+    (0x2801, "TEXTURE_MIN_FILTER"),
+    (0x2802, "TEXTURE_WRAP_S"),
+    (0x2803, "TEXTURE_WRAP_T"),
+    (0x2901, "REPEAT"),
+    (0x8001, "CONSTANT_COLOR"),
+    (0x8002, "ONE_MINUS_CONSTANT_COLOR"),
+    (0x8003, "CONSTANT_ALPHA"),
+    (0x8004, "ONE_MINUS_CONSTANT_ALPHA"),
+    (0x8005, "BLEND_COLOR"),
+    (0x8006, "FUNC_ADD"),
+    (0x8007, "MIN"),
+    (0x8008, "MAX"),
+    (0x8009, "BLEND_EQUATION_RGB"),
+    (0x800A, "FUNC_SUBTRACT"),
+    (0x800B, "FUNC_REVERSE_SUBTRACT"),
+    (0x8051, "RGB8"),
+    (0x8056, "RGBA4"),
+    (0x8057, "RGB5_A1"),
+    (0x8058, "RGBA8"),
+    (0x806D, "UNPACK_SKIP_IMAGES"),
+    (0x806E, "UNPACK_IMAGE_HEIGHT"),
+    (0x806F, "TEXTURE_3D"),
+    (0x8072, "TEXTURE_WRAP_R"),
+    (0x809E, "SAMPLE_ALPHA_TO_COVERAGE"),
+    (0x80A0, "SAMPLE_COVERAGE"),
+    (0x80A8, "SAMPLE_BUFFERS"),
+    (0x80A9, "SAMPLES"),
+    (0x80AA, "SAMPLE_COVERAGE_VALUE"),
+    (0x80AB, "SAMPLE_COVERAGE_INVERT"),
+    (0x80C8, "BLEND_DST_RGB"),
+    (0x80C9, "BLEND_SRC_RGB"),
+    (0x80CA, "BLEND_DST_ALPHA"),
+    (0x80CB, "BLEND_SRC_ALPHA"),
+    (0x812F, "CLAMP_TO_EDGE"),
+    (0x81A5, "DEPTH_COMPONENT16"),
+    (0x81A6, "DEPTH_COMPONENT24"),
+    (0x821A, "DEPTH_STENCIL_ATTACHMENT"),
+    (0x8227, "RG"),
+    (0x8228, "RG_INTEGER"),
+    (0x822D, "R16F"),
+    (0x822E, "R32F"),
+    (0x822F, "RG16F"),
+    (0x8230, "RG32F"),
+    (0x8231, "R8I"),
+    (0x8232, "R8UI"),
+    (0x8233, "R16I"),
+    (0x8234, "R16UI"),
+    (0x8235, "R32I"),
+    (0x8236, "R32UI"),
+    (0x8237, "RG8I"),
+    (0x8238, "RG8UI"),
+    (0x8239, "RG16I"),
+    (0x823A, "RG16UI"),
+    (0x823B, "RG32I"),
+    (0x823C, "RG32UI"),
+    (0x8258, "PROGRAM_SEPARABLE"),
+    (0x8259, "ACTIVE_PROGRAM"),
+    (0x825A, "PROGRAM_PIPELINE_BINDING"),
+    (0x8368, "UNSIGNED_INT_2_10_10_10_REV"),
+    (0x8370, "MIRRORED_REPEAT"),
+    (0x846D, "ALIASED_POINT_SIZE_RANGE"),
+    (0x84C0, "TEXTURE0"),
+    (0x84DF, "TEXTURE31"),
+    (0x84E0, "ACTIVE_TEXTURE"),
+    (0x84E8, "MAX_RENDERBUFFER_SIZE"),
+    (0x84F9, "DEPTH_STENCIL"),
+    (0x84FA, "UNSIGNED_INT_24_8"),
+    (0x8501, "TEXTURE_LOD_BIAS"),
+    (0x8507, "INCR_WRAP"),
+    (0x8508, "DECR_WRAP"),
+    (0x8622, "VERTEX_ATTRIB_ARRAY_ENABLED"),
+    (0x8623, "VERTEX_ATTRIB_ARRAY_SIZE"),
+    (0x8624, "VERTEX_ATTRIB_ARRAY_STRIDE"),
+    (0x8625, "VERTEX_ATTRIB_ARRAY_TYPE"),
+    (0x8626, "CURRENT_VERTEX_ATTRIB"),
+    (0x8645, "VERTEX_ATTRIB_ARRAY_POINTER"),
+    (0x8764, "BUFFER_SIZE"),
+    (0x8800, "STENCIL_BACK_FUNC"),
+    (0x8801, "STENCIL_BACK_FAIL"),
+    (0x8802, "STENCIL_BACK_PASS_DEPTH_FAIL"),
+    (0x8803, "STENCIL_BACK_PASS_DEPTH_PASS"),
+    (0x8814, "RGBA32F"),
+    (0x8815, "RGB32F"),
+    (0x881A, "RGBA16F"),
+    (0x881B, "RGB16F"),
+    (0x8824, "MAX_DRAW_BUFFERS"),
+    (0x8825, "DRAW_BUFFER0"),
+    (0x882C, "DRAW_BUFFER7"),
+    (0x883D, "BLEND_EQUATION_ALPHA"),
+    (0x8864, "QUERY_COUNTER_BITS_EXT"),
+    (0x8865, "CURRENT_QUERY"),
+    (0x8866, "QUERY_RESULT"),
+    (0x8867, "QUERY_RESULT_AVAILABLE"),
+    (0x8869, "MAX_VERTEX_ATTRIBS"),
+    (0x886A, "VERTEX_ATTRIB_ARRAY_NORMALIZED"),
+    (0x8892, "ARRAY_BUFFER"),
+    (0x8893, "ELEMENT_ARRAY_BUFFER"),
+    (0x889F, "VERTEX_ATTRIB_ARRAY_BUFFER_BINDING"),
+    (0x88BF, "TIME_ELAPSED_EXT"),
+    (0x88E4, "STATIC_DRAW"),
+    (0x88EB, "PIXEL_PACK_BUFFER"),
+    (0x88EC, "PIXEL_UNPACK_BUFFER"),
+    (0x88F0, "DEPTH24_STENCIL8"),
+    (0x88FD, "VERTEX_ATTRIB_ARRAY_INTEGER"),
+    (0x88FE, "VERTEX_ATTRIB_ARRAY_DIVISOR"),
+    (0x8A11, "UNIFORM_BUFFER"),
+    (0x8A28, "UNIFORM_BUFFER_BINDING"),
+    (0x8A29, "UNIFORM_BUFFER_START"),
+    (0x8A2A, "UNIFORM_BUFFER_SIZE"),
+    (0x8A2F, "MAX_UNIFORM_BUFFER_BINDINGS"),
+    (0x8A36, "ACTIVE_UNIFORM_BLOCKS"),
+    (0x8A3F, "UNIFORM_BLOCK_BINDING"),
+    (0x8A40, "UNIFORM_BLOCK_DATA_SIZE"),
+    (0x8A42, "UNIFORM_BLOCK_ACTIVE_UNIFORMS"),
+    (0x8A43, "UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES"),
+    (0x8A44, "UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER"),
+    (0x8A46, "UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER"),
+    (0x8B30, "FRAGMENT_SHADER"),
+    (0x8B31, "VERTEX_SHADER"),
+    (0x8B4B, "MAX_VARYING_COMPONENTS"),
+    (0x8B4F, "SHADER_TYPE"),
+    (0x8B50, "FLOAT_VEC2"),
+    (0x8B51, "FLOAT_VEC3"),
+    (0x8B52, "FLOAT_VEC4"),
+    (0x8B53, "INT_VEC2"),
+    (0x8B54, "INT_VEC3"),
+    (0x8B55, "INT_VEC4"),
+    (0x8B56, "BOOL"),
+    (0x8B57, "BOOL_VEC2"),
+    (0x8B58, "BOOL_VEC3"),
+    (0x8B59, "BOOL_VEC4"),
+    (0x8B5A, "FLOAT_MAT2"),
+    (0x8B5B, "FLOAT_MAT3"),
+    (0x8B5C, "FLOAT_MAT4"),
+    (0x8B5E, "SAMPLER_2D"),
+    (0x8B5F, "SAMPLER_3D"),
+    (0x8B60, "SAMPLER_CUBE"),
+    (0x8B80, "DELETE_STATUS"),
+    (0x8B81, "COMPILE_STATUS"),
+    (0x8B82, "LINK_STATUS"),
+    (0x8B83, "VALIDATE_STATUS"),
+    (0x8B84, "INFO_LOG_LENGTH"),
+    (0x8B85, "ATTACHED_SHADERS"),
+    (0x8B86, "ACTIVE_UNIFORMS"),
+    (0x8B89, "ACTIVE_ATTRIBUTES"),
+    (0x8B8C, "SHADING_LANGUAGE_VERSION"),
+    (0x8C1A, "TEXTURE_2D_ARRAY"),
+    (0x8C76, "TRANSFORM_FEEDBACK_VARYING_MAX_LENGTH"),
+    (0x8C7F, "TRANSFORM_FEEDBACK_BUFFER_MODE"),
+    (0x8C80, "MAX_TRANSFORM_FEEDBACK_SEPARATE_COMPONENTS"),
+    (0x8C83, "TRANSFORM_FEEDBACK_VARYINGS"),
+    (0x8C84, "TRANSFORM_FEEDBACK_BUFFER_START"),
+    (0x8C85, "TRANSFORM_FEEDBACK_BUFFER_SIZE"),
+    (0x8C89, "RASTERIZER_DISCARD"),
+    (0x8C8A, "MAX_TRANSFORM_FEEDBACK_INTERLEAVED_COMPONENTS"),
+    (0x8C8B, "MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS"),
+    (0x8C8C, "INTERLEAVED_ATTRIBS"),
+    (0x8C8D, "SEPARATE_ATTRIBS"),
+    (0x8C8E, "TRANSFORM_FEEDBACK_BUFFER"),
+    (0x8C8F, "TRANSFORM_FEEDBACK_BUFFER_BINDING"),
+    (0x8CA3, "STENCIL_BACK_REF"),
+    (0x8CA4, "STENCIL_BACK_VALUE_MASK"),
+    (0x8CA5, "STENCIL_BACK_WRITEMASK"),
+    (0x8CA6, "DRAW_FRAMEBUFFER_BINDING"),
+    (0x8CA7, "RENDERBUFFER_BINDING"),
+    (0x8CA8, "READ_FRAMEBUFFER"),
+    (0x8CA9, "DRAW_FRAMEBUFFER"),
+    (0x8CAA, "READ_FRAMEBUFFER_BINDING"),
+    (0x8CAC, "DEPTH_COMPONENT32F"),
+    (0x8CD5, "FRAMEBUFFER_COMPLETE"),
+    (0x8CD6, "FRAMEBUFFER_INCOMPLETE_ATTACHMENT"),
+    (0x8CD7, "FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT"),
+    (0x8CD9, "FRAMEBUFFER_INCOMPLETE_DIMENSIONS"),
+    (0x8CDD, "FRAMEBUFFER_UNSUPPORTED"),
+    (0x8CDF, "MAX_COLOR_ATTACHMENTS"),
+    (0x8CE0, "COLOR_ATTACHMENT0"),
+    (0x8CE1, "COLOR_ATTACHMENT1"),
+    (0x8CE2, "COLOR_ATTACHMENT2"),
+    (0x8CE3, "COLOR_ATTACHMENT3"),
+    (0x8CE4, "COLOR_ATTACHMENT4"),
+    (0x8CE5, "COLOR_ATTACHMENT5"),
+    (0x8CE6, "COLOR_ATTACHMENT6"),
+    (0x8CE7, "COLOR_ATTACHMENT7"),
+    (0x8D00, "DEPTH_ATTACHMENT"),
+    (0x8D20, "STENCIL_ATTACHMENT"),
+    (0x8D40, "FRAMEBUFFER"),
+    (0x8D41, "RENDERBUFFER"),
+    (0x8D48, "STENCIL_INDEX8"),
+    (0x8D62, "RGB565"),
+    (0x8D70, "RGBA32UI"),
+    (0x8D71, "RGB32UI"),
+    (0x8D76, "RGBA16UI"),
+    (0x8D77, "RGB16UI"),
+    (0x8D7C, "RGBA8UI"),
+    (0x8D7D, "RGB8UI"),
+    (0x8D82, "RGBA32I"),
+    (0x8D83, "RGB32I"),
+    (0x8D88, "RGBA16I"),
+    (0x8D89, "RGB16I"),
+    (0x8D8E, "RGBA8I"),
+    (0x8D8F, "RGB8I"),
+    (0x8D94, "RED_INTEGER"),
+    (0x8D98, "RGB_INTEGER"),
+    (0x8D9E, "RGBA_INTEGER"),
+    (0x8D9F, "INT_2_10_10_10_REV"),
+    (0x8DC6, "UNSIGNED_INT_VEC2"),
+    (0x8DC7, "UNSIGNED_INT_VEC3"),
+    (0x8DC8, "UNSIGNED_INT_VEC4"),
+    (0x8DFB, "MAX_VERTEX_UNIFORM_VECTORS"),
+    (0x8DFC, "MAX_VARYING_VECTORS"),
+    (0x8DFD, "MAX_FRAGMENT_UNIFORM_VECTORS"),
+    (0x8E22, "TRANSFORM_FEEDBACK"),
+    (0x8E23, "TRANSFORM_FEEDBACK_PAUSED"),
+    (0x8E24, "TRANSFORM_FEEDBACK_ACTIVE"),
+    (0x8E25, "TRANSFORM_FEEDBACK_BINDING"),
+    (0x8E28, "TIMESTAMP_EXT"),
+    (0x8F36, "COPY_READ_BUFFER"),
+    (0x8F37, "COPY_WRITE_BUFFER"),
+    (0x9240, "UNPACK_FLIP_Y_WEBGL"),
+    (0x9241, "UNPACK_PREMULTIPLY_ALPHA_WEBGL"),
+    (0x9243, "UNPACK_COLORSPACE_CONVERSION_WEBGL"),
+];
+
+/// Name of a GL enum value without the `GL_` prefix, e.g. `TEXTURE_2D` for
+/// `0x0DE1`. Returns `None` for unknown values and for values below `0x100`,
+/// which are ambiguous without knowing the parameter they were passed to.
+pub fn enum_name(value: u32) -> Option<&'static str> {
+    ENUM_NAMES
+        .binary_search_by_key(&value, |&(v, _)| v)
+        .ok()
+        .map(|i| ENUM_NAMES[i].1)
+}
+
+/// Name of a primitive mode passed to `drawArrays`/`drawElements`.
+pub fn primitive_mode_name(mode: u32) -> Option<&'static str> {
+    Some(match mode {
+        GL_POINTS => "POINTS",
+        GL_LINES => "LINES",
+        GL_LINE_LOOP => "LINE_LOOP",
+        GL_LINE_STRIP => "LINE_STRIP",
+        GL_TRIANGLES => "TRIANGLES",
+        GL_TRIANGLE_STRIP => "TRIANGLE_STRIP",
+        GL_TRIANGLE_FAN => "TRIANGLE_FAN",
+        _ => return None,
+    })
+}
+
+/// Human readable form of an enum value for diagnostics: `TEXTURE_2D (0x0DE1)`
+/// when the value is known, otherwise just the hex value.
+pub fn describe_enum(value: u32) -> String {
+    match enum_name(value) {
+        Some(name) => format!("{} (0x{:04X})", name, value),
+        None => format!("0x{:04X}", value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enum_names_are_sorted_and_unique() {
+        assert!(ENUM_NAMES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn describes_known_and_unknown_enums() {
+        assert_eq!(enum_name(GL_TEXTURE_2D), Some("TEXTURE_2D"));
+        assert_eq!(
+            enum_name(GL_FRAMEBUFFER_COMPLETE),
+            Some("FRAMEBUFFER_COMPLETE")
+        );
+        assert_eq!(enum_name(GL_POINTS), None);
+        assert_eq!(primitive_mode_name(GL_TRIANGLE_FAN), Some("TRIANGLE_FAN"));
+        assert_eq!(
+            describe_enum(GL_TEXTURE_LOD_BIAS),
+            "TEXTURE_LOD_BIAS (0x8501)"
+        );
+        assert_eq!(describe_enum(0xBEEF), "0xBEEF");
+    }
+}
@@ -0,0 +1,473 @@
+//! GLSL source formatter
+//!
+//! Reprints GLSL (or the GLSL-like output of the [decompiler](crate::decompiler))
+//! with fixed rules, so a shader and its round trip through the compiler and
+//! decompiler can be diffed without whitespace noise:
+//!
+//! - one statement per line, with `{` ending the line that opens a block and
+//!   `}` on a line of its own (`} else {` and `} while (...);` stay joined)
+//! - binary and assignment operators, `?` and `:` spaced; unary operators,
+//!   calls, subscripts and member access not
+//! - `case` labels one level inside their `switch`, statements two
+//! - runs of blank lines collapsed to one, and none after `{` or before `}`
+//! - preprocessor directives and comments kept as written, directives at
+//!   column 0
+//!
+//! Formatting is idempotent: formatted source formats to itself.
+
+/// Formatter settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Indent string (e.g., "  " or "\t")
+    pub indent: String,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: "    ".to_string(),
+        }
+    }
+}
+
+/// Format GLSL source.
+pub fn format_glsl(source: &str, options: &FormatOptions) -> String {
+    let mut printer = Printer::new(options);
+    let tokens = tokenize(source);
+    for (i, token) in tokens.iter().enumerate() {
+        printer.token(token, tokens.get(i + 1));
+    }
+    printer.finish()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    /// A preprocessor directive, with its continuation lines
+    Directive,
+    LineComment,
+    BlockComment,
+    /// Identifier, keyword or number
+    Word,
+    Punct,
+}
+
+#[derive(Debug)]
+struct Token<'a> {
+    kind: Kind,
+    text: &'a str,
+    /// Whether a line break separates it from the previous token
+    newline_before: bool,
+    /// Whether a blank line separates it from the previous token
+    blank_before: bool,
+}
+
+/// Operators, longest first so each matches greedily.
+const PUNCTUATION: [&str; 22] = [
+    "<<=", ">>=", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "^^", "+=", "-=",
+    "*=", "/=", "%=", "&=", "|=", "^=", "->",
+];
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let bytes = source.as_bytes();
+    let mut i = 0;
+    let mut newlines = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c == b'\n' {
+            newlines += 1;
+            i += 1;
+            continue;
+        }
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        let rest = &source[i..];
+        let at_line_start = tokens.is_empty() || newlines > 0;
+        let (kind, len) = if c == b'#' && at_line_start {
+            // Up to the first newline not escaped by a trailing backslash
+            let mut end = 0;
+            loop {
+                match rest[end..].find('\n') {
+                    Some(at) if rest[..end + at].trim_end().ends_with('\\') => end += at + 1,
+                    Some(at) => break (Kind::Directive, end + at),
+                    None => break (Kind::Directive, rest.len()),
+                }
+            }
+        } else if rest.starts_with("//") {
+            (Kind::LineComment, rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            let len = body.find("*/").map_or(rest.len(), |end| end + 4);
+            (Kind::BlockComment, len)
+        } else if c.is_ascii_digit()
+            || (c == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+        {
+            (Kind::Word, number_len(rest))
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (Kind::Word, len)
+        } else {
+            let len = PUNCTUATION
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .map_or_else(
+                    || rest.chars().next().map_or(1, char::len_utf8),
+                    |op| op.len(),
+                );
+            (Kind::Punct, len)
+        };
+        tokens.push(Token {
+            kind,
+            text: rest[..len].trim_end(),
+            newline_before: newlines > 0,
+            blank_before: newlines > 1,
+        });
+        newlines = 0;
+        i += len;
+    }
+    tokens
+}
+
+/// Length of the number at the start of `text`, with exponent and suffix.
+fn number_len(text: &str) -> usize {
+    let bytes = text.as_bytes();
+    let hex = text.starts_with("0x") || text.starts_with("0X");
+    let mut len = 0;
+    while len < bytes.len() {
+        let c = bytes[len];
+        let exponent_sign =
+            !hex && (c == b'+' || c == b'-') && len > 0 && matches!(bytes[len - 1], b'e' | b'E');
+        if c.is_ascii_alphanumeric() || c == b'.' || c == b'_' || exponent_sign {
+            len += 1;
+        } else {
+            break;
+        }
+    }
+    len
+}
+
+/// What a `{` opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Brace {
+    /// A function body or control flow block
+    Code,
+    /// The body of a `do`, whose `}` the `while` follows
+    Do,
+    Switch,
+    /// A struct or interface block, whose `}` is followed by a name or `;`
+    Declaration,
+}
+
+struct Printer<'o> {
+    options: &'o FormatOptions,
+    out: String,
+    line: String,
+    indent: usize,
+    braces: Vec<Brace>,
+    /// Open parentheses and brackets on the current line
+    parens: u32,
+    /// The current line is complete; the next token starts another
+    line_done: bool,
+    /// The last line written opened a block
+    after_open: bool,
+    /// First word of the current statement
+    statement: Option<String>,
+    /// The current line is a `case` or `default` label
+    label: bool,
+    prev: Option<(Kind, String)>,
+    /// The previous token was a unary prefix operator
+    prev_unary: bool,
+}
+
+impl<'o> Printer<'o> {
+    fn new(options: &'o FormatOptions) -> Self {
+        Self {
+            options,
+            out: String::new(),
+            line: String::new(),
+            indent: 0,
+            braces: Vec::new(),
+            parens: 0,
+            line_done: false,
+            after_open: false,
+            statement: None,
+            label: false,
+            prev: None,
+            prev_unary: false,
+        }
+    }
+
+    fn token(&mut self, token: &Token, next: Option<&Token>) {
+        let text = token.text;
+        if self.line_done {
+            let trailing = !token.newline_before
+                && matches!(token.kind, Kind::LineComment | Kind::BlockComment);
+            if trailing {
+                self.line.push(' ');
+                self.line.push_str(text);
+                return;
+            }
+            self.break_line();
+        }
+        if token.blank_before
+            && !self.out.is_empty()
+            && !self.after_open
+            && text != "}"
+            && (self.line.is_empty() || token.kind == Kind::Directive)
+        {
+            self.break_line();
+            self.out.push('\n');
+        }
+
+        match token.kind {
+            Kind::Directive => {
+                self.break_line();
+                self.out.push_str(text);
+                self.out.push('\n');
+                self.after_open = false;
+                self.prev = None;
+                return;
+            }
+            Kind::LineComment | Kind::BlockComment => {
+                if token.newline_before {
+                    self.break_line();
+                }
+                let space = !self.line.is_empty();
+                self.push(text, space);
+                if token.kind == Kind::LineComment {
+                    self.line_done = true;
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        if self.statement.is_none() && token.kind == Kind::Word {
+            self.statement = Some(text.to_string());
+            self.label = matches!(text, "case" | "default") && self.in_switch();
+        }
+        match text {
+            "{" => {
+                let brace = match (&self.prev, self.statement.as_deref()) {
+                    (_, Some("switch")) => Brace::Switch,
+                    (Some((Kind::Word, prev)), _) if prev == "do" => Brace::Do,
+                    (Some((Kind::Word, prev)), _) if prev != "else" => Brace::Declaration,
+                    _ => Brace::Code,
+                };
+                let space = !self.line.is_empty();
+                self.push("{", space);
+                self.braces.push(brace);
+                self.end_line();
+                self.indent += if brace == Brace::Switch { 2 } else { 1 };
+                self.after_open = true;
+                return;
+            }
+            "}" => {
+                self.break_line();
+                let brace = self.braces.pop().unwrap_or(Brace::Code);
+                let depth = if brace == Brace::Switch { 2 } else { 1 };
+                self.indent = self.indent.saturating_sub(depth);
+                self.push("}", false);
+                self.statement = None;
+                let joined = match next.map(|t| t.text) {
+                    Some(";") => true,
+                    Some("else") => true,
+                    Some("while") => brace == Brace::Do,
+                    _ => brace == Brace::Declaration,
+                };
+                if !joined {
+                    self.end_line();
+                } else if brace == Brace::Declaration {
+                    self.statement = Some("}".to_string());
+                }
+                return;
+            }
+            ";" if self.parens == 0 => {
+                self.push(";", false);
+                self.end_line();
+                return;
+            }
+            ":" if self.label => {
+                self.push(":", false);
+                self.end_line();
+                return;
+            }
+            _ => {}
+        }
+
+        let space = !self.line.is_empty() && self.space_before(token);
+        let unary = self.is_unary(text);
+        self.push(text, space);
+        match text {
+            "(" | "[" => self.parens += 1,
+            ")" | "]" => self.parens = self.parens.saturating_sub(1),
+            _ => {}
+        }
+        self.prev_unary = unary;
+        self.prev = Some((token.kind, text.to_string()));
+    }
+
+    fn in_switch(&self) -> bool {
+        self.braces.last() == Some(&Brace::Switch)
+    }
+
+    /// Whether an operator in the current position is a prefix operator.
+    fn is_unary(&self, text: &str) -> bool {
+        if !matches!(text, "-" | "+" | "!" | "~" | "++" | "--") {
+            return false;
+        }
+        match &self.prev {
+            None => true,
+            Some((Kind::Word, prev)) => matches!(prev.as_str(), "return" | "case"),
+            Some((_, prev)) => {
+                !matches!(prev.as_str(), ")" | "]")
+                    && (!matches!(prev.as_str(), "++" | "--") || self.prev_unary)
+            }
+        }
+    }
+
+    fn space_before(&self, token: &Token) -> bool {
+        let text = token.text;
+        let Some((prev_kind, prev)) = &self.prev else {
+            return true;
+        };
+        if self.prev_unary || matches!(prev.as_str(), "(" | "[" | ".") {
+            return false;
+        }
+        match text {
+            ")" | "]" | "," | ";" | "." => false,
+            "(" | "[" => match prev_kind {
+                Kind::Word => {
+                    matches!(
+                        prev.as_str(),
+                        "if" | "for" | "while" | "switch" | "return" | "case"
+                    ) && text == "("
+                }
+                _ => !matches!(prev.as_str(), ")" | "]"),
+            },
+            // Postfix increments hug their operand
+            "++" | "--" if !self.is_unary(text) => false,
+            _ => true,
+        }
+    }
+
+    fn push(&mut self, text: &str, space: bool) {
+        if self.line.is_empty() {
+            let mut indent = self.indent;
+            if self.label {
+                indent = indent.saturating_sub(1);
+            }
+            for _ in 0..indent {
+                self.line.push_str(&self.options.indent);
+            }
+        } else if space {
+            self.line.push(' ');
+        }
+        self.line.push_str(text);
+    }
+
+    /// Finish the current line once a trailing comment had its chance.
+    fn end_line(&mut self) {
+        self.line_done = true;
+        self.statement = None;
+        self.label = false;
+        self.parens = 0;
+        self.prev = None;
+        self.prev_unary = false;
+    }
+
+    fn break_line(&mut self) {
+        self.line_done = false;
+        if self.line.is_empty() {
+            return;
+        }
+        self.out.push_str(self.line.trim_end());
+        self.out.push('\n');
+        self.after_open = self.line.ends_with('{');
+        self.line.clear();
+    }
+
+    fn finish(mut self) -> String {
+        self.break_line();
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(source: &str) -> String {
+        format_glsl(source, &FormatOptions::default())
+    }
+
+    #[test]
+    fn test_statements_blocks_and_operators() {
+        let source = "#version 300 es\n\
+                      precision  highp float;\n\n\n\
+                      uniform Lights{vec4 color;int count;}lights;\n\
+                      out vec4 fragColor; // output\n\
+                      void main(){\n\n\
+                      \tfloat x=-lights.color.r*2.0e-3+ float(lights.count) ;\n\
+                      for(int i=0;i<lights.count;i++){x+=texture(tex,uv)[i];}\n\
+                      if(x>0.5&&!(x<1.0)){fragColor=vec4(x);}else{fragColor=x>0.0?vec4(1):vec4(0);}\n\
+                      do{x--;}while(x>0.0);\n\
+                      switch(lights.count){case 0:x=1.0;break;default:break;}\n\
+                      }\n";
+        assert_eq!(
+            format(source),
+            "#version 300 es\n\
+             precision highp float;\n\
+             \n\
+             uniform Lights {\n\
+             \x20   vec4 color;\n\
+             \x20   int count;\n\
+             } lights;\n\
+             out vec4 fragColor; // output\n\
+             void main() {\n\
+             \x20   float x = -lights.color.r * 2.0e-3 + float(lights.count);\n\
+             \x20   for (int i = 0; i < lights.count; i++) {\n\
+             \x20       x += texture(tex, uv)[i];\n\
+             \x20   }\n\
+             \x20   if (x > 0.5 && !(x < 1.0)) {\n\
+             \x20       fragColor = vec4(x);\n\
+             \x20   } else {\n\
+             \x20       fragColor = x > 0.0 ? vec4(1) : vec4(0);\n\
+             \x20   }\n\
+             \x20   do {\n\
+             \x20       x--;\n\
+             \x20   } while (x > 0.0);\n\
+             \x20   switch (lights.count) {\n\
+             \x20       case 0:\n\
+             \x20           x = 1.0;\n\
+             \x20           break;\n\
+             \x20       default:\n\
+             \x20           break;\n\
+             \x20   }\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_formatting_is_idempotent_and_indent_is_configurable() {
+        let source = "// header\n\
+                      /* block */ float f(float a){return a*a;}\n\
+                      #define SQUARE(x) ((x) * (x))\n\
+                      void main(){gl_Position=vec4(f(- 1.0),SQUARE(2.0),0,1);}";
+        let formatted = format(source);
+        assert_eq!(format(&formatted), formatted);
+
+        let tabs = format_glsl(
+            source,
+            &FormatOptions {
+                indent: "\t".to_string(),
+            },
+        );
+        assert!(tabs.contains("\n\treturn a * a;\n"));
+        assert!(tabs.contains("\n#define SQUARE(x) ((x) * (x))\n"));
+        assert!(tabs.contains("vec4(f(-1.0), SQUARE(2.0), 0, 1);"));
+    }
+}
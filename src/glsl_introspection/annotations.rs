@@ -1,6 +1,8 @@
 //! Custom annotation types for GLSL
 
+use super::parser::ParseError;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Custom annotations in GLSL comments
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +12,10 @@ pub enum Annotation {
     UniformGroup(UniformGroup),
     /// @buffer_layout(std140)
     BufferLayout(BufferLayout),
+    /// @default(1.0) or @default(1, 0, 0) on a uniform
+    Default(DefaultValue),
+    /// @range(0, 1) on a uniform
+    Range(ValueRange),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,3 +27,125 @@ pub struct UniformGroup {
 pub struct BufferLayout {
     pub layout: String, // "std140", "std430", etc.
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefaultValue {
+    /// Components, column-major for matrices
+    pub values: Vec<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValueRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Annotations of each uniform by name.
+///
+/// Annotations go in `//` comments, either trailing the uniform's declaration
+/// or on the comment lines right above it:
+///
+/// ```glsl
+/// // @range(0, 1)
+/// uniform float mixAmount; // @default(0.5)
+/// ```
+pub fn parse_uniform_annotations(
+    source: &str,
+) -> Result<HashMap<String, Vec<Annotation>>, ParseError> {
+    let mut annotations = HashMap::new();
+    let mut pending = Vec::new();
+    for line in source.lines() {
+        let (code, comment) = match line.find("//") {
+            Some(at) => (line[..at].trim(), &line[at + 2..]),
+            None => (line.trim(), ""),
+        };
+        pending.extend(parse_comment(comment)?);
+        if code.is_empty() {
+            continue;
+        }
+        if let Some(names) = uniform_names(code) {
+            for name in names {
+                annotations.insert(name, pending.clone());
+            }
+        }
+        pending.clear();
+    }
+    annotations.retain(|_, found: &mut Vec<Annotation>| !found.is_empty());
+    Ok(annotations)
+}
+
+/// Names declared by a `uniform` declaration, or `None` for other code.
+fn uniform_names(code: &str) -> Option<Vec<String>> {
+    let mut words = code.trim_end_matches(';').split_whitespace();
+    // Skip layout(...) and precision qualifiers up to the keyword
+    words.find(|word| *word == "uniform")?;
+    let _ty = words.find(|word| !matches!(*word, "lowp" | "mediump" | "highp"))?;
+    let declarators: String = words.collect::<Vec<_>>().join(" ");
+    Some(
+        declarators
+            .split(',')
+            .filter_map(|declarator| {
+                let name = declarator.split(['[', '=']).next()?.trim();
+                let is_identifier =
+                    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                is_identifier.then(|| name.to_string())
+            })
+            .collect(),
+    )
+}
+
+fn parse_comment(comment: &str) -> Result<Vec<Annotation>, ParseError> {
+    let mut found = Vec::new();
+    let mut rest = comment;
+    while let Some(at) = rest.find('@') {
+        rest = &rest[at + 1..];
+        let name_len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        let name = &rest[..name_len];
+        let Some(args) = rest[name_len..].strip_prefix('(') else {
+            continue;
+        };
+        let Some(close) = args.find(')') else {
+            return Err(ParseError::AnnotationError(format!(
+                "unclosed @{name} annotation"
+            )));
+        };
+        let args = &args[..close];
+        rest = &rest[name_len + 1 + close..];
+        let numbers = || {
+            args.split(',')
+                .map(|arg| {
+                    let value = arg.trim().parse::<f64>().ok().filter(|v| v.is_finite());
+                    value.ok_or_else(|| {
+                        ParseError::AnnotationError(format!(
+                            "@{name} expects numbers, got '{}'",
+                            arg.trim()
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        match name {
+            "default" => found.push(Annotation::Default(DefaultValue { values: numbers()? })),
+            "range" => match numbers()?[..] {
+                [min, max] if min <= max => found.push(Annotation::Range(ValueRange { min, max })),
+                _ => {
+                    return Err(ParseError::AnnotationError(format!(
+                        "@range expects (min, max), got ({args})"
+                    )))
+                }
+            },
+            "uniform_group" => {
+                if let Ok(group) = args.trim().parse() {
+                    found.push(Annotation::UniformGroup(UniformGroup { group }));
+                }
+            }
+            "buffer_layout" => found.push(Annotation::BufferLayout(BufferLayout {
+                layout: args.trim().to_string(),
+            })),
+            _ => {}
+        }
+    }
+    Ok(found)
+}
@@ -1,7 +1,13 @@
 //! Resource manifest generation
 
-use naga::Module;
+use super::annotations::Annotation;
+use crate::naga_wasm_backend::interface_layout;
+use crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset;
+use crate::naga_wasm_backend::types::type_size;
+use crate::naga_wasm_backend::varying_packing;
+use naga::{AddressSpace, Block, Expression, Function, Module, ShaderStage, Statement, TypeInner};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Complete resource manifest for a shader
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,14 +16,36 @@ pub struct ResourceManifest {
     pub attributes: Vec<AttributeInfo>,
     pub varyings: Vec<VaryingInfo>,
     pub textures: Vec<TextureInfo>,
+    pub blocks: Vec<UniformBlockInfo>,
+    pub stats: ShaderStats,
+}
+
+/// Statistics derived from the shader without running it, for budgeting
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShaderStats {
+    /// 32-bit components of the vertex outputs or fragment inputs
+    pub varying_components: u32,
+    /// Texture uniforms an image operation reads
+    pub texture_units: u32,
+    /// Arithmetic, relational and conversion expressions, before optimization
+    pub alu_ops: u32,
+    pub uses_derivatives: bool,
+    pub uses_discard: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UniformInfo {
     pub name: String,
     pub glsl_type: String,
+    /// Location, as assigned at link time
+    pub location: u32,
+    /// Offset of the value in the uniform buffer
     pub offset: u32,
     pub size: u32,
+    /// Initial value from an `@default` annotation
+    pub default: Option<Vec<f64>>,
+    /// Playground control range from an `@range` annotation
+    pub range: Option<(f64, f64)>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,21 +59,252 @@ pub struct AttributeInfo {
 pub struct VaryingInfo {
     pub name: String,
     pub glsl_type: String,
+    /// Packed location, as assigned at link time
+    pub location: u32,
+    /// First component within `location`
+    pub component: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UniformBlockInfo {
+    /// Block name, which programs share the block by
+    pub name: String,
+    /// Size of the block's buffer data
+    pub size: u32,
+    pub members: Vec<BlockMemberInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockMemberInfo {
+    pub name: String,
+    pub glsl_type: String,
+    /// Offset within the block's buffer data
+    pub offset: u32,
+    pub size: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TextureInfo {
     pub name: String,
+    /// Uniform location holding the texture unit
     pub binding: u32,
 }
 
-/// Generate a resource manifest from parsed Naga module
-pub fn generate_manifest(_module: &Module) -> ResourceManifest {
-    // Phase 0: Return empty manifest
+/// Generate a resource manifest from parsed Naga module, with the locations
+/// the linker would assign to it as a vertex shader parsed from `source`
+pub fn generate_manifest(module: &Module, source: &str) -> ResourceManifest {
+    // A layout error fails the link, so such a shader has no locations
+    let Ok(layout) = interface_layout::compute_layout(
+        Some((module, source)),
+        None,
+        &interface_layout::LocationBindings::default(),
+        &interface_layout::LinkLimits::default(),
+        0,
+    ) else {
+        return ResourceManifest {
+            uniforms: Vec::new(),
+            attributes: Vec::new(),
+            varyings: Vec::new(),
+            textures: Vec::new(),
+            blocks: Vec::new(),
+            stats: shader_stats(module),
+        };
+    };
+
+    let attributes = module
+        .entry_points
+        .iter()
+        .filter(|ep| ep.stage == ShaderStage::Vertex)
+        .flat_map(|ep| &ep.function.arguments)
+        .filter_map(|arg| {
+            let name = arg.name.as_ref()?;
+            Some(AttributeInfo {
+                name: name.clone(),
+                glsl_type: varying_packing::glsl_type_name(module, arg.ty),
+                location: *layout.attribute_locations.get(name)?,
+            })
+        })
+        .collect();
+
+    let mut globals: Vec<_> = module.global_variables.iter().collect();
+    globals.sort_by_key(|(handle, _)| handle.index());
+    let mut uniforms = Vec::new();
+    let mut textures = Vec::new();
+    let mut blocks = Vec::new();
+    for (_, var) in globals {
+        if let Some(block_name) = interface_layout::uniform_block_name(module, var) {
+            if let TypeInner::Struct { members, span } = &module.types[var.ty].inner {
+                blocks.push(UniformBlockInfo {
+                    name: block_name.clone(),
+                    size: *span,
+                    members: members
+                        .iter()
+                        .filter_map(|member| {
+                            Some(BlockMemberInfo {
+                                name: member.name.clone()?,
+                                glsl_type: varying_packing::glsl_type_name(module, member.ty),
+                                offset: member.offset,
+                                size: type_size(&module.types[member.ty].inner).unwrap_or(4),
+                            })
+                        })
+                        .collect(),
+                });
+            }
+            continue;
+        }
+        let Some(name) = &var.name else {
+            continue;
+        };
+        let Some(&location) = layout.uniform_locations.get(name) else {
+            continue;
+        };
+        if var.space == AddressSpace::Handle {
+            textures.push(TextureInfo {
+                name: name.clone(),
+                binding: location,
+            });
+        } else {
+            uniforms.push(UniformInfo {
+                name: name.clone(),
+                glsl_type: varying_packing::glsl_type_name(module, var.ty),
+                location,
+                offset: get_webgl_uniform_data_offset(location),
+                size: type_size(&module.types[var.ty].inner).unwrap_or(4),
+                default: None,
+                range: None,
+            });
+        }
+    }
+
+    // Vertex outputs with the slots the linker packs them into
+    let varyings = varying_packing::stage_varyings(module, ShaderStage::Vertex)
+        .into_iter()
+        .map(|output| VaryingInfo {
+            glsl_type: varying_packing::glsl_type_name(module, output.ty),
+            location: layout.varying_locations[&output.name],
+            component: layout.varying_components[&output.name],
+            name: output.name,
+        })
+        .collect();
+
     ResourceManifest {
-        uniforms: Vec::new(),
-        attributes: Vec::new(),
-        varyings: Vec::new(),
-        textures: Vec::new(),
+        uniforms,
+        attributes,
+        varyings,
+        textures,
+        blocks,
+        stats: shader_stats(module),
+    }
+}
+
+/// Attach `@default` and `@range` annotations to the uniforms they name
+pub fn apply_annotations(
+    manifest: &mut ResourceManifest,
+    annotations: &HashMap<String, Vec<Annotation>>,
+) {
+    for uniform in &mut manifest.uniforms {
+        for annotation in annotations.get(&uniform.name).into_iter().flatten() {
+            match annotation {
+                Annotation::Default(default) => uniform.default = Some(default.values.clone()),
+                Annotation::Range(range) => uniform.range = Some((range.min, range.max)),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Derive the statistics of every function of the module
+pub fn shader_stats(module: &Module) -> ShaderStats {
+    let functions: Vec<&Function> = module
+        .functions
+        .iter()
+        .map(|(_, func)| func)
+        .chain(module.entry_points.iter().map(|ep| &ep.function))
+        .collect();
+
+    let mut textures = Vec::new();
+    let mut stats = ShaderStats {
+        varying_components: [ShaderStage::Vertex, ShaderStage::Fragment]
+            .into_iter()
+            .flat_map(|stage| varying_packing::stage_varyings(module, stage))
+            .map(|varying| varying.components)
+            .sum(),
+        ..ShaderStats::default()
+    };
+    for func in functions {
+        for (_, expr) in func.expressions.iter() {
+            match *expr {
+                Expression::Unary { .. }
+                | Expression::Binary { .. }
+                | Expression::Select { .. }
+                | Expression::Relational { .. }
+                | Expression::Math { .. }
+                | Expression::As { .. } => stats.alu_ops += 1,
+                Expression::Derivative { .. } => {
+                    stats.alu_ops += 1;
+                    stats.uses_derivatives = true;
+                }
+                Expression::ImageSample { image, .. }
+                | Expression::ImageLoad { image, .. }
+                | Expression::ImageQuery { image, .. } => {
+                    if let Expression::GlobalVariable(var) = func.expressions[image] {
+                        if !textures.contains(&var) {
+                            textures.push(var);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        stats.uses_discard |= block_kills(&func.body);
+    }
+    stats.texture_units = textures.len() as u32;
+    stats
+}
+
+fn block_kills(block: &Block) -> bool {
+    block.iter().any(|stmt| match stmt {
+        Statement::Kill => true,
+        Statement::Block(inner) => block_kills(inner),
+        Statement::If { accept, reject, .. } => block_kills(accept) || block_kills(reject),
+        Statement::Switch { cases, .. } => cases.iter().any(|case| block_kills(&case.body)),
+        Statement::Loop {
+            body, continuing, ..
+        } => block_kills(body) || block_kills(continuing),
+        _ => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats_count_varyings_textures_and_flags() {
+        let options = naga::front::glsl::Options::from(ShaderStage::Fragment);
+        let module = naga::front::glsl::Frontend::default()
+            .parse(
+                &options,
+                "#version 300 es
+                precision highp float;
+                uniform sampler2D a;
+                uniform sampler2D b;
+                uniform sampler2D unused;
+                in vec2 uv;
+                out vec4 color;
+                void main() {
+                    vec4 c = texture(a, uv) + texture(b, uv * 2.0) + texture(a, uv);
+                    if (dFdx(c.r) > 0.5) discard;
+                    color = c;
+                }",
+            )
+            .unwrap();
+
+        let stats = shader_stats(&module);
+        assert_eq!(stats.texture_units, 2);
+        assert!(stats.uses_derivatives);
+        assert!(stats.uses_discard);
+        assert!(stats.alu_ops >= 4);
+        assert_eq!(stats.varying_components, 2);
     }
 }
@@ -6,13 +6,31 @@
 mod annotations;
 mod manifest;
 mod parser;
+mod variants;
 
-pub use annotations::{Annotation, BufferLayout, UniformGroup};
-pub use manifest::{AttributeInfo, ResourceManifest, UniformInfo};
-pub use parser::{parse_glsl, ParseError};
+pub use annotations::{
+    parse_uniform_annotations, Annotation, BufferLayout, DefaultValue, UniformGroup, ValueRange,
+};
+pub use manifest::{
+    AttributeInfo, BlockMemberInfo, ResourceManifest, ShaderStats, UniformBlockInfo, UniformInfo,
+    VaryingInfo,
+};
+pub use parser::{parse_glsl, parse_glsl_stage, ParseError};
+pub use variants::{compile_variants, preprocess, ShaderVariant, VariantTable, MAX_FEATURES};
 
 /// Parse GLSL with annotations and generate a resource manifest
 pub fn introspect_shader(source: &str) -> Result<ResourceManifest, ParseError> {
-    let parsed = parser::parse_glsl(source)?;
-    Ok(manifest::generate_manifest(&parsed))
+    introspect_stage(source, naga::ShaderStage::Vertex)
+}
+
+/// Like [`introspect_shader`], parsing the source as a `stage` shader
+pub fn introspect_stage(
+    source: &str,
+    stage: naga::ShaderStage,
+) -> Result<ResourceManifest, ParseError> {
+    let parsed = parser::parse_glsl_stage(source, stage)?;
+    let annotations = annotations::parse_uniform_annotations(source)?;
+    let mut manifest = manifest::generate_manifest(&parsed, source);
+    manifest::apply_annotations(&mut manifest, &annotations);
+    Ok(manifest)
 }
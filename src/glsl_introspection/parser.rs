@@ -4,7 +4,12 @@ use naga::{valid::Validator, Module};
 
 /// Parse GLSL source code into Naga IR
 pub fn parse_glsl(source: &str) -> Result<Module, ParseError> {
-    let options = naga::front::glsl::Options::from(naga::ShaderStage::Vertex);
+    parse_glsl_stage(source, naga::ShaderStage::Vertex)
+}
+
+/// Parse GLSL source code for `stage` into Naga IR
+pub fn parse_glsl_stage(source: &str, stage: naga::ShaderStage) -> Result<Module, ParseError> {
+    let options = naga::front::glsl::Options::from(stage);
 
     let module = naga::front::glsl::Frontend::default()
         .parse(&options, source)
@@ -34,4 +39,7 @@ pub enum ParseError {
 
     #[error("Annotation parsing error: {0}")]
     AnnotationError(String),
+
+    #[error("Preprocessor error: {0}")]
+    PreprocessError(String),
 }
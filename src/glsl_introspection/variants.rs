@@ -0,0 +1,469 @@
+//! Shader permutations over feature defines
+//!
+//! An ubershader guards optional features with `#ifdef FEATURE` blocks, and
+//! every combination of features is a variant to compile. Many combinations
+//! usually come out identical (a feature that only matters when another is
+//! on, a define the stage never tests), so the variants are deduplicated by
+//! a hash of their preprocessed source and only the distinct ones compiled.
+
+use super::manifest::ResourceManifest;
+use super::parser::ParseError;
+use std::collections::HashMap;
+
+/// Most features a matrix can have; it compiles up to 2^n variants.
+pub const MAX_FEATURES: usize = 12;
+
+/// Macro definitions deeper than this in `#if` are taken as recursive.
+const MAX_EXPANSION_DEPTH: u32 = 32;
+
+/// Every variant of a shader over a set of features.
+#[derive(Debug, Clone)]
+pub struct VariantTable {
+    /// Feature defines; bit `i` of a variant mask is `features[i]`
+    pub features: Vec<String>,
+    /// One entry per mask, indexed by mask
+    pub variants: Vec<ShaderVariant>,
+    /// Preprocessed source of each distinct variant
+    pub sources: Vec<String>,
+    /// Resource manifest of each distinct variant
+    pub manifests: Vec<ResourceManifest>,
+}
+
+/// One combination of features.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderVariant {
+    pub mask: u32,
+    /// FNV-1a hash of the preprocessed source
+    pub hash: u64,
+    /// Index into `sources` and `manifests`
+    pub unique: usize,
+}
+
+impl VariantTable {
+    /// Mask for a set of feature names, or `None` if one is not a feature.
+    pub fn mask_of(&self, features: &[&str]) -> Option<u32> {
+        features.iter().try_fold(0u32, |mask, name| {
+            let bit = self.features.iter().position(|f| f == name)?;
+            Some(mask | 1 << bit)
+        })
+    }
+}
+
+/// Preprocess and compile `source` for `stage` under every combination of
+/// `features`, each defined as `1` when on and left undefined when off.
+pub fn compile_variants(
+    source: &str,
+    stage: naga::ShaderStage,
+    features: &[&str],
+) -> Result<VariantTable, ParseError> {
+    if features.len() > MAX_FEATURES {
+        return Err(ParseError::PreprocessError(format!(
+            "{} features make too many variants; the limit is {}",
+            features.len(),
+            MAX_FEATURES
+        )));
+    }
+    let mut table = VariantTable {
+        features: features.iter().map(|f| f.to_string()).collect(),
+        variants: Vec::new(),
+        sources: Vec::new(),
+        manifests: Vec::new(),
+    };
+    let mut by_hash = HashMap::new();
+    for mask in 0..1u32 << features.len() {
+        let defines: Vec<&str> = features
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| mask & 1 << bit != 0)
+            .map(|(_, name)| *name)
+            .collect();
+        let preprocessed = preprocess(source, &defines)?;
+        let hash = fnv1a(&preprocessed);
+        let unique = match by_hash.get(&hash) {
+            Some(&unique) => unique,
+            None => {
+                let manifest = super::introspect_stage(&preprocessed, stage).map_err(|e| {
+                    ParseError::PreprocessError(format!("variant [{}]: {}", defines.join(", "), e))
+                })?;
+                table.sources.push(preprocessed);
+                table.manifests.push(manifest);
+                by_hash.insert(hash, table.sources.len() - 1);
+                table.sources.len() - 1
+            }
+        };
+        table.variants.push(ShaderVariant { mask, hash, unique });
+    }
+    Ok(table)
+}
+
+/// Resolve the conditional directives of `source` with `defines` set to
+/// `1`, dropping inactive lines. `#define`s are kept for the compiler to
+/// expand, and each define the remaining code names is declared after the
+/// `#version` line.
+pub fn preprocess(source: &str, defines: &[&str]) -> Result<String, ParseError> {
+    let mut macros: HashMap<String, String> = defines
+        .iter()
+        .map(|name| (name.to_string(), "1".to_string()))
+        .collect();
+    // Per open conditional: (enclosing block active, a branch was taken,
+    // current branch active)
+    let mut stack: Vec<(bool, bool, bool)> = Vec::new();
+    let mut lines = Vec::new();
+    for (number, line) in source.lines().enumerate() {
+        let active = stack.last().is_none_or(|frame| frame.2);
+        let error = |message: String| {
+            ParseError::PreprocessError(format!("line {}: {}", number + 1, message))
+        };
+        let Some(directive) = line.trim_start().strip_prefix('#').map(str::trim_start) else {
+            if active {
+                lines.push(line);
+            }
+            continue;
+        };
+        let end = directive
+            .find(|c: char| !c.is_ascii_alphanumeric())
+            .unwrap_or(directive.len());
+        let (keyword, rest) = (&directive[..end], directive[end..].trim());
+        match keyword {
+            "if" | "ifdef" | "ifndef" => {
+                let taken = active
+                    && match keyword {
+                        "if" => evaluate(rest, &macros).map_err(error)? != 0,
+                        "ifdef" => macros.contains_key(first_word(rest)),
+                        _ => !macros.contains_key(first_word(rest)),
+                    };
+                stack.push((active, taken, taken));
+            }
+            "elif" => {
+                let Some(&(parent, taken, _)) = stack.last() else {
+                    return Err(error("#elif without #if".to_string()));
+                };
+                let now = parent && !taken && evaluate(rest, &macros).map_err(error)? != 0;
+                *stack.last_mut().unwrap() = (parent, taken || now, now);
+            }
+            "else" => {
+                let Some(&(parent, taken, _)) = stack.last() else {
+                    return Err(error("#else without #if".to_string()));
+                };
+                *stack.last_mut().unwrap() = (parent, true, parent && !taken);
+            }
+            "endif" => {
+                if stack.pop().is_none() {
+                    return Err(error("#endif without #if".to_string()));
+                }
+            }
+            _ if !active => {}
+            "define" => {
+                let name = first_word(rest);
+                // Function-like macros can't appear in `#if`; record them as
+                // defined for `#ifdef` only
+                let value = rest[name.len()..].trim();
+                macros.insert(name.to_string(), value.to_string());
+                lines.push(line);
+            }
+            "undef" => {
+                macros.remove(first_word(rest));
+                lines.push(line);
+            }
+            _ => lines.push(line),
+        }
+    }
+    if !stack.is_empty() {
+        return Err(ParseError::PreprocessError(
+            "unterminated #if at end of source".to_string(),
+        ));
+    }
+
+    let mut named: Vec<&str> = defines
+        .iter()
+        .copied()
+        .filter(|name| lines.iter().any(|line| has_word(line, name)))
+        .collect();
+    named.sort_unstable();
+    let declarations: String = named
+        .iter()
+        .map(|name| format!("#define {} 1\n", name))
+        .collect();
+    let version = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("#version"))
+        .map_or(0, |at| at + 1);
+    let mut out = String::with_capacity(source.len() + declarations.len());
+    for line in &lines[..version] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push_str(&declarations);
+    for line in &lines[version..] {
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn first_word(text: &str) -> &str {
+    let end = text
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+    &text[..end]
+}
+
+fn has_word(line: &str, word: &str) -> bool {
+    line.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .any(|w| w == word)
+}
+
+fn fnv1a(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// Evaluate the integer expression of an `#if` or `#elif`.
+fn evaluate(expression: &str, macros: &HashMap<String, String>) -> Result<i64, String> {
+    let tokens = expand(&tokenize(expression)?, macros, 0)?;
+    let mut parser = Parser { tokens, at: 0 };
+    let value = parser.binary(0)?;
+    match parser.tokens.get(parser.at) {
+        None => Ok(value),
+        Some(token) => Err(format!("unexpected '{}' in #if", token)),
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<String>, String> {
+    // Two-character operators first, so `<` doesn't take the start of `<=`
+    const OPERATORS: [&str; 22] = [
+        "&&", "||", "==", "!=", "<=", ">=", "<<", ">>", "(", ")", "!", "~", "+", "-", "*", "/",
+        "%", "<", ">", "&", "^", "|",
+    ];
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim_start();
+    while !rest.is_empty() {
+        if rest.starts_with("//") {
+            break;
+        }
+        let len = if rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') {
+            first_word(rest).len()
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            op.len()
+        } else {
+            return Err(format!("unexpected '{}' in #if", &rest[..1]));
+        };
+        tokens.push(rest[..len].to_string());
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Resolve `defined` and replace macros by their values.
+fn expand(
+    tokens: &[String],
+    macros: &HashMap<String, String>,
+    depth: u32,
+) -> Result<Vec<String>, String> {
+    if depth > MAX_EXPANSION_DEPTH {
+        return Err("recursive macro in #if".to_string());
+    }
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = &tokens[i];
+        i += 1;
+        if token == "defined" {
+            let parenthesized = tokens.get(i).is_some_and(|t| t == "(");
+            let name = tokens
+                .get(i + parenthesized as usize)
+                .ok_or("missing name after defined")?;
+            i += 1 + 2 * parenthesized as usize;
+            if parenthesized && tokens.get(i - 1).map(String::as_str) != Some(")") {
+                return Err("missing ')' after defined".to_string());
+            }
+            out.push(if macros.contains_key(name) { "1" } else { "0" }.to_string());
+        } else if token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            match macros.get(token) {
+                Some(value) => out.extend(expand(&tokenize(value)?, macros, depth + 1)?),
+                None => return Err(format!("undefined identifier '{}' in #if", token)),
+            }
+        } else {
+            out.push(token.clone());
+        }
+    }
+    Ok(out)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    at: usize,
+}
+
+impl Parser {
+    /// Binary operators from loosest to tightest, as in C.
+    const LEVELS: [&'static [&'static str]; 10] = [
+        &["||"],
+        &["&&"],
+        &["|"],
+        &["^"],
+        &["&"],
+        &["==", "!="],
+        &["<", ">", "<=", ">="],
+        &["<<", ">>"],
+        &["+", "-"],
+        &["*", "/", "%"],
+    ];
+
+    fn binary(&mut self, level: usize) -> Result<i64, String> {
+        if level == Self::LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        while let Some(op) = self
+            .tokens
+            .get(self.at)
+            .filter(|t| Self::LEVELS[level].contains(&t.as_str()))
+            .cloned()
+        {
+            self.at += 1;
+            let right = self.binary(level + 1)?;
+            left = match op.as_str() {
+                "||" => (left != 0 || right != 0) as i64,
+                "&&" => (left != 0 && right != 0) as i64,
+                "|" => left | right,
+                "^" => left ^ right,
+                "&" => left & right,
+                "==" => (left == right) as i64,
+                "!=" => (left != right) as i64,
+                "<" => (left < right) as i64,
+                ">" => (left > right) as i64,
+                "<=" => (left <= right) as i64,
+                ">=" => (left >= right) as i64,
+                "<<" => left.wrapping_shl(right as u32),
+                ">>" => left.wrapping_shr(right as u32),
+                "+" => left.wrapping_add(right),
+                "-" => left.wrapping_sub(right),
+                "*" => left.wrapping_mul(right),
+                _ if right == 0 => return Err("division by zero in #if".to_string()),
+                "/" => left.wrapping_div(right),
+                _ => left.wrapping_rem(right),
+            };
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<i64, String> {
+        let token = self
+            .tokens
+            .get(self.at)
+            .cloned()
+            .ok_or("unexpected end of #if")?;
+        self.at += 1;
+        match token.as_str() {
+            "!" => Ok((self.unary()? == 0) as i64),
+            "~" => Ok(!self.unary()?),
+            "-" => Ok(self.unary()?.wrapping_neg()),
+            "+" => self.unary(),
+            "(" => {
+                let value = self.binary(0)?;
+                if self.tokens.get(self.at).map(String::as_str) != Some(")") {
+                    return Err("missing ')' in #if".to_string());
+                }
+                self.at += 1;
+                Ok(value)
+            }
+            _ => parse_integer(&token).ok_or_else(|| format!("unexpected '{}' in #if", token)),
+        }
+    }
+}
+
+fn parse_integer(token: &str) -> Option<i64> {
+    let digits = token.trim_end_matches(['u', 'U']);
+    if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        i64::from_str_radix(hex, 16).ok()
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        i64::from_str_radix(&digits[1..], 8).ok()
+    } else {
+        digits.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UBERSHADER: &str = "#version 300 es
+precision highp float;
+#define FOG_MODE 2
+#if defined(USE_FOG) && FOG_MODE > 1
+uniform float fogDensity;
+#endif
+#ifdef USE_TINT
+uniform vec4 tint;
+#endif
+out vec4 color;
+void main() {
+#ifdef USE_TINT
+    color = tint;
+#else
+    color = vec4(1.0);
+#endif
+#if defined USE_FOG
+    color.rgb *= fogDensity;
+#endif
+#ifdef UNUSED
+#endif
+}
+";
+
+    #[test]
+    fn test_conditionals_resolve_with_defines() {
+        let tinted = preprocess(UBERSHADER, &["USE_TINT"]).unwrap();
+        assert!(tinted.contains("uniform vec4 tint;\n"));
+        assert!(tinted.contains("color = tint;"));
+        assert!(!tinted.contains("vec4(1.0)"));
+        assert!(!tinted.contains("#ifdef"));
+        let fogged = preprocess(UBERSHADER, &["USE_FOG"]).unwrap();
+        assert!(fogged.contains("uniform float fogDensity;\n"));
+        assert!(fogged.contains("color.rgb *= fogDensity;"));
+
+        // Undefined identifiers are an error in GLSL ES, not 0 as in C
+        assert!(preprocess("#if MISSING\n#endif\n", &[]).is_err());
+
+        assert_eq!(
+            evaluate(
+                "(1 + 2) * 3 == 9 && !defined(X) || 0x10 >> 4",
+                &HashMap::new()
+            ),
+            Ok(1)
+        );
+        assert!(preprocess("#if 1\n", &[]).is_err());
+    }
+
+    #[test]
+    fn test_variants_are_deduplicated_by_preprocessed_source() {
+        let table = compile_variants(
+            UBERSHADER,
+            naga::ShaderStage::Fragment,
+            &["USE_FOG", "USE_TINT", "UNUSED"],
+        )
+        .unwrap();
+
+        assert_eq!(table.variants.len(), 8);
+        // UNUSED changes nothing, leaving fog x tint
+        assert_eq!(table.sources.len(), 4);
+        let fog_tint = table.mask_of(&["USE_TINT", "USE_FOG"]).unwrap();
+        assert_eq!(fog_tint, 0b011);
+        assert_eq!(
+            table.variants[fog_tint as usize].unique,
+            table.variants[0b111].unique
+        );
+        let manifest = &table.manifests[table.variants[fog_tint as usize].unique];
+        assert!(manifest.uniforms.iter().any(|u| u.name == "fogDensity"));
+        assert!(manifest.uniforms.iter().any(|u| u.name == "tint"));
+        assert_eq!(table.mask_of(&["MISSING"]), None);
+    }
+}
@@ -0,0 +1,149 @@
+//! Bounds-checked access to caller-provided memory
+//!
+//! Exported entry points receive raw `(ptr, len)` pairs from the host. Building a
+//! slice from such a pair without validation is undefined behaviour whenever the
+//! range runs past the end of linear memory. The helpers here validate the range
+//! against the current memory size first and fail with `ERR_INVALID_ARGS` (setting
+//! the last error message) instead.
+//!
+//! A zero-length range is always valid and yields an empty slice, regardless of
+//! the pointer value, so callers may pass `(0, 0)` for "no data".
+
+/// Size of linear memory in bytes; every guest range must end at or before it.
+#[cfg(target_arch = "wasm32")]
+fn memory_limit() -> usize {
+    core::arch::wasm32::memory_size(0) * 65536
+}
+
+/// Native builds have no linear memory to check against; only overflow and null
+/// pointers are rejected.
+#[cfg(not(target_arch = "wasm32"))]
+fn memory_limit() -> usize {
+    usize::MAX
+}
+
+fn fail(msg: String) -> u32 {
+    crate::error::report(crate::error::Webgl2Error::new(
+        crate::error::ErrorCategory::InvalidArgs,
+        msg,
+    ))
+}
+
+/// Validate that `[addr, addr + bytes)` lies inside linear memory and that `addr`
+/// is suitably aligned for an element type of alignment `align`.
+fn check(addr: usize, bytes: usize, align: usize) -> Result<(), u32> {
+    if addr == 0 {
+        return Err(fail(format!("null pointer for {} byte range", bytes)));
+    }
+    let end = match addr.checked_add(bytes) {
+        Some(end) => end,
+        None => {
+            return Err(fail(format!(
+                "pointer range {:#x}+{} overflows",
+                addr, bytes
+            )))
+        }
+    };
+    if end > memory_limit() {
+        return Err(fail(format!(
+            "pointer range {:#x}..{:#x} is outside linear memory ({} bytes)",
+            addr,
+            end,
+            memory_limit()
+        )));
+    }
+    if !addr.is_multiple_of(align) {
+        return Err(fail(format!(
+            "pointer {:#x} is not aligned to {} bytes",
+            addr, align
+        )));
+    }
+    Ok(())
+}
+
+/// Borrow `len` bytes at `ptr` for reading.
+pub fn read<'a>(ptr: u32, len: u32) -> Result<&'a [u8], u32> {
+    read_slice(ptr as usize as *const u8, len as usize)
+}
+
+/// Borrow `len` bytes at `ptr` for writing.
+pub fn write<'a>(ptr: u32, len: u32) -> Result<&'a mut [u8], u32> {
+    write_slice(ptr as usize as *mut u8, len as usize)
+}
+
+/// Borrow `len` bytes at `ptr` as UTF-8 text.
+pub fn read_str<'a>(ptr: u32, len: u32) -> Result<&'a str, u32> {
+    read_utf8(ptr as usize as *const u8, len as usize)
+}
+
+/// Borrow `len` bytes at `ptr` as UTF-8 text (raw pointer form).
+pub fn read_utf8<'a>(ptr: *const u8, len: usize) -> Result<&'a str, u32> {
+    let bytes = read_slice(ptr, len)?;
+    std::str::from_utf8(bytes).map_err(|_| fail("string argument is not valid UTF-8".into()))
+}
+
+/// Borrow `count` elements of `T` at `ptr` for reading.
+// Not `unsafe`: the range is checked against linear memory before the read.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn read_slice<'a, T: Copy>(ptr: *const T, count: usize) -> Result<&'a [T], u32> {
+    if count == 0 {
+        return Ok(&[]);
+    }
+    let bytes = count
+        .checked_mul(std::mem::size_of::<T>())
+        .ok_or_else(|| fail(format!("element count {} overflows", count)))?;
+    check(ptr as usize, bytes, std::mem::align_of::<T>())?;
+    // SAFETY: the range was validated to lie inside linear memory and be aligned.
+    Ok(unsafe { std::slice::from_raw_parts(ptr, count) })
+}
+
+/// Borrow `count` elements of `T` at `ptr` for writing.
+// Not `unsafe`: the range is checked against linear memory before the write.
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub fn write_slice<'a, T: Copy>(ptr: *mut T, count: usize) -> Result<&'a mut [T], u32> {
+    if count == 0 {
+        return Ok(&mut []);
+    }
+    let bytes = count
+        .checked_mul(std::mem::size_of::<T>())
+        .ok_or_else(|| fail(format!("element count {} overflows", count)))?;
+    check(ptr as usize, bytes, std::mem::align_of::<T>())?;
+    // SAFETY: the range was validated to lie inside linear memory and be aligned.
+    Ok(unsafe { std::slice::from_raw_parts_mut(ptr, count) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl2_context::types::ERR_INVALID_ARGS;
+
+    #[test]
+    fn empty_range_is_always_valid() {
+        assert_eq!(read(0, 0).map(|s| s.len()), Ok(0));
+    }
+
+    #[test]
+    fn null_pointer_is_rejected() {
+        assert_eq!(read(0, 4), Err(ERR_INVALID_ARGS));
+    }
+
+    #[test]
+    fn overflowing_range_is_rejected() {
+        let ptr = (usize::MAX - 1) as *const u8;
+        assert_eq!(read_slice(ptr, 4).map(|s| s.len()), Err(ERR_INVALID_ARGS));
+    }
+
+    #[test]
+    fn misaligned_pointer_is_rejected() {
+        let data = [0u32; 2];
+        let ptr = (data.as_ptr() as usize + 1) as *const u32;
+        assert_eq!(read_slice(ptr, 1).map(|s| s.len()), Err(ERR_INVALID_ARGS));
+    }
+
+    #[test]
+    fn valid_range_round_trips() {
+        let mut data = [1u32, 2, 3];
+        write_slice(data.as_mut_ptr(), 3).unwrap()[1] = 7;
+        assert_eq!(read_slice(data.as_ptr(), 3).unwrap(), &[1, 7, 3]);
+    }
+}
@@ -0,0 +1,341 @@
+//! C header and TypeScript declarations of the module's exports.
+//!
+//! The `#[no_mangle] extern "C"` exports are read from the crate's own
+//! source, so the generated files can't drift from the functions they
+//! describe. Pointers are addresses in the module's linear memory (wasm32),
+//! and 64-bit integers cross the JS boundary as `bigint`.
+
+use super::CodegenError;
+
+/// Source files holding the exports, in output order.
+const SOURCES: [&str; 2] = [include_str!("../lib.rs"), include_str!("../error.rs")];
+
+/// One exported function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FfiExport {
+    pub name: String,
+    /// Doc comment lines, without the `///`
+    pub docs: Vec<String>,
+    /// Cargo feature the export is compiled under, if any
+    pub feature: Option<String>,
+    /// Parameter names and Rust types
+    pub params: Vec<(String, String)>,
+    /// Rust return type, or `None` for `()`
+    pub result: Option<String>,
+}
+
+/// The `wasm_*` exports of the crate, in declaration order.
+pub fn ffi_exports() -> Result<Vec<FfiExport>, CodegenError> {
+    let mut exports = Vec::new();
+    for source in SOURCES {
+        exports.extend(parse_exports(source)?);
+    }
+    Ok(exports)
+}
+
+/// Parse the `#[no_mangle] pub extern "C" fn wasm_*` items of Rust source.
+pub fn parse_exports(source: &str) -> Result<Vec<FfiExport>, CodegenError> {
+    let mut exports = Vec::new();
+    let mut docs = Vec::new();
+    let mut feature = None;
+    let mut no_mangle = false;
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if let Some(doc) = line.strip_prefix("///") {
+            docs.push(doc.strip_prefix(' ').unwrap_or(doc).to_string());
+            continue;
+        }
+        if line == "#[no_mangle]" {
+            no_mangle = true;
+            continue;
+        }
+        if let Some(cfg) = line.strip_prefix("#[cfg(feature = \"") {
+            feature = cfg.split('"').next().map(str::to_string);
+            continue;
+        }
+        if line.starts_with("#[") {
+            continue;
+        }
+        let signature = line
+            .strip_prefix("pub extern \"C\" fn ")
+            .or_else(|| line.strip_prefix("pub unsafe extern \"C\" fn "));
+        if let (true, Some(signature)) = (no_mangle, signature) {
+            let mut signature = signature.to_string();
+            while !signature.contains('{') {
+                let Some(next) = lines.next() else {
+                    break;
+                };
+                signature.push(' ');
+                signature.push_str(next.trim());
+            }
+            let export = parse_signature(&signature, std::mem::take(&mut docs), feature.take())?;
+            if export.name.starts_with("wasm_") {
+                exports.push(export);
+            }
+        }
+        docs.clear();
+        feature = None;
+        no_mangle = false;
+    }
+    Ok(exports)
+}
+
+/// Parse `name(params) -> result {` into an export.
+fn parse_signature(
+    signature: &str,
+    docs: Vec<String>,
+    feature: Option<String>,
+) -> Result<FfiExport, CodegenError> {
+    let malformed = || CodegenError::TypeMappingError(format!("malformed export: {}", signature));
+    let open = signature.find('(').ok_or_else(malformed)?;
+    let close = signature.rfind(')').ok_or_else(malformed)?;
+    let name = signature[..open].trim().to_string();
+    let params = signature[open + 1..close]
+        .split(',')
+        .map(str::trim)
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, ty) = param.split_once(':').ok_or_else(malformed)?;
+            Ok((name.trim().to_string(), ty.trim().to_string()))
+        })
+        .collect::<Result<Vec<_>, CodegenError>>()?;
+    let result = signature[close + 1..]
+        .split('{')
+        .next()
+        .and_then(|rest| rest.trim().strip_prefix("->"))
+        .map(|ty| ty.trim().to_string());
+    Ok(FfiExport {
+        name,
+        docs,
+        feature,
+        params,
+        result,
+    })
+}
+
+/// C and TypeScript spellings of a Rust FFI type.
+fn map_type(ty: &str) -> Result<(&'static str, &'static str), CodegenError> {
+    Ok(match ty {
+        "u32" | "usize" => ("uint32_t", "number"),
+        "u16" => ("uint16_t", "number"),
+        "i32" | "isize" => ("int32_t", "number"),
+        "u64" => ("uint64_t", "bigint"),
+        "i64" => ("int64_t", "bigint"),
+        "f32" => ("float", "number"),
+        "f64" => ("double", "number"),
+        // Booleans are i32 0 or 1 on the WASM boundary
+        "bool" => ("bool", "number"),
+        // 0 none, 1 low power, 2 high performance
+        "wgpu_types::PowerPreference" => ("int32_t", "number"),
+        _ if ty.starts_with("*const ") || ty.starts_with("*mut ") => ("webgl2_ptr", "number"),
+        _ => {
+            return Err(CodegenError::TypeMappingError(format!(
+                "no FFI mapping for {}",
+                ty
+            )))
+        }
+    })
+}
+
+/// FNV-1a hash of the C prototypes, identifying the ABI.
+fn abi_hash(exports: &[FfiExport]) -> Result<u64, CodegenError> {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for export in exports {
+        let line = c_prototype(export)?;
+        for byte in line.bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    Ok(hash)
+}
+
+fn c_prototype(export: &FfiExport) -> Result<String, CodegenError> {
+    let result = match &export.result {
+        Some(ty) => map_type(ty)?.0,
+        None => "void",
+    };
+    let params = export
+        .params
+        .iter()
+        .map(|(name, ty)| Ok(format!("{} {}", map_type(ty)?.0, name)))
+        .collect::<Result<Vec<_>, CodegenError>>()?;
+    let params = if params.is_empty() {
+        "void".to_string()
+    } else {
+        params.join(", ")
+    };
+    Ok(format!("{} {}({});", result, export.name, params))
+}
+
+/// Doc comment block for an export, indented by `indent`.
+fn doc_block(export: &FfiExport, indent: &str) -> String {
+    let mut lines = export.docs.clone();
+    if let Some(feature) = &export.feature {
+        lines.push(format!("Only exported with the `{}` feature.", feature));
+    }
+    if lines.is_empty() {
+        return String::new();
+    }
+    let body: String = lines
+        .iter()
+        .map(|line| {
+            format!(
+                "{} *{}{}\n",
+                indent,
+                if line.is_empty() { "" } else { " " },
+                line
+            )
+        })
+        .collect();
+    format!("{}/**\n{}{} */\n", indent, body, indent)
+}
+
+/// Generate `webgl2.h`, declaring every export for C and C++ embedders.
+pub fn generate_c_header() -> Result<String, CodegenError> {
+    let exports = ffi_exports()?;
+    let mut out = format!(
+        r#"/*
+ * C declarations of the webgl2.wasm exports. Generated by `webgl2 headers`;
+ * do not edit.
+ *
+ * The functions are exports of the WASM module, to be called through the
+ * embedder's runtime (wasmtime, wasmer, wasm2c, ...), not linked natively.
+ * Pointers are addresses in the module's exported linear memory. Functions
+ * returning an errno use the WEBGL2_ERR_* codes; the message of the last
+ * failure is at wasm_get_last_error_msg_ptr/wasm_get_last_error_msg_len.
+ */
+#ifndef WEBGL2_H
+#define WEBGL2_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#define WEBGL2_FFI_VERSION "{}"
+/* Hash of every declaration below; it changes whenever one does */
+#define WEBGL2_FFI_ABI_HASH 0x{:016x}ULL
+
+#define WEBGL2_ERR_OK 0
+#define WEBGL2_ERR_INVALID_HANDLE 1
+#define WEBGL2_ERR_OOM 2
+#define WEBGL2_ERR_INVALID_ARGS 3
+#define WEBGL2_ERR_NOT_IMPLEMENTED 4
+#define WEBGL2_ERR_GL 5
+#define WEBGL2_ERR_INTERNAL 6
+
+/* Address in the module's linear memory (wasm32) */
+typedef uint32_t webgl2_ptr;
+
+#ifdef __cplusplus
+extern "C" {{
+#endif
+"#,
+        env!("CARGO_PKG_VERSION"),
+        abi_hash(&exports)?
+    );
+    for export in &exports {
+        out.push('\n');
+        out.push_str(&doc_block(export, ""));
+        out.push_str(&c_prototype(export)?);
+        out.push('\n');
+    }
+    out.push_str(
+        r#"
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* WEBGL2_H */
+"#,
+    );
+    Ok(out)
+}
+
+/// Generate `webgl2.wasm.d.ts`, typing the module's exports for JS hosts.
+pub fn generate_ts_declarations() -> Result<String, CodegenError> {
+    let exports = ffi_exports()?;
+    let mut out = format!(
+        r#"// TypeScript declarations of the webgl2.wasm exports. Generated by
+// `webgl2 headers`; do not edit. Pointers are addresses in `memory`.
+
+export type Webgl2FfiVersion = "{}";
+/** Hash of every declaration, as in webgl2.h */
+export type Webgl2FfiAbiHash = "{:016x}";
+
+export interface Webgl2Exports {{
+    memory: WebAssembly.Memory;
+"#,
+        env!("CARGO_PKG_VERSION"),
+        abi_hash(&exports)?
+    );
+    for export in &exports {
+        let result = match &export.result {
+            Some(ty) => map_type(ty)?.1,
+            None => "void",
+        };
+        let params = export
+            .params
+            .iter()
+            .map(|(name, ty)| Ok(format!("{}: {}", name, map_type(ty)?.1)))
+            .collect::<Result<Vec<_>, CodegenError>>()?;
+        out.push_str(&doc_block(export, "    "));
+        out.push_str(&format!(
+            "    {}({}): {};\n",
+            export.name,
+            params.join(", "),
+            result
+        ));
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_export_is_declared() {
+        let declared = SOURCES
+            .iter()
+            .map(|source| source.matches("extern \"C\" fn wasm_").count())
+            .sum::<usize>();
+        // Host imports are declared `fn wasm_*` without `extern "C"`
+        assert_eq!(ffi_exports().unwrap().len(), declared);
+        assert!(generate_c_header().is_ok());
+        assert!(generate_ts_declarations().is_ok());
+    }
+
+    #[test]
+    fn test_signatures_map_to_c_and_ts() {
+        let exports = parse_exports(
+            "/// Read pixels.
+            #[no_mangle]
+            #[cfg(feature = \"extra\")]
+            pub unsafe extern \"C\" fn wasm_read(
+                ctx: u32,
+                dest_ptr: *mut u8,
+                scale: f64,
+            ) -> u64 {
+                0
+            }
+
+            #[no_mangle]
+            pub extern \"C\" fn wasm_tick() {}
+
+            #[no_mangle]
+            pub extern \"C\" fn gl_helper(x: f32) -> f32 { x }",
+        )
+        .unwrap();
+
+        assert_eq!(exports.len(), 2);
+        assert_eq!(exports[0].docs, vec!["Read pixels."]);
+        assert_eq!(exports[0].feature.as_deref(), Some("extra"));
+        assert_eq!(
+            c_prototype(&exports[0]).unwrap(),
+            "uint64_t wasm_read(uint32_t ctx, webgl2_ptr dest_ptr, double scale);"
+        );
+        assert_eq!(c_prototype(&exports[1]).unwrap(), "void wasm_tick(void);");
+    }
+}
@@ -1,27 +1,544 @@
 //! Code generation logic
 
-use crate::glsl_introspection::ResourceManifest;
+use crate::glsl_introspection::{ResourceManifest, UniformBlockInfo, UniformInfo, VariantTable};
 
 /// Generate TypeScript harness code
-pub fn generate_harness(_manifest: &ResourceManifest) -> Result<String, CodegenError> {
-    // Phase 0: Return minimal template
-    Ok(r#"
+pub fn generate_harness(manifest: &ResourceManifest) -> Result<String, CodegenError> {
+    let attributes: Vec<(&str, u32)> = manifest
+        .attributes
+        .iter()
+        .map(|a| (a.name.as_str(), a.location))
+        .collect();
+    let uniforms: Vec<(&str, u32)> = manifest
+        .uniforms
+        .iter()
+        .map(|u| (u.name.as_str(), u.location))
+        .chain(
+            manifest
+                .textures
+                .iter()
+                .map(|t| (t.name.as_str(), t.binding)),
+        )
+        .collect();
+    let integer_uniforms: Vec<String> = manifest
+        .uniforms
+        .iter()
+        .filter(|u| is_integer_type(&u.glsl_type))
+        .map(|u| format!("\"{}\"", u.name))
+        .chain(manifest.textures.iter().map(|t| format!("\"{}\"", t.name)))
+        .collect();
+    let defaults: Vec<(&str, String)> = manifest
+        .uniforms
+        .iter()
+        .filter_map(|u| Some((u.name.as_str(), number_list(u.default.as_ref()?))))
+        .collect();
+    let setters: Vec<(&str, String)> = manifest
+        .uniforms
+        .iter()
+        .filter(|u| u.default.is_some())
+        .filter_map(|u| {
+            Some((
+                u.name.as_str(),
+                format!("\"{}\"", uniform_setter(&u.glsl_type)?),
+            ))
+        })
+        .collect();
+    let controls: String = manifest.uniforms.iter().filter_map(control).collect();
+
+    Ok(format!(
+        r#"
 // Generated TypeScript harness for WebGL2 shader
-export class ShaderProgram {
-    constructor() {
-        // TODO: Initialize shader
+type Handle = number | {{ _handle: number }};
+const handleOf = (h: Handle) => (typeof h === "number" ? h : h._handle);
+
+export interface AttributeBinding {{
+    buffer: Handle | null;
+    size: number;
+    type: number;
+    normalized?: boolean;
+    stride?: number;
+    offset?: number;
+}}
+
+export interface DrawPacket {{
+    /** Program to use; omit to keep the current one */
+    program?: Handle;
+    /** Vertex attributes by name; a null buffer disables the attribute */
+    attributes?: Record<string, AttributeBinding>;
+    /** Uniform values by name; matrices are column-major */
+    uniforms?: Record<string, number | ArrayLike<number>>;
+    mode: number;
+    /** First vertex, or byte offset into the element buffer when indexed */
+    first?: number;
+    count: number;
+    /** Index type for an indexed draw; omit for drawArrays */
+    indexType?: number;
+    instanceCount?: number;
+}}
+
+/** A slider for a scalar uniform, for dat.gui or Tweakpane */
+export interface UniformControl {{
+    name: string;
+    value: number;
+    min?: number;
+    max?: number;
+    step?: number;
+}}
+
+export class ShaderProgram {{
+    /** Attribute locations, as assigned at link time */
+    static readonly attributeLocations: Record<string, number> = {};
+
+    /** Uniform locations, as assigned at link time */
+    static readonly uniformLocations: Record<string, number> = {};
+
+    /** Uniforms stored as integers rather than floats */
+    static readonly integerUniforms = new Set<string>([{}]);
+
+    /** Initial uniform values from `@default` annotations */
+    static readonly uniformDefaults: Record<string, number[]> = {};
+
+    /** `gl` method that uploads each uniform of `uniformDefaults` */
+    static readonly uniformSetters: Record<string, string> = {};
+
+    /**
+     * Controls for the scalar uniforms with a `@range` or `@default`. With
+     * `const params = ShaderProgram.controlParams()`, add each one as
+     * `gui.add(params, c.name, c.min, c.max, c.step)` (dat.gui) or
+     * `pane.addBinding(params, c.name, {{ min: c.min, max: c.max, step: c.step }})`
+     * (Tweakpane), and upload `params` on change.
+     */
+    static readonly uniformControls: UniformControl[] = [{}];
+
+    /** Control values by uniform name, starting from their defaults */
+    static controlParams(): Record<string, number> {{
+        return Object.fromEntries(ShaderProgram.uniformControls.map((c) => [c.name, c.value]));
+    }}
+
+    /** Make `program` current and upload the `@default` values into it */
+    static applyDefaults(gl: WebGL2RenderingContext, program: WebGLProgram): void {{
+        gl.useProgram(program);
+        for (const [name, values] of Object.entries(ShaderProgram.uniformDefaults)) {{
+            const location = gl.getUniformLocation(program, name);
+            const setter = ShaderProgram.uniformSetters[name];
+            if (location === null || setter === undefined) continue;
+            const upload = (gl as any)[setter].bind(gl);
+            if (setter.startsWith("uniformMatrix")) upload(location, false, values);
+            else upload(location, values);
+        }}
+    }}
+
+    /**
+     * Pack a draw for `gl.drawPacket`, which binds, uploads and draws in a
+     * single call into the emulator.
+     */
+    static packDraw(draw: DrawPacket): Uint32Array {{
+        const attributes = Object.entries(draw.attributes ?? {{}});
+        const uniforms = Object.entries(draw.uniforms ?? {{}});
+        const words: number[] = [
+            draw.program === undefined ? 0 : handleOf(draw.program),
+            attributes.length,
+            uniforms.length,
+            draw.mode,
+            draw.first ?? 0,
+            draw.count,
+            draw.indexType ?? 0,
+            draw.instanceCount ?? 1,
+        ];
+        for (const [name, binding] of attributes) {{
+            const index = ShaderProgram.attributeLocations[name];
+            if (index === undefined) throw new Error(`unknown attribute ${{name}}`);
+            words.push(
+                index,
+                binding.buffer === null ? 0 : handleOf(binding.buffer),
+                binding.size,
+                binding.type,
+                binding.normalized ? 1 : 0,
+                binding.stride ?? 0,
+                binding.offset ?? 0,
+            );
+        }}
+        for (const [name, value] of uniforms) {{
+            const location = ShaderProgram.uniformLocations[name];
+            if (location === undefined) throw new Error(`unknown uniform ${{name}}`);
+            const values = typeof value === "number" ? [value] : Array.from(value);
+            const typed = ShaderProgram.integerUniforms.has(name)
+                ? Int32Array.from(values)
+                : Float32Array.from(values);
+            words.push(location, values.length, ...new Uint32Array(typed.buffer));
+        }}
+        return Uint32Array.from(words);
+    }}
+}}
+"#,
+        object_literal(&attributes),
+        object_literal(&uniforms),
+        integer_uniforms.join(", "),
+        record_literal(&defaults),
+        record_literal(&setters),
+        if controls.is_empty() {
+            String::new()
+        } else {
+            format!("\n{}    ", controls)
+        }
+    ))
+}
+
+/// Whether a GLSL uniform type (int, uvec3, bool[2], ...) is stored as
+/// 32-bit integers.
+fn is_integer_type(glsl_type: &str) -> bool {
+    let base = glsl_type.split('[').next().unwrap_or(glsl_type);
+    base.starts_with(['i', 'u', 'b']) || base.contains("sampler")
+}
+
+/// `WebGL2RenderingContext` method uploading a uniform of a GLSL type, if
+/// it has one (samplers and structs do not).
+fn uniform_setter(glsl_type: &str) -> Option<String> {
+    let base = glsl_type.split('[').next().unwrap_or(glsl_type);
+    let vector = |prefix: &str, suffix: &str| {
+        let size = base.strip_prefix(prefix)?;
+        matches!(size, "2" | "3" | "4").then(|| format!("uniform{}{}v", size, suffix))
+    };
+    match base {
+        "float" => Some("uniform1fv".to_string()),
+        "int" | "bool" => Some("uniform1iv".to_string()),
+        "uint" => Some("uniform1uiv".to_string()),
+        _ if base.starts_with("mat") => {
+            let shape = &base[3..];
+            let dims: Vec<&str> = shape.split('x').collect();
+            let valid = dims.len() <= 2 && dims.iter().all(|dim| matches!(*dim, "2" | "3" | "4"));
+            valid.then(|| format!("uniformMatrix{}fv", shape))
+        }
+        _ => vector("vec", "f")
+            .or_else(|| vector("ivec", "i"))
+            .or_else(|| vector("bvec", "i"))
+            .or_else(|| vector("uvec", "ui")),
+    }
+}
+
+/// Control descriptor line for a scalar uniform with a range or default.
+fn control(uniform: &UniformInfo) -> Option<String> {
+    let integer = match uniform.glsl_type.as_str() {
+        "float" => false,
+        "int" | "uint" => true,
+        _ => return None,
+    };
+    if uniform.default.is_none() && uniform.range.is_none() {
+        return None;
+    }
+    let value = uniform
+        .default
+        .as_ref()
+        .and_then(|values| values.first().copied())
+        .or(uniform.range.map(|(min, _)| min))
+        .unwrap_or(0.0);
+    let mut fields = format!("name: \"{}\", value: {}", uniform.name, value);
+    if let Some((min, max)) = uniform.range {
+        let step = if integer { 1.0 } else { (max - min) / 100.0 };
+        fields += &format!(", min: {}, max: {}, step: {}", min, max, step);
+    } else if integer {
+        fields += ", step: 1";
+    }
+    Some(format!("        {{ {} }},\n", fields))
+}
+
+/// TypeScript array literal of numbers.
+fn number_list(values: &[f64]) -> String {
+    let items: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    format!("[{}]", items.join(", "))
+}
+
+/// Generate a material system for several programs, given by name.
+///
+/// Uniform blocks go by name across programs, so a block every program
+/// declares alike (a camera, the lights) gets one shared buffer binding and
+/// one ArrayBuffer that `beginFrame` uploads at most once per frame, however
+/// many materials read it. A block declared with different layouts by two
+/// programs is an error.
+pub fn generate_material_harness(
+    programs: &[(&str, &ResourceManifest)],
+) -> Result<String, CodegenError> {
+    let mut shared: Vec<&UniformBlockInfo> = Vec::new();
+    let mut program_blocks = Vec::new();
+    for (program, manifest) in programs {
+        for block in &manifest.blocks {
+            match shared.iter().find(|b| b.name == block.name) {
+                Some(existing) if *existing != block => {
+                    return Err(CodegenError::BlockMismatch(format!(
+                        "uniform block {} of program {} differs from an earlier declaration",
+                        block.name, program
+                    )));
+                }
+                Some(_) => {}
+                None => shared.push(block),
+            }
+        }
+        let names: Vec<String> = manifest
+            .blocks
+            .iter()
+            .map(|b| format!("\"{}\"", b.name))
+            .collect();
+        program_blocks.push((*program, format!("[{}]", names.join(", "))));
+    }
+
+    let blocks: Vec<(&str, String)> = shared
+        .iter()
+        .enumerate()
+        .map(|(binding, block)| {
+            let offsets: Vec<(&str, u32)> = block
+                .members
+                .iter()
+                .map(|m| (m.name.as_str(), m.offset))
+                .collect();
+            let integers: Vec<String> = block
+                .members
+                .iter()
+                .filter(|m| is_integer_type(&m.glsl_type))
+                .map(|m| format!("\"{}\"", m.name))
+                .collect();
+            // Nested one level deeper than the record it goes in
+            let offsets = object_literal(&offsets).replace("\n    ", "\n        ");
+            (
+                block.name.as_str(),
+                format!(
+                    "new SharedUniformBlock({}, {}, {}, new Set<string>([{}]))",
+                    binding,
+                    block.size,
+                    offsets,
+                    integers.join(", ")
+                ),
+            )
+        })
+        .collect();
+
+    Ok(format!(
+        r#"
+// Generated TypeScript material system for WebGL2 shaders
+
+/** A uniform block shared by every program that declares it */
+export class SharedUniformBlock {{
+    readonly data: ArrayBuffer;
+    private readonly floats: Float32Array;
+    private readonly ints: Int32Array;
+    private buffer: WebGLBuffer | null = null;
+    private dirty = true;
+
+    constructor(
+        /** Buffer binding point the block is read from */
+        readonly binding: number,
+        size: number,
+        /** Byte offset of each member in the block */
+        readonly offsets: Record<string, number>,
+        /** Members stored as integers rather than floats */
+        readonly integerMembers: Set<string>,
+    ) {{
+        this.data = new ArrayBuffer(size);
+        this.floats = new Float32Array(this.data);
+        this.ints = new Int32Array(this.data);
+    }}
+
+    /** Write a member; matrices are column-major with std140 column padding */
+    set(member: string, value: number | ArrayLike<number>): void {{
+        const offset = this.offsets[member];
+        if (offset === undefined) throw new Error(`unknown block member ${{member}}`);
+        const values = typeof value === "number" ? [value] : Array.from(value);
+        const view = this.integerMembers.has(member) ? this.ints : this.floats;
+        view.set(values, offset / 4);
+        this.dirty = true;
+    }}
+
+    /** Upload the data if it changed, and bind it for drawing */
+    upload(gl: WebGL2RenderingContext): void {{
+        if (this.buffer === null) this.buffer = gl.createBuffer();
+        gl.bindBuffer(gl.UNIFORM_BUFFER, this.buffer);
+        if (this.dirty) {{
+            gl.bufferData(gl.UNIFORM_BUFFER, this.data, gl.DYNAMIC_DRAW);
+            this.dirty = false;
+        }}
+        gl.bindBufferBase(gl.UNIFORM_BUFFER, this.binding, this.buffer);
+    }}
+}}
+
+/** One instance of every uniform block, by block name */
+export const sharedBlocks: Record<string, SharedUniformBlock> = {};
+
+/** Uniform blocks each program declares, by program name */
+export const programBlocks: Record<string, string[]> = {};
+
+/** A linked program whose blocks read from `sharedBlocks` */
+export class Material {{
+    constructor(readonly name: string, readonly program: WebGLProgram) {{}}
+
+    /** Point the program's blocks at their shared bindings; call once after linking */
+    bind(gl: WebGL2RenderingContext): void {{
+        for (const block of programBlocks[this.name] ?? []) {{
+            const index = gl.getUniformBlockIndex(this.program, block);
+            if (index === gl.INVALID_INDEX) continue;
+            gl.uniformBlockBinding(this.program, index, sharedBlocks[block].binding);
+        }}
+    }}
+}}
+
+/** Upload the shared blocks that changed; call once per frame before drawing */
+export function beginFrame(gl: WebGL2RenderingContext): void {{
+    for (const block of Object.values(sharedBlocks)) block.upload(gl);
+}}
+"#,
+        record_literal(&blocks),
+        record_literal(&program_blocks)
+    ))
+}
+
+/// Generate the variant table of an ubershader, from
+/// [`compile_variants`](crate::glsl_introspection::compile_variants).
+///
+/// Each distinct variant's preprocessed source is embedded once, and
+/// `VariantCache` compiles it the first time a feature set needing it is
+/// drawn with.
+pub fn generate_variant_harness(table: &VariantTable) -> Result<String, CodegenError> {
+    let json = |text: &str| {
+        serde_json::to_string(text).map_err(|e| CodegenError::TemplateError(e.to_string()))
+    };
+    let features = table
+        .features
+        .iter()
+        .map(|f| json(f))
+        .collect::<Result<Vec<_>, _>>()?;
+    let index: Vec<String> = table
+        .variants
+        .iter()
+        .map(|v| v.unique.to_string())
+        .collect();
+    let sources = table
+        .sources
+        .iter()
+        .map(|s| Ok(format!("    {},\n", json(s)?)))
+        .collect::<Result<String, CodegenError>>()?;
+
+    Ok(format!(
+        r#"
+// Generated TypeScript variant table for a WebGL2 ubershader
+
+/** Feature defines; bit `i` of a variant mask is `variantFeatures[i]` */
+export const variantFeatures = [{}] as const;
+export type VariantFeature = (typeof variantFeatures)[number];
+
+/** Distinct variant of each mask */
+export const variantIndex: readonly number[] = [{}];
+
+/** Preprocessed source of each distinct variant, with its defines declared */
+export const variantSources: readonly string[] = [
+{}];
+
+/** Mask of a feature set */
+export function variantMask(features: Iterable<VariantFeature>): number {{
+    let mask = 0;
+    for (const feature of features) mask |= 1 << variantFeatures.indexOf(feature);
+    return mask;
+}}
+
+/** Compiles each distinct variant once, the first time it is asked for */
+export class VariantCache<T> {{
+    private readonly compiled = new Map<number, T>();
+
+    constructor(private readonly compile: (source: string, variant: number) => T) {{}}
+
+    get(features: Iterable<VariantFeature>): T {{
+        const variant = variantIndex[variantMask(features)];
+        let value = this.compiled.get(variant);
+        if (value === undefined) {{
+            value = this.compile(variantSources[variant], variant);
+            this.compiled.set(variant, value);
+        }}
+        return value;
+    }}
+}}
+"#,
+        features.join(", "),
+        index.join(", "),
+        sources
+    ))
+}
+
+/// Generate the IndexedDB side of the persistent shader cache.
+///
+/// Linking asks the cache synchronously, so the class loads every entry into
+/// memory when opened and writes new entries back in the background. Pass an
+/// opened instance to `setShaderCache` (or the `shaderCache` option of
+/// `webGL2`).
+pub fn generate_shader_cache_harness() -> String {
+    r#"
+// Generated IndexedDB store for compiled shader WASM
+export class IndexedDBShaderCache {
+    private constructor(
+        private readonly db: IDBDatabase,
+        private readonly entries: Map<string, Uint8Array>,
+    ) {}
+
+    /** Open (or create) the database and load its entries */
+    static async open(name = "webgl2-shader-cache"): Promise<IndexedDBShaderCache> {
+        const db = await new Promise<IDBDatabase>((resolve, reject) => {
+            const request = indexedDB.open(name, 1);
+            request.onupgradeneeded = () => request.result.createObjectStore("modules");
+            request.onsuccess = () => resolve(request.result);
+            request.onerror = () => reject(request.error);
+        });
+        const entries = new Map<string, Uint8Array>();
+        await new Promise<void>((resolve, reject) => {
+            const request = db.transaction("modules").objectStore("modules").openCursor();
+            request.onsuccess = () => {
+                const cursor = request.result;
+                if (!cursor) return resolve();
+                entries.set(String(cursor.key), new Uint8Array(cursor.value));
+                cursor.continue();
+            };
+            request.onerror = () => reject(request.error);
+        });
+        return new IndexedDBShaderCache(db, entries);
+    }
+
+    get(key: string): Uint8Array | undefined {
+        return this.entries.get(key);
     }
 
-    setUniforms(uniforms: any) {
-        // TODO: Set uniform values
+    put(key: string, bytes: Uint8Array): void {
+        this.entries.set(key, bytes);
+        this.db.transaction("modules", "readwrite").objectStore("modules").put(bytes, key);
     }
 
-    draw(attributes: any) {
-        // TODO: Execute shader
+    /** Drop every entry, e.g. after upgrading the emulator */
+    async clear(): Promise<void> {
+        this.entries.clear();
+        await new Promise<void>((resolve, reject) => {
+            const request = this.db.transaction("modules", "readwrite").objectStore("modules").clear();
+            request.onsuccess = () => resolve();
+            request.onerror = () => reject(request.error);
+        });
     }
 }
 "#
-    .to_string())
+    .to_string()
+}
+
+/// TypeScript object literal mapping names to locations.
+fn object_literal(entries: &[(&str, u32)]) -> String {
+    let entries: Vec<(&str, String)> = entries
+        .iter()
+        .map(|(name, location)| (*name, location.to_string()))
+        .collect();
+    record_literal(&entries)
+}
+
+/// TypeScript object literal mapping names to values already in TypeScript.
+fn record_literal(entries: &[(&str, String)]) -> String {
+    if entries.is_empty() {
+        return "{}".to_string();
+    }
+    let fields: String = entries
+        .iter()
+        .map(|(name, value)| format!("        {}: {},\n", name, value))
+        .collect();
+    format!("{{\n{}    }}", fields)
 }
 
 /// Code generation errors
@@ -32,4 +549,95 @@ pub enum CodegenError {
 
     #[error("Type mapping error: {0}")]
     TypeMappingError(String),
+
+    #[error("Uniform block mismatch: {0}")]
+    BlockMismatch(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glsl_introspection::{
+        compile_variants, introspect_shader, parse_uniform_annotations,
+    };
+
+    #[test]
+    fn test_annotated_uniforms_get_defaults_and_controls() {
+        let manifest = introspect_shader(
+            "#version 300 es
+            // @range(0, 2)
+            uniform float scale; // @default(1.5)
+            uniform highp vec3 tint; // @default(1, 0.5, 0)
+            layout(location = 0) in vec2 position;
+            void main() { gl_Position = vec4(position * scale, tint.x, 1.0); }",
+        )
+        .unwrap();
+        let harness = generate_harness(&manifest).unwrap();
+
+        assert!(harness.contains("scale: [1.5],"));
+        assert!(harness.contains("tint: [1, 0.5, 0],"));
+        assert!(harness.contains("tint: \"uniform3fv\","));
+        assert!(harness.contains("{ name: \"scale\", value: 1.5, min: 0, max: 2, step: 0.02 },"));
+        assert!(!harness.contains("name: \"tint\""));
+
+        assert!(parse_uniform_annotations("uniform float x; // @range(1)").is_err());
+    }
+
+    #[test]
+    fn test_materials_share_blocks_by_name() {
+        let camera = "layout(std140) uniform Camera { mat4 viewProj; vec4 eye; };";
+        let program = |blocks: &str, body: &str| {
+            introspect_shader(&format!(
+                "#version 300 es
+                {blocks}
+                layout(location = 0) in vec4 position;
+                void main() {{ gl_Position = {body}; }}"
+            ))
+            .unwrap()
+        };
+        let unlit = program(camera, "viewProj * position");
+        let lit = program(
+            &format!("{camera}\nlayout(std140) uniform Lights {{ vec4 color; ivec4 count; }};"),
+            "viewProj * position * color * float(count.x)",
+        );
+        let harness = generate_material_harness(&[("unlit", &unlit), ("lit", &lit)]).unwrap();
+
+        assert_eq!(harness.matches("new SharedUniformBlock(").count(), 2);
+        assert!(harness.contains("Camera: new SharedUniformBlock(0, 80, {"));
+        assert!(harness.contains("Lights: new SharedUniformBlock(1, 32, {"));
+        assert!(harness.contains("new Set<string>([\"count\"])"));
+        assert!(harness.contains("lit: [\"Camera\", \"Lights\"],"));
+
+        let other = program(
+            "layout(std140) uniform Camera { mat4 viewProj; };",
+            "viewProj * position",
+        );
+        assert!(generate_material_harness(&[("unlit", &unlit), ("other", &other)]).is_err());
+    }
+
+    #[test]
+    fn test_variant_table_embeds_each_distinct_source_once() {
+        let table = compile_variants(
+            "#version 300 es
+            layout(location = 0) in vec4 position;
+            #ifdef SKINNED
+            uniform mat4 bone;
+            #endif
+            void main() {
+            #ifdef SKINNED
+                gl_Position = bone * position;
+            #else
+                gl_Position = position;
+            #endif
+            }",
+            naga::ShaderStage::Vertex,
+            &["SKINNED", "SHADOWED"],
+        )
+        .unwrap();
+        let harness = generate_variant_harness(&table).unwrap();
+
+        assert!(harness.contains("variantFeatures = [\"SKINNED\", \"SHADOWED\"] as const;"));
+        assert!(harness.contains("variantIndex: readonly number[] = [0, 1, 0, 1];"));
+        assert_eq!(harness.matches("#version 300 es").count(), 2);
+    }
 }
@@ -3,10 +3,15 @@
 //! This module generates TypeScript wrapper code that makes it easy to use
 //! compiled WASM shaders from JavaScript applications.
 
+pub mod ffi;
 mod generator;
 mod types;
 
-pub use generator::{generate_harness, CodegenError};
+pub use ffi::{generate_c_header, generate_ts_declarations};
+pub use generator::{
+    generate_harness, generate_material_harness, generate_shader_cache_harness,
+    generate_variant_harness, CodegenError,
+};
 pub use types::TypeScriptType;
 
 use crate::glsl_introspection::ResourceManifest;
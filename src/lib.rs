@@ -8,11 +8,17 @@
 //! - [`naga_wasm_backend`] - Compile Naga IR to WASM with DWARF debug information
 //! - [`wasm_gl_emu`] - Software rasterizer and WASM shader runtime
 //! - [`glsl_introspection`] - GLSL parser with annotation extraction
+//! - [`glsl_format`] - GLSL source formatter
 //! - [`js_codegen`] - TypeScript harness code generator
+//! - [`gl_constants`] - Named GL enum values and enum-to-string helpers
 
+pub mod bench;
 pub mod decompiler;
 pub mod error;
+pub mod gl_constants;
+pub mod glsl_format;
 pub mod glsl_introspection;
+pub mod guest_mem;
 pub mod js_codegen;
 pub mod naga_wasm_backend;
 pub mod wasm_gl_emu;
@@ -22,13 +28,42 @@ pub mod webgpu;
 #[cfg(feature = "coverage")]
 pub mod coverage;
 
+#[cfg(all(feature = "python", not(target_arch = "wasm32")))]
+pub mod python;
+
 #[cfg(target_arch = "wasm32")]
 #[link(wasm_import_module = "env")]
 extern "C" {
     fn print(ptr: *const u8, len: usize);
-    fn dispatch_uncaptured_error(ptr: *const u8, len: usize);
+    fn dispatch_device_events();
     fn wasm_register_shader(ptr: *const u8, len: usize) -> u32;
     fn wasm_release_shader_index(idx: u32);
+    fn shader_cache_get(
+        key_ptr: *const u8,
+        key_len: usize,
+        dst_ptr: *mut u8,
+        dst_len: usize,
+    ) -> i32;
+    fn shader_cache_put(key_ptr: *const u8, key_len: usize, data_ptr: *const u8, data_len: usize);
+    fn pipeline_hook_vertex(
+        ctx: u32,
+        vertex_id: u32,
+        instance_id: u32,
+        varyings_ptr: *const u32,
+        varyings_len: usize,
+    ) -> u32;
+    fn pipeline_hook_fragment(
+        ctx: u32,
+        x: i32,
+        y: i32,
+        depth: f32,
+        front_facing: u32,
+        varyings_ptr: *const u32,
+        varyings_len: usize,
+    ) -> u32;
+    // Shared with the `instant` crate (via egg), which imports it as `now`
+    #[link_name = "now"]
+    fn host_now_ms() -> f64;
 }
 
 // Globals used to communicate with shader WASM modules.
@@ -103,11 +138,11 @@ mod native_fallbacks {
     /// This function is unsafe because it reads from a raw pointer.
     pub unsafe fn print(_ptr: *const u8, _len: usize) {}
 
-    /// Dispatch an uncaptured error to the host.
+    /// Ask the host to drain the WebGPU device event queue.
     ///
     /// # Safety
-    /// This function is unsafe because it reads from a raw pointer.
-    pub unsafe fn dispatch_uncaptured_error(_ptr: *const u8, _len: usize) {}
+    /// This function is unsafe to match the signature of the wasm import.
+    pub unsafe fn dispatch_device_events() {}
 
     /// Register a shader with the host.
     ///
@@ -123,6 +158,74 @@ mod native_fallbacks {
     /// This function is unsafe because it is a stub for a host-provided function.
     pub unsafe fn wasm_release_shader_index(_idx: u32) {}
 
+    /// Look up a compiled shader in the host cache; there is none natively.
+    ///
+    /// # Safety
+    /// This function is unsafe because it is a stub for a host-provided function.
+    pub unsafe fn shader_cache_get(
+        _key_ptr: *const u8,
+        _key_len: usize,
+        _dst_ptr: *mut u8,
+        _dst_len: usize,
+    ) -> i32 {
+        -1
+    }
+
+    /// Store a compiled shader in the host cache.
+    ///
+    /// # Safety
+    /// This function is unsafe because it is a stub for a host-provided function.
+    pub unsafe fn shader_cache_put(
+        _key_ptr: *const u8,
+        _key_len: usize,
+        _data_ptr: *const u8,
+        _data_len: usize,
+    ) {
+    }
+
+    /// Pass a vertex to the host's pipeline hook; natively, keep it.
+    ///
+    /// # Safety
+    /// This function is unsafe to match the signature of the wasm import.
+    pub unsafe fn pipeline_hook_vertex(
+        _ctx: u32,
+        _vertex_id: u32,
+        _instance_id: u32,
+        _varyings_ptr: *const u32,
+        _varyings_len: usize,
+    ) -> u32 {
+        1
+    }
+
+    /// Pass a fragment to the host's pipeline hook; natively, keep it.
+    ///
+    /// # Safety
+    /// This function is unsafe to match the signature of the wasm import.
+    pub unsafe fn pipeline_hook_fragment(
+        _ctx: u32,
+        _x: i32,
+        _y: i32,
+        _depth: f32,
+        _front_facing: u32,
+        _varyings_ptr: *const u32,
+        _varyings_len: usize,
+    ) -> u32 {
+        1
+    }
+
+    /// Host monotonic time in milliseconds; natively, time since the first call.
+    ///
+    /// # Safety
+    /// This function is unsafe to match the signature of the wasm import.
+    pub unsafe fn host_now_ms() -> f64 {
+        static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+        START
+            .get_or_init(std::time::Instant::now)
+            .elapsed()
+            .as_secs_f64()
+            * 1000.0
+    }
+
     pub static mut __heap_base_local: i32 = 0;
     #[no_mangle]
     pub static __heap_base: &i32 = unsafe { &__heap_base_local };
@@ -231,9 +334,9 @@ pub fn js_print(s: &str) {
     }
 }
 
-pub fn js_dispatch_uncaptured_error(s: &str) {
+pub fn js_dispatch_device_events() {
     unsafe {
-        dispatch_uncaptured_error(s.as_ptr(), s.len());
+        dispatch_device_events();
     }
 }
 
@@ -259,6 +362,59 @@ pub fn js_release_shader_index(idx: u32) {
     unsafe { wasm_release_shader_index(idx) }
 }
 
+/// Fetch compiled shader WASM stored under `key` from the host cache.
+///
+/// The host returns the entry's length, or -1 if there is none, and copies
+/// the entry only when it fits the destination: the first call asks for the
+/// length, the second fills a buffer of that size.
+pub fn js_shader_cache_get(key: &str) -> Option<Vec<u8>> {
+    let len = unsafe { shader_cache_get(key.as_ptr(), key.len(), std::ptr::null_mut(), 0) };
+    let len = usize::try_from(len).ok()?;
+    let mut bytes = vec![0u8; len];
+    let copied = unsafe { shader_cache_get(key.as_ptr(), key.len(), bytes.as_mut_ptr(), len) };
+    (copied as usize == len && len > 0).then_some(bytes)
+}
+
+/// Hand compiled shader WASM to the host cache under `key`.
+pub fn js_shader_cache_put(key: &str, bytes: &[u8]) {
+    unsafe { shader_cache_put(key.as_ptr(), key.len(), bytes.as_ptr(), bytes.len()) }
+}
+
+/// Whether the host's vertex hook for context `ctx` keeps a vertex.
+pub fn js_pipeline_hook_vertex(ctx: u32, vertex: &wasm_gl_emu::hooks::VertexStage) -> bool {
+    let varyings = vertex.varyings;
+    unsafe {
+        pipeline_hook_vertex(
+            ctx,
+            vertex.vertex_id,
+            vertex.instance_id,
+            varyings.as_ptr(),
+            varyings.len(),
+        ) != 0
+    }
+}
+
+/// Whether the host's fragment hook for context `ctx` keeps a fragment.
+pub fn js_pipeline_hook_fragment(ctx: u32, fragment: &wasm_gl_emu::hooks::FragmentStage) -> bool {
+    let varyings = fragment.varyings;
+    unsafe {
+        pipeline_hook_fragment(
+            ctx,
+            fragment.x,
+            fragment.y,
+            fragment.depth,
+            fragment.front_facing as u32,
+            varyings.as_ptr(),
+            varyings.len(),
+        ) != 0
+    }
+}
+
+/// Host monotonic time in milliseconds.
+pub fn js_now_ms() -> f64 {
+    unsafe { host_now_ms() }
+}
+
 // ============================================================================
 // Math Builtins (Skip Host)
 // ============================================================================
@@ -453,19 +609,90 @@ pub extern "C" fn gl_smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
     t * t * (3.0 - 2.0 * t)
 }
 
+/// Sample a WebGPU texture binding from a shader compiled with host sampling.
+/// `level_kind` selects how `level` and the gradients are read: 0 implicit,
+/// 1 explicit level, 2 bias, 3 gradient. Returns a pointer to four floats.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn webgpu_texture_sample(
+    texture: u32,
+    sampler: u32,
+    u: f32,
+    v: f32,
+    w: f32,
+    level_kind: u32,
+    level: f32,
+    ddx_u: f32,
+    ddx_v: f32,
+    ddy_u: f32,
+    ddy_v: f32,
+) -> *const f32 {
+    use webgpu::texture::SampleLevel;
+    let level = match level_kind {
+        1 => SampleLevel::Exact(level),
+        2 => SampleLevel::Bias(level),
+        3 => SampleLevel::Gradient {
+            ddx: [ddx_u, ddx_v],
+            ddy: [ddy_u, ddy_v],
+        },
+        _ => SampleLevel::Auto,
+    };
+    webgpu::texture::shader_sample(texture, sampler, [u, v, w], level)
+}
+
 // Re-export commonly used types
 pub use glsl_introspection::ResourceManifest;
-pub use js_codegen::generate_harness;
+pub use js_codegen::{generate_harness, generate_material_harness, generate_variant_harness};
 pub use naga_wasm_backend::{BackendError, WasmBackend, WasmBackendConfig, WasmModule};
 
 // ---- Context Lifecycle ----
 
-/// Create a context with flags (bit0 = shader debug).
+/// Create a context with flags (bit0 = shader debug, bit1 = shader coverage,
+/// bit2 = shader profiling, bit3 = WebGL1 compatibility).
 #[no_mangle]
 pub extern "C" fn wasm_create_context_with_flags(flags: u32, width: u32, height: u32) -> u32 {
     webgl2_context::registry::create_context_with_flags(flags, width, height)
 }
 
+/// Create a context with flags and default framebuffer attributes (bit0 =
+/// alpha, bit1 = depth, bit2 = stencil, bit3 = antialias, bit4 =
+/// premultipliedAlpha, bit5 = preserveDrawingBuffer).
+#[no_mangle]
+pub extern "C" fn wasm_create_context_with_attributes(
+    flags: u32,
+    attributes: u32,
+    width: u32,
+    height: u32,
+) -> u32 {
+    webgl2_context::registry::create_context_with_attributes(
+        flags,
+        webgl2_context::types::ContextAttributes::from_bits(attributes),
+        width,
+        height,
+    )
+}
+
+/// Lower a context's implementation limits; 0 keeps a limit's default.
+/// Returns errno (0 on success).
+#[no_mangle]
+pub extern "C" fn wasm_ctx_set_limits(
+    ctx: u32,
+    max_texture_size: u32,
+    max_vertex_attribs: u32,
+    max_varying_vectors: u32,
+    max_uniform_vectors: u32,
+    max_draw_buffers: u32,
+) -> u32 {
+    webgl2_context::ctx_set_limits(
+        ctx,
+        max_texture_size,
+        max_vertex_attribs,
+        max_varying_vectors,
+        max_uniform_vectors,
+        max_draw_buffers,
+    )
+}
+
 /// Destroy a WebGL2 context by handle.
 /// Returns errno (0 on success).
 #[no_mangle]
@@ -517,6 +744,14 @@ pub extern "C" fn wasm_ctx_resize(ctx: u32, width: u32, height: u32) -> u32 {
     webgl2_context::state::ctx_resize(ctx, width, height)
 }
 
+/// Present the default framebuffer of a context, clearing it afterwards
+/// unless it was created with preserveDrawingBuffer.
+/// Returns a pointer to the composited RGBA8 frame, or 0 on failure.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_present(ctx: u32) -> u32 {
+    webgl2_context::state::ctx_present(ctx)
+}
+
 // ---- Memory Management ----
 
 /// Allocate memory from WASM linear memory.
@@ -526,13 +761,47 @@ pub extern "C" fn wasm_alloc(size: u32) -> u32 {
     webgl2_context::wasm_alloc(size)
 }
 
-/// Free memory allocated by wasm_alloc.
+/// Allocate memory owned by a context, freed with it unless wasm_free releases it first.
+/// Returns pointer (0 on failure; sets last_error).
+#[no_mangle]
+pub extern "C" fn wasm_ctx_alloc(ctx: u32, size: u32) -> u32 {
+    webgl2_context::wasm_ctx_alloc(ctx, size)
+}
+
+/// Free memory allocated by wasm_alloc or wasm_ctx_alloc.
 /// Returns errno (0 on success).
 #[no_mangle]
 pub extern "C" fn wasm_free(ptr: u32) -> u32 {
     webgl2_context::wasm_free(ptr)
 }
 
+/// Allocate transient memory from the per-frame bump arena.
+/// The memory stays valid until the next wasm_frame_reset and must not be freed.
+/// Returns pointer (0 on failure).
+#[no_mangle]
+pub extern "C" fn wasm_frame_alloc(size: u32) -> u32 {
+    webgl2_context::wasm_frame_alloc(size)
+}
+
+/// Reclaim all memory handed out by wasm_frame_alloc.
+/// Returns errno (0 on success).
+#[no_mangle]
+pub extern "C" fn wasm_frame_reset() -> u32 {
+    webgl2_context::wasm_frame_reset()
+}
+
+/// Number of live WebGL2 contexts.
+#[no_mangle]
+pub extern "C" fn wasm_context_count() -> u32 {
+    webgl2_context::context_count()
+}
+
+/// Number of live wasm_alloc and wasm_ctx_alloc allocations.
+#[no_mangle]
+pub extern "C" fn wasm_allocation_count() -> u32 {
+    webgl2_context::allocation_count()
+}
+
 // ---- Error Reporting ----
 
 /// Get pointer to last error message (UTF-8).
@@ -597,6 +866,13 @@ pub extern "C" fn wasm_ctx_tex_parameter_i(ctx: u32, target: u32, pname: u32, pa
     webgl2_context::ctx_tex_parameter_i(ctx, target, pname, param)
 }
 
+/// Set a float texture parameter (TEXTURE_LOD_BIAS, or any integer one).
+/// Returns errno.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_tex_parameter_f(ctx: u32, target: u32, pname: u32, param: f32) -> u32 {
+    webgl2_context::ctx_tex_parameter_f(ctx, target, pname, param)
+}
+
 /// Upload pixel data to a texture.
 /// ptr/len point to RGBA u8 pixel data in WASM linear memory.
 /// Returns errno.
@@ -629,6 +905,25 @@ pub extern "C" fn wasm_ctx_tex_image_2d(
     )
 }
 
+/// Upload a PNG or JPEG image to the bound 2D texture as RGBA8.
+#[cfg(feature = "image-decode")]
+#[no_mangle]
+pub extern "C" fn wasm_ctx_tex_image_2d_encoded(
+    ctx: u32,
+    target: u32,
+    level: i32,
+    ptr: u32,
+    len: u32,
+) -> u32 {
+    webgl2_context::ctx_tex_image_2d_encoded(ctx, target, level, ptr, len)
+}
+
+/// Upload every level of a KTX2 file to the bound 2D texture.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_tex_image_ktx2(ctx: u32, target: u32, ptr: u32, len: u32) -> u32 {
+    webgl2_context::ctx_tex_image_ktx2(ctx, target, ptr, len)
+}
+
 #[no_mangle]
 pub extern "C" fn wasm_ctx_tex_image_3d(
     ctx: u32,
@@ -679,6 +974,56 @@ pub extern "C" fn wasm_ctx_tex_sub_image_2d(
     )
 }
 
+/// Import a host video/camera frame into the bound texture.
+/// ptr/len point to the frame in WASM linear memory; format is RGBA, RGB,
+/// I420 or NV12.
+/// Returns errno.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_tex_image_external(
+    ctx: u32,
+    ptr: u32,
+    len: u32,
+    width: u32,
+    height: u32,
+    format: u32,
+) -> u32 {
+    webgl2_context::ctx_tex_image_external(ctx, ptr, len, width, height, format)
+}
+
+/// Alias a level of the bound 2D texture over linear memory instead of
+/// copying it (see docs/1.1.2-texture.md for the aliasing rules).
+/// Returns errno.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn wasm_ctx_tex_image_2d_alias(
+    ctx: u32,
+    target: u32,
+    level: i32,
+    internal_format: u32,
+    width: u32,
+    height: u32,
+    ptr: u32,
+    len: u32,
+) -> u32 {
+    webgl2_context::ctx_tex_image_2d_alias(
+        ctx,
+        target,
+        level,
+        internal_format,
+        width,
+        height,
+        ptr,
+        len,
+    )
+}
+
+/// End a texture alias; the level keeps a copy of the region.
+/// Returns errno.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_invalidate_texture_alias(ctx: u32, target: u32, level: i32) -> u32 {
+    webgl2_context::ctx_invalidate_texture_alias(ctx, target, level)
+}
+
 /// Generate mipmaps.
 /// Returns errno.
 #[no_mangle]
@@ -817,6 +1162,55 @@ pub unsafe extern "C" fn wasm_ctx_read_pixels(
     webgl2_context::ctx_read_pixels(ctx, x, y, width, height, format, type_)
 }
 
+/// FNV-1a hash of the read framebuffer's RGBA8 contents and size, for cheap
+/// golden comparisons. Returns 0 on failure (check last error).
+#[no_mangle]
+pub extern "C" fn wasm_ctx_framebuffer_hash(ctx: u32) -> u64 {
+    webgl2_context::framebuffer_hash(ctx).unwrap_or(0)
+}
+
+/// Read layer `layer` of mip `level` of texture `tex` without binding it.
+/// Returns an ephemeral pointer (as for `wasm_ctx_read_pixels`) to the
+/// level's width, height and internal format as `u32`s followed by its
+/// texels, or 0 on failure (check last error).
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_texture_pixels(ctx: u32, tex: u32, level: u32, layer: u32) -> u32 {
+    webgl2_context::ctx_get_texture_pixels(ctx, tex, level, layer)
+}
+
+/// Read the depth and stencil planes of renderbuffer `rb` without binding
+/// it. Returns an ephemeral pointer to its width, height, internal format
+/// and depth value count as `u32`s, followed by the depth values as `f32`s
+/// and the stencil bytes, or 0 on failure (check last error).
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_renderbuffer_pixels(ctx: u32, rb: u32) -> u32 {
+    webgl2_context::ctx_get_renderbuffer_pixels(ctx, rb)
+}
+
+/// Read `len` bytes of buffer `buf` from `offset` without binding it
+/// (`len == u32::MAX` reads to the end). Returns an ephemeral pointer, or 0
+/// on failure (check last error).
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_buffer_data(ctx: u32, buf: u32, offset: u32, len: u32) -> u32 {
+    webgl2_context::ctx_get_buffer_data(ctx, buf, offset, len)
+}
+
+/// Play a command buffer of `len` bytes at `ptr` (see
+/// `webgl2_context::command_buffer` for the encoding).
+/// Returns errno.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_play_command_buffer(ctx: u32, ptr: u32, len: u32) -> u32 {
+    webgl2_context::ctx_play_command_buffer(ctx, ptr, len)
+}
+
+/// Run a draw packet of `len` bytes at `ptr`: program, vertex bindings,
+/// uniforms and the draw in one call (see `webgl2_context::draw_packet`).
+/// Returns errno.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_draw_packet(ctx: u32, ptr: u32, len: u32) -> u32 {
+    webgl2_context::ctx_draw_packet(ctx, ptr, len)
+}
+
 // ---- State Management ----
 
 /// Set the clear color.
@@ -825,6 +1219,12 @@ pub extern "C" fn wasm_ctx_clear_color(ctx: u32, r: f32, g: f32, b: f32, a: f32)
     webgl2_context::ctx_clear_color(ctx, r, g, b, a)
 }
 
+/// Set the clear depth.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_clear_depth(ctx: u32, depth: f32) -> u32 {
+    webgl2_context::ctx_clear_depth(ctx, depth)
+}
+
 /// Clear buffers.
 #[no_mangle]
 pub extern "C" fn wasm_ctx_clear(ctx: u32, mask: u32) -> u32 {
@@ -896,6 +1296,13 @@ pub extern "C" fn wasm_ctx_blend_color(ctx: u32, r: f32, g: f32, b: f32, a: f32)
     webgl2_context::ctx_blend_color(ctx, r, g, b, a)
 }
 
+/// Set a pixel storage parameter.
+/// Returns errno (0 on success).
+#[no_mangle]
+pub extern "C" fn wasm_ctx_pixel_storei(ctx: u32, pname: u32, param: i32) -> u32 {
+    webgl2_context::ctx_pixel_storei(ctx, pname, param)
+}
+
 /// Set the viewport.
 #[no_mangle]
 pub extern "C" fn wasm_ctx_viewport(ctx: u32, x: i32, y: i32, width: u32, height: u32) -> u32 {
@@ -962,6 +1369,26 @@ pub extern "C" fn wasm_ctx_stencil_op_separate(
     webgl2_context::state::ctx_stencil_op_separate(ctx, face, fail, zfail, zpass)
 }
 
+/// Set sample coverage value and inversion.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_sample_coverage(ctx: u32, value: f32, invert: u32) -> u32 {
+    webgl2_context::state::ctx_sample_coverage(ctx, value, invert != 0)
+}
+
+/// Select a debug visualization (0 none, 1 depth, 2 overdraw, 3 wireframe,
+/// 4 mip level).
+#[no_mangle]
+pub extern "C" fn wasm_ctx_set_debug_view(ctx: u32, mode: u32) -> u32 {
+    webgl2_context::state::ctx_set_debug_view(ctx, mode)
+}
+
+/// Forward draws' pipeline stages to the `pipeline_hook_*` imports (bit0 =
+/// vertices, bit1 = fragments).
+#[no_mangle]
+pub extern "C" fn wasm_ctx_set_pipeline_hooks(ctx: u32, stages: u32) -> u32 {
+    webgl2_context::state::ctx_set_pipeline_hooks(ctx, stages)
+}
+
 /// Set stencil mask.
 #[no_mangle]
 pub extern "C" fn wasm_ctx_stencil_mask(ctx: u32, mask: u32) -> u32 {
@@ -1004,6 +1431,13 @@ pub extern "C" fn wasm_ctx_get_error(ctx: u32) -> u32 {
     webgl2_context::ctx_get_error(ctx)
 }
 
+/// Get the context's default framebuffer attributes, packed as for
+/// `wasm_create_context_with_attributes`.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_context_attributes(ctx: u32) -> u32 {
+    webgl2_context::ctx_get_context_attributes(ctx)
+}
+
 // ---- Buffer Operations ----
 
 /// Create a buffer.
@@ -1170,12 +1604,37 @@ pub extern "C" fn wasm_ctx_attach_shader(ctx: u32, program: u32, shader: u32) ->
     webgl2_context::ctx_attach_shader(ctx, program, shader)
 }
 
+/// Detach a shader from a program.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_detach_shader(ctx: u32, program: u32, shader: u32) -> u32 {
+    webgl2_context::ctx_detach_shader(ctx, program, shader)
+}
+
+/// Validate a program.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_validate_program(ctx: u32, program: u32) -> u32 {
+    webgl2_context::ctx_validate_program(ctx, program)
+}
+
 /// Link a program.
 #[no_mangle]
 pub extern "C" fn wasm_ctx_link_program(ctx: u32, program: u32) -> u32 {
     webgl2_context::ctx_link_program(ctx, program)
 }
 
+/// Recompile a program's shaders from new sources and relink it in place.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_reload_program(
+    ctx: u32,
+    program: u32,
+    vs_ptr: u32,
+    vs_len: u32,
+    fs_ptr: u32,
+    fs_len: u32,
+) -> u32 {
+    webgl2_context::ctx_reload_program(ctx, program, vs_ptr, vs_len, fs_ptr, fs_len)
+}
+
 /// Get program parameter.
 #[no_mangle]
 pub extern "C" fn wasm_ctx_get_program_parameter(ctx: u32, program: u32, pname: u32) -> i32 {
@@ -1188,6 +1647,34 @@ pub extern "C" fn wasm_ctx_get_program_info_log(ctx: u32, program: u32) -> u32 {
     webgl2_context::ctx_get_program_info_log(ctx, program)
 }
 
+/// Recover from a trap that aborted an export. Returns the stage of the
+/// shader it hit, or 0 if it came from the emulator and cannot be recovered.
+#[no_mangle]
+pub extern "C" fn wasm_recover_shader_trap() -> u32 {
+    webgl2_context::recover_shader_trap()
+}
+
+/// Report a recovered shader trap: `offsets` (u32 module offsets of the
+/// trapping frames, innermost first) are symbolized to a GLSL line.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_report_shader_trap(
+    ctx: u32,
+    stage: u32,
+    offsets_ptr: u32,
+    offsets_len: u32,
+    message_ptr: u32,
+    message_len: u32,
+) -> u32 {
+    webgl2_context::ctx_report_shader_trap(
+        ctx,
+        stage,
+        offsets_ptr,
+        offsets_len,
+        message_ptr,
+        message_len,
+    )
+}
+
 /// Register compiled shader function table indices.
 /// Called from JS after shader WASM instances are created.
 #[no_mangle]
@@ -1218,6 +1705,26 @@ pub extern "C" fn wasm_ctx_get_program_wasm(
     webgl2_context::ctx_get_program_wasm(ctx, program, shader_type, ptr, len)
 }
 
+/// Copy up to `len` bytes of a program shader's WASM from byte `offset`.
+/// Returns the number of bytes copied.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_program_wasm_chunk(
+    ctx: u32,
+    program: u32,
+    shader_type: u32,
+    offset: u32,
+    ptr: u32,
+    len: u32,
+) -> u32 {
+    webgl2_context::ctx_get_program_wasm_chunk(ctx, program, shader_type, offset, ptr, len)
+}
+
+/// FNV-1a hash of a program shader's WASM, 0 when there is none.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_program_wasm_hash(ctx: u32, program: u32, shader_type: u32) -> u64 {
+    webgl2_context::ctx_get_program_wasm_hash(ctx, program, shader_type)
+}
+
 /// Get attribute location.
 #[no_mangle]
 pub extern "C" fn wasm_ctx_get_attrib_location(ctx: u32, program: u32, ptr: u32, len: u32) -> i32 {
@@ -1236,6 +1743,30 @@ pub extern "C" fn wasm_ctx_bind_attrib_location(
     webgl2_context::ctx_bind_attrib_location(ctx, program, index, ptr, len)
 }
 
+/// Bind uniform location.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_bind_uniform_location(
+    ctx: u32,
+    program: u32,
+    location: u32,
+    ptr: u32,
+    len: u32,
+) -> u32 {
+    webgl2_context::ctx_bind_uniform_location(ctx, program, location, ptr, len)
+}
+
+/// Bind varying location.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_bind_varying_location(
+    ctx: u32,
+    program: u32,
+    location: u32,
+    ptr: u32,
+    len: u32,
+) -> u32 {
+    webgl2_context::ctx_bind_varying_location(ctx, program, location, ptr, len)
+}
+
 #[no_mangle]
 pub extern "C" fn wasm_ctx_get_uniform_block_index(
     ctx: u32,
@@ -1261,6 +1792,25 @@ pub extern "C" fn wasm_ctx_uniform_block_binding(
     )
 }
 
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_active_uniform_block_name(
+    ctx: u32,
+    program: u32,
+    index: u32,
+) -> u32 {
+    webgl2_context::ctx_get_active_uniform_block_name(ctx, program, index)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_active_uniform_block_parameter(
+    ctx: u32,
+    program: u32,
+    index: u32,
+    pname: u32,
+) -> u32 {
+    webgl2_context::ctx_get_active_uniform_block_parameter(ctx, program, index, pname)
+}
+
 /// Get uniform location.
 #[no_mangle]
 pub extern "C" fn wasm_ctx_get_uniform_location(ctx: u32, program: u32, ptr: u32, len: u32) -> i32 {
@@ -1316,6 +1866,18 @@ pub extern "C" fn wasm_ctx_uniform_matrix_4fv(
     webgl2_context::ctx_uniform_matrix_4fv(ctx, location, transpose != 0, ptr, len)
 }
 
+/// Number of uniform setter calls that changed the stored value.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_uniform_upload_count(ctx: u32) -> u32 {
+    webgl2_context::ctx_uniform_stats(ctx).map_or(0, |s| s.uploads)
+}
+
+/// Number of uniform setter calls skipped because the value was unchanged.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_uniform_skip_count(ctx: u32) -> u32 {
+    webgl2_context::ctx_uniform_stats(ctx).map_or(0, |s| s.skipped)
+}
+
 /// Use a program.
 #[no_mangle]
 pub extern "C" fn wasm_ctx_use_program(ctx: u32, program: u32) -> u32 {
@@ -1530,6 +2092,75 @@ pub extern "C" fn wasm_ctx_get_program_debug_stub(
     webgl2_context::ctx_get_program_debug_stub(ctx, program, shader_type, ptr, len)
 }
 
+/// Get a program debug stub with options: `flags` are `DEBUG_STUB_*`, and
+/// `watch_ptr`/`watch_len` a comma-separated list of variables to watch.
+/// Returns the stub length when `ptr` is 0.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "C" fn wasm_ctx_get_program_debug_stub_with_options(
+    ctx: u32,
+    program: u32,
+    shader_type: u32,
+    flags: u32,
+    watch_ptr: u32,
+    watch_len: u32,
+    ptr: u32,
+    len: u32,
+) -> u32 {
+    webgl2_context::ctx_get_program_debug_stub_with_options(
+        ctx,
+        program,
+        shader_type,
+        flags,
+        watch_ptr,
+        watch_len,
+        ptr,
+        len,
+    )
+}
+
+/// Get per-line execution counts of an instrumented program shader.
+/// Returns the report length when `ptr` is 0.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_program_coverage(
+    ctx: u32,
+    program: u32,
+    shader_type: u32,
+    ptr: u32,
+    len: u32,
+) -> u32 {
+    webgl2_context::ctx_get_program_coverage(ctx, program, shader_type, ptr, len)
+}
+
+/// Copy the JSON report of the context's last draw call, explaining what
+/// became of it, to `ptr`. Returns the report length when `ptr` is 0, and 0
+/// before the first draw.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_explain_last_draw(ctx: u32, ptr: u32, len: u32) -> u32 {
+    webgl2_context::ctx_explain_last_draw(ctx, ptr, len)
+}
+
+/// Get the per-line instruction profile of a program shader: JSON for
+/// `format` 0, an annotated source listing for `format` 1.
+/// Returns the report length when `ptr` is 0.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_program_profile(
+    ctx: u32,
+    program: u32,
+    shader_type: u32,
+    format: u32,
+    ptr: u32,
+    len: u32,
+) -> u32 {
+    webgl2_context::ctx_get_program_profile(ctx, program, shader_type, format, ptr, len)
+}
+
+/// Zero the coverage counters of a program.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_reset_program_coverage(ctx: u32, program: u32) -> u32 {
+    webgl2_context::ctx_reset_program_coverage(ctx, program)
+}
+
 // ---- WAT Testing Support (docs/1.9-wat-testing.md) ----
 
 /// Get a reference to compiled WASM bytes for a program's shader.
@@ -1552,13 +2183,32 @@ pub extern "C" fn wasm_ctx_get_program_wat_ref(ctx: u32, program: u32, shader_ty
     ((len as u64) << 32) | (ptr as u64)
 }
 
+/// Get a reference to the compiler output for a program's shader:
+/// `format` 0 is the Naga IR debug dump, 1 the WAT disassembly.
+/// Returns a packed u64: low 32 bits = ptr, high 32 bits = len.
+/// On failure or missing module, returns 0.
+/// The pointer is ephemeral; callers must copy/decode synchronously.
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_program_ir(
+    ctx: u32,
+    program: u32,
+    shader_type: u32,
+    format: u32,
+) -> u64 {
+    let (ptr, len) = webgl2_context::ctx_get_program_ir(ctx, program, shader_type, format);
+    ((len as u64) << 32) | (ptr as u64)
+}
+
 // ---- GLSL Decompiler Support (docs/11.b-decompile-theory.md) ----
 
 /// Decompile WASM bytes to GLSL.
 /// Returns an ephemeral pointer to the GLSL string.
 #[no_mangle]
 pub unsafe extern "C" fn wasm_decompile_to_glsl(wasm_ptr: u32, wasm_len: u32) -> u32 {
-    let wasm_bytes = std::slice::from_raw_parts(wasm_ptr as *const u8, wasm_len as usize);
+    let wasm_bytes = match guest_mem::read(wasm_ptr, wasm_len) {
+        Ok(b) => b,
+        Err(_) => return 0,
+    };
 
     let glsl = match decompiler::decompile_to_glsl(wasm_bytes) {
         Ok(glsl) => glsl,
@@ -1567,6 +2217,37 @@ pub unsafe extern "C" fn wasm_decompile_to_glsl(wasm_ptr: u32, wasm_len: u32) ->
     webgl2_context::ephemeral::alloc_tls_string(&glsl)
 }
 
+/// Decompile WASM bytes to GLSL using a symbol map in text form
+/// (see `decompiler::symbols`). Returns an ephemeral pointer to the GLSL string.
+#[no_mangle]
+pub extern "C" fn wasm_decompile_to_glsl_with_symbols(
+    wasm_ptr: u32,
+    wasm_len: u32,
+    symbols_ptr: u32,
+    symbols_len: u32,
+) -> u32 {
+    let wasm_bytes = match guest_mem::read(wasm_ptr, wasm_len) {
+        Ok(b) => b,
+        Err(_) => return 0,
+    };
+    let symbols_text = match guest_mem::read_str(symbols_ptr, symbols_len) {
+        Ok(s) => s,
+        Err(_) => return 0,
+    };
+
+    let glsl = match decompiler::SymbolMap::parse(symbols_text).and_then(|symbols| {
+        decompiler::decompile_to_glsl_with_symbols(
+            wasm_bytes,
+            decompiler::emitter::EmitterConfig::default(),
+            &symbols,
+        )
+    }) {
+        Ok(glsl) => glsl,
+        Err(e) => format!("// Error: {}", e),
+    };
+    webgl2_context::ephemeral::alloc_tls_string(&glsl)
+}
+
 /// Decompile a single function from WASM bytes to GLSL.
 /// Returns an ephemeral pointer to the GLSL string.
 ///
@@ -1579,7 +2260,10 @@ pub unsafe extern "C" fn wasm_decompile_function_to_glsl(
     wasm_len: u32,
     func_idx: u32,
 ) -> u32 {
-    let wasm_bytes = std::slice::from_raw_parts(wasm_ptr as *const u8, wasm_len as usize);
+    let wasm_bytes = match guest_mem::read(wasm_ptr, wasm_len) {
+        Ok(b) => b,
+        Err(_) => return 0,
+    };
 
     let glsl = match decompiler::decompile_function_to_glsl(wasm_bytes, func_idx) {
         Ok(glsl) => glsl,
@@ -1620,6 +2304,143 @@ pub extern "C" fn wasm_ctx_is_vertex_array(ctx: u32, vao: u32) -> u32 {
     webgl2_context::ctx_is_vertex_array(ctx, vao)
 }
 
+// ---- Program Pipelines ----
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_create_program_pipeline(ctx: u32) -> u32 {
+    webgl2_context::ctx_create_program_pipeline(ctx)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_delete_program_pipeline(ctx: u32, pipeline: u32) -> u32 {
+    webgl2_context::ctx_delete_program_pipeline(ctx, pipeline)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_is_program_pipeline(ctx: u32, pipeline: u32) -> u32 {
+    if webgl2_context::ctx_is_program_pipeline(ctx, pipeline) {
+        1
+    } else {
+        0
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_bind_program_pipeline(ctx: u32, pipeline: u32) -> u32 {
+    webgl2_context::ctx_bind_program_pipeline(ctx, pipeline)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_use_program_stages(
+    ctx: u32,
+    pipeline: u32,
+    stages: u32,
+    program: u32,
+) -> u32 {
+    webgl2_context::ctx_use_program_stages(ctx, pipeline, stages, program)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_active_shader_program(ctx: u32, pipeline: u32, program: u32) -> u32 {
+    webgl2_context::ctx_active_shader_program(ctx, pipeline, program)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_validate_program_pipeline(ctx: u32, pipeline: u32) -> u32 {
+    webgl2_context::ctx_validate_program_pipeline(ctx, pipeline)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_program_pipeline_parameter(
+    ctx: u32,
+    pipeline: u32,
+    pname: u32,
+) -> i32 {
+    webgl2_context::ctx_get_program_pipeline_parameter(ctx, pipeline, pname)
+}
+
+/// Get program pipeline info log (ephemeral string, like the program info log).
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_program_pipeline_info_log(ctx: u32, pipeline: u32) -> u32 {
+    webgl2_context::ctx_get_program_pipeline_info_log(ctx, pipeline)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_program_parameteri(
+    ctx: u32,
+    program: u32,
+    pname: u32,
+    value: i32,
+) -> u32 {
+    webgl2_context::ctx_program_parameteri(ctx, program, pname, value)
+}
+
+// ---- Queries and the virtual GPU timeline ----
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_create_query(ctx: u32) -> u32 {
+    webgl2_context::ctx_create_query(ctx)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_delete_query(ctx: u32, query: u32) -> u32 {
+    webgl2_context::ctx_delete_query(ctx, query)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_is_query(ctx: u32, query: u32) -> u32 {
+    if webgl2_context::ctx_is_query(ctx, query) {
+        1
+    } else {
+        0
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_begin_query(ctx: u32, target: u32, query: u32) -> u32 {
+    webgl2_context::ctx_begin_query(ctx, target, query)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_end_query(ctx: u32, target: u32) -> u32 {
+    webgl2_context::ctx_end_query(ctx, target)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_query_counter(ctx: u32, query: u32, target: u32) -> u32 {
+    webgl2_context::ctx_query_counter(ctx, query, target)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_query(ctx: u32, target: u32, pname: u32) -> i32 {
+    webgl2_context::ctx_get_query(ctx, target, pname)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_query_parameter(ctx: u32, query: u32, pname: u32) -> i64 {
+    webgl2_context::ctx_get_query_parameter(ctx, query, pname)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_set_gpu_cost_model(
+    ctx: u32,
+    draw_ns: f64,
+    vertex_ns: f64,
+    fragment_ns: f64,
+) -> u32 {
+    webgl2_context::ctx_set_gpu_cost_model(ctx, draw_ns, vertex_ns, fragment_ns)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_set_gpu_clock(ctx: u32, clock: u32) -> u32 {
+    webgl2_context::ctx_set_gpu_clock(ctx, clock)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_ctx_get_gpu_time(ctx: u32) -> i64 {
+    webgl2_context::ctx_get_gpu_time(ctx)
+}
+
 // ---- Transform Feedback ----
 
 #[no_mangle]
@@ -1676,7 +2497,10 @@ pub extern "C" fn wasm_ctx_transform_feedback_varyings(
 ) -> u32 {
     // varyings is a packed null-separated list of names
     let mut varyings = Vec::new();
-    let mem = unsafe { std::slice::from_raw_parts(ptr as *const u8, len as usize) };
+    let mem = match guest_mem::read(ptr, len) {
+        Ok(m) => m,
+        Err(code) => return code,
+    };
     let mut start = 0;
     for (i, &b) in mem.iter().enumerate() {
         if b == 0 {
@@ -1745,7 +2569,7 @@ pub extern "C" fn wasm_webgpu_get_preferred_canvas_format() -> u32 {
 
 /// Get the limits of an adapter.
 /// # Safety
-/// The caller must ensure that `ptr` points to a valid memory region of at least 64 bytes (16 u32 values).
+/// The caller must ensure that `ptr` points to a valid memory region of at least 144 bytes (36 u32 values).
 #[no_mangle]
 pub unsafe extern "C" fn wasm_webgpu_get_adapter_limits(
     ctx_handle: u32,
@@ -1753,42 +2577,47 @@ pub unsafe extern "C" fn wasm_webgpu_get_adapter_limits(
     ptr: *mut u32,
 ) {
     let limits = webgpu::adapter::get_adapter_limits(ctx_handle, adapter_handle);
-    *ptr.offset(0) = limits.max_texture_dimension_1d;
-    *ptr.offset(1) = limits.max_texture_dimension_2d;
-    *ptr.offset(2) = limits.max_texture_dimension_3d;
-    *ptr.offset(3) = limits.max_texture_array_layers;
-    *ptr.offset(4) = limits.max_bind_groups;
-    *ptr.offset(5) = 0; // Padding
-    *ptr.offset(6) = limits.max_bindings_per_bind_group;
-    *ptr.offset(7) = limits.max_dynamic_uniform_buffers_per_pipeline_layout;
-    *ptr.offset(8) = limits.max_dynamic_storage_buffers_per_pipeline_layout;
-    *ptr.offset(9) = limits.max_sampled_textures_per_shader_stage;
-    *ptr.offset(10) = limits.max_samplers_per_shader_stage;
-    *ptr.offset(11) = limits.max_storage_buffers_per_shader_stage;
-    *ptr.offset(12) = limits.max_storage_textures_per_shader_stage;
-    *ptr.offset(13) = limits.max_uniform_buffers_per_shader_stage;
-    *ptr.offset(14) = limits.max_uniform_buffer_binding_size;
-    *ptr.offset(15) = limits.max_storage_buffer_binding_size;
-    *ptr.offset(16) = limits.max_vertex_buffers;
-    *ptr.offset(17) = limits.max_vertex_attributes;
-    *ptr.offset(18) = limits.max_vertex_buffer_array_stride;
-    *ptr.offset(19) = limits.max_immediate_size;
-    *ptr.offset(20) = limits.min_uniform_buffer_offset_alignment;
-    *ptr.offset(21) = limits.min_storage_buffer_offset_alignment;
-    *ptr.offset(22) = 0; // Padding
-    *ptr.offset(23) = limits.max_inter_stage_shader_variables;
-    *ptr.offset(24) = limits.max_color_attachments;
-    *ptr.offset(25) = limits.max_color_attachment_bytes_per_sample;
-    *ptr.offset(26) = limits.max_compute_workgroup_storage_size;
-    *ptr.offset(27) = limits.max_compute_invocations_per_workgroup;
-    *ptr.offset(28) = limits.max_compute_workgroup_size_x;
-    *ptr.offset(29) = limits.max_compute_workgroup_size_y;
-    *ptr.offset(30) = limits.max_compute_workgroup_size_z;
-    *ptr.offset(31) = limits.max_compute_workgroups_per_dimension;
-    *ptr.offset(32) = limits.min_uniform_buffer_offset_alignment;
-    *ptr.offset(33) = limits.min_storage_buffer_offset_alignment;
-    let mbs_ptr = ptr.offset(34) as *mut u64;
-    *mbs_ptr = limits.max_buffer_size;
+    let out = match guest_mem::write_slice(ptr, 36) {
+        Ok(o) => o,
+        Err(_) => return,
+    };
+    out[0] = limits.max_texture_dimension_1d;
+    out[1] = limits.max_texture_dimension_2d;
+    out[2] = limits.max_texture_dimension_3d;
+    out[3] = limits.max_texture_array_layers;
+    out[4] = limits.max_bind_groups;
+    out[5] = 0; // Padding
+    out[6] = limits.max_bindings_per_bind_group;
+    out[7] = limits.max_dynamic_uniform_buffers_per_pipeline_layout;
+    out[8] = limits.max_dynamic_storage_buffers_per_pipeline_layout;
+    out[9] = limits.max_sampled_textures_per_shader_stage;
+    out[10] = limits.max_samplers_per_shader_stage;
+    out[11] = limits.max_storage_buffers_per_shader_stage;
+    out[12] = limits.max_storage_textures_per_shader_stage;
+    out[13] = limits.max_uniform_buffers_per_shader_stage;
+    out[14] = limits.max_uniform_buffer_binding_size;
+    out[15] = limits.max_storage_buffer_binding_size;
+    out[16] = limits.max_vertex_buffers;
+    out[17] = limits.max_vertex_attributes;
+    out[18] = limits.max_vertex_buffer_array_stride;
+    out[19] = limits.max_immediate_size;
+    out[20] = limits.min_uniform_buffer_offset_alignment;
+    out[21] = limits.min_storage_buffer_offset_alignment;
+    out[22] = 0; // Padding
+    out[23] = limits.max_inter_stage_shader_variables;
+    out[24] = limits.max_color_attachments;
+    out[25] = limits.max_color_attachment_bytes_per_sample;
+    out[26] = limits.max_compute_workgroup_storage_size;
+    out[27] = limits.max_compute_invocations_per_workgroup;
+    out[28] = limits.max_compute_workgroup_size_x;
+    out[29] = limits.max_compute_workgroup_size_y;
+    out[30] = limits.max_compute_workgroup_size_z;
+    out[31] = limits.max_compute_workgroups_per_dimension;
+    out[32] = limits.min_uniform_buffer_offset_alignment;
+    out[33] = limits.min_storage_buffer_offset_alignment;
+    // max_buffer_size is a u64 stored little-endian in slots 34..36
+    out[34] = limits.max_buffer_size as u32;
+    out[35] = (limits.max_buffer_size >> 32) as u32;
 }
 
 #[no_mangle]
@@ -1938,6 +2767,35 @@ pub extern "C" fn wasm_webgpu_destroy_texture(ctx_handle: u32, texture_handle: u
     webgpu::texture::destroy_texture(ctx_handle, texture_handle)
 }
 
+#[no_mangle]
+pub extern "C" fn wasm_webgpu_generate_mipmaps(
+    ctx_handle: u32,
+    device_handle: u32,
+    texture_handle: u32,
+) -> u32 {
+    webgpu::blit::generate_mipmaps(ctx_handle, device_handle, texture_handle)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_webgpu_blit_texture(
+    ctx_handle: u32,
+    device_handle: u32,
+    source_texture_handle: u32,
+    source_mip_level: u32,
+    dest_texture_handle: u32,
+    dest_mip_level: u32,
+    filter: u32,
+) -> u32 {
+    let config = webgpu::blit::BlitConfig {
+        source_texture_handle,
+        source_mip_level,
+        dest_texture_handle,
+        dest_mip_level,
+        filter: filter.into(),
+    };
+    webgpu::blit::blit_texture(ctx_handle, device_handle, config)
+}
+
 /// Create a shader module from WGSL code.
 ///
 /// # Safety
@@ -1993,6 +2851,8 @@ pub unsafe extern "C" fn wasm_webgpu_create_render_pipeline(
     depth_format: u32,
     depth_write_enabled: u32,
     depth_compare: u32,
+    stencil_ptr: *const u32,
+    stencil_len: usize,
     blend_enabled: u32,
     color_blend_src: u32,
     color_blend_dst: u32,
@@ -2001,15 +2861,22 @@ pub unsafe extern "C" fn wasm_webgpu_create_render_pipeline(
     alpha_blend_dst: u32,
     alpha_blend_op: u32,
 ) -> u32 {
-    let v_entry = {
-        let slice = std::slice::from_raw_parts(vertex_entry_ptr, vertex_entry_len);
-        std::str::from_utf8_unchecked(slice)
+    let v_entry = match guest_mem::read_utf8(vertex_entry_ptr, vertex_entry_len) {
+        Ok(s) => s,
+        Err(_) => return webgpu::NULL_HANDLE,
+    };
+    let f_entry = match guest_mem::read_utf8(fragment_entry_ptr, fragment_entry_len) {
+        Ok(s) => s,
+        Err(_) => return webgpu::NULL_HANDLE,
+    };
+    let layout_data = match guest_mem::read_slice(layout_ptr, layout_len) {
+        Ok(d) => d,
+        Err(_) => return webgpu::NULL_HANDLE,
     };
-    let f_entry = {
-        let slice = std::slice::from_raw_parts(fragment_entry_ptr, fragment_entry_len);
-        std::str::from_utf8_unchecked(slice)
+    let stencil = match guest_mem::read_slice(stencil_ptr, stencil_len) {
+        Ok(d) => d,
+        Err(_) => return webgpu::NULL_HANDLE,
     };
-    let layout_data = std::slice::from_raw_parts(layout_ptr, layout_len);
 
     let config = webgpu::pipeline::RenderPipelineConfig {
         vertex_module_handle,
@@ -2022,6 +2889,7 @@ pub unsafe extern "C" fn wasm_webgpu_create_render_pipeline(
         depth_format,
         depth_write_enabled: depth_write_enabled != 0,
         depth_compare,
+        stencil,
         blend_enabled: blend_enabled != 0,
         color_blend_src,
         color_blend_dst,
@@ -2056,7 +2924,10 @@ pub unsafe extern "C" fn wasm_webgpu_create_bind_group_layout(
     entries_ptr: *const u32,
     entries_len: usize,
 ) -> u32 {
-    let entries = std::slice::from_raw_parts(entries_ptr, entries_len);
+    let entries = match guest_mem::read_slice(entries_ptr, entries_len) {
+        Ok(e) => e,
+        Err(_) => return webgpu::NULL_HANDLE,
+    };
     webgpu::bind_group::create_bind_group_layout(ctx_handle, device_handle, entries)
 }
 
@@ -2073,7 +2944,10 @@ pub unsafe extern "C" fn wasm_webgpu_create_bind_group(
     entries_ptr: *const u32,
     entries_len: usize,
 ) -> u32 {
-    let entries = std::slice::from_raw_parts(entries_ptr, entries_len);
+    let entries = match guest_mem::read_slice(entries_ptr, entries_len) {
+        Ok(e) => e,
+        Err(_) => return webgpu::NULL_HANDLE,
+    };
     webgpu::bind_group::create_bind_group(ctx_handle, device_handle, layout_handle, entries)
 }
 
@@ -2123,7 +2997,10 @@ pub unsafe extern "C" fn wasm_webgpu_queue_submit(
     cb_handles_ptr: *const u32,
     cb_handles_len: usize,
 ) -> u32 {
-    let cb_handles = std::slice::from_raw_parts(cb_handles_ptr, cb_handles_len);
+    let cb_handles = match guest_mem::read_slice(cb_handles_ptr, cb_handles_len) {
+        Ok(h) => h,
+        Err(_) => return webgpu::WEBGPU_ERROR_VALIDATION,
+    };
     webgpu::command::queue_submit(ctx_handle, device_handle, cb_handles)
 }
 
@@ -2141,7 +3018,10 @@ pub unsafe extern "C" fn wasm_webgpu_queue_write_buffer(
     data_ptr: *const u8,
     data_len: usize,
 ) -> u32 {
-    let data = std::slice::from_raw_parts(data_ptr, data_len);
+    let data = match guest_mem::read_slice(data_ptr, data_len) {
+        Ok(d) => d,
+        Err(_) => return webgpu::WEBGPU_ERROR_VALIDATION,
+    };
     webgpu::command::queue_write_buffer(ctx_handle, device_handle, buffer_handle, offset, data)
 }
 
@@ -2163,7 +3043,10 @@ pub unsafe extern "C" fn wasm_webgpu_queue_write_texture(
     height: u32,
     depth: u32,
 ) -> u32 {
-    let data = std::slice::from_raw_parts(data_ptr, data_len);
+    let data = match guest_mem::read_slice(data_ptr, data_len) {
+        Ok(d) => d,
+        Err(_) => return webgpu::WEBGPU_ERROR_VALIDATION,
+    };
     webgpu::command::queue_write_texture(
         ctx_handle,
         device_handle,
@@ -2182,6 +3065,7 @@ pub extern "C" fn wasm_webgpu_command_encoder_copy_texture_to_buffer(
     ctx_handle: u32,
     encoder_handle: u32,
     source_texture_handle: u32,
+    source_mip_level: u32,
     dest_buffer_handle: u32,
     dest_offset: u64,
     dest_bytes_per_row: u32,
@@ -2192,6 +3076,7 @@ pub extern "C" fn wasm_webgpu_command_encoder_copy_texture_to_buffer(
 ) -> u32 {
     let config = webgpu::command::CopyTextureToBufferConfig {
         source_texture_handle,
+        source_mip_level,
         dest_buffer_handle,
         dest_offset,
         dest_bytes_per_row,
@@ -2359,11 +3244,158 @@ pub extern "C" fn wasm_webgpu_render_pass_set_scissor_rect(
     webgpu::command::render_pass_set_scissor_rect(ctx_handle, pass_handle, x, y, w, h)
 }
 
+#[no_mangle]
+pub extern "C" fn wasm_webgpu_render_pass_set_blend_constant(
+    ctx_handle: u32,
+    pass_handle: u32,
+    r: f64,
+    g: f64,
+    b: f64,
+    a: f64,
+) -> u32 {
+    webgpu::command::render_pass_set_blend_constant(ctx_handle, pass_handle, r, g, b, a)
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_webgpu_render_pass_set_stencil_reference(
+    ctx_handle: u32,
+    pass_handle: u32,
+    reference: u32,
+) -> u32 {
+    webgpu::command::render_pass_set_stencil_reference(ctx_handle, pass_handle, reference)
+}
+
 #[no_mangle]
 pub extern "C" fn wasm_webgpu_render_pass_end(ctx_handle: u32, pass_handle: u32) -> u32 {
     webgpu::command::render_pass_end(ctx_handle, pass_handle)
 }
 
+/// Open a debug group on a command encoder.
+///
+/// # Safety
+///
+/// The caller must ensure that `label_ptr` points to `label_len` bytes of UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_webgpu_command_encoder_push_debug_group(
+    ctx_handle: u32,
+    encoder_handle: u32,
+    label_ptr: *const u8,
+    label_len: usize,
+) -> u32 {
+    let label = match guest_mem::read_utf8(label_ptr, label_len) {
+        Ok(l) => l,
+        Err(_) => return webgpu::WEBGPU_ERROR_VALIDATION,
+    };
+    webgpu::command::command_encoder_debug(
+        ctx_handle,
+        encoder_handle,
+        webgpu::command::DebugCommand::PushGroup(label),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_webgpu_command_encoder_pop_debug_group(
+    ctx_handle: u32,
+    encoder_handle: u32,
+) -> u32 {
+    webgpu::command::command_encoder_debug(
+        ctx_handle,
+        encoder_handle,
+        webgpu::command::DebugCommand::PopGroup,
+    )
+}
+
+/// Insert a debug marker on a command encoder.
+///
+/// # Safety
+///
+/// The caller must ensure that `label_ptr` points to `label_len` bytes of UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_webgpu_command_encoder_insert_debug_marker(
+    ctx_handle: u32,
+    encoder_handle: u32,
+    label_ptr: *const u8,
+    label_len: usize,
+) -> u32 {
+    let label = match guest_mem::read_utf8(label_ptr, label_len) {
+        Ok(l) => l,
+        Err(_) => return webgpu::WEBGPU_ERROR_VALIDATION,
+    };
+    webgpu::command::command_encoder_debug(
+        ctx_handle,
+        encoder_handle,
+        webgpu::command::DebugCommand::InsertMarker(label),
+    )
+}
+
+/// Open a debug group in a render pass.
+///
+/// # Safety
+///
+/// The caller must ensure that `label_ptr` points to `label_len` bytes of UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_webgpu_render_pass_push_debug_group(
+    ctx_handle: u32,
+    pass_handle: u32,
+    label_ptr: *const u8,
+    label_len: usize,
+) -> u32 {
+    let label = match guest_mem::read_utf8(label_ptr, label_len) {
+        Ok(l) => l,
+        Err(_) => return webgpu::WEBGPU_ERROR_VALIDATION,
+    };
+    webgpu::command::render_pass_debug(
+        ctx_handle,
+        pass_handle,
+        webgpu::command::DebugCommand::PushGroup(label),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn wasm_webgpu_render_pass_pop_debug_group(
+    ctx_handle: u32,
+    pass_handle: u32,
+) -> u32 {
+    webgpu::command::render_pass_debug(
+        ctx_handle,
+        pass_handle,
+        webgpu::command::DebugCommand::PopGroup,
+    )
+}
+
+/// Insert a debug marker in a render pass.
+///
+/// # Safety
+///
+/// The caller must ensure that `label_ptr` points to `label_len` bytes of UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_webgpu_render_pass_insert_debug_marker(
+    ctx_handle: u32,
+    pass_handle: u32,
+    label_ptr: *const u8,
+    label_len: usize,
+) -> u32 {
+    let label = match guest_mem::read_utf8(label_ptr, label_len) {
+        Ok(l) => l,
+        Err(_) => return webgpu::WEBGPU_ERROR_VALIDATION,
+    };
+    webgpu::command::render_pass_debug(
+        ctx_handle,
+        pass_handle,
+        webgpu::command::DebugCommand::InsertMarker(label),
+    )
+}
+
+/// Serialize the recorded commands of a command buffer to JSON.
+/// Returns a null-terminated string valid until the next dump, or null.
+#[no_mangle]
+pub extern "C" fn wasm_webgpu_command_buffer_dump(
+    ctx_handle: u32,
+    command_buffer_handle: u32,
+) -> *const std::os::raw::c_char {
+    webgpu::trace::command_buffer_dump(ctx_handle, command_buffer_handle)
+}
+
 // ============================================================================
 // Renderbuffer Exports
 // ============================================================================
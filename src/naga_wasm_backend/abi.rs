@@ -0,0 +1,431 @@
+//! Shader module ABI: export names and versioning
+//!
+//! Every generated module follows the same convention, so hosts can find
+//! what they need without knowing the GLSL entry point name:
+//!
+//! - `env.memory` (memory import): the shared linear memory
+//! - `vs_main`, `fs_main` or `cs_main` (function export): the entry point
+//!   of the compiled stage
+//! - `main` (function export): alias of the stage entry point
+//! - `__shader_abi_version` (global export): immutable i32 holding
+//!   [`SHADER_ABI_VERSION`]
+//!
+//! Entry points are also exported under their source name. Bump
+//! [`SHADER_ABI_VERSION`] whenever the calling convention, memory layout or
+//! any of these names change, so stale modules (e.g. from a persistent
+//! shader cache) are rejected instead of misbehaving.
+//!
+//! [`describe`] lists everything a particular module needs from and offers
+//! to its host, so embedders can check their instantiation environment up
+//! front instead of failing with a `LinkError`.
+
+use super::BackendError;
+
+/// Version of the convention generated modules follow.
+pub const SHADER_ABI_VERSION: u32 = 1;
+
+/// Exported global holding the module's ABI version.
+pub const ABI_VERSION_EXPORT: &str = "__shader_abi_version";
+
+/// Module and field of the imported linear memory.
+pub const MEMORY_IMPORT: (&str, &str) = ("env", "memory");
+
+/// Stage-independent alias of the entry point.
+pub const MAIN_EXPORT: &str = "main";
+
+/// Export name of the entry point for `stage`.
+pub fn entry_export_name(stage: naga::ShaderStage) -> &'static str {
+    match stage {
+        naga::ShaderStage::Vertex => "vs_main",
+        naga::ShaderStage::Fragment => "fs_main",
+        naga::ShaderStage::Compute => "cs_main",
+        _ => MAIN_EXPORT,
+    }
+}
+
+/// The ABI version a module declares, if it exports one.
+pub fn abi_version(wasm_bytes: &[u8]) -> Option<u32> {
+    use wasmparser::{ExternalKind, Operator, Payload, TypeRef};
+
+    let mut imported_globals = 0;
+    let mut export_index = None;
+    let mut inits = Vec::new();
+    for payload in wasmparser::Parser::new(0).parse_all(wasm_bytes) {
+        match payload.ok()? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    if let TypeRef::Global(_) = import.ok()?.ty {
+                        imported_globals += 1;
+                    }
+                }
+            }
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    let mut ops = global.ok()?.init_expr.get_operators_reader();
+                    inits.push(match ops.read().ok()? {
+                        Operator::I32Const { value } => Some(value as u32),
+                        _ => None,
+                    });
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export.ok()?;
+                    if export.kind == ExternalKind::Global && export.name == ABI_VERSION_EXPORT {
+                        export_index = Some(export.index);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    let local = export_index?.checked_sub(imported_globals)?;
+    inits.get(local as usize).copied().flatten()
+}
+
+/// WASM value type, with all reference types folded into `Ref`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    I32,
+    I64,
+    F32,
+    F64,
+    V128,
+    Ref,
+}
+
+impl From<wasmparser::ValType> for ValueType {
+    fn from(ty: wasmparser::ValType) -> Self {
+        use wasmparser::ValType;
+        match ty {
+            ValType::I32 => ValueType::I32,
+            ValType::I64 => ValueType::I64,
+            ValType::F32 => ValueType::F32,
+            ValType::F64 => ValueType::F64,
+            ValType::V128 => ValueType::V128,
+            ValType::Ref(_) => ValueType::Ref,
+        }
+    }
+}
+
+/// Size limits of a memory (in 64 KiB pages) or table (in elements).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    pub initial: u64,
+    pub maximum: Option<u64>,
+}
+
+/// Type of an imported or exported item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternType {
+    Func {
+        params: Vec<ValueType>,
+        results: Vec<ValueType>,
+    },
+    Table(Limits),
+    Memory(Limits),
+    Global {
+        ty: ValueType,
+        mutable: bool,
+    },
+    Tag,
+}
+
+/// An item the host must provide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub module: String,
+    pub name: String,
+    pub ty: ExternType,
+}
+
+/// An item the module provides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Export {
+    pub name: String,
+    pub ty: ExternType,
+}
+
+/// Instantiation interface of a generated module, see [`describe`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModuleDescription {
+    pub imports: Vec<Import>,
+    pub exports: Vec<Export>,
+    /// Memories in index order, imported ones first
+    pub memories: Vec<Limits>,
+    /// Tables in index order, imported ones first
+    pub tables: Vec<Limits>,
+    /// See [`abi_version`]
+    pub abi_version: Option<u32>,
+}
+
+impl ModuleDescription {
+    /// The import `module.name`, if the module has it.
+    pub fn import(&self, module: &str, name: &str) -> Option<&Import> {
+        self.imports
+            .iter()
+            .find(|i| i.module == module && i.name == name)
+    }
+
+    /// The export `name`, if the module has it.
+    pub fn export(&self, name: &str) -> Option<&Export> {
+        self.exports.iter().find(|e| e.name == name)
+    }
+}
+
+/// List the imports, exports, memories and tables of a module.
+pub fn describe(wasm_bytes: &[u8]) -> Result<ModuleDescription, BackendError> {
+    use wasmparser::{CompositeInnerType, ExternalKind, Payload, TypeRef};
+
+    let malformed = |e: wasmparser::BinaryReaderError| BackendError::Validation(e.to_string());
+    let limits = |initial: u64, maximum: Option<u64>| Limits { initial, maximum };
+
+    let mut desc = ModuleDescription {
+        abi_version: abi_version(wasm_bytes),
+        ..Default::default()
+    };
+    // Function types by type index, then the index spaces exports refer to
+    let mut types = Vec::new();
+    let mut funcs = Vec::new();
+    let mut globals = Vec::new();
+    let mut tags = 0;
+    let mut exports = Vec::new();
+
+    let func_type = |types: &[Option<ExternType>], index: u32| {
+        types
+            .get(index as usize)
+            .cloned()
+            .flatten()
+            .ok_or_else(|| BackendError::Validation(format!("unknown function type {}", index)))
+    };
+
+    for payload in wasmparser::Parser::new(0).parse_all(wasm_bytes) {
+        match payload.map_err(malformed)? {
+            Payload::TypeSection(reader) => {
+                for rec_group in reader {
+                    for sub_type in rec_group.map_err(malformed)?.types() {
+                        types.push(match &sub_type.composite_type.inner {
+                            CompositeInnerType::Func(f) => Some(ExternType::Func {
+                                params: f.params().iter().map(|&t| t.into()).collect(),
+                                results: f.results().iter().map(|&t| t.into()).collect(),
+                            }),
+                            _ => None,
+                        });
+                    }
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(malformed)?;
+                    let ty = match import.ty {
+                        TypeRef::Func(index) => {
+                            let ty = func_type(&types, index)?;
+                            funcs.push(ty.clone());
+                            ty
+                        }
+                        TypeRef::Table(t) => {
+                            let l = limits(t.initial, t.maximum);
+                            desc.tables.push(l);
+                            ExternType::Table(l)
+                        }
+                        TypeRef::Memory(m) => {
+                            let l = limits(m.initial, m.maximum);
+                            desc.memories.push(l);
+                            ExternType::Memory(l)
+                        }
+                        TypeRef::Global(g) => {
+                            let ty = ExternType::Global {
+                                ty: g.content_type.into(),
+                                mutable: g.mutable,
+                            };
+                            globals.push(ty.clone());
+                            ty
+                        }
+                        TypeRef::Tag(_) => {
+                            tags += 1;
+                            ExternType::Tag
+                        }
+                    };
+                    desc.imports.push(Import {
+                        module: import.module.to_string(),
+                        name: import.name.to_string(),
+                        ty,
+                    });
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                for index in reader {
+                    funcs.push(func_type(&types, index.map_err(malformed)?)?);
+                }
+            }
+            Payload::TableSection(reader) => {
+                for table in reader {
+                    let t = table.map_err(malformed)?.ty;
+                    desc.tables.push(limits(t.initial, t.maximum));
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for memory in reader {
+                    let m = memory.map_err(malformed)?;
+                    desc.memories.push(limits(m.initial, m.maximum));
+                }
+            }
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    let g = global.map_err(malformed)?.ty;
+                    globals.push(ExternType::Global {
+                        ty: g.content_type.into(),
+                        mutable: g.mutable,
+                    });
+                }
+            }
+            Payload::TagSection(reader) => tags += reader.count(),
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export.map_err(malformed)?;
+                    exports.push((export.name.to_string(), export.kind, export.index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    for (name, kind, index) in exports {
+        let i = index as usize;
+        let ty = match kind {
+            ExternalKind::Func => funcs.get(i).cloned(),
+            ExternalKind::Table => desc.tables.get(i).copied().map(ExternType::Table),
+            ExternalKind::Memory => desc.memories.get(i).copied().map(ExternType::Memory),
+            ExternalKind::Global => globals.get(i).cloned(),
+            ExternalKind::Tag => (index < tags).then_some(ExternType::Tag),
+        };
+        let ty = ty.ok_or_else(|| {
+            BackendError::Validation(format!("export {} refers to a missing item", name))
+        })?;
+        desc.exports.push(Export { name, ty });
+    }
+    Ok(desc)
+}
+
+/// Reject modules built for a different ABI version.
+pub fn check_abi(wasm_bytes: &[u8]) -> Result<(), BackendError> {
+    match abi_version(wasm_bytes) {
+        Some(SHADER_ABI_VERSION) => Ok(()),
+        found => Err(BackendError::AbiMismatch {
+            expected: SHADER_ABI_VERSION,
+            found,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module_with_version(version: Option<i32>) -> Vec<u8> {
+        use wasm_encoder::{
+            ConstExpr, EntityType, ExportKind, ExportSection, GlobalSection, GlobalType,
+            ImportSection, Module, ValType,
+        };
+        let i32_global = GlobalType {
+            val_type: ValType::I32,
+            mutable: false,
+            shared: false,
+        };
+        let mut imports = ImportSection::new();
+        imports.import("env", "ACTIVE_ATTR_PTR", EntityType::Global(i32_global));
+        let mut globals = GlobalSection::new();
+        globals.global(i32_global, &ConstExpr::i32_const(1024));
+        let mut exports = ExportSection::new();
+        if let Some(version) = version {
+            globals.global(i32_global, &ConstExpr::i32_const(version));
+            exports.export(ABI_VERSION_EXPORT, ExportKind::Global, 2);
+        }
+        let mut module = Module::new();
+        module.section(&imports);
+        module.section(&globals);
+        module.section(&exports);
+        module.finish()
+    }
+
+    #[test]
+    fn check_abi_rejects_other_versions() {
+        let current = module_with_version(Some(SHADER_ABI_VERSION as i32));
+        assert_eq!(abi_version(&current), Some(SHADER_ABI_VERSION));
+        assert!(check_abi(&current).is_ok());
+
+        let stale = module_with_version(Some(0));
+        let err = check_abi(&stale).unwrap_err().to_string();
+        assert!(err.contains("module has Some(0)"), "{}", err);
+        assert!(matches!(
+            check_abi(&module_with_version(None)),
+            Err(BackendError::AbiMismatch { found: None, .. })
+        ));
+    }
+
+    #[test]
+    fn describe_lists_imports_exports_and_limits() {
+        use wasm_encoder::{
+            CodeSection, EntityType, ExportKind, ExportSection, Function, FunctionSection,
+            ImportSection, Instruction, MemoryType, Module, TypeSection, ValType,
+        };
+        let mut types = TypeSection::new();
+        types.ty().function([ValType::F32], [ValType::F32]);
+        types.ty().function([], []);
+        let mut imports = ImportSection::new();
+        imports.import(
+            MEMORY_IMPORT.0,
+            MEMORY_IMPORT.1,
+            EntityType::Memory(MemoryType {
+                minimum: 100,
+                maximum: Some(200),
+                memory64: false,
+                shared: false,
+                page_size_log2: None,
+            }),
+        );
+        imports.import("env", "gl_sin", EntityType::Function(0));
+        let mut functions = FunctionSection::new();
+        functions.function(1);
+        let mut exports = ExportSection::new();
+        exports.export(MAIN_EXPORT, ExportKind::Func, 1);
+        let mut code = CodeSection::new();
+        let mut body = Function::new([]);
+        body.instruction(&Instruction::End);
+        code.function(&body);
+        let mut module = Module::new();
+        module.section(&types);
+        module.section(&imports);
+        module.section(&functions);
+        module.section(&exports);
+        module.section(&code);
+
+        let desc = describe(&module.finish()).unwrap();
+        let memory = Limits {
+            initial: 100,
+            maximum: Some(200),
+        };
+        assert_eq!(desc.memories, vec![memory]);
+        assert!(desc.tables.is_empty());
+        assert_eq!(desc.abi_version, None);
+        assert_eq!(
+            desc.import("env", "memory").map(|i| &i.ty),
+            Some(&ExternType::Memory(memory))
+        );
+        assert_eq!(
+            desc.import("env", "gl_sin").map(|i| &i.ty),
+            Some(&ExternType::Func {
+                params: vec![ValueType::F32],
+                results: vec![ValueType::F32],
+            })
+        );
+        assert_eq!(
+            desc.export(MAIN_EXPORT).map(|e| &e.ty),
+            Some(&ExternType::Func {
+                params: vec![],
+                results: vec![],
+            })
+        );
+        assert!(describe(b"\0asm").is_err());
+    }
+}
@@ -1,6 +1,10 @@
 //! Core WASM code generation logic
 
 use super::{output_layout, BackendError, CompileConfig, MemoryLayout, WasmBackend, WasmModule};
+use crate::gl_constants::{
+    GL_LINEAR, GL_LINEAR_MIPMAP_LINEAR, GL_LINEAR_MIPMAP_NEAREST, GL_R32F, GL_R32UI, GL_REPEAT,
+    GL_RGBA, GL_RGBA32F, GL_RGBA32UI, GL_RGBA8,
+};
 use naga::{front::Typifier, valid::ModuleInfo, Module};
 use std::collections::HashMap;
 use wasm_encoder::{
@@ -28,6 +32,16 @@ pub(super) fn compile_module(
     Ok(compiler.finish())
 }
 
+/// Mutable i32 globals imported from `env`, in import order.
+const GLOBAL_IMPORT_NAMES: [&str; 6] = [
+    "ACTIVE_ATTR_PTR",
+    "ACTIVE_UNIFORM_PTR",
+    "ACTIVE_VARYING_PTR",
+    "ACTIVE_PRIVATE_PTR",
+    "ACTIVE_TEXTURE_PTR",
+    "ACTIVE_FRAME_SP",
+];
+
 /// Internal compiler state
 struct Compiler<'a> {
     _backend: &'a WasmBackend,
@@ -37,6 +51,7 @@ struct Compiler<'a> {
     attribute_locations: &'a HashMap<String, u32>,
     uniform_locations: &'a HashMap<String, u32>,
     varying_locations: &'a HashMap<String, u32>,
+    varying_components: &'a HashMap<String, u32>,
     varying_types: &'a HashMap<String, (u8, u32)>,
     uniform_types: &'a HashMap<String, (u8, u32)>,
     attribute_types: &'a HashMap<String, (u8, u32)>,
@@ -68,6 +83,8 @@ struct Compiler<'a> {
     webgl_sampler_3d_idx: Option<u32>,
     /// Index of the emitted module-local helper function `__webgl_image_load`
     webgl_image_load_idx: Option<u32>,
+    /// Index of the host sampler import (if `host_sampling` is enabled)
+    webgpu_sample_idx: Option<u32>,
     /// Mapping of Naga math functions to their imported WASM function indices
     math_import_map: HashMap<naga::MathFunction, u32>,
     /// Index of the gl_debug4 import (if present)
@@ -78,6 +95,11 @@ struct Compiler<'a> {
 
     // Debug info (if enabled)
     debug_generator: Option<super::debug::DwarfGenerator>,
+
+    /// Basic block tracker (if coverage is enabled)
+    coverage: Option<super::coverage::CoverageBuilder>,
+    /// Constant tables laid out in the table buffer
+    constant_data: Option<super::constants::ConstantDataBuilder>,
 }
 
 impl<'a> Compiler<'a> {
@@ -87,9 +109,14 @@ impl<'a> Compiler<'a> {
         name: Option<&'a str>,
         function_registry: &'a super::functions::FunctionRegistry,
     ) -> Self {
-        // DWARF generation is currently a placeholder/stub in the backend.
-        // It is not used for coverage or runtime debugging.
-        let debug_generator = None;
+        // Debug builds carry a line table for symbolizing trap PCs
+        let debug_generator = backend.config.debug_shaders.then(|| {
+            let default_name = match config.stage {
+                naga::ShaderStage::Fragment => "shader.frag",
+                _ => "shader.vert",
+            };
+            super::debug::DwarfGenerator::new(name.unwrap_or(default_name))
+        });
 
         Self {
             _backend: backend,
@@ -99,6 +126,7 @@ impl<'a> Compiler<'a> {
             attribute_locations: config.attribute_locations,
             uniform_locations: config.uniform_locations,
             varying_locations: config.varying_locations,
+            varying_components: config.varying_components,
             varying_types: config.varying_types,
             uniform_types: config.uniform_types,
             attribute_types: config.attribute_types,
@@ -124,10 +152,13 @@ impl<'a> Compiler<'a> {
             webgl_sampler_2d_idx: None,
             webgl_sampler_3d_idx: None,
             webgl_image_load_idx: None,
+            webgpu_sample_idx: None,
             math_import_map: HashMap::new(),
             debug4_idx: None,
             inverse_mat2_idx: None,
             inverse_mat3_idx: None,
+            coverage: None,
+            constant_data: None,
             debug_generator,
         }
     }
@@ -347,7 +378,7 @@ impl<'a> Compiler<'a> {
 
         // 4. Load RGBA based on bytes-per-pixel and format
 
-        let gl_rgba8: i32 = 0x8058;
+        let gl_rgba8 = GL_RGBA8 as i32;
 
         // Push R channel
         func.instruction(&Instruction::LocalGet(l_format));
@@ -499,15 +530,24 @@ impl<'a> Compiler<'a> {
     }
 
     /// Emits a WASM function for texture sampling (ImageSample in Naga).
-    /// Handles 2D/3D dimensions, interpolation (Linear/Nearest), and Wrap modes.
-    /// Covered by: test/samplers/rgba8_unorm.test.js (2D/3D), rgba32f.test.js, r32f.test.js
+    /// Handles 2D/3D dimensions, interpolation (Linear/Nearest), Wrap modes,
+    /// texel offsets and explicit levels of detail. Only level 0 is sampled:
+    /// the level of detail selects between the minification and magnification
+    /// filter.
+    /// Covered by: test/samplers/rgba8_unorm.test.js (2D/3D), rgba32f.test.js, r32f.test.js,
+    /// test/textureGrad.test.js
     fn emit_sampler(&mut self, dim: naga::ImageDimension) -> u32 {
         let is_3d = dim == naga::ImageDimension::D3;
-        // Params: 0: texture_desc_addr, 1: sampler_desc_addr, 2: u, 3: v, [4: w]
-        let mut params = vec![ValType::I32, ValType::I32, ValType::F32, ValType::F32];
-        if is_3d {
-            params.push(ValType::F32);
-        }
+        let n = if is_3d { 3 } else { 2 };
+        // Params: 0: texture_desc_addr, 1: sampler_desc_addr, 2: u, 3: v, [4: w],
+        // then lod_kind (0 auto, 1 exact, 2 bias, 3 gradient), lod, the n components
+        // of ddx and ddy and the n texel offsets
+        let mut params = vec![ValType::I32, ValType::I32];
+        params.extend(vec![ValType::F32; n as usize]);
+        params.extend([ValType::I32, ValType::F32]);
+        params.extend(vec![ValType::F32; 2 * n as usize]);
+        params.extend(vec![ValType::I32; n as usize]);
+        let p_count = params.len() as u32;
 
         let type_index = self.type_count;
         self.type_count += 1;
@@ -520,7 +560,11 @@ impl<'a> Compiler<'a> {
         self.function_count += 1;
         self.functions.function(type_index);
 
-        let p_count = if is_3d { 5 } else { 4 };
+        let p_lod_kind = 2 + n;
+        let p_lod = 3 + n;
+        let p_ddx = 4 + n;
+        let p_ddy = 4 + 2 * n;
+        let p_offset = 4 + 3 * n;
         let mut func = Function::new(vec![
             (24, ValType::I32), // locals: width, height, ptr, depth, format, bpp, tx, ty, tz, addr, ws, wt, wr, layout, minf, magf, x0, y0, x1, y1, z0, z1, loop_cnt, temp_i
             (12, ValType::F32), // locals: res_r, res_g, res_b, res_a, wx, wy, wz, temp_f, weight, tmp_rg, tmp_ba, unused
@@ -562,6 +606,7 @@ impl<'a> Compiler<'a> {
         let l_wz = p_count + 30;
         let l_temp_f = p_count + 31;
         let l_weight = p_count + 32;
+        let l_lambda = p_count + 33;
 
         // 1. Load texture metadata from l_tex_desc
         {
@@ -635,31 +680,105 @@ impl<'a> Compiler<'a> {
             func.instruction(&Instruction::LocalSet(l_mag_filter));
         }
 
+        // 2b. Apply texel offsets: coord += offset / size
+        let sizes = [l_width, l_height, l_depth];
+        for axis in 0..n {
+            func.instruction(&Instruction::LocalGet(2 + axis));
+            func.instruction(&Instruction::LocalGet(p_offset + axis));
+            func.instruction(&Instruction::F32ConvertI32S);
+            func.instruction(&Instruction::LocalGet(sizes[axis as usize]));
+            func.instruction(&Instruction::F32ConvertI32S);
+            func.instruction(&Instruction::F32Div);
+            func.instruction(&Instruction::F32Add);
+            func.instruction(&Instruction::LocalSet(2 + axis));
+        }
+
+        // 2c. Level of detail of explicit levels and gradients, plus the sampler's bias
+        {
+            func.instruction(&Instruction::LocalGet(p_lod_kind));
+            func.instruction(&Instruction::I32Const(3));
+            func.instruction(&Instruction::I32Eq);
+            func.instruction(&Instruction::If(BlockType::Result(ValType::F32)));
+            // rho^2 = max(|ddx * size|^2, |ddy * size|^2)
+            for base in [p_ddx, p_ddy] {
+                for axis in 0..n {
+                    func.instruction(&Instruction::LocalGet(base + axis));
+                    func.instruction(&Instruction::LocalGet(sizes[axis as usize]));
+                    func.instruction(&Instruction::F32ConvertI32S);
+                    func.instruction(&Instruction::F32Mul);
+                    func.instruction(&Instruction::LocalTee(l_temp_f));
+                    func.instruction(&Instruction::LocalGet(l_temp_f));
+                    func.instruction(&Instruction::F32Mul);
+                    if axis > 0 {
+                        func.instruction(&Instruction::F32Add);
+                    }
+                }
+            }
+            func.instruction(&Instruction::F32Max);
+            // lambda = log2(rho^2) / 2, with log2 approximated from the float's bits
+            func.instruction(&Instruction::I32ReinterpretF32);
+            func.instruction(&Instruction::F32ConvertI32S);
+            func.instruction(&Instruction::F32Const(1.0 / (1 << 23) as f32));
+            func.instruction(&Instruction::F32Mul);
+            func.instruction(&Instruction::F32Const(127.0));
+            func.instruction(&Instruction::F32Sub);
+            func.instruction(&Instruction::F32Const(0.5));
+            func.instruction(&Instruction::F32Mul);
+            func.instruction(&Instruction::Else);
+            func.instruction(&Instruction::LocalGet(p_lod));
+            func.instruction(&Instruction::End);
+
+            func.instruction(&Instruction::LocalGet(l_sam_desc));
+            func.instruction(&Instruction::F32Load(wasm_encoder::MemArg {
+                offset: output_layout::TEX_LOD_BIAS_OFFSET,
+                align: 2,
+                memory_index: 0,
+            }));
+            func.instruction(&Instruction::F32Add);
+            func.instruction(&Instruction::LocalSet(l_lambda));
+        }
+
         // 3. Compute texel coords and weights
         {
             // Detect if linear filtering is requested
-            // 0x2601 = LINEAR
-            // 0x2701 = LINEAR_MIPMAP_NEAREST
-            // 0x2703 = LINEAR_MIPMAP_LINEAR
             let is_linear = |func: &mut Function, local: u32| {
                 func.instruction(&Instruction::LocalGet(local));
-                func.instruction(&Instruction::I32Const(0x2601)); // LINEAR
+                func.instruction(&Instruction::I32Const(GL_LINEAR as i32));
                 func.instruction(&Instruction::I32Eq);
 
                 func.instruction(&Instruction::LocalGet(local));
-                func.instruction(&Instruction::I32Const(0x2701)); // LINEAR_MIPMAP_NEAREST
+                func.instruction(&Instruction::I32Const(GL_LINEAR_MIPMAP_NEAREST as i32));
                 func.instruction(&Instruction::I32Eq);
                 func.instruction(&Instruction::I32Or);
 
                 func.instruction(&Instruction::LocalGet(local));
-                func.instruction(&Instruction::I32Const(0x2703)); // LINEAR_MIPMAP_LINEAR
+                func.instruction(&Instruction::I32Const(GL_LINEAR_MIPMAP_LINEAR as i32));
                 func.instruction(&Instruction::I32Eq);
                 func.instruction(&Instruction::I32Or);
             };
 
+            // With an explicit level of detail, lambda > 0 minifies. Without
+            // derivatives the implicit one is unknown, so either filter being
+            // linear filters linearly.
+            func.instruction(&Instruction::LocalGet(p_lod_kind));
+            func.instruction(&Instruction::I32Const(1));
+            func.instruction(&Instruction::I32Eq);
+            func.instruction(&Instruction::LocalGet(p_lod_kind));
+            func.instruction(&Instruction::I32Const(3));
+            func.instruction(&Instruction::I32Eq);
+            func.instruction(&Instruction::I32Or);
+            func.instruction(&Instruction::If(BlockType::Result(ValType::I32)));
+            is_linear(&mut func, l_min_filter);
+            is_linear(&mut func, l_mag_filter);
+            func.instruction(&Instruction::LocalGet(l_lambda));
+            func.instruction(&Instruction::F32Const(0.0));
+            func.instruction(&Instruction::F32Gt);
+            func.instruction(&Instruction::Select);
+            func.instruction(&Instruction::Else);
             is_linear(&mut func, l_mag_filter);
             is_linear(&mut func, l_min_filter);
             func.instruction(&Instruction::I32Or);
+            func.instruction(&Instruction::End);
 
             func.instruction(&Instruction::If(BlockType::Empty));
             let mut compute_linear = |coord_param: u32,
@@ -703,7 +822,7 @@ impl<'a> Compiler<'a> {
                 let mut apply_wrap = |local: u32, wrap_local: u32| {
                     // Detect wrap mode
                     func.instruction(&Instruction::LocalGet(wrap_local));
-                    func.instruction(&Instruction::I32Const(0x2901)); // GL_REPEAT
+                    func.instruction(&Instruction::I32Const(GL_REPEAT as i32));
                     func.instruction(&Instruction::I32Eq);
                     func.instruction(&Instruction::If(BlockType::Empty));
                     // Repeat: val = (val % size + size) % size
@@ -765,7 +884,7 @@ impl<'a> Compiler<'a> {
 
                     // Detect wrap mode
                     func.instruction(&Instruction::LocalGet(wrap_local));
-                    func.instruction(&Instruction::I32Const(0x2901)); // GL_REPEAT
+                    func.instruction(&Instruction::I32Const(GL_REPEAT as i32));
                     func.instruction(&Instruction::I32Eq);
                     func.instruction(&Instruction::If(BlockType::Empty));
                     // Repeat: val = (val % size + size) % size
@@ -997,7 +1116,7 @@ impl<'a> Compiler<'a> {
         // 5. Load and accumulate
         // RGBA32UI - Covered by test/samplers/rgba32ui.test.js
         func.instruction(&Instruction::LocalGet(l_format));
-        func.instruction(&Instruction::I32Const(0x8D70)); // RGBA32UI
+        func.instruction(&Instruction::I32Const(GL_RGBA32UI as i32));
         func.instruction(&Instruction::I32Eq);
         func.instruction(&Instruction::If(wasm_encoder::BlockType::Empty));
         // Integer formats: take raw bits from first tap only (Nearest)
@@ -1019,7 +1138,7 @@ impl<'a> Compiler<'a> {
         func.instruction(&Instruction::Else);
         // R32UI - Covered by test/samplers/r32ui.test.js
         func.instruction(&Instruction::LocalGet(l_format));
-        func.instruction(&Instruction::I32Const(0x8236)); // R32UI
+        func.instruction(&Instruction::I32Const(GL_R32UI as i32));
         func.instruction(&Instruction::I32Eq);
         func.instruction(&Instruction::If(wasm_encoder::BlockType::Empty));
         func.instruction(&Instruction::LocalGet(l_loop_cnt));
@@ -1038,10 +1157,10 @@ impl<'a> Compiler<'a> {
         func.instruction(&Instruction::Else);
         // RGBA8 - Covered by test/samplers/rgba8_unorm.test.js
         func.instruction(&Instruction::LocalGet(l_format));
-        func.instruction(&Instruction::I32Const(0x8058)); // RGBA8
+        func.instruction(&Instruction::I32Const(GL_RGBA8 as i32));
         func.instruction(&Instruction::I32Eq);
         func.instruction(&Instruction::LocalGet(l_format));
-        func.instruction(&Instruction::I32Const(0x1908)); // RGBA
+        func.instruction(&Instruction::I32Const(GL_RGBA as i32));
         func.instruction(&Instruction::I32Eq);
         func.instruction(&Instruction::I32Or);
         func.instruction(&Instruction::If(wasm_encoder::BlockType::Empty));
@@ -1064,7 +1183,7 @@ impl<'a> Compiler<'a> {
         func.instruction(&Instruction::Else);
         // RGBA32F - Covered by test/samplers/rgba32f.test.js
         func.instruction(&Instruction::LocalGet(l_format));
-        func.instruction(&Instruction::I32Const(0x8814)); // RGBA32F
+        func.instruction(&Instruction::I32Const(GL_RGBA32F as i32));
         func.instruction(&Instruction::I32Eq);
         func.instruction(&Instruction::If(wasm_encoder::BlockType::Empty));
         for i in 0..4 {
@@ -1083,7 +1202,7 @@ impl<'a> Compiler<'a> {
         func.instruction(&Instruction::Else);
         // R32F - Covered by test/samplers/r32f.test.js
         func.instruction(&Instruction::LocalGet(l_format));
-        func.instruction(&Instruction::I32Const(0x822E)); // R32F
+        func.instruction(&Instruction::I32Const(GL_R32F as i32));
         func.instruction(&Instruction::I32Eq);
         func.instruction(&Instruction::If(wasm_encoder::BlockType::Empty));
         func.instruction(&Instruction::LocalGet(l_addr));
@@ -1131,9 +1250,10 @@ impl<'a> Compiler<'a> {
 
     fn compile(&mut self) -> Result<(), BackendError> {
         // Import memory from host
+        let (memory_module, memory_field) = super::abi::MEMORY_IMPORT;
         self.imports.import(
-            "env",
-            "memory",
+            memory_module,
+            memory_field,
             MemoryType {
                 minimum: 100, // 6.4MB
                 maximum: None,
@@ -1182,7 +1302,6 @@ impl<'a> Compiler<'a> {
             (naga::MathFunction::Atanh, "gl_atanh", 1),
         ];
 
-        // TODO: Many of these math functions are missing dedicated precision and edge-case tests.
         for (func, name, param_count) in math_funcs {
             let type_idx = self.type_count;
             self.type_count += 1;
@@ -1220,29 +1339,41 @@ impl<'a> Compiler<'a> {
         self.inverse_mat3_idx = Some(self.import_fn_count);
         self.import_fn_count += 1;
 
-        // Emit the module-local texture sampling helpers
+        // Emit the module-local texture sampling helpers, or import the host sampler
         let (need_2d, need_3d) = self.has_image_sampling();
-        if need_2d {
-            self.webgl_sampler_2d_idx = Some(self.emit_sampler(naga::ImageDimension::D2));
-        }
-        if need_3d {
-            self.webgl_sampler_3d_idx = Some(self.emit_sampler(naga::ImageDimension::D3));
+        if self._backend.config.host_sampling {
+            if need_2d || need_3d {
+                // Signature: (texture_desc: i32, sampler_desc: i32, u, v, w: f32,
+                //   level_kind: i32, level: f32, ddx_u, ddx_v, ddy_u, ddy_v: f32) -> rgba_ptr: i32
+                let type_idx = self.type_count;
+                self.type_count += 1;
+                let mut params = vec![ValType::I32, ValType::I32];
+                params.extend([ValType::F32; 3]);
+                params.extend([ValType::I32, ValType::F32]);
+                params.extend([ValType::F32; 4]);
+                self.types.ty().function(params, vec![ValType::I32]);
+                self.imports.import(
+                    "env",
+                    "webgpu_texture_sample",
+                    wasm_encoder::EntityType::Function(type_idx),
+                );
+                self.webgpu_sample_idx = Some(self.import_fn_count);
+                self.import_fn_count += 1;
+            }
+        } else {
+            if need_2d {
+                self.webgl_sampler_2d_idx = Some(self.emit_sampler(naga::ImageDimension::D2));
+            }
+            if need_3d {
+                self.webgl_sampler_3d_idx = Some(self.emit_sampler(naga::ImageDimension::D3));
+            }
         }
 
         if self.has_image_load() {
             self.emit_image_load_helper();
         }
 
-        let global_names = [
-            "ACTIVE_ATTR_PTR",
-            "ACTIVE_UNIFORM_PTR",
-            "ACTIVE_VARYING_PTR",
-            "ACTIVE_PRIVATE_PTR",
-            "ACTIVE_TEXTURE_PTR",
-            "ACTIVE_FRAME_SP",
-        ];
-
-        for name in global_names.iter() {
+        for name in GLOBAL_IMPORT_NAMES.iter() {
             self.imports.import(
                 "env",
                 name,
@@ -1255,7 +1386,24 @@ impl<'a> Compiler<'a> {
         }
 
         // Number of global imports we registered; used to offset module-local global indices
-        let _global_import_count = global_names.len() as u32;
+        let global_import_count = GLOBAL_IMPORT_NAMES.len() as u32;
+
+        // The coverage counter address is the first module-local global
+        let config = &self._backend.config;
+        if config.coverage || config.profile {
+            let builder = super::coverage::CoverageBuilder::new(global_import_count);
+            self.coverage = Some(if config.profile {
+                builder.split_lines()
+            } else {
+                builder
+            });
+        }
+
+        // The constant table base follows it
+        let constant_global_idx = global_import_count + self.coverage.is_some() as u32;
+        self.constant_data = Some(super::constants::ConstantDataBuilder::new(
+            constant_global_idx,
+        ));
 
         // Calculate global offsets per address space
         let mut varying_offset = 32; // User varyings start after Position and PointSize (16+16=32)
@@ -1345,7 +1493,8 @@ impl<'a> Compiler<'a> {
                         } else if let Some(&loc) = self.attribute_locations.get(name) {
                             output_layout::compute_input_offset(loc, naga::ShaderStage::Vertex)
                         } else if let Some(&loc) = self.varying_locations.get(name) {
-                            output_layout::compute_input_offset(loc, naga::ShaderStage::Fragment)
+                            let component = self.varying_components.get(name).copied().unwrap_or(0);
+                            output_layout::compute_varying_offset(loc, component)
                         } else if self.stage == naga::ShaderStage::Vertex {
                             let o = varying_offset;
                             varying_offset += size;
@@ -1516,7 +1665,8 @@ impl<'a> Compiler<'a> {
 
         // Attempt to discover local variables that are initialized from globals (pointer origin tracing).
         // We scan the function body for Store statements that assign a global-derived pointer to a local.
-        // Make this robust to wrapped pointers like AccessIndex/Access and wrapped values (As/Load/AccessIndex/Swizzle).
+        // Make this robust to wrapped pointers like AccessIndex/Access and wrapped values (Load/AccessIndex/Swizzle).
+        // A conversion (As) ends the walk: the local no longer holds the global's scalar kind.
         let mut local_origins: HashMap<
             naga::Handle<naga::LocalVariable>,
             naga::Handle<naga::GlobalVariable>,
@@ -1565,9 +1715,6 @@ impl<'a> Compiler<'a> {
                             naga::Expression::Access { base: b, .. } => {
                                 cur = b;
                             }
-                            naga::Expression::As { expr, .. } => {
-                                cur = expr;
-                            }
                             naga::Expression::Swizzle { vector, .. } => {
                                 cur = vector;
                             }
@@ -1580,30 +1727,28 @@ impl<'a> Compiler<'a> {
             }
         }
 
-        let mut call_result_locals = HashMap::new();
         let mut locals_types = vec![];
         let mut next_local_idx = params.len() as u32;
 
-        // Track CallResult expressions and their declaration indices
-        let mut call_result_decl_indices: Vec<(naga::Handle<naga::Expression>, u32, usize)> =
-            Vec::new();
-
-        // Map CallResult expressions to WASM locals (place them after scratch F32 region)
+        // Map CallResult expressions to WASM locals, sharing locals between
+        // results whose live ranges do not overlap
+        let mut call_results = Vec::new();
         for (handle, expr) in func.expressions.iter() {
             if let naga::Expression::CallResult(func_handle) = expr {
                 let called_func = &self.module.functions[*func_handle];
                 if let Some(ret) = &called_func.result {
                     let types = super::types::naga_to_wasm_types(&self.module.types[ret.ty].inner)?;
-                    let decl_idx = next_local_idx;
-                    let num_components = types.len();
-                    call_result_decl_indices.push((handle, decl_idx, num_components));
-                    for vtype in types {
-                        locals_types.push((1, vtype));
-                    }
-                    next_local_idx += num_components as u32;
+                    call_results.push((handle, types));
                 }
             }
         }
+        let allocated =
+            super::locals::allocate_call_result_locals(func, &call_results, next_local_idx);
+        let call_result_locals = allocated.bases;
+        for vtype in allocated.local_types {
+            locals_types.push((1, vtype));
+            next_local_idx += 1;
+        }
 
         // Add explicit swap locals at the END to preserve existing indices
         // These will be used by store_components_to_memory instead of scanning
@@ -1615,9 +1760,10 @@ impl<'a> Compiler<'a> {
         locals_types.push((1, ValType::F32)); // swap_f32_local
         next_local_idx += 1;
 
-        // Detect need for Float Modulo or Inverse swap locals
+        // Detect need for Float Modulo, Inverse or Normalize swap locals
         let mut uses_float_modulo = false;
         let mut uses_inverse = false;
+        let mut uses_normalize = false;
 
         for (_handle, expr) in func.expressions.iter() {
             match expr {
@@ -1647,11 +1793,17 @@ impl<'a> Compiler<'a> {
                 } => {
                     uses_inverse = true;
                 }
+                naga::Expression::Math {
+                    fun: naga::MathFunction::Normalize,
+                    ..
+                } => {
+                    uses_normalize = true;
+                }
                 _ => {}
             }
         }
 
-        let needs_secondary_f32 = uses_float_modulo || uses_inverse;
+        let needs_secondary_f32 = uses_float_modulo || uses_inverse || uses_normalize;
 
         let swap_f32_local_2 = if needs_secondary_f32 {
             let idx = next_local_idx;
@@ -1708,12 +1860,6 @@ impl<'a> Compiler<'a> {
             }
         }
 
-        // wasm-encoder preserves local declaration order.
-        // Simply map handle to declaration index.
-        for (handle, decl_idx, _num_components) in call_result_decl_indices {
-            call_result_locals.insert(handle, decl_idx);
-        }
-
         // Create function body
         let mut wasm_func = Function::new(locals_types);
 
@@ -1726,6 +1872,20 @@ impl<'a> Compiler<'a> {
                     wasm_func.instruction(&Instruction::LocalGet(2));
                     wasm_func
                         .instruction(&Instruction::GlobalSet(output_layout::VARYING_PTR_GLOBAL));
+                    // Nothing else writes the position slot; don't leave the previous vertex's there
+                    if !output_layout::vertex_writes_position(self.module, Some(&ep.name)) {
+                        for (i, value) in output_layout::DEFAULT_POSITION.into_iter().enumerate() {
+                            wasm_func.instruction(&Instruction::GlobalGet(
+                                output_layout::VARYING_PTR_GLOBAL,
+                            ));
+                            wasm_func.instruction(&Instruction::F32Const(value));
+                            wasm_func.instruction(&Instruction::F32Store(wasm_encoder::MemArg {
+                                offset: i as u64 * 4,
+                                align: 2,
+                                memory_index: 0,
+                            }));
+                        }
+                    }
                 }
                 naga::ShaderStage::Fragment => {
                     // Argument 0 is varying_in_ptr. Sync it to VARYING_PTR_GLOBAL (offset by imports).
@@ -1791,6 +1951,8 @@ impl<'a> Compiler<'a> {
                             stage,
                             debug_shaders: self._backend.config.debug_shaders,
                             debug_step_idx: self.debug_step_idx,
+                            coverage: None,
+                            constant_data: self.constant_data.as_mut(),
                             math_import_map: &self.math_import_map,
                             typifier: &typifier,
                             naga_function_map: &self.naga_function_map,
@@ -1799,6 +1961,7 @@ impl<'a> Compiler<'a> {
                             attribute_locations: self.attribute_locations,
                             uniform_locations: self.uniform_locations,
                             varying_locations: self.varying_locations,
+                            varying_components: self.varying_components,
                             varying_types: self.varying_types,
                             uniform_types: self.uniform_types,
                             attribute_types: self.attribute_types,
@@ -1814,6 +1977,7 @@ impl<'a> Compiler<'a> {
                             webgl_sampler_2d_idx: self.webgl_sampler_2d_idx,
                             webgl_sampler_3d_idx: self.webgl_sampler_3d_idx,
                             webgl_image_load_idx: self.webgl_image_load_idx,
+                            webgpu_sample_idx: self.webgpu_sample_idx,
                             frame_temp_idx: Some(frame_temp_local),
                             sample_f32_locals,
                             inverse_scratch_base,
@@ -1861,6 +2025,8 @@ impl<'a> Compiler<'a> {
             stage,
             debug_shaders: self._backend.config.debug_shaders,
             debug_step_idx: self.debug_step_idx,
+            coverage: self.coverage.as_mut(),
+            constant_data: self.constant_data.as_mut(),
             math_import_map: &self.math_import_map,
             typifier: &typifier,
             naga_function_map: &self.naga_function_map,
@@ -1869,6 +2035,7 @@ impl<'a> Compiler<'a> {
             attribute_locations: self.attribute_locations,
             uniform_locations: self.uniform_locations,
             varying_locations: self.varying_locations,
+            varying_components: self.varying_components,
             varying_types: self.varying_types,
             uniform_types: self.uniform_types,
             attribute_types: self.attribute_types,
@@ -1885,6 +2052,7 @@ impl<'a> Compiler<'a> {
             webgl_sampler_2d_idx: self.webgl_sampler_2d_idx,
             webgl_sampler_3d_idx: self.webgl_sampler_3d_idx,
             webgl_image_load_idx: self.webgl_image_load_idx,
+            webgpu_sample_idx: self.webgpu_sample_idx,
             frame_temp_idx: Some(frame_temp_local),
             sample_f32_locals,
             inverse_scratch_base,
@@ -1896,9 +2064,7 @@ impl<'a> Compiler<'a> {
             block_stack: Vec::new(),
         };
 
-        for (stmt, span) in func.body.span_iter() {
-            super::control_flow::translate_statement(stmt, span, &mut ctx)?;
-        }
+        super::control_flow::translate_block(&func.body, &mut ctx)?;
 
         if let Some(_ep) = entry_point {
             // Tier 3: Results are already stored in memory via shared globals. Return void.
@@ -1938,8 +2104,13 @@ impl<'a> Compiler<'a> {
             None => true, // Default to first available if not specified
         };
 
-        if is_target && self.exported_names.insert("main".to_string()) {
-            self.exports.export("main", ExportKind::Func, func_idx);
+        if is_target {
+            let stage_name = super::abi::entry_export_name(entry_point.stage);
+            for name in [super::abi::MAIN_EXPORT, stage_name] {
+                if self.exported_names.insert(name.to_string()) {
+                    self.exports.export(name, ExportKind::Func, func_idx);
+                }
+            }
         }
 
         self.entry_points.insert(entry_point.name.clone(), func_idx);
@@ -1953,7 +2124,53 @@ impl<'a> Compiler<'a> {
         Ok(())
     }
 
-    fn finish(self) -> WasmModule {
+    fn finish(mut self) -> WasmModule {
+        // Allocate coverage counters now that all blocks are known
+        let mut coverage = self.coverage.take().map(|builder| {
+            let coverage = builder.finish();
+            self.globals.global(
+                wasm_encoder::GlobalType {
+                    val_type: ValType::I32,
+                    mutable: false,
+                    shared: false,
+                },
+                &wasm_encoder::ConstExpr::i32_const(coverage.counters_ptr() as i32),
+            );
+            coverage
+        });
+
+        // Relocate the constant tables into a reserved buffer
+        let constant_data = self
+            .constant_data
+            .take()
+            .and_then(|builder| builder.finish())
+            .inspect(|data| {
+                self.globals.global(
+                    wasm_encoder::GlobalType {
+                        val_type: ValType::I32,
+                        mutable: false,
+                        shared: false,
+                    },
+                    &wasm_encoder::ConstExpr::i32_const(data.base_ptr() as i32),
+                );
+            });
+
+        // Declare the ABI version as the last global
+        let abi_global = GLOBAL_IMPORT_NAMES.len() as u32 + self.globals.len();
+        self.globals.global(
+            wasm_encoder::GlobalType {
+                val_type: ValType::I32,
+                mutable: false,
+                shared: false,
+            },
+            &wasm_encoder::ConstExpr::i32_const(super::abi::SHADER_ABI_VERSION as i32),
+        );
+        self.exports.export(
+            super::abi::ABI_VERSION_EXPORT,
+            ExportKind::Global,
+            abi_global,
+        );
+
         // Assemble WASM module
         let mut module = wasm_encoder::Module::new();
 
@@ -1996,23 +2213,25 @@ impl<'a> Compiler<'a> {
             module.section(&names);
         }
 
-        // Add DWARF debug information if enabled
-        let dwarf_bytes = if let Some(debug_gen) = self.debug_generator {
-            let dwarf_data = debug_gen.finish();
+        // Debug sections go into the module, or with split_debug into a
+        // separate artifact linked to it by build ID
+        let mut debug_module = self
+            ._backend
+            .config
+            .split_debug
+            .then(wasm_encoder::Module::new);
 
-            // Add custom sections for DWARF
-            for (name, data) in dwarf_data {
-                let custom = CustomSection {
-                    name: std::borrow::Cow::Borrowed(&name),
-                    data: std::borrow::Cow::Borrowed(&data),
-                };
-                module.section(&custom);
-            }
-
-            Some(vec![]) // Placeholder for separate DWARF file (future use)
-        } else {
-            None
-        };
+        // Embed the GLSL source so decompiled output can quote it per line
+        if self._backend.config.debug_shaders {
+            let custom = CustomSection {
+                name: std::borrow::Cow::Borrowed(super::debug::SOURCE_SECTION_NAME),
+                data: std::borrow::Cow::Borrowed(self._source.as_bytes()),
+            };
+            debug_module
+                .as_mut()
+                .unwrap_or(&mut module)
+                .section(&custom);
+        }
 
         // Generate JS stub if enabled
         let debug_stub = if self._backend.config.debug_shaders {
@@ -2023,7 +2242,45 @@ impl<'a> Compiler<'a> {
             None
         };
 
-        let wasm_bytes = module.finish();
+        let mut wasm_bytes = module.finish();
+
+        // DWARF line tables address the finished code section, so they are
+        // appended after it
+        if let (Some(debug_gen), Some(debug_step_idx)) = (self.debug_generator, self.debug_step_idx)
+        {
+            match debug_gen.finish(&wasm_bytes, debug_step_idx) {
+                Ok(sections) => {
+                    for (name, data) in sections {
+                        match debug_module.as_mut() {
+                            Some(debug_module) => {
+                                debug_module.section(&CustomSection {
+                                    name: std::borrow::Cow::Borrowed(&name),
+                                    data: std::borrow::Cow::Borrowed(&data),
+                                });
+                            }
+                            None => {
+                                super::debug::append_custom_section(&mut wasm_bytes, &name, &data)
+                            }
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!("Shader line table unavailable: {}", e),
+            }
+        }
+
+        let dwarf_bytes = debug_module.map(|debug_module| {
+            let mut dwarf_bytes = debug_module.finish();
+            let build_id = super::debug::compute_build_id(&wasm_bytes, &dwarf_bytes);
+            super::debug::append_build_id(&mut wasm_bytes, &build_id);
+            super::debug::append_build_id(&mut dwarf_bytes, &build_id);
+            dwarf_bytes
+        });
+
+        if let Some(coverage) = coverage.as_mut().filter(|_| self._backend.config.profile) {
+            if let Err(e) = coverage.attach_profile(&wasm_bytes, self._source) {
+                tracing::warn!("Shader profile unavailable: {}", e);
+            }
+        }
 
         // No debug dump: finished compilation
         // Attempt to write WAT text for easier debugging
@@ -2046,6 +2303,20 @@ impl<'a> Compiler<'a> {
             self.entry_points.len()
         );
 
+        let (varyings, shading_rate) = if self.stage == naga::ShaderStage::Fragment {
+            (
+                output_layout::fragment_varyings(
+                    self.module,
+                    self.entry_point_name,
+                    self.varying_locations,
+                    self.varying_components,
+                ),
+                output_layout::fragment_shading_rate(self.module, self.entry_point_name),
+            )
+        } else {
+            (Vec::new(), output_layout::ShadingRate::PerPixel)
+        };
+
         WasmModule {
             wasm_bytes,
             dwarf_bytes,
@@ -2053,6 +2324,10 @@ impl<'a> Compiler<'a> {
             entry_points: self.entry_points,
             memory_layout: MemoryLayout::default(),
             table_index: 0,
+            coverage: coverage.map(std::sync::Arc::new),
+            constant_data: constant_data.map(std::sync::Arc::new),
+            varyings,
+            shading_rate,
         }
     }
 }
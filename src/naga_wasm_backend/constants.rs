@@ -0,0 +1,212 @@
+//! Table buffer for constant arrays indexed at runtime.
+//!
+//! Expressions are translated lazily, so a dynamically indexed constant array
+//! (a Poisson disk kernel, a lookup table) would otherwise need every element
+//! rematerialized as `const` instructions at each access. Instead, the first
+//! dynamic access lays the array out in a table buffer and later accesses
+//! load from it.
+//!
+//! Offsets are assigned relative to the buffer while functions are
+//! translated and read through a module-local global holding its base. Once
+//! the module is finished the tables are copied into a heap buffer in linear
+//! memory and the global is set to its address. The module carries no data
+//! segment, so instantiating it writes nothing, but the address is baked into
+//! its bytes: the buffer must outlive the program, and modules with tables are
+//! never written to the persistent shader cache.
+
+use naga::{Expression, Handle, Literal, TypeInner};
+use std::collections::HashMap;
+
+/// Collects constant tables while a module is being translated.
+#[derive(Debug, Default)]
+pub struct ConstantDataBuilder {
+    /// Index of the module-local global holding the table base address
+    pub global_idx: u32,
+    /// Segment offset of each table laid out so far
+    offsets: HashMap<Handle<naga::Constant>, u32>,
+    /// Constants that cannot be laid out as a table
+    rejected: Vec<Handle<naga::Constant>>,
+    bytes: Vec<u8>,
+}
+
+impl ConstantDataBuilder {
+    /// Create a builder whose tables are addressed through `global_idx`.
+    pub fn new(global_idx: u32) -> Self {
+        Self {
+            global_idx,
+            ..Self::default()
+        }
+    }
+
+    /// Segment offset of constant `handle`, laying it out on first use.
+    ///
+    /// Only arrays of scalars, vectors and matrices with 32-bit components
+    /// are tables; their elements are packed like memory-backed arrays, one
+    /// 4-byte word per component. Returns `None` for anything else.
+    pub fn table_offset(
+        &mut self,
+        module: &naga::Module,
+        handle: Handle<naga::Constant>,
+    ) -> Option<u32> {
+        if let Some(&offset) = self.offsets.get(&handle) {
+            return Some(offset);
+        }
+        if self.rejected.contains(&handle) {
+            return None;
+        }
+
+        let constant = &module.constants[handle];
+        let words = match module.types[constant.ty].inner {
+            TypeInner::Array {
+                base,
+                size: naga::ArraySize::Constant(count),
+                ..
+            } if is_table_element(&module.types[base].inner) => {
+                let expected = count.get()
+                    * super::types::component_count(&module.types[base].inner, &module.types);
+                let mut words = Vec::new();
+                let complete = flatten(module, constant.init, &mut words);
+                (complete && words.len() as u32 == expected).then_some(words)
+            }
+            _ => None,
+        };
+        let Some(words) = words else {
+            self.rejected.push(handle);
+            return None;
+        };
+
+        let offset = self.bytes.len() as u32;
+        for word in words {
+            self.bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        self.offsets.insert(handle, offset);
+        Some(offset)
+    }
+
+    /// Copy the tables into their buffer, or `None` if no table was used.
+    pub fn finish(self) -> Option<ConstantData> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        let buffer = self
+            .bytes
+            .chunks_exact(4)
+            .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+            .collect();
+        Some(ConstantData { buffer })
+    }
+}
+
+/// Constant tables of a finished module.
+///
+/// The module reads the tables from `buffer`, so the buffer must stay alive
+/// (and in place) for as long as the module may run.
+#[derive(Debug)]
+pub struct ConstantData {
+    buffer: Box<[u32]>,
+}
+
+impl ConstantData {
+    /// Linear memory address the tables are relocated to.
+    pub fn base_ptr(&self) -> u32 {
+        self.buffer.as_ptr() as usize as u32
+    }
+
+    /// The tables, one word per component.
+    pub fn words(&self) -> &[u32] {
+        &self.buffer
+    }
+}
+
+fn is_table_element(inner: &TypeInner) -> bool {
+    match *inner {
+        TypeInner::Scalar(scalar)
+        | TypeInner::Vector { scalar, .. }
+        | TypeInner::Matrix { scalar, .. } => scalar.width == 4,
+        _ => false,
+    }
+}
+
+/// Append the components of constant expression `expr` as 32-bit words.
+/// Returns false if it contains anything other than plain 32-bit values.
+fn flatten(module: &naga::Module, expr: Handle<Expression>, words: &mut Vec<u32>) -> bool {
+    match module.global_expressions[expr] {
+        Expression::Literal(literal) => {
+            let word = match literal {
+                Literal::F32(f) => f.to_bits(),
+                Literal::I32(i) => i as u32,
+                Literal::U32(u) => u,
+                Literal::Bool(b) => b as u32,
+                _ => return false,
+            };
+            words.push(word);
+            true
+        }
+        Expression::Constant(handle) => flatten(module, module.constants[handle].init, words),
+        Expression::Compose { ref components, .. } => components
+            .iter()
+            .all(|&component| flatten(module, component, words)),
+        Expression::Splat { size, value } => {
+            let start = words.len();
+            if !flatten(module, value, words) {
+                return false;
+            }
+            let word = words[start..].to_vec();
+            for _ in 1..super::types::vector_component_count(size) {
+                words.extend_from_slice(&word);
+            }
+            true
+        }
+        Expression::ZeroValue(ty) => {
+            let count =
+                super::types::component_count(&module.types[ty].inner, &module.types) as usize;
+            words.resize(words.len() + count, 0);
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_are_packed_and_reused() {
+        let module = naga::front::wgsl::parse_str(
+            "const KERNEL = array<vec2<f32>, 3>(vec2(0.5, -0.5), vec2(1.0), vec2<f32>());
+            const SIGNS = array<i32, 2>(-1, 1);
+            const SCALE: f32 = 2.0;",
+        )
+        .unwrap();
+        let handle = |name: &str| {
+            module
+                .constants
+                .iter()
+                .find(|(_, c)| c.name.as_deref() == Some(name))
+                .unwrap()
+                .0
+        };
+
+        let mut builder = ConstantDataBuilder::new(3);
+        let kernel = builder.table_offset(&module, handle("KERNEL"));
+        let signs = builder.table_offset(&module, handle("SIGNS"));
+        let scale = builder.table_offset(&module, handle("SCALE"));
+        let again = builder.table_offset(&module, handle("KERNEL"));
+        let data = builder.finish().unwrap();
+
+        assert_eq!(
+            (kernel, signs, scale, again),
+            (Some(0), Some(24), None, Some(0))
+        );
+        assert_eq!(
+            data.words(),
+            [0.5f32, -0.5, 1.0, 1.0, 0.0, 0.0]
+                .iter()
+                .map(|f| f.to_bits())
+                .chain([-1i32 as u32, 1])
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(data.base_ptr() % 4, 0);
+    }
+}
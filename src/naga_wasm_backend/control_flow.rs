@@ -36,6 +36,28 @@ fn get_output_destination(binding: &naga::Binding, ctx: &TranslationContext) ->
     output_layout::compute_output_destination(binding, ctx.stage)
 }
 
+/// Output destination of an entry point result struct member.
+///
+/// Vertex outputs the program assigned a packed slot to are found by name;
+/// everything else falls back to [`get_output_destination`].
+fn get_member_output_destination(
+    member: &naga::StructMember,
+    ctx: &TranslationContext,
+) -> (u32, u32) {
+    let Some(binding) = &member.binding else {
+        return (0, 0);
+    };
+    if let (naga::ShaderStage::Vertex, naga::Binding::Location { .. }, Some(name)) =
+        (ctx.stage, binding, &member.name)
+    {
+        if let Some(&location) = ctx.varying_locations.get(name) {
+            let component = ctx.varying_components.get(name).copied().unwrap_or(0);
+            return output_layout::compute_varying_offset(location, component);
+        }
+    }
+    get_output_destination(binding, ctx)
+}
+
 /// Helper function to store components to memory.
 ///
 /// # Parameters
@@ -120,18 +142,58 @@ fn store_single_value_to_output(
     store_components_to_memory(offset, base_ptr, num_components, is_int, ctx);
 }
 
+/// Translate a statement list that starts a new basic block.
+pub fn translate_block(
+    block: &naga::Block,
+    ctx: &mut TranslationContext,
+) -> Result<(), BackendError> {
+    if let Some(coverage) = ctx.coverage.as_deref_mut() {
+        coverage.end_block();
+    }
+    for (s, s_span) in block.span_iter() {
+        translate_statement(s, s_span, ctx)?;
+    }
+    Ok(())
+}
+
 pub fn translate_statement(
     stmt: &naga::Statement,
     span: &naga::Span,
     ctx: &mut TranslationContext,
 ) -> Result<(), BackendError> {
-    // Calculate line number for debug
-    let line = if let Some(_debug_step_idx) = ctx.debug_step_idx {
+    // Calculate line number for debug and coverage
+    let line = if ctx.debug_step_idx.is_some() || ctx.coverage.is_some() {
         span.location(ctx.source).line_number as i32
     } else {
         0
     };
 
+    // Control statements end the current basic block; whatever follows
+    // them (or runs inside them) is counted separately.
+    let ends_block = matches!(
+        stmt,
+        naga::Statement::Block(_)
+            | naga::Statement::Loop { .. }
+            | naga::Statement::Switch { .. }
+            | naga::Statement::If { .. }
+            | naga::Statement::Return { .. }
+            | naga::Statement::Kill
+            | naga::Statement::Break
+            | naga::Statement::Continue
+    );
+    if let Some(coverage) = ctx.coverage.as_deref_mut() {
+        // Same heuristic as debug_step: line 1 of a #version shader is not a statement
+        let skip = line <= 1 && ctx.source.trim_start().starts_with("#version");
+        let opened = coverage.statement(if skip { 0 } else { line as u32 });
+        let global_idx = coverage.global_idx;
+        if ends_block {
+            coverage.end_block();
+        }
+        if let Some(block) = opened {
+            super::coverage::emit_increment(ctx.wasm_func, global_idx, block);
+        }
+    }
+
     let is_call = matches!(stmt, naga::Statement::Call { .. });
 
     // Emit debug step for non-call statements
@@ -169,9 +231,7 @@ pub fn translate_statement(
                 .instruction(&Instruction::Block(wasm_encoder::BlockType::Empty));
 
             // Translate all statements in the block
-            for (s, s_span) in block.span_iter() {
-                translate_statement(s, s_span, ctx)?;
-            }
+            translate_block(block, ctx)?;
 
             // End the block
             ctx.wasm_func.instruction(&Instruction::End);
@@ -215,9 +275,7 @@ pub fn translate_statement(
             });
 
             // 4. Body
-            for (s, s_span) in body.span_iter() {
-                translate_statement(s, s_span, ctx)?;
-            }
+            translate_block(body, ctx)?;
 
             // Close body wrapper block
             ctx.wasm_func.instruction(&Instruction::End);
@@ -225,9 +283,7 @@ pub fn translate_statement(
 
             // 5. Continuing
             // Now we are at depth 0 relative to LoopHeader
-            for (s, s_span) in continuing.span_iter() {
-                translate_statement(s, s_span, ctx)?;
-            }
+            translate_block(continuing, ctx)?;
 
             // 6. Break if
             if let Some(break_cond) = break_if {
@@ -374,9 +430,7 @@ pub fn translate_statement(
                 ctx.block_stack.pop();
 
                 // Body statements
-                for (s, s_span) in case.body.span_iter() {
-                    translate_statement(s, s_span, ctx)?;
-                }
+                translate_block(&case.body, ctx)?;
 
                 // Handle fallthrough
                 if !case.fall_through {
@@ -576,12 +630,8 @@ pub fn translate_statement(
                                     );
 
                                     // Determine offset and base pointer based on binding and shader stage
-                                    let (offset, base_ptr) = if let Some(binding) = &member.binding
-                                    {
-                                        get_output_destination(binding, ctx)
-                                    } else {
-                                        (0, 0)
-                                    };
+                                    let (offset, base_ptr) =
+                                        get_member_output_destination(member, ctx);
 
                                     store_components_to_memory(
                                         offset,
@@ -652,20 +702,22 @@ pub fn translate_statement(
             // Push If label to track stack depth
             ctx.block_stack.push(super::BlockLabel::If);
 
-            for (s, s_span) in accept.span_iter() {
-                translate_statement(s, s_span, ctx)?;
-            }
+            translate_block(accept, ctx)?;
             if !reject.is_empty() {
                 ctx.wasm_func.instruction(&Instruction::Else);
                 // Note: Else block shares the same stack depth/scope as If block relative to outside
-                for (s, s_span) in reject.span_iter() {
-                    translate_statement(s, s_span, ctx)?;
-                }
+                translate_block(reject, ctx)?;
             }
             ctx.wasm_func.instruction(&Instruction::End);
             ctx.block_stack.pop();
         }
         _ => {}
     }
+
+    if ends_block {
+        if let Some(coverage) = ctx.coverage.as_deref_mut() {
+            coverage.end_block();
+        }
+    }
     Ok(())
 }
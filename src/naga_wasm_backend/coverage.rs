@@ -0,0 +1,408 @@
+//! Basic-block coverage instrumentation for generated shader WASM.
+//!
+//! With [`WasmBackendConfig::coverage`](super::WasmBackendConfig) enabled, the
+//! backend starts a new counter at every basic block (function entry, each
+//! branch of an `if`/`switch`, loop bodies and the code following any control
+//! statement) and emits an `i32` increment into a dedicated counter buffer.
+//! The buffer lives in linear memory and its address is baked into a
+//! module-local global, so counting works under any WASM runtime without
+//! host callbacks or profiler support.
+//!
+//! Each block remembers the GLSL lines of the statements it covers, which
+//! lets [`ShaderCoverage::line_counts`] turn raw counters into per-line
+//! execution counts.
+//!
+//! With [`WasmBackendConfig::profile`](super::WasmBackendConfig) blocks are
+//! also split wherever the source line changes, and the finished module is
+//! scanned for the instructions that follow each counter increment. Counter
+//! times instruction count then gives the instructions executed per line
+//! ([`ShaderCoverage::profile`]). Instructions emitted outside any statement,
+//! such as input and output marshalling, are not attributed.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU32, Ordering};
+use wasm_encoder::{Function, Instruction, MemArg};
+use wasmparser::Operator;
+
+/// Number of instructions emitted by [`emit_increment`].
+const INCREMENT_LEN: usize = 6;
+
+/// Collects basic blocks while a module is being translated.
+#[derive(Debug, Default)]
+pub struct CoverageBuilder {
+    /// Index of the module-local global holding the counter buffer address
+    pub global_idx: u32,
+    /// Source lines covered by each block, in block id order
+    block_lines: Vec<Vec<u32>>,
+    /// Block that the next straight-line statement belongs to
+    current: Option<u32>,
+    /// Start a new block whenever the source line changes (profiling)
+    split_lines: bool,
+}
+
+impl CoverageBuilder {
+    /// Create a builder whose counters are addressed through `global_idx`.
+    pub fn new(global_idx: u32) -> Self {
+        Self {
+            global_idx,
+            ..Self::default()
+        }
+    }
+
+    /// Keep every block on a single source line, so block costs map to lines.
+    pub fn split_lines(mut self) -> Self {
+        self.split_lines = true;
+        self
+    }
+
+    /// End the current block; the next statement starts a new one.
+    pub fn end_block(&mut self) {
+        self.current = None;
+    }
+
+    /// Note a statement on `line` (0 when unknown).
+    ///
+    /// Returns the id of a newly opened block if this statement starts one,
+    /// in which case the caller must emit its counter increment.
+    pub fn statement(&mut self, line: u32) -> Option<u32> {
+        if let Some(current) = self.current {
+            let lines = &self.block_lines[current as usize];
+            if self.split_lines && line > 0 && !lines.is_empty() && !lines.contains(&line) {
+                self.current = None;
+            }
+        }
+        let opened = match self.current {
+            Some(_) => None,
+            None => {
+                let id = self.block_lines.len() as u32;
+                self.block_lines.push(Vec::new());
+                self.current = Some(id);
+                Some(id)
+            }
+        };
+        if let Some(current) = self.current {
+            let lines = &mut self.block_lines[current as usize];
+            if line > 0 && !lines.contains(&line) {
+                lines.push(line);
+            }
+        }
+        opened
+    }
+
+    /// Allocate the counter buffer for the collected blocks.
+    pub fn finish(self) -> ShaderCoverage {
+        ShaderCoverage {
+            counters: (0..self.block_lines.len())
+                .map(|_| AtomicU32::new(0))
+                .collect(),
+            block_lines: self.block_lines,
+            global_idx: self.global_idx,
+            block_instructions: None,
+            source: String::new(),
+        }
+    }
+}
+
+/// Emit `counters[block] += 1` for the buffer addressed by `global_idx`.
+pub fn emit_increment(func: &mut Function, global_idx: u32, block: u32) {
+    let memarg = MemArg {
+        offset: block as u64 * 4,
+        align: 2,
+        memory_index: 0,
+    };
+    func.instruction(&Instruction::GlobalGet(global_idx));
+    func.instruction(&Instruction::GlobalGet(global_idx));
+    func.instruction(&Instruction::I32Load(memarg));
+    func.instruction(&Instruction::I32Const(1));
+    func.instruction(&Instruction::I32Add);
+    func.instruction(&Instruction::I32Store(memarg));
+}
+
+/// Execution counters of an instrumented shader.
+///
+/// The generated module writes straight into `counters`, so the buffer must
+/// stay alive (and in place) for as long as the module may run.
+#[derive(Debug)]
+pub struct ShaderCoverage {
+    counters: Box<[AtomicU32]>,
+    block_lines: Vec<Vec<u32>>,
+    global_idx: u32,
+    /// Instructions per block, when profiling
+    block_instructions: Option<Vec<u64>>,
+    /// GLSL source, for annotated profiles
+    source: String,
+}
+
+impl ShaderCoverage {
+    /// Linear memory address of the counter buffer.
+    pub fn counters_ptr(&self) -> u32 {
+        self.counters.as_ptr() as usize as u32
+    }
+
+    /// Number of instrumented basic blocks.
+    pub fn block_count(&self) -> usize {
+        self.counters.len()
+    }
+
+    /// Execution count of each basic block.
+    pub fn block_counts(&self) -> Vec<u32> {
+        self.counters
+            .iter()
+            .map(|c| c.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// Execution count of each GLSL line that belongs to an instrumented
+    /// block, including lines that never ran (count 0).
+    pub fn line_counts(&self) -> BTreeMap<u32, u64> {
+        let mut counts = BTreeMap::new();
+        for (lines, counter) in self.block_lines.iter().zip(self.counters.iter()) {
+            for line in lines {
+                *counts.entry(*line).or_insert(0) += counter.load(Ordering::Relaxed) as u64;
+            }
+        }
+        counts
+    }
+
+    /// Zero all counters.
+    pub fn reset(&self) {
+        for counter in self.counters.iter() {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Count the instructions of each block in the finished module, enabling
+    /// [`profile`](Self::profile). Blocks are delimited by their counter
+    /// increments, which are not counted themselves.
+    pub fn attach_profile(
+        &mut self,
+        wasm_bytes: &[u8],
+        source: &str,
+    ) -> Result<(), wasmparser::BinaryReaderError> {
+        let mut costs = vec![0u64; self.counters.len()];
+        for payload in wasmparser::Parser::new(0).parse_all(wasm_bytes) {
+            let wasmparser::Payload::CodeSectionEntry(body) = payload? else {
+                continue;
+            };
+            let ops = body
+                .get_operators_reader()?
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut current = None;
+            let mut i = 0;
+            while i < ops.len() {
+                if let Some(block) = self.increment_at(&ops[i..]) {
+                    current = Some(block);
+                    i += INCREMENT_LEN;
+                    continue;
+                }
+                if let Some(cost) = current.and_then(|block| costs.get_mut(block)) {
+                    *cost += 1;
+                }
+                i += 1;
+            }
+        }
+        self.block_instructions = Some(costs);
+        self.source = source.to_string();
+        Ok(())
+    }
+
+    /// Block whose counter increment starts `ops`, if any.
+    fn increment_at(&self, ops: &[Operator]) -> Option<usize> {
+        match (ops.first(), ops.get(1), ops.get(2)) {
+            (
+                Some(Operator::GlobalGet { global_index: a }),
+                Some(Operator::GlobalGet { global_index: b }),
+                Some(Operator::I32Load { memarg }),
+            ) if *a == self.global_idx && *b == self.global_idx => Some(memarg.offset as usize / 4),
+            _ => None,
+        }
+    }
+
+    /// Instructions executed per GLSL line since the last reset, or `None`
+    /// when the shader was not compiled for profiling.
+    pub fn profile(&self) -> Option<ShaderProfile> {
+        let costs = self.block_instructions.as_ref()?;
+        let mut lines: BTreeMap<u32, (u64, u64)> = BTreeMap::new();
+        let mut total_instructions = 0;
+        for ((block_lines, counter), cost) in
+            self.block_lines.iter().zip(self.counters.iter()).zip(costs)
+        {
+            let count = counter.load(Ordering::Relaxed) as u64;
+            total_instructions += count * cost;
+            // Split blocks carry at most one line
+            if let Some(&line) = block_lines.first() {
+                let entry = lines.entry(line).or_default();
+                entry.0 += count;
+                entry.1 += count * cost;
+            }
+        }
+        let percent = |instructions: u64| {
+            if total_instructions == 0 {
+                0.0
+            } else {
+                instructions as f64 * 100.0 / total_instructions as f64
+            }
+        };
+        Some(ShaderProfile {
+            total_instructions,
+            lines: lines
+                .into_iter()
+                .map(|(line, (executions, instructions))| LineProfile {
+                    line,
+                    executions,
+                    instructions,
+                    percent: percent(instructions),
+                })
+                .collect(),
+        })
+    }
+
+    /// GLSL source the shader was compiled from (profiling only).
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Cost of one GLSL line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineProfile {
+    pub line: u32,
+    /// Times the statements on this line started executing
+    pub executions: u64,
+    /// WASM instructions executed for this line
+    pub instructions: u64,
+    /// Share of all instructions executed by the shader
+    pub percent: f64,
+}
+
+/// Per-line instruction costs of a shader, sorted by line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShaderProfile {
+    pub total_instructions: u64,
+    pub lines: Vec<LineProfile>,
+}
+
+impl ShaderProfile {
+    /// List `source` with the percent and instruction count of each line in
+    /// front of it, in the manner of `perf annotate`.
+    pub fn annotate(&self, source: &str) -> String {
+        let by_line: HashMap<u32, &LineProfile> =
+            self.lines.iter().map(|line| (line.line, line)).collect();
+        let mut out = format!(
+            "{:>8}  {:>12}  {:>5}  Source\n",
+            "Percent", "Instructions", "Line"
+        );
+        for (text, line) in source.lines().zip(1u32..) {
+            match by_line.get(&line) {
+                Some(cost) => out.push_str(&format!(
+                    "{:>7.2}%  {:>12}  {:>5}  {}\n",
+                    cost.percent, cost.instructions, line, text
+                )),
+                None => out.push_str(&format!("{:>8}  {:>12}  {:>5}  {}\n", "", "", line, text)),
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blocks_split_on_end_block() {
+        let mut builder = CoverageBuilder::new(6);
+        assert_eq!(builder.statement(3), Some(0));
+        assert_eq!(builder.statement(4), None);
+        builder.end_block();
+        assert_eq!(builder.statement(5), Some(1));
+        assert_eq!(builder.statement(5), None);
+
+        let coverage = builder.finish();
+        assert_eq!(coverage.block_count(), 2);
+        coverage.counters[0].store(2, Ordering::Relaxed);
+        coverage.counters[1].store(7, Ordering::Relaxed);
+        let lines: Vec<_> = coverage.line_counts().into_iter().collect();
+        assert_eq!(lines, vec![(3, 2), (4, 2), (5, 7)]);
+
+        coverage.reset();
+        assert_eq!(coverage.block_counts(), vec![0, 0]);
+    }
+
+    #[test]
+    fn test_line_counts_sum_blocks_sharing_a_line() {
+        // `if (c) a(); else b();` on one line: both branches count toward it
+        let mut builder = CoverageBuilder::new(6);
+        builder.statement(2);
+        builder.end_block();
+        builder.statement(2);
+        builder.end_block();
+        builder.statement(0);
+
+        let coverage = builder.finish();
+        coverage.counters[0].store(1, Ordering::Relaxed);
+        coverage.counters[1].store(3, Ordering::Relaxed);
+        coverage.counters[2].store(4, Ordering::Relaxed);
+        assert_eq!(coverage.line_counts().get(&2), Some(&4));
+        assert_eq!(coverage.line_counts().len(), 1, "line 0 is not reported");
+    }
+
+    #[test]
+    fn test_profile_attributes_block_instructions_to_lines() {
+        let mut builder = CoverageBuilder::new(0).split_lines();
+        assert_eq!(builder.statement(3), Some(0));
+        assert_eq!(builder.statement(3), None);
+        assert_eq!(
+            builder.statement(4),
+            Some(1),
+            "a new line opens a new block"
+        );
+        let mut coverage = builder.finish();
+
+        // Block 0: three instructions, block 1: one plus the final `end`
+        let mut func = Function::new([]);
+        emit_increment(&mut func, 0, 0);
+        func.instruction(&Instruction::I32Const(1));
+        func.instruction(&Instruction::Drop);
+        func.instruction(&Instruction::Nop);
+        emit_increment(&mut func, 0, 1);
+        func.instruction(&Instruction::Nop);
+        func.instruction(&Instruction::End);
+        let mut types = wasm_encoder::TypeSection::new();
+        types.ty().function([], []);
+        let mut functions = wasm_encoder::FunctionSection::new();
+        functions.function(0);
+        let mut code = wasm_encoder::CodeSection::new();
+        code.function(&func);
+        let mut module = wasm_encoder::Module::new();
+        module.section(&types).section(&functions).section(&code);
+
+        let source = "#version 300 es\nvoid main() {\n  a();\n  b();\n}";
+        coverage.attach_profile(&module.finish(), source).unwrap();
+        coverage.counters[0].store(2, Ordering::Relaxed);
+        coverage.counters[1].store(5, Ordering::Relaxed);
+
+        let profile = coverage.profile().unwrap();
+        assert_eq!(profile.total_instructions, 16);
+        let lines: Vec<_> = profile
+            .lines
+            .iter()
+            .map(|l| (l.line, l.executions, l.instructions, l.percent))
+            .collect();
+        assert_eq!(lines, vec![(3, 2, 6, 37.5), (4, 5, 10, 62.5)]);
+
+        let listing = profile.annotate(coverage.source());
+        assert_eq!(
+            listing.lines().nth(3),
+            Some("  37.50%             6      3    a();")
+        );
+        assert_eq!(
+            listing.lines().nth(1),
+            Some("                            1  #version 300 es")
+        );
+    }
+}
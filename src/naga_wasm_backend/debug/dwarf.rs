@@ -1,26 +1,208 @@
 //! DWARF debug information generation
 
-use std::collections::HashMap;
+use super::spans::SourceSpan;
+use crate::naga_wasm_backend::BackendError;
+use gimli::write::{
+    Address, AttributeValue, DwarfUnit, EndianVec, LineProgram, LineString, Sections,
+};
+use std::collections::BTreeMap;
+use wasmparser::{Operator, Payload};
 
 /// DWARF generator for shader debugging
+///
+/// Debug builds report each statement's GLSL line to `env.debug_step` right
+/// before running it. The generator turns those calls into a line table, so
+/// any code offset (a trap PC, say) maps back to the statement around it.
 pub struct DwarfGenerator {
-    /// The original GLSL source code used for generating debug symbols.
-    /// This field is preserved to allow mapping WASM instructions back to the high-level shader source lines during debugging sessions.
-    _source: String,
+    /// Name of the GLSL source in the line table
+    file_name: String,
+}
+
+/// A function body and the statements in it, in code section offsets
+struct Sequence {
+    start: usize,
+    end: usize,
+    /// (offset, line) of each `debug_step` call
+    rows: Vec<(usize, u64)>,
+}
+
+fn dwarf_error(e: impl std::fmt::Display) -> BackendError {
+    BackendError::DwarfGeneration(e.to_string())
 }
 
 impl DwarfGenerator {
-    /// Create a new DWARF generator for the given GLSL source
-    pub fn new(source: &str) -> Self {
+    /// Create a DWARF generator for a GLSL source called `file_name`
+    pub fn new(file_name: &str) -> Self {
         Self {
-            _source: source.to_string(),
+            file_name: file_name.to_string(),
         }
     }
 
-    /// Finish generating DWARF and return custom sections
-    pub fn finish(self) -> HashMap<String, Vec<u8>> {
-        // Phase 0: Placeholder returning empty sections
-        // Future: Generate .debug_line, .debug_info, .debug_str, etc.
-        HashMap::new()
+    /// Generate the DWARF sections (name and contents) of a finished module
+    /// whose statements call import `debug_step_idx`. Addresses are code section offsets, as in the
+    /// WebAssembly DWARF conventions, so the sections can be appended to the
+    /// module or go into a split artifact alike.
+    pub fn finish(
+        self,
+        wasm_bytes: &[u8],
+        debug_step_idx: u32,
+    ) -> Result<Vec<(String, Vec<u8>)>, BackendError> {
+        let mut code = 0..0;
+        let mut sequences = Vec::new();
+        for payload in wasmparser::Parser::new(0).parse_all(wasm_bytes) {
+            match payload.map_err(dwarf_error)? {
+                Payload::CodeSectionStart { range, .. } => code = range,
+                Payload::CodeSectionEntry(body) => {
+                    let ops = body
+                        .get_operators_reader()
+                        .map_err(dwarf_error)?
+                        .into_iter_with_offsets()
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(dwarf_error)?;
+                    // debug_step(line, func, result): the line is pushed first
+                    let rows = ops
+                        .windows(4)
+                        .filter_map(|window| {
+                            let (Operator::I32Const { value: line }, offset) = window[0] else {
+                                return None;
+                            };
+                            let (Operator::Call { function_index }, _) = window[3] else {
+                                return None;
+                            };
+                            (function_index == debug_step_idx && line > 0)
+                                .then_some((offset - code.start, line as u64))
+                        })
+                        .collect();
+                    let range = body.range();
+                    sequences.push(Sequence {
+                        start: range.start - code.start,
+                        end: range.end - code.start,
+                        rows,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let encoding = gimli::Encoding {
+            format: gimli::Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let file_name = LineString::String(self.file_name.clone().into_bytes());
+        let mut program = LineProgram::new(
+            encoding,
+            gimli::LineEncoding::default(),
+            LineString::String(Vec::new()),
+            file_name.clone(),
+            None,
+        );
+        let directory = program.default_directory();
+        let file = program.add_file(file_name, directory, None);
+        for sequence in sequences.iter().filter(|s| !s.rows.is_empty()) {
+            program.begin_sequence(Some(Address::Constant(sequence.start as u64)));
+            for &(offset, line) in &sequence.rows {
+                let row = program.row();
+                row.address_offset = (offset - sequence.start) as u64;
+                row.file = file;
+                row.line = line;
+                program.generate_row();
+            }
+            program.end_sequence((sequence.end - sequence.start) as u64);
+        }
+
+        let mut dwarf = DwarfUnit::new(encoding);
+        dwarf.unit.line_program = program;
+        let root = dwarf.unit.root();
+        let unit = dwarf.unit.get_mut(root);
+        unit.set(
+            gimli::DW_AT_name,
+            AttributeValue::String(self.file_name.into_bytes()),
+        );
+        unit.set(
+            gimli::DW_AT_low_pc,
+            AttributeValue::Address(Address::Constant(0)),
+        );
+        unit.set(
+            gimli::DW_AT_high_pc,
+            AttributeValue::Udata(code.len() as u64),
+        );
+
+        let mut sections = Sections::new(EndianVec::new(gimli::LittleEndian));
+        dwarf.write(&mut sections).map_err(dwarf_error)?;
+        let mut custom_sections = Vec::new();
+        sections
+            .for_each(|id, data| {
+                if !data.slice().is_empty() {
+                    custom_sections.push((id.name().to_string(), data.slice().to_vec()));
+                }
+                Ok::<_, gimli::write::Error>(())
+            })
+            .map_err(dwarf_error)?;
+        Ok(custom_sections)
+    }
+}
+
+/// Read the GLSL line of each statement from a `.debug_line` section, keyed
+/// by the code section offset the statement starts at. None if the section
+/// doesn't hold a line program.
+pub fn statement_lines(debug_line: &[u8]) -> Option<BTreeMap<u64, u32>> {
+    let debug_line = gimli::DebugLine::new(debug_line, gimli::LittleEndian);
+    let program = debug_line
+        .program(gimli::DebugLineOffset(0), 4, None, None)
+        .ok()?;
+    let mut rows = program.rows();
+    let mut lines = BTreeMap::new();
+    while let Some((_, row)) = rows.next_row().ok()? {
+        if let Some(line) = row.line().filter(|_| !row.end_sequence()) {
+            lines.insert(row.address(), line.get() as u32);
+        }
+    }
+    Some(lines)
+}
+
+/// Map `offset`, a byte offset into a module such as a trap PC, to the GLSL
+/// statement it belongs to, through the line table of the module or of its
+/// split debug artifact. None without debug info or outside any statement.
+pub fn symbolize(wasm_bytes: &[u8], dwarf_bytes: Option<&[u8]>, offset: u32) -> Option<SourceSpan> {
+    let code_start = wasmparser::Parser::new(0)
+        .parse_all(wasm_bytes)
+        .find_map(|payload| match payload {
+            Ok(Payload::CodeSectionStart { range, .. }) => Some(range.start),
+            _ => None,
+        })?;
+    let address = (offset as usize).checked_sub(code_start)? as u64;
+    let (_, data) = super::custom_sections(dwarf_bytes.unwrap_or(wasm_bytes))
+        .ok()?
+        .into_iter()
+        .find(|(name, _)| name == ".debug_line")?;
+
+    let debug_line = gimli::DebugLine::new(&data, gimli::LittleEndian);
+    let program = debug_line
+        .program(gimli::DebugLineOffset(0), 4, None, None)
+        .ok()?;
+    let mut rows = program.rows();
+    // The statement a PC belongs to is the last row at or before it
+    let mut previous: Option<(u64, SourceSpan)> = None;
+    while let Some((header, row)) = rows.next_row().ok()? {
+        if let Some((start, span)) = previous.take() {
+            if start <= address && address < row.address() {
+                return Some(span);
+            }
+        }
+        if row.end_sequence() {
+            continue;
+        }
+        let file = match row.file(header).map(|file| file.path_name()) {
+            Some(gimli::AttributeValue::String(name)) => name.to_string_lossy().into_owned(),
+            _ => String::new(),
+        };
+        let span = SourceSpan {
+            file,
+            line: row.line().map_or(0, |line| line.get() as u32),
+            column: 0,
+        };
+        previous = Some((row.address(), span));
     }
+    None
 }
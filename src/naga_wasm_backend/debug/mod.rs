@@ -1,11 +1,228 @@
 //! Debug module for DWARF generation
 //!
-//! Placeholder for DWARF generation infrastructure
+//! Debug builds embed their GLSL source and a DWARF line table, which maps
+//! code offsets such as trap PCs back to GLSL lines ([`symbolize`]) and lets
+//! the decompiler annotate statements ([`statement_lines`]).
 
 pub mod dwarf;
 pub mod spans;
 pub mod stub;
 pub mod variables;
 
-pub use dwarf::DwarfGenerator;
-pub use stub::JsStubGenerator;
+pub use dwarf::{statement_lines, symbolize, DwarfGenerator};
+pub use spans::SourceSpan;
+pub use stub::{JsStubGenerator, StubOptions};
+
+use super::BackendError;
+
+/// Custom section holding the original GLSL source in debug builds.
+///
+/// Together with the line numbers passed to `env.debug_step`, this lets the
+/// decompiler annotate its output with the source lines it came from.
+pub const SOURCE_SECTION_NAME: &str = "webgl2.glsl_source";
+
+/// The GLSL source embedded in a debug build of a shader module, if any.
+pub fn embedded_source(wasm_bytes: &[u8]) -> Option<String> {
+    for payload in wasmparser::Parser::new(0).parse_all(wasm_bytes) {
+        if let Ok(wasmparser::Payload::CustomSection(reader)) = payload {
+            if reader.name() == SOURCE_SECTION_NAME {
+                return Some(String::from_utf8_lossy(reader.data()).into_owned());
+            }
+        }
+    }
+    None
+}
+
+/// Custom section tying a module to its split debug artifact, as in the
+/// WebAssembly tool conventions: a length-prefixed byte vector.
+pub const BUILD_ID_SECTION_NAME: &str = "build_id";
+
+/// Build ID of a split module and debug artifact pair: FNV-1a over both.
+pub(crate) fn compute_build_id(wasm_bytes: &[u8], debug_bytes: &[u8]) -> [u8; 8] {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in wasm_bytes.iter().chain(debug_bytes) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash.to_le_bytes()
+}
+
+/// Append a `build_id` custom section to finished module bytes.
+pub(crate) fn append_build_id(wasm_bytes: &mut Vec<u8>, build_id: &[u8]) {
+    use wasm_encoder::Encode;
+    let mut data = Vec::new();
+    build_id.encode(&mut data);
+    append_custom_section(wasm_bytes, BUILD_ID_SECTION_NAME, &data);
+}
+
+pub(crate) fn append_custom_section(wasm_bytes: &mut Vec<u8>, name: &str, data: &[u8]) {
+    use wasm_encoder::Encode;
+    let section = wasm_encoder::CustomSection {
+        name: std::borrow::Cow::Borrowed(name),
+        data: std::borrow::Cow::Borrowed(data),
+    };
+    wasm_bytes.push(wasm_encoder::SectionId::Custom as u8);
+    section.encode(wasm_bytes);
+}
+
+/// The build ID of a module or split debug artifact, if it has one.
+pub fn build_id(wasm_bytes: &[u8]) -> Option<Vec<u8>> {
+    custom_sections(wasm_bytes)
+        .ok()?
+        .into_iter()
+        .find(|(name, _)| name == BUILD_ID_SECTION_NAME)
+        .and_then(|(_, data)| {
+            let mut reader = wasmparser::BinaryReader::new(&data, 0);
+            let len = reader.read_var_u32().ok()? as usize;
+            Some(reader.read_bytes(len).ok()?.to_vec())
+        })
+}
+
+/// Put a split debug artifact back into its module, for tools that expect
+/// the debug sections inline. Fails if the build IDs do not match.
+pub fn merge_debug_info(wasm_bytes: &[u8], debug_bytes: &[u8]) -> Result<Vec<u8>, BackendError> {
+    let module_id = build_id(wasm_bytes);
+    if module_id.is_none() || module_id != build_id(debug_bytes) {
+        return Err(BackendError::DwarfGeneration(
+            "debug artifact does not match the module's build ID".to_string(),
+        ));
+    }
+    let sections =
+        custom_sections(debug_bytes).map_err(|e| BackendError::DwarfGeneration(e.to_string()))?;
+    let mut merged = wasm_bytes.to_vec();
+    for (name, data) in sections {
+        if name != BUILD_ID_SECTION_NAME {
+            append_custom_section(&mut merged, &name, &data);
+        }
+    }
+    Ok(merged)
+}
+
+fn custom_sections(wasm_bytes: &[u8]) -> wasmparser::Result<Vec<(String, Vec<u8>)>> {
+    let mut sections = Vec::new();
+    for payload in wasmparser::Parser::new(0).parse_all(wasm_bytes) {
+        if let wasmparser::Payload::CustomSection(reader) = payload? {
+            sections.push((reader.name().to_string(), reader.data().to_vec()));
+        }
+    }
+    Ok(sections)
+}
+
+/// Generate a JS debug stub for any compiled shader module, with `options`.
+///
+/// The source comes from the module itself, so it must be a debug build
+/// (`WasmBackendConfig::debug_shaders`); returns None otherwise. Pass the
+/// Naga module the shader was compiled from to have lines with calls step
+/// into them.
+pub fn generate_debug_stub(
+    wasm: &super::WasmModule,
+    module: Option<&naga::Module>,
+    name: Option<&str>,
+    options: StubOptions,
+) -> Option<String> {
+    let source = embedded_source(&wasm.wasm_bytes)
+        .or_else(|| wasm.dwarf_bytes.as_deref().and_then(embedded_source))?;
+    let generator = match module {
+        Some(module) => JsStubGenerator::new(&source, module, name),
+        None => JsStubGenerator::from_source(&source, name),
+    };
+    Some(generator.with_options(options).generate())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module_with_source(source: Option<&str>) -> Vec<u8> {
+        let mut module = wasm_encoder::Module::new();
+        if let Some(source) = source {
+            module.section(&wasm_encoder::CustomSection {
+                name: std::borrow::Cow::Borrowed(SOURCE_SECTION_NAME),
+                data: std::borrow::Cow::Borrowed(source.as_bytes()),
+            });
+        }
+        module.finish()
+    }
+
+    #[test]
+    fn test_split_debug_info_merges_back_by_build_id() {
+        let mut wasm = module_with_source(None);
+        let mut debug = module_with_source(Some("void main() {}"));
+        let id = compute_build_id(&wasm, &debug);
+        append_build_id(&mut wasm, &id);
+        append_build_id(&mut debug, &id);
+
+        assert_eq!(build_id(&wasm), Some(id.to_vec()));
+        assert_eq!(embedded_source(&wasm), None);
+        let merged = merge_debug_info(&wasm, &debug).unwrap();
+        assert_eq!(embedded_source(&merged).as_deref(), Some("void main() {}"));
+        assert_eq!(build_id(&merged), Some(id.to_vec()));
+
+        let mut other = module_with_source(Some("void main() {}"));
+        append_build_id(&mut other, &[0; 8]);
+        assert!(merge_debug_info(&wasm, &other).is_err());
+        assert!(merge_debug_info(&module_with_source(None), &debug).is_err());
+    }
+
+    #[test]
+    fn test_line_table_symbolizes_trap_offsets() {
+        use wasm_encoder::{CodeSection, EntityType, Function, FunctionSection, ImportSection};
+        use wasm_encoder::{Instruction, TypeSection, ValType};
+
+        // debug_step(7, -1, 0) followed by a trap
+        let mut types = TypeSection::new();
+        types.ty().function(vec![ValType::I32; 3], vec![]);
+        types.ty().function(vec![], vec![]);
+        let mut imports = ImportSection::new();
+        imports.import("env", "debug_step", EntityType::Function(0));
+        let mut functions = FunctionSection::new();
+        functions.function(1);
+        let mut body = Function::new(vec![]);
+        body.instruction(&Instruction::Nop);
+        body.instruction(&Instruction::I32Const(7));
+        body.instruction(&Instruction::I32Const(-1));
+        body.instruction(&Instruction::I32Const(0));
+        body.instruction(&Instruction::Call(0));
+        body.instruction(&Instruction::Unreachable);
+        body.instruction(&Instruction::End);
+        let mut code = CodeSection::new();
+        code.function(&body);
+        let mut module = wasm_encoder::Module::new();
+        module
+            .section(&types)
+            .section(&imports)
+            .section(&functions)
+            .section(&code);
+        let mut wasm = module.finish();
+
+        let offset_of = |wasm: &[u8], wanted: fn(&wasmparser::Operator) -> bool| {
+            wasmparser::Parser::new(0)
+                .parse_all(wasm)
+                .find_map(|payload| match payload {
+                    Ok(wasmparser::Payload::CodeSectionEntry(body)) => body
+                        .get_operators_reader()
+                        .unwrap()
+                        .into_iter_with_offsets()
+                        .map(Result::unwrap)
+                        .find(|(op, _)| wanted(op)),
+                    _ => None,
+                })
+                .unwrap()
+                .1 as u32
+        };
+        let nop = offset_of(&wasm, |op| matches!(op, wasmparser::Operator::Nop));
+        let trap = offset_of(&wasm, |op| matches!(op, wasmparser::Operator::Unreachable));
+
+        let sections = DwarfGenerator::new("test.frag").finish(&wasm, 0).unwrap();
+        assert!(sections.iter().any(|(name, _)| name == ".debug_line"));
+        assert!(symbolize(&wasm, None, trap).is_none());
+        for (name, data) in &sections {
+            append_custom_section(&mut wasm, name, data);
+        }
+
+        let span = symbolize(&wasm, None, trap).unwrap();
+        assert_eq!((span.file.as_str(), span.line), ("test.frag", 7));
+        // Code before the first statement has no line
+        assert!(symbolize(&wasm, None, nop).is_none());
+    }
+}
@@ -1,31 +1,68 @@
 use naga::{Module, Statement};
 use std::collections::HashSet;
 
+/// What a debug stub contains beyond one function per source line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StubOptions {
+    /// Embed the GLSL source in the source map (`sourcesContent`). Without
+    /// it the stub is smaller, and the debugger has to find the source by
+    /// file name.
+    pub embed_source: bool,
+    /// Start each line with `if (this?.breakpoints?.has(line)) debugger;`, so
+    /// the host can break on lines by filling a set instead of placing
+    /// breakpoints in generated code.
+    pub breakpoints: bool,
+    /// Variables to watch: lines mentioning one call
+    /// `this?.watch?.(name, line)`.
+    pub watch: Vec<String>,
+}
+
+impl Default for StubOptions {
+    fn default() -> Self {
+        Self {
+            embed_source: true,
+            breakpoints: false,
+            watch: Vec::new(),
+        }
+    }
+}
+
 /// Generate the JS stub for shader debugging
 pub struct JsStubGenerator<'a> {
     source: &'a str,
-    module: &'a Module,
     name: Option<&'a str>,
     call_lines: HashSet<usize>,
+    options: StubOptions,
 }
 
 impl<'a> JsStubGenerator<'a> {
     pub fn new(source: &'a str, module: &'a Module, name: Option<&'a str>) -> Self {
-        let mut generator = Self {
+        let mut generator = Self::from_source(source, name);
+        generator.analyze_calls(module);
+        generator
+    }
+
+    /// A generator without the Naga module. Call sites are not known, so no
+    /// line steps into a function.
+    pub fn from_source(source: &'a str, name: Option<&'a str>) -> Self {
+        Self {
             source,
-            module,
             name,
             call_lines: HashSet::new(),
-        };
-        generator.analyze_calls();
-        generator
+            options: StubOptions::default(),
+        }
     }
 
-    fn analyze_calls(&mut self) {
-        for (_, func) in self.module.functions.iter() {
+    pub fn with_options(mut self, options: StubOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    fn analyze_calls(&mut self, module: &Module) {
+        for (_, func) in module.functions.iter() {
             self.scan_block(&func.body);
         }
-        for ep in self.module.entry_points.iter() {
+        for ep in module.entry_points.iter() {
             self.scan_block(&ep.function.body);
         }
     }
@@ -74,7 +111,7 @@ impl<'a> JsStubGenerator<'a> {
         let mut prev_source_col = 0;
 
         // We generate one entry per line of source code
-        for (i, _line_content) in lines.iter().enumerate() {
+        for (i, line_content) in lines.iter().enumerate() {
             let line_num = i + 1;
             let is_call = self.call_lines.contains(&line_num);
 
@@ -121,10 +158,28 @@ impl<'a> JsStubGenerator<'a> {
             prev_source_line = source_line;
             prev_source_col = source_col;
 
-            if is_call {
-                js.push_str(&format!("({}) => this?.go?.(),", params));
+            let mut body = String::new();
+            if self.options.breakpoints {
+                body.push_str(&format!(
+                    "if (this?.breakpoints?.has({})) debugger; ",
+                    line_num
+                ));
+            }
+            for name in self.watched_in(line_content) {
+                body.push_str(&format!("this?.watch?.({:?}, {}); ", name, line_num));
+            }
+
+            if body.is_empty() {
+                if is_call {
+                    js.push_str(&format!("({}) => this?.go?.(),", params));
+                } else {
+                    js.push_str(&format!("({}) => {{}},", params));
+                }
             } else {
-                js.push_str(&format!("({}) => {{}},", params));
+                if is_call {
+                    body.push_str("this?.go?.(); ");
+                }
+                js.push_str(&format!("({}) => {{ {}}},", params, body));
             }
             js.push('\n');
         }
@@ -133,13 +188,18 @@ impl<'a> JsStubGenerator<'a> {
 
         // Construct Source Map JSON
         // We need to escape the source content for JSON string
-        let source_content_json =
-            serde_json::to_string(self.source).unwrap_or_else(|_| "\"\"".to_string());
+        let sources_content = if self.options.embed_source {
+            let source_content_json =
+                serde_json::to_string(self.source).unwrap_or_else(|_| "\"\"".to_string());
+            format!(r#","sourcesContent":[{}]"#, source_content_json)
+        } else {
+            String::new()
+        };
         let filename = self.name.unwrap_or("shader.glsl");
 
         let map_json = format!(
-            r#"{{"version":3,"file":"generated.js","sourceRoot":"","sources":["{}"],"names":[],"mappings":"{}","sourcesContent":[{}]}}"#,
-            filename, mappings, source_content_json
+            r#"{{"version":3,"file":"generated.js","sourceRoot":"","sources":["{}"],"names":[],"mappings":"{}"{}}}"#,
+            filename, mappings, sources_content
         );
 
         let b64_map = base64_encode(map_json.as_bytes());
@@ -152,6 +212,22 @@ impl<'a> JsStubGenerator<'a> {
     }
 }
 
+impl JsStubGenerator<'_> {
+    /// Watched variables that appear as identifiers on `line`.
+    fn watched_in(&self, line: &str) -> Vec<&str> {
+        let idents: HashSet<&str> = line
+            .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .filter(|s| !s.is_empty())
+            .collect();
+        self.options
+            .watch
+            .iter()
+            .map(String::as_str)
+            .filter(|name| idents.contains(name))
+            .collect()
+    }
+}
+
 fn encode_vlq(value: i32) -> String {
     let mut vlq = String::new();
     let mut val = value as i64;
@@ -132,6 +132,66 @@ fn translate_const_expression_component(
     Ok(())
 }
 
+/// Table buffer global and offset of `expr`, if it is a constant array laid
+/// out as a table (see [`super::constants`]).
+fn constant_table(
+    expr: naga::Handle<Expression>,
+    ctx: &mut TranslationContext,
+) -> Option<(u32, u32)> {
+    let Expression::Constant(handle) = ctx.func.expressions[expr] else {
+        return None;
+    };
+    let tables = ctx.constant_data.as_deref_mut()?;
+    let offset = tables.table_offset(ctx.module, handle)?;
+    Some((tables.global_idx, offset))
+}
+
+/// Emit `a` if `a <cmp> b` holds, else `b`, for an i32 comparison `cmp`.
+/// Each operand is emitted twice rather than kept in a swap local, which a
+/// nested expression could clobber.
+fn select_by(
+    a: &dyn Fn(&mut TranslationContext) -> Result<(), BackendError>,
+    b: &dyn Fn(&mut TranslationContext) -> Result<(), BackendError>,
+    cmp: &Instruction,
+    ctx: &mut TranslationContext,
+) -> Result<(), BackendError> {
+    a(ctx)?;
+    b(ctx)?;
+    a(ctx)?;
+    b(ctx)?;
+    ctx.wasm_func.instruction(cmp);
+    ctx.wasm_func.instruction(&Instruction::Select);
+    Ok(())
+}
+
+/// Emit the determinant of the submatrix of `matrix`, which has `n` rows,
+/// made of `rows` and `cols`, by cofactor expansion along its first row.
+/// Components are emitted again for every product rather than kept in locals.
+fn emit_determinant(
+    matrix: naga::Handle<Expression>,
+    n: u32,
+    rows: &[u32],
+    cols: &[u32],
+    ctx: &mut TranslationContext,
+) -> Result<(), BackendError> {
+    if rows.len() == 1 {
+        return translate_expression_component(matrix, cols[0] * n + rows[0], ctx);
+    }
+    for (j, &col) in cols.iter().enumerate() {
+        translate_expression_component(matrix, col * n + rows[0], ctx)?;
+        let rest: Vec<u32> = cols.iter().copied().filter(|&c| c != col).collect();
+        emit_determinant(matrix, n, &rows[1..], &rest, ctx)?;
+        ctx.wasm_func.instruction(&Instruction::F32Mul);
+        if j % 2 == 1 {
+            ctx.wasm_func.instruction(&Instruction::F32Neg);
+        }
+        if j > 0 {
+            ctx.wasm_func.instruction(&Instruction::F32Add);
+        }
+    }
+    Ok(())
+}
+
 /// Translate a Naga expression component to WASM instructions
 pub fn translate_expression_component(
     expr_handle: naga::Handle<Expression>,
@@ -579,6 +639,35 @@ pub fn translate_expression_component(
                             ctx.wasm_func.instruction(&Instruction::LocalGet(1));
                             return Ok(());
                         }
+                        (naga::BuiltIn::SampleIndex, naga::ShaderStage::Fragment) => {
+                            // gl_SampleID is stored by the rasterizer in the varying buffer
+                            ctx.wasm_func.instruction(&Instruction::GlobalGet(
+                                output_layout::VARYING_PTR_GLOBAL,
+                            ));
+                            ctx.wasm_func.instruction(&Instruction::I32Load(
+                                wasm_encoder::MemArg {
+                                    offset: output_layout::SAMPLE_INDEX_OFFSET as u64,
+                                    align: 2,
+                                    memory_index: 0,
+                                },
+                            ));
+                            return Ok(());
+                        }
+                        (naga::BuiltIn::PointCoord, naga::ShaderStage::Fragment) => {
+                            // gl_PointCoord is stored by the rasterizer for point sprites
+                            ctx.wasm_func.instruction(&Instruction::GlobalGet(
+                                output_layout::VARYING_PTR_GLOBAL,
+                            ));
+                            ctx.wasm_func.instruction(&Instruction::F32Load(
+                                wasm_encoder::MemArg {
+                                    offset: (output_layout::POINT_COORD_OFFSET + component_idx * 4)
+                                        as u64,
+                                    align: 2,
+                                    memory_index: 0,
+                                },
+                            ));
+                            return Ok(());
+                        }
                         _ => {}
                     }
                 }
@@ -619,6 +708,15 @@ pub fn translate_expression_component(
                     _ => None,
                 } {
                     (offset, _) = output_layout::compute_input_offset(location, ctx.stage);
+                    if ctx.stage == naga::ShaderStage::Fragment {
+                        let component = arg
+                            .name
+                            .as_ref()
+                            .and_then(|name| ctx.varying_components.get(name))
+                            .copied()
+                            .unwrap_or(0);
+                        (offset, _) = output_layout::compute_varying_offset(location, component);
+                    }
                     found_location = true;
                 }
 
@@ -1065,6 +1163,36 @@ pub fn translate_expression_component(
             }
         }
         Expression::Access { base, index } => {
+            if let Some((global_idx, offset)) = constant_table(*base, ctx) {
+                // Constant array laid out in the table buffer
+                let element_inner = match ctx.typifier.get(*base, &ctx.module.types) {
+                    naga::TypeInner::Array { base, .. } => &ctx.module.types[*base].inner,
+                    other => other,
+                };
+                let element_size = super::types::type_size(element_inner)?;
+                let is_int = is_integer_type(element_inner, &ctx.module.types);
+
+                ctx.wasm_func
+                    .instruction(&Instruction::GlobalGet(global_idx));
+                translate_expression_component(*index, 0, ctx)?;
+                ctx.wasm_func
+                    .instruction(&Instruction::I32Const(element_size as i32));
+                ctx.wasm_func.instruction(&Instruction::I32Mul);
+                ctx.wasm_func.instruction(&Instruction::I32Add);
+
+                let memarg = wasm_encoder::MemArg {
+                    offset: (offset + component_idx * 4) as u64,
+                    align: 2,
+                    memory_index: 0,
+                };
+                if is_int {
+                    ctx.wasm_func.instruction(&Instruction::I32Load(memarg));
+                } else {
+                    ctx.wasm_func.instruction(&Instruction::F32Load(memarg));
+                }
+                return Ok(());
+            }
+
             let base_ty = ctx.typifier.get(*base, &ctx.module.types);
             match base_ty {
                 naga::TypeInner::Pointer {
@@ -1268,6 +1396,17 @@ pub fn translate_expression_component(
                     (naga::ScalarKind::Bool, naga::ScalarKind::Uint) => {
                         // bool -> u32: no-op
                     }
+                    (naga::ScalarKind::Float, naga::ScalarKind::Bool) => {
+                        // f32 -> bool: x != 0.0
+                        ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
+                        ctx.wasm_func.instruction(&Instruction::F32Ne);
+                    }
+                    (naga::ScalarKind::Sint, naga::ScalarKind::Bool)
+                    | (naga::ScalarKind::Uint, naga::ScalarKind::Bool) => {
+                        // i32/u32 -> bool: x != 0
+                        ctx.wasm_func.instruction(&Instruction::I32Const(0));
+                        ctx.wasm_func.instruction(&Instruction::I32Ne);
+                    }
                     (naga::ScalarKind::Sint, naga::ScalarKind::Uint)
                     | (naga::ScalarKind::Uint, naga::ScalarKind::Sint) => {
                         // i32 <-> u32: no-op (same bit representation)
@@ -1310,6 +1449,113 @@ pub fn translate_expression_component(
             image,
             coordinate,
             sampler,
+            array_index,
+            level,
+            ..
+        } if ctx.webgpu_sample_idx.is_some() => {
+            // Host sampler (WebGPU): the host resolves both descriptors against the
+            // draw's bindings and applies address modes, filters and mip selection
+            let ty_handle = ctx.typifier[*image].handle().unwrap();
+            let (dim, is_integer) = match ctx.module.types[ty_handle].inner {
+                naga::TypeInner::Image {
+                    dim,
+                    class: naga::ImageClass::Sampled { kind, .. },
+                    ..
+                } => (dim, kind != naga::ScalarKind::Float),
+                naga::TypeInner::Image { dim, .. } => (dim, false),
+                _ => (naga::ImageDimension::D2, false),
+            };
+            let push_f32 = |h_expr: naga::Handle<Expression>, ctx: &mut TranslationContext| {
+                translate_expression_component(h_expr, 0, ctx)?;
+                match ctx.typifier.get(h_expr, &ctx.module.types).scalar_kind() {
+                    Some(naga::ScalarKind::Sint) => {
+                        ctx.wasm_func.instruction(&Instruction::F32ConvertI32S);
+                    }
+                    Some(naga::ScalarKind::Uint) => {
+                        ctx.wasm_func.instruction(&Instruction::F32ConvertI32U);
+                    }
+                    _ => {}
+                }
+                Ok::<(), BackendError>(())
+            };
+
+            // 1. Descriptor addresses (translate_expression_component follows the indirection)
+            translate_expression_component(*image, 0, ctx)?;
+            translate_expression_component(*sampler, 0, ctx)?;
+
+            // 2. Coordinates: u, v and either w (3D) or the array layer
+            translate_expression_component(*coordinate, 0, ctx)?;
+            if dim == naga::ImageDimension::D1 {
+                ctx.wasm_func.instruction(&Instruction::F32Const(0.5));
+            } else {
+                translate_expression_component(*coordinate, 1, ctx)?;
+            }
+            if dim == naga::ImageDimension::D3 || dim == naga::ImageDimension::Cube {
+                translate_expression_component(*coordinate, 2, ctx)?;
+            } else if let Some(layer) = array_index {
+                push_f32(*layer, ctx)?;
+            } else {
+                ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
+            }
+
+            // 3. Level of detail: kind (0 auto, 1 exact, 2 bias, 3 gradient), value, gradients
+            let gradients = match level {
+                naga::SampleLevel::Auto => {
+                    ctx.wasm_func.instruction(&Instruction::I32Const(0));
+                    ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
+                    None
+                }
+                naga::SampleLevel::Zero => {
+                    ctx.wasm_func.instruction(&Instruction::I32Const(1));
+                    ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
+                    None
+                }
+                naga::SampleLevel::Exact(lod) => {
+                    ctx.wasm_func.instruction(&Instruction::I32Const(1));
+                    push_f32(*lod, ctx)?;
+                    None
+                }
+                naga::SampleLevel::Bias(bias) => {
+                    ctx.wasm_func.instruction(&Instruction::I32Const(2));
+                    push_f32(*bias, ctx)?;
+                    None
+                }
+                naga::SampleLevel::Gradient { x, y } => {
+                    ctx.wasm_func.instruction(&Instruction::I32Const(3));
+                    ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
+                    Some((*x, *y))
+                }
+            };
+            if let Some((x, y)) = gradients {
+                translate_expression_component(x, 0, ctx)?;
+                translate_expression_component(x, 1, ctx)?;
+                translate_expression_component(y, 0, ctx)?;
+                translate_expression_component(y, 1, ctx)?;
+            } else {
+                for _ in 0..4 {
+                    ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
+                }
+            }
+
+            // 4. Call the import, which returns the address of the sampled RGBA value
+            ctx.wasm_func
+                .instruction(&Instruction::Call(ctx.webgpu_sample_idx.unwrap()));
+            ctx.wasm_func
+                .instruction(&Instruction::F32Load(wasm_encoder::MemArg {
+                    offset: component_idx as u64 * 4,
+                    align: 2,
+                    memory_index: 0,
+                }));
+            if is_integer {
+                ctx.wasm_func.instruction(&Instruction::I32ReinterpretF32);
+            }
+        }
+        Expression::ImageSample {
+            image,
+            coordinate,
+            sampler,
+            level,
+            offset,
             ..
         } => {
             let ty_handle = ctx.typifier[*image].handle().unwrap();
@@ -1380,16 +1626,55 @@ pub fn translate_expression_component(
                 push_handle_addr(*sampler, ctx)?;
 
                 // 3. Push coordinates
-                translate_expression_component(*coordinate, 0, ctx)?;
-                translate_expression_component(*coordinate, 1, ctx)?;
-                if dim == naga::ImageDimension::D3 {
-                    translate_expression_component(*coordinate, 2, ctx)?;
+                let n = if dim == naga::ImageDimension::D3 {
+                    3
+                } else {
+                    2
+                };
+                for i in 0..n {
+                    translate_expression_component(*coordinate, i, ctx)?;
+                }
+
+                // 4. Level of detail: kind (0 auto, 1 exact, 2 bias, 3 gradient), value, gradients
+                let (kind, value, gradients) = match level {
+                    naga::SampleLevel::Auto => (0, None, None),
+                    naga::SampleLevel::Zero => (1, None, None),
+                    naga::SampleLevel::Exact(lod) => (1, Some(*lod), None),
+                    naga::SampleLevel::Bias(bias) => (2, Some(*bias), None),
+                    naga::SampleLevel::Gradient { x, y } => (3, None, Some((*x, *y))),
+                };
+                ctx.wasm_func.instruction(&Instruction::I32Const(kind));
+                match value {
+                    Some(value) => translate_expression_component(value, 0, ctx)?,
+                    None => {
+                        ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
+                    }
+                }
+                for i in 0..2 * n {
+                    match gradients {
+                        Some((x, y)) => {
+                            translate_expression_component(if i < n { x } else { y }, i % n, ctx)?
+                        }
+                        None => {
+                            ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
+                        }
+                    }
                 }
 
-                // 4. Call helper (expects texture_desc, sampler_desc, u, v, [w])
+                // 5. Texel offsets (textureOffset and friends)
+                for i in 0..n {
+                    match offset {
+                        Some(offset) => translate_expression_component(*offset, i, ctx)?,
+                        None => {
+                            ctx.wasm_func.instruction(&Instruction::I32Const(0));
+                        }
+                    }
+                }
+
+                // 6. Call helper (expects texture_desc, sampler_desc, coordinates, level, offsets)
                 ctx.wasm_func.instruction(&Instruction::Call(tex_fetch_idx));
 
-                // 5. Store results
+                // 7. Store results
                 let sample_base = ctx
                     .sample_f32_locals
                     .expect("Sampling locals not allocated for function with ImageSample?");
@@ -1403,7 +1688,7 @@ pub fn translate_expression_component(
                 ctx.wasm_func
                     .instruction(&Instruction::LocalSet(sample_base)); // r
 
-                // 6. Return requested component
+                // 8. Return requested component
                 ctx.wasm_func
                     .instruction(&Instruction::LocalGet(sample_base + component_idx));
 
@@ -1652,65 +1937,52 @@ pub fn translate_expression_component(
                     ctx.wasm_func.instruction(&Instruction::Call(func_idx));
                 }
                 MathFunction::Abs => {
-                    translate_expression_component(*arg, component_idx, ctx)?;
                     let ty = ctx.typifier.get(*arg, &ctx.module.types);
                     if is_integer_type(ty, &ctx.module.types) {
-                        // abs(x) = (x ^ (x >> 31)) - (x >> 31)
-                        // But simpler for WASM: push 0, sub, then select max
+                        // abs(x) = x < 0 ? 0 - x : x
+                        ctx.wasm_func.instruction(&Instruction::I32Const(0));
+                        translate_expression_component(*arg, component_idx, ctx)?;
                         ctx.wasm_func
                             .instruction(&Instruction::LocalTee(ctx.swap_i32_local));
-                        ctx.wasm_func.instruction(&Instruction::I32Const(0));
+                        ctx.wasm_func.instruction(&Instruction::I32Sub);
                         ctx.wasm_func
                             .instruction(&Instruction::LocalGet(ctx.swap_i32_local));
-                        ctx.wasm_func.instruction(&Instruction::I32Sub);
                         ctx.wasm_func
                             .instruction(&Instruction::LocalGet(ctx.swap_i32_local));
                         ctx.wasm_func.instruction(&Instruction::I32Const(0));
                         ctx.wasm_func.instruction(&Instruction::I32LtS);
                         ctx.wasm_func.instruction(&Instruction::Select);
                     } else {
+                        translate_expression_component(*arg, component_idx, ctx)?;
                         ctx.wasm_func.instruction(&Instruction::F32Abs);
                     }
                 }
-                MathFunction::Min => {
-                    translate_expression_component(*arg, component_idx, ctx)?;
-                    if let Some(a1) = arg1 {
-                        translate_expression_component(*a1, component_idx, ctx)?;
-                        let ty = ctx.typifier.get(*arg, &ctx.module.types);
-                        if is_integer_type(ty, &ctx.module.types) {
-                            // i32.min: select if a < b
-                            let temp_a = ctx.swap_i32_local;
-                            let temp_b = ctx.swap_i32_local; // use i32 swap local explicitly
-                            ctx.wasm_func.instruction(&Instruction::LocalSet(temp_b));
-                            ctx.wasm_func.instruction(&Instruction::LocalTee(temp_a));
-                            ctx.wasm_func.instruction(&Instruction::LocalGet(temp_b));
-                            ctx.wasm_func.instruction(&Instruction::LocalGet(temp_a));
-                            ctx.wasm_func.instruction(&Instruction::LocalGet(temp_b));
-                            ctx.wasm_func.instruction(&Instruction::I32LtS);
-                            ctx.wasm_func.instruction(&Instruction::Select);
-                        } else {
-                            ctx.wasm_func.instruction(&Instruction::F32Min);
-                        }
-                    }
-                }
-                MathFunction::Max => {
-                    translate_expression_component(*arg, component_idx, ctx)?;
-                    if let Some(a1) = arg1 {
-                        translate_expression_component(*a1, component_idx, ctx)?;
-                        let ty = ctx.typifier.get(*arg, &ctx.module.types);
-                        if is_integer_type(ty, &ctx.module.types) {
-                            let temp_a = ctx.swap_i32_local;
-                            let temp_b = ctx.swap_i32_local; // use i32 swap local explicitly
-                            ctx.wasm_func.instruction(&Instruction::LocalSet(temp_b));
-                            ctx.wasm_func.instruction(&Instruction::LocalTee(temp_a));
-                            ctx.wasm_func.instruction(&Instruction::LocalGet(temp_b));
-                            ctx.wasm_func.instruction(&Instruction::LocalGet(temp_a));
-                            ctx.wasm_func.instruction(&Instruction::LocalGet(temp_b));
-                            ctx.wasm_func.instruction(&Instruction::I32GtS);
-                            ctx.wasm_func.instruction(&Instruction::Select);
+                MathFunction::Min | MathFunction::Max => {
+                    let a = *arg;
+                    let b = arg1.expect("Min and Max need 2 arguments");
+                    let ty = ctx.typifier.get(a, &ctx.module.types);
+                    if is_integer_type(ty, &ctx.module.types) {
+                        let unsigned = ty.scalar_kind() == Some(ScalarKind::Uint);
+                        let cmp = match (fun, unsigned) {
+                            (MathFunction::Min, false) => Instruction::I32LtS,
+                            (MathFunction::Min, true) => Instruction::I32LtU,
+                            (_, false) => Instruction::I32GtS,
+                            (_, true) => Instruction::I32GtU,
+                        };
+                        select_by(
+                            &|ctx| translate_expression_component(a, component_idx, ctx),
+                            &|ctx| translate_expression_component(b, component_idx, ctx),
+                            &cmp,
+                            ctx,
+                        )?;
+                    } else {
+                        translate_expression_component(a, component_idx, ctx)?;
+                        translate_expression_component(b, component_idx, ctx)?;
+                        ctx.wasm_func.instruction(&if *fun == MathFunction::Min {
+                            Instruction::F32Min
                         } else {
-                            ctx.wasm_func.instruction(&Instruction::F32Max);
-                        }
+                            Instruction::F32Max
+                        });
                     }
                 }
                 MathFunction::Clamp => {
@@ -1723,29 +1995,27 @@ pub fn translate_expression_component(
                     let is_int = is_integer_type(ty, &ctx.module.types);
 
                     if is_int {
-                        // min(x, max)
-                        translate_expression_component(x, component_idx, ctx)?;
-                        translate_expression_component(max_val, component_idx, ctx)?;
-                        let temp_a = ctx.swap_i32_local;
-                        let temp_b = ctx.swap_i32_local;
-                        ctx.wasm_func.instruction(&Instruction::LocalSet(temp_b));
-                        ctx.wasm_func.instruction(&Instruction::LocalTee(temp_a));
-                        ctx.wasm_func.instruction(&Instruction::LocalGet(temp_b));
-                        ctx.wasm_func.instruction(&Instruction::LocalGet(temp_a));
-                        ctx.wasm_func.instruction(&Instruction::LocalGet(temp_b));
-                        ctx.wasm_func.instruction(&Instruction::I32LtS);
-                        ctx.wasm_func.instruction(&Instruction::Select);
-
-                        // max(result, min)
-                        translate_expression_component(min_val, component_idx, ctx)?;
-                        // stack: [result_min, min]
-                        ctx.wasm_func.instruction(&Instruction::LocalSet(temp_b));
-                        ctx.wasm_func.instruction(&Instruction::LocalTee(temp_a));
-                        ctx.wasm_func.instruction(&Instruction::LocalGet(temp_b));
-                        ctx.wasm_func.instruction(&Instruction::LocalGet(temp_a));
-                        ctx.wasm_func.instruction(&Instruction::LocalGet(temp_b));
-                        ctx.wasm_func.instruction(&Instruction::I32GtS);
-                        ctx.wasm_func.instruction(&Instruction::Select);
+                        let (less, greater) = if ty.scalar_kind() == Some(ScalarKind::Uint) {
+                            (Instruction::I32LtU, Instruction::I32GtU)
+                        } else {
+                            (Instruction::I32LtS, Instruction::I32GtS)
+                        };
+                        // max(min(x, max), min)
+                        select_by(
+                            &|ctx| {
+                                select_by(
+                                    &|ctx| translate_expression_component(x, component_idx, ctx),
+                                    &|ctx| {
+                                        translate_expression_component(max_val, component_idx, ctx)
+                                    },
+                                    &less,
+                                    ctx,
+                                )
+                            },
+                            &|ctx| translate_expression_component(min_val, component_idx, ctx),
+                            &greater,
+                            ctx,
+                        )?;
                     } else {
                         translate_expression_component(x, component_idx, ctx)?;
                         translate_expression_component(max_val, component_idx, ctx)?;
@@ -1828,11 +2098,11 @@ pub fn translate_expression_component(
                 }
                 MathFunction::Step => {
                     // step(edge, x) = x < edge ? 0.0 : 1.0
+                    ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
+                    ctx.wasm_func.instruction(&Instruction::F32Const(1.0));
                     translate_expression_component(*arg1.as_ref().unwrap(), component_idx, ctx)?; // x
                     translate_expression_component(*arg, component_idx, ctx)?; // edge
                     ctx.wasm_func.instruction(&Instruction::F32Lt);
-                    ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
-                    ctx.wasm_func.instruction(&Instruction::F32Const(1.0));
                     ctx.wasm_func.instruction(&Instruction::Select);
                 }
                 MathFunction::Dot => {
@@ -1905,39 +2175,39 @@ pub fn translate_expression_component(
                     ctx.wasm_func.instruction(&Instruction::F32Div);
                 }
                 MathFunction::Sign => {
-                    translate_expression_component(*arg, component_idx, ctx)?;
+                    // sign(x) = x > 0 ? 1 : (x < 0 ? -1 : 0)
                     let ty = ctx.typifier.get(*arg, &ctx.module.types);
                     if is_integer_type(ty, &ctx.module.types) {
+                        ctx.wasm_func.instruction(&Instruction::I32Const(1));
+                        ctx.wasm_func.instruction(&Instruction::I32Const(-1));
+                        ctx.wasm_func.instruction(&Instruction::I32Const(0));
+                        translate_expression_component(*arg, component_idx, ctx)?;
                         ctx.wasm_func
                             .instruction(&Instruction::LocalTee(ctx.swap_i32_local));
                         ctx.wasm_func.instruction(&Instruction::I32Const(0));
-                        ctx.wasm_func.instruction(&Instruction::I32GtS);
-                        ctx.wasm_func.instruction(&Instruction::I32Const(1));
+                        ctx.wasm_func.instruction(&Instruction::I32LtS);
+                        ctx.wasm_func.instruction(&Instruction::Select);
 
                         ctx.wasm_func
                             .instruction(&Instruction::LocalGet(ctx.swap_i32_local));
                         ctx.wasm_func.instruction(&Instruction::I32Const(0));
-                        ctx.wasm_func.instruction(&Instruction::I32LtS);
-                        ctx.wasm_func.instruction(&Instruction::I32Const(-1));
-                        ctx.wasm_func.instruction(&Instruction::I32Const(0));
-                        ctx.wasm_func.instruction(&Instruction::Select);
-
+                        ctx.wasm_func.instruction(&Instruction::I32GtS);
                         ctx.wasm_func.instruction(&Instruction::Select);
                     } else {
+                        ctx.wasm_func.instruction(&Instruction::F32Const(1.0));
+                        ctx.wasm_func.instruction(&Instruction::F32Const(-1.0));
+                        ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
+                        translate_expression_component(*arg, component_idx, ctx)?;
                         ctx.wasm_func
                             .instruction(&Instruction::LocalTee(ctx.swap_f32_local));
                         ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
-                        ctx.wasm_func.instruction(&Instruction::F32Gt);
-                        ctx.wasm_func.instruction(&Instruction::F32Const(1.0));
+                        ctx.wasm_func.instruction(&Instruction::F32Lt);
+                        ctx.wasm_func.instruction(&Instruction::Select);
 
                         ctx.wasm_func
                             .instruction(&Instruction::LocalGet(ctx.swap_f32_local));
                         ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
-                        ctx.wasm_func.instruction(&Instruction::F32Lt);
-                        ctx.wasm_func.instruction(&Instruction::F32Const(-1.0));
-                        ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
-                        ctx.wasm_func.instruction(&Instruction::Select);
-
+                        ctx.wasm_func.instruction(&Instruction::F32Gt);
                         ctx.wasm_func.instruction(&Instruction::Select);
                     }
                 }
@@ -2287,6 +2557,10 @@ pub fn translate_expression_component(
                             translate_expression_component(a, j, ctx)?;
                             translate_expression_component(b, j, ctx)?;
                             ctx.wasm_func.instruction(&Instruction::F32Sub);
+                            ctx.wasm_func
+                                .instruction(&Instruction::LocalTee(ctx.swap_f32_local));
+                            ctx.wasm_func
+                                .instruction(&Instruction::LocalGet(ctx.swap_f32_local));
                             ctx.wasm_func.instruction(&Instruction::F32Mul);
                             ctx.wasm_func.instruction(&Instruction::F32Add);
                         }
@@ -2367,6 +2641,9 @@ pub fn translate_expression_component(
                                     ctx.wasm_func
                                         .instruction(&Instruction::LocalGet(ctx.swap_f32_local));
                                 }
+                                (naga::VectorSize::Quad, naga::VectorSize::Quad) => {
+                                    emit_determinant(*arg, 4, &[0, 1, 2, 3], &[0, 1, 2, 3], ctx)?;
+                                }
                                 _ => {
                                     ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
                                 }
@@ -2388,6 +2665,8 @@ pub fn translate_expression_component(
                             (naga::VectorSize::Tri, naga::VectorSize::Tri) => ctx.inverse_mat3_idx,
                             _ => None,
                         };
+                        let is_mat4 =
+                            (*columns, *rows) == (naga::VectorSize::Quad, naga::VectorSize::Quad);
 
                         if let Some(func_idx) = helper_idx {
                             let frame_temp = ctx.frame_temp_idx.expect("Frame temp local missing");
@@ -2448,6 +2727,18 @@ pub fn translate_expression_component(
                             ctx.wasm_func.instruction(&Instruction::GlobalSet(
                                 output_layout::FRAME_SP_GLOBAL,
                             ));
+                        } else if is_mat4 {
+                            // No host helper: the cofactor of the transposed
+                            // element over the determinant
+                            let (col, row) = (component_idx / 4, component_idx % 4);
+                            let minor_rows: Vec<u32> = (0..4).filter(|&i| i != col).collect();
+                            let minor_cols: Vec<u32> = (0..4).filter(|&j| j != row).collect();
+                            emit_determinant(*arg, 4, &minor_rows, &minor_cols, ctx)?;
+                            if (col + row) % 2 == 1 {
+                                ctx.wasm_func.instruction(&Instruction::F32Neg);
+                            }
+                            emit_determinant(*arg, 4, &[0, 1, 2, 3], &[0, 1, 2, 3], ctx)?;
+                            ctx.wasm_func.instruction(&Instruction::F32Div);
                         } else {
                             ctx.wasm_func.instruction(&Instruction::F32Const(0.0));
                         }
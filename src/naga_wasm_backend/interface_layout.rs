@@ -0,0 +1,1058 @@
+//! Locations of a program's attributes, uniforms and varyings.
+//!
+//! The linker, the resource manifest and through it the generated JS harness
+//! all need the same answer to "where does this input live", so it is
+//! computed here once from the Naga modules of a program. The backend then
+//! compiles against the resulting maps.
+//!
+//! Layout runs in two passes. The first collects each stage's interface:
+//! entry point arguments, uniform globals and uniform blocks, packable
+//! varyings and the remaining private globals. The second assigns locations:
+//!
+//! * attributes take their layout qualifier, else their `bindAttribLocation`
+//!   binding, and a matrix takes one location per column. Naga gives an
+//!   unqualified input location 0, so the qualifiers are read from the
+//!   vertex source. Two attributes on
+//!   one location, or one past the last location, fail the link. The rest
+//!   fill the lowest free run of locations in declaration order.
+//! * uniforms take their bound location, if any. The rest are numbered in
+//!   declaration order, vertex stage first, each name once, skipping bound
+//!   locations. A fragment-only program starts at the base given by the
+//!   caller.
+//! * vertex outputs are packed by [`varying_packing`] after checking that
+//!   they match the fragment inputs, with bound ones pinned to their
+//!   location; a fragment-only program packs its inputs instead. Other
+//!   private globals follow the packed locations.
+//!
+//! Bindings come from [`LocationBindings`], so embedders with fixed slot
+//! conventions can place resources before the program links. Location and
+//! uniform vector counts are checked against [`LinkLimits`], which contexts
+//! may set lower than the backend's own.
+
+use super::output_layout::{
+    get_webgl_uniform_data_offset, MAX_UNIFORM_VECTORS, MAX_VARYING_VECTORS, MAX_VERTEX_ATTRIBS,
+};
+use super::varying_packing::{self, StageVarying, VaryingSlot};
+use naga::{AddressSpace, Binding, Module, ShaderStage};
+use std::collections::{HashMap, HashSet};
+
+/// `(type_code, components)` of an interface variable, where type_code is
+/// 0 for float, 1 for signed and 2 for unsigned integers.
+pub type TypeInfo = (u8, u32);
+
+/// Locations fixed by name before a program links. Names the program does
+/// not declare are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct LocationBindings {
+    /// `bindAttribLocation` calls; layout qualifiers take precedence
+    pub attributes: HashMap<String, u32>,
+    /// Uniform locations, below
+    /// [`MAX_UNIFORM_LOCATIONS`](super::output_layout::MAX_UNIFORM_LOCATIONS)
+    pub uniforms: HashMap<String, u32>,
+    /// Varying locations, below [`MAX_VARYING_VECTORS`]
+    pub varyings: HashMap<String, u32>,
+}
+
+/// Implementation limits a program links against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkLimits {
+    /// `MAX_VERTEX_ATTRIBS`, at most [`MAX_VERTEX_ATTRIBS`]
+    pub vertex_attribs: u32,
+    /// `MAX_VARYING_VECTORS`, at most [`MAX_VARYING_VECTORS`]
+    pub varying_vectors: u32,
+    /// `MAX_VERTEX_UNIFORM_VECTORS` and `MAX_FRAGMENT_UNIFORM_VECTORS`
+    pub uniform_vectors: u32,
+}
+
+impl Default for LinkLimits {
+    fn default() -> Self {
+        Self {
+            vertex_attribs: MAX_VERTEX_ATTRIBS,
+            varying_vectors: MAX_VARYING_VECTORS,
+            uniform_vectors: MAX_UNIFORM_VECTORS,
+        }
+    }
+}
+
+/// Locations assigned to the interface of one program.
+#[derive(Debug, Clone, Default)]
+pub struct InterfaceLayout {
+    pub attribute_locations: HashMap<String, u32>,
+    pub attribute_types: HashMap<String, TypeInfo>,
+    pub uniform_locations: HashMap<String, u32>,
+    pub uniform_types: HashMap<String, TypeInfo>,
+    /// Uniform blocks, in declaration order
+    pub uniform_blocks: Vec<String>,
+    pub varying_locations: HashMap<String, u32>,
+    /// First component of each packed varying within its location
+    pub varying_components: HashMap<String, u32>,
+    pub varying_types: HashMap<String, TypeInfo>,
+}
+
+impl InterfaceLayout {
+    /// Linear memory offset of a uniform's value in the uniform buffer.
+    pub fn uniform_offset(&self, name: &str) -> Option<u32> {
+        let &location = self.uniform_locations.get(name)?;
+        Some(get_webgl_uniform_data_offset(location))
+    }
+}
+
+/// Interface of one shader stage, as found by the first pass.
+struct StageInterface<'m> {
+    module: &'m Module,
+    /// Named entry point arguments with their layout location, if any
+    arguments: Vec<(&'m String, Option<u32>, naga::Handle<naga::Type>)>,
+    /// Default-block uniforms and samplers, in declaration order
+    uniforms: Vec<(&'m String, naga::Handle<naga::Type>)>,
+    uniform_blocks: Vec<&'m String>,
+    /// Varyings that are packed into shared locations
+    varyings: Vec<StageVarying>,
+    /// Other named globals, in declaration order
+    privates: Vec<(&'m String, naga::Handle<naga::Type>)>,
+}
+
+impl<'m> StageInterface<'m> {
+    fn collect(module: &'m Module, stage: ShaderStage) -> Self {
+        let arguments = module
+            .entry_points
+            .iter()
+            .filter(|ep| ep.stage == stage)
+            .flat_map(|ep| &ep.function.arguments)
+            .filter_map(|arg| {
+                let location = match arg.binding {
+                    Some(Binding::Location { location, .. }) => Some(location),
+                    _ => None,
+                };
+                Some((arg.name.as_ref()?, location, arg.ty))
+            })
+            .collect();
+
+        let mut globals: Vec<_> = module.global_variables.iter().collect();
+        globals.sort_by_key(|(handle, _)| handle.index());
+        let mut uniforms = Vec::new();
+        let mut uniform_blocks = Vec::new();
+        let mut privates = Vec::new();
+        for (_, var) in globals {
+            if let Some(name) = uniform_block_name(module, var) {
+                uniform_blocks.push(name);
+                continue;
+            }
+            let Some(name) = &var.name else {
+                continue;
+            };
+            if var.space == AddressSpace::Handle || var.space == AddressSpace::Uniform {
+                uniforms.push((name, var.ty));
+            } else {
+                privates.push((name, var.ty));
+            }
+        }
+
+        Self {
+            module,
+            arguments,
+            uniforms,
+            uniform_blocks,
+            varyings: varying_packing::stage_varyings(module, stage),
+            privates,
+        }
+    }
+
+    fn type_info(&self, ty: naga::Handle<naga::Type>) -> TypeInfo {
+        type_info(&self.module.types[ty])
+    }
+}
+
+/// Name of the uniform block a global declares, if it is one: a bound
+/// uniform, or an anonymous one of a named struct type. Blocks go by their
+/// block name; the instance name is optional and only qualifies the member
+/// names.
+pub fn uniform_block_name<'m>(
+    module: &'m Module,
+    var: &'m naga::GlobalVariable,
+) -> Option<&'m String> {
+    if var.space != AddressSpace::Uniform {
+        return None;
+    }
+    let ty = &module.types[var.ty];
+    let is_struct = matches!(ty.inner, naga::TypeInner::Struct { .. });
+    if var.binding.is_none() && (var.name.is_some() || !is_struct) {
+        return None;
+    }
+    ty.name.as_ref().or(var.name.as_ref())
+}
+
+/// Map a Naga type to its [`TypeInfo`].
+pub fn type_info(ty: &naga::Type) -> TypeInfo {
+    match ty.inner {
+        naga::TypeInner::Scalar(s) => match s.kind {
+            naga::ScalarKind::Sint => (1, 1),
+            naga::ScalarKind::Uint => (2, 1),
+            _ => (0, 1),
+        },
+        naga::TypeInner::Vector { size, scalar, .. } => match scalar.kind {
+            naga::ScalarKind::Sint => (1, size as u32),
+            naga::ScalarKind::Uint => (2, size as u32),
+            _ => (0, size as u32),
+        },
+        naga::TypeInner::Matrix { columns, rows, .. } => (0, (columns as u32) * (rows as u32)),
+        _ => (0, 1),
+    }
+}
+
+/// Fragment outputs are not varyings.
+fn is_fragment_output(name: &str) -> bool {
+    matches!(
+        name,
+        "color" | "fragColor" | "gl_FragColor" | "gl_FragColor_1"
+    ) || name.ends_with("Color")
+}
+
+/// Compute the interface layout of a program from its stages.
+///
+/// `vs` is the vertex module with the source it was parsed from.
+/// `bindings` are the locations fixed before linking.
+/// `fragment_uniform_base` is the first uniform location of a program
+/// without a vertex stage. Errors are info log messages.
+pub fn compute_layout(
+    vs: Option<(&Module, &str)>,
+    fs: Option<&Module>,
+    bindings: &LocationBindings,
+    limits: &LinkLimits,
+    fragment_uniform_base: u32,
+) -> Result<InterfaceLayout, String> {
+    let vs = vs.map(|(module, source)| {
+        let mut stage = StageInterface::collect(module, ShaderStage::Vertex);
+        let qualified = qualified_inputs(source);
+        for (name, location, _) in &mut stage.arguments {
+            if !qualified.contains(name.as_str()) {
+                *location = None;
+            }
+        }
+        stage
+    });
+    let fs = fs.map(|module| StageInterface::collect(module, ShaderStage::Fragment));
+    for (stage, name) in [(&vs, "VERTEX"), (&fs, "FRAGMENT")] {
+        let Some(stage) = stage else {
+            continue;
+        };
+        let used: u32 = stage
+            .uniforms
+            .iter()
+            .map(|&(_, ty)| uniform_vectors(stage.module, ty))
+            .sum();
+        if used > limits.uniform_vectors {
+            return Err(format!(
+                "Link failed: {} uniform vectors exceed MAX_{}_UNIFORM_VECTORS ({})",
+                used, name, limits.uniform_vectors
+            ));
+        }
+    }
+
+    let mut layout = InterfaceLayout::default();
+    let mut vs_varying_types = HashMap::new();
+    let mut fs_varying_types = HashMap::new();
+    let mut next_varying = 0;
+    let mut uniforms = UniformNumbering {
+        bindings: &bindings.uniforms,
+        bound: bound_uniform_locations(vs.iter().chain(&fs), &bindings.uniforms)?,
+        next: 0,
+    };
+
+    if let Some(vs) = &vs {
+        assign_attributes(&mut layout, vs, &bindings.attributes, limits.vertex_attribs)?;
+
+        // Every fragment input must match a vertex output by name, type and
+        // interpolation
+        if let Some(fs) = &fs {
+            check_varying_interface(vs.module, &vs.varyings, fs.module, &fs.varyings)
+                .map_err(|log| format!("Link failed: {}", log))?;
+        }
+        let (slots, locations_used) =
+            pack_stage_varyings(&vs.varyings, &bindings.varyings, limits.varying_vectors)?;
+        for (varying, slot) in vs.varyings.iter().zip(&slots) {
+            layout
+                .varying_locations
+                .insert(varying.name.clone(), slot.location);
+            layout
+                .varying_components
+                .insert(varying.name.clone(), slot.component);
+            vs_varying_types.insert(varying.name.clone(), vs.type_info(varying.ty));
+        }
+        // Private globals are given the locations after the packed ones
+        next_varying = locations_used;
+
+        uniforms.assign(&mut layout, vs);
+        for &(name, ty) in &vs.privates {
+            if name != "gl_Position"
+                && name != "gl_Position_1"
+                && !layout.attribute_locations.contains_key(name)
+                && !layout.varying_locations.contains_key(name)
+            {
+                layout.varying_locations.insert(name.clone(), next_varying);
+                vs_varying_types.insert(name.clone(), vs.type_info(ty));
+                next_varying += 1;
+            }
+        }
+    }
+
+    if let Some(fs) = &fs {
+        // A fragment-only separable program packs its inputs on its own;
+        // the pipeline checks the layout against its vertex program
+        if vs.is_none() {
+            let (slots, locations_used) =
+                pack_stage_varyings(&fs.varyings, &bindings.varyings, limits.varying_vectors)?;
+            for (varying, slot) in fs.varyings.iter().zip(&slots) {
+                layout
+                    .varying_locations
+                    .insert(varying.name.clone(), slot.location);
+                layout
+                    .varying_components
+                    .insert(varying.name.clone(), slot.component);
+                fs_varying_types.insert(varying.name.clone(), fs.type_info(varying.ty));
+            }
+            next_varying = locations_used;
+            uniforms.next = fragment_uniform_base;
+        }
+
+        // Inputs not seen yet keep their layout location when it is free
+        for &(name, location, ty) in &fs.arguments {
+            if is_fragment_output(name)
+                || layout.uniform_locations.contains_key(name)
+                || layout.varying_locations.contains_key(name)
+            {
+                continue;
+            }
+            let location = match location {
+                Some(loc) if !layout.varying_locations.values().any(|&v| v == loc) => {
+                    next_varying = next_varying.max(loc + 1);
+                    loc
+                }
+                _ => {
+                    next_varying += 1;
+                    next_varying - 1
+                }
+            };
+            layout.varying_locations.insert(name.clone(), location);
+            fs_varying_types.insert(name.clone(), fs.type_info(ty));
+        }
+
+        uniforms.assign(&mut layout, fs);
+        for &(name, ty) in &fs.privates {
+            if !is_fragment_output(name) && !layout.varying_locations.contains_key(name) {
+                layout.varying_locations.insert(name.clone(), next_varying);
+                fs_varying_types.insert(name.clone(), fs.type_info(ty));
+                next_varying += 1;
+            }
+        }
+    }
+
+    // Both stages must agree on the type of a shared varying
+    for (name, &loc) in &layout.varying_locations {
+        if let (Some(vs_type), Some(fs_type)) =
+            (vs_varying_types.get(name), fs_varying_types.get(name))
+        {
+            if vs_type != fs_type {
+                return Err(format!(
+                    "Link failed: Varying '{}' type mismatch at location {}: VS={:?} FS={:?}",
+                    name, loc, vs_type, fs_type
+                ));
+            }
+        }
+    }
+    layout.varying_types = fs_varying_types;
+    layout.varying_types.extend(vs_varying_types);
+
+    Ok(layout)
+}
+
+/// Give every vertex attribute one of the first `max_locations` locations.
+fn assign_attributes<'m>(
+    layout: &mut InterfaceLayout,
+    vs: &StageInterface<'m>,
+    attribute_bindings: &HashMap<String, u32>,
+    max_locations: u32,
+) -> Result<(), String> {
+    let mut owners: [Option<&'m String>; MAX_VERTEX_ATTRIBS as usize] = Default::default();
+    let mut unassigned = Vec::new();
+    for &(name, layout_location, ty) in &vs.arguments {
+        // The layout qualifier takes precedence over bindAttribLocation
+        let Some(loc) = layout_location.or_else(|| attribute_bindings.get(name).copied()) else {
+            unassigned.push((name, ty));
+            continue;
+        };
+        let slots = attribute_slots(vs, ty);
+        claim_attribute_locations(&mut owners, name, loc, slots, max_locations)?;
+        layout.attribute_locations.insert(name.clone(), loc);
+        layout
+            .attribute_types
+            .insert(name.clone(), vs.type_info(ty));
+    }
+
+    for (name, ty) in unassigned {
+        let slots = attribute_slots(vs, ty);
+        let free = |loc: &u32| (*loc..*loc + slots).all(|l| owners[l as usize].is_none());
+        let Some(loc) = (0..=max_locations.saturating_sub(slots)).find(free) else {
+            return Err(format!(
+                "Link failed: No free attribute location left for '{}'",
+                name
+            ));
+        };
+        claim_attribute_locations(&mut owners, name, loc, slots, max_locations)?;
+        layout.attribute_locations.insert(name.clone(), loc);
+        layout
+            .attribute_types
+            .insert(name.clone(), vs.type_info(ty));
+    }
+    Ok(())
+}
+
+/// Names of the inputs `source` declares with a `layout(location)`
+/// qualifier, which Naga's module does not tell apart from its default
+/// location 0.
+fn qualified_inputs(source: &str) -> HashSet<String> {
+    let mut code = String::with_capacity(source.len());
+    let mut in_comment = false;
+    for mut line in source.lines() {
+        if !in_comment && line.trim_start().starts_with('#') {
+            continue;
+        }
+        loop {
+            if in_comment {
+                let Some(end) = line.find("*/") else {
+                    break;
+                };
+                line = &line[end + 2..];
+                in_comment = false;
+                continue;
+            }
+            let block = line.find("/*");
+            let rest = line.find("//");
+            match (block, rest) {
+                (Some(start), rest) if rest.is_none_or(|rest| start < rest) => {
+                    code.push_str(&line[..start]);
+                    code.push(' ');
+                    line = &line[start + 2..];
+                    in_comment = true;
+                }
+                (_, Some(start)) => {
+                    code.push_str(&line[..start]);
+                    break;
+                }
+                _ => {
+                    code.push_str(line);
+                    break;
+                }
+            }
+        }
+        code.push('\n');
+    }
+
+    let mut names = HashSet::new();
+    for declaration in code.split([';', '{', '}']) {
+        let tokens = glsl_tokens(declaration);
+        let Some(open) = tokens.windows(2).position(|pair| pair == ["layout", "("]) else {
+            continue;
+        };
+        let Some(close) = tokens[open..].iter().position(|&t| t == ")") else {
+            continue;
+        };
+        let (qualifiers, rest) = tokens[open..].split_at(close);
+        let rest = rest.split(|&t| t == "[" || t == "=").next().unwrap_or(&[]);
+        if qualifiers.contains(&"location") && rest.contains(&"in") {
+            if let Some(name) = rest.last() {
+                names.insert(name.to_string());
+            }
+        }
+    }
+    names
+}
+
+/// Split GLSL `code` into identifiers, numbers and single punctuation
+/// characters.
+fn glsl_tokens(code: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut word_start = None;
+    for (i, c) in code.char_indices() {
+        let is_word = c.is_alphanumeric() || c == '_';
+        if let (false, Some(start)) = (is_word, word_start) {
+            tokens.push(&code[start..i]);
+            word_start = None;
+        }
+        if is_word {
+            word_start.get_or_insert(i);
+        } else if !c.is_whitespace() {
+            tokens.push(&code[i..i + c.len_utf8()]);
+        }
+    }
+    if let Some(start) = word_start {
+        tokens.push(&code[start..]);
+    }
+    tokens
+}
+
+/// Locations an attribute of type `ty` takes: one per column of a matrix.
+fn attribute_slots(vs: &StageInterface, ty: naga::Handle<naga::Type>) -> u32 {
+    match vs.module.types[ty].inner {
+        naga::TypeInner::Matrix { columns, .. } => columns as u32,
+        _ => 1,
+    }
+}
+
+/// Record `name` as the owner of `slots` locations from `loc`, failing if
+/// another attribute already has one of them or they end past
+/// `max_locations`.
+fn claim_attribute_locations<'m>(
+    owners: &mut [Option<&'m String>],
+    name: &'m String,
+    loc: u32,
+    slots: u32,
+    max_locations: u32,
+) -> Result<(), String> {
+    if loc.saturating_add(slots) > max_locations {
+        return Err(format!(
+            "Link failed: Attribute '{}' at location {} does not fit in the {} attribute locations",
+            name, loc, max_locations
+        ));
+    }
+    for l in loc..loc + slots {
+        match owners[l as usize] {
+            Some(other) if other != name => {
+                return Err(format!(
+                    "Link failed: Attributes '{}' and '{}' are both bound to location {}",
+                    name, other, l
+                ));
+            }
+            _ => owners[l as usize] = Some(name),
+        }
+    }
+    Ok(())
+}
+
+/// Locations taken by the bound uniforms the stages declare, failing if two
+/// uniforms are bound to one location.
+fn bound_uniform_locations<'s, 'm: 's>(
+    stages: impl Iterator<Item = &'s StageInterface<'m>>,
+    bindings: &HashMap<String, u32>,
+) -> Result<HashMap<u32, &'m String>, String> {
+    let mut owners: HashMap<u32, &'m String> = HashMap::new();
+    for &(name, _) in stages.flat_map(|stage| &stage.uniforms) {
+        let Some(&loc) = bindings.get(name) else {
+            continue;
+        };
+        match owners.get(&loc) {
+            Some(&other) if other != name => {
+                return Err(format!(
+                    "Link failed: Uniforms '{}' and '{}' are both bound to location {}",
+                    name, other, loc
+                ));
+            }
+            _ => {
+                owners.insert(loc, name);
+            }
+        }
+    }
+    Ok(owners)
+}
+
+/// Uniform numbering shared by the stages of a program.
+struct UniformNumbering<'a, 'm> {
+    bindings: &'a HashMap<String, u32>,
+    /// Locations of the bound uniforms, which numbering skips
+    bound: HashMap<u32, &'m String>,
+    next: u32,
+}
+
+impl UniformNumbering<'_, '_> {
+    /// Number the uniforms of one stage, skipping names already assigned.
+    fn assign(&mut self, layout: &mut InterfaceLayout, stage: &StageInterface) {
+        for &(name, ty) in &stage.uniforms {
+            if layout.uniform_locations.contains_key(name) {
+                continue;
+            }
+            let loc = match self.bindings.get(name) {
+                Some(&loc) => loc,
+                None => {
+                    while self.bound.contains_key(&self.next) {
+                        self.next += 1;
+                    }
+                    self.next += 1;
+                    self.next - 1
+                }
+            };
+            layout.uniform_locations.insert(name.clone(), loc);
+            layout
+                .uniform_types
+                .insert(name.clone(), stage.type_info(ty));
+        }
+        for &name in &stage.uniform_blocks {
+            if !layout.uniform_blocks.contains(name) {
+                layout.uniform_blocks.push(name.clone());
+            }
+        }
+    }
+}
+
+/// Pack the varyings of one stage into `max_vectors` locations, with those
+/// in `bindings` pinned to their location, returning the slots and the
+/// number of locations they take.
+pub fn pack_stage_varyings(
+    varyings: &[StageVarying],
+    bindings: &HashMap<String, u32>,
+    max_vectors: u32,
+) -> Result<(Vec<VaryingSlot>, u32), String> {
+    let components: Vec<u32> = varyings.iter().map(|v| v.components).collect();
+    let pinned: Vec<Option<u32>> = varyings
+        .iter()
+        .map(|v| bindings.get(&v.name).copied())
+        .collect();
+
+    // A bound varying owns every location it spans
+    let mut owners: HashMap<u32, &String> = HashMap::new();
+    for (varying, loc) in varyings.iter().zip(&pinned) {
+        let Some(loc) = *loc else {
+            continue;
+        };
+        for l in loc..loc.saturating_add(varying_packing::location_count(varying.components)) {
+            if let Some(other) = owners.insert(l, &varying.name) {
+                return Err(format!(
+                    "Link failed: Varyings '{}' and '{}' are both bound to location {}",
+                    varying.name, other, l
+                ));
+            }
+        }
+    }
+
+    match varying_packing::pack_varyings(&components, &pinned, max_vectors) {
+        Ok(slots) => {
+            let locations_used = varying_packing::locations_used(&components, &slots);
+            Ok((slots, locations_used))
+        }
+        Err(index) => Err(format!(
+            "Link failed: Too many varyings: '{}' does not fit in MAX_VARYING_VECTORS ({})",
+            varyings[index].name, max_vectors
+        )),
+    }
+}
+
+/// Vectors a default-block uniform of type `ty` takes against
+/// `MAX_*_UNIFORM_VECTORS`: one per vector or matrix column, times the
+/// length of an array, summed over a struct. Samplers take none.
+fn uniform_vectors(module: &Module, ty: naga::Handle<naga::Type>) -> u32 {
+    match &module.types[ty].inner {
+        naga::TypeInner::Scalar(_) | naga::TypeInner::Vector { .. } => 1,
+        naga::TypeInner::Matrix { columns, .. } => *columns as u32,
+        naga::TypeInner::Array { base, size, .. } => {
+            let count = match size {
+                naga::ArraySize::Constant(count) => count.get(),
+                _ => 1,
+            };
+            count.saturating_mul(uniform_vectors(module, *base))
+        }
+        naga::TypeInner::Struct { members, .. } => members
+            .iter()
+            .map(|member| uniform_vectors(module, member.ty))
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// Check that each fragment input is written by the vertex shader with the
+/// same type and interpolation qualifier, as GLSL ES 3.00 requires.
+pub fn check_varying_interface(
+    vs: &Module,
+    vs_outputs: &[StageVarying],
+    fs: &Module,
+    fs_inputs: &[StageVarying],
+) -> Result<(), String> {
+    let qualifier = |interpolation: naga::Interpolation| match interpolation {
+        naga::Interpolation::Flat => "flat",
+        naga::Interpolation::Linear => "noperspective",
+        _ => "smooth",
+    };
+    for input in fs_inputs {
+        let Some(output) = vs_outputs.iter().find(|o| o.name == input.name) else {
+            return Err(format!(
+                "Fragment shader input '{}' is not declared in the vertex shader",
+                input.name
+            ));
+        };
+        let vs_type = varying_packing::glsl_type_name(vs, output.ty);
+        let fs_type = varying_packing::glsl_type_name(fs, input.ty);
+        if vs_type != fs_type {
+            return Err(format!(
+                "Varying '{}' type mismatch: VS={} FS={}",
+                input.name, vs_type, fs_type
+            ));
+        }
+        if output.interpolation != input.interpolation {
+            return Err(format!(
+                "Varying '{}' interpolation mismatch: VS={} FS={}",
+                input.name,
+                qualifier(output.interpolation),
+                qualifier(input.interpolation)
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(stage: ShaderStage, source: &str) -> Module {
+        naga::front::glsl::Frontend::default()
+            .parse(&naga::front::glsl::Options::from(stage), source)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_qualifiers_override_bindings_and_uniforms_share_names() {
+        let vs_source = "#version 300 es
+            layout(location = 1) in vec2 a_uv;
+            layout(location = 0) in vec4 a_pos;
+            layout(location = 2) in ivec2 a_id;
+            uniform mat4 u_mvp;
+            uniform float u_scale;
+            out vec2 v_uv;
+            flat out int v_id;
+            void main() {
+                v_uv = a_uv * u_scale;
+                v_id = a_id.x;
+                gl_Position = u_mvp * a_pos;
+            }";
+        let vs = parse(ShaderStage::Vertex, vs_source);
+        let fs = parse(
+            ShaderStage::Fragment,
+            "#version 300 es
+            precision highp float;
+            in vec2 v_uv;
+            flat in int v_id;
+            uniform float u_scale;
+            uniform sampler2D u_tex;
+            out vec4 color;
+            void main() { color = texture(u_tex, v_uv) * u_scale + float(v_id); }",
+        );
+        // The layout qualifier of a_id wins over its binding
+        let bindings = LocationBindings {
+            attributes: HashMap::from([("a_id".to_string(), 3)]),
+            ..LocationBindings::default()
+        };
+        let layout = compute_layout(
+            Some((&vs, vs_source)),
+            Some(&fs),
+            &bindings,
+            &LinkLimits::default(),
+            32,
+        )
+        .unwrap();
+
+        fn sorted(map: &HashMap<String, u32>) -> Vec<(&str, u32)> {
+            let mut entries: Vec<_> = map.iter().map(|(k, &v)| (k.as_str(), v)).collect();
+            entries.sort();
+            entries
+        }
+        assert_eq!(
+            sorted(&layout.attribute_locations),
+            [("a_id", 2), ("a_pos", 0), ("a_uv", 1)]
+        );
+        assert_eq!(layout.attribute_types["a_id"], (1, 2));
+        assert_eq!(
+            sorted(&layout.uniform_locations),
+            [("u_mvp", 0), ("u_scale", 1), ("u_tex", 2)]
+        );
+        assert_eq!(
+            layout.uniform_offset("u_tex"),
+            Some(get_webgl_uniform_data_offset(2))
+        );
+        // The int fills the last column of the vec2's location
+        assert_eq!(
+            (
+                layout.varying_locations["v_id"],
+                layout.varying_components["v_id"]
+            ),
+            (0, 3)
+        );
+        assert_eq!(layout.varying_types["v_id"], (1, 1));
+
+        // A fragment-only program numbers its uniforms from the base
+        let fs_only = compute_layout(
+            None,
+            Some(&fs),
+            &LocationBindings::default(),
+            &LinkLimits::default(),
+            32,
+        )
+        .unwrap();
+        assert_eq!(
+            sorted(&fs_only.uniform_locations),
+            [("u_scale", 32), ("u_tex", 33)]
+        );
+    }
+
+    #[test]
+    fn test_attribute_bindings_alias_across_matrix_columns() {
+        let vs_source = "#version 300 es
+            in mat4 a_model;
+            in vec4 a_pos;
+            in float a_w;
+            void main() { gl_Position = a_model * a_pos * a_w; }";
+        let vs = parse(ShaderStage::Vertex, vs_source);
+        let fs = parse(
+            ShaderStage::Fragment,
+            "#version 300 es
+            precision highp float;
+            out vec4 color;
+            void main() { color = vec4(1.0); }",
+        );
+        let link = |bindings: &[(&str, u32)]| {
+            let bindings = LocationBindings {
+                attributes: bindings
+                    .iter()
+                    .map(|&(name, loc)| (name.to_string(), loc))
+                    .collect(),
+                ..LocationBindings::default()
+            };
+            compute_layout(
+                Some((&vs, vs_source)),
+                Some(&fs),
+                &bindings,
+                &LinkLimits::default(),
+                32,
+            )
+            .map(|layout| {
+                let mut entries: Vec<_> = layout.attribute_locations.into_iter().collect();
+                entries.sort();
+                entries
+            })
+        };
+        let locations = |entries: &[(&str, u32)]| {
+            Ok(entries
+                .iter()
+                .map(|&(name, loc)| (name.to_string(), loc))
+                .collect::<Vec<_>>())
+        };
+
+        // Unbound attributes fill the lowest runs left free
+        assert_eq!(
+            link(&[("a_pos", 2)]),
+            locations(&[("a_model", 3), ("a_pos", 2), ("a_w", 0)])
+        );
+        assert_eq!(
+            link(&[("a_model", 0), ("a_pos", 3)]).unwrap_err(),
+            "Link failed: Attributes 'a_pos' and 'a_model' are both bound to location 3"
+        );
+        assert_eq!(
+            link(&[("a_pos", 5), ("a_w", 5)]).unwrap_err(),
+            "Link failed: Attributes 'a_w' and 'a_pos' are both bound to location 5"
+        );
+        assert_eq!(
+            link(&[("a_model", 13)]).unwrap_err(),
+            "Link failed: Attribute 'a_model' at location 13 does not fit in the 16 attribute locations"
+        );
+    }
+
+    #[test]
+    fn test_bound_uniforms_and_varyings_keep_their_locations() {
+        let vs_source = "#version 300 es
+            layout(location = 0) in vec4 a_pos;
+            uniform mat4 u_mvp;
+            uniform vec4 u_tint;
+            uniform float u_scale;
+            out vec4 v_color;
+            out vec2 v_uv;
+            void main() {
+                v_color = u_tint;
+                v_uv = a_pos.xy * u_scale;
+                gl_Position = u_mvp * a_pos;
+            }";
+        let vs = parse(ShaderStage::Vertex, vs_source);
+        let fs = parse(
+            ShaderStage::Fragment,
+            "#version 300 es
+            precision highp float;
+            in vec4 v_color;
+            in vec2 v_uv;
+            uniform float u_scale;
+            out vec4 color;
+            void main() { color = v_color * v_uv.x * u_scale; }",
+        );
+        let bind = |uniforms: &[(&str, u32)], varyings: &[(&str, u32)]| {
+            let map = |entries: &[(&str, u32)]| {
+                entries
+                    .iter()
+                    .map(|&(name, loc)| (name.to_string(), loc))
+                    .collect()
+            };
+            LocationBindings {
+                uniforms: map(uniforms),
+                varyings: map(varyings),
+                ..LocationBindings::default()
+            }
+        };
+
+        // Unbound uniforms skip the bound locations; bindings of names the
+        // program lacks are ignored
+        let bindings = bind(
+            &[("u_tint", 1), ("u_scale", 8), ("u_unused", 0)],
+            &[("v_uv", 4)],
+        );
+        let layout = compute_layout(
+            Some((&vs, vs_source)),
+            Some(&fs),
+            &bindings,
+            &LinkLimits::default(),
+            32,
+        )
+        .unwrap();
+        let location = |name: &str| layout.uniform_locations.get(name).copied();
+        assert_eq!(
+            [location("u_mvp"), location("u_tint"), location("u_scale")],
+            [Some(0), Some(1), Some(8)]
+        );
+        assert_eq!(layout.varying_locations["v_uv"], 4);
+        assert_eq!(layout.varying_components["v_uv"], 0);
+        assert_eq!(layout.varying_locations["v_color"], 0);
+
+        // A fragment-only program keeps the same bound locations
+        let fs_only =
+            compute_layout(None, Some(&fs), &bindings, &LinkLimits::default(), 32).unwrap();
+        assert_eq!(fs_only.uniform_locations["u_scale"], 8);
+        assert_eq!(fs_only.varying_locations["v_uv"], 4);
+
+        assert_eq!(
+            compute_layout(
+                Some((&vs, vs_source)),
+                Some(&fs),
+                &bind(&[("u_mvp", 2), ("u_scale", 2)], &[]),
+                &LinkLimits::default(),
+                32
+            )
+            .unwrap_err(),
+            "Link failed: Uniforms 'u_scale' and 'u_mvp' are both bound to location 2"
+        );
+        assert_eq!(
+            compute_layout(
+                Some((&vs, vs_source)),
+                Some(&fs),
+                &bind(&[], &[("v_color", 3), ("v_uv", 3)]),
+                &LinkLimits::default(),
+                32
+            )
+            .unwrap_err(),
+            "Link failed: Varyings 'v_uv' and 'v_color' are both bound to location 3"
+        );
+    }
+
+    #[test]
+    fn test_uniform_blocks_go_by_block_name() {
+        let vs_source = "#version 300 es
+            layout(std140) uniform Camera {
+                mat4 view;
+                vec4 tint;
+            };
+            layout(location = 0) in vec4 a_pos;
+            out vec4 v_tint;
+            void main() {
+                v_tint = tint;
+                gl_Position = view * a_pos;
+            }";
+        let vs = parse(ShaderStage::Vertex, vs_source);
+        let fs = parse(
+            ShaderStage::Fragment,
+            "#version 300 es
+            precision highp float;
+            in vec4 v_tint;
+            layout(std140) uniform Camera {
+                mat4 view;
+                vec4 tint;
+            };
+            out vec4 color;
+            void main() { color = v_tint * tint; }",
+        );
+        let layout = compute_layout(
+            Some((&vs, vs_source)),
+            Some(&fs),
+            &LocationBindings::default(),
+            &LinkLimits::default(),
+            32,
+        )
+        .unwrap();
+        assert_eq!(layout.uniform_blocks, ["Camera"]);
+        assert!(layout.uniform_locations.is_empty());
+    }
+
+    #[test]
+    fn test_lowered_limits_fail_the_link() {
+        let vs_source = "#version 300 es
+            in vec4 a_pos;
+            in vec4 a_color;
+            in vec2 a_uv;
+            uniform mat4 u_mvp;
+            uniform vec4 u_tint[2];
+            out vec4 v_color;
+            out vec2 v_uv;
+            void main() {
+                v_color = a_color * u_tint[0] * u_tint[1];
+                v_uv = a_uv;
+                gl_Position = u_mvp * a_pos;
+            }";
+        let vs = parse(ShaderStage::Vertex, vs_source);
+        let fs = parse(
+            ShaderStage::Fragment,
+            "#version 300 es
+            precision highp float;
+            in vec4 v_color;
+            in vec2 v_uv;
+            uniform sampler2D u_tex;
+            out vec4 color;
+            void main() { color = v_color * texture(u_tex, v_uv); }",
+        );
+        let link = |limits: LinkLimits| {
+            compute_layout(
+                Some((&vs, vs_source)),
+                Some(&fs),
+                &LocationBindings::default(),
+                &limits,
+                32,
+            )
+            .err()
+        };
+        let defaults = LinkLimits::default();
+
+        assert_eq!(link(defaults), None);
+        // Exactly at each limit still links: the sampler takes no vectors
+        assert_eq!(
+            link(LinkLimits {
+                vertex_attribs: 3,
+                varying_vectors: 2,
+                uniform_vectors: 6,
+            }),
+            None
+        );
+        assert_eq!(
+            link(LinkLimits {
+                vertex_attribs: 2,
+                ..defaults
+            })
+            .unwrap(),
+            "Link failed: No free attribute location left for 'a_uv'"
+        );
+        assert_eq!(
+            link(LinkLimits {
+                varying_vectors: 1,
+                ..defaults
+            })
+            .unwrap(),
+            "Link failed: Too many varyings: 'v_uv' does not fit in MAX_VARYING_VECTORS (1)"
+        );
+        assert_eq!(
+            link(LinkLimits {
+                uniform_vectors: 5,
+                ..defaults
+            })
+            .unwrap(),
+            "Link failed: 6 uniform vectors exceed MAX_VERTEX_UNIFORM_VECTORS (5)"
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Liveness-based reuse of the WASM locals that hold call results.
+//!
+//! Expressions are translated lazily at each use, so the only values the
+//! backend keeps in WASM locals across statements are `CallResult`s. Each one
+//! is live from its `Call` statement to the last statement that reads it,
+//! directly or through other expressions. Results with the same component
+//! types whose live ranges do not overlap share one set of locals.
+//!
+//! Statements are numbered in program order. A value defined before a loop
+//! and read inside it stays live until the loop ends, since every iteration
+//! reads it again. Expressions and statements whose operands are not known
+//! here are assumed to read every call result made so far.
+
+use naga::{Expression, Handle, Statement};
+use std::collections::HashMap;
+use wasm_encoder::ValType;
+
+/// Locals assigned to the call results of one function.
+#[derive(Debug, Default)]
+pub struct CallResultLocals {
+    /// First local of each call result; its components follow in order
+    pub bases: HashMap<Handle<Expression>, u32>,
+    /// Types of the locals to declare, starting at the first local passed in
+    pub local_types: Vec<ValType>,
+}
+
+/// Live range of a call result, in statement numbers.
+#[derive(Debug, Clone, Copy)]
+struct Interval {
+    start: usize,
+    end: usize,
+}
+
+/// Assign locals starting at `first_local` to `call_results`, the call
+/// results of `func` with the WASM types of their components.
+pub fn allocate_call_result_locals(
+    func: &naga::Function,
+    call_results: &[(Handle<Expression>, Vec<ValType>)],
+    first_local: u32,
+) -> CallResultLocals {
+    let mut liveness = Liveness {
+        reads: expression_reads(func),
+        position: 0,
+        intervals: HashMap::new(),
+    };
+    liveness.visit_block(&func.body);
+
+    let mut order: Vec<_> = call_results.iter().collect();
+    order.sort_by_key(|(handle, _)| {
+        liveness
+            .intervals
+            .get(handle)
+            .map_or(usize::MAX, |interval| interval.start)
+    });
+
+    // Each slot is a run of locals and the last statement it is live at
+    let mut slots: Vec<(&[ValType], u32, usize)> = Vec::new();
+    let mut allocated = CallResultLocals::default();
+    for (handle, types) in order {
+        // Results without a call statement never share
+        let interval = liveness.intervals.get(handle).copied().unwrap_or(Interval {
+            start: usize::MAX,
+            end: usize::MAX,
+        });
+        let reused = slots
+            .iter_mut()
+            .find(|(slot_types, _, end)| *slot_types == types.as_slice() && *end < interval.start);
+        let base = match reused {
+            Some((_, base, end)) => {
+                *end = interval.end;
+                *base
+            }
+            None => {
+                let base = first_local + allocated.local_types.len() as u32;
+                allocated.local_types.extend_from_slice(types);
+                slots.push((types, base, interval.end));
+                base
+            }
+        };
+        allocated.bases.insert(*handle, base);
+    }
+    allocated
+}
+
+struct Liveness {
+    /// Call results each expression reads, by expression index
+    reads: Vec<Vec<Handle<Expression>>>,
+    /// Number of the statement being visited
+    position: usize,
+    intervals: HashMap<Handle<Expression>, Interval>,
+}
+
+impl Liveness {
+    fn visit_block(&mut self, block: &naga::Block) {
+        for stmt in block.iter() {
+            self.position += 1;
+            let position = self.position;
+            match statement_operands(stmt) {
+                Some(operands) => {
+                    for operand in operands {
+                        self.read(operand);
+                    }
+                }
+                None => self.read_all(),
+            }
+
+            match *stmt {
+                Statement::Block(ref block) => self.visit_block(block),
+                Statement::If {
+                    ref accept,
+                    ref reject,
+                    ..
+                } => {
+                    self.visit_block(accept);
+                    self.visit_block(reject);
+                }
+                Statement::Switch { ref cases, .. } => {
+                    for case in cases {
+                        self.visit_block(&case.body);
+                    }
+                }
+                Statement::Loop {
+                    ref body,
+                    ref continuing,
+                    break_if,
+                } => {
+                    self.visit_block(body);
+                    self.visit_block(continuing);
+                    self.position += 1;
+                    if let Some(condition) = break_if {
+                        self.read(condition);
+                    }
+                    let end = self.position;
+                    for interval in self.intervals.values_mut() {
+                        if interval.start < position && interval.end >= position {
+                            interval.end = interval.end.max(end);
+                        }
+                    }
+                }
+                Statement::Call {
+                    result: Some(result),
+                    ..
+                } => {
+                    self.intervals.insert(
+                        result,
+                        Interval {
+                            start: position,
+                            end: position,
+                        },
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn read(&mut self, expr: Handle<Expression>) {
+        let Some(results) = self.reads.get(expr.index()) else {
+            return self.read_all();
+        };
+        for result in results {
+            if let Some(interval) = self.intervals.get_mut(result) {
+                interval.end = interval.end.max(self.position);
+            }
+        }
+    }
+
+    fn read_all(&mut self) {
+        for interval in self.intervals.values_mut() {
+            interval.end = interval.end.max(self.position);
+        }
+    }
+}
+
+/// The call results each expression reads, following operands. Operands
+/// precede their users in a validated arena, so one pass suffices.
+fn expression_reads(func: &naga::Function) -> Vec<Vec<Handle<Expression>>> {
+    let mut reads: Vec<Vec<Handle<Expression>>> = Vec::with_capacity(func.expressions.len());
+    let mut call_results = Vec::new();
+    for (handle, expr) in func.expressions.iter() {
+        let mut results = match expression_operands(expr) {
+            Some(operands) => operands
+                .iter()
+                .filter_map(|operand| reads.get(operand.index()))
+                .flatten()
+                .copied()
+                .collect(),
+            None => call_results.clone(),
+        };
+        if let Expression::CallResult(_) = *expr {
+            results.push(handle);
+            call_results.push(handle);
+        }
+        results.sort_by_key(|result| result.index());
+        results.dedup();
+        reads.push(results);
+    }
+    reads
+}
+
+/// Direct operands of an expression, or `None` if not known here.
+fn expression_operands(expr: &Expression) -> Option<Vec<Handle<Expression>>> {
+    Some(match *expr {
+        Expression::Literal(_)
+        | Expression::Constant(_)
+        | Expression::Override(_)
+        | Expression::ZeroValue(_)
+        | Expression::FunctionArgument(_)
+        | Expression::GlobalVariable(_)
+        | Expression::LocalVariable(_)
+        | Expression::CallResult(_) => Vec::new(),
+        Expression::Compose { ref components, .. } => components.clone(),
+        Expression::Access { base, index } => vec![base, index],
+        Expression::AccessIndex { base, .. } => vec![base],
+        Expression::Splat { value, .. } => vec![value],
+        Expression::Swizzle { vector, .. } => vec![vector],
+        Expression::Load { pointer } => vec![pointer],
+        Expression::Unary { expr, .. }
+        | Expression::As { expr, .. }
+        | Expression::Derivative { expr, .. }
+        | Expression::ArrayLength(expr) => vec![expr],
+        Expression::Binary { left, right, .. } => vec![left, right],
+        Expression::Select {
+            condition,
+            accept,
+            reject,
+        } => vec![condition, accept, reject],
+        Expression::Relational { argument, .. } => vec![argument],
+        Expression::Math {
+            arg,
+            arg1,
+            arg2,
+            arg3,
+            ..
+        } => [Some(arg), arg1, arg2, arg3]
+            .into_iter()
+            .flatten()
+            .collect(),
+        Expression::ImageSample {
+            image,
+            sampler,
+            coordinate,
+            array_index,
+            level,
+            depth_ref,
+            ..
+        } => {
+            let level = match level {
+                naga::SampleLevel::Auto | naga::SampleLevel::Zero => vec![],
+                naga::SampleLevel::Exact(h) | naga::SampleLevel::Bias(h) => vec![h],
+                naga::SampleLevel::Gradient { x, y } => vec![x, y],
+            };
+            [
+                Some(image),
+                Some(sampler),
+                Some(coordinate),
+                array_index,
+                depth_ref,
+            ]
+            .into_iter()
+            .flatten()
+            .chain(level)
+            .collect()
+        }
+        Expression::ImageLoad {
+            image,
+            coordinate,
+            array_index,
+            sample,
+            level,
+        } => [Some(image), Some(coordinate), array_index, sample, level]
+            .into_iter()
+            .flatten()
+            .collect(),
+        Expression::ImageQuery { image, query } => match query {
+            naga::ImageQuery::Size { level: Some(level) } => vec![image, level],
+            _ => vec![image],
+        },
+        _ => return None,
+    })
+}
+
+/// Expressions a statement reads itself, not counting nested blocks, or
+/// `None` if not known here.
+fn statement_operands(stmt: &Statement) -> Option<Vec<Handle<Expression>>> {
+    Some(match *stmt {
+        Statement::Emit(_)
+        | Statement::Block(_)
+        | Statement::Loop { .. }
+        | Statement::Break
+        | Statement::Continue
+        | Statement::Kill => Vec::new(),
+        Statement::If { condition, .. } => vec![condition],
+        Statement::Switch { selector, .. } => vec![selector],
+        Statement::Return { value } => value.into_iter().collect(),
+        Statement::Store { pointer, value } => vec![pointer, value],
+        Statement::Call { ref arguments, .. } => arguments.clone(),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn main_function(source: &str) -> naga::Function {
+        let options = naga::front::glsl::Options::from(naga::ShaderStage::Fragment);
+        let module = naga::front::glsl::Frontend::default()
+            .parse(&options, source)
+            .unwrap();
+        // The entry point wraps the shader's own main function
+        let (_, main) = module
+            .functions
+            .iter()
+            .find(|(_, func)| func.name.as_deref() == Some("main"))
+            .unwrap();
+        main.clone()
+    }
+
+    fn call_results(func: &naga::Function) -> Vec<(Handle<Expression>, Vec<ValType>)> {
+        func.expressions
+            .iter()
+            .filter(|(_, expr)| matches!(expr, Expression::CallResult(_)))
+            .map(|(handle, _)| (handle, vec![ValType::F32]))
+            .collect()
+    }
+
+    #[test]
+    fn test_sequential_and_branch_results_share_locals() {
+        let func = main_function(
+            "#version 300 es
+            precision highp float;
+            out vec4 color;
+            float f(float x) { return x * 2.0; }
+            void main() {
+                float a = f(1.0);
+                float b = f(a);
+                vec4 c = vec4(0.0);
+                if (a > b) { c.x = f(3.0); } else { c.y = f(4.0); }
+                color = c + vec4(b);
+            }",
+        );
+        let results = call_results(&func);
+        let locals = allocate_call_result_locals(&func, &results, 5);
+        assert_eq!(results.len(), 4);
+        assert_eq!(locals.local_types, vec![ValType::F32]);
+        assert!(locals.bases.values().all(|&base| base == 5));
+    }
+
+    #[test]
+    fn test_overlapping_and_loop_results_keep_their_locals() {
+        let func = main_function(
+            "#version 300 es
+            precision highp float;
+            out vec4 color;
+            float f(float x) { return x * 2.0; }
+            void main() {
+                float acc = 0.0;
+                for (int i = 0; i < 4; i++) {
+                    acc += f(float(i)) + f(acc);
+                }
+                color = vec4(acc + f(1.0) * f(2.0));
+            }",
+        );
+        let results = call_results(&func);
+        let locals = allocate_call_result_locals(&func, &results, 0);
+        // Both loop calls are read by the same statement, as are both later ones;
+        // the later pair can reuse the loop pair's locals
+        assert_eq!(results.len(), 4);
+        assert_eq!(locals.local_types.len(), 2);
+        let mut bases: Vec<_> = locals.bases.values().copied().collect();
+        bases.sort();
+        assert_eq!(bases, vec![0, 0, 1, 1]);
+    }
+}
@@ -3,26 +3,51 @@
 //! This module provides a backend for the Naga shader IR that compiles to WebAssembly
 //! bytecode with embedded DWARF debug information for browser DevTools integration.
 
+pub mod abi;
 mod backend;
 mod builtins;
 mod call_lowering;
+pub mod constants;
 mod control_flow;
+pub mod coverage;
 pub mod debug;
 mod expressions;
 pub mod function_abi;
 pub mod functions;
+pub mod interface_layout;
+mod locals;
 mod memory_layout;
 pub mod output_layout;
+#[cfg(test)]
+mod reference_semantics;
 pub mod types;
+pub mod varying_packing;
 
 use naga::{valid::ModuleInfo, Module};
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Configuration for WASM generation
 #[derive(Debug, Clone)]
 pub struct WasmBackendConfig {
     /// Enable shader stepping via JS stub
     pub debug_shaders: bool,
+    /// Count basic block executions (see [`coverage`])
+    pub coverage: bool,
+    /// Count executed instructions per source line (see [`coverage`]);
+    /// instruments like `coverage`, with one block per line
+    pub profile: bool,
+    /// Sample textures through the host import `webgpu_texture_sample`
+    /// instead of the emitted helpers, for WebGPU sampler and mip state
+    pub host_sampling: bool,
+    /// Emit debug sections (the embedded GLSL source and DWARF) into
+    /// [`WasmModule::dwarf_bytes`] instead of the module, both tagged with
+    /// the same `build_id` section. [`debug::merge_debug_info`] puts them
+    /// back together for local debugging.
+    pub split_debug: bool,
+    /// Validate every generated module with wasmparser, against `features`,
+    /// and fail compilation with [`BackendError::Validation`] if it is invalid
+    pub validate: bool,
     /// Optimize generated WASM (future: dead code elimination, constant folding)
     pub optimize: bool,
     /// Target WASM features (SIMD, threads, etc.)
@@ -33,6 +58,11 @@ impl Default for WasmBackendConfig {
     fn default() -> Self {
         Self {
             debug_shaders: true,
+            coverage: false,
+            profile: false,
+            host_sampling: false,
+            split_debug: false,
+            validate: false,
             optimize: false,
             features: WasmFeatures::default(),
         }
@@ -55,7 +85,9 @@ pub struct WasmFeatures {
 pub struct WasmModule {
     /// WASM bytecode
     pub wasm_bytes: Vec<u8>,
-    /// Separate DWARF debug data (optional, embedded in custom sections)
+    /// Split debug artifact (with [`WasmBackendConfig::split_debug`]): a
+    /// WASM module holding only the debug custom sections and the build ID
+    /// of `wasm_bytes`
     pub dwarf_bytes: Option<Vec<u8>>,
     /// JS debug stub (optional, for shader stepping)
     pub debug_stub: Option<String>,
@@ -65,6 +97,24 @@ pub struct WasmModule {
     pub memory_layout: MemoryLayout,
     /// Allocated index in the host's shared function table
     pub table_index: u32,
+    /// Basic block counters (when coverage is enabled); the module writes
+    /// into them, so they must outlive every instance of `wasm_bytes`
+    pub coverage: Option<Arc<coverage::ShaderCoverage>>,
+    /// Constant tables (when any are indexed at runtime); the module reads
+    /// them at their address, so they must outlive every instance
+    pub constant_data: Option<Arc<constants::ConstantData>>,
+    /// Interpolation of each fragment input (empty for vertex shaders)
+    pub varyings: Vec<output_layout::VaryingInfo>,
+    /// Shading rate the fragment shader requires (per pixel for vertex shaders)
+    pub shading_rate: output_layout::ShadingRate,
+}
+
+impl WasmModule {
+    /// Imports, exports, memories and tables of `wasm_bytes`, for checking
+    /// an instantiation environment before instantiating.
+    pub fn describe(&self) -> Result<abi::ModuleDescription, BackendError> {
+        abi::describe(&self.wasm_bytes)
+    }
 }
 
 /// Memory layout for shader execution
@@ -110,6 +160,8 @@ pub struct CompileConfig<'a> {
     pub attribute_locations: &'a HashMap<String, u32>,
     pub uniform_locations: &'a HashMap<String, u32>,
     pub varying_locations: &'a HashMap<String, u32>,
+    /// First component of each packed varying within its location.
+    pub varying_components: &'a HashMap<String, u32>,
     /// Program-level varying type map (name -> (type_code, components)).
     pub varying_types: &'a HashMap<String, (u8, u32)>,
     /// Program-level uniform type map: name -> (type_code, components)
@@ -135,10 +187,38 @@ impl WasmBackend {
         config: CompileConfig,
         name: Option<&str>,
     ) -> Result<WasmModule, BackendError> {
-        backend::compile_module(self, config, name)
+        let module = backend::compile_module(self, config, name)?;
+        if self.config.validate {
+            validate_wasm(&module.wasm_bytes, &self.config.features)?;
+        }
+        Ok(module)
     }
 }
 
+/// Validate generated WASM with wasmparser.
+///
+/// Only the proposals the backend relies on are enabled (mutable globals,
+/// saturating float-to-int, sign extension, multi-value), plus those turned
+/// on in `features`, so stray instructions of other proposals are reported
+/// as encoder bugs here rather than by the engine that instantiates them.
+pub fn validate_wasm(wasm_bytes: &[u8], features: &WasmFeatures) -> Result<(), BackendError> {
+    use wasmparser::WasmFeatures as F;
+
+    let mut enabled = F::FLOATS
+        | F::MUTABLE_GLOBAL
+        | F::SATURATING_FLOAT_TO_INT
+        | F::SIGN_EXTENSION
+        | F::MULTI_VALUE;
+    enabled.set(F::SIMD, features.simd);
+    enabled.set(F::BULK_MEMORY, features.bulk_memory);
+    enabled.set(F::REFERENCE_TYPES, features.reference_types);
+
+    wasmparser::Validator::new_with_features(enabled)
+        .validate_all(wasm_bytes)
+        .map(|_| ())
+        .map_err(|e| BackendError::Validation(e.to_string()))
+}
+
 /// Error types for WASM backend compilation
 #[derive(Debug, thiserror::Error)]
 pub enum BackendError {
@@ -159,6 +239,12 @@ pub enum BackendError {
 
     #[error("Internal compiler error: {0}")]
     InternalError(String),
+
+    #[error("Generated WASM failed validation: {0}")]
+    Validation(String),
+
+    #[error("Shader ABI version mismatch: module has {found:?}, runtime expects {expected}")]
+    AbiMismatch { expected: u32, found: Option<u32> },
 }
 
 /// Context for translating a single Naga IR function into a WebAssembly function.
@@ -195,6 +281,10 @@ pub struct TranslationContext<'a> {
     pub debug_shaders: bool,
     /// Index of the debug_step host function (if imported)
     pub debug_step_idx: Option<u32>,
+    /// Basic block tracker when coverage instrumentation is enabled
+    pub coverage: Option<&'a mut coverage::CoverageBuilder>,
+    /// Constant tables indexed at runtime, laid out in the table buffer
+    pub constant_data: Option<&'a mut constants::ConstantDataBuilder>,
     /// Typifier used to query the inferred types of Naga expressions.
     pub typifier: &'a naga::front::Typifier,
     /// Mapping from Naga function handles to their corresponding WASM function
@@ -212,6 +302,8 @@ pub struct TranslationContext<'a> {
     pub uniform_locations: &'a HashMap<String, u32>,
     /// Mapping from varying names to their locations.
     pub varying_locations: &'a HashMap<String, u32>,
+    /// Mapping from varying names to their first component within the location.
+    pub varying_components: &'a HashMap<String, u32>,
     /// Program-level varying type map (name -> (type_code, components)).
     pub varying_types: &'a HashMap<String, (u8, u32)>,
     /// Program-level uniform type map (name -> (type_code, components)).
@@ -250,6 +342,8 @@ pub struct TranslationContext<'a> {
     pub webgl_sampler_3d_idx: Option<u32>,
     /// Index of the emitted module-local helper `__webgl_image_load`
     pub webgl_image_load_idx: Option<u32>,
+    /// Index of the `webgpu_texture_sample` import, used for ImageSample when set
+    pub webgpu_sample_idx: Option<u32>,
     /// Base index for the 4 explicit f32 locals used for texture sampling results
     pub sample_f32_locals: Option<u32>,
     /// Mapping of Naga math functions to their imported WASM function indices
@@ -33,6 +33,8 @@ pub const TEX_WRAP_R_OFFSET: u64 = 32;
 pub const TEX_LAYOUT_OFFSET: u64 = 36;
 pub const TEX_MIN_FILTER_OFFSET: u64 = 40;
 pub const TEX_MAG_FILTER_OFFSET: u64 = 44;
+/// Level of detail bias added to every sample (f32)
+pub const TEX_LOD_BIAS_OFFSET: u64 = 48;
 
 /// Frame stack configuration.
 pub const FRAME_STACK_SIZE: u32 = 0x20000; // 128KB size
@@ -44,6 +46,33 @@ pub const MAX_GROUPS: u32 = 4;
 pub const CONTEXT_BLOCK_SIZE: u32 = MAX_GROUPS * MAX_BINDINGS_PER_GROUP * 4; // 64 bindings * 4 bytes = 256 bytes
 pub const BINDING_POINTER_SIZE: u32 = 4;
 
+/// Vertex attribute locations (`GL_MAX_VERTEX_ATTRIBS`).
+pub const MAX_VERTEX_ATTRIBS: u32 = 16;
+
+/// Uniform locations, one per binding pointer of the context block.
+pub const MAX_UNIFORM_LOCATIONS: u32 = MAX_GROUPS * MAX_BINDINGS_PER_GROUP;
+
+/// Uniform vectors per stage (`GL_MAX_*_UNIFORM_VECTORS`): the 64 bytes of
+/// data behind each uniform location hold four.
+pub const MAX_UNIFORM_VECTORS: u32 = MAX_UNIFORM_LOCATIONS * 4;
+
+/// Locations available to user varyings (`GL_MAX_VARYING_VECTORS`).
+pub const MAX_VARYING_VECTORS: u32 = 15;
+
+/// Size of one vertex's varying buffer in 32-bit words: `gl_Position`,
+/// `gl_PointSize` and the user varyings, with room after them for the
+/// private globals of the vertex shader.
+pub const VARYING_BUFFER_WORDS: usize = 128;
+
+/// Offset of `gl_SampleID` in a fragment shader's varying buffer. The
+/// rasterizer stores the sample being shaded there, over the interpolated
+/// `gl_PointSize`, which fragment shaders cannot read.
+pub const SAMPLE_INDEX_OFFSET: u32 = 16;
+
+/// Offset of `gl_PointCoord` in a fragment shader's varying buffer, after
+/// `gl_SampleID` in the same unused slot. Only points store it.
+pub const POINT_COORD_OFFSET: u32 = 20;
+
 /// Compute the memory destination for a shader output binding.
 #[inline]
 pub fn compute_output_destination(binding: &Binding, stage: ShaderStage) -> (u32, u32) {
@@ -69,6 +98,13 @@ pub fn compute_input_offset(location: u32, stage: ShaderStage) -> (u32, u32) {
     }
 }
 
+/// Compute the offset of a packed varying: component `component` of `location`.
+#[inline]
+pub fn compute_varying_offset(location: u32, component: u32) -> (u32, u32) {
+    let (offset, base_ptr) = compute_input_offset(location, ShaderStage::Fragment);
+    (offset + component * 4, base_ptr)
+}
+
 /// Returns the index into the context block for a (group, binding) pair.
 #[inline]
 pub fn get_context_block_index(group: u32, binding: u32) -> u32 {
@@ -142,6 +178,165 @@ pub fn get_uniform_map(
     map
 }
 
+/// How the rasterizer interpolates one fragment shader input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaryingInfo {
+    /// Varying location the input is read from (see [`compute_varying_offset`])
+    pub location: u32,
+    /// First component of the input within `location`
+    pub component: u32,
+    /// Number of 32-bit components the input occupies
+    pub components: u32,
+    /// `Perspective` (smooth), `Linear` (noperspective) or `Flat`
+    pub interpolation: naga::Interpolation,
+    /// `Centroid` samples inside the primitive; without multisampling this
+    /// is the pixel center, the same as `Center`
+    pub sampling: naga::Sampling,
+}
+
+impl VaryingInfo {
+    /// Index of the first component in the varying buffer, in 32-bit words.
+    pub fn first_component(&self) -> u32 {
+        compute_varying_offset(self.location, self.component).0 / 4
+    }
+}
+
+/// Interpolation metadata for the inputs of a fragment entry point.
+///
+/// Locations resolve exactly as input loads do: the program-assigned
+/// location and component for the argument's name, falling back to its
+/// `layout(location)`. Inputs without a qualifier are smooth, except
+/// integers, which are flat.
+pub fn fragment_varyings(
+    module: &naga::Module,
+    entry_point: Option<&str>,
+    varying_locations: &std::collections::HashMap<String, u32>,
+    varying_components: &std::collections::HashMap<String, u32>,
+) -> Vec<VaryingInfo> {
+    let Some(ep) = module.entry_points.iter().find(|ep| {
+        ep.stage == ShaderStage::Fragment && entry_point.is_none_or(|name| ep.name == name)
+    }) else {
+        return Vec::new();
+    };
+
+    let mut varyings = Vec::new();
+    for arg in &ep.function.arguments {
+        let Some(Binding::Location {
+            location,
+            interpolation,
+            sampling,
+            ..
+        }) = arg.binding
+        else {
+            continue;
+        };
+        let name = arg.name.as_ref();
+        let location = name
+            .and_then(|name| varying_locations.get(name))
+            .copied()
+            .unwrap_or(location);
+        let component = name
+            .and_then(|name| varying_components.get(name))
+            .copied()
+            .unwrap_or(0);
+
+        let inner = &module.types[arg.ty].inner;
+        varyings.push(VaryingInfo {
+            location,
+            component,
+            components: super::types::type_size(inner).map_or(4, |size| size.div_ceil(4)),
+            interpolation: super::varying_packing::resolve_interpolation(inner, interpolation),
+            sampling: sampling.unwrap_or(naga::Sampling::Center),
+        });
+    }
+    varyings
+}
+
+/// How often the rasterizer invokes a fragment shader for a covered pixel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShadingRate {
+    /// Once, at the pixel center (or the centroid of its covered samples)
+    #[default]
+    PerPixel,
+    /// Once for every covered sample, at the sample's position
+    PerSample,
+}
+
+impl ShadingRate {
+    /// Fragment shader invocations for a pixel of `samples` samples.
+    pub fn invocations_per_pixel(self, samples: u32) -> u32 {
+        match self {
+            ShadingRate::PerPixel => 1,
+            ShadingRate::PerSample => samples.max(1),
+        }
+    }
+}
+
+/// Shading rate a fragment entry point requires: per sample when it reads
+/// `gl_SampleID` or has `sample`-qualified inputs, per pixel otherwise.
+///
+/// `centroid` inputs shade per pixel. The rasterizer covers a pixel when its
+/// center is inside the primitive, so the centroid of the covered samples is
+/// the center until it stores more than one sample per pixel.
+pub fn fragment_shading_rate(module: &naga::Module, entry_point: Option<&str>) -> ShadingRate {
+    let Some(ep) = module.entry_points.iter().find(|ep| {
+        ep.stage == ShaderStage::Fragment && entry_point.is_none_or(|name| ep.name == name)
+    }) else {
+        return ShadingRate::PerPixel;
+    };
+
+    let per_sample = ep.function.arguments.iter().any(|arg| {
+        matches!(
+            arg.binding,
+            Some(Binding::BuiltIn(BuiltIn::SampleIndex))
+                | Some(Binding::Location {
+                    sampling: Some(naga::Sampling::Sample),
+                    ..
+                })
+        )
+    });
+    if per_sample {
+        ShadingRate::PerSample
+    } else {
+        ShadingRate::PerPixel
+    }
+}
+
+/// Position a vertex shader that never writes `gl_Position` produces: the
+/// clip-space origin, as permissive drivers do.
+pub const DEFAULT_POSITION: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+/// Whether the vertex entry point writes `gl_Position`, either through its
+/// result or through the `gl_Position` global. The GLSL front end only adds
+/// the builtin once the shader refers to it, so a shader that never does
+/// (e.g. one that only sets `gl_PointSize`) has neither. Returns true when
+/// there is no vertex entry point.
+pub fn vertex_writes_position(module: &naga::Module, entry_point: Option<&str>) -> bool {
+    let Some(ep) = module.entry_points.iter().find(|ep| {
+        ep.stage == ShaderStage::Vertex && entry_point.is_none_or(|name| ep.name == name)
+    }) else {
+        return true;
+    };
+
+    let is_position = |binding: &Option<Binding>| {
+        matches!(binding, Some(Binding::BuiltIn(BuiltIn::Position { .. })))
+    };
+    let in_result = ep.function.result.as_ref().is_some_and(|result| {
+        is_position(&result.binding)
+            || match &module.types[result.ty].inner {
+                naga::TypeInner::Struct { members, .. } => {
+                    members.iter().any(|m| is_position(&m.binding))
+                }
+                _ => false,
+            }
+    });
+    in_result
+        || module
+            .global_variables
+            .iter()
+            .any(|(_, var)| matches!(var.name.as_deref(), Some("gl_Position" | "gl_Position_1")))
+}
+
 /// Validate that a binding is supported for the given shader stage.
 pub fn is_binding_valid(binding: &Binding, stage: ShaderStage) -> bool {
     matches!(
@@ -150,6 +345,11 @@ pub fn is_binding_valid(binding: &Binding, stage: ShaderStage) -> bool {
             Binding::BuiltIn(BuiltIn::Position { .. }),
             ShaderStage::Vertex
         ) | (Binding::BuiltIn(BuiltIn::FragDepth), ShaderStage::Fragment)
+            | (
+                Binding::BuiltIn(BuiltIn::SampleIndex),
+                ShaderStage::Fragment
+            )
+            | (Binding::BuiltIn(BuiltIn::PointCoord), ShaderStage::Fragment)
             | (Binding::BuiltIn(BuiltIn::PointSize), ShaderStage::Vertex)
             | (Binding::BuiltIn(BuiltIn::VertexIndex), ShaderStage::Vertex)
             | (
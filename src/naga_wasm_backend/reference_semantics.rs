@@ -0,0 +1,973 @@
+//! Differential tests of generated code against reference GLSL semantics
+//!
+//! [`eval`] compiles a fragment shader computing one GLSL expression of its
+//! uniforms through the same parse, layout and backend steps as a program
+//! link, runs the generated WASM under wasmi and returns the words of the
+//! result. The tests check every builtin and operator the backend translates
+//! against a Rust model of its GLSL ES 3.00 definition, over inputs that
+//! cross sign changes, rounding ties and the edges of each domain.
+//!
+//! Texture lookups and derivatives depend on pipeline state rather than on
+//! their arguments alone, and are left to the JS suite. `matrixCompMult`,
+//! `modf` and `frexp` are not covered: naga's GLSL frontend rejects them.
+
+use super::interface_layout::{compute_layout, LinkLimits, LocationBindings};
+use super::{abi, CompileConfig, WasmBackend, WasmBackendConfig};
+use crate::webgl2_context::types::GL_FRAGMENT_SHADER;
+use wasmi::{Caller, Engine, Global, Linker, Memory, MemoryType, Module, Mutability, Store, Val};
+
+/// Addresses the harness gives the regions a shader addresses through its
+/// pointer globals; the frame stack grows up from `FRAME_SP`.
+const UNIFORM_PTR: u32 = 0x1_0000;
+const VARYING_PTR: u32 = 0x2_0000;
+const PRIVATE_PTR: u32 = 0x3_0000;
+const FRAME_SP: u32 = 0x4_0000;
+const MEMORY_PAGES: u32 = 128;
+
+/// Uniforms holding the inputs, in order
+const INPUT_NAMES: [&str; 4] = ["a", "b", "c", "d"];
+
+/// A uniform an expression reads: its GLSL type and the words of its value
+#[derive(Debug, Clone)]
+struct Input {
+    glsl_type: String,
+    words: Vec<u32>,
+}
+
+fn vector_type(prefix: &str, scalar: &str, len: usize) -> String {
+    if len == 1 {
+        scalar.to_string()
+    } else {
+        format!("{prefix}vec{len}")
+    }
+}
+
+/// A `float`, or a `vecN` of `xs.len()` components
+fn float(xs: &[f32]) -> Input {
+    Input {
+        glsl_type: vector_type("", "float", xs.len()),
+        words: xs.iter().map(|x| x.to_bits()).collect(),
+    }
+}
+
+/// An `int` or `ivecN`
+fn int(xs: &[i32]) -> Input {
+    Input {
+        glsl_type: vector_type("i", "int", xs.len()),
+        words: xs.iter().map(|&x| x as u32).collect(),
+    }
+}
+
+/// A `uint` or `uvecN`
+fn uint(xs: &[u32]) -> Input {
+    Input {
+        glsl_type: vector_type("u", "uint", xs.len()),
+        words: xs.to_vec(),
+    }
+}
+
+/// A matrix of `columns` columns, given column by column
+fn mat(columns: usize, xs: &[f32]) -> Input {
+    let rows = xs.len() / columns;
+    Input {
+        glsl_type: if rows == columns {
+            format!("mat{columns}")
+        } else {
+            format!("mat{columns}x{rows}")
+        },
+        words: xs.iter().map(|x| x.to_bits()).collect(),
+    }
+}
+
+/// Components of a scalar or vector type
+fn components(glsl_type: &str) -> usize {
+    glsl_type
+        .chars()
+        .last()
+        .and_then(|c| c.to_digit(10))
+        .map_or(1, |n| n as usize)
+}
+
+/// The output type and the statement storing `r`, of `result_type`, in
+/// the four-component `color` output. Booleans are stored as 0 or 1.
+fn output_of(result_type: &str) -> (&'static str, String) {
+    let n = components(result_type);
+    let (output, zero) = match result_type.trim_end_matches(char::is_numeric) {
+        "float" | "vec" => ("vec4", "0.0"),
+        "int" | "ivec" => ("ivec4", "0"),
+        "uint" | "uvec" | "bool" | "bvec" => ("uvec4", "0u"),
+        other => panic!("unsupported result type {other}"),
+    };
+    let value = match (result_type.starts_with('b'), n) {
+        (true, 1) => "uint(r)".to_string(),
+        (true, n) => format!("uvec{n}(r)"),
+        _ => "r".to_string(),
+    };
+    let args: Vec<String> = std::iter::once(value)
+        .chain(std::iter::repeat_n(zero.to_string(), 4 - n))
+        .collect();
+    (output, format!("color = {output}({});", args.join(", ")))
+}
+
+fn fragment_source(result_type: &str, statements: &str, inputs: &[Input]) -> String {
+    let (output, store) = output_of(result_type);
+    let mut source =
+        String::from("#version 300 es\nprecision highp float;\nprecision highp int;\n");
+    for (input, name) in inputs.iter().zip(INPUT_NAMES) {
+        source += &format!("uniform {} {};\n", input.glsl_type, name);
+    }
+    source += &format!(
+        "out {output} color;\nvoid main() {{\n    {result_type} r;\n    {statements}\n    {store}\n}}\n"
+    );
+    source
+}
+
+fn err(e: impl std::fmt::Display) -> String {
+    e.to_string()
+}
+
+/// Imports of a shader module, as the main module provides them. Matrix
+/// inverses come from the reference [`inverse`].
+fn host_linker(
+    engine: &Engine,
+    store: &mut Store<()>,
+    memory: Memory,
+) -> Result<Linker<()>, String> {
+    let mut linker = Linker::new(engine);
+    let (module, field) = abi::MEMORY_IMPORT;
+    linker.define(module, field, memory).map_err(err)?;
+    let pointers = [
+        ("ACTIVE_ATTR_PTR", 0),
+        ("ACTIVE_UNIFORM_PTR", UNIFORM_PTR),
+        ("ACTIVE_VARYING_PTR", VARYING_PTR),
+        ("ACTIVE_PRIVATE_PTR", PRIVATE_PTR),
+        ("ACTIVE_TEXTURE_PTR", 0),
+        ("ACTIVE_FRAME_SP", FRAME_SP),
+    ];
+    for (name, value) in pointers {
+        let global = Global::new(&mut *store, Val::I32(value as i32), Mutability::Var);
+        linker.define("env", name, global).map_err(err)?;
+    }
+
+    let unary: [(&str, extern "C" fn(f32) -> f32); 16] = [
+        ("gl_sin", crate::gl_sin),
+        ("gl_cos", crate::gl_cos),
+        ("gl_tan", crate::gl_tan),
+        ("gl_asin", crate::gl_asin),
+        ("gl_acos", crate::gl_acos),
+        ("gl_atan", crate::gl_atan),
+        ("gl_exp", crate::gl_exp),
+        ("gl_exp2", crate::gl_exp2),
+        ("gl_log", crate::gl_log),
+        ("gl_log2", crate::gl_log2),
+        ("gl_sinh", crate::gl_sinh),
+        ("gl_cosh", crate::gl_cosh),
+        ("gl_tanh", crate::gl_tanh),
+        ("gl_asinh", crate::gl_asinh),
+        ("gl_acosh", crate::gl_acosh),
+        ("gl_atanh", crate::gl_atanh),
+    ];
+    for (name, f) in unary {
+        linker
+            .func_wrap("env", name, move |x: f32| f(x))
+            .map_err(err)?;
+    }
+    linker
+        .func_wrap("env", "gl_atan2", |y: f32, x: f32| crate::gl_atan2(y, x))
+        .map_err(err)?;
+    linker
+        .func_wrap("env", "gl_pow", |x: f32, y: f32| crate::gl_pow(x, y))
+        .map_err(err)?;
+    for (name, n) in [("gl_inverse_mat2", 2), ("gl_inverse_mat3", 3)] {
+        let invert = move |mut caller: Caller<'_, ()>, src: i32, dst: i32| {
+            let mut bytes = vec![0u8; n * n * 4];
+            memory.read(&caller, src as usize, &mut bytes).unwrap();
+            let m: Vec<f32> = bytes
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect();
+            let out: Vec<u8> = inverse(&m, n)
+                .iter()
+                .flat_map(|x| x.to_le_bytes())
+                .collect();
+            memory.write(&mut caller, dst as usize, &out).unwrap();
+        };
+        linker.func_wrap("env", name, invert).map_err(err)?;
+    }
+    Ok(linker)
+}
+
+/// Compile `source` as a program link does, then run it once with the
+/// uniforms set to `inputs` and return the words of its output.
+fn run(source: &str, inputs: &[Input]) -> Result<[u32; 4], String> {
+    let (module, info) = crate::webgl2_context::shaders::compile_glsl(GL_FRAGMENT_SHADER, source)?;
+    let layout = compute_layout(
+        None,
+        Some(&module),
+        &LocationBindings::default(),
+        &LinkLimits::default(),
+        0,
+    )?;
+    let backend = WasmBackend::new(WasmBackendConfig {
+        debug_shaders: false,
+        validate: true,
+        ..Default::default()
+    });
+    let wasm = backend
+        .compile(
+            CompileConfig {
+                module: &module,
+                info: &info,
+                source,
+                stage: naga::ShaderStage::Fragment,
+                entry_point: Some("main"),
+                attribute_locations: &layout.attribute_locations,
+                uniform_locations: &layout.uniform_locations,
+                varying_locations: &layout.varying_locations,
+                varying_components: &layout.varying_components,
+                varying_types: &layout.varying_types,
+                uniform_types: &layout.uniform_types,
+                attribute_types: &layout.attribute_types,
+            },
+            Some("reference.frag"),
+        )
+        .map_err(err)?;
+
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let memory_type = MemoryType::new(MEMORY_PAGES, None).map_err(err)?;
+    let memory = Memory::new(&mut store, memory_type).map_err(err)?;
+    for (input, name) in inputs.iter().zip(INPUT_NAMES) {
+        // Uniforms the expression does not read may be optimized out
+        let Some(offset) = layout.uniform_offset(name) else {
+            continue;
+        };
+        let bytes: Vec<u8> = input.words.iter().flat_map(|w| w.to_le_bytes()).collect();
+        memory
+            .write(&mut store, (UNIFORM_PTR + offset) as usize, &bytes)
+            .map_err(err)?;
+    }
+
+    let linker = host_linker(&engine, &mut store, memory)?;
+    let module = Module::new(&engine, &wasm.wasm_bytes).map_err(err)?;
+    let instance = linker
+        .instantiate(&mut store, &module)
+        .map_err(err)?
+        .start(&mut store)
+        .map_err(err)?;
+    let entry = abi::entry_export_name(naga::ShaderStage::Fragment);
+    let main = instance
+        .get_typed_func::<(i32, i32), ()>(&store, entry)
+        .map_err(err)?;
+    main.call(&mut store, (VARYING_PTR as i32, PRIVATE_PTR as i32))
+        .map_err(err)?;
+
+    let mut bytes = [0u8; 16];
+    memory
+        .read(&store, PRIVATE_PTR as usize, &mut bytes)
+        .map_err(err)?;
+    Ok(std::array::from_fn(|i| {
+        u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap())
+    }))
+}
+
+/// Run `statements`, which assign `r` of `result_type` from uniforms `a`,
+/// `b`, ... holding `inputs`, and return the words of `r`.
+#[track_caller]
+fn eval_statements(result_type: &str, statements: &str, inputs: &[Input]) -> Vec<u32> {
+    let source = fragment_source(result_type, statements, inputs);
+    match run(&source, inputs) {
+        Ok(words) => words[..components(result_type)].to_vec(),
+        Err(e) => panic!("{e}\n{source}"),
+    }
+}
+
+/// Evaluate the `result_type` expression `expr` of uniforms `a`, `b`, ...
+#[track_caller]
+fn eval(result_type: &str, expr: &str, inputs: &[Input]) -> Vec<u32> {
+    eval_statements(result_type, &format!("r = {expr};"), inputs)
+}
+
+/// Evaluate a `columns`x`rows` matrix expression, a column at a time
+#[track_caller]
+fn eval_matrix(columns: usize, rows: usize, expr: &str, inputs: &[Input]) -> Vec<f32> {
+    (0..columns)
+        .flat_map(|i| {
+            floats(&eval(
+                &format!("vec{rows}"),
+                &format!("({expr})[{i}]"),
+                inputs,
+            ))
+        })
+        .collect()
+}
+
+fn floats(words: &[u32]) -> Vec<f32> {
+    words.iter().map(|&w| f32::from_bits(w)).collect()
+}
+
+/// `m`, `n`x`n` and column-major, without row `row` and column `column`
+fn minor(m: &[f64], n: usize, row: usize, column: usize) -> Vec<f64> {
+    (0..n)
+        .filter(|&j| j != column)
+        .flat_map(|j| (0..n).filter(move |&i| i != row).map(move |i| m[j * n + i]))
+        .collect()
+}
+
+fn det64(m: &[f64], n: usize) -> f64 {
+    if n == 1 {
+        return m[0];
+    }
+    // Laplace expansion along the first row
+    (0..n)
+        .map(|j| {
+            let sign = if j % 2 == 0 { 1.0 } else { -1.0 };
+            sign * m[j * n] * det64(&minor(m, n, 0, j), n - 1)
+        })
+        .sum()
+}
+
+/// Determinant of the `n`x`n` matrix `m`
+fn determinant(m: &[f32], n: usize) -> f32 {
+    let m: Vec<f64> = m.iter().map(|&x| x as f64).collect();
+    det64(&m, n) as f32
+}
+
+/// Inverse of the `n`x`n` matrix `m`: its adjugate over its determinant
+fn inverse(m: &[f32], n: usize) -> Vec<f32> {
+    let m: Vec<f64> = m.iter().map(|&x| x as f64).collect();
+    let m = &m;
+    let det = det64(m, n);
+    (0..n)
+        .flat_map(|j| {
+            (0..n).map(move |i| {
+                let sign = if (i + j) % 2 == 0 { 1.0 } else { -1.0 };
+                let cofactor = if n == 1 {
+                    1.0
+                } else {
+                    det64(&minor(m, n, j, i), n - 1)
+                };
+                (sign * cofactor / det) as f32
+            })
+        })
+        .collect()
+}
+
+/// `a`, with `rows` rows, times `b`, both column-major
+fn mat_mul(a: &[f32], rows: usize, b: &[f32], b_columns: usize) -> Vec<f32> {
+    let inner = a.len() / rows;
+    (0..b_columns)
+        .flat_map(|j| {
+            (0..rows).map(move |i| {
+                (0..inner)
+                    .map(|k| a[k * rows + i] * b[j * inner + k])
+                    .sum::<f32>()
+            })
+        })
+        .collect()
+}
+
+/// Transpose of the column-major `m`, with `rows` rows
+fn transpose(m: &[f32], rows: usize) -> Vec<f32> {
+    let columns = m.len() / rows;
+    (0..rows)
+        .flat_map(|i| (0..columns).map(move |j| m[j * rows + i]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOLERANCE: f32 = 1e-5;
+
+    /// A builtin name, its reference implementation and the inputs it is checked on
+    type UnaryCase<'a> = (&'a str, fn(f32) -> f32, &'a [[f32; 4]]);
+    /// An operator or builtin and its reference implementation
+    type BinaryCase<'a, T, R = T> = (&'a str, fn(T, T) -> R);
+    /// A comparison operator, its vector relational builtin and its reference
+    type ComparisonCase<'a> = (&'a str, &'a str, fn(f32, f32) -> bool);
+
+    #[track_caller]
+    fn assert_close(what: &str, actual: &[f32], expected: &[f32], tolerance: f32) {
+        let close = |a: f32, e: f32| {
+            (a.is_nan() && e.is_nan()) || a == e || (a - e).abs() <= tolerance * e.abs().max(1.0)
+        };
+        assert!(
+            actual.len() == expected.len()
+                && actual.iter().zip(expected).all(|(&a, &e)| close(a, e)),
+            "{what}: got {actual:?}, expected {expected:?}"
+        );
+    }
+
+    /// Check a float-valued `expr` against `expected`
+    #[track_caller]
+    fn check(result_type: &str, expr: &str, inputs: &[Input], expected: &[f32]) {
+        let actual = floats(&eval(result_type, expr, inputs));
+        assert_close(expr, &actual, expected, TOLERANCE);
+    }
+
+    fn map(xs: &[f32], f: impl Fn(f32) -> f32) -> Vec<f32> {
+        xs.iter().map(|&x| f(x)).collect()
+    }
+
+    fn zip(xs: &[f32], ys: &[f32], f: impl Fn(f32, f32) -> f32) -> Vec<f32> {
+        xs.iter().zip(ys).map(|(&x, &y)| f(x, y)).collect()
+    }
+
+    fn sign(x: f32) -> f32 {
+        if x > 0.0 {
+            1.0
+        } else if x < 0.0 {
+            -1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn dot(a: &[f32], b: &[f32]) -> f32 {
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    fn scale(a: &[f32], s: f32) -> Vec<f32> {
+        map(a, |x| x * s)
+    }
+
+    fn sub(a: &[f32], b: &[f32]) -> Vec<f32> {
+        zip(a, b, |x, y| x - y)
+    }
+
+    fn bools(words: &[u32]) -> Vec<bool> {
+        words.iter().map(|&w| w != 0).collect()
+    }
+
+    #[test]
+    fn test_unary_float_builtins() {
+        let any: &[[f32; 4]] = &[[-2.5, -0.75, 0.5, 3.0], [0.0, 1.25, -1.5, 7.5]];
+        let unit: &[[f32; 4]] = &[[-1.0, -0.5, 0.25, 1.0], [0.0, 0.75, -0.9, 0.1]];
+        let open_unit: &[[f32; 4]] = &[[-0.9, -0.5, 0.0, 0.75]];
+        let positive: &[[f32; 4]] = &[[0.25, 1.0, 2.5, 100.0], [0.5, 3.0, 8.0, 1e-3]];
+        let at_least_one: &[[f32; 4]] = &[[1.0, 1.5, 4.0, 10.0]];
+        let ties: &[[f32; 4]] = &[[-2.5, -0.5, 0.5, 1.5], [2.5, 3.5, -1.25, 0.75]];
+        let inversesqrt = |x: f32| 1.0 / x.sqrt();
+        let fract = |x: f32| x - x.floor();
+        let unary: &[UnaryCase] = &[
+            ("radians", f32::to_radians, any),
+            ("degrees", f32::to_degrees, any),
+            ("sin", f32::sin, any),
+            ("cos", f32::cos, any),
+            ("tan", f32::tan, any),
+            ("asin", f32::asin, unit),
+            ("acos", f32::acos, unit),
+            ("atan", f32::atan, any),
+            ("sinh", f32::sinh, any),
+            ("cosh", f32::cosh, any),
+            ("tanh", f32::tanh, any),
+            ("asinh", f32::asinh, any),
+            ("acosh", f32::acosh, at_least_one),
+            ("atanh", f32::atanh, open_unit),
+            ("exp", f32::exp, any),
+            ("log", f32::ln, positive),
+            ("exp2", f32::exp2, any),
+            ("log2", f32::log2, positive),
+            ("sqrt", f32::sqrt, positive),
+            ("inversesqrt", inversesqrt, positive),
+            ("abs", f32::abs, any),
+            ("sign", sign, any),
+            ("floor", f32::floor, any),
+            ("trunc", f32::trunc, any),
+            ("round", f32::round_ties_even, ties),
+            ("roundEven", f32::round_ties_even, ties),
+            ("ceil", f32::ceil, any),
+            ("fract", fract, any),
+        ];
+        for &(name, reference, samples) in unary {
+            for xs in samples {
+                check(
+                    "vec4",
+                    &format!("{name}(a)"),
+                    &[float(xs)],
+                    &map(xs, reference),
+                );
+            }
+            let x = samples[0][1];
+            check(
+                "float",
+                &format!("{name}(a)"),
+                &[float(&[x])],
+                &[reference(x)],
+            );
+        }
+    }
+
+    #[test]
+    fn test_binary_float_builtins() {
+        let xs = [-2.5, -0.75, 0.5, 3.0];
+        let ys = [1.5, -2.0, 0.25, 3.0];
+        let modulo = |x: f32, y: f32| x - y * (x / y).floor();
+        let step = |edge: f32, x: f32| if x < edge { 0.0 } else { 1.0 };
+        let binary: &[BinaryCase<f32>] = &[
+            ("atan", f32::atan2),
+            ("mod", modulo),
+            ("min", f32::min),
+            ("max", f32::max),
+            ("step", step),
+        ];
+        let inputs = [float(&xs), float(&ys)];
+        for &(name, reference) in binary {
+            check(
+                "vec4",
+                &format!("{name}(a, b)"),
+                &inputs,
+                &zip(&xs, &ys, reference),
+            );
+            check(
+                "float",
+                &format!("{name}(a.w, b.y)"),
+                &inputs,
+                &[reference(xs[3], ys[1])],
+            );
+        }
+
+        // Forms taking a scalar for a vector argument
+        let y = ys[1];
+        check("vec4", "mod(a, b.y)", &inputs, &map(&xs, |x| modulo(x, y)));
+        check("vec4", "min(a, b.y)", &inputs, &map(&xs, |x| x.min(y)));
+        check("vec4", "max(a, b.y)", &inputs, &map(&xs, |x| x.max(y)));
+        check("vec4", "step(b.y, a)", &inputs, &map(&xs, |x| step(y, x)));
+
+        let bases = [0.5, 2.0, 3.0, 10.0];
+        let inputs = [float(&bases), float(&xs)];
+        check("vec4", "pow(a, b)", &inputs, &zip(&bases, &xs, f32::powf));
+        check("float", "pow(a.y, b.w)", &inputs, &[bases[1].powf(xs[3])]);
+    }
+
+    #[test]
+    fn test_ternary_float_builtins() {
+        let xs = [-2.5, 0.25, 0.75, 3.0];
+        let lo = [-1.0, -1.0, 0.0, 0.0];
+        let hi = [1.0, 0.5, 0.25, 2.0];
+        let inputs = [float(&xs), float(&lo), float(&hi)];
+        let clamp = |x: f32, lo: f32, hi: f32| x.max(lo).min(hi);
+        let expected: Vec<f32> = (0..4).map(|i| clamp(xs[i], lo[i], hi[i])).collect();
+        check("vec4", "clamp(a, b, c)", &inputs, &expected);
+        check(
+            "vec4",
+            "clamp(a, b.x, c.x)",
+            &inputs,
+            &map(&xs, |x| clamp(x, lo[0], hi[0])),
+        );
+
+        let ys = [4.0, -1.0, 2.0, 3.0];
+        let ts = [0.0, 0.25, 1.0, 1.5];
+        let inputs = [float(&xs), float(&ys), float(&ts)];
+        let mix = |x: f32, y: f32, t: f32| x * (1.0 - t) + y * t;
+        let expected: Vec<f32> = (0..4).map(|i| mix(xs[i], ys[i], ts[i])).collect();
+        check("vec4", "mix(a, b, c)", &inputs, &expected);
+        check(
+            "vec4",
+            "mix(a, b, c.y)",
+            &inputs,
+            &zip(&xs, &ys, |x, y| mix(x, y, ts[1])),
+        );
+        // A false component takes x, a true one y
+        let expected = zip(&xs, &ys, |x, y| if x < y { y } else { x });
+        check("vec4", "mix(a, b, lessThan(a, b))", &inputs, &expected);
+
+        let e0 = [0.0, -1.0, 0.0, 1.0];
+        let e1 = [1.0, 1.0, 2.0, 3.0];
+        let xs = [0.5, -2.0, 1.5, 2.5];
+        let inputs = [float(&e0), float(&e1), float(&xs)];
+        let smoothstep = |e0: f32, e1: f32, x: f32| {
+            let t = ((x - e0) / (e1 - e0)).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        };
+        let expected: Vec<f32> = (0..4).map(|i| smoothstep(e0[i], e1[i], xs[i])).collect();
+        check("vec4", "smoothstep(a, b, c)", &inputs, &expected);
+        check(
+            "vec4",
+            "smoothstep(a.x, b.x, c)",
+            &inputs,
+            &map(&xs, |x| smoothstep(e0[0], e1[0], x)),
+        );
+    }
+
+    #[test]
+    fn test_geometric_builtins() {
+        let p = [1.0, -2.0, 2.0, 0.5];
+        let q = [0.5, 3.0, -1.5, 2.0];
+        for n in 2..=4 {
+            let ty = vector_type("", "float", n);
+            let (p, q) = (&p[..n], &q[..n]);
+            let inputs = [float(p), float(q)];
+            let length = dot(p, p).sqrt();
+            check("float", "length(a)", &inputs, &[length]);
+            check(
+                "float",
+                "distance(a, b)",
+                &inputs,
+                &[dot(&sub(p, q), &sub(p, q)).sqrt()],
+            );
+            check("float", "dot(a, b)", &inputs, &[dot(p, q)]);
+            check(&ty, "normalize(a)", &inputs, &scale(p, 1.0 / length));
+        }
+
+        let (p, q) = ([1.0, -2.0, 2.0], [0.5, 3.0, -1.5]);
+        let cross = [
+            p[1] * q[2] - p[2] * q[1],
+            p[2] * q[0] - p[0] * q[2],
+            p[0] * q[1] - p[1] * q[0],
+        ];
+        check("vec3", "cross(a, b)", &[float(&p), float(&q)], &cross);
+
+        // faceforward(N, I, Nref) flips N unless I points against Nref
+        let normal = [0.0, 1.0, 0.0];
+        for incident in [[1.0, -1.0, 0.0], [1.0, 1.0, 0.0]] {
+            let inputs = [float(&normal), float(&incident), float(&normal)];
+            let expected = if dot(&normal, &incident) < 0.0 {
+                normal.to_vec()
+            } else {
+                scale(&normal, -1.0)
+            };
+            check("vec3", "faceforward(a, b, c)", &inputs, &expected);
+        }
+
+        let incident = [0.6, -0.8, 0.0];
+        let inputs = [float(&incident), float(&normal)];
+        let reflected = sub(&incident, &scale(&normal, 2.0 * dot(&normal, &incident)));
+        check("vec3", "reflect(a, b)", &inputs, &reflected);
+
+        // Refraction, and total internal reflection past the critical angle
+        for eta in [0.75f32, 1.5] {
+            let inputs = [float(&incident), float(&normal), float(&[eta])];
+            let d = dot(&normal, &incident);
+            let k = 1.0 - eta * eta * (1.0 - d * d);
+            let expected = if k < 0.0 {
+                vec![0.0; 3]
+            } else {
+                sub(&scale(&incident, eta), &scale(&normal, eta * d + k.sqrt()))
+            };
+            check("vec3", "refract(a, b, c)", &inputs, &expected);
+        }
+    }
+
+    #[test]
+    fn test_matrix_operators_and_builtins() {
+        let m2 = [2.0, 1.0, -1.0, 3.0];
+        let m3 = [2.0, 0.0, 1.0, 1.0, 3.0, -1.0, 0.0, 1.0, 4.0];
+        let n3 = [1.0, -2.0, 0.5, 0.0, 1.0, 2.0, 3.0, 0.0, -1.0];
+        let m4 = [
+            2.0, 0.0, 0.0, 1.0, 1.0, 3.0, 0.0, 0.0, 0.0, 1.0, 4.0, 0.0, 0.0, 0.0, 1.0, 5.0,
+        ];
+        let m2x3 = [1.0, 2.0, 3.0, -1.0, 0.5, 4.0];
+        let (v2, v3, v4) = ([1.5, -2.0], [1.0, 2.0, -0.5], [1.0, -1.0, 2.0, 0.5]);
+
+        check(
+            "vec2",
+            "a * b",
+            &[mat(2, &m2), float(&v2)],
+            &mat_mul(&m2, 2, &v2, 1),
+        );
+        check(
+            "vec3",
+            "a * b",
+            &[mat(3, &m3), float(&v3)],
+            &mat_mul(&m3, 3, &v3, 1),
+        );
+        check(
+            "vec4",
+            "a * b",
+            &[mat(4, &m4), float(&v4)],
+            &mat_mul(&m4, 4, &v4, 1),
+        );
+        check(
+            "vec3",
+            "a * b",
+            &[mat(2, &m2x3), float(&v2)],
+            &mat_mul(&m2x3, 3, &v2, 1),
+        );
+        let row_times = mat_mul(&transpose(&m3, 3), 3, &v3, 1);
+        check("vec3", "b * a", &[mat(3, &m3), float(&v3)], &row_times);
+
+        let pair = [mat(3, &m3), mat(3, &n3)];
+        let product = eval_matrix(3, 3, "a * b", &pair);
+        assert_close("a * b", &product, &mat_mul(&m3, 3, &n3, 3), TOLERANCE);
+        let sum = eval_matrix(3, 3, "a + b", &pair);
+        assert_close("a + b", &sum, &zip(&m3, &n3, |x, y| x + y), TOLERANCE);
+        let difference = eval_matrix(3, 3, "a - b", &pair);
+        assert_close("a - b", &difference, &sub(&m3, &n3), TOLERANCE);
+        let scaled = eval_matrix(3, 3, "a * 2.5", &pair);
+        assert_close("a * 2.5", &scaled, &scale(&m3, 2.5), TOLERANCE);
+
+        let transposed = eval_matrix(3, 2, "transpose(a)", &[mat(2, &m2x3)]);
+        assert_close("transpose", &transposed, &transpose(&m2x3, 3), TOLERANCE);
+        // naga gives a non-square outer product the transposed shape, so
+        // only the square one is checked
+        let u3 = [2.0, -1.0, 0.25];
+        let outer = eval_matrix(3, 3, "outerProduct(a, b)", &[float(&v3), float(&u3)]);
+        assert_close("outerProduct", &outer, &mat_mul(&v3, 3, &u3, 3), TOLERANCE);
+
+        for (n, m) in [(2, &m2[..]), (3, &m3[..]), (4, &m4[..])] {
+            check(
+                "float",
+                "determinant(a)",
+                &[mat(n, m)],
+                &[determinant(m, n)],
+            );
+            let inverted = eval_matrix(n, n, "inverse(a)", &[mat(n, m)]);
+            assert_close("inverse", &inverted, &inverse(m, n), 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_float_operators() {
+        let xs = [-2.5, -0.75, 0.5, 3.0];
+        let ys = [1.5, -2.0, 0.25, -3.0];
+        let inputs = [float(&xs), float(&ys)];
+        check("vec4", "a + b", &inputs, &zip(&xs, &ys, |x, y| x + y));
+        check("vec4", "a - b", &inputs, &zip(&xs, &ys, |x, y| x - y));
+        check("vec4", "a * b", &inputs, &zip(&xs, &ys, |x, y| x * y));
+        check("vec4", "a / b", &inputs, &zip(&xs, &ys, |x, y| x / y));
+        check("vec4", "-a", &inputs, &map(&xs, |x| -x));
+        check("vec4", "a + b.x", &inputs, &map(&xs, |x| x + ys[0]));
+        check("vec4", "b.y * a", &inputs, &map(&xs, |x| ys[1] * x));
+        check("vec4", "a / b.z", &inputs, &map(&xs, |x| x / ys[2]));
+        check("vec4", "b.x - a", &inputs, &map(&xs, |x| ys[0] - x));
+        check("float", "a.w / b.y", &inputs, &[xs[3] / ys[1]]);
+        check("vec4", "a.wzyx", &inputs, &[xs[3], xs[2], xs[1], xs[0]]);
+
+        let statements = "r = a; r += b; r *= b.x; r -= a; r /= b.y;";
+        let expected = map(&zip(&xs, &ys, |x, y| (x + y) * ys[0] - x), |v| v / ys[1]);
+        let actual = floats(&eval_statements("vec4", statements, &inputs));
+        assert_close(statements, &actual, &expected, TOLERANCE);
+    }
+
+    #[test]
+    fn test_int_operators_and_builtins() {
+        let xs = [7, -7, 100, -3];
+        let ys = [2, 3, -9, 5];
+        let inputs = [int(&xs), int(&ys)];
+        let binary: &[BinaryCase<i32>] = &[
+            ("a + b", i32::wrapping_add),
+            ("a - b", i32::wrapping_sub),
+            ("a * b", i32::wrapping_mul),
+            ("a / b", i32::wrapping_div),
+            ("a & b", |x, y| x & y),
+            ("a | b", |x, y| x | y),
+            ("a ^ b", |x, y| x ^ y),
+            ("min(a, b)", i32::min),
+            ("max(a, b)", i32::max),
+        ];
+        let check_int = |expr: &str, inputs: &[Input], expected: Vec<i32>| {
+            let actual: Vec<i32> = eval("ivec4", expr, inputs)
+                .iter()
+                .map(|&w| w as i32)
+                .collect();
+            assert_eq!(actual, expected, "{expr}");
+        };
+        for &(expr, reference) in binary {
+            check_int(
+                expr,
+                &inputs,
+                (0..4).map(|i| reference(xs[i], ys[i])).collect(),
+            );
+        }
+        check_int("-a", &inputs, xs.map(|x| -x).to_vec());
+        check_int("~a", &inputs, xs.map(|x| !x).to_vec());
+        check_int("abs(a)", &inputs, xs.map(i32::abs).to_vec());
+        check_int("sign(a)", &inputs, xs.map(i32::signum).to_vec());
+        check_int("a * b.y", &inputs, xs.map(|x| x * ys[1]).to_vec());
+        check_int(
+            "clamp(a, -5, 50)",
+            &inputs,
+            xs.map(|x| x.clamp(-5, 50)).to_vec(),
+        );
+        check_int(
+            "clamp(a, b, ivec4(60))",
+            &inputs,
+            (0..4).map(|i| xs[i].clamp(ys[i], 60)).collect(),
+        );
+
+        // Remainders of non-negative operands and in-range shifts are defined
+        let (ns, ds, shifts) = ([7, 20, 100, 3], [2, 3, 9, 5], [0, 1, 4, 30]);
+        let inputs = [int(&ns), int(&ds), int(&shifts), int(&xs)];
+        check_int("a % b", &inputs, (0..4).map(|i| ns[i] % ds[i]).collect());
+        check_int(
+            "d << c",
+            &inputs,
+            (0..4).map(|i| xs[i] << shifts[i]).collect(),
+        );
+        check_int(
+            "d >> c",
+            &inputs,
+            (0..4).map(|i| xs[i] >> shifts[i]).collect(),
+        );
+        check_int("a >> 1", &inputs, ns.map(|n| n >> 1).to_vec());
+
+        // naga rejects a vector %= scalar, so the divisor is a vector
+        let statements = "r = a; r++; r <<= 2; r |= 1; r -= b; r %= ivec4(7);";
+        let expected = (0..4)
+            .map(|i| ((((ns[i] + 1) << 2) | 1) - ds[i]) % 7)
+            .collect::<Vec<_>>();
+        let actual: Vec<i32> = eval_statements("ivec4", statements, &inputs)
+            .iter()
+            .map(|&w| w as i32)
+            .collect();
+        assert_eq!(actual, expected, "{statements}");
+    }
+
+    #[test]
+    fn test_uint_operators_and_builtins() {
+        let xs = [7, 3_000_000_000, 100, 1];
+        let ys = [2, 2_000_000_000, 9, 5];
+        let inputs = [uint(&xs), uint(&ys)];
+        let binary: &[BinaryCase<u32>] = &[
+            ("a + b", u32::wrapping_add),
+            ("a - b", u32::wrapping_sub),
+            ("a * b", u32::wrapping_mul),
+            ("a / b", |x, y| x / y),
+            ("a % b", |x, y| x % y),
+            ("a & b", |x, y| x & y),
+            ("a | b", |x, y| x | y),
+            ("a ^ b", |x, y| x ^ y),
+            ("min(a, b)", u32::min),
+            ("max(a, b)", u32::max),
+        ];
+        for &(expr, reference) in binary {
+            let expected: Vec<u32> = (0..4).map(|i| reference(xs[i], ys[i])).collect();
+            assert_eq!(eval("uvec4", expr, &inputs), expected, "{expr}");
+        }
+        assert_eq!(eval("uvec4", "~a", &inputs), xs.map(|x| !x).to_vec());
+        let clamped = xs.map(|x| x.clamp(5, 200)).to_vec();
+        assert_eq!(eval("uvec4", "clamp(a, 5u, 200u)", &inputs), clamped);
+
+        // Right shifts of unsigned values are logical
+        let shifts = [0, 1, 4, 31];
+        let inputs = [uint(&xs), uint(&shifts)];
+        let shifted: Vec<u32> = (0..4).map(|i| xs[i] >> shifts[i]).collect();
+        assert_eq!(eval("uvec4", "a >> b", &inputs), shifted);
+        let shifted: Vec<u32> = (0..4).map(|i| xs[i].wrapping_shl(shifts[i])).collect();
+        assert_eq!(eval("uvec4", "a << b", &inputs), shifted);
+    }
+
+    #[test]
+    fn test_conversions() {
+        let xs = [-2.7, 2.7, 0.5, 100.9];
+        let to_int: Vec<u32> = xs.map(|x| x as i32 as u32).to_vec();
+        assert_eq!(eval("ivec4", "ivec4(a)", &[float(&xs)]), to_int);
+        let positive = [0.5, 3.9, 1e6, 42.0];
+        let to_uint = positive.map(|x| x as u32).to_vec();
+        assert_eq!(eval("uvec4", "uvec4(a)", &[float(&positive)]), to_uint);
+
+        let ints = [-7, 0, 16_777_217, i32::MIN];
+        check("vec4", "vec4(a)", &[int(&ints)], &ints.map(|x| x as f32));
+        let uints = [0, 1, 3_000_000_000, u32::MAX];
+        check("vec4", "vec4(a)", &[uint(&uints)], &uints.map(|x| x as f32));
+        let wrapped = ints.map(|x| x as u32).to_vec();
+        assert_eq!(eval("uvec4", "uvec4(a)", &[int(&ints)]), wrapped);
+
+        let bits = [1.0f32, -0.0, f32::INFINITY, 0.1];
+        let words = bits.map(f32::to_bits).to_vec();
+        assert_eq!(eval("ivec4", "floatBitsToInt(a)", &[float(&bits)]), words);
+        assert_eq!(eval("uvec4", "floatBitsToUint(a)", &[float(&bits)]), words);
+        let back = eval(
+            "vec4",
+            "intBitsToFloat(a)",
+            &[int(&words.iter().map(|&w| w as i32).collect::<Vec<_>>())],
+        );
+        assert_eq!(back, words);
+        assert_eq!(eval("vec4", "uintBitsToFloat(a)", &[uint(&words)]), words);
+
+        let truthy = [0.0, -1.5, 2.0, 0.0];
+        let expected = vec![false, true, true, false];
+        assert_eq!(
+            bools(&eval("bvec4", "bvec4(a)", &[float(&truthy)])),
+            expected
+        );
+        let ys = [1.0, -2.0, 3.0, 0.0];
+        let flags = eval(
+            "ivec4",
+            "ivec4(lessThan(a, b))",
+            &[float(&truthy), float(&ys)],
+        );
+        assert_eq!(flags, vec![1, 0, 1, 0]);
+        check(
+            "vec4",
+            "vec4(lessThan(a, b))",
+            &[float(&truthy), float(&ys)],
+            &[1.0, 0.0, 1.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn test_comparisons_and_vector_relational_builtins() {
+        let xs = [1.0, 2.0, 3.0, -0.0];
+        let ys = [2.0, 2.0, 2.0, 0.0];
+        let comparisons: &[ComparisonCase] = &[
+            ("<", "lessThan", |x, y| x < y),
+            ("<=", "lessThanEqual", |x, y| x <= y),
+            (">", "greaterThan", |x, y| x > y),
+            (">=", "greaterThanEqual", |x, y| x >= y),
+            ("==", "equal", |x, y| x == y),
+            ("!=", "notEqual", |x, y| x != y),
+        ];
+        let as_ints = |v: [f32; 4]| v.map(|x| x as i32);
+        let as_uints = |v: [f32; 4]| v.map(|x| x.abs() as u32);
+        for &(op, builtin, reference) in comparisons {
+            let expected: Vec<bool> = (0..4).map(|i| reference(xs[i], ys[i])).collect();
+            let float_inputs = [float(&xs), float(&ys)];
+            let int_inputs = [int(&as_ints(xs)), int(&as_ints(ys))];
+            let uint_inputs = [uint(&as_uints(xs)), uint(&as_uints(ys))];
+            for inputs in [&float_inputs, &int_inputs, &uint_inputs] {
+                let vector = bools(&eval("bvec4", &format!("{builtin}(a, b)"), inputs));
+                assert_eq!(vector, expected, "{builtin} of {}", inputs[0].glsl_type);
+                let scalars: Vec<bool> = ["x", "y", "z", "w"]
+                    .iter()
+                    .map(|c| eval("bool", &format!("a.{c} {op} b.{c}"), inputs)[0] != 0)
+                    .collect();
+                assert_eq!(scalars, expected, "{op} of {}", inputs[0].glsl_type);
+            }
+        }
+
+        let inputs = [float(&xs), float(&ys)];
+        let less = [true, false, false, false];
+        assert_eq!(eval("bool", "any(lessThan(a, b))", &inputs), vec![1]);
+        assert_eq!(eval("bool", "all(lessThan(a, b))", &inputs), vec![0]);
+        assert_eq!(eval("bool", "all(lessThanEqual(a, a))", &inputs), vec![1]);
+        assert_eq!(eval("bool", "any(greaterThan(a, a))", &inputs), vec![0]);
+        let negated = bools(&eval("bvec4", "not(lessThan(a, b))", &inputs));
+        assert_eq!(negated, less.map(|b| !b).to_vec());
+
+        let special = [f32::NAN, 1.0, f32::INFINITY, f32::NEG_INFINITY];
+        let inputs = [float(&special)];
+        let nan = bools(&eval("bvec4", "isnan(a)", &inputs));
+        assert_eq!(nan, vec![true, false, false, false]);
+        let inf = bools(&eval("bvec4", "isinf(a)", &inputs));
+        assert_eq!(inf, vec![false, false, true, true]);
+    }
+
+    #[test]
+    fn test_logical_and_selection_operators() {
+        for (p, q) in [(false, false), (false, true), (true, false), (true, true)] {
+            let signs = [if p { 1.0 } else { -1.0 }, if q { 1.0 } else { -1.0 }];
+            let inputs = [float(&signs)];
+            let truth = |expr: &str| eval("bool", expr, &inputs)[0] != 0;
+            assert_eq!(truth("a.x > 0.0 && a.y > 0.0"), p && q);
+            assert_eq!(truth("a.x > 0.0 || a.y > 0.0"), p || q);
+            assert_eq!(truth("a.x > 0.0 ^^ a.y > 0.0"), p ^ q);
+            assert_eq!(truth("!(a.x > 0.0)"), !p);
+            check(
+                "float",
+                "a.x > 0.0 ? 2.0 : a.y * 3.0",
+                &inputs,
+                &[if p { 2.0 } else { signs[1] * 3.0 }],
+            );
+        }
+
+        let xs = [1.0, 5.0, -2.0, 0.5];
+        let ys = [3.0, -1.0, 4.0, 0.25];
+        let inputs = [float(&xs), float(&ys)];
+        check("vec4", "a.x > b.x ? a : b", &inputs, &ys);
+        check("vec4", "a.y > b.y ? a : b", &inputs, &xs);
+    }
+}
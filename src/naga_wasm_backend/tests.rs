@@ -43,6 +43,7 @@ mod tests {
                 attribute_locations: &HashMap::new(),
                 uniform_locations: &HashMap::new(),
                 varying_locations: &HashMap::new(),
+                varying_components: &HashMap::new(),
                 varying_types: &HashMap::new(),
                 uniform_types: &HashMap::new(),
                 attribute_types: &HashMap::new(),
@@ -83,6 +84,7 @@ mod tests {
                 attribute_locations: &HashMap::new(),
                 uniform_locations: &HashMap::new(),
                 varying_locations: &HashMap::new(),
+                varying_components: &HashMap::new(),
                 varying_types: &HashMap::new(),
                 uniform_types: &HashMap::new(),
                 attribute_types: &HashMap::new(),
@@ -121,6 +123,7 @@ mod tests {
                 attribute_locations: &HashMap::new(),
                 uniform_locations: &HashMap::new(),
                 varying_locations: &HashMap::new(),
+                varying_components: &HashMap::new(),
                 varying_types: &HashMap::new(),
                 uniform_types: &HashMap::new(),
                 attribute_types: &HashMap::new(),
@@ -130,4 +133,30 @@ mod tests {
 
         assert!(res.is_ok());
     }
+
+    #[test]
+    fn fragment_shading_rate_from_inputs() {
+        use super::output_layout::{fragment_shading_rate, ShadingRate};
+
+        let rate = |inputs: &str| {
+            let src = format!(
+                "@fragment fn main({inputs}) -> @location(0) vec4<f32> {{ return vec4<f32>(0.0); }}"
+            );
+            let module = naga::front::wgsl::parse_str(&src).expect("WGSL parse");
+            fragment_shading_rate(&module, Some("main"))
+        };
+
+        assert_eq!(rate(""), ShadingRate::PerPixel);
+        assert_eq!(
+            rate("@location(0) @interpolate(perspective, centroid) v: f32"),
+            ShadingRate::PerPixel
+        );
+        assert_eq!(
+            rate("@location(0) @interpolate(perspective, sample) v: f32"),
+            ShadingRate::PerSample
+        );
+        assert_eq!(rate("@builtin(sample_index) s: u32"), ShadingRate::PerSample);
+        assert_eq!(ShadingRate::PerSample.invocations_per_pixel(4), 4);
+        assert_eq!(ShadingRate::PerPixel.invocations_per_pixel(4), 1);
+    }
 }
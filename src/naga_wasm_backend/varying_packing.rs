@@ -0,0 +1,257 @@
+//! Varying location assignment with component packing.
+//!
+//! User varyings are packed into [`MAX_VARYING_VECTORS`] locations of four
+//! components each, following the packing rules of GLSL ES 3.00 appendix A.7:
+//! the widest varyings are placed first, each in the first location with room
+//! for it, so two `vec2`s, or a `vec3` and a `float`, share one location.
+//! Matrices and arrays occupy whole consecutive locations, because their
+//! components are stored contiguously.
+//!
+//! [`MAX_VARYING_VECTORS`]: super::output_layout::MAX_VARYING_VECTORS
+
+use naga::{Binding, Handle, Interpolation, Module, ShaderStage, Type, TypeInner};
+
+/// Position of a varying in the varying buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VaryingSlot {
+    pub location: u32,
+    /// First component used within `location` (0-3)
+    pub component: u32,
+}
+
+/// A user-defined vertex output or fragment input.
+#[derive(Debug, Clone)]
+pub struct StageVarying {
+    pub name: String,
+    pub ty: Handle<Type>,
+    /// Number of 32-bit components
+    pub components: u32,
+    pub interpolation: Interpolation,
+}
+
+/// Interpolation of a varying, applying the defaults for a missing qualifier:
+/// integers are flat, everything else is smooth.
+pub fn resolve_interpolation(inner: &TypeInner, qualifier: Option<Interpolation>) -> Interpolation {
+    let is_integer = matches!(
+        inner.scalar_kind(),
+        Some(naga::ScalarKind::Sint | naga::ScalarKind::Uint)
+    );
+    match qualifier {
+        Some(interpolation) => interpolation,
+        None if is_integer => Interpolation::Flat,
+        None => Interpolation::Perspective,
+    }
+}
+
+/// User varyings of the `stage` entry point in declaration order: the
+/// location-bound members of a vertex result, or the location-bound
+/// arguments of a fragment entry point.
+pub fn stage_varyings(module: &Module, stage: ShaderStage) -> Vec<StageVarying> {
+    let Some(ep) = module.entry_points.iter().find(|ep| ep.stage == stage) else {
+        return Vec::new();
+    };
+
+    let interface: Vec<(Option<&String>, Handle<Type>, Option<&Binding>)> = match stage {
+        ShaderStage::Vertex => match ep
+            .function
+            .result
+            .as_ref()
+            .map(|r| &module.types[r.ty].inner)
+        {
+            Some(TypeInner::Struct { members, .. }) => members
+                .iter()
+                .map(|m| (m.name.as_ref(), m.ty, m.binding.as_ref()))
+                .collect(),
+            _ => Vec::new(),
+        },
+        _ => ep
+            .function
+            .arguments
+            .iter()
+            .map(|a| (a.name.as_ref(), a.ty, a.binding.as_ref()))
+            .collect(),
+    };
+
+    interface
+        .into_iter()
+        .filter_map(|(name, ty, binding)| {
+            let Some(Binding::Location { interpolation, .. }) = binding else {
+                return None;
+            };
+            let inner = &module.types[ty].inner;
+            Some(StageVarying {
+                name: name?.clone(),
+                ty,
+                components: super::types::component_count(inner, &module.types),
+                interpolation: resolve_interpolation(inner, *interpolation),
+            })
+        })
+        .collect()
+}
+
+/// GLSL spelling of a varying type, for link errors and manifests.
+pub fn glsl_type_name(module: &Module, ty: Handle<Type>) -> String {
+    let prefix = |kind: naga::ScalarKind| match kind {
+        naga::ScalarKind::Sint => "i",
+        naga::ScalarKind::Uint => "u",
+        naga::ScalarKind::Bool => "b",
+        _ => "",
+    };
+    match &module.types[ty].inner {
+        TypeInner::Scalar(scalar) => match scalar.kind {
+            naga::ScalarKind::Sint => "int".to_string(),
+            naga::ScalarKind::Uint => "uint".to_string(),
+            naga::ScalarKind::Bool => "bool".to_string(),
+            _ => "float".to_string(),
+        },
+        TypeInner::Vector { size, scalar } => format!("{}vec{}", prefix(scalar.kind), *size as u8),
+        TypeInner::Matrix { columns, rows, .. } if columns == rows => {
+            format!("mat{}", *columns as u8)
+        }
+        TypeInner::Matrix { columns, rows, .. } => format!("mat{}x{}", *columns as u8, *rows as u8),
+        TypeInner::Array {
+            base,
+            size: naga::ArraySize::Constant(count),
+            ..
+        } => format!("{}[{}]", glsl_type_name(module, *base), count),
+        _ => module.types[ty]
+            .name
+            .clone()
+            .unwrap_or_else(|| "struct".to_string()),
+    }
+}
+
+/// Number of locations a varying of `components` components spans.
+pub fn location_count(components: u32) -> u32 {
+    shape(components).0
+}
+
+/// Rows and columns a varying of `components` components occupies.
+fn shape(components: u32) -> (u32, u32) {
+    if components > 4 {
+        (components.div_ceil(4), 4)
+    } else {
+        (1, components.max(1))
+    }
+}
+
+/// Assign a slot to each varying, given their component counts in
+/// declaration order. A varying with a `pinned` location is placed there,
+/// from its first component, before the others are packed around it.
+///
+/// Returns the index of the first varying that does not fit when the set
+/// needs more than `max_vectors` locations.
+pub fn pack_varyings(
+    components: &[u32],
+    pinned: &[Option<u32>],
+    max_vectors: u32,
+) -> Result<Vec<VaryingSlot>, usize> {
+    // Pinned first, then widest first; the sort is stable, so ties keep
+    // declaration order
+    let mut order: Vec<usize> = (0..components.len()).collect();
+    order.sort_by_key(|&i| {
+        let (rows, columns) = shape(components[i]);
+        (pinned[i].is_none(), std::cmp::Reverse((columns, rows)))
+    });
+
+    // Occupied components of each location, one bit per component
+    let mut used = vec![0u8; max_vectors as usize];
+    let mut slots = vec![
+        VaryingSlot {
+            location: 0,
+            component: 0,
+        };
+        components.len()
+    ];
+    for i in order {
+        let (rows, columns) = shape(components[i]);
+        // vec2s stay aligned; floats fill the last column left by vec3s first
+        let candidates: &[u32] = match columns {
+            _ if pinned[i].is_some() => &[0],
+            4 | 3 => &[0],
+            2 => &[0, 2],
+            _ => &[3, 2, 1, 0],
+        };
+        let mask = (1u8 << columns) - 1;
+        let rows_to_try = match pinned[i] {
+            Some(location) => location..location.saturating_add(1),
+            None => 0..max_vectors,
+        };
+        let slot = rows_to_try
+            .filter(|&row| row.saturating_add(rows) <= max_vectors)
+            .find_map(|row| {
+                candidates.iter().find_map(|&component| {
+                    (row..row + rows)
+                        .all(|r| used[r as usize] & (mask << component) == 0)
+                        .then_some(VaryingSlot {
+                            location: row,
+                            component,
+                        })
+                })
+            })
+            .ok_or(i)?;
+        for r in slot.location..slot.location + rows {
+            used[r as usize] |= mask << slot.component;
+        }
+        slots[i] = slot;
+    }
+    Ok(slots)
+}
+
+/// Number of locations a packed set of varyings spans.
+pub fn locations_used(components: &[u32], slots: &[VaryingSlot]) -> u32 {
+    components
+        .iter()
+        .zip(slots)
+        .map(|(&c, slot)| slot.location + shape(c).0)
+        .max()
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(location: u32, component: u32) -> VaryingSlot {
+        VaryingSlot {
+            location,
+            component,
+        }
+    }
+
+    #[test]
+    fn test_two_vec2_share_a_location() {
+        assert_eq!(
+            pack_varyings(&[2, 2], &[None; 2], 15).unwrap(),
+            vec![slot(0, 0), slot(0, 2)]
+        );
+    }
+
+    #[test]
+    fn test_widest_first() {
+        // float, vec3, vec4, mat2x3 (6 components, two rows)
+        let slots = pack_varyings(&[1, 3, 4, 6], &[None; 4], 15).unwrap();
+        assert_eq!(slots, vec![slot(3, 3), slot(3, 0), slot(2, 0), slot(0, 0)]);
+        assert_eq!(locations_used(&[1, 3, 4, 6], &slots), 4);
+    }
+
+    #[test]
+    fn test_exceeds_max_vectors() {
+        assert!(pack_varyings(&[4; 15], &[None; 15], 15).is_ok());
+        assert_eq!(pack_varyings(&[4; 16], &[None; 16], 15), Err(15));
+        // 30 vec2s fit, a 31st does not
+        assert!(pack_varyings(&[2; 30], &[None; 30], 15).is_ok());
+        assert_eq!(pack_varyings(&[2; 31], &[None; 31], 15), Err(30));
+        assert_eq!(pack_varyings(&[64], &[None], 15), Err(0));
+    }
+
+    #[test]
+    fn test_pinned_varyings_are_packed_around() {
+        // The vec4 pinned to location 0 and the float pinned to 1 push the
+        // vec3 past them; the vec2 shares the float's location
+        let slots = pack_varyings(&[3, 4, 1, 2], &[None, Some(0), Some(1), None], 15).unwrap();
+        assert_eq!(slots, vec![slot(2, 0), slot(0, 0), slot(1, 0), slot(1, 2)]);
+        // A pinned mat2x3 must fit below max_vectors
+        assert_eq!(pack_varyings(&[6], &[Some(14)], 15), Err(0));
+    }
+}
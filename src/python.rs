@@ -0,0 +1,671 @@
+//! Python bindings for headless shader testing.
+//!
+//! Shaders compile to WASM that only runs inside the WASM build of this
+//! crate, so the module hosts `webgl2.wasm` in wasmtime the way `index.js`
+//! hosts it in a JS engine: it provides the `env` imports, instantiates each
+//! linked program's shader modules against the shared memory, function table
+//! and `ACTIVE_*` globals, and registers their table slots with the context.
+//!
+//! ```python
+//! import webgl2
+//! gl = webgl2.Context(64, 64)
+//! program = gl.program(vertex_source, fragment_source)
+//! gl.use_program(program)
+//! gl.attribute(0, gl.buffer([-1, -1, 3, -1, -1, 3]), 2)
+//! gl.uniform(program, "tint", [1.0, 0.5, 0.0, 1.0])
+//! gl.draw_arrays(webgl2.Context.TRIANGLES, 0, 3)
+//! pixels = gl.read_pixels()  # RGBA8 rows, bottom row first
+//! ```
+
+use crate::gl_constants::*;
+use crate::naga_wasm_backend::abi::SHADER_ABI_VERSION;
+use crate::webgl2_context::types::{ERR_GL, ERR_OK};
+use anyhow::{anyhow, bail};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Instant;
+use wasmtime::{
+    AsContextMut, Caller, Engine, Extern, ExternType, Func, Global, GlobalType, Instance, Memory,
+    MemoryType, Module, Mutability, Ref, RefType, Store, Table, TableType, Val, ValType,
+    WasmParams, WasmResults,
+};
+
+const NO_SHADER_INDEX: u32 = u32::MAX;
+/// First table slot for shaders; the slots below belong to the Rust code
+const FIRST_SHADER_SLOT: u32 = 5000;
+
+const TURBO_GLOBALS: [&str; 6] = [
+    "ACTIVE_ATTR_PTR",
+    "ACTIVE_UNIFORM_PTR",
+    "ACTIVE_VARYING_PTR",
+    "ACTIVE_PRIVATE_PTR",
+    "ACTIVE_TEXTURE_PTR",
+    "ACTIVE_FRAME_SP",
+];
+
+/// State the host imports share.
+struct Host {
+    table: Option<Table>,
+    globals: Vec<Global>,
+    main: Option<Instance>,
+    free_slots: Vec<u32>,
+    next_slot: u32,
+    started: Instant,
+}
+
+impl Host {
+    fn global(&self, name: &str) -> Option<Global> {
+        let index = TURBO_GLOBALS.iter().position(|g| *g == name)?;
+        self.globals.get(index).copied()
+    }
+
+    fn allocate_slot(&mut self) -> u32 {
+        self.free_slots.pop().unwrap_or_else(|| {
+            self.next_slot += 1;
+            self.next_slot - 1
+        })
+    }
+}
+
+thread_local! {
+    /// Compiled `webgl2.wasm` by path, so contexts after the first start fast
+    static MODULES: RefCell<HashMap<PathBuf, Module>> = RefCell::new(HashMap::new());
+}
+
+fn to_py(err: impl std::fmt::Display) -> PyErr {
+    PyRuntimeError::new_err(err.to_string())
+}
+
+fn caller_memory(caller: &mut Caller<'_, Host>) -> anyhow::Result<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| anyhow!("webgl2.wasm exports no memory"))
+}
+
+/// The `len` bytes at `ptr` in the guest's memory, or an error if they run
+/// past its end.
+fn guest_bytes<'a>(
+    memory: &Memory,
+    caller: &'a Caller<'_, Host>,
+    ptr: u32,
+    len: u32,
+) -> anyhow::Result<&'a [u8]> {
+    ptr.checked_add(len)
+        .and_then(|end| memory.data(caller).get(ptr as usize..end as usize))
+        .ok_or_else(|| anyhow!("guest range {:#x}+{} is out of bounds", ptr, len))
+}
+
+/// Instantiate a shader module and put its entry point in a free table slot.
+fn instantiate_shader(
+    mut store: impl AsContextMut<Data = Host>,
+    bytes: &[u8],
+) -> anyhow::Result<u32> {
+    let mut store = store.as_context_mut();
+    let module = Module::new(store.engine(), bytes)?;
+    let main = store
+        .data()
+        .main
+        .ok_or_else(|| anyhow!("webgl2.wasm is not instantiated"))?;
+    let table = store
+        .data()
+        .table
+        .ok_or_else(|| anyhow!("no function table"))?;
+
+    let mut imports = Vec::new();
+    for import in module.imports() {
+        let name = import.name();
+        let resolved = if name == "__indirect_function_table" {
+            Extern::Table(table)
+        } else if let Some(global) = store.data().global(name) {
+            Extern::Global(global)
+        } else {
+            // Memory and the math helpers come from the main module
+            main.get_export(&mut store, name).ok_or_else(|| {
+                anyhow!("shader import {}.{} is not provided", import.module(), name)
+            })?
+        };
+        imports.push(resolved);
+    }
+    let instance = Instance::new(&mut store, &module, &imports)?;
+
+    let abi = instance
+        .get_global(&mut store, "__shader_abi_version")
+        .map(|g| g.get(&mut store));
+    if !matches!(abi, Some(Val::I32(v)) if v as u32 == SHADER_ABI_VERSION) {
+        bail!(
+            "shader ABI version mismatch: runtime expects {}",
+            SHADER_ABI_VERSION
+        );
+    }
+    let entry = instance
+        .get_func(&mut store, "main")
+        .ok_or_else(|| anyhow!("shader module exports no main"))?;
+    let slot = store.data_mut().allocate_slot();
+    table.set(&mut store, slot as u64, Ref::Func(Some(entry)))?;
+    Ok(slot)
+}
+
+/// A `math.*` import, evaluated in f64 and returned in the import's type.
+fn math_import(
+    store: &mut Store<Host>,
+    name: &str,
+    ty: wasmtime::FuncType,
+) -> anyhow::Result<Func> {
+    let op: fn(f64, f64) -> f64 = match name {
+        "sin" => |x, _| x.sin(),
+        "cos" => |x, _| x.cos(),
+        "tan" => |x, _| x.tan(),
+        "asin" => |x, _| x.asin(),
+        "acos" => |x, _| x.acos(),
+        "atan" => |x, _| x.atan(),
+        "atan2" => |y, x| y.atan2(x),
+        "exp" => |x, _| x.exp(),
+        "exp2" => |x, _| x.exp2(),
+        "log" => |x, _| x.ln(),
+        "log2" => |x, _| x.log2(),
+        "pow" => |x, y| x.powf(y),
+        _ => bail!("unsupported import math.{}", name),
+    };
+    Ok(Func::new(store, ty, move |_, params, results| {
+        let arg = |i: usize| match params.get(i) {
+            Some(Val::F32(bits)) => f32::from_bits(*bits) as f64,
+            Some(Val::F64(bits)) => f64::from_bits(*bits),
+            _ => 0.0,
+        };
+        let value = op(arg(0), arg(1));
+        results[0] = match results[0] {
+            Val::F32(_) => Val::F32((value as f32).to_bits()),
+            _ => Val::F64(value.to_bits()),
+        };
+        Ok(())
+    }))
+}
+
+/// Resolve an `env.*` import of `webgl2.wasm`.
+fn env_import(store: &mut Store<Host>, name: &str, ty: &ExternType) -> anyhow::Result<Extern> {
+    if let Some(global) = store.data().global(name) {
+        return Ok(Extern::Global(global));
+    }
+    Ok(match name {
+        "__indirect_function_table" => Extern::Table(
+            store
+                .data()
+                .table
+                .ok_or_else(|| anyhow!("no function table"))?,
+        ),
+        "memory" => Extern::Memory(Memory::new(&mut *store, MemoryType::new(100, None))?),
+        "print" => Func::wrap(
+            &mut *store,
+            |mut caller: Caller<'_, Host>, ptr: u32, len: u32| -> anyhow::Result<()> {
+                let memory = caller_memory(&mut caller)?;
+                let bytes = guest_bytes(&memory, &caller, ptr, len)?;
+                println!("{}", String::from_utf8_lossy(bytes));
+                Ok(())
+            },
+        )
+        .into(),
+        "wasm_register_shader" => Func::wrap(
+            &mut *store,
+            |mut caller: Caller<'_, Host>, ptr: u32, len: u32| -> anyhow::Result<u32> {
+                let memory = caller_memory(&mut caller)?;
+                let bytes = guest_bytes(&memory, &caller, ptr, len)?.to_vec();
+                instantiate_shader(&mut caller, &bytes)
+            },
+        )
+        .into(),
+        "wasm_release_shader_index" => {
+            Func::wrap(&mut *store, |mut caller: Caller<'_, Host>, idx: u32| {
+                caller.data_mut().free_slots.push(idx);
+            })
+            .into()
+        }
+        // No persistent shader cache in Python: every lookup misses
+        "shader_cache_get" => {
+            Func::wrap(&mut *store, |_: u32, _: u32, _: u32, _: u32| -1i32).into()
+        }
+        "shader_cache_put" => Func::wrap(&mut *store, |_: u32, _: u32, _: u32, _: u32| {}).into(),
+        "wasm_sync_turbo_globals" => Func::wrap(
+            &mut *store,
+            |mut caller: Caller<'_, Host>,
+             attr: u32,
+             uniform: u32,
+             varying: u32,
+             private: u32,
+             texture: u32,
+             frame_sp: u32|
+             -> anyhow::Result<()> {
+                let globals = caller.data().globals.clone();
+                let values = [attr, uniform, varying, private, texture, frame_sp];
+                for (global, value) in globals.iter().zip(values) {
+                    global.set(&mut caller, Val::I32(value as i32))?;
+                }
+                Ok(())
+            },
+        )
+        .into(),
+        "dispatch_device_events" => Func::wrap(&mut *store, || {}).into(),
+        "now" => Func::wrap(&mut *store, |caller: Caller<'_, Host>| {
+            caller.data().started.elapsed().as_secs_f64() * 1000.0
+        })
+        .into(),
+        _ => bail!("unsupported import env.{} ({:?})", name, ty),
+    })
+}
+
+/// A WebGL2 context running in its own instance of `webgl2.wasm`.
+#[pyclass(unsendable)]
+pub struct Context {
+    store: Store<Host>,
+    instance: Instance,
+    memory: Memory,
+    ctx: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Context {
+    fn call<P: WasmParams, R: WasmResults>(&mut self, name: &str, params: P) -> PyResult<R> {
+        let func = self
+            .instance
+            .get_typed_func::<P, R>(&mut self.store, name)
+            .map_err(to_py)?;
+        func.call(&mut self.store, params).map_err(to_py)
+    }
+
+    /// Raise on a failed errno, with the module's message for it.
+    fn check(&mut self, code: u32) -> PyResult<()> {
+        if code == ERR_OK || code == ERR_GL {
+            return Ok(());
+        }
+        let ptr: u32 = self.call("wasm_last_error", ())?;
+        let message = if ptr == 0 {
+            format!("error {}", code)
+        } else {
+            String::from_utf8_lossy(&self.payload(ptr)?).into_owned()
+        };
+        Err(PyRuntimeError::new_err(message))
+    }
+
+    /// Copy out an ephemeral payload, whose length precedes it.
+    fn payload(&self, ptr: u32) -> PyResult<Vec<u8>> {
+        let data = self.memory.data(&self.store);
+        let at = ptr as usize;
+        let len = at
+            .checked_sub(16)
+            .and_then(|header| data.get(header..header + 4))
+            .map(|len| u32::from_le_bytes(len.try_into().unwrap()) as usize);
+        len.and_then(|len| data.get(at..at.checked_add(len)?))
+            .map(<[u8]>::to_vec)
+            .ok_or_else(|| to_py(format!("payload at {:#x} is out of bounds", ptr)))
+    }
+
+    /// Copy bytes into a fresh allocation in the module's memory.
+    fn upload(&mut self, bytes: &[u8]) -> PyResult<u32> {
+        let ptr: u32 = self.call("wasm_ctx_alloc", (self.ctx, bytes.len() as u32))?;
+        if ptr == 0 {
+            return Err(PyRuntimeError::new_err("out of WASM memory"));
+        }
+        self.memory
+            .write(&mut self.store, ptr as usize, bytes)
+            .map_err(to_py)?;
+        Ok(ptr)
+    }
+
+    fn compile(&mut self, kind: u32, source: &str) -> PyResult<u32> {
+        let shader: u32 = self.call("wasm_ctx_create_shader", (self.ctx, kind))?;
+        let ptr = self.upload(source.as_bytes())?;
+        let code: u32 = self.call(
+            "wasm_ctx_shader_source",
+            (self.ctx, shader, ptr, source.len() as u32),
+        )?;
+        self.call::<u32, u32>("wasm_free", ptr)?;
+        self.check(code)?;
+        let code: u32 = self.call("wasm_ctx_compile_shader", (self.ctx, shader))?;
+        self.check(code)?;
+        let compiled: i32 = self.call(
+            "wasm_ctx_get_shader_parameter",
+            (self.ctx, shader, GL_COMPILE_STATUS),
+        )?;
+        if compiled == 0 {
+            let log: u32 = self.call("wasm_ctx_get_shader_info_log", (self.ctx, shader))?;
+            let log = if log == 0 {
+                Vec::new()
+            } else {
+                self.payload(log)?
+            };
+            return Err(PyValueError::new_err(format!(
+                "shader failed to compile: {}",
+                String::from_utf8_lossy(&log)
+            )));
+        }
+        Ok(shader)
+    }
+
+    /// Instantiate a linked program's shader modules and register their slots.
+    fn instantiate_program(&mut self, program: u32) -> PyResult<()> {
+        let mut slots = [NO_SHADER_INDEX; 2];
+        for (slot, kind) in slots.iter_mut().zip([GL_VERTEX_SHADER, GL_FRAGMENT_SHADER]) {
+            let len: u32 = self.call("wasm_ctx_get_program_wasm_len", (self.ctx, program, kind))?;
+            if len == 0 {
+                continue;
+            }
+            let ptr: u32 = self.call("wasm_ctx_alloc", (self.ctx, len))?;
+            let copied: u32 = self.call(
+                "wasm_ctx_get_program_wasm",
+                (self.ctx, program, kind, ptr, len),
+            )?;
+            let bytes = self
+                .memory
+                .data(&self.store)
+                .get(ptr as usize..)
+                .and_then(|data| data.get(..copied as usize))
+                .ok_or_else(|| to_py("shader module is out of bounds"))?
+                .to_vec();
+            self.call::<u32, u32>("wasm_free", ptr)?;
+            *slot = instantiate_shader(&mut self.store, &bytes).map_err(to_py)?;
+        }
+        if slots != [NO_SHADER_INDEX; 2] {
+            let code: u32 = self.call(
+                "wasm_ctx_register_shader_indices",
+                (self.ctx, program, slots[0], slots[1]),
+            )?;
+            self.check(code)?;
+        }
+        Ok(())
+    }
+}
+
+#[pymethods]
+impl Context {
+    #[classattr]
+    const POINTS: u32 = GL_POINTS;
+    #[classattr]
+    const LINES: u32 = GL_LINES;
+    #[classattr]
+    const LINE_STRIP: u32 = GL_LINE_STRIP;
+    #[classattr]
+    const TRIANGLES: u32 = GL_TRIANGLES;
+    #[classattr]
+    const TRIANGLE_STRIP: u32 = GL_TRIANGLE_STRIP;
+    #[classattr]
+    const TRIANGLE_FAN: u32 = GL_TRIANGLE_FAN;
+
+    /// Create a context with a `width` x `height` default framebuffer.
+    /// `wasm_path` defaults to the WEBGL2_WASM environment variable, then to
+    /// `webgl2.wasm` in the working directory.
+    #[new]
+    #[pyo3(signature = (width = 256, height = 256, wasm_path = None))]
+    fn new(width: u32, height: u32, wasm_path: Option<PathBuf>) -> PyResult<Self> {
+        let path = wasm_path
+            .or_else(|| std::env::var_os("WEBGL2_WASM").map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("webgl2.wasm"));
+        let module = MODULES.with(|modules| {
+            let mut modules = modules.borrow_mut();
+            if let Some(module) = modules.get(&path) {
+                return Ok(module.clone());
+            }
+            let module = Module::from_file(&Engine::default(), &path)
+                .map_err(|e| to_py(format!("failed to load {}: {}", path.display(), e)))?;
+            modules.insert(path.clone(), module.clone());
+            Ok::<_, PyErr>(module)
+        })?;
+
+        let mut store = Store::new(
+            module.engine(),
+            Host {
+                table: None,
+                globals: Vec::new(),
+                main: None,
+                free_slots: Vec::new(),
+                next_slot: FIRST_SHADER_SLOT,
+                started: Instant::now(),
+            },
+        );
+        let table = Table::new(
+            &mut store,
+            TableType::new(RefType::FUNCREF, 8192, Some(65536)),
+            Ref::Func(None),
+        )
+        .map_err(to_py)?;
+        let globals = TURBO_GLOBALS
+            .iter()
+            .map(|_| {
+                Global::new(
+                    &mut store,
+                    GlobalType::new(ValType::I32, Mutability::Var),
+                    Val::I32(0),
+                )
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(to_py)?;
+        store.data_mut().table = Some(table);
+        store.data_mut().globals = globals;
+
+        let mut imports = Vec::new();
+        for import in module.imports() {
+            let resolved = match (import.module(), import.ty()) {
+                ("math", ExternType::Func(ty)) => {
+                    math_import(&mut store, import.name(), ty).map(Extern::from)
+                }
+                ("env", ty) => env_import(&mut store, import.name(), &ty),
+                (other, _) => Err(anyhow!("unsupported import {}.{}", other, import.name())),
+            };
+            imports.push(resolved.map_err(to_py)?);
+        }
+        let instance = Instance::new(&mut store, &module, &imports).map_err(to_py)?;
+        store.data_mut().main = Some(instance);
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| to_py("webgl2.wasm exports no memory"))?;
+
+        let mut context = Context {
+            store,
+            instance,
+            memory,
+            ctx: 0,
+            width,
+            height,
+        };
+        context.ctx = context.call("wasm_create_context_with_flags", (0u32, width, height))?;
+        if context.ctx == 0 {
+            context.check(u32::MAX)?;
+        }
+        context.viewport(0, 0, width, height)?;
+        Ok(context)
+    }
+
+    #[getter]
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    #[getter]
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Compile and link a program; raises ValueError with the info log if
+    /// either stage fails to compile or the program fails to link.
+    fn program(&mut self, vertex: &str, fragment: &str) -> PyResult<u32> {
+        let vs = self.compile(GL_VERTEX_SHADER, vertex)?;
+        let fs = self.compile(GL_FRAGMENT_SHADER, fragment)?;
+        let program: u32 = self.call("wasm_ctx_create_program", self.ctx)?;
+        for shader in [vs, fs] {
+            let code: u32 = self.call("wasm_ctx_attach_shader", (self.ctx, program, shader))?;
+            self.check(code)?;
+        }
+        let code: u32 = self.call("wasm_ctx_link_program", (self.ctx, program))?;
+        self.check(code)?;
+        let linked: i32 = self.call(
+            "wasm_ctx_get_program_parameter",
+            (self.ctx, program, GL_LINK_STATUS),
+        )?;
+        if linked == 0 {
+            let log: u32 = self.call("wasm_ctx_get_program_info_log", (self.ctx, program))?;
+            let log = if log == 0 {
+                Vec::new()
+            } else {
+                self.payload(log)?
+            };
+            return Err(PyValueError::new_err(format!(
+                "program failed to link: {}",
+                String::from_utf8_lossy(&log)
+            )));
+        }
+        self.instantiate_program(program)?;
+        Ok(program)
+    }
+
+    fn use_program(&mut self, program: u32) -> PyResult<()> {
+        let code: u32 = self.call("wasm_ctx_use_program", (self.ctx, program))?;
+        self.check(code)
+    }
+
+    /// Create an ARRAY_BUFFER holding `data` as 32-bit floats.
+    fn buffer(&mut self, data: Vec<f32>) -> PyResult<u32> {
+        let buffer: u32 = self.call("wasm_ctx_create_buffer", self.ctx)?;
+        let code: u32 = self.call("wasm_ctx_bind_buffer", (self.ctx, GL_ARRAY_BUFFER, buffer))?;
+        self.check(code)?;
+        let bytes: Vec<u8> = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+        let ptr = self.upload(&bytes)?;
+        let code: u32 = self.call(
+            "wasm_ctx_buffer_data",
+            (
+                self.ctx,
+                GL_ARRAY_BUFFER,
+                ptr,
+                bytes.len() as u32,
+                GL_STATIC_DRAW,
+            ),
+        )?;
+        self.call::<u32, u32>("wasm_free", ptr)?;
+        self.check(code)?;
+        Ok(buffer)
+    }
+
+    /// Feed attribute `location` with `size` floats per vertex from `buffer`.
+    #[pyo3(signature = (location, buffer, size, stride = 0, offset = 0))]
+    fn attribute(
+        &mut self,
+        location: u32,
+        buffer: u32,
+        size: i32,
+        stride: i32,
+        offset: u32,
+    ) -> PyResult<()> {
+        let code: u32 = self.call("wasm_ctx_bind_buffer", (self.ctx, GL_ARRAY_BUFFER, buffer))?;
+        self.check(code)?;
+        let code: u32 = self.call("wasm_ctx_enable_vertex_attrib_array", (self.ctx, location))?;
+        self.check(code)?;
+        let code: u32 = self.call(
+            "wasm_ctx_vertex_attrib_pointer",
+            (self.ctx, location, size, GL_FLOAT, 0u32, stride, offset),
+        )?;
+        self.check(code)
+    }
+
+    /// Set a float, vecN or mat4 (column-major, 16 values) uniform of the
+    /// current program. Unknown names are ignored, as in WebGL.
+    fn uniform(&mut self, program: u32, name: &str, values: Vec<f32>) -> PyResult<()> {
+        let ptr = self.upload(name.as_bytes())?;
+        let location: i32 = self.call(
+            "wasm_ctx_get_uniform_location",
+            (self.ctx, program, ptr, name.len() as u32),
+        )?;
+        self.call::<u32, u32>("wasm_free", ptr)?;
+        if location < 0 {
+            return Ok(());
+        }
+        let code: u32 = match values[..] {
+            [x] => self.call("wasm_ctx_uniform1f", (self.ctx, location, x))?,
+            [x, y] => self.call("wasm_ctx_uniform2f", (self.ctx, location, x, y))?,
+            [x, y, z] => self.call("wasm_ctx_uniform3f", (self.ctx, location, x, y, z))?,
+            [x, y, z, w] => self.call("wasm_ctx_uniform4f", (self.ctx, location, x, y, z, w))?,
+            _ if values.len() == 16 => {
+                let bytes: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+                let ptr = self.upload(&bytes)?;
+                let code = self.call(
+                    "wasm_ctx_uniform_matrix_4fv",
+                    (self.ctx, location, 0u32, ptr, 16u32),
+                )?;
+                self.call::<u32, u32>("wasm_free", ptr)?;
+                code
+            }
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "uniform {} takes 1 to 4 or 16 values, got {}",
+                    name,
+                    values.len()
+                )))
+            }
+        };
+        self.check(code)
+    }
+
+    fn viewport(&mut self, x: i32, y: i32, width: u32, height: u32) -> PyResult<()> {
+        let code: u32 = self.call("wasm_ctx_viewport", (self.ctx, x, y, width, height))?;
+        self.check(code)
+    }
+
+    /// Clear the color buffer to (r, g, b, a) and the depth buffer to 1.
+    #[pyo3(signature = (r = 0.0, g = 0.0, b = 0.0, a = 1.0))]
+    fn clear(&mut self, r: f32, g: f32, b: f32, a: f32) -> PyResult<()> {
+        let code: u32 = self.call("wasm_ctx_clear_color", (self.ctx, r, g, b, a))?;
+        self.check(code)?;
+        let code: u32 = self.call(
+            "wasm_ctx_clear",
+            (self.ctx, GL_COLOR_BUFFER_BIT | GL_DEPTH_BUFFER_BIT),
+        )?;
+        self.check(code)
+    }
+
+    fn draw_arrays(&mut self, mode: u32, first: i32, count: i32) -> PyResult<()> {
+        let code: u32 = self.call("wasm_ctx_draw_arrays", (self.ctx, mode, first, count))?;
+        self.call::<(), u32>("wasm_frame_reset", ())?;
+        self.check(code)
+    }
+
+    /// The framebuffer as RGBA8 bytes, bottom row first.
+    fn read_pixels<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let ptr: u32 = self.call(
+            "wasm_ctx_read_pixels",
+            (
+                self.ctx,
+                0i32,
+                0i32,
+                self.width,
+                self.height,
+                GL_RGBA,
+                GL_UNSIGNED_BYTE,
+            ),
+        )?;
+        if ptr == 0 {
+            self.check(u32::MAX)?;
+        }
+        Ok(PyBytes::new_bound(py, &self.payload(ptr)?))
+    }
+
+    /// The GL error flag, as `gl.getError()`.
+    fn get_error(&mut self) -> PyResult<u32> {
+        self.call("wasm_ctx_get_error", self.ctx)
+    }
+}
+
+impl Drop for Context {
+    fn drop(&mut self) {
+        if self.ctx != 0 {
+            let _ = self.call::<u32, u32>("wasm_destroy_context", self.ctx);
+        }
+    }
+}
+
+/// The `webgl2` Python module.
+#[pymodule]
+fn webgl2(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Context>()
+}
@@ -39,9 +39,48 @@ pub enum StorageLayout {
     Morton,
 }
 
+/// Bytes backing a [`GpuBuffer`]
+pub enum Storage {
+    /// Allocated and owned by the kernel
+    Owned(Vec<u8>),
+    /// `len` bytes of linear memory at `ptr`, owned by the host; see
+    /// [`GpuKernel::create_alias`]
+    Aliased { ptr: usize, len: usize },
+}
+
+impl Storage {
+    pub fn is_aliased(&self) -> bool {
+        matches!(self, Storage::Aliased { .. })
+    }
+}
+
+impl std::ops::Deref for Storage {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Storage::Owned(data) => data,
+            Storage::Aliased { ptr, len } => unsafe {
+                std::slice::from_raw_parts(*ptr as *const u8, *len)
+            },
+        }
+    }
+}
+
+impl std::ops::DerefMut for Storage {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            Storage::Owned(data) => data,
+            Storage::Aliased { ptr, len } => unsafe {
+                std::slice::from_raw_parts_mut(*ptr as *mut u8, *len)
+            },
+        }
+    }
+}
+
 /// A centralized GPU buffer owned by the GpuKernel
 pub struct GpuBuffer {
-    pub data: Vec<u8>,
+    pub data: Storage,
     pub width: u32,
     pub height: u32,
     pub depth: u32,
@@ -72,7 +111,7 @@ impl GpuBuffer {
             }
         };
         Self {
-            data: vec![0; size as usize],
+            data: Storage::Owned(vec![0; size as usize]),
             width,
             height,
             depth,
@@ -81,6 +120,48 @@ impl GpuBuffer {
         }
     }
 
+    /// Pixels in one plane of a `width` x `height` image stored in `layout`,
+    /// counting the padding of partial tiles
+    fn plane_pixels(width: u32, height: u32, layout: StorageLayout) -> usize {
+        match layout {
+            StorageLayout::Linear => width as usize * height as usize,
+            StorageLayout::Tiled8x8 => {
+                width.div_ceil(8) as usize * height.div_ceil(8) as usize * 64
+            }
+            StorageLayout::Morton => {
+                let dim = width.max(height).next_power_of_two() as usize;
+                dim * dim
+            }
+        }
+    }
+
+    /// Split a depth/stencil buffer into its f32 depth plane and u8 stencil
+    /// plane, both indexed by pixel as a one-byte-per-pixel image in the
+    /// buffer's layout. A plane is empty when the format lacks that aspect
+    /// or the buffer has no room for it (depth textures carry no stencil).
+    pub fn depth_stencil_planes(&mut self) -> (&mut [f32], &mut [u8]) {
+        let pixels = Self::plane_pixels(self.width, self.height, self.layout);
+        let depth_len = if self.format.has_depth_aspect() {
+            pixels
+        } else {
+            0
+        };
+        let data: &mut [u8] = &mut self.data;
+        if data.len() < depth_len * 4 {
+            return (&mut [], &mut []);
+        }
+        let (depth, rest) = data.split_at_mut(depth_len * 4);
+        let stencil = if self.format.has_stencil_aspect() && rest.len() >= pixels {
+            &mut rest[..pixels]
+        } else {
+            &mut []
+        };
+        // Depth is kept as native-endian f32s, as the rasterizer compares them
+        let depth =
+            unsafe { std::slice::from_raw_parts_mut(depth.as_mut_ptr() as *mut f32, depth_len) };
+        (depth, stencil)
+    }
+
     /// Calculate byte offset for a pixel at (x, y, z)
     pub fn get_pixel_offset(&self, x: u32, y: u32, z: u32) -> usize {
         Self::offset_for_layout(
@@ -159,6 +240,7 @@ pub struct TextureBinding {
     pub wrap_r: u32,
     pub min_filter: u32,
     pub mag_filter: u32,
+    pub lod_bias: f32,
     pub gpu_handle: GpuHandle,
 }
 
@@ -181,11 +263,43 @@ impl GpuKernel {
         handle
     }
 
+    /// Create a depth and/or stencil buffer for a `format` with those
+    /// aspects: an f32 depth plane cleared to 1.0 followed by a u8 stencil
+    /// plane cleared to 0, whichever the format has (see
+    /// [`GpuBuffer::depth_stencil_planes`]).
+    pub fn create_depth_stencil_buffer(
+        &mut self,
+        width: u32,
+        height: u32,
+        format: wgt::TextureFormat,
+        layout: StorageLayout,
+    ) -> GpuHandle {
+        let pixels = GpuBuffer::plane_pixels(width, height, layout);
+        let mut data = Vec::new();
+        if format.has_depth_aspect() {
+            data.extend(std::iter::repeat_n(1.0f32.to_ne_bytes(), pixels).flatten());
+        }
+        if format.has_stencil_aspect() {
+            data.resize(data.len() + pixels, 0);
+        }
+        let handle = GpuHandle::next();
+        let buffer = GpuBuffer {
+            data: Storage::Owned(data),
+            width,
+            height,
+            depth: 1,
+            format,
+            layout,
+        };
+        self.resources.insert(handle, buffer);
+        handle
+    }
+
     /// Shortcut for creating a 1D blob buffer (e.g., EBO, VBO)
     pub fn create_buffer_blob(&mut self, size: usize) -> GpuHandle {
         let handle = GpuHandle::next();
         let buffer = GpuBuffer {
-            data: vec![0; size],
+            data: Storage::Owned(vec![0; size]),
             width: size as u32,
             height: 1,
             depth: 1,
@@ -196,6 +310,51 @@ impl GpuKernel {
         handle
     }
 
+    /// Create a linear buffer over `len` bytes of linear memory at `ptr`
+    /// instead of allocating one: reads and writes of the buffer go straight
+    /// to that memory.
+    ///
+    /// # Safety
+    ///
+    /// The range must be valid for reads and writes, and hold at least a
+    /// `width` x `height` image of `format`, until the buffer is destroyed
+    /// or [detached](Self::detach_alias).
+    pub unsafe fn create_alias(
+        &mut self,
+        ptr: u32,
+        len: usize,
+        width: u32,
+        height: u32,
+        format: wgt::TextureFormat,
+    ) -> GpuHandle {
+        let handle = GpuHandle::next();
+        let buffer = GpuBuffer {
+            data: Storage::Aliased {
+                ptr: ptr as usize,
+                len,
+            },
+            width,
+            height,
+            depth: 1,
+            format,
+            layout: StorageLayout::Linear,
+        };
+        self.resources.insert(handle, buffer);
+        handle
+    }
+
+    /// End aliasing of an aliased buffer, which keeps a copy of the memory's
+    /// current contents. Returns false if the buffer is not aliased.
+    pub fn detach_alias(&mut self, handle: GpuHandle) -> bool {
+        match self.resources.get_mut(&handle) {
+            Some(buf) if buf.data.is_aliased() => {
+                buf.data = Storage::Owned(buf.data.to_vec());
+                true
+            }
+            _ => false,
+        }
+    }
+
     pub fn get_buffer(&self, handle: GpuHandle) -> Option<&GpuBuffer> {
         self.resources.get(&handle)
     }
@@ -232,7 +391,7 @@ impl GpuKernel {
             dst_y as i32,
             (dst_x + width) as i32,
             (dst_y + height) as i32,
-            0x2600, /* GL_NEAREST */
+            crate::gl_constants::GL_NEAREST,
         );
     }
 
@@ -255,7 +414,7 @@ impl GpuKernel {
         let (src_data, src_w, src_h, src_d, src_format, src_layout) =
             if let Some(buf) = self.get_buffer(src_handle) {
                 (
-                    buf.data.clone(),
+                    buf.data.to_vec(),
                     buf.width,
                     buf.height,
                     buf.depth,
@@ -454,13 +613,13 @@ impl GpuKernel {
     }
 
     /// Writes texture metadata to the specified linear memory pointer for shader access
-    pub fn write_texture_metadata(&self, bindings: &[Option<TextureBinding>], dest_ptr: u32) {
+    pub fn write_texture_metadata(&self, bindings: &[Option<TextureBinding>], dest_ptr: usize) {
         for (i, binding) in bindings.iter().enumerate() {
             let offset = i * 64; // Match Naga stride (aligned to 64 bytes)
             if let Some(b) = binding {
                 if let Some(buf) = self.get_buffer(b.gpu_handle) {
                     unsafe {
-                        let base = (dest_ptr + offset as u32) as *mut i32;
+                        let base = (dest_ptr + offset) as *mut i32;
                         *base.offset(0) = b.width as i32;
                         *base.offset(1) = b.height as i32;
                         *base.offset(2) = buf.data.as_ptr() as i32;
@@ -473,6 +632,7 @@ impl GpuKernel {
                         *base.offset(9) = buf.layout as i32;
                         *base.offset(10) = b.min_filter as i32;
                         *base.offset(11) = b.mag_filter as i32;
+                        *base.offset(12) = b.lod_bias.to_bits() as i32;
                     }
                 }
             }
@@ -1,4 +1,7 @@
 //! Framebuffer management for render targets
+use crate::gl_constants::{
+    GL_R32F, GL_RG32F, GL_RGB565, GL_RGB5_A1, GL_RGBA32F, GL_RGBA4, GL_RGBA8,
+};
 use crate::wasm_gl_emu::device::{GpuBuffer, GpuHandle, GpuKernel, StorageLayout};
 use wgpu_types as wgt;
 
@@ -15,7 +18,7 @@ pub struct OwnedFramebuffer {
 
 impl OwnedFramebuffer {
     pub fn new(kernel: &mut GpuKernel, width: u32, height: u32) -> Self {
-        Self::new_with_format(kernel, width, height, 0x8058) // GL_RGBA8
+        Self::new_with_format(kernel, width, height, GL_RGBA8)
     }
 
     pub fn new_with_format(
@@ -25,10 +28,10 @@ impl OwnedFramebuffer {
         internal_format: u32,
     ) -> Self {
         let format = match internal_format {
-            0x822E => wgt::TextureFormat::R32Float,    // GL_R32F
-            0x8230 => wgt::TextureFormat::Rg32Float,   // GL_RG32F
-            0x8814 => wgt::TextureFormat::Rgba32Float, // GL_RGBA32F
-            _ => wgt::TextureFormat::Rgba8Unorm,       // GL_RGBA8
+            GL_R32F => wgt::TextureFormat::R32Float,
+            GL_RG32F => wgt::TextureFormat::Rg32Float,
+            GL_RGBA32F => wgt::TextureFormat::Rgba32Float,
+            _ => wgt::TextureFormat::Rgba8Unorm, // GL_RGBA8
         };
 
         let layout = StorageLayout::Linear;
@@ -134,9 +137,13 @@ impl<'a> Framebuffer<'a> {
         layout: StorageLayout,
     ) -> usize {
         let format = match internal_format {
-            0x822E => wgt::TextureFormat::R32Float,
-            0x8230 => wgt::TextureFormat::Rg32Float,
-            0x8814 => wgt::TextureFormat::Rgba32Float,
+            GL_R32F => wgt::TextureFormat::R32Float,
+            GL_RG32F => wgt::TextureFormat::Rg32Float,
+            GL_RGBA32F => wgt::TextureFormat::Rgba32Float,
+            // 16-bit packed formats, see `gl_to_wgt_format`
+            GL_RGB565 => wgt::TextureFormat::R16Uint,
+            GL_RGBA4 => wgt::TextureFormat::Rg8Uint,
+            GL_RGB5_A1 => wgt::TextureFormat::R16Sint,
             _ => wgt::TextureFormat::Rgba8Unorm,
         };
         GpuBuffer::offset_for_layout(x, y, z, width, height, 1, format, layout)
@@ -0,0 +1,78 @@
+//! Pipeline-stage hooks for research instrumentation.
+//!
+//! A [`PipelineHooks`] installed on a [`Rasterizer`](super::Rasterizer)
+//! sees every vertex right after the vertex shader and every fragment right
+//! before the depth and stencil tests, with its interpolated varyings, and
+//! may discard either. Custom culling schemes and pipeline statistics can so
+//! be prototyped without forking the rasterizer.
+//!
+//! Varyings are the raw words of the varying buffer: `gl_Position`, then
+//! built-ins such as `gl_PointSize`, then four words per location from word
+//! 8. Float varyings are `f32` bit patterns; `bindVaryingLocation` pins the
+//! locations of the ones of interest.
+//!
+//! The JS host installs hooks through `setPipelineHooks`; [`HostHooks`]
+//! forwards each stage to the `pipeline_hook_vertex` and
+//! `pipeline_hook_fragment` imports.
+
+/// A vertex after the vertex shader
+#[derive(Debug, Clone, Copy)]
+pub struct VertexStage<'a> {
+    /// `gl_VertexID`: the index for indexed draws
+    pub vertex_id: u32,
+    /// `gl_InstanceID`
+    pub instance_id: u32,
+    /// Clip-space position
+    pub position: [f32; 4],
+    /// The varying buffer the vertex shader wrote
+    pub varyings: &'a [u32],
+}
+
+/// A fragment before the depth and stencil tests
+#[derive(Debug, Clone, Copy)]
+pub struct FragmentStage<'a> {
+    /// Window coordinates of the pixel
+    pub x: i32,
+    pub y: i32,
+    /// Window-space depth the depth test will compare
+    pub depth: f32,
+    pub front_facing: bool,
+    /// Varyings interpolated for the fragment shader
+    pub varyings: &'a [u32],
+}
+
+/// Callbacks the rasterizer makes at each pipeline stage. Both default to
+/// keeping everything. Hooks live in the context registry, hence `Send`.
+pub trait PipelineHooks: Send {
+    /// Called once per vertex shader invocation. Returning `false` culls
+    /// every point or triangle that uses the vertex.
+    fn vertex(&self, _vertex: &VertexStage) -> bool {
+        true
+    }
+
+    /// Called for each covered pixel before the depth and stencil tests.
+    /// Returning `false` discards the fragment, leaving the framebuffer and
+    /// the depth and stencil buffers alone.
+    fn fragment(&self, _fragment: &FragmentStage) -> bool {
+        true
+    }
+}
+
+/// Hooks implemented by the JS host for context `ctx`. Stages the host has
+/// no callback for are not forwarded, so they cost nothing.
+#[derive(Debug, Clone, Copy)]
+pub struct HostHooks {
+    pub ctx: u32,
+    pub vertex: bool,
+    pub fragment: bool,
+}
+
+impl PipelineHooks for HostHooks {
+    fn vertex(&self, vertex: &VertexStage) -> bool {
+        !self.vertex || crate::js_pipeline_hook_vertex(self.ctx, vertex)
+    }
+
+    fn fragment(&self, fragment: &FragmentStage) -> bool {
+        !self.fragment || crate::js_pipeline_hook_fragment(self.ctx, fragment)
+    }
+}
@@ -6,17 +6,22 @@
 
 pub mod device;
 mod framebuffer;
+pub mod hooks;
 mod pipeline;
 pub mod rasterizer;
+#[cfg(test)]
+mod reference_rasterizer;
 mod state;
 mod texture;
 pub mod transfer;
 
 pub use device::{GpuBuffer, GpuHandle, GpuKernel, StorageLayout};
 pub use framebuffer::{Framebuffer, OwnedFramebuffer};
+pub use hooks::PipelineHooks;
 pub use pipeline::{Pipeline, VertexOutput};
 pub use rasterizer::{
-    ProcessedVertex, RasterPipeline, Rasterizer, RenderState, ShaderMemoryLayout, VertexFetcher,
+    DebugView, ProcessedVertex, RasterPipeline, Rasterizer, RenderState, ShaderMemoryLayout,
+    VertexFetcher,
 };
 pub use state::WebGLState;
 pub use texture::Texture;
@@ -4,9 +4,12 @@
 //! by both WebGL2 and WebGPU implementations. It handles vertex fetching,
 //! barycentric interpolation, and fragment shading.
 
+use crate::naga_wasm_backend::output_layout::{self, ShadingRate};
 use crate::wasm_gl_emu::device::{GpuHandle, GpuKernel, StorageLayout};
 use crate::wasm_gl_emu::framebuffer::ColorAttachment;
+use crate::wasm_gl_emu::hooks::{FragmentStage, PipelineHooks, VertexStage};
 use crate::webgl2_context::types::*;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -18,6 +21,17 @@ use crate::wasm_sync_turbo_globals;
 type VsEntryFn = extern "C" fn(i32, i32, i32);
 type FsEntryFn = extern "C" fn(i32, i32);
 
+/// Stage of the shader running right now (`GL_VERTEX_SHADER` or
+/// `GL_FRAGMENT_SHADER`), or 0. A trap unwinds past the reset, so after one
+/// it tells whether the trap came from shader code or from the emulator.
+static RUNNING_STAGE: AtomicU32 = AtomicU32::new(0);
+
+/// Take the stage of the shader a trap interrupted: 0 when no shader was
+/// running.
+pub fn take_trapped_stage() -> u32 {
+    RUNNING_STAGE.swap(0, Ordering::Relaxed)
+}
+
 pub type PrepareTexturesFn<'a> = dyn Fn(&ShaderMemoryLayout) + 'a;
 
 /// Vertex data after vertex shader execution
@@ -32,21 +46,23 @@ pub struct ProcessedVertex {
 }
 
 /// Memory pointers for shader execution
-/// This replaces hardcoded memory offsets with flexible pointers
+/// This replaces hardcoded memory offsets with flexible pointers.
+/// They are host addresses, narrowed to the shaders' `u32` globals only on
+/// wasm32 where they fit, so native draws can still fill these buffers
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ShaderMemoryLayout {
     /// Pointer to attribute data (vertex shader input)
-    pub attr_ptr: u32,
+    pub attr_ptr: usize,
     /// Pointer to uniform data
-    pub uniform_ptr: u32,
+    pub uniform_ptr: usize,
     /// Pointer to varying data (VS output / FS input)
-    pub varying_ptr: u32,
+    pub varying_ptr: usize,
     /// Pointer to private/local shader data
-    pub private_ptr: u32,
+    pub private_ptr: usize,
     /// Pointer to texture metadata
-    pub texture_ptr: u32,
+    pub texture_ptr: usize,
     /// Pointer to frame stack
-    pub frame_sp: u32,
+    pub frame_sp: usize,
 }
 
 impl ShaderMemoryLayout {
@@ -85,6 +101,182 @@ pub struct RenderState<'a> {
     pub cull_face_mode: u32,
     /// Front face winding (GL_CW, GL_CCW)
     pub front_face: u32,
+    /// Diagnostic view replacing shader colors
+    pub debug_view: DebugView,
+    /// Dither writes to low-bit-depth color formats (GL_DITHER)
+    pub dither: bool,
+}
+
+/// Diagnostic replacement for the colors triangles write to RGBA8
+/// attachments. Other attachment formats keep the shader's output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DebugView {
+    /// Normal rendering
+    #[default]
+    None,
+    /// Window-space depth as gray, black at the near plane
+    Depth,
+    /// Every fragment adds [`OVERDRAW_STEP`] to the red channel
+    Overdraw,
+    /// Only fragments within a pixel of a triangle edge, in white
+    Wireframe,
+    /// Shader output mixed with the tint of the sampled mip level
+    /// ([`MIP_LEVEL_TINTS`]). Samplers read level 0 only, so textured and
+    /// untextured fragments all get the level 0 tint for now.
+    MipLevel,
+}
+
+impl DebugView {
+    pub fn from_u32(mode: u32) -> Option<Self> {
+        Some(match mode {
+            0 => DebugView::None,
+            1 => DebugView::Depth,
+            2 => DebugView::Overdraw,
+            3 => DebugView::Wireframe,
+            4 => DebugView::MipLevel,
+            _ => return None,
+        })
+    }
+}
+
+/// Red added per fragment by [`DebugView::Overdraw`]; a pixel saturates
+/// after eight fragments.
+pub const OVERDRAW_STEP: u8 = 32;
+
+/// Tints of mip levels 0.. for [`DebugView::MipLevel`]: red, green, blue,
+/// yellow, magenta, cyan; deeper levels reuse the last.
+pub const MIP_LEVEL_TINTS: [[u8; 3]; 6] = [
+    [255, 0, 0],
+    [0, 255, 0],
+    [0, 0, 255],
+    [255, 255, 0],
+    [255, 0, 255],
+    [0, 255, 255],
+];
+
+/// Color `view` writes to the RGBA8 pixel at `idx` of `att` in place of
+/// `shader` (the fragment shader's output), or `None` to keep the latter.
+fn debug_view_color(
+    view: DebugView,
+    depth: f32,
+    att: &ColorAttachment,
+    idx: usize,
+    shader: &[u8],
+) -> Option<[u8; 4]> {
+    if att.internal_format != GL_RGBA8 || idx + 4 > att.data.len() || shader.len() < 4 {
+        return None;
+    }
+    match view {
+        DebugView::None => None,
+        DebugView::Depth => {
+            let gray = (depth.clamp(0.0, 1.0) * 255.0).round() as u8;
+            Some([gray, gray, gray, 255])
+        }
+        DebugView::Overdraw => Some([att.data[idx].saturating_add(OVERDRAW_STEP), 0, 0, 255]),
+        DebugView::Wireframe => Some([255, 255, 255, 255]),
+        DebugView::MipLevel => {
+            let tint = MIP_LEVEL_TINTS[0];
+            let mix = |c: usize| ((shader[c] as u16 + tint[c] as u16) / 2) as u8;
+            Some([mix(0), mix(1), mix(2), 255])
+        }
+    }
+}
+
+/// 4x4 ordered dither matrix, in sixteenths of a quantization step
+const DITHER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// `(bits, shift)` of the red, green, blue and alpha channels of a packed
+/// 16-bit color format, matching the layouts clears and readback use, or
+/// `None` for other formats. A channel with 0 bits is absent.
+fn packed_layout(format: u32) -> Option<[(u32, u32); 4]> {
+    match format {
+        GL_RGB565 => Some([(5, 0), (6, 5), (5, 11), (0, 0)]),
+        GL_RGBA4 => Some([(4, 12), (4, 8), (4, 4), (4, 0)]),
+        GL_RGB5_A1 => Some([(5, 11), (5, 6), (5, 1), (1, 0)]),
+        _ => None,
+    }
+}
+
+/// Whether `format` is one of the packed 16-bit color formats (RGB565,
+/// RGBA4, RGB5_A1)
+pub fn is_packed_format(format: u32) -> bool {
+    packed_layout(format).is_some()
+}
+
+/// Quantize `color` to a packed 16-bit format. Each channel is scaled to
+/// its range and `threshold` (0.5 rounds to nearest) is added before
+/// truncating.
+pub fn pack_color(format: u32, color: [f32; 4], threshold: f32) -> u16 {
+    let Some(layout) = packed_layout(format) else {
+        return 0;
+    };
+    let mut packed = 0u16;
+    for (c, (bits, shift)) in color.into_iter().zip(layout) {
+        if bits > 0 {
+            let max = ((1u32 << bits) - 1) as f32;
+            let q = (c.clamp(0.0, 1.0) * max + threshold).floor().min(max);
+            packed |= (q as u16) << shift;
+        }
+    }
+    packed
+}
+
+/// Expand a packed 16-bit color to floats; a missing alpha channel reads
+/// as 1.
+pub fn unpack_color(format: u32, packed: u16) -> [f32; 4] {
+    let Some(layout) = packed_layout(format) else {
+        return [0.0; 4];
+    };
+    layout.map(|(bits, shift)| {
+        if bits == 0 {
+            return 1.0;
+        }
+        let max = (1u16 << bits) - 1;
+        ((packed >> shift) & max) as f32 / max as f32
+    })
+}
+
+/// Rounding threshold for pixel `(x, y)`: the ordered dither pattern when
+/// dithering, so gradients quantize to a stipple rather than bands, and
+/// round-to-nearest otherwise.
+fn dither_threshold(dither: bool, x: i32, y: i32) -> f32 {
+    if dither {
+        (DITHER_4X4[(y & 3) as usize][(x & 3) as usize] as f32 + 0.5) / 16.0
+    } else {
+        0.5
+    }
+}
+
+/// Blend, mask and quantize a fragment (four `f32`s, as the fragment shader
+/// returns them for packed formats) into the packed pixel at `idx`.
+fn write_packed_pixel(
+    att: &mut ColorAttachment,
+    idx: usize,
+    x: i32,
+    y: i32,
+    color: &[u8],
+    state: &RenderState,
+) {
+    if idx + 2 > att.data.len() || color.len() < 16 {
+        return;
+    }
+    let src: [f32; 4] =
+        std::array::from_fn(|i| f32::from_ne_bytes(color[i * 4..i * 4 + 4].try_into().unwrap()));
+    let dst = unpack_color(
+        att.internal_format,
+        u16::from_ne_bytes([att.data[idx], att.data[idx + 1]]),
+    );
+    let blended = blend_pixel_f32(src, dst, &state.blend);
+    let mask = [
+        state.color_mask.r,
+        state.color_mask.g,
+        state.color_mask.b,
+        state.color_mask.a,
+    ];
+    let out = std::array::from_fn(|i| if mask[i] { blended[i] } else { dst[i] });
+    let threshold = dither_threshold(state.dither, x, y);
+    let packed = pack_color(att.internal_format, out, threshold);
+    att.data[idx..idx + 2].copy_from_slice(&packed.to_ne_bytes());
 }
 
 /// Interface for fetching vertex attributes
@@ -104,8 +296,13 @@ pub enum ColorTarget<'a> {
 
 /// Depth/Stencil target for rasterization
 pub enum DepthStencilTarget<'a> {
-    /// Target is a buffer managed by the kernel
-    Handle(GpuHandle),
+    /// Target is the depth plane of one kernel buffer and the stencil plane
+    /// of another (or the same), as attached to a framebuffer object. An
+    /// invalid handle leaves that plane out, so its test always passes.
+    Handles {
+        depth: GpuHandle,
+        stencil: GpuHandle,
+    },
     /// Target is a raw F32/U8 slice (used by default framebuffer)
     Raw {
         depth: &'a mut [f32],
@@ -182,6 +379,8 @@ pub struct DepthState {
     pub enabled: bool,
     pub func: u32,  // GL_LESS
     pub mask: bool, // true
+    /// Representation of the depth buffer being drawn to
+    pub precision: DepthPrecision,
 }
 
 impl Default for DepthState {
@@ -190,6 +389,50 @@ impl Default for DepthState {
             enabled: false,
             func: GL_LESS,
             mask: true,
+            precision: DepthPrecision::Float32,
+        }
+    }
+}
+
+/// How a depth buffer represents values. Depth buffers are always stored as
+/// `f32`; fixed-point buffers round every value they test and store to their
+/// grid, so depth fighting shows up as it does on hardware.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DepthPrecision {
+    /// 32-bit float (`DEPTH_COMPONENT32F`)
+    #[default]
+    Float32,
+    /// Unsigned normalized fixed point with this many bits
+    /// (`DEPTH_COMPONENT16`, `DEPTH_COMPONENT24`)
+    Fixed(u32),
+}
+
+impl DepthPrecision {
+    /// Representation of a depth attachment of `internal_format`.
+    pub fn of_format(internal_format: u32) -> Self {
+        match internal_format {
+            GL_DEPTH_COMPONENT16 => Self::Fixed(16),
+            GL_DEPTH_COMPONENT24 | GL_DEPTH24_STENCIL8 => Self::Fixed(24),
+            _ => Self::Float32,
+        }
+    }
+
+    /// Bits of depth, as `DEPTH_BITS` reports them.
+    pub fn bits(self) -> u32 {
+        match self {
+            Self::Float32 => 32,
+            Self::Fixed(bits) => bits,
+        }
+    }
+
+    /// `depth`, in `[0, 1]`, as the buffer stores it.
+    pub fn quantize(self, depth: f32) -> f32 {
+        match self {
+            Self::Float32 => depth,
+            Self::Fixed(bits) => {
+                let max = ((1u64 << bits) - 1) as f64;
+                ((depth as f64 * max).round() / max) as f32
+            }
         }
     }
 }
@@ -229,7 +472,11 @@ pub struct RasterPipeline {
     /// Memory layout for this pipeline
     pub memory: ShaderMemoryLayout,
     /// Bitmask of flat varyings (1 = flat, 0 = smooth)
-    pub flat_varyings_mask: u64,
+    pub flat_varyings_mask: u128,
+    /// Bitmask of noperspective varyings, interpolated linearly in screen space
+    pub noperspective_varyings_mask: u128,
+    /// How often the fragment shader runs per covered pixel
+    pub shading_rate: ShadingRate,
     /// Function table index for vertex shader (if available)
     pub vs_table_idx: Option<u32>,
     /// Function table index for fragment shader (if available)
@@ -237,76 +484,27 @@ pub struct RasterPipeline {
 }
 
 impl RasterPipeline {
-    /// Computes the flat varyings mask from a fragment shader Naga module
-    pub fn compute_flat_varyings_mask(fs_module: &naga::Module) -> u64 {
-        let mut mask = 0u64;
-        for ep in &fs_module.entry_points {
-            if ep.stage == naga::ShaderStage::Fragment {
-                for arg in &ep.function.arguments {
-                    let mut make_flat = false;
-                    let mut location = None;
-
-                    if let Some(naga::Binding::Location {
-                        location: loc,
-                        interpolation,
-                        ..
-                    }) = &arg.binding
-                    {
-                        location = Some(*loc);
-                        if let Some(interp) = interpolation {
-                            if *interp == naga::Interpolation::Flat {
-                                make_flat = true;
-                            }
-                        }
-                    }
-
-                    if !make_flat {
-                        let ty = &fs_module.types[arg.ty];
-                        match &ty.inner {
-                            naga::TypeInner::Scalar(scalar) => {
-                                if scalar.kind == naga::ScalarKind::Sint
-                                    || scalar.kind == naga::ScalarKind::Uint
-                                {
-                                    make_flat = true;
-                                }
-                            }
-                            naga::TypeInner::Vector { scalar, .. } => {
-                                if scalar.kind == naga::ScalarKind::Sint
-                                    || scalar.kind == naga::ScalarKind::Uint
-                                {
-                                    make_flat = true;
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-
-                    if make_flat {
-                        if let Some(loc) = location {
-                            let ty = &fs_module.types[arg.ty];
-                            let components = match &ty.inner {
-                                naga::TypeInner::Scalar(_) => 1,
-                                naga::TypeInner::Vector { size, .. } => match size {
-                                    naga::VectorSize::Bi => 2,
-                                    naga::VectorSize::Tri => 3,
-                                    naga::VectorSize::Quad => 4,
-                                },
-                                _ => 1,
-                            };
-
-                            let start_bit = (loc + 2) * 4;
-                            for i in 0..components {
-                                let bit = start_bit + i;
-                                if bit < 64 {
-                                    mask |= 1u64 << bit;
-                                }
-                            }
-                        }
-                    }
+    /// Computes the flat and noperspective varying component masks from the
+    /// fragment input metadata recorded by the backend.
+    pub fn compute_varying_masks(
+        varyings: &[crate::naga_wasm_backend::output_layout::VaryingInfo],
+    ) -> (u128, u128) {
+        let mut flat = 0u128;
+        let mut noperspective = 0u128;
+        for varying in varyings {
+            let mask = match varying.interpolation {
+                naga::Interpolation::Flat => &mut flat,
+                naga::Interpolation::Linear => &mut noperspective,
+                _ => continue,
+            };
+            let start_bit = varying.first_component();
+            for bit in start_bit..start_bit + varying.components {
+                if bit < 128 {
+                    *mask |= 1u128 << bit;
                 }
             }
         }
-        mask
+        (flat, noperspective)
     }
 }
 
@@ -323,12 +521,441 @@ impl RasterPipeline {
             fragment_shader_type: GL_FRAGMENT_SHADER,
             memory: ShaderMemoryLayout::new(),
             flat_varyings_mask: 0,
+            noperspective_varyings_mask: 0,
+            shading_rate: ShadingRate::PerPixel,
             vs_table_idx: None,
             fs_table_idx: None,
         }
     }
 }
 
+/// Per-fragment test that discarded a fragment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Rejection {
+    Stencil,
+    /// The depth test, or a depth outside [0, 1]
+    Depth,
+}
+
+/// Stencil and depth tests for the fragment at pixel index `fb_idx` with
+/// window-space `depth`, applying the stencil operations of `face_state` and
+/// the depth write. Returns the test that discarded the fragment, if any.
+fn depth_stencil_test(
+    fb: &mut crate::wasm_gl_emu::Framebuffer,
+    fb_idx: usize,
+    depth: f32,
+    face_state: &StencilFaceState,
+    state: &RenderState,
+) -> Result<(), Rejection> {
+    let stencil_enabled = state.stencil.enabled && !fb.stencil.is_empty();
+    let update_stencil = |fb: &mut crate::wasm_gl_emu::Framebuffer, op: u32| {
+        let current_stencil = fb.stencil[fb_idx];
+        let new_val = apply_stencil_op(op, current_stencil, face_state.ref_val);
+        fb.stencil[fb_idx] = (current_stencil & !face_state.write_mask as u8)
+            | (new_val & face_state.write_mask as u8);
+    };
+
+    // --- Stencil Test ---
+    if stencil_enabled
+        && !compare_stencil(
+            face_state.func,
+            face_state.ref_val,
+            fb.stencil[fb_idx],
+            face_state.mask,
+        )
+    {
+        update_stencil(fb, face_state.fail);
+        return Err(Rejection::Stencil);
+    }
+
+    // --- Depth Test ---
+    // Check bounds [0,1]
+    if !(0.0..=1.0).contains(&depth) {
+        return Err(Rejection::Depth);
+    }
+    let depth = state.depth.precision.quantize(depth);
+
+    // When depth test is disabled or no buffer exists, it always passes
+    let depth_pass = !state.depth.enabled
+        || fb.depth.is_empty()
+        || compare_depth(state.depth.func, depth, fb.depth[fb_idx]);
+
+    // Handle Depth Fail / Pass for Stencil
+    if stencil_enabled {
+        update_stencil(
+            fb,
+            if depth_pass {
+                face_state.zpass
+            } else {
+                face_state.zfail
+            },
+        );
+    }
+    if !depth_pass {
+        return Err(Rejection::Depth);
+    }
+
+    // --- Write Depth ---
+    if state.depth.mask && !fb.depth.is_empty() {
+        fb.depth[fb_idx] = depth;
+    }
+    Ok(())
+}
+
+/// Write a shaded fragment's `colors` (one per draw buffer, converted by
+/// [`Rasterizer::execute_fragment_shader`]) to pixel (`x`, `y`), applying
+/// blending, the color mask and the debug view.
+fn write_fragment(
+    fb: &mut crate::wasm_gl_emu::Framebuffer,
+    x: i32,
+    y: i32,
+    depth: f32,
+    colors: &[Vec<u8>],
+    state: &RenderState,
+) {
+    let fb_w = fb.width;
+    let fb_h = fb.height;
+    let fb_layout = fb.layout;
+
+    for (i, attachment) in fb.color_attachments.iter_mut().enumerate() {
+        if let Some(att) = attachment {
+            let color_idx = crate::wasm_gl_emu::Framebuffer::get_pixel_offset_params(
+                x as u32,
+                y as u32,
+                0,
+                att.internal_format,
+                fb_w,
+                fb_h,
+                fb_layout,
+            );
+            let debug_color = debug_view_color(state.debug_view, depth, att, color_idx, &colors[i]);
+            let color = debug_color
+                .as_ref()
+                .map_or(colors[i].as_slice(), |c| c.as_slice());
+
+            if is_packed_format(att.internal_format) {
+                write_packed_pixel(att, color_idx, x, y, color, state);
+            } else if color_idx + color.len() <= att.data.len() {
+                // For 32-bit formats, write directly with optional blending
+                if att.internal_format == GL_R32F
+                    || att.internal_format == GL_RG32F
+                    || att.internal_format == GL_RGBA32F
+                    || att.internal_format == GL_R32UI
+                    || att.internal_format == GL_RG32UI
+                    || att.internal_format == GL_RGBA32UI
+                    || att.internal_format == GL_R32I
+                    || att.internal_format == GL_RG32I
+                    || att.internal_format == GL_RGBA32I
+                    || att.internal_format == GL_R8UI
+                    || att.internal_format == GL_RG8UI
+                    || att.internal_format == GL_RGBA8UI
+                    || att.internal_format == GL_R8I
+                    || att.internal_format == GL_RG8I
+                    || att.internal_format == GL_RGBA8I
+                    || att.internal_format == GL_R16UI
+                    || att.internal_format == GL_RG16UI
+                    || att.internal_format == GL_RGBA16UI
+                    || att.internal_format == GL_R16I
+                    || att.internal_format == GL_RG16I
+                    || att.internal_format == GL_RGBA16I
+                {
+                    // GL_R32F, GL_RG32F, GL_RGBA32F
+                    if state.blend.enabled
+                        && (att.internal_format == GL_R32F
+                            || att.internal_format == GL_RG32F
+                            || att.internal_format == GL_RGBA32F)
+                    {
+                        let existing: [f32; 4] = match att.internal_format {
+                            GL_R32F => {
+                                let v = f32::from_ne_bytes(
+                                    att.data[color_idx..color_idx + 4].try_into().unwrap(),
+                                );
+                                [v, 0.0, 0.0, 1.0]
+                            }
+                            GL_RG32F => {
+                                let v0 = f32::from_ne_bytes(
+                                    att.data[color_idx..color_idx + 4].try_into().unwrap(),
+                                );
+                                let v1 = f32::from_ne_bytes(
+                                    att.data[color_idx + 4..color_idx + 8].try_into().unwrap(),
+                                );
+                                [v0, v1, 0.0, 1.0]
+                            }
+                            GL_RGBA32F => {
+                                let v0 = f32::from_ne_bytes(
+                                    att.data[color_idx..color_idx + 4].try_into().unwrap(),
+                                );
+                                let v1 = f32::from_ne_bytes(
+                                    att.data[color_idx + 4..color_idx + 8].try_into().unwrap(),
+                                );
+                                let v2 = f32::from_ne_bytes(
+                                    att.data[color_idx + 8..color_idx + 12].try_into().unwrap(),
+                                );
+                                let v3 = f32::from_ne_bytes(
+                                    att.data[color_idx + 12..color_idx + 16].try_into().unwrap(),
+                                );
+                                [v0, v1, v2, v3]
+                            }
+                            _ => [0.0, 0.0, 0.0, 1.0],
+                        };
+
+                        let src_color: [f32; 4] = match att.internal_format {
+                            GL_R32F => [
+                                f32::from_ne_bytes(color[0..4].try_into().unwrap()),
+                                0.0,
+                                0.0,
+                                1.0,
+                            ],
+                            GL_RG32F => [
+                                f32::from_ne_bytes(color[0..4].try_into().unwrap()),
+                                f32::from_ne_bytes(color[4..8].try_into().unwrap()),
+                                0.0,
+                                1.0,
+                            ],
+                            GL_RGBA32F => [
+                                f32::from_ne_bytes(color[0..4].try_into().unwrap()),
+                                f32::from_ne_bytes(color[4..8].try_into().unwrap()),
+                                f32::from_ne_bytes(color[8..12].try_into().unwrap()),
+                                f32::from_ne_bytes(color[12..16].try_into().unwrap()),
+                            ],
+                            _ => [0.0, 0.0, 0.0, 1.0],
+                        };
+
+                        let blended = blend_pixel_f32(src_color, existing, &state.blend);
+
+                        // Write back blended
+                        match att.internal_format {
+                            GL_R32F => {
+                                if state.color_mask.r {
+                                    att.data[color_idx..color_idx + 4]
+                                        .copy_from_slice(&blended[0].to_ne_bytes());
+                                }
+                            }
+                            GL_RG32F => {
+                                if state.color_mask.r {
+                                    att.data[color_idx..color_idx + 4]
+                                        .copy_from_slice(&blended[0].to_ne_bytes());
+                                }
+                                if state.color_mask.g {
+                                    att.data[color_idx + 4..color_idx + 8]
+                                        .copy_from_slice(&blended[1].to_ne_bytes());
+                                }
+                            }
+                            GL_RGBA32F => {
+                                if state.color_mask.r {
+                                    att.data[color_idx..color_idx + 4]
+                                        .copy_from_slice(&blended[0].to_ne_bytes());
+                                }
+                                if state.color_mask.g {
+                                    att.data[color_idx + 4..color_idx + 8]
+                                        .copy_from_slice(&blended[1].to_ne_bytes());
+                                }
+                                if state.color_mask.b {
+                                    att.data[color_idx + 8..color_idx + 12]
+                                        .copy_from_slice(&blended[2].to_ne_bytes());
+                                }
+                                if state.color_mask.a {
+                                    att.data[color_idx + 12..color_idx + 16]
+                                        .copy_from_slice(&blended[3].to_ne_bytes());
+                                }
+                            }
+                            _ => {}
+                        }
+                    } else {
+                        // Clamp to mask if not blending
+                        match att.internal_format {
+                            GL_R32F | GL_R32UI | GL_R32I => {
+                                if state.color_mask.r {
+                                    att.data[color_idx..color_idx + 4]
+                                        .copy_from_slice(&color[0..4]);
+                                }
+                            }
+                            GL_RG32F | GL_RG32UI | GL_RG32I => {
+                                if state.color_mask.r {
+                                    att.data[color_idx..color_idx + 4]
+                                        .copy_from_slice(&color[0..4]);
+                                }
+                                if state.color_mask.g {
+                                    att.data[color_idx + 4..color_idx + 8]
+                                        .copy_from_slice(&color[4..8]);
+                                }
+                            }
+                            GL_RGBA32F | GL_RGBA32UI | GL_RGBA32I => {
+                                if state.color_mask.r {
+                                    att.data[color_idx..color_idx + 4]
+                                        .copy_from_slice(&color[0..4]);
+                                }
+                                if state.color_mask.g {
+                                    att.data[color_idx + 4..color_idx + 8]
+                                        .copy_from_slice(&color[4..8]);
+                                }
+                                if state.color_mask.b {
+                                    att.data[color_idx + 8..color_idx + 12]
+                                        .copy_from_slice(&color[8..12]);
+                                }
+                                if state.color_mask.a {
+                                    att.data[color_idx + 12..color_idx + 16]
+                                        .copy_from_slice(&color[12..16]);
+                                }
+                            }
+                            GL_R16UI | GL_R16I => {
+                                if state.color_mask.r {
+                                    att.data[color_idx..color_idx + 2]
+                                        .copy_from_slice(&color[0..2]);
+                                }
+                            }
+                            GL_RG16UI | GL_RG16I => {
+                                if state.color_mask.r {
+                                    att.data[color_idx..color_idx + 2]
+                                        .copy_from_slice(&color[0..2]);
+                                }
+                                if state.color_mask.g {
+                                    att.data[color_idx + 2..color_idx + 4]
+                                        .copy_from_slice(&color[2..4]);
+                                }
+                            }
+                            GL_RGBA16UI | GL_RGBA16I => {
+                                if state.color_mask.r {
+                                    att.data[color_idx..color_idx + 2]
+                                        .copy_from_slice(&color[0..2]);
+                                }
+                                if state.color_mask.g {
+                                    att.data[color_idx + 2..color_idx + 4]
+                                        .copy_from_slice(&color[2..4]);
+                                }
+                                if state.color_mask.b {
+                                    att.data[color_idx + 4..color_idx + 6]
+                                        .copy_from_slice(&color[4..6]);
+                                }
+                                if state.color_mask.a {
+                                    att.data[color_idx + 6..color_idx + 8]
+                                        .copy_from_slice(&color[6..8]);
+                                }
+                            }
+                            GL_R8UI | GL_R8I => {
+                                if state.color_mask.r {
+                                    att.data[color_idx] = color[0];
+                                }
+                            }
+                            GL_RG8UI | GL_RG8I => {
+                                if state.color_mask.r {
+                                    att.data[color_idx] = color[0];
+                                }
+                                if state.color_mask.g {
+                                    att.data[color_idx + 1] = color[1];
+                                }
+                            }
+                            GL_RGBA8UI | GL_RGBA8I => {
+                                if state.color_mask.r {
+                                    att.data[color_idx] = color[0];
+                                }
+                                if state.color_mask.g {
+                                    att.data[color_idx + 1] = color[1];
+                                }
+                                if state.color_mask.b {
+                                    att.data[color_idx + 2] = color[2];
+                                }
+                                if state.color_mask.a {
+                                    att.data[color_idx + 3] = color[3];
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                } else if att.internal_format == GL_RGBA8 {
+                    // GL_RGBA8: Use quantized blending
+                    let existing = [
+                        att.data[color_idx],
+                        att.data[color_idx + 1],
+                        att.data[color_idx + 2],
+                        att.data[color_idx + 3],
+                    ];
+                    let color_arr = [color[0], color[1], color[2], color[3]];
+                    let blended = blend_pixel(color_arr, existing, &state.blend);
+
+                    // Color Mask
+                    if state.color_mask.r {
+                        att.data[color_idx] = blended[0];
+                    }
+                    if state.color_mask.g {
+                        att.data[color_idx + 1] = blended[1];
+                    }
+                    if state.color_mask.b {
+                        att.data[color_idx + 2] = blended[2];
+                    }
+                    if state.color_mask.a {
+                        att.data[color_idx + 3] = blended[3];
+                    }
+                } else {
+                    // Float formats: Direct write (no blending yet)
+                    att.data[color_idx..color_idx + color.len()].copy_from_slice(color);
+                }
+            }
+        }
+    }
+}
+
+/// Store `gl_SampleID` for the next fragment shader invocation. The
+/// rasterizer does not multisample, so per-sample shading runs the fragment
+/// shader once per pixel, for sample 0.
+fn store_sample_index(varyings: &mut [u32], sample: u32) {
+    if let Some(word) = varyings.get_mut(output_layout::SAMPLE_INDEX_OFFSET as usize / 4) {
+        *word = sample;
+    }
+}
+
+/// Store `gl_PointCoord` for the next fragment shader invocation.
+fn store_point_coord(varyings: &mut [u32], coord: (f32, f32)) {
+    let word = output_layout::POINT_COORD_OFFSET as usize / 4;
+    if let Some(words) = varyings.get_mut(word..word + 2) {
+        words[0] = coord.0.to_bits();
+        words[1] = coord.1.to_bits();
+    }
+}
+
+/// Largest point the rasterizer draws (`GL_ALIASED_POINT_SIZE_RANGE`).
+pub const MAX_POINT_SIZE: f32 = 1024.0;
+
+/// Rasterized size of a point. A vertex shader that never writes
+/// `gl_PointSize` leaves it 0, which draws as a single pixel.
+fn point_size(v: &ProcessedVertex) -> f32 {
+    let size = v
+        .varyings
+        .as_slice()
+        .get(4)
+        .map_or(1.0, |bits| f32::from_bits(*bits));
+    if size.is_finite() && size >= 1.0 {
+        size.min(MAX_POINT_SIZE)
+    } else {
+        1.0
+    }
+}
+
+/// Half-open pixel range `(min_x, min_y, max_x, max_y)` of a point of
+/// `size` pixels at window position (`sx`, `sy`): the pixels whose centers
+/// lie in `(s - size / 2, s + size / 2]` on each axis, so a one-pixel point
+/// lands on pixel `floor(s)`.
+pub fn point_pixel_bounds(sx: f32, sy: f32, size: f32) -> (i32, i32, i32, i32) {
+    let half = size * 0.5;
+    (
+        (sx - half - 0.5).floor() as i32 + 1,
+        (sy - half - 0.5).floor() as i32 + 1,
+        (sx + half - 0.5).floor() as i32 + 1,
+        (sy + half - 0.5).floor() as i32 + 1,
+    )
+}
+
+/// `gl_PointCoord` of pixel (`x`, `y`) in a point of `size` pixels at
+/// window position (`sx`, `sy`): 0 to 1 across the sprite, with t = 0 at
+/// the top, as `GL_POINT_SPRITE_COORD_ORIGIN` is `GL_UPPER_LEFT` in ES.
+pub fn point_coord(sx: f32, sy: f32, size: f32, x: i32, y: i32) -> (f32, f32) {
+    let half = size * 0.5;
+    (
+        (x as f32 + 0.5 - (sx - half)) / size,
+        ((sy + half) - (y as f32 + 0.5)) / size,
+    )
+}
+
 fn get_factor(
     factor: u32,
     src: [f32; 4],
@@ -475,12 +1102,155 @@ fn blend_pixel_f32(src: [f32; 4], dst: [f32; 4], state: &BlendState) -> [f32; 4]
     ]
 }
 
+/// Shader invocations run by the rasterizer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ShaderInvocations {
+    pub vertices: u64,
+    pub fragments: u64,
+}
+
+/// What happened to the primitives and fragments of the draws since the
+/// last [`Rasterizer::take_draw_stats`], for explaining draws that leave no
+/// trace.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DrawStats {
+    /// Points and triangles assembled from the vertices
+    pub primitives: u64,
+    /// Primitives entirely outside the clip volume
+    pub clipped: u64,
+    /// Triangles discarded by face culling
+    pub culled: u64,
+    /// Primitives covering no pixel center inside the framebuffer and the
+    /// scissor box
+    pub empty: u64,
+    /// Fragments discarded by the stencil test
+    pub stencil_failed: u64,
+    /// Fragments discarded by the depth test or outside the depth range
+    pub depth_failed: u64,
+    /// Fragments that passed both tests and were shaded
+    pub shaded: u64,
+}
+
+/// What became of a primitive, from worst to best: a triangle split by
+/// clipping counts as the best of its pieces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum PrimitiveFate {
+    Clipped,
+    Empty,
+    Culled,
+    Rasterized,
+}
+
 /// Software triangle rasterizer
-pub struct Rasterizer {}
+///
+/// Besides the invocation and draw counts it owns the scratch buffers draws reuse, so
+/// a draw allocates nothing per vertex or fragment once the buffers have
+/// grown to fit it. Each buffer is taken out of its cell for the duration of
+/// a draw and put back afterwards.
+pub struct Rasterizer {
+    invocations: std::cell::Cell<ShaderInvocations>,
+    draw_stats: std::cell::Cell<DrawStats>,
+    /// Processed vertices of the instance being drawn; only the first
+    /// `count` are live, the rest keep their varying buffers for later draws.
+    vertices: std::cell::Cell<Vec<ProcessedVertex>>,
+    /// Position of the first vertex shaded for each index of an indexed
+    /// instance, so repeated indices reuse its outputs
+    shaded_indices: std::cell::Cell<std::collections::HashMap<u32, usize>>,
+    /// Varyings of the fragment being shaded
+    fragment_varyings: std::cell::Cell<Vec<u32>>,
+    /// Outputs of the fragment being shaded, one per color target
+    fragment_colors: std::cell::Cell<Vec<Vec<u8>>>,
+    /// Which processed vertices the vertex hook culled
+    culled_vertices: std::cell::Cell<Vec<bool>>,
+    /// Positions of the primitive restart indices of an indexed instance
+    restarts: std::cell::Cell<Vec<usize>>,
+    hooks: Option<Box<dyn PipelineHooks>>,
+}
 
 impl Rasterizer {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            invocations: std::cell::Cell::new(ShaderInvocations::default()),
+            draw_stats: std::cell::Cell::new(DrawStats::default()),
+            vertices: std::cell::Cell::new(Vec::new()),
+            shaded_indices: std::cell::Cell::new(std::collections::HashMap::new()),
+            fragment_varyings: std::cell::Cell::new(Vec::new()),
+            fragment_colors: std::cell::Cell::new(Vec::new()),
+            culled_vertices: std::cell::Cell::new(Vec::new()),
+            restarts: std::cell::Cell::new(Vec::new()),
+            hooks: None,
+        }
+    }
+
+    /// Install the [`PipelineHooks`] later draws call, or remove them.
+    pub fn set_hooks(&mut self, hooks: Option<Box<dyn PipelineHooks>>) {
+        self.hooks = hooks;
+    }
+
+    /// Size the per-fragment scratch buffers for a program with
+    /// `color_outputs` fragment outputs, so its first draw doesn't grow them.
+    pub fn reserve_scratch(&self, color_outputs: usize) {
+        let mut varyings = self.fragment_varyings.take();
+        varyings.reserve(output_layout::VARYING_BUFFER_WORDS);
+        self.fragment_varyings.set(varyings);
+
+        let mut colors = self.fragment_colors.take();
+        if colors.len() < color_outputs {
+            colors.resize_with(color_outputs, || Vec::with_capacity(16));
+        }
+        self.fragment_colors.set(colors);
+    }
+
+    /// Drop the scratch buffers and counts of a draw a shader trap aborted,
+    /// which may have left them half-filled.
+    pub fn reset_draw_state(&self) {
+        self.invocations.take();
+        self.draw_stats.take();
+        self.vertices.take();
+        self.shaded_indices.take();
+        self.fragment_varyings.take();
+        self.fragment_colors.take();
+        self.culled_vertices.take();
+        self.restarts.take();
+    }
+
+    /// Shader invocations since the last call, resetting the counts.
+    pub fn take_invocations(&self) -> ShaderInvocations {
+        self.invocations.take()
+    }
+
+    fn count_invocations(&self, vertices: u64, fragments: u64) {
+        let mut counts = self.invocations.get();
+        counts.vertices += vertices;
+        counts.fragments += fragments;
+        self.invocations.set(counts);
+    }
+
+    /// Primitive and fragment counts since the last call, resetting them.
+    pub fn take_draw_stats(&self) -> DrawStats {
+        self.draw_stats.take()
+    }
+
+    fn count_primitive(&self, fate: PrimitiveFate) {
+        let mut stats = self.draw_stats.get();
+        stats.primitives += 1;
+        match fate {
+            PrimitiveFate::Clipped => stats.clipped += 1,
+            PrimitiveFate::Empty => stats.empty += 1,
+            PrimitiveFate::Culled => stats.culled += 1,
+            PrimitiveFate::Rasterized => {}
+        }
+        self.draw_stats.set(stats);
+    }
+
+    fn count_fragment(&self, test: Result<(), Rejection>) {
+        let mut stats = self.draw_stats.get();
+        match test {
+            Ok(()) => stats.shaded += 1,
+            Err(Rejection::Stencil) => stats.stencil_failed += 1,
+            Err(Rejection::Depth) => stats.depth_failed += 1,
+        }
+        self.draw_stats.set(stats);
     }
 }
 
@@ -497,6 +1267,11 @@ pub trait IndexBuffer {
         self.len() == 0
     }
     fn get(&self, i: usize) -> u32;
+    /// The index that ends the current primitive and starts a new one
+    /// instead of naming a vertex, if any
+    fn restart_index(&self) -> Option<u32> {
+        None
+    }
 }
 
 impl IndexBuffer for [u32] {
@@ -563,7 +1338,9 @@ impl Rasterizer {
                         height,
                         layout,
                     );
-                    if color_idx + color.len() <= att.data.len() {
+                    if is_packed_format(att.internal_format) {
+                        write_packed_pixel(att, color_idx, ix, iy, color, state);
+                    } else if color_idx + color.len() <= att.data.len() {
                         if att.internal_format == GL_RGBA8 {
                             let existing = [
                                 att.data[color_idx],
@@ -643,6 +1420,11 @@ impl Rasterizer {
 
     /// Rasterize a triangle with perspective-correct interpolation
     /// This is the core rasterization function extracted from drawing.rs
+    ///
+    /// Triangles crossing the near or far plane are clipped in clip space
+    /// first, so no vertex reaches the perspective divide with `w <= 0`.
+    /// Clipping against x and y is left to the bounding box, which is
+    /// limited to the framebuffer and scissor (an unbounded guard band).
     #[allow(clippy::too_many_arguments)]
     pub fn rasterize_triangle(
         &self,
@@ -654,6 +1436,113 @@ impl Rasterizer {
         state: &RenderState,
         internal_formats: &[u32],
     ) {
+        let fate = if [v0, v1, v2].into_iter().all(inside_depth_planes) {
+            self.rasterize_clipped_triangle(fb, v0, v1, v2, pipeline, state, internal_formats)
+        } else {
+            clip_triangle(v0, v1, v2, pipeline.flat_varyings_mask)
+                .iter()
+                .map(|[a, b, c]| {
+                    self.rasterize_clipped_triangle(fb, a, b, c, pipeline, state, internal_formats)
+                })
+                .max()
+                .unwrap_or(PrimitiveFate::Clipped)
+        };
+        self.count_primitive(fate);
+    }
+
+    /// Rasterize a point sprite: the square `gl_PointSize` pixels wide
+    /// centered on the vertex, covering the pixels whose centers it
+    /// contains. Every fragment gets the vertex's varyings and its own
+    /// `gl_PointCoord`. Points whose center is outside the clip volume are
+    /// discarded whole, as in GL.
+    pub fn rasterize_point(
+        &self,
+        fb: &mut crate::wasm_gl_emu::Framebuffer,
+        v: &ProcessedVertex,
+        pipeline: &RasterPipeline,
+        state: &RenderState,
+        internal_formats: &[u32],
+    ) {
+        let [x, y, z, w] = v.position;
+        let inside = w > 0.0 && [x, y, z].iter().all(|c| c.abs() <= w);
+        if !inside {
+            self.count_primitive(PrimitiveFate::Clipped);
+            return;
+        }
+
+        let (vx, vy, vw, vh) = state.viewport;
+        let (sx, sy) = screen_position(&v.position, vx, vy, vw, vh);
+        let depth = (z / w + 1.0) * 0.5;
+        let size = point_size(v);
+
+        let mut limits = (0, 0, fb.width as i32, fb.height as i32);
+        if state.scissor_enabled {
+            let (x0, y0, w, h) = state.scissor;
+            limits.0 = limits.0.max(x0);
+            limits.1 = limits.1.max(y0);
+            limits.2 = limits.2.min(x0 + w as i32);
+            limits.3 = limits.3.min(y0 + h as i32);
+        }
+        let (min_x, min_y, max_x, max_y) = point_pixel_bounds(sx, sy, size);
+        let (min_x, min_y) = (min_x.max(limits.0), min_y.max(limits.1));
+        let (max_x, max_y) = (max_x.min(limits.2), max_y.min(limits.3));
+        self.count_primitive(if min_x < max_x && min_y < max_y {
+            PrimitiveFate::Rasterized
+        } else {
+            PrimitiveFate::Empty
+        });
+
+        let mut varyings = self.fragment_varyings.take();
+        varyings.clear();
+        varyings.extend_from_slice(&v.varyings);
+        if pipeline.shading_rate == ShadingRate::PerSample {
+            store_sample_index(&mut varyings, 0);
+        }
+        let mut colors = self.fragment_colors.take();
+
+        for py in min_y..max_y {
+            for px in min_x..max_x {
+                store_point_coord(&mut varyings, point_coord(sx, sy, size, px, py));
+                if !self.fragment_hook(px, py, depth, true, &varyings) {
+                    continue;
+                }
+
+                let fb_idx = fb.get_pixel_index(px as u32, py as u32, 0);
+                // Points are always front facing
+                let test = depth_stencil_test(fb, fb_idx, depth, &state.stencil.front, state);
+                self.count_fragment(test);
+                if test.is_err() {
+                    continue;
+                }
+
+                self.execute_fragment_shader(
+                    &varyings,
+                    pipeline,
+                    state,
+                    internal_formats,
+                    &mut colors,
+                );
+                write_fragment(fb, px, py, depth, &colors, state);
+            }
+        }
+
+        self.fragment_varyings.set(varyings);
+        self.fragment_colors.set(colors);
+    }
+
+    /// [`Self::rasterize_triangle`] for a triangle inside the near and far
+    /// planes, returning what became of it.
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_clipped_triangle(
+        &self,
+        fb: &mut crate::wasm_gl_emu::Framebuffer,
+        v0: &ProcessedVertex,
+        v1: &ProcessedVertex,
+        v2: &ProcessedVertex,
+        pipeline: &RasterPipeline,
+        state: &RenderState,
+        internal_formats: &[u32],
+    ) -> PrimitiveFate {
         let (vx, vy, vw, vh) = state.viewport;
 
         // Scissor limit
@@ -673,38 +1562,44 @@ impl Rasterizer {
         let p1 = screen_position(&v1.position, vx, vy, vw, vh);
         let p2 = screen_position(&v2.position, vx, vy, vw, vh);
 
-        // Bounding box
-        let min_x = (p0.0.min(p1.0).min(p2.0).floor() as i32).max(limit_x0);
-        let max_x = (p0.0.max(p1.0).max(p2.0).ceil() as i32).min(limit_x1 - 1);
-        let min_y = (p0.1.min(p1.1).min(p2.1).floor() as i32).max(limit_y0);
-        let max_y = (p0.1.max(p1.1).max(p2.1).ceil() as i32).min(limit_y1 - 1);
+        // Zero-area triangles (after snapping) produce no fragments
+        let Some(edges) = TriangleEdges::new(p0, p1, p2) else {
+            return PrimitiveFate::Empty;
+        };
+
+        // Bounding box of the pixel centers the triangle can cover; empty
+        // for sub-pixel triangles that miss every center
+        let (min_x, min_y, max_x, max_y) = edges.pixel_bounds();
+        let (min_x, min_y) = (min_x.max(limit_x0), min_y.max(limit_y0));
+        let (max_x, max_y) = (max_x.min(limit_x1 - 1), max_y.min(limit_y1 - 1));
 
         if max_x < min_x || max_y < min_y {
-            return;
+            return PrimitiveFate::Empty;
         }
 
         // Calculate triangle area to determine facing
         let tri_area = (p1.0 - p0.0) * (p2.1 - p0.1) - (p1.1 - p0.1) * (p2.0 - p0.0);
 
         // Standard OpenGL: CCW is front by default.
-        // With Y-up in screen space, CCW area sign is positive.
-        let is_ccw = tri_area > 0.0;
-        let is_front = if state.front_face == crate::webgl2_context::types::GL_CCW {
+        // With Y-up in screen space, CCW area sign is positive. The snapped
+        // area decides, so facing agrees with coverage for tiny triangles.
+        let is_ccw = edges.is_ccw();
+        let is_front = if state.front_face == GL_CCW {
             is_ccw
         } else {
             !is_ccw
         };
 
         if state.cull_face_enabled {
-            let should_cull = if state.cull_face_mode == crate::webgl2_context::types::GL_FRONT {
+            let should_cull = if state.cull_face_mode == GL_FRONT {
                 is_front
-            } else if state.cull_face_mode == crate::webgl2_context::types::GL_BACK {
+            } else if state.cull_face_mode == GL_BACK {
                 !is_front
             } else {
-                state.cull_face_mode == crate::webgl2_context::types::GL_FRONT_AND_BACK
+                state.cull_face_mode == GL_FRONT_AND_BACK
             };
             if should_cull {
-                return;
+                return PrimitiveFate::Culled;
             }
         }
 
@@ -713,53 +1608,46 @@ impl Rasterizer {
         let w1_inv = 1.0 / v1.position[3];
         let w2_inv = 1.0 / v2.position[3];
 
-        // Pre-allocate varyings buffer to avoid allocation per pixel
+        // Edge distances for the wireframe view: a barycentric weight times
+        // twice the area over the opposite edge's length, in pixels
+        let edge_length = |a: (f32, f32), b: (f32, f32)| (b.0 - a.0).hypot(b.1 - a.1).max(1e-6);
+        let edge_scale = [
+            tri_area.abs() / edge_length(p1, p2),
+            tri_area.abs() / edge_length(p2, p0),
+            tri_area.abs() / edge_length(p0, p1),
+        ];
+
+        // Reuse the scratch buffers to avoid allocation per pixel
         let varying_count = v0
             .varyings
             .len()
             .min(v1.varyings.len())
             .min(v2.varyings.len());
 
-        let mut interp_varyings = vec![0u32; varying_count];
+        let mut interp_varyings = self.fragment_varyings.take();
+        interp_varyings.clear();
+        interp_varyings.resize(varying_count, 0);
+        let mut colors = self.fragment_colors.take();
+        let face_state = if is_front {
+            &state.stencil.front
+        } else {
+            &state.stencil.back
+        };
 
         for y in min_y..=max_y {
             for x in min_x..=max_x {
-                let px = (x as f32) + 0.5;
-                let py = (y as f32) + 0.5;
-                let (u, v, w) = barycentric((px, py), p0, p1, p2);
+                if let Some((u, v, w)) = edges.coverage(x, y) {
+                    if state.debug_view == DebugView::Wireframe
+                        && (u * edge_scale[0])
+                            .min(v * edge_scale[1])
+                            .min(w * edge_scale[2])
+                            >= 1.0
+                    {
+                        continue;
+                    }
 
-                if u >= 0.0 && v >= 0.0 && w >= 0.0 {
                     let fb_idx = fb.get_pixel_index(x as u32, y as u32, 0);
 
-                    // --- Stencil Test ---
-                    if state.stencil.enabled && !fb.stencil.is_empty() {
-                        let current_stencil = fb.stencil[fb_idx];
-                        let face_state = if is_front {
-                            &state.stencil.front
-                        } else {
-                            &state.stencil.back
-                        };
-
-                        // Compare
-                        if !compare_stencil(
-                            face_state.func,
-                            face_state.ref_val,
-                            current_stencil,
-                            face_state.mask,
-                        ) {
-                            // Fail
-                            let new_val = apply_stencil_op(
-                                face_state.fail,
-                                current_stencil,
-                                face_state.ref_val,
-                            );
-                            fb.stencil[fb_idx] = (current_stencil & !face_state.write_mask as u8)
-                                | (new_val & face_state.write_mask as u8);
-                            continue;
-                        }
-                    }
-
-                    // --- Depth Test ---
                     // Depth is interpolated linearly in screen space for GL
                     let z0 = v0.position[2] / v0.position[3];
                     let z1 = v1.position[2] / v1.position[3];
@@ -767,396 +1655,90 @@ impl Rasterizer {
                     let depth_ndc = u * z0 + v * z1 + w * z2;
                     let depth = (depth_ndc + 1.0) * 0.5;
 
-                    // Check bounds [0,1]
-                    if !(0.0..=1.0).contains(&depth) {
-                        continue;
-                    }
-
-                    // Determine depth comparison result
-                    let depth_pass = if state.depth.enabled && !fb.depth.is_empty() {
-                        compare_depth(state.depth.func, depth, fb.depth[fb_idx])
-                    } else {
-                        // When depth test is disabled or no buffer exists, it always passes
-                        true
-                    };
-
-                    // Handle Depth Fail / Pass for Stencil
-                    if state.stencil.enabled && !fb.stencil.is_empty() {
-                        let current_stencil = fb.stencil[fb_idx];
-                        let face_state = if is_front {
-                            &state.stencil.front
-                        } else {
-                            &state.stencil.back
-                        };
-
-                        if !depth_pass {
-                            // ZFail
-                            let new_val = apply_stencil_op(
-                                face_state.zfail,
-                                current_stencil,
-                                face_state.ref_val,
-                            );
-                            fb.stencil[fb_idx] = (current_stencil & !face_state.write_mask as u8)
-                                | (new_val & face_state.write_mask as u8);
-                            continue; // Discard
-                        } else {
-                            // Pass (ZPass)
-                            let new_val = apply_stencil_op(
-                                face_state.zpass,
-                                current_stencil,
-                                face_state.ref_val,
-                            );
-                            fb.stencil[fb_idx] = (current_stencil & !face_state.write_mask as u8)
-                                | (new_val & face_state.write_mask as u8);
+                    // Varyings are only interpolated for fragments passing the
+                    // tests, unless the fragment hook wants to see them first
+                    let interpolated = self.hooks.is_some();
+                    if interpolated {
+                        interpolate_varyings(
+                            &mut interp_varyings,
+                            [v0, v1, v2],
+                            [u, v, w],
+                            [w0_inv, w1_inv, w2_inv],
+                            pipeline,
+                        );
+                        if !self.fragment_hook(x, y, depth, is_front, &interp_varyings) {
+                            continue;
                         }
-                    } else if !depth_pass {
-                        continue; // Discard if depth failed and no stencil updates needed
                     }
 
-                    // --- Write Depth ---
-                    // Write depth when mask is true AND depth test passed
-                    if state.depth.mask && depth_pass && !fb.depth.is_empty() {
-                        fb.depth[fb_idx] = depth;
+                    let test = depth_stencil_test(fb, fb_idx, depth, face_state, state);
+                    self.count_fragment(test);
+                    if test.is_err() {
+                        continue;
                     }
 
                     // --- Fragment Shader & Color Write ---
-                    // Perspective correct interpolation of varyings
-                    let w_interp_inv = u * w0_inv + v * w1_inv + w * w2_inv;
-                    let w_interp = 1.0 / w_interp_inv;
-
-                    for (k, varying) in interp_varyings.iter_mut().enumerate() {
-                        if (pipeline.flat_varyings_mask & (1u64 << k)) != 0 {
-                            // Flat shading: copy raw bits from provoking vertex (v2)
-                            *varying = v2.varyings[k];
-                        } else {
-                            // Smooth shading: interpolate as floats, then store as bits
-                            let v0_f = f32::from_bits(v0.varyings[k]);
-                            let v1_f = f32::from_bits(v1.varyings[k]);
-                            let v2_f = f32::from_bits(v2.varyings[k]);
-                            let interp_f =
-                                (u * v0_f * w0_inv + v * v1_f * w1_inv + w * v2_f * w2_inv)
-                                    * w_interp;
-                            *varying = interp_f.to_bits();
-                        }
+                    if !interpolated {
+                        interpolate_varyings(
+                            &mut interp_varyings,
+                            [v0, v1, v2],
+                            [u, v, w],
+                            [w0_inv, w1_inv, w2_inv],
+                            pipeline,
+                        );
                     }
 
                     // Execute fragment shader and get color
-                    let colors = self.execute_fragment_shader(
+                    self.execute_fragment_shader(
                         &interp_varyings,
                         pipeline,
                         state,
                         internal_formats,
+                        &mut colors,
                     );
 
-                    let fb_w = fb.width;
-                    let fb_h = fb.height;
-                    let fb_layout = fb.layout;
-
-                    for (i, attachment) in fb.color_attachments.iter_mut().enumerate() {
-                        if let Some(att) = attachment {
-                            let color = &colors[i];
-                            let color_idx =
-                                crate::wasm_gl_emu::Framebuffer::get_pixel_offset_params(
-                                    x as u32,
-                                    y as u32,
-                                    0,
-                                    att.internal_format,
-                                    fb_w,
-                                    fb_h,
-                                    fb_layout,
-                                );
-
-                            if color_idx + color.len() <= att.data.len() {
-                                // For 32-bit formats, write directly with optional blending
-                                if att.internal_format == GL_R32F
-                                    || att.internal_format == GL_RG32F
-                                    || att.internal_format == GL_RGBA32F
-                                    || att.internal_format == GL_R32UI
-                                    || att.internal_format == GL_RG32UI
-                                    || att.internal_format == GL_RGBA32UI
-                                    || att.internal_format == GL_R32I
-                                    || att.internal_format == GL_RG32I
-                                    || att.internal_format == GL_RGBA32I
-                                    || att.internal_format == GL_R8UI
-                                    || att.internal_format == GL_RG8UI
-                                    || att.internal_format == GL_RGBA8UI
-                                    || att.internal_format == GL_R8I
-                                    || att.internal_format == GL_RG8I
-                                    || att.internal_format == GL_RGBA8I
-                                    || att.internal_format == GL_R16UI
-                                    || att.internal_format == GL_RG16UI
-                                    || att.internal_format == GL_RGBA16UI
-                                    || att.internal_format == GL_R16I
-                                    || att.internal_format == GL_RG16I
-                                    || att.internal_format == GL_RGBA16I
-                                {
-                                    // GL_R32F, GL_RG32F, GL_RGBA32F
-                                    if state.blend.enabled
-                                        && (att.internal_format == GL_R32F
-                                            || att.internal_format == GL_RG32F
-                                            || att.internal_format == GL_RGBA32F)
-                                    {
-                                        let existing: [f32; 4] = match att.internal_format {
-                                            GL_R32F => {
-                                                let v = f32::from_ne_bytes(
-                                                    att.data[color_idx..color_idx + 4]
-                                                        .try_into()
-                                                        .unwrap(),
-                                                );
-                                                [v, 0.0, 0.0, 1.0]
-                                            }
-                                            GL_RG32F => {
-                                                let v0 = f32::from_ne_bytes(
-                                                    att.data[color_idx..color_idx + 4]
-                                                        .try_into()
-                                                        .unwrap(),
-                                                );
-                                                let v1 = f32::from_ne_bytes(
-                                                    att.data[color_idx + 4..color_idx + 8]
-                                                        .try_into()
-                                                        .unwrap(),
-                                                );
-                                                [v0, v1, 0.0, 1.0]
-                                            }
-                                            GL_RGBA32F => {
-                                                let v0 = f32::from_ne_bytes(
-                                                    att.data[color_idx..color_idx + 4]
-                                                        .try_into()
-                                                        .unwrap(),
-                                                );
-                                                let v1 = f32::from_ne_bytes(
-                                                    att.data[color_idx + 4..color_idx + 8]
-                                                        .try_into()
-                                                        .unwrap(),
-                                                );
-                                                let v2 = f32::from_ne_bytes(
-                                                    att.data[color_idx + 8..color_idx + 12]
-                                                        .try_into()
-                                                        .unwrap(),
-                                                );
-                                                let v3 = f32::from_ne_bytes(
-                                                    att.data[color_idx + 12..color_idx + 16]
-                                                        .try_into()
-                                                        .unwrap(),
-                                                );
-                                                [v0, v1, v2, v3]
-                                            }
-                                            _ => [0.0, 0.0, 0.0, 1.0],
-                                        };
-
-                                        let src_color: [f32; 4] = match att.internal_format {
-                                            GL_R32F => [
-                                                f32::from_ne_bytes(color[0..4].try_into().unwrap()),
-                                                0.0,
-                                                0.0,
-                                                1.0,
-                                            ],
-                                            GL_RG32F => [
-                                                f32::from_ne_bytes(color[0..4].try_into().unwrap()),
-                                                f32::from_ne_bytes(color[4..8].try_into().unwrap()),
-                                                0.0,
-                                                1.0,
-                                            ],
-                                            GL_RGBA32F => [
-                                                f32::from_ne_bytes(color[0..4].try_into().unwrap()),
-                                                f32::from_ne_bytes(color[4..8].try_into().unwrap()),
-                                                f32::from_ne_bytes(
-                                                    color[8..12].try_into().unwrap(),
-                                                ),
-                                                f32::from_ne_bytes(
-                                                    color[12..16].try_into().unwrap(),
-                                                ),
-                                            ],
-                                            _ => [0.0, 0.0, 0.0, 1.0],
-                                        };
-
-                                        let blended =
-                                            blend_pixel_f32(src_color, existing, &state.blend);
-
-                                        // Write back blended
-                                        match att.internal_format {
-                                            GL_R32F => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx..color_idx + 4]
-                                                        .copy_from_slice(&blended[0].to_ne_bytes());
-                                                }
-                                            }
-                                            GL_RG32F => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx..color_idx + 4]
-                                                        .copy_from_slice(&blended[0].to_ne_bytes());
-                                                }
-                                                if state.color_mask.g {
-                                                    att.data[color_idx + 4..color_idx + 8]
-                                                        .copy_from_slice(&blended[1].to_ne_bytes());
-                                                }
-                                            }
-                                            GL_RGBA32F => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx..color_idx + 4]
-                                                        .copy_from_slice(&blended[0].to_ne_bytes());
-                                                }
-                                                if state.color_mask.g {
-                                                    att.data[color_idx + 4..color_idx + 8]
-                                                        .copy_from_slice(&blended[1].to_ne_bytes());
-                                                }
-                                                if state.color_mask.b {
-                                                    att.data[color_idx + 8..color_idx + 12]
-                                                        .copy_from_slice(&blended[2].to_ne_bytes());
-                                                }
-                                                if state.color_mask.a {
-                                                    att.data[color_idx + 12..color_idx + 16]
-                                                        .copy_from_slice(&blended[3].to_ne_bytes());
-                                                }
-                                            }
-                                            _ => {}
-                                        }
-                                    } else {
-                                        // Clamp to mask if not blending
-                                        match att.internal_format {
-                                            GL_R32F | GL_R32UI | GL_R32I => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx..color_idx + 4]
-                                                        .copy_from_slice(&color[0..4]);
-                                                }
-                                            }
-                                            GL_RG32F | GL_RG32UI | GL_RG32I => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx..color_idx + 4]
-                                                        .copy_from_slice(&color[0..4]);
-                                                }
-                                                if state.color_mask.g {
-                                                    att.data[color_idx + 4..color_idx + 8]
-                                                        .copy_from_slice(&color[4..8]);
-                                                }
-                                            }
-                                            GL_RGBA32F | GL_RGBA32UI | GL_RGBA32I => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx..color_idx + 4]
-                                                        .copy_from_slice(&color[0..4]);
-                                                }
-                                                if state.color_mask.g {
-                                                    att.data[color_idx + 4..color_idx + 8]
-                                                        .copy_from_slice(&color[4..8]);
-                                                }
-                                                if state.color_mask.b {
-                                                    att.data[color_idx + 8..color_idx + 12]
-                                                        .copy_from_slice(&color[8..12]);
-                                                }
-                                                if state.color_mask.a {
-                                                    att.data[color_idx + 12..color_idx + 16]
-                                                        .copy_from_slice(&color[12..16]);
-                                                }
-                                            }
-                                            GL_R16UI | GL_R16I => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx..color_idx + 2]
-                                                        .copy_from_slice(&color[0..2]);
-                                                }
-                                            }
-                                            GL_RG16UI | GL_RG16I => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx..color_idx + 2]
-                                                        .copy_from_slice(&color[0..2]);
-                                                }
-                                                if state.color_mask.g {
-                                                    att.data[color_idx + 2..color_idx + 4]
-                                                        .copy_from_slice(&color[2..4]);
-                                                }
-                                            }
-                                            GL_RGBA16UI | GL_RGBA16I => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx..color_idx + 2]
-                                                        .copy_from_slice(&color[0..2]);
-                                                }
-                                                if state.color_mask.g {
-                                                    att.data[color_idx + 2..color_idx + 4]
-                                                        .copy_from_slice(&color[2..4]);
-                                                }
-                                                if state.color_mask.b {
-                                                    att.data[color_idx + 4..color_idx + 6]
-                                                        .copy_from_slice(&color[4..6]);
-                                                }
-                                                if state.color_mask.a {
-                                                    att.data[color_idx + 6..color_idx + 8]
-                                                        .copy_from_slice(&color[6..8]);
-                                                }
-                                            }
-                                            GL_R8UI | GL_R8I => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx] = color[0];
-                                                }
-                                            }
-                                            GL_RG8UI | GL_RG8I => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx] = color[0];
-                                                }
-                                                if state.color_mask.g {
-                                                    att.data[color_idx + 1] = color[1];
-                                                }
-                                            }
-                                            GL_RGBA8UI | GL_RGBA8I => {
-                                                if state.color_mask.r {
-                                                    att.data[color_idx] = color[0];
-                                                }
-                                                if state.color_mask.g {
-                                                    att.data[color_idx + 1] = color[1];
-                                                }
-                                                if state.color_mask.b {
-                                                    att.data[color_idx + 2] = color[2];
-                                                }
-                                                if state.color_mask.a {
-                                                    att.data[color_idx + 3] = color[3];
-                                                }
-                                            }
-                                            _ => {}
-                                        }
-                                    }
-                                } else if att.internal_format == GL_RGBA8 {
-                                    // GL_RGBA8: Use quantized blending
-                                    let existing = [
-                                        att.data[color_idx],
-                                        att.data[color_idx + 1],
-                                        att.data[color_idx + 2],
-                                        att.data[color_idx + 3],
-                                    ];
-                                    let color_arr = [color[0], color[1], color[2], color[3]];
-                                    let blended = blend_pixel(color_arr, existing, &state.blend);
-
-                                    // Color Mask
-                                    if state.color_mask.r {
-                                        att.data[color_idx] = blended[0];
-                                    }
-                                    if state.color_mask.g {
-                                        att.data[color_idx + 1] = blended[1];
-                                    }
-                                    if state.color_mask.b {
-                                        att.data[color_idx + 2] = blended[2];
-                                    }
-                                    if state.color_mask.a {
-                                        att.data[color_idx + 3] = blended[3];
-                                    }
-                                } else {
-                                    // Float formats: Direct write (no blending yet)
-                                    att.data[color_idx..color_idx + color.len()]
-                                        .copy_from_slice(color);
-                                }
-                            }
-                        }
-                    }
+                    write_fragment(fb, x, y, depth, &colors, state);
                 }
             }
         }
+
+        self.fragment_varyings.set(interp_varyings);
+        self.fragment_colors.set(colors);
+        PrimitiveFate::Rasterized
+    }
+
+    /// Whether the fragment hook, if any, keeps a fragment
+    fn fragment_hook(
+        &self,
+        x: i32,
+        y: i32,
+        depth: f32,
+        front_facing: bool,
+        varyings: &[u32],
+    ) -> bool {
+        self.hooks.as_ref().is_none_or(|hooks| {
+            hooks.fragment(&FragmentStage {
+                x,
+                y,
+                depth,
+                front_facing,
+                varyings,
+            })
+        })
     }
 
-    /// Execute fragment shader and return color (format-aware)
+    /// Execute fragment shader and store the color of each target
+    /// (format-aware) in `results`, reusing its buffers
     fn execute_fragment_shader(
         &self,
         varyings: &[u32],
         pipeline: &RasterPipeline,
         _state: &RenderState,
         target_formats: &[u32],
-    ) -> Vec<Vec<u8>> {
+        results: &mut Vec<Vec<u8>>,
+    ) {
+        self.count_invocations(0, 1);
+
         // Turbo FS: Direct call with register arguments (Tier 2) and results (Tier 3)
         let fs_table_idx = pipeline.fs_table_idx.unwrap_or(0);
 
@@ -1165,23 +1747,27 @@ impl Rasterizer {
 
         if cfg!(target_arch = "wasm32") && fs_table_idx > 0 {
             unsafe {
-                crate::ACTIVE_UNIFORM_PTR = pipeline.memory.uniform_ptr;
+                crate::ACTIVE_UNIFORM_PTR = pipeline.memory.uniform_ptr as u32;
                 crate::ACTIVE_VARYING_PTR = varying_in_ptr as u32;
                 crate::ACTIVE_PRIVATE_PTR = private_ptr as u32;
-                crate::ACTIVE_TEXTURE_PTR = pipeline.memory.texture_ptr;
+                crate::ACTIVE_TEXTURE_PTR = pipeline.memory.texture_ptr as u32;
             }
             let fs_func: FsEntryFn = unsafe { core::mem::transmute(fs_table_idx as usize) };
+            RUNNING_STAGE.store(GL_FRAGMENT_SHADER, Ordering::Relaxed);
             fs_func(varying_in_ptr, private_ptr);
+            RUNNING_STAGE.store(0, Ordering::Relaxed);
         }
 
-        let mut results = Vec::with_capacity(target_formats.len());
-
-        for (i, &format) in target_formats.iter().enumerate() {
+        // Entries past the targets are stale but keep their buffers
+        if results.len() < target_formats.len() {
+            results.resize_with(target_formats.len(), Vec::new);
+        }
+        for ((i, &format), result) in target_formats.iter().enumerate().zip(results.iter_mut()) {
             // Read color from private memory (each location is 16 bytes)
             let mut color_bytes = [0u8; 16];
             unsafe {
                 std::ptr::copy_nonoverlapping(
-                    (pipeline.memory.private_ptr + (i as u32 * 16)) as *const u8,
+                    (pipeline.memory.private_ptr + i * 16) as *const u8,
                     color_bytes.as_mut_ptr(),
                     16,
                 );
@@ -1189,63 +1775,63 @@ impl Rasterizer {
             let c: [f32; 4] = unsafe { std::mem::transmute(color_bytes) };
 
             // Format-aware output
-            let output = match format {
+            let mut packed = [0u8; 16];
+            let output: &[u8] = match format {
                 GL_R32F | GL_R32UI | GL_R32I => {
                     // 1 channel × 4 bytes
-                    color_bytes[0..4].to_vec()
+                    &color_bytes[0..4]
                 }
                 GL_RG32F | GL_RG32UI | GL_RG32I => {
                     // 2 channels × 4 bytes
-                    color_bytes[0..8].to_vec()
+                    &color_bytes[0..8]
                 }
                 GL_RGBA32F | GL_RGBA32UI | GL_RGBA32I => {
                     // 4 channels × 4 bytes
-                    color_bytes[0..16].to_vec()
+                    &color_bytes[0..16]
                 }
-                GL_R16UI | GL_R16I => color_bytes[0..2].to_vec(),
+                GL_R16UI | GL_R16I => &color_bytes[0..2],
                 GL_RG16UI | GL_RG16I => {
-                    let mut res = Vec::with_capacity(4);
-                    res.extend_from_slice(&color_bytes[0..2]);
-                    res.extend_from_slice(&color_bytes[4..6]);
-                    res
+                    packed[0..2].copy_from_slice(&color_bytes[0..2]);
+                    packed[2..4].copy_from_slice(&color_bytes[4..6]);
+                    &packed[0..4]
                 }
                 GL_RGBA16UI | GL_RGBA16I => {
-                    let mut res = Vec::with_capacity(8);
-                    res.extend_from_slice(&color_bytes[0..2]);
-                    res.extend_from_slice(&color_bytes[4..6]);
-                    res.extend_from_slice(&color_bytes[8..10]);
-                    res.extend_from_slice(&color_bytes[12..14]);
-                    res
+                    packed[0..2].copy_from_slice(&color_bytes[0..2]);
+                    packed[2..4].copy_from_slice(&color_bytes[4..6]);
+                    packed[4..6].copy_from_slice(&color_bytes[8..10]);
+                    packed[6..8].copy_from_slice(&color_bytes[12..14]);
+                    &packed[0..8]
                 }
-                GL_R8UI | GL_R8I => {
-                    vec![color_bytes[0]]
+                GL_R8UI | GL_R8I => &color_bytes[0..1],
+                GL_RGB565 | GL_RGBA4 | GL_RGB5_A1 => {
+                    // Packed on write, where the pixel position for
+                    // dithering is known
+                    &color_bytes
                 }
                 GL_RG8UI | GL_RG8I => {
-                    vec![color_bytes[0], color_bytes[4]]
+                    packed[0] = color_bytes[0];
+                    packed[1] = color_bytes[4];
+                    &packed[0..2]
                 }
                 GL_RGBA8UI | GL_RGBA8I => {
-                    vec![
-                        color_bytes[0],
-                        color_bytes[4],
-                        color_bytes[8],
-                        color_bytes[12],
-                    ]
+                    packed[0] = color_bytes[0];
+                    packed[1] = color_bytes[4];
+                    packed[2] = color_bytes[8];
+                    packed[3] = color_bytes[12];
+                    &packed[0..4]
                 }
                 _ => {
                     // GL_RGBA8: Quantize to u8.
                     // Note: We use clamp/scale for Unorm formats only.
-                    vec![
-                        (c[0].clamp(0.0, 1.0) * 255.0) as u8,
-                        (c[1].clamp(0.0, 1.0) * 255.0) as u8,
-                        (c[2].clamp(0.0, 1.0) * 255.0) as u8,
-                        (c[3].clamp(0.0, 1.0) * 255.0) as u8,
-                    ]
+                    for (byte, channel) in packed.iter_mut().zip(c) {
+                        *byte = (channel.clamp(0.0, 1.0) * 255.0) as u8;
+                    }
+                    &packed[0..4]
                 }
             };
-            results.push(output);
+            result.clear();
+            result.extend_from_slice(output);
         }
-
-        results
     }
 
     /// Draw primitives
@@ -1259,24 +1845,24 @@ impl Rasterizer {
         // (turboGlobals). We synchronize them once per draw via a single import
         // call to avoid per-vertex JS overhead.
         unsafe {
-            crate::ACTIVE_ATTR_PTR = config.state.memory.attr_ptr;
-            crate::ACTIVE_UNIFORM_PTR = config.state.memory.uniform_ptr;
-            crate::ACTIVE_VARYING_PTR = config.state.memory.varying_ptr;
-            crate::ACTIVE_PRIVATE_PTR = config.state.memory.private_ptr;
-            crate::ACTIVE_TEXTURE_PTR = config.state.memory.texture_ptr;
-            crate::ACTIVE_FRAME_SP = config.state.memory.frame_sp;
+            crate::ACTIVE_ATTR_PTR = config.state.memory.attr_ptr as u32;
+            crate::ACTIVE_UNIFORM_PTR = config.state.memory.uniform_ptr as u32;
+            crate::ACTIVE_VARYING_PTR = config.state.memory.varying_ptr as u32;
+            crate::ACTIVE_PRIVATE_PTR = config.state.memory.private_ptr as u32;
+            crate::ACTIVE_TEXTURE_PTR = config.state.memory.texture_ptr as u32;
+            crate::ACTIVE_FRAME_SP = config.state.memory.frame_sp as u32;
         }
 
         #[cfg(target_arch = "wasm32")]
         unsafe {
             // Call into the host to sync the WebAssembly.Global objects
             wasm_sync_turbo_globals(
-                config.state.memory.attr_ptr,
-                config.state.memory.uniform_ptr,
-                config.state.memory.varying_ptr,
-                config.state.memory.private_ptr,
-                config.state.memory.texture_ptr,
-                config.state.memory.frame_sp,
+                config.state.memory.attr_ptr as u32,
+                config.state.memory.uniform_ptr as u32,
+                config.state.memory.varying_ptr as u32,
+                config.state.memory.private_ptr as u32,
+                config.state.memory.texture_ptr as u32,
+                config.state.memory.frame_sp as u32,
             );
         }
 
@@ -1286,7 +1872,7 @@ impl Rasterizer {
             // Copy uniforms
             let copy_len = config.state.uniform_data.len().min(16384); // TODO: magic number is not appropriate
             let uniform_ptr = config.state.memory.uniform_ptr;
-            if config.state.uniform_data.as_ptr() as u32 != uniform_ptr {
+            if config.state.uniform_data.as_ptr() as usize != uniform_ptr {
                 std::ptr::copy_nonoverlapping(
                     config.state.uniform_data.as_ptr(),
                     uniform_ptr as *mut u8,
@@ -1301,7 +1887,7 @@ impl Rasterizer {
                 if current_val > 0 && current_val < 16384 {
                     // TODO: magic number is not appropriate
                     // It's a relative offset
-                    *ctx_block.add(i) = current_val + uniform_ptr;
+                    *ctx_block.add(i) = current_val + uniform_ptr as u32;
                 }
             }
 
@@ -1311,23 +1897,49 @@ impl Rasterizer {
             }
         }
 
+        let mut vertices = self.vertices.take();
+        let mut shaded_indices = self.shaded_indices.take();
+        let mut culled = self.culled_vertices.take();
+        let mut restarts = self.restarts.take();
+        let restart_index = config.indices.and_then(|idxs| idxs.restart_index());
         for instance_id in 0..config.instance_count {
             let actual_instance_id = config.first_instance + instance_id;
-            let mut vertices = Vec::with_capacity(config.vertex_count);
 
-            // 1. Run Vertex Shader for all vertices
+            // 1. Run Vertex Shader for all vertices, once per distinct index
             let count = if let Some(idxs) = config.indices {
                 idxs.len()
             } else {
                 config.vertex_count
             };
+            let vertices = reset_vertices(&mut vertices, count);
+            shaded_indices.clear();
+            culled.clear();
+            culled.resize(count, false);
+            restarts.clear();
+            let mut invocations = 0;
 
             for i in 0..count {
                 let vertex_id = if let Some(idxs) = config.indices {
-                    idxs.get(i)
+                    let index = idxs.get(i);
+                    if Some(index) == restart_index {
+                        // Not a vertex: it splits the primitives around it
+                        restarts.push(i);
+                        continue;
+                    }
+                    if let Some(&shaded) = shaded_indices.get(&index) {
+                        let (done, rest) = vertices.split_at_mut(i);
+                        rest[0].position = done[shaded].position;
+                        rest[0].varyings.copy_from_slice(&done[shaded].varyings);
+                        culled[i] = culled[shaded];
+                        continue;
+                    }
+                    shaded_indices.insert(index, i);
+                    index
                 } else {
                     (config.first_vertex + i) as u32
                 };
+                invocations += 1;
+                let vertex = &mut vertices[i];
 
                 // Fetch attributes directly into the shader memory
                 let attr_ptr = config.state.memory.attr_ptr;
@@ -1343,57 +1955,53 @@ impl Rasterizer {
                 // Turbo VS: Direct call with register arguments (Tier 2)
                 let vs_table_idx = config.pipeline.vs_table_idx.unwrap_or(0);
 
-                // Pre-allocate varying buffer for this vertex (256 bytes = 64 u32s)
-                let mut varyings_u32 = vec![0u32; 64];
-                let varying_out_ptr = varyings_u32.as_mut_ptr() as i32;
+                // The vertex's reused varying buffer, zeroed
+                let varying_out_ptr = vertex.varyings.as_mut_ptr() as i32;
 
                 if cfg!(target_arch = "wasm32") && vs_table_idx > 0 {
                     unsafe {
                         crate::ACTIVE_VARYING_PTR = varying_out_ptr as u32;
                     }
                     let vs_func: VsEntryFn = unsafe { core::mem::transmute(vs_table_idx as usize) };
+                    RUNNING_STAGE.store(GL_VERTEX_SHADER, Ordering::Relaxed);
                     vs_func(vertex_id as i32, actual_instance_id as i32, varying_out_ptr);
+                    RUNNING_STAGE.store(0, Ordering::Relaxed);
                 }
 
                 // gl_Position is expected at the start of varyings (Varying location 0)
-                let pos = [
-                    f32::from_bits(varyings_u32[0]),
-                    f32::from_bits(varyings_u32[1]),
-                    f32::from_bits(varyings_u32[2]),
-                    f32::from_bits(varyings_u32[3]),
+                vertex.position = [
+                    f32::from_bits(vertex.varyings[0]),
+                    f32::from_bits(vertex.varyings[1]),
+                    f32::from_bits(vertex.varyings[2]),
+                    f32::from_bits(vertex.varyings[3]),
                 ];
-
-                vertices.push(ProcessedVertex {
-                    position: pos,
-                    varyings: varyings_u32,
-                });
+                if let Some(hooks) = &self.hooks {
+                    culled[i] = !hooks.vertex(&VertexStage {
+                        vertex_id,
+                        instance_id: actual_instance_id as u32,
+                        position: vertex.position,
+                        varyings: &vertex.varyings,
+                    });
+                }
             }
+            self.count_invocations(invocations, 0);
 
             let mut fb_attachments = Vec::with_capacity(config.color_targets.len());
-            let fb_layout = if config.color_targets.is_empty() {
-                StorageLayout::Linear
-            } else {
-                match &config.color_targets[0] {
-                    ColorTarget::Handle(handle) => {
-                        if handle.is_valid() {
-                            kernel.get_buffer(*handle).expect("buffer lost").layout
-                        } else {
-                            StorageLayout::Linear
-                        }
-                    }
-                    ColorTarget::Raw(_) => StorageLayout::Linear,
-                };
-                // Re-match because of borrow checker (we used kernel immutably above)
-                match &config.color_targets[0] {
-                    ColorTarget::Handle(handle) => {
-                        if handle.is_valid() {
-                            kernel.get_buffer(*handle).expect("buffer lost").layout
-                        } else {
-                            StorageLayout::Linear
-                        }
-                    }
-                    ColorTarget::Raw(_) => StorageLayout::Linear,
+            // Depth and stencil are addressed like the first color target, or
+            // like the depth/stencil buffers when there is no color to draw
+            let ds_handles = match &config.depth_stencil_target {
+                DepthStencilTarget::Handles { depth, stencil } => [*depth, *stencil],
+                DepthStencilTarget::Raw { .. } => [GpuHandle::invalid(); 2],
+            };
+            let fb_layout = match config.color_targets.first() {
+                Some(ColorTarget::Raw(_)) => StorageLayout::Linear,
+                Some(ColorTarget::Handle(handle)) if handle.is_valid() => {
+                    kernel.get_buffer(*handle).expect("buffer lost").layout
                 }
+                _ => ds_handles
+                    .iter()
+                    .find_map(|handle| kernel.get_buffer(*handle))
+                    .map_or(StorageLayout::Linear, |buffer| buffer.layout),
             };
 
             // Use unsafe to circumvent borrow checker for multi-target buffer access.
@@ -1411,7 +2019,7 @@ impl Rasterizer {
                                     .expect("color buffer lost")
                             };
                             Some(ColorAttachment {
-                                data: color_buffer.data.as_mut_slice(),
+                                data: &mut color_buffer.data,
                                 internal_format: config.internal_formats[i],
                             })
                         }
@@ -1425,29 +2033,18 @@ impl Rasterizer {
             }
 
             let (target_depth, target_stencil) = match &mut config.depth_stencil_target {
-                DepthStencilTarget::Handle(handle) => {
-                    let ds_buffer = kernel.get_buffer_mut(*handle).expect("ds buffer lost");
-                    let width = config.width as usize;
-                    let height = config.height as usize;
-                    let data = &mut ds_buffer.data;
-
-                    if data.len() >= width * height * 4 {
-                        let d_slice = unsafe {
-                            std::slice::from_raw_parts_mut(
-                                data.as_mut_ptr() as *mut f32,
-                                data.len() / 4, // TODO: suspicious magic number
-                            )
-                        };
-                        let s_slice = if data.len() >= width * height * 5 {
-                            // TODO: suspicious magic numbers
-                            &mut data[width * height * 4..width * height * 5] // TODO: suspicious magic numbers
-                        } else {
-                            &mut []
-                        };
-                        (d_slice, s_slice)
-                    } else {
-                        (&mut [] as &mut [f32], &mut [] as &mut [u8])
-                    }
+                DepthStencilTarget::Handles { depth, stencil } => {
+                    // As for color targets: the two planes are disjoint even
+                    // when both come from one buffer
+                    let d_slice = match unsafe { (*kernel_raw).get_buffer_mut(*depth) } {
+                        Some(buffer) => buffer.depth_stencil_planes().0,
+                        None => &mut [],
+                    };
+                    let s_slice = match unsafe { (*kernel_raw).get_buffer_mut(*stencil) } {
+                        Some(buffer) => buffer.depth_stencil_planes().1,
+                        None => &mut [],
+                    };
+                    (d_slice, s_slice)
                 }
                 DepthStencilTarget::Raw { depth, stencil } => {
                     (*depth as &mut [f32], *stencil as &mut [u8])
@@ -1463,21 +2060,33 @@ impl Rasterizer {
                 layout: fb_layout,
             };
 
-            self.rasterize_all(
-                &mut fb,
-                &vertices,
-                config.mode,
-                config.pipeline,
-                config.state,
-                &config.internal_formats,
-            );
+            // Each run of vertices between restarts assembles on its own
+            let mut start = 0;
+            for end in restarts.iter().copied().chain([count]) {
+                self.rasterize_all(
+                    &mut fb,
+                    &vertices[start..end],
+                    &culled[start..end],
+                    config.mode,
+                    config.pipeline,
+                    config.state,
+                    &config.internal_formats,
+                );
+                start = end + 1;
+            }
         }
+        self.vertices.set(vertices);
+        self.shaded_indices.set(shaded_indices);
+        self.culled_vertices.set(culled);
+        self.restarts.set(restarts);
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn rasterize_all(
         &self,
         fb: &mut crate::wasm_gl_emu::Framebuffer,
         vertices: &[ProcessedVertex],
+        culled: &[bool],
         mode: u32,
         pipeline: &RasterPipeline,
         state: &RenderState,
@@ -1485,17 +2094,8 @@ impl Rasterizer {
     ) {
         if mode == GL_POINTS {
             // GL_POINTS
-            for v in vertices {
-                let (_vx, _vy, _vw, _vh) = state.viewport;
-                let screen_x =
-                    _vx as f32 + (v.position[0] / v.position[3] + 1.0) * 0.5 * _vw as f32;
-                let screen_y =
-                    _vy as f32 + (v.position[1] / v.position[3] + 1.0) * 0.5 * _vh as f32;
-
-                // Run FS
-                let colors =
-                    self.execute_fragment_shader(&v.varyings, pipeline, state, internal_formats);
-                self.draw_point(fb, screen_x, screen_y, &colors, state);
+            for (v, _) in vertices.iter().zip(culled).filter(|(_, &culled)| !culled) {
+                self.rasterize_point(fb, v, pipeline, state, internal_formats);
             }
         } else if mode == GL_TRIANGLES {
             // GL_TRIANGLES
@@ -1503,6 +2103,9 @@ impl Rasterizer {
                 if i + 2 >= vertices.len() {
                     break;
                 }
+                if culled[i..i + 3].contains(&true) {
+                    continue;
+                }
                 let v0 = &vertices[i];
                 let v1 = &vertices[i + 1];
                 let v2 = &vertices[i + 2];
@@ -1512,6 +2115,9 @@ impl Rasterizer {
         } else if mode == GL_TRIANGLE_STRIP {
             // GL_TRIANGLE_STRIP
             for i in 0..vertices.len().saturating_sub(2) {
+                if culled[i..i + 3].contains(&true) {
+                    continue;
+                }
                 let (v0, v1, v2) = if i % 2 == 0 {
                     (&vertices[i], &vertices[i + 1], &vertices[i + 2])
                 } else {
@@ -1523,6 +2129,235 @@ impl Rasterizer {
     }
 }
 
+/// Interpolate the varyings of triangle `v` at barycentrics `b` into `out`:
+/// perspective-correct with the vertices' `1/w` in `w_inv`, except for flat
+/// varyings, which take the provoking vertex's bits, and noperspective ones.
+fn interpolate_varyings(
+    out: &mut [u32],
+    [v0, v1, v2]: [&ProcessedVertex; 3],
+    [u, v, w]: [f32; 3],
+    [w0_inv, w1_inv, w2_inv]: [f32; 3],
+    pipeline: &RasterPipeline,
+) {
+    let w_interp = 1.0 / (u * w0_inv + v * w1_inv + w * w2_inv);
+    for (k, varying) in out.iter_mut().enumerate() {
+        if (pipeline.flat_varyings_mask & (1u128 << k)) != 0 {
+            // Flat shading: copy raw bits from provoking vertex (v2)
+            *varying = v2.varyings[k];
+        } else if (pipeline.noperspective_varyings_mask & (1u128 << k)) != 0 {
+            // noperspective: plain screen-space barycentrics
+            let interp_f = u * f32::from_bits(v0.varyings[k])
+                + v * f32::from_bits(v1.varyings[k])
+                + w * f32::from_bits(v2.varyings[k]);
+            *varying = interp_f.to_bits();
+        } else {
+            // Smooth shading: interpolate as floats, then store as bits
+            let v0_f = f32::from_bits(v0.varyings[k]);
+            let v1_f = f32::from_bits(v1.varyings[k]);
+            let v2_f = f32::from_bits(v2.varyings[k]);
+            let interp_f = (u * v0_f * w0_inv + v * v1_f * w1_inv + w * v2_f * w2_inv) * w_interp;
+            *varying = interp_f.to_bits();
+        }
+    }
+    if pipeline.shading_rate == ShadingRate::PerSample {
+        store_sample_index(out, 0);
+    }
+}
+
+/// The first `count` vertices of the pool `vertices`, with zeroed varying
+/// buffers. The pool only grows, so the buffers of earlier draws are reused.
+fn reset_vertices(vertices: &mut Vec<ProcessedVertex>, count: usize) -> &mut [ProcessedVertex] {
+    let words = output_layout::VARYING_BUFFER_WORDS;
+    if vertices.len() < count {
+        vertices.resize_with(count, || ProcessedVertex {
+            position: [0.0; 4],
+            varyings: vec![0; words],
+        });
+    }
+    let live = &mut vertices[..count];
+    for vertex in live.iter_mut() {
+        vertex.varyings.fill(0);
+    }
+    live
+}
+
+/// Signed distances of a clip-space position to the near (`z = -w`) and far
+/// (`z = w`) planes; both are non-negative inside the view volume.
+fn depth_plane_distances(pos: &[f32; 4]) -> [f32; 2] {
+    [pos[3] + pos[2], pos[3] - pos[2]]
+}
+
+fn inside_depth_planes(v: &ProcessedVertex) -> bool {
+    depth_plane_distances(&v.position).iter().all(|&d| d >= 0.0)
+}
+
+/// Clip a triangle against the near and far planes (Sutherland-Hodgman)
+/// and fan the resulting polygon back into triangles, keeping the winding.
+///
+/// New vertices interpolate position and varyings linearly in clip space.
+/// Flat varyings come from the provoking vertex `v2` on every output
+/// vertex, as the fan changes which vertex provokes each triangle.
+pub fn clip_triangle(
+    v0: &ProcessedVertex,
+    v1: &ProcessedVertex,
+    v2: &ProcessedVertex,
+    flat_varyings_mask: u128,
+) -> Vec<[ProcessedVertex; 3]> {
+    if [v0, v1, v2]
+        .iter()
+        .any(|v| v.position.iter().any(|c| !c.is_finite()))
+    {
+        return Vec::new();
+    }
+
+    let mut polygon = vec![v0.clone(), v1.clone(), v2.clone()];
+    for plane in 0..2 {
+        let distance = |v: &ProcessedVertex| depth_plane_distances(&v.position)[plane];
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (i, a) in polygon.iter().enumerate() {
+            let b = &polygon[(i + 1) % polygon.len()];
+            let (da, db) = (distance(a), distance(b));
+            if da >= 0.0 {
+                clipped.push(a.clone());
+            }
+            if (da >= 0.0) != (db >= 0.0) {
+                clipped.push(lerp_vertex(a, b, da / (da - db)));
+            }
+        }
+        polygon = clipped;
+    }
+
+    for v in polygon.iter_mut() {
+        for (k, word) in v.varyings.iter_mut().enumerate() {
+            if k < 128 && flat_varyings_mask & (1u128 << k) != 0 {
+                *word = v2.varyings.as_slice().get(k).copied().unwrap_or(*word);
+            }
+        }
+    }
+
+    (1..polygon.len().saturating_sub(1))
+        .map(|i| {
+            [
+                polygon[0].clone(),
+                polygon[i].clone(),
+                polygon[i + 1].clone(),
+            ]
+        })
+        .collect()
+}
+
+/// Vertex at `t` along the edge from `a` to `b`; varyings are treated as
+/// floats (flat ones are fixed up by the caller).
+fn lerp_vertex(a: &ProcessedVertex, b: &ProcessedVertex, t: f32) -> ProcessedVertex {
+    let lerp = |x: f32, y: f32| x + (y - x) * t;
+    ProcessedVertex {
+        position: std::array::from_fn(|i| lerp(a.position[i], b.position[i])),
+        varyings: a
+            .varyings
+            .iter()
+            .zip(&b.varyings)
+            .map(|(&x, &y)| lerp(f32::from_bits(x), f32::from_bits(y)).to_bits())
+            .collect(),
+    }
+}
+
+/// Fractional bits of the fixed-point window coordinates used for coverage.
+pub const SUBPIXEL_BITS: u32 = 8;
+
+/// Snapped coordinates are clamped to this many pixels from the origin,
+/// keeping edge function products well inside `i64`.
+const GUARD_BAND_PIXELS: f32 = (1 << 22) as f32;
+
+/// Coverage test of a triangle with fixed-point edge functions.
+///
+/// Vertices are snapped to 1/256 pixel, so coverage doesn't depend on float
+/// rounding, and samples exactly on an edge follow the top-left rule: they
+/// belong to the triangle only if the edge is a left edge or a horizontal
+/// top edge. Triangles sharing an edge therefore shade each pixel along it
+/// exactly once.
+pub struct TriangleEdges {
+    /// Snapped vertices
+    v: [(i64, i64); 3],
+    /// Twice the signed area, in squared sub-pixels
+    area: i64,
+    /// Subtracted from edge `i` (opposite vertex `i`) to exclude samples on
+    /// edges the triangle doesn't own
+    bias: [i64; 3],
+}
+
+impl TriangleEdges {
+    /// Set up the edges of a triangle in window coordinates; `None` if it
+    /// has no area after snapping.
+    pub fn new(p0: (f32, f32), p1: (f32, f32), p2: (f32, f32)) -> Option<Self> {
+        let snap = |c: f32| {
+            (c.clamp(-GUARD_BAND_PIXELS, GUARD_BAND_PIXELS) * (1 << SUBPIXEL_BITS) as f32).round()
+                as i64
+        };
+        let v = [p0, p1, p2].map(|p| (snap(p.0), snap(p.1)));
+        let area = Self::edge(v[0], v[1], v[2]);
+        if area == 0 {
+            return None;
+        }
+        // Edges run v1->v2, v2->v0, v0->v1; with clockwise triangles they
+        // are walked the other way round, so the interior stays on the left
+        let bias = std::array::from_fn(|i| {
+            let (a, b) = (v[(i + 1) % 3], v[(i + 2) % 3]);
+            let (dx, dy) = ((b.0 - a.0) * area.signum(), (b.1 - a.1) * area.signum());
+            let owned = dy < 0 || (dy == 0 && dx < 0);
+            i64::from(!owned)
+        });
+        Some(Self { v, area, bias })
+    }
+
+    /// Whether the vertices are counter-clockwise in window coordinates.
+    pub fn is_ccw(&self) -> bool {
+        self.area > 0
+    }
+
+    /// Inclusive range `(min_x, min_y, max_x, max_y)` of pixels whose
+    /// centers lie in the snapped bounding box. Empty (min > max) when the
+    /// box falls between centers.
+    pub fn pixel_bounds(&self) -> (i32, i32, i32, i32) {
+        let half = 1i64 << (SUBPIXEL_BITS - 1);
+        let xs = self.v.map(|v| v.0);
+        let ys = self.v.map(|v| v.1);
+        // Pixel n's center is at n * 256 + 128
+        let first = |min: i64| -(half - min).div_euclid(1 << SUBPIXEL_BITS) as i32;
+        let last = |max: i64| (max - half).div_euclid(1 << SUBPIXEL_BITS) as i32;
+        (
+            first(*xs.iter().min().unwrap()),
+            first(*ys.iter().min().unwrap()),
+            last(*xs.iter().max().unwrap()),
+            last(*ys.iter().max().unwrap()),
+        )
+    }
+
+    /// Edge function of `a->b` at `p`: twice the signed area of `(a, b, p)`.
+    fn edge(a: (i64, i64), b: (i64, i64), p: (i64, i64)) -> i64 {
+        (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+    }
+
+    /// Barycentric weights of the center of pixel `(x, y)`, if the triangle
+    /// covers it.
+    pub fn coverage(&self, x: i32, y: i32) -> Option<(f32, f32, f32)> {
+        let half = 1i64 << (SUBPIXEL_BITS - 1);
+        let p = (
+            ((x as i64) << SUBPIXEL_BITS) + half,
+            ((y as i64) << SUBPIXEL_BITS) + half,
+        );
+        let sign = self.area.signum();
+        let mut e = [0i64; 3];
+        for (i, value) in e.iter_mut().enumerate() {
+            *value = Self::edge(self.v[(i + 1) % 3], self.v[(i + 2) % 3], p) * sign;
+            if *value - self.bias[i] < 0 {
+                return None;
+            }
+        }
+        let area = (self.area * sign) as f32;
+        Some((e[0] as f32 / area, e[1] as f32 / area, e[2] as f32 / area))
+    }
+}
+
 /// Calculate screen position from clip-space position
 fn screen_position(pos: &[f32; 4], vx: i32, vy: i32, vw: u32, vh: u32) -> (f32, f32) {
     (
@@ -1638,6 +2473,8 @@ mod tests {
             fragment_shader_type: 0,
             memory: ShaderMemoryLayout::default(),
             flat_varyings_mask: 0,
+            noperspective_varyings_mask: 0,
+            shading_rate: ShadingRate::PerPixel,
             vs_table_idx: None,
             fs_table_idx: None,
         };
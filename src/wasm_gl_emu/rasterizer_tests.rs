@@ -37,6 +37,8 @@ fn test_raster_pipeline_custom() {
         fragment_shader_type: 200,
         memory: ShaderMemoryLayout::default(),
         flat_varyings_mask: 0,
+        noperspective_varyings_mask: 0,
+        shading_rate: ShadingRate::PerPixel,
         vs_table_idx: None,
         fs_table_idx: None,
     };
@@ -120,6 +122,8 @@ fn test_rasterizer_draw_point() {
         cull_face_enabled: false,
         cull_face_mode: 0x0405, // GL_BACK
         front_face: 0x0901,     // GL_CCW
+        debug_view: DebugView::None,
+        dither: false,
     };
 
     // Draw a point at (50, 50)
@@ -155,6 +159,8 @@ fn test_rasterizer_draw_point_out_of_bounds() {
         cull_face_enabled: false,
         cull_face_mode: 0x0405, // GL_BACK
         front_face: 0x0901,     // GL_CCW
+        debug_view: DebugView::None,
+        dither: false,
     };
 
     // Try to draw outside framebuffer
@@ -221,6 +227,8 @@ fn test_render_state_creation() {
         cull_face_enabled: false,
         cull_face_mode: 0x0405, // GL_BACK
         front_face: 0x0901,     // GL_CCW
+        debug_view: DebugView::None,
+        dither: false,
     };
 
     assert_eq!(state.viewport.2, 800);
@@ -247,3 +255,370 @@ fn test_perspective_interpolation_setup() {
     assert_eq!(v1.varyings[1], 1.0f32.to_bits());
     assert_eq!(v2.varyings[2], 1.0f32.to_bits());
 }
+
+#[test]
+fn test_clip_triangle_near_plane() {
+    let vertex = |position: [f32; 4], smooth: f32, flat: u32| ProcessedVertex {
+        position,
+        varyings: vec![smooth.to_bits(), flat],
+    };
+    let v0 = vertex([-1.0, -1.0, 0.0, 1.0], 0.0, 1);
+    let v1 = vertex([1.0, -1.0, 0.0, 1.0], 0.0, 2);
+    // Behind the near plane: z + w = -2
+    let v2 = vertex([0.0, 1.0, -3.0, 1.0], 3.0, 7);
+
+    let tris = clip_triangle(&v0, &v1, &v2, 0b10);
+    assert_eq!(tris.len(), 2);
+    for v in tris.iter().flatten() {
+        assert!(v.position[2] + v.position[3] >= -1e-6);
+        assert!(v.position[3] > 0.0);
+        assert_eq!(v.varyings[1], 7);
+    }
+
+    // The v1-v2 edge crosses the plane a third of the way along
+    let new = &tris[0][2];
+    assert!((new.position[0] - 2.0 / 3.0).abs() < 1e-6);
+    assert!((new.position[2] + 1.0).abs() < 1e-6);
+    assert!((f32::from_bits(new.varyings[0]) - 1.0).abs() < 1e-6);
+
+    // Entirely behind the camera
+    let behind = |x: f32| vertex([x, 0.0, 0.5, -1.0], 0.0, 0);
+    assert!(clip_triangle(&behind(-1.0), &behind(1.0), &behind(0.0), 0).is_empty());
+}
+
+#[test]
+fn test_triangle_edges_top_left_rule() {
+    // A quad split along a diagonal through pixel centers, in both windings:
+    // every pixel of the quad is covered exactly once
+    let (a, b, c, d) = ((0.0, 0.0), (8.0, 0.0), (8.0, 8.0), (0.0, 8.0));
+    for tris in [[(a, b, c), (a, c, d)], [(a, c, b), (a, d, c)]] {
+        let edges: Vec<_> = tris
+            .iter()
+            .map(|&(p0, p1, p2)| TriangleEdges::new(p0, p1, p2).unwrap())
+            .collect();
+        for y in -1..9 {
+            for x in -1..9 {
+                let hits = edges.iter().filter(|e| e.coverage(x, y).is_some()).count();
+                let inside = (0..8).contains(&x) && (0..8).contains(&y);
+                assert_eq!(hits, inside as usize, "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    // Weights sum to one and favor the nearest vertex
+    let edges = TriangleEdges::new((0.0, 0.0), (16.0, 0.0), (0.0, 16.0)).unwrap();
+    let (u, v, w) = edges.coverage(0, 0).unwrap();
+    assert!((u + v + w - 1.0).abs() < 1e-6);
+    assert!(u > v && u > w);
+
+    // Degenerate after snapping
+    assert!(TriangleEdges::new((0.0, 0.0), (4.0, 0.0), (8.0, 0.001)).is_none());
+}
+
+#[test]
+fn test_triangle_fans_have_no_cracks_or_overdraw() {
+    // Deterministic LCG, so failures reproduce
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    let mut random = move || {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) as f64 / (1u64 << 31) as f64
+    };
+    let sub = (1 << SUBPIXEL_BITS) as f64;
+    // Twice the signed area of (a, b, p) on the sub-pixel grid
+    let edge = |a: (i64, i64), b: (i64, i64), p: (i64, i64)| {
+        (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0)
+    };
+
+    let mut fans = 0;
+    while fans < 300 {
+        // Star-shaped polygon around `center`, on the sub-pixel grid so
+        // snapping is exact; radii from a fraction of a pixel to 12 pixels
+        let center = (
+            (random() * 24.0 * sub) as i64,
+            (random() * 24.0 * sub) as i64,
+        );
+        let scale = if fans % 2 == 0 { 0.5 } else { 12.0 };
+        let count = 3 + (random() * 10.0) as usize;
+        let mut angles: Vec<f64> = (0..count)
+            .map(|_| random() * std::f64::consts::TAU)
+            .collect();
+        angles.sort_by(f64::total_cmp);
+        let mut rim: Vec<(i64, i64)> = angles
+            .iter()
+            .map(|a| {
+                let r = (0.1 + random()) * scale * sub;
+                (
+                    center.0 + (r * a.cos()) as i64,
+                    center.1 + (r * a.sin()) as i64,
+                )
+            })
+            .collect();
+        if fans % 3 == 0 {
+            rim.reverse();
+        }
+        let tris: Vec<_> = (0..count)
+            .map(|i| (center, rim[i], rim[(i + 1) % count]))
+            .collect();
+        // Fans whose triangles don't all turn the same way overlap for real
+        let ccw = edge(tris[0].0, tris[0].1, tris[0].2) > 0;
+        if tris
+            .iter()
+            .any(|&(a, b, c)| edge(a, b, c) == 0 || (edge(a, b, c) > 0) != ccw)
+        {
+            continue;
+        }
+        fans += 1;
+
+        let to_px = |p: (i64, i64)| (p.0 as f32 / sub as f32, p.1 as f32 / sub as f32);
+        let edges: Vec<_> = tris
+            .iter()
+            .map(|&(a, b, c)| TriangleEdges::new(to_px(a), to_px(b), to_px(c)).unwrap())
+            .collect();
+        let sign = if ccw { 1 } else { -1 };
+        for y in -2..40 {
+            for x in -2..40 {
+                let p = ((x << SUBPIXEL_BITS) + 128, (y << SUBPIXEL_BITS) + 128);
+                let (px, py) = (x as i32, y as i32);
+                let hits = edges
+                    .iter()
+                    .filter(|e| e.coverage(px, py).is_some())
+                    .count();
+                let in_union = tris.iter().any(|&(a, b, c)| {
+                    [edge(b, c, p), edge(c, a, p), edge(a, b, p)]
+                        .iter()
+                        .all(|&e| e * sign >= 0)
+                });
+                let on_rim = tris.iter().any(|&(_, b, c)| edge(b, c, p) == 0);
+                let expected = if in_union && !on_rim { 1..=1 } else { 0..=1 };
+                assert!(
+                    expected.contains(&hits),
+                    "fan {} pixel ({}, {}) covered {} times",
+                    fans,
+                    x,
+                    y,
+                    hits
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_packed_formats_round_trip_and_dither() {
+    for format in [GL_RGB565, GL_RGBA4, GL_RGB5_A1] {
+        // Every stored value survives unpacking and repacking, dithered or not,
+        // so masked channels keep their contents
+        for packed in 0..=u16::MAX {
+            let color = unpack_color(format, packed);
+            assert_eq!(pack_color(format, color, 0.5), packed);
+            for (x, y) in [(0, 0), (1, 2), (3, 3)] {
+                assert_eq!(
+                    pack_color(format, color, dither_threshold(true, x, y)),
+                    packed
+                );
+            }
+        }
+    }
+
+    // Over a 4x4 block, the dithered red channel averages to the source
+    // value within a sixteenth of a step, where rounding alone is off by up
+    // to half a step
+    for value in [0.1f32, 0.25, 0.5, 0.77, 0.9] {
+        let mut sum = 0.0;
+        for y in 0..4 {
+            for x in 0..4 {
+                let threshold = dither_threshold(true, x, y);
+                let packed = pack_color(GL_RGB565, [value, 0.0, 0.0, 1.0], threshold);
+                sum += (packed & 0x1F) as f32;
+            }
+        }
+        assert!((sum / 16.0 - value * 31.0).abs() <= 1.0 / 32.0 + 1e-4);
+    }
+    assert_eq!(
+        pack_color(GL_RGB565, [0.5, 0.5, 0.5, 1.0], 0.5),
+        16 | (32 << 5) | (16 << 11)
+    );
+}
+
+#[test]
+fn test_point_sprite_bounds_and_coords() {
+    // A one-pixel point covers the pixel containing it
+    assert_eq!(point_pixel_bounds(32.0, 32.0, 1.0), (32, 32, 33, 33));
+    assert_eq!(point_pixel_bounds(4.7, 2.2, 1.0), (4, 2, 5, 3));
+    // Odd sizes center on that pixel, even sizes on the nearest corner
+    assert_eq!(point_pixel_bounds(4.5, 4.5, 3.0), (3, 3, 6, 6));
+    assert_eq!(point_pixel_bounds(4.0, 4.0, 4.0), (2, 2, 6, 6));
+
+    // gl_PointCoord runs from the upper left corner at the pixel centers
+    let size = 4.0;
+    let corner = point_coord(4.0, 4.0, size, 2, 5);
+    assert_eq!(corner, (0.125, 0.125));
+    let corner = point_coord(4.0, 4.0, size, 5, 2);
+    assert_eq!(corner, (0.875, 0.875));
+}
+
+#[test]
+fn test_fixed_depth_precision_rounds_to_its_grid() {
+    assert_eq!(
+        DepthPrecision::of_format(GL_DEPTH_COMPONENT24),
+        DepthPrecision::Fixed(24)
+    );
+    assert_eq!(
+        DepthPrecision::of_format(GL_DEPTH_COMPONENT16),
+        DepthPrecision::Fixed(16)
+    );
+    assert_eq!(
+        DepthPrecision::of_format(GL_DEPTH_COMPONENT32F),
+        DepthPrecision::Float32
+    );
+
+    let fixed16 = DepthPrecision::Fixed(16);
+    assert_eq!(fixed16.quantize(0.0), 0.0);
+    assert_eq!(fixed16.quantize(1.0), 1.0);
+    assert_eq!(fixed16.quantize(0.5), 32768.0 / 65535.0);
+    // Values closer than a step collapse; a float buffer keeps them apart
+    assert_eq!(fixed16.quantize(0.4), fixed16.quantize(0.400001));
+    assert_ne!(
+        DepthPrecision::Float32.quantize(0.4),
+        DepthPrecision::Float32.quantize(0.400001)
+    );
+}
+
+#[test]
+fn test_draw_stats_explain_discarded_primitives_and_fragments() {
+    let rasterizer = Rasterizer::default();
+    let mut kernel = GpuKernel::new();
+    let mut owned_fb = OwnedFramebuffer::new(&mut kernel, 8, 8);
+    let mut fb = owned_fb.as_framebuffer(&mut kernel);
+    fb.color_attachments.clear();
+    let state = RenderState {
+        ctx_handle: 0,
+        memory: ShaderMemoryLayout::default(),
+        viewport: (0, 0, 8, 8),
+        scissor: (0, 0, 8, 8),
+        scissor_enabled: false,
+        uniform_data: &[],
+        prepare_textures: None,
+        blend: BlendState::default(),
+        color_mask: ColorMaskState::default(),
+        depth: DepthState {
+            enabled: true,
+            ..DepthState::default()
+        },
+        stencil: StencilState::default(),
+        cull_face_enabled: true,
+        cull_face_mode: GL_BACK,
+        front_face: GL_CCW,
+        debug_view: DebugView::None,
+        dither: false,
+    };
+    let pipeline = RasterPipeline::new();
+    let vertex = |x: f32, y: f32, z: f32| ProcessedVertex {
+        position: [x, y, z, 1.0],
+        varyings: vec![0; output_layout::VARYING_BUFFER_WORDS],
+    };
+    let ccw = [
+        vertex(-1.0, -1.0, 0.0),
+        vertex(1.0, -1.0, 0.0),
+        vertex(-1.0, 1.0, 0.0),
+    ];
+    let mut draw = |v: &[ProcessedVertex; 3]| {
+        rasterizer.rasterize_triangle(&mut fb, &v[0], &v[1], &v[2], &pipeline, &state, &[]);
+    };
+
+    // Front facing and in view: every covered fragment passes and writes depth
+    draw(&ccw);
+    let shaded = rasterizer.take_draw_stats().shaded;
+    assert!(shaded > 0);
+
+    // Back facing, behind the far plane, and off to the side
+    draw(&[ccw[0].clone(), ccw[2].clone(), ccw[1].clone()]);
+    draw(&[
+        vertex(-1.0, -1.0, 2.0),
+        vertex(1.0, -1.0, 2.0),
+        vertex(-1.0, 1.0, 2.0),
+    ]);
+    draw(&[
+        vertex(3.0, -1.0, 0.0),
+        vertex(4.0, -1.0, 0.0),
+        vertex(3.0, 1.0, 0.0),
+    ]);
+    let stats = rasterizer.take_draw_stats();
+    assert_eq!(
+        (stats.primitives, stats.culled, stats.clipped, stats.empty),
+        (3, 1, 1, 1)
+    );
+    assert_eq!(stats.shaded, 0);
+
+    // The same triangle again fails LESS against its own depth everywhere
+    draw(&ccw);
+    let stats = rasterizer.take_draw_stats();
+    assert_eq!((stats.depth_failed, stats.shaded), (shaded, 0));
+}
+
+#[test]
+fn test_fragment_hook_sees_varyings_and_discards_before_depth_test() {
+    use crate::wasm_gl_emu::hooks::{FragmentStage, PipelineHooks};
+    use std::sync::{Arc, Mutex};
+
+    /// Records the first varying after gl_Position and keeps the right half
+    struct RightHalf(Arc<Mutex<Vec<f32>>>);
+    impl PipelineHooks for RightHalf {
+        fn fragment(&self, fragment: &FragmentStage) -> bool {
+            self.0
+                .lock()
+                .unwrap()
+                .push(f32::from_bits(fragment.varyings[4]));
+            fragment.x >= 4
+        }
+    }
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let mut rasterizer = Rasterizer::default();
+    rasterizer.set_hooks(Some(Box::new(RightHalf(seen.clone()))));
+    let mut kernel = GpuKernel::new();
+    let mut owned_fb = OwnedFramebuffer::new(&mut kernel, 8, 8);
+    let mut fb = owned_fb.as_framebuffer(&mut kernel);
+    fb.color_attachments.clear();
+    let state = RenderState {
+        ctx_handle: 0,
+        memory: ShaderMemoryLayout::default(),
+        viewport: (0, 0, 8, 8),
+        scissor: (0, 0, 8, 8),
+        scissor_enabled: false,
+        uniform_data: &[],
+        prepare_textures: None,
+        blend: BlendState::default(),
+        color_mask: ColorMaskState::default(),
+        depth: DepthState {
+            enabled: true,
+            ..DepthState::default()
+        },
+        stencil: StencilState::default(),
+        cull_face_enabled: false,
+        cull_face_mode: GL_BACK,
+        front_face: GL_CCW,
+        debug_view: DebugView::None,
+        dither: false,
+    };
+    let pipeline = RasterPipeline::new();
+    let vertex = |x: f32, y: f32| {
+        let mut varyings = vec![0; output_layout::VARYING_BUFFER_WORDS];
+        varyings[4] = 0.25f32.to_bits();
+        ProcessedVertex {
+            position: [x, y, 0.0, 1.0],
+            varyings,
+        }
+    };
+    let (v0, v1, v2) = (vertex(-1.0, -1.0), vertex(3.0, -1.0), vertex(-1.0, 3.0));
+    rasterizer.rasterize_triangle(&mut fb, &v0, &v1, &v2, &pipeline, &state, &[]);
+
+    // Every pixel reached the hook with the interpolated varying; only the
+    // kept half reached the depth test and wrote depth
+    assert_eq!(*seen.lock().unwrap(), vec![0.25; 64]);
+    let stats = rasterizer.take_draw_stats();
+    assert_eq!((stats.shaded, stats.depth_failed), (32, 0));
+    assert_eq!(fb.depth.iter().filter(|&&d| d == 0.5).count(), 32);
+}
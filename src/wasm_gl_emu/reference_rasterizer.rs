@@ -0,0 +1,149 @@
+//! Reference rasterizer for conformance tests
+//!
+//! A deliberately slow restatement of the coverage rules the optimized
+//! [`TriangleEdges`](super::rasterizer::TriangleEdges) implements, sharing no
+//! code with it:
+//!
+//! - vertices are snapped to 1/2^[`SUBPIXEL_BITS`] pixel (round half away
+//!   from zero);
+//! - a pixel is sampled at its center, and its barycentric coordinates are
+//!   solved exactly, as fractions of `i128`s, with Cramer's rule;
+//! - a sample on an edge is covered if nudging it by an infinitesimal
+//!   `(ε, -ε²)` moves it inside, which gives left edges and horizontal top
+//!   edges to the triangle.
+
+use super::rasterizer::SUBPIXEL_BITS;
+
+/// Exact barycentric coordinates `num[i] / den`, with `den > 0`.
+pub struct Barycentric {
+    pub num: [i128; 3],
+    pub den: i128,
+}
+
+impl Barycentric {
+    pub fn weights(&self) -> [f64; 3] {
+        self.num.map(|n| n as f64 / self.den as f64)
+    }
+}
+
+/// A window coordinate on the sub-pixel grid.
+fn snap(c: f32) -> i128 {
+    (c as f64 * (1u32 << SUBPIXEL_BITS) as f64).round() as i128
+}
+
+/// Determinant of the 2x2 matrix with columns `a` and `b`.
+fn det(a: (i128, i128), b: (i128, i128)) -> i128 {
+    a.0 * b.1 - a.1 * b.0
+}
+
+/// Coverage of the center of pixel `(x, y)` by a triangle in window
+/// coordinates, with the exact barycentric coordinates of the center.
+pub fn coverage(tri: [(f32, f32); 3], x: i32, y: i32) -> Option<Barycentric> {
+    let [a, b, c] = tri.map(|p| (snap(p.0), snap(p.1)));
+    let one = 1i128 << SUBPIXEL_BITS;
+    let p = (x as i128 * one + one / 2, y as i128 * one + one / 2);
+    let sub = |u: (i128, i128), v: (i128, i128)| (u.0 - v.0, u.1 - v.1);
+
+    // p = a + l1 (b - a) + l2 (c - a), l0 = 1 - l1 - l2
+    let mut den = det(sub(b, a), sub(c, a));
+    if den == 0 {
+        return None;
+    }
+    let mut n1 = det(sub(p, a), sub(c, a));
+    let mut n2 = det(sub(b, a), sub(p, a));
+    // Derivatives of n1 and n2 along x and y
+    let mut d1 = (c.1 - a.1, -(c.0 - a.0));
+    let mut d2 = (-(b.1 - a.1), b.0 - a.0);
+    if den < 0 {
+        (den, n1, n2) = (-den, -n1, -n2);
+        (d1, d2) = ((-d1.0, -d1.1), (-d2.0, -d2.1));
+    }
+    let n0 = den - n1 - n2;
+    let d0 = (-d1.0 - d2.0, -d1.1 - d2.1);
+
+    let inside = |n: i128, d: (i128, i128)| {
+        if n != 0 {
+            return n > 0;
+        }
+        // On the edge: follow the nudge (ε, -ε²)
+        if d.0 != 0 {
+            d.0 > 0
+        } else {
+            d.1 < 0
+        }
+    };
+    (inside(n0, d0) && inside(n1, d1) && inside(n2, d2)).then_some(Barycentric {
+        num: [n0, n1, n2],
+        den,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wasm_gl_emu::rasterizer::TriangleEdges;
+
+    #[test]
+    fn optimized_coverage_matches_reference() {
+        // Deterministic LCG, so failures reproduce
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        let mut random = move || {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 40) as f32 / (1u32 << 24) as f32
+        };
+
+        for case in 0..2000 {
+            // Arbitrary float coordinates; every fourth case on the pixel grid,
+            // where samples land exactly on edges, and some sub-pixel sized
+            let size = [0.75, 4.0, 16.0, 40.0][case % 4];
+            let origin = (random() * 24.0, random() * 24.0);
+            let mut tri = [(0.0f32, 0.0f32); 3];
+            for v in tri.iter_mut() {
+                *v = (
+                    origin.0 + (random() - 0.5) * size,
+                    origin.1 + (random() - 0.5) * size,
+                );
+                if case % 4 == 3 {
+                    *v = (v.0.round() + 0.5, v.1.round());
+                }
+            }
+
+            let edges = TriangleEdges::new(tri[0], tri[1], tri[2]);
+            let (min_x, min_y, max_x, max_y) = edges
+                .as_ref()
+                .map_or((0, 0, -1, -1), TriangleEdges::pixel_bounds);
+            for y in -24..64 {
+                for x in -24..64 {
+                    let expected = coverage(tri, x, y);
+                    let actual = edges.as_ref().and_then(|e| e.coverage(x, y));
+                    assert_eq!(
+                        actual.is_some(),
+                        expected.is_some(),
+                        "case {} {:?} pixel ({}, {})",
+                        case,
+                        tri,
+                        x,
+                        y
+                    );
+                    let (Some((u, v, w)), Some(exact)) = (actual, expected) else {
+                        continue;
+                    };
+                    assert!((min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y));
+                    for (got, want) in [u, v, w].into_iter().zip(exact.weights()) {
+                        assert!(
+                            (got as f64 - want).abs() < 1e-5,
+                            "case {} pixel ({}, {}): {} vs {}",
+                            case,
+                            x,
+                            y,
+                            got,
+                            want
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
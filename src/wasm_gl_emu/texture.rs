@@ -2,6 +2,8 @@
 //!
 //! Phase 0: Basic texture structure
 
+use crate::gl_constants::{GL_R32F, GL_RG32F, GL_RGBA32F, GL_RGBA8};
+
 /// 2D texture with format-aware storage
 pub struct Texture {
     pub width: u32,
@@ -13,15 +15,15 @@ pub struct Texture {
 impl Texture {
     /// Create a new texture with the given dimensions and format
     pub fn new(width: u32, height: u32) -> Self {
-        Self::new_with_format(width, height, 0x8058) // GL_RGBA8 default
+        Self::new_with_format(width, height, GL_RGBA8)
     }
 
     pub fn new_with_format(width: u32, height: u32, internal_format: u32) -> Self {
         let bytes_per_pixel = match internal_format {
-            0x822E => 4,  // GL_R32F: 1 channel × 4 bytes
-            0x8230 => 8,  // GL_RG32F: 2 channels × 4 bytes
-            0x8814 => 16, // GL_RGBA32F: 4 channels × 4 bytes
-            _ => 4,       // GL_RGBA8: 4 channels × 1 byte
+            GL_R32F => 4,
+            GL_RG32F => 8,
+            GL_RGBA32F => 16,
+            _ => 4, // GL_RGBA8
         };
         let size = (width * height * bytes_per_pixel) as usize;
         Self {
@@ -43,10 +45,10 @@ impl Texture {
         let x = x.min(self.width - 1);
         let y = y.min(self.height - 1);
         let bytes_per_pixel = match self.internal_format {
-            0x822E => 4,  // GL_R32F
-            0x8230 => 8,  // GL_RG32F
-            0x8814 => 16, // GL_RGBA32F
-            _ => 4,       // GL_RGBA8
+            GL_R32F => 4,
+            GL_RG32F => 8,
+            GL_RGBA32F => 16,
+            _ => 4, // GL_RGBA8
         };
         let offset = ((y * self.width + x) * bytes_per_pixel) as usize;
         [
@@ -61,10 +63,10 @@ impl Texture {
     pub fn set_pixel(&mut self, x: u32, y: u32, color: [u8; 4]) {
         if x < self.width && y < self.height {
             let bytes_per_pixel = match self.internal_format {
-                0x822E => 4,  // GL_R32F
-                0x8230 => 8,  // GL_RG32F
-                0x8814 => 16, // GL_RGBA32F
-                _ => 4,       // GL_RGBA8
+                GL_R32F => 4,
+                GL_RG32F => 8,
+                GL_RGBA32F => 16,
+                _ => 4, // GL_RGBA8
             };
             let offset = ((y * self.width + x) * bytes_per_pixel) as usize;
             for (i, &byte) in color.iter().enumerate() {
@@ -3,6 +3,7 @@
 //! Handles movement and interpretation of raw bytes between
 //! GPU buffers and shader-accessible formats (Vertex, Index, etc.)
 
+use crate::gl_constants::{GL_INT_2_10_10_10_REV, GL_UNSIGNED_INT_2_10_10_10_REV};
 use crate::wasm_gl_emu::device::{GpuBuffer, StorageLayout};
 use wgpu_types as wgt;
 
@@ -62,6 +63,16 @@ impl crate::wasm_gl_emu::rasterizer::IndexBuffer for LazyIndexBuffer {
             }
         }
     }
+
+    /// WebGL2 always has PRIMITIVE_RESTART_FIXED_INDEX enabled: the largest
+    /// value of the index type restarts the primitive
+    fn restart_index(&self) -> Option<u32> {
+        Some(match self.index_type {
+            IndexType::U8 => u8::MAX as u32,
+            IndexType::U16 => u16::MAX as u32,
+            IndexType::U32 => u32::MAX,
+        })
+    }
 }
 
 /// Description of a vertex attribute binding for backend fetching
@@ -89,6 +100,121 @@ pub struct AttributeBinding {
     pub default_value: [u32; 4],
 }
 
+// SAFETY: `buffer_ptr` points into the module's own memory, and WASM is
+// single-threaded; contexts keep bindings between draws to reuse the Vec.
+unsafe impl Send for AttributeBinding {}
+
+fn is_packed_type(type_: u32) -> bool {
+    matches!(
+        type_,
+        GL_INT_2_10_10_10_REV | GL_UNSIGNED_INT_2_10_10_10_REV
+    )
+}
+
+impl AttributeBinding {
+    /// Size in bytes of one component of `type_`. Packed types report the
+    /// whole 32-bit word, which holds all four components.
+    pub fn component_size(type_: u32) -> usize {
+        match type_ {
+            0x1401 | 0x1400 => 1,          // BYTE, UNSIGNED_BYTE
+            0x1403 | 0x1402 | 0x140B => 2, // SHORT, UNSIGNED_SHORT, HALF_FLOAT
+            _ => 4,                        // FLOAT, INT, UNSIGNED_INT, packed
+        }
+    }
+
+    /// Stride between vertices when the attribute data is tightly packed.
+    pub fn tight_stride(type_: u32, size: i32) -> usize {
+        if is_packed_type(type_) {
+            4
+        } else {
+            size as usize * Self::component_size(type_)
+        }
+    }
+}
+
+/// Read `N` bytes from a possibly unaligned pointer.
+unsafe fn read_bytes<const N: usize>(ptr: *const u8) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    std::ptr::copy_nonoverlapping(ptr, bytes.as_mut_ptr(), N);
+    bytes
+}
+
+/// Convert one integer component of `bits` width to the bits the shader
+/// reads. Integer attributes keep the value; normalized float attributes
+/// map unsigned values to [0, 1] and signed values to [-1, 1] using
+/// `max(c / (2^(b-1) - 1), -1)` as OpenGL ES 3.0 specifies; other
+/// float attributes convert the value directly.
+fn convert_integer(binding: &AttributeBinding, value: i64, bits: u32, signed: bool) -> u32 {
+    if binding.is_integer {
+        value as u32
+    } else if binding.normalized {
+        let max = if signed {
+            (1i64 << (bits - 1)) - 1
+        } else {
+            (1i64 << bits) - 1
+        };
+        ((value as f64 / max as f64).max(-1.0) as f32).to_bits()
+    } else {
+        (value as f32).to_bits()
+    }
+}
+
+/// Widen an IEEE 754 binary16 value to f32.
+pub(crate) fn half_to_f32(half: u16) -> f32 {
+    let sign = ((half >> 15) as u32) << 31;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+    let bits = match exponent {
+        0 if mantissa == 0 => sign,
+        // Subnormal: mantissa * 2^-24
+        0 => {
+            let magnitude = mantissa as f32 * f32::from_bits(0x3380_0000);
+            sign | magnitude.to_bits()
+        }
+        0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+/// Narrow an f32 to IEEE 754 binary16, rounding to nearest even.
+pub(crate) fn f32_to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    if exponent == 0xff {
+        // Infinity stays infinity, NaN stays quiet NaN
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    let (half, remainder, halfway) = if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        // Subnormal: shift the mantissa, implicit bit included, into place
+        let mantissa = mantissa | 0x80_0000;
+        let shift = (14 - exponent) as u32;
+        (
+            mantissa >> shift,
+            mantissa & ((1 << shift) - 1),
+            1 << (shift - 1),
+        )
+    } else {
+        (
+            ((exponent as u32) << 10) | (mantissa >> 13),
+            mantissa & 0x1fff,
+            0x1000,
+        )
+    };
+    // A carry out of the mantissa correctly bumps the exponent
+    let round_up = remainder > halfway || (remainder == halfway && half & 1 == 1);
+    sign | (half + round_up as u32) as u16
+}
+
 pub struct TransferEngine;
 
 impl TransferEngine {
@@ -178,6 +304,26 @@ impl TransferEngine {
 
         let base_offset = binding.offset + vertex_index * binding.stride;
 
+        if is_packed_type(binding.type_) {
+            // All four components share one 32-bit word: x, y, z in 10 bits each, w in 2
+            let word = unsafe { read_bytes::<4>(binding.buffer_ptr.add(base_offset)) };
+            let word = u32::from_le_bytes(word);
+            let signed = binding.type_ == GL_INT_2_10_10_10_REV;
+            for (i, (shift, bits)) in [(0, 10), (10, 10), (20, 10), (30, 2)]
+                .into_iter()
+                .enumerate()
+            {
+                let value = if signed {
+                    // Sign-extend the field from its top bit
+                    ((word << (32 - shift - bits)) as i32 >> (32 - bits)) as i64
+                } else {
+                    ((word >> shift) & ((1 << bits) - 1)) as i64
+                };
+                dest[i] = convert_integer(binding, value, bits, signed);
+            }
+            return;
+        }
+
         #[allow(clippy::needless_range_loop)]
         for i in 0..(binding.size as usize).min(4) {
             let component_offset = base_offset + i * binding.type_size;
@@ -185,70 +331,31 @@ impl TransferEngine {
             unsafe {
                 let ptr = binding.buffer_ptr.add(component_offset);
 
-                match binding.type_ {
-                    0x1406 /* FLOAT */ => {
-                        let mut bytes = [0u8; 4];
-                        std::ptr::copy_nonoverlapping(ptr, bytes.as_mut_ptr(), 4);
-                        dest[i] = u32::from_le_bytes(bytes);
-                    }
-                    0x1401 /* UNSIGNED_BYTE */ => {
-                        let val = *ptr;
-                        if binding.is_integer {
-                            dest[i] = val as u32;
-                        } else if binding.normalized {
-                            dest[i] = (val as f32 / 255.0).to_bits();
-                        } else {
-                            dest[i] = (val as f32).to_bits();
-                        }
-                    }
-                    0x1400 /* BYTE */ => {
-                        let val = *ptr as i8;
-                        if binding.is_integer {
-                            dest[i] = val as i32 as u32;
-                        } else if binding.normalized {
-                            dest[i] = (val as f32 / 127.0).clamp(-1.0, 1.0).to_bits();
-                        } else {
-                            dest[i] = (val as f32).to_bits();
-                        }
+                dest[i] = match binding.type_ {
+                    0x1406 /* FLOAT */ => u32::from_le_bytes(read_bytes(ptr)),
+                    0x140B /* HALF_FLOAT */ => {
+                        half_to_f32(u16::from_le_bytes(read_bytes(ptr))).to_bits()
                     }
+                    0x1401 /* UNSIGNED_BYTE */ => convert_integer(binding, *ptr as i64, 8, false),
+                    0x1400 /* BYTE */ => convert_integer(binding, *ptr as i8 as i64, 8, true),
                     0x1403 /* UNSIGNED_SHORT */ => {
-                        let mut bytes = [0u8; 2];
-                        std::ptr::copy_nonoverlapping(ptr, bytes.as_mut_ptr(), 2);
-                        let val = u16::from_le_bytes(bytes);
-                        if binding.is_integer {
-                            dest[i] = val as u32;
-                        } else if binding.normalized {
-                            dest[i] = (val as f32 / 65535.0).to_bits();
-                        } else {
-                            dest[i] = (val as f32).to_bits();
-                        }
+                        let val = u16::from_le_bytes(read_bytes(ptr));
+                        convert_integer(binding, val as i64, 16, false)
                     }
                     0x1402 /* SHORT */ => {
-                        let mut bytes = [0u8; 2];
-                        std::ptr::copy_nonoverlapping(ptr, bytes.as_mut_ptr(), 2);
-                        let val = i16::from_le_bytes(bytes);
-                        if binding.is_integer {
-                            dest[i] = val as i32 as u32;
-                        } else if binding.normalized {
-                            dest[i] = (val as f32 / 32767.0).clamp(-1.0, 1.0).to_bits();
-                        } else {
-                            dest[i] = (val as f32).to_bits();
-                        }
+                        let val = i16::from_le_bytes(read_bytes(ptr));
+                        convert_integer(binding, val as i64, 16, true)
                     }
                     0x1405 /* UNSIGNED_INT */ => {
-                        let mut bytes = [0u8; 4];
-                        std::ptr::copy_nonoverlapping(ptr, bytes.as_mut_ptr(), 4);
-                        let val = u32::from_le_bytes(bytes);
-                        dest[i] = val;
+                        let val = u32::from_le_bytes(read_bytes(ptr));
+                        convert_integer(binding, val as i64, 32, false)
                     }
                     0x1404 /* INT */ => {
-                        let mut bytes = [0u8; 4];
-                        std::ptr::copy_nonoverlapping(ptr, bytes.as_mut_ptr(), 4);
-                        let val = i32::from_le_bytes(bytes);
-                        dest[i] = val as u32;
+                        let val = i32::from_le_bytes(read_bytes(ptr));
+                        convert_integer(binding, val as i64, 32, true)
                     }
-                    _ => {}
-                }
+                    _ => 0,
+                };
             }
         }
     }
@@ -257,7 +364,7 @@ impl TransferEngine {
     pub fn write_texture_metadata(
         kernel: &crate::wasm_gl_emu::device::GpuKernel,
         bindings: &[Option<super::device::TextureBinding>],
-        dest_ptr: u32,
+        dest_ptr: usize,
     ) {
         kernel.write_texture_metadata(bindings, dest_ptr);
     }
@@ -484,3 +591,113 @@ impl TransferEngine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fetch(data: &[u8], type_: u32, size: i32, normalized: bool, is_integer: bool) -> [u32; 4] {
+        let binding = AttributeBinding {
+            buffer_ptr: data.as_ptr(),
+            offset: 0,
+            stride: AttributeBinding::tight_stride(type_, size),
+            size,
+            type_,
+            normalized,
+            is_integer,
+            type_size: AttributeBinding::component_size(type_),
+            divisor: 0,
+            default_value: [0; 4],
+        };
+        let mut dest = [0u32; 4];
+        TransferEngine::fetch_vertex_attribute(&binding, 0, &mut dest);
+        dest
+    }
+
+    fn floats(bits: [u32; 4]) -> [f32; 4] {
+        bits.map(f32::from_bits)
+    }
+
+    #[test]
+    fn test_fetch_normalized_signed_clamps_to_minus_one() {
+        let data: Vec<u8> = [i16::MIN, i16::MAX, 0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert_eq!(
+            floats(fetch(&data, 0x1402, 3, true, false)),
+            [-1.0, 1.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_fetch_int_types() {
+        let data: Vec<u8> = [-2i32, 7].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(
+            floats(fetch(&data, 0x1404, 2, false, false)),
+            [-2.0, 7.0, 0.0, 1.0]
+        );
+        assert_eq!(
+            fetch(&data, 0x1404, 2, false, true),
+            [-2i32 as u32, 7, 0, 1]
+        );
+
+        let data: Vec<u8> = [u32::MAX, 0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        assert_eq!(
+            floats(fetch(&data, 0x1405, 2, true, false)),
+            [1.0, 0.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_fetch_half_float() {
+        // 1.0, -2.5, 65504 (max), 2^-24 (smallest subnormal)
+        let data: Vec<u8> = [0x3c00u16, 0xc100, 0x7bff, 0x0001]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert_eq!(
+            floats(fetch(&data, 0x140B, 4, false, false)),
+            [1.0, -2.5, 65504.0, 2f32.powi(-24)]
+        );
+    }
+
+    #[test]
+    fn test_fetch_packed_2_10_10_10() {
+        // x = -511 (0x201), y = 511, z = 0, w = -1 (0b11)
+        let word: u32 = 0x201 | (511 << 10) | (3 << 30);
+        let data = word.to_le_bytes();
+        assert_eq!(
+            floats(fetch(&data, GL_INT_2_10_10_10_REV, 4, true, false)),
+            [-1.0, 1.0, 0.0, -1.0]
+        );
+        assert_eq!(
+            floats(fetch(&data, GL_INT_2_10_10_10_REV, 4, false, false)),
+            [-511.0, 511.0, 0.0, -1.0]
+        );
+        assert_eq!(
+            floats(fetch(
+                &data,
+                GL_UNSIGNED_INT_2_10_10_10_REV,
+                4,
+                false,
+                false
+            )),
+            [513.0, 511.0, 0.0, 3.0]
+        );
+    }
+
+    #[test]
+    fn test_half_round_trip() {
+        for half in 0..=u16::MAX {
+            let value = half_to_f32(half);
+            if !value.is_nan() {
+                assert_eq!(f32_to_half(value), half);
+            }
+        }
+        // Ties round to even; out of range saturates to infinity
+        assert_eq!(f32_to_half(1.0 + 2f32.powi(-11)), 0x3c00);
+        assert_eq!(f32_to_half(65520.0), 0x7c00);
+        assert_eq!(f32_to_half(-1e-9), 0x8000);
+    }
+}
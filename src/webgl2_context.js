@@ -5,7 +5,7 @@
 
 /** @typedef {number} u32 */
 
-// Errno constants (must match src/webgl2_context.rs)
+// Errno constants (must match src/webgl2_context/types.rs)
 export const ERR_OK = 0;
 export const ERR_INVALID_HANDLE = 1;
 export const ERR_OOM = 2;
@@ -13,42 +13,135 @@ export const ERR_INVALID_ARGS = 3;
 export const ERR_NOT_IMPLEMENTED = 4;
 export const ERR_GL = 5;
 export const ERR_INTERNAL = 6;
+export const ERR_INVALID_OPERATION = 7;
+export const ERR_INVALID_ENUM = 8;
+export const ERR_COMPILATION = 9;
+
+// Status of wasm_ctx_reload_program when attribute/uniform locations moved
+export const RELOAD_INTERFACE_CHANGED = 0x100;
+
+// Version of the export convention generated shader modules follow
+// (src/naga_wasm_backend/abi.rs). Bump both together.
+export const SHADER_ABI_VERSION = 1;
+
+// Shader table index registered for a stage a separable program does not have
+const NO_SHADER_INDEX = 0xFFFFFFFF;
+
+// Methods a WebGL1 context does not have. Vertex array objects and instanced
+// draws come back through OES_vertex_array_object and ANGLE_instanced_arrays.
+const WEBGL2_ONLY_METHODS = [
+  'texImage3D', 'vertexAttribIPointer', 'vertexAttribI4i', 'vertexAttribI4ui',
+  'vertexAttribI4iv', 'vertexAttribI4uiv', 'vertexAttribDivisor', 'bindBufferRange',
+  'bindBufferBase', 'copyBufferSubData', 'drawArraysInstanced', 'drawElementsInstanced',
+  'drawRangeElements', 'drawBuffers', 'readBuffer', 'createVertexArray', 'bindVertexArray',
+  'deleteVertexArray', 'isVertexArray', 'createTransformFeedback', 'deleteTransformFeedback',
+  'isTransformFeedback', 'bindTransformFeedback', 'beginTransformFeedback',
+  'pauseTransformFeedback', 'resumeTransformFeedback', 'endTransformFeedback',
+  'transformFeedbackVaryings', 'getTransformFeedbackVarying', 'createQuery', 'deleteQuery',
+  'isQuery', 'beginQuery', 'endQuery', 'getQuery', 'getQueryParameter', 'fenceSync', 'clientWaitSync', 'waitSync',
+  'deleteSync', 'getSyncParameter', 'createSampler', 'deleteSampler', 'bindSampler',
+  'samplerParameteri', 'samplerParameterf', 'getUniformBlockIndex', 'uniformBlockBinding',
+  'getActiveUniformBlockName', 'getActiveUniformBlockParameter',
+  'clearBufferfv', 'blitFramebuffer',
+];
+
+// Calls beginCommandRecording() captures into a command buffer instead of
+// crossing into WASM. Each entry is [opcode, encoder]; the encoder turns the
+// WebGL arguments into u32 words (see src/webgl2_context/command_buffer.rs)
+// and updates the JS-side bookkeeping the direct method would.
+const _cmdF32 = new Float32Array(1);
+const _cmdU32 = new Uint32Array(_cmdF32.buffer);
+const f32Bits = (v) => { _cmdF32[0] = +v; return _cmdU32[0]; };
+const objHandle = (o) => (o && typeof o === 'object' && typeof o._handle === 'number' ? o._handle : (o >>> 0));
+const locHandle = (loc) => (loc === null ? 0xFFFFFFFF : (typeof loc === 'number' ? loc >>> 0 : loc._handle >>> 0));
+const COMMAND_ENCODERS = {
+  enable: [1, (gl, cap) => [cap >>> 0]],
+  disable: [2, (gl, cap) => [cap >>> 0]],
+  viewport: [3, (gl, x, y, w, h) => [x >>> 0, y >>> 0, w >>> 0, h >>> 0]],
+  scissor: [4, (gl, x, y, w, h) => [x >>> 0, y >>> 0, w >>> 0, h >>> 0]],
+  clearColor: [5, (gl, r, g, b, a) => [f32Bits(r), f32Bits(g), f32Bits(b), f32Bits(a)]],
+  clear: [6, (gl, mask) => [mask >>> 0]],
+  bindBuffer: [7, (gl, target, buffer) => [target >>> 0, objHandle(buffer)]],
+  bindTexture: [8, (gl, target, tex) => {
+    const handle = objHandle(tex);
+    gl._boundTexture = handle;
+    gl._textureUnits = gl._textureUnits || [];
+    gl._textureUnits[gl._activeTextureUnit || 0] = handle;
+    return [target >>> 0, handle];
+  }],
+  activeTexture: [9, (gl, texture) => {
+    gl._activeTextureUnit = (texture >>> 0) - 0x84C0;
+    return [texture >>> 0];
+  }],
+  useProgram: [10, (gl, program) => {
+    gl._currentProgram = program;
+    return [objHandle(program)];
+  }],
+  uniform1f: [11, (gl, loc, x) => [locHandle(loc), f32Bits(x)]],
+  uniform2f: [12, (gl, loc, x, y) => [locHandle(loc), f32Bits(x), f32Bits(y)]],
+  uniform3f: [13, (gl, loc, x, y, z) => [locHandle(loc), f32Bits(x), f32Bits(y), f32Bits(z)]],
+  uniform4f: [14, (gl, loc, x, y, z, w) => [locHandle(loc), f32Bits(x), f32Bits(y), f32Bits(z), f32Bits(w)]],
+  uniform1i: [15, (gl, loc, x) => [locHandle(loc), x >>> 0]],
+  bindVertexArray: [16, (gl, vao) => [objHandle(vao)]],
+  vertexAttribPointer: [17, (gl, index, size, type, normalized, stride, offset) =>
+    [index >>> 0, size >>> 0, type >>> 0, normalized ? 1 : 0, stride >>> 0, offset >>> 0]],
+  enableVertexAttribArray: [18, (gl, index) => [index >>> 0]],
+  disableVertexAttribArray: [19, (gl, index) => [index >>> 0]],
+  drawArrays: [20, (gl, mode, first, count) => [mode >>> 0, first >>> 0, count >>> 0]],
+  drawElements: [21, (gl, mode, count, type, offset) => [mode >>> 0, count >>> 0, type >>> 0, offset >>> 0]],
+  drawArraysInstanced: [22, (gl, mode, first, count, instances) =>
+    [mode >>> 0, first >>> 0, count >>> 0, instances >>> 0]],
+  drawElementsInstanced: [23, (gl, mode, count, type, offset, instances) =>
+    [mode >>> 0, count >>> 0, type >>> 0, offset >>> 0, instances >>> 0]],
+  depthFunc: [24, (gl, func) => [func >>> 0]],
+  depthMask: [25, (gl, flag) => [flag ? 1 : 0]],
+  colorMask: [26, (gl, r, g, b, a) => [r ? 1 : 0, g ? 1 : 0, b ? 1 : 0, a ? 1 : 0]],
+  blendFunc: [27, (gl, sfactor, dfactor) => [sfactor >>> 0, dfactor >>> 0]],
+  cullFace: [28, (gl, mode) => [mode >>> 0]],
+  frontFace: [29, (gl, mode) => [mode >>> 0]],
+  bindFramebuffer: [30, (gl, target, fb) => [target >>> 0, objHandle(fb)]],
+};
+
+// Distinct shader modules whose instances a context keeps for reuse
+const SHADER_POOL_SIZE = 64;
+
+function fnv1a(bytes) {
+  let hash = 0x811c9dc5;
+  for (let i = 0; i < bytes.length; i++) {
+    hash = Math.imul(hash ^ bytes[i], 0x01000193);
+  }
+  return hash >>> 0;
+}
 
-function getBPP(internalFormat) {
-  switch (internalFormat) {
-    case 0x822E: // R32F
-    case 0x8236: // R32UI
-    case 0x8235: // R32I
-      return 4;
-    case 0x8230: // RG32F
-    case 0x823C: // RG32UI
-    case 0x823B: // RG32I
-      return 8;
-    case 0x8814: // RGBA32F
-    case 0x8D70: // RGBA32UI
-    case 0x8D82: // RGBA32I
-    case 0x8815: // RGB32F (often promoted to RGBA32F)
-    case 0x8D71: // RGB32UI
-    case 0x8D83: // RGB32I
-      return 16;
-    case 0x822D: // R16F
-    case 0x8234: // R16UI
-    case 0x8233: // R16I
-      return 2;
-    case 0x822F: // RG16F
-    case 0x823A: // RG16UI
-    case 0x8239: // RG16I
-      return 4;
-    case 0x881A: // RGBA16F
-    case 0x8D76: // RGBA16UI
-    case 0x8D88: // RGBA16I
-    case 0x881B: // RGB16F
-    case 0x8D77: // RGB16UI
-    case 0x8D89: // RGB16I
-      return 8;
-    default:
-      return 4; // Default to RGBA8
+function bytesEqual(a, b) {
+  if (a.length !== b.length) return false;
+  for (let i = 0; i < a.length; i++) {
+    if (a[i] !== b[i]) return false;
   }
+  return true;
+}
+
+/**
+ * Whether a WebAssembly module has a data section, whose active segments
+ * write linear memory each time the module is instantiated.
+ * @param {Uint8Array} wasm
+ * @returns {boolean}
+ */
+function hasDataSection(wasm) {
+  const DATA_SECTION = 11;
+  let offset = 8; // magic and version
+  while (offset < wasm.length) {
+    const id = wasm[offset++];
+    let size = 0;
+    for (let shift = 0; ; shift += 7) {
+      const byte = wasm[offset++];
+      size += (byte & 0x7f) * 2 ** shift;
+      if (byte < 0x80) break;
+    }
+    if (id === DATA_SECTION) return true;
+    offset += size;
+  }
+  return false;
 }
 
 import { WasmWebGLTexture } from './webgl2_texture.js';
@@ -63,6 +156,7 @@ import {
   WasmWebGLSampler,
   WasmWebGLSync,
   WasmWebGLTransformFeedback,
+  WasmWebGLProgramPipeline,
   WasmWebGLUniformLocation
 } from './webgl2_resources.js';
 
@@ -79,21 +173,50 @@ export class WasmWebGL2RenderingContext {
   DEPTH_BUFFER_BIT = 0x00000100;
   DEPTH_TEST = 0x0B71;
   STENCIL_TEST = 0x0B90;
+  SCISSOR_BOX = 0x0C10;
   SCISSOR_TEST = 0x0C11;
   BLEND = 0x0BE2;
   CULL_FACE = 0x0B44;
+  DITHER = 0x0BD0;
+  SAMPLE_ALPHA_TO_COVERAGE = 0x809E;
+  SAMPLE_COVERAGE = 0x80A0;
   STENCIL_BUFFER_BIT = 0x00000400;
   COMPILE_STATUS = 0x8B81;
   LINK_STATUS = 0x8B82;
   DELETE_STATUS = 0x8B80;
   VALIDATE_STATUS = 0x8B83;
+  ATTACHED_SHADERS = 0x8B85;
+  CURRENT_QUERY = 0x8865;
+  QUERY_RESULT = 0x8866;
+  QUERY_RESULT_AVAILABLE = 0x8867;
+  PROGRAM_SEPARABLE = 0x8258;
+  ACTIVE_PROGRAM = 0x8259;
+  PROGRAM_PIPELINE_BINDING = 0x825A;
+  VERTEX_SHADER_BIT = 0x00000001;
+  FRAGMENT_SHADER_BIT = 0x00000002;
+  ALL_SHADER_BITS = 0xFFFFFFFF;
   ARRAY_BUFFER = 0x8892;
   ELEMENT_ARRAY_BUFFER = 0x8893;
   COPY_READ_BUFFER = 0x8F36;
   COPY_WRITE_BUFFER = 0x8F37;
   PIXEL_PACK_BUFFER = 0x88EB;
   PIXEL_UNPACK_BUFFER = 0x88EC;
+  PACK_ALIGNMENT = 0x0D05;
+  UNPACK_ALIGNMENT = 0x0CF5;
+  UNPACK_ROW_LENGTH = 0x0CF2;
+  UNPACK_SKIP_ROWS = 0x0CF3;
+  UNPACK_SKIP_PIXELS = 0x0CF4;
+  UNPACK_FLIP_Y_WEBGL = 0x9240;
+  UNPACK_PREMULTIPLY_ALPHA_WEBGL = 0x9241;
+  UNPACK_COLORSPACE_CONVERSION_WEBGL = 0x9243;
   UNIFORM_BUFFER = 0x8A11;
+  ACTIVE_UNIFORM_BLOCKS = 0x8A36;
+  UNIFORM_BLOCK_BINDING = 0x8A3F;
+  UNIFORM_BLOCK_DATA_SIZE = 0x8A40;
+  UNIFORM_BLOCK_ACTIVE_UNIFORMS = 0x8A42;
+  UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES = 0x8A43;
+  UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER = 0x8A44;
+  UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER = 0x8A46;
   TRANSFORM_FEEDBACK_BUFFER = 0x8C8E;
   TRANSFORM_FEEDBACK_BUFFER_BINDING = 0x8C8F;
   TRANSFORM_FEEDBACK_BUFFER_START = 0x8C84;
@@ -119,6 +242,9 @@ export class WasmWebGL2RenderingContext {
   INT = 0x1404;
   UNSIGNED_INT = 0x1405;
   FLOAT = 0x1406;
+  HALF_FLOAT = 0x140B;
+  INT_2_10_10_10_REV = 0x8D9F;
+  UNSIGNED_INT_2_10_10_10_REV = 0x8368;
   FLOAT_VEC2 = 0x8B50;
   FLOAT_VEC3 = 0x8B51;
   FLOAT_VEC4 = 0x8B52;
@@ -150,8 +276,17 @@ export class WasmWebGL2RenderingContext {
   ACTIVE_ATTRIBUTES = 0x8B89;
   VIEWPORT = 0x0BA2;
   COLOR_CLEAR_VALUE = 0x0C22;
+  ALIASED_POINT_SIZE_RANGE = 0x846D;
   COLOR_WRITEMASK = 0x0C23;
   DEPTH_WRITEMASK = 0x0B72;
+  DEPTH_CLEAR_VALUE = 0x0B73;
+  ALPHA_BITS = 0x0D55;
+  DEPTH_BITS = 0x0D56;
+  STENCIL_BITS = 0x0D57;
+  SAMPLES = 0x80A9;
+  SAMPLE_BUFFERS = 0x80A8;
+  SAMPLE_COVERAGE_VALUE = 0x80AA;
+  SAMPLE_COVERAGE_INVERT = 0x80AB;
   STENCIL_WRITEMASK = 0x0B98;
   STENCIL_BACK_WRITEMASK = 0x8CA5;
 
@@ -171,14 +306,47 @@ export class WasmWebGL2RenderingContext {
 
   BUFFER_SIZE = 0x8764;
   MAX_VERTEX_ATTRIBS = 0x8869;
+  MAX_VARYING_VECTORS = 0x8DFC;
+  MAX_VARYING_COMPONENTS = 0x8B4B;
+  MAX_VERTEX_UNIFORM_VECTORS = 0x8DFB;
+  MAX_FRAGMENT_UNIFORM_VECTORS = 0x8DFD;
+  MAX_TEXTURE_SIZE = 0x0D33;
+  MAX_RENDERBUFFER_SIZE = 0x84E8;
   NO_ERROR = 0;
   INVALID_ENUM = 0x0500;
   INVALID_VALUE = 0x0501;
   INVALID_OPERATION = 0x0502;
   OUT_OF_MEMORY = 0x0505;
+  INVALID_FRAMEBUFFER_OPERATION = 0x0506;
 
   ZERO = 0;
   ONE = 1;
+  SRC_COLOR = 0x0300;
+  ONE_MINUS_SRC_COLOR = 0x0301;
+  SRC_ALPHA = 0x0302;
+  ONE_MINUS_SRC_ALPHA = 0x0303;
+  DST_ALPHA = 0x0304;
+  ONE_MINUS_DST_ALPHA = 0x0305;
+  DST_COLOR = 0x0306;
+  ONE_MINUS_DST_COLOR = 0x0307;
+  SRC_ALPHA_SATURATE = 0x0308;
+  CONSTANT_COLOR = 0x8001;
+  ONE_MINUS_CONSTANT_COLOR = 0x8002;
+  CONSTANT_ALPHA = 0x8003;
+  ONE_MINUS_CONSTANT_ALPHA = 0x8004;
+  FUNC_ADD = 0x8006;
+  FUNC_SUBTRACT = 0x800A;
+  FUNC_REVERSE_SUBTRACT = 0x800B;
+  MIN = 0x8007;
+  MAX = 0x8008;
+  BLEND_COLOR = 0x8005;
+  BLEND_EQUATION = 0x8009;
+  BLEND_EQUATION_RGB = 0x8009;
+  BLEND_EQUATION_ALPHA = 0x883D;
+  BLEND_DST_RGB = 0x80C8;
+  BLEND_SRC_RGB = 0x80C9;
+  BLEND_DST_ALPHA = 0x80CA;
+  BLEND_SRC_ALPHA = 0x80CB;
 
   CURRENT_VERTEX_ATTRIB = 0x8626;
   VERTEX_ATTRIB_ARRAY_ENABLED = 0x8622;
@@ -220,7 +388,10 @@ export class WasmWebGL2RenderingContext {
   RENDERBUFFER_BINDING = 0x8CA7;
 
   DEPTH_COMPONENT16 = 0x81A5;
+  DEPTH_COMPONENT24 = 0x81A6;
+  DEPTH_COMPONENT32F = 0x8CAC;
   DEPTH_STENCIL = 0x84F9;
+  DEPTH24_STENCIL8 = 0x88F0;
   RGBA4 = 0x8056;
   RGB565 = 0x8D62;
   RGB5_A1 = 0x8057;
@@ -303,7 +474,12 @@ export class WasmWebGL2RenderingContext {
   TEXTURE_WRAP_R = 0x8072;
   TEXTURE_MAG_FILTER = 0x2800;
   TEXTURE_MIN_FILTER = 0x2801;
+  TEXTURE_LOD_BIAS = 0x8501;
   RGBA = 0x1908;
+  RGB = 0x1907;
+  // Host frame layouts accepted by texImageExternal (FourCC codes)
+  EXTERNAL_FORMAT_I420 = 0x30323449;
+  EXTERNAL_FORMAT_NV12 = 0x3231564E;
   RED = 0x1903;
   RG = 0x8227;
   UNSIGNED_BYTE = 0x1401;
@@ -325,11 +501,12 @@ export class WasmWebGL2RenderingContext {
    *  width: number,
    *  height: number,
    *  debugShaders: boolean,
+   *  webgl1?: boolean,
    *  sharedTable: any,
    *  tableAllocator: any
    * }} options
    */
-  constructor({ instance, ctxHandle, width, height, debugShaders = false, sharedTable = null, tableAllocator = null, turboGlobals = null }) {
+  constructor({ instance, ctxHandle, width, height, debugShaders = false, webgl1 = false, sharedTable = null, tableAllocator = null, turboGlobals = null }) {
     this._instance = instance;
     this._ctxHandle = ctxHandle;
     this._destroyed = false;
@@ -337,6 +514,13 @@ export class WasmWebGL2RenderingContext {
     this._currentProgram = null;
     // Explicit booleans for clarity
     this._debugShaders = !!debugShaders;
+    this._webgl1 = !!webgl1;
+    if (this._webgl1) {
+      // Shadow the prototype methods so feature detection sees WebGL1
+      for (const name of WEBGL2_ONLY_METHODS) {
+        this[name] = undefined;
+      }
+    }
     this._drawingBufferWidth = width;
     this._drawingBufferHeight = height;
     this._sharedTable = sharedTable;
@@ -345,6 +529,19 @@ export class WasmWebGL2RenderingContext {
     // TODO: potentially retrieve those one demand from the main WASM module when shader WASM modules are initialised
     this._turboGlobals = turboGlobals;
 
+    /** Staging allocation reused by texImageExternal, freed with the context. */
+    this._externalStaging = null;
+
+    /** Words captured since beginCommandRecording(), or null. */
+    this._commandWords = null;
+    /** Staging allocation reused by drawPacket, freed with the context. */
+    this._packetStaging = null;
+    /** Debug stub options set by setDebugStubOptions(), or null. */
+    this._debugStubOptions = null;
+    /** Shader instances by module hash, least recently used first. */
+    this._shaderPool = new Map();
+    this._shaderPoolStats = { hits: 0, misses: 0 };
+
     /** @type {Map<number, WasmWebGLTexture>} */
     this._textureHandles = new Map();
     /** @type {Map<number, WasmWebGLFramebuffer>} */
@@ -367,6 +564,8 @@ export class WasmWebGL2RenderingContext {
     this._syncHandles = new Map();
     /** @type {Map<number, WasmWebGLTransformFeedback>} */
     this._tfHandles = new Map();
+    /** @type {Map<number, WasmWebGLProgramPipeline>} */
+    this._pipelineHandles = new Map();
 
     WasmWebGL2RenderingContext._contexts.set(this._ctxHandle, this);
   }
@@ -391,6 +590,25 @@ export class WasmWebGL2RenderingContext {
     this._drawingBufferHeight = height;
   }
 
+  // Present the drawing buffer, as a browser does at the end of an animation
+  // frame. Without preserveDrawingBuffer the drawing buffer is cleared after
+  // presenting. Returns the frame as the canvas compositor sees it: RGBA8
+  // rows bottom to top, premultiplied by alpha, and opaque without alpha.
+  present() {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_present !== 'function') {
+      throw new Error('wasm_ctx_present not found');
+    }
+    const ptr = ex.wasm_ctx_present(this._ctxHandle);
+    if (ptr === 0) {
+      const msg = readErrorMessage(this._instance);
+      throw new Error(`present failed: ${msg}`);
+    }
+    const len = new DataView(ex.memory.buffer).getUint32(ptr - 16, true);
+    return new Uint8Array(ex.memory.buffer.slice(ptr, ptr + len));
+  }
+
   // Set the viewport for rendering
   viewport(x, y, width, height) {
     this._assertNotDestroyed();
@@ -408,6 +626,7 @@ export class WasmWebGL2RenderingContext {
   destroy() {
     if (this._destroyed) return;
     WasmWebGL2RenderingContext._contexts.delete(this._ctxHandle);
+    this._shaderPool.clear();
     const ex = this._instance.exports;
     if (ex && typeof ex.wasm_destroy_context === 'function') {
       const code = ex.wasm_destroy_context(this._ctxHandle);
@@ -416,6 +635,26 @@ export class WasmWebGL2RenderingContext {
     this._destroyed = true;
   }
 
+  /**
+   * Allocate transient staging memory from the per-frame arena.
+   * The pointer is only valid during the call that allocated it: each caller
+   * copies its data in, passes it to one export and resets the arena before
+   * returning, so the arena never outgrows a single call. Draws reset it too.
+   * It must not be freed.
+   * @param {number} len
+   * @returns {number}
+   */
+  _frameAlloc(len) {
+    return this._instance.exports.wasm_frame_alloc(len >>> 0);
+  }
+
+  /**
+   * Release everything handed out by _frameAlloc since the previous reset.
+   */
+  _frameReset() {
+    this._instance.exports.wasm_frame_reset();
+  }
+
   _assertNotDestroyed() {
     if (this._destroyed) throw new Error('context has been destroyed');
   }
@@ -476,18 +715,17 @@ export class WasmWebGL2RenderingContext {
       throw new Error('wasm_ctx_tex_image_2d not found');
     }
 
-    let data = pixels;
-    if (!data) {
-      data = new Uint8Array(width * height * getBPP(internalFormat));
-    } else if (ArrayBuffer.isView(data)) {
+    // Without pixels the upload is empty, which leaves the level zero-filled
+    let data = pixels || new Uint8Array(0);
+    if (ArrayBuffer.isView(data)) {
       data = new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
     } else if (data instanceof ArrayBuffer) {
       data = new Uint8Array(data);
     }
 
     const len = data.length;
-    const ptr = ex.wasm_alloc(len);
-    if (ptr === 0) throw new Error('Failed to allocate memory for pixel data');
+    const ptr = len === 0 ? 0 : ex.wasm_ctx_alloc(this._ctxHandle, len);
+    if (len !== 0 && ptr === 0) throw new Error('Failed to allocate memory for pixel data');
 
     try {
       const mem = new Uint8Array(ex.memory.buffer);
@@ -506,6 +744,7 @@ export class WasmWebGL2RenderingContext {
         ptr >>> 0,
         len >>> 0
       );
+      if (code === 5) return; // ERR_GL
       _checkErr(code, this._instance);
     } finally {
       ex.wasm_free(ptr);
@@ -519,18 +758,17 @@ export class WasmWebGL2RenderingContext {
       throw new Error('wasm_ctx_tex_image_3d not found');
     }
 
-    let data = pixels;
-    if (!data) {
-      data = new Uint8Array(width * height * depth * getBPP(internalFormat));
-    } else if (ArrayBuffer.isView(data)) {
+    // Without pixels the upload is empty, which leaves the level zero-filled
+    let data = pixels || new Uint8Array(0);
+    if (ArrayBuffer.isView(data)) {
       data = new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
     } else if (data instanceof ArrayBuffer) {
       data = new Uint8Array(data);
     }
 
     const len = data.length;
-    const ptr = ex.wasm_alloc(len);
-    if (ptr === 0) throw new Error('Failed to allocate memory for pixel data');
+    const ptr = len === 0 ? 0 : ex.wasm_ctx_alloc(this._ctxHandle, len);
+    if (len !== 0 && ptr === 0) throw new Error('Failed to allocate memory for pixel data');
 
     try {
       const mem = new Uint8Array(ex.memory.buffer);
@@ -550,6 +788,7 @@ export class WasmWebGL2RenderingContext {
         ptr >>> 0,
         len >>> 0
       );
+      if (code === 5) return; // ERR_GL
       _checkErr(code, this._instance);
     } finally {
       ex.wasm_free(ptr);
@@ -633,6 +872,7 @@ export class WasmWebGL2RenderingContext {
       texHandle,
       level >>> 0
     );
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -693,6 +933,7 @@ export class WasmWebGL2RenderingContext {
       throw new Error('wasm_ctx_renderbuffer_storage not found');
     }
     const code = ex.wasm_ctx_renderbuffer_storage(this._ctxHandle, target >>> 0, internalFormat >>> 0, width | 0, height | 0);
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -710,9 +951,244 @@ export class WasmWebGL2RenderingContext {
       renderbuffertarget >>> 0,
       rbHandle
     );
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * 64-bit FNV-1a hash of the read framebuffer's RGBA8 contents and size, as
+   * 16 hex digits. Cheap to compare against a golden value before diffing
+   * images.
+   */
+  framebufferHash() {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_framebuffer_hash !== 'function') {
+      throw new Error('wasm_ctx_framebuffer_hash not found');
+    }
+    const hash = BigInt.asUintN(64, BigInt(ex.wasm_ctx_framebuffer_hash(this._ctxHandle)));
+    if (hash === 0n) {
+      const msg = readErrorMessage(this._instance);
+      throw new Error(`framebufferHash failed: ${msg}`);
+    }
+    return hash.toString(16).padStart(16, '0');
+  }
+
+  /**
+   * Read a texture level without binding it: its size, internal format and
+   * texels in their storage format, tightly packed, first uploaded row first.
+   * @param {WebGLTexture} texture
+   * @param {number} [level]
+   * @param {number} [layer] layer of a 3D or array texture
+   * @returns {{ width: number, height: number, internalFormat: number, data: Uint8Array }}
+   */
+  getTexturePixels(texture, level = 0, layer = 0) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_texture_pixels !== 'function') {
+      throw new Error('wasm_ctx_get_texture_pixels not found');
+    }
+    const handle = texture && typeof texture === 'object' && typeof texture._handle === 'number' ? texture._handle : (texture >>> 0);
+    const ptr = ex.wasm_ctx_get_texture_pixels(this._ctxHandle, handle, level >>> 0, layer >>> 0);
+    if (ptr === 0) {
+      const msg = readErrorMessage(this._instance);
+      throw new Error(`getTexturePixels failed: ${msg}`);
+    }
+    const dv = new DataView(ex.memory.buffer);
+    const len = dv.getUint32(ptr - 16, true);
+    return {
+      width: dv.getUint32(ptr, true),
+      height: dv.getUint32(ptr + 4, true),
+      internalFormat: dv.getUint32(ptr + 8, true),
+      data: new Uint8Array(ex.memory.buffer, ptr + 12, len - 12).slice(),
+    };
+  }
+
+  /**
+   * Read a renderbuffer's depth and stencil planes without binding it, one
+   * value per pixel, bottom row first. A plane the format lacks is empty.
+   * @param {WebGLRenderbuffer} renderbuffer
+   * @returns {{ width: number, height: number, internalFormat: number, depth: Float32Array, stencil: Uint8Array }}
+   */
+  getRenderbufferPixels(renderbuffer) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_renderbuffer_pixels !== 'function') {
+      throw new Error('wasm_ctx_get_renderbuffer_pixels not found');
+    }
+    const handle = renderbuffer && typeof renderbuffer === 'object' && typeof renderbuffer._handle === 'number' ? renderbuffer._handle : (renderbuffer >>> 0);
+    const ptr = ex.wasm_ctx_get_renderbuffer_pixels(this._ctxHandle, handle);
+    if (ptr === 0) {
+      const msg = readErrorMessage(this._instance);
+      throw new Error(`getRenderbufferPixels failed: ${msg}`);
+    }
+    const dv = new DataView(ex.memory.buffer);
+    const len = dv.getUint32(ptr - 16, true);
+    const depthCount = dv.getUint32(ptr + 12, true);
+    const depthEnd = ptr + 16 + depthCount * 4;
+    const depth = new Float32Array(depthCount);
+    for (let i = 0; i < depthCount; i++) depth[i] = dv.getFloat32(ptr + 16 + i * 4, true);
+    return {
+      width: dv.getUint32(ptr, true),
+      height: dv.getUint32(ptr + 4, true),
+      internalFormat: dv.getUint32(ptr + 8, true),
+      depth,
+      stencil: new Uint8Array(ex.memory.buffer, depthEnd, ptr + len - depthEnd).slice(),
+    };
+  }
+
+  /**
+   * Read a buffer's contents without binding it.
+   * @param {WebGLBuffer} buffer
+   * @param {number} [offset]
+   * @param {number} [length] bytes to read; the rest of the buffer by default
+   * @returns {Uint8Array}
+   */
+  getBufferData(buffer, offset = 0, length = 0xFFFFFFFF) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_buffer_data !== 'function') {
+      throw new Error('wasm_ctx_get_buffer_data not found');
+    }
+    const handle = buffer && typeof buffer === 'object' && typeof buffer._handle === 'number' ? buffer._handle : (buffer >>> 0);
+    const ptr = ex.wasm_ctx_get_buffer_data(this._ctxHandle, handle, offset >>> 0, length >>> 0);
+    if (ptr === 0) {
+      const msg = readErrorMessage(this._instance);
+      throw new Error(`getBufferData failed: ${msg}`);
+    }
+    const len = new DataView(ex.memory.buffer).getUint32(ptr - 16, true);
+    return new Uint8Array(ex.memory.buffer, ptr, len).slice();
+  }
+
+  /**
+   * Start capturing calls into a command buffer. Until endCommandRecording(),
+   * the methods listed in COMMAND_ENCODERS append to the buffer instead of
+   * calling into WASM; everything else still runs immediately.
+   */
+  beginCommandRecording() {
+    this._assertNotDestroyed();
+    if (this._commandWords) throw new Error('already recording a command buffer');
+    const words = [];
+    this._commandWords = words;
+    for (const [name, [opcode, encode]] of Object.entries(COMMAND_ENCODERS)) {
+      if (this._webgl1 && WEBGL2_ONLY_METHODS.includes(name)) continue;
+      this[name] = (...args) => {
+        const argWords = encode(this, ...args);
+        words.push(opcode | (argWords.length << 16), ...argWords);
+      };
+    }
+  }
+
+  /**
+   * Stop capturing and return the recorded commands. The buffer can be
+   * passed to playCommandBuffer() right away or kept and replayed later.
+   * @returns {Uint32Array}
+   */
+  endCommandRecording() {
+    const words = this._commandWords;
+    if (!words) throw new Error('not recording a command buffer');
+    this._commandWords = null;
+    for (const name of Object.keys(COMMAND_ENCODERS)) {
+      if (this._webgl1 && WEBGL2_ONLY_METHODS.includes(name)) continue;
+      delete this[name];
+    }
+    return Uint32Array.from(words);
+  }
+
+  /**
+   * Run a recorded command buffer in a single WASM call. GL errors are
+   * recorded for getError() and do not stop playback.
+   * @param {Uint32Array} commands
+   */
+  playCommandBuffer(commands) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_play_command_buffer !== 'function') {
+      throw new Error('wasm_ctx_play_command_buffer not found');
+    }
+    const len = commands.byteLength;
+    if (len === 0) return;
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
+    if (ptr === 0) throw new Error('Failed to allocate memory for command buffer');
+    let code;
+    try {
+      new Uint32Array(ex.memory.buffer, ptr, commands.length).set(commands);
+      code = this._runShaders(() => ex.wasm_ctx_play_command_buffer(this._ctxHandle, ptr >>> 0, len >>> 0));
+    } finally {
+      ex.wasm_free(ptr);
+    }
+    this._frameReset();
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * Bind, upload and draw in a single call. `packet` is a Uint32Array laid
+   * out as described in src/webgl2_context/draw_packet.rs, typically built
+   * by the generated harness's ShaderProgram.packDraw().
+   * @param {Uint32Array} packet
+   */
+  drawPacket(packet) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_draw_packet !== 'function') {
+      throw new Error('wasm_ctx_draw_packet not found');
+    }
+    const len = packet.byteLength;
+    if (!this._packetStaging || this._packetStaging.len < len) {
+      if (this._packetStaging) ex.wasm_free(this._packetStaging.ptr);
+      this._packetStaging = null;
+      const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
+      if (ptr === 0) throw new Error('Failed to allocate memory for draw packet');
+      this._packetStaging = { ptr, len };
+    }
+    const { ptr } = this._packetStaging;
+    new Uint32Array(ex.memory.buffer, ptr, packet.length).set(packet);
+
+    // The frame arena is reset on the WASM side, saving a second call
+    const code = this._runShaders(() => ex.wasm_ctx_draw_packet(this._ctxHandle, ptr >>> 0, len >>> 0));
     _checkErr(code, this._instance);
   }
 
+  /**
+   * Call `call`, an export that may run shaders, and return its code. WASM
+   * without exception handling cannot catch a shader trap (an out of bounds
+   * access, an integer division by zero), so it unwinds out of the module:
+   * catch it here, release the state the aborted call held and report the
+   * trap with its GLSL line as INVALID_OPERATION (ERR_GL). The line needs a
+   * `debug: 'shaders'` context and a V8 stack trace (see trapFrameOffsets);
+   * other engines report the trap without one. Traps outside shaders are
+   * rethrown.
+   * @param {() => number} call
+   * @returns {number}
+   */
+  _runShaders(call) {
+    const ex = this._instance.exports;
+    const stackPointer = ex.__stack_pointer;
+    const sp = stackPointer?.value;
+    try {
+      return call();
+    } catch (e) {
+      if (!(e instanceof WebAssembly.RuntimeError) || typeof ex.wasm_recover_shader_trap !== 'function') throw e;
+      // The aborted call never popped its stack frames
+      if (stackPointer) stackPointer.value = sp;
+      const stage = ex.wasm_recover_shader_trap();
+      if (stage === 0) throw e;
+      const offsets = trapFrameOffsets(e);
+      const message = new TextEncoder().encode(e.message);
+      // wasm_ctx_alloc returns 0 for an empty allocation
+      const ptr = ex.wasm_ctx_alloc(this._ctxHandle, Math.max(1, offsets.length * 4 + message.length));
+      if (ptr === 0) throw e;
+      try {
+        new Uint32Array(ex.memory.buffer, ptr, offsets.length).set(offsets);
+        new Uint8Array(ex.memory.buffer).set(message, ptr + offsets.length * 4);
+        return ex.wasm_ctx_report_shader_trap(this._ctxHandle, stage, ptr, offsets.length,
+          ptr + offsets.length * 4, message.length);
+      } finally {
+        ex.wasm_free(ptr);
+      }
+    }
+  }
+
   readPixels(x, y, width, height, format, type_, out) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
@@ -776,7 +1252,7 @@ export class WasmWebGL2RenderingContext {
     const sourceStr = String(source);
     const bytes = new TextEncoder().encode(sourceStr);
     const len = bytes.length;
-    const ptr = ex.wasm_alloc(len);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
     if (ptr === 0) throw new Error('Failed to allocate memory for shaderSource');
 
     try {
@@ -840,7 +1316,17 @@ export class WasmWebGL2RenderingContext {
     _checkErr(code, this._instance);
   }
 
-  detachShader(program, shader) { this._assertNotDestroyed(); throw new Error('not implemented'); }
+  detachShader(program, shader) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_detach_shader !== 'function') {
+      throw new Error('wasm_ctx_detach_shader not found');
+    }
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const shaderHandle = shader && typeof shader === 'object' && typeof shader._handle === 'number' ? shader._handle : (shader >>> 0);
+    const code = ex.wasm_ctx_detach_shader(this._ctxHandle, programHandle, shaderHandle);
+    _checkErr(code, this._instance);
+  }
 
   getActiveUniform(program, index) {
     this._assertNotDestroyed();
@@ -905,22 +1391,65 @@ export class WasmWebGL2RenderingContext {
     }
   }
 
+  /**
+   * Recompile a linked program's attached shaders from new sources and relink
+   * it in place, keeping the program and shader objects, uniform values and
+   * attribute bindings. If either source fails to compile, or the new shaders
+   * fail to link, the previous shaders stay in use and an error is thrown.
+   * @param {WebGLProgram} program
+   * @param {string} vsSource
+   * @param {string} fsSource
+   * @returns {boolean} true if attribute and uniform locations are unchanged,
+   *   false if locations cached from the old program must be looked up again
+   */
+  reloadProgram(program, vsSource, fsSource) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_reload_program !== 'function') {
+      throw new Error('wasm_ctx_reload_program not found');
+    }
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const vsBytes = new TextEncoder().encode(String(vsSource));
+    const fsBytes = new TextEncoder().encode(String(fsSource));
+    const vsPtr = ex.wasm_ctx_alloc(this._ctxHandle, vsBytes.length);
+    const fsPtr = ex.wasm_ctx_alloc(this._ctxHandle, fsBytes.length);
+    let code;
+    try {
+      if (vsPtr === 0 || fsPtr === 0) throw new Error('Failed to allocate memory for reloadProgram');
+      const mem = new Uint8Array(ex.memory.buffer);
+      mem.set(vsBytes, vsPtr);
+      mem.set(fsBytes, fsPtr);
+      code = ex.wasm_ctx_reload_program(this._ctxHandle, programHandle, vsPtr, vsBytes.length, fsPtr, fsBytes.length);
+    } finally {
+      if (vsPtr !== 0) ex.wasm_free(vsPtr);
+      if (fsPtr !== 0) ex.wasm_free(fsPtr);
+    }
+    if (code !== RELOAD_INTERFACE_CHANGED) _checkErr(code, this._instance);
+
+    if (program && typeof program === 'object') {
+      this._instantiateProgramShaders(program);
+    }
+    return code === ERR_OK;
+  }
+
   _instantiateProgramShaders(program) {
     const vsWasm = this.getProgramWasm(program, this.VERTEX_SHADER);
     const fsWasm = this.getProgramWasm(program, this.FRAGMENT_SHADER);
 
-    if (!vsWasm || !fsWasm) {
+    // A separable program may have only one of the stages
+    if (!vsWasm && !fsWasm) {
       return;
     }
 
-    // Allocate table slots for both shaders
-    const vsIdx = this._tableAllocator ? this._tableAllocator.allocate() : null;
-    const fsIdx = this._tableAllocator ? this._tableAllocator.allocate() : null;
+    // Allocate table slots for the linked shaders
+    const vsIdx = vsWasm && this._tableAllocator ? this._tableAllocator.allocate() : null;
+    const fsIdx = fsWasm && this._tableAllocator ? this._tableAllocator.allocate() : null;
 
     const createDebugEnv = (type, instanceRef) => {
       if (!this._debugShaders) return {};
 
-      const stubCode = this.getProgramDebugStub(program, type);
+      const options = this._debugStubOptions;
+      const stubCode = this.getProgramDebugStub(program, type, options);
       if (!stubCode) return {};
 
       // // Add sourceURL for debugging
@@ -947,7 +1476,9 @@ export class WasmWebGL2RenderingContext {
               go: () => {
                 // Trampoline logic would go here
                 // For now we rely on WASM calling the function after debug_step returns
-              }
+              },
+              breakpoints: options && options.breakpoints,
+              watch: (name, watchLine) => options?.onWatch?.(name, watchLine, type),
             };
             try {
               func.call(ctx);
@@ -959,24 +1490,7 @@ export class WasmWebGL2RenderingContext {
       };
     };
 
-    let vsModule;
-    vsModule = new WebAssembly.Module(vsWasm);
-    const vsInstanceRef = { current: null };
-    const vsDebugEnv = createDebugEnv(this.VERTEX_SHADER, vsInstanceRef);
-
-    const env = {
-      memory: this._instance.exports.memory,
-      __indirect_function_table: this._sharedTable,
-      ACTIVE_ATTR_PTR: this._turboGlobals.ACTIVE_ATTR_PTR,
-      ACTIVE_UNIFORM_PTR: this._turboGlobals.ACTIVE_UNIFORM_PTR,
-      ACTIVE_VARYING_PTR: this._turboGlobals.ACTIVE_VARYING_PTR,
-      ACTIVE_PRIVATE_PTR: this._turboGlobals.ACTIVE_PRIVATE_PTR,
-      ACTIVE_TEXTURE_PTR: this._turboGlobals.ACTIVE_TEXTURE_PTR,
-      ACTIVE_FRAME_SP: this._turboGlobals.ACTIVE_FRAME_SP,
-      ...vsDebugEnv
-    };
-
-    // Add math builtins from renderer (skipping host)
+    // Math builtins from renderer (skipping host)
     const mathFuncs = [
       'gl_sin', 'gl_cos', 'gl_tan', 'gl_asin', 'gl_acos', 'gl_atan', 'gl_atan2',
       'gl_exp', 'gl_exp2', 'gl_log', 'gl_log2', 'gl_pow',
@@ -985,168 +1499,592 @@ export class WasmWebGL2RenderingContext {
       'gl_debug4'
 
     ];
-    for (const name of mathFuncs) {
-      if (this._instance.exports[name]) {
-        env[name] = this._instance.exports[name];
-      }
-    }
 
-    program._vsInstance = new WebAssembly.Instance(vsModule, {
-      env
-    });
-    vsInstanceRef.current = program._vsInstance;
+    if (vsWasm) {
+      const vsInstanceRef = { current: null };
+      const vsDebugEnv = createDebugEnv(this.VERTEX_SHADER, vsInstanceRef);
+
+      const env = {
+        memory: this._instance.exports.memory,
+        __indirect_function_table: this._sharedTable,
+        ACTIVE_ATTR_PTR: this._turboGlobals.ACTIVE_ATTR_PTR,
+        ACTIVE_UNIFORM_PTR: this._turboGlobals.ACTIVE_UNIFORM_PTR,
+        ACTIVE_VARYING_PTR: this._turboGlobals.ACTIVE_VARYING_PTR,
+        ACTIVE_PRIVATE_PTR: this._turboGlobals.ACTIVE_PRIVATE_PTR,
+        ACTIVE_TEXTURE_PTR: this._turboGlobals.ACTIVE_TEXTURE_PTR,
+        ACTIVE_FRAME_SP: this._turboGlobals.ACTIVE_FRAME_SP,
+        ...vsDebugEnv
+      };
 
-    // Register in table
-    if (this._sharedTable && vsIdx !== null && program._vsInstance.exports.main) {
-      this._sharedTable.set(vsIdx, program._vsInstance.exports.main);
-      program._vsTableIndex = vsIdx;
-    }
+      // Add math builtins
+      for (const name of mathFuncs) {
+        if (this._instance.exports[name]) {
+          env[name] = this._instance.exports[name];
+        }
+      }
 
-    let fsModule;
-    // Dump WASM to disk when debug_shaders is enabled to aid diagnostics
-    if (this._debugShaders) {
-      try {
-        require('fs').writeFileSync('test_debug/failing_fragment.wasm', Buffer.from(fsWasm));
-      } catch (e) {
-        // Best-effort; ignore write failures in constrained environments
+      program._vsInstance = this._shaderInstance(vsWasm, env);
+      vsInstanceRef.current = program._vsInstance;
+      checkShaderAbi(program._vsInstance);
+
+      // Register in table
+      if (this._sharedTable && vsIdx !== null && program._vsInstance.exports.main) {
+        this._sharedTable.set(vsIdx, program._vsInstance.exports.main);
+        program._vsTableIndex = vsIdx;
       }
     }
-    if (this._debug) {
-        console.log("Compiling FS Module...");
-    }
-    fsModule = new WebAssembly.Module(fsWasm);
 
-    const fsInstanceRef = { current: null };
-    const fsDebugEnv = createDebugEnv(this.FRAGMENT_SHADER, fsInstanceRef);
+    if (fsWasm) {
+      // Dump WASM to disk when debug_shaders is enabled to aid diagnostics
+      if (this._debugShaders) {
+        try {
+          require('fs').writeFileSync('test_debug/failing_fragment.wasm', Buffer.from(fsWasm));
+        } catch (e) {
+          // Best-effort; ignore write failures in constrained environments
+        }
+      }
+      if (this._debug) {
+          console.log("Compiling FS Module...");
+      }
 
-    const fsEnv = {
-      memory: this._instance.exports.memory,
-      __indirect_function_table: this._sharedTable,
-      ACTIVE_ATTR_PTR: this._turboGlobals.ACTIVE_ATTR_PTR,
-      ACTIVE_UNIFORM_PTR: this._turboGlobals.ACTIVE_UNIFORM_PTR,
-      ACTIVE_VARYING_PTR: this._turboGlobals.ACTIVE_VARYING_PTR,
-      ACTIVE_PRIVATE_PTR: this._turboGlobals.ACTIVE_PRIVATE_PTR,
-      ACTIVE_TEXTURE_PTR: this._turboGlobals.ACTIVE_TEXTURE_PTR,
-      ACTIVE_FRAME_SP: this._turboGlobals.ACTIVE_FRAME_SP,
-      ...fsDebugEnv
-    };
+      const fsInstanceRef = { current: null };
+      const fsDebugEnv = createDebugEnv(this.FRAGMENT_SHADER, fsInstanceRef);
+
+      const fsEnv = {
+        memory: this._instance.exports.memory,
+        __indirect_function_table: this._sharedTable,
+        ACTIVE_ATTR_PTR: this._turboGlobals.ACTIVE_ATTR_PTR,
+        ACTIVE_UNIFORM_PTR: this._turboGlobals.ACTIVE_UNIFORM_PTR,
+        ACTIVE_VARYING_PTR: this._turboGlobals.ACTIVE_VARYING_PTR,
+        ACTIVE_PRIVATE_PTR: this._turboGlobals.ACTIVE_PRIVATE_PTR,
+        ACTIVE_TEXTURE_PTR: this._turboGlobals.ACTIVE_TEXTURE_PTR,
+        ACTIVE_FRAME_SP: this._turboGlobals.ACTIVE_FRAME_SP,
+        ...fsDebugEnv
+      };
 
-    for (const name of mathFuncs) {
-      if (this._instance.exports[name]) {
-        fsEnv[name] = this._instance.exports[name];
+      for (const name of mathFuncs) {
+        if (this._instance.exports[name]) {
+          fsEnv[name] = this._instance.exports[name];
+        }
       }
-    }
 
-    if (!fsEnv.gl_debug4) fsEnv.gl_debug4 = (a, b, c, d) => {};
-    if (!fsEnv.gl_inverse_mat2) fsEnv.gl_inverse_mat2 = (in_ptr, out_ptr) => {};
-    if (!fsEnv.gl_inverse_mat3) fsEnv.gl_inverse_mat3 = (in_ptr, out_ptr) => {};
+      if (!fsEnv.gl_debug4) fsEnv.gl_debug4 = (a, b, c, d) => {};
+      if (!fsEnv.gl_inverse_mat2) fsEnv.gl_inverse_mat2 = (in_ptr, out_ptr) => {};
+      if (!fsEnv.gl_inverse_mat3) fsEnv.gl_inverse_mat3 = (in_ptr, out_ptr) => {};
 
-    program._fsInstance = new WebAssembly.Instance(fsModule, {
-      env: fsEnv
-    });
-    fsInstanceRef.current = program._fsInstance;
+      program._fsInstance = this._shaderInstance(fsWasm, fsEnv);
+      fsInstanceRef.current = program._fsInstance;
+      checkShaderAbi(program._fsInstance);
 
-    // Register in table
-    if (this._sharedTable && fsIdx !== null && program._fsInstance.exports.main) {
-      this._sharedTable.set(fsIdx, program._fsInstance.exports.main);
-      program._fsTableIndex = fsIdx;
+      // Register in table
+      if (this._sharedTable && fsIdx !== null && program._fsInstance.exports.main) {
+        this._sharedTable.set(fsIdx, program._fsInstance.exports.main);
+        program._fsTableIndex = fsIdx;
+      }
     }
 
     // Notify Rust of table indices (requires Phase 4)
-    if (vsIdx !== null && fsIdx !== null) {
+    if (vsIdx !== null || fsIdx !== null) {
       const ex = this._instance.exports;
       if (ex.wasm_ctx_register_shader_indices) {
         ex.wasm_ctx_register_shader_indices(
           this._ctxHandle,
           program._handle,
-          vsIdx,
-          fsIdx
+          vsIdx ?? NO_SHADER_INDEX,
+          fsIdx ?? NO_SHADER_INDEX
         );
       }
     }
   }
 
-  getProgramDebugStub(program, shaderType) {
+  /**
+   * Instantiate a shader module, reusing the instance of an identical module
+   * linked earlier.
+   *
+   * The pool lives here in the JS host, not in a Rust shader runtime: the
+   * host instantiates shader modules at link time, and no wasmi runtime
+   * exists in this tree. Memory, the function table and the ACTIVE_* globals
+   * are all the context's, so one instance can back any number of programs
+   * as long as instantiating it has no other effect. A module with a data
+   * section writes linear memory when it is instantiated, so it is
+   * instantiated afresh each time and is neither pooled nor counted. Debug
+   * contexts wire per-program stubs into each instance and always
+   * instantiate afresh too.
+   * @param {Uint8Array} wasm
+   * @param {object} env
+   * @returns {WebAssembly.Instance}
+   */
+  _shaderInstance(wasm, env) {
+    if (this._debugShaders || hasDataSection(wasm)) {
+      return new WebAssembly.Instance(new WebAssembly.Module(wasm), { env });
+    }
+    const key = fnv1a(wasm);
+    const pooled = this._shaderPool.get(key);
+    if (pooled && bytesEqual(pooled.wasm, wasm)) {
+      // Move to the back so the least recently used module is evicted first
+      this._shaderPool.delete(key);
+      this._shaderPool.set(key, pooled);
+      this._shaderPoolStats.hits++;
+      return pooled.instance;
+    }
+    const instance = new WebAssembly.Instance(new WebAssembly.Module(wasm), { env });
+    this._shaderPool.delete(key);
+    this._shaderPool.set(key, { wasm, instance });
+    if (this._shaderPool.size > SHADER_POOL_SIZE) {
+      this._shaderPool.delete(this._shaderPool.keys().next().value);
+    }
+    this._shaderPoolStats.misses++;
+    return instance;
+  }
+
+  /**
+   * Shader instance pool counters: `hits` are links that reused an instance,
+   * `misses` links that compiled and instantiated a module, and `size` the
+   * number of modules currently pooled.
+   * @returns {{ hits: number, misses: number, size: number }}
+   */
+  getShaderPoolStats() {
+    this._assertNotDestroyed();
+    return { ...this._shaderPoolStats, size: this._shaderPool.size };
+  }
+
+  /**
+   * Drop pooled shader instances, so the next link of every program
+   * instantiates its modules afresh. Linked programs keep their instances.
+   */
+  clearShaderPool() {
+    this._assertNotDestroyed();
+    this._shaderPool.clear();
+  }
+
+  /**
+   * Options for the debug stubs of programs linked from now on (debug
+   * contexts only):
+   * - `embedSource` (default true): include the GLSL in the stub's source map
+   * - `breakpoints`: GLSL line numbers to break at; stubs check this set, so
+   *   lines can be added and removed while running
+   * - `watch`: variable names; `onWatch(name, line, shaderType)` is called
+   *   when a line mentioning one of them runs
+   * @param {{ embedSource?: boolean, breakpoints?: Iterable<number>, watch?: string[], onWatch?: Function } | null} options
+   */
+  setDebugStubOptions(options) {
+    this._assertNotDestroyed();
+    if (!options) {
+      this._debugStubOptions = null;
+      return;
+    }
+    this._debugStubOptions = {
+      embedSource: options.embedSource !== false,
+      breakpoints: options.breakpoints ? new Set(options.breakpoints) : null,
+      watch: options.watch ? Array.from(options.watch, String) : [],
+      onWatch: options.onWatch || null,
+    };
+  }
+
+  /**
+   * The JS debug stub of a program shader. `options` are as for
+   * setDebugStubOptions; without them this is the stub generated at link time.
+   */
+  getProgramDebugStub(program, shaderType, options = null) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
-    if (!ex || typeof ex.wasm_ctx_get_program_debug_stub !== 'function') {
+    if (!ex || typeof ex.wasm_ctx_get_program_debug_stub_with_options !== 'function') {
       return null;
     }
     const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
-    const len = ex.wasm_ctx_get_program_debug_stub(this._ctxHandle, programHandle, shaderType, 0, 0);
-    if (len === 0) return null;
 
-    const ptr = ex.wasm_alloc(len);
-    if (ptr === 0) return null;
+    let flags = 0;
+    if (options && options.embedSource === false) flags |= 0x1; // DEBUG_STUB_NO_SOURCE
+    if (options && options.breakpoints) flags |= 0x2; // DEBUG_STUB_BREAKPOINTS
+    const watch = new TextEncoder().encode(options && options.watch ? Array.from(options.watch).join(',') : '');
 
+    let watchPtr = 0;
+    if (watch.length > 0) {
+      watchPtr = ex.wasm_ctx_alloc(this._ctxHandle, watch.length);
+      if (watchPtr === 0) return null;
+      new Uint8Array(ex.memory.buffer).set(watch, watchPtr);
+    }
+    const getStub = (ptr, len) => ex.wasm_ctx_get_program_debug_stub_with_options(
+      this._ctxHandle, programHandle, shaderType, flags, watchPtr, watch.length, ptr, len);
+
+    let ptr = 0;
     try {
-      const actualLen = ex.wasm_ctx_get_program_debug_stub(this._ctxHandle, programHandle, shaderType, ptr, len);
+      const len = getStub(0, 0);
+      if (len === 0) return null;
+
+      ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
+      if (ptr === 0) return null;
+      const actualLen = getStub(ptr, len);
       const mem = new Uint8Array(ex.memory.buffer);
       const bytes = mem.subarray(ptr, ptr + actualLen);
       return new TextDecoder().decode(bytes);
     } finally {
-      ex.wasm_free(ptr);
+      if (ptr !== 0) ex.wasm_free(ptr);
+      if (watchPtr !== 0) ex.wasm_free(watchPtr);
     }
   }
 
-  deleteProgram(program) {
+  /**
+   * Per-line execution counts of a program shader, for contexts created
+   * with `coverage: true`. Counts accumulate across draws until
+   * `resetProgramCoverage` is called.
+   * @param {WebGLProgram} program
+   * @param {number} shaderType - VERTEX_SHADER or FRAGMENT_SHADER
+   * @returns {Map<number, number> | null} GLSL line -> execution count
+   */
+  getProgramCoverage(program, shaderType) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
-    if (!ex || typeof ex.wasm_ctx_delete_program !== 'function') {
-      throw new Error('wasm_ctx_delete_program not found');
+    if (!ex || typeof ex.wasm_ctx_get_program_coverage !== 'function') {
+      return null;
     }
     const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const len = ex.wasm_ctx_get_program_coverage(this._ctxHandle, programHandle, shaderType, 0, 0);
+    if (len === 0) return null;
 
-    // Table indices are now freed by Rust when the Program's refcount reaches zero.
-    // This allows bound programs to remain valid even if deleted by the user,
-    // as required by the WebGL specification.
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
+    if (ptr === 0) return null;
 
-    const code = ex.wasm_ctx_delete_program(this._ctxHandle, programHandle);
-    _checkErr(code, this._instance);
-    if (program && typeof program === 'object') {
-      try { program._handle = 0; program._deleted = true; } catch (e) { /* ignore */ }
+    let report;
+    try {
+      const actualLen = ex.wasm_ctx_get_program_coverage(this._ctxHandle, programHandle, shaderType, ptr, len);
+      const mem = new Uint8Array(ex.memory.buffer);
+      report = new TextDecoder().decode(mem.subarray(ptr, ptr + actualLen));
+    } finally {
+      ex.wasm_free(ptr);
     }
-  }
 
-  useProgram(program) {
-    this._assertNotDestroyed();
-    const ex = this._instance.exports;
-    if (!ex || typeof ex.wasm_ctx_use_program !== 'function') {
-      throw new Error('wasm_ctx_use_program not found');
+    const counts = new Map();
+    for (const entry of report.split('\n')) {
+      if (!entry) continue;
+      const [line, count] = entry.split(' ').map(Number);
+      counts.set(line, count);
     }
-    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
-    const code = ex.wasm_ctx_use_program(this._ctxHandle, programHandle);
-    _checkErr(code, this._instance);
-    this._currentProgram = program;
+    return counts;
   }
 
-  getShaderParameter(shader, pname) {
+  /**
+   * Per-line instruction profile of a program shader, for contexts created
+   * with `profile: true`. Like coverage, counts accumulate across draws until
+   * `resetProgramCoverage`, so reset before a frame to profile that frame.
+   * @param {WebGLProgram} program
+   * @param {number} shaderType - VERTEX_SHADER or FRAGMENT_SHADER
+   * @returns {{ totalInstructions: number, lines: { line: number, executions: number, instructions: number, percent: number }[] } | null}
+   */
+  getProgramProfile(program, shaderType) {
+    const report = this._readProgramProfile(program, shaderType, 0);
+    return report === null ? null : JSON.parse(report);
+  }
+
+  /**
+   * The shader source with the percent and instruction count of each line
+   * in front of it, like `perf annotate`.
+   * @param {WebGLProgram} program
+   * @param {number} shaderType - VERTEX_SHADER or FRAGMENT_SHADER
+   * @returns {string | null}
+   */
+  getProgramProfileListing(program, shaderType) {
+    return this._readProgramProfile(program, shaderType, 1);
+  }
+
+  _readProgramProfile(program, shaderType, format) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
-    if (!ex || typeof ex.wasm_ctx_get_shader_parameter !== 'function') {
-      throw new Error('wasm_ctx_get_shader_parameter not found');
+    if (!ex || typeof ex.wasm_ctx_get_program_profile !== 'function') {
+      return null;
     }
-    const shaderHandle = shader && typeof shader === 'object' && typeof shader._handle === 'number' ? shader._handle : (shader >>> 0);
-    const val = ex.wasm_ctx_get_shader_parameter(this._ctxHandle, shaderHandle, pname >>> 0);
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const len = ex.wasm_ctx_get_program_profile(this._ctxHandle, programHandle, shaderType, format, 0, 0);
+    if (len === 0) return null;
 
-    // WebGL returns boolean for status parameters
-    if (pname === 0x8B81 /* COMPILE_STATUS */ || pname === 0x8B80 /* DELETE_STATUS */) {
-      return !!val;
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
+    if (ptr === 0) return null;
+
+    try {
+      const actualLen = ex.wasm_ctx_get_program_profile(this._ctxHandle, programHandle, shaderType, format, ptr, len);
+      const mem = new Uint8Array(ex.memory.buffer);
+      return new TextDecoder().decode(mem.subarray(ptr, ptr + actualLen));
+    } finally {
+      ex.wasm_free(ptr);
     }
-    return val;
   }
 
-  getProgramParameter(program, pname) {
+  /**
+   * What became of the last draw call: the program, attributes, viewport
+   * and scissor coverage, primitives clipped, culled or too small, fragments
+   * failing the stencil and depth tests, and blending and color mask. `issues`
+   * lists the likely reasons the draw changed nothing, most fundamental first.
+   * @returns {{ call: string, mode: string, count: number, instanceCount: number, error: string | null, issues: string[], [key: string]: any } | null}
+   *   null before the first draw
+   */
+  explainLastDraw() {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
-    if (!ex || typeof ex.wasm_ctx_get_program_parameter !== 'function') {
-      throw new Error('wasm_ctx_get_program_parameter not found');
+    if (!ex || typeof ex.wasm_ctx_explain_last_draw !== 'function') {
+      throw new Error('wasm_ctx_explain_last_draw not found');
+    }
+    const len = ex.wasm_ctx_explain_last_draw(this._ctxHandle, 0, 0);
+    if (len === 0) return null;
+
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
+    if (ptr === 0) return null;
+
+    try {
+      const actualLen = ex.wasm_ctx_explain_last_draw(this._ctxHandle, ptr, len);
+      const mem = new Uint8Array(ex.memory.buffer);
+      return JSON.parse(new TextDecoder().decode(mem.subarray(ptr, ptr + actualLen)));
+    } finally {
+      ex.wasm_free(ptr);
+    }
+  }
+
+  /**
+   * Zero the coverage counters of a program's shaders.
+   * @param {WebGLProgram} program
+   */
+  resetProgramCoverage(program) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_reset_program_coverage !== 'function') {
+      throw new Error('wasm_ctx_reset_program_coverage not found');
+    }
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const code = ex.wasm_ctx_reset_program_coverage(this._ctxHandle, programHandle);
+    _checkErr(code, this._instance);
+  }
+
+  deleteProgram(program) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_delete_program !== 'function') {
+      throw new Error('wasm_ctx_delete_program not found');
+    }
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+
+    // Table indices are now freed by Rust when the Program's refcount reaches zero.
+    // This allows bound programs to remain valid even if deleted by the user,
+    // as required by the WebGL specification.
+
+    const code = ex.wasm_ctx_delete_program(this._ctxHandle, programHandle);
+    _checkErr(code, this._instance);
+    if (program && typeof program === 'object') {
+      try { program._handle = 0; program._deleted = true; } catch (e) { /* ignore */ }
+    }
+  }
+
+  /**
+   * Check whether the program can draw with the current state. The outcome
+   * is reported by VALIDATE_STATUS and getProgramInfoLog.
+   * @param {WasmWebGLProgram} program
+   */
+  validateProgram(program) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_validate_program !== 'function') {
+      throw new Error('wasm_ctx_validate_program not found');
+    }
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const code = ex.wasm_ctx_validate_program(this._ctxHandle, programHandle);
+    _checkErr(code, this._instance);
+  }
+
+  useProgram(program) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_use_program !== 'function') {
+      throw new Error('wasm_ctx_use_program not found');
+    }
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const code = ex.wasm_ctx_use_program(this._ctxHandle, programHandle);
+    _checkErr(code, this._instance);
+    this._currentProgram = program;
+  }
+
+  /**
+   * Set a program parameter before linking. With PROGRAM_SEPARABLE set the
+   * program may link a single stage and be used in a program pipeline.
+   * @param {WebGLProgram} program
+   * @param {number} pname - PROGRAM_SEPARABLE
+   * @param {number} value
+   */
+  programParameteri(program, pname, value) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_program_parameteri !== 'function') {
+      throw new Error('wasm_ctx_program_parameteri not found');
+    }
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const code = ex.wasm_ctx_program_parameteri(this._ctxHandle, programHandle, pname >>> 0, value | 0);
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  createProgramPipeline() {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_create_program_pipeline !== 'function') {
+      throw new Error('wasm_ctx_create_program_pipeline not found');
+    }
+    const handle = ex.wasm_ctx_create_program_pipeline(this._ctxHandle);
+    if (handle === 0) {
+      const msg = readErrorMessage(this._instance);
+      throw new Error(`Failed to create program pipeline: ${msg}`);
+    }
+    const pipeline = new WasmWebGLProgramPipeline(this, handle);
+    this._pipelineHandles.set(handle, pipeline);
+    return pipeline;
+  }
+
+  deleteProgramPipeline(pipeline) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_delete_program_pipeline !== 'function') {
+      throw new Error('wasm_ctx_delete_program_pipeline not found');
+    }
+    const handle = pipeline && typeof pipeline === 'object' && typeof pipeline._handle === 'number' ? pipeline._handle : (pipeline >>> 0);
+    const code = ex.wasm_ctx_delete_program_pipeline(this._ctxHandle, handle);
+    _checkErr(code, this._instance);
+    this._pipelineHandles.delete(handle);
+    if (pipeline && typeof pipeline === 'object') {
+      try { pipeline._handle = 0; pipeline._deleted = true; } catch (e) { /* ignore */ }
+    }
+  }
+
+  isProgramPipeline(pipeline) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_is_program_pipeline !== 'function') {
+      throw new Error('wasm_ctx_is_program_pipeline not found');
+    }
+    const handle = pipeline && typeof pipeline === 'object' && typeof pipeline._handle === 'number' ? pipeline._handle : (pipeline >>> 0);
+    return !!ex.wasm_ctx_is_program_pipeline(this._ctxHandle, handle);
+  }
+
+  /**
+   * Bind a program pipeline. Draws use it while no program is current.
+   * @param {WasmWebGLProgramPipeline | null} pipeline
+   */
+  bindProgramPipeline(pipeline) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_bind_program_pipeline !== 'function') {
+      throw new Error('wasm_ctx_bind_program_pipeline not found');
+    }
+    const handle = pipeline && typeof pipeline === 'object' && typeof pipeline._handle === 'number' ? pipeline._handle : (pipeline >>> 0);
+    const code = ex.wasm_ctx_bind_program_pipeline(this._ctxHandle, handle);
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * Use the given stages of a separable program in a pipeline.
+   * @param {WasmWebGLProgramPipeline} pipeline
+   * @param {number} stages - VERTEX_SHADER_BIT, FRAGMENT_SHADER_BIT or ALL_SHADER_BITS
+   * @param {WebGLProgram | null} program
+   */
+  useProgramStages(pipeline, stages, program) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_use_program_stages !== 'function') {
+      throw new Error('wasm_ctx_use_program_stages not found');
+    }
+    const handle = pipeline && typeof pipeline === 'object' && typeof pipeline._handle === 'number' ? pipeline._handle : (pipeline >>> 0);
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const code = ex.wasm_ctx_use_program_stages(this._ctxHandle, handle, stages >>> 0, programHandle);
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  activeShaderProgram(pipeline, program) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_active_shader_program !== 'function') {
+      throw new Error('wasm_ctx_active_shader_program not found');
+    }
+    const handle = pipeline && typeof pipeline === 'object' && typeof pipeline._handle === 'number' ? pipeline._handle : (pipeline >>> 0);
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const code = ex.wasm_ctx_active_shader_program(this._ctxHandle, handle, programHandle);
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  validateProgramPipeline(pipeline) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_validate_program_pipeline !== 'function') {
+      throw new Error('wasm_ctx_validate_program_pipeline not found');
+    }
+    const handle = pipeline && typeof pipeline === 'object' && typeof pipeline._handle === 'number' ? pipeline._handle : (pipeline >>> 0);
+    const code = ex.wasm_ctx_validate_program_pipeline(this._ctxHandle, handle);
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * @param {WasmWebGLProgramPipeline} pipeline
+   * @param {number} pname - ACTIVE_PROGRAM, VERTEX_SHADER, FRAGMENT_SHADER,
+   *   VALIDATE_STATUS or INFO_LOG_LENGTH
+   */
+  getProgramPipelineParameter(pipeline, pname) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_program_pipeline_parameter !== 'function') {
+      throw new Error('wasm_ctx_get_program_pipeline_parameter not found');
+    }
+    const handle = pipeline && typeof pipeline === 'object' && typeof pipeline._handle === 'number' ? pipeline._handle : (pipeline >>> 0);
+    const val = ex.wasm_ctx_get_program_pipeline_parameter(this._ctxHandle, handle, pname >>> 0);
+    if (pname === this.VALIDATE_STATUS) {
+      return !!val;
+    }
+    return val;
+  }
+
+  getProgramPipelineInfoLog(pipeline) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_program_pipeline_info_log !== 'function') {
+      throw new Error('wasm_ctx_get_program_pipeline_info_log not found');
+    }
+    const handle = pipeline && typeof pipeline === 'object' && typeof pipeline._handle === 'number' ? pipeline._handle : (pipeline >>> 0);
+
+    const ptr = ex.wasm_ctx_get_program_pipeline_info_log(this._ctxHandle, handle);
+    if (ptr === 0) return "";
+
+    const dv = new DataView(ex.memory.buffer);
+    const len = dv.getUint32(ptr - 16, true);
+    const mem = new Uint8Array(ex.memory.buffer);
+    const bytes = mem.slice(ptr, ptr + len);
+    return new TextDecoder().decode(bytes);
+  }
+
+  getShaderParameter(shader, pname) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_shader_parameter !== 'function') {
+      throw new Error('wasm_ctx_get_shader_parameter not found');
+    }
+    const shaderHandle = shader && typeof shader === 'object' && typeof shader._handle === 'number' ? shader._handle : (shader >>> 0);
+    const val = ex.wasm_ctx_get_shader_parameter(this._ctxHandle, shaderHandle, pname >>> 0);
+
+    // WebGL returns boolean for status parameters
+    if (pname === 0x8B81 /* COMPILE_STATUS */ || pname === 0x8B80 /* DELETE_STATUS */) {
+      return !!val;
+    }
+    return val;
+  }
+
+  getProgramParameter(program, pname) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_program_parameter !== 'function') {
+      throw new Error('wasm_ctx_get_program_parameter not found');
     }
     const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
     const val = ex.wasm_ctx_get_program_parameter(this._ctxHandle, programHandle, pname >>> 0);
 
     // WebGL returns boolean for status parameters
-    if (pname === 0x8B82 /* LINK_STATUS */ || pname === 0x8B80 /* DELETE_STATUS */ || pname === 0x8B83 /* VALIDATE_STATUS */) {
+    if (pname === 0x8B82 /* LINK_STATUS */ || pname === 0x8B80 /* DELETE_STATUS */ || pname === 0x8B83 /* VALIDATE_STATUS */ ||
+      pname === 0x8258 /* PROGRAM_SEPARABLE */) {
       return !!val;
     }
     return val;
@@ -1188,28 +2126,69 @@ export class WasmWebGL2RenderingContext {
     return new TextDecoder().decode(bytes);
   }
 
-  getProgramWasm(program, shaderType) {
+  /**
+   * Copy a program shader's generated WASM out in chunks of `chunkSize`
+   * bytes, so large modules need no guest buffer of their full size. The
+   * read is retried if the program relinks meanwhile (its hash changes).
+   * @param {WebGLProgram} program
+   * @param {number} shaderType VERTEX_SHADER or FRAGMENT_SHADER
+   * @param {{chunkSize?: number}} [options]
+   * @returns {Uint8Array|null}
+   */
+  getProgramWasm(program, shaderType, options = {}) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
-    if (!ex || typeof ex.wasm_ctx_get_program_wasm_len !== 'function') {
-      throw new Error('wasm_ctx_get_program_wasm_len not found');
+    if (!ex || typeof ex.wasm_ctx_get_program_wasm_chunk !== 'function') {
+      throw new Error('wasm_ctx_get_program_wasm_chunk not found');
     }
     const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
-    const len = ex.wasm_ctx_get_program_wasm_len(this._ctxHandle, programHandle, shaderType);
-    if (len === 0) return null;
+    const chunkSize = Math.max(1, options.chunkSize ?? 64 * 1024) >>> 0;
 
-    const ptr = ex.wasm_alloc(len);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, chunkSize);
     if (ptr === 0) throw new Error('Failed to allocate memory for getProgramWasm');
 
     try {
-      const actualLen = ex.wasm_ctx_get_program_wasm(this._ctxHandle, programHandle, shaderType, ptr, len);
-      const mem = new Uint8Array(ex.memory.buffer);
-      return new Uint8Array(mem.buffer, ptr, actualLen).slice();
+      for (;;) {
+        const hash = ex.wasm_ctx_get_program_wasm_hash(this._ctxHandle, programHandle, shaderType);
+        const len = ex.wasm_ctx_get_program_wasm_len(this._ctxHandle, programHandle, shaderType);
+        if (len === 0) return null;
+
+        const out = new Uint8Array(len);
+        let offset = 0;
+        while (offset < len) {
+          const n = ex.wasm_ctx_get_program_wasm_chunk(this._ctxHandle, programHandle, shaderType, offset, ptr, chunkSize);
+          if (n === 0) break;
+          out.set(new Uint8Array(ex.memory.buffer, ptr, n), offset);
+          offset += n;
+        }
+        if (offset === len && ex.wasm_ctx_get_program_wasm_hash(this._ctxHandle, programHandle, shaderType) === hash) {
+          return out;
+        }
+      }
     } finally {
       ex.wasm_free(ptr);
     }
   }
 
+  /**
+   * 64-bit FNV-1a hash of a program shader's generated WASM, as 16 hex
+   * digits, or null if the program has no module for that stage. Stable
+   * across runs, for keying module caches.
+   * @param {WebGLProgram} program
+   * @param {number} shaderType VERTEX_SHADER or FRAGMENT_SHADER
+   * @returns {string|null}
+   */
+  getProgramWasmHash(program, shaderType) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_program_wasm_hash !== 'function') {
+      throw new Error('wasm_ctx_get_program_wasm_hash not found');
+    }
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const hash = BigInt.asUintN(64, BigInt(ex.wasm_ctx_get_program_wasm_hash(this._ctxHandle, programHandle, shaderType)));
+    return hash === 0n ? null : hash.toString(16).padStart(16, '0');
+  }
+
   getAttribLocation(program, name) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
@@ -1220,7 +2199,7 @@ export class WasmWebGL2RenderingContext {
     const nameStr = String(name);
     const bytes = new TextEncoder().encode(nameStr);
     const len = bytes.length;
-    const ptr = ex.wasm_alloc(len);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
     if (ptr === 0) throw new Error('Failed to allocate memory for getAttribLocation');
 
     try {
@@ -1242,13 +2221,61 @@ export class WasmWebGL2RenderingContext {
     const nameStr = String(name);
     const bytes = new TextEncoder().encode(nameStr);
     const len = bytes.length;
-    const ptr = ex.wasm_alloc(len);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
     if (ptr === 0) throw new Error('Failed to allocate memory for bindAttribLocation');
 
     try {
       const mem = new Uint8Array(ex.memory.buffer);
       mem.set(bytes, ptr);
       const code = ex.wasm_ctx_bind_attrib_location(this._ctxHandle, programHandle, index >>> 0, ptr, len);
+      if (code === 5) return; // ERR_GL
+      _checkErr(code, this._instance);
+    } finally {
+      ex.wasm_free(ptr);
+    }
+  }
+
+  /**
+   * Bind the uniform `name` to `location` for the next link, in place of the
+   * location the linker would number it with, so engines with fixed uniform
+   * slots can rely on them. Unbound uniforms skip bound locations.
+   * @param {WasmWebGLProgram} program
+   * @param {number} location
+   * @param {string} name
+   */
+  bindUniformLocation(program, location, name) {
+    this._bindLocation('wasm_ctx_bind_uniform_location', program, location, name);
+  }
+
+  /**
+   * Bind the varying `name` to `location` for the next link, in place of the
+   * location the linker would pack it into. The programs of a pipeline need
+   * the same bindings.
+   * @param {WasmWebGLProgram} program
+   * @param {number} location
+   * @param {string} name
+   */
+  bindVaryingLocation(program, location, name) {
+    this._bindLocation('wasm_ctx_bind_varying_location', program, location, name);
+  }
+
+  /**
+   * Pass a location binding for `name` to the `exportName` export.
+   */
+  _bindLocation(exportName, program, location, name) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex[exportName] !== 'function') {
+      throw new Error(`${exportName} not found`);
+    }
+    const programHandle = program && typeof program === 'object' && typeof program._handle === 'number' ? program._handle : (program >>> 0);
+    const bytes = new TextEncoder().encode(String(name));
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, bytes.length);
+    if (ptr === 0) throw new Error(`Failed to allocate memory for ${exportName}`);
+    try {
+      new Uint8Array(ex.memory.buffer).set(bytes, ptr);
+      const code = ex[exportName](this._ctxHandle, programHandle, location >>> 0, ptr, bytes.length);
+      if (code === 5) return; // ERR_GL
       _checkErr(code, this._instance);
     } finally {
       ex.wasm_free(ptr);
@@ -1262,6 +2289,7 @@ export class WasmWebGL2RenderingContext {
       throw new Error('wasm_ctx_enable_vertex_attrib_array not found');
     }
     const code = ex.wasm_ctx_enable_vertex_attrib_array(this._ctxHandle, index >>> 0);
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -1272,6 +2300,7 @@ export class WasmWebGL2RenderingContext {
       throw new Error('wasm_ctx_disable_vertex_attrib_array not found');
     }
     const code = ex.wasm_ctx_disable_vertex_attrib_array(this._ctxHandle, index >>> 0);
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -1427,6 +2456,7 @@ export class WasmWebGL2RenderingContext {
       throw new Error('wasm_ctx_vertex_attrib_divisor not found');
     }
     const code = ex.wasm_ctx_vertex_attrib_divisor(this._ctxHandle, index >>> 0, divisor >>> 0);
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -1526,7 +2556,7 @@ export class WasmWebGL2RenderingContext {
       return;
     }
 
-    const ptr = ex.wasm_alloc(len);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
     if (ptr === 0) throw new Error('Failed to allocate memory for bufferData');
 
     try {
@@ -1553,7 +2583,7 @@ export class WasmWebGL2RenderingContext {
     else bytes = new Uint8Array(data); // Fallback for arrays
 
     const len = bytes.length;
-    const ptr = ex.wasm_alloc(len);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
     if (ptr === 0) throw new Error('Failed to allocate memory for bufferSubData');
 
     try {
@@ -1608,7 +2638,9 @@ export class WasmWebGL2RenderingContext {
     if (!ex || typeof ex.wasm_ctx_draw_arrays !== 'function') {
       throw new Error('wasm_ctx_draw_arrays not found');
     }
-    const code = ex.wasm_ctx_draw_arrays(this._ctxHandle, mode >>> 0, first >>> 0, count >>> 0);
+    const code = this._runShaders(() => ex.wasm_ctx_draw_arrays(this._ctxHandle, mode >>> 0, first >>> 0, count >>> 0));
+    this._frameReset();
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -1618,7 +2650,9 @@ export class WasmWebGL2RenderingContext {
     if (!ex || typeof ex.wasm_ctx_draw_elements !== 'function') {
       throw new Error('wasm_ctx_draw_elements not found');
     }
-    const code = ex.wasm_ctx_draw_elements(this._ctxHandle, mode >>> 0, count >>> 0, type >>> 0, offset >>> 0);
+    const code = this._runShaders(() => ex.wasm_ctx_draw_elements(this._ctxHandle, mode >>> 0, count | 0, type >>> 0, offset >>> 0));
+    this._frameReset();
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
   drawArraysInstanced(mode, first, count, instanceCount) {
@@ -1627,7 +2661,9 @@ export class WasmWebGL2RenderingContext {
     if (!ex || typeof ex.wasm_ctx_draw_arrays_instanced !== 'function') {
       throw new Error('wasm_ctx_draw_arrays_instanced not found');
     }
-    const code = ex.wasm_ctx_draw_arrays_instanced(this._ctxHandle, mode >>> 0, first | 0, count | 0, instanceCount | 0);
+    const code = this._runShaders(() => ex.wasm_ctx_draw_arrays_instanced(this._ctxHandle, mode >>> 0, first | 0, count | 0, instanceCount | 0));
+    this._frameReset();
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
   drawElementsInstanced(mode, count, type, offset, instanceCount) {
@@ -1636,7 +2672,9 @@ export class WasmWebGL2RenderingContext {
     if (!ex || typeof ex.wasm_ctx_draw_elements_instanced !== 'function') {
       throw new Error('wasm_ctx_draw_elements_instanced not found');
     }
-    const code = ex.wasm_ctx_draw_elements_instanced(this._ctxHandle, mode >>> 0, count | 0, type >>> 0, offset >>> 0, instanceCount | 0);
+    const code = this._runShaders(() => ex.wasm_ctx_draw_elements_instanced(this._ctxHandle, mode >>> 0, count | 0, type >>> 0, offset >>> 0, instanceCount | 0));
+    this._frameReset();
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
   drawRangeElements(mode, start, end, count, type, offset) { this._assertNotDestroyed(); throw new Error('not implemented'); }
@@ -1653,12 +2691,16 @@ export class WasmWebGL2RenderingContext {
         bufs[i] = buffers[i];
     }
 
-    const ptr = ex.wasm_alloc(count * 4);
-    const view = new Uint32Array(ex.memory.buffer, ptr, count);
-    view.set(bufs);
-
-    const code = ex.wasm_ctx_draw_buffers(this._ctxHandle, ptr, count);
-    ex.wasm_free(ptr, count * 4);
+    const ptr = this._frameAlloc(count * 4);
+    let code;
+    try {
+      const view = new Uint32Array(ex.memory.buffer, ptr, count);
+      view.set(bufs);
+      code = ex.wasm_ctx_draw_buffers(this._ctxHandle, ptr, count);
+    } finally {
+      this._frameReset();
+    }
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -1829,7 +2871,7 @@ export class WasmWebGL2RenderingContext {
       return b;
     });
 
-    const ptr = ex.wasm_alloc(totalLen);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, totalLen);
     if (ptr === 0) throw new Error("Failed to allocate memory for transformFeedbackVaryings");
 
     try {
@@ -1868,11 +2910,148 @@ export class WasmWebGL2RenderingContext {
     return { name, size, type };
   }
 
-  createQuery() { this._assertNotDestroyed(); throw new Error('not implemented'); }
-  deleteQuery(q) { this._assertNotDestroyed(); throw new Error('not implemented'); }
-  beginQuery(target, id) { this._assertNotDestroyed(); throw new Error('not implemented'); }
-  endQuery(target) { this._assertNotDestroyed(); throw new Error('not implemented'); }
-  getQueryParameter(query, pname) { this._assertNotDestroyed(); throw new Error('not implemented'); }
+  // Queries. Only the timer queries of EXT_disjoint_timer_query_webgl2 are
+  // supported; they measure the virtual GPU timeline (see setGpuCostModel).
+  createQuery() {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_create_query !== 'function') {
+      throw new Error('wasm_ctx_create_query not found');
+    }
+    const handle = ex.wasm_ctx_create_query(this._ctxHandle);
+    if (handle === 0) {
+      const msg = readErrorMessage(this._instance);
+      throw new Error(`Failed to create query: ${msg}`);
+    }
+    const query = new WasmWebGLQuery(this, handle);
+    this._queryHandles.set(handle, query);
+    return query;
+  }
+
+  deleteQuery(query) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_delete_query !== 'function') {
+      throw new Error('wasm_ctx_delete_query not found');
+    }
+    const handle = query && typeof query === 'object' && typeof query._handle === 'number' ? query._handle : (query >>> 0);
+    const code = ex.wasm_ctx_delete_query(this._ctxHandle, handle);
+    _checkErr(code, this._instance);
+    this._queryHandles.delete(handle);
+    if (query && typeof query === 'object') {
+      try { query._handle = 0; query._deleted = true; } catch (e) { /* ignore */ }
+    }
+  }
+
+  isQuery(query) {
+    this._assertNotDestroyed();
+    if (!query || typeof query !== 'object' || !(query instanceof WasmWebGLQuery)) return false;
+    if (query._ctx !== this) return false;
+    const ex = this._instance.exports;
+    return ex.wasm_ctx_is_query(this._ctxHandle, query._handle) !== 0;
+  }
+
+  beginQuery(target, query) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_begin_query !== 'function') {
+      throw new Error('wasm_ctx_begin_query not found');
+    }
+    const handle = query && typeof query === 'object' && typeof query._handle === 'number' ? query._handle : (query >>> 0);
+    const code = ex.wasm_ctx_begin_query(this._ctxHandle, target >>> 0, handle);
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  endQuery(target) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_end_query !== 'function') {
+      throw new Error('wasm_ctx_end_query not found');
+    }
+    const code = ex.wasm_ctx_end_query(this._ctxHandle, target >>> 0);
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * @param {number} target - TIME_ELAPSED_EXT or TIMESTAMP_EXT
+   * @param {number} pname - CURRENT_QUERY or QUERY_COUNTER_BITS_EXT
+   */
+  getQuery(target, pname) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_query !== 'function') {
+      throw new Error('wasm_ctx_get_query not found');
+    }
+    const val = ex.wasm_ctx_get_query(this._ctxHandle, target >>> 0, pname >>> 0);
+    if (val < 0) return null;
+    if (pname === this.CURRENT_QUERY) {
+      return val === 0 ? null : this._queryHandles.get(val) || null;
+    }
+    return val;
+  }
+
+  getQueryParameter(query, pname) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_query_parameter !== 'function') {
+      throw new Error('wasm_ctx_get_query_parameter not found');
+    }
+    const handle = query && typeof query === 'object' && typeof query._handle === 'number' ? query._handle : (query >>> 0);
+    const val = Number(ex.wasm_ctx_get_query_parameter(this._ctxHandle, handle, pname >>> 0));
+    if (val < 0) return null;
+    return pname === this.QUERY_RESULT_AVAILABLE ? val !== 0 : val;
+  }
+
+  /**
+   * Set the cost model of the virtual GPU timeline. Each draw advances the
+   * timeline by `drawNs` plus `vertexNs` per vertex shader invocation and
+   * `fragmentNs` per fragment shader invocation; timer queries measure it.
+   * @param {{ drawNs?: number, vertexNs?: number, fragmentNs?: number }} model
+   */
+  setGpuCostModel({ drawNs = 0, vertexNs = 0, fragmentNs = 0 } = {}) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_set_gpu_cost_model !== 'function') {
+      throw new Error('wasm_ctx_set_gpu_cost_model not found');
+    }
+    const code = ex.wasm_ctx_set_gpu_cost_model(this._ctxHandle, drawNs, vertexNs, fragmentNs);
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * Select the clock of the GPU timeline. 'virtual' (the default) advances
+   * only by the cost model, deterministically; 'cpu' follows the host's
+   * monotonic clock, so timer queries measure real emulation time. Switching
+   * keeps the current time, so the timeline never runs backwards.
+   * @param {'virtual' | 'cpu'} clock
+   */
+  setGpuClock(clock) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_set_gpu_clock !== 'function') {
+      throw new Error('wasm_ctx_set_gpu_clock not found');
+    }
+    const code = { virtual: 0, cpu: 1 }[clock];
+    if (code === undefined) {
+      throw new Error(`Unknown GPU clock '${clock}'`);
+    }
+    _checkErr(ex.wasm_ctx_set_gpu_clock(this._ctxHandle, code), this._instance);
+  }
+
+  /**
+   * Current time of the virtual GPU timeline, in nanoseconds.
+   * @returns {number}
+   */
+  getGpuTime() {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_gpu_time !== 'function') {
+      throw new Error('wasm_ctx_get_gpu_time not found');
+    }
+    return Number(ex.wasm_ctx_get_gpu_time(this._ctxHandle));
+  }
 
   fenceSync(condition, flags) { this._assertNotDestroyed(); throw new Error('not implemented'); }
   clientWaitSync(sync, flags, timeout) { this._assertNotDestroyed(); throw new Error('not implemented'); }
@@ -1907,6 +3086,15 @@ export class WasmWebGL2RenderingContext {
     const code = ex.wasm_ctx_tex_parameter_i(this._ctxHandle, target >>> 0, pname >>> 0, param | 0);
     _checkErr(code, this._instance);
   }
+  texParameterf(target, pname, param) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_tex_parameter_f !== 'function') {
+      throw new Error('wasm_ctx_tex_parameter_f not found');
+    }
+    const code = ex.wasm_ctx_tex_parameter_f(this._ctxHandle, target >>> 0, pname >>> 0, +param);
+    _checkErr(code, this._instance);
+  }
   generateMipmap(target) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
@@ -1937,6 +3125,156 @@ export class WasmWebGL2RenderingContext {
     _checkErr(code, this._instance);
   }
   copyTexSubImage2D(target, level, xoffset, yoffset, x, y, width, height) { this._assertNotDestroyed(); throw new Error('not implemented'); }
+  /**
+   * Import a frame streamed from the host (video decoder, camera) into level 0
+   * of the bound texture. `format` is RGBA, RGB, EXTERNAL_FORMAT_I420 or
+   * EXTERNAL_FORMAT_NV12; YUV frames are converted to RGBA8. The staging
+   * allocation and the texture storage are reused from frame to frame.
+   */
+  texImageExternal(width, height, format, frame) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_tex_image_external !== 'function') {
+      throw new Error('wasm_ctx_tex_image_external not found');
+    }
+
+    let data = frame;
+    if (ArrayBuffer.isView(data)) {
+      data = new Uint8Array(data.buffer, data.byteOffset, data.byteLength);
+    } else {
+      data = new Uint8Array(data);
+    }
+
+    const len = data.length;
+    if (!this._externalStaging || this._externalStaging.len < len) {
+      if (this._externalStaging) ex.wasm_free(this._externalStaging.ptr);
+      this._externalStaging = null;
+      const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
+      if (ptr === 0) throw new Error('Failed to allocate memory for external frame');
+      this._externalStaging = { ptr, len };
+    }
+    const { ptr } = this._externalStaging;
+    new Uint8Array(ex.memory.buffer).set(data, ptr);
+
+    const code = ex.wasm_ctx_tex_image_external(
+      this._ctxHandle,
+      ptr >>> 0,
+      len >>> 0,
+      width >>> 0,
+      height >>> 0,
+      format >>> 0
+    );
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * Decode a PNG or JPEG file in the module and upload it to `level` of the
+   * bound 2D texture as RGBA8, sized to the image. Needs a build with the
+   * `image-decode` feature.
+   * @param {number} target
+   * @param {number} level
+   * @param {ArrayBuffer | ArrayBufferView} encoded
+   */
+  texImage2DEncoded(target, level, encoded) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_tex_image_2d_encoded !== 'function') {
+      throw new Error('wasm_ctx_tex_image_2d_encoded not found (build with the image-decode feature)');
+    }
+    const data = ArrayBuffer.isView(encoded)
+      ? new Uint8Array(encoded.buffer, encoded.byteOffset, encoded.byteLength)
+      : new Uint8Array(encoded);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, data.length);
+    if (ptr === 0) throw new Error('Failed to allocate memory for texImage2DEncoded');
+    let code;
+    try {
+      new Uint8Array(ex.memory.buffer).set(data, ptr);
+      code = ex.wasm_ctx_tex_image_2d_encoded(this._ctxHandle, target >>> 0, level | 0, ptr, data.length);
+    } finally {
+      ex.wasm_free(ptr);
+    }
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * Alias `level` of the bound 2D texture over `byteLength` bytes of the
+   * module's linear memory at `ptr` (e.g. from `wasm_ctx_alloc`) instead of
+   * copying them. Draws sample the memory as it is when they run; call
+   * invalidateTextureAlias before freeing or reusing it. See
+   * docs/1.1.2-texture.md for the aliasing rules.
+   * @param {number} target
+   * @param {number} level
+   * @param {number} internalformat RGBA8, R32F, RG32F or RGBA32F
+   * @param {number} width
+   * @param {number} height
+   * @param {number} ptr
+   * @param {number} byteLength
+   */
+  texImage2DAlias(target, level, internalformat, width, height, ptr, byteLength) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_tex_image_2d_alias !== 'function') {
+      throw new Error('wasm_ctx_tex_image_2d_alias not found');
+    }
+    const code = ex.wasm_ctx_tex_image_2d_alias(
+      this._ctxHandle,
+      target >>> 0,
+      level | 0,
+      internalformat >>> 0,
+      width >>> 0,
+      height >>> 0,
+      ptr >>> 0,
+      byteLength >>> 0
+    );
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * End the alias texImage2DAlias set up for `level` of the bound texture. The
+   * level keeps a copy of the memory's current contents.
+   * @param {number} target
+   * @param {number} level
+   */
+  invalidateTextureAlias(target, level) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_invalidate_texture_alias !== 'function') {
+      throw new Error('wasm_ctx_invalidate_texture_alias not found');
+    }
+    const code = ex.wasm_ctx_invalidate_texture_alias(this._ctxHandle, target >>> 0, level | 0);
+    if (code === 5) return; // ERR_GL
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * Upload every mip level of a KTX2 file to the bound 2D texture. Supports
+   * uncompressed 2D textures; Basis Universal and supercompressed files throw.
+   * @param {number} target
+   * @param {ArrayBuffer | ArrayBufferView} ktx2
+   */
+  texImageKTX2(target, ktx2) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_tex_image_ktx2 !== 'function') {
+      throw new Error('wasm_ctx_tex_image_ktx2 not found');
+    }
+    const data = ArrayBuffer.isView(ktx2)
+      ? new Uint8Array(ktx2.buffer, ktx2.byteOffset, ktx2.byteLength)
+      : new Uint8Array(ktx2);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, data.length);
+    if (ptr === 0) throw new Error('Failed to allocate memory for texImageKTX2');
+    let code;
+    try {
+      new Uint8Array(ex.memory.buffer).set(data, ptr);
+      code = ex.wasm_ctx_tex_image_ktx2(this._ctxHandle, target >>> 0, ptr, data.length);
+    } finally {
+      ex.wasm_free(ptr);
+    }
+    _checkErr(code, this._instance);
+  }
+
   texSubImage2D(target, level, xoffset, yoffset, width, height, format, type_, pixels) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
@@ -1955,7 +3293,7 @@ export class WasmWebGL2RenderingContext {
     }
 
     const len = data.length;
-    const ptr = ex.wasm_alloc(len);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
     if (ptr === 0) throw new Error('Failed to allocate memory for sub-pixel data');
 
     try {
@@ -1975,6 +3313,7 @@ export class WasmWebGL2RenderingContext {
         ptr >>> 0,
         len >>> 0
       );
+      if (code === 5) return; // ERR_GL
       _checkErr(code, this._instance);
     } finally {
       ex.wasm_free(ptr);
@@ -2004,17 +3343,93 @@ export class WasmWebGL2RenderingContext {
     _checkErr(code, this._instance);
   }
 
-  pixelStorei(pname, param) { this._assertNotDestroyed(); throw new Error('not implemented'); }
+  pixelStorei(pname, param) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_pixel_storei !== 'function') {
+      throw new Error('wasm_ctx_pixel_storei not found');
+    }
+    const code = ex.wasm_ctx_pixel_storei(this._ctxHandle, pname >>> 0, typeof param === 'boolean' ? (param ? 1 : 0) : (param | 0));
+    _checkErr(code, this._instance);
+  }
   getExtension(name) {
     this._assertNotDestroyed();
-    if (name === 'EXT_color_buffer_float') {
+    if (this._webgl1) {
+      return this._getWebGL1Extension(name);
+    }
+    if (name === 'EXT_color_buffer_float' || name === 'NV_shader_noperspective_interpolation') {
       return {};
     }
+    if (name === 'EXT_disjoint_timer_query_webgl2') {
+      return {
+        QUERY_COUNTER_BITS_EXT: 0x8864,
+        TIME_ELAPSED_EXT: 0x88BF,
+        TIMESTAMP_EXT: 0x8E28,
+        GPU_DISJOINT_EXT: 0x8FBB,
+        queryCounterEXT: (query, target) => {
+          this._assertNotDestroyed();
+          const ex = this._instance.exports;
+          const handle = query && typeof query === 'object' && typeof query._handle === 'number' ? query._handle : (query >>> 0);
+          const code = ex.wasm_ctx_query_counter(this._ctxHandle, handle, target >>> 0);
+          if (code === 5) return; // ERR_GL
+          _checkErr(code, this._instance);
+        },
+      };
+    }
     return null;
   }
+  getContextAttributes() {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_context_attributes !== 'function') {
+      throw new Error('wasm_ctx_get_context_attributes not found');
+    }
+    const bits = ex.wasm_ctx_get_context_attributes(this._ctxHandle);
+    return {
+      alpha: (bits & 1) !== 0,
+      depth: (bits & 2) !== 0,
+      stencil: (bits & 4) !== 0,
+      antialias: (bits & 8) !== 0,
+      premultipliedAlpha: (bits & 16) !== 0,
+      preserveDrawingBuffer: (bits & 32) !== 0,
+    };
+  }
   getSupportedExtensions() {
     this._assertNotDestroyed();
-    return ['EXT_color_buffer_float'];
+    if (this._webgl1) {
+      return ['ANGLE_instanced_arrays', 'EXT_frag_depth', 'EXT_shader_texture_lod', 'OES_standard_derivatives', 'OES_vertex_array_object'];
+    }
+    return ['EXT_color_buffer_float', 'EXT_disjoint_timer_query_webgl2', 'NV_shader_noperspective_interpolation'];
+  }
+
+  // WebGL1 extensions for features that are core in WebGL2. Their shader
+  // side is handled when GLSL ES 1.00 is translated, so they only need the API.
+  _getWebGL1Extension(name) {
+    const proto = WasmWebGL2RenderingContext.prototype;
+    switch (name) {
+      case 'OES_vertex_array_object':
+        return {
+          VERTEX_ARRAY_BINDING_OES: 0x85B5,
+          createVertexArrayOES: () => proto.createVertexArray.call(this),
+          deleteVertexArrayOES: (vao) => proto.deleteVertexArray.call(this, vao),
+          isVertexArrayOES: (vao) => proto.isVertexArray.call(this, vao),
+          bindVertexArrayOES: (vao) => proto.bindVertexArray.call(this, vao),
+        };
+      case 'ANGLE_instanced_arrays':
+        return {
+          VERTEX_ATTRIB_ARRAY_DIVISOR_ANGLE: 0x88FE,
+          drawArraysInstancedANGLE: (...args) => proto.drawArraysInstanced.apply(this, args),
+          drawElementsInstancedANGLE: (...args) => proto.drawElementsInstanced.apply(this, args),
+          vertexAttribDivisorANGLE: (...args) => proto.vertexAttribDivisor.apply(this, args),
+        };
+      case 'OES_standard_derivatives':
+        return { FRAGMENT_SHADER_DERIVATIVE_HINT_OES: 0x8B8B };
+      case 'EXT_frag_depth':
+      case 'EXT_shader_texture_lod':
+        return {};
+      default:
+        return null;
+    }
   }
 
   getUniformLocation(program, name) {
@@ -2027,7 +3442,7 @@ export class WasmWebGL2RenderingContext {
     const nameStr = String(name);
     const bytes = new TextEncoder().encode(nameStr);
     const len = bytes.length;
-    const ptr = ex.wasm_alloc(len);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
     if (ptr === 0) throw new Error('Failed to allocate memory for getUniformLocation');
 
     try {
@@ -2050,7 +3465,7 @@ export class WasmWebGL2RenderingContext {
     const nameStr = String(name);
     const bytes = new TextEncoder().encode(nameStr);
     const len = bytes.length;
-    const ptr = ex.wasm_alloc(len);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
     if (ptr === 0) throw new Error("Failed to allocate memory for getUniformBlockIndex");
 
     try {
@@ -2080,6 +3495,49 @@ export class WasmWebGL2RenderingContext {
     _checkErr(code, this._instance);
   }
 
+  getActiveUniformBlockName(program, uniformBlockIndex) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_active_uniform_block_name !== "function") {
+      throw new Error("wasm_ctx_get_active_uniform_block_name not found");
+    }
+    const programHandle = program && typeof program === "object" && typeof program._handle === "number" ? program._handle : (program >>> 0);
+    const ptr = ex.wasm_ctx_get_active_uniform_block_name(this._ctxHandle, programHandle, uniformBlockIndex >>> 0);
+    if (ptr === 0) return null;
+
+    const dv = new DataView(ex.memory.buffer);
+    const len = dv.getUint32(ptr - 16, true);
+    return new TextDecoder().decode(new Uint8Array(ex.memory.buffer).slice(ptr, ptr + len));
+  }
+
+  getActiveUniformBlockParameter(program, uniformBlockIndex, pname) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_get_active_uniform_block_parameter !== "function") {
+      throw new Error("wasm_ctx_get_active_uniform_block_parameter not found");
+    }
+    const programHandle = program && typeof program === "object" && typeof program._handle === "number" ? program._handle : (program >>> 0);
+    const ptr = ex.wasm_ctx_get_active_uniform_block_parameter(
+      this._ctxHandle,
+      programHandle,
+      uniformBlockIndex >>> 0,
+      pname >>> 0
+    );
+    if (ptr === 0) return null;
+
+    const dv = new DataView(ex.memory.buffer);
+    const len = dv.getUint32(ptr - 16, true);
+    switch (pname) {
+      case this.UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES:
+        return new Uint32Array(ex.memory.buffer.slice(ptr, ptr + len));
+      case this.UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER:
+      case this.UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER:
+        return dv.getInt32(ptr, true) !== 0;
+      default:
+        return dv.getInt32(ptr, true);
+    }
+  }
+
   uniform1f(loc, x) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
@@ -2151,18 +3609,63 @@ export class WasmWebGL2RenderingContext {
     }
 
     const len = bytes.length;
-    const ptr = ex.wasm_alloc(len);
+    const ptr = this._frameAlloc(len);
     if (ptr === 0) throw new Error('Failed to allocate memory for uniformMatrix4fv');
 
+    let code;
     try {
       const mem = new Uint8Array(ex.memory.buffer);
       mem.set(bytes, ptr);
       const count = len / 4;
-      const code = ex.wasm_ctx_uniform_matrix_4fv(this._ctxHandle, locHandle, transpose ? 1 : 0, ptr, count);
-      _checkErr(code, this._instance);
+      code = ex.wasm_ctx_uniform_matrix_4fv(this._ctxHandle, locHandle, transpose ? 1 : 0, ptr, count);
     } finally {
-      ex.wasm_free(ptr);
+      this._frameReset();
     }
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * Uniform setter counts since the context was created: `uploads` changed a
+   * value, `skipped` set the value already stored and did nothing.
+   * @returns {{ uploads: number, skipped: number }}
+   */
+  getUniformStats() {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_uniform_skip_count !== 'function') {
+      throw new Error('wasm_ctx_uniform_skip_count not found');
+    }
+    return {
+      uploads: ex.wasm_ctx_uniform_upload_count(this._ctxHandle),
+      skipped: ex.wasm_ctx_uniform_skip_count(this._ctxHandle),
+    };
+  }
+
+  // Uniform values are stored per context rather than per program, and
+  // separable programs are given disjoint locations, so programUniform*
+  // writes the same storage as uniform*.
+  programUniform1f(program, loc, x) {
+    this.uniform1f(loc, x);
+  }
+
+  programUniform2f(program, loc, x, y) {
+    this.uniform2f(loc, x, y);
+  }
+
+  programUniform3f(program, loc, x, y, z) {
+    this.uniform3f(loc, x, y, z);
+  }
+
+  programUniform4f(program, loc, x, y, z, w) {
+    this.uniform4f(loc, x, y, z, w);
+  }
+
+  programUniform1i(program, loc, x) {
+    this.uniform1i(loc, x);
+  }
+
+  programUniformMatrix4fv(program, loc, transpose, value) {
+    this.uniformMatrix4fv(loc, transpose, value);
   }
 
   getVertexAttrib(index, pname) {
@@ -2176,7 +3679,7 @@ export class WasmWebGL2RenderingContext {
     // Most params return 1 int (4 bytes).
     // CURRENT_VERTEX_ATTRIB returns 4 values (16 bytes) + type (4 bytes) = 20 bytes.
     const len = 20;
-    const ptr = ex.wasm_alloc(len);
+    const ptr = ex.wasm_ctx_alloc(this._ctxHandle, len);
     if (ptr === 0) throw new Error('Failed to allocate memory for getVertexAttrib');
 
     try {
@@ -2239,6 +3742,14 @@ export class WasmWebGL2RenderingContext {
       return 8;
     }
 
+    // EXT_disjoint_timer_query_webgl2: the virtual GPU timeline is never disjoint
+    if (pname === 0x8E28 /* TIMESTAMP_EXT */) {
+      return this.getGpuTime();
+    }
+    if (pname === 0x8FBB /* GPU_DISJOINT_EXT */) {
+      return false;
+    }
+
     const ptr = ex.wasm_ctx_get_parameter(this._ctxHandle, pname);
     if (ptr === 0) {
       return null;
@@ -2257,11 +3768,16 @@ export class WasmWebGL2RenderingContext {
       }
 
       case this.VIEWPORT:
+      case this.SCISSOR_BOX:
         return new Int32Array(ex.memory.buffer.slice(ptr, ptr + 16));
 
       case this.COLOR_CLEAR_VALUE:
+      case this.BLEND_COLOR:
         return new Float32Array(ex.memory.buffer.slice(ptr, ptr + 16));
 
+      case this.ALIASED_POINT_SIZE_RANGE:
+        return new Float32Array(ex.memory.buffer.slice(ptr, ptr + 8));
+
       case this.COLOR_WRITEMASK: {
         const mem = new Uint8Array(ex.memory.buffer, ptr, 4);
         return [mem[0] !== 0, mem[1] !== 0, mem[2] !== 0, mem[3] !== 0];
@@ -2280,12 +3796,30 @@ export class WasmWebGL2RenderingContext {
         return this._rbHandles.get(handle) || null;
       }
 
+      case this.PROGRAM_PIPELINE_BINDING: {
+        const handle = dv.getInt32(ptr, true);
+        if (handle === 0) return null;
+        return this._pipelineHandles.get(handle) || null;
+      }
+
       case this.MAX_DRAW_BUFFERS:
       case this.MAX_COLOR_ATTACHMENTS:
       case this.MAX_VERTEX_ATTRIBS:
+      case this.MAX_VARYING_VECTORS:
+      case this.MAX_VARYING_COMPONENTS:
+      case this.MAX_VERTEX_UNIFORM_VECTORS:
+      case this.MAX_FRAGMENT_UNIFORM_VECTORS:
+      case this.MAX_TEXTURE_SIZE:
+      case this.MAX_RENDERBUFFER_SIZE:
       case this.STENCIL_WRITEMASK:
       case this.STENCIL_BACK_WRITEMASK:
       case this.DEPTH_FUNC:
+      case this.BLEND_SRC_RGB:
+      case this.BLEND_DST_RGB:
+      case this.BLEND_SRC_ALPHA:
+      case this.BLEND_DST_ALPHA:
+      case this.BLEND_EQUATION_RGB:
+      case this.BLEND_EQUATION_ALPHA:
       case this.STENCIL_FUNC:
       case this.STENCIL_VALUE_MASK:
       case this.STENCIL_REF:
@@ -2298,9 +3832,19 @@ export class WasmWebGL2RenderingContext {
       case this.STENCIL_BACK_FAIL:
       case this.STENCIL_BACK_PASS_DEPTH_FAIL:
       case this.STENCIL_BACK_PASS_DEPTH_PASS:
+      case this.ALPHA_BITS:
+      case this.DEPTH_BITS:
+      case this.STENCIL_BITS:
+      case this.SAMPLES:
+      case this.SAMPLE_BUFFERS:
         return dv.getInt32(ptr, true);
 
+      case this.SAMPLE_COVERAGE_VALUE:
+      case this.DEPTH_CLEAR_VALUE:
+        return dv.getFloat32(ptr, true);
+
       case this.DEPTH_WRITEMASK:
+      case this.SAMPLE_COVERAGE_INVERT:
         return dv.getUint8(ptr) !== 0;
 
       default:
@@ -2404,6 +3948,7 @@ export class WasmWebGL2RenderingContext {
       throw new Error('wasm_ctx_blend_func not found');
     }
     const code = ex.wasm_ctx_blend_func(this._ctxHandle, sfactor >>> 0, dfactor >>> 0);
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -2420,6 +3965,7 @@ export class WasmWebGL2RenderingContext {
       srcAlpha >>> 0,
       dstAlpha >>> 0
     );
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -2430,6 +3976,7 @@ export class WasmWebGL2RenderingContext {
       throw new Error('wasm_ctx_blend_equation not found');
     }
     const code = ex.wasm_ctx_blend_equation(this._ctxHandle, mode >>> 0);
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -2444,6 +3991,7 @@ export class WasmWebGL2RenderingContext {
       modeRGB >>> 0,
       modeAlpha >>> 0
     );
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
 
@@ -2497,6 +4045,7 @@ export class WasmWebGL2RenderingContext {
       throw new Error('wasm_ctx_clear not found');
     }
     const code = ex.wasm_ctx_clear(this._ctxHandle, mask >>> 0);
+    if (code === 5) return; // ERR_GL
     _checkErr(code, this._instance);
   }
   clearColor(r, g, b, a) {
@@ -2508,7 +4057,15 @@ export class WasmWebGL2RenderingContext {
     const code = ex.wasm_ctx_clear_color(this._ctxHandle, +r, +g, +b, +a);
     _checkErr(code, this._instance);
   }
-  clearDepth(depth) { this._assertNotDestroyed(); throw new Error('not implemented'); }
+  clearDepth(depth) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_clear_depth !== 'function') {
+      throw new Error('wasm_ctx_clear_depth not found');
+    }
+    const code = ex.wasm_ctx_clear_depth(this._ctxHandle, +depth);
+    _checkErr(code, this._instance);
+  }
   depthFunc(func) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
@@ -2537,7 +4094,18 @@ export class WasmWebGL2RenderingContext {
     _checkErr(code, this._instance);
   }
   polygonOffset(factor, units) { this._assertNotDestroyed(); throw new Error('not implemented'); }
-  sampleCoverage(value, invert) { this._assertNotDestroyed(); throw new Error('not implemented'); }
+  // The emulator never multisamples, so sample coverage is tracked for
+  // queries but, as the spec requires without sample buffers, has no effect
+  // on rendering.
+  sampleCoverage(value, invert) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_sample_coverage !== 'function') {
+      throw new Error('wasm_ctx_sample_coverage not found');
+    }
+    const code = ex.wasm_ctx_sample_coverage(this._ctxHandle, +value, invert ? 1 : 0);
+    _checkErr(code, this._instance);
+  }
   stencilFunc(func, ref, mask) {
     this._assertNotDestroyed();
     const ex = this._instance.exports;
@@ -2592,6 +4160,48 @@ export class WasmWebGL2RenderingContext {
     const code = ex.wasm_ctx_stencil_mask_separate(this._ctxHandle, face >>> 0, mask >>> 0);
     _checkErr(code, this._instance);
   }
+
+  /**
+   * Replace the output of subsequent draws with a debug visualization:
+   * 'none', 'depth' (fragment depth as gray), 'overdraw' (red rises per
+   * fragment), 'wireframe' (triangle edges only) or 'mip-level' (tinted by
+   * the sampled level). Applies to RGBA8 color attachments.
+   * @param {string} mode
+   */
+  setDebugView(mode) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_set_debug_view !== 'function') {
+      throw new Error('wasm_ctx_set_debug_view not found');
+    }
+    const index = DEBUG_VIEWS.indexOf(mode);
+    if (index < 0) throw new Error(`unknown debug view: ${mode}`);
+    const code = ex.wasm_ctx_set_debug_view(this._ctxHandle, index);
+    _checkErr(code, this._instance);
+  }
+
+  /**
+   * Call `hooks.vertex` for each vertex after the vertex shader and
+   * `hooks.fragment` for each fragment before the depth and stencil tests,
+   * for custom culling or statistics. Vertices get `{ vertexId, instanceId,
+   * position, varyings }`, fragments `{ x, y, depth, frontFacing, varyings }`
+   * with interpolated `varyings`: a Float32Array of four floats per varying
+   * location (see `bindVaryingLocation`). Returning `false` culls the
+   * primitives using a vertex, or discards a fragment. Hooks run inside the
+   * draw and must not call back into the context. Pass null to remove them.
+   * @param {{ vertex?: Function, fragment?: Function } | null} hooks
+   */
+  setPipelineHooks(hooks) {
+    this._assertNotDestroyed();
+    const ex = this._instance.exports;
+    if (!ex || typeof ex.wasm_ctx_set_pipeline_hooks !== 'function') {
+      throw new Error('wasm_ctx_set_pipeline_hooks not found');
+    }
+    this._pipelineHooks = hooks || null;
+    const stages = (hooks?.vertex ? 1 : 0) | (hooks?.fragment ? 2 : 0);
+    const code = ex.wasm_ctx_set_pipeline_hooks(this._ctxHandle, stages);
+    _checkErr(code, this._instance);
+  }
 }
 
 /**
@@ -2600,6 +4210,22 @@ export class WasmWebGL2RenderingContext {
  */
 // WebGLTexture wrapper moved to `src/webgl2_texture.js`.
 
+/** Debug views accepted by `setDebugView`, in FFI order. */
+const DEBUG_VIEWS = ['none', 'depth', 'overdraw', 'wireframe', 'mip-level'];
+
+/**
+ * Throw if a shader module was generated for a different ABI version, e.g.
+ * by a webgl2.wasm that does not match this file.
+ * @param {WebAssembly.Instance} shaderInstance
+ */
+export function checkShaderAbi(shaderInstance) {
+  const global = shaderInstance.exports.__shader_abi_version;
+  const found = global instanceof WebAssembly.Global ? global.value : undefined;
+  if (found !== SHADER_ABI_VERSION) {
+    throw new Error(`shader ABI version mismatch: module has ${found ?? 'none'}, runtime expects ${SHADER_ABI_VERSION}`);
+  }
+}
+
 /**
  * Read an error message from WASM memory and return it as string.
  * Exported so callers outside this module can report errors.
@@ -2629,6 +4255,25 @@ export function readErrorMessage(instance) {
   return new TextDecoder('utf-8').decode(bytes);
 }
 
+/**
+ * Module offsets of the innermost WASM frames of a trap, which belong to
+ * the module that trapped. Only V8 stacks are parsed, where frames read
+ * `wasm://wasm/<module>:wasm-function[<index>]:0x<offset>`. Other engines
+ * format frames differently: nothing matches, no offsets are returned and
+ * the trap is reported at an unknown location.
+ * @param {Error} error
+ * @returns {number[]}
+ */
+function trapFrameOffsets(error) {
+  const frames = [...String(error.stack).matchAll(/(wasm:\/\/wasm\/[^:\s)]+):wasm-function\[\d+\]:0x([0-9a-f]+)/g)];
+  const offsets = [];
+  for (const [, url, offset] of frames) {
+    if (url !== frames[0][1]) break;
+    offsets.push(parseInt(offset, 16));
+  }
+  return offsets;
+}
+
 function _checkErr(code, instance) {
   if (code === ERR_OK) return;
   const msg = readErrorMessage(instance);
@@ -2705,9 +4350,43 @@ export function getShaderWat(ctxHandle, programHandle, shaderType) {
   }
 
   // Call the WASM function - it returns a packed u64 (BigInt or Number)
-  const result = ex.wasm_ctx_get_program_wat_ref(ctxHandle, programHandle, shaderType);
+  return decodePackedString(ex, ex.wasm_ctx_get_program_wat_ref(ctxHandle, programHandle, shaderType));
+}
 
-  // Unpack: low 32 bits = ptr, high 32 bits = len
+/**
+ * Get the compiler output for a shader in a program: the Naga IR the shader
+ * was linked from, or the WAT disassembly of the generated module.
+ *
+ * @param {number} ctxHandle - Context handle
+ * @param {number} programHandle - Program handle
+ * @param {number} shaderType - Shader type (VERTEX_SHADER or FRAGMENT_SHADER)
+ * @param {'naga' | 'wat'} [format] - Which stage of the compiler to show
+ * @returns {string | null} IR text or null if not available
+ */
+export function getShaderIr(ctxHandle, programHandle, shaderType, format = 'naga') {
+  const ctx = WasmWebGL2RenderingContext._contexts.get(ctxHandle);
+  if (!ctx) {
+    throw new Error('Invalid context handle');
+  }
+
+  const formatCode = { naga: 0, wat: 1 }[format];
+  if (formatCode === undefined) {
+    throw new Error(`Unknown IR format: ${format}`);
+  }
+
+  const ex = ctx._instance.exports;
+  if (!ex || typeof ex.wasm_ctx_get_program_ir !== 'function') {
+    throw new Error('wasm_ctx_get_program_ir not found');
+  }
+  return decodePackedString(ex, ex.wasm_ctx_get_program_ir(ctxHandle, programHandle, shaderType, formatCode));
+}
+
+/**
+ * Decode an ephemeral UTF-8 string returned as a packed u64
+ * (low 32 bits = ptr, high 32 bits = len).
+ * @returns {string | null} the string, or null for (0, 0)
+ */
+function decodePackedString(ex, result) {
   let ptr, len;
   if (typeof result === 'bigint') {
     ptr = Number(result & 0xFFFFFFFFn);
@@ -2725,11 +4404,7 @@ export function getShaderWat(ctxHandle, programHandle, shaderType) {
 
   // Copy bytes from WASM memory and decode as UTF-8
   const mem = new Uint8Array(ex.memory.buffer);
-  const bytes = mem.subarray(ptr, ptr + len);
-  const decoder = new TextDecoder('utf-8');
-  const watText = decoder.decode(bytes);
-
-  return watText;
+  return new TextDecoder('utf-8').decode(mem.subarray(ptr, ptr + len));
 }
 
 /**
@@ -2798,9 +4473,12 @@ export function getShaderGlsl(ctxHandle, programHandle, shaderType) {
  * 
  * @param {WasmWebGL2RenderingContext} gl - WebGL2 context
  * @param {Uint8Array} wasmBytes - Raw WASM bytecode to decompile
+ * @param {string} [symbols] - Optional symbol map, one entry per line:
+ *   `func <index> <name>`, `local <func> <index> <name>` or `mem <offset> <name>`.
+ *   These names override the ones found in the module.
  * @returns {string | null} GLSL source code or null on error
  */
-export function decompileWasmToGlsl(gl, wasmBytes) {
+export function decompileWasmToGlsl(gl, wasmBytes, symbols) {
   if (!gl || !gl._instance) {
     throw new Error('Invalid WebGL2 context');
   }
@@ -2817,13 +4495,27 @@ export function decompileWasmToGlsl(gl, wasmBytes) {
     throw new Error('Failed to allocate memory for WASM bytes');
   }
 
+  const symbolBytes = symbols ? new TextEncoder().encode(symbols) : null;
+  let symbolsPtr = 0;
+
   try {
     // Copy WASM bytes to linear memory
     const mem = new Uint8Array(ex.memory.buffer);
     mem.set(wasmBytes, wasmBytesPtr);
 
     // Call the decompiler
-    const glslPtr = ex.wasm_decompile_to_glsl(wasmBytesPtr, wasmBytesLen);
+    let glslPtr;
+    if (symbolBytes) {
+      symbolsPtr = ex.wasm_alloc(symbolBytes.length);
+      if (symbolsPtr === 0) {
+        throw new Error('Failed to allocate memory for symbol map');
+      }
+      new Uint8Array(ex.memory.buffer).set(symbolBytes, symbolsPtr);
+      glslPtr = ex.wasm_decompile_to_glsl_with_symbols(
+        wasmBytesPtr, wasmBytesLen, symbolsPtr, symbolBytes.length);
+    } else {
+      glslPtr = ex.wasm_decompile_to_glsl(wasmBytesPtr, wasmBytesLen);
+    }
 
     if (glslPtr === 0) {
       return null;
@@ -2838,5 +4530,8 @@ export function decompileWasmToGlsl(gl, wasmBytes) {
   } finally {
     // Free the allocated memory
     ex.wasm_free(wasmBytesPtr);
+    if (symbolsPtr !== 0) {
+      ex.wasm_free(symbolsPtr);
+    }
   }
 }
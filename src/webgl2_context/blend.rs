@@ -22,6 +22,24 @@ pub fn ctx_blend_func_separate(
         }
     };
 
+    for factor in [src_rgb, dst_rgb, src_alpha, dst_alpha] {
+        if !is_blend_factor(factor) {
+            set_last_error(&format!("invalid blend factor: {}", describe_enum(factor)));
+            ctx_obj.set_error(GL_INVALID_ENUM);
+            return ERR_GL;
+        }
+    }
+    // WebGL forbids pairing a constant color factor with a constant alpha one
+    let constant_color = |f| matches!(f, GL_CONSTANT_COLOR | GL_ONE_MINUS_CONSTANT_COLOR);
+    let constant_alpha = |f| matches!(f, GL_CONSTANT_ALPHA | GL_ONE_MINUS_CONSTANT_ALPHA);
+    if (constant_color(src_rgb) && constant_alpha(dst_rgb))
+        || (constant_alpha(src_rgb) && constant_color(dst_rgb))
+    {
+        set_last_error("constant color and constant alpha blend factors cannot be combined");
+        ctx_obj.set_error(GL_INVALID_OPERATION);
+        return ERR_GL;
+    }
+
     ctx_obj.blend_state.src_rgb = src_rgb;
     ctx_obj.blend_state.dst_rgb = dst_rgb;
     ctx_obj.blend_state.src_alpha = src_alpha;
@@ -45,6 +63,14 @@ pub fn ctx_blend_equation_separate(ctx: u32, mode_rgb: u32, mode_alpha: u32) ->
         }
     };
 
+    for mode in [mode_rgb, mode_alpha] {
+        if !is_blend_equation(mode) {
+            set_last_error(&format!("invalid blend equation: {}", describe_enum(mode)));
+            ctx_obj.set_error(GL_INVALID_ENUM);
+            return ERR_GL;
+        }
+    }
+
     ctx_obj.blend_state.eq_rgb = mode_rgb;
     ctx_obj.blend_state.eq_alpha = mode_alpha;
 
@@ -65,3 +91,33 @@ pub fn ctx_blend_color(ctx: u32, r: f32, g: f32, b: f32, a: f32) -> u32 {
     ctx_obj.blend_state.color = [r, g, b, a];
     ERR_OK
 }
+
+/// Whether `factor` is a blend factor.
+fn is_blend_factor(factor: u32) -> bool {
+    matches!(
+        factor,
+        GL_ZERO
+            | GL_ONE
+            | GL_SRC_COLOR
+            | GL_ONE_MINUS_SRC_COLOR
+            | GL_SRC_ALPHA
+            | GL_ONE_MINUS_SRC_ALPHA
+            | GL_DST_ALPHA
+            | GL_ONE_MINUS_DST_ALPHA
+            | GL_DST_COLOR
+            | GL_ONE_MINUS_DST_COLOR
+            | GL_SRC_ALPHA_SATURATE
+            | GL_CONSTANT_COLOR
+            | GL_ONE_MINUS_CONSTANT_COLOR
+            | GL_CONSTANT_ALPHA
+            | GL_ONE_MINUS_CONSTANT_ALPHA
+    )
+}
+
+/// Whether `mode` is a blend equation.
+fn is_blend_equation(mode: u32) -> bool {
+    matches!(
+        mode,
+        GL_FUNC_ADD | GL_FUNC_SUBTRACT | GL_FUNC_REVERSE_SUBTRACT | GL_MIN | GL_MAX
+    )
+}
@@ -9,7 +9,7 @@ pub fn ctx_is_buffer(ctx: u32, handle: u32) -> bool {
     }
     let reg = get_registry().borrow();
     if let Some(c) = reg.contexts.get(&ctx) {
-        c.buffers.contains_key(&handle)
+        c.buffers.get(&handle).is_some_and(|b| !b.deleted)
     } else {
         false
     }
@@ -52,7 +52,7 @@ pub fn ctx_get_buffer_parameter(ctx: u32, target: u32, pname: u32) -> i32 {
             }
         }
         _ => {
-            set_last_error("invalid parameter name");
+            set_last_error(&format!("invalid parameter name: {}", describe_enum(pname)));
             -1
         }
     }
@@ -80,6 +80,7 @@ pub fn ctx_create_buffer(ctx: u32) -> u32 {
         Buffer {
             gpu_handle,
             usage: 0,
+            deleted: false,
         },
     );
     buf_id
@@ -99,27 +100,10 @@ pub fn ctx_delete_buffer(ctx: u32, buf: u32) -> u32 {
             return ERR_INVALID_HANDLE;
         }
     };
-    if let Some(b) = ctx_obj.buffers.remove(&buf) {
-        ctx_obj.kernel.destroy_buffer(b.gpu_handle);
-    }
-
-    // Unbind from all targets
-    for val in ctx_obj.buffer_bindings.values_mut() {
-        if *val == Some(buf) {
-            *val = None;
-        }
-    }
-
-    // Unbind from all VAOs
-    for vao in ctx_obj.vertex_arrays.values_mut() {
-        if vao.element_array_buffer == Some(buf) {
-            vao.element_array_buffer = None;
-        }
-        for attr in &mut vao.attributes {
-            if attr.buffer == Some(buf) {
-                attr.buffer = None;
-            }
-        }
+    // Unbinds it from every target and the bound vertex array; storage
+    // other vertex arrays still hold is freed once they let go of it
+    if ctx_obj.buffers.contains_key(&buf) {
+        super::deletion::delete_buffer(ctx_obj, buf);
     }
     ERR_OK
 }
@@ -190,7 +174,7 @@ pub fn ctx_bind_buffer(ctx: u32, target: u32, buf: u32) -> u32 {
         }
     };
 
-    if buf != 0 && !ctx_obj.buffers.contains_key(&buf) {
+    if buf != 0 && ctx_obj.buffers.get(&buf).is_none_or(|b| b.deleted) {
         set_last_error("buffer not found");
         return ERR_INVALID_HANDLE;
     }
@@ -198,6 +182,7 @@ pub fn ctx_bind_buffer(ctx: u32, target: u32, buf: u32) -> u32 {
     if target == GL_ELEMENT_ARRAY_BUFFER {
         if let Some(vao) = ctx_obj.vertex_arrays.get_mut(&ctx_obj.bound_vertex_array) {
             vao.element_array_buffer = if buf == 0 { None } else { Some(buf) };
+            super::deletion::release_deleted_objects(ctx_obj);
         } else {
             set_last_error("current vertex array not found");
             return ERR_INVALID_OPERATION;
@@ -315,7 +300,10 @@ pub fn ctx_buffer_data(ctx: u32, target: u32, ptr: u32, len: u32, usage: u32) ->
         }
     };
 
-    let src_slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, len as usize) };
+    let src_slice = match crate::guest_mem::read(ptr, len) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
 
     if let Some(buf) = ctx_obj.buffers.get_mut(&buf_handle) {
         ctx_obj.kernel.destroy_buffer(buf.gpu_handle);
@@ -351,7 +339,10 @@ pub fn ctx_buffer_sub_data(ctx: u32, target: u32, offset: u32, ptr: u32, len: u3
         }
     };
 
-    let src_slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, len as usize) };
+    let src_slice = match crate::guest_mem::read(ptr, len) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
 
     if let Some(buf) = ctx_obj.buffers.get_mut(&buf_handle) {
         let gpu_handle = buf.gpu_handle;
@@ -0,0 +1,258 @@
+//! Command buffers: batched ctx_* calls
+//!
+//! Chatty WebGL code pays a WASM boundary crossing for every state change
+//! and uniform. A command buffer packs many such calls into one array of
+//! little-endian u32 words that is decoded and dispatched in a single call.
+//!
+//! Each command is a header word `opcode | (argc << 16)` followed by `argc`
+//! argument words. Floats are stored as their bit patterns, booleans as 0/1,
+//! signed values as two's complement. Object arguments are raw handles.
+//!
+//! GL errors raised by a command are recorded on the context exactly as if
+//! the call had been made directly, and playback carries on. Any other
+//! failure (bad handle, malformed buffer) stops playback at that command.
+
+use super::blend::ctx_blend_func;
+use super::buffers::ctx_bind_buffer;
+use super::drawing::{
+    ctx_draw_arrays, ctx_draw_arrays_instanced, ctx_draw_elements, ctx_draw_elements_instanced,
+};
+use super::framebuffers::ctx_bind_framebuffer;
+use super::registry::{clear_last_error, set_last_error};
+use super::shaders::{
+    ctx_uniform1f, ctx_uniform1i, ctx_uniform2f, ctx_uniform3f, ctx_uniform4f, ctx_use_program,
+};
+use super::state::{
+    ctx_active_texture, ctx_clear, ctx_clear_color, ctx_color_mask, ctx_cull_face, ctx_depth_func,
+    ctx_depth_mask, ctx_disable, ctx_enable, ctx_front_face, ctx_scissor, ctx_viewport,
+};
+use super::textures::ctx_bind_texture;
+use super::types::*;
+use super::vaos::{
+    ctx_bind_vertex_array, ctx_disable_vertex_attrib_array, ctx_enable_vertex_attrib_array,
+    ctx_vertex_attrib_pointer,
+};
+
+/// Command opcodes. Values are part of the wire format shared with the JS
+/// recorder and must not be renumbered.
+pub mod op {
+    pub const ENABLE: u32 = 1;
+    pub const DISABLE: u32 = 2;
+    pub const VIEWPORT: u32 = 3;
+    pub const SCISSOR: u32 = 4;
+    pub const CLEAR_COLOR: u32 = 5;
+    pub const CLEAR: u32 = 6;
+    pub const BIND_BUFFER: u32 = 7;
+    pub const BIND_TEXTURE: u32 = 8;
+    pub const ACTIVE_TEXTURE: u32 = 9;
+    pub const USE_PROGRAM: u32 = 10;
+    pub const UNIFORM1F: u32 = 11;
+    pub const UNIFORM2F: u32 = 12;
+    pub const UNIFORM3F: u32 = 13;
+    pub const UNIFORM4F: u32 = 14;
+    pub const UNIFORM1I: u32 = 15;
+    pub const BIND_VERTEX_ARRAY: u32 = 16;
+    pub const VERTEX_ATTRIB_POINTER: u32 = 17;
+    pub const ENABLE_VERTEX_ATTRIB_ARRAY: u32 = 18;
+    pub const DISABLE_VERTEX_ATTRIB_ARRAY: u32 = 19;
+    pub const DRAW_ARRAYS: u32 = 20;
+    pub const DRAW_ELEMENTS: u32 = 21;
+    pub const DRAW_ARRAYS_INSTANCED: u32 = 22;
+    pub const DRAW_ELEMENTS_INSTANCED: u32 = 23;
+    pub const DEPTH_FUNC: u32 = 24;
+    pub const DEPTH_MASK: u32 = 25;
+    pub const COLOR_MASK: u32 = 26;
+    pub const BLEND_FUNC: u32 = 27;
+    pub const CULL_FACE: u32 = 28;
+    pub const FRONT_FACE: u32 = 29;
+    pub const BIND_FRAMEBUFFER: u32 = 30;
+}
+
+/// Number of argument words each opcode takes, or None if unknown.
+fn arg_count(opcode: u32) -> Option<usize> {
+    Some(match opcode {
+        op::ENABLE | op::DISABLE | op::CLEAR | op::ACTIVE_TEXTURE | op::USE_PROGRAM => 1,
+        op::BIND_VERTEX_ARRAY | op::ENABLE_VERTEX_ATTRIB_ARRAY => 1,
+        op::DISABLE_VERTEX_ATTRIB_ARRAY | op::DEPTH_FUNC | op::DEPTH_MASK => 1,
+        op::CULL_FACE | op::FRONT_FACE => 1,
+        op::BIND_BUFFER | op::BIND_TEXTURE | op::BIND_FRAMEBUFFER | op::BLEND_FUNC => 2,
+        op::UNIFORM1F | op::UNIFORM1I => 2,
+        op::UNIFORM2F | op::DRAW_ARRAYS => 3,
+        op::VIEWPORT | op::SCISSOR | op::CLEAR_COLOR | op::COLOR_MASK => 4,
+        op::UNIFORM3F | op::DRAW_ELEMENTS | op::DRAW_ARRAYS_INSTANCED => 4,
+        op::UNIFORM4F | op::DRAW_ELEMENTS_INSTANCED => 5,
+        op::VERTEX_ATTRIB_POINTER => 6,
+        _ => return None,
+    })
+}
+
+/// Builder for command buffers on the Rust side.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommandBuffer {
+    words: Vec<u32>,
+}
+
+impl CommandBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a command with raw argument words.
+    pub fn push(&mut self, opcode: u32, args: &[u32]) -> &mut Self {
+        self.words.push(opcode | ((args.len() as u32) << 16));
+        self.words.extend_from_slice(args);
+        self
+    }
+
+    /// Append a command whose arguments are a location followed by floats.
+    pub fn push_uniform_f(&mut self, opcode: u32, location: i32, values: &[f32]) -> &mut Self {
+        self.words
+            .push(opcode | (((values.len() + 1) as u32) << 16));
+        self.words.push(location as u32);
+        self.words.extend(values.iter().map(|v| v.to_bits()));
+        self
+    }
+
+    pub fn words(&self) -> &[u32] {
+        &self.words
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// Play `len` bytes of commands at `ptr` against `ctx`.
+pub fn ctx_play_command_buffer(ctx: u32, ptr: u32, len: u32) -> u32 {
+    clear_last_error();
+    if !len.is_multiple_of(4) {
+        set_last_error("command buffer length is not a multiple of 4");
+        return ERR_INVALID_ARGS;
+    }
+    // Copy out first: commands may allocate and must not alias guest memory
+    let words = match crate::guest_mem::read_slice(ptr as usize as *const u32, len as usize / 4) {
+        Ok(w) => w.to_vec(),
+        Err(code) => return code,
+    };
+    match play_commands(ctx, &words) {
+        Ok(()) => ERR_OK,
+        Err(code) => code,
+    }
+}
+
+/// Decode and dispatch every command in `words`. Stops at the first command
+/// that fails with anything other than a GL error; the last error names the
+/// failing command's index.
+pub fn play_commands(ctx: u32, words: &[u32]) -> Result<(), u32> {
+    let mut pos = 0;
+    let mut index = 0;
+    while pos < words.len() {
+        let opcode = words[pos] & 0xFFFF;
+        let argc = (words[pos] >> 16) as usize;
+        let args = match words.get(pos + 1..pos + 1 + argc) {
+            Some(a) => a,
+            None => {
+                set_last_error(&format!("command {}: truncated arguments", index));
+                return Err(ERR_INVALID_ARGS);
+            }
+        };
+        match arg_count(opcode) {
+            Some(n) if n == argc => {}
+            Some(n) => {
+                set_last_error(&format!(
+                    "command {}: opcode {} takes {} arguments, got {}",
+                    index, opcode, n, argc
+                ));
+                return Err(ERR_INVALID_ARGS);
+            }
+            None => {
+                set_last_error(&format!("command {}: unknown opcode {}", index, opcode));
+                return Err(ERR_INVALID_ARGS);
+            }
+        }
+
+        let code = dispatch(ctx, opcode, args);
+        if code != ERR_OK && code != ERR_GL {
+            let msg = crate::error::get_last_error_message().unwrap_or_default();
+            set_last_error(&format!("command {}: {}", index, msg));
+            return Err(code);
+        }
+        pos += 1 + argc;
+        index += 1;
+    }
+    Ok(())
+}
+
+fn dispatch(ctx: u32, opcode: u32, a: &[u32]) -> u32 {
+    let f = |i: usize| f32::from_bits(a[i]);
+    let i = |i: usize| a[i] as i32;
+    let b = |i: usize| a[i] != 0;
+    match opcode {
+        op::ENABLE => ctx_enable(ctx, a[0]),
+        op::DISABLE => ctx_disable(ctx, a[0]),
+        op::VIEWPORT => ctx_viewport(ctx, i(0), i(1), a[2], a[3]),
+        op::SCISSOR => ctx_scissor(ctx, i(0), i(1), a[2], a[3]),
+        op::CLEAR_COLOR => ctx_clear_color(ctx, f(0), f(1), f(2), f(3)),
+        op::CLEAR => ctx_clear(ctx, a[0]),
+        op::BIND_BUFFER => ctx_bind_buffer(ctx, a[0], a[1]),
+        op::BIND_TEXTURE => ctx_bind_texture(ctx, a[0], a[1]),
+        op::ACTIVE_TEXTURE => ctx_active_texture(ctx, a[0]),
+        op::USE_PROGRAM => ctx_use_program(ctx, a[0]),
+        op::UNIFORM1F => ctx_uniform1f(ctx, i(0), f(1)),
+        op::UNIFORM2F => ctx_uniform2f(ctx, i(0), f(1), f(2)),
+        op::UNIFORM3F => ctx_uniform3f(ctx, i(0), f(1), f(2), f(3)),
+        op::UNIFORM4F => ctx_uniform4f(ctx, i(0), f(1), f(2), f(3), f(4)),
+        op::UNIFORM1I => ctx_uniform1i(ctx, i(0), i(1)),
+        op::BIND_VERTEX_ARRAY => ctx_bind_vertex_array(ctx, a[0]),
+        op::VERTEX_ATTRIB_POINTER => {
+            ctx_vertex_attrib_pointer(ctx, a[0], i(1), a[2], b(3), i(4), a[5])
+        }
+        op::ENABLE_VERTEX_ATTRIB_ARRAY => ctx_enable_vertex_attrib_array(ctx, a[0]),
+        op::DISABLE_VERTEX_ATTRIB_ARRAY => ctx_disable_vertex_attrib_array(ctx, a[0]),
+        op::DRAW_ARRAYS => ctx_draw_arrays(ctx, a[0], i(1), i(2)),
+        op::DRAW_ELEMENTS => ctx_draw_elements(ctx, a[0], i(1), a[2], a[3]),
+        op::DRAW_ARRAYS_INSTANCED => ctx_draw_arrays_instanced(ctx, a[0], i(1), i(2), i(3)),
+        op::DRAW_ELEMENTS_INSTANCED => {
+            ctx_draw_elements_instanced(ctx, a[0], i(1), a[2], a[3], i(4))
+        }
+        op::DEPTH_FUNC => ctx_depth_func(ctx, a[0]),
+        op::DEPTH_MASK => ctx_depth_mask(ctx, b(0)),
+        op::COLOR_MASK => ctx_color_mask(ctx, b(0), b(1), b(2), b(3)),
+        op::BLEND_FUNC => ctx_blend_func(ctx, a[0], a[1]),
+        op::CULL_FACE => ctx_cull_face(ctx, a[0]),
+        op::FRONT_FACE => ctx_front_face(ctx, a[0]),
+        op::BIND_FRAMEBUFFER => ctx_bind_framebuffer(ctx, a[0], a[1]),
+        _ => unreachable!("opcode validated by arg_count"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl2_context::readback::{read_pixels_rgba, Rect};
+    use crate::webgl2_context::registry::{create_context_with_flags, destroy_context};
+
+    #[test]
+    fn plays_recorded_clear() {
+        let ctx = create_context_with_flags(0, 2, 2);
+        let mut cb = CommandBuffer::new();
+        cb.push(
+            op::CLEAR_COLOR,
+            &[0.0f32, 1.0, 0.0, 1.0].map(|v: f32| v.to_bits()),
+        )
+        .push(op::CLEAR, &[GL_COLOR_BUFFER_BIT]);
+        let played = play_commands(ctx, cb.words());
+        let pixels = read_pixels_rgba(ctx, Rect::new(0, 0, 1, 1)).unwrap();
+
+        let bad = play_commands(ctx, &[op::CLEAR | (2 << 16), 0, 0]);
+        let unknown = play_commands(ctx, &[0xFFFF]);
+        let truncated = play_commands(ctx, &[op::ENABLE | (1 << 16)]);
+        destroy_context(ctx);
+
+        assert_eq!(played, Ok(()));
+        assert_eq!(pixels, vec![0, 255, 0, 255]);
+        assert_eq!(bad, Err(ERR_INVALID_ARGS));
+        assert_eq!(unknown, Err(ERR_INVALID_ARGS));
+        assert_eq!(truncated, Err(ERR_INVALID_ARGS));
+    }
+}
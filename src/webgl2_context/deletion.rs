@@ -0,0 +1,238 @@
+//! Deferred deletion of objects that are still in use.
+//!
+//! A delete call unbinds the object from the current context's bind points
+//! and from the containers bound to it, as WebGL requires. Storage that a
+//! container elsewhere still holds (a texture attached to another
+//! framebuffer, a buffer in another vertex array, a shader attached to a
+//! program, the current program) survives the call: the object is only
+//! flagged as deleted, no longer answers to `is*` queries, and is freed once
+//! its last reference goes away.
+
+use super::types::*;
+
+/// Detach `attachment` from the framebuffers bound for drawing and reading.
+pub(crate) fn detach_from_bound_framebuffers(ctx_obj: &mut Context, attachment: Attachment) {
+    let bound = [
+        ctx_obj.bound_draw_framebuffer,
+        ctx_obj.bound_read_framebuffer,
+    ];
+    for fb in bound.into_iter().flatten() {
+        let Some(fb) = ctx_obj.framebuffers.get_mut(&fb) else {
+            continue;
+        };
+        let slots = fb
+            .color_attachments
+            .iter_mut()
+            .chain([&mut fb.depth_attachment, &mut fb.stencil_attachment]);
+        for slot in slots {
+            if *slot == Some(attachment) {
+                *slot = None;
+            }
+        }
+    }
+}
+
+/// Framebuffer attachments holding `attachment`.
+fn attachment_refs(ctx_obj: &Context, attachment: Attachment) -> usize {
+    ctx_obj
+        .framebuffers
+        .values()
+        .flat_map(|fb| {
+            fb.color_attachments
+                .iter()
+                .chain([&fb.depth_attachment, &fb.stencil_attachment])
+        })
+        .filter(|slot| **slot == Some(attachment))
+        .count()
+}
+
+/// Vertex array bindings holding buffer `buf`.
+fn buffer_refs(ctx_obj: &Context, buf: u32) -> usize {
+    ctx_obj
+        .vertex_arrays
+        .values()
+        .flat_map(|vao| {
+            vao.attributes
+                .iter()
+                .map(|attr| attr.buffer)
+                .chain([vao.element_array_buffer])
+        })
+        .filter(|b| *b == Some(buf))
+        .count()
+}
+
+/// Programs `shader` is attached to.
+fn shader_refs(ctx_obj: &Context, shader: u32) -> usize {
+    ctx_obj
+        .programs
+        .values()
+        .filter(|p| p.attached_shaders.contains(&shader))
+        .count()
+}
+
+/// Uses of `program` as the current program or as a pipeline stage.
+fn program_refs(ctx_obj: &Context, program: u32) -> usize {
+    let stages = ctx_obj
+        .program_pipelines
+        .values()
+        .flat_map(|pl| [pl.vertex_program, pl.fragment_program])
+        .filter(|p| *p == Some(program))
+        .count();
+    stages + (ctx_obj.current_program == Some(program)) as usize
+}
+
+/// Flag a texture as deleted and free it unless a framebuffer holds it.
+pub(crate) fn delete_texture(ctx_obj: &mut Context, tex: u32) {
+    if ctx_obj.bound_texture == Some(tex) {
+        ctx_obj.bound_texture = None;
+    }
+    for unit in &mut ctx_obj.texture_units {
+        if *unit == Some(tex) {
+            *unit = None;
+        }
+    }
+    detach_from_bound_framebuffers(ctx_obj, Attachment::Texture(tex));
+    if let Some(t) = ctx_obj.textures.get_mut(&tex) {
+        t.deleted = true;
+    }
+    ctx_obj.deletions_pending = true;
+    release_deleted_objects(ctx_obj);
+}
+
+/// Flag a renderbuffer as deleted and free it unless a framebuffer holds it.
+pub(crate) fn delete_renderbuffer(ctx_obj: &mut Context, renderbuffer: u32) {
+    if ctx_obj.bound_renderbuffer == Some(renderbuffer) {
+        ctx_obj.bound_renderbuffer = None;
+    }
+    detach_from_bound_framebuffers(ctx_obj, Attachment::Renderbuffer(renderbuffer));
+    if let Some(rb) = ctx_obj.renderbuffers.get_mut(&renderbuffer) {
+        rb.deleted = true;
+    }
+    ctx_obj.deletions_pending = true;
+    release_deleted_objects(ctx_obj);
+}
+
+/// Flag a buffer as deleted and free it unless another vertex array holds it.
+pub(crate) fn delete_buffer(ctx_obj: &mut Context, buf: u32) {
+    for val in ctx_obj.buffer_bindings.values_mut() {
+        if *val == Some(buf) {
+            *val = None;
+        }
+    }
+    let indexed = ctx_obj
+        .uniform_buffer_bindings
+        .iter_mut()
+        .chain(ctx_obj.transform_feedback_buffer_bindings.iter_mut());
+    for binding in indexed {
+        if binding.is_some_and(|b| b.buffer_handle == buf) {
+            *binding = None;
+        }
+    }
+    if let Some(vao) = ctx_obj.vertex_arrays.get_mut(&ctx_obj.bound_vertex_array) {
+        if vao.element_array_buffer == Some(buf) {
+            vao.element_array_buffer = None;
+        }
+        for attr in &mut vao.attributes {
+            if attr.buffer == Some(buf) {
+                attr.buffer = None;
+            }
+        }
+    }
+    if let Some(b) = ctx_obj.buffers.get_mut(&buf) {
+        b.deleted = true;
+    }
+    ctx_obj.deletions_pending = true;
+    release_deleted_objects(ctx_obj);
+}
+
+/// Flag a shader as deleted and free it unless a program holds it.
+pub(crate) fn delete_shader(ctx_obj: &mut Context, shader: u32) {
+    if let Some(s) = ctx_obj.shaders.get_mut(&shader) {
+        s.deleted = true;
+    }
+    ctx_obj.deletions_pending = true;
+    release_deleted_objects(ctx_obj);
+}
+
+/// Flag a program as deleted and free it unless it is in use.
+pub(crate) fn delete_program(ctx_obj: &mut Context, program: u32) {
+    if let Some(p) = ctx_obj.programs.get_mut(&program) {
+        p.deleted = true;
+    }
+    ctx_obj.deletions_pending = true;
+    release_deleted_objects(ctx_obj);
+}
+
+/// Free the objects flagged for deletion that nothing references anymore.
+///
+/// Called after every operation that can drop a reference. Programs go
+/// first, since freeing one releases its attached shaders.
+pub(crate) fn release_deleted_objects(ctx_obj: &mut Context) {
+    if !ctx_obj.deletions_pending {
+        return;
+    }
+
+    let programs: Vec<u32> = ctx_obj
+        .programs
+        .iter()
+        .filter(|(&h, p)| p.deleted && program_refs(ctx_obj, h) == 0)
+        .map(|(&h, _)| h)
+        .collect();
+    for h in programs {
+        ctx_obj.programs.remove(&h);
+    }
+
+    let shaders: Vec<u32> = ctx_obj
+        .shaders
+        .iter()
+        .filter(|(&h, s)| s.deleted && shader_refs(ctx_obj, h) == 0)
+        .map(|(&h, _)| h)
+        .collect();
+    for h in shaders {
+        ctx_obj.shaders.remove(&h);
+    }
+
+    let textures: Vec<u32> = ctx_obj
+        .textures
+        .iter()
+        .filter(|(&h, t)| t.deleted && attachment_refs(ctx_obj, Attachment::Texture(h)) == 0)
+        .map(|(&h, _)| h)
+        .collect();
+    for h in textures {
+        if let Some(tex) = ctx_obj.textures.remove(&h) {
+            for level in tex.levels.values() {
+                ctx_obj.kernel.destroy_buffer(level.gpu_handle);
+            }
+        }
+    }
+
+    let renderbuffers: Vec<u32> = ctx_obj
+        .renderbuffers
+        .iter()
+        .filter(|(&h, rb)| rb.deleted && attachment_refs(ctx_obj, Attachment::Renderbuffer(h)) == 0)
+        .map(|(&h, _)| h)
+        .collect();
+    for h in renderbuffers {
+        if let Some(rb) = ctx_obj.renderbuffers.remove(&h) {
+            ctx_obj.kernel.destroy_buffer(rb.gpu_handle);
+        }
+    }
+
+    let buffers: Vec<u32> = ctx_obj
+        .buffers
+        .iter()
+        .filter(|(&h, b)| b.deleted && buffer_refs(ctx_obj, h) == 0)
+        .map(|(&h, _)| h)
+        .collect();
+    for h in buffers {
+        if let Some(b) = ctx_obj.buffers.remove(&h) {
+            ctx_obj.kernel.destroy_buffer(b.gpu_handle);
+        }
+    }
+
+    ctx_obj.deletions_pending = ctx_obj.programs.values().any(|p| p.deleted)
+        || ctx_obj.shaders.values().any(|s| s.deleted)
+        || ctx_obj.textures.values().any(|t| t.deleted)
+        || ctx_obj.renderbuffers.values().any(|rb| rb.deleted)
+        || ctx_obj.buffers.values().any(|b| b.deleted);
+}
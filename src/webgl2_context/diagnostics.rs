@@ -0,0 +1,157 @@
+//! Shader info logs in the layout of reference GLSL compilers.
+//!
+//! Each problem becomes a line `ERROR: 0:<line>: '<token>' : <message>`
+//! (source string 0, 1-based line), followed by a summary line, so problem
+//! matchers and engines written against glslang/ANGLE logs can parse them.
+
+use naga::front::glsl::{ErrorKind, ParseErrors};
+use naga::valid::ValidationError;
+use naga::{Span, WithSpan};
+use std::fmt::Write;
+
+/// Info log for a failed GLSL parse.
+pub(crate) fn format_parse_errors(errors: &ParseErrors, source: &str) -> String {
+    let mut log = String::new();
+    for error in &errors.errors {
+        let (token, message) = match &error.kind {
+            ErrorKind::UnknownVariable(name) => (name.clone(), "undeclared identifier".into()),
+            ErrorKind::UnknownType(name) => (name.clone(), "unknown type".into()),
+            ErrorKind::UnknownField(name) => (name.clone(), "no such field in structure".into()),
+            ErrorKind::VariableAlreadyDeclared(name) => (name.clone(), "redefinition".into()),
+            ErrorKind::EndOfFile => (String::new(), "unexpected end of file".into()),
+            ErrorKind::InvalidToken(..) => (
+                span_text(error.meta, source),
+                format!("syntax error: {}", error.kind),
+            ),
+            kind => (span_text(error.meta, source), kind.to_string()),
+        };
+        push_error(&mut log, span_line(error.meta, source), &token, &message);
+    }
+    finish(log, errors.errors.len())
+}
+
+/// Info log for a module that parsed but failed validation.
+pub(crate) fn format_validation_error(error: &WithSpan<ValidationError>, source: &str) -> String {
+    let span = error
+        .spans()
+        .map(|(span, _)| *span)
+        .find(Span::is_defined)
+        .unwrap_or_default();
+
+    // The top-level error only names the function; the cause has the detail
+    let mut message = error.as_inner().to_string();
+    let mut cause = std::error::Error::source(error.as_inner());
+    while let Some(inner) = cause {
+        write!(message, ": {}", inner).unwrap();
+        cause = inner.source();
+    }
+
+    let mut log = String::new();
+    push_error(
+        &mut log,
+        span_line(span, source),
+        &span_text(span, source),
+        &message,
+    );
+    finish(log, 1)
+}
+
+/// Info log for a single error that has no source location.
+pub(crate) fn format_error(token: &str, message: &str) -> String {
+    let mut log = String::new();
+    push_error(&mut log, 0, token, message);
+    finish(log, 1)
+}
+
+/// Move the line numbers of `log` up by `lines`, for source that had lines
+/// inserted in front of it before compiling.
+pub(crate) fn shift_lines(log: &str, lines: u32) -> String {
+    let mut shifted = String::with_capacity(log.len());
+    for line in log.split_inclusive('\n') {
+        let number = line
+            .strip_prefix("ERROR: 0:")
+            .and_then(|rest| rest.split_once(':'))
+            .and_then(|(number, rest)| Some((number.parse::<u32>().ok()?, rest)));
+        match number {
+            Some((number, rest)) => write!(
+                shifted,
+                "ERROR: 0:{}:{}",
+                number.saturating_sub(lines),
+                rest
+            )
+            .unwrap(),
+            None => shifted.push_str(line),
+        }
+    }
+    shifted
+}
+
+fn push_error(log: &mut String, line: u32, token: &str, message: &str) {
+    writeln!(log, "ERROR: 0:{}: '{}' : {}", line, token, message).unwrap();
+}
+
+fn finish(mut log: String, count: usize) -> String {
+    let noun = if count == 1 { "error" } else { "errors" };
+    writeln!(
+        log,
+        "ERROR: {} compilation {}.  No code generated.",
+        count, noun
+    )
+    .unwrap();
+    log
+}
+
+/// 1-based line of `span`, or 0 when the error has no location.
+fn span_line(span: Span, source: &str) -> u32 {
+    match span.to_range() {
+        Some(range) if source.is_char_boundary(range.start) => span.location(source).line_number,
+        _ => 0,
+    }
+}
+
+/// Source text covered by `span`, cut at the end of its first line.
+fn span_text(span: Span, source: &str) -> String {
+    span.to_range()
+        .and_then(|range| source.get(range))
+        .and_then(|text| text.lines().next())
+        .unwrap_or("")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use naga::front::glsl::{Frontend, Options};
+
+    fn parse_log(source: &str) -> String {
+        let errors = Frontend::default()
+            .parse(&Options::from(naga::ShaderStage::Fragment), source)
+            .unwrap_err();
+        format_parse_errors(&errors, source)
+    }
+
+    #[test]
+    fn test_undeclared_identifier() {
+        let log = parse_log(
+            "#version 300 es\nprecision highp float;\nout vec4 color;\nvoid main() {\n    color = foo;\n}\n",
+        );
+        assert_eq!(
+            log.lines().next(),
+            Some("ERROR: 0:5: 'foo' : undeclared identifier")
+        );
+        assert!(log.ends_with("No code generated.\n"), "{}", log);
+    }
+
+    #[test]
+    fn test_error_count_summary() {
+        assert_eq!(
+            finish(String::new(), 1),
+            "ERROR: 1 compilation error.  No code generated.\n"
+        );
+        assert_eq!(
+            finish(String::new(), 3),
+            "ERROR: 3 compilation errors.  No code generated.\n"
+        );
+    }
+}
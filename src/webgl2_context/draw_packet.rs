@@ -0,0 +1,160 @@
+//! Draw packets: one FFI call per draw
+//!
+//! A draw packet carries everything a typical draw changes: the program,
+//! vertex attribute bindings, uniform values and the draw itself. The
+//! layout is little-endian u32 words:
+//!
+//! ```text
+//! header    program, binding_count, uniform_count,
+//!           mode, first, count, index_type, instance_count
+//! binding   index, buffer, size, type, normalized, stride, offset
+//! uniform   location, word_count, word_count value words
+//! ```
+//!
+//! `program` 0 keeps the current program. A binding with `buffer` 0
+//! disables the attribute; otherwise the buffer is bound to ARRAY_BUFFER
+//! (and stays bound), the pointer is set and the attribute is enabled.
+//! Uniform words are stored as-is, so floats travel as their bits.
+//! `index_type` 0 draws arrays from `first`; otherwise it is the index type
+//! of an indexed draw and `first` is the byte offset into the element
+//! buffer.
+//!
+//! GL errors are recorded on the context and do not stop the packet, as
+//! with the individual calls. Other failures abandon it at that step.
+
+use super::buffers::ctx_bind_buffer;
+use super::drawing::{ctx_draw_arrays_instanced, ctx_draw_elements_instanced};
+use super::registry::{clear_last_error, set_last_error, wasm_frame_reset};
+use super::shaders::{ctx_use_program, set_uniform_words};
+use super::types::*;
+use super::vaos::{
+    ctx_disable_vertex_attrib_array, ctx_enable_vertex_attrib_array, ctx_vertex_attrib_pointer,
+};
+
+const HEADER_WORDS: usize = 8;
+const BINDING_WORDS: usize = 7;
+
+/// Run the draw packet of `len` bytes at `ptr`. The per-frame arena is reset
+/// afterwards, as after any draw.
+pub fn ctx_draw_packet(ctx: u32, ptr: u32, len: u32) -> u32 {
+    clear_last_error();
+    if !len.is_multiple_of(4) {
+        set_last_error("draw packet length is not a multiple of 4");
+        return ERR_INVALID_ARGS;
+    }
+    let words = match crate::guest_mem::read_slice(ptr as usize as *const u32, len as usize / 4) {
+        Ok(w) => w.to_vec(),
+        Err(code) => return code,
+    };
+    let code = match draw_packet(ctx, &words) {
+        Ok(()) => ERR_OK,
+        Err(code) => code,
+    };
+    wasm_frame_reset();
+    code
+}
+
+/// Decode and run a draw packet.
+pub fn draw_packet(ctx: u32, words: &[u32]) -> Result<(), u32> {
+    let header = match words.get(..HEADER_WORDS) {
+        Some(h) => h,
+        None => return Err(malformed("truncated header")),
+    };
+    let program = header[0];
+    let binding_count = header[1] as usize;
+    let uniform_count = header[2] as usize;
+    let (mode, first, count) = (header[3], header[4], header[5] as i32);
+    let (index_type, instance_count) = (header[6], header[7] as i32);
+
+    // Counts come from the host; on wasm32 they can overflow usize
+    let bindings_end = binding_count
+        .checked_mul(BINDING_WORDS)
+        .and_then(|words| words.checked_add(HEADER_WORDS));
+    let bindings = match bindings_end.and_then(|end| words.get(HEADER_WORDS..end)) {
+        Some(b) => b,
+        None => return Err(malformed("truncated vertex bindings")),
+    };
+
+    if program != 0 {
+        check("program", ctx_use_program(ctx, program))?;
+    }
+
+    for b in bindings.chunks_exact(BINDING_WORDS) {
+        let index = b[0];
+        if b[1] == 0 {
+            check("binding", ctx_disable_vertex_attrib_array(ctx, index))?;
+            continue;
+        }
+        check("binding", ctx_bind_buffer(ctx, GL_ARRAY_BUFFER, b[1]))?;
+        check(
+            "binding",
+            ctx_vertex_attrib_pointer(ctx, index, b[2] as i32, b[3], b[4] != 0, b[5] as i32, b[6]),
+        )?;
+        check("binding", ctx_enable_vertex_attrib_array(ctx, index))?;
+    }
+
+    let mut pos = HEADER_WORDS + bindings.len();
+    for _ in 0..uniform_count {
+        let (location, word_count) = match words.get(pos..pos + 2) {
+            Some(u) => (u[0] as i32, u[1] as usize),
+            None => return Err(malformed("truncated uniform")),
+        };
+        let values_end = (pos + 2).checked_add(word_count);
+        let values = match values_end.and_then(|end| words.get(pos + 2..end)) {
+            Some(v) => v,
+            None => return Err(malformed("truncated uniform values")),
+        };
+        check("uniform", set_uniform_words(ctx, location, values))?;
+        pos += 2 + word_count;
+    }
+    if pos != words.len() {
+        return Err(malformed("trailing words"));
+    }
+
+    let code = if index_type == 0 {
+        ctx_draw_arrays_instanced(ctx, mode, first as i32, count, instance_count)
+    } else {
+        ctx_draw_elements_instanced(ctx, mode, count, index_type, first, instance_count)
+    };
+    check("draw", code)
+}
+
+fn malformed(what: &str) -> u32 {
+    set_last_error(&format!("malformed draw packet: {}", what));
+    ERR_INVALID_ARGS
+}
+
+/// Pass GL errors through (they are already recorded on the context) and
+/// stop on anything else, naming the step that failed.
+fn check(step: &str, code: u32) -> Result<(), u32> {
+    if code == ERR_OK || code == ERR_GL {
+        return Ok(());
+    }
+    let msg = crate::error::get_last_error_message().unwrap_or_default();
+    set_last_error(&format!("draw packet {}: {}", step, msg));
+    Err(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_counts_past_the_packet() {
+        // binding_count * BINDING_WORDS wraps a 32-bit usize to 3
+        let huge_bindings = [0, 0x2492_4925, 0, GL_TRIANGLES, 0, 3, 0, 1];
+        // A uniform whose word count runs past the end of the packet
+        let huge_uniform = [0, 0, 1, GL_TRIANGLES, 0, 3, 0, 1, 0, u32::MAX, 0];
+
+        assert_eq!(draw_packet(1, &huge_bindings), Err(ERR_INVALID_ARGS));
+        assert_eq!(
+            crate::error::get_last_error_message().as_deref(),
+            Some("malformed draw packet: truncated vertex bindings")
+        );
+        assert_eq!(draw_packet(1, &huge_uniform), Err(ERR_INVALID_ARGS));
+        assert_eq!(
+            crate::error::get_last_error_message().as_deref(),
+            Some("malformed draw packet: truncated uniform values")
+        );
+    }
+}
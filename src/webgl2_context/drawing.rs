@@ -1,20 +1,23 @@
-use super::registry::{clear_last_error, get_registry, set_last_error};
+use super::explain::{record_draw, DrawCall};
+use super::registry::{clear_last_error, get_registry, set_last_error, with_checked_out_context};
 use super::types::*;
+use crate::naga_wasm_backend::output_layout::ShadingRate;
 use crate::wasm_gl_emu::rasterizer::{
     RasterPipeline, RenderState, ShaderMemoryLayout, VertexFetcher,
 };
 
-fn ctx_get_program_flat_varyings_mask(ctx: &Context) -> u64 {
-    if let Some(program_id) = ctx.current_program {
-        if let Some(program) = ctx.programs.get(&program_id) {
-            if let Some(ref fs_module) = program.fs_module {
-                return crate::wasm_gl_emu::rasterizer::RasterPipeline::compute_flat_varyings_mask(
-                    fs_module,
-                );
-            }
-        }
-    }
-    0
+/// Flat and noperspective varying masks of the program running the fragment stage.
+fn ctx_get_program_varying_masks(ctx: &Context, fs_program: u32) -> (u128, u128) {
+    ctx.programs.get(&fs_program).map_or((0, 0), |program| {
+        RasterPipeline::compute_varying_masks(&program.fs_varyings)
+    })
+}
+
+/// Shading rate of the program running the fragment stage.
+fn ctx_get_program_shading_rate(ctx: &Context, fs_program: u32) -> ShadingRate {
+    ctx.programs
+        .get(&fs_program)
+        .map_or(ShadingRate::PerPixel, |program| program.fs_shading_rate)
 }
 
 struct WebGLVertexFetcher {
@@ -68,44 +71,92 @@ pub fn ctx_draw_arrays_instanced(
     count: i32,
     instance_count: i32,
 ) -> u32 {
+    let code = draw_arrays_instanced(ctx, mode, first, count, instance_count);
+    let call = DrawCall {
+        call: "drawArrays",
+        mode,
+        first: Some(first),
+        count,
+        instance_count,
+    };
+    record_draw(ctx, call, code);
+    code
+}
+
+fn draw_arrays_instanced(ctx: u32, mode: u32, first: i32, count: i32, instance_count: i32) -> u32 {
     clear_last_error();
-    let mut reg = get_registry().borrow_mut();
-    let reg_ptr = &mut *reg;
+    with_checked_out_context(ctx, |ctx_obj| {
+        draw_arrays_in(ctx_obj, ctx, mode, first, count, instance_count)
+    })
+}
 
-    let ctx_obj = match reg_ptr.contexts.get_mut(&ctx) {
-        Some(c) => c,
-        None => return ERR_INVALID_HANDLE,
-    };
+fn draw_arrays_in(
+    ctx_obj: &mut Context,
+    ctx: u32,
+    mode: u32,
+    first: i32,
+    count: i32,
+    instance_count: i32,
+) -> u32 {
+    if primitive_mode_name(mode).is_none() {
+        set_last_error(&format!("invalid draw mode: {}", describe_enum(mode)));
+        ctx_obj.set_error(GL_INVALID_ENUM);
+        return ERR_GL;
+    }
+    if let Err(code) = ctx_obj.check_draw_framebuffer() {
+        return code;
+    }
 
-    let _program_id = match ctx_obj.current_program {
-        Some(p) => p,
-        None => {
-            set_last_error("no program bound");
-            return ERR_INVALID_ARGS;
-        }
+    let (vs_program, fs_program) = match super::pipelines::draw_stage_programs(ctx_obj) {
+        Ok(programs) => programs,
+        Err(code) => return code,
     };
+    let incomplete_units =
+        match super::sampling::validate_sampler_units(ctx_obj, vs_program, fs_program) {
+            Ok(units) => units,
+            Err(code) => return code,
+        };
+
+    #[cfg(feature = "gpu-backend")]
+    if ctx_obj.gpu_backend {
+        let draw = super::gpu_backend::Draw {
+            mode,
+            first,
+            count,
+            instance_count,
+            indices: None,
+        };
+        if super::gpu_backend::forward_draw(ctx_obj, vs_program, fs_program, &draw) {
+            return ERR_OK;
+        }
+    }
 
-    // Get table indices from program
-    let (vs_table_idx, fs_table_idx) = if let Some(prog) = ctx_obj.programs.get(&_program_id) {
-        (prog.vs_table_idx, prog.fs_table_idx)
-    } else {
-        (None, None)
-    };
+    // Get table indices from the program of each stage
+    let vs_table_idx = ctx_obj
+        .programs
+        .get(&vs_program)
+        .and_then(|prog| prog.vs_table_idx);
+    let fs_table_idx = ctx_obj
+        .programs
+        .get(&fs_program)
+        .and_then(|prog| prog.fs_table_idx);
 
     let (vx, vy, vw, vh) = ctx_obj.viewport;
 
     // Create pipeline configuration
-    let mask = ctx_get_program_flat_varyings_mask(ctx_obj);
+    let (flat_mask, noperspective_mask) = ctx_get_program_varying_masks(ctx_obj, fs_program);
     let memory = ShaderMemoryLayout {
-        attr_ptr: ctx_obj.attribute_buffer.as_ptr() as u32,
-        uniform_ptr: ctx_obj.uniform_data.as_ptr() as u32,
-        varying_ptr: ctx_obj.varying_buffer.as_ptr() as u32,
-        private_ptr: ctx_obj.private_buffer.as_ptr() as u32,
-        texture_ptr: ctx_obj.texture_metadata.as_ptr() as u32,
-        frame_sp: (ctx_obj.frame_stack.as_ptr() as usize + ctx_obj.frame_stack.len()) as u32,
+        attr_ptr: ctx_obj.attribute_buffer.as_ptr() as usize,
+        uniform_ptr: ctx_obj.uniform_data.as_ptr() as usize,
+        varying_ptr: ctx_obj.varying_buffer.as_ptr() as usize,
+        private_ptr: ctx_obj.private_buffer.as_ptr() as usize,
+        texture_ptr: ctx_obj.texture_metadata.as_ptr() as usize,
+        frame_sp: ctx_obj.frame_stack.as_ptr() as usize + ctx_obj.frame_stack.len(),
     };
     let pipeline = RasterPipeline {
-        flat_varyings_mask: mask,
+        flat_varyings_mask: flat_mask,
+        noperspective_varyings_mask: noperspective_mask,
+        shading_rate: ctx_get_program_shading_rate(ctx_obj, fs_program),
         vs_table_idx,
         fs_table_idx,
         memory,
@@ -113,7 +164,7 @@ pub fn ctx_draw_arrays_instanced(
     };
 
     // Prepare textures once
-    ctx_obj.prepare_texture_metadata(memory.texture_ptr);
+    ctx_obj.prepare_texture_metadata(memory.texture_ptr, &incomplete_units);
 
     let state = RenderState {
         ctx_handle: ctx,
@@ -123,18 +174,20 @@ pub fn ctx_draw_arrays_instanced(
         scissor_enabled: ctx_obj.scissor_test_enabled,
         uniform_data: &ctx_obj.uniform_data,
         prepare_textures: None,
-        blend: ctx_obj.blend_state,
-        color_mask: ctx_obj.color_mask,
-        depth: ctx_obj.depth_state,
-        stencil: ctx_obj.stencil_state,
+        blend: ctx_obj.draw_blend_state(),
+        color_mask: ctx_obj.draw_color_mask(),
+        depth: ctx_obj.draw_depth_state(),
+        stencil: ctx_obj.draw_stencil_state(),
         cull_face_enabled: ctx_obj.cull_face_enabled,
         cull_face_mode: ctx_obj.cull_face_mode,
         front_face: ctx_obj.front_face,
+        debug_view: ctx_obj.debug_view,
+        dither: ctx_obj.dither_enabled,
     };
 
-    let fetcher = WebGLVertexFetcher {
-        bindings: ctx_obj.get_attribute_bindings(),
-    };
+    let mut bindings = std::mem::take(&mut ctx_obj.attribute_bindings);
+    ctx_obj.fill_attribute_bindings(&mut bindings);
+    let fetcher = WebGLVertexFetcher { bindings };
 
     // Calculate indices for draw_arrays (just 0..count)
     // But draw takes indices: Option<&[u32]>.
@@ -146,10 +199,12 @@ pub fn ctx_draw_arrays_instanced(
     // If we want start from 'first', we should probably pass indices.
 
     let (target_handles, target_formats, target_w, target_h) = ctx_obj.get_draw_targets();
-    let (ds_handle, _, _, _) = ctx_obj.get_depth_attachment_handle();
 
-    let depth_stencil_target = if ds_handle.is_valid() {
-        crate::wasm_gl_emu::rasterizer::DepthStencilTarget::Handle(ds_handle)
+    // A framebuffer object only has the depth and stencil planes attached to it
+    let depth_stencil_target = if ctx_obj.bound_draw_framebuffer.is_some() {
+        let (depth, _, _, _) = ctx_obj.get_depth_attachment_handle();
+        let (stencil, _, _, _) = ctx_obj.get_stencil_attachment_handle();
+        crate::wasm_gl_emu::rasterizer::DepthStencilTarget::Handles { depth, stencil }
     } else {
         crate::wasm_gl_emu::rasterizer::DepthStencilTarget::Raw {
             depth: &mut ctx_obj.default_framebuffer.depth,
@@ -181,6 +236,10 @@ pub fn ctx_draw_arrays_instanced(
             mode,
         },
     );
+    ctx_obj
+        .gpu_timeline
+        .charge_draw(ctx_obj.rasterizer.take_invocations());
+    ctx_obj.attribute_bindings = fetcher.bindings;
 
     ERR_OK
 }
@@ -197,58 +256,149 @@ pub fn ctx_draw_elements_instanced(
     type_: u32,
     offset: u32,
     instance_count: i32,
+) -> u32 {
+    let code = draw_elements_instanced(ctx, mode, count, type_, offset, instance_count);
+    let call = DrawCall {
+        call: "drawElements",
+        mode,
+        first: None,
+        count,
+        instance_count,
+    };
+    record_draw(ctx, call, code);
+    code
+}
+
+fn draw_elements_instanced(
+    ctx: u32,
+    mode: u32,
+    count: i32,
+    type_: u32,
+    offset: u32,
+    instance_count: i32,
 ) -> u32 {
     clear_last_error();
-    let mut reg = get_registry().borrow_mut();
-    let reg_ptr = &mut *reg;
+    with_checked_out_context(ctx, |ctx_obj| {
+        draw_elements_in(ctx_obj, ctx, mode, count, type_, offset, instance_count)
+    })
+}
 
-    let ctx_obj = match reg_ptr.contexts.get_mut(&ctx) {
-        Some(c) => c,
-        None => return ERR_INVALID_HANDLE,
-    };
+fn draw_elements_in(
+    ctx_obj: &mut Context,
+    ctx: u32,
+    mode: u32,
+    count: i32,
+    type_: u32,
+    offset: u32,
+    instance_count: i32,
+) -> u32 {
+    if primitive_mode_name(mode).is_none() {
+        set_last_error(&format!("invalid draw mode: {}", describe_enum(mode)));
+        ctx_obj.set_error(GL_INVALID_ENUM);
+        return ERR_GL;
+    }
+    if let Err(code) = ctx_obj.check_draw_framebuffer() {
+        return code;
+    }
 
-    let _program_id = match ctx_obj.current_program {
-        Some(p) => p,
-        None => {
-            set_last_error("no program bound");
-            return ERR_INVALID_ARGS;
-        }
+    let (vs_program, fs_program) = match super::pipelines::draw_stage_programs(ctx_obj) {
+        Ok(programs) => programs,
+        Err(code) => return code,
     };
-
-    // Get table indices from program
-    let (vs_table_idx, fs_table_idx) = if let Some(prog) = ctx_obj.programs.get(&_program_id) {
-        (prog.vs_table_idx, prog.fs_table_idx)
-    } else {
-        (None, None)
+    let incomplete_units =
+        match super::sampling::validate_sampler_units(ctx_obj, vs_program, fs_program) {
+            Ok(units) => units,
+            Err(code) => return code,
+        };
+
+    // Get table indices from the program of each stage
+    let vs_table_idx = ctx_obj
+        .programs
+        .get(&vs_program)
+        .and_then(|prog| prog.vs_table_idx);
+    let fs_table_idx = ctx_obj
+        .programs
+        .get(&fs_program)
+        .and_then(|prog| prog.fs_table_idx);
+
+    let (itype, index_size) = match type_ {
+        GL_UNSIGNED_BYTE => (crate::wasm_gl_emu::IndexType::U8, 1),
+        GL_UNSIGNED_SHORT => (crate::wasm_gl_emu::IndexType::U16, 2),
+        GL_UNSIGNED_INT => (crate::wasm_gl_emu::IndexType::U32, 4),
+        _ => {
+            set_last_error(&format!("invalid index type: {}", describe_enum(type_)));
+            ctx_obj.set_error(GL_INVALID_ENUM);
+            return ERR_GL;
+        }
     };
+    if count < 0 || instance_count < 0 {
+        set_last_error("count and instance count must not be negative");
+        ctx_obj.set_error(GL_INVALID_VALUE);
+        return ERR_GL;
+    }
 
-    // Get EBO
-    let ebo_handle = if let Some(vao) = ctx_obj.vertex_arrays.get(&ctx_obj.bound_vertex_array) {
-        vao.element_array_buffer
-    } else {
-        None
+    // The indices come from the bound vertex array's element array buffer
+    let ebo = ctx_obj
+        .vertex_arrays
+        .get(&ctx_obj.bound_vertex_array)
+        .and_then(|vao| vao.element_array_buffer)
+        .and_then(|h| ctx_obj.buffers.get(&h))
+        .and_then(|buf| ctx_obj.kernel.get_buffer(buf.gpu_handle))
+        .map(|gpu_buf| (gpu_buf.data.as_ptr(), gpu_buf.data.len()));
+    let Some((ebo_ptr, ebo_len)) = ebo else {
+        set_last_error("no element array buffer is bound");
+        ctx_obj.set_error(GL_INVALID_OPERATION);
+        return ERR_GL;
     };
+    if !offset.is_multiple_of(index_size) {
+        set_last_error(&format!(
+            "offset {} is not a multiple of the index size {}",
+            offset, index_size
+        ));
+        ctx_obj.set_error(GL_INVALID_OPERATION);
+        return ERR_GL;
+    }
+    let end = offset as u64 + count as u64 * index_size as u64;
+    if end > ebo_len as u64 {
+        set_last_error(&format!(
+            "indices {}..{} are outside the element array buffer of {} bytes",
+            offset, end, ebo_len
+        ));
+        ctx_obj.set_error(GL_INVALID_OPERATION);
+        return ERR_GL;
+    }
 
-    let itype = match type_ {
-        GL_UNSIGNED_BYTE => crate::wasm_gl_emu::IndexType::U8,
-        GL_UNSIGNED_SHORT => crate::wasm_gl_emu::IndexType::U16,
-        GL_UNSIGNED_INT => crate::wasm_gl_emu::IndexType::U32,
-        _ => return ERR_INVALID_ENUM,
-    };
+    #[cfg(feature = "gpu-backend")]
+    if ctx_obj.gpu_backend {
+        let draw = super::gpu_backend::Draw {
+            mode,
+            first: 0,
+            count,
+            instance_count,
+            indices: Some((type_, offset)),
+        };
+        if super::gpu_backend::forward_draw(ctx_obj, vs_program, fs_program, &draw) {
+            return ERR_OK;
+        }
+    }
 
     let (vx, vy, vw, vh) = ctx_obj.viewport;
 
     // Create pipeline configuration
     let memory = ShaderMemoryLayout {
-        attr_ptr: ctx_obj.attribute_buffer.as_ptr() as u32,
-        uniform_ptr: ctx_obj.uniform_data.as_ptr() as u32,
-        varying_ptr: ctx_obj.varying_buffer.as_ptr() as u32,
-        private_ptr: ctx_obj.private_buffer.as_ptr() as u32,
-        texture_ptr: ctx_obj.texture_metadata.as_ptr() as u32,
-        frame_sp: (ctx_obj.frame_stack.as_ptr() as usize + ctx_obj.frame_stack.len()) as u32,
+        attr_ptr: ctx_obj.attribute_buffer.as_ptr() as usize,
+        uniform_ptr: ctx_obj.uniform_data.as_ptr() as usize,
+        varying_ptr: ctx_obj.varying_buffer.as_ptr() as usize,
+        private_ptr: ctx_obj.private_buffer.as_ptr() as usize,
+        texture_ptr: ctx_obj.texture_metadata.as_ptr() as usize,
+        frame_sp: ctx_obj.frame_stack.as_ptr() as usize + ctx_obj.frame_stack.len(),
     };
+    let (flat_varyings_mask, noperspective_varyings_mask) =
+        ctx_get_program_varying_masks(ctx_obj, fs_program);
     let pipeline = RasterPipeline {
-        flat_varyings_mask: ctx_get_program_flat_varyings_mask(ctx_obj),
+        flat_varyings_mask,
+        noperspective_varyings_mask,
+        shading_rate: ctx_get_program_shading_rate(ctx_obj, fs_program),
         vs_table_idx,
         fs_table_idx,
         memory,
@@ -256,7 +406,7 @@ pub fn ctx_draw_elements_instanced(
     };
 
     // Prepare textures once
-    ctx_obj.prepare_texture_metadata(memory.texture_ptr);
+    ctx_obj.prepare_texture_metadata(memory.texture_ptr, &incomplete_units);
 
     let state = RenderState {
         ctx_handle: ctx,
@@ -266,36 +416,39 @@ pub fn ctx_draw_elements_instanced(
         scissor_enabled: ctx_obj.scissor_test_enabled,
         uniform_data: &ctx_obj.uniform_data,
         prepare_textures: None,
-        blend: ctx_obj.blend_state,
-        color_mask: ctx_obj.color_mask,
-        depth: ctx_obj.depth_state,
-        stencil: ctx_obj.stencil_state,
+        blend: ctx_obj.draw_blend_state(),
+        color_mask: ctx_obj.draw_color_mask(),
+        depth: ctx_obj.draw_depth_state(),
+        stencil: ctx_obj.draw_stencil_state(),
         cull_face_enabled: ctx_obj.cull_face_enabled,
         cull_face_mode: ctx_obj.cull_face_mode,
         front_face: ctx_obj.front_face,
+        debug_view: ctx_obj.debug_view,
+        dither: ctx_obj.dither_enabled,
     };
 
-    let fetcher = WebGLVertexFetcher {
-        bindings: ctx_obj.get_attribute_bindings(),
+    let mut bindings = std::mem::take(&mut ctx_obj.attribute_bindings);
+    ctx_obj.fill_attribute_bindings(&mut bindings);
+    let fetcher = WebGLVertexFetcher { bindings };
+
+    // Indices are decoded as the rasterizer reads them; the largest value of
+    // the index type restarts the primitive, as WebGL2 always enables
+    // PRIMITIVE_RESTART_FIXED_INDEX
+    let lazy_indices = crate::wasm_gl_emu::transfer::LazyIndexBuffer {
+        src_ptr: ebo_ptr,
+        src_len: ebo_len,
+        index_type: itype,
+        offset,
+        count: count as u32,
     };
 
-    // Prepare indices lazily if EBO is bound
-    let lazy_indices = ebo_handle
-        .and_then(|h| ctx_obj.buffers.get(&h))
-        .and_then(|buf| ctx_obj.kernel.get_buffer(buf.gpu_handle))
-        .map(|gpu_buf| crate::wasm_gl_emu::transfer::LazyIndexBuffer {
-            src_ptr: gpu_buf.data.as_ptr(),
-            src_len: gpu_buf.data.len(),
-            index_type: itype,
-            offset,
-            count: count as u32,
-        });
-
     let (target_handles, target_formats, target_w, target_h) = ctx_obj.get_draw_targets();
-    let (ds_handle, _, _, _) = ctx_obj.get_depth_attachment_handle();
 
-    let depth_stencil_target = if ds_handle.is_valid() {
-        crate::wasm_gl_emu::rasterizer::DepthStencilTarget::Handle(ds_handle)
+    // A framebuffer object only has the depth and stencil planes attached to it
+    let depth_stencil_target = if ctx_obj.bound_draw_framebuffer.is_some() {
+        let (depth, _, _, _) = ctx_obj.get_depth_attachment_handle();
+        let (stencil, _, _, _) = ctx_obj.get_stencil_attachment_handle();
+        crate::wasm_gl_emu::rasterizer::DepthStencilTarget::Handles { depth, stencil }
     } else {
         crate::wasm_gl_emu::rasterizer::DepthStencilTarget::Raw {
             depth: &mut ctx_obj.default_framebuffer.depth,
@@ -323,12 +476,14 @@ pub fn ctx_draw_elements_instanced(
             instance_count: instance_count as usize,
             first_vertex: 0,
             first_instance: 0,
-            indices: lazy_indices
-                .as_ref()
-                .map(|l| l as &dyn crate::wasm_gl_emu::rasterizer::IndexBuffer),
+            indices: Some(&lazy_indices),
             mode,
         },
     );
+    ctx_obj
+        .gpu_timeline
+        .charge_draw(ctx_obj.rasterizer.take_invocations());
+    ctx_obj.attribute_bindings = fetcher.bindings;
 
     ERR_OK
 }
@@ -374,53 +529,67 @@ pub unsafe fn ctx_read_pixels(
         }
     };
 
-    // Get the source handle and dimensions
-    let (src_handle, _src_width, _src_height, _src_format) = ctx.get_color_attachment_info(true);
+    let expected_size = (width as u64)
+        .saturating_mul(height as u64)
+        .saturating_mul(read_pixels_bpp(format, type_) as u64);
 
-    if !src_handle.is_valid() {
-        set_last_error("no color attachment to read from");
-        return 0;
+    // Allocate from blob arena
+    let ptr = ctx.alloc_blob(expected_size as u32);
+    let dest_slice =
+        unsafe { std::slice::from_raw_parts_mut(ptr as *mut u8, expected_size as usize) };
+
+    match read_color_into(ctx, x, y, width, height, format, type_, dest_slice) {
+        Ok(()) => ptr,
+        Err(_) => 0,
     }
+}
 
-    // Calculate bytes per pixel based on format and type
-    let bytes_per_pixel = if type_ == GL_FLOAT {
-        // GL_FLOAT
+/// Bytes per pixel `readPixels` writes for a format/type pair.
+pub(crate) fn read_pixels_bpp(format: u32, type_: u32) -> u32 {
+    if type_ == GL_FLOAT {
         match format {
-            GL_RED => 4,   // GL_RED: 1 channel
-            GL_RG => 8,    // GL_RG: 2 channels
-            GL_RGBA => 16, // GL_RGBA: 4 channels
-            _ => 16,       // Default
+            GL_RED => 4,
+            GL_RG => 8,
+            _ => 16,
         }
     } else if type_ == GL_INT || type_ == GL_UNSIGNED_INT {
         match format {
             GL_RED_INTEGER => 4,
             GL_RG_INTEGER => 8,
-            GL_RGBA_INTEGER => 16,
             _ => 16,
         }
     } else {
         // GL_UNSIGNED_BYTE or other
-        match format {
-            GL_RGBA => 4, // GL_RGBA: 4 bytes
-            _ => 4,
-        }
-    };
-
-    // Verify output buffer size
-    let expected_size = (width as u64)
-        .saturating_mul(height as u64)
-        .saturating_mul(bytes_per_pixel as u64);
+        4
+    }
+}
 
-    // Allocate from blob arena
-    let ptr = ctx.alloc_blob(expected_size as u32);
-    let dest_slice =
-        unsafe { std::slice::from_raw_parts_mut(ptr as *mut u8, expected_size as usize) };
+/// Read a rectangle of the read framebuffer's color buffer into `dest`,
+/// converted to `format`/`type_` as `readPixels` does. `dest` holds
+/// `width * height * read_pixels_bpp(format, type_)` bytes. `x`, `y` and the
+/// row order follow the context's [`ReadOrigin`].
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn read_color_into(
+    ctx: &Context,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    format: u32,
+    type_: u32,
+    dest: &mut [u8],
+) -> Result<(), u32> {
+    let (src_handle, _, _, _) = ctx.get_color_attachment_info(true);
+    if !src_handle.is_valid() {
+        set_last_error("no color attachment to read from");
+        return Err(ERR_INVALID_OPERATION);
+    }
 
     let src_buffer = match ctx.kernel.get_buffer(src_handle) {
         Some(b) => b,
         None => {
             set_last_error("source buffer not found in kernel");
-            return 0;
+            return Err(ERR_INTERNAL);
         }
     };
 
@@ -449,6 +618,12 @@ pub unsafe fn ctx_read_pixels(
         wgpu_types::TextureFormat::Rgba8Unorm
     };
 
+    let top_left = ctx.read_origin == ReadOrigin::TopLeft;
+    let y = if top_left {
+        src_buffer.height as i32 - y - height as i32
+    } else {
+        y
+    };
     crate::wasm_gl_emu::TransferEngine::read_pixels(
         &crate::wasm_gl_emu::TransferRequest {
             src_buffer,
@@ -459,8 +634,39 @@ pub unsafe fn ctx_read_pixels(
             width,
             height,
         },
-        dest_slice,
+        dest,
     );
 
-    ptr
+    // A drawing buffer without alpha reads as opaque
+    if ctx.bound_read_framebuffer.is_none() && !ctx.attributes.alpha && format == GL_RGBA {
+        if type_ == GL_FLOAT {
+            for pixel in dest.chunks_exact_mut(16) {
+                pixel[12..].copy_from_slice(&1.0f32.to_le_bytes());
+            }
+        } else {
+            for pixel in dest.chunks_exact_mut(4) {
+                pixel[3] = 255;
+            }
+        }
+    }
+
+    if top_left {
+        flip_rows(dest, height);
+    }
+    Ok(())
+}
+
+/// Reverse the order of the `height` equal rows in `data`.
+pub(crate) fn flip_rows<T>(data: &mut [T], height: u32) {
+    if height < 2 {
+        return;
+    }
+    let row_len = data.len() / height as usize;
+    let (mut top, mut bottom) = (0, height as usize - 1);
+    while top < bottom {
+        let (upper, lower) = data.split_at_mut(bottom * row_len);
+        upper[top * row_len..(top + 1) * row_len].swap_with_slice(&mut lower[..row_len]);
+        top += 1;
+        bottom -= 1;
+    }
 }
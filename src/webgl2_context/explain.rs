@@ -0,0 +1,413 @@
+//! Why a draw left no trace.
+//!
+//! Every draw call records a [`DrawReport`] that walks the usual WebGL
+//! debugging checklist: was a linked program in use, did the attributes have
+//! data, did the viewport and scissor box leave any pixels, what became of
+//! the primitives and of their fragments in the stencil and depth tests, and
+//! could blending or the color mask hide what was shaded. `issues` lists the
+//! likely reasons the draw changed nothing, most fundamental first.
+
+use super::registry::{clear_last_error, get_registry, set_last_error};
+use super::types::*;
+use crate::wasm_gl_emu::rasterizer::DrawStats;
+use crate::wasm_gl_emu::transfer::AttributeBinding;
+use serde::Serialize;
+
+/// The last draw call of a context and what became of it.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrawReport {
+    /// `drawArrays` or `drawElements`; instanced draws included
+    pub call: &'static str,
+    pub mode: String,
+    pub count: i32,
+    pub instance_count: i32,
+    /// Why the draw was rejected, when it drew nothing at all
+    pub error: Option<String>,
+    pub program: ProgramReport,
+    /// The program's attributes, by location
+    pub attributes: Vec<AttributeReport>,
+    pub viewport: [i32; 4],
+    /// Scissor box, when the scissor test is enabled
+    pub scissor: Option<[i32; 4]>,
+    /// Pixels of the draw target inside the viewport and scissor box
+    pub visible_pixels: u64,
+    pub stencil_test: TestReport,
+    pub depth_test: TestReport,
+    pub blend: BlendReport,
+    pub color_mask: [bool; 4],
+    /// Color buffers the draw writes to
+    pub color_targets: usize,
+    pub primitives: PrimitiveReport,
+    /// Fragments that passed the stencil and depth tests and were shaded
+    pub shaded_fragments: u64,
+    pub issues: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramReport {
+    /// Program in use; `None` with a program pipeline or nothing bound
+    pub handle: Option<u32>,
+    pub linked: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributeReport {
+    pub name: String,
+    pub location: i32,
+    pub enabled: bool,
+    pub buffer: Option<u32>,
+    pub buffer_bytes: u64,
+    /// Bytes the draw reads from the buffer; unknown for indexed draws
+    pub needed_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestReport {
+    pub enabled: bool,
+    pub func: String,
+    /// Fragments the test discarded
+    pub failed: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlendReport {
+    pub enabled: bool,
+    pub src_rgb: String,
+    pub dst_rgb: String,
+    /// The factors keep the destination color whatever the shader outputs
+    pub keeps_destination: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrimitiveReport {
+    /// Points and triangles assembled from the vertices
+    pub assembled: u64,
+    /// Entirely outside the clip volume
+    pub clipped: u64,
+    /// Discarded by face culling
+    pub culled: u64,
+    /// Covering no pixel center inside the draw target and scissor box
+    pub empty: u64,
+}
+
+/// A draw call as the application made it.
+pub(crate) struct DrawCall {
+    pub(crate) call: &'static str,
+    pub(crate) mode: u32,
+    /// First vertex of `drawArrays`; `None` for indexed draws
+    pub(crate) first: Option<i32>,
+    pub(crate) count: i32,
+    pub(crate) instance_count: i32,
+}
+
+/// Record the report of `draw`, which returned `code`, on the context.
+pub(crate) fn record_draw(ctx: u32, draw: DrawCall, code: u32) {
+    let error = (code != ERR_OK).then(|| {
+        crate::error::get_last_error_message().unwrap_or_else(|| format!("error code {}", code))
+    });
+    let mut reg = get_registry().borrow_mut();
+    let Some(ctx_obj) = reg.contexts.get_mut(&ctx) else {
+        return;
+    };
+    let stats = ctx_obj.rasterizer.take_draw_stats();
+    let report = build_report(ctx_obj, &draw, error, stats);
+    ctx_obj.last_draw = Some(report);
+}
+
+fn enum_label(value: u32) -> String {
+    enum_name(value).map_or_else(|| format!("0x{:04X}", value), str::to_string)
+}
+
+/// `enum_label` for blend factors, whose `ZERO` and `ONE` share values with
+/// other enums.
+fn factor_label(factor: u32) -> String {
+    match factor {
+        GL_ZERO => "ZERO".to_string(),
+        GL_ONE => "ONE".to_string(),
+        _ => enum_label(factor),
+    }
+}
+
+fn build_report(
+    ctx: &Context,
+    draw: &DrawCall,
+    error: Option<String>,
+    stats: DrawStats,
+) -> DrawReport {
+    let program = ctx
+        .current_program
+        .and_then(|handle| ctx.programs.get(&handle).map(|p| (handle, p)));
+    let attributes = program.map_or_else(Vec::new, |(_, p)| attribute_reports(ctx, p, draw));
+
+    let (color_handles, _, target_width, target_height) = ctx.get_draw_targets();
+    let (vx, vy, vw, vh) = ctx.viewport;
+    let mut visible = (
+        vx.max(0),
+        vy.max(0),
+        (vx + vw as i32).min(target_width as i32),
+        (vy + vh as i32).min(target_height as i32),
+    );
+    let scissor = ctx.scissor_test_enabled.then(|| {
+        let (sx, sy, sw, sh) = ctx.scissor_box;
+        visible = (
+            visible.0.max(sx),
+            visible.1.max(sy),
+            visible.2.min(sx + sw as i32),
+            visible.3.min(sy + sh as i32),
+        );
+        [sx, sy, sw as i32, sh as i32]
+    });
+    let visible_pixels =
+        (visible.2 - visible.0).max(0) as u64 * (visible.3 - visible.1).max(0) as u64;
+
+    let depth = ctx.draw_depth_state();
+    let stencil = ctx.draw_stencil_state();
+    let blend = ctx.draw_blend_state();
+    let mask = ctx.draw_color_mask();
+
+    let mut report = DrawReport {
+        call: draw.call,
+        mode: primitive_mode_name(draw.mode).map_or_else(|| enum_label(draw.mode), str::to_string),
+        count: draw.count,
+        instance_count: draw.instance_count,
+        error,
+        program: ProgramReport {
+            handle: program.map(|(handle, _)| handle),
+            linked: program.is_some_and(|(_, p)| p.linked),
+        },
+        attributes,
+        viewport: [vx, vy, vw as i32, vh as i32],
+        scissor,
+        visible_pixels,
+        stencil_test: TestReport {
+            enabled: stencil.enabled,
+            func: enum_label(stencil.front.func),
+            failed: stats.stencil_failed,
+        },
+        depth_test: TestReport {
+            enabled: depth.enabled,
+            func: enum_label(depth.func),
+            failed: stats.depth_failed,
+        },
+        blend: BlendReport {
+            enabled: blend.enabled,
+            src_rgb: factor_label(blend.src_rgb),
+            dst_rgb: factor_label(blend.dst_rgb),
+            keeps_destination: blend.enabled
+                && blend.eq_rgb == GL_FUNC_ADD
+                && blend.src_rgb == GL_ZERO
+                && blend.dst_rgb == GL_ONE,
+        },
+        color_mask: [mask.r, mask.g, mask.b, mask.a],
+        color_targets: color_handles.iter().filter(|h| h.is_valid()).count(),
+        primitives: PrimitiveReport {
+            assembled: stats.primitives,
+            clipped: stats.clipped,
+            culled: stats.culled,
+            empty: stats.empty,
+        },
+        shaded_fragments: stats.shaded,
+        issues: Vec::new(),
+    };
+    report.issues = issues(ctx, draw, &report);
+    report
+}
+
+fn attribute_reports(ctx: &Context, program: &Program, draw: &DrawCall) -> Vec<AttributeReport> {
+    let vao = ctx.vertex_arrays.get(&ctx.bound_vertex_array);
+    let mut attributes: Vec<(&String, i32)> = program
+        .attributes
+        .iter()
+        .map(|(name, &location)| (name, location))
+        .filter(|&(_, location)| location >= 0)
+        .collect();
+    attributes.sort_by_key(|&(_, location)| location);
+
+    attributes
+        .into_iter()
+        .map(|(name, location)| {
+            let attribute = vao.and_then(|vao| vao.attributes.get(location as usize));
+            let enabled = attribute.is_some_and(|a| a.enabled);
+            let buffer = attribute.and_then(|a| a.buffer);
+            let buffer_bytes = buffer
+                .and_then(|b| ctx.buffers.get(&b))
+                .and_then(|b| ctx.kernel.get_buffer(b.gpu_handle))
+                .map_or(0, |b| b.data.len() as u64);
+            let needed_bytes = attribute
+                .filter(|a| a.enabled)
+                .zip(draw.first)
+                .and_then(|(a, first)| needed_bytes(a, first, draw.count, draw.instance_count));
+            AttributeReport {
+                name: name.clone(),
+                location,
+                enabled,
+                buffer,
+                buffer_bytes,
+                needed_bytes,
+            }
+        })
+        .collect()
+}
+
+/// Bytes of its buffer an enabled attribute reads in a non-indexed draw.
+fn needed_bytes(
+    attribute: &VertexAttribute,
+    first: i32,
+    count: i32,
+    instance_count: i32,
+) -> Option<u64> {
+    let last = if attribute.divisor == 0 {
+        (first as i64 + count as i64 - 1).max(0)
+    } else {
+        ((instance_count as i64 - 1) / attribute.divisor as i64).max(0)
+    };
+    let element = AttributeBinding::tight_stride(attribute.type_, attribute.size) as i64;
+    let stride = if attribute.stride == 0 {
+        element
+    } else {
+        attribute.stride as i64
+    };
+    (count > 0 && instance_count > 0)
+        .then(|| (attribute.offset as i64 + last * stride + element) as u64)
+}
+
+/// Likely reasons the draw changed nothing, most fundamental first.
+fn issues(ctx: &Context, draw: &DrawCall, report: &DrawReport) -> Vec<String> {
+    let mut issues = Vec::new();
+    if let Some(error) = &report.error {
+        issues.push(format!("the draw was rejected: {}", error));
+        return issues;
+    }
+    if report.count <= 0 {
+        issues.push("count is 0, so there is nothing to draw".to_string());
+    }
+    if report.instance_count <= 0 {
+        issues.push("instance count is 0, so there is nothing to draw".to_string());
+    }
+    if report.program.handle.is_some() && !report.program.linked {
+        issues.push("the program in use is not linked".to_string());
+    }
+    for attribute in &report.attributes {
+        let label = format!(
+            "attribute '{}' (location {})",
+            attribute.name, attribute.location
+        );
+        if !attribute.enabled {
+            issues.push(format!(
+                "{} is not enabled, so every vertex reads its constant value",
+                label
+            ));
+        } else if attribute.buffer.is_none() {
+            issues.push(format!("{} is enabled but has no buffer", label));
+        } else if let Some(needed) = attribute.needed_bytes {
+            if needed > attribute.buffer_bytes {
+                issues.push(format!(
+                    "{} reads {} bytes but its buffer holds {}",
+                    label, needed, attribute.buffer_bytes
+                ));
+            }
+        }
+    }
+    if !matches!(draw.mode, GL_POINTS | GL_TRIANGLES | GL_TRIANGLE_STRIP) {
+        issues.push(format!(
+            "{} primitives are not rasterized by the emulator",
+            report.mode
+        ));
+    }
+    if report.visible_pixels == 0 {
+        issues.push(if report.scissor.is_some() {
+            "the viewport and scissor box leave no pixels of the draw target".to_string()
+        } else {
+            "the viewport covers no pixels of the draw target".to_string()
+        });
+    }
+
+    let primitives = &report.primitives;
+    if primitives.assembled > 0 && report.shaded_fragments == 0 {
+        if primitives.clipped == primitives.assembled {
+            issues.push(
+                "every primitive is outside the clip volume; check gl_Position and its w"
+                    .to_string(),
+            );
+        } else if primitives.culled > 0
+            && primitives.clipped + primitives.culled == primitives.assembled
+        {
+            issues.push(format!(
+                "every primitive in view was culled (cullFace {}, frontFace {})",
+                enum_label(ctx.cull_face_mode),
+                enum_label(ctx.front_face)
+            ));
+        } else if primitives.empty > 0
+            && primitives.clipped + primitives.culled + primitives.empty == primitives.assembled
+        {
+            issues.push(
+                "the primitives cover no pixel centers inside the draw target and scissor box"
+                    .to_string(),
+            );
+        }
+        if report.stencil_test.failed > 0 {
+            issues.push(format!(
+                "{} fragments failed the stencil test (stencilFunc {})",
+                report.stencil_test.failed, report.stencil_test.func
+            ));
+        }
+        if report.depth_test.failed > 0 {
+            issues.push(format!(
+                "{} fragments failed the depth test (depthFunc {}, clearDepth {})",
+                report.depth_test.failed, report.depth_test.func, ctx.clear_depth
+            ));
+        }
+    }
+
+    if report.shaded_fragments > 0 {
+        if report.color_targets == 0 {
+            issues.push("no color buffer is bound for drawing".to_string());
+        }
+        if report.color_mask.iter().all(|&channel| !channel) {
+            issues.push("colorMask disables every channel".to_string());
+        }
+        if report.blend.keeps_destination {
+            issues.push(format!(
+                "blending with {}, {} keeps the destination color",
+                report.blend.src_rgb, report.blend.dst_rgb
+            ));
+        }
+    }
+    issues
+}
+
+/// Copy the JSON report of the context's last draw to `ptr`, at most
+/// `max_len` bytes. With `ptr == 0` returns the report's length. Returns 0
+/// before the first draw.
+pub fn ctx_explain_last_draw(ctx: u32, ptr: u32, max_len: u32) -> u32 {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    let ctx_obj = match reg.contexts.get(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return 0;
+        }
+    };
+    let Some(report) = &ctx_obj.last_draw else {
+        return 0;
+    };
+    let json = serde_json::to_string(report).unwrap_or_default();
+    let bytes = json.as_bytes();
+    if ptr == 0 {
+        return bytes.len() as u32;
+    }
+    let copy_len = std::cmp::min(bytes.len() as u32, max_len);
+    let dest_slice = match crate::guest_mem::write(ptr, copy_len) {
+        Ok(s) => s,
+        Err(_) => return 0,
+    };
+    dest_slice.copy_from_slice(&bytes[..copy_len as usize]);
+    copy_len
+}
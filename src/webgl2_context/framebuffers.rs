@@ -5,9 +5,6 @@ use super::types::*;
 // Framebuffer Operations
 // ============================================================================
 
-pub const GL_READ_FRAMEBUFFER: u32 = 0x8CA8;
-pub const GL_DRAW_FRAMEBUFFER: u32 = 0x8CA9;
-
 /// Check if object is a framebuffer.
 pub fn ctx_is_framebuffer(ctx: u32, handle: u32) -> bool {
     clear_last_error();
@@ -79,6 +76,8 @@ pub fn ctx_delete_framebuffer(ctx: u32, fb: u32) -> u32 {
     if ctx_obj.bound_draw_framebuffer == Some(fb) {
         ctx_obj.bound_draw_framebuffer = None;
     }
+    // Attachments flagged for deletion may have been held only by this one
+    super::deletion::release_deleted_objects(ctx_obj);
     ERR_OK
 }
 
@@ -123,20 +122,31 @@ pub fn ctx_bind_framebuffer(ctx: u32, target: u32, fb: u32) -> u32 {
     ERR_OK
 }
 
-/// Check framebuffer status.
-pub fn ctx_check_framebuffer_status(ctx: u32, _target: u32) -> u32 {
+/// Check the completeness of the framebuffer bound to `target`. The default
+/// framebuffer is always complete.
+pub fn ctx_check_framebuffer_status(ctx: u32, target: u32) -> u32 {
     clear_last_error();
-    let reg = get_registry().borrow();
-    match reg.contexts.get(&ctx) {
-        Some(_) => {
-            // For now, we always return COMPLETE if the context is valid.
-            // In a more complete implementation, we'd check the attachments.
-            GL_FRAMEBUFFER_COMPLETE
-        }
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
         None => {
             set_last_error("invalid context handle");
-            0 // Should probably be GL_INVALID_ENUM or similar in a real GL but here we return 0 as error
+            return 0;
+        }
+    };
+
+    let fb_handle = match target {
+        GL_FRAMEBUFFER | GL_DRAW_FRAMEBUFFER => ctx_obj.bound_draw_framebuffer,
+        GL_READ_FRAMEBUFFER => ctx_obj.bound_read_framebuffer,
+        _ => {
+            set_last_error(&format!("invalid target: {}", describe_enum(target)));
+            ctx_obj.set_error(GL_INVALID_ENUM);
+            return 0;
         }
+    };
+    match fb_handle.and_then(|h| ctx_obj.framebuffers.get(&h)) {
+        Some(fb) => ctx_obj.framebuffer_status(fb),
+        None => GL_FRAMEBUFFER_COMPLETE,
     }
 }
 
@@ -173,6 +183,9 @@ pub fn ctx_framebuffer_texture2d(
             return ERR_INVALID_OPERATION;
         }
     };
+    if let Err(code) = check_color_attachment(ctx_obj, _attachment) {
+        return code;
+    }
 
     let fb = match ctx_obj.framebuffers.get_mut(&fb_handle) {
         Some(f) => f,
@@ -217,6 +230,7 @@ pub fn ctx_framebuffer_texture2d(
         fb.color_attachments[0] = attachment_obj;
     }
 
+    super::deletion::release_deleted_objects(ctx_obj);
     ERR_OK
 }
 
@@ -260,6 +274,9 @@ pub fn ctx_framebuffer_renderbuffer(
             return ERR_INVALID_OPERATION;
         }
     };
+    if let Err(code) = check_color_attachment(ctx_obj, attachment) {
+        return code;
+    }
 
     let fb = match ctx_obj.framebuffers.get_mut(&fb_handle) {
         Some(f) => f,
@@ -286,13 +303,33 @@ pub fn ctx_framebuffer_renderbuffer(
         fb.depth_attachment = attachment_obj;
         fb.stencil_attachment = attachment_obj;
     } else {
-        set_last_error("invalid attachment");
+        set_last_error(&format!(
+            "invalid attachment: {}",
+            describe_enum(attachment)
+        ));
         return ERR_INVALID_ENUM;
     }
 
+    super::deletion::release_deleted_objects(ctx_obj);
     ERR_OK
 }
 
+/// Fail with INVALID_ENUM for a color attachment at or past
+/// `MAX_COLOR_ATTACHMENTS`.
+fn check_color_attachment(ctx_obj: &mut Context, attachment: u32) -> Result<(), u32> {
+    let max = ctx_obj.limits.max_draw_buffers;
+    if (GL_COLOR_ATTACHMENT0 + max..=GL_COLOR_ATTACHMENT7).contains(&attachment) {
+        set_last_error(&format!(
+            "{} is not below MAX_COLOR_ATTACHMENTS ({})",
+            describe_enum(attachment),
+            max
+        ));
+        ctx_obj.set_error(GL_INVALID_ENUM);
+        return Err(ERR_GL);
+    }
+    Ok(())
+}
+
 /// Blit a region from the read framebuffer to the draw framebuffer.
 #[allow(clippy::too_many_arguments)]
 pub fn ctx_blit_framebuffer(
@@ -352,10 +389,13 @@ pub fn ctx_draw_buffers(ctx: u32, ptr: u32, count: u32) -> u32 {
                 set_last_error("default framebuffer only supports one draw buffer");
                 return GL_INVALID_VALUE;
             }
-            let buf_slice = unsafe { std::slice::from_raw_parts(ptr as *const u32, 1) };
+            let buf_slice = match crate::guest_mem::read_slice(ptr as *const u32, 1) {
+                Ok(s) => s,
+                Err(code) => return code,
+            };
             let db = buf_slice[0];
             // TODO: consider if we can refer to named constants GL_NONE or GL_BACK
-            if db != 0 && db != 0x0405 {
+            if db != GL_NONE && db != GL_BACK {
                 set_last_error("invalid draw buffer for default framebuffer");
                 return ERR_INVALID_OPERATION;
             }
@@ -364,21 +404,28 @@ pub fn ctx_draw_buffers(ctx: u32, ptr: u32, count: u32) -> u32 {
         }
     };
 
+    if count > ctx_obj.limits.max_draw_buffers {
+        set_last_error(&format!(
+            "{} draw buffers exceed MAX_DRAW_BUFFERS ({})",
+            count, ctx_obj.limits.max_draw_buffers
+        ));
+        ctx_obj.set_error(GL_INVALID_VALUE);
+        return ERR_GL;
+    }
+
     let fb = match ctx_obj.framebuffers.get_mut(&fb_handle) {
         Some(f) => f,
         None => return ERR_INTERNAL,
     };
 
-    if count as usize > MAX_DRAW_BUFFERS {
-        set_last_error("too many draw buffers");
-        return GL_INVALID_VALUE;
-    }
-
-    let buf_slice = unsafe { std::slice::from_raw_parts(ptr as *const u32, count as usize) };
+    let buf_slice = match crate::guest_mem::read_slice(ptr as *const u32, count as usize) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
     let mut new_draw_buffers = [GL_NONE; MAX_DRAW_BUFFERS];
     for (i, &buf) in buf_slice.iter().enumerate() {
         // TODO: consider if we can refer to named constants GL_NONE or GL_COLOR_ATTACHMENTi
-        if buf != 0 && buf != 0x8CE0 + i as u32 {
+        if buf != GL_NONE && buf != GL_COLOR_ATTACHMENT0 + i as u32 {
             set_last_error("invalid draw buffer enum for framebuffer object");
             return ERR_INVALID_OPERATION;
         }
@@ -400,13 +447,16 @@ pub fn ctx_read_buffer(ctx: u32, mode: u32) -> u32 {
 
     if let Some(fb_handle) = ctx_obj.bound_read_framebuffer {
         if let Some(fb) = ctx_obj.framebuffers.get_mut(&fb_handle) {
-            if mode == 0x0405 {
+            if mode == GL_BACK {
                 // GL_BACK
                 set_last_error("invalid read buffer BACK for framebuffer object");
                 return ERR_INVALID_OPERATION;
             }
             // GL_NONE (0) or GL_COLOR_ATTACHMENTi
-            if mode != 0 && (mode < 0x8CE0 || mode >= 0x8CE0 + MAX_DRAW_BUFFERS as u32) {
+            if mode != GL_NONE
+                && (mode < GL_COLOR_ATTACHMENT0
+                    || mode >= GL_COLOR_ATTACHMENT0 + MAX_DRAW_BUFFERS as u32)
+            {
                 set_last_error("invalid read buffer enum");
                 return ERR_INVALID_ENUM;
             }
@@ -414,11 +464,11 @@ pub fn ctx_read_buffer(ctx: u32, mode: u32) -> u32 {
         }
     } else {
         // Default framebuffer
-        if (0x8CE0..=0x8CE7).contains(&mode) {
+        if (GL_COLOR_ATTACHMENT0..=GL_COLOR_ATTACHMENT7).contains(&mode) {
             set_last_error("invalid read buffer color attachment for default framebuffer");
             return ERR_INVALID_OPERATION;
         }
-        if mode != 0 && mode != 0x0405 {
+        if mode != GL_NONE && mode != GL_BACK {
             // GL_NONE or GL_BACK
             set_last_error("invalid read buffer for default framebuffer");
             return ERR_INVALID_ENUM;
@@ -0,0 +1,300 @@
+//! GLSL ES 1.00 support for WebGL1 contexts.
+//!
+//! WebGL2 contexts created with the GLSL ES 1.00 compatibility shim use the
+//! same translation for shaders that don't declare `#version 300 es`.
+//!
+//! Naga parses GLSL ES 3.00, so a WebGL1 context rewrites each 1.00 shader
+//! into the equivalent 3.00 source before compiling it: `attribute` and
+//! `varying` become `in`/`out`, the 1.00 texture lookup functions become their
+//! overloaded 3.00 forms, and `gl_FragColor` becomes a declared output.
+//! Extensions that 3.00 made core are dropped, and identifiers that became
+//! reserved in 3.00 are renamed.
+//!
+//! Rewrites keep every line in place, so info log lines match the 1.00
+//! source. The one exception is a shader without a `#version` directive,
+//! which gets `#version 300 es` as an extra first line; `added_lines` reports
+//! this so the caller can shift info log line numbers back.
+
+use super::diagnostics::format_error;
+
+/// Prefix for outputs and renamed identifiers, outside the user's namespace
+/// of shaders that compile as GLSL ES 1.00.
+const PREFIX: &str = "webgl1_";
+
+/// Extensions whose functionality is core in GLSL ES 3.00.
+const CORE_EXTENSIONS: &[&str] = &[
+    "GL_OES_standard_derivatives",
+    "GL_EXT_shader_texture_lod",
+    "GL_EXT_frag_depth",
+    "GL_EXT_draw_buffers",
+];
+
+/// Identifiers that are free in GLSL ES 1.00 but keywords or built-in
+/// functions in 3.00.
+const RESERVED_IN_300: &[&str] = &[
+    "texture",
+    "layout",
+    "centroid",
+    "flat",
+    "smooth",
+    "uint",
+    "uvec2",
+    "uvec3",
+    "uvec4",
+    "sampler3D",
+    "sampler2DArray",
+];
+
+/// A GLSL ES 1.00 shader rewritten as GLSL ES 3.00.
+#[derive(Debug)]
+pub(crate) struct Translated {
+    pub(crate) source: String,
+    /// Lines inserted before the first line of the original source
+    pub(crate) added_lines: u32,
+}
+
+/// Whether `source` is GLSL ES 1.00: it declares `#version 100`, or no
+/// version at all.
+pub(crate) fn is_glsl100(source: &str) -> bool {
+    source
+        .lines()
+        .find_map(|line| {
+            line.trim_start()
+                .strip_prefix('#')
+                .map(str::trim_start)
+                .and_then(|rest| rest.strip_prefix("version"))
+        })
+        .is_none_or(|version| version.trim() == "100")
+}
+
+/// Rewrite GLSL ES 1.00 `source` for `stage` as GLSL ES 3.00, returning an
+/// info log if the source declares another version.
+pub(crate) fn translate(source: &str, stage: naga::ShaderStage) -> Result<Translated, String> {
+    let mut body = String::with_capacity(source.len() + 64);
+    let mut has_version = false;
+    for line in source.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let ending = &line[content.len()..];
+        let directive = content.trim_start();
+        if let Some(rest) = directive.strip_prefix('#').map(str::trim_start) {
+            if let Some(version) = rest.strip_prefix("version") {
+                let version = version.trim();
+                if version != "100" {
+                    return Err(format_error(
+                        "version",
+                        &format!("GLSL ES {} is not available in a WebGL1 context", version),
+                    ));
+                }
+                has_version = true;
+                body.push_str("#version 300 es");
+                body.push_str(ending);
+                continue;
+            }
+            if let Some(extension) = rest.strip_prefix("extension") {
+                let name = extension.split(':').next().unwrap_or("").trim();
+                if CORE_EXTENSIONS.contains(&name) {
+                    body.push_str(ending);
+                    continue;
+                }
+            }
+        }
+        body.push_str(line);
+    }
+
+    let mut translated = rewrite_identifiers(&body, stage);
+    let mut added_lines = 0;
+    if !has_version {
+        translated.insert_str(0, "#version 300 es\n");
+        added_lines = 1;
+    }
+    Ok(Translated {
+        source: translated,
+        added_lines,
+    })
+}
+
+/// Rewrite the identifiers of `source` outside comments, declaring the
+/// fragment output in front of `main` if `gl_FragColor` or `gl_FragData[0]`
+/// is written.
+fn rewrite_identifiers(source: &str, stage: naga::ShaderStage) -> String {
+    let fragment = stage == naga::ShaderStage::Fragment;
+    let bytes = source.as_bytes();
+    let mut out = String::with_capacity(source.len() + 64);
+    let mut uses_frag_color = false;
+    let mut main_position = None;
+    let mut depth = 0u32;
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &source[i..];
+        if rest.starts_with("//") {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            out.push_str(&rest[..end]);
+            i += end;
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            let end = body.find("*/").map_or(rest.len(), |end| end + 4);
+            out.push_str(&rest[..end]);
+            i += end;
+        } else if bytes[i].is_ascii_digit() {
+            // Numbers may contain letters (`1e5`, `0xFF`); skip them whole
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            out.push_str(&rest[..end]);
+            i += end;
+        } else if bytes[i].is_ascii_alphabetic() || bytes[i] == b'_' {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            i += end;
+            match word {
+                "void" if depth == 0 && next_word(&source[i..]) == Some("main") => {
+                    main_position.get_or_insert(out.len());
+                    out.push_str(word);
+                }
+                "attribute" if !fragment => out.push_str("in"),
+                "varying" => out.push_str(if fragment { "in" } else { "out" }),
+                "texture2D" | "textureCube" => out.push_str("texture"),
+                "texture2DProj" => out.push_str("textureProj"),
+                "texture2DLod" | "textureCubeLod" | "texture2DLodEXT" | "textureCubeLodEXT" => {
+                    out.push_str("textureLod")
+                }
+                "texture2DProjLod" | "texture2DProjLodEXT" => out.push_str("textureProjLod"),
+                "texture2DGradEXT" | "textureCubeGradEXT" => out.push_str("textureGrad"),
+                "texture2DProjGradEXT" => out.push_str("textureProjGrad"),
+                "gl_FragColor" if fragment => {
+                    uses_frag_color = true;
+                    out.push_str(PREFIX);
+                    out.push_str("FragColor");
+                }
+                "gl_FragData" if fragment => match first_element(&source[i..]) {
+                    Some(len) => {
+                        uses_frag_color = true;
+                        out.push_str(PREFIX);
+                        out.push_str("FragColor");
+                        i += len;
+                    }
+                    None => out.push_str(word),
+                },
+                "gl_FragDepthEXT" if fragment => out.push_str("gl_FragDepth"),
+                _ if RESERVED_IN_300.contains(&word) => {
+                    out.push_str(PREFIX);
+                    out.push_str(word);
+                }
+                _ => out.push_str(word),
+            }
+        } else {
+            let c = rest.chars().next().unwrap();
+            match c {
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            out.push(c);
+            i += c.len_utf8();
+        }
+    }
+
+    if uses_frag_color {
+        let declaration = format!("out highp vec4 {}FragColor; ", PREFIX);
+        match main_position {
+            Some(position) => out.insert_str(position, &declaration),
+            None => out.push_str(&declaration),
+        }
+    }
+    out
+}
+
+/// The identifier at the start of `source`, after whitespace.
+fn next_word(source: &str) -> Option<&str> {
+    let source = source.trim_start();
+    let end = source
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(source.len());
+    (end > 0).then(|| &source[..end])
+}
+
+/// Length of a `[0]` subscript at the start of `source`, if there is one.
+/// Without `EXT_draw_buffers`, element 0 is the only one a shader can write.
+fn first_element(source: &str) -> Option<usize> {
+    let mut len = 0;
+    for expected in ["[", "0", "]"] {
+        let rest = &source[len..];
+        let trimmed = rest.trim_start();
+        len += rest.len() - trimmed.len();
+        if !trimmed.starts_with(expected) {
+            return None;
+        }
+        len += 1;
+    }
+    Some(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vertex_shader_keywords() {
+        let translated = translate(
+            "#version 100\n\
+             attribute vec2 position; // attribute varying\n\
+             varying vec2 uv;\n\
+             void main() { uv = position; gl_Position = vec4(position, 0.0, 1.0); }\n",
+            naga::ShaderStage::Vertex,
+        )
+        .unwrap();
+        assert_eq!(translated.added_lines, 0);
+        assert_eq!(
+            translated.source,
+            "#version 300 es\n\
+             in vec2 position; // attribute varying\n\
+             out vec2 uv;\n\
+             void main() { uv = position; gl_Position = vec4(position, 0.0, 1.0); }\n"
+        );
+    }
+
+    #[test]
+    fn test_fragment_shader_outputs_and_lookups() {
+        let translated = translate(
+            "#extension GL_OES_standard_derivatives : enable\n\
+             precision mediump float;\n\
+             uniform sampler2D texture;\n\
+             varying vec2 uv;\n\
+             void main() {\n\
+             gl_FragData [ 0 ] = texture2D(texture, uv) + vec4(dFdx(uv.x));\n\
+             }\n",
+            naga::ShaderStage::Fragment,
+        )
+        .unwrap();
+        assert_eq!(translated.added_lines, 1);
+        assert_eq!(
+            translated.source,
+            "#version 300 es\n\
+             \n\
+             precision mediump float;\n\
+             uniform sampler2D webgl1_texture;\n\
+             in vec2 uv;\n\
+             out highp vec4 webgl1_FragColor; void main() {\n\
+             webgl1_FragColor = texture(webgl1_texture, uv) + vec4(dFdx(uv.x));\n\
+             }\n"
+        );
+    }
+
+    #[test]
+    fn test_glsl_300_is_rejected() {
+        let log = translate(
+            "#version 300 es\nvoid main() {}\n",
+            naga::ShaderStage::Vertex,
+        )
+        .unwrap_err();
+        assert!(log.starts_with("ERROR: 0:0: 'version' : GLSL ES 300 es is not available"));
+    }
+
+    #[test]
+    fn test_version_detection() {
+        assert!(is_glsl100("attribute vec2 position;\nvoid main() {}\n"));
+        assert!(is_glsl100("// comment\n  #  version 100\nvoid main() {}\n"));
+        assert!(!is_glsl100("#version 300 es\nin vec2 position;\n"));
+    }
+}
@@ -0,0 +1,1173 @@
+//! Hardware cross-check backend (`gpu-backend` feature).
+//!
+//! A context created with flag bit 10 forwards its draws to a wgpu device
+//! instead of the software rasterizer, through the same `ctx_*` calls, so a
+//! scene can be rendered both ways and the readbacks compared. The device is
+//! opened on the first forwarded draw and shared by every context;
+//! `WGPU_BACKEND` and `WGPU_POWER_PREF` pick it as they do in wgpu.
+//!
+//! Each draw is a render pass over an RGBA8 copy of the default
+//! framebuffer's color buffer, which is read back afterwards, so clears,
+//! `readPixels` and later software draws see the result. The program's Naga
+//! modules are rebound the way WebGPU expects them: attributes at their
+//! linked locations, each varying at a location of its own, and each
+//! default-block uniform as a uniform buffer of group 0.
+//!
+//! Draws the device cannot reproduce stay in software, with a warning:
+//! framebuffer objects, depth and stencil testing, textures and uniform
+//! blocks, uniform arrays and structs, program pipelines, transform feedback,
+//! LINE_LOOP, TRIANGLE_FAN, and attribute formats or alignments WebGPU lacks.
+//! Wasm builds find no adapter, so all their draws stay in software.
+
+use super::types::*;
+use crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset;
+use crate::wasm_gl_emu::rasterizer::{BlendState, DebugView};
+use crate::wasm_gl_emu::transfer::AttributeBinding;
+use naga::{AddressSpace, Binding, ShaderStage, TypeInner};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use wgpu_core::global::Global;
+use wgpu_core::id::{
+    BindGroupId, BindGroupLayoutId, BufferId, CommandBufferId, CommandEncoderId, DeviceId,
+    PipelineLayoutId, QueueId, RenderPipelineId, ShaderModuleId, TextureId, TextureViewId,
+};
+use wgpu_core::{binding_model, command, pipeline, resource};
+use wgpu_types as wgt;
+
+// Opened on the first forwarded draw; None if no adapter was found. Never
+// dropped: wgpu-core cannot tear a device down from a thread-local destructor.
+static DEVICE: OnceLock<Option<GpuDevice>> = OnceLock::new();
+
+/// A draw call, as issued.
+pub(crate) struct Draw {
+    pub mode: u32,
+    pub first: i32,
+    pub count: i32,
+    pub instance_count: i32,
+    /// Index type and byte offset into the element array buffer (drawElements)
+    pub indices: Option<(u32, u32)>,
+}
+
+/// Run `draw` on the device and store the result in the default framebuffer.
+///
+/// Returns false, leaving the framebuffer untouched, if the draw has to be
+/// rasterized in software instead.
+pub(crate) fn forward_draw(
+    ctx_obj: &mut Context,
+    vs_program: u32,
+    fs_program: u32,
+    draw: &Draw,
+) -> bool {
+    let result = prepare(ctx_obj, vs_program, fs_program, draw).and_then(|frame| {
+        match DEVICE.get_or_init(GpuDevice::open) {
+            Some(device) => device.draw(frame),
+            None => Err("no GPU adapter is available".to_string()),
+        }
+    });
+    match result {
+        Ok(mut pixels) => {
+            super::drawing::flip_rows(&mut pixels, ctx_obj.default_framebuffer.height);
+            if let Some(color) = ctx_obj
+                .kernel
+                .get_buffer_mut(ctx_obj.default_framebuffer.gpu_handle)
+            {
+                color.data[..pixels.len()].copy_from_slice(&pixels);
+            }
+            true
+        }
+        Err(reason) => {
+            tracing::warn!("gpu backend: drawing in software: {}", reason);
+            false
+        }
+    }
+}
+
+/// Everything the device needs for one draw, read from the context.
+struct Frame {
+    vs: naga::Module,
+    fs: naga::Module,
+    /// Value of each uniform buffer binding
+    uniforms: Vec<Vec<u8>>,
+    /// Vertex buffer contents, shared by the inputs reading them
+    buffers: Vec<Vec<u8>>,
+    inputs: Vec<VertexInput>,
+    indices: Option<(Vec<u8>, wgt::IndexFormat)>,
+    primitive: wgt::PrimitiveState,
+    blend: Option<wgt::BlendState>,
+    blend_constant: wgt::Color,
+    write_mask: wgt::ColorWrites,
+    /// x, y, width, height in framebuffer coordinates (top-left origin)
+    viewport: [f32; 4],
+    scissor: [u32; 4],
+    first: u32,
+    count: u32,
+    instance_count: u32,
+    width: u32,
+    height: u32,
+    /// RGBA8 color buffer, top row first
+    pixels: Vec<u8>,
+}
+
+/// A vertex shader input and the buffer feeding it.
+struct VertexInput {
+    location: u32,
+    /// Index into [`Frame::buffers`]
+    buffer: usize,
+    offset: u64,
+    stride: u64,
+    step_mode: wgt::VertexStepMode,
+    format: wgt::VertexFormat,
+}
+
+/// Gather the state of a draw, or the reason the device cannot run it.
+fn prepare(
+    ctx_obj: &Context,
+    vs_program: u32,
+    fs_program: u32,
+    draw: &Draw,
+) -> Result<Frame, String> {
+    if vs_program != fs_program {
+        return Err("the stages come from a program pipeline".into());
+    }
+    if ctx_obj.bound_draw_framebuffer.is_some() {
+        return Err("a framebuffer object is bound".into());
+    }
+    if ctx_obj.default_draw_buffers[0] == GL_NONE {
+        return Err("the draw buffer is NONE".into());
+    }
+    if ctx_obj.debug_view != DebugView::None {
+        return Err("a debug view is active".into());
+    }
+    if ctx_obj.draw_depth_state().enabled || ctx_obj.draw_stencil_state().enabled {
+        return Err("depth and stencil testing are not forwarded".into());
+    }
+    let tf = ctx_obj.bound_transform_feedback.unwrap_or(0);
+    if ctx_obj
+        .transform_feedbacks
+        .get(&tf)
+        .is_some_and(|tf| tf.active && !tf.paused)
+    {
+        return Err("transform feedback is active".into());
+    }
+    let (first, count, instance_count) = match (
+        u32::try_from(draw.first),
+        u32::try_from(draw.count),
+        u32::try_from(draw.instance_count),
+    ) {
+        (Ok(first), Ok(count), Ok(instances)) => (first, count, instances),
+        _ => return Err("negative first, count or instance count".into()),
+    };
+
+    let topology = match draw.mode {
+        GL_POINTS => wgt::PrimitiveTopology::PointList,
+        GL_LINES => wgt::PrimitiveTopology::LineList,
+        GL_LINE_STRIP => wgt::PrimitiveTopology::LineStrip,
+        GL_TRIANGLES => wgt::PrimitiveTopology::TriangleList,
+        GL_TRIANGLE_STRIP => wgt::PrimitiveTopology::TriangleStrip,
+        mode => return Err(format!("{} has no WebGPU topology", describe_enum(mode))),
+    };
+    let cull_mode = match (ctx_obj.cull_face_enabled, ctx_obj.cull_face_mode) {
+        (false, _) => None,
+        (true, GL_FRONT) => Some(wgt::Face::Front),
+        (true, GL_BACK) => Some(wgt::Face::Back),
+        (true, _) => return Err("FRONT_AND_BACK culling is not forwarded".into()),
+    };
+    let front_face = match ctx_obj.front_face {
+        GL_CW => wgt::FrontFace::Cw,
+        _ => wgt::FrontFace::Ccw,
+    };
+
+    let p = ctx_obj
+        .programs
+        .get(&vs_program)
+        .ok_or("the program was not found")?;
+    let (Some(vs), Some(fs)) = (p.vs_module.as_deref(), p.fs_module.as_deref()) else {
+        return Err("the program lacks a stage".into());
+    };
+
+    // One binding per uniform name, shared by both stages
+    let mut uniform_bindings = HashMap::new();
+    let mut uniforms = Vec::new();
+    for module in [vs, fs] {
+        for (_, var) in module.global_variables.iter() {
+            if crate::naga_wasm_backend::interface_layout::uniform_block_name(module, var).is_some()
+            {
+                return Err("uniform blocks are not forwarded".into());
+            }
+            if var.space == AddressSpace::Handle {
+                return Err("textures are not forwarded".into());
+            }
+            let Some(name) = var.name.as_deref() else {
+                continue;
+            };
+            if var.space != AddressSpace::Uniform || uniform_bindings.contains_key(name) {
+                continue;
+            }
+            let &location = p
+                .uniforms
+                .get(name)
+                .ok_or_else(|| format!("uniform {} has no location", name))?;
+            let offset = get_webgl_uniform_data_offset(location as u32) as usize;
+            let value = ctx_obj.uniform_data.get(offset..).unwrap_or_default();
+            uniforms.push(uniform_bytes(&module.types[var.ty].inner, value, name)?);
+            uniform_bindings.insert(name, uniforms.len() as u32 - 1);
+        }
+    }
+
+    let attributes = p
+        .attributes
+        .iter()
+        .map(|(name, &location)| (name.as_str(), location as u32))
+        .collect();
+    let varyings = location_outputs(vs)
+        .into_iter()
+        .enumerate()
+        .map(|(location, name)| (name, location as u32))
+        .collect();
+    let vs = bind_interface(
+        vs,
+        ShaderStage::Vertex,
+        &attributes,
+        Some(&varyings),
+        &uniform_bindings,
+    )?;
+    let fs = bind_interface(
+        fs,
+        ShaderStage::Fragment,
+        &varyings,
+        None,
+        &uniform_bindings,
+    )?;
+
+    let vao = &ctx_obj.vertex_arrays[&ctx_obj.bound_vertex_array];
+    let mut buffers = Vec::new();
+    let mut buffer_slots = HashMap::new();
+    let mut inputs = Vec::new();
+    for location in input_locations(&vs) {
+        let attr = vao
+            .attributes
+            .get(location as usize)
+            .ok_or_else(|| format!("attribute location {} is out of range", location))?;
+        if !attr.enabled {
+            // The current value, repeated for every vertex
+            let format = match attr.current_value_type {
+                GL_INT => wgt::VertexFormat::Sint32x4,
+                GL_UNSIGNED_INT => wgt::VertexFormat::Uint32x4,
+                _ => wgt::VertexFormat::Float32x4,
+            };
+            buffers.push(attr.default_value.map(u32::to_le_bytes).concat());
+            inputs.push(VertexInput {
+                location,
+                buffer: buffers.len() - 1,
+                offset: 0,
+                stride: 0,
+                step_mode: wgt::VertexStepMode::Vertex,
+                format,
+            });
+            continue;
+        }
+        let format = vertex_format(attr.type_, attr.size, attr.normalized, attr.is_integer)
+            .ok_or_else(|| format!("attribute {} has no WebGPU vertex format", location))?;
+        let stride = match attr.stride {
+            0 => AttributeBinding::tight_stride(attr.type_, attr.size) as u64,
+            stride => stride as u64,
+        };
+        if stride % 4 != 0 || attr.offset % 4 != 0 {
+            return Err(format!("attribute {} is not 4-byte aligned", location));
+        }
+        let step_mode = match attr.divisor {
+            0 => wgt::VertexStepMode::Vertex,
+            1 => wgt::VertexStepMode::Instance,
+            divisor => return Err(format!("attribute {} has divisor {}", location, divisor)),
+        };
+        let handle = attr
+            .buffer
+            .ok_or_else(|| format!("attribute {} has no buffer", location))?;
+        let buffer = match buffer_slots.get(&handle) {
+            Some(&slot) => slot,
+            None => {
+                buffers.push(buffer_bytes(ctx_obj, handle)?.to_vec());
+                buffer_slots.insert(handle, buffers.len() - 1);
+                buffers.len() - 1
+            }
+        };
+        inputs.push(VertexInput {
+            location,
+            buffer,
+            offset: attr.offset as u64,
+            stride,
+            step_mode,
+            format,
+        });
+    }
+
+    let strip = matches!(
+        topology,
+        wgt::PrimitiveTopology::LineStrip | wgt::PrimitiveTopology::TriangleStrip
+    );
+    let indices = match draw.indices {
+        Some((type_, offset)) => {
+            let handle = vao
+                .element_array_buffer
+                .ok_or("no element array buffer is bound")?;
+            let size = match type_ {
+                GL_UNSIGNED_BYTE => 1,
+                GL_UNSIGNED_SHORT => 2,
+                _ => 4,
+            };
+            let start = offset as usize;
+            let data = buffer_bytes(ctx_obj, handle)?
+                .get(start..start + count as usize * size)
+                .ok_or("the indices are outside the element array buffer")?;
+            Some(index_data(data, type_, strip)?)
+        }
+        None => None,
+    };
+
+    let fb = &ctx_obj.default_framebuffer;
+    let (width, height) = (fb.width, fb.height);
+    let color = ctx_obj
+        .kernel
+        .get_buffer(fb.gpu_handle)
+        .filter(|color| color.format == wgt::TextureFormat::Rgba8Unorm)
+        .ok_or("the default framebuffer is not RGBA8")?;
+    let mut pixels = color
+        .data
+        .get(..(width * height * 4) as usize)
+        .ok_or("the default framebuffer is truncated")?
+        .to_vec();
+    super::drawing::flip_rows(&mut pixels, height);
+
+    // GL windows have their origin at the bottom left, WebGPU's at the top left
+    let (vx, vy, vw, vh) = ctx_obj.viewport;
+    let viewport = [
+        vx as f32,
+        height as f32 - (vy as f32 + vh as f32),
+        vw as f32,
+        vh as f32,
+    ];
+    let scissor = if ctx_obj.scissor_test_enabled {
+        let (sx, sy, sw, sh) = ctx_obj.scissor_box;
+        let clamp = |v: i64, max: u32| v.clamp(0, max as i64) as u32;
+        let (x0, x1) = (clamp(sx as i64, width), clamp(sx as i64 + sw as i64, width));
+        let (y0, y1) = (
+            clamp(sy as i64, height),
+            clamp(sy as i64 + sh as i64, height),
+        );
+        [x0, height - y1, x1 - x0, y1 - y0]
+    } else {
+        [0, 0, width, height]
+    };
+
+    let mask = ctx_obj.draw_color_mask();
+    let mut write_mask = wgt::ColorWrites::empty();
+    write_mask.set(wgt::ColorWrites::RED, mask.r);
+    write_mask.set(wgt::ColorWrites::GREEN, mask.g);
+    write_mask.set(wgt::ColorWrites::BLUE, mask.b);
+    write_mask.set(wgt::ColorWrites::ALPHA, mask.a);
+    let blend = ctx_obj.draw_blend_state();
+    let [r, g, b, a] = blend.color.map(f64::from);
+
+    Ok(Frame {
+        vs,
+        fs,
+        uniforms,
+        buffers,
+        inputs,
+        primitive: wgt::PrimitiveState {
+            topology,
+            strip_index_format: indices.as_ref().filter(|_| strip).map(|(_, f)| *f),
+            front_face,
+            cull_mode,
+            ..Default::default()
+        },
+        indices,
+        blend: blend_state(&blend)?,
+        blend_constant: wgt::Color { r, g, b, a },
+        write_mask,
+        viewport,
+        scissor,
+        first,
+        count,
+        instance_count,
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Contents of the buffer object `handle`.
+fn buffer_bytes(ctx_obj: &Context, handle: u32) -> Result<&[u8], String> {
+    ctx_obj
+        .buffers
+        .get(&handle)
+        .and_then(|buf| ctx_obj.kernel.get_buffer(buf.gpu_handle))
+        .map(|gpu_buf| &gpu_buf.data[..])
+        .ok_or_else(|| format!("buffer {} was not found", handle))
+}
+
+/// Lay out the value of uniform `name` as WebGPU reads a uniform buffer.
+/// `value` holds its components packed, as the context stores them.
+fn uniform_bytes(ty: &TypeInner, value: &[u8], name: &str) -> Result<Vec<u8>, String> {
+    // Matrix columns are 16-byte aligned, except those of two components
+    let (columns, rows, scalar) = match *ty {
+        TypeInner::Scalar(scalar) => (1, 1, scalar),
+        TypeInner::Vector { size, scalar } => (1, size as usize, scalar),
+        TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => (columns as usize, rows as usize, scalar),
+        _ => {
+            return Err(format!(
+                "uniform {} is not a scalar, vector or matrix",
+                name
+            ))
+        }
+    };
+    if scalar.width != 4 || scalar.kind == naga::ScalarKind::Bool {
+        return Err(format!("uniform {} is not 32-bit numeric", name));
+    }
+    let column = rows * 4;
+    let column_stride = if columns == 1 || rows == 2 {
+        column
+    } else {
+        16
+    };
+    let value = value
+        .get(..columns * column)
+        .ok_or_else(|| format!("uniform {} is outside the uniform data", name))?;
+    let mut bytes = vec![0; (columns * column_stride).next_multiple_of(16)];
+    for (dst, src) in bytes
+        .chunks_mut(column_stride)
+        .zip(value.chunks_exact(column))
+    {
+        dst[..column].copy_from_slice(src);
+    }
+    Ok(bytes)
+}
+
+/// Names of a module's vertex outputs at user locations, in member order.
+fn location_outputs(module: &naga::Module) -> Vec<&str> {
+    let Some(result) =
+        entry_point(module, ShaderStage::Vertex).and_then(|ep| ep.function.result.as_ref())
+    else {
+        return Vec::new();
+    };
+    match &module.types[result.ty].inner {
+        TypeInner::Struct { members, .. } => members
+            .iter()
+            .filter(|member| matches!(member.binding, Some(Binding::Location { .. })))
+            .filter_map(|member| member.name.as_deref())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Locations of the vertex shader inputs.
+fn input_locations(module: &naga::Module) -> Vec<u32> {
+    entry_point(module, ShaderStage::Vertex)
+        .map(|ep| {
+            ep.function
+                .arguments
+                .iter()
+                .filter_map(|arg| match arg.binding {
+                    Some(Binding::Location { location, .. }) => Some(location),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn entry_point(module: &naga::Module, stage: ShaderStage) -> Option<&naga::EntryPoint> {
+    module
+        .entry_points
+        .iter()
+        .find(|ep| ep.stage == stage && ep.name == "main")
+}
+
+/// Copy of `module` with its interface where the pipeline expects it.
+///
+/// Entry point arguments move to their location in `inputs`, output members
+/// to theirs in `outputs` (if given; fragment outputs keep their own), and
+/// uniforms get their binding in group 0. The GLSL frontend leaves every
+/// unqualified input and output at location 0 and uniforms unbound.
+fn bind_interface(
+    module: &naga::Module,
+    stage: ShaderStage,
+    inputs: &HashMap<&str, u32>,
+    outputs: Option<&HashMap<&str, u32>>,
+    uniforms: &HashMap<&str, u32>,
+) -> Result<naga::Module, String> {
+    let mut module = module.clone();
+    let relocate =
+        |binding: &mut Option<Binding>, name: Option<&String>, map: &HashMap<&str, u32>| {
+            if let Some(Binding::Location { location, .. }) = binding {
+                let name = name.map_or("", String::as_str);
+                *location = *map
+                    .get(name)
+                    .ok_or_else(|| format!("{} has no location", name))?;
+            }
+            Ok::<_, String>(())
+        };
+
+    for (_, var) in module.global_variables.iter_mut() {
+        if var.space == AddressSpace::Uniform {
+            let name = var.name.as_deref().unwrap_or_default();
+            var.binding = Some(naga::ResourceBinding {
+                group: 0,
+                binding: uniforms[name],
+            });
+        }
+    }
+
+    let index = module
+        .entry_points
+        .iter()
+        .position(|ep| ep.stage == stage && ep.name == "main")
+        .ok_or("the module has no main entry point")?;
+    let mut ep = module.entry_points.swap_remove(index);
+    for arg in &mut ep.function.arguments {
+        relocate(&mut arg.binding, arg.name.as_ref(), inputs)?;
+    }
+    if let (Some(outputs), Some(result)) = (outputs, &mut ep.function.result) {
+        if let TypeInner::Struct { members, span } = &module.types[result.ty].inner {
+            let mut members = members.clone();
+            for member in &mut members {
+                relocate(&mut member.binding, member.name.as_ref(), outputs)?;
+            }
+            let old = result.ty;
+            result.ty = module.types.insert(
+                naga::Type {
+                    name: None,
+                    inner: TypeInner::Struct {
+                        members,
+                        span: *span,
+                    },
+                },
+                naga::Span::UNDEFINED,
+            );
+            // The wrapper composes its result with the old type
+            for (_, expr) in ep.function.expressions.iter_mut() {
+                if let naga::Expression::Compose { ty, .. } = expr {
+                    if *ty == old {
+                        *ty = result.ty;
+                    }
+                }
+            }
+        }
+    }
+    module.entry_points.push(ep);
+    Ok(module)
+}
+
+/// Index buffer contents in a WebGPU index format. Byte indices are widened,
+/// keeping the restart index the largest value of the format.
+fn index_data(data: &[u8], type_: u32, strip: bool) -> Result<(Vec<u8>, wgt::IndexFormat), String> {
+    let (indices, format, restart) = match type_ {
+        GL_UNSIGNED_BYTE => (
+            data.iter()
+                .map(|&i| if i == u8::MAX { u16::MAX } else { i as u16 })
+                .flat_map(u16::to_le_bytes)
+                .collect(),
+            wgt::IndexFormat::Uint16,
+            u8::MAX as u32,
+        ),
+        GL_UNSIGNED_SHORT => (data.to_vec(), wgt::IndexFormat::Uint16, u16::MAX as u32),
+        _ => (data.to_vec(), wgt::IndexFormat::Uint32, u32::MAX),
+    };
+    // WebGPU only restarts strips; a list would read the restart index as a vertex
+    if !strip {
+        let restarts = match type_ {
+            GL_UNSIGNED_BYTE => data.iter().any(|&i| i as u32 == restart),
+            GL_UNSIGNED_SHORT => data
+                .chunks_exact(2)
+                .any(|i| u16::from_le_bytes([i[0], i[1]]) as u32 == restart),
+            _ => data
+                .chunks_exact(4)
+                .any(|i| u32::from_le_bytes([i[0], i[1], i[2], i[3]]) == restart),
+        };
+        if restarts {
+            return Err("a list draw uses the restart index".into());
+        }
+    }
+    Ok((indices, format))
+}
+
+/// WebGPU format of a vertex attribute, if it has one.
+fn vertex_format(
+    type_: u32,
+    size: i32,
+    normalized: bool,
+    is_integer: bool,
+) -> Option<wgt::VertexFormat> {
+    use wgt::VertexFormat as F;
+    let pick = |formats: [Option<F>; 4]| formats.get(size as usize - 1).copied().flatten();
+    match (type_, is_integer, normalized) {
+        (GL_FLOAT, false, _) => pick([
+            Some(F::Float32),
+            Some(F::Float32x2),
+            Some(F::Float32x3),
+            Some(F::Float32x4),
+        ]),
+        (GL_HALF_FLOAT, false, false) => pick([None, Some(F::Float16x2), None, Some(F::Float16x4)]),
+        (GL_INT, true, _) => pick([
+            Some(F::Sint32),
+            Some(F::Sint32x2),
+            Some(F::Sint32x3),
+            Some(F::Sint32x4),
+        ]),
+        (GL_UNSIGNED_INT, true, _) => pick([
+            Some(F::Uint32),
+            Some(F::Uint32x2),
+            Some(F::Uint32x3),
+            Some(F::Uint32x4),
+        ]),
+        (GL_SHORT, true, _) => pick([None, Some(F::Sint16x2), None, Some(F::Sint16x4)]),
+        (GL_UNSIGNED_SHORT, true, _) => pick([None, Some(F::Uint16x2), None, Some(F::Uint16x4)]),
+        (GL_BYTE, true, _) => pick([None, Some(F::Sint8x2), None, Some(F::Sint8x4)]),
+        (GL_UNSIGNED_BYTE, true, _) => pick([None, Some(F::Uint8x2), None, Some(F::Uint8x4)]),
+        (GL_SHORT, false, true) => pick([None, Some(F::Snorm16x2), None, Some(F::Snorm16x4)]),
+        (GL_UNSIGNED_SHORT, false, true) => {
+            pick([None, Some(F::Unorm16x2), None, Some(F::Unorm16x4)])
+        }
+        (GL_BYTE, false, true) => pick([None, Some(F::Snorm8x2), None, Some(F::Snorm8x4)]),
+        (GL_UNSIGNED_BYTE, false, true) => pick([None, Some(F::Unorm8x2), None, Some(F::Unorm8x4)]),
+        _ => None,
+    }
+}
+
+/// WebGPU blend state of a GL one, None when blending is disabled.
+fn blend_state(blend: &BlendState) -> Result<Option<wgt::BlendState>, String> {
+    if !blend.enabled {
+        return Ok(None);
+    }
+    // The constant's alpha is CONSTANT_ALPHA in the alpha component
+    let factor = |f: u32, alpha: bool| {
+        Ok(match f {
+            GL_ZERO => wgt::BlendFactor::Zero,
+            GL_ONE => wgt::BlendFactor::One,
+            GL_SRC_COLOR => wgt::BlendFactor::Src,
+            GL_ONE_MINUS_SRC_COLOR => wgt::BlendFactor::OneMinusSrc,
+            GL_DST_COLOR => wgt::BlendFactor::Dst,
+            GL_ONE_MINUS_DST_COLOR => wgt::BlendFactor::OneMinusDst,
+            GL_SRC_ALPHA => wgt::BlendFactor::SrcAlpha,
+            GL_ONE_MINUS_SRC_ALPHA => wgt::BlendFactor::OneMinusSrcAlpha,
+            GL_DST_ALPHA => wgt::BlendFactor::DstAlpha,
+            GL_ONE_MINUS_DST_ALPHA => wgt::BlendFactor::OneMinusDstAlpha,
+            GL_SRC_ALPHA_SATURATE => wgt::BlendFactor::SrcAlphaSaturated,
+            GL_CONSTANT_COLOR => wgt::BlendFactor::Constant,
+            GL_ONE_MINUS_CONSTANT_COLOR => wgt::BlendFactor::OneMinusConstant,
+            GL_CONSTANT_ALPHA if alpha => wgt::BlendFactor::Constant,
+            GL_ONE_MINUS_CONSTANT_ALPHA if alpha => wgt::BlendFactor::OneMinusConstant,
+            _ => return Err(format!("{} has no WebGPU blend factor", describe_enum(f))),
+        })
+    };
+    // MIN and MAX ignore the factors, which WebGPU requires to be ONE
+    let component = |src: u32, dst: u32, eq: u32, alpha: bool| {
+        let operation = match eq {
+            GL_FUNC_SUBTRACT => wgt::BlendOperation::Subtract,
+            GL_FUNC_REVERSE_SUBTRACT => wgt::BlendOperation::ReverseSubtract,
+            GL_MIN => wgt::BlendOperation::Min,
+            GL_MAX => wgt::BlendOperation::Max,
+            _ => wgt::BlendOperation::Add,
+        };
+        let (src_factor, dst_factor) = match operation {
+            wgt::BlendOperation::Min | wgt::BlendOperation::Max => {
+                (wgt::BlendFactor::One, wgt::BlendFactor::One)
+            }
+            _ => (factor(src, alpha)?, factor(dst, alpha)?),
+        };
+        Ok::<_, String>(wgt::BlendComponent {
+            src_factor,
+            dst_factor,
+            operation,
+        })
+    };
+    Ok(Some(wgt::BlendState {
+        color: component(blend.src_rgb, blend.dst_rgb, blend.eq_rgb, false)?,
+        alpha: component(blend.src_alpha, blend.dst_alpha, blend.eq_alpha, true)?,
+    }))
+}
+
+/// The device draws are forwarded to.
+struct GpuDevice {
+    global: Global,
+    device: DeviceId,
+    queue: QueueId,
+}
+
+impl GpuDevice {
+    fn open() -> Option<Self> {
+        Self::try_open()
+            .inspect_err(|e| tracing::warn!("gpu backend: no device: {}", e))
+            .ok()
+    }
+
+    fn try_open() -> Result<Self, String> {
+        let desc = wgt::InstanceDescriptor::from_env_or_default();
+        let global = Global::new("webgl2-gpu-backend", &desc, None);
+        let adapter = global
+            .request_adapter(
+                &wgt::RequestAdapterOptions {
+                    power_preference: wgt::PowerPreference::from_env().unwrap_or_default(),
+                    force_fallback_adapter: false,
+                    compatible_surface: None,
+                },
+                desc.backends,
+                None,
+            )
+            .map_err(|e| e.to_string())?;
+        let device_desc = wgt::DeviceDescriptor {
+            label: None,
+            required_features: wgt::Features::empty(),
+            required_limits: wgt::Limits::default(),
+            memory_hints: wgt::MemoryHints::default(),
+            experimental_features: wgt::ExperimentalFeatures::default(),
+            trace: wgt::Trace::Off,
+        };
+        let (device, queue) = global
+            .adapter_request_device(adapter, &device_desc, None, None)
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            global,
+            device,
+            queue,
+        })
+    }
+
+    /// Run one draw, returning the color buffer afterwards, top row first.
+    fn draw(&self, frame: Frame) -> Result<Vec<u8>, String> {
+        let g = &self.global;
+        let mut res = Resources::new(g);
+
+        let shader_desc = pipeline::ShaderModuleDescriptor {
+            label: None,
+            runtime_checks: wgt::ShaderRuntimeChecks::default(),
+        };
+        let mut shader = |module| {
+            let source = pipeline::ShaderModuleSource::Naga(Cow::Owned(module));
+            let created = g.device_create_shader_module(self.device, &shader_desc, source, None);
+            track(&mut res.shaders, created)
+        };
+        let vs = shader(frame.vs)?;
+        let fs = shader(frame.fs)?;
+
+        let layout_entries: Vec<_> = (0..frame.uniforms.len() as u32)
+            .map(|binding| wgt::BindGroupLayoutEntry {
+                binding,
+                visibility: wgt::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgt::BindingType::Buffer {
+                    ty: wgt::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            })
+            .collect();
+        let bind_group_layout = track(
+            &mut res.bind_group_layouts,
+            g.device_create_bind_group_layout(
+                self.device,
+                &binding_model::BindGroupLayoutDescriptor {
+                    label: None,
+                    entries: Cow::Owned(layout_entries),
+                },
+                None,
+            ),
+        )?;
+        let mut group_entries = Vec::new();
+        for (binding, value) in frame.uniforms.iter().enumerate() {
+            let buffer = self.upload(&mut res, value, wgt::BufferUsages::UNIFORM)?;
+            group_entries.push(binding_model::BindGroupEntry {
+                binding: binding as u32,
+                resource: binding_model::BindingResource::Buffer(binding_model::BufferBinding {
+                    buffer,
+                    offset: 0,
+                    size: None,
+                }),
+            });
+        }
+        let bind_group = track(
+            &mut res.bind_groups,
+            g.device_create_bind_group(
+                self.device,
+                &binding_model::BindGroupDescriptor {
+                    label: None,
+                    layout: bind_group_layout,
+                    entries: Cow::Owned(group_entries),
+                },
+                None,
+            ),
+        )?;
+        let pipeline_layout = track(
+            &mut res.pipeline_layouts,
+            g.device_create_pipeline_layout(
+                self.device,
+                &binding_model::PipelineLayoutDescriptor {
+                    label: None,
+                    bind_group_layouts: Cow::Borrowed(&[bind_group_layout]),
+                    immediate_size: 0,
+                },
+                None,
+            ),
+        )?;
+
+        let attributes: Vec<_> = frame
+            .inputs
+            .iter()
+            .map(|input| {
+                [wgt::VertexAttribute {
+                    format: input.format,
+                    offset: 0,
+                    shader_location: input.location,
+                }]
+            })
+            .collect();
+        let vertex_buffers: Vec<_> = frame
+            .inputs
+            .iter()
+            .zip(&attributes)
+            .map(|(input, attributes)| pipeline::VertexBufferLayout {
+                array_stride: input.stride,
+                step_mode: input.step_mode,
+                attributes: Cow::Borrowed(attributes),
+            })
+            .collect();
+        let stage = |module| pipeline::ProgrammableStageDescriptor {
+            module,
+            entry_point: Some(Cow::Borrowed("main")),
+            constants: Default::default(),
+            zero_initialize_workgroup_memory: true,
+        };
+        let targets = [Some(wgt::ColorTargetState {
+            format: wgt::TextureFormat::Rgba8Unorm,
+            blend: frame.blend,
+            write_mask: frame.write_mask,
+        })];
+        let render_pipeline = track(
+            &mut res.render_pipelines,
+            g.device_create_render_pipeline(
+                self.device,
+                &pipeline::RenderPipelineDescriptor {
+                    label: None,
+                    layout: Some(pipeline_layout),
+                    vertex: pipeline::VertexState {
+                        stage: stage(vs),
+                        buffers: Cow::Owned(vertex_buffers),
+                    },
+                    primitive: frame.primitive,
+                    depth_stencil: None,
+                    multisample: wgt::MultisampleState::default(),
+                    fragment: Some(pipeline::FragmentState {
+                        stage: stage(fs),
+                        targets: Cow::Borrowed(&targets),
+                    }),
+                    multiview_mask: None,
+                    cache: None,
+                },
+                None,
+            ),
+        )?;
+
+        let buffers = frame
+            .buffers
+            .iter()
+            .map(|data| self.upload(&mut res, data, wgt::BufferUsages::VERTEX))
+            .collect::<Result<Vec<_>, _>>()?;
+        let index_buffer = match &frame.indices {
+            Some((data, format)) => Some((
+                self.upload(&mut res, data, wgt::BufferUsages::INDEX)?,
+                *format,
+            )),
+            None => None,
+        };
+
+        // The pass loads the current color buffer and draws over it
+        let size = wgt::Extent3d {
+            width: frame.width,
+            height: frame.height,
+            depth_or_array_layers: 1,
+        };
+        let texture = track(
+            &mut res.textures,
+            g.device_create_texture(
+                self.device,
+                &wgt::TextureDescriptor {
+                    label: None,
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgt::TextureDimension::D2,
+                    format: wgt::TextureFormat::Rgba8Unorm,
+                    usage: wgt::TextureUsages::RENDER_ATTACHMENT
+                        | wgt::TextureUsages::COPY_SRC
+                        | wgt::TextureUsages::COPY_DST,
+                    view_formats: vec![],
+                },
+                None,
+            ),
+        )?;
+        let texture_copy = wgt::TexelCopyTextureInfo {
+            texture,
+            mip_level: 0,
+            origin: wgt::Origin3d::ZERO,
+            aspect: wgt::TextureAspect::All,
+        };
+        g.queue_write_texture(
+            self.queue,
+            &texture_copy,
+            &frame.pixels,
+            &wgt::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(frame.width * 4),
+                rows_per_image: Some(frame.height),
+            },
+            &size,
+        )
+        .map_err(|e| e.to_string())?;
+        let view = track(
+            &mut res.views,
+            g.texture_create_view(texture, &resource::TextureViewDescriptor::default(), None),
+        )?;
+
+        let encoder = track(
+            &mut res.encoders,
+            g.device_create_command_encoder(
+                self.device,
+                &wgt::CommandEncoderDescriptor { label: None },
+                None,
+            ),
+        )?;
+        let (mut pass, error) = g.command_encoder_begin_render_pass(
+            encoder,
+            &command::RenderPassDescriptor {
+                label: None,
+                color_attachments: Cow::Owned(vec![Some(command::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    load_op: command::LoadOp::Load,
+                    store_op: wgt::StoreOp::Store,
+                    depth_slice: None,
+                })]),
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                multiview_mask: None,
+            },
+        );
+        if let Some(e) = error {
+            return Err(e.to_string());
+        }
+        let [x, y, w, h] = frame.viewport;
+        let [sx, sy, sw, sh] = frame.scissor;
+        let recorded = (|| {
+            g.render_pass_set_pipeline(&mut pass, render_pipeline)?;
+            g.render_pass_set_bind_group(&mut pass, 0, Some(bind_group), &[])?;
+            for (slot, input) in frame.inputs.iter().enumerate() {
+                let buffer = buffers[input.buffer];
+                g.render_pass_set_vertex_buffer(
+                    &mut pass,
+                    slot as u32,
+                    buffer,
+                    input.offset,
+                    None,
+                )?;
+            }
+            g.render_pass_set_viewport(&mut pass, x, y, w, h, 0.0, 1.0)?;
+            g.render_pass_set_scissor_rect(&mut pass, sx, sy, sw, sh)?;
+            g.render_pass_set_blend_constant(&mut pass, frame.blend_constant)?;
+            match index_buffer {
+                Some((buffer, format)) => {
+                    g.render_pass_set_index_buffer(&mut pass, buffer, format, 0, None)?;
+                    g.render_pass_draw_indexed(
+                        &mut pass,
+                        frame.count,
+                        frame.instance_count,
+                        0,
+                        0,
+                        0,
+                    )
+                }
+                None => {
+                    g.render_pass_draw(&mut pass, frame.count, frame.instance_count, frame.first, 0)
+                }
+            }
+        })();
+        recorded.map_err(|e| e.to_string())?;
+        g.render_pass_end(&mut pass).map_err(|e| e.to_string())?;
+
+        // Rows of a texture copy are 256-byte aligned
+        let row = frame.width * 4;
+        let padded_row = row.next_multiple_of(wgt::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let readback_size = padded_row as u64 * frame.height as u64;
+        let readback = track(
+            &mut res.buffers,
+            g.device_create_buffer(
+                self.device,
+                &resource::BufferDescriptor {
+                    label: None,
+                    size: readback_size,
+                    usage: wgt::BufferUsages::MAP_READ | wgt::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                },
+                None,
+            ),
+        )?;
+        g.command_encoder_copy_texture_to_buffer(
+            encoder,
+            &texture_copy,
+            &wgt::TexelCopyBufferInfo {
+                buffer: readback,
+                layout: wgt::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row),
+                    rows_per_image: Some(frame.height),
+                },
+            },
+            &size,
+        )
+        .map_err(|e| e.to_string())?;
+        let (command_buffer, error) =
+            g.command_encoder_finish(encoder, &wgt::CommandBufferDescriptor { label: None }, None);
+        res.command_buffers.push(command_buffer);
+        if let Some((_, e)) = error {
+            return Err(e.to_string());
+        }
+        g.queue_submit(self.queue, &[command_buffer])
+            .map_err(|(_, e)| e.to_string())?;
+
+        g.buffer_map_async(
+            readback,
+            0,
+            Some(readback_size),
+            resource::BufferMapOperation {
+                host: wgpu_core::device::HostMap::Read,
+                callback: None,
+            },
+        )
+        .map_err(|e| e.to_string())?;
+        g.device_poll(self.device, wgt::PollType::wait_indefinitely())
+            .map_err(|e| e.to_string())?;
+        let (ptr, len) = g
+            .buffer_get_mapped_range(readback, 0, Some(readback_size))
+            .map_err(|e| e.to_string())?;
+        // SAFETY: the range stays mapped until the unmap below
+        let mapped = unsafe { std::slice::from_raw_parts(ptr.as_ptr(), len as usize) };
+        let pixels = mapped
+            .chunks(padded_row as usize)
+            .flat_map(|padded| &padded[..row as usize])
+            .copied()
+            .collect();
+        g.buffer_unmap(readback).map_err(|e| e.to_string())?;
+        Ok(pixels)
+    }
+
+    /// A buffer holding `data`, padded to the 4-byte copy alignment.
+    fn upload(
+        &self,
+        res: &mut Resources,
+        data: &[u8],
+        usage: wgt::BufferUsages,
+    ) -> Result<BufferId, String> {
+        let mut padded = data.to_vec();
+        padded.resize(data.len().next_multiple_of(4).max(4), 0);
+        let buffer = track(
+            &mut res.buffers,
+            self.global.device_create_buffer(
+                self.device,
+                &resource::BufferDescriptor {
+                    label: None,
+                    size: padded.len() as u64,
+                    usage: usage | wgt::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                },
+                None,
+            ),
+        )?;
+        self.global
+            .queue_write_buffer(self.queue, buffer, 0, &padded)
+            .map_err(|e| e.to_string())?;
+        Ok(buffer)
+    }
+}
+
+/// Keep a created resource for release, failing with its creation error.
+fn track<I: Copy, E: std::fmt::Display>(
+    ids: &mut Vec<I>,
+    (id, error): (I, Option<E>),
+) -> Result<I, String> {
+    // Failed creations still hand out an id to release
+    ids.push(id);
+    match error {
+        Some(e) => Err(e.to_string()),
+        None => Ok(id),
+    }
+}
+
+/// Resources of one forwarded draw, released when it ends.
+struct Resources<'a> {
+    global: &'a Global,
+    shaders: Vec<ShaderModuleId>,
+    bind_group_layouts: Vec<BindGroupLayoutId>,
+    bind_groups: Vec<BindGroupId>,
+    pipeline_layouts: Vec<PipelineLayoutId>,
+    render_pipelines: Vec<RenderPipelineId>,
+    buffers: Vec<BufferId>,
+    textures: Vec<TextureId>,
+    views: Vec<TextureViewId>,
+    encoders: Vec<CommandEncoderId>,
+    command_buffers: Vec<CommandBufferId>,
+}
+
+impl<'a> Resources<'a> {
+    fn new(global: &'a Global) -> Self {
+        Self {
+            global,
+            shaders: Vec::new(),
+            bind_group_layouts: Vec::new(),
+            bind_groups: Vec::new(),
+            pipeline_layouts: Vec::new(),
+            render_pipelines: Vec::new(),
+            buffers: Vec::new(),
+            textures: Vec::new(),
+            views: Vec::new(),
+            encoders: Vec::new(),
+            command_buffers: Vec::new(),
+        }
+    }
+}
+
+impl Drop for Resources<'_> {
+    fn drop(&mut self) {
+        let g = self.global;
+        self.render_pipelines
+            .drain(..)
+            .for_each(|id| g.render_pipeline_drop(id));
+        self.pipeline_layouts
+            .drain(..)
+            .for_each(|id| g.pipeline_layout_drop(id));
+        self.bind_groups
+            .drain(..)
+            .for_each(|id| g.bind_group_drop(id));
+        self.bind_group_layouts
+            .drain(..)
+            .for_each(|id| g.bind_group_layout_drop(id));
+        self.shaders
+            .drain(..)
+            .for_each(|id| g.shader_module_drop(id));
+        self.command_buffers
+            .drain(..)
+            .for_each(|id| g.command_buffer_drop(id));
+        self.encoders
+            .drain(..)
+            .for_each(|id| g.command_encoder_drop(id));
+        self.views.drain(..).for_each(|id| {
+            let _ = g.texture_view_drop(id);
+        });
+        self.textures.drain(..).for_each(|id| g.texture_drop(id));
+        self.buffers.drain(..).for_each(|id| g.buffer_drop(id));
+    }
+}
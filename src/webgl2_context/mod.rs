@@ -1,39 +1,64 @@
 pub mod blend;
 pub mod buffers;
+pub mod command_buffer;
+pub(crate) mod deletion;
+pub(crate) mod diagnostics;
+pub mod draw_packet;
 pub mod drawing;
 pub mod ephemeral;
+pub mod explain;
 pub mod framebuffers;
+pub(crate) mod glsl100;
+#[cfg(feature = "gpu-backend")]
+pub(crate) mod gpu_backend;
+pub(crate) mod noise;
+pub mod pipelines;
+pub mod queries;
+pub mod readback;
 pub mod registry;
 pub mod renderbuffers;
+pub(crate) mod sampling;
+pub(crate) mod shader_cache;
 pub mod shaders;
 pub mod state;
+pub mod texture_loader;
 pub mod textures;
+pub mod timing;
 pub mod transform_feedback;
 pub mod types;
 pub mod vaos;
 
 pub use blend::*;
 pub use buffers::*;
+pub use command_buffer::{ctx_play_command_buffer, play_commands, CommandBuffer};
+pub use draw_packet::*;
 pub use drawing::*;
+pub use explain::{ctx_explain_last_draw, DrawReport};
 pub use framebuffers::*;
+pub use pipelines::*;
+pub use queries::*;
+pub use readback::*;
 pub use registry::{
-    create_context_with_flags, destroy_context, last_error_len, last_error_ptr, set_last_error,
-    wasm_alloc, wasm_free,
+    allocation_count, context_count, create_context_with_attributes, create_context_with_flags,
+    ctx_set_limits, destroy_context, last_error_len, last_error_ptr, recover_shader_trap,
+    set_last_error, wasm_alloc, wasm_ctx_alloc, wasm_frame_alloc, wasm_frame_reset, wasm_free,
 };
 pub use renderbuffers::*;
 pub use shaders::*;
 pub use state::*;
+pub use texture_loader::{ctx_tex_image_ktx2, tex_image_ktx2, Ktx2};
 pub use textures::*;
+pub use timing::*;
 pub use transform_feedback::*;
 pub use types::{
     ERR_GL, ERR_INTERNAL, ERR_INVALID_ARGS, ERR_INVALID_ENUM, ERR_INVALID_HANDLE,
     ERR_INVALID_OPERATION, ERR_NOT_IMPLEMENTED, ERR_OK, ERR_OOM, GL_ACTIVE_ATTRIBUTES,
     GL_ACTIVE_UNIFORMS, GL_ARRAY_BUFFER, GL_ATTACHED_SHADERS, GL_BUFFER_SIZE, GL_COLOR_BUFFER_BIT,
     GL_COLOR_CLEAR_VALUE, GL_COMPILE_STATUS, GL_DELETE_STATUS, GL_DEPTH_COMPONENT16,
-    GL_DEPTH_STENCIL, GL_ELEMENT_ARRAY_BUFFER, GL_FRAMEBUFFER, GL_INFO_LOG_LENGTH, GL_INVALID_ENUM,
-    GL_INVALID_OPERATION, GL_INVALID_VALUE, GL_LINK_STATUS, GL_NO_ERROR, GL_OUT_OF_MEMORY,
-    GL_RENDERBUFFER, GL_RGB565, GL_RGB5_A1, GL_RGBA4, GL_RGBA8, GL_SHADER_TYPE, GL_STENCIL_INDEX8,
-    GL_TEXTURE_MAG_FILTER, GL_TEXTURE_MIN_FILTER, GL_TEXTURE_WRAP_S, GL_TEXTURE_WRAP_T,
-    GL_VIEWPORT,
+    GL_DEPTH_STENCIL, GL_DRAW_FRAMEBUFFER, GL_ELEMENT_ARRAY_BUFFER, GL_FRAMEBUFFER,
+    GL_INFO_LOG_LENGTH, GL_INVALID_ENUM, GL_INVALID_OPERATION, GL_INVALID_VALUE, GL_LINK_STATUS,
+    GL_NO_ERROR, GL_OUT_OF_MEMORY, GL_READ_FRAMEBUFFER, GL_RENDERBUFFER, GL_RGB565, GL_RGB5_A1,
+    GL_RGBA4, GL_RGBA8, GL_SHADER_TYPE, GL_STENCIL_INDEX8, GL_TEXTURE_MAG_FILTER,
+    GL_TEXTURE_MIN_FILTER, GL_TEXTURE_WRAP_S, GL_TEXTURE_WRAP_T, GL_VIEWPORT,
 };
 pub use vaos::*;
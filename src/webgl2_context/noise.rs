@@ -0,0 +1,270 @@
+//! Built-in noise functions for shaders.
+//!
+//! GLSL ES has no noise functions, so shaders ported from other engines
+//! often depend on `hash()`/`noise()` helpers pasted in from elsewhere, each
+//! with slightly different results. A shader can instead enable
+//!
+//! ```glsl
+//! #extension GL_WEBGL2_noise : enable
+//! ```
+//!
+//! to get these overloads for `vec2` and `vec3` (and `float` for `hash`):
+//!
+//! - `float hash(p)`: a hash of the exact bits of `p`, in `[0, 1)`
+//! - `float noise(p)`: value noise, smoothly interpolated between hashes of
+//!   the integer lattice, in `[0, 1]`
+//! - `float snoise(p)`: simplex noise, in `[-1, 1]`
+//!
+//! The functions are plain GLSL built on integer hashing, so the WASM
+//! backend lowers them like user code and they give the same results on
+//! every host. The directive line is replaced by the definitions of the
+//! functions the shader names, on that one line, so info log lines still
+//! match the source. Helpers are prefixed `webgl2_`.
+
+/// Shader extension that enables the noise functions.
+pub(crate) const EXTENSION: &str = "GL_WEBGL2_noise";
+
+/// Integer hashing and lattice lookups shared by every group.
+const COMMON: &str = "
+uint webgl2_hash(uint x) {
+    x ^= x >> 16u;
+    x *= 2146121005u;
+    x ^= x >> 15u;
+    x *= 2221713035u;
+    x ^= x >> 16u;
+    return x;
+}
+float webgl2_unit(uint h) {
+    return float(h >> 8u) * (1.0 / 16777216.0);
+}
+uint webgl2_lattice2(ivec2 c) {
+    return webgl2_hash(uint(c.x) ^ webgl2_hash(uint(c.y)));
+}
+uint webgl2_lattice3(ivec3 c) {
+    return webgl2_hash(uint(c.x) ^ webgl2_hash(uint(c.y) ^ webgl2_hash(uint(c.z))));
+}
+";
+
+/// `hash()`; adding `0.0` makes `-0.0` hash like `0.0`.
+const HASH: &str = "
+float hash(float p) {
+    return webgl2_unit(webgl2_hash(floatBitsToUint(p + 0.0)));
+}
+float hash(vec2 p) {
+    uvec2 b = floatBitsToUint(p + 0.0);
+    return webgl2_unit(webgl2_hash(b.x ^ webgl2_hash(b.y)));
+}
+float hash(vec3 p) {
+    uvec3 b = floatBitsToUint(p + 0.0);
+    return webgl2_unit(webgl2_hash(b.x ^ webgl2_hash(b.y ^ webgl2_hash(b.z))));
+}
+";
+
+/// `noise()`, interpolating with the smoothstep polynomial.
+const VALUE_NOISE: &str = "
+float noise(vec2 p) {
+    vec2 i = floor(p);
+    vec2 f = p - i;
+    vec2 u = f * f * (3.0 - 2.0 * f);
+    ivec2 c = ivec2(i);
+    float a = webgl2_unit(webgl2_lattice2(c));
+    float b = webgl2_unit(webgl2_lattice2(c + ivec2(1, 0)));
+    float d = webgl2_unit(webgl2_lattice2(c + ivec2(0, 1)));
+    float e = webgl2_unit(webgl2_lattice2(c + ivec2(1, 1)));
+    return mix(mix(a, b, u.x), mix(d, e, u.x), u.y);
+}
+float noise(vec3 p) {
+    vec3 i = floor(p);
+    vec3 f = p - i;
+    vec3 u = f * f * (3.0 - 2.0 * f);
+    ivec3 c = ivec3(i);
+    float a = mix(
+        webgl2_unit(webgl2_lattice3(c)),
+        webgl2_unit(webgl2_lattice3(c + ivec3(1, 0, 0))),
+        u.x);
+    float b = mix(
+        webgl2_unit(webgl2_lattice3(c + ivec3(0, 1, 0))),
+        webgl2_unit(webgl2_lattice3(c + ivec3(1, 1, 0))),
+        u.x);
+    float d = mix(
+        webgl2_unit(webgl2_lattice3(c + ivec3(0, 0, 1))),
+        webgl2_unit(webgl2_lattice3(c + ivec3(1, 0, 1))),
+        u.x);
+    float e = mix(
+        webgl2_unit(webgl2_lattice3(c + ivec3(0, 1, 1))),
+        webgl2_unit(webgl2_lattice3(c + ivec3(1, 1, 1))),
+        u.x);
+    return mix(mix(a, b, u.y), mix(d, e, u.y), u.z);
+}
+";
+
+/// `snoise()`, with gradients picked by hash from fixed sets (eight unit
+/// vectors in 2D, Perlin's twelve cube edges in 3D) rather than computed
+/// with trigonometry.
+const SIMPLEX_NOISE: &str = "
+float webgl2_corner2(vec2 x, ivec2 c) {
+    float t = max(0.5 - dot(x, x), 0.0);
+    uint h = webgl2_lattice2(c);
+    float gx = (h & 1u) == 0u ? 1.0 : -1.0;
+    float gy = (h & 2u) == 0u ? 1.0 : -1.0;
+    float g = (h & 4u) == 0u
+        ? (gx * x.x + gy * x.y) * 0.70710678
+        : ((h & 8u) == 0u ? gx * x.x : gy * x.y);
+    t *= t;
+    return t * t * g;
+}
+float snoise(vec2 p) {
+    vec2 s = floor(p + (p.x + p.y) * 0.36602540);
+    vec2 x0 = p - s + (s.x + s.y) * 0.21132487;
+    float k = step(x0.y, x0.x);
+    vec2 o = vec2(k, 1.0 - k);
+    ivec2 c = ivec2(s);
+    float n = webgl2_corner2(x0, c)
+        + webgl2_corner2(x0 - o + 0.21132487, c + ivec2(o))
+        + webgl2_corner2(x0 - 1.0 + 0.42264973, c + ivec2(1, 1));
+    return clamp(70.0 * n, -1.0, 1.0);
+}
+float webgl2_corner3(vec3 x, ivec3 c) {
+    float t = max(0.6 - dot(x, x), 0.0);
+    uint h = webgl2_lattice3(c) & 15u;
+    float u = h < 8u ? x.x : x.y;
+    float v = h < 4u ? x.y : ((h == 12u || h == 14u) ? x.x : x.z);
+    float g = ((h & 1u) == 0u ? u : -u) + ((h & 2u) == 0u ? v : -v);
+    t *= t;
+    return t * t * g;
+}
+float snoise(vec3 p) {
+    vec3 s = floor(p + (p.x + p.y + p.z) * 0.33333333);
+    vec3 x0 = p - s + (s.x + s.y + s.z) * 0.16666667;
+    vec3 g = step(x0.yzx, x0.xyz);
+    vec3 l = 1.0 - g;
+    vec3 o1 = min(g, l.zxy);
+    vec3 o2 = max(g, l.zxy);
+    ivec3 c = ivec3(s);
+    float n = webgl2_corner3(x0, c)
+        + webgl2_corner3(x0 - o1 + 0.16666667, c + ivec3(o1))
+        + webgl2_corner3(x0 - o2 + 0.33333333, c + ivec3(o2))
+        + webgl2_corner3(x0 - 0.5, c + ivec3(1, 1, 1));
+    return clamp(32.0 * n, -1.0, 1.0);
+}
+";
+
+/// Function groups with the name that pulls each one in.
+const GROUPS: [(&str, &str); 3] = [
+    ("hash", HASH),
+    ("noise", VALUE_NOISE),
+    ("snoise", SIMPLEX_NOISE),
+];
+
+/// Replace a `#extension GL_WEBGL2_noise` directive in `source` with the
+/// noise functions the shader uses, or with nothing if the behavior is
+/// `disable`. Returns `None` for source without the directive.
+pub(crate) fn expand(source: &str) -> Option<String> {
+    let mut out = String::with_capacity(source.len());
+    let mut expanded = false;
+    for line in source.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        let behavior = content
+            .trim_start()
+            .strip_prefix('#')
+            .and_then(|rest| rest.trim_start().strip_prefix("extension"))
+            .and_then(|rest| rest.split_once(':'))
+            .filter(|(name, _)| name.trim() == EXTENSION)
+            .map(|(_, behavior)| behavior.trim());
+        match behavior {
+            Some(behavior) => {
+                expanded = true;
+                if behavior != "disable" {
+                    out.push_str(&prelude(source));
+                }
+                out.push_str(&line[content.len()..]);
+            }
+            None => out.push_str(line),
+        }
+    }
+    expanded.then_some(out)
+}
+
+/// The definitions for the groups named in `source`, on a single line.
+fn prelude(source: &str) -> String {
+    let used: Vec<&str> = GROUPS
+        .iter()
+        .filter(|(name, _)| uses_identifier(source, name))
+        .map(|(_, group)| *group)
+        .collect();
+    if used.is_empty() {
+        return String::new();
+    }
+    std::iter::once(COMMON)
+        .chain(used)
+        .flat_map(str::lines)
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether `name` appears in `source` as a whole identifier.
+fn uses_identifier(source: &str, name: &str) -> bool {
+    source
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .any(|word| word == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl2_context::shaders::compile_glsl;
+    use crate::webgl2_context::types::{GL_FRAGMENT_SHADER, GL_VERTEX_SHADER};
+
+    #[test]
+    fn test_source_without_directive_is_unchanged() {
+        assert_eq!(expand("#version 300 es\nvoid main() {}\n"), None);
+        assert_eq!(
+            expand("#version 300 es\n#extension GL_WEBGL2_noise : disable\nvoid main() {}\n")
+                .as_deref(),
+            Some("#version 300 es\n\nvoid main() {}\n")
+        );
+    }
+
+    #[test]
+    fn test_only_named_groups_are_injected_on_one_line() {
+        let source = "#version 300 es\r\n\
+                      #extension GL_WEBGL2_noise : enable\r\n\
+                      precision highp float;\r\n\
+                      out vec4 color;\r\n\
+                      void main() { color = vec4(snoise(gl_FragCoord.xy)); }\r\n";
+        let expanded = expand(source).unwrap();
+        assert_eq!(expanded.lines().count(), source.lines().count());
+        assert!(expanded.contains("float snoise(vec3 p)"));
+        assert!(!expanded.contains("float noise(vec2 p)"));
+        assert!(!expanded.contains("float hash(vec2 p)"));
+    }
+
+    #[test]
+    fn test_every_function_compiles_in_both_stages() {
+        let vertex = "#version 300 es\n\
+                      #extension GL_WEBGL2_noise : require\n\
+                      layout(location = 0) in vec3 position;\n\
+                      void main() {\n\
+                      float h = hash(position.x) + hash(position.xy) + hash(position);\n\
+                      float n = noise(position.xy) + noise(position);\n\
+                      float s = snoise(position.xy) + snoise(position);\n\
+                      gl_Position = vec4(position, h + n + s);\n\
+                      }\n";
+        let fragment = "#version 300 es\n\
+                        #extension GL_WEBGL2_noise : enable\n\
+                        precision highp float;\n\
+                        out vec4 color;\n\
+                        void main() {\n\
+                        vec3 p = gl_FragCoord.xyz;\n\
+                        color = vec4(hash(p), noise(p.xy), snoise(p), snoise(p.xy));\n\
+                        }\n";
+        for (type_, source) in [(GL_VERTEX_SHADER, vertex), (GL_FRAGMENT_SHADER, fragment)] {
+            let expanded = expand(source).unwrap();
+            if let Err(log) = compile_glsl(type_, &expanded) {
+                panic!("{}", log);
+            }
+        }
+    }
+}
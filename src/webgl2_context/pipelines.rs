@@ -0,0 +1,328 @@
+//! Program pipeline objects, as in GL_EXT_separate_shader_objects.
+//!
+//! A pipeline draws with the vertex stage of one separable program and the
+//! fragment stage of another. It is only used while no program is current.
+//! Each separable program packs its side of the varying interface on its own,
+//! so the vertex outputs and fragment inputs must be declared alike (same
+//! names, types and order) for the two layouts to agree; the pipeline fails
+//! validation when they do not.
+
+use super::registry::{clear_last_error, get_registry, set_last_error};
+use super::types::*;
+use crate::naga_wasm_backend::interface_layout::check_varying_interface;
+use crate::naga_wasm_backend::varying_packing;
+use naga::ShaderStage;
+
+/// First uniform location of a fragment-only separable program.
+///
+/// Uniform values live in storage shared by all programs, so a fragment
+/// program numbers its uniforms after the ones of the vertex program it is
+/// paired with in a pipeline.
+pub(crate) const FRAGMENT_PROGRAM_UNIFORM_BASE: i32 = 32;
+
+fn gl_error(ctx_obj: &mut Context, error: u32, message: &str) -> u32 {
+    set_last_error(message);
+    ctx_obj.set_error(error);
+    ERR_GL
+}
+
+/// Create a program pipeline.
+pub fn ctx_create_program_pipeline(ctx: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return 0;
+        }
+    };
+    let handle = ctx_obj.allocate_program_pipeline_handle();
+    ctx_obj
+        .program_pipelines
+        .insert(handle, ProgramPipeline::default());
+    handle
+}
+
+/// Delete a program pipeline, unbinding it if it is bound.
+pub fn ctx_delete_program_pipeline(ctx: u32, pipeline: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    ctx_obj.program_pipelines.remove(&pipeline);
+    if ctx_obj.bound_program_pipeline == Some(pipeline) {
+        ctx_obj.bound_program_pipeline = None;
+    }
+    super::deletion::release_deleted_objects(ctx_obj);
+    ERR_OK
+}
+
+/// Check if object is a program pipeline.
+pub fn ctx_is_program_pipeline(ctx: u32, pipeline: u32) -> bool {
+    let reg = get_registry().borrow();
+    reg.contexts
+        .get(&ctx)
+        .is_some_and(|c| c.program_pipelines.contains_key(&pipeline))
+}
+
+/// Bind a program pipeline; 0 unbinds.
+pub fn ctx_bind_program_pipeline(ctx: u32, pipeline: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    if pipeline == 0 {
+        ctx_obj.bound_program_pipeline = None;
+        return ERR_OK;
+    }
+    if !ctx_obj.program_pipelines.contains_key(&pipeline) {
+        return gl_error(ctx_obj, GL_INVALID_OPERATION, "program pipeline not found");
+    }
+    ctx_obj.bound_program_pipeline = Some(pipeline);
+    ERR_OK
+}
+
+/// Install the `stages` of `program` in a pipeline. Stages the program has
+/// no shader for, or all of them when `program` is 0, are cleared.
+pub fn ctx_use_program_stages(ctx: u32, pipeline: u32, stages: u32, program: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+
+    if stages != GL_ALL_SHADER_BITS
+        && stages & !(GL_VERTEX_SHADER_BIT | GL_FRAGMENT_SHADER_BIT) != 0
+    {
+        return gl_error(ctx_obj, GL_INVALID_VALUE, "invalid shader stage bits");
+    }
+    if !ctx_obj.program_pipelines.contains_key(&pipeline) {
+        return gl_error(ctx_obj, GL_INVALID_OPERATION, "program pipeline not found");
+    }
+
+    let (vertex, fragment) = if program == 0 {
+        (None, None)
+    } else {
+        let Some(p) = ctx_obj.programs.get(&program).filter(|p| !p.deleted) else {
+            return gl_error(ctx_obj, GL_INVALID_VALUE, "program not found");
+        };
+        if !p.linked || !p.separable {
+            return gl_error(
+                ctx_obj,
+                GL_INVALID_OPERATION,
+                "program is not linked with PROGRAM_SEPARABLE",
+            );
+        }
+        (
+            p.vs_module.is_some().then_some(program),
+            p.fs_module.is_some().then_some(program),
+        )
+    };
+
+    let pl = ctx_obj.program_pipelines.get_mut(&pipeline).unwrap();
+    if stages & GL_VERTEX_SHADER_BIT != 0 {
+        pl.vertex_program = vertex;
+    }
+    if stages & GL_FRAGMENT_SHADER_BIT != 0 {
+        pl.fragment_program = fragment;
+    }
+    super::deletion::release_deleted_objects(ctx_obj);
+    ERR_OK
+}
+
+/// Set the program a pipeline's uniform commands are meant for.
+pub fn ctx_active_shader_program(ctx: u32, pipeline: u32, program: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    if !ctx_obj.program_pipelines.contains_key(&pipeline) {
+        return gl_error(ctx_obj, GL_INVALID_OPERATION, "program pipeline not found");
+    }
+    if program != 0 {
+        match ctx_obj.programs.get(&program).map(|p| p.linked) {
+            None => return gl_error(ctx_obj, GL_INVALID_VALUE, "program not found"),
+            Some(false) => return gl_error(ctx_obj, GL_INVALID_OPERATION, "program is not linked"),
+            Some(true) => {}
+        }
+    }
+    let pl = ctx_obj.program_pipelines.get_mut(&pipeline).unwrap();
+    pl.active_program = (program != 0).then_some(program);
+    ERR_OK
+}
+
+/// Validate a pipeline, updating its VALIDATE_STATUS and info log.
+pub fn ctx_validate_program_pipeline(ctx: u32, pipeline: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    if !ctx_obj.program_pipelines.contains_key(&pipeline) {
+        return gl_error(ctx_obj, GL_INVALID_OPERATION, "program pipeline not found");
+    }
+    let _ = validate_pipeline(ctx_obj, pipeline);
+    ERR_OK
+}
+
+/// Get a program pipeline parameter.
+pub fn ctx_get_program_pipeline_parameter(ctx: u32, pipeline: u32, pname: u32) -> i32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => return 0,
+    };
+    let Some(pl) = ctx_obj.program_pipelines.get(&pipeline) else {
+        gl_error(ctx_obj, GL_INVALID_OPERATION, "program pipeline not found");
+        return 0;
+    };
+    match pname {
+        GL_ACTIVE_PROGRAM => pl.active_program.unwrap_or(0) as i32,
+        GL_VERTEX_SHADER => pl.vertex_program.unwrap_or(0) as i32,
+        GL_FRAGMENT_SHADER => pl.fragment_program.unwrap_or(0) as i32,
+        GL_VALIDATE_STATUS => pl.validate_status as i32,
+        GL_INFO_LOG_LENGTH => pl.info_log.len() as i32,
+        _ => {
+            gl_error(ctx_obj, GL_INVALID_ENUM, "invalid pipeline parameter");
+            0
+        }
+    }
+}
+
+/// Get the info log of the last validation of a pipeline.
+/// Returns a pointer to an ephemeral payload containing the log string
+/// (see [`super::shaders::ctx_get_program_info_log`]), or 0 on failure.
+pub fn ctx_get_program_pipeline_info_log(ctx: u32, pipeline: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => return 0,
+    };
+    let log = match ctx_obj.program_pipelines.get(&pipeline) {
+        Some(pl) => pl.info_log.clone(),
+        None => return 0,
+    };
+    super::ephemeral::alloc_string(ctx_obj, &log)
+}
+
+/// Set a program parameter. Only PROGRAM_SEPARABLE is supported; it takes
+/// effect at the next link.
+pub fn ctx_program_parameteri(ctx: u32, program: u32, pname: u32, value: i32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    if pname != GL_PROGRAM_SEPARABLE {
+        return gl_error(ctx_obj, GL_INVALID_ENUM, "invalid program parameter");
+    }
+    if value != 0 && value != 1 {
+        return gl_error(
+            ctx_obj,
+            GL_INVALID_VALUE,
+            "PROGRAM_SEPARABLE must be 0 or 1",
+        );
+    }
+    match ctx_obj.programs.get_mut(&program) {
+        Some(p) => {
+            p.separable = value != 0;
+            ERR_OK
+        }
+        None => gl_error(ctx_obj, GL_INVALID_VALUE, "program not found"),
+    }
+}
+
+/// Programs providing the vertex and fragment stages of a draw: the current
+/// program, or else the stages of the bound pipeline.
+pub(crate) fn draw_stage_programs(ctx_obj: &mut Context) -> Result<(u32, u32), u32> {
+    if let Some(program) = ctx_obj.current_program {
+        return Ok((program, program));
+    }
+    let Some(pipeline) = ctx_obj.bound_program_pipeline else {
+        set_last_error("no program bound");
+        return Err(ERR_INVALID_ARGS);
+    };
+    validate_pipeline(ctx_obj, pipeline)
+        .map_err(|log| gl_error(ctx_obj, GL_INVALID_OPERATION, &log))
+}
+
+/// Check that a pipeline has a linked vertex and fragment stage whose
+/// varying interfaces match, recording the outcome on the pipeline.
+fn validate_pipeline(ctx_obj: &mut Context, pipeline: u32) -> Result<(u32, u32), String> {
+    let result = check_pipeline(ctx_obj, pipeline);
+    let pl = ctx_obj.program_pipelines.get_mut(&pipeline).unwrap();
+    pl.validate_status = result.is_ok();
+    pl.info_log = result.as_ref().err().cloned().unwrap_or_default();
+    result
+}
+
+fn check_pipeline(ctx_obj: &Context, pipeline: u32) -> Result<(u32, u32), String> {
+    let pl = &ctx_obj.program_pipelines[&pipeline];
+    let stage_program = |handle: Option<u32>, stage: &str| {
+        let handle = handle.ok_or_else(|| format!("Program pipeline has no {} program", stage))?;
+        match ctx_obj.programs.get(&handle) {
+            Some(p) if p.linked && p.separable => Ok((handle, p)),
+            _ => Err(format!(
+                "Program pipeline {} program {} is no longer a linked separable program",
+                stage, handle
+            )),
+        }
+    };
+    let (vs_handle, vs_program) = stage_program(pl.vertex_program, "vertex")?;
+    let (fs_handle, fs_program) = stage_program(pl.fragment_program, "fragment")?;
+    if vs_handle == fs_handle {
+        return Ok((vs_handle, fs_handle));
+    }
+
+    let (Some(vs), Some(fs)) = (&vs_program.vs_module, &fs_program.fs_module) else {
+        return Err("Program pipeline stage has no shader".to_string());
+    };
+    let vs_outputs = varying_packing::stage_varyings(vs, ShaderStage::Vertex);
+    let fs_inputs = varying_packing::stage_varyings(fs, ShaderStage::Fragment);
+    check_varying_interface(vs, &vs_outputs, fs, &fs_inputs)?;
+    for input in &fs_inputs {
+        let slot = |p: &Program| {
+            (
+                p.varying_locations.get(&input.name).copied(),
+                p.varying_components.get(&input.name).copied(),
+            )
+        };
+        if slot(vs_program) != slot(fs_program) {
+            return Err(format!(
+                "Varying '{}' is packed differently by the vertex and fragment programs",
+                input.name
+            ));
+        }
+    }
+    Ok((vs_handle, fs_handle))
+}
@@ -0,0 +1,223 @@
+//! Query objects.
+//!
+//! Only the timer queries of EXT_disjoint_timer_query_webgl2 are supported:
+//! TIME_ELAPSED_EXT between beginQuery and endQuery, and TIMESTAMP_EXT from
+//! queryCounterEXT. Both measure the GPU timeline (see [`super::timing`]),
+//! virtual or following the host clock, and their results are available as soon as the query
+//! ends; the timeline is never disjoint.
+
+use super::registry::{clear_last_error, get_registry, set_last_error};
+use super::types::*;
+
+fn gl_error(ctx_obj: &mut Context, error: u32, message: &str) -> u32 {
+    set_last_error(message);
+    ctx_obj.set_error(error);
+    ERR_GL
+}
+
+/// Create a query object.
+pub fn ctx_create_query(ctx: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return 0;
+        }
+    };
+    let handle = ctx_obj.allocate_query_handle();
+    ctx_obj.queries.insert(handle, Query::default());
+    handle
+}
+
+/// Delete a query object, ending it if it is active.
+pub fn ctx_delete_query(ctx: u32, query: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    if query == 0 {
+        return ERR_OK;
+    }
+    if ctx_obj.active_timer_query == Some(query) {
+        ctx_obj.active_timer_query = None;
+    }
+    ctx_obj.queries.remove(&query);
+    ERR_OK
+}
+
+/// Check if object is a query that has been started.
+pub fn ctx_is_query(ctx: u32, query: u32) -> bool {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    match reg.contexts.get(&ctx) {
+        Some(c) => c.queries.get(&query).is_some_and(|q| q.target != 0),
+        None => false,
+    }
+}
+
+/// Start measuring elapsed virtual GPU time.
+pub fn ctx_begin_query(ctx: u32, target: u32, query: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    if target != GL_TIME_ELAPSED_EXT {
+        return gl_error(ctx_obj, GL_INVALID_ENUM, "unsupported query target");
+    }
+    if ctx_obj.active_timer_query.is_some() {
+        return gl_error(
+            ctx_obj,
+            GL_INVALID_OPERATION,
+            "a timer query is already active",
+        );
+    }
+    let now = ctx_obj.gpu_timeline.now_ns();
+    let Some(q) = ctx_obj.queries.get_mut(&query) else {
+        return gl_error(ctx_obj, GL_INVALID_OPERATION, "query not found");
+    };
+    if q.target != 0 && q.target != target {
+        return gl_error(
+            ctx_obj,
+            GL_INVALID_OPERATION,
+            "query was used with a different target",
+        );
+    }
+    *q = Query {
+        target,
+        begin_ns: now,
+        result: None,
+    };
+    ctx_obj.active_timer_query = Some(query);
+    ERR_OK
+}
+
+/// End the active query of `target`, making its result available.
+pub fn ctx_end_query(ctx: u32, target: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    if target != GL_TIME_ELAPSED_EXT {
+        return gl_error(ctx_obj, GL_INVALID_ENUM, "unsupported query target");
+    }
+    let Some(query) = ctx_obj.active_timer_query.take() else {
+        return gl_error(ctx_obj, GL_INVALID_OPERATION, "no timer query is active");
+    };
+    let now = ctx_obj.gpu_timeline.now_ns();
+    if let Some(q) = ctx_obj.queries.get_mut(&query) {
+        q.result = Some(now.saturating_sub(q.begin_ns));
+    }
+    ERR_OK
+}
+
+/// Record the virtual GPU time into `query` (queryCounterEXT).
+pub fn ctx_query_counter(ctx: u32, query: u32, target: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    if target != GL_TIMESTAMP_EXT {
+        return gl_error(
+            ctx_obj,
+            GL_INVALID_ENUM,
+            "queryCounterEXT target must be TIMESTAMP_EXT",
+        );
+    }
+    if ctx_obj.active_timer_query == Some(query) {
+        return gl_error(ctx_obj, GL_INVALID_OPERATION, "query is active");
+    }
+    let now = ctx_obj.gpu_timeline.now_ns();
+    let Some(q) = ctx_obj.queries.get_mut(&query) else {
+        return gl_error(ctx_obj, GL_INVALID_OPERATION, "query not found");
+    };
+    if q.target != 0 && q.target != target {
+        return gl_error(
+            ctx_obj,
+            GL_INVALID_OPERATION,
+            "query was used with a different target",
+        );
+    }
+    *q = Query {
+        target,
+        begin_ns: now,
+        result: Some(now),
+    };
+    ERR_OK
+}
+
+/// Get a parameter of the queries of `target` (getQuery).
+pub fn ctx_get_query(ctx: u32, target: u32, pname: u32) -> i32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return -1;
+        }
+    };
+    match (target, pname) {
+        (GL_TIME_ELAPSED_EXT, GL_CURRENT_QUERY) => ctx_obj.active_timer_query.unwrap_or(0) as i32,
+        (GL_TIMESTAMP_EXT, GL_CURRENT_QUERY) => 0,
+        (GL_TIME_ELAPSED_EXT | GL_TIMESTAMP_EXT, GL_QUERY_COUNTER_BITS_EXT) => 64,
+        _ => {
+            gl_error(
+                ctx_obj,
+                GL_INVALID_ENUM,
+                "invalid query target or parameter",
+            );
+            -1
+        }
+    }
+}
+
+/// Get QUERY_RESULT or QUERY_RESULT_AVAILABLE of a query, or -1 on error.
+pub fn ctx_get_query_parameter(ctx: u32, query: u32, pname: u32) -> i64 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return -1;
+        }
+    };
+    if ctx_obj.active_timer_query == Some(query) {
+        gl_error(ctx_obj, GL_INVALID_OPERATION, "query is active");
+        return -1;
+    }
+    let Some(q) = ctx_obj.queries.get(&query).filter(|q| q.target != 0) else {
+        gl_error(ctx_obj, GL_INVALID_OPERATION, "query has not been started");
+        return -1;
+    };
+    match pname {
+        GL_QUERY_RESULT => q.result.unwrap_or(0) as i64,
+        GL_QUERY_RESULT_AVAILABLE => q.result.is_some() as i64,
+        _ => {
+            gl_error(ctx_obj, GL_INVALID_ENUM, "invalid query parameter");
+            -1
+        }
+    }
+}
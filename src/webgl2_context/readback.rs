@@ -0,0 +1,586 @@
+//! Typed readback for Rust callers
+//!
+//! `ctx_read_pixels` hands JavaScript an ephemeral pointer into the blob
+//! arena; native tests and tools want owned, typed data instead. These
+//! helpers read the current read framebuffer, or a texture or buffer
+//! without binding it, and return errno on failure, with the message
+//! available from the last error as usual.
+
+use super::drawing::{flip_rows, read_color_into, read_pixels_bpp};
+use super::registry::{clear_last_error, get_registry, set_last_error};
+use super::types::*;
+use crate::wasm_gl_emu::{GpuBuffer, StorageLayout};
+
+/// A window-space rectangle, origin at the bottom left as in `readPixels`
+/// (top left for contexts with [`ReadOrigin::TopLeft`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn pixel_count(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+}
+
+/// Read `rect` of the read framebuffer as tightly packed RGBA8 rows, bottom
+/// row first (top row first with [`ReadOrigin::TopLeft`]). Pixels outside
+/// the framebuffer read as zero.
+pub fn read_pixels_rgba(ctx: u32, rect: Rect) -> Result<Vec<u8>, u32> {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    let ctx_obj = match reg.contexts.get(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return Err(ERR_INVALID_HANDLE);
+        }
+    };
+
+    let mut pixels =
+        vec![0; rect.pixel_count() * read_pixels_bpp(GL_RGBA, GL_UNSIGNED_BYTE) as usize];
+    read_color_into(
+        ctx_obj,
+        rect.x,
+        rect.y,
+        rect.width,
+        rect.height,
+        GL_RGBA,
+        GL_UNSIGNED_BYTE,
+        &mut pixels,
+    )?;
+    Ok(pixels)
+}
+
+/// Read `rect` of the read framebuffer's depth buffer, in the row order of
+/// [`read_pixels_rgba`].
+/// Pixels outside the framebuffer, or every pixel when there is no depth
+/// buffer, read as the cleared depth 1.0.
+pub fn read_depth(ctx: u32, rect: Rect) -> Result<Vec<f32>, u32> {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    let ctx_obj = match reg.contexts.get(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return Err(ERR_INVALID_HANDLE);
+        }
+    };
+
+    let top_left = ctx_obj.read_origin == ReadOrigin::TopLeft;
+    let rect = if top_left {
+        let (_, _, fb_height, _) = ctx_obj.get_color_attachment_info(true);
+        Rect {
+            y: fb_height as i32 - rect.y - rect.height as i32,
+            ..rect
+        }
+    } else {
+        rect
+    };
+    let mut depth = vec![1.0; rect.pixel_count()];
+    match ctx_obj.bound_read_framebuffer {
+        None => {
+            let fb = &ctx_obj.default_framebuffer;
+            copy_depth(
+                |i| fb.depth.get(i).copied(),
+                (fb.width, fb.height, fb.layout),
+                rect,
+                &mut depth,
+            );
+        }
+        Some(fb_handle) => {
+            let attachment = ctx_obj
+                .framebuffers
+                .get(&fb_handle)
+                .and_then(|fb| fb.depth_attachment);
+            let handle = match attachment {
+                Some(Attachment::Texture(tex)) => ctx_obj
+                    .textures
+                    .get(&tex)
+                    .and_then(|t| t.levels.get(&0))
+                    .map(|level| level.gpu_handle),
+                Some(Attachment::Renderbuffer(rb)) => {
+                    ctx_obj.renderbuffers.get(&rb).map(|rb| rb.gpu_handle)
+                }
+                None => None,
+            };
+            if let Some(buffer) = handle.and_then(|h| ctx_obj.kernel.get_buffer(h)) {
+                // The rasterizer addresses depth with the color target's layout
+                let (color, _, _, _) = ctx_obj.get_color_attachment_info(true);
+                let layout = ctx_obj
+                    .kernel
+                    .get_buffer(color)
+                    .map_or(StorageLayout::Linear, |b| b.layout);
+                copy_depth(
+                    |i| {
+                        let bytes = buffer.data.get(i * 4..i * 4 + 4)?;
+                        Some(f32::from_le_bytes(bytes.try_into().ok()?))
+                    },
+                    (buffer.width, buffer.height, layout),
+                    rect,
+                    &mut depth,
+                );
+            }
+        }
+    }
+    if top_left {
+        flip_rows(&mut depth, rect.height);
+    }
+    Ok(depth)
+}
+
+fn copy_depth(
+    depth_at: impl Fn(usize) -> Option<f32>,
+    (width, height, layout): (u32, u32, StorageLayout),
+    rect: Rect,
+    out: &mut [f32],
+) {
+    for row in 0..rect.height {
+        for col in 0..rect.width {
+            let (x, y) = (rect.x + col as i32, rect.y + row as i32);
+            if x < 0 || y < 0 || x as u32 >= width || y as u32 >= height {
+                continue;
+            }
+            let index = GpuBuffer::offset_for_layout(
+                x as u32,
+                y as u32,
+                0,
+                width,
+                height,
+                1,
+                wgpu_types::TextureFormat::R8Unorm,
+                layout,
+            );
+            if let Some(value) = depth_at(index) {
+                out[(row * rect.width + col) as usize] = value;
+            }
+        }
+    }
+}
+
+/// FNV-1a hash of the whole read framebuffer as RGBA8, including its size.
+/// Cheap enough to compare against a golden value in CI before falling back
+/// to an image diff.
+pub fn framebuffer_hash(ctx: u32) -> Result<u64, u32> {
+    let (width, height) = match read_framebuffer_size(ctx) {
+        Some(size) => size,
+        None => {
+            set_last_error("invalid context handle");
+            return Err(ERR_INVALID_HANDLE);
+        }
+    };
+    let pixels = read_pixels_rgba(ctx, Rect::new(0, 0, width, height))?;
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in width
+        .to_le_bytes()
+        .iter()
+        .chain(&height.to_le_bytes())
+        .chain(&pixels)
+    {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    Ok(hash)
+}
+
+/// A texture level's texels as [`texture_pixels`] reads them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TexturePixels {
+    pub width: u32,
+    pub height: u32,
+    /// Storage format of the level, which sets the bytes per texel
+    pub internal_format: u32,
+    pub data: Vec<u8>,
+}
+
+/// Read layer `layer` of mip `level` of texture `tex` without binding it:
+/// the texels in their storage format, tightly packed, first uploaded row
+/// first.
+pub fn texture_pixels(ctx: u32, tex: u32, level: u32, layer: u32) -> Result<TexturePixels, u32> {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    let ctx_obj = match reg.contexts.get(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return Err(ERR_INVALID_HANDLE);
+        }
+    };
+    let texture = match ctx_obj.textures.get(&tex) {
+        Some(t) => t,
+        None => {
+            set_last_error(&format!("texture {} not found", tex));
+            return Err(ERR_INVALID_HANDLE);
+        }
+    };
+    let mip = match texture.levels.get(&(level as usize)) {
+        Some(m) => m,
+        None => {
+            set_last_error(&format!("texture {} has no level {}", tex, level));
+            return Err(ERR_INVALID_ARGS);
+        }
+    };
+    if layer >= mip.depth.max(1) {
+        set_last_error(&format!(
+            "level {} of texture {} has no layer {}",
+            level, tex, layer
+        ));
+        return Err(ERR_INVALID_ARGS);
+    }
+    let buffer = match ctx_obj.kernel.get_buffer(mip.gpu_handle) {
+        Some(b) => b,
+        None => {
+            set_last_error("texture storage not found in kernel");
+            return Err(ERR_INTERNAL);
+        }
+    };
+
+    let bpp = buffer.format.block_copy_size(None).unwrap_or(4) as usize;
+    let mut data = Vec::with_capacity(buffer.width as usize * buffer.height as usize * bpp);
+    for y in 0..buffer.height {
+        for x in 0..buffer.width {
+            let offset = buffer.get_pixel_offset(x, y, layer);
+            data.extend_from_slice(&buffer.data[offset..offset + bpp]);
+        }
+    }
+    Ok(TexturePixels {
+        width: buffer.width,
+        height: buffer.height,
+        internal_format: mip.internal_format,
+        data,
+    })
+}
+
+/// A renderbuffer's depth and stencil planes as [`renderbuffer_pixels`]
+/// reads them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderbufferPixels {
+    pub width: u32,
+    pub height: u32,
+    pub internal_format: u32,
+    /// One depth value per pixel, or empty when the format has no depth
+    pub depth: Vec<f32>,
+    /// One stencil value per pixel, or empty when the format has no stencil
+    pub stencil: Vec<u8>,
+}
+
+/// Read the depth and stencil planes of renderbuffer `rb` without binding
+/// it, bottom row first as draws address them. Color renderbuffers read as
+/// empty planes; [`read_pixels_rgba`] reads their colors.
+pub fn renderbuffer_pixels(ctx: u32, rb: u32) -> Result<RenderbufferPixels, u32> {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return Err(ERR_INVALID_HANDLE);
+        }
+    };
+    let renderbuffer = match ctx_obj.renderbuffers.get(&rb) {
+        Some(r) => r,
+        None => {
+            set_last_error(&format!("renderbuffer {} not found", rb));
+            return Err(ERR_INVALID_HANDLE);
+        }
+    };
+    let (width, height, internal_format) = (
+        renderbuffer.width,
+        renderbuffer.height,
+        renderbuffer.internal_format,
+    );
+    let mut pixels = RenderbufferPixels {
+        width,
+        height,
+        internal_format,
+        depth: Vec::new(),
+        stencil: Vec::new(),
+    };
+    let Some(buffer) = ctx_obj.kernel.get_buffer_mut(renderbuffer.gpu_handle) else {
+        return Ok(pixels);
+    };
+    if !buffer.format.is_depth_stencil_format() {
+        return Ok(pixels);
+    }
+    let layout = buffer.layout;
+    let (depth, stencil) = buffer.depth_stencil_planes();
+    for y in 0..height {
+        for x in 0..width {
+            let index = GpuBuffer::offset_for_layout(
+                x,
+                y,
+                0,
+                width,
+                height,
+                1,
+                wgpu_types::TextureFormat::R8Unorm,
+                layout,
+            );
+            if let Some(&value) = depth.get(index) {
+                pixels.depth.push(value);
+            }
+            if let Some(&value) = stencil.get(index) {
+                pixels.stencil.push(value);
+            }
+        }
+    }
+    Ok(pixels)
+}
+
+/// Read `len` bytes of buffer `buf` from `offset` without binding it.
+/// `len == u32::MAX` reads to the end of the buffer.
+pub fn buffer_data(ctx: u32, buf: u32, offset: u32, len: u32) -> Result<Vec<u8>, u32> {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    let ctx_obj = match reg.contexts.get(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return Err(ERR_INVALID_HANDLE);
+        }
+    };
+    let buffer = match ctx_obj.buffers.get(&buf) {
+        Some(b) => b,
+        None => {
+            set_last_error(&format!("buffer {} not found", buf));
+            return Err(ERR_INVALID_HANDLE);
+        }
+    };
+    let data: &[u8] = ctx_obj
+        .kernel
+        .get_buffer(buffer.gpu_handle)
+        .map_or(&[], |b| &b.data[..]);
+    let end = if len == u32::MAX {
+        (data.len() as u64).max(offset as u64)
+    } else {
+        offset as u64 + len as u64
+    };
+    if end > data.len() as u64 {
+        set_last_error(&format!(
+            "range {}..{} is outside buffer {} of {} bytes",
+            offset,
+            end,
+            buf,
+            data.len()
+        ));
+        return Err(ERR_INVALID_ARGS);
+    }
+    Ok(data[offset as usize..end as usize].to_vec())
+}
+
+/// [`texture_pixels`] for JavaScript: returns an ephemeral blob pointer
+/// (see `ctx_read_pixels`) to the level's width, height and internal format
+/// as little-endian `u32`s, followed by its texels. Returns 0 on failure.
+pub fn ctx_get_texture_pixels(ctx: u32, tex: u32, level: u32, layer: u32) -> u32 {
+    let pixels = match texture_pixels(ctx, tex, level, layer) {
+        Ok(p) => p,
+        Err(_) => return 0,
+    };
+    let header = [pixels.width, pixels.height, pixels.internal_format];
+    let mut reg = get_registry().borrow_mut();
+    let Some(ctx_obj) = reg.contexts.get_mut(&ctx) else {
+        return 0;
+    };
+    let len = header.len() * 4 + pixels.data.len();
+    let ptr = ctx_obj.alloc_blob(len as u32);
+    let dest = &mut ctx_obj.blob_arena[16..16 + len];
+    for (chunk, value) in dest.chunks_exact_mut(4).zip(header) {
+        chunk.copy_from_slice(&value.to_le_bytes());
+    }
+    dest[header.len() * 4..].copy_from_slice(&pixels.data);
+    ptr
+}
+
+/// [`buffer_data`] for JavaScript: returns an ephemeral blob pointer (see
+/// `ctx_read_pixels`) to the bytes. Returns 0 on failure.
+pub fn ctx_get_buffer_data(ctx: u32, buf: u32, offset: u32, len: u32) -> u32 {
+    let data = match buffer_data(ctx, buf, offset, len) {
+        Ok(d) => d,
+        Err(_) => return 0,
+    };
+    let mut reg = get_registry().borrow_mut();
+    let Some(ctx_obj) = reg.contexts.get_mut(&ctx) else {
+        return 0;
+    };
+    let ptr = ctx_obj.alloc_blob(data.len() as u32);
+    ctx_obj.blob_arena[16..16 + data.len()].copy_from_slice(&data);
+    ptr
+}
+
+/// [`renderbuffer_pixels`] for JavaScript: returns an ephemeral blob pointer
+/// (see `ctx_read_pixels`) to the renderbuffer's width, height, internal
+/// format and number of depth values as little-endian `u32`s, followed by
+/// the depth values as little-endian `f32`s and then the stencil bytes.
+/// Returns 0 on failure.
+pub fn ctx_get_renderbuffer_pixels(ctx: u32, rb: u32) -> u32 {
+    let pixels = match renderbuffer_pixels(ctx, rb) {
+        Ok(p) => p,
+        Err(_) => return 0,
+    };
+    let header = [
+        pixels.width,
+        pixels.height,
+        pixels.internal_format,
+        pixels.depth.len() as u32,
+    ];
+    let mut reg = get_registry().borrow_mut();
+    let Some(ctx_obj) = reg.contexts.get_mut(&ctx) else {
+        return 0;
+    };
+    let depth_end = (header.len() + pixels.depth.len()) * 4;
+    let len = depth_end + pixels.stencil.len();
+    let ptr = ctx_obj.alloc_blob(len as u32);
+    let dest = &mut ctx_obj.blob_arena[16..16 + len];
+    let words = header
+        .into_iter()
+        .chain(pixels.depth.iter().map(|d| d.to_bits()));
+    for (chunk, value) in dest[..depth_end].chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&value.to_le_bytes());
+    }
+    dest[depth_end..].copy_from_slice(&pixels.stencil);
+    ptr
+}
+
+fn read_framebuffer_size(ctx: u32) -> Option<(u32, u32)> {
+    super::registry::with_context(ctx, |c| {
+        let (_, width, height, _) = c.get_color_attachment_info(true);
+        (width, height)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl2_context::buffers::ctx_create_buffer;
+    use crate::webgl2_context::registry::{
+        create_context_with_flags, destroy_context, with_context,
+    };
+    use crate::webgl2_context::state::{
+        ctx_clear, ctx_clear_color, ctx_enable, ctx_resize, ctx_scissor,
+    };
+    use crate::webgl2_context::textures::{
+        ctx_bind_texture, ctx_create_texture, tex_image_2d_data,
+    };
+
+    #[test]
+    fn reads_back_cleared_framebuffer() {
+        let ctx = create_context_with_flags(0, 2, 2);
+        let before = framebuffer_hash(ctx).unwrap();
+        ctx_clear_color(ctx, 1.0, 0.0, 0.0, 1.0);
+        ctx_clear(ctx, GL_COLOR_BUFFER_BIT | GL_DEPTH_BUFFER_BIT);
+
+        let pixels = read_pixels_rgba(ctx, Rect::new(1, 1, 1, 1)).unwrap();
+        let depth = read_depth(ctx, Rect::new(0, 0, 2, 1)).unwrap();
+        let after = framebuffer_hash(ctx).unwrap();
+        destroy_context(ctx);
+
+        assert_eq!(pixels, vec![255, 0, 0, 255]);
+        assert_eq!(depth, vec![1.0, 1.0]);
+        assert_ne!(before, after);
+        assert_eq!(
+            read_pixels_rgba(ctx, Rect::new(0, 0, 1, 1)),
+            Err(ERR_INVALID_HANDLE)
+        );
+    }
+
+    #[test]
+    fn top_left_origin_reads_top_row_first() {
+        // Red only in the bottom-left pixel of a 2x2 framebuffer
+        let rgba = |flags| {
+            let ctx = create_context_with_flags(flags, 2, 2);
+            ctx_enable(ctx, GL_SCISSOR_TEST);
+            ctx_scissor(ctx, 0, 0, 1, 1);
+            ctx_clear_color(ctx, 1.0, 0.0, 0.0, 1.0);
+            ctx_clear(ctx, GL_COLOR_BUFFER_BIT);
+            let all = read_pixels_rgba(ctx, Rect::new(0, 0, 2, 2)).unwrap();
+            let corner = read_pixels_rgba(ctx, Rect::new(0, 0, 1, 1)).unwrap();
+            destroy_context(ctx);
+            (all, corner)
+        };
+        let (red, black) = ([255, 0, 0, 255], [0, 0, 0, 0]);
+
+        let (all, corner) = rgba(0);
+        assert_eq!(all, [red, black, black, black].concat());
+        assert_eq!(corner, red);
+        let (all, corner) = rgba(0x100);
+        assert_eq!(all, [black, black, red, black].concat());
+        assert_eq!(corner, black);
+    }
+
+    #[test]
+    fn reads_textures_and_buffers_without_binding_them() {
+        let ctx = create_context_with_flags(0, 1, 1);
+        let tex = ctx_create_texture(ctx);
+        ctx_bind_texture(ctx, GL_TEXTURE_2D, tex);
+        let texels: Vec<u8> = (0..3 * 2 * 4).collect();
+        let upload = tex_image_2d_data(
+            ctx,
+            0,
+            GL_RGBA8 as i32,
+            3,
+            2,
+            GL_UNSIGNED_BYTE as i32,
+            &texels,
+        );
+        ctx_bind_texture(ctx, GL_TEXTURE_2D, 0);
+        let pixels = texture_pixels(ctx, tex, 0, 0).unwrap();
+        let missing_level = texture_pixels(ctx, tex, 1, 0);
+        let missing_layer = texture_pixels(ctx, tex, 0, 1);
+
+        let buf = ctx_create_buffer(ctx);
+        let whole = buffer_data(ctx, buf, 0, u32::MAX);
+        let past_end = buffer_data(ctx, buf, 0, 4);
+        let missing_buffer = buffer_data(ctx, buf + 100, 0, 0);
+        destroy_context(ctx);
+
+        assert_eq!(upload, ERR_OK);
+        assert_eq!(
+            pixels,
+            TexturePixels {
+                width: 3,
+                height: 2,
+                internal_format: GL_RGBA8,
+                data: texels,
+            }
+        );
+        assert_eq!(missing_level, Err(ERR_INVALID_ARGS));
+        assert_eq!(missing_layer, Err(ERR_INVALID_ARGS));
+        assert_eq!(whole, Ok(Vec::new()));
+        assert_eq!(past_end, Err(ERR_INVALID_ARGS));
+        assert_eq!(missing_buffer, Err(ERR_INVALID_HANDLE));
+    }
+
+    #[test]
+    fn resize_reallocates_planes_at_4k() {
+        let ctx = create_context_with_flags(0, 640, 480);
+        assert_eq!(ctx_resize(ctx, 3840, 2160), ERR_OK);
+        ctx_clear_color(ctx, 0.0, 0.0, 1.0, 1.0);
+        ctx_clear(ctx, GL_COLOR_BUFFER_BIT | GL_DEPTH_BUFFER_BIT);
+
+        let corner = read_pixels_rgba(ctx, Rect::new(3839, 2159, 1, 1)).unwrap();
+        let depth = read_depth(ctx, Rect::new(3839, 2159, 1, 1)).unwrap();
+        let (viewport, scissor) = with_context(ctx, |c| (c.viewport, c.scissor_box)).unwrap();
+        let too_large = ctx_resize(ctx, 16385, 1);
+        destroy_context(ctx);
+
+        assert_eq!(corner, [0, 0, 255, 255]);
+        assert_eq!(depth, [1.0]);
+        assert_eq!(viewport, (0, 0, 3840, 2160));
+        assert_eq!(scissor, (0, 0, 3840, 2160));
+        assert_eq!(too_large, ERR_INVALID_ARGS);
+    }
+}
@@ -1,7 +1,7 @@
 use super::types::*;
 use std::alloc::{alloc, dealloc, Layout};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Global registry: handle -> Context
 ///
@@ -18,29 +18,258 @@ pub(crate) fn get_registry() -> &'static RefCell<Registry> {
     &REGISTRY
         .get_or_init(|| {
             SyncRefCell(RefCell::new(Registry {
-                contexts: HashMap::new(),
-                next_context_handle: FIRST_HANDLE,
+                contexts: ContextTable::default(),
                 allocations: HashMap::new(),
+                frame_arena: FrameArena::default(),
+                checked_out: None,
             }))
         })
         .0
 }
 
+/// Recover from a trap that aborted an export: returns the stage of the
+/// shader the trap hit (`GL_VERTEX_SHADER` or `GL_FRAGMENT_SHADER`), or 0
+/// if no shader was running. A trap in the emulator itself may have left any
+/// state half-updated, so nothing is released and the module should not be
+/// used again.
+///
+/// Shaders only run inside [`with_checked_out_context`], which holds no
+/// registry borrow, so a trap leaves just the drawing context checked out.
+/// Recovery checks it back in with its draw state reset and rewinds the
+/// frame arena. A trap unwinds the WASM stack without running destructors,
+/// so temporary buffers of the aborted draw leak.
+pub fn recover_shader_trap() -> u32 {
+    let stage = crate::wasm_gl_emu::rasterizer::take_trapped_stage();
+    if stage == 0 {
+        return 0;
+    }
+    let mut reg = get_registry().borrow_mut();
+    if let Some(CheckedOut { handle, context }) = reg.checked_out.take() {
+        // SAFETY: the pointer came from `Box::into_raw` in
+        // `with_checked_out_context`, whose call the trap ended.
+        let mut context = unsafe { Box::from_raw(context) };
+        context.rasterizer.reset_draw_state();
+        context.attribute_bindings.clear();
+        reg.contexts.check_in(handle, context);
+    }
+    reg.frame_arena.reset();
+    stage
+}
+
+/// Run a draw on the context for `handle` with the registry released, so
+/// that a shader trap leaves no borrow behind (see [`recover_shader_trap`]).
+/// Returns `ERR_INVALID_HANDLE` for an unknown handle.
+pub(crate) fn with_checked_out_context(handle: u32, draw: impl FnOnce(&mut Context) -> u32) -> u32 {
+    let context = {
+        let mut reg = get_registry().borrow_mut();
+        let Some(context) = reg.contexts.check_out(handle) else {
+            return ERR_INVALID_HANDLE;
+        };
+        let context = Box::into_raw(context);
+        reg.checked_out = Some(CheckedOut { handle, context });
+        context
+    };
+    // SAFETY: the registry handed over the box, and takes it back below or,
+    // if a trap ends this call, in `recover_shader_trap`.
+    let code = draw(unsafe { &mut *context });
+    let mut reg = get_registry().borrow_mut();
+    reg.checked_out = None;
+    reg.contexts
+        .check_in(handle, unsafe { Box::from_raw(context) });
+    code
+}
+
 pub(crate) struct Registry {
-    pub(crate) contexts: HashMap<u32, Context>,
-    pub(crate) next_context_handle: u32,
-    /// Track allocations created via `wasm_alloc`: ptr -> size
-    pub(crate) allocations: HashMap<u32, u32>,
-}
-
-impl Registry {
-    fn allocate_context_handle(&mut self) -> u32 {
-        let h = self.next_context_handle;
-        self.next_context_handle = self.next_context_handle.saturating_add(1);
-        if self.next_context_handle == 0 {
-            self.next_context_handle = FIRST_HANDLE;
+    pub(crate) contexts: ContextTable,
+    /// Track allocations created via `wasm_alloc` and `wasm_ctx_alloc`
+    pub(crate) allocations: HashMap<u32, Allocation>,
+    /// Bump region for transient per-frame staging data (`wasm_frame_alloc`)
+    pub(crate) frame_arena: FrameArena,
+    /// Context a draw has checked out (see [`with_checked_out_context`])
+    checked_out: Option<CheckedOut>,
+}
+
+struct CheckedOut {
+    handle: u32,
+    context: *mut Context,
+}
+
+// SAFETY: WASM is single-threaded; the pointer is only dereferenced by the
+// draw that checked the context out, or by trap recovery after it.
+unsafe impl Send for CheckedOut {}
+
+/// A live `wasm_alloc` allocation.
+#[derive(Clone, Copy)]
+pub(crate) struct Allocation {
+    size: u32,
+    /// Context that frees the allocation when destroyed, or `INVALID_HANDLE`
+    owner: u32,
+}
+
+/// Bits of a context handle that hold its slot index (plus one, so that no
+/// handle is 0). The remaining high bits hold the slot's generation.
+const CONTEXT_INDEX_BITS: u32 = 20;
+const CONTEXT_INDEX_MASK: u32 = (1 << CONTEXT_INDEX_BITS) - 1;
+/// Last generation a handle can encode.
+const MAX_CONTEXT_GENERATION: u32 = u32::MAX >> CONTEXT_INDEX_BITS;
+
+struct ContextSlot {
+    handle: u32,
+    /// `None` while a draw has the context checked out
+    context: Option<Box<Context>>,
+    /// Allocations owned by the context (`wasm_ctx_alloc`)
+    allocations: HashSet<u32>,
+}
+
+/// Live contexts, indexed by handle in O(1).
+///
+/// Handles encode a slot index and a generation. Destroyed slots are reused
+/// with the next generation, so a stale handle never resolves to a newer
+/// context. A slot whose last generation is used up is retired instead of
+/// wrapping around, which grows the table by one slot every
+/// `MAX_CONTEXT_GENERATION + 1` contexts created in it.
+#[derive(Default)]
+pub(crate) struct ContextTable {
+    slots: Vec<Option<ContextSlot>>,
+    /// Generation of the next context stored in each slot
+    generations: Vec<u32>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl ContextTable {
+    fn slot(&self, handle: u32) -> Option<&ContextSlot> {
+        let index = (handle & CONTEXT_INDEX_MASK).checked_sub(1)? as usize;
+        self.slots
+            .get(index)?
+            .as_ref()
+            .filter(|slot| slot.handle == handle)
+    }
+
+    fn slot_mut(&mut self, handle: u32) -> Option<&mut ContextSlot> {
+        let index = (handle & CONTEXT_INDEX_MASK).checked_sub(1)? as usize;
+        self.slots
+            .get_mut(index)?
+            .as_mut()
+            .filter(|slot| slot.handle == handle)
+    }
+
+    pub(crate) fn get(&self, handle: &u32) -> Option<&Context> {
+        self.slot(*handle)?.context.as_deref()
+    }
+
+    pub(crate) fn get_mut(&mut self, handle: &u32) -> Option<&mut Context> {
+        self.slot_mut(*handle)?.context.as_deref_mut()
+    }
+
+    /// Take a context out of its slot; the handle stays allocated, but
+    /// resolves to nothing until [`ContextTable::check_in`].
+    fn check_out(&mut self, handle: u32) -> Option<Box<Context>> {
+        self.slot_mut(handle)?.context.take()
+    }
+
+    /// Put a checked-out context back. It is dropped if its context was
+    /// destroyed in the meantime.
+    fn check_in(&mut self, handle: u32, context: Box<Context>) {
+        if let Some(slot) = self.slot_mut(handle) {
+            slot.context = Some(context);
+        }
+    }
+
+    /// Store a context, returning its handle (`INVALID_HANDLE` when full).
+    fn insert(&mut self, context: Context) -> u32 {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None if self.slots.len() < CONTEXT_INDEX_MASK as usize => {
+                self.slots.push(None);
+                self.generations.push(0);
+                self.slots.len() - 1
+            }
+            None => return INVALID_HANDLE,
+        };
+        let generation = self.generations[index];
+        self.generations[index] = generation + 1;
+        let handle = (generation << CONTEXT_INDEX_BITS) | (index as u32 + 1);
+        self.slots[index] = Some(ContextSlot {
+            handle,
+            context: Some(Box::new(context)),
+            allocations: HashSet::new(),
+        });
+        self.len += 1;
+        handle
+    }
+
+    fn remove(&mut self, handle: &u32) -> Option<ContextSlot> {
+        self.slot(*handle)?;
+        let index = (*handle & CONTEXT_INDEX_MASK) as usize - 1;
+        if self.generations[index] <= MAX_CONTEXT_GENERATION {
+            self.free.push(index);
         }
-        h
+        self.len -= 1;
+        self.slots[index].take()
+    }
+
+    /// Number of live contexts.
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Default size of a single frame arena chunk.
+const FRAME_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Alignment of every pointer handed out by the frame arena.
+const FRAME_ALIGN: usize = 8;
+
+/// Bump allocator for transient data whose lifetime ends at the next `wasm_frame_reset`.
+///
+/// Memory is carved out of a list of chunks. Chunks are never moved or freed while a
+/// frame is in flight, so pointers stay valid until the reset. On reset the cursor
+/// rewinds to the first chunk and the chunks are reused for the next frame.
+#[derive(Default)]
+pub(crate) struct FrameArena {
+    chunks: Vec<Vec<u8>>,
+    /// Index of the chunk currently being bumped
+    current: usize,
+    /// Byte offset of the next free position within the current chunk
+    offset: usize,
+}
+
+impl FrameArena {
+    /// Bump-allocate `size` bytes. Returns a pointer aligned to `FRAME_ALIGN`.
+    fn alloc(&mut self, size: usize) -> u32 {
+        while self.current < self.chunks.len() {
+            let chunk = &self.chunks[self.current];
+            let base = chunk.as_ptr() as usize;
+            let start = (base + self.offset + FRAME_ALIGN - 1) & !(FRAME_ALIGN - 1);
+            let end = start - base + size;
+            if end <= chunk.len() {
+                self.offset = end;
+                return start as u32;
+            }
+            self.current += 1;
+            self.offset = 0;
+        }
+
+        // No existing chunk fits: append one large enough (plus alignment slack).
+        let chunk_size = FRAME_CHUNK_SIZE.max(size + FRAME_ALIGN);
+        self.chunks.push(vec![0u8; chunk_size]);
+        self.current = self.chunks.len() - 1;
+        self.offset = 0;
+        self.alloc(size)
+    }
+
+    /// Rewind the arena. All pointers previously returned become invalid.
+    fn reset(&mut self) {
+        // If the last frame spilled into several chunks, merge them into one so the
+        // next frame of similar size fits into a single contiguous region.
+        if self.current > 0 {
+            let total: usize = self.chunks.iter().map(|c| c.len()).sum();
+            self.chunks.clear();
+            self.chunks.push(vec![0u8; total]);
+        }
+        self.current = 0;
+        self.offset = 0;
     }
 }
 
@@ -48,9 +277,12 @@ impl Registry {
 // Public API (exported to WASM)
 // ============================================================================
 
-/// Set last error message (internal helper)
+/// Set last error message for a GL error (internal helper)
 pub fn set_last_error(msg: &str) {
-    crate::error::set_error(crate::error::ErrorSource::WebGL, 0, msg);
+    crate::error::report(crate::error::Webgl2Error::new(
+        crate::error::ErrorCategory::Gl,
+        msg,
+    ));
 }
 
 /// Get pointer to last error string (UTF-8)
@@ -84,22 +316,128 @@ where
 
 /// Create a new WebGL2 context with flags. Flags bits:
 /// bit0 = shader debug (enable shader debug stubs).
+/// bit1 = shader coverage (count basic block executions per program).
+/// bit2 = shader profiling (count executed instructions per source line).
+/// bit3 = WebGL1 compatibility (GLSL ES 1.00 shaders, WebGL1 version strings).
+/// bits4-5 = texture validation at draw time (0 = lenient, 1 = spec, 2 = strict).
+/// bit6 = validate generated shader WASM with wasmparser at link time.
+/// bit7 = 24-bit fixed-point default depth buffer instead of 32-bit float.
+/// bit8 = readback with a top-left origin, rows top to bottom.
+/// bit9 = GLSL ES 1.00 compatibility shim for WebGL2 shaders.
+/// bit10 = forward draws to a wgpu device (`gpu-backend` feature only).
 pub fn create_context_with_flags(flags: u32, width: u32, height: u32) -> u32 {
+    create_context_with_attributes(flags, ContextAttributes::default(), width, height)
+}
+
+/// Create a new context with flags (see [`create_context_with_flags`]) and
+/// the attributes of its default framebuffer.
+pub fn create_context_with_attributes(
+    flags: u32,
+    attributes: ContextAttributes,
+    width: u32,
+    height: u32,
+) -> u32 {
     clear_last_error();
     let mut reg = get_registry().borrow_mut();
     let mut ctx = Context::new(width, height);
+    // The rasterizer does not multisample
+    ctx.attributes = ContextAttributes {
+        antialias: false,
+        ..attributes
+    };
 
     // Determine debug mode from flags: only shader debug is relevant here
     let shader = (flags & 0x1) != 0;
 
     ctx.debug_shaders = shader;
+    ctx.coverage_shaders = (flags & 0x2) != 0;
+    ctx.profile_shaders = (flags & 0x4) != 0;
+    ctx.webgl1 = (flags & 0x8) != 0;
+    ctx.glsl100_compat = (flags & 0x200) != 0;
+    ctx.gpu_backend = (flags & 0x400) != 0;
+    ctx.texture_validation = TextureValidation::from_flags(flags);
+    ctx.validate_shaders = (flags & 0x40) != 0;
+    ctx.read_origin = ReadOrigin::from_flags(flags);
+    if (flags & 0x80) != 0 {
+        ctx.default_depth_precision = crate::wasm_gl_emu::rasterizer::DepthPrecision::Fixed(24);
+    }
 
-    let handle = reg.allocate_context_handle();
-    reg.contexts.insert(handle, ctx);
+    let handle = reg.contexts.insert(ctx);
+    if handle == INVALID_HANDLE {
+        set_last_error("too many live contexts");
+    }
     handle
 }
 
-/// Destroy a context by handle, freeing all its resources.
+/// Lower the implementation limits of a context (see [`Limits`]) before it
+/// is used. A limit of 0 keeps the default; one above the default fails
+/// with `ERR_INVALID_ARGS`, as the emulator cannot provide it.
+pub fn ctx_set_limits(
+    ctx: u32,
+    max_texture_size: u32,
+    max_vertex_attribs: u32,
+    max_varying_vectors: u32,
+    max_uniform_vectors: u32,
+    max_draw_buffers: u32,
+) -> u32 {
+    clear_last_error();
+    let defaults = Limits::default();
+    let mut limits = defaults;
+    for (limit, value, max, name) in [
+        (
+            &mut limits.max_texture_size,
+            max_texture_size,
+            defaults.max_texture_size,
+            "MAX_TEXTURE_SIZE",
+        ),
+        (
+            &mut limits.max_vertex_attribs,
+            max_vertex_attribs,
+            defaults.max_vertex_attribs,
+            "MAX_VERTEX_ATTRIBS",
+        ),
+        (
+            &mut limits.max_varying_vectors,
+            max_varying_vectors,
+            defaults.max_varying_vectors,
+            "MAX_VARYING_VECTORS",
+        ),
+        (
+            &mut limits.max_uniform_vectors,
+            max_uniform_vectors,
+            defaults.max_uniform_vectors,
+            "MAX_UNIFORM_VECTORS",
+        ),
+        (
+            &mut limits.max_draw_buffers,
+            max_draw_buffers,
+            defaults.max_draw_buffers,
+            "MAX_DRAW_BUFFERS",
+        ),
+    ] {
+        if value > max {
+            set_last_error(&format!(
+                "{} of {} exceeds the supported {}",
+                name, value, max
+            ));
+            return ERR_INVALID_ARGS;
+        }
+        if value != 0 {
+            *limit = value;
+        }
+    }
+
+    let mut reg = get_registry().borrow_mut();
+    let Some(ctx_obj) = reg.contexts.get_mut(&ctx) else {
+        set_last_error("invalid context handle");
+        return ERR_INVALID_HANDLE;
+    };
+    ctx_obj.limits = limits;
+    ERR_OK
+}
+
+/// Destroy a context by handle, freeing all its resources, including the
+/// `wasm_ctx_alloc` allocations it still owns.
 /// Returns errno (0 on success).
 pub fn destroy_context(handle: u32) -> u32 {
     clear_last_error();
@@ -108,13 +446,28 @@ pub fn destroy_context(handle: u32) -> u32 {
         return ERR_INVALID_HANDLE;
     }
     let mut reg = get_registry().borrow_mut();
-    if reg.contexts.remove(&handle).is_none() {
+    let Some(slot) = reg.contexts.remove(&handle) else {
         set_last_error("context not found");
         return ERR_INVALID_HANDLE;
+    };
+    for ptr in slot.allocations {
+        if let Some(allocation) = reg.allocations.remove(&ptr) {
+            dealloc_raw(ptr, allocation.size);
+        }
     }
     ERR_OK
 }
 
+/// Number of live contexts.
+pub fn context_count() -> u32 {
+    get_registry().borrow().contexts.len() as u32
+}
+
+/// Number of live `wasm_alloc` and `wasm_ctx_alloc` allocations.
+pub fn allocation_count() -> u32 {
+    get_registry().borrow().allocations.len() as u32
+}
+
 // ============================================================================
 // Memory Allocation
 // ============================================================================
@@ -126,6 +479,47 @@ pub fn wasm_alloc(size: u32) -> u32 {
     if size == 0 {
         return 0; // Valid: allocating 0 bytes is OK but we return 0 for simplicity
     }
+    let ptr_u32 = alloc_raw(size);
+    if ptr_u32 != 0 {
+        let mut reg = get_registry().borrow_mut();
+        reg.allocations.insert(
+            ptr_u32,
+            Allocation {
+                size,
+                owner: INVALID_HANDLE,
+            },
+        );
+    }
+    ptr_u32
+}
+
+/// Allocate memory owned by context `ctx`, as `wasm_alloc` does.
+///
+/// The allocation can be released with `wasm_free` and is released with the
+/// context otherwise, so a call that throws between allocating and freeing its
+/// staging memory does not leak it past `destroy()`.
+/// Returns pointer (0 on failure).
+pub fn wasm_ctx_alloc(ctx: u32, size: u32) -> u32 {
+    clear_last_error();
+    if size == 0 {
+        return 0;
+    }
+    let mut reg = get_registry().borrow_mut();
+    let reg = &mut *reg;
+    let Some(slot) = reg.contexts.slot_mut(ctx) else {
+        set_last_error("invalid context handle");
+        return 0;
+    };
+    let ptr_u32 = alloc_raw(size);
+    if ptr_u32 != 0 {
+        slot.allocations.insert(ptr_u32);
+        reg.allocations
+            .insert(ptr_u32, Allocation { size, owner: ctx });
+    }
+    ptr_u32
+}
+
+fn alloc_raw(size: u32) -> u32 {
     let layout = match Layout::from_size_align(size as usize, 8) {
         Ok(l) => l,
         Err(_) => {
@@ -138,13 +532,7 @@ pub fn wasm_alloc(size: u32) -> u32 {
         set_last_error("out of memory");
         return 0;
     }
-    let ptr_u32 = ptr as u32;
-    // Record allocation size so wasm_free can deallocate later.
-    {
-        let mut reg = get_registry().borrow_mut();
-        reg.allocations.insert(ptr_u32, size);
-    }
-    ptr_u32
+    ptr as u32
 }
 
 /// Free memory allocated by wasm_alloc.
@@ -157,14 +545,20 @@ pub fn wasm_free(ptr: u32) -> u32 {
     }
     // Look up allocation size
     let mut reg = get_registry().borrow_mut();
-    let size = match reg.allocations.remove(&ptr) {
-        Some(s) => s,
+    let allocation = match reg.allocations.remove(&ptr) {
+        Some(a) => a,
         None => {
             set_last_error("invalid or unknown allocation");
             return ERR_INVALID_ARGS;
         }
     };
+    if let Some(slot) = reg.contexts.slot_mut(allocation.owner) {
+        slot.allocations.remove(&ptr);
+    }
+    dealloc_raw(ptr, allocation.size)
+}
 
+fn dealloc_raw(ptr: u32, size: u32) -> u32 {
     let layout = match Layout::from_size_align(size as usize, 8) {
         Ok(l) => l,
         Err(_) => {
@@ -175,3 +569,52 @@ pub fn wasm_free(ptr: u32) -> u32 {
     unsafe { dealloc(ptr as *mut u8, layout) };
     ERR_OK
 }
+
+/// Allocate transient memory from the per-frame arena.
+///
+/// Unlike `wasm_alloc`, no bookkeeping is recorded: the memory must not be passed to
+/// `wasm_free` and is reclaimed in bulk by `wasm_frame_reset`. The host resets the
+/// arena at the end of every call that stages data through it and after every draw,
+/// so a pointer is only valid until the export it was allocated for returns.
+/// Returns pointer (0 on failure).
+pub fn wasm_frame_alloc(size: u32) -> u32 {
+    clear_last_error();
+    if size == 0 {
+        return 0;
+    }
+    let mut reg = get_registry().borrow_mut();
+    reg.frame_arena.alloc(size as usize)
+}
+
+/// Release every allocation made by `wasm_frame_alloc` since the previous reset.
+/// Returns errno (0 on success).
+pub fn wasm_frame_reset() -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    reg.frame_arena.reset();
+    ERR_OK
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slots_retire_instead_of_reusing_handles() {
+        let mut table = ContextTable::default();
+        let mut handles = HashSet::new();
+        for _ in 0..=MAX_CONTEXT_GENERATION {
+            let handle = table.insert(Context::new(1, 1));
+            assert_eq!(handle & CONTEXT_INDEX_MASK, 1);
+            assert!(handles.insert(handle));
+            table.remove(&handle);
+        }
+
+        // Every generation of the first slot is used up, so it is retired
+        let next = table.insert(Context::new(1, 1));
+        assert_eq!(next & CONTEXT_INDEX_MASK, 2);
+        assert!(handles.iter().all(|handle| table.get(handle).is_none()));
+        assert!(table.get(&next).is_some());
+        assert_eq!(table.len(), 1);
+    }
+}
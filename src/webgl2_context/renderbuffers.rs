@@ -13,7 +13,7 @@ pub fn ctx_is_renderbuffer(ctx: u32, handle: u32) -> bool {
     }
     let reg = get_registry().borrow();
     if let Some(c) = reg.contexts.get(&ctx) {
-        c.renderbuffers.contains_key(&handle)
+        c.renderbuffers.get(&handle).is_some_and(|rb| !rb.deleted)
     } else {
         false
     }
@@ -38,6 +38,7 @@ pub fn ctx_create_renderbuffer(ctx: u32) -> u32 {
             height: 0,
             internal_format: GL_RGBA4, // Default
             gpu_handle: GpuHandle::invalid(),
+            deleted: false,
         },
     );
     rb_id
@@ -60,7 +61,12 @@ pub fn ctx_bind_renderbuffer(ctx: u32, target: u32, renderbuffer: u32) -> u32 {
         }
     };
 
-    if renderbuffer != 0 && !ctx_obj.renderbuffers.contains_key(&renderbuffer) {
+    if renderbuffer != 0
+        && ctx_obj
+            .renderbuffers
+            .get(&renderbuffer)
+            .is_none_or(|rb| rb.deleted)
+    {
         set_last_error("renderbuffer not found");
         return ERR_INVALID_HANDLE;
     }
@@ -89,11 +95,8 @@ pub fn ctx_delete_renderbuffer(ctx: u32, renderbuffer: u32) -> u32 {
         return ERR_OK;
     }
 
-    if let Some(rb) = ctx_obj.renderbuffers.remove(&renderbuffer) {
-        ctx_obj.kernel.destroy_buffer(rb.gpu_handle);
-        if ctx_obj.bound_renderbuffer == Some(renderbuffer) {
-            ctx_obj.bound_renderbuffer = None;
-        }
+    if ctx_obj.renderbuffers.contains_key(&renderbuffer) {
+        super::deletion::delete_renderbuffer(ctx_obj, renderbuffer);
         ERR_OK
     } else {
         set_last_error("renderbuffer not found");
@@ -127,6 +130,9 @@ pub fn ctx_renderbuffer_storage(
             return ERR_INVALID_HANDLE;
         }
     };
+    if let Err(code) = ctx_obj.check_image_size(width as u32, height as u32) {
+        return code;
+    }
 
     let rb_handle = match ctx_obj.bound_renderbuffer {
         Some(h) => h,
@@ -144,6 +150,12 @@ pub fn ctx_renderbuffer_storage(
         }
     };
 
+    // WebGL 1's unsized depth/stencil format is DEPTH24_STENCIL8
+    let internal_format = if internal_format == GL_DEPTH_STENCIL {
+        GL_DEPTH24_STENCIL8
+    } else {
+        internal_format
+    };
     rb.width = width as u32;
     rb.height = height as u32;
     rb.internal_format = internal_format;
@@ -153,14 +165,19 @@ pub fn ctx_renderbuffer_storage(
         ctx_obj.kernel.destroy_buffer(rb.gpu_handle);
     }
 
-    // Create new buffer in kernel
-    rb.gpu_handle = ctx_obj.kernel.create_buffer(
-        rb.width,
-        rb.height,
-        1,
-        gl_to_wgt_format(internal_format),
-        crate::wasm_gl_emu::device::StorageLayout::Tiled8x8,
-    );
+    // Create new buffer in kernel; depth and stencil formats get the planes
+    // draws test against rather than packed texels
+    let format = gl_to_wgt_format(internal_format);
+    let layout = crate::wasm_gl_emu::device::StorageLayout::Tiled8x8;
+    rb.gpu_handle = if format.is_depth_stencil_format() {
+        ctx_obj
+            .kernel
+            .create_depth_stencil_buffer(rb.width, rb.height, format, layout)
+    } else {
+        ctx_obj
+            .kernel
+            .create_buffer(rb.width, rb.height, 1, format, layout)
+    };
 
     ERR_OK
 }
@@ -0,0 +1,253 @@
+//! Sampler uniform validation at draw time.
+//!
+//! Before a draw, each sampler uniform of the stage programs is resolved to
+//! the texture unit its value names. What happens to units that cannot be
+//! sampled depends on the context's [`TextureValidation`] level: `Lenient`
+//! skips the checks, `Spec` binds an opaque black texel in place of
+//! incomplete textures (WebGL's `(0, 0, 0, 1)` rule) and rejects samplers of
+//! different types sharing a unit, and `Strict` fails the draw for
+//! incomplete textures as well, naming the sampler and the reason.
+
+use super::registry::set_last_error;
+use super::types::*;
+use crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset;
+use naga::{AddressSpace, ImageClass, ImageDimension, TypeInner};
+
+/// A sampler uniform and the texture unit it is set to.
+struct SamplerUnit {
+    name: String,
+    unit: usize,
+    ty: (ImageDimension, bool, ImageClass),
+}
+
+/// Check the sampler uniforms of the programs drawing each stage.
+///
+/// Returns the texture units to sample as incomplete, or an errno after
+/// recording INVALID_OPERATION when the draw must not happen.
+pub(crate) fn validate_sampler_units(
+    ctx_obj: &mut Context,
+    vs_program: u32,
+    fs_program: u32,
+) -> Result<Vec<usize>, u32> {
+    if ctx_obj.texture_validation == TextureValidation::Lenient {
+        return Ok(Vec::new());
+    }
+
+    let samplers = sampler_units(ctx_obj, vs_program, fs_program);
+    if let Some(conflict) = unit_type_conflict(&samplers) {
+        return Err(invalid_operation(ctx_obj, &conflict));
+    }
+
+    let mut incomplete = Vec::new();
+    for sampler in &samplers {
+        let tex = ctx_obj
+            .texture_units
+            .get(sampler.unit)
+            .copied()
+            .flatten()
+            .and_then(|handle| ctx_obj.textures.get(&handle));
+        let reason = match tex {
+            Some(tex) => {
+                let (mag_filter, min_filter, ..) = ctx_obj.sampling_parameters(sampler.unit, tex);
+                texture_incompleteness(tex, min_filter, mag_filter)
+            }
+            None => Some("no texture is bound".to_string()),
+        };
+        let Some(reason) = reason else {
+            continue;
+        };
+        if ctx_obj.texture_validation == TextureValidation::Strict {
+            return Err(invalid_operation(
+                ctx_obj,
+                &format!(
+                    "sampler '{}' (texture unit {}): {}",
+                    sampler.name, sampler.unit, reason
+                ),
+            ));
+        }
+        if !incomplete.contains(&sampler.unit) {
+            incomplete.push(sampler.unit);
+        }
+    }
+
+    if !incomplete.is_empty() && ctx_obj.incomplete_texture.is_none() {
+        let handle = ctx_obj.kernel.create_buffer(
+            1,
+            1,
+            1,
+            gl_to_wgt_format(GL_RGBA8),
+            crate::wasm_gl_emu::device::StorageLayout::Tiled8x8,
+        );
+        crate::wasm_gl_emu::transfer::TransferEngine::write_pixels(
+            &mut ctx_obj.kernel,
+            handle,
+            0,
+            0,
+            0,
+            1,
+            1,
+            1,
+            &[0, 0, 0, 255],
+        );
+        ctx_obj.incomplete_texture = Some(handle);
+    }
+    Ok(incomplete)
+}
+
+/// Describe two samplers of different types set to the same texture unit,
+/// which makes draws with the programs fail, as validateProgram reports.
+pub(crate) fn sampler_type_conflict(
+    ctx_obj: &Context,
+    vs_program: u32,
+    fs_program: u32,
+) -> Option<String> {
+    unit_type_conflict(&sampler_units(ctx_obj, vs_program, fs_program))
+}
+
+fn unit_type_conflict(samplers: &[SamplerUnit]) -> Option<String> {
+    samplers.iter().enumerate().find_map(|(i, a)| {
+        let b = samplers[..i]
+            .iter()
+            .find(|b| b.unit == a.unit && b.ty != a.ty)?;
+        Some(format!(
+            "samplers '{}' and '{}' of different types use texture unit {}",
+            b.name, a.name, a.unit
+        ))
+    })
+}
+
+/// Why `tex` cannot be sampled with these filters, or `None` if it is
+/// complete.
+pub(crate) fn texture_incompleteness(
+    tex: &Texture,
+    min_filter: u32,
+    mag_filter: u32,
+) -> Option<String> {
+    let Some(base) = tex.levels.get(&0) else {
+        return Some("level 0 is not defined".to_string());
+    };
+    if base.width == 0 || base.height == 0 {
+        return Some("level 0 is empty".to_string());
+    }
+
+    let nearest =
+        mag_filter == GL_NEAREST && matches!(min_filter, GL_NEAREST | GL_NEAREST_MIPMAP_NEAREST);
+    if is_integer_format(base.internal_format) && !nearest {
+        return Some("integer textures must use NEAREST filtering".to_string());
+    }
+    if matches!(base.internal_format, GL_R32F | GL_RG32F | GL_RGBA32F) && !nearest {
+        return Some(
+            "32-bit float textures are not filterable without OES_texture_float_linear".to_string(),
+        );
+    }
+
+    let mipmapped = matches!(
+        min_filter,
+        GL_NEAREST_MIPMAP_NEAREST
+            | GL_LINEAR_MIPMAP_NEAREST
+            | GL_NEAREST_MIPMAP_LINEAR
+            | GL_LINEAR_MIPMAP_LINEAR
+    );
+    if mipmapped {
+        let levels = 32 - base.width.max(base.height).leading_zeros() as usize;
+        for i in 1..levels {
+            let expected = ((base.width >> i).max(1), (base.height >> i).max(1));
+            match tex.levels.get(&i) {
+                None => {
+                    return Some(format!(
+                        "the minification filter uses mipmaps but level {} is not defined",
+                        i
+                    ))
+                }
+                Some(level)
+                    if (level.width, level.height) != expected
+                        || level.internal_format != base.internal_format =>
+                {
+                    return Some(format!(
+                        "level {} does not match the size and format of level 0",
+                        i
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    None
+}
+
+fn is_integer_format(internal_format: u32) -> bool {
+    matches!(
+        internal_format,
+        GL_R8UI
+            | GL_RG8UI
+            | GL_RGB8UI
+            | GL_RGBA8UI
+            | GL_R8I
+            | GL_RG8I
+            | GL_RGB8I
+            | GL_RGBA8I
+            | GL_R16UI
+            | GL_RG16UI
+            | GL_RGB16UI
+            | GL_RGBA16UI
+            | GL_R16I
+            | GL_RG16I
+            | GL_RGB16I
+            | GL_RGBA16I
+            | GL_R32UI
+            | GL_RG32UI
+            | GL_RGB32UI
+            | GL_RGBA32UI
+            | GL_R32I
+            | GL_RG32I
+            | GL_RGB32I
+            | GL_RGBA32I
+    )
+}
+
+/// The sampler uniforms of both stages with the units their values name.
+fn sampler_units(ctx_obj: &Context, vs_program: u32, fs_program: u32) -> Vec<SamplerUnit> {
+    let mut samplers = Vec::new();
+    for (program, vertex) in [(vs_program, true), (fs_program, false)] {
+        let Some(p) = ctx_obj.programs.get(&program) else {
+            continue;
+        };
+        let module = if vertex { &p.vs_module } else { &p.fs_module };
+        let Some(module) = module else {
+            continue;
+        };
+        for (_, var) in module.global_variables.iter() {
+            let TypeInner::Image {
+                dim,
+                arrayed,
+                class,
+            } = module.types[var.ty].inner
+            else {
+                continue;
+            };
+            let (Some(name), AddressSpace::Handle) = (&var.name, var.space) else {
+                continue;
+            };
+            let Some(&location) = p.uniforms.get(name) else {
+                continue;
+            };
+            let offset = get_webgl_uniform_data_offset(location as u32) as usize;
+            let unit = ctx_obj
+                .uniform_data
+                .get(offset..offset + 4)
+                .map_or(0, |bytes| i32::from_le_bytes(bytes.try_into().unwrap()));
+            samplers.push(SamplerUnit {
+                name: name.clone(),
+                unit: unit.max(0) as usize,
+                ty: (dim, arrayed, class),
+            });
+        }
+    }
+    samplers
+}
+
+fn invalid_operation(ctx_obj: &mut Context, message: &str) -> u32 {
+    set_last_error(message);
+    ctx_obj.set_error(GL_INVALID_OPERATION);
+    ERR_GL
+}
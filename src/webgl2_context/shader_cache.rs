@@ -0,0 +1,117 @@
+//! Persistent cache of compiled shader WASM.
+//!
+//! Linking compiles each stage with the WASM backend, which dominates load
+//! times of shader-heavy apps. The host can keep the output across sessions
+//! through two imports, `shader_cache_get` and `shader_cache_put` (see
+//! [`crate::js_shader_cache_get`]), backed by IndexedDB, the filesystem or
+//! nothing at all. Entries are keyed by a hash of everything the output
+//! depends on: the crate version, the stage, its source and the program's
+//! interface layout.
+//!
+//! Only modules without addresses baked in are cached: debug stubs, coverage
+//! and profiling counters and constant tables all live in this session's
+//! linear memory, so stages compiled with them always go to the backend.
+//! Entries whose [shader ABI version](crate::naga_wasm_backend::abi) differs
+//! from this build's are treated as misses.
+
+use crate::naga_wasm_backend::{
+    abi, output_layout, validate_wasm, BackendError, CompileConfig, MemoryLayout, WasmBackend,
+    WasmBackendConfig, WasmModule,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Compile a program stage, reusing the host's cached WASM when possible.
+pub(crate) fn compile_stage(
+    backend_config: &WasmBackendConfig,
+    config: CompileConfig,
+    name: &str,
+) -> Result<WasmModule, BackendError> {
+    let cacheable =
+        !backend_config.debug_shaders && !backend_config.coverage && !backend_config.profile;
+    let key = cacheable.then(|| cache_key(&config));
+
+    let cached = key.as_deref().and_then(crate::js_shader_cache_get);
+    // Entries written by a build with another ABI are recompiled and replaced
+    let cached = cached.filter(|bytes| match abi::check_abi(bytes) {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!("discarding cached shader {}: {}", name, e);
+            false
+        }
+    });
+    if let Some(wasm_bytes) = cached {
+        if backend_config.validate {
+            validate_wasm(&wasm_bytes, &backend_config.features)?;
+        }
+        let (varyings, shading_rate) = if config.stage == naga::ShaderStage::Fragment {
+            (
+                output_layout::fragment_varyings(
+                    config.module,
+                    config.entry_point,
+                    config.varying_locations,
+                    config.varying_components,
+                ),
+                output_layout::fragment_shading_rate(config.module, config.entry_point),
+            )
+        } else {
+            (Vec::new(), output_layout::ShadingRate::PerPixel)
+        };
+        return Ok(WasmModule {
+            wasm_bytes,
+            dwarf_bytes: None,
+            debug_stub: None,
+            entry_points: HashMap::new(),
+            memory_layout: MemoryLayout::default(),
+            table_index: 0,
+            coverage: None,
+            constant_data: None,
+            varyings,
+            shading_rate,
+        });
+    }
+
+    let wasm = WasmBackend::new(backend_config.clone()).compile(config, Some(name))?;
+    // Constant tables are only known once compiled; their address is baked in
+    if let Some(key) = key.filter(|_| wasm.constant_data.is_none()) {
+        crate::js_shader_cache_put(&key, &wasm.wasm_bytes);
+    }
+    Ok(wasm)
+}
+
+/// Cache key of a stage: FNV-1a of its inputs, as 16 hex digits.
+fn cache_key(config: &CompileConfig) -> String {
+    fn sorted<V: Copy>(map: &HashMap<String, V>) -> BTreeMap<&str, V> {
+        map.iter().map(|(k, v)| (k.as_str(), *v)).collect()
+    }
+
+    let layout = format!(
+        "{:?}{:?}{:?}{:?}{:?}{:?}{:?}",
+        sorted(config.attribute_locations),
+        sorted(config.uniform_locations),
+        sorted(config.varying_locations),
+        sorted(config.varying_components),
+        sorted(config.varying_types),
+        sorted(config.uniform_types),
+        sorted(config.attribute_types),
+    );
+    let parts = [
+        env!("CARGO_PKG_VERSION"),
+        match config.stage {
+            naga::ShaderStage::Vertex => "vertex",
+            naga::ShaderStage::Fragment => "fragment",
+            _ => "compute",
+        },
+        config.entry_point.unwrap_or(""),
+        config.source,
+        &layout,
+    ];
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for &byte in part.as_bytes().iter().chain(&[0]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    format!("{:016x}", hash)
+}
@@ -1,12 +1,20 @@
+use super::diagnostics::{format_error, format_parse_errors, format_validation_error, shift_lines};
+use super::glsl100;
+use super::noise;
+use super::pipelines::FRAGMENT_PROGRAM_UNIFORM_BASE;
 use super::registry::{clear_last_error, get_registry, set_last_error};
+use super::shader_cache;
 use super::types::ActiveInfo;
 use super::types::*;
-use crate::naga_wasm_backend::{WasmBackend, WasmBackendConfig};
+use crate::error::Webgl2Error;
+use crate::naga_wasm_backend::interface_layout::{self, InterfaceLayout, LocationBindings};
+use crate::naga_wasm_backend::output_layout::MAX_UNIFORM_LOCATIONS;
+use crate::naga_wasm_backend::WasmBackendConfig;
 use naga::front::glsl::{Frontend, Options};
 use naga::valid::{Capabilities, ValidationFlags, Validator};
-use naga::{AddressSpace, Binding, ShaderStage};
+use naga::Binding;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 // ============================================================================
@@ -21,7 +29,7 @@ pub fn ctx_is_shader(ctx: u32, handle: u32) -> bool {
     }
     let reg = get_registry().borrow();
     if let Some(c) = reg.contexts.get(&ctx) {
-        c.shaders.contains_key(&handle)
+        c.shaders.get(&handle).is_some_and(|s| !s.deleted)
     } else {
         false
     }
@@ -35,7 +43,7 @@ pub fn ctx_is_program(ctx: u32, handle: u32) -> bool {
     }
     let reg = get_registry().borrow();
     if let Some(c) = reg.contexts.get(&ctx) {
-        c.programs.contains_key(&handle)
+        c.programs.get(&handle).is_some_and(|p| !p.deleted)
     } else {
         false
     }
@@ -58,10 +66,12 @@ pub fn ctx_create_shader(ctx: u32, type_: u32) -> u32 {
         Shader {
             type_,
             source: String::new(),
+            translated_source: None,
             compiled: false,
             info_log: String::new(),
             module: None,
             info: None,
+            deleted: false,
         },
     );
     shader_id
@@ -78,7 +88,8 @@ pub fn ctx_delete_shader(ctx: u32, shader: u32) -> u32 {
             return ERR_INVALID_HANDLE;
         }
     };
-    ctx_obj.shaders.remove(&shader);
+    // Storage stays until the programs it is attached to let go of it
+    super::deletion::delete_shader(ctx_obj, shader);
     ERR_OK
 }
 
@@ -94,8 +105,10 @@ pub fn ctx_shader_source(ctx: u32, shader: u32, source_ptr: u32, source_len: u32
         }
     };
 
-    let source_slice =
-        unsafe { std::slice::from_raw_parts(source_ptr as *const u8, source_len as usize) };
+    let source_slice = match crate::guest_mem::read(source_ptr, source_len) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
     let source = String::from_utf8_lossy(source_slice).into_owned();
 
     if let Some(s) = ctx_obj.shaders.get_mut(&shader) {
@@ -119,53 +132,147 @@ pub fn ctx_compile_shader(ctx: u32, shader: u32) -> u32 {
         }
     };
 
+    let (webgl1, glsl100_compat) = (ctx_obj.webgl1, ctx_obj.glsl100_compat);
     if let Some(s) = ctx_obj.shaders.get_mut(&shader) {
-        let stage = match s.type_ {
-            GL_VERTEX_SHADER => naga::ShaderStage::Vertex,
-            GL_FRAGMENT_SHADER => naga::ShaderStage::Fragment,
-            _ => {
-                s.compiled = false;
-                s.info_log = "Invalid shader type".to_string();
-                return ERR_INVALID_ARGS;
-            }
-        };
+        if s.type_ != GL_VERTEX_SHADER && s.type_ != GL_FRAGMENT_SHADER {
+            s.compiled = false;
+            s.info_log = "Invalid shader type".to_string();
+            return ERR_INVALID_ARGS;
+        }
 
-        let mut frontend = Frontend::default();
-        let options = Options::from(stage);
-
-        match frontend.parse(&options, &s.source) {
-            Ok(module) => {
-                let mut validator = Validator::new(
-                    ValidationFlags::all() & !ValidationFlags::BINDINGS,
-                    Capabilities::all(),
-                );
-                match validator.validate(&module) {
-                    Ok(info) => {
-                        s.compiled = true;
-                        s.info_log = "Shader compiled successfully".to_string();
-                        s.module = Some(Arc::new(module));
-                        s.info = Some(Arc::new(info));
-                        ERR_OK
-                    }
-                    Err(e) => {
-                        s.compiled = false;
-                        s.info_log = format!("Validation error: {:?}", e);
-                        ERR_OK
-                    }
-                }
+        let glsl100 = webgl1 || (glsl100_compat && glsl100::is_glsl100(&s.source));
+        match compile_context_glsl(glsl100, s.type_, &s.source) {
+            Ok((module, info, translated_source)) => {
+                s.translated_source = translated_source;
+                s.compiled = true;
+                s.info_log = compiled_log(glsl100 && !webgl1);
+                s.module = Some(Arc::new(module));
+                s.info = Some(Arc::new(info));
             }
-            Err(e) => {
+            Err(log) => {
                 s.compiled = false;
-                s.info_log = format!("Compilation error: {:?}", e);
-                ERR_OK
+                s.info_log = log;
             }
         }
+        ERR_OK
     } else {
         set_last_error("shader not found");
         ERR_INVALID_HANDLE
     }
 }
 
+/// GLSL ES 3.00 only has `noperspective` through this extension.
+const NOPERSPECTIVE_EXTENSION: &str = "GL_NV_shader_noperspective_interpolation";
+
+/// Whether any entry point input or output is declared `noperspective`.
+fn uses_noperspective(module: &naga::Module) -> bool {
+    let is_linear = |binding: &Option<Binding>| {
+        matches!(
+            binding,
+            Some(Binding::Location {
+                interpolation: Some(naga::Interpolation::Linear),
+                ..
+            })
+        )
+    };
+    module.entry_points.iter().any(|ep| {
+        ep.function
+            .arguments
+            .iter()
+            .any(|arg| is_linear(&arg.binding))
+            || ep.function.result.as_ref().is_some_and(|result| {
+                is_linear(&result.binding)
+                    || match &module.types[result.ty].inner {
+                        naga::TypeInner::Struct { members, .. } => {
+                            members.iter().any(|member| is_linear(&member.binding))
+                        }
+                        _ => false,
+                    }
+            })
+    })
+}
+
+/// Info log of a successful compile. `shimmed` shaders were GLSL ES 1.00
+/// compiled by a WebGL2 context through the compatibility shim.
+fn compiled_log(shimmed: bool) -> String {
+    let mut log = "Shader compiled successfully".to_string();
+    if shimmed {
+        log.push_str(
+            "\nWARNING: GLSL ES 1.00 shader translated to GLSL ES 3.00; declare #version 300 es to compile it as written",
+        );
+    }
+    log
+}
+
+/// Compile shader source in a context. GLSL ES 1.00 source (any shader of
+/// a WebGL1 context) is translated to 3.00 first, and the noise extension
+/// directive is expanded; either also returns the source that was compiled.
+#[allow(clippy::type_complexity)]
+fn compile_context_glsl(
+    glsl100: bool,
+    type_: u32,
+    source: &str,
+) -> Result<(naga::Module, naga::valid::ModuleInfo, Option<String>), String> {
+    if !glsl100 {
+        let expanded = noise::expand(source);
+        let (module, info) = compile_glsl(type_, expanded.as_deref().unwrap_or(source))?;
+        return Ok((module, info, expanded));
+    }
+    let stage = if type_ == GL_VERTEX_SHADER {
+        naga::ShaderStage::Vertex
+    } else {
+        naga::ShaderStage::Fragment
+    };
+    let translated = glsl100::translate(source, stage)?;
+    let source = noise::expand(&translated.source).unwrap_or(translated.source);
+    let (module, info) =
+        compile_glsl(type_, &source).map_err(|log| shift_lines(&log, translated.added_lines))?;
+    Ok((module, info, Some(source)))
+}
+
+/// Parse and validate GLSL source for a `GL_VERTEX_SHADER` or
+/// `GL_FRAGMENT_SHADER`, returning the info log on failure.
+pub(crate) fn compile_glsl(
+    type_: u32,
+    source: &str,
+) -> Result<(naga::Module, naga::valid::ModuleInfo), String> {
+    let stage = if type_ == GL_VERTEX_SHADER {
+        naga::ShaderStage::Vertex
+    } else {
+        naga::ShaderStage::Fragment
+    };
+
+    let mut frontend = Frontend::default();
+    let options = Options::from(stage);
+    let module = frontend
+        .parse(&options, source)
+        .map_err(|e| format_parse_errors(&e, source))?;
+
+    if uses_noperspective(&module)
+        && !frontend
+            .metadata()
+            .extensions
+            .contains(NOPERSPECTIVE_EXTENSION)
+    {
+        return Err(format_error(
+            "noperspective",
+            &format!(
+                "requires extension {} to be enabled",
+                NOPERSPECTIVE_EXTENSION
+            ),
+        ));
+    }
+
+    let mut validator = Validator::new(
+        ValidationFlags::all() & !ValidationFlags::BINDINGS,
+        Capabilities::all(),
+    );
+    let info = validator
+        .validate(&module)
+        .map_err(|e| format_validation_error(&e, source))?;
+    Ok((module, info))
+}
+
 /// Get shader parameter.
 pub fn ctx_get_shader_parameter(ctx: u32, shader: u32, pname: u32) -> i32 {
     clear_last_error();
@@ -185,7 +292,7 @@ pub fn ctx_get_shader_parameter(ctx: u32, shader: u32, pname: u32) -> i32 {
                     0
                 }
             }
-            GL_DELETE_STATUS => 0, // Not implemented
+            GL_DELETE_STATUS => s.deleted as i32,
             _ => 0,
         }
     } else {
@@ -253,10 +360,8 @@ pub fn ctx_delete_program(ctx: u32, program: u32) -> u32 {
             return ERR_INVALID_HANDLE;
         }
     };
-    ctx_obj.programs.remove(&program);
-    if ctx_obj.current_program == Some(program) {
-        ctx_obj.current_program = None;
-    }
+    // A program in use stays until it is replaced
+    super::deletion::delete_program(ctx_obj, program);
     ERR_OK
 }
 
@@ -272,7 +377,7 @@ pub fn ctx_attach_shader(ctx: u32, program: u32, shader: u32) -> u32 {
         }
     };
 
-    if !ctx_obj.shaders.contains_key(&shader) {
+    if ctx_obj.shaders.get(&shader).is_none_or(|s| s.deleted) {
         set_last_error("shader not found");
         return ERR_INVALID_HANDLE;
     }
@@ -288,6 +393,62 @@ pub fn ctx_attach_shader(ctx: u32, program: u32, shader: u32) -> u32 {
     }
 }
 
+/// Detach a shader from a program, freeing it if it was flagged for deletion.
+pub fn ctx_detach_shader(ctx: u32, program: u32, shader: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+
+    let Some(p) = ctx_obj.programs.get_mut(&program) else {
+        set_last_error("program not found");
+        return ERR_INVALID_HANDLE;
+    };
+    let Some(pos) = p.attached_shaders.iter().position(|&s| s == shader) else {
+        set_last_error("shader is not attached to the program");
+        ctx_obj.set_error(GL_INVALID_OPERATION);
+        return ERR_GL;
+    };
+    p.attached_shaders.remove(pos);
+    super::deletion::release_deleted_objects(ctx_obj);
+    ERR_OK
+}
+
+/// Validate a program against the current state, updating its
+/// VALIDATE_STATUS and replacing its info log with the outcome.
+pub fn ctx_validate_program(ctx: u32, program: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+
+    let Some(p) = ctx_obj.programs.get(&program).filter(|p| !p.deleted) else {
+        set_last_error("program not found");
+        ctx_obj.set_error(GL_INVALID_VALUE);
+        return ERR_GL;
+    };
+    let problem = if !p.linked {
+        Some("Program is not linked".to_string())
+    } else {
+        super::sampling::sampler_type_conflict(ctx_obj, program, program)
+    };
+
+    let p = ctx_obj.programs.get_mut(&program).unwrap();
+    p.validate_status = problem.is_none();
+    p.info_log = problem.unwrap_or_default();
+    ERR_OK
+}
+
 /// Link a program.
 pub fn ctx_link_program(ctx: u32, program: u32) -> u32 {
     clear_last_error();
@@ -319,19 +480,24 @@ pub fn ctx_link_program(ctx: u32, program: u32) -> u32 {
                     GL_VERTEX_SHADER => {
                         vs_module = s.module.clone();
                         vs_info = s.info.clone();
-                        vs_source = s.source.clone();
+                        vs_source = s.translated_source.as_ref().unwrap_or(&s.source).clone();
                     }
                     GL_FRAGMENT_SHADER => {
                         fs_module = s.module.clone();
                         fs_info = s.info.clone();
-                        fs_source = s.source.clone();
+                        fs_source = s.translated_source.as_ref().unwrap_or(&s.source).clone();
                     }
                     _ => {}
                 }
             }
         }
 
-        if vs_module.is_none() || fs_module.is_none() {
+        if p.separable && vs_module.is_none() && fs_module.is_none() {
+            p.linked = false;
+            p.info_log = "Separable program must have a vertex or fragment shader".to_string();
+            return ERR_OK;
+        }
+        if !p.separable && (vs_module.is_none() || fs_module.is_none()) {
             p.linked = false;
             p.info_log = "Program must have both vertex and fragment shaders".to_string();
             return ERR_OK;
@@ -342,438 +508,97 @@ pub fn ctx_link_program(ctx: u32, program: u32) -> u32 {
         p.vs_info = vs_info;
         p.fs_info = fs_info;
 
+        // Uniform locations may move on relink; carry the current values over by name
+        let uniform_values = p
+            .linked
+            .then(|| snapshot_uniform_values(p, &ctx_obj.uniform_data));
+
         // Extract attributes and uniforms from Naga modules to ensure consistent locations
         p.attributes.clear();
         p.uniforms.clear();
         p.uniform_blocks.clear();
         p.uniform_block_bindings.clear();
-        let mut attribute_locations = HashMap::new();
-        let mut uniform_locations = HashMap::new();
-        let mut next_uniform_loc = 0;
-        let mut varying_locations = HashMap::new();
-        let mut next_varying_loc = 0; // gl_Position is handled separately at offset 0
-                                      // Temporary maps to collect type information from VS and FS so we can validate compatibility
-        let mut vs_varying_types: HashMap<String, (u8, u32)> = HashMap::new();
-        let mut fs_varying_types: HashMap<String, (u8, u32)> = HashMap::new();
-
-        // Helper to map naga::Type to (type_code, components)
-        let get_type_info = |ty: &naga::Type| -> (u8, u32) {
-            match ty.inner {
-                naga::TypeInner::Scalar(s) => match s.kind {
-                    naga::ScalarKind::Sint => (1, 1),
-                    naga::ScalarKind::Uint => (2, 1),
-                    _ => (0, 1),
-                },
-                naga::TypeInner::Vector { size, scalar, .. } => match scalar.kind {
-                    naga::ScalarKind::Sint => (1, size as u32),
-                    naga::ScalarKind::Uint => (2, size as u32),
-                    _ => (0, size as u32),
-                },
-                naga::TypeInner::Matrix { columns, rows, .. } => {
-                    (0, (columns as u32) * (rows as u32))
-                }
-                _ => (0, 1),
+        p.varying_locations.clear();
+        p.varying_components.clear();
+        p.varying_types.clear();
+        let layout = match interface_layout::compute_layout(
+            p.vs_module.as_deref().map(|vs| (vs, vs_source.as_str())),
+            p.fs_module.as_deref(),
+            &p.bindings,
+            &ctx_obj.limits.link_limits(),
+            FRAGMENT_PROGRAM_UNIFORM_BASE as u32,
+        ) {
+            Ok(layout) => layout,
+            Err(log) => {
+                p.linked = false;
+                p.info_log = log;
+                return ERR_OK;
             }
         };
-
-        if let Some(vs) = &p.vs_module {
-            // 1. Collect active attributes and their requested locations
-            let mut active_attributes = Vec::new();
-            for ep in &vs.entry_points {
-                if ep.stage == ShaderStage::Vertex {
-                    for arg in &ep.function.arguments {
-                        if let Some(name) = &arg.name {
-                            active_attributes.push(name.clone());
-                        }
-                    }
-                }
-            }
-
-            // 2. Assign locations
-            // We keep track of the origin of a location so we can resolve collisions
-            // Priority: Layout (shader) > bindAttribLocation (user) > Naga default (assigned)
-            // used_locations: location -> (name, origin)
-            // origin: 0 = assigned (default), 1 = layout, 2 = bindAttribLocation
-            let mut used_locations: HashMap<u32, (String, u8)> = HashMap::new();
-            let mut unassigned = Vec::new();
-
-            for name in &active_attributes {
-                let mut location: Option<u32> = None;
-                let mut origin: u8 = 0; // default: assigned
-
-                // Prefer user-specified bindAttribLocation first (so user binding overrides default Naga-assigned locations)
-                if let Some(&loc) = p.attribute_bindings.get(name) {
-                    location = Some(loc);
-                    origin = 2;
-                }
-
-                // If no user binding, check for layout qualifier in shader (explicit location)
-                if location.is_none() {
-                    for ep in &vs.entry_points {
-                        if ep.stage == ShaderStage::Vertex {
-                            for arg in &ep.function.arguments {
-                                if arg.name.as_ref() == Some(name) {
-                                    if let Some(Binding::Location { location: loc, .. }) =
-                                        &arg.binding
-                                    {
-                                        location = Some(*loc);
-                                        origin = 1;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-
-                if let Some(loc) = location {
-                    if let Some((other_name_ref, other_origin)) = used_locations.get(&loc) {
-                        // Clone the data we need so we don't hold an immutable borrow while mutating
-                        let other_name = other_name_ref.clone();
-                        let other_origin = *other_origin;
-                        if other_name != *name {
-                            // If existing mapping was a Naga-assigned default (0) and the new one is user/layout (1 or 2),
-                            // prefer the new mapping and bump the old attribute to unassigned so it can get a new location.
-                            if other_origin == 0 && origin > 0 {
-                                // remove previous assignment and mark it unassigned
-                                attribute_locations.remove(&other_name);
-                                p.attributes.remove(&other_name);
-                                used_locations.insert(loc, (name.clone(), origin));
-                                attribute_locations.insert(name.clone(), loc);
-                                p.attributes.insert(name.clone(), loc as i32);
-                                unassigned.push(other_name);
-                            } else if origin == 0 && other_origin > 0 {
-                                // keep existing user/layout mapping, mark current as unassigned
-                                unassigned.push(name.clone());
-                            } else {
-                                // Both are user-specified (or both layout/bind) -> link error
-                                p.linked = false;
-                                p.info_log = format!(
-                                    "Link failed: Attributes '{}' and '{}' are both bound to location {}",
-                                    name, other_name, loc
-                                );
-                                return ERR_OK;
-                            }
-                        }
-                    } else {
-                        used_locations.insert(loc, (name.clone(), origin));
-                        attribute_locations.insert(name.clone(), loc);
-                        p.attributes.insert(name.clone(), loc as i32);
-                        // Record attribute type info based on VS entry point args
-                        for ep in &vs.entry_points {
-                            if ep.stage == ShaderStage::Vertex {
-                                for arg in &ep.function.arguments {
-                                    if arg.name.as_ref() == Some(name) {
-                                        let ty = &vs.types[arg.ty];
-                                        let type_info = get_type_info(ty);
-                                        p.attribute_types.insert(name.clone(), type_info);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    unassigned.push(name.clone());
-                }
-            }
-
-            // 3. Assign remaining attributes to unused locations
-            let mut next_loc = 0;
-            for name in unassigned {
-                while used_locations.contains_key(&next_loc) {
-                    next_loc += 1;
-                }
-                used_locations.insert(next_loc, (name.clone(), 0));
-                attribute_locations.insert(name.clone(), next_loc);
-                p.attributes.insert(name.clone(), next_loc as i32);
-                // Record attribute type info based on VS entry point args
-                for ep in &vs.entry_points {
-                    if ep.stage == ShaderStage::Vertex {
-                        for arg in &ep.function.arguments {
-                            if arg.name.as_ref() == Some(&name) {
-                                let ty = &vs.types[arg.ty];
-                                let type_info = get_type_info(ty);
-                                p.attribute_types.insert(name.clone(), type_info);
-                            }
-                        }
-                    }
-                }
-                next_loc += 1;
+        let InterfaceLayout {
+            attribute_locations,
+            attribute_types,
+            uniform_locations,
+            uniform_types,
+            uniform_blocks,
+            varying_locations,
+            varying_components,
+            varying_types,
+        } = layout;
+        p.attributes = attribute_locations
+            .iter()
+            .map(|(name, &loc)| (name.clone(), loc as i32))
+            .collect();
+        p.attribute_types = attribute_types;
+        p.uniforms = uniform_locations
+            .iter()
+            .map(|(name, &loc)| (name.clone(), loc as i32))
+            .collect();
+        p.uniform_types = uniform_types;
+        p.uniform_blocks = uniform_blocks
+            .into_iter()
+            .map(|name| UniformBlock {
+                name,
+                ..Default::default()
+            })
+            .collect();
+        p.varying_locations = varying_locations.clone();
+        p.varying_components = varying_components.clone();
+        p.varying_types = varying_types;
+
+        // Transform feedback varyings take effect at link time
+        let tf_linked = match super::transform_feedback::link_transform_feedback_varyings(p) {
+            Ok(varyings) => varyings,
+            Err(log) => {
+                p.linked = false;
+                p.info_log = log;
+                return ERR_OK;
             }
-
-            // 0. Pre-scan VS entry point results for explicit varying locations
-            let mut explicit_candidates = Vec::new();
-            for ep in &vs.entry_points {
-                if ep.stage == ShaderStage::Vertex {
-                    if let Some(result) = &ep.function.result {
-                        if let naga::TypeInner::Struct { members, .. } = &vs.types[result.ty].inner
-                        {
-                            for member in members {
-                                if let Some(Binding::Location { location, .. }) = member.binding {
-                                    if let Some(name) = &member.name {
-                                        explicit_candidates.push((name.clone(), location));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Filter candidates: if a location is used multiple times, discard all usages (treat as implicit)
-            let mut loc_counts = HashMap::new();
-            for (_, loc) in &explicit_candidates {
-                *loc_counts.entry(*loc).or_insert(0) += 1;
-            }
-
-            for (name, loc) in explicit_candidates {
-                if loc_counts[&loc] == 1 {
-                    varying_locations.insert(name, loc);
-                    if loc >= next_varying_loc {
-                        next_varying_loc = loc + 1;
-                    }
-                }
-            }
-
-            // Collect uniforms and varyings from globals _and_ from entry point args/results
-            let mut vs_globals: Vec<_> = vs.global_variables.iter().collect();
-            vs_globals.sort_by_key(|(handle, _)| handle.index());
-
-            // TODO: is handle a stray local in the API? Why is it there?
-            for (_handle, var) in vs_globals {
-                if var.space == AddressSpace::Handle
-                    || (var.space == AddressSpace::Uniform && var.binding.is_none())
-                {
-                    if let Some(name) = &var.name {
-                        if !p.uniforms.contains_key(name) {
-                            let location = next_uniform_loc;
-                            next_uniform_loc += 1;
-                            p.uniforms.insert(name.clone(), location);
-                            uniform_locations.insert(name.clone(), location as u32);
-                            // Record uniform type info
-                            let ty = &vs.types[var.ty];
-                            let type_info = get_type_info(ty);
-                            p.uniform_types.insert(name.clone(), type_info);
-                        }
-                    }
-                } else if var.space == AddressSpace::Uniform {
-                    // Uniform Block (has binding)
-                    if let Some(name) = &var.name {
-                        if !p.uniform_blocks.contains(name) {
-                            p.uniform_blocks.push(name.clone());
-                        }
-                    }
-                } else {
-                    // Treat any non-uniform/handle globals as varyings (covers Private / In / Out)
-                    if let Some(name) = &var.name {
-                        if name != "gl_Position"
-                            && name != "gl_Position_1"
-                            && !p.attributes.contains_key(name)
-                            && !varying_locations.contains_key(name)
-                        {
-                            varying_locations.insert(name.clone(), next_varying_loc);
-                            // record VS type info
-                            let ty = &vs.types[var.ty];
-                            let type_info = get_type_info(ty);
-                            vs_varying_types.insert(name.clone(), type_info);
-                            // also store on the program for runtime mapping
-                            p.varying_locations.insert(name.clone(), next_varying_loc);
-                            p.varying_types.insert(name.clone(), type_info);
-                            next_varying_loc += 1;
-                        }
-                    }
-                }
-            }
-
-            // Also scan entry point arguments/results for varyings (addresses AddressSpace::Input/Output cases)
-            for ep in &vs.entry_points {
-                if ep.stage == ShaderStage::Vertex {
-                    for arg in &ep.function.arguments {
-                        if let Some(name) = &arg.name {
-                            if name != "gl_Position"
-                                && !p.attributes.contains_key(name)
-                                && !p.uniforms.contains_key(name)
-                                && !varying_locations.contains_key(name)
-                            {
-                                // If this arg has an explicit location binding, use it when possible
-                                if let Some(Binding::Location { location: loc, .. }) = &arg.binding
-                                {
-                                    // If that location is already used by another varying, do not overwrite (keep unique)
-                                    if !varying_locations.values().any(|&v| v == *loc) {
-                                        varying_locations.insert(name.clone(), *loc);
-                                        if *loc >= next_varying_loc {
-                                            next_varying_loc = *loc + 1;
-                                        }
-                                        continue;
-                                    }
-                                }
-                                varying_locations.insert(name.clone(), next_varying_loc);
-                                next_varying_loc += 1;
-                            }
-                        }
-                    }
-                }
-            }
-
-            // Collect uniforms and varyings from globals _and_ from entry point args/results
-            let mut vs_globals: Vec<_> = vs.global_variables.iter().collect();
-            vs_globals.sort_by_key(|(handle, _)| handle.index());
-
-            for (_handle, var) in vs_globals {
-                if var.space == AddressSpace::Handle
-                    || (var.space == AddressSpace::Uniform && var.binding.is_none())
-                {
-                    if let Some(name) = &var.name {
-                        if !p.uniforms.contains_key(name) {
-                            let location = next_uniform_loc;
-                            next_uniform_loc += 1;
-                            p.uniforms.insert(name.clone(), location);
-                            uniform_locations.insert(name.clone(), location as u32);
-                            // Record uniform type info
-                            let ty = &vs.types[var.ty];
-                            let type_info = get_type_info(ty);
-                            p.uniform_types.insert(name.clone(), type_info);
-                        }
-                    }
-                } else if var.space == AddressSpace::Uniform {
-                    // Collect uniform blocks
-                    if let Some(name) = &var.name {
-                        if !p.uniform_blocks.contains(name) {
-                            p.uniform_blocks.push(name.clone());
-                        }
-                    }
-                }
-            }
-
-            if let Some(fs) = &p.fs_module {
-                // Also scan FS entry points for input varyings
-                for ep in &fs.entry_points {
-                    if ep.stage == ShaderStage::Fragment {
-                        for arg in &ep.function.arguments {
-                            if let Some(name) = &arg.name {
-                                if name != "color"
-                                    && name != "fragColor"
-                                    && !name.ends_with("Color")
-                                    && !p.uniforms.contains_key(name)
-                                    && !varying_locations.contains_key(name)
-                                {
-                                    if let Some(Binding::Location { location: loc, .. }) =
-                                        &arg.binding
-                                    {
-                                        if !varying_locations.values().any(|&v| v == *loc) {
-                                            varying_locations.insert(name.clone(), *loc);
-                                            // FS arg type info
-                                            let ty = &fs.types[arg.ty];
-                                            let type_info = get_type_info(ty);
-                                            fs_varying_types.insert(name.clone(), type_info);
-                                            p.varying_locations.insert(name.clone(), *loc);
-                                            p.varying_types.insert(name.clone(), type_info);
-                                            if *loc >= next_varying_loc {
-                                                next_varying_loc = *loc + 1;
-                                            }
-                                            continue;
-                                        }
-                                    }
-                                    varying_locations.insert(name.clone(), next_varying_loc);
-                                    let ty = &fs.types[arg.ty];
-                                    let type_info = get_type_info(ty);
-                                    fs_varying_types.insert(name.clone(), type_info);
-                                    p.varying_locations.insert(name.clone(), next_varying_loc);
-                                    p.varying_types.insert(name.clone(), type_info);
-                                    next_varying_loc += 1;
-                                }
-                            }
-                        }
-                    }
-                }
-
-                let mut fs_globals: Vec<_> = fs.global_variables.iter().collect();
-                fs_globals.sort_by_key(|(handle, _)| handle.index());
-
-                for (_handle, var) in fs_globals {
-                    if var.space == AddressSpace::Handle
-                        || (var.space == AddressSpace::Uniform && var.binding.is_none())
-                    {
-                        if let Some(name) = &var.name {
-                            if !p.uniforms.contains_key(name) {
-                                let location = next_uniform_loc;
-                                next_uniform_loc += 1;
-                                p.uniforms.insert(name.clone(), location);
-                                uniform_locations.insert(name.clone(), location as u32);
-                                // Record uniform type info
-                                let ty = &fs.types[var.ty];
-                                let type_info = get_type_info(ty);
-                                p.uniform_types.insert(name.clone(), type_info);
-                            }
-                        }
-                    } else if var.space == AddressSpace::Uniform {
-                        // Uniform Block (has binding)
-                        if let Some(name) = &var.name {
-                            if !p.uniform_blocks.contains(name) {
-                                p.uniform_blocks.push(name.clone());
-                            }
-                        }
-                    } else {
-                        // Treat any non-uniform/handle globals as varyings (covers Private / In / Out)
-                        if let Some(name) = &var.name {
-                            if name != "color"
-                                && name != "gl_FragColor"
-                                && name != "gl_FragColor_1"
-                                && name != "fragColor"
-                                && !name.ends_with("Color")
-                                && !varying_locations.contains_key(name)
-                            {
-                                varying_locations.insert(name.clone(), next_varying_loc);
-                                // record FS type info
-                                let ty = &fs.types[var.ty];
-                                let type_info = get_type_info(ty);
-                                fs_varying_types.insert(name.clone(), type_info);
-                                p.varying_locations.insert(name.clone(), next_varying_loc);
-                                p.varying_types.insert(name.clone(), type_info);
-                                next_varying_loc += 1;
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        // Link-time type compatibility checks: ensure VS and FS agree on varying types
-        for (name, &loc) in varying_locations.iter() {
-            if let (Some(vs_type), Some(fs_type)) =
-                (vs_varying_types.get(name), fs_varying_types.get(name))
-            {
-                if vs_type != fs_type {
-                    p.linked = false;
-                    p.info_log = format!(
-                        "Link failed: Varying '{}' type mismatch at location {}: VS={:?} FS={:?}",
-                        name, loc, vs_type, fs_type
-                    );
-                    return ERR_OK;
-                }
-            }
-            // Ensure program-level maps are populated (in case some were only discovered in FS)
-            if !p.varying_locations.contains_key(name) {
-                p.varying_locations.insert(name.clone(), loc);
-            }
-            if !p.varying_types.contains_key(name) {
-                if let Some(t) = vs_varying_types.get(name) {
-                    p.varying_types.insert(name.clone(), *t);
-                } else if let Some(t) = fs_varying_types.get(name) {
-                    p.varying_types.insert(name.clone(), *t);
-                }
-            }
-        }
+        };
 
         // Compile to WASM
         let config = WasmBackendConfig {
             debug_shaders: ctx_obj.debug_shaders,
+            coverage: ctx_obj.coverage_shaders,
+            profile: ctx_obj.profile_shaders,
+            validate: ctx_obj.validate_shaders,
             ..Default::default()
         };
-        let backend = WasmBackend::new(config);
+
+        // A separable program may link a single stage; drop the other one of a previous link
+        if p.vs_module.is_none() {
+            p.vs_wasm = None;
+        }
+        if p.fs_module.is_none() {
+            p.fs_wasm = None;
+            p.fs_varyings.clear();
+            p.fs_shading_rate = Default::default();
+        }
 
         if let (Some(vs), Some(vsi)) = (&p.vs_module, &p.vs_info) {
             let vs_name = format!("program_{}_vs.glsl", program);
-            match backend.compile(
+            match shader_cache::compile_stage(
+                &config,
                 crate::naga_wasm_backend::CompileConfig {
                     module: vs,
                     info: vsi,
@@ -783,19 +608,22 @@ pub fn ctx_link_program(ctx: u32, program: u32) -> u32 {
                     attribute_locations: &attribute_locations,
                     uniform_locations: &uniform_locations,
                     varying_locations: &varying_locations,
+                    varying_components: &varying_components,
                     varying_types: &p.varying_types,
                     uniform_types: &p.uniform_types,
                     attribute_types: &p.attribute_types,
                 },
-                Some(&vs_name),
+                &vs_name,
             ) {
                 Ok(wasm) => {
                     p.vs_wasm = Some(wasm.wasm_bytes);
                     p.vs_stub = wasm.debug_stub;
+                    p.vs_coverage = wasm.coverage;
+                    p.vs_constants = wasm.constant_data;
                 }
                 Err(e) => {
                     p.linked = false;
-                    p.info_log = format!("VS Backend error: {:?}", e);
+                    p.info_log = format!("VS Backend error: {}", Webgl2Error::from(e));
                     return ERR_OK;
                 }
             }
@@ -803,7 +631,8 @@ pub fn ctx_link_program(ctx: u32, program: u32) -> u32 {
 
         if let (Some(fs), Some(fsi)) = (&p.fs_module, &p.fs_info) {
             let fs_name = format!("program_{}_fs.glsl", program);
-            match backend.compile(
+            match shader_cache::compile_stage(
+                &config,
                 crate::naga_wasm_backend::CompileConfig {
                     module: fs,
                     info: fsi,
@@ -813,19 +642,24 @@ pub fn ctx_link_program(ctx: u32, program: u32) -> u32 {
                     attribute_locations: &attribute_locations,
                     uniform_locations: &uniform_locations,
                     varying_locations: &varying_locations,
+                    varying_components: &varying_components,
                     varying_types: &p.varying_types,
                     uniform_types: &p.uniform_types,
                     attribute_types: &p.attribute_types,
                 },
-                Some(&fs_name),
+                &fs_name,
             ) {
                 Ok(wasm) => {
                     p.fs_wasm = Some(wasm.wasm_bytes);
                     p.fs_stub = wasm.debug_stub;
+                    p.fs_coverage = wasm.coverage;
+                    p.fs_constants = wasm.constant_data;
+                    p.fs_varyings = wasm.varyings;
+                    p.fs_shading_rate = wasm.shading_rate;
                 }
                 Err(e) => {
                     p.linked = false;
-                    p.info_log = format!("FS Backend error: {:?}", e);
+                    p.info_log = format!("FS Backend error: {}", Webgl2Error::from(e));
                     return ERR_OK;
                 }
             }
@@ -833,8 +667,21 @@ pub fn ctx_link_program(ctx: u32, program: u32) -> u32 {
 
         p.linked = true;
         p.info_log = "Program linked successfully.".to_string();
+        if p.vs_module.as_deref().is_some_and(|vs| {
+            !crate::naga_wasm_backend::output_layout::vertex_writes_position(vs, Some("main"))
+        }) {
+            p.info_log.push_str(
+                "\nWARNING: vertex shader does not write gl_Position; it defaults to (0, 0, 0, 1)",
+            );
+        }
 
+        p.tf_linked = tf_linked;
+        p.tf_linked_buffer_mode = p.tf_buffer_mode;
         reflect_program_resources(p);
+        ctx_obj.rasterizer.reserve_scratch(fragment_output_count(p));
+        if let Some(values) = uniform_values {
+            restore_uniform_values(p, &mut ctx_obj.uniform_data, values);
+        }
 
         ERR_OK
     } else {
@@ -843,6 +690,230 @@ pub fn ctx_link_program(ctx: u32, program: u32) -> u32 {
     }
 }
 
+/// Number of color outputs of a program's fragment shader.
+fn fragment_output_count(p: &Program) -> usize {
+    let Some(module) = p.fs_module.as_deref() else {
+        return 0;
+    };
+    let is_location = |binding: &Option<Binding>| matches!(binding, Some(Binding::Location { .. }));
+    module
+        .entry_points
+        .iter()
+        .filter(|ep| ep.stage == naga::ShaderStage::Fragment)
+        .filter_map(|ep| ep.function.result.as_ref())
+        .map(|result| match &module.types[result.ty].inner {
+            naga::TypeInner::Struct { members, .. } => members
+                .iter()
+                .filter(|member| is_location(&member.binding))
+                .count(),
+            _ => usize::from(is_location(&result.binding)),
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Uniform value bytes of a linked program as `(name, type info, bytes)`.
+fn snapshot_uniform_values(p: &Program, uniform_data: &[u8]) -> Vec<(String, (u8, u32), Vec<u8>)> {
+    let mut values = Vec::new();
+    for (name, &location) in &p.uniforms {
+        let Some(&type_info) = p.uniform_types.get(name) else {
+            continue;
+        };
+        let offset =
+            crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset(location as u32)
+                as usize;
+        let size = type_info.1 as usize * 4;
+        if let Some(bytes) = uniform_data.get(offset..offset + size) {
+            values.push((name.clone(), type_info, bytes.to_vec()));
+        }
+    }
+    values
+}
+
+/// Write snapshotted uniform values back at their (possibly new) locations.
+/// Uniforms that are new or changed type start at zero, as after a fresh link.
+fn restore_uniform_values(
+    p: &Program,
+    uniform_data: &mut [u8],
+    values: Vec<(String, (u8, u32), Vec<u8>)>,
+) {
+    let mut slots = HashMap::new();
+    for (name, &location) in &p.uniforms {
+        let Some(&type_info) = p.uniform_types.get(name) else {
+            continue;
+        };
+        let offset =
+            crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset(location as u32)
+                as usize;
+        let size = type_info.1 as usize * 4;
+        if let Some(dst) = uniform_data.get_mut(offset..offset + size) {
+            dst.fill(0);
+            slots.insert(name.as_str(), (type_info, offset));
+        }
+    }
+    for (name, type_info, bytes) in values {
+        if let Some(&(new_type, offset)) = slots.get(name.as_str()) {
+            if new_type == type_info {
+                uniform_data[offset..offset + bytes.len()].copy_from_slice(&bytes);
+            }
+        }
+    }
+}
+
+/// [`ctx_reload_program`] status: the program was relinked, but its
+/// attribute or uniform locations differ from the previous link.
+pub const RELOAD_INTERFACE_CHANGED: u32 = 0x100;
+
+/// `(location, GL type, size)` of a linked program's attributes and uniforms,
+/// keyed by `(is_uniform, name)`.
+type ProgramInterface = BTreeMap<(bool, String), (i32, u32, i32)>;
+
+fn program_interface(p: &Program) -> ProgramInterface {
+    let attributes = p.active_attributes.iter().map(|a| {
+        let location = p.attributes.get(&a.name).copied().unwrap_or(-1);
+        ((false, a.name.clone()), (location, a.type_, a.size))
+    });
+    let uniforms = p.active_uniforms.iter().map(|u| {
+        let location = p.uniforms.get(&u.name).copied().unwrap_or(-1);
+        ((true, u.name.clone()), (location, u.type_, u.size))
+    });
+    attributes.chain(uniforms).collect()
+}
+
+/// Replace the sources of a program's attached shaders and relink it in place.
+///
+/// Both sources are compiled before anything is touched, so a compile error
+/// leaves the running program as it was. A link error restores the previous
+/// shaders and relinks them. Uniform values and attribute bindings survive the
+/// reload. Returns `ERR_OK` when the attribute and uniform interface is
+/// unchanged, [`RELOAD_INTERFACE_CHANGED`] when cached locations are stale,
+/// and `ERR_INVALID_OPERATION` (with the info log as the last error) on
+/// failure.
+pub fn ctx_reload_program(
+    ctx: u32,
+    program: u32,
+    vs_ptr: u32,
+    vs_len: u32,
+    fs_ptr: u32,
+    fs_len: u32,
+) -> u32 {
+    clear_last_error();
+    let vs_source = match crate::guest_mem::read(vs_ptr, vs_len) {
+        Ok(s) => String::from_utf8_lossy(s).into_owned(),
+        Err(code) => return code,
+    };
+    let fs_source = match crate::guest_mem::read(fs_ptr, fs_len) {
+        Ok(s) => String::from_utf8_lossy(s).into_owned(),
+        Err(code) => return code,
+    };
+
+    let (old_interface, previous) = {
+        let mut reg = get_registry().borrow_mut();
+        let ctx_obj = match reg.contexts.get_mut(&ctx) {
+            Some(c) => c,
+            None => {
+                set_last_error("invalid context handle");
+                return ERR_INVALID_HANDLE;
+            }
+        };
+        let p = match ctx_obj.programs.get(&program) {
+            Some(p) => p,
+            None => {
+                set_last_error("program not found");
+                return ERR_INVALID_HANDLE;
+            }
+        };
+
+        let mut vs_shader = None;
+        let mut fs_shader = None;
+        for &s_id in &p.attached_shaders {
+            match ctx_obj.shaders.get(&s_id).map(|s| s.type_) {
+                Some(GL_VERTEX_SHADER) => vs_shader = Some(s_id),
+                Some(GL_FRAGMENT_SHADER) => fs_shader = Some(s_id),
+                _ => {}
+            }
+        }
+        let (Some(vs_id), Some(fs_id)) = (vs_shader, fs_shader) else {
+            set_last_error("program must have both vertex and fragment shaders attached");
+            return ERR_INVALID_OPERATION;
+        };
+        let old_interface = p.linked.then(|| program_interface(p));
+
+        let mut compiled = Vec::new();
+        for (s_id, type_, source) in [
+            (vs_id, GL_VERTEX_SHADER, vs_source),
+            (fs_id, GL_FRAGMENT_SHADER, fs_source),
+        ] {
+            let glsl100 =
+                ctx_obj.webgl1 || (ctx_obj.glsl100_compat && glsl100::is_glsl100(&source));
+            match compile_context_glsl(glsl100, type_, &source) {
+                Ok((module, info, translated_source)) => compiled.push((
+                    s_id,
+                    source,
+                    translated_source,
+                    compiled_log(glsl100 && !ctx_obj.webgl1),
+                    module,
+                    info,
+                )),
+                Err(log) => {
+                    let stage = if type_ == GL_VERTEX_SHADER {
+                        "vertex"
+                    } else {
+                        "fragment"
+                    };
+                    set_last_error(&format!("{} shader: {}", stage, log));
+                    return ERR_INVALID_OPERATION;
+                }
+            }
+        }
+
+        let mut previous = Vec::new();
+        for (s_id, source, translated_source, info_log, module, info) in compiled {
+            if let Some(s) = ctx_obj.shaders.get_mut(&s_id) {
+                previous.push((s_id, s.clone()));
+                s.source = source;
+                s.translated_source = translated_source;
+                s.compiled = true;
+                s.info_log = info_log;
+                s.module = Some(Arc::new(module));
+                s.info = Some(Arc::new(info));
+            }
+        }
+        (old_interface, previous)
+    };
+
+    let code = ctx_link_program(ctx, program);
+    if code != ERR_OK {
+        return code;
+    }
+
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => return ERR_INVALID_HANDLE,
+    };
+    let (linked, log, new_interface) = match ctx_obj.programs.get(&program) {
+        Some(p) => (p.linked, p.info_log.clone(), program_interface(p)),
+        None => return ERR_INVALID_HANDLE,
+    };
+
+    if !linked {
+        for (s_id, shader) in previous {
+            ctx_obj.shaders.insert(s_id, shader);
+        }
+        drop(reg);
+        ctx_link_program(ctx, program);
+        set_last_error(&log);
+        return ERR_INVALID_OPERATION;
+    }
+
+    if old_interface.as_ref() == Some(&new_interface) {
+        ERR_OK
+    } else {
+        RELOAD_INTERFACE_CHANGED
+    }
+}
+
 /// Get program parameter.
 pub fn ctx_get_program_parameter(ctx: u32, program: u32, pname: u32) -> i32 {
     clear_last_error();
@@ -864,7 +935,15 @@ pub fn ctx_get_program_parameter(ctx: u32, program: u32, pname: u32) -> i32 {
             GL_ATTACHED_SHADERS => p.attached_shaders.len() as i32,
             GL_ACTIVE_UNIFORMS => p.active_uniforms.len() as i32,
             GL_ACTIVE_ATTRIBUTES => p.active_attributes.len() as i32,
-            GL_DELETE_STATUS => 0,
+            GL_DELETE_STATUS => p.deleted as i32,
+            GL_VALIDATE_STATUS => p.validate_status as i32,
+            GL_PROGRAM_SEPARABLE => p.separable as i32,
+            GL_ACTIVE_UNIFORM_BLOCKS => p.uniform_blocks.len() as i32,
+            GL_TRANSFORM_FEEDBACK_VARYINGS => p.tf_linked.len() as i32,
+            GL_TRANSFORM_FEEDBACK_BUFFER_MODE => match p.tf_linked_buffer_mode {
+                GL_SEPARATE_ATTRIBS => GL_SEPARATE_ATTRIBS as i32,
+                _ => GL_INTERLEAVED_ATTRIBS as i32,
+            },
             _ => 0,
         }
     } else {
@@ -905,6 +984,80 @@ pub fn ctx_get_program_info_log(ctx: u32, program: u32) -> u32 {
     super::ephemeral::alloc_string(ctx_obj, &log)
 }
 
+/// Report a trap in the `stage` shader of the last draw, after
+/// `recover_shader_trap`. `offsets` are the module offsets of the trapping
+/// frames, innermost first; the first one the shader's line table knows
+/// gives the GLSL line. The trap goes into the program info log and the last
+/// error, and raises INVALID_OPERATION.
+pub fn ctx_report_shader_trap(
+    ctx: u32,
+    stage: u32,
+    offsets_ptr: u32,
+    offsets_len: u32,
+    message_ptr: u32,
+    message_len: u32,
+) -> u32 {
+    clear_last_error();
+    let offsets =
+        match crate::guest_mem::read_slice(offsets_ptr as *const u32, offsets_len as usize) {
+            Ok(s) => s,
+            Err(code) => return code,
+        };
+    let message = match crate::guest_mem::read_str(message_ptr, message_len) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => return ERR_INVALID_HANDLE,
+    };
+
+    let (vs_program, fs_program) = match super::pipelines::draw_stage_programs(ctx_obj) {
+        Ok(programs) => programs,
+        Err(code) => return code,
+    };
+    let (program, stage_name) = match stage {
+        GL_VERTEX_SHADER => (vs_program, "vertex"),
+        GL_FRAGMENT_SHADER => (fs_program, "fragment"),
+        _ => {
+            set_last_error("invalid shader stage");
+            return ERR_INVALID_ARGS;
+        }
+    };
+    let Some(p) = ctx_obj.programs.get_mut(&program) else {
+        set_last_error("program not found");
+        return ERR_INVALID_HANDLE;
+    };
+
+    let wasm = if stage == GL_VERTEX_SHADER {
+        &p.vs_wasm
+    } else {
+        &p.fs_wasm
+    };
+    let span = wasm.as_deref().and_then(|wasm| {
+        offsets
+            .iter()
+            .find_map(|&offset| crate::naga_wasm_backend::debug::symbolize(wasm, None, offset))
+    });
+    let location = match (span, offsets.first()) {
+        (Some(span), _) => format!("{}:{}", span.file, span.line),
+        (None, Some(offset)) => format!("offset {:#x} (no debug info)", offset),
+        (None, None) => "an unknown location".to_string(),
+    };
+    let log = format!(
+        "Shader trap in {} shader: {} at {}",
+        stage_name, message, location
+    );
+    if !p.info_log.is_empty() {
+        p.info_log.push('\n');
+    }
+    p.info_log.push_str(&log);
+    set_last_error(&log);
+    ctx_obj.set_error(GL_INVALID_OPERATION);
+    ERR_GL
+}
+
 /// Get the length of the generated WASM for a program's shader.
 pub fn ctx_get_program_wasm_len(ctx: u32, program: u32, shader_type: u32) -> u32 {
     clear_last_error();
@@ -955,9 +1108,11 @@ pub fn ctx_get_program_wasm(
 
         if let Some(bytes) = wasm {
             let len = std::cmp::min(bytes.len(), dest_len as usize);
-            unsafe {
-                std::ptr::copy_nonoverlapping(bytes.as_ptr(), dest_ptr as *mut u8, len);
-            }
+            let dest = match crate::guest_mem::write(dest_ptr, len as u32) {
+                Ok(d) => d,
+                Err(_) => return 0,
+            };
+            dest.copy_from_slice(&bytes[..len]);
             len as u32
         } else {
             0
@@ -967,6 +1122,69 @@ pub fn ctx_get_program_wasm(
     }
 }
 
+/// Copy up to `dest_len` bytes of a program shader's WASM, starting at byte
+/// `offset`, so hosts can read large modules in chunks. Returns the number of
+/// bytes copied, 0 past the end.
+pub fn ctx_get_program_wasm_chunk(
+    ctx: u32,
+    program: u32,
+    shader_type: u32,
+    offset: u32,
+    dest_ptr: u32,
+    dest_len: u32,
+) -> u32 {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    let Some(bytes) = reg
+        .contexts
+        .get(&ctx)
+        .and_then(|c| c.programs.get(&program))
+        .and_then(|p| match shader_type {
+            GL_VERTEX_SHADER => p.vs_wasm.as_deref(),
+            GL_FRAGMENT_SHADER => p.fs_wasm.as_deref(),
+            _ => None,
+        })
+    else {
+        return 0;
+    };
+
+    let start = (offset as usize).min(bytes.len());
+    let len = (bytes.len() - start).min(dest_len as usize);
+    let dest = match crate::guest_mem::write(dest_ptr, len as u32) {
+        Ok(d) => d,
+        Err(_) => return 0,
+    };
+    dest.copy_from_slice(&bytes[start..start + len]);
+    len as u32
+}
+
+/// FNV-1a hash of a program shader's WASM. Identical modules hash the same
+/// across runs, so hosts can key caches by it and check that a chunked read
+/// did not straddle a relink. Returns 0 when there is no module.
+pub fn ctx_get_program_wasm_hash(ctx: u32, program: u32, shader_type: u32) -> u64 {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    let Some(bytes) = reg
+        .contexts
+        .get(&ctx)
+        .and_then(|c| c.programs.get(&program))
+        .and_then(|p| match shader_type {
+            GL_VERTEX_SHADER => p.vs_wasm.as_deref(),
+            GL_FRAGMENT_SHADER => p.fs_wasm.as_deref(),
+            _ => None,
+        })
+    else {
+        return 0;
+    };
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
 pub fn ctx_use_program(ctx: u32, program: u32) -> u32 {
     clear_last_error();
     let mut reg = get_registry().borrow_mut();
@@ -980,11 +1198,13 @@ pub fn ctx_use_program(ctx: u32, program: u32) -> u32 {
 
     if program == 0 {
         ctx_obj.current_program = None;
+        super::deletion::release_deleted_objects(ctx_obj);
         return ERR_OK;
     }
 
-    if ctx_obj.programs.contains_key(&program) {
+    if ctx_obj.programs.get(&program).is_some_and(|p| !p.deleted) {
         ctx_obj.current_program = Some(program);
+        super::deletion::release_deleted_objects(ctx_obj);
         ERR_OK
     } else {
         set_last_error("program not found");
@@ -1000,8 +1220,10 @@ pub fn ctx_get_uniform_location(ctx: u32, program: u32, name_ptr: u32, name_len:
         None => return -1,
     };
 
-    let name_slice =
-        unsafe { std::slice::from_raw_parts(name_ptr as *const u8, name_len as usize) };
+    let name_slice = match crate::guest_mem::read(name_ptr, name_len) {
+        Ok(s) => s,
+        Err(_) => return -1,
+    };
     let name = String::from_utf8_lossy(name_slice).into_owned();
 
     if let Some(p) = ctx_obj.programs.get(&program) {
@@ -1030,13 +1252,15 @@ pub fn ctx_get_uniform_block_index(ctx: u32, program: u32, name_ptr: u32, name_l
         None => return 0xFFFFFFFF,
     };
 
-    let name_slice =
-        unsafe { std::slice::from_raw_parts(name_ptr as *const u8, name_len as usize) };
+    let name_slice = match crate::guest_mem::read(name_ptr, name_len) {
+        Ok(s) => s,
+        Err(_) => return 0xFFFFFFFF,
+    };
     let name = String::from_utf8_lossy(name_slice).into_owned();
 
     if let Some(p) = ctx_obj.programs.get(&program) {
-        for (i, block_name) in p.uniform_blocks.iter().enumerate() {
-            if block_name == &name {
+        for (i, block) in p.uniform_blocks.iter().enumerate() {
+            if block.name == name {
                 return i as u32;
             }
         }
@@ -1071,137 +1295,225 @@ pub fn ctx_uniform_block_binding(ctx: u32, program: u32, index: u32, binding: u3
     }
 }
 
-pub fn ctx_get_attrib_location(ctx: u32, program: u32, name_ptr: u32, name_len: u32) -> i32 {
+/// Get the name of an active uniform block.
+/// Returns a pointer to an ephemeral string, see [`ctx_get_program_info_log`].
+///
+/// Returns 0 on failure (check last error).
+pub fn ctx_get_active_uniform_block_name(ctx: u32, program: u32, index: u32) -> u32 {
     clear_last_error();
-    let reg = get_registry().borrow();
-    let ctx_obj = match reg.contexts.get(&ctx) {
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
         Some(c) => c,
-        None => return -1,
+        None => {
+            set_last_error("invalid context handle");
+            return 0;
+        }
     };
 
-    let name_slice =
-        unsafe { std::slice::from_raw_parts(name_ptr as *const u8, name_len as usize) };
-    let name = String::from_utf8_lossy(name_slice).into_owned();
-
-    if let Some(p) = ctx_obj.programs.get(&program) {
-        if let Some(&loc) = p.attributes.get(&name) {
-            loc
-        } else {
-            -1
+    let name = match ctx_obj.programs.get(&program) {
+        Some(p) => match p.uniform_blocks.get(index as usize) {
+            Some(block) => block.name.clone(),
+            None => {
+                set_last_error("uniform block index out of range");
+                ctx_obj.set_error(GL_INVALID_VALUE);
+                return 0;
+            }
+        },
+        None => {
+            set_last_error("program not found");
+            return 0;
         }
-    } else {
-        -1
-    }
+    };
+
+    super::ephemeral::alloc_string(ctx_obj, &name)
 }
 
-/// Bind attribute location.
-pub fn ctx_bind_attrib_location(
+/// Get a parameter of an active uniform block.
+/// Returns a pointer to an ephemeral payload of `i32` values: one, or the
+/// active uniform indices for `GL_UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES`.
+/// The header holds the payload length in bytes, as for
+/// [`ctx_get_active_uniform`].
+///
+/// Returns 0 on failure (check last error).
+pub fn ctx_get_active_uniform_block_parameter(
     ctx: u32,
     program: u32,
     index: u32,
-    name_ptr: u32,
-    name_len: u32,
+    pname: u32,
 ) -> u32 {
     clear_last_error();
     let mut reg = get_registry().borrow_mut();
     let ctx_obj = match reg.contexts.get_mut(&ctx) {
         Some(c) => c,
-        None => return ERR_INVALID_HANDLE,
+        None => {
+            set_last_error("invalid context handle");
+            return 0;
+        }
     };
 
-    let name_slice =
-        unsafe { std::slice::from_raw_parts(name_ptr as *const u8, name_len as usize) };
-    let name = String::from_utf8_lossy(name_slice).into_owned();
+    let Some(p) = ctx_obj.programs.get(&program) else {
+        set_last_error("program not found");
+        return 0;
+    };
+    let Some(block) = p.uniform_blocks.get(index as usize) else {
+        set_last_error("uniform block index out of range");
+        ctx_obj.set_error(GL_INVALID_VALUE);
+        return 0;
+    };
+    let values: Vec<i32> = match pname {
+        GL_UNIFORM_BLOCK_BINDING => {
+            vec![p.uniform_block_bindings.get(&index).copied().unwrap_or(0) as i32]
+        }
+        GL_UNIFORM_BLOCK_DATA_SIZE => vec![block.data_size as i32],
+        GL_UNIFORM_BLOCK_ACTIVE_UNIFORMS => vec![block.uniforms.len() as i32],
+        GL_UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES => block
+            .uniforms
+            .iter()
+            .filter_map(|name| p.active_uniforms.iter().position(|u| &u.name == name))
+            .map(|i| i as i32)
+            .collect(),
+        GL_UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER => vec![block.referenced_by_vertex as i32],
+        GL_UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER => {
+            vec![block.referenced_by_fragment as i32]
+        }
+        _ => {
+            set_last_error("invalid uniform block parameter");
+            ctx_obj.set_error(GL_INVALID_ENUM);
+            return 0;
+        }
+    };
 
-    if let Some(p) = ctx_obj.programs.get_mut(&program) {
-        p.attribute_bindings.insert(name, index);
-        ERR_OK
+    let payload_len = values.len() as u32 * 4;
+    let ptr = if payload_len <= 128 {
+        ctx_obj.alloc_small(payload_len)
     } else {
-        set_last_error("program not found");
-        ERR_INVALID_HANDLE
-    }
+        ctx_obj.alloc_blob(payload_len)
+    };
+    let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, values.len()) };
+    dest.copy_from_slice(&values);
+    ptr
 }
 
-/// Set uniform 1f.
-pub fn ctx_uniform1f(ctx: u32, location: i32, x: f32) -> u32 {
+pub fn ctx_get_attrib_location(ctx: u32, program: u32, name_ptr: u32, name_len: u32) -> i32 {
     clear_last_error();
-    let mut reg = get_registry().borrow_mut();
-    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+    let reg = get_registry().borrow();
+    let ctx_obj = match reg.contexts.get(&ctx) {
         Some(c) => c,
-        None => return ERR_INVALID_HANDLE,
+        None => return -1,
     };
 
-    if location < 0 {
-        return ERR_OK;
-    }
+    let name_slice = match crate::guest_mem::read(name_ptr, name_len) {
+        Ok(s) => s,
+        Err(_) => return -1,
+    };
+    let name = String::from_utf8_lossy(name_slice).into_owned();
 
-    let offset =
-        crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset(location as u32)
-            as usize;
-    if (offset + 4) <= ctx_obj.uniform_data.len() {
-        ctx_obj.uniform_data[offset..offset + 4].copy_from_slice(&x.to_le_bytes());
-        ERR_OK
+    if let Some(p) = ctx_obj.programs.get(&program) {
+        if let Some(&loc) = p.attributes.get(&name) {
+            loc
+        } else {
+            -1
+        }
     } else {
-        set_last_error("invalid uniform location");
-        ERR_INVALID_ARGS
+        -1
     }
 }
 
-/// Set uniform 2f.
-pub fn ctx_uniform2f(ctx: u32, location: i32, x: f32, y: f32) -> u32 {
-    clear_last_error();
-    let mut reg = get_registry().borrow_mut();
-    let ctx_obj = match reg.contexts.get_mut(&ctx) {
-        Some(c) => c,
-        None => return ERR_INVALID_HANDLE,
-    };
+/// Bind a generic attribute location to an attribute name. The binding only
+/// takes effect at the next link, and a layout qualifier in the shader takes
+/// precedence over it.
+pub fn ctx_bind_attrib_location(
+    ctx: u32,
+    program: u32,
+    index: u32,
+    name_ptr: u32,
+    name_len: u32,
+) -> u32 {
+    bind_location(
+        BoundKind::Attribute,
+        ctx,
+        program,
+        index,
+        name_ptr,
+        name_len,
+    )
+}
 
-    if location < 0 {
-        return ERR_OK;
-    }
+/// Bind a uniform location to a uniform name, in place of the one the next
+/// link would number it with.
+pub fn ctx_bind_uniform_location(
+    ctx: u32,
+    program: u32,
+    location: u32,
+    name_ptr: u32,
+    name_len: u32,
+) -> u32 {
+    bind_location(
+        BoundKind::Uniform,
+        ctx,
+        program,
+        location,
+        name_ptr,
+        name_len,
+    )
+}
 
-    let offset =
-        crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset(location as u32)
-            as usize;
-    if (offset + 8) <= ctx_obj.uniform_data.len() {
-        ctx_obj.uniform_data[offset..offset + 4].copy_from_slice(&x.to_le_bytes());
-        ctx_obj.uniform_data[offset + 4..offset + 8].copy_from_slice(&y.to_le_bytes());
-        ERR_OK
-    } else {
-        set_last_error("invalid uniform location");
-        ERR_INVALID_ARGS
-    }
+/// Bind a varying location to a varying name, in place of the one the next
+/// link would pack it into. Both programs of a pipeline need the same
+/// bindings.
+pub fn ctx_bind_varying_location(
+    ctx: u32,
+    program: u32,
+    location: u32,
+    name_ptr: u32,
+    name_len: u32,
+) -> u32 {
+    bind_location(
+        BoundKind::Varying,
+        ctx,
+        program,
+        location,
+        name_ptr,
+        name_len,
+    )
 }
 
-/// Set uniform 3f.
-pub fn ctx_uniform3f(ctx: u32, location: i32, x: f32, y: f32, z: f32) -> u32 {
-    clear_last_error();
-    let mut reg = get_registry().borrow_mut();
-    let ctx_obj = match reg.contexts.get_mut(&ctx) {
-        Some(c) => c,
-        None => return ERR_INVALID_HANDLE,
-    };
+/// Kind of interface variable a location is bound for.
+#[derive(Clone, Copy)]
+enum BoundKind {
+    Attribute,
+    Uniform,
+    Varying,
+}
 
-    if location < 0 {
-        return ERR_OK;
+impl BoundKind {
+    /// Number of locations under the context's limits and the limit's name
+    fn limit(self, limits: &Limits) -> (u32, &'static str) {
+        match self {
+            BoundKind::Attribute => (limits.max_vertex_attribs, "MAX_VERTEX_ATTRIBS"),
+            BoundKind::Uniform => (MAX_UNIFORM_LOCATIONS, "the uniform location count"),
+            BoundKind::Varying => (limits.max_varying_vectors, "MAX_VARYING_VECTORS"),
+        }
     }
 
-    let offset =
-        crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset(location as u32)
-            as usize;
-    if (offset + 12) <= ctx_obj.uniform_data.len() {
-        ctx_obj.uniform_data[offset..offset + 4].copy_from_slice(&x.to_le_bytes());
-        ctx_obj.uniform_data[offset + 4..offset + 8].copy_from_slice(&y.to_le_bytes());
-        ctx_obj.uniform_data[offset + 8..offset + 12].copy_from_slice(&z.to_le_bytes());
-        ERR_OK
-    } else {
-        set_last_error("invalid uniform location");
-        ERR_INVALID_ARGS
+    fn bindings(self, bindings: &mut LocationBindings) -> &mut HashMap<String, u32> {
+        match self {
+            BoundKind::Attribute => &mut bindings.attributes,
+            BoundKind::Uniform => &mut bindings.uniforms,
+            BoundKind::Varying => &mut bindings.varyings,
+        }
     }
 }
 
-/// Set uniform 4f.
-pub fn ctx_uniform4f(ctx: u32, location: i32, x: f32, y: f32, z: f32, w: f32) -> u32 {
+/// Record a location binding of `kind` on a program for its next link.
+fn bind_location(
+    kind: BoundKind,
+    ctx: u32,
+    program: u32,
+    index: u32,
+    name_ptr: u32,
+    name_len: u32,
+) -> u32 {
     clear_last_error();
     let mut reg = get_registry().borrow_mut();
     let ctx_obj = match reg.contexts.get_mut(&ctx) {
@@ -1209,6 +1521,40 @@ pub fn ctx_uniform4f(ctx: u32, location: i32, x: f32, y: f32, z: f32, w: f32) ->
         None => return ERR_INVALID_HANDLE,
     };
 
+    let name_slice = match crate::guest_mem::read(name_ptr, name_len) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
+    let name = String::from_utf8_lossy(name_slice).into_owned();
+
+    if ctx_obj.programs.get(&program).is_none_or(|p| p.deleted) {
+        set_last_error("program not found");
+        ctx_obj.set_error(GL_INVALID_VALUE);
+        return ERR_GL;
+    }
+    let (limit, limit_name) = kind.limit(&ctx_obj.limits);
+    if index >= limit {
+        set_last_error(&format!("index {} is not below {}", index, limit_name));
+        ctx_obj.set_error(GL_INVALID_VALUE);
+        return ERR_GL;
+    }
+    if ["gl_", "webgl_", "_webgl_"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+    {
+        set_last_error(&format!("'{}' uses a reserved prefix", name));
+        ctx_obj.set_error(GL_INVALID_OPERATION);
+        return ERR_GL;
+    }
+    let p = ctx_obj.programs.get_mut(&program).unwrap();
+    kind.bindings(&mut p.bindings).insert(name, index);
+    ERR_OK
+}
+
+/// Store the little-endian value `bytes` at the uniform data for `location`,
+/// skipping the write (and counting it in `uniform_stats`) when the stored
+/// value is unchanged.
+fn store_uniform(ctx_obj: &mut Context, location: i32, bytes: &[u8]) -> u32 {
     if location < 0 {
         return ERR_OK;
     }
@@ -1216,52 +1562,69 @@ pub fn ctx_uniform4f(ctx: u32, location: i32, x: f32, y: f32, z: f32, w: f32) ->
     let offset =
         crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset(location as u32)
             as usize;
-    if (offset + 16) <= ctx_obj.uniform_data.len() {
-        ctx_obj.uniform_data[offset..offset + 4].copy_from_slice(&x.to_le_bytes());
-        ctx_obj.uniform_data[offset + 4..offset + 8].copy_from_slice(&y.to_le_bytes());
-        ctx_obj.uniform_data[offset + 8..offset + 12].copy_from_slice(&z.to_le_bytes());
-        ctx_obj.uniform_data[offset + 12..offset + 16].copy_from_slice(&w.to_le_bytes());
-        ERR_OK
-    } else {
-        set_last_error("invalid uniform location");
-        ERR_INVALID_ARGS
+    let dst = match ctx_obj.uniform_data.get_mut(offset..offset + bytes.len()) {
+        Some(d) => d,
+        None => {
+            set_last_error("invalid uniform location or data length");
+            return ERR_INVALID_ARGS;
+        }
+    };
+    if dst == bytes {
+        ctx_obj.uniform_stats.skipped += 1;
+        return ERR_OK;
     }
+    dst.copy_from_slice(bytes);
+    ctx_obj.uniform_stats.uploads += 1;
+    ERR_OK
 }
 
-/// Set uniform 1i.
-pub fn ctx_uniform1i(ctx: u32, location: i32, x: i32) -> u32 {
+/// Look up `ctx` and store a uniform value.
+fn set_uniform_bytes(ctx: u32, location: i32, bytes: &[u8]) -> u32 {
     clear_last_error();
     let mut reg = get_registry().borrow_mut();
-    let ctx_obj = match reg.contexts.get_mut(&ctx) {
-        Some(c) => c,
-        None => return ERR_INVALID_HANDLE,
-    };
-
-    if location < 0 {
-        return ERR_OK;
+    match reg.contexts.get_mut(&ctx) {
+        Some(ctx_obj) => store_uniform(ctx_obj, location, bytes),
+        None => ERR_INVALID_HANDLE,
     }
+}
 
-    let offset =
-        crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset(location as u32)
-            as usize;
-    if (offset + 4) <= ctx_obj.uniform_data.len() {
-        ctx_obj.uniform_data[offset..offset + 4].copy_from_slice(&x.to_le_bytes());
-        ERR_OK
-    } else {
-        set_last_error("invalid uniform location");
-        ERR_INVALID_ARGS
-    }
+/// [`set_uniform_bytes`] with a scalar or vector value, encoded on the stack.
+fn set_uniform<const N: usize>(ctx: u32, location: i32, words: [u32; N]) -> u32 {
+    set_uniform_bytes(ctx, location, words.map(u32::to_le_bytes).as_flattened())
+}
+
+/// Set uniform 1f.
+pub fn ctx_uniform1f(ctx: u32, location: i32, x: f32) -> u32 {
+    set_uniform(ctx, location, [x.to_bits()])
+}
+
+/// Set uniform 2f.
+pub fn ctx_uniform2f(ctx: u32, location: i32, x: f32, y: f32) -> u32 {
+    set_uniform(ctx, location, [x.to_bits(), y.to_bits()])
+}
+
+/// Set uniform 3f.
+pub fn ctx_uniform3f(ctx: u32, location: i32, x: f32, y: f32, z: f32) -> u32 {
+    set_uniform(ctx, location, [x.to_bits(), y.to_bits(), z.to_bits()])
+}
+
+/// Set uniform 4f.
+pub fn ctx_uniform4f(ctx: u32, location: i32, x: f32, y: f32, z: f32, w: f32) -> u32 {
+    set_uniform(
+        ctx,
+        location,
+        [x.to_bits(), y.to_bits(), z.to_bits(), w.to_bits()],
+    )
+}
+
+/// Set uniform 1i.
+pub fn ctx_uniform1i(ctx: u32, location: i32, x: i32) -> u32 {
+    set_uniform(ctx, location, [x as u32])
 }
 
 /// Set uniform matrix 4fv.
 pub fn ctx_uniform_matrix_4fv(ctx: u32, location: i32, transpose: bool, ptr: u32, len: u32) -> u32 {
     clear_last_error();
-    let mut reg = get_registry().borrow_mut();
-    let ctx_obj = match reg.contexts.get_mut(&ctx) {
-        Some(c) => c,
-        None => return ERR_INVALID_HANDLE,
-    };
-
     if location < 0 {
         return ERR_OK;
     }
@@ -1271,19 +1634,49 @@ pub fn ctx_uniform_matrix_4fv(ctx: u32, location: i32, transpose: bool, ptr: u32
         return ERR_INVALID_ARGS;
     }
 
-    let offset =
-        crate::naga_wasm_backend::output_layout::get_webgl_uniform_data_offset(location as u32)
-            as usize;
-    if (offset + len as usize * 4) <= ctx_obj.uniform_data.len() {
-        let src_slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, (len * 4) as usize) };
-        ctx_obj.uniform_data[offset..offset + (len * 4) as usize].copy_from_slice(src_slice);
-        ERR_OK
-    } else {
-        set_last_error("invalid uniform location or data length");
-        ERR_INVALID_ARGS
+    let Some(byte_len) = len.checked_mul(4) else {
+        set_last_error("uniform data length overflows");
+        return ERR_INVALID_ARGS;
+    };
+    // The floats are stored as the guest laid them out, without a copy
+    let src_slice = match crate::guest_mem::read(ptr, byte_len) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
+    set_uniform_bytes(ctx, location, src_slice)
+}
+
+/// Store raw little-endian uniform words at `location`, whatever the
+/// uniform's type. Used by draw packets, which carry pre-encoded values.
+pub(crate) fn set_uniform_words(ctx: u32, location: i32, words: &[u32]) -> u32 {
+    // Values up to a mat4 are encoded on the stack, longer arrays on the heap
+    let mut stack = [0u8; 64];
+    let mut heap = Vec::new();
+    let bytes = match stack.get_mut(..words.len() * 4) {
+        Some(bytes) => bytes,
+        None => {
+            heap.resize(words.len() * 4, 0);
+            &mut heap[..]
+        }
+    };
+    for (dst, word) in bytes.chunks_exact_mut(4).zip(words) {
+        dst.copy_from_slice(&word.to_le_bytes());
     }
+    set_uniform_bytes(ctx, location, bytes)
+}
+
+/// Uniform setter counts for `ctx`, or None for an invalid handle.
+pub fn ctx_uniform_stats(ctx: u32) -> Option<UniformStats> {
+    super::registry::with_context(ctx, |c| c.uniform_stats)
 }
 
+/// Flag of [`ctx_get_program_debug_stub_with_options`]: leave the GLSL
+/// source out of the stub's source map.
+pub const DEBUG_STUB_NO_SOURCE: u32 = 0x1;
+/// Flag of [`ctx_get_program_debug_stub_with_options`]: emit breakpoint
+/// checks on every line.
+pub const DEBUG_STUB_BREAKPOINTS: u32 = 0x2;
+
 /// Get program debug stub.
 pub fn ctx_get_program_debug_stub(
     ctx: u32,
@@ -1292,63 +1685,256 @@ pub fn ctx_get_program_debug_stub(
     ptr: u32,
     max_len: u32,
 ) -> u32 {
+    ctx_get_program_debug_stub_with_options(ctx, program, shader_type, 0, 0, 0, ptr, max_len)
+}
+
+/// Get a program debug stub generated with `flags` (`DEBUG_STUB_*`) and a
+/// comma-separated watch list of `watch_len` bytes at `watch_ptr`. With no
+/// flags and no watch list this is the stub generated at link time; other
+/// options regenerate it from the source embedded in the debug build.
+///
+/// With `ptr` 0 returns the stub's length; otherwise copies up to `max_len`
+/// bytes to `ptr` and returns the number copied. Returns 0 on failure or
+/// when the program has no debug stub.
+#[allow(clippy::too_many_arguments)]
+pub fn ctx_get_program_debug_stub_with_options(
+    ctx: u32,
+    program: u32,
+    shader_type: u32,
+    flags: u32,
+    watch_ptr: u32,
+    watch_len: u32,
+    ptr: u32,
+    max_len: u32,
+) -> u32 {
+    use crate::naga_wasm_backend::debug::{embedded_source, JsStubGenerator, StubOptions};
+    use std::borrow::Cow;
+
     clear_last_error();
-    let mut reg = get_registry().borrow_mut();
-    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+    let watch = if watch_len == 0 {
+        Vec::new()
+    } else {
+        match crate::guest_mem::read_str(watch_ptr, watch_len) {
+            Ok(list) => list
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(String::from)
+                .collect(),
+            Err(_) => return 0,
+        }
+    };
+    let options = StubOptions {
+        embed_source: flags & DEBUG_STUB_NO_SOURCE == 0,
+        breakpoints: flags & DEBUG_STUB_BREAKPOINTS != 0,
+        watch,
+    };
+
+    let reg = get_registry().borrow();
+    let ctx_obj = match reg.contexts.get(&ctx) {
         Some(c) => c,
         None => {
             set_last_error("invalid context handle");
             return 0;
         }
     };
+    let p = match ctx_obj.programs.get(&program) {
+        Some(p) => p,
+        None => {
+            set_last_error("program not found");
+            return 0;
+        }
+    };
+    let (cached, wasm, module, stage) = match shader_type {
+        GL_VERTEX_SHADER => (&p.vs_stub, &p.vs_wasm, &p.vs_module, "vs"),
+        GL_FRAGMENT_SHADER => (&p.fs_stub, &p.fs_wasm, &p.fs_module, "fs"),
+        _ => {
+            set_last_error("invalid shader type");
+            return 0;
+        }
+    };
 
-    if let Some(p) = ctx_obj.programs.get(&program) {
-        let stub = match shader_type {
-            GL_VERTEX_SHADER => &p.vs_stub,   // VERTEX_SHADER
-            GL_FRAGMENT_SHADER => &p.fs_stub, // FRAGMENT_SHADER
-            _ => {
-                set_last_error("invalid shader type");
-                return 0;
-            }
-        };
+    let stub: Option<Cow<str>> = if options == StubOptions::default() {
+        cached.as_deref().map(Cow::Borrowed)
+    } else {
+        wasm.as_deref().and_then(embedded_source).map(|source| {
+            let name = format!("program_{}_{}.glsl", program, stage);
+            let generator = match module {
+                Some(m) => JsStubGenerator::new(&source, m, Some(&name)),
+                None => JsStubGenerator::from_source(&source, Some(&name)),
+            };
+            Cow::Owned(generator.with_options(options).generate())
+        })
+    };
 
-        if let Some(s) = stub {
-            let bytes = s.as_bytes();
-            let len = bytes.len() as u32;
-            if len > max_len {
-                // Return needed length if buffer too small?
-                // Or just copy what fits?
-                // Standard pattern: if ptr is 0, return length.
-                if ptr == 0 {
-                    return len;
-                }
-                // If ptr != 0 and max_len too small, return 0 or partial?
-                // Let's return actual length copied.
-            }
+    let bytes = match &stub {
+        Some(s) => s.as_bytes(),
+        None => return 0,
+    };
+    let len = bytes.len() as u32;
+    if ptr == 0 {
+        return len;
+    }
+    let copy_len = std::cmp::min(len, max_len);
+    let dest_slice = match crate::guest_mem::write(ptr, copy_len) {
+        Ok(s) => s,
+        Err(_) => return 0,
+    };
+    dest_slice.copy_from_slice(&bytes[..copy_len as usize]);
+    copy_len
+}
 
-            if ptr != 0 {
-                let copy_len = std::cmp::min(len, max_len);
-                let dest_slice =
-                    unsafe { std::slice::from_raw_parts_mut(ptr as *mut u8, copy_len as usize) };
-                dest_slice.copy_from_slice(&bytes[..copy_len as usize]);
-                copy_len
-            } else {
-                len
-            }
-        } else {
-            0
+/// Get per-line execution counts of an instrumented program shader.
+///
+/// The report holds one `line count` pair per line, sorted by line. Returns
+/// the report length when `ptr` is 0, otherwise the number of bytes copied.
+pub fn ctx_get_program_coverage(
+    ctx: u32,
+    program: u32,
+    shader_type: u32,
+    ptr: u32,
+    max_len: u32,
+) -> u32 {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    let ctx_obj = match reg.contexts.get(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return 0;
         }
-    } else {
-        set_last_error("program not found");
-        0
+    };
+    let p = match ctx_obj.programs.get(&program) {
+        Some(p) => p,
+        None => {
+            set_last_error("program not found");
+            return 0;
+        }
+    };
+    let coverage = match shader_type {
+        GL_VERTEX_SHADER => &p.vs_coverage,
+        GL_FRAGMENT_SHADER => &p.fs_coverage,
+        _ => {
+            set_last_error("invalid shader type");
+            return 0;
+        }
+    };
+    let coverage = match coverage {
+        Some(c) => c,
+        None => return 0,
+    };
+
+    let report: String = coverage
+        .line_counts()
+        .iter()
+        .map(|(line, count)| format!("{} {}\n", line, count))
+        .collect();
+    let bytes = report.as_bytes();
+    if ptr == 0 {
+        return bytes.len() as u32;
+    }
+    let copy_len = std::cmp::min(bytes.len() as u32, max_len);
+    let dest_slice = match crate::guest_mem::write(ptr, copy_len) {
+        Ok(s) => s,
+        Err(_) => return 0,
+    };
+    dest_slice.copy_from_slice(&bytes[..copy_len as usize]);
+    copy_len
+}
+
+/// Get the per-line instruction profile of a program shader compiled for
+/// profiling, as JSON (`format` 0) or as an annotated source listing
+/// (`format` 1). Returns the report length when `ptr` is 0, otherwise the
+/// number of bytes copied.
+pub fn ctx_get_program_profile(
+    ctx: u32,
+    program: u32,
+    shader_type: u32,
+    format: u32,
+    ptr: u32,
+    max_len: u32,
+) -> u32 {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    let ctx_obj = match reg.contexts.get(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return 0;
+        }
+    };
+    let p = match ctx_obj.programs.get(&program) {
+        Some(p) => p,
+        None => {
+            set_last_error("program not found");
+            return 0;
+        }
+    };
+    let coverage = match shader_type {
+        GL_VERTEX_SHADER => &p.vs_coverage,
+        GL_FRAGMENT_SHADER => &p.fs_coverage,
+        _ => {
+            set_last_error("invalid shader type");
+            return 0;
+        }
+    };
+    let Some((coverage, profile)) = coverage
+        .as_ref()
+        .and_then(|c| c.profile().map(|profile| (c, profile)))
+    else {
+        return 0;
+    };
+
+    let report = match format {
+        0 => serde_json::to_string(&profile).unwrap_or_default(),
+        1 => profile.annotate(coverage.source()),
+        _ => {
+            set_last_error("invalid profile format");
+            return 0;
+        }
+    };
+    let bytes = report.as_bytes();
+    if ptr == 0 {
+        return bytes.len() as u32;
+    }
+    let copy_len = std::cmp::min(bytes.len() as u32, max_len);
+    let dest_slice = match crate::guest_mem::write(ptr, copy_len) {
+        Ok(s) => s,
+        Err(_) => return 0,
+    };
+    dest_slice.copy_from_slice(&bytes[..copy_len as usize]);
+    copy_len
+}
+
+/// Zero the coverage counters of both program shaders.
+pub fn ctx_reset_program_coverage(ctx: u32, program: u32) -> u32 {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    let ctx_obj = match reg.contexts.get(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    let p = match ctx_obj.programs.get(&program) {
+        Some(p) => p,
+        None => {
+            set_last_error("program not found");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    for coverage in [&p.vs_coverage, &p.fs_coverage].into_iter().flatten() {
+        coverage.reset();
     }
+    ERR_OK
 }
 
 // ============================================================================
 // WAT Testing Support (docs/1.9-wat-testing.md)
 // ============================================================================
 
-// Thread-local storage for ephemeral WAT strings.
+// Thread-local storage for ephemeral WAT and IR strings.
 // These strings are generated on-demand and must be copied synchronously by the caller.
 thread_local! {
     static WAT_STRING_STORAGE: RefCell<String> = const { RefCell::new(String::new()) };
@@ -1393,13 +1979,28 @@ pub fn ctx_get_program_wasm_ref(ctx: u32, program: u32, shader_type: u32) -> (u3
     }
 }
 
+/// Format code of [`ctx_get_program_ir`]: the Naga IR debug dump.
+pub const PROGRAM_IR_NAGA: u32 = 0;
+/// Format code of [`ctx_get_program_ir`]: the WAT disassembly.
+pub const PROGRAM_IR_WAT: u32 = 1;
+
 /// Get a reference to the WAT (WebAssembly Text) representation for a program's shader.
 /// Returns (ptr, len) pointing to a UTF-8 encoded WAT string owned by Rust.
 /// On failure or missing module, returns (0, 0).
 /// The pointer is ephemeral; callers must copy/decode synchronously.
-///
-/// Implementation: WAT is generated on-demand using wasmprinter and stored in thread-local storage.
 pub fn ctx_get_program_wat_ref(ctx: u32, program: u32, shader_type: u32) -> (u32, u32) {
+    ctx_get_program_ir(ctx, program, shader_type, PROGRAM_IR_WAT)
+}
+
+/// Get a reference to the compiler output for a program's shader: the Naga
+/// IR it was linked from ([`PROGRAM_IR_NAGA`]) or the WAT disassembly of the
+/// generated module ([`PROGRAM_IR_WAT`]).
+/// Returns (ptr, len) pointing to a UTF-8 string owned by Rust.
+/// On failure or missing module, returns (0, 0).
+/// The pointer is ephemeral; callers must copy/decode synchronously.
+///
+/// Implementation: the text is generated on-demand and stored in thread-local storage.
+pub fn ctx_get_program_ir(ctx: u32, program: u32, shader_type: u32, format: u32) -> (u32, u32) {
     clear_last_error();
     let reg = get_registry().borrow();
     let ctx_obj = match reg.contexts.get(&ctx) {
@@ -1410,42 +2011,47 @@ pub fn ctx_get_program_wat_ref(ctx: u32, program: u32, shader_type: u32) -> (u32
         }
     };
 
-    if let Some(p) = ctx_obj.programs.get(&program) {
-        let wasm = match shader_type {
-            GL_VERTEX_SHADER => &p.vs_wasm,   // VERTEX_SHADER
-            GL_FRAGMENT_SHADER => &p.fs_wasm, // FRAGMENT_SHADER
-            _ => {
-                set_last_error("invalid shader type");
-                return (0, 0);
-            }
-        };
+    let Some(p) = ctx_obj.programs.get(&program) else {
+        set_last_error("program not found");
+        return (0, 0);
+    };
+    let (module, wasm) = match shader_type {
+        GL_VERTEX_SHADER => (&p.vs_module, &p.vs_wasm),
+        GL_FRAGMENT_SHADER => (&p.fs_module, &p.fs_wasm),
+        _ => {
+            set_last_error("invalid shader type");
+            return (0, 0);
+        }
+    };
+    // Nothing to show until the stage has been compiled
+    let (Some(module), Some(bytes)) = (module, wasm) else {
+        return (0, 0);
+    };
 
-        if let Some(bytes) = wasm {
-            // Generate WAT from WASM bytes using wasmprinter
-            match wasmprinter::print_bytes(bytes) {
-                Ok(wat_string) => {
-                    // Store in thread-local storage and return pointer
-                    WAT_STRING_STORAGE.with(|storage| {
-                        let mut s = storage.borrow_mut();
-                        *s = wat_string;
-                        let ptr = s.as_ptr() as u32;
-                        let len = s.len() as u32;
-                        (ptr, len)
-                    })
-                }
-                Err(e) => {
-                    set_last_error(&format!("Failed to generate WAT: {}", e));
-                    (0, 0)
-                }
+    let text = match format {
+        PROGRAM_IR_NAGA => format!("{:#?}", module),
+        // Generate WAT from WASM bytes using wasmprinter
+        PROGRAM_IR_WAT => match wasmprinter::print_bytes(bytes) {
+            Ok(wat_string) => wat_string,
+            Err(e) => {
+                set_last_error(&format!("Failed to generate WAT: {}", e));
+                return (0, 0);
             }
-        } else {
-            // Module not compiled yet
-            (0, 0)
+        },
+        _ => {
+            set_last_error("invalid IR format");
+            return (0, 0);
         }
-    } else {
-        set_last_error("program not found");
-        (0, 0)
-    }
+    };
+
+    // Store in thread-local storage and return pointer
+    WAT_STRING_STORAGE.with(|storage| {
+        let mut s = storage.borrow_mut();
+        *s = text;
+        let ptr = s.as_ptr() as u32;
+        let len = s.len() as u32;
+        (ptr, len)
+    })
 }
 
 fn reflect_program_resources(p: &mut Program) {
@@ -1457,62 +2063,39 @@ fn reflect_program_resources(p: &mut Program) {
     // We only need to ensure ActiveInfo is populated for introspection APIs.
 
     fn map_type(ty: &naga::Type, arena: &naga::UniqueArena<naga::Type>) -> (u32, i32) {
-        let gl_float: u32 = 0x1406;
-        let gl_float_vec2: u32 = 0x8B50;
-        let gl_float_vec3: u32 = 0x8B51;
-        let gl_float_vec4: u32 = 0x8B52;
-        let gl_int: u32 = 0x1404;
-        let gl_int_vec2: u32 = 0x8B53;
-        let gl_int_vec3: u32 = 0x8B54;
-        let gl_int_vec4: u32 = 0x8B55;
-        let gl_unsigned_int: u32 = 0x1405;
-        let gl_unsigned_int_vec2: u32 = 0x8DC6;
-        let gl_unsigned_int_vec3: u32 = 0x8DC7;
-        let gl_unsigned_int_vec4: u32 = 0x8DC8;
-        let gl_bool: u32 = 0x8B56;
-        let gl_bool_vec2: u32 = 0x8B57;
-        let gl_bool_vec3: u32 = 0x8B58;
-        let gl_bool_vec4: u32 = 0x8B59;
-        let gl_float_mat2: u32 = 0x8B5A;
-        let gl_float_mat3: u32 = 0x8B5B;
-        let gl_float_mat4: u32 = 0x8B5C;
-        let gl_sampler_2d: u32 = 0x8B5E;
-        let gl_sampler_3d: u32 = 0x8B5F;
-        let gl_sampler_cube: u32 = 0x8B60;
-
         match &ty.inner {
             TypeInner::Scalar(s) => match s.kind {
-                ScalarKind::Float => (gl_float, 1),
-                ScalarKind::Sint => (gl_int, 1),
-                ScalarKind::Uint => (gl_unsigned_int, 1),
-                ScalarKind::Bool => (gl_bool, 1),
+                ScalarKind::Float => (GL_FLOAT, 1),
+                ScalarKind::Sint => (GL_INT, 1),
+                ScalarKind::Uint => (GL_UNSIGNED_INT, 1),
+                ScalarKind::Bool => (GL_BOOL, 1),
                 _ => (0, 1),
             },
             TypeInner::Vector { size, scalar } => match (scalar.kind, size) {
-                (ScalarKind::Float, VectorSize::Bi) => (gl_float_vec2, 1),
-                (ScalarKind::Float, VectorSize::Tri) => (gl_float_vec3, 1),
-                (ScalarKind::Float, VectorSize::Quad) => (gl_float_vec4, 1),
-                (ScalarKind::Sint, VectorSize::Bi) => (gl_int_vec2, 1),
-                (ScalarKind::Sint, VectorSize::Tri) => (gl_int_vec3, 1),
-                (ScalarKind::Sint, VectorSize::Quad) => (gl_int_vec4, 1),
-                (ScalarKind::Uint, VectorSize::Bi) => (gl_unsigned_int_vec2, 1),
-                (ScalarKind::Uint, VectorSize::Tri) => (gl_unsigned_int_vec3, 1),
-                (ScalarKind::Uint, VectorSize::Quad) => (gl_unsigned_int_vec4, 1),
-                (ScalarKind::Bool, VectorSize::Bi) => (gl_bool_vec2, 1),
-                (ScalarKind::Bool, VectorSize::Tri) => (gl_bool_vec3, 1),
-                (ScalarKind::Bool, VectorSize::Quad) => (gl_bool_vec4, 1),
+                (ScalarKind::Float, VectorSize::Bi) => (GL_FLOAT_VEC2, 1),
+                (ScalarKind::Float, VectorSize::Tri) => (GL_FLOAT_VEC3, 1),
+                (ScalarKind::Float, VectorSize::Quad) => (GL_FLOAT_VEC4, 1),
+                (ScalarKind::Sint, VectorSize::Bi) => (GL_INT_VEC2, 1),
+                (ScalarKind::Sint, VectorSize::Tri) => (GL_INT_VEC3, 1),
+                (ScalarKind::Sint, VectorSize::Quad) => (GL_INT_VEC4, 1),
+                (ScalarKind::Uint, VectorSize::Bi) => (GL_UNSIGNED_INT_VEC2, 1),
+                (ScalarKind::Uint, VectorSize::Tri) => (GL_UNSIGNED_INT_VEC3, 1),
+                (ScalarKind::Uint, VectorSize::Quad) => (GL_UNSIGNED_INT_VEC4, 1),
+                (ScalarKind::Bool, VectorSize::Bi) => (GL_BOOL_VEC2, 1),
+                (ScalarKind::Bool, VectorSize::Tri) => (GL_BOOL_VEC3, 1),
+                (ScalarKind::Bool, VectorSize::Quad) => (GL_BOOL_VEC4, 1),
                 _ => (0, 1),
             },
             TypeInner::Matrix { columns, rows, .. } => match (columns, rows) {
-                (VectorSize::Bi, VectorSize::Bi) => (gl_float_mat2, 1),
-                (VectorSize::Tri, VectorSize::Tri) => (gl_float_mat3, 1),
-                (VectorSize::Quad, VectorSize::Quad) => (gl_float_mat4, 1),
+                (VectorSize::Bi, VectorSize::Bi) => (GL_FLOAT_MAT2, 1),
+                (VectorSize::Tri, VectorSize::Tri) => (GL_FLOAT_MAT3, 1),
+                (VectorSize::Quad, VectorSize::Quad) => (GL_FLOAT_MAT4, 1),
                 _ => (0, 1),
             },
             TypeInner::Image { dim, .. } => match dim {
-                naga::ImageDimension::D2 => (gl_sampler_2d, 1),
-                naga::ImageDimension::D3 => (gl_sampler_3d, 1),
-                naga::ImageDimension::Cube => (gl_sampler_cube, 1),
+                naga::ImageDimension::D2 => (GL_SAMPLER_2D, 1),
+                naga::ImageDimension::D3 => (GL_SAMPLER_3D, 1),
+                naga::ImageDimension::Cube => (GL_SAMPLER_CUBE, 1),
                 _ => (0, 1),
             },
             TypeInner::Array { base, size, .. } => {
@@ -1599,6 +2182,54 @@ fn reflect_program_resources(p: &mut Program) {
         extract_active_uniforms(fs, &mut p.uniforms);
     }
 
+    // Uniform block members are active uniforms too, qualified by the block
+    // name when the block has an instance name
+    for (module, vertex) in [(&p.vs_module, true), (&p.fs_module, false)] {
+        let Some(module) = module else {
+            continue;
+        };
+        for (_, var) in module.global_variables.iter() {
+            let Some(block_name) = interface_layout::uniform_block_name(module, var) else {
+                continue;
+            };
+            let Some(block) = p.uniform_blocks.iter_mut().find(|b| &b.name == block_name) else {
+                continue;
+            };
+            if vertex {
+                block.referenced_by_vertex = true;
+            } else {
+                block.referenced_by_fragment = true;
+            }
+            let TypeInner::Struct { members, span } = &module.types[var.ty].inner else {
+                continue;
+            };
+            block.data_size = *span;
+            block.uniforms.clear();
+            for member in members {
+                let Some(member_name) = &member.name else {
+                    continue;
+                };
+                let (gl_type, size) = map_type(&module.types[member.ty], &module.types);
+                if gl_type == 0 {
+                    continue;
+                }
+                let mut name = match var.name {
+                    Some(_) => format!("{}.{}", block_name, member_name),
+                    None => member_name.clone(),
+                };
+                if size > 1 {
+                    name.push_str("[0]");
+                }
+                uni_map.entry(name.clone()).or_insert_with(|| ActiveInfo {
+                    name: name.clone(),
+                    size,
+                    type_: gl_type,
+                });
+                block.uniforms.push(name);
+            }
+        }
+    }
+
     let mut uniforms: Vec<ActiveInfo> = uni_map.into_values().collect();
     uniforms.sort_by(|a, b| a.name.cmp(&b.name));
     p.active_uniforms = uniforms;
@@ -1722,6 +2353,9 @@ pub fn ctx_get_active_attrib(ctx_handle: u32, program: u32, index: u32) -> u32 {
     }
 }
 
+/// Table index JS passes for a stage the program does not have.
+const NO_SHADER_INDEX: u32 = u32::MAX;
+
 /// Store shader table indices for direct calling.
 /// Called from JS after shader WASM instances are created and registered in the function table.
 pub fn ctx_register_shader_indices(ctx: u32, program: u32, vs_idx: u32, fs_idx: u32) -> u32 {
@@ -1738,8 +2372,17 @@ pub fn ctx_register_shader_indices(ctx: u32, program: u32, vs_idx: u32, fs_idx:
     };
 
     if let Some(prog) = ctx_obj.programs.get_mut(&program) {
-        prog.vs_table_idx = Some(vs_idx);
-        prog.fs_table_idx = Some(fs_idx);
+        // A single-stage separable program has no slot for the missing stage
+        let vs_idx = (vs_idx != NO_SHADER_INDEX).then_some(vs_idx);
+        let fs_idx = (fs_idx != NO_SHADER_INDEX).then_some(fs_idx);
+        // A relinked program hands in fresh slots; free the ones it replaces
+        for (old, new) in [(prog.vs_table_idx, vs_idx), (prog.fs_table_idx, fs_idx)] {
+            if let Some(old) = old.filter(|&old| Some(old) != new) {
+                crate::js_release_shader_index(old);
+            }
+        }
+        prog.vs_table_idx = vs_idx;
+        prog.fs_table_idx = fs_idx;
         ERR_OK
     } else {
         set_last_error("invalid program handle");
@@ -21,6 +21,21 @@ pub fn ctx_clear_color(ctx: u32, r: f32, g: f32, b: f32, a: f32) -> u32 {
     ERR_OK
 }
 
+/// Set the value depth buffers are cleared to, clamped to [0, 1].
+pub fn ctx_clear_depth(ctx: u32, depth: f32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    ctx_obj.clear_depth = depth.clamp(0.0, 1.0);
+    ERR_OK
+}
+
 /// Set debug mode.
 /// Deprecated: runtime debug mode toggling is not supported. Debug mode must be set at context creation.
 pub fn ctx_set_debug_mode(_ctx: u32, _mode: u32) -> u32 {
@@ -40,6 +55,9 @@ pub fn ctx_clear(ctx: u32, mask: u32) -> u32 {
             return ERR_INVALID_HANDLE;
         }
     };
+    if let Err(code) = ctx_obj.check_draw_framebuffer() {
+        return code;
+    }
 
     if (mask & GL_COLOR_BUFFER_BIT) != 0 {
         let fb_draw_buffers = if let Some(fb_handle) = ctx_obj.bound_draw_framebuffer {
@@ -53,9 +71,8 @@ pub fn ctx_clear(ctx: u32, mask: u32) -> u32 {
 
         if let Some(draw_buffers) = fb_draw_buffers {
             for &mode in draw_buffers.iter().take(8) {
-                if mode >= 0x8CE0 {
-                    // GL_COLOR_ATTACHMENTi
-                    let idx = (mode - 0x8CE0) as usize;
+                if mode >= GL_COLOR_ATTACHMENT0 {
+                    let idx = (mode - GL_COLOR_ATTACHMENT0) as usize;
                     let handle = if let Some(fb_handle) = ctx_obj.bound_draw_framebuffer {
                         ctx_obj.framebuffers.get(&fb_handle).and_then(|fb| {
                             if idx < fb.color_attachments.len() {
@@ -108,23 +125,37 @@ pub fn ctx_clear(ctx: u32, mask: u32) -> u32 {
         }
     }
 
-    if (mask & 0x00000100) != 0 {
-        // GL_DEPTH_BUFFER_BIT
+    if (mask & GL_DEPTH_BUFFER_BIT) != 0 && ctx_obj.depth_state.mask {
+        // Stored as the buffer would store it, so a draw at the clear depth
+        // compares equal
+        let depth = ctx_obj
+            .draw_depth_state()
+            .precision
+            .quantize(ctx_obj.clear_depth);
         if ctx_obj.bound_draw_framebuffer.is_none() {
-            ctx_obj
-                .default_framebuffer
-                .clear_depth(1.0, ctx_obj.depth_state.mask);
+            ctx_obj.default_framebuffer.clear_depth(depth, true);
+        } else {
+            let (handle, _, _, _) = ctx_obj.get_depth_attachment_handle();
+            if let Some(buffer) = ctx_obj.kernel.get_buffer_mut(handle) {
+                buffer.depth_stencil_planes().0.fill(depth);
+            }
         }
     }
 
-    if (mask & 0x00000400) != 0 {
-        // GL_STENCIL_BUFFER_BIT
+    if (mask & GL_STENCIL_BUFFER_BIT) != 0 {
+        let write_mask = ctx_obj.stencil_state.front.write_mask as u8;
+        let clear_val = 0; // TODO: get from state
         if ctx_obj.bound_draw_framebuffer.is_none() {
-            let write_mask = ctx_obj.stencil_state.front.write_mask;
-            let clear_val = 0; // TODO: get from state
             ctx_obj
                 .default_framebuffer
-                .clear_stencil(clear_val as u8, write_mask as u8);
+                .clear_stencil(clear_val, write_mask);
+        } else {
+            let (handle, _, _, _) = ctx_obj.get_stencil_attachment_handle();
+            if let Some(buffer) = ctx_obj.kernel.get_buffer_mut(handle) {
+                for s in buffer.depth_stencil_planes().1 {
+                    *s = (*s & !write_mask) | (clear_val & write_mask);
+                }
+            }
         }
     }
 
@@ -146,6 +177,14 @@ pub fn ctx_viewport(ctx: u32, x: i32, y: i32, width: u32, height: u32) -> u32 {
     ERR_OK
 }
 
+/// Largest width or height of the default framebuffer, so its RGBA8 frame
+/// fits the 32-bit sizes of readback payloads.
+pub const MAX_DRAWING_BUFFER_SIZE: u32 = 16384;
+
+/// Resize the default framebuffer, reallocating its color, depth and stencil
+/// planes cleared. A viewport or scissor box still covering the whole old
+/// drawing buffer follows the new size, as if the context had been created
+/// at it.
 pub fn ctx_resize(ctx: u32, width: u32, height: u32) -> u32 {
     clear_last_error();
     let mut reg = get_registry().borrow_mut();
@@ -156,11 +195,145 @@ pub fn ctx_resize(ctx: u32, width: u32, height: u32) -> u32 {
             return ERR_INVALID_HANDLE;
         }
     };
-    ctx_obj.default_framebuffer =
-        crate::wasm_gl_emu::OwnedFramebuffer::new(&mut ctx_obj.kernel, width, height);
+    if width > MAX_DRAWING_BUFFER_SIZE || height > MAX_DRAWING_BUFFER_SIZE {
+        set_last_error(&format!(
+            "drawing buffer size {}x{} exceeds {}",
+            width, height, MAX_DRAWING_BUFFER_SIZE
+        ));
+        return ERR_INVALID_ARGS;
+    }
+
+    let old = &ctx_obj.default_framebuffer;
+    let (old_handle, internal_format) = (old.gpu_handle, old.internal_format);
+    let whole = (0, 0, old.width, old.height);
+    ctx_obj.default_framebuffer = crate::wasm_gl_emu::OwnedFramebuffer::new_with_format(
+        &mut ctx_obj.kernel,
+        width,
+        height,
+        internal_format,
+    );
+    ctx_obj.kernel.destroy_buffer(old_handle);
+
+    if ctx_obj.viewport == whole {
+        ctx_obj.viewport = (0, 0, width, height);
+    }
+    if ctx_obj.scissor_box == whole {
+        ctx_obj.scissor_box = (0, 0, width, height);
+    }
     ERR_OK
 }
 
+/// Set a pixel storage parameter. UNPACK_PREMULTIPLY_ALPHA_WEBGL changes
+/// uploads, and the unpack alignment, row length and skips lay out
+/// `texSubImage2D` sources; the other WebGL parameters are accepted and have
+/// no effect yet.
+pub fn ctx_pixel_storei(ctx: u32, pname: u32, param: i32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    match pname {
+        GL_UNPACK_PREMULTIPLY_ALPHA_WEBGL => {
+            ctx_obj.unpack_premultiply_alpha = param != 0;
+        }
+        GL_UNPACK_ALIGNMENT => {
+            if matches!(param, 1 | 2 | 4 | 8) {
+                ctx_obj.unpack_layout.alignment = param as u32;
+            } else {
+                ctx_obj.set_error(GL_INVALID_VALUE);
+            }
+        }
+        GL_UNPACK_ROW_LENGTH | GL_UNPACK_SKIP_PIXELS | GL_UNPACK_SKIP_ROWS if param < 0 => {
+            ctx_obj.set_error(GL_INVALID_VALUE);
+        }
+        GL_UNPACK_ROW_LENGTH => ctx_obj.unpack_layout.row_length = param as u32,
+        GL_UNPACK_SKIP_PIXELS => ctx_obj.unpack_layout.skip_pixels = param as u32,
+        GL_UNPACK_SKIP_ROWS => ctx_obj.unpack_layout.skip_rows = param as u32,
+        GL_PACK_ALIGNMENT
+        | GL_UNPACK_FLIP_Y_WEBGL
+        | GL_UNPACK_COLORSPACE_CONVERSION_WEBGL
+        | GL_PACK_ROW_LENGTH
+        | GL_PACK_SKIP_PIXELS
+        | GL_PACK_SKIP_ROWS
+        | GL_UNPACK_IMAGE_HEIGHT
+        | GL_UNPACK_SKIP_IMAGES => {}
+        _ => ctx_obj.set_error(GL_INVALID_ENUM),
+    }
+    ERR_OK
+}
+
+/// Present the default framebuffer, as a browser does after each animation
+/// frame. Unless the context was created with `preserveDrawingBuffer`, the
+/// drawing buffer is then cleared to its initial contents regardless of the
+/// scissor test and write masks.
+///
+/// Returns a pointer to the presented frame as the compositor sees it:
+/// RGBA8 rows bottom to top, with premultiplied colors and opaque alpha for
+/// a context without alpha. A drawing buffer created with
+/// `premultipliedAlpha: false` is premultiplied here, as canvas compositing
+/// does. Returns 0 on failure (check last error).
+pub fn ctx_present(ctx: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return 0;
+        }
+    };
+
+    let (width, height) = (
+        ctx_obj.default_framebuffer.width,
+        ctx_obj.default_framebuffer.height,
+    );
+    let ptr = ctx_obj.alloc_blob(width * height * 4);
+    let frame =
+        unsafe { std::slice::from_raw_parts_mut(ptr as *mut u8, (width * height * 4) as usize) };
+    let Some(src_buffer) = ctx_obj
+        .kernel
+        .get_buffer(ctx_obj.default_framebuffer.gpu_handle)
+    else {
+        set_last_error("default framebuffer not found in kernel");
+        return 0;
+    };
+    crate::wasm_gl_emu::TransferEngine::read_pixels(
+        &crate::wasm_gl_emu::TransferRequest {
+            src_buffer,
+            dst_format: TextureFormat::Rgba8Unorm,
+            dst_layout: crate::wasm_gl_emu::StorageLayout::Linear,
+            x: 0,
+            y: 0,
+            width,
+            height,
+        },
+        frame,
+    );
+    for pixel in frame.chunks_exact_mut(4) {
+        if !ctx_obj.attributes.alpha {
+            pixel[3] = 255;
+        } else if !ctx_obj.attributes.premultiplied_alpha {
+            let alpha = pixel[3] as u32;
+            for channel in &mut pixel[..3] {
+                *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+            }
+        }
+    }
+
+    if !ctx_obj.attributes.preserve_drawing_buffer {
+        let handle = ctx_obj.default_framebuffer.gpu_handle;
+        ctx_obj.kernel.clear(handle, [0.0; 4]);
+        ctx_obj.default_framebuffer.clear_depth(1.0, true);
+        ctx_obj.default_framebuffer.clear_stencil(0, 0xFF);
+    }
+    ptr
+}
+
 pub fn ctx_scissor(ctx: u32, x: i32, y: i32, width: u32, height: u32) -> u32 {
     clear_last_error();
     let mut reg = get_registry().borrow_mut();
@@ -220,9 +393,25 @@ pub fn ctx_color_mask(ctx: u32, r: bool, g: bool, b: bool, a: bool) -> u32 {
     ERR_OK
 }
 
+/// Set the sample coverage value (clamped to [0, 1]) and inversion.
+pub fn ctx_sample_coverage(ctx: u32, value: f32, invert: bool) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    ctx_obj.sample_coverage_value = value.clamp(0.0, 1.0);
+    ctx_obj.sample_coverage_invert = invert;
+    ERR_OK
+}
+
 pub fn ctx_stencil_func(ctx: u32, func: u32, ref_: i32, mask: u32) -> u32 {
     // Sets both front and back
-    ctx_stencil_func_separate(ctx, 0x0408, func, ref_, mask) // GL_FRONT_AND_BACK
+    ctx_stencil_func_separate(ctx, GL_FRONT_AND_BACK, func, ref_, mask)
 }
 
 pub fn ctx_stencil_func_separate(ctx: u32, face: u32, func: u32, ref_: i32, mask: u32) -> u32 {
@@ -236,13 +425,12 @@ pub fn ctx_stencil_func_separate(ctx: u32, face: u32, func: u32, ref_: i32, mask
         }
     };
 
-    // GL_FRONT = 0x0404, GL_BACK = 0x0405, GL_FRONT_AND_BACK = 0x0408
-    if face == 0x0404 || face == 0x0408 {
+    if face == GL_FRONT || face == GL_FRONT_AND_BACK {
         ctx_obj.stencil_state.front.func = func;
         ctx_obj.stencil_state.front.ref_val = ref_;
         ctx_obj.stencil_state.front.mask = mask;
     }
-    if face == 0x0405 || face == 0x0408 {
+    if face == GL_BACK || face == GL_FRONT_AND_BACK {
         ctx_obj.stencil_state.back.func = func;
         ctx_obj.stencil_state.back.ref_val = ref_;
         ctx_obj.stencil_state.back.mask = mask;
@@ -252,7 +440,7 @@ pub fn ctx_stencil_func_separate(ctx: u32, face: u32, func: u32, ref_: i32, mask
 
 pub fn ctx_stencil_op(ctx: u32, fail: u32, zfail: u32, zpass: u32) -> u32 {
     // Sets both front and back
-    ctx_stencil_op_separate(ctx, 0x0408, fail, zfail, zpass)
+    ctx_stencil_op_separate(ctx, GL_FRONT_AND_BACK, fail, zfail, zpass)
 }
 
 pub fn ctx_stencil_op_separate(ctx: u32, face: u32, fail: u32, zfail: u32, zpass: u32) -> u32 {
@@ -266,13 +454,12 @@ pub fn ctx_stencil_op_separate(ctx: u32, face: u32, fail: u32, zfail: u32, zpass
         }
     };
 
-    // GL_FRONT = 0x0404, GL_BACK = 0x0405, GL_FRONT_AND_BACK = 0x0408
-    if face == 0x0404 || face == 0x0408 {
+    if face == GL_FRONT || face == GL_FRONT_AND_BACK {
         ctx_obj.stencil_state.front.fail = fail;
         ctx_obj.stencil_state.front.zfail = zfail;
         ctx_obj.stencil_state.front.zpass = zpass;
     }
-    if face == 0x0405 || face == 0x0408 {
+    if face == GL_BACK || face == GL_FRONT_AND_BACK {
         ctx_obj.stencil_state.back.fail = fail;
         ctx_obj.stencil_state.back.zfail = zfail;
         ctx_obj.stencil_state.back.zpass = zpass;
@@ -282,7 +469,7 @@ pub fn ctx_stencil_op_separate(ctx: u32, face: u32, fail: u32, zfail: u32, zpass
 
 pub fn ctx_stencil_mask(ctx: u32, mask: u32) -> u32 {
     // Sets both front and back
-    ctx_stencil_mask_separate(ctx, 0x0408, mask)
+    ctx_stencil_mask_separate(ctx, GL_FRONT_AND_BACK, mask)
 }
 
 pub fn ctx_stencil_mask_separate(ctx: u32, face: u32, mask: u32) -> u32 {
@@ -296,10 +483,10 @@ pub fn ctx_stencil_mask_separate(ctx: u32, face: u32, mask: u32) -> u32 {
         }
     };
 
-    if face == 0x0404 || face == 0x0408 {
+    if face == GL_FRONT || face == GL_FRONT_AND_BACK {
         ctx_obj.stencil_state.front.write_mask = mask;
     }
-    if face == 0x0405 || face == 0x0408 {
+    if face == GL_BACK || face == GL_FRONT_AND_BACK {
         ctx_obj.stencil_state.back.write_mask = mask;
     }
     ERR_OK
@@ -315,12 +502,11 @@ pub fn ctx_active_texture(ctx: u32, texture: u32) -> u32 {
             return ERR_INVALID_HANDLE;
         }
     };
-    // texture is GL_TEXTURE0 + i
-    if !(0x84C0..=0x84DF).contains(&texture) {
+    if !(GL_TEXTURE0..=GL_TEXTURE31).contains(&texture) {
         set_last_error("invalid texture unit");
         return ERR_INVALID_ARGS;
     }
-    ctx_obj.active_texture_unit = texture - 0x84C0;
+    ctx_obj.active_texture_unit = texture - GL_TEXTURE0;
     ERR_OK
 }
 
@@ -340,6 +526,9 @@ pub fn ctx_enable(ctx: u32, cap: u32) -> u32 {
         GL_BLEND => ctx_obj.blend_state.enabled = true,
         GL_STENCIL_TEST => ctx_obj.stencil_state.enabled = true,
         GL_CULL_FACE => ctx_obj.cull_face_enabled = true,
+        GL_DITHER => ctx_obj.dither_enabled = true,
+        GL_SAMPLE_ALPHA_TO_COVERAGE => ctx_obj.sample_alpha_to_coverage_enabled = true,
+        GL_SAMPLE_COVERAGE => ctx_obj.sample_coverage_enabled = true,
         _ => {
             set_last_error("unsupported capability");
             return ERR_NOT_IMPLEMENTED;
@@ -362,6 +551,9 @@ pub fn ctx_is_enabled(ctx: u32, cap: u32) -> i32 {
         GL_BLEND => ctx_obj.blend_state.enabled,
         GL_STENCIL_TEST => ctx_obj.stencil_state.enabled,
         GL_CULL_FACE => ctx_obj.cull_face_enabled,
+        GL_DITHER => ctx_obj.dither_enabled,
+        GL_SAMPLE_ALPHA_TO_COVERAGE => ctx_obj.sample_alpha_to_coverage_enabled,
+        GL_SAMPLE_COVERAGE => ctx_obj.sample_coverage_enabled,
         _ => false,
     };
     if val {
@@ -387,6 +579,9 @@ pub fn ctx_disable(ctx: u32, cap: u32) -> u32 {
         GL_BLEND => ctx_obj.blend_state.enabled = false,
         GL_STENCIL_TEST => ctx_obj.stencil_state.enabled = false,
         GL_CULL_FACE => ctx_obj.cull_face_enabled = false,
+        GL_DITHER => ctx_obj.dither_enabled = false,
+        GL_SAMPLE_ALPHA_TO_COVERAGE => ctx_obj.sample_alpha_to_coverage_enabled = false,
+        GL_SAMPLE_COVERAGE => ctx_obj.sample_coverage_enabled = false,
         _ => {
             set_last_error("unsupported capability");
             return ERR_NOT_IMPLEMENTED;
@@ -423,6 +618,52 @@ pub fn ctx_front_face(ctx: u32, mode: u32) -> u32 {
     ERR_OK
 }
 
+/// Select a debug visualization for subsequent draws (0 turns it off).
+pub fn ctx_set_debug_view(ctx: u32, mode: u32) -> u32 {
+    clear_last_error();
+    let view = match crate::wasm_gl_emu::DebugView::from_u32(mode) {
+        Some(v) => v,
+        None => {
+            set_last_error(&format!("invalid debug view {}", mode));
+            return ERR_INVALID_ARGS;
+        }
+    };
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    ctx_obj.debug_view = view;
+    ERR_OK
+}
+
+/// Forward pipeline stages of subsequent draws to the host's hooks (bit0 =
+/// vertices after the vertex shader, bit1 = fragments before the depth and
+/// stencil tests); 0 removes the hooks.
+pub fn ctx_set_pipeline_hooks(ctx: u32, stages: u32) -> u32 {
+    clear_last_error();
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    let hooks = (stages & 3 != 0).then(|| {
+        Box::new(crate::wasm_gl_emu::hooks::HostHooks {
+            ctx,
+            vertex: stages & 1 != 0,
+            fragment: stages & 2 != 0,
+        }) as Box<dyn crate::wasm_gl_emu::PipelineHooks>
+    });
+    ctx_obj.rasterizer.set_hooks(hooks);
+    ERR_OK
+}
+
 /// Get the last GL error.
 pub fn ctx_get_error(ctx: u32) -> u32 {
     clear_last_error();
@@ -441,6 +682,20 @@ pub fn ctx_get_error(ctx: u32) -> u32 {
     err
 }
 
+/// Get the attributes the context was created with, packed as for
+/// [`ContextAttributes::from_bits`], or 0 for an invalid handle.
+pub fn ctx_get_context_attributes(ctx: u32) -> u32 {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    match reg.contexts.get(&ctx) {
+        Some(ctx_obj) => ctx_obj.attributes.to_bits(),
+        None => {
+            set_last_error("invalid context handle");
+            0
+        }
+    }
+}
+
 /// Get a GL parameter value.
 /// Returns a pointer to an ephemeral payload containing the parameter value(s).
 ///
@@ -466,30 +721,34 @@ pub fn ctx_get_parameter(ctx_handle: u32, pname: u32) -> u32 {
     };
 
     match pname {
-        0x1F00 => {
-            // GL_VENDOR
+        GL_VENDOR => {
             let s = "GitHub";
             let ptr = ctx.alloc_string(s.len() as u32);
             unsafe { std::ptr::copy_nonoverlapping(s.as_ptr(), ptr as *mut u8, s.len()) };
             ptr
         }
-        0x1F01 => {
-            // GL_RENDERER
+        GL_RENDERER => {
             let s = "WebGL2 WASM (Gemini)";
             let ptr = ctx.alloc_string(s.len() as u32);
             unsafe { std::ptr::copy_nonoverlapping(s.as_ptr(), ptr as *mut u8, s.len()) };
             ptr
         }
-        0x1F02 => {
-            // GL_VERSION
-            let s = "WebGL 2.0 (OpenGL ES 3.0)";
+        GL_VERSION => {
+            let s = if ctx.webgl1 {
+                "WebGL 1.0 (OpenGL ES 2.0)"
+            } else {
+                "WebGL 2.0 (OpenGL ES 3.0)"
+            };
             let ptr = ctx.alloc_string(s.len() as u32);
             unsafe { std::ptr::copy_nonoverlapping(s.as_ptr(), ptr as *mut u8, s.len()) };
             ptr
         }
-        0x8B8C => {
-            // GL_SHADING_LANGUAGE_VERSION
-            let s = "WebGL GLSL ES 3.00";
+        GL_SHADING_LANGUAGE_VERSION => {
+            let s = if ctx.webgl1 {
+                "WebGL GLSL ES 1.0"
+            } else {
+                "WebGL GLSL ES 3.00"
+            };
             let ptr = ctx.alloc_string(s.len() as u32);
             unsafe { std::ptr::copy_nonoverlapping(s.as_ptr(), ptr as *mut u8, s.len()) };
             ptr
@@ -503,6 +762,15 @@ pub fn ctx_get_parameter(ctx_handle: u32, pname: u32) -> u32 {
             dest[3] = ctx.viewport.3 as i32;
             ptr
         }
+        GL_SCISSOR_BOX => {
+            let ptr = ctx.alloc_small(16);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 4) };
+            dest[0] = ctx.scissor_box.0;
+            dest[1] = ctx.scissor_box.1;
+            dest[2] = ctx.scissor_box.2 as i32;
+            dest[3] = ctx.scissor_box.3 as i32;
+            ptr
+        }
         GL_COLOR_CLEAR_VALUE => {
             let ptr = ctx.alloc_small(16);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut f32, 4) };
@@ -512,8 +780,46 @@ pub fn ctx_get_parameter(ctx_handle: u32, pname: u32) -> u32 {
             dest[3] = ctx.clear_color[3];
             ptr
         }
-        0x0C23 => {
-            // COLOR_WRITEMASK
+        GL_BLEND_COLOR => {
+            let ptr = ctx.alloc_small(16);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut f32, 4) };
+            dest.copy_from_slice(&ctx.blend_state.color);
+            ptr
+        }
+        GL_BLEND_SRC_RGB
+        | GL_BLEND_DST_RGB
+        | GL_BLEND_SRC_ALPHA
+        | GL_BLEND_DST_ALPHA
+        | GL_BLEND_EQUATION_RGB
+        | GL_BLEND_EQUATION_ALPHA => {
+            let blend = &ctx.blend_state;
+            let value = match pname {
+                GL_BLEND_SRC_RGB => blend.src_rgb,
+                GL_BLEND_DST_RGB => blend.dst_rgb,
+                GL_BLEND_SRC_ALPHA => blend.src_alpha,
+                GL_BLEND_DST_ALPHA => blend.dst_alpha,
+                GL_BLEND_EQUATION_RGB => blend.eq_rgb,
+                _ => blend.eq_alpha,
+            };
+            let ptr = ctx.alloc_small(4);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
+            dest[0] = value as i32;
+            ptr
+        }
+        GL_DEPTH_CLEAR_VALUE => {
+            let ptr = ctx.alloc_small(4);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut f32, 1) };
+            dest[0] = ctx.clear_depth;
+            ptr
+        }
+        GL_ALIASED_POINT_SIZE_RANGE => {
+            let ptr = ctx.alloc_small(8);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut f32, 2) };
+            dest[0] = 1.0;
+            dest[1] = crate::wasm_gl_emu::rasterizer::MAX_POINT_SIZE;
+            ptr
+        }
+        GL_COLOR_WRITEMASK => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut u8, 4) };
             dest[0] = ctx.color_mask.r as u8;
@@ -522,160 +828,195 @@ pub fn ctx_get_parameter(ctx_handle: u32, pname: u32) -> u32 {
             dest[3] = ctx.color_mask.a as u8;
             ptr
         }
-        0x0B72 => {
-            // DEPTH_WRITEMASK
+        GL_DEPTH_WRITEMASK => {
             let ptr = ctx.alloc_small(1);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut u8, 1) };
             dest[0] = ctx.depth_state.mask as u8;
             ptr
         }
-        0x0B98 => {
-            // STENCIL_WRITEMASK
+        GL_SAMPLE_COVERAGE_VALUE => {
+            let ptr = ctx.alloc_small(4);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut f32, 1) };
+            dest[0] = ctx.sample_coverage_value;
+            ptr
+        }
+        GL_SAMPLE_COVERAGE_INVERT => {
+            let ptr = ctx.alloc_small(1);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut u8, 1) };
+            dest[0] = ctx.sample_coverage_invert as u8;
+            ptr
+        }
+        GL_STENCIL_WRITEMASK => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.front.write_mask as i32;
             ptr
         }
-        0x8CA5 => {
-            // STENCIL_BACK_WRITEMASK
+        GL_STENCIL_BACK_WRITEMASK => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.back.write_mask as i32;
             ptr
         }
-        0x0B74 => {
-            // DEPTH_FUNC
+        GL_DEPTH_FUNC => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.depth_state.func as i32;
             ptr
         }
-        0x0B92 => {
-            // STENCIL_FUNC
+        GL_STENCIL_FUNC => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.front.func as i32;
             ptr
         }
-        0x0B93 => {
-            // STENCIL_VALUE_MASK
+        GL_STENCIL_VALUE_MASK => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.front.mask as i32;
             ptr
         }
-        0x0B97 => {
-            // STENCIL_REF
+        GL_STENCIL_REF => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.front.ref_val as i32;
             ptr
         }
-        0x8800 => {
-            // STENCIL_BACK_FUNC
+        GL_STENCIL_BACK_FUNC => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.back.func as i32;
             ptr
         }
-        0x8CA4 => {
-            // STENCIL_BACK_VALUE_MASK
+        GL_STENCIL_BACK_VALUE_MASK => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.back.mask as i32;
             ptr
         }
-        0x8CA3 => {
-            // STENCIL_BACK_REF
+        GL_STENCIL_BACK_REF => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.back.ref_val as i32;
             ptr
         }
-        0x0B94 => {
-            // STENCIL_FAIL
+        GL_STENCIL_FAIL => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.front.fail as i32;
             ptr
         }
-        0x0B95 => {
-            // STENCIL_PASS_DEPTH_FAIL
+        GL_STENCIL_PASS_DEPTH_FAIL => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.front.zfail as i32;
             ptr
         }
-        0x0B96 => {
-            // STENCIL_PASS_DEPTH_PASS
+        GL_STENCIL_PASS_DEPTH_PASS => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.front.zpass as i32;
             ptr
         }
-        0x8801 => {
-            // STENCIL_BACK_FAIL
+        GL_STENCIL_BACK_FAIL => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.back.fail as i32;
             ptr
         }
-        0x8802 => {
-            // STENCIL_BACK_PASS_DEPTH_FAIL
+        GL_STENCIL_BACK_PASS_DEPTH_FAIL => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.back.zfail as i32;
             ptr
         }
-        0x8803 => {
-            // STENCIL_BACK_PASS_DEPTH_PASS
+        GL_STENCIL_BACK_PASS_DEPTH_PASS => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.stencil_state.back.zpass as i32;
             ptr
         }
-        0x8CA6 | 0x8CAA => {
-            // DRAW_FRAMEBUFFER_BINDING or READ_FRAMEBUFFER_BINDING
+        GL_ALPHA_BITS | GL_DEPTH_BITS | GL_STENCIL_BITS | GL_SAMPLES | GL_SAMPLE_BUFFERS
+            if ctx.bound_draw_framebuffer.is_none() =>
+        {
+            // Bit depths and sample count of the default framebuffer, which
+            // is never multisampled
+            let attributes = ctx.attributes;
+            let depth_bits = match ctx.default_depth_precision {
+                // Reported like the 24-bit buffer browsers allocate
+                crate::wasm_gl_emu::rasterizer::DepthPrecision::Float32 => 24,
+                precision => precision.bits() as i32,
+            };
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
-            dest[0] = if pname == 0x8CA6 {
+            dest[0] = match pname {
+                GL_ALPHA_BITS => 8 * attributes.alpha as i32,
+                GL_DEPTH_BITS => depth_bits * attributes.depth as i32,
+                GL_STENCIL_BITS => 8 * attributes.stencil as i32,
+                _ => 0,
+            };
+            ptr
+        }
+        GL_DRAW_FRAMEBUFFER_BINDING | GL_READ_FRAMEBUFFER_BINDING => {
+            let ptr = ctx.alloc_small(4);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
+            dest[0] = if pname == GL_DRAW_FRAMEBUFFER_BINDING {
                 ctx.bound_draw_framebuffer.unwrap_or(0) as i32
             } else {
                 ctx.bound_read_framebuffer.unwrap_or(0) as i32
             };
             ptr
         }
-        0x8CA7 => {
-            // RENDERBUFFER_BINDING
+        GL_RENDERBUFFER_BINDING => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             dest[0] = ctx.bound_renderbuffer.unwrap_or(0) as i32;
             ptr
         }
-        0x8869 => {
-            // MAX_VERTEX_ATTRIBS
+        GL_PROGRAM_PIPELINE_BINDING => {
+            let ptr = ctx.alloc_small(4);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
+            dest[0] = ctx.bound_program_pipeline.unwrap_or(0) as i32;
+            ptr
+        }
+        GL_MAX_VERTEX_ATTRIBS => {
+            let ptr = ctx.alloc_small(4);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
+            dest[0] = ctx.limits.max_vertex_attribs as i32;
+            ptr
+        }
+        GL_MAX_VARYING_VECTORS => {
+            let ptr = ctx.alloc_small(4);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
+            dest[0] = ctx.limits.max_varying_vectors as i32;
+            ptr
+        }
+        GL_MAX_VARYING_COMPONENTS => {
+            let ptr = ctx.alloc_small(4);
+            let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
+            dest[0] = ctx.limits.max_varying_vectors as i32 * 4;
+            ptr
+        }
+        GL_MAX_VERTEX_UNIFORM_VECTORS | GL_MAX_FRAGMENT_UNIFORM_VECTORS => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
-            dest[0] = 16;
+            dest[0] = ctx.limits.max_uniform_vectors as i32;
             ptr
         }
-        0x8824 => {
-            // MAX_DRAW_BUFFERS
+        GL_MAX_DRAW_BUFFERS | GL_MAX_COLOR_ATTACHMENTS => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
-            dest[0] = 8;
+            dest[0] = ctx.limits.max_draw_buffers as i32;
             ptr
         }
-        0x8CDF => {
-            // MAX_COLOR_ATTACHMENTS
+        GL_MAX_TEXTURE_SIZE | GL_MAX_RENDERBUFFER_SIZE => {
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
-            dest[0] = 8;
+            dest[0] = ctx.limits.max_texture_size as i32;
             ptr
         }
-        0x8825..=0x882C => {
-            // DRAW_BUFFER0..7
-            let idx = (pname - 0x8825) as usize;
+        GL_DRAW_BUFFER0..=GL_DRAW_BUFFER7 => {
+            let idx = (pname - GL_DRAW_BUFFER0) as usize;
             let ptr = ctx.alloc_small(4);
             let dest = unsafe { std::slice::from_raw_parts_mut(ptr as *mut i32, 1) };
             if let Some(fb_handle) = ctx.bound_draw_framebuffer {
@@ -690,7 +1031,7 @@ pub fn ctx_get_parameter(ctx_handle: u32, pname: u32) -> u32 {
             ptr
         }
         _ => {
-            set_last_error("unsupported parameter");
+            set_last_error(&format!("unsupported parameter: {}", describe_enum(pname)));
             0
         }
     }
@@ -723,7 +1064,10 @@ pub fn ctx_clear_buffer_fv(ctx: u32, buffer: u32, drawbuffer: i32, ptr: u32) ->
         let handle = ctx_obj.get_color_attachment_handle_at(drawbuffer as usize);
         if handle.is_valid() {
             let (vw, vh) = ctx_obj.get_attachment_size(handle);
-            let values = unsafe { std::slice::from_raw_parts(ptr as *const f32, 4) };
+            let values = match crate::guest_mem::read_slice(ptr as *const f32, 4) {
+                Ok(v) => v,
+                Err(code) => return code,
+            };
             ctx_obj.kernel.clear_rect(
                 handle,
                 [values[0], values[1], values[2], values[3]],
@@ -757,7 +1101,10 @@ pub fn ctx_clear_buffer_iv(ctx: u32, buffer: u32, drawbuffer: i32, ptr: u32) ->
                 .and_then(|f| f.block_copy_size(None))
                 .unwrap_or(4) as usize;
 
-            let values = unsafe { std::slice::from_raw_parts(ptr as *const i32, 4) };
+            let values = match crate::guest_mem::read_slice(ptr as *const i32, 4) {
+                Ok(v) => v,
+                Err(code) => return code,
+            };
             let mut pixel_bytes = vec![0u8; bpp];
 
             if bpp == 16 {
@@ -811,7 +1158,10 @@ pub fn ctx_clear_buffer_uiv(ctx: u32, buffer: u32, drawbuffer: i32, ptr: u32) ->
                 .and_then(|f| f.block_copy_size(None))
                 .unwrap_or(4) as usize;
 
-            let values = unsafe { std::slice::from_raw_parts(ptr as *const u32, 4) };
+            let values = match crate::guest_mem::read_slice(ptr as *const u32, 4) {
+                Ok(v) => v,
+                Err(code) => return code,
+            };
             let mut pixel_bytes = vec![0u8; bpp];
 
             if bpp == 16 {
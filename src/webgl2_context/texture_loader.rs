@@ -0,0 +1,304 @@
+//! Texture containers: KTX2
+//!
+//! Parses [KTX2](https://registry.khronos.org/KTX/specs/2.0/ktxspec.v2.html)
+//! files and uploads every mip level to the bound 2D texture, so assets from
+//! a modern pipeline load without host-side unpacking.
+//!
+//! Supported are single-layer, single-face 2D textures without
+//! supercompression, in the uncompressed Vulkan formats listed in
+//! [`gl_format`]. sRGB formats upload as their UNORM counterparts, as the
+//! emulator stores no colour space. Basis Universal payloads (BasisLZ
+//! supercompression or UASTC) are not supported: they need a transcoder this
+//! crate does not include, and are rejected with that reason, as are
+//! Zstandard and ZLIB supercompression.
+
+use super::registry::{clear_last_error, get_registry, set_last_error};
+use super::textures::tex_image_2d_data;
+use super::types::*;
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+const HEADER_BYTES: usize = 80;
+const LEVEL_INDEX_BYTES: usize = 24;
+
+const SUPERCOMPRESSION_NONE: u32 = 0;
+const SUPERCOMPRESSION_BASIS_LZ: u32 = 1;
+
+/// DFD colour model of UASTC payloads.
+const KHR_DF_MODEL_UASTC: u8 = 166;
+
+/// A parsed KTX2 file borrowing its level data from the input.
+#[derive(Debug)]
+pub struct Ktx2<'a> {
+    pub vk_format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub supercompression: u32,
+    /// Level data, base level first.
+    pub levels: Vec<&'a [u8]>,
+    /// Colour model of the first data format descriptor block.
+    color_model: Option<u8>,
+}
+
+impl<'a> Ktx2<'a> {
+    /// Parse the header, level index and level data of a KTX2 file.
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_BYTES || bytes[..12] != IDENTIFIER {
+            return Err("not a KTX2 file".to_string());
+        }
+        let u32_at = |off: usize| u32::from_le_bytes(bytes[off..off + 4].try_into().unwrap());
+        let u64_at = |off: usize| u64::from_le_bytes(bytes[off..off + 8].try_into().unwrap());
+
+        let vk_format = u32_at(12);
+        let width = u32_at(20);
+        let height = u32_at(24).max(1);
+        let (depth, layers, faces) = (u32_at(28), u32_at(32), u32_at(36));
+        let level_count = u32_at(40).max(1) as usize;
+        let supercompression = u32_at(44);
+        let (dfd_offset, dfd_len) = (u32_at(48) as usize, u32_at(52) as usize);
+
+        if width == 0 || depth > 1 || layers > 1 || faces != 1 {
+            return Err(format!(
+                "only 2D textures are supported (width {}, depth {}, layers {}, faces {})",
+                width, depth, layers, faces
+            ));
+        }
+
+        // The count comes from the file, so the size may overflow on wasm32
+        let index_end = level_count
+            .checked_mul(LEVEL_INDEX_BYTES)
+            .and_then(|len| len.checked_add(HEADER_BYTES));
+        if index_end.is_none_or(|end| bytes.len() < end) {
+            return Err("truncated level index".to_string());
+        }
+        let mut levels = Vec::with_capacity(level_count);
+        for level in 0..level_count {
+            let entry = HEADER_BYTES + level * LEVEL_INDEX_BYTES;
+            let (offset, len) = (u64_at(entry), u64_at(entry + 8));
+            let data = usize::try_from(offset)
+                .ok()
+                .zip(usize::try_from(len).ok())
+                .and_then(|(offset, len)| bytes.get(offset..offset.checked_add(len)?));
+            match data {
+                Some(data) => levels.push(data),
+                None => return Err(format!("level {} lies outside the file", level)),
+            }
+        }
+
+        // The first descriptor block starts after the DFD's total size word;
+        // its colour model is byte 8 of the block
+        let color_model = if dfd_len >= 16 {
+            dfd_offset
+                .checked_add(4 + 8)
+                .and_then(|at| bytes.get(at))
+                .copied()
+        } else {
+            None
+        };
+
+        Ok(Ktx2 {
+            vk_format,
+            width,
+            height,
+            supercompression,
+            levels,
+            color_model,
+        })
+    }
+
+    /// Whether the payload is Basis Universal (ETC1S/BasisLZ or UASTC).
+    pub fn is_basis(&self) -> bool {
+        self.supercompression == SUPERCOMPRESSION_BASIS_LZ
+            || self.color_model == Some(KHR_DF_MODEL_UASTC)
+    }
+}
+
+/// GL internal format and type of an uncompressed Vulkan format.
+pub fn gl_format(vk_format: u32) -> Option<(u32, u32)> {
+    Some(match vk_format {
+        // R8G8B8A8_UNORM, R8G8B8A8_SRGB
+        37 | 43 => (GL_RGBA8, GL_UNSIGNED_BYTE),
+        // R8G8B8A8_UINT, R8G8B8A8_SINT
+        41 => (GL_RGBA8UI, GL_UNSIGNED_BYTE),
+        42 => (GL_RGBA8I, GL_BYTE),
+        // R16_SFLOAT, R16G16_SFLOAT, R16G16B16A16_SFLOAT
+        76 => (GL_R16F, GL_HALF_FLOAT),
+        83 => (GL_RG16F, GL_HALF_FLOAT),
+        97 => (GL_RGBA16F, GL_HALF_FLOAT),
+        // R32_SFLOAT, R32G32_SFLOAT, R32G32B32A32_SFLOAT
+        100 => (GL_R32F, GL_FLOAT),
+        103 => (GL_RG32F, GL_FLOAT),
+        109 => (GL_RGBA32F, GL_FLOAT),
+        // R32_UINT, R32G32B32A32_UINT
+        98 => (GL_R32UI, GL_UNSIGNED_INT),
+        107 => (GL_RGBA32UI, GL_UNSIGNED_INT),
+        _ => return None,
+    })
+}
+
+/// Upload every level of a KTX2 file to the bound 2D texture.
+pub fn tex_image_ktx2(ctx: u32, target: u32, bytes: &[u8]) -> u32 {
+    clear_last_error();
+    if target != GL_TEXTURE_2D {
+        let mut reg = get_registry().borrow_mut();
+        let Some(ctx_obj) = reg.contexts.get_mut(&ctx) else {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        };
+        set_last_error("invalid target for texImageKTX2");
+        ctx_obj.set_error(GL_INVALID_ENUM);
+        return ERR_GL;
+    }
+    let ktx = match Ktx2::parse(bytes) {
+        Ok(ktx) => ktx,
+        Err(msg) => {
+            set_last_error(&format!("invalid KTX2: {}", msg));
+            return ERR_INVALID_ARGS;
+        }
+    };
+    if ktx.is_basis() {
+        set_last_error(
+            "KTX2 Basis Universal textures need a transcoder, which this build does not include",
+        );
+        return ERR_NOT_IMPLEMENTED;
+    }
+    if ktx.supercompression != SUPERCOMPRESSION_NONE {
+        set_last_error(&format!(
+            "KTX2 supercompression scheme {} is not supported",
+            ktx.supercompression
+        ));
+        return ERR_NOT_IMPLEMENTED;
+    }
+    let (internal_format, type_) = match gl_format(ktx.vk_format) {
+        Some(format) => format,
+        None => {
+            set_last_error(&format!("KTX2 vkFormat {} is not supported", ktx.vk_format));
+            return ERR_NOT_IMPLEMENTED;
+        }
+    };
+
+    for (level, data) in ktx.levels.iter().enumerate() {
+        let code = tex_image_2d_data(
+            ctx,
+            level as i32,
+            internal_format as i32,
+            (ktx.width >> level).max(1),
+            (ktx.height >> level).max(1),
+            type_ as i32,
+            data,
+        );
+        if code != ERR_OK {
+            return code;
+        }
+    }
+    ERR_OK
+}
+
+/// [`tex_image_ktx2`] with the file at `ptr`/`len` in WASM linear memory.
+pub fn ctx_tex_image_ktx2(ctx: u32, target: u32, ptr: u32, len: u32) -> u32 {
+    let bytes = match crate::guest_mem::read(ptr, len) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
+    tex_image_ktx2(ctx, target, bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl2_context::framebuffers::{
+        ctx_bind_framebuffer, ctx_create_framebuffer, ctx_framebuffer_texture2d,
+    };
+    use crate::webgl2_context::readback::{read_pixels_rgba, Rect};
+    use crate::webgl2_context::registry::{create_context_with_flags, destroy_context};
+    use crate::webgl2_context::state::ctx_get_error;
+    use crate::webgl2_context::textures::{ctx_bind_texture, ctx_create_texture};
+
+    /// A KTX2 file with the given levels, stored smallest first as the spec
+    /// recommends.
+    fn ktx2(vk_format: u32, width: u32, height: u32, scheme: u32, levels: &[&[u8]]) -> Vec<u8> {
+        let mut out = IDENTIFIER.to_vec();
+        for word in [
+            vk_format,
+            1,
+            width,
+            height,
+            0,
+            0,
+            1,
+            levels.len() as u32,
+            scheme,
+        ] {
+            out.extend_from_slice(&word.to_le_bytes());
+        }
+        out.resize(HEADER_BYTES + levels.len() * LEVEL_INDEX_BYTES, 0);
+        for (level, data) in levels.iter().enumerate().rev() {
+            let offset = out.len() as u64;
+            let entry = HEADER_BYTES + level * LEVEL_INDEX_BYTES;
+            out[entry..entry + 8].copy_from_slice(&offset.to_le_bytes());
+            out[entry + 8..entry + 16].copy_from_slice(&(data.len() as u64).to_le_bytes());
+            out[entry + 16..entry + 24].copy_from_slice(&(data.len() as u64).to_le_bytes());
+            out.extend_from_slice(data);
+        }
+        out
+    }
+
+    #[test]
+    fn uploads_ktx2_levels() {
+        let base = [
+            255, 0, 0, 255, 0, 255, 0, 255, //
+            0, 0, 255, 255, 255, 255, 255, 255,
+        ];
+        let file = ktx2(37, 2, 2, 0, &[&base, &[9, 9, 9, 255]]);
+        let parsed = Ktx2::parse(&file).unwrap();
+        assert_eq!((parsed.width, parsed.height), (2, 2));
+        assert_eq!(parsed.levels, vec![&base[..], &[9, 9, 9, 255][..]]);
+
+        let ctx = create_context_with_flags(0, 2, 2);
+        let tex = ctx_create_texture(ctx);
+        ctx_bind_texture(ctx, GL_TEXTURE_2D, tex);
+        let uploaded = tex_image_ktx2(ctx, GL_TEXTURE_2D, &file);
+        let basis = tex_image_ktx2(ctx, GL_TEXTURE_2D, &ktx2(0, 2, 2, 1, &[&base]));
+        let unsupported = tex_image_ktx2(ctx, GL_TEXTURE_2D, &ktx2(131, 2, 2, 0, &[&base]));
+        let truncated = tex_image_ktx2(ctx, GL_TEXTURE_2D, &file[..file.len() - 1]);
+        let array = tex_image_ktx2(ctx, GL_TEXTURE_2D_ARRAY, &file);
+        let array_error = ctx_get_error(ctx);
+        let fb = ctx_create_framebuffer(ctx);
+        ctx_bind_framebuffer(ctx, GL_FRAMEBUFFER, fb);
+        ctx_framebuffer_texture2d(
+            ctx,
+            GL_FRAMEBUFFER,
+            GL_COLOR_ATTACHMENT0,
+            GL_TEXTURE_2D,
+            tex,
+            0,
+        );
+        let pixels = read_pixels_rgba(ctx, Rect::new(0, 0, 2, 2));
+        destroy_context(ctx);
+
+        assert_eq!(uploaded, ERR_OK);
+        assert_eq!(basis, ERR_NOT_IMPLEMENTED);
+        assert_eq!(unsupported, ERR_NOT_IMPLEMENTED);
+        assert_eq!(truncated, ERR_INVALID_ARGS);
+        assert_eq!((array, array_error), (ERR_GL, GL_INVALID_ENUM));
+        assert_eq!(pixels, Ok(base.to_vec()));
+    }
+
+    #[test]
+    fn rejects_level_counts_past_the_file() {
+        let mut file = ktx2(37, 1, 1, 0, &[&[1, 2, 3, 4]]);
+        // LEVEL_INDEX_BYTES * level_count wraps a 32-bit usize
+        file[40..44].copy_from_slice(&0x0AAA_AAABu32.to_le_bytes());
+        assert_eq!(
+            Ktx2::parse(&file).unwrap_err(),
+            "truncated level index".to_string()
+        );
+
+        // A DFD offset near the top of the address space has no colour model
+        let mut file = ktx2(37, 1, 1, 0, &[&[1, 2, 3, 4]]);
+        file[48..52].copy_from_slice(&u32::MAX.to_le_bytes());
+        file[52..56].copy_from_slice(&16u32.to_le_bytes());
+        assert!(!Ktx2::parse(&file).unwrap().is_basis());
+    }
+}
@@ -10,7 +10,7 @@ pub fn ctx_is_texture(ctx: u32, handle: u32) -> bool {
     }
     let reg = get_registry().borrow();
     if let Some(c) = reg.contexts.get(&ctx) {
-        c.textures.contains_key(&handle)
+        c.textures.get(&handle).is_some_and(|t| !t.deleted)
     } else {
         false
     }
@@ -37,6 +37,8 @@ pub fn ctx_create_texture(ctx: u32) -> u32 {
             wrap_s: GL_REPEAT,                    // GL_REPEAT (default)
             wrap_t: GL_REPEAT,                    // GL_REPEAT (default)
             wrap_r: GL_REPEAT,                    // GL_REPEAT (default)
+            lod_bias: 0.0,
+            deleted: false,
         },
     );
     tex_id
@@ -58,19 +60,13 @@ pub fn ctx_delete_texture(ctx: u32, tex: u32) -> u32 {
             return ERR_INVALID_HANDLE;
         }
     };
-    if let Some(tex_obj) = ctx.textures.remove(&tex) {
-        // Destroy all GPU buffers associated with this texture's mip levels
-        for level in tex_obj.levels.values() {
-            ctx.kernel.destroy_buffer(level.gpu_handle);
-        }
-    } else {
+    if !ctx.textures.contains_key(&tex) {
         set_last_error("texture not found");
         return ERR_INVALID_HANDLE;
     }
-    // If this was the bound texture, unbind it
-    if ctx.bound_texture == Some(tex) {
-        ctx.bound_texture = None;
-    }
+    // Unbinds it everywhere in this context; storage a framebuffer still
+    // holds is freed once detached
+    super::deletion::delete_texture(ctx, tex);
     ERR_OK
 }
 
@@ -115,7 +111,10 @@ pub fn ctx_tex_parameter_i(ctx: u32, target: u32, pname: u32, param: i32) -> u32
         GL_TEXTURE_WRAP_T => tex.wrap_t = param as u32,
         GL_TEXTURE_WRAP_R => tex.wrap_r = param as u32,
         _ => {
-            set_last_error(&format!("invalid texture parameter: 0x{:04X}", pname));
+            set_last_error(&format!(
+                "invalid texture parameter: {}",
+                describe_enum(pname)
+            ));
             return ERR_INVALID_ARGS;
         }
     }
@@ -123,6 +122,42 @@ pub fn ctx_tex_parameter_i(ctx: u32, target: u32, pname: u32, param: i32) -> u32
     ERR_OK
 }
 
+/// Set a float texture parameter. TEXTURE_LOD_BIAS keeps its fraction, every
+/// other parameter goes through [`ctx_tex_parameter_i`].
+/// Returns errno.
+pub fn ctx_tex_parameter_f(ctx: u32, target: u32, pname: u32, param: f32) -> u32 {
+    if pname != GL_TEXTURE_LOD_BIAS {
+        return ctx_tex_parameter_i(ctx, target, pname, param as i32);
+    }
+    clear_last_error();
+    if target != GL_TEXTURE_2D && target != GL_TEXTURE_3D && target != GL_TEXTURE_2D_ARRAY {
+        set_last_error("invalid texture target");
+        return ERR_INVALID_ARGS;
+    }
+    if !param.is_finite() {
+        set_last_error("TEXTURE_LOD_BIAS must be finite");
+        return ERR_INVALID_ARGS;
+    }
+
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    let Some(tex) = ctx_obj
+        .bound_texture
+        .and_then(|h| ctx_obj.textures.get_mut(&h))
+    else {
+        set_last_error("no texture bound");
+        return ERR_INVALID_ARGS;
+    };
+    tex.lod_bias = param;
+    ERR_OK
+}
+
 /// Bind a texture in the given context.
 /// Returns errno.
 pub fn ctx_bind_texture(ctx: u32, _target: u32, tex: u32) -> u32 {
@@ -136,7 +171,7 @@ pub fn ctx_bind_texture(ctx: u32, _target: u32, tex: u32) -> u32 {
                 return ERR_INVALID_HANDLE;
             }
         };
-        if !ctx_obj.textures.contains_key(&tex) {
+        if ctx_obj.textures.get(&tex).is_none_or(|t| t.deleted) {
             set_last_error("texture not found");
             return ERR_INVALID_HANDLE;
         }
@@ -158,46 +193,21 @@ pub fn ctx_bind_texture(ctx: u32, _target: u32, tex: u32) -> u32 {
     ERR_OK
 }
 
-/// Upload pixel data to a texture.
-/// ptr and len point to RGBA u8 pixel data in WASM linear memory.
-/// Returns errno.
-#[allow(clippy::too_many_arguments)]
-pub fn ctx_tex_image_2d(
-    ctx: u32,
-    _target: u32,
-    level: i32,
-    internal_format: i32,
-    width: u32,
-    height: u32,
-    _border: i32,
-    _format: i32,
-    _type_: i32,
-    ptr: u32,
-    len: u32,
-) -> u32 {
-    clear_last_error();
-
-    let mut reg = get_registry().borrow_mut();
-    let ctx_obj = match reg.contexts.get_mut(&ctx) {
-        Some(c) => c,
-        None => {
-            set_last_error("invalid context handle");
-            return ERR_INVALID_HANDLE;
+/// Multiply the color of RGBA8 pixels by their alpha, for uploads with
+/// UNPACK_PREMULTIPLY_ALPHA_WEBGL.
+fn premultiply_alpha(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        for channel in &mut pixel[..3] {
+            *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
         }
-    };
-
-    // Determine which texture to write to (bound or error)
-    let tex_handle = match ctx_obj.bound_texture {
-        Some(h) => h,
-        None => {
-            set_last_error("no texture bound");
-            return ERR_INVALID_ARGS;
-        }
-    };
+    }
+}
 
-    // Determine storage internal format from the requested internalFormat and type
-    let requested_internal = internal_format as u32;
-    let storage_internal_format = match (requested_internal, _type_ as u32) {
+/// Internal format a level uploaded with `internal_format` and `type_` is
+/// stored in.
+fn storage_format(internal_format: u32, type_: u32) -> u32 {
+    match (internal_format, type_) {
         (GL_R32F, _) => GL_R32F,
         (GL_RG32F, _) => GL_RG32F,
         (GL_RGBA32F, _) => GL_RGBA32F,
@@ -255,27 +265,177 @@ pub fn ctx_tex_image_2d(
         (GL_RGBA8, _) => GL_RGBA8,
         (v, _) if v == GL_RGBA => GL_RGBA8,
         _ => GL_RGBA8,
+    }
+}
+
+/// Widen 8-bit pixels of `format` to RGBA8 as sampling reads them: missing
+/// color channels are 0, luminance fills all three and a missing alpha is
+/// 255. Returns `None` for formats that do not widen.
+fn expand_to_rgba8(format: u32, pixels: &[u8]) -> Option<Vec<u8>> {
+    let expand: fn(&[u8]) -> [u8; 4] = match format {
+        GL_RED => |p| [p[0], 0, 0, 255],
+        GL_RG => |p| [p[0], p[1], 0, 255],
+        GL_RGB => |p| [p[0], p[1], p[2], 255],
+        GL_ALPHA => |p| [0, 0, 0, p[0]],
+        GL_LUMINANCE => |p| [p[0], p[0], p[0], 255],
+        GL_LUMINANCE_ALPHA => |p| [p[0], p[0], p[0], p[1]],
+        _ => return None,
+    };
+    let components = source_bytes_per_pixel(format, GL_UNSIGNED_BYTE)? as usize;
+    Some(pixels.chunks_exact(components).flat_map(expand).collect())
+}
+
+/// Read the source pixels of a `width`x`height`x`depth` upload at
+/// `ptr`/`len` with the context's [`UnpackLayout`], tightly packed in
+/// `storage_internal_format`. Source pixels are sized by `format` and
+/// `type_`; 8-bit ones stored as RGBA8 are widened, and other pixels that
+/// do not match the storage format fail with `INVALID_OPERATION`.
+#[allow(clippy::too_many_arguments)]
+fn unpack_pixels(
+    ctx_obj: &mut Context,
+    ptr: u32,
+    len: u32,
+    width: u32,
+    height: u32,
+    depth: u32,
+    format: u32,
+    type_: u32,
+    storage_internal_format: u32,
+) -> Result<Vec<u8>, u32> {
+    let Some(bytes_per_pixel) = source_bytes_per_pixel(format, type_) else {
+        set_last_error(&format!(
+            "format 0x{:X} with type 0x{:X} is not an upload format",
+            format, type_
+        ));
+        ctx_obj.set_error(GL_INVALID_ENUM);
+        return Err(ERR_GL);
+    };
+    let src = crate::guest_mem::read(ptr, len)?;
+    let pixels = match ctx_obj
+        .unpack_layout
+        .gather(src, width, height, depth, bytes_per_pixel)
+    {
+        Ok(pixels) => pixels,
+        Err(required) => {
+            set_last_error(&format!(
+                "pixel data holds {} bytes, {} required",
+                len, required
+            ));
+            ctx_obj.set_error(GL_INVALID_OPERATION);
+            return Err(ERR_GL);
+        }
     };
+
+    if bytes_per_pixel == get_bytes_per_pixel(storage_internal_format) {
+        return Ok(pixels);
+    }
+    if storage_internal_format == GL_RGBA8 && type_ == GL_UNSIGNED_BYTE {
+        if let Some(pixels) = expand_to_rgba8(format, &pixels) {
+            return Ok(pixels);
+        }
+    }
+    set_last_error(&format!(
+        "format 0x{:X} with type 0x{:X} cannot be stored as internal format 0x{:X}",
+        format, type_, storage_internal_format
+    ));
+    ctx_obj.set_error(GL_INVALID_OPERATION);
+    Err(ERR_GL)
+}
+
+/// Upload pixel data to a texture.
+/// ptr and len point to `format`/`type_` pixels in WASM linear memory, read
+/// with the context's [`UnpackLayout`]. An empty upload (`len` 0) leaves
+/// the level zero-filled.
+/// Returns errno.
+#[allow(clippy::too_many_arguments)]
+pub fn ctx_tex_image_2d(
+    ctx: u32,
+    _target: u32,
+    level: i32,
+    internal_format: i32,
+    width: u32,
+    height: u32,
+    _border: i32,
+    format: i32,
+    type_: i32,
+    ptr: u32,
+    len: u32,
+) -> u32 {
+    clear_last_error();
+
+    let pixels = if len == 0 {
+        Vec::new()
+    } else {
+        let mut reg = get_registry().borrow_mut();
+        let Some(ctx_obj) = reg.contexts.get_mut(&ctx) else {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        };
+        let storage_internal_format = storage_format(internal_format as u32, type_ as u32);
+        match unpack_pixels(
+            ctx_obj,
+            ptr,
+            len,
+            width,
+            height,
+            1,
+            format as u32,
+            type_ as u32,
+            storage_internal_format,
+        ) {
+            Ok(pixels) => pixels,
+            Err(code) => return code,
+        }
+    };
+    tex_image_2d_data(ctx, level, internal_format, width, height, type_, &pixels)
+}
+
+/// Upload a level of the bound 2D texture from a host slice of tightly
+/// packed pixels in the level's storage format. A short slice is padded with
+/// zeros.
+pub(crate) fn tex_image_2d_data(
+    ctx: u32,
+    level: i32,
+    internal_format: i32,
+    width: u32,
+    height: u32,
+    _type_: i32,
+    src_slice: &[u8],
+) -> u32 {
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    if let Err(code) = ctx_obj.check_image_size(width, height) {
+        return code;
+    }
+
+    // Determine which texture to write to (bound or error)
+    let tex_handle = match ctx_obj.bound_texture {
+        Some(h) => h,
+        None => {
+            set_last_error("no texture bound");
+            return ERR_INVALID_ARGS;
+        }
+    };
+
+    let storage_internal_format = storage_format(internal_format as u32, _type_ as u32);
     let bytes_per_pixel = super::types::get_bytes_per_pixel(storage_internal_format);
 
-    // Validate dimensions
     let expected_size = (width as u64)
         .saturating_mul(height as u64)
         .saturating_mul(bytes_per_pixel as u64);
-    if len as u64 != expected_size {
-        // If it's a pointer-based upload and the length doesn't match the expected size,
-        // we might be receiving RGBA8 for a RGBA32F texture or vice versa.
-        // But for now, let's just log and try to handle it.
-    }
-
-    // Copy pixel data from WASM linear memory
-    let src_slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, len as usize) };
     let mut pixel_data = src_slice.to_vec();
-
-    // If the provided data is smaller than expected (e.g. JS passed 4 bytes for 16-byte pixel),
-    // pad it with zeros so we don't crash later.
-    if pixel_data.len() < expected_size as usize {
-        pixel_data.resize(expected_size as usize, 0);
+    pixel_data.resize(expected_size as usize, 0);
+    if ctx_obj.unpack_premultiply_alpha
+        && storage_internal_format == GL_RGBA8
+        && _type_ as u32 == GL_UNSIGNED_BYTE
+    {
+        premultiply_alpha(&mut pixel_data);
     }
 
     // Store texture data
@@ -321,6 +481,45 @@ pub fn ctx_tex_image_2d(
     }
 }
 
+/// Decode a PNG or JPEG image and upload it as an RGBA8 level of the bound
+/// 2D texture, sized to the image.
+#[cfg(feature = "image-decode")]
+pub fn tex_image_2d_encoded(ctx: u32, _target: u32, level: i32, encoded: &[u8]) -> u32 {
+    clear_last_error();
+    let image = match image::load_from_memory(encoded) {
+        Ok(image) => image.to_rgba8(),
+        Err(e) => {
+            set_last_error(&format!("failed to decode image: {}", e));
+            return ERR_INVALID_ARGS;
+        }
+    };
+    let (width, height) = image.dimensions();
+    tex_image_2d_data(
+        ctx,
+        level,
+        GL_RGBA8 as i32,
+        width,
+        height,
+        GL_UNSIGNED_BYTE as i32,
+        image.as_raw(),
+    )
+}
+
+/// [`tex_image_2d_encoded`] with the image at `ptr`/`len` in WASM linear memory.
+#[cfg(feature = "image-decode")]
+pub fn ctx_tex_image_2d_encoded(ctx: u32, target: u32, level: i32, ptr: u32, len: u32) -> u32 {
+    let encoded = match crate::guest_mem::read(ptr, len) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
+    tex_image_2d_encoded(ctx, target, level, encoded)
+}
+
+/// Upload a level of the bound 3D or 2D array texture. ptr and len point to
+/// `format`/`type_` pixels in WASM linear memory, read image by image with
+/// the context's [`UnpackLayout`]. An empty upload (`len` 0) leaves the
+/// level zero-filled.
+/// Returns errno.
 #[allow(clippy::too_many_arguments)]
 pub fn ctx_tex_image_3d(
     ctx: u32,
@@ -331,7 +530,7 @@ pub fn ctx_tex_image_3d(
     height: u32,
     depth: u32,
     _border: i32,
-    _format: i32,
+    format: i32,
     _type_: i32,
     ptr: u32,
     len: u32,
@@ -352,6 +551,9 @@ pub fn ctx_tex_image_3d(
         ctx_obj.set_error(GL_INVALID_ENUM);
         return ERR_GL;
     }
+    if let Err(code) = ctx_obj.check_image_size(width, height) {
+        return code;
+    }
 
     let tex_handle = match ctx_obj.bound_texture {
         Some(h) => h,
@@ -362,68 +564,7 @@ pub fn ctx_tex_image_3d(
         }
     };
 
-    // Determine storage internal format from the requested internalFormat and type
-    let requested_internal = internal_format as u32;
-    let _is_3d = target == GL_TEXTURE_3D;
-    let storage_internal_format = match (requested_internal, _type_ as u32) {
-        (GL_R32F, _) => GL_R32F,
-        (GL_RG32F, _) => GL_RG32F,
-        (GL_RGBA32F, _) => GL_RGBA32F,
-        (GL_R16F, _) => GL_R16F,
-        (GL_RG16F, _) => GL_RG16F,
-        (GL_RGBA16F, _) => GL_RGBA16F,
-
-        (GL_R32UI, _) => GL_R32UI,
-        (GL_RG32UI, _) => GL_RG32UI,
-        (GL_RGBA32UI, _) => GL_RGBA32UI,
-        (GL_R32I, _) => GL_R32I,
-        (GL_RG32I, _) => GL_RG32I,
-        (GL_RGBA32I, _) => GL_RGBA32I,
-
-        (GL_R16UI, _) => GL_R16UI,
-        (GL_RG16UI, _) => GL_RG16UI,
-        (GL_RGBA16UI, _) => GL_RGBA16UI,
-        (GL_R16I, _) => GL_R16I,
-        (GL_RG16I, _) => GL_RG16I,
-        (GL_RGBA16I, _) => GL_RGBA16I,
-
-        (GL_R8UI, _) => GL_R8UI,
-        (GL_RG8UI, _) => GL_RG8UI,
-        (GL_RGBA8UI, _) => GL_RGBA8UI,
-        (GL_R8I, _) => GL_R8I,
-        (GL_RG8I, _) => GL_RG8I,
-        (GL_RGBA8I, _) => GL_RGBA8I,
-
-        (v, GL_FLOAT) if v == GL_RGBA => GL_RGBA32F,
-        (v, GL_FLOAT) if v == GL_RED => GL_R32F,
-        (GL_RG, GL_FLOAT) => GL_RG32F,
-
-        (v, GL_UNSIGNED_INT) if v == GL_RGBA_INTEGER => GL_RGBA32UI,
-        (v, GL_UNSIGNED_INT) if v == GL_RED_INTEGER => GL_R32UI,
-        (v, GL_UNSIGNED_INT) if v == GL_RG_INTEGER => GL_RG32UI,
-        (v, GL_INT) if v == GL_RGBA_INTEGER => GL_RGBA32I,
-        (v, GL_INT) if v == GL_RED_INTEGER => GL_R32I,
-        (v, GL_INT) if v == GL_RG_INTEGER => GL_RG32I,
-
-        (v, GL_UNSIGNED_SHORT) if v == GL_RGBA_INTEGER => GL_RGBA16UI,
-        (v, GL_UNSIGNED_SHORT) if v == GL_RED_INTEGER => GL_R16UI,
-        (v, GL_UNSIGNED_SHORT) if v == GL_RG_INTEGER => GL_RG16UI,
-        (v, GL_SHORT) if v == GL_RGBA_INTEGER => GL_RGBA16I,
-        (v, GL_SHORT) if v == GL_RED_INTEGER => GL_R16I,
-        (v, GL_SHORT) if v == GL_RG_INTEGER => GL_RG16I,
-
-        (v, GL_UNSIGNED_BYTE) if v == GL_RGBA_INTEGER => GL_RGBA8UI,
-        (v, GL_UNSIGNED_BYTE) if v == GL_RED_INTEGER => GL_R8UI,
-        (v, GL_UNSIGNED_BYTE) if v == GL_RG_INTEGER => GL_RG8UI,
-        (v, GL_BYTE) if v == GL_RGBA_INTEGER => GL_RGBA8I,
-        (v, GL_BYTE) if v == GL_RED_INTEGER => GL_R8I,
-        (v, GL_BYTE) if v == GL_RG_INTEGER => GL_RG8I,
-
-        (v, GL_UNSIGNED_BYTE) if v == GL_RGBA => GL_RGBA8,
-        (GL_RGBA8, _) => GL_RGBA8,
-        (v, _) if v == GL_RGBA => GL_RGBA8,
-        _ => GL_RGBA8,
-    };
+    let storage_internal_format = storage_format(internal_format as u32, _type_ as u32);
     let bytes_per_pixel = super::types::get_bytes_per_pixel(storage_internal_format);
 
     let expected_size = (width as u64)
@@ -431,17 +572,25 @@ pub fn ctx_tex_image_3d(
         .saturating_mul(depth as u64)
         .saturating_mul(bytes_per_pixel as u64);
 
-    let src_slice = unsafe { std::slice::from_raw_parts(ptr as *const u8, len as usize) };
-    let mut pixel_data = src_slice.to_vec();
-
-    // If the provided data is smaller than expected, an INVALID_OPERATION error is generated.
-    if pixel_data.len() < expected_size as usize {
-        set_last_error("pixel data too small");
-        ctx_obj.set_error(GL_INVALID_OPERATION);
-        return ERR_GL;
-    } else if pixel_data.len() > expected_size as usize {
-        pixel_data.truncate(expected_size as usize);
-    }
+    let mut pixel_data = if len == 0 {
+        Vec::new()
+    } else {
+        match unpack_pixels(
+            ctx_obj,
+            ptr,
+            len,
+            width,
+            height,
+            depth,
+            format as u32,
+            _type_ as u32,
+            storage_internal_format,
+        ) {
+            Ok(pixels) => pixels,
+            Err(code) => return code,
+        }
+    };
+    pixel_data.resize(expected_size as usize, 0);
 
     if let Some(tex) = ctx_obj.textures.get_mut(&tex_handle) {
         if level == 0 {
@@ -486,7 +635,12 @@ pub fn ctx_tex_image_3d(
     }
 }
 
-/// Upload pixel data to a sub-region of a texture.
+/// Upload pixel data to a sub-rectangle of an existing texture level,
+/// leaving the rest of the level alone, so atlases and video frames can be
+/// streamed without reallocating the texture. The source rows are read with
+/// the context's [`UnpackLayout`] and hold `format`/`type_` pixels, which
+/// must match the level's format or widen to it.
+/// Returns errno.
 #[allow(clippy::too_many_arguments)]
 pub fn ctx_tex_sub_image_2d(
     ctx: u32,
@@ -496,8 +650,8 @@ pub fn ctx_tex_sub_image_2d(
     yoffset: i32,
     width: u32,
     height: u32,
-    _format: i32,
-    _type: i32,
+    format: i32,
+    type_: i32,
     ptr: u32,
     len: u32,
 ) -> u32 {
@@ -519,37 +673,397 @@ pub fn ctx_tex_sub_image_2d(
             return ERR_INVALID_ARGS;
         }
     };
+    let Some(tex) = ctx_obj.textures.get(&tex_handle) else {
+        set_last_error("texture not found");
+        return ERR_INVALID_HANDLE;
+    };
+    let level_data = usize::try_from(level)
+        .ok()
+        .and_then(|level| tex.levels.get(&level));
+    let Some(level_data) = level_data else {
+        set_last_error("texture level not initialized");
+        ctx_obj.set_error(GL_INVALID_OPERATION);
+        return ERR_GL;
+    };
+    let (gpu_handle, internal_format) = (level_data.gpu_handle, level_data.internal_format);
+
+    // Widths come from JS as unsigned, so negative ones fail here too
+    if xoffset < 0
+        || yoffset < 0
+        || xoffset as u64 + width as u64 > level_data.width as u64
+        || yoffset as u64 + height as u64 > level_data.height as u64
+    {
+        set_last_error(&format!(
+            "sub-image {}x{} at ({}, {}) exceeds the {}x{} level",
+            width as i32, height as i32, xoffset, yoffset, level_data.width, level_data.height
+        ));
+        ctx_obj.set_error(GL_INVALID_VALUE);
+        return ERR_GL;
+    }
+    if width == 0 || height == 0 {
+        return ERR_OK;
+    }
 
-    if let Some(tex) = ctx_obj.textures.get_mut(&tex_handle) {
-        let level_idx = level as usize;
-        let level_data = match tex.levels.get_mut(&level_idx) {
-            Some(l) => l,
-            None => {
-                set_last_error("texture level not initialized");
-                return ERR_INVALID_ARGS;
+    let mut pixels = match unpack_pixels(
+        ctx_obj,
+        ptr,
+        len,
+        width,
+        height,
+        1,
+        format as u32,
+        type_ as u32,
+        internal_format,
+    ) {
+        Ok(pixels) => pixels,
+        Err(code) => return code,
+    };
+    if ctx_obj.unpack_premultiply_alpha
+        && internal_format == GL_RGBA8
+        && type_ as u32 == GL_UNSIGNED_BYTE
+    {
+        premultiply_alpha(&mut pixels);
+    }
+
+    crate::wasm_gl_emu::TransferEngine::write_pixels(
+        &mut ctx_obj.kernel,
+        gpu_handle,
+        xoffset,
+        yoffset,
+        0,
+        width,
+        height,
+        1,
+        &pixels,
+    );
+    ERR_OK
+}
+
+/// Planar YUV 4:2:0 frames (`I420` FourCC): a full-resolution Y plane
+/// followed by U and V planes at half resolution in both directions.
+pub const EXTERNAL_FORMAT_I420: u32 = 0x3032_3449;
+/// Semi-planar YUV 4:2:0 frames (`NV12` FourCC): a full-resolution Y plane
+/// followed by a half-resolution plane of interleaved U/V pairs.
+pub const EXTERNAL_FORMAT_NV12: u32 = 0x3231_564E;
+
+/// Bytes a `width`x`height` external frame occupies in `format`, or `None`
+/// for formats that cannot be imported.
+fn external_frame_size(format: u32, width: u32, height: u32) -> Option<u64> {
+    let pixels = width as u64 * height as u64;
+    let chroma = width.div_ceil(2) as u64 * height.div_ceil(2) as u64;
+    match format {
+        GL_RGBA => Some(pixels * 4),
+        GL_RGB => Some(pixels * 3),
+        EXTERNAL_FORMAT_I420 | EXTERNAL_FORMAT_NV12 => Some(pixels + 2 * chroma),
+        _ => None,
+    }
+}
+
+/// Convert one BT.601 limited-range YUV sample to RGBA8.
+fn yuv_to_rgba(y: u8, u: u8, v: u8) -> [u8; 4] {
+    let c = 298 * (y as i32 - 16);
+    let d = u as i32 - 128;
+    let e = v as i32 - 128;
+    let clamp = |x: i32| ((x + 128) >> 8).clamp(0, 255) as u8;
+    [
+        clamp(c + 409 * e),
+        clamp(c - 100 * d - 208 * e),
+        clamp(c + 516 * d),
+        255,
+    ]
+}
+
+/// Expand an RGB or YUV external frame to tightly packed RGBA8.
+fn convert_external_frame(src: &[u8], width: u32, height: u32, format: u32) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    let mut rgba = Vec::with_capacity(w * h * 4);
+    match format {
+        GL_RGB => {
+            for rgb in src.chunks_exact(3).take(w * h) {
+                rgba.extend_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
             }
-        };
+        }
+        _ => {
+            let cw = w.div_ceil(2);
+            let (luma, chroma) = src.split_at(w * h);
+            let plane = cw * h.div_ceil(2);
+            for row in 0..h {
+                for col in 0..w {
+                    let c = (row / 2) * cw + col / 2;
+                    let (u, v) = if format == EXTERNAL_FORMAT_NV12 {
+                        (chroma[2 * c], chroma[2 * c + 1])
+                    } else {
+                        (chroma[c], chroma[plane + c])
+                    };
+                    rgba.extend_from_slice(&yuv_to_rgba(luma[row * w + col], u, v));
+                }
+            }
+        }
+    }
+    rgba
+}
 
-        // SAFETY: ptr/len validated by JS caller
-        let sub_data = unsafe { std::slice::from_raw_parts(ptr as *const u8, len as usize) };
+/// Import a frame streamed from the host (video decoder, camera) into level 0
+/// of the bound texture as RGBA8. `format` is `GL_RGBA`, `GL_RGB` or one of
+/// the `EXTERNAL_FORMAT_*` YUV layouts, which are converted with BT.601
+/// limited-range coefficients. While the frame size stays the same the
+/// existing storage is overwritten in place, so per-frame updates don't
+/// allocate texture memory.
+/// Returns errno.
+pub fn ctx_tex_image_external(
+    ctx: u32,
+    ptr: u32,
+    len: u32,
+    width: u32,
+    height: u32,
+    format: u32,
+) -> u32 {
+    clear_last_error();
 
-        crate::wasm_gl_emu::TransferEngine::write_pixels(
-            &mut ctx_obj.kernel,
-            level_data.gpu_handle,
-            xoffset,
-            yoffset,
-            0,
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+
+    let tex_handle = match ctx_obj.bound_texture {
+        Some(h) => h,
+        None => {
+            set_last_error("no texture bound");
+            return ERR_INVALID_ARGS;
+        }
+    };
+    if let Err(code) = ctx_obj.check_image_size(width, height) {
+        return code;
+    }
+
+    let frame_size = match external_frame_size(format, width, height) {
+        Some(size) => size,
+        None => {
+            set_last_error(&format!(
+                "invalid external frame format: {}",
+                describe_enum(format)
+            ));
+            ctx_obj.set_error(GL_INVALID_ENUM);
+            return ERR_GL;
+        }
+    };
+    if (len as u64) < frame_size {
+        set_last_error("external frame is smaller than its dimensions require");
+        ctx_obj.set_error(GL_INVALID_VALUE);
+        return ERR_GL;
+    }
+
+    let src = match crate::guest_mem::read(ptr, frame_size as u32) {
+        Ok(s) => s,
+        Err(code) => return code,
+    };
+    let converted;
+    let pixels = if format == GL_RGBA {
+        src
+    } else {
+        converted = convert_external_frame(src, width, height, format);
+        &converted[..]
+    };
+
+    let tex = match ctx_obj.textures.get_mut(&tex_handle) {
+        Some(t) => t,
+        None => {
+            set_last_error("texture not found");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+
+    let reusable = tex
+        .levels
+        .get(&0)
+        .filter(|l| {
+            l.width == width && l.height == height && l.depth == 1 && l.internal_format == GL_RGBA8
+        })
+        .map(|l| l.gpu_handle);
+    let gpu_handle = match reusable {
+        Some(handle) => handle,
+        None => {
+            // The frame size changed: drop the old storage and any mips that
+            // were generated from a previous frame
+            for (_, level) in std::mem::take(&mut tex.levels) {
+                ctx_obj.kernel.destroy_buffer(level.gpu_handle);
+            }
+            let handle = ctx_obj.kernel.create_buffer(
+                width,
+                height,
+                1,
+                super::types::gl_to_wgt_format(GL_RGBA8),
+                crate::wasm_gl_emu::device::StorageLayout::Tiled8x8,
+            );
+            tex.levels.insert(
+                0,
+                MipLevel {
+                    width,
+                    height,
+                    depth: 1,
+                    internal_format: GL_RGBA8,
+                    gpu_handle: handle,
+                },
+            );
+            tex.internal_format = GL_RGBA8;
+            handle
+        }
+    };
+
+    crate::wasm_gl_emu::TransferEngine::write_pixels(
+        &mut ctx_obj.kernel,
+        gpu_handle,
+        0,
+        0,
+        0,
+        width,
+        height,
+        1,
+        pixels,
+    );
+    ERR_OK
+}
+
+/// Alias `level` of the bound 2D texture over `len` bytes of linear memory
+/// at `ptr` instead of copying them, for large textures the host rewrites
+/// every frame. The region holds tightly packed rows of `internal_format`
+/// (RGBA8, R32F, RG32F or RGBA32F), bottom row first; pixel store
+/// parameters do not apply.
+///
+/// Draws sample the region as it is when they run, and rendering or
+/// uploading into the level writes to it. The region must stay allocated
+/// until [`ctx_invalidate_texture_alias`] ends the alias or the level's
+/// storage is replaced or freed.
+/// Returns errno.
+#[allow(clippy::too_many_arguments)]
+pub fn ctx_tex_image_2d_alias(
+    ctx: u32,
+    _target: u32,
+    level: i32,
+    internal_format: u32,
+    width: u32,
+    height: u32,
+    ptr: u32,
+    len: u32,
+) -> u32 {
+    clear_last_error();
+
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+
+    let tex_handle = match ctx_obj.bound_texture {
+        Some(h) => h,
+        None => {
+            set_last_error("no texture bound");
+            return ERR_INVALID_ARGS;
+        }
+    };
+
+    if !matches!(internal_format, GL_RGBA8 | GL_R32F | GL_RG32F | GL_RGBA32F) {
+        set_last_error(&format!(
+            "texture aliases do not support {}",
+            describe_enum(internal_format)
+        ));
+        ctx_obj.set_error(GL_INVALID_ENUM);
+        return ERR_GL;
+    }
+    if level < 0 {
+        set_last_error("level must not be negative");
+        ctx_obj.set_error(GL_INVALID_VALUE);
+        return ERR_GL;
+    }
+    if let Err(code) = ctx_obj.check_image_size(width, height) {
+        return code;
+    }
+    let size =
+        width as u64 * height as u64 * super::types::get_bytes_per_pixel(internal_format) as u64;
+    if (len as u64) < size {
+        set_last_error("alias region is smaller than its dimensions require");
+        ctx_obj.set_error(GL_INVALID_VALUE);
+        return ERR_GL;
+    }
+    if let Err(code) = crate::guest_mem::write(ptr, size as u32) {
+        return code;
+    }
+
+    let tex = match ctx_obj.textures.get_mut(&tex_handle) {
+        Some(t) => t,
+        None => {
+            set_last_error("texture not found");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+
+    // Safety: the range was checked against linear memory above, and the
+    // caller keeps it allocated for as long as the alias lasts
+    let gpu_handle = unsafe {
+        ctx_obj.kernel.create_alias(
+            ptr,
+            size as usize,
             width,
             height,
-            1,
-            sub_data,
-        );
+            super::types::gl_to_wgt_format(internal_format),
+        )
+    };
+    let previous = tex.levels.insert(
+        level as usize,
+        MipLevel {
+            width,
+            height,
+            depth: 1,
+            internal_format,
+            gpu_handle,
+        },
+    );
+    if let Some(previous) = previous {
+        ctx_obj.kernel.destroy_buffer(previous.gpu_handle);
+    }
+    if level == 0 {
+        tex.internal_format = internal_format;
+    }
+    ERR_OK
+}
 
-        ERR_OK
-    } else {
-        set_last_error("texture not found");
-        ERR_INVALID_HANDLE
+/// End the alias [`ctx_tex_image_2d_alias`] set up for `level` of the bound
+/// texture: the level keeps a copy of the region's current contents, and the
+/// host may free or reuse the region afterwards. Invalidating a level that
+/// is not aliased is an `INVALID_OPERATION` error.
+/// Returns errno.
+pub fn ctx_invalidate_texture_alias(ctx: u32, _target: u32, level: i32) -> u32 {
+    clear_last_error();
+
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+
+    let gpu_handle = ctx_obj
+        .bound_texture
+        .and_then(|h| ctx_obj.textures.get(&h))
+        .and_then(|tex| tex.levels.get(&(level.max(0) as usize)))
+        .map(|l| l.gpu_handle);
+    let detached =
+        level >= 0 && gpu_handle.is_some_and(|handle| ctx_obj.kernel.detach_alias(handle));
+    if !detached {
+        set_last_error(&format!("texture level {} is not aliased", level));
+        ctx_obj.set_error(GL_INVALID_OPERATION);
+        return ERR_GL;
     }
+    ERR_OK
 }
 
 /// Generate mipmaps for the bound texture.
@@ -601,7 +1115,7 @@ pub fn ctx_generate_mipmap(ctx: u32, target: u32) -> u32 {
         let mut current_level_idx = 0;
 
         let mut prev_data = if let Some(buf) = ctx_obj.kernel.get_buffer(base.gpu_handle) {
-            buf.data.clone()
+            buf.data.to_vec()
         } else {
             return ERR_INTERNAL;
         };
@@ -688,6 +1202,10 @@ pub fn ctx_copy_tex_image_2d(
         }
     };
 
+    if let Err(code) = ctx_obj.check_image_size(width as u32, height as u32) {
+        return code;
+    }
+
     // 1. Identify Source
     let (src_handle, _, _, _) = ctx_obj.get_color_attachment_info(true);
     if !src_handle.is_valid() {
@@ -744,3 +1262,73 @@ pub fn ctx_copy_tex_image_2d(
         ERR_INVALID_HANDLE
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webgl2_context::registry::{
+        create_context_with_flags, ctx_set_limits, destroy_context,
+    };
+    use crate::webgl2_context::state::ctx_get_error;
+
+    #[cfg(feature = "image-decode")]
+    #[test]
+    fn uploads_decoded_png() {
+        use crate::webgl2_context::framebuffers::{
+            ctx_bind_framebuffer, ctx_create_framebuffer, ctx_framebuffer_texture2d,
+        };
+        use crate::webgl2_context::readback::{read_pixels_rgba, Rect};
+
+        let image = image::RgbaImage::from_raw(2, 1, vec![255, 0, 0, 255, 0, 255, 0, 255]).unwrap();
+        let mut png = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+
+        let ctx = create_context_with_flags(0, 2, 2);
+        let tex = ctx_create_texture(ctx);
+        ctx_bind_texture(ctx, GL_TEXTURE_2D, tex);
+        let uploaded = tex_image_2d_encoded(ctx, GL_TEXTURE_2D, 0, &png);
+        let garbage = tex_image_2d_encoded(ctx, GL_TEXTURE_2D, 0, b"not an image");
+        let fb = ctx_create_framebuffer(ctx);
+        ctx_bind_framebuffer(ctx, GL_FRAMEBUFFER, fb);
+        ctx_framebuffer_texture2d(
+            ctx,
+            GL_FRAMEBUFFER,
+            GL_COLOR_ATTACHMENT0,
+            GL_TEXTURE_2D,
+            tex,
+            0,
+        );
+        let pixels = read_pixels_rgba(ctx, Rect::new(0, 0, 2, 1));
+        destroy_context(ctx);
+
+        assert_eq!(uploaded, ERR_OK);
+        assert_eq!(garbage, ERR_INVALID_ARGS);
+        assert_eq!(pixels, Ok(vec![255, 0, 0, 255, 0, 255, 0, 255]));
+    }
+
+    #[test]
+    fn uploads_past_max_texture_size_fail() {
+        let ctx = create_context_with_flags(0, 8, 8);
+        assert_eq!(ctx_set_limits(ctx, 4, 0, 0, 0, 0), ERR_OK);
+        let tex = ctx_create_texture(ctx);
+        ctx_bind_texture(ctx, GL_TEXTURE_2D, tex);
+
+        // Rejected before the (absent) source data is read
+        let external = ctx_tex_image_external(ctx, 0, u32::MAX, 5, 1, GL_RGBA);
+        let external_error = ctx_get_error(ctx);
+        let alias = ctx_tex_image_2d_alias(ctx, GL_TEXTURE_2D, 0, GL_RGBA8, 1, 5, 0, u32::MAX);
+        let alias_error = ctx_get_error(ctx);
+        let copy = ctx_copy_tex_image_2d(ctx, GL_TEXTURE_2D, 0, GL_RGBA8, 0, 0, 5, 5, 0);
+        let copy_error = ctx_get_error(ctx);
+        let copy_at_limit = ctx_copy_tex_image_2d(ctx, GL_TEXTURE_2D, 0, GL_RGBA8, 0, 0, 4, 4, 0);
+        let at_limit_error = ctx_get_error(ctx);
+        destroy_context(ctx);
+
+        assert_eq!((external, external_error), (ERR_GL, GL_INVALID_VALUE));
+        assert_eq!((alias, alias_error), (ERR_GL, GL_INVALID_VALUE));
+        assert_eq!((copy, copy_error), (ERR_GL, GL_INVALID_VALUE));
+        assert_eq!((copy_at_limit, at_limit_error), (ERR_OK, GL_NO_ERROR));
+    }
+}
@@ -0,0 +1,146 @@
+//! Virtual GPU timeline.
+//!
+//! Draws do not take measurable time on real hardware here, so frame pacing
+//! logic has nothing to react to. An optional [`GpuCostModel`] charges each
+//! draw a virtual duration from the shader invocations the rasterizer ran,
+//! and the timeline advances by that amount. Timer queries
+//! (EXT_disjoint_timer_query_webgl2) read the timeline, which makes their
+//! results deterministic. With the default model, which costs nothing, the
+//! timeline stands still and every timer query measures zero.
+//!
+//! In the non-deterministic [`GpuClock::HostCpu`] mode the timeline follows
+//! the host's monotonic clock instead, so timer queries measure the real CPU
+//! time the emulator spent.
+
+use super::registry::{clear_last_error, get_registry, set_last_error};
+use super::types::*;
+use crate::wasm_gl_emu::rasterizer::ShaderInvocations;
+
+/// Virtual GPU time charged per unit of work, in nanoseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GpuCostModel {
+    /// Fixed cost of every draw call
+    pub draw_ns: f64,
+    /// Cost of each vertex shader invocation
+    pub vertex_ns: f64,
+    /// Cost of each fragment shader invocation
+    pub fragment_ns: f64,
+}
+
+/// What the GPU timeline of a context follows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GpuClock {
+    /// Draws advance the clock by their cost under the cost model
+    #[default]
+    Virtual,
+    /// The clock follows the host's monotonic clock
+    HostCpu,
+}
+
+/// The virtual GPU clock of a context.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuTimeline {
+    pub cost_model: GpuCostModel,
+    clock: GpuClock,
+    now_ns: f64,
+    /// Host time in milliseconds when the HostCpu clock was selected
+    host_origin_ms: f64,
+}
+
+impl GpuTimeline {
+    /// Current time in nanoseconds since the context was created.
+    pub fn now_ns(&self) -> u64 {
+        self.now_f64().round() as u64
+    }
+
+    fn now_f64(&self) -> f64 {
+        match self.clock {
+            GpuClock::Virtual => self.now_ns,
+            GpuClock::HostCpu => {
+                self.now_ns + ((crate::js_now_ms() - self.host_origin_ms) * 1e6).max(0.0)
+            }
+        }
+    }
+
+    /// Switch the clock the timeline follows. Time carries over, so the
+    /// timeline never runs backwards.
+    pub fn set_clock(&mut self, clock: GpuClock) {
+        self.now_ns = self.now_f64();
+        self.host_origin_ms = crate::js_now_ms();
+        self.clock = clock;
+    }
+
+    /// Advance the clock by the cost of a draw that ran `invocations`.
+    pub fn charge_draw(&mut self, invocations: ShaderInvocations) {
+        if self.clock == GpuClock::HostCpu {
+            return;
+        }
+        let model = &self.cost_model;
+        self.now_ns += model.draw_ns
+            + model.vertex_ns * invocations.vertices as f64
+            + model.fragment_ns * invocations.fragments as f64;
+    }
+}
+
+/// Set the cost model that draws are charged by.
+pub fn ctx_set_gpu_cost_model(ctx: u32, draw_ns: f64, vertex_ns: f64, fragment_ns: f64) -> u32 {
+    clear_last_error();
+    if [draw_ns, vertex_ns, fragment_ns]
+        .iter()
+        .any(|cost| !cost.is_finite() || *cost < 0.0)
+    {
+        set_last_error("GPU costs must be finite and non-negative");
+        return ERR_INVALID_ARGS;
+    }
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    ctx_obj.gpu_timeline.cost_model = GpuCostModel {
+        draw_ns,
+        vertex_ns,
+        fragment_ns,
+    };
+    ERR_OK
+}
+
+/// Select the clock of the GPU timeline: 0 for the virtual clock, 1 for the
+/// host's CPU clock.
+pub fn ctx_set_gpu_clock(ctx: u32, clock: u32) -> u32 {
+    clear_last_error();
+    let clock = match clock {
+        0 => GpuClock::Virtual,
+        1 => GpuClock::HostCpu,
+        _ => {
+            set_last_error("unknown GPU clock");
+            return ERR_INVALID_ARGS;
+        }
+    };
+    let mut reg = get_registry().borrow_mut();
+    let ctx_obj = match reg.contexts.get_mut(&ctx) {
+        Some(c) => c,
+        None => {
+            set_last_error("invalid context handle");
+            return ERR_INVALID_HANDLE;
+        }
+    };
+    ctx_obj.gpu_timeline.set_clock(clock);
+    ERR_OK
+}
+
+/// Current virtual GPU time in nanoseconds, or -1 for an invalid context.
+pub fn ctx_get_gpu_time(ctx: u32) -> i64 {
+    clear_last_error();
+    let reg = get_registry().borrow();
+    match reg.contexts.get(&ctx) {
+        Some(c) => c.gpu_timeline.now_ns() as i64,
+        None => {
+            set_last_error("invalid context handle");
+            -1
+        }
+    }
+}
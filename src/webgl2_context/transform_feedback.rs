@@ -1,4 +1,4 @@
-use crate::webgl2_context::registry::get_registry;
+use crate::webgl2_context::registry::{clear_last_error, get_registry, set_last_error};
 use crate::webgl2_context::types::*;
 
 pub fn ctx_create_transform_feedback(ctx: u32) -> u32 {
@@ -157,6 +157,54 @@ pub fn ctx_transform_feedback_varyings(
     }
 }
 
+/// Resolve the varyings `transformFeedbackVaryings` requested against the
+/// vertex outputs of a program being linked. Returns the link error for a
+/// name the vertex shader does not write.
+pub(crate) fn link_transform_feedback_varyings(p: &Program) -> Result<Vec<ActiveInfo>, String> {
+    p.tf_varyings
+        .iter()
+        .map(|name| {
+            let type_ = match (name.as_str(), p.vs_module.is_some()) {
+                (_, false) => None,
+                ("gl_Position", true) => Some(GL_FLOAT_VEC4),
+                ("gl_PointSize", true) => Some(GL_FLOAT),
+                _ => p.varying_types.get(name).map(|&info| varying_gl_type(info)),
+            };
+            match type_ {
+                Some(type_) => Ok(ActiveInfo {
+                    name: name.clone(),
+                    size: 1,
+                    type_,
+                }),
+                None => Err(format!(
+                    "Link failed: transform feedback varying '{}' is not written by the vertex shader",
+                    name
+                )),
+            }
+        })
+        .collect()
+}
+
+/// GL type of a varying from its `(type_code, components)`.
+fn varying_gl_type((type_code, components): (u8, u32)) -> u32 {
+    const FLOAT: [u32; 4] = [GL_FLOAT, GL_FLOAT_VEC2, GL_FLOAT_VEC3, GL_FLOAT_VEC4];
+    const INT: [u32; 4] = [GL_INT, GL_INT_VEC2, GL_INT_VEC3, GL_INT_VEC4];
+    const UINT: [u32; 4] = [
+        GL_UNSIGNED_INT,
+        GL_UNSIGNED_INT_VEC2,
+        GL_UNSIGNED_INT_VEC3,
+        GL_UNSIGNED_INT_VEC4,
+    ];
+    match (type_code, components) {
+        (0, 9) => GL_FLOAT_MAT3,
+        (0, 16) => GL_FLOAT_MAT4,
+        (_, n) if n == 0 || n > 4 => GL_FLOAT,
+        (1, n) => INT[n as usize - 1],
+        (2, n) => UINT[n as usize - 1],
+        (_, n) => FLOAT[n as usize - 1],
+    }
+}
+
 /// Get active transform feedback varying info.
 /// Returns a pointer to an ephemeral payload:
 /// - bytes [ptr+0 .. ptr+3]: `size: i32`
@@ -177,6 +225,7 @@ pub fn ctx_transform_feedback_varyings(
 ///
 /// Returns 0 on failure (check last error).
 pub fn ctx_get_transform_feedback_varying(ctx_handle: u32, program: u32, index: u32) -> u32 {
+    clear_last_error();
     let mut reg = get_registry().borrow_mut();
     let ctx = match reg.contexts.get_mut(&ctx_handle) {
         Some(c) => c,
@@ -184,26 +233,13 @@ pub fn ctx_get_transform_feedback_varying(ctx_handle: u32, program: u32, index:
     };
 
     if let Some(p) = ctx.programs.get(&program) {
-        if index >= p.tf_varyings.len() as u32 {
+        let Some(info) = p.tf_linked.get(index as usize).cloned() else {
+            set_last_error("transform feedback varying index out of range");
+            ctx.set_error(GL_INVALID_VALUE);
             return 0;
-        }
-
-        let name = p.tf_varyings[index as usize].clone();
-        // Look up type info from linked varying info
-        let (type_code, components) = p.varying_types.get(&name).cloned().unwrap_or((0, 1));
-
-        // Map internal type info back to GL enum
-        let gl_type = match (type_code, components) {
-            (0, 1) => 0x1406, // FLOAT
-            (0, 2) => 0x8B50, // FLOAT_VEC2
-            (0, 3) => 0x8B51, // FLOAT_VEC3
-            (0, 4) => 0x8B52, // FLOAT_VEC4
-            (1, 1) => 0x1404, // INT
-            (2, 1) => 0x1405, // UNSIGNED_INT
-            _ => 0x1406,
         };
 
-        let name_bytes = name.as_bytes();
+        let name_bytes = info.name.as_bytes();
         let payload_len = 8 + name_bytes.len() as u32;
 
         let ptr = if payload_len <= 128 {
@@ -213,8 +249,8 @@ pub fn ctx_get_transform_feedback_varying(ctx_handle: u32, program: u32, index:
         };
 
         unsafe {
-            *(ptr as *mut i32) = 1; // Assuming size 1 for now
-            *((ptr + 4) as *mut u32) = gl_type;
+            *(ptr as *mut i32) = info.size;
+            *((ptr + 4) as *mut u32) = info.type_;
             let dest = std::slice::from_raw_parts_mut((ptr + 8) as *mut u8, name_bytes.len());
             dest.copy_from_slice(name_bytes);
         }
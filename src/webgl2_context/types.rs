@@ -13,257 +13,9 @@ pub const ERR_GL: u32 = 5;
 pub const ERR_INTERNAL: u32 = 6;
 pub const ERR_INVALID_OPERATION: u32 = 7;
 pub const ERR_INVALID_ENUM: u32 = 8;
+pub const ERR_COMPILATION: u32 = 9;
 
-// GL Error constants
-pub const GL_NO_ERROR: u32 = 0;
-pub const GL_INVALID_ENUM: u32 = 0x0500;
-pub const GL_INVALID_VALUE: u32 = 0x0501;
-pub const GL_INVALID_OPERATION: u32 = 0x0502;
-pub const GL_OUT_OF_MEMORY: u32 = 0x0505;
-
-pub const GL_ARRAY_BUFFER: u32 = 0x8892;
-pub const GL_ELEMENT_ARRAY_BUFFER: u32 = 0x8893;
-pub const GL_COPY_READ_BUFFER: u32 = 0x8F36;
-pub const GL_COPY_WRITE_BUFFER: u32 = 0x8F37;
-pub const GL_PIXEL_PACK_BUFFER: u32 = 0x88EB;
-pub const GL_PIXEL_UNPACK_BUFFER: u32 = 0x88EC;
-
-pub const GL_COMPILE_STATUS: u32 = 0x8B81;
-pub const GL_LINK_STATUS: u32 = 0x8B82;
-pub const GL_SHADER_TYPE: u32 = 0x8B4F;
-pub const GL_DELETE_STATUS: u32 = 0x8B80;
-pub const GL_INFO_LOG_LENGTH: u32 = 0x8B84;
-pub const GL_ATTACHED_SHADERS: u32 = 0x8B85;
-pub const GL_ACTIVE_UNIFORMS: u32 = 0x8B86;
-pub const GL_ACTIVE_ATTRIBUTES: u32 = 0x8B89;
-
-pub const GL_POINTS: u32 = 0x0000;
-pub const GL_LINES: u32 = 0x0001;
-pub const GL_LINE_LOOP: u32 = 0x0002;
-pub const GL_LINE_STRIP: u32 = 0x0003;
-pub const GL_TRIANGLES: u32 = 0x0004;
-pub const GL_TRIANGLE_STRIP: u32 = 0x0005;
-pub const GL_TRIANGLE_FAN: u32 = 0x0006;
-
-pub const GL_FRONT: u32 = 0x0404;
-pub const GL_BACK: u32 = 0x0405;
-pub const GL_FRONT_AND_BACK: u32 = 0x0408;
-
-pub const GL_COLOR: u32 = 0x1800;
-pub const GL_DEPTH: u32 = 0x1801;
-pub const GL_STENCIL: u32 = 0x1802;
-pub const GL_CW: u32 = 0x0900;
-pub const GL_CCW: u32 = 0x0901;
-
-pub const GL_BYTE: u32 = 0x1400;
-pub const GL_UNSIGNED_BYTE: u32 = 0x1401;
-pub const GL_SHORT: u32 = 0x1402;
-pub const GL_UNSIGNED_SHORT: u32 = 0x1403;
-pub const GL_INT: u32 = 0x1404;
-pub const GL_UNSIGNED_INT: u32 = 0x1405;
-pub const GL_FLOAT: u32 = 0x1406;
-pub const GL_HALF_FLOAT: u32 = 0x140B;
-
-pub const GL_TEXTURE_2D: u32 = 0x0DE1;
-pub const GL_TEXTURE_3D: u32 = 0x806F;
-pub const GL_TEXTURE_2D_ARRAY: u32 = 0x8C1A;
-
-pub const GL_RGBA: u32 = 0x1908;
-pub const GL_RGB: u32 = 0x1907;
-pub const GL_RED: u32 = 0x1903;
-pub const GL_RG: u32 = 0x8227;
-
-pub const GL_RGBA_INTEGER: u32 = 0x8D9E;
-pub const GL_RGB_INTEGER: u32 = 0x8D98;
-pub const GL_RG_INTEGER: u32 = 0x8228;
-pub const GL_RED_INTEGER: u32 = 0x8D94;
-pub const GL_DEPTH_COMPONENT: u32 = 0x1902;
-pub const GL_UNSIGNED_INT_24_8: u32 = 0x84FA;
-pub const GL_DEPTH24_STENCIL8: u32 = 0x88F0;
-
-pub const GL_RGBA8: u32 = 0x8058;
-pub const GL_RGB8: u32 = 0x8051;
-
-pub const GL_R32F: u32 = 0x822E;
-pub const GL_RG32F: u32 = 0x8230;
-pub const GL_RGBA32F: u32 = 0x8814;
-
-pub const GL_R32UI: u32 = 0x8232;
-pub const GL_RG32UI: u32 = 0x823C;
-pub const GL_RGBA32UI: u32 = 0x8D70;
-
-pub const GL_R32I: u32 = 0x8231;
-pub const GL_RG32I: u32 = 0x823B;
-pub const GL_RGBA32I: u32 = 0x8D82;
-
-pub const GL_R8UI: u32 = 0x8238;
-pub const GL_RG8UI: u32 = 0x823E;
-pub const GL_RGB8UI: u32 = 0x8D7D;
-pub const GL_RGBA8UI: u32 = 0x8D7C;
-pub const GL_R8I: u32 = 0x8237;
-pub const GL_RG8I: u32 = 0x823D;
-pub const GL_RGB8I: u32 = 0x8D8F;
-pub const GL_RGBA8I: u32 = 0x8D8E;
-
-pub const GL_R16UI: u32 = 0x8234;
-pub const GL_RG16UI: u32 = 0x8240;
-pub const GL_RGB16UI: u32 = 0x8D77;
-pub const GL_RGBA16UI: u32 = 0x8D76;
-pub const GL_R16I: u32 = 0x8233;
-pub const GL_RG16I: u32 = 0x823F;
-pub const GL_RGB16I: u32 = 0x8D89;
-pub const GL_RGBA16I: u32 = 0x8D88;
-
-pub const GL_RGB32UI: u32 = 0x8D71;
-pub const GL_RGB32I: u32 = 0x8D83;
-
-pub const GL_R16F: u32 = 0x822D;
-pub const GL_RG16F: u32 = 0x822F;
-pub const GL_RGB16F: u32 = 0x881B;
-pub const GL_RGBA16F: u32 = 0x881A;
-pub const GL_RGB32F: u32 = 0x8815;
-
-pub const GL_RGBA4: u32 = 0x8056;
-pub const GL_RGB565: u32 = 0x8D62;
-pub const GL_RGB5_A1: u32 = 0x8057;
-
-pub const GL_DEPTH_COMPONENT16: u32 = 0x81A5;
-pub const GL_DEPTH_COMPONENT24: u32 = 0x81A6;
-pub const GL_DEPTH_COMPONENT32F: u32 = 0x8CAC;
-pub const GL_STENCIL_INDEX8: u32 = 0x8D48;
-
-pub const GL_VERTEX_ATTRIB_ARRAY_ENABLED: u32 = 0x8622;
-pub const GL_VERTEX_ATTRIB_ARRAY_SIZE: u32 = 0x8623;
-pub const GL_VERTEX_ATTRIB_ARRAY_STRIDE: u32 = 0x8624;
-pub const GL_VERTEX_ATTRIB_ARRAY_TYPE: u32 = 0x8625;
-pub const GL_VERTEX_ATTRIB_ARRAY_NORMALIZED: u32 = 0x886A;
-pub const GL_VERTEX_ATTRIB_ARRAY_POINTER: u32 = 0x8645;
-pub const GL_VERTEX_ATTRIB_ARRAY_BUFFER_BINDING: u32 = 0x889F;
-pub const GL_VERTEX_ATTRIB_ARRAY_DIVISOR: u32 = 0x88FE;
-pub const GL_VERTEX_ATTRIB_ARRAY_INTEGER: u32 = 0x88FD;
-pub const GL_CURRENT_VERTEX_ATTRIB: u32 = 0x8626;
-
-pub const GL_TEXTURE_MAG_FILTER: u32 = 0x2800;
-pub const GL_TEXTURE_MIN_FILTER: u32 = 0x2801;
-pub const GL_TEXTURE_WRAP_S: u32 = 0x2802;
-pub const GL_TEXTURE_WRAP_T: u32 = 0x2803;
-pub const GL_TEXTURE_WRAP_R: u32 = 0x8072;
-
-pub const GL_NEAREST: u32 = 0x2600;
-pub const GL_LINEAR: u32 = 0x2601;
-pub const GL_NEAREST_MIPMAP_NEAREST: u32 = 0x2700;
-pub const GL_LINEAR_MIPMAP_NEAREST: u32 = 0x2701;
-pub const GL_NEAREST_MIPMAP_LINEAR: u32 = 0x2702;
-pub const GL_LINEAR_MIPMAP_LINEAR: u32 = 0x2703;
-pub const GL_REPEAT: u32 = 0x2901;
-pub const GL_CLAMP_TO_EDGE: u32 = 0x812F;
-pub const GL_MIRRORED_REPEAT: u32 = 0x8370;
-
-pub const GL_COLOR_ATTACHMENT0: u32 = 0x8CE0;
-pub const GL_COLOR_ATTACHMENT1: u32 = 0x8CE1;
-pub const GL_COLOR_ATTACHMENT2: u32 = 0x8CE2;
-pub const GL_COLOR_ATTACHMENT3: u32 = 0x8CE3;
-pub const GL_COLOR_ATTACHMENT4: u32 = 0x8CE4;
-pub const GL_COLOR_ATTACHMENT5: u32 = 0x8CE5;
-pub const GL_COLOR_ATTACHMENT6: u32 = 0x8CE6;
-pub const GL_COLOR_ATTACHMENT7: u32 = 0x8CE7;
-pub const GL_DEPTH_ATTACHMENT: u32 = 0x8D00;
-pub const GL_STENCIL_ATTACHMENT: u32 = 0x8D20;
-pub const GL_DEPTH_STENCIL_ATTACHMENT: u32 = 0x821A;
-
-pub const GL_DRAW_FRAMEBUFFER: u32 = 0x8CA9;
-pub const GL_READ_FRAMEBUFFER: u32 = 0x8CA8;
-
-pub const GL_MAX_COLOR_ATTACHMENTS: u32 = 0x8CDF;
-pub const GL_MAX_DRAW_BUFFERS: u32 = 0x8824;
-pub const GL_NONE: u32 = 0;
-
-pub const GL_VERTEX_SHADER: u32 = 0x8B31;
-pub const GL_FRAGMENT_SHADER: u32 = 0x8B30;
-
-pub const GL_LESS: u32 = 0x0201;
-pub const GL_EQUAL: u32 = 0x0202;
-pub const GL_LEQUAL: u32 = 0x0203;
-pub const GL_GREATER: u32 = 0x0204;
-pub const GL_NOTEQUAL: u32 = 0x0205;
-pub const GL_GEQUAL: u32 = 0x0206;
-pub const GL_ALWAYS: u32 = 0x0207;
-pub const GL_NEVER: u32 = 0x0200;
-
-pub const GL_STENCIL_TEST: u32 = 0x0B90;
-pub const GL_DEPTH_TEST: u32 = 0x0B71;
-pub const GL_BLEND: u32 = 0x0BE2;
-pub const GL_CULL_FACE: u32 = 0x0B44;
-pub const GL_SCISSOR_TEST: u32 = 0x0C11;
-
-pub const GL_TRANSFORM_FEEDBACK_BUFFER: u32 = 0x8C8E;
-pub const GL_TRANSFORM_FEEDBACK_BUFFER_BINDING: u32 = 0x8C8F;
-pub const GL_TRANSFORM_FEEDBACK_BUFFER_START: u32 = 0x8C84;
-pub const GL_TRANSFORM_FEEDBACK_BUFFER_SIZE: u32 = 0x8C85;
-pub const GL_TRANSFORM_FEEDBACK_BINDING: u32 = 0x8E25;
-pub const GL_TRANSFORM_FEEDBACK_BUFFER_MODE: u32 = 0x8C7F;
-pub const GL_TRANSFORM_FEEDBACK_VARYINGS: u32 = 0x8C83;
-pub const GL_TRANSFORM_FEEDBACK_VARYING_MAX_LENGTH: u32 = 0x8C76;
-pub const GL_MAX_TRANSFORM_FEEDBACK_SEPARATE_ATTRIBS: u32 = 0x8C8B;
-pub const GL_MAX_TRANSFORM_FEEDBACK_INTERLEAVED_COMPONENTS: u32 = 0x8C8A;
-pub const GL_MAX_TRANSFORM_FEEDBACK_SEPARATE_COMPONENTS: u32 = 0x8C80;
-pub const GL_INTERLEAVED_ATTRIBS: u32 = 0x8C8C;
-pub const GL_SEPARATE_ATTRIBS: u32 = 0x8C8D;
-pub const GL_TRANSFORM_FEEDBACK_PAUSED: u32 = 0x8E23;
-pub const GL_TRANSFORM_FEEDBACK_ACTIVE: u32 = 0x8E24;
-pub const GL_TRANSFORM_FEEDBACK: u32 = 0x8E22;
-
-pub const GL_RASTERIZER_DISCARD: u32 = 0x8C89;
-
-pub const GL_UNIFORM_BUFFER: u32 = 0x8A11;
-pub const GL_UNIFORM_BUFFER_BINDING: u32 = 0x8A28;
-pub const GL_UNIFORM_BUFFER_START: u32 = 0x8A29;
-pub const GL_UNIFORM_BUFFER_SIZE: u32 = 0x8A2A;
-pub const GL_MAX_UNIFORM_BUFFER_BINDINGS: u32 = 0x8A2F;
-
-pub const GL_KEEP: u32 = 0x1E00;
-pub const GL_REPLACE: u32 = 0x1E01;
-pub const GL_INCR: u32 = 0x1E02;
-pub const GL_DECR: u32 = 0x1E03;
-pub const GL_INVERT: u32 = 0x150A;
-pub const GL_INCR_WRAP: u32 = 0x8507;
-pub const GL_DECR_WRAP: u32 = 0x8508;
-
-pub const GL_FUNC_ADD: u32 = 0x8006;
-pub const GL_FUNC_SUBTRACT: u32 = 0x800A;
-pub const GL_FUNC_REVERSE_SUBTRACT: u32 = 0x800B;
-pub const GL_MIN: u32 = 0x8007;
-pub const GL_MAX: u32 = 0x8008;
-
-pub const GL_ZERO: u32 = 0;
-pub const GL_ONE: u32 = 1;
-pub const GL_SRC_COLOR: u32 = 0x0300;
-pub const GL_ONE_MINUS_SRC_COLOR: u32 = 0x0301;
-pub const GL_SRC_ALPHA: u32 = 0x0302;
-pub const GL_ONE_MINUS_SRC_ALPHA: u32 = 0x0303;
-pub const GL_DST_ALPHA: u32 = 0x0304;
-pub const GL_ONE_MINUS_DST_ALPHA: u32 = 0x0305;
-pub const GL_DST_COLOR: u32 = 0x0306;
-pub const GL_ONE_MINUS_DST_COLOR: u32 = 0x0307;
-pub const GL_SRC_ALPHA_SATURATE: u32 = 0x0308;
-pub const GL_CONSTANT_COLOR: u32 = 0x8001;
-pub const GL_ONE_MINUS_CONSTANT_COLOR: u32 = 0x8002;
-pub const GL_CONSTANT_ALPHA: u32 = 0x8003;
-pub const GL_ONE_MINUS_CONSTANT_ALPHA: u32 = 0x8004;
-
-pub const GL_VIEWPORT: u32 = 0x0BA2;
-pub const GL_COLOR_CLEAR_VALUE: u32 = 0x0C22;
-pub const GL_BUFFER_SIZE: u32 = 0x8764;
-pub const GL_COLOR_BUFFER_BIT: u32 = 0x00004000;
-pub const GL_RENDERBUFFER: u32 = 0x8D41;
-pub const GL_FRAMEBUFFER: u32 = 0x8D40;
-pub const GL_FRAMEBUFFER_COMPLETE: u32 = 0x8CD5;
-pub const GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT: u32 = 0x8CD6;
-pub const GL_FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT: u32 = 0x8CD7;
-pub const GL_FRAMEBUFFER_INCOMPLETE_DIMENSIONS: u32 = 0x8CD9;
-pub const GL_FRAMEBUFFER_UNSUPPORTED: u32 = 0x8DDD;
-
-pub const GL_DEPTH_STENCIL: u32 = 0x84F9;
+pub use crate::gl_constants::*;
 
 pub(crate) const INVALID_HANDLE: u32 = 0;
 pub(crate) const FIRST_HANDLE: u32 = 1;
@@ -286,6 +38,10 @@ pub(crate) struct Texture {
     pub(crate) wrap_s: u32,
     pub(crate) wrap_t: u32,
     pub(crate) wrap_r: u32,
+    /// Added to the level of detail of every sample (TEXTURE_LOD_BIAS)
+    pub(crate) lod_bias: f32,
+    /// Flagged by deleteTexture while a framebuffer still holds it
+    pub(crate) deleted: bool,
 }
 
 #[derive(Clone)]
@@ -297,6 +53,16 @@ pub(crate) struct Sampler {
     pub(crate) wrap_r: u32,
 }
 
+/// A timer query. `target` is 0 until the query is first used.
+#[derive(Clone, Default)]
+pub(crate) struct Query {
+    pub(crate) target: u32,
+    /// Virtual GPU time at beginQuery
+    pub(crate) begin_ns: u64,
+    /// Nanoseconds elapsed, or the timestamp, once the query has ended
+    pub(crate) result: Option<u64>,
+}
+
 #[derive(Clone)]
 pub(crate) struct TransformFeedback {
     pub(crate) active: bool,
@@ -305,12 +71,24 @@ pub(crate) struct TransformFeedback {
     pub(crate) buffer_bindings: Vec<Option<IndexedBufferBinding>>,
 }
 
+/// Vertex and fragment stages taken from separable programs.
+#[derive(Clone, Default)]
+pub(crate) struct ProgramPipeline {
+    pub(crate) vertex_program: Option<u32>,
+    pub(crate) fragment_program: Option<u32>,
+    pub(crate) active_program: Option<u32>,
+    pub(crate) validate_status: bool,
+    pub(crate) info_log: String,
+}
+
 #[derive(Clone)]
 pub(crate) struct Renderbuffer {
     pub(crate) width: u32,
     pub(crate) height: u32,
     pub(crate) internal_format: u32,
     pub(crate) gpu_handle: GpuHandle,
+    /// Flagged by deleteRenderbuffer while a framebuffer still holds it
+    pub(crate) deleted: bool,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -342,16 +120,23 @@ pub(crate) struct IndexedBufferBinding {
 pub(crate) struct Buffer {
     pub(crate) gpu_handle: crate::wasm_gl_emu::GpuHandle,
     pub(crate) usage: u32,
+    /// Flagged by deleteBuffer while a vertex array still holds it
+    pub(crate) deleted: bool,
 }
 
 #[derive(Clone)]
 pub(crate) struct Shader {
     pub(crate) type_: u32,
     pub(crate) source: String,
+    /// GLSL ES 3.00 the module was parsed from, when a WebGL1 context
+    /// translated `source`
+    pub(crate) translated_source: Option<String>,
     pub(crate) compiled: bool,
     pub(crate) info_log: String,
     pub(crate) module: Option<Arc<naga::Module>>,
     pub(crate) info: Option<Arc<naga::valid::ModuleInfo>>,
+    /// DELETE_STATUS: flagged by deleteShader while attached to a program
+    pub(crate) deleted: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -361,16 +146,32 @@ pub(crate) struct ActiveInfo {
     pub(crate) type_: u32,
 }
 
+/// A uniform block of a linked program, as `getActiveUniformBlockParameter`
+/// reports it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct UniformBlock {
+    pub(crate) name: String,
+    /// Size of the block's std140 layout in bytes
+    pub(crate) data_size: u32,
+    /// Active uniform names of the block members
+    pub(crate) uniforms: Vec<String>,
+    pub(crate) referenced_by_vertex: bool,
+    pub(crate) referenced_by_fragment: bool,
+}
+
 #[derive(Debug, Clone, Default)]
 pub(crate) struct Program {
     pub(crate) attached_shaders: Vec<u32>,
     pub(crate) linked: bool,
+    /// PROGRAM_SEPARABLE: the program may link a single stage and be used in a pipeline
+    pub(crate) separable: bool,
     pub(crate) info_log: String,
     pub(crate) attributes: HashMap<String, i32>,
-    pub(crate) attribute_bindings: HashMap<String, u32>,
+    /// Locations bound by name, applied at the next link
+    pub(crate) bindings: crate::naga_wasm_backend::interface_layout::LocationBindings,
     pub(crate) uniforms: HashMap<String, i32>,
     pub(crate) uniform_types: HashMap<String, (u8, u32)>,
-    pub(crate) uniform_blocks: Vec<String>,
+    pub(crate) uniform_blocks: Vec<UniformBlock>,
     pub(crate) uniform_block_bindings: HashMap<u32, u32>,
     pub(crate) active_attributes: Vec<ActiveInfo>,
     pub(crate) active_uniforms: Vec<ActiveInfo>,
@@ -382,8 +183,20 @@ pub(crate) struct Program {
     pub(crate) fs_wasm: Option<Vec<u8>>,
     pub(crate) vs_stub: Option<String>,
     pub(crate) fs_stub: Option<String>,
+    /// Basic block counters of the instrumented shaders (coverage contexts only)
+    pub(crate) vs_coverage: Option<Arc<crate::naga_wasm_backend::coverage::ShaderCoverage>>,
+    pub(crate) fs_coverage: Option<Arc<crate::naga_wasm_backend::coverage::ShaderCoverage>>,
+    /// Constant tables the shaders' data segments are written to
+    pub(crate) vs_constants: Option<Arc<crate::naga_wasm_backend::constants::ConstantData>>,
+    pub(crate) fs_constants: Option<Arc<crate::naga_wasm_backend::constants::ConstantData>>,
+    /// Interpolation of the fragment inputs, recorded by the backend at link time
+    pub(crate) fs_varyings: Vec<crate::naga_wasm_backend::output_layout::VaryingInfo>,
+    /// Shading rate the fragment shader requires, recorded by the backend
+    pub(crate) fs_shading_rate: crate::naga_wasm_backend::output_layout::ShadingRate,
     // Varying meta populated at link time (name -> location)
     pub(crate) varying_locations: HashMap<String, u32>,
+    /// First component of each packed vertex output within its location
+    pub(crate) varying_components: HashMap<String, u32>,
     // Varying types populated at link time (name -> (type_code, components))
     // type_code: 0=float, 1=int (signed), 2=uint
     pub(crate) varying_types: HashMap<String, (u8, u32)>,
@@ -392,8 +205,16 @@ pub(crate) struct Program {
     /// Function table indices for direct calling
     pub(crate) vs_table_idx: Option<u32>,
     pub(crate) fs_table_idx: Option<u32>,
+    /// Varyings and buffer mode `transformFeedbackVaryings` set for the next link
     pub(crate) tf_varyings: Vec<String>,
     pub(crate) tf_buffer_mode: u32,
+    /// Transform feedback varyings and buffer mode of the last successful link
+    pub(crate) tf_linked: Vec<ActiveInfo>,
+    pub(crate) tf_linked_buffer_mode: u32,
+    /// DELETE_STATUS: flagged by deleteProgram while the program is in use
+    pub(crate) deleted: bool,
+    /// VALIDATE_STATUS of the last validateProgram
+    pub(crate) validate_status: bool,
 }
 
 impl Drop for Program {
@@ -455,6 +276,272 @@ impl Default for VertexAttribute {
     }
 }
 
+/// Attributes of the default framebuffer, as passed to
+/// `getContext('webgl2', attributes)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextAttributes {
+    /// The drawing buffer has an alpha channel; without one it reads as opaque
+    pub alpha: bool,
+    /// The drawing buffer has a 24-bit depth buffer
+    pub depth: bool,
+    /// The drawing buffer has an 8-bit stencil buffer
+    pub stencil: bool,
+    /// Multisampling was requested; the rasterizer never multisamples, so the
+    /// context reports it as unavailable like a browser without MSAA
+    pub antialias: bool,
+    /// Colors in the drawing buffer are already premultiplied by alpha;
+    /// otherwise `ctx_present` premultiplies them like canvas compositing
+    pub premultiplied_alpha: bool,
+    /// The drawing buffer keeps its contents after `ctx_present` instead of
+    /// being cleared
+    pub preserve_drawing_buffer: bool,
+}
+
+impl Default for ContextAttributes {
+    fn default() -> Self {
+        Self {
+            alpha: true,
+            depth: true,
+            stencil: false,
+            antialias: true,
+            premultiplied_alpha: true,
+            preserve_drawing_buffer: false,
+        }
+    }
+}
+
+impl ContextAttributes {
+    const ALPHA: u32 = 0x1;
+    const DEPTH: u32 = 0x2;
+    const STENCIL: u32 = 0x4;
+    const ANTIALIAS: u32 = 0x8;
+    const PREMULTIPLIED_ALPHA: u32 = 0x10;
+    const PRESERVE_DRAWING_BUFFER: u32 = 0x20;
+
+    /// Decode attributes packed one bit each: bit0 = alpha, bit1 = depth,
+    /// bit2 = stencil, bit3 = antialias, bit4 = premultipliedAlpha,
+    /// bit5 = preserveDrawingBuffer.
+    pub fn from_bits(bits: u32) -> Self {
+        Self {
+            alpha: bits & Self::ALPHA != 0,
+            depth: bits & Self::DEPTH != 0,
+            stencil: bits & Self::STENCIL != 0,
+            antialias: bits & Self::ANTIALIAS != 0,
+            premultiplied_alpha: bits & Self::PREMULTIPLIED_ALPHA != 0,
+            preserve_drawing_buffer: bits & Self::PRESERVE_DRAWING_BUFFER != 0,
+        }
+    }
+
+    /// Pack attributes in the layout read by [`ContextAttributes::from_bits`].
+    pub fn to_bits(self) -> u32 {
+        [
+            (self.alpha, Self::ALPHA),
+            (self.depth, Self::DEPTH),
+            (self.stencil, Self::STENCIL),
+            (self.antialias, Self::ANTIALIAS),
+            (self.premultiplied_alpha, Self::PREMULTIPLIED_ALPHA),
+            (self.preserve_drawing_buffer, Self::PRESERVE_DRAWING_BUFFER),
+        ]
+        .into_iter()
+        .filter(|&(set, _)| set)
+        .fold(0, |bits, (_, bit)| bits | bit)
+    }
+}
+
+/// Largest width or height of a texture or renderbuffer image.
+pub const MAX_TEXTURE_SIZE: u32 = 16384;
+
+/// Implementation limits a context reports through `getParameter` and
+/// enforces. They default to the most the emulator supports; lowering them
+/// lets apps test the fallbacks they take on smaller GPUs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// `MAX_TEXTURE_SIZE`, also reported as `MAX_RENDERBUFFER_SIZE`
+    pub max_texture_size: u32,
+    /// `MAX_VERTEX_ATTRIBS`
+    pub max_vertex_attribs: u32,
+    /// `MAX_VARYING_VECTORS`
+    pub max_varying_vectors: u32,
+    /// `MAX_VERTEX_UNIFORM_VECTORS` and `MAX_FRAGMENT_UNIFORM_VECTORS`
+    pub max_uniform_vectors: u32,
+    /// `MAX_DRAW_BUFFERS`, also reported as `MAX_COLOR_ATTACHMENTS`
+    pub max_draw_buffers: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        let link = crate::naga_wasm_backend::interface_layout::LinkLimits::default();
+        Self {
+            max_texture_size: MAX_TEXTURE_SIZE,
+            max_vertex_attribs: link.vertex_attribs,
+            max_varying_vectors: link.varying_vectors,
+            max_uniform_vectors: link.uniform_vectors,
+            max_draw_buffers: MAX_DRAW_BUFFERS as u32,
+        }
+    }
+}
+
+impl Limits {
+    /// The limits programs link against.
+    pub fn link_limits(&self) -> crate::naga_wasm_backend::interface_layout::LinkLimits {
+        crate::naga_wasm_backend::interface_layout::LinkLimits {
+            vertex_attribs: self.max_vertex_attribs,
+            varying_vectors: self.max_varying_vectors,
+            uniform_vectors: self.max_uniform_vectors,
+        }
+    }
+}
+
+/// How draws treat sampler uniforms whose texture unit cannot be sampled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureValidation {
+    /// Sample level 0 of whatever texture the unit has, without checks
+    #[default]
+    Lenient,
+    /// Follow WebGL: incomplete textures sample as (0, 0, 0, 1), and samplers
+    /// of different types sharing a unit fail the draw with INVALID_OPERATION
+    Spec,
+    /// As `Spec`, but sampling an incomplete texture also fails the draw,
+    /// with the reason in the last error
+    Strict,
+}
+
+impl TextureValidation {
+    /// Decode bits 4-5 of the context creation flags.
+    pub fn from_flags(flags: u32) -> Self {
+        match (flags >> 4) & 0x3 {
+            0 => Self::Lenient,
+            1 => Self::Spec,
+            _ => Self::Strict,
+        }
+    }
+}
+
+/// Where readback puts row 0. Rendering always uses GL window coordinates,
+/// with the origin at the bottom left and pixel centers at half-integers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReadOrigin {
+    /// As WebGL: `y` counts up from the bottom row, which is read first
+    #[default]
+    BottomLeft,
+    /// As image files: `y` counts down from the top row, which is read
+    /// first, so snapshots need no flip
+    TopLeft,
+}
+
+impl ReadOrigin {
+    /// Decode bit 8 of the context creation flags.
+    pub fn from_flags(flags: u32) -> Self {
+        if (flags & 0x100) != 0 {
+            Self::TopLeft
+        } else {
+            Self::BottomLeft
+        }
+    }
+}
+
+/// UNPACK_ALIGNMENT, UNPACK_ROW_LENGTH, UNPACK_SKIP_PIXELS and
+/// UNPACK_SKIP_ROWS: where the rows of a sub-image sit in the client data, so
+/// an atlas region can be uploaded straight out of a larger image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnpackLayout {
+    pub alignment: u32,
+    /// Pixels per source row; 0 means the width of the upload
+    pub row_length: u32,
+    pub skip_pixels: u32,
+    pub skip_rows: u32,
+}
+
+impl Default for UnpackLayout {
+    fn default() -> Self {
+        Self {
+            alignment: 4,
+            row_length: 0,
+            skip_pixels: 0,
+            skip_rows: 0,
+        }
+    }
+}
+
+impl UnpackLayout {
+    /// Byte offset of the first pixel and byte stride between rows of a
+    /// `width` pixel wide upload of `bytes_per_pixel` pixels.
+    pub fn rows(&self, width: u32, bytes_per_pixel: u32) -> (u64, u64) {
+        let row_pixels = if self.row_length > 0 {
+            self.row_length
+        } else {
+            width
+        };
+        let stride =
+            (row_pixels as u64 * bytes_per_pixel as u64).next_multiple_of(self.alignment as u64);
+        let offset =
+            self.skip_rows as u64 * stride + self.skip_pixels as u64 * bytes_per_pixel as u64;
+        (offset, stride)
+    }
+
+    /// Copy the pixels of a `width`x`height`x`depth` upload out of `src`,
+    /// tightly packed. Images follow each other `height` rows apart. Fails
+    /// with the number of bytes the upload needs if `src` is shorter.
+    pub fn gather(
+        &self,
+        src: &[u8],
+        width: u32,
+        height: u32,
+        depth: u32,
+        bytes_per_pixel: u32,
+    ) -> Result<Vec<u8>, u64> {
+        let row_size = width as u64 * bytes_per_pixel as u64;
+        let rows = height as u64 * depth as u64;
+        if row_size == 0 || rows == 0 {
+            return Ok(Vec::new());
+        }
+        let (first_row, stride) = self.rows(width, bytes_per_pixel);
+        let required = first_row + (rows - 1) * stride + row_size;
+        if (src.len() as u64) < required {
+            return Err(required);
+        }
+        let mut pixels = Vec::with_capacity((rows * row_size) as usize);
+        for row in 0..rows {
+            let start = (first_row + row * stride) as usize;
+            pixels.extend_from_slice(&src[start..start + row_size as usize]);
+        }
+        Ok(pixels)
+    }
+}
+
+/// Bytes a source pixel of `format` and `type_` takes in an upload, or
+/// `None` for a format or type uploads do not take.
+pub(crate) fn source_bytes_per_pixel(format: u32, type_: u32) -> Option<u32> {
+    let components = match format {
+        GL_RED | GL_RED_INTEGER | GL_ALPHA | GL_LUMINANCE | GL_DEPTH_COMPONENT => 1,
+        GL_RG | GL_RG_INTEGER | GL_LUMINANCE_ALPHA | GL_DEPTH_STENCIL => 2,
+        GL_RGB | GL_RGB_INTEGER => 3,
+        GL_RGBA | GL_RGBA_INTEGER => 4,
+        _ => return None,
+    };
+    match type_ {
+        GL_UNSIGNED_BYTE | GL_BYTE => Some(components),
+        GL_UNSIGNED_SHORT | GL_SHORT | GL_HALF_FLOAT => Some(components * 2),
+        GL_UNSIGNED_INT | GL_INT | GL_FLOAT => Some(components * 4),
+        GL_UNSIGNED_SHORT_5_6_5 | GL_UNSIGNED_SHORT_4_4_4_4 | GL_UNSIGNED_SHORT_5_5_5_1 => Some(2),
+        GL_UNSIGNED_INT_2_10_10_10_REV
+        | GL_UNSIGNED_INT_10F_11F_11F_REV
+        | GL_UNSIGNED_INT_5_9_9_9_REV
+        | GL_UNSIGNED_INT_24_8 => Some(4),
+        GL_FLOAT_32_UNSIGNED_INT_24_8_REV => Some(8),
+        _ => None,
+    }
+}
+
+/// Uniform setter calls that changed a value, and those skipped because the
+/// value was already set. Animation loops that re-set the same matrices
+/// every frame show up as a high skip count.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UniformStats {
+    pub uploads: u32,
+    pub skipped: u32,
+}
+
 // TODO: each field in this structure should be sufficiently explained
 pub struct Context {
     pub(crate) textures: HashMap<u32, Texture>,
@@ -466,6 +553,8 @@ pub struct Context {
     pub(crate) renderbuffers: HashMap<u32, Renderbuffer>,
     pub(crate) samplers: HashMap<u32, Sampler>,
     pub(crate) transform_feedbacks: HashMap<u32, TransformFeedback>,
+    pub(crate) program_pipelines: HashMap<u32, ProgramPipeline>,
+    pub(crate) queries: HashMap<u32, Query>,
 
     pub(crate) next_texture_handle: u32,
     pub(crate) next_framebuffer_handle: u32,
@@ -475,6 +564,8 @@ pub struct Context {
     pub(crate) next_vertex_array_handle: u32,
     pub(crate) next_renderbuffer_handle: u32,
     pub(crate) next_transform_feedback_handle: u32,
+    pub(crate) next_program_pipeline_handle: u32,
+    pub(crate) next_query_handle: u32,
 
     pub(crate) bound_texture: Option<u32>,
     pub(crate) bound_read_framebuffer: Option<u32>,
@@ -486,8 +577,11 @@ pub struct Context {
     pub(crate) bound_vertex_array: u32,
     pub(crate) bound_transform_feedback: Option<u32>,
     pub(crate) current_program: Option<u32>,
+    pub(crate) bound_program_pipeline: Option<u32>,
+    pub(crate) active_timer_query: Option<u32>,
 
     pub(crate) uniform_data: Vec<u8>,
+    pub(crate) uniform_stats: UniformStats,
     pub(crate) attribute_buffer: Vec<u8>,
     pub(crate) varying_buffer: Vec<u8>,
     pub(crate) private_buffer: Vec<u8>,
@@ -506,8 +600,16 @@ pub struct Context {
     pub kernel: GpuKernel,
     pub default_framebuffer: crate::wasm_gl_emu::OwnedFramebuffer,
     pub rasterizer: crate::wasm_gl_emu::Rasterizer,
+    /// Attribute bindings of the current draw, kept to reuse the allocation
+    pub(crate) attribute_bindings: Vec<crate::wasm_gl_emu::transfer::AttributeBinding>,
+    /// Virtual GPU clock that draws advance and timer queries read
+    pub gpu_timeline: super::timing::GpuTimeline,
 
     pub(crate) clear_color: [f32; 4],
+    /// `clearDepth` value, clamped to [0, 1]
+    pub(crate) clear_depth: f32,
+    /// Representation of the default framebuffer's depth buffer
+    pub(crate) default_depth_precision: crate::wasm_gl_emu::rasterizer::DepthPrecision,
     pub(crate) viewport: (i32, i32, u32, u32),
     pub(crate) scissor_box: (i32, i32, u32, u32),
     pub(crate) scissor_test_enabled: bool,
@@ -518,6 +620,15 @@ pub struct Context {
     pub(crate) cull_face_enabled: bool,
     pub(crate) cull_face_mode: u32,
     pub(crate) front_face: u32,
+    pub(crate) dither_enabled: bool,
+    /// Multisample fragment state. Tracked for queries only: no framebuffer
+    /// has sample buffers, and without them these operations have no effect.
+    pub(crate) sample_alpha_to_coverage_enabled: bool,
+    pub(crate) sample_coverage_enabled: bool,
+    pub(crate) sample_coverage_value: f32,
+    pub(crate) sample_coverage_invert: bool,
+    /// Active debug visualization, see [`crate::wasm_gl_emu::rasterizer::DebugView`].
+    pub(crate) debug_view: crate::wasm_gl_emu::rasterizer::DebugView,
     pub(crate) active_texture_unit: u32,
     pub(crate) texture_units: Vec<Option<u32>>,
     pub(crate) sampler_units: Vec<Option<u32>>,
@@ -525,6 +636,31 @@ pub struct Context {
     pub(crate) default_draw_buffers: Vec<u32>,
     pub(crate) default_read_buffer: u32,
     pub debug_shaders: bool,
+    pub coverage_shaders: bool,
+    pub profile_shaders: bool,
+    /// Validate generated shader modules with wasmparser at link time
+    pub validate_shaders: bool,
+    pub webgl1: bool,
+    /// Compile shaders without `#version 300 es` as GLSL ES 1.00, with a
+    /// warning, in a WebGL2 context
+    pub glsl100_compat: bool,
+    /// Forward draws to a wgpu device instead of the rasterizer; only
+    /// honoured with the `gpu-backend` feature
+    pub gpu_backend: bool,
+    pub attributes: ContextAttributes,
+    pub limits: Limits,
+    /// UNPACK_PREMULTIPLY_ALPHA_WEBGL: premultiply RGBA8 uploads by alpha
+    pub(crate) unpack_premultiply_alpha: bool,
+    /// Row layout of `texSubImage2D` source data
+    pub(crate) unpack_layout: UnpackLayout,
+    pub texture_validation: TextureValidation,
+    pub read_origin: ReadOrigin,
+    /// What became of the last draw call, for `explainLastDraw`
+    pub(crate) last_draw: Option<super::explain::DrawReport>,
+    /// Opaque black texel bound in place of incomplete textures
+    pub(crate) incomplete_texture: Option<GpuHandle>,
+    /// Some object is flagged for deletion but still referenced
+    pub(crate) deletions_pending: bool,
 }
 
 impl Context {
@@ -534,6 +670,77 @@ impl Context {
         }
     }
 
+    /// Blend state for a draw; debug views write their colors unblended.
+    pub(crate) fn draw_blend_state(&self) -> crate::wasm_gl_emu::rasterizer::BlendState {
+        let mut blend = self.blend_state;
+        if self.debug_view != crate::wasm_gl_emu::rasterizer::DebugView::None {
+            blend.enabled = false;
+        }
+        blend
+    }
+
+    /// Color mask for a draw; debug views write all channels.
+    pub(crate) fn draw_color_mask(&self) -> crate::wasm_gl_emu::rasterizer::ColorMaskState {
+        if self.debug_view != crate::wasm_gl_emu::rasterizer::DebugView::None {
+            return Default::default();
+        }
+        self.color_mask
+    }
+
+    /// Depth state for a draw, with the precision of the depth buffer drawn
+    /// to. The default framebuffer has no depth buffer to test against when
+    /// created with `depth: false`.
+    pub(crate) fn draw_depth_state(&self) -> crate::wasm_gl_emu::rasterizer::DepthState {
+        let mut depth = self.depth_state;
+        if self.bound_draw_framebuffer.is_none() {
+            depth.enabled &= self.attributes.depth;
+            depth.precision = self.default_depth_precision;
+        } else {
+            let (_, _, _, internal_format) = self.get_depth_attachment_handle();
+            depth.precision =
+                crate::wasm_gl_emu::rasterizer::DepthPrecision::of_format(internal_format);
+        }
+        depth
+    }
+
+    /// Fail with INVALID_VALUE unless `index` is below `MAX_VERTEX_ATTRIBS`.
+    pub(crate) fn check_attrib_index(&mut self, index: u32) -> Result<(), u32> {
+        if index >= self.limits.max_vertex_attribs {
+            super::registry::set_last_error(&format!(
+                "attribute index {} is not below MAX_VERTEX_ATTRIBS ({})",
+                index, self.limits.max_vertex_attribs
+            ));
+            self.set_error(GL_INVALID_VALUE);
+            return Err(ERR_GL);
+        }
+        Ok(())
+    }
+
+    /// Fail with INVALID_VALUE when an image is larger than
+    /// `MAX_TEXTURE_SIZE` in either dimension.
+    pub(crate) fn check_image_size(&mut self, width: u32, height: u32) -> Result<(), u32> {
+        let max = self.limits.max_texture_size;
+        if width > max || height > max {
+            super::registry::set_last_error(&format!(
+                "{}x{} image exceeds MAX_TEXTURE_SIZE ({})",
+                width, height, max
+            ));
+            self.set_error(GL_INVALID_VALUE);
+            return Err(ERR_GL);
+        }
+        Ok(())
+    }
+
+    /// Stencil state for a draw. The default framebuffer has no stencil buffer
+    /// to test against unless created with `stencil: true`.
+    pub(crate) fn draw_stencil_state(&self) -> crate::wasm_gl_emu::rasterizer::StencilState {
+        let mut stencil = self.stencil_state;
+        if self.bound_draw_framebuffer.is_none() && !self.attributes.stencil {
+            stencil.enabled = false;
+        }
+        stencil
+    }
+
     pub fn new(width: u32, height: u32) -> Self {
         let mut kernel = GpuKernel::new();
         let default_framebuffer =
@@ -562,6 +769,8 @@ impl Context {
             renderbuffers: HashMap::new(),
             samplers: HashMap::new(),
             transform_feedbacks,
+            program_pipelines: HashMap::new(),
+            queries: HashMap::new(),
 
             next_texture_handle: FIRST_HANDLE,
             next_framebuffer_handle: FIRST_HANDLE,
@@ -571,6 +780,8 @@ impl Context {
             next_vertex_array_handle: FIRST_HANDLE,
             next_renderbuffer_handle: FIRST_HANDLE,
             next_transform_feedback_handle: FIRST_HANDLE,
+            next_program_pipeline_handle: FIRST_HANDLE,
+            next_query_handle: FIRST_HANDLE,
 
             bound_texture: None,
             bound_read_framebuffer: None,
@@ -582,7 +793,10 @@ impl Context {
             bound_vertex_array: 0,
             bound_transform_feedback: Some(0),
             current_program: None,
+            bound_program_pipeline: None,
+            active_timer_query: None,
 
+            uniform_stats: UniformStats::default(),
             uniform_data: {
                 let mut data = vec![0u8; 65536];
                 // Pre-populate context block pointers to data area
@@ -611,8 +825,12 @@ impl Context {
             kernel,
             default_framebuffer,
             rasterizer: crate::wasm_gl_emu::Rasterizer::new(),
+            attribute_bindings: Vec::new(),
+            gpu_timeline: super::timing::GpuTimeline::default(),
 
             clear_color: [0.0, 0.0, 0.0, 0.0],
+            clear_depth: 1.0,
+            default_depth_precision: Default::default(),
             viewport: (0, 0, width, height),
             scissor_box: (0, 0, width, height),
             scissor_test_enabled: false,
@@ -622,11 +840,18 @@ impl Context {
                 enabled: false,
                 func: 0x0201,
                 mask: true,
+                precision: Default::default(),
             },
             stencil_state: crate::wasm_gl_emu::rasterizer::StencilState::default(),
             cull_face_enabled: false,
             cull_face_mode: GL_BACK,
             front_face: GL_CCW,
+            dither_enabled: true,
+            sample_alpha_to_coverage_enabled: false,
+            sample_coverage_enabled: false,
+            sample_coverage_value: 1.0,
+            sample_coverage_invert: false,
+            debug_view: crate::wasm_gl_emu::rasterizer::DebugView::None,
             active_texture_unit: 0,
             texture_units: vec![None; 16],
             sampler_units: vec![None; 16],
@@ -634,6 +859,21 @@ impl Context {
             default_draw_buffers: vec![0x0405], // GL_BACK
             default_read_buffer: 0x0405,        // GL_BACK
             debug_shaders: false,
+            coverage_shaders: false,
+            profile_shaders: false,
+            validate_shaders: false,
+            webgl1: false,
+            glsl100_compat: false,
+            gpu_backend: false,
+            attributes: ContextAttributes::default(),
+            limits: Limits::default(),
+            unpack_premultiply_alpha: false,
+            unpack_layout: UnpackLayout::default(),
+            texture_validation: TextureValidation::default(),
+            read_origin: ReadOrigin::default(),
+            last_draw: None,
+            incomplete_texture: None,
+            deletions_pending: false,
         }
     }
 }
@@ -690,6 +930,24 @@ impl Context {
         h
     }
 
+    pub(crate) fn allocate_program_pipeline_handle(&mut self) -> u32 {
+        let h = self.next_program_pipeline_handle;
+        self.next_program_pipeline_handle = self.next_program_pipeline_handle.saturating_add(1);
+        if self.next_program_pipeline_handle == 0 {
+            self.next_program_pipeline_handle = FIRST_HANDLE;
+        }
+        h
+    }
+
+    pub(crate) fn allocate_query_handle(&mut self) -> u32 {
+        let h = self.next_query_handle;
+        self.next_query_handle = self.next_query_handle.saturating_add(1);
+        if self.next_query_handle == 0 {
+            self.next_query_handle = FIRST_HANDLE;
+        }
+        h
+    }
+
     pub(crate) fn allocate_vertex_array_handle(&mut self) -> u32 {
         let h = self.next_vertex_array_handle;
         self.next_vertex_array_handle = self.next_vertex_array_handle.saturating_add(1);
@@ -726,56 +984,56 @@ impl Context {
         );
     }
 
-    pub(crate) fn get_attribute_bindings(
+    /// Replace the contents of `bindings` with the bindings of the bound
+    /// vertex array.
+    pub(crate) fn fill_attribute_bindings(
         &self,
-    ) -> Vec<crate::wasm_gl_emu::transfer::AttributeBinding> {
+        bindings: &mut Vec<crate::wasm_gl_emu::transfer::AttributeBinding>,
+    ) {
         let vao = &self.vertex_arrays[&self.bound_vertex_array];
-        vao.attributes
-            .iter()
-            .map(|attr| {
-                let (buffer_ptr, offset) = if attr.enabled {
-                    let ptr = if let Some(buffer_id) = attr.buffer {
-                        if let Some(buf_obj) = self.buffers.get(&buffer_id) {
-                            if let Some(gpu_buf) = self.kernel.get_buffer(buf_obj.gpu_handle) {
-                                gpu_buf.data.as_ptr()
-                            } else {
-                                std::ptr::null()
-                            }
+        bindings.clear();
+        bindings.extend(vao.attributes.iter().map(|attr| {
+            let (buffer_ptr, offset) = if attr.enabled {
+                let ptr = if let Some(buffer_id) = attr.buffer {
+                    if let Some(buf_obj) = self.buffers.get(&buffer_id) {
+                        if let Some(gpu_buf) = self.kernel.get_buffer(buf_obj.gpu_handle) {
+                            gpu_buf.data.as_ptr()
                         } else {
                             std::ptr::null()
                         }
                     } else {
                         std::ptr::null()
-                    };
-                    (ptr, attr.offset as usize)
+                    }
                 } else {
-                    (std::ptr::null(), 0)
-                };
-
-                let type_size = match attr.type_ {
-                    0x1401 | 0x1400 => 1, // BYTE, UNSIGNED_BYTE
-                    0x1403 | 0x1402 => 2, // SHORT, UNSIGNED_SHORT
-                    _ => 4,               // FLOAT, INT, UNSIGNED_INT
+                    std::ptr::null()
                 };
+                (ptr, attr.offset as usize)
+            } else {
+                (std::ptr::null(), 0)
+            };
 
-                crate::wasm_gl_emu::transfer::AttributeBinding {
-                    buffer_ptr,
-                    type_: attr.type_,
-                    size: attr.size,
-                    normalized: attr.normalized,
-                    is_integer: attr.is_integer,
-                    offset,
-                    stride: if attr.stride == 0 {
-                        (attr.size as usize) * type_size
-                    } else {
-                        attr.stride as usize
-                    },
-                    type_size,
-                    divisor: attr.divisor,
-                    default_value: attr.default_value,
-                }
-            })
-            .collect()
+            let type_size =
+                crate::wasm_gl_emu::transfer::AttributeBinding::component_size(attr.type_);
+
+            crate::wasm_gl_emu::transfer::AttributeBinding {
+                buffer_ptr,
+                type_: attr.type_,
+                size: attr.size,
+                normalized: attr.normalized,
+                is_integer: attr.is_integer,
+                offset,
+                stride: if attr.stride == 0 {
+                    crate::wasm_gl_emu::transfer::AttributeBinding::tight_stride(
+                        attr.type_, attr.size,
+                    )
+                } else {
+                    attr.stride as usize
+                },
+                type_size,
+                divisor: attr.divisor,
+                default_value: attr.default_value,
+            }
+        }));
     }
 
     pub(crate) fn fetch_vertex_attributes_static(
@@ -815,11 +1073,8 @@ impl Context {
                     (std::ptr::null(), 0)
                 };
 
-                let type_size = match attr.type_ {
-                    0x1401 | 0x1400 => 1,
-                    0x1403 | 0x1402 => 2,
-                    _ => 4,
-                };
+                let type_size =
+                    crate::wasm_gl_emu::transfer::AttributeBinding::component_size(attr.type_);
 
                 crate::wasm_gl_emu::transfer::AttributeBinding {
                     buffer_ptr,
@@ -829,7 +1084,9 @@ impl Context {
                     is_integer: attr.is_integer,
                     offset,
                     stride: if attr.stride == 0 {
-                        (attr.size as usize) * type_size
+                        crate::wasm_gl_emu::transfer::AttributeBinding::tight_stride(
+                            attr.type_, attr.size,
+                        )
                     } else {
                         attr.stride as usize
                     },
@@ -848,61 +1105,77 @@ impl Context {
         );
     }
 
-    pub(crate) fn prepare_texture_metadata(&self, dest_ptr: u32) {
+    /// Filters and wrap modes `(mag, min, wrap_s, wrap_t, wrap_r)` that
+    /// texture unit `unit` samples `tex` with: those of the unit's sampler
+    /// object if one is bound, otherwise the texture's own.
+    pub(crate) fn sampling_parameters(
+        &self,
+        unit: usize,
+        tex: &Texture,
+    ) -> (u32, u32, u32, u32, u32) {
+        match self
+            .sampler_units
+            .get(unit)
+            .copied()
+            .flatten()
+            .and_then(|handle| self.samplers.get(&handle))
+        {
+            Some(s) => (s.mag_filter, s.min_filter, s.wrap_s, s.wrap_t, s.wrap_r),
+            None => (
+                tex.mag_filter,
+                tex.min_filter,
+                tex.wrap_s,
+                tex.wrap_t,
+                tex.wrap_r,
+            ),
+        }
+    }
+
+    /// Write the texture descriptors of all units to `dest_ptr`. Units in
+    /// `incomplete_units` sample the opaque black incomplete texture.
+    pub(crate) fn prepare_texture_metadata(&self, dest_ptr: usize, incomplete_units: &[usize]) {
         let mut bindings = Vec::with_capacity(self.texture_units.len());
-        for tex_handle in &self.texture_units {
-            let binding = if let Some(h) = tex_handle {
-                if let Some(tex) = self.textures.get(h) {
-                    if let Some(level0) = tex.levels.get(&0) {
-                        // Get sampler parameters (either from texture or separate sampler object)
-                        let idx = bindings.len();
-                        let (mag_filter, min_filter, wrap_s, wrap_t, wrap_r) =
-                            if let Some(sampler_handle) =
-                                self.sampler_units.get(idx).copied().flatten()
-                            {
-                                if let Some(s) = self.samplers.get(&sampler_handle) {
-                                    (s.mag_filter, s.min_filter, s.wrap_s, s.wrap_t, s.wrap_r)
-                                } else {
-                                    (
-                                        tex.mag_filter,
-                                        tex.min_filter,
-                                        tex.wrap_s,
-                                        tex.wrap_t,
-                                        tex.wrap_r,
-                                    )
-                                }
-                            } else {
-                                (
-                                    tex.mag_filter,
-                                    tex.min_filter,
-                                    tex.wrap_s,
-                                    tex.wrap_t,
-                                    tex.wrap_r,
-                                )
-                            };
-
-                        Some(crate::wasm_gl_emu::device::TextureBinding {
-                            width: level0.width,
-                            height: level0.height,
-                            depth: level0.depth,
-                            format: level0.internal_format,
-                            bytes_per_pixel: get_bytes_per_pixel(level0.internal_format),
-                            wrap_s,
-                            wrap_t,
-                            wrap_r,
-                            min_filter,
-                            mag_filter,
-                            gpu_handle: level0.gpu_handle,
-                        })
-                    } else {
-                        None
+        for (unit, tex_handle) in self.texture_units.iter().enumerate() {
+            if incomplete_units.contains(&unit) {
+                bindings.push(self.incomplete_texture.map(|gpu_handle| {
+                    crate::wasm_gl_emu::device::TextureBinding {
+                        width: 1,
+                        height: 1,
+                        depth: 1,
+                        format: GL_RGBA8,
+                        bytes_per_pixel: 4,
+                        wrap_s: GL_CLAMP_TO_EDGE,
+                        wrap_t: GL_CLAMP_TO_EDGE,
+                        wrap_r: GL_CLAMP_TO_EDGE,
+                        min_filter: GL_NEAREST,
+                        mag_filter: GL_NEAREST,
+                        lod_bias: 0.0,
+                        gpu_handle,
                     }
-                } else {
-                    None
-                }
-            } else {
-                None
-            };
+                }));
+                continue;
+            }
+            let binding = tex_handle
+                .and_then(|h| self.textures.get(&h))
+                .and_then(|tex| Some((tex, tex.levels.get(&0)?)))
+                .map(|(tex, level0)| {
+                    let (mag_filter, min_filter, wrap_s, wrap_t, wrap_r) =
+                        self.sampling_parameters(unit, tex);
+                    crate::wasm_gl_emu::device::TextureBinding {
+                        width: level0.width,
+                        height: level0.height,
+                        depth: level0.depth,
+                        format: level0.internal_format,
+                        bytes_per_pixel: get_bytes_per_pixel(level0.internal_format),
+                        wrap_s,
+                        wrap_t,
+                        wrap_r,
+                        min_filter,
+                        mag_filter,
+                        lod_bias: tex.lod_bias,
+                        gpu_handle: level0.gpu_handle,
+                    }
+                });
             bindings.push(binding);
         }
 
@@ -1006,6 +1279,12 @@ impl Context {
                         }
                     }
                 }
+                // Without color to draw, draws cover the depth/stencil buffers
+                if width == 0 && height == 0 {
+                    let (_, w, h, _) = self.get_depth_attachment_handle();
+                    let (_, sw, sh, _) = self.get_stencil_attachment_handle();
+                    (width, height) = if w != 0 || h != 0 { (w, h) } else { (sw, sh) };
+                }
                 return (handles, formats, width, height);
             }
         }
@@ -1103,28 +1382,27 @@ impl Context {
     }
 
     pub(crate) fn get_depth_attachment_handle(&self) -> (GpuHandle, u32, u32, u32) {
+        self.draw_attachment_handle(|fb| fb.depth_attachment)
+    }
+
+    pub(crate) fn get_stencil_attachment_handle(&self) -> (GpuHandle, u32, u32, u32) {
+        self.draw_attachment_handle(|fb| fb.stencil_attachment)
+    }
+
+    /// Handle, size and internal format of an attachment of the bound draw
+    /// framebuffer object
+    fn draw_attachment_handle(
+        &self,
+        attachment: impl Fn(&FramebufferObj) -> Option<Attachment>,
+    ) -> (GpuHandle, u32, u32, u32) {
         if let Some(fb_handle) = self.bound_draw_framebuffer {
-            if let Some(fb) = self.framebuffers.get(&fb_handle) {
-                match fb.depth_attachment {
-                    Some(Attachment::Texture(tex_handle)) => {
-                        if let Some(tex) = self.textures.get(&tex_handle) {
-                            if let Some(level0) = tex.levels.get(&0) {
-                                return (
-                                    level0.gpu_handle,
-                                    level0.width,
-                                    level0.height,
-                                    level0.internal_format,
-                                );
-                            }
-                        }
-                    }
-                    Some(Attachment::Renderbuffer(rb_handle)) => {
-                        if let Some(rb) = self.renderbuffers.get(&rb_handle) {
-                            return (rb.gpu_handle, rb.width, rb.height, rb.internal_format);
-                        }
-                    }
-                    None => {}
-                }
+            if let Some(image) = self
+                .framebuffers
+                .get(&fb_handle)
+                .and_then(attachment)
+                .and_then(|a| self.attachment_image(a))
+            {
+                return image;
             }
             (GpuHandle::invalid(), 0, 0, 0)
         } else {
@@ -1132,6 +1410,93 @@ impl Context {
         }
     }
 
+    /// Handle, size and internal format of the image an attachment refers to:
+    /// level 0 of a texture, or a renderbuffer's storage
+    fn attachment_image(&self, attachment: Attachment) -> Option<(GpuHandle, u32, u32, u32)> {
+        match attachment {
+            Attachment::Texture(tex_handle) => {
+                let level0 = self.textures.get(&tex_handle)?.levels.get(&0)?;
+                Some((
+                    level0.gpu_handle,
+                    level0.width,
+                    level0.height,
+                    level0.internal_format,
+                ))
+            }
+            Attachment::Renderbuffer(rb_handle) => {
+                let rb = self.renderbuffers.get(&rb_handle)?;
+                Some((rb.gpu_handle, rb.width, rb.height, rb.internal_format))
+            }
+        }
+    }
+
+    /// Completeness of a framebuffer object, as `checkFramebufferStatus`
+    /// reports it. Each attachment needs a non-empty image of a format that
+    /// fits its attachment point, all images must have the same size, and
+    /// WebGL2 only supports depth and stencil together as one image.
+    pub(crate) fn framebuffer_status(&self, fb: &FramebufferObj) -> u32 {
+        let points = fb
+            .color_attachments
+            .iter()
+            .map(|a| (*a, GL_COLOR_ATTACHMENT0))
+            .chain([
+                (fb.depth_attachment, GL_DEPTH_ATTACHMENT),
+                (fb.stencil_attachment, GL_STENCIL_ATTACHMENT),
+            ])
+            .filter_map(|(a, point)| a.map(|a| (self.attachment_image(a), point)));
+
+        let mut sizes = Vec::new();
+        for (image, point) in points {
+            let Some((_, width, height, internal_format)) = image else {
+                return GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT;
+            };
+            let format = gl_to_wgt_format(internal_format);
+            let fits = match point {
+                GL_DEPTH_ATTACHMENT => format.has_depth_aspect(),
+                GL_STENCIL_ATTACHMENT => format.has_stencil_aspect(),
+                _ => !format.is_depth_stencil_format(),
+            };
+            if width == 0 || height == 0 || internal_format == 0 || !fits {
+                return GL_FRAMEBUFFER_INCOMPLETE_ATTACHMENT;
+            }
+            sizes.push((width, height));
+        }
+
+        if sizes.is_empty() {
+            GL_FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT
+        } else if sizes.iter().any(|&size| size != sizes[0]) {
+            GL_FRAMEBUFFER_INCOMPLETE_DIMENSIONS
+        } else if fb.depth_attachment.is_some()
+            && fb.stencil_attachment.is_some()
+            && fb.depth_attachment != fb.stencil_attachment
+        {
+            GL_FRAMEBUFFER_UNSUPPORTED
+        } else {
+            GL_FRAMEBUFFER_COMPLETE
+        }
+    }
+
+    /// Fail with INVALID_FRAMEBUFFER_OPERATION when the bound draw
+    /// framebuffer object is incomplete, as draws and clears must.
+    pub(crate) fn check_draw_framebuffer(&mut self) -> Result<(), u32> {
+        let Some(fb) = self
+            .bound_draw_framebuffer
+            .and_then(|handle| self.framebuffers.get(&handle))
+        else {
+            return Ok(());
+        };
+        let status = self.framebuffer_status(fb);
+        if status != GL_FRAMEBUFFER_COMPLETE {
+            super::registry::set_last_error(&format!(
+                "draw framebuffer is incomplete: {}",
+                describe_enum(status)
+            ));
+            self.set_error(GL_INVALID_FRAMEBUFFER_OPERATION);
+            return Err(ERR_GL);
+        }
+        Ok(())
+    }
+
     pub(crate) fn get_buffer_handle_for_target(&self, target: u32) -> Option<u32> {
         if target == GL_ELEMENT_ARRAY_BUFFER {
             return self
@@ -42,6 +42,7 @@ pub fn ctx_delete_vertex_array(ctx: u32, vao: u32) -> u32 {
         if ctx_obj.bound_vertex_array == vao {
             ctx_obj.bound_vertex_array = 0;
         }
+        super::deletion::release_deleted_objects(ctx_obj);
         ERR_OK
     } else {
         set_last_error("vertex array not found");
@@ -98,6 +99,9 @@ pub fn ctx_enable_vertex_attrib_array(ctx: u32, index: u32) -> u32 {
         Some(c) => c,
         None => return ERR_INVALID_HANDLE,
     };
+    if let Err(code) = ctx_obj.check_attrib_index(index) {
+        return code;
+    }
 
     if let Some(vao) = ctx_obj.vertex_arrays.get_mut(&ctx_obj.bound_vertex_array) {
         if (index as usize) < vao.attributes.len() {
@@ -121,6 +125,9 @@ pub fn ctx_disable_vertex_attrib_array(ctx: u32, index: u32) -> u32 {
         Some(c) => c,
         None => return ERR_INVALID_HANDLE,
     };
+    if let Err(code) = ctx_obj.check_attrib_index(index) {
+        return code;
+    }
 
     if let Some(vao) = ctx_obj.vertex_arrays.get_mut(&ctx_obj.bound_vertex_array) {
         if (index as usize) < vao.attributes.len() {
@@ -136,16 +143,86 @@ pub fn ctx_disable_vertex_attrib_array(ctx: u32, index: u32) -> u32 {
     }
 }
 
-/// Vertex attribute pointer.
-fn get_type_size(type_: u32) -> u32 {
+/// Size in bytes of one component of `type_`, or `None` if `type_` is not
+/// a vertex attribute type. Packed types count as a single 4-byte component.
+fn get_type_size(type_: u32) -> Option<u32> {
     match type_ {
-        GL_BYTE | GL_UNSIGNED_BYTE => 1,
-        GL_SHORT | GL_UNSIGNED_SHORT | GL_HALF_FLOAT => 2,
-        GL_INT | GL_UNSIGNED_INT | GL_FLOAT => 4,
-        _ => 1,
+        GL_BYTE | GL_UNSIGNED_BYTE => Some(1),
+        GL_SHORT | GL_UNSIGNED_SHORT | GL_HALF_FLOAT => Some(2),
+        GL_INT | GL_UNSIGNED_INT | GL_FLOAT => Some(4),
+        GL_INT_2_10_10_10_REV | GL_UNSIGNED_INT_2_10_10_10_REV => Some(4),
+        _ => None,
     }
 }
 
+/// Check the arguments of vertexAttribPointer/vertexAttribIPointer in the
+/// order the WebGL 2 spec lists them. On failure returns the GL error to
+/// record and a message for the last-error string.
+fn check_attrib_pointer(
+    size: i32,
+    type_: u32,
+    stride: i32,
+    offset: u32,
+    integer: bool,
+    has_buffer: bool,
+) -> Result<(), (u32, &'static str)> {
+    if !(1..=4).contains(&size) {
+        return Err((GL_INVALID_VALUE, "size out of range"));
+    }
+    if !(0..=255).contains(&stride) {
+        return Err((GL_INVALID_VALUE, "stride out of range"));
+    }
+    // Offsets arrive as unsigned; anything past i32::MAX was negative in JS
+    if offset > i32::MAX as u32 {
+        return Err((GL_INVALID_VALUE, "offset must be non-negative"));
+    }
+
+    // Float-only types are not valid for vertexAttribIPointer
+    let packed = matches!(
+        type_,
+        GL_INT_2_10_10_10_REV | GL_UNSIGNED_INT_2_10_10_10_REV
+    );
+    let type_size = match type_ {
+        GL_FLOAT | GL_HALF_FLOAT if integer => None,
+        _ if packed && integer => None,
+        _ => get_type_size(type_),
+    };
+    let Some(type_size) = type_size else {
+        return Err((
+            GL_INVALID_ENUM,
+            if integer {
+                "invalid type for vertexAttribIPointer"
+            } else {
+                "invalid type for vertexAttribPointer"
+            },
+        ));
+    };
+
+    if packed && size != 4 {
+        return Err((GL_INVALID_OPERATION, "packed types require size 4"));
+    }
+    if !offset.is_multiple_of(type_size) {
+        return Err((
+            GL_INVALID_OPERATION,
+            "offset must be a multiple of the type size",
+        ));
+    }
+    if !(stride as u32).is_multiple_of(type_size) {
+        return Err((
+            GL_INVALID_OPERATION,
+            "stride must be a multiple of the type size",
+        ));
+    }
+    if !has_buffer && offset != 0 {
+        return Err((
+            GL_INVALID_OPERATION,
+            "offset is non-zero but no buffer is bound to ARRAY_BUFFER",
+        ));
+    }
+    Ok(())
+}
+
+/// Vertex attribute pointer.
 pub fn ctx_vertex_attrib_pointer(
     ctx: u32,
     index: u32,
@@ -161,24 +238,17 @@ pub fn ctx_vertex_attrib_pointer(
         Some(c) => c,
         None => return ERR_INVALID_HANDLE,
     };
-
-    let bound_buffer = ctx_obj.get_buffer_handle_for_target(GL_ARRAY_BUFFER);
-
-    if bound_buffer.is_none() && offset != 0 {
-        set_last_error("offset is non-zero but no buffer is bound to ARRAY_BUFFER");
-        ctx_obj.set_error(GL_INVALID_OPERATION);
-        return ERR_GL;
+    if let Err(code) = ctx_obj.check_attrib_index(index) {
+        return code;
     }
 
-    if !(0..=255).contains(&stride) {
-        set_last_error("stride out of range");
-        ctx_obj.set_error(GL_INVALID_VALUE);
-        return ERR_GL;
-    }
+    let bound_buffer = ctx_obj.get_buffer_handle_for_target(GL_ARRAY_BUFFER);
 
-    if !(1..=4).contains(&size) {
-        set_last_error("size out of range");
-        ctx_obj.set_error(GL_INVALID_VALUE);
+    if let Err((error, message)) =
+        check_attrib_pointer(size, type_, stride, offset, false, bound_buffer.is_some())
+    {
+        set_last_error(message);
+        ctx_obj.set_error(error);
         return ERR_GL;
     }
 
@@ -192,6 +262,7 @@ pub fn ctx_vertex_attrib_pointer(
             attr.offset = offset;
             attr.buffer = bound_buffer;
             attr.is_integer = false;
+            super::deletion::release_deleted_objects(ctx_obj);
             ERR_OK
         } else {
             set_last_error("index out of range");
@@ -218,49 +289,20 @@ pub fn ctx_vertex_attrib_ipointer(
         Some(c) => c,
         None => return ERR_INVALID_HANDLE,
     };
-
-    let bound_buffer = ctx_obj.get_buffer_handle_for_target(GL_ARRAY_BUFFER);
-
-    if bound_buffer.is_none() && offset != 0 {
-        set_last_error("offset is non-zero but no buffer is bound to ARRAY_BUFFER");
-        ctx_obj.set_error(GL_INVALID_OPERATION);
-        return ERR_GL;
+    if let Err(code) = ctx_obj.check_attrib_index(index) {
+        return code;
     }
 
-    if !(0..=255).contains(&stride) {
-        set_last_error("stride out of range");
-        ctx_obj.set_error(GL_INVALID_VALUE);
-        return ERR_GL;
-    }
+    let bound_buffer = ctx_obj.get_buffer_handle_for_target(GL_ARRAY_BUFFER);
 
-    if !(1..=4).contains(&size) {
-        set_last_error("size out of range");
-        ctx_obj.set_error(GL_INVALID_VALUE);
+    if let Err((error, message)) =
+        check_attrib_pointer(size, type_, stride, offset, true, bound_buffer.is_some())
+    {
+        set_last_error(message);
+        ctx_obj.set_error(error);
         return ERR_GL;
     }
 
-    // Check type and alignment (IPointer specific)
-    match type_ {
-        GL_BYTE | GL_UNSIGNED_BYTE | GL_SHORT | GL_UNSIGNED_SHORT | GL_INT | GL_UNSIGNED_INT => {
-            let type_size = get_type_size(type_);
-            if !offset.is_multiple_of(type_size) {
-                set_last_error("offset must be a multiple of the type size");
-                ctx_obj.set_error(GL_INVALID_OPERATION);
-                return ERR_GL;
-            }
-            if stride > 0 && !(stride as u32).is_multiple_of(type_size) {
-                set_last_error("stride must be a multiple of the type size");
-                ctx_obj.set_error(GL_INVALID_OPERATION);
-                return ERR_GL;
-            }
-        }
-        _ => {
-            set_last_error("invalid type for vertexAttribIPointer");
-            ctx_obj.set_error(GL_INVALID_ENUM);
-            return ERR_GL;
-        }
-    }
-
     if let Some(vao) = ctx_obj.vertex_arrays.get_mut(&ctx_obj.bound_vertex_array) {
         if (index as usize) < vao.attributes.len() {
             let attr = &mut vao.attributes[index as usize];
@@ -271,6 +313,7 @@ pub fn ctx_vertex_attrib_ipointer(
             attr.offset = offset;
             attr.buffer = bound_buffer;
             attr.is_integer = true;
+            super::deletion::release_deleted_objects(ctx_obj);
             ERR_OK
         } else {
             set_last_error("index out of range");
@@ -290,6 +333,9 @@ pub fn ctx_vertex_attrib_divisor(ctx: u32, index: u32, divisor: u32) -> u32 {
         Some(c) => c,
         None => return ERR_INVALID_HANDLE,
     };
+    if let Err(code) = ctx_obj.check_attrib_index(index) {
+        return code;
+    }
 
     if let Some(vao) = ctx_obj.vertex_arrays.get_mut(&ctx_obj.bound_vertex_array) {
         if (index as usize) < vao.attributes.len() {
@@ -329,6 +375,9 @@ pub fn ctx_vertex_attrib4f(ctx: u32, index: u32, v0: f32, v1: f32, v2: f32, v3:
         Some(c) => c,
         None => return ERR_INVALID_HANDLE,
     };
+    if let Err(code) = ctx_obj.check_attrib_index(index) {
+        return code;
+    }
 
     if let Some(vao) = ctx_obj.vertex_arrays.get_mut(&ctx_obj.bound_vertex_array) {
         if (index as usize) < vao.attributes.len() {
@@ -355,6 +404,9 @@ pub fn ctx_vertex_attrib_i4i(ctx: u32, index: u32, v0: i32, v1: i32, v2: i32, v3
         Some(c) => c,
         None => return ERR_INVALID_HANDLE,
     };
+    if let Err(code) = ctx_obj.check_attrib_index(index) {
+        return code;
+    }
 
     if let Some(vao) = ctx_obj.vertex_arrays.get_mut(&ctx_obj.bound_vertex_array) {
         if (index as usize) < vao.attributes.len() {
@@ -381,6 +433,9 @@ pub fn ctx_vertex_attrib_i4ui(ctx: u32, index: u32, v0: u32, v1: u32, v2: u32, v
         Some(c) => c,
         None => return ERR_INVALID_HANDLE,
     };
+    if let Err(code) = ctx_obj.check_attrib_index(index) {
+        return code;
+    }
 
     if let Some(vao) = ctx_obj.vertex_arrays.get_mut(&ctx_obj.bound_vertex_array) {
         if (index as usize) < vao.attributes.len() {
@@ -414,9 +469,8 @@ pub fn ctx_get_vertex_attrib_v4(
         None => return ERR_INVALID_HANDLE,
     };
 
-    if index >= 16 {
-        ctx_obj.gl_error = GL_INVALID_VALUE;
-        return ERR_GL;
+    if let Err(code) = ctx_obj.check_attrib_index(index) {
+        return code;
     }
 
     // Debug: check index
@@ -444,7 +498,10 @@ pub fn ctx_get_vertex_attrib_v4(
             if dest_len < 4 {
                 return ERR_INVALID_ARGS;
             }
-            let dest = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut i32, 1) };
+            let dest = match crate::guest_mem::write_slice(dest_ptr as *mut i32, 1) {
+                Ok(d) => d,
+                Err(code) => return code,
+            };
             dest[0] = if attr.enabled { 1 } else { 0 };
             ERR_OK
         }
@@ -452,7 +509,10 @@ pub fn ctx_get_vertex_attrib_v4(
             if dest_len < 4 {
                 return ERR_INVALID_ARGS;
             }
-            let dest = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut i32, 1) };
+            let dest = match crate::guest_mem::write_slice(dest_ptr as *mut i32, 1) {
+                Ok(d) => d,
+                Err(code) => return code,
+            };
             dest[0] = attr.size;
             ERR_OK
         }
@@ -460,7 +520,10 @@ pub fn ctx_get_vertex_attrib_v4(
             if dest_len < 4 {
                 return ERR_INVALID_ARGS;
             }
-            let dest = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut i32, 1) };
+            let dest = match crate::guest_mem::write_slice(dest_ptr as *mut i32, 1) {
+                Ok(d) => d,
+                Err(code) => return code,
+            };
             dest[0] = attr.stride;
             ERR_OK
         }
@@ -468,7 +531,10 @@ pub fn ctx_get_vertex_attrib_v4(
             if dest_len < 4 {
                 return ERR_INVALID_ARGS;
             }
-            let dest = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut i32, 1) };
+            let dest = match crate::guest_mem::write_slice(dest_ptr as *mut i32, 1) {
+                Ok(d) => d,
+                Err(code) => return code,
+            };
             dest[0] = attr.type_ as i32;
             ERR_OK
         }
@@ -476,7 +542,10 @@ pub fn ctx_get_vertex_attrib_v4(
             if dest_len < 4 {
                 return ERR_INVALID_ARGS;
             }
-            let dest = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut i32, 1) };
+            let dest = match crate::guest_mem::write_slice(dest_ptr as *mut i32, 1) {
+                Ok(d) => d,
+                Err(code) => return code,
+            };
             dest[0] = if attr.normalized { 1 } else { 0 };
             ERR_OK
         }
@@ -484,7 +553,10 @@ pub fn ctx_get_vertex_attrib_v4(
             if dest_len < 4 {
                 return ERR_INVALID_ARGS;
             }
-            let dest = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut i32, 1) };
+            let dest = match crate::guest_mem::write_slice(dest_ptr as *mut i32, 1) {
+                Ok(d) => d,
+                Err(code) => return code,
+            };
             dest[0] = if attr.is_integer { 1 } else { 0 };
             ERR_OK
         }
@@ -492,7 +564,10 @@ pub fn ctx_get_vertex_attrib_v4(
             if dest_len < 4 {
                 return ERR_INVALID_ARGS;
             }
-            let dest = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut i32, 1) };
+            let dest = match crate::guest_mem::write_slice(dest_ptr as *mut i32, 1) {
+                Ok(d) => d,
+                Err(code) => return code,
+            };
             dest[0] = attr.divisor as i32;
             ERR_OK
         }
@@ -500,7 +575,10 @@ pub fn ctx_get_vertex_attrib_v4(
             if dest_len < 4 {
                 return ERR_INVALID_ARGS;
             }
-            let dest = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut i32, 1) };
+            let dest = match crate::guest_mem::write_slice(dest_ptr as *mut i32, 1) {
+                Ok(d) => d,
+                Err(code) => return code,
+            };
             dest[0] = attr.offset as i32;
             ERR_OK
         }
@@ -508,7 +586,10 @@ pub fn ctx_get_vertex_attrib_v4(
             if dest_len < 4 {
                 return ERR_INVALID_ARGS;
             }
-            let dest = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut i32, 1) };
+            let dest = match crate::guest_mem::write_slice(dest_ptr as *mut i32, 1) {
+                Ok(d) => d,
+                Err(code) => return code,
+            };
             dest[0] = attr.buffer.unwrap_or(0) as i32;
             ERR_OK
         }
@@ -516,12 +597,13 @@ pub fn ctx_get_vertex_attrib_v4(
             if dest_len < 16 {
                 return ERR_INVALID_ARGS;
             }
-            let dest = unsafe { std::slice::from_raw_parts_mut(dest_ptr as *mut u32, 5) };
-            dest[0] = attr.default_value[0];
-            dest[1] = attr.default_value[1];
-            dest[2] = attr.default_value[2];
-            dest[3] = attr.default_value[3];
-            if dest_len >= 20 {
+            let count = if dest_len >= 20 { 5 } else { 4 };
+            let dest = match crate::guest_mem::write_slice(dest_ptr as *mut u32, count) {
+                Ok(d) => d,
+                Err(code) => return code,
+            };
+            dest[..4].copy_from_slice(&attr.default_value);
+            if count == 5 {
                 dest[4] = attr.current_value_type;
             }
             ERR_OK
@@ -146,6 +146,18 @@ export class WasmWebGLTransformFeedback {
   }
 }
 
+/**
+ * Thin wrapper for a program pipeline handle returned from WASM
+ * (GL_EXT_separate_shader_objects).
+ */
+export class WasmWebGLProgramPipeline {
+  constructor(ctx, handle) {
+    this._ctx = ctx;
+    this._handle = handle;
+    this._deleted = false;
+  }
+}
+
 /**
  * Thin wrapper for a WebGLUniformLocation handle returned from WASM.
  * @implements {WebGLUniformLocation}
@@ -0,0 +1,193 @@
+// @ts-check
+
+/**
+ * API traces: every method call made on a context, recorded so it can be
+ * replayed later on another context (or another build of the emulator).
+ *
+ * A trace is plain JSON:
+ * `{ version: 1, width, height, calls: [{ method, args, result? }] }`.
+ * Arguments that are WebGL objects become `{ ref: id }`, with the id given
+ * by the `result` of the call that created them; typed arrays and
+ * ArrayBuffers become `{ typed: 'Float32Array', base64 }`. Calls made on
+ * extension objects and arguments that aren't data (images, canvases) are
+ * not captured.
+ */
+
+export const TRACE_VERSION = 1;
+
+/** Calls after which a replay hashes the draw framebuffer */
+export const DRAW_METHODS = new Set([
+  'clear',
+  'clearBufferfv',
+  'clearBufferiv',
+  'clearBufferuiv',
+  'clearBufferfi',
+  'drawArrays',
+  'drawElements',
+  'drawArraysInstanced',
+  'drawElementsInstanced',
+  'drawRangeElements',
+  'blitFramebuffer',
+]);
+
+const TYPED_ARRAYS = {
+  Int8Array, Uint8Array, Uint8ClampedArray, Int16Array, Uint16Array, Int32Array, Uint32Array,
+  Float32Array, Float64Array, BigInt64Array, BigUint64Array,
+};
+
+/**
+ * Start recording the calls made through the returned `context`, a proxy of
+ * `gl` to hand to the code being traced. `stop()` ends the recording and
+ * returns the trace.
+ * @param {any} gl
+ */
+export function startTrace(gl) {
+  const calls = [];
+  /** @type {Map<object, number>} */
+  const ids = new Map();
+  let recording = true;
+
+  const encode = (value) => {
+    if (value === undefined) return { undefined: true };
+    if (value === null || typeof value !== 'object') return value;
+    if (Array.isArray(value)) return value.map(encode);
+    if (ArrayBuffer.isView(value) || value instanceof ArrayBuffer) {
+      const bytes = value instanceof ArrayBuffer
+        ? new Uint8Array(value)
+        : new Uint8Array(value.buffer, value.byteOffset, value.byteLength);
+      return { typed: value.constructor.name, base64: toBase64(bytes) };
+    }
+    const id = ids.get(value);
+    if (id !== undefined) return { ref: id };
+    return { unsupported: value.constructor?.name ?? typeof value };
+  };
+
+  const context = new Proxy(gl, {
+    get(target, property) {
+      const value = Reflect.get(target, property, target);
+      if (typeof value !== 'function' || typeof property !== 'string' || property.startsWith('_')) {
+        return value;
+      }
+      return (...args) => {
+        const result = value.apply(target, args);
+        if (recording) {
+          // Trailing undefined arguments are the same as absent ones
+          while (args.length && args[args.length - 1] === undefined) args.pop();
+          const call = { method: property, args: args.map(encode) };
+          if (result && typeof result === 'object' && !ArrayBuffer.isView(result) && !Array.isArray(result)) {
+            if (!ids.has(result)) ids.set(result, ids.size + 1);
+            call.result = { ref: ids.get(result) };
+          }
+          calls.push(call);
+        }
+        return result;
+      };
+    },
+  });
+
+  return {
+    context,
+    stop() {
+      recording = false;
+      return {
+        version: TRACE_VERSION,
+        width: gl.drawingBufferWidth,
+        height: gl.drawingBufferHeight,
+        calls,
+      };
+    },
+  };
+}
+
+/**
+ * Replay `trace` on `gl`, which should have the trace's size. `onDraw` is
+ * called after each call in `DRAW_METHODS` with the call's index.
+ * @param {any} gl
+ * @param {{ version: number, calls: { method: string, args: any[], result?: { ref: number } }[] }} trace
+ * @param {{ onDraw?: (index: number) => void, until?: number }} [options]
+ *   `until` stops the replay after that call
+ */
+export function replayTrace(gl, trace, { onDraw, until = Infinity } = {}) {
+  if (trace.version !== TRACE_VERSION) {
+    throw new Error(`unsupported trace version ${trace.version}`);
+  }
+  /** @type {Map<number, any>} */
+  const objects = new Map();
+  const decode = (value) => {
+    if (value === null || typeof value !== 'object') return value;
+    if (Array.isArray(value)) return value.map(decode);
+    if (value.undefined) return undefined;
+    if (value.ref !== undefined) return objects.get(value.ref) ?? null;
+    if (value.typed !== undefined) {
+      const bytes = fromBase64(value.base64);
+      if (value.typed === 'ArrayBuffer') return bytes.buffer;
+      const Typed = TYPED_ARRAYS[value.typed] ?? (value.typed === 'DataView' ? DataView : Uint8Array);
+      return Typed === DataView
+        ? new DataView(bytes.buffer)
+        : new Typed(bytes.buffer, 0, bytes.byteLength / Typed.BYTES_PER_ELEMENT);
+    }
+    return null;
+  };
+
+  for (let index = 0; index < trace.calls.length && index <= until; index++) {
+    const { method, args, result } = trace.calls[index];
+    if (typeof gl[method] !== 'function') {
+      throw new Error(`call ${index}: the context has no method ${method}`);
+    }
+    const value = gl[method](...args.map(decode));
+    if (result) objects.set(result.ref, value);
+    if (onDraw && DRAW_METHODS.has(method)) onDraw(index);
+  }
+}
+
+/**
+ * Read the viewport of the draw framebuffer as RGBA8. Framebuffers that
+ * can't be read that way (e.g. float attachments) read as zeros; the error
+ * this raises is left for the replayed code, so both sides of a comparison
+ * see the same errors.
+ * @param {any} gl
+ */
+export function readDrawFramebuffer(gl) {
+  const [x, y, width, height] = gl.getParameter(gl.VIEWPORT);
+  const read = gl.getParameter(gl.READ_FRAMEBUFFER_BINDING);
+  gl.bindFramebuffer(gl.READ_FRAMEBUFFER, gl.getParameter(gl.DRAW_FRAMEBUFFER_BINDING));
+  const pixels = new Uint8Array(width * height * 4);
+  try {
+    gl.readPixels(x, y, width, height, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+  } catch {
+    // Left as zeros
+  } finally {
+    gl.bindFramebuffer(gl.READ_FRAMEBUFFER, read);
+  }
+  return { width, height, pixels };
+}
+
+/**
+ * FNV-1a hash of pixels as 8 hex digits.
+ * @param {Uint8Array} pixels
+ */
+export function hashPixels(pixels) {
+  let hash = 0x811c9dc5;
+  for (let i = 0; i < pixels.length; i++) {
+    hash ^= pixels[i];
+    hash = Math.imul(hash, 0x01000193);
+  }
+  return (hash >>> 0).toString(16).padStart(8, '0');
+}
+
+function toBase64(bytes) {
+  if (typeof Buffer !== 'undefined') return Buffer.from(bytes).toString('base64');
+  let binary = '';
+  for (let i = 0; i < bytes.length; i += 0x8000) {
+    binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+  }
+  return btoa(binary);
+}
+
+function fromBase64(text) {
+  if (typeof Buffer !== 'undefined') return new Uint8Array(Buffer.from(text, 'base64'));
+  const binary = atob(text);
+  const bytes = new Uint8Array(binary.length);
+  for (let i = 0; i < binary.length; i++) bytes[i] = binary.charCodeAt(i);
+  return bytes;
+}
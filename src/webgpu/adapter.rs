@@ -1,7 +1,8 @@
 //! WebGPU Adapter, Instance, and Device initialization
 
+use super::trace::RecordedCommand;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use wgpu_core::global::Global;
 use wgpu_core::id::{
@@ -27,6 +28,11 @@ pub struct WebGpuContext {
     pub queues: HashMap<u32, QueueId>,
     pub buffers: HashMap<u32, BufferId>,
     pub buffer_to_device: HashMap<BufferId, DeviceId>,
+    /// Usage flags each buffer was created with, checked on every buffer operation
+    pub buffer_usages: HashMap<u32, wgt::BufferUsages>,
+    /// Buffers whose `destroy()` was called. Their handles stay registered so
+    /// later references fail validation instead of reaching a freed resource.
+    pub destroyed_buffers: HashSet<u32>,
     pub shader_modules: HashMap<u32, ShaderModuleId>,
     pub pipeline_layouts: HashMap<u32, PipelineLayoutId>,
     pub bind_group_layouts: HashMap<u32, BindGroupLayoutId>,
@@ -37,8 +43,18 @@ pub struct WebGpuContext {
     pub command_buffers: HashMap<u32, CommandBufferId>,
     pub textures: HashMap<u32, TextureId>,
     pub texture_views: HashMap<u32, TextureViewId>,
+    /// Texture each view was created from
+    pub texture_view_textures: HashMap<u32, u32>,
+    /// Textures whose `destroy()` was called, kept like `destroyed_buffers`
+    pub destroyed_textures: HashSet<u32>,
     pub samplers: HashMap<u32, SamplerId>,
     pub render_passes: HashMap<u32, wgpu_core::command::RenderPass>,
+    /// Encoder each open render pass was begun on
+    pub render_pass_encoders: HashMap<u32, u32>,
+    /// Commands recorded on each encoder, see [`crate::webgpu::trace`]
+    pub encoder_commands: HashMap<u32, Vec<RecordedCommand>>,
+    /// Commands of each finished command buffer until it is submitted
+    pub command_buffer_commands: HashMap<u32, Vec<RecordedCommand>>,
 
     pub next_adapter_id: u32,
     pub next_device_id: u32,
@@ -79,6 +95,8 @@ impl WebGpuContext {
             queues: HashMap::new(),
             buffers: HashMap::new(),
             buffer_to_device: HashMap::new(),
+            buffer_usages: HashMap::new(),
+            destroyed_buffers: HashSet::new(),
             shader_modules: HashMap::new(),
             pipeline_layouts: HashMap::new(),
             bind_group_layouts: HashMap::new(),
@@ -89,8 +107,13 @@ impl WebGpuContext {
             command_buffers: HashMap::new(),
             textures: HashMap::new(),
             texture_views: HashMap::new(),
+            texture_view_textures: HashMap::new(),
+            destroyed_textures: HashSet::new(),
             samplers: HashMap::new(),
             render_passes: HashMap::new(),
+            render_pass_encoders: HashMap::new(),
+            encoder_commands: HashMap::new(),
+            command_buffer_commands: HashMap::new(),
 
             next_adapter_id: 1,
             next_device_id: 1,
@@ -258,13 +281,22 @@ pub fn request_device(ctx_handle: u32, adapter_handle: u32) -> u32 {
 
 /// Destroy a device
 pub fn destroy_device(ctx_handle: u32, device_handle: u32) -> u32 {
-    with_context(ctx_handle, |ctx| {
+    let result: u32 = with_context(ctx_handle, |ctx| {
         if ctx.devices.remove(&device_handle).is_some() {
             super::WEBGPU_SUCCESS
         } else {
             super::WEBGPU_ERROR_INVALID_HANDLE
         }
-    })
+    });
+    if result == super::WEBGPU_SUCCESS {
+        crate::error::webgpu_device_lost(
+            ctx_handle,
+            device_handle,
+            crate::error::DeviceLostReason::Destroyed,
+            "Device was destroyed.",
+        );
+    }
+    result
 }
 
 pub fn get_adapter_features(ctx_handle: u32, adapter_handle: u32) -> u64 {
@@ -3,9 +3,10 @@ use crate::wasm_gl_emu;
 use std::any::Any;
 use std::collections::HashMap;
 use std::num::NonZero;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
 
 use crate::webgl2_context::types::*;
+use crate::webgpu::texture::{SampledTexture, ShaderBinding};
 use std::time::Duration;
 use wgpu_hal as hal;
 use wgpu_types as wgt;
@@ -106,12 +107,12 @@ impl hal::Instance for SoftInstance {
     ) -> Vec<hal::ExposedAdapter<SoftApi>> {
         let adapter = SoftAdapter {
             memory: wasm_gl_emu::ShaderMemoryLayout {
-                attr_ptr: self.attribute_buffer.as_ptr() as u32,
+                attr_ptr: self.attribute_buffer.as_ptr() as usize,
                 uniform_ptr: 0, // Set per draw
-                varying_ptr: self.varying_buffer.as_ptr() as u32,
-                private_ptr: self.private_buffer.as_ptr() as u32,
-                texture_ptr: self.texture_metadata.as_ptr() as u32,
-                frame_sp: (self.frame_stack.as_ptr() as usize + self.frame_stack.len()) as u32,
+                varying_ptr: self.varying_buffer.as_ptr() as usize,
+                private_ptr: self.private_buffer.as_ptr() as usize,
+                texture_ptr: self.texture_metadata.as_ptr() as usize,
+                frame_sp: self.frame_stack.as_ptr() as usize + self.frame_stack.len(),
             },
         };
 
@@ -262,6 +263,7 @@ impl SoftDevice {
 
         let backend_config = crate::naga_wasm_backend::WasmBackendConfig {
             debug_shaders: false,
+            host_sampling: true,
             ..Default::default()
         };
 
@@ -276,6 +278,7 @@ impl SoftDevice {
             attribute_locations: &HashMap::new(),
             uniform_locations: &HashMap::new(),
             varying_locations: &HashMap::new(),
+            varying_components: &HashMap::new(),
             varying_types: &HashMap::new(),
             uniform_types: &HashMap::new(),
             attribute_types: &HashMap::new(),
@@ -528,15 +531,12 @@ impl hal::Device for SoftDevice {
         &self,
         desc: &hal::TextureDescriptor,
     ) -> Result<SoftTexture, hal::DeviceError> {
-        let block_size = desc.format.block_copy_size(None).unwrap_or(4);
-        let size =
-            (desc.size.width * desc.size.height * desc.size.depth_or_array_layers * block_size)
-                as usize;
-        let data = vec![0; size];
+        let desc = SoftTextureDescriptor::from(desc);
+        let data = vec![0; desc.mip_level_offset(desc.mip_level_count)];
 
         Ok(SoftTexture {
             data: Arc::new(Mutex::new(data)),
-            desc: desc.into(),
+            desc,
         })
     }
 
@@ -764,6 +764,12 @@ pub enum SoftRenderCommand {
         width: u32,
         height: u32,
     },
+    SetBlendConstant {
+        color: [f32; 4],
+    },
+    SetStencilReference {
+        reference: u32,
+    },
 }
 
 #[derive(Debug)]
@@ -857,6 +863,159 @@ impl SoftQueue {
     }
 }
 
+/// A texture or sampler binding as seen by shaders: the descriptor block laid
+/// out at `output_layout::TEX_*_OFFSET` for the emitted helpers, and the state
+/// used by the host sampler.
+struct ShaderTexture {
+    block: [u32; 12],
+    binding: ShaderBinding,
+}
+
+fn gl_internal_format(format: wgt::TextureFormat) -> u32 {
+    match format {
+        wgt::TextureFormat::R32Float => GL_R32F,
+        wgt::TextureFormat::Rg32Float => GL_RG32F,
+        wgt::TextureFormat::Rgba32Float => GL_RGBA32F,
+        _ => GL_RGBA8,
+    }
+}
+
+fn gl_wrap_mode(mode: wgt::AddressMode) -> u32 {
+    match mode {
+        wgt::AddressMode::MirrorRepeat => GL_MIRRORED_REPEAT,
+        wgt::AddressMode::ClampToEdge => GL_CLAMP_TO_EDGE,
+        _ => GL_REPEAT,
+    }
+}
+
+fn gl_filter(filter: wgt::FilterMode) -> u32 {
+    match filter {
+        wgt::FilterMode::Nearest => GL_NEAREST,
+        wgt::FilterMode::Linear => GL_LINEAR,
+    }
+}
+
+fn gl_compare_func(func: wgt::CompareFunction) -> u32 {
+    match func {
+        wgt::CompareFunction::Never => GL_NEVER,
+        wgt::CompareFunction::Less => GL_LESS,
+        wgt::CompareFunction::Equal => GL_EQUAL,
+        wgt::CompareFunction::LessEqual => GL_LEQUAL,
+        wgt::CompareFunction::Greater => GL_GREATER,
+        wgt::CompareFunction::NotEqual => GL_NOTEQUAL,
+        wgt::CompareFunction::GreaterEqual => GL_GEQUAL,
+        wgt::CompareFunction::Always => GL_ALWAYS,
+    }
+}
+
+fn gl_stencil_op(op: wgt::StencilOperation) -> u32 {
+    match op {
+        wgt::StencilOperation::Keep => GL_KEEP,
+        wgt::StencilOperation::Zero => GL_ZERO,
+        wgt::StencilOperation::Replace => GL_REPLACE,
+        wgt::StencilOperation::Invert => GL_INVERT,
+        wgt::StencilOperation::IncrementClamp => GL_INCR,
+        wgt::StencilOperation::DecrementClamp => GL_DECR,
+        wgt::StencilOperation::IncrementWrap => GL_INCR_WRAP,
+        wgt::StencilOperation::DecrementWrap => GL_DECR_WRAP,
+    }
+}
+
+/// Stencil test state of a pipeline, compared against the pass's current
+/// stencil reference (`setStencilReference`).
+fn stencil_state(
+    ds: &wgt::DepthStencilState,
+    reference: u32,
+) -> wasm_gl_emu::rasterizer::StencilState {
+    let face = |f: &wgt::StencilFaceState| wasm_gl_emu::rasterizer::StencilFaceState {
+        func: gl_compare_func(f.compare),
+        ref_val: reference as i32,
+        mask: ds.stencil.read_mask,
+        fail: gl_stencil_op(f.fail_op),
+        zfail: gl_stencil_op(f.depth_fail_op),
+        zpass: gl_stencil_op(f.pass_op),
+        write_mask: ds.stencil.write_mask,
+    };
+    wasm_gl_emu::rasterizer::StencilState {
+        enabled: ds.stencil.is_enabled(),
+        front: face(&ds.stencil.front),
+        back: face(&ds.stencil.back),
+    }
+}
+
+/// Lock the textures bound to the stages and describe their texture and
+/// sampler bindings, keyed by uniform offset. The guards keep the storage
+/// pointers valid for the whole draw.
+fn lock_shader_textures<'a>(
+    bind_groups: &'a [Option<SoftBindGroup>],
+    stages: &[&SoftShaderStage],
+    guards: &mut Vec<MutexGuard<'a, Vec<u8>>>,
+) -> HashMap<u32, ShaderTexture> {
+    let mut locked: HashMap<usize, (*const u8, usize)> = HashMap::new();
+    let mut textures = HashMap::new();
+    for stage in stages {
+        for (&(group, binding), &(offset, _)) in &stage.uniform_map {
+            let Some(Some(bg)) = bind_groups.get(group as usize) else {
+                continue;
+            };
+            let texture = match bg.resources.get(&binding) {
+                Some(SoftResource::TextureView(view)) => {
+                    let (data, len) = *locked
+                        .entry(Arc::as_ptr(&view.texture) as usize)
+                        .or_insert_with(|| {
+                            let guard = view.texture.lock().unwrap();
+                            let storage = (guard.as_ptr(), guard.len());
+                            guards.push(guard);
+                            storage
+                        });
+                    let sampled = SampledTexture::new(view, data, len);
+                    let size = sampled.level_size(0);
+                    ShaderTexture {
+                        block: [
+                            size.width,
+                            size.height,
+                            data as u32 + sampled.base_offset() as u32,
+                            size.depth_or_array_layers,
+                            gl_internal_format(view.desc.format),
+                            view.texture_desc.texel_size(),
+                            GL_REPEAT,
+                            GL_REPEAT,
+                            GL_REPEAT,
+                            0, // Layout: Linear
+                            GL_LINEAR,
+                            GL_LINEAR,
+                        ],
+                        binding: ShaderBinding::Texture(sampled),
+                    }
+                }
+                Some(SoftResource::Sampler(sampler)) => {
+                    let modes = sampler.desc.address_modes;
+                    ShaderTexture {
+                        block: [
+                            0,
+                            0,
+                            0,
+                            0,
+                            0,
+                            0,
+                            gl_wrap_mode(modes[0]),
+                            gl_wrap_mode(modes[1]),
+                            gl_wrap_mode(modes[2]),
+                            0, // Layout
+                            gl_filter(sampler.desc.min_filter),
+                            gl_filter(sampler.desc.mag_filter),
+                        ],
+                        binding: ShaderBinding::Sampler(sampler.into()),
+                    }
+                }
+                _ => continue,
+            };
+            textures.insert(offset, texture);
+        }
+    }
+    textures
+}
+
 impl hal::Queue for SoftQueue {
     type A = SoftApi;
 
@@ -939,12 +1098,9 @@ impl hal::Queue for SoftQueue {
                         let src_data = src.lock().unwrap();
                         let mut dst_data = dst.lock().unwrap();
                         for region in regions {
-                            let bytes_per_pixel = match texture_desc.format {
-                                wgt::TextureFormat::R32Float => 4,
-                                wgt::TextureFormat::Rg32Float => 8,
-                                wgt::TextureFormat::Rgba32Float => 16,
-                                _ => 4,
-                            };
+                            let bytes_per_pixel = texture_desc.texel_size();
+                            let mip_level = region.texture_base.mip_level;
+                            let level_size = texture_desc.mip_level_size(mip_level);
                             let width = region.size.width;
                             let height = region.size.height;
                             let depth = region.size.depth;
@@ -955,14 +1111,15 @@ impl hal::Queue for SoftQueue {
                             let buffer_slice_pitch =
                                 region.buffer_layout.rows_per_image.unwrap_or(height)
                                     * buffer_row_pitch;
-                            let texture_row_pitch = texture_desc.size.width * bytes_per_pixel;
-                            let texture_slice_pitch = texture_desc.size.height * texture_row_pitch;
+                            let texture_row_pitch = level_size.width * bytes_per_pixel;
+                            let texture_slice_pitch = level_size.height * texture_row_pitch;
                             let src_offset = region.buffer_layout.offset;
                             let dst_origin = region.texture_base.origin;
-                            let dst_offset = (dst_origin.z * texture_slice_pitch
-                                + dst_origin.y * texture_row_pitch
-                                + dst_origin.x * bytes_per_pixel)
-                                as u64;
+                            let dst_offset = texture_desc.mip_level_offset(mip_level) as u64
+                                + (dst_origin.z * texture_slice_pitch
+                                    + dst_origin.y * texture_row_pitch
+                                    + dst_origin.x * bytes_per_pixel)
+                                    as u64;
 
                             for z in 0..depth {
                                 for y in 0..height {
@@ -1050,6 +1207,8 @@ impl hal::Queue for SoftQueue {
                     let mut viewport = (0i32, 0i32, 0u32, 0u32);
                     let mut scissor = (0i32, 0i32, 0u32, 0u32);
                     let mut scissor_enabled = false;
+                    let mut blend_constant = [0.0f32; 4];
+                    let mut stencil_reference = 0u32;
 
                     if let Some(Some(att)) = desc.color_attachments.first() {
                         viewport = (
@@ -1086,6 +1245,12 @@ impl hal::Queue for SoftQueue {
                                 scissor = (*x as i32, *y as i32, *width, *height);
                                 scissor_enabled = true;
                             }
+                            SoftRenderCommand::SetBlendConstant { color } => {
+                                blend_constant = *color;
+                            }
+                            SoftRenderCommand::SetStencilReference { reference } => {
+                                stencil_reference = *reference;
+                            }
                             SoftRenderCommand::SetPipeline(pipeline) => {
                                 current_pipeline = Some(pipeline);
                             }
@@ -1131,12 +1296,12 @@ impl hal::Queue for SoftQueue {
                                         let height = att.view.texture_desc.size.height;
 
                                         let internal_format = match att.view.texture_desc.format {
-                                            wgt::TextureFormat::R32Float => 0x822E,    // GL_R32F
-                                            wgt::TextureFormat::Rg32Float => 0x8230,   // GL_RG32F
-                                            wgt::TextureFormat::Rgba32Float => 0x8814, // GL_RGBA32F
+                                            wgt::TextureFormat::R32Float => GL_R32F,
+                                            wgt::TextureFormat::Rg32Float => GL_RG32F,
+                                            wgt::TextureFormat::Rgba32Float => GL_RGBA32F,
                                             wgt::TextureFormat::Rgba8Unorm
-                                            | wgt::TextureFormat::Bgra8Unorm => 0x8058, // GL_RGBA8
-                                            _ => 0x8058,
+                                            | wgt::TextureFormat::Bgra8Unorm => GL_RGBA8,
+                                            _ => GL_RGBA8,
                                         };
 
                                         let mut dummy_depth = vec![1.0; (width * height) as usize];
@@ -1157,195 +1322,40 @@ impl hal::Queue for SoftQueue {
                                             &[&pipeline.vertex_stage, &pipeline.fragment_stage],
                                         );
 
-                                        // Pre-lock textures to keep pointers valid during draw
-                                        type TexMetadata = (
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                        );
-                                        let mut texture_metas: HashMap<u32, TexMetadata> =
-                                            HashMap::new();
+                                        // Lock sampled textures so their storage stays valid during the draw
                                         let mut _texture_guards = Vec::new();
-                                        let mut _already_locked = HashMap::new();
-
-                                        for stage in
-                                            &[&pipeline.vertex_stage, &pipeline.fragment_stage]
-                                        {
-                                            for (&(group, binding), &(offset, _)) in
-                                                &stage.uniform_map
-                                            {
-                                                if let Some(Some(bg)) =
-                                                    bind_groups.get(group as usize)
-                                                {
-                                                    match bg.resources.get(&binding) {
-                                                        Some(SoftResource::TextureView(view)) => {
-                                                            let tex_ptr =
-                                                                Arc::as_ptr(&view.texture) as usize;
-                                                            let data_ptr = if let Some(&p) =
-                                                                _already_locked.get(&tex_ptr)
-                                                            {
-                                                                p
-                                                            } else {
-                                                                let guard =
-                                                                    view.texture.lock().unwrap();
-                                                                let p = guard.as_ptr() as u32;
-                                                                _texture_guards.push(guard);
-                                                                _already_locked.insert(tex_ptr, p);
-                                                                p
-                                                            };
-
-                                                            let format = match view
-                                                                .texture_desc
-                                                                .format
-                                                            {
-                                                                wgt::TextureFormat::R32Float => {
-                                                                    0x822E
-                                                                }
-                                                                wgt::TextureFormat::Rg32Float => {
-                                                                    0x8230
-                                                                }
-                                                                wgt::TextureFormat::Rgba32Float => {
-                                                                    0x8814
-                                                                }
-                                                                wgt::TextureFormat::Rgba8Unorm
-                                                                | wgt::TextureFormat::Bgra8Unorm => {
-                                                                    0x8058
-                                                                }
-                                                                _ => 0x8058,
-                                                            };
-                                                            let bpp = match format {
-                                                                0x822E => 4,
-                                                                0x8230 => 8,
-                                                                0x8814 => 16,
-                                                                _ => 4,
-                                                            };
-
-                                                            texture_metas.insert(
-                                                                offset,
-                                                                (
-                                                                    view.texture_desc.size.width,
-                                                                    view.texture_desc.size.height,
-                                                                    data_ptr,
-                                                                    view.texture_desc
-                                                                        .size
-                                                                        .depth_or_array_layers,
-                                                                    format,
-                                                                    bpp,
-                                                                    0x2901, // GL_REPEAT
-                                                                    0x2901, // GL_REPEAT
-                                                                    0x2901, // Default wrap_r
-                                                                    0,      // Layout: Linear
-                                                                    0x2601, // min_filter: Linear
-                                                                    0x2601, // mag_filter: Linear
-                                                                ),
-                                                            );
-                                                        }
-                                                        Some(SoftResource::Sampler(sampler)) => {
-                                                            let wrap_s = match sampler
-                                                                .desc
-                                                                .address_modes[0]
-                                                            {
-                                                                wgt::AddressMode::Repeat => 0x2901,
-                                                                wgt::AddressMode::MirrorRepeat => {
-                                                                    0x8370
-                                                                }
-                                                                wgt::AddressMode::ClampToEdge => {
-                                                                    0x812F
-                                                                }
-                                                                _ => 0x2901,
-                                                            };
-                                                            let wrap_t = match sampler
-                                                                .desc
-                                                                .address_modes[1]
-                                                            {
-                                                                wgt::AddressMode::Repeat => 0x2901,
-                                                                wgt::AddressMode::MirrorRepeat => {
-                                                                    0x8370
-                                                                }
-                                                                wgt::AddressMode::ClampToEdge => {
-                                                                    0x812F
-                                                                }
-                                                                _ => 0x2901,
-                                                            };
-                                                            let wrap_r = match sampler
-                                                                .desc
-                                                                .address_modes[2]
-                                                            {
-                                                                wgt::AddressMode::Repeat => 0x2901,
-                                                                wgt::AddressMode::MirrorRepeat => {
-                                                                    0x8370
-                                                                }
-                                                                _ => 0x2901,
-                                                            };
-                                                            let min_filter = match sampler
-                                                                .desc
-                                                                .min_filter
-                                                            {
-                                                                wgt::FilterMode::Nearest => 0x2600,
-                                                                wgt::FilterMode::Linear => 0x2601,
-                                                            };
-                                                            let mag_filter = match sampler
-                                                                .desc
-                                                                .mag_filter
-                                                            {
-                                                                wgt::FilterMode::Nearest => 0x2600,
-                                                                wgt::FilterMode::Linear => 0x2601,
-                                                            };
-
-                                                            texture_metas.insert(
-                                                                offset,
-                                                                (
-                                                                    0, 0, 0, 0, 0, 0, wrap_s,
-                                                                    wrap_t, wrap_r,
-                                                                    0, // Layout
-                                                                    min_filter, mag_filter,
-                                                                ),
-                                                            );
-                                                        }
-                                                        _ => {}
-                                                    }
-                                                }
-                                            }
-                                        }
+                                        let shader_textures = lock_shader_textures(
+                                            &bind_groups,
+                                            &[&pipeline.vertex_stage, &pipeline.fragment_stage],
+                                            &mut _texture_guards,
+                                        );
 
                                         let prepare_textures = Box::new(
                                             move |mem: &wasm_gl_emu::ShaderMemoryLayout| {
-                                                for (&offset, meta) in &texture_metas {
+                                                for (&offset, texture) in &shader_textures {
                                                     // Place metadata after the 256-byte context block in the texture region.
                                                     // Each binding gets 64 bytes for its metadata.
-                                                    let data_offset = 256 + (offset / 4) * 64;
-                                                    let data_addr = mem.texture_ptr + data_offset;
+                                                    let data_addr = mem.texture_ptr
+                                                        + 256
+                                                        + (offset as usize / 4) * 64;
                                                     unsafe {
                                                         // 1. Write the pointer to the UNIFORM context block (where the shader looks)
-                                                        let ctx_ptr =
-                                                            (mem.uniform_ptr + offset) as *mut u32;
-                                                        *ctx_ptr = data_addr;
+                                                        *((mem.uniform_ptr + offset as usize)
+                                                            as *mut u32) = data_addr as u32;
 
                                                         // 2. Write metadata to the pointed-to location (in the texture region)
-                                                        let base = data_addr as *mut i32;
-                                                        *base.offset(0) = meta.0 as i32; // width
-                                                        *base.offset(1) = meta.1 as i32; // height
-                                                        *base.offset(2) = meta.2 as i32; // data_ptr
-                                                        *base.offset(3) = meta.3 as i32; // depth
-                                                        *base.offset(4) = meta.4 as i32; // format
-                                                        *base.offset(5) = meta.5 as i32; // bpp
-                                                        *base.offset(6) = meta.6 as i32; // wrap_s
-                                                        *base.offset(7) = meta.7 as i32; // wrap_t
-                                                        *base.offset(8) = meta.8 as i32; // wrap_r
-                                                        *base.offset(9) = meta.9 as i32; // layout
-                                                        *base.offset(10) = meta.10 as i32; // min_filter
-                                                        *base.offset(11) = meta.11 as i32;
-                                                        // mag_filter
+                                                        let base = data_addr as *mut u32;
+                                                        for (i, &value) in
+                                                            texture.block.iter().enumerate()
+                                                        {
+                                                            *base.add(i) = value;
+                                                        }
                                                     }
+                                                    // 3. Register the binding for the host sampler
+                                                    crate::webgpu::texture::bind_for_draw(
+                                                        data_addr as u32,
+                                                        texture.binding.clone(),
+                                                    );
                                                 }
                                             },
                                         );
@@ -1354,11 +1364,13 @@ impl hal::Queue for SoftQueue {
                                             wasm_gl_emu::RasterPipeline::new();
                                         raster_pipeline.memory = self.memory;
                                         raster_pipeline.memory.uniform_ptr =
-                                            combined_uniforms.as_ptr() as u32;
+                                            combined_uniforms.as_ptr() as usize;
                                         raster_pipeline.vs_table_idx =
                                             Some(pipeline.vertex_stage.wasm_module.table_index);
                                         raster_pipeline.fs_table_idx =
                                             Some(pipeline.fragment_stage.wasm_module.table_index);
+                                        raster_pipeline.shading_rate =
+                                            pipeline.fragment_stage.wasm_module.shading_rate;
 
                                         let mut state = wasm_gl_emu::RenderState {
                                             ctx_handle: 0,
@@ -1377,9 +1389,11 @@ impl hal::Queue for SoftQueue {
                                             cull_face_enabled: false,
                                             cull_face_mode: GL_BACK,
                                             front_face: GL_CCW,
+                                            debug_view: wasm_gl_emu::DebugView::None,
+                                            dither: false,
                                         };
                                         state.memory.uniform_ptr =
-                                            combined_uniforms.as_ptr() as u32;
+                                            combined_uniforms.as_ptr() as usize;
 
                                         // Map depth/blend state
                                         if let Some(ds) = &pipeline.depth_stencil {
@@ -1395,7 +1409,9 @@ impl hal::Queue for SoftQueue {
                                                 wgt::CompareFunction::GreaterEqual => GL_GEQUAL,
                                                 wgt::CompareFunction::Always => GL_ALWAYS,
                                             };
+                                            state.stencil = stencil_state(ds, stencil_reference);
                                         }
+                                        state.blend.color = blend_constant;
 
                                         if let Some(Some(target)) = pipeline.color_targets.first() {
                                             if let Some(blend) = &target.blend {
@@ -1419,6 +1435,10 @@ impl hal::Queue for SoftQueue {
                                                     wgt::BlendFactor::OneMinusDstAlpha => {
                                                         GL_ONE_MINUS_DST_ALPHA
                                                     }
+                                                    wgt::BlendFactor::Constant => GL_CONSTANT_COLOR,
+                                                    wgt::BlendFactor::OneMinusConstant => {
+                                                        GL_ONE_MINUS_CONSTANT_COLOR
+                                                    }
                                                     _ => GL_ONE,
                                                 };
                                                 let map_op = |o| match o {
@@ -1490,6 +1510,7 @@ impl hal::Queue for SoftQueue {
                                                     },
                                                 },
                                             );
+                                        crate::webgpu::texture::clear_draw_bindings();
                                     }
                                 }
                             }
@@ -1507,12 +1528,12 @@ impl hal::Queue for SoftQueue {
                                         let height = att.view.texture_desc.size.height;
 
                                         let internal_format = match att.view.texture_desc.format {
-                                            wgt::TextureFormat::R32Float => 0x822E,
-                                            wgt::TextureFormat::Rg32Float => 0x8230,
-                                            wgt::TextureFormat::Rgba32Float => 0x8814,
+                                            wgt::TextureFormat::R32Float => GL_R32F,
+                                            wgt::TextureFormat::Rg32Float => GL_RG32F,
+                                            wgt::TextureFormat::Rgba32Float => GL_RGBA32F,
                                             wgt::TextureFormat::Rgba8Unorm
-                                            | wgt::TextureFormat::Bgra8Unorm => 0x8058,
-                                            _ => 0x8058,
+                                            | wgt::TextureFormat::Bgra8Unorm => GL_RGBA8,
+                                            _ => GL_RGBA8,
                                         };
 
                                         let mut dummy_depth = vec![1.0; (width * height) as usize];
@@ -1533,195 +1554,40 @@ impl hal::Queue for SoftQueue {
                                             &[&pipeline.vertex_stage, &pipeline.fragment_stage],
                                         );
 
-                                        // Pre-lock textures to keep pointers valid during draw
-                                        type TexMetadata = (
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                            u32,
-                                        );
-                                        let mut texture_metas: HashMap<u32, TexMetadata> =
-                                            HashMap::new();
+                                        // Lock sampled textures so their storage stays valid during the draw
                                         let mut _texture_guards = Vec::new();
-                                        let mut _already_locked = HashMap::new();
-
-                                        for stage in
-                                            &[&pipeline.vertex_stage, &pipeline.fragment_stage]
-                                        {
-                                            for (&(group, binding), &(offset, _)) in
-                                                &stage.uniform_map
-                                            {
-                                                if let Some(Some(bg)) =
-                                                    bind_groups.get(group as usize)
-                                                {
-                                                    match bg.resources.get(&binding) {
-                                                        Some(SoftResource::TextureView(view)) => {
-                                                            let tex_ptr =
-                                                                Arc::as_ptr(&view.texture) as usize;
-                                                            let data_ptr = if let Some(&p) =
-                                                                _already_locked.get(&tex_ptr)
-                                                            {
-                                                                p
-                                                            } else {
-                                                                let guard =
-                                                                    view.texture.lock().unwrap();
-                                                                let p = guard.as_ptr() as u32;
-                                                                _texture_guards.push(guard);
-                                                                _already_locked.insert(tex_ptr, p);
-                                                                p
-                                                            };
-
-                                                            let format = match view
-                                                                .texture_desc
-                                                                .format
-                                                            {
-                                                                wgt::TextureFormat::R32Float => {
-                                                                    0x822E
-                                                                }
-                                                                wgt::TextureFormat::Rg32Float => {
-                                                                    0x8230
-                                                                }
-                                                                wgt::TextureFormat::Rgba32Float => {
-                                                                    0x8814
-                                                                }
-                                                                wgt::TextureFormat::Rgba8Unorm
-                                                                | wgt::TextureFormat::Bgra8Unorm => {
-                                                                    0x8058
-                                                                }
-                                                                _ => 0x8058,
-                                                            };
-                                                            let bpp = match format {
-                                                                0x822E => 4,
-                                                                0x8230 => 8,
-                                                                0x8814 => 16,
-                                                                _ => 4,
-                                                            };
-
-                                                            texture_metas.insert(
-                                                                offset,
-                                                                (
-                                                                    view.texture_desc.size.width,
-                                                                    view.texture_desc.size.height,
-                                                                    data_ptr,
-                                                                    view.texture_desc
-                                                                        .size
-                                                                        .depth_or_array_layers,
-                                                                    format,
-                                                                    bpp,
-                                                                    0x2901, // GL_REPEAT
-                                                                    0x2901, // GL_REPEAT
-                                                                    0x2901, // Default wrap_r
-                                                                    0,      // Layout: Linear
-                                                                    0x2601, // min_filter: Linear
-                                                                    0x2601, // mag_filter: Linear
-                                                                ),
-                                                            );
-                                                        }
-                                                        Some(SoftResource::Sampler(sampler)) => {
-                                                            let wrap_s = match sampler
-                                                                .desc
-                                                                .address_modes[0]
-                                                            {
-                                                                wgt::AddressMode::Repeat => 0x2901,
-                                                                wgt::AddressMode::MirrorRepeat => {
-                                                                    0x8370
-                                                                }
-                                                                wgt::AddressMode::ClampToEdge => {
-                                                                    0x812F
-                                                                }
-                                                                _ => 0x2901,
-                                                            };
-                                                            let wrap_t = match sampler
-                                                                .desc
-                                                                .address_modes[1]
-                                                            {
-                                                                wgt::AddressMode::Repeat => 0x2901,
-                                                                wgt::AddressMode::MirrorRepeat => {
-                                                                    0x8370
-                                                                }
-                                                                wgt::AddressMode::ClampToEdge => {
-                                                                    0x812F
-                                                                }
-                                                                _ => 0x2901,
-                                                            };
-                                                            let wrap_r = match sampler
-                                                                .desc
-                                                                .address_modes[2]
-                                                            {
-                                                                wgt::AddressMode::Repeat => 0x2901,
-                                                                wgt::AddressMode::MirrorRepeat => {
-                                                                    0x8370
-                                                                }
-                                                                _ => 0x2901,
-                                                            };
-                                                            let min_filter = match sampler
-                                                                .desc
-                                                                .min_filter
-                                                            {
-                                                                wgt::FilterMode::Nearest => 0x2600,
-                                                                wgt::FilterMode::Linear => 0x2601,
-                                                            };
-                                                            let mag_filter = match sampler
-                                                                .desc
-                                                                .mag_filter
-                                                            {
-                                                                wgt::FilterMode::Nearest => 0x2600,
-                                                                wgt::FilterMode::Linear => 0x2601,
-                                                            };
-
-                                                            texture_metas.insert(
-                                                                offset,
-                                                                (
-                                                                    0, 0, 0, 0, 0, 0, wrap_s,
-                                                                    wrap_t, wrap_r,
-                                                                    0, // Layout
-                                                                    min_filter, mag_filter,
-                                                                ),
-                                                            );
-                                                        }
-                                                        _ => {}
-                                                    }
-                                                }
-                                            }
-                                        }
+                                        let shader_textures = lock_shader_textures(
+                                            &bind_groups,
+                                            &[&pipeline.vertex_stage, &pipeline.fragment_stage],
+                                            &mut _texture_guards,
+                                        );
 
                                         let prepare_textures = Box::new(
                                             move |mem: &wasm_gl_emu::ShaderMemoryLayout| {
-                                                for (&offset, meta) in &texture_metas {
-                                                    // Place metadata after the 256-byte context block.
+                                                for (&offset, texture) in &shader_textures {
+                                                    // Place metadata after the 256-byte context block in the texture region.
                                                     // Each binding gets 64 bytes for its metadata.
-                                                    let data_offset = 256 + (offset / 4) * 64;
-                                                    let data_addr = mem.texture_ptr + data_offset;
+                                                    let data_addr = mem.texture_ptr
+                                                        + 256
+                                                        + (offset as usize / 4) * 64;
                                                     unsafe {
-                                                        // 1. Write the pointer to the UNIFORM context block
-                                                        let ctx_ptr =
-                                                            (mem.uniform_ptr + offset) as *mut u32;
-                                                        *ctx_ptr = data_addr;
-
-                                                        // 2. Write metadata to the pointed-to location
-                                                        let base = data_addr as *mut i32;
-                                                        *base.offset(0) = meta.0 as i32; // width
-                                                        *base.offset(1) = meta.1 as i32; // height
-                                                        *base.offset(2) = meta.2 as i32; // data_ptr
-                                                        *base.offset(3) = meta.3 as i32; // depth
-                                                        *base.offset(4) = meta.4 as i32; // format
-                                                        *base.offset(5) = meta.5 as i32; // bpp
-                                                        *base.offset(6) = meta.6 as i32; // wrap_s
-                                                        *base.offset(7) = meta.7 as i32; // wrap_t
-                                                        *base.offset(8) = meta.8 as i32; // wrap_r
-                                                        *base.offset(9) = meta.9 as i32; // layout
-                                                        *base.offset(10) = meta.10 as i32; // min_filter
-                                                        *base.offset(11) = meta.11 as i32;
-                                                        // mag_filter
+                                                        // 1. Write the pointer to the UNIFORM context block (where the shader looks)
+                                                        *((mem.uniform_ptr + offset as usize)
+                                                            as *mut u32) = data_addr as u32;
+
+                                                        // 2. Write metadata to the pointed-to location (in the texture region)
+                                                        let base = data_addr as *mut u32;
+                                                        for (i, &value) in
+                                                            texture.block.iter().enumerate()
+                                                        {
+                                                            *base.add(i) = value;
+                                                        }
                                                     }
+                                                    // 3. Register the binding for the host sampler
+                                                    crate::webgpu::texture::bind_for_draw(
+                                                        data_addr as u32,
+                                                        texture.binding.clone(),
+                                                    );
                                                 }
                                             },
                                         );
@@ -1743,9 +1609,11 @@ impl hal::Queue for SoftQueue {
                                             cull_face_enabled: false,
                                             cull_face_mode: GL_BACK,
                                             front_face: GL_CCW,
+                                            debug_view: wasm_gl_emu::DebugView::None,
+                                            dither: false,
                                         };
                                         state.memory.uniform_ptr =
-                                            combined_uniforms.as_ptr() as u32;
+                                            combined_uniforms.as_ptr() as usize;
 
                                         // Map depth/blend state (same as Draw)
                                         if let Some(ds) = &pipeline.depth_stencil {
@@ -1761,7 +1629,9 @@ impl hal::Queue for SoftQueue {
                                                 wgt::CompareFunction::GreaterEqual => GL_GEQUAL,
                                                 wgt::CompareFunction::Always => GL_ALWAYS,
                                             };
+                                            state.stencil = stencil_state(ds, stencil_reference);
                                         }
+                                        state.blend.color = blend_constant;
 
                                         // Map blend state from pipeline
                                         if let Some(Some(target)) = pipeline.color_targets.first() {
@@ -1786,6 +1656,10 @@ impl hal::Queue for SoftQueue {
                                                     wgt::BlendFactor::OneMinusDstAlpha => {
                                                         GL_ONE_MINUS_DST_ALPHA
                                                     }
+                                                    wgt::BlendFactor::Constant => GL_CONSTANT_COLOR,
+                                                    wgt::BlendFactor::OneMinusConstant => {
+                                                        GL_ONE_MINUS_CONSTANT_COLOR
+                                                    }
                                                     _ => GL_ONE,
                                                 };
                                                 let map_op = |o| match o {
@@ -1817,11 +1691,13 @@ impl hal::Queue for SoftQueue {
                                             wasm_gl_emu::RasterPipeline::new();
                                         raster_pipeline.memory = self.memory;
                                         raster_pipeline.memory.uniform_ptr =
-                                            combined_uniforms.as_ptr() as u32;
+                                            combined_uniforms.as_ptr() as usize;
                                         raster_pipeline.vs_table_idx =
                                             Some(pipeline.vertex_stage.wasm_module.table_index);
                                         raster_pipeline.fs_table_idx =
                                             Some(pipeline.fragment_stage.wasm_module.table_index);
+                                        raster_pipeline.shading_rate =
+                                            pipeline.fragment_stage.wasm_module.shading_rate;
 
                                         // Fetch index buffer data
                                         let indices = if let Some((buffer, offset, format)) =
@@ -1905,6 +1781,7 @@ impl hal::Queue for SoftQueue {
                                                     wgt::PrimitiveTopology::TriangleStrip => GL_TRIANGLE_STRIP,
                                                 },
                                             });
+                                        crate::webgpu::texture::clear_draw_bindings();
                                     }
                                 }
                             }
@@ -1932,29 +1809,38 @@ impl hal::Queue for SoftQueue {
                     let src_data = src.lock().unwrap();
                     let mut dst_data = dst.lock().unwrap();
                     for region in regions {
-                        let bytes_per_pixel = 4;
+                        let bytes_per_pixel = texture_desc.texel_size();
+                        let mip_level = region.texture_base.mip_level;
+                        let level_size = texture_desc.mip_level_size(mip_level);
                         let width = region.size.width;
                         let height = region.size.height;
                         let depth = region.size.depth;
-                        let row_pitch = width * bytes_per_pixel;
-                        let slice_pitch = row_pitch * height;
+                        let texture_row_pitch = level_size.width * bytes_per_pixel;
+                        let texture_slice_pitch = level_size.height * texture_row_pitch;
+                        let row_pitch = region
+                            .buffer_layout
+                            .bytes_per_row
+                            .unwrap_or(width * bytes_per_pixel);
+                        let slice_pitch =
+                            region.buffer_layout.rows_per_image.unwrap_or(height) * row_pitch;
                         let src_origin = region.texture_base.origin;
-                        let src_offset =
-                            (src_origin.z * texture_desc.size.height * texture_desc.size.width
-                                + src_origin.y * texture_desc.size.width
-                                + src_origin.x)
-                                * bytes_per_pixel;
+                        let src_offset = texture_desc.mip_level_offset(mip_level) as u32
+                            + src_origin.z * texture_slice_pitch
+                            + src_origin.y * texture_row_pitch
+                            + src_origin.x * bytes_per_pixel;
                         let dst_offset = region.buffer_layout.offset;
 
                         for z in 0..depth {
                             for y in 0..height {
-                                let src_idx =
-                                    (src_offset + (z * slice_pitch) + (y * row_pitch)) as usize;
+                                let src_idx = (src_offset
+                                    + (z * texture_slice_pitch)
+                                    + (y * texture_row_pitch))
+                                    as usize;
                                 let dst_idx = (dst_offset
                                     + (z as u64 * slice_pitch as u64)
                                     + (y as u64 * row_pitch as u64))
                                     as usize;
-                                let len = row_pitch as usize;
+                                let len = (width * bytes_per_pixel) as usize;
                                 if src_idx + len <= src_data.len()
                                     && dst_idx + len <= dst_data.len()
                                 {
@@ -2277,8 +2163,16 @@ impl hal::CommandEncoder for SoftCommandEncoder {
             });
         }
     }
-    unsafe fn set_stencil_reference(&mut self, _reference: u32) {}
-    unsafe fn set_blend_constants(&mut self, _color: &[f32; 4]) {}
+    unsafe fn set_stencil_reference(&mut self, reference: u32) {
+        if let Some((_, commands)) = &mut self.current_render_pass {
+            commands.push(SoftRenderCommand::SetStencilReference { reference });
+        }
+    }
+    unsafe fn set_blend_constants(&mut self, color: &[f32; 4]) {
+        if let Some((_, commands)) = &mut self.current_render_pass {
+            commands.push(SoftRenderCommand::SetBlendConstant { color: *color });
+        }
+    }
 
     unsafe fn draw(
         &mut self,
@@ -2507,6 +2401,38 @@ impl From<&hal::TextureDescriptor<'_>> for SoftTextureDescriptor {
     }
 }
 
+impl SoftTextureDescriptor {
+    /// Bytes per texel.
+    pub fn texel_size(&self) -> u32 {
+        self.format.block_copy_size(None).unwrap_or(4)
+    }
+
+    /// Size of a mip level. 3D textures also shrink in depth; array layers
+    /// do not.
+    pub fn mip_level_size(&self, level: u32) -> wgt::Extent3d {
+        wgt::Extent3d {
+            width: (self.size.width >> level).max(1),
+            height: (self.size.height >> level).max(1),
+            depth_or_array_layers: match self.dimension {
+                wgt::TextureDimension::D3 => (self.size.depth_or_array_layers >> level).max(1),
+                _ => self.size.depth_or_array_layers,
+            },
+        }
+    }
+
+    /// Byte offset of a mip level in the texture data. Levels are stored
+    /// tightly packed one after another, level 0 first, so code that only
+    /// deals with level 0 can ignore the others.
+    pub fn mip_level_offset(&self, level: u32) -> usize {
+        (0..level)
+            .map(|l| {
+                let size = self.mip_level_size(l);
+                (size.width * size.height * size.depth_or_array_layers * self.texel_size()) as usize
+            })
+            .sum()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SoftTexture {
     pub data: Arc<Mutex<Vec<u8>>>,
@@ -121,6 +121,20 @@ pub fn create_bind_group(
                 let resource = match res_type {
                     0 => {
                         // Buffer
+                        let operation = "createBindGroup buffer binding";
+                        if super::buffer::validate_buffer_alive(ctx, res_handle, operation)
+                            .and_then(|_| {
+                                super::buffer::validate_buffer_usage_any(
+                                    ctx,
+                                    res_handle,
+                                    wgt::BufferUsages::UNIFORM | wgt::BufferUsages::STORAGE,
+                                    operation,
+                                )
+                            })
+                            .is_err()
+                        {
+                            return super::NULL_HANDLE;
+                        }
                         if let Some(id) = ctx.buffers.get(&res_handle) {
                             wgpu_core::binding_model::BindingResource::Buffer(
                                 wgpu_core::binding_model::BufferBinding {
@@ -135,6 +149,15 @@ pub fn create_bind_group(
                     }
                     1 => {
                         // TextureView
+                        if super::texture::validate_texture_view_alive(
+                            ctx,
+                            res_handle,
+                            "createBindGroup texture binding",
+                        )
+                        .is_err()
+                        {
+                            return super::NULL_HANDLE;
+                        }
                         if let Some(id) = ctx.texture_views.get(&res_handle) {
                             wgpu_core::binding_model::BindingResource::TextureView(*id)
                         } else {
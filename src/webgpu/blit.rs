@@ -0,0 +1,352 @@
+//! Built-in texture utilities: mip chain generation and blits
+//!
+//! Browsers generate mipmaps and convert between formats with internal
+//! render pipelines. The software backend can do the same work directly on
+//! texture storage: texels are decoded to linear floating point, filtered,
+//! and encoded in the destination format. Both helpers run on the queue
+//! timeline immediately, like `writeTexture`, after flushing pending writes.
+
+use super::adapter::{with_context_val, WebGpuContext};
+use super::backend::{SoftApi, SoftTextureDescriptor};
+use super::buffer::validation_error;
+use super::texture::{validate_texture_alive, TexelLayout};
+use std::sync::{Arc, Mutex};
+use wgpu_types as wgt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlitFilter {
+    Nearest,
+    Linear,
+}
+
+impl From<u32> for BlitFilter {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => BlitFilter::Linear,
+            _ => BlitFilter::Nearest,
+        }
+    }
+}
+
+pub struct BlitConfig {
+    pub source_texture_handle: u32,
+    pub source_mip_level: u32,
+    pub dest_texture_handle: u32,
+    pub dest_mip_level: u32,
+    pub filter: BlitFilter,
+}
+
+/// One decoded mip level, layer by layer, rows top to bottom.
+struct Level {
+    width: u32,
+    height: u32,
+    depth: u32,
+    texels: Vec<[f32; 4]>,
+}
+
+impl Level {
+    fn read(data: &[u8], desc: &SoftTextureDescriptor, layout: TexelLayout, mip: u32) -> Level {
+        let size = desc.mip_level_size(mip);
+        let texel_size = desc.texel_size() as usize;
+        let offset = desc.mip_level_offset(mip);
+        let count = (size.width * size.height * size.depth_or_array_layers) as usize;
+        let texels = data[offset..offset + count * texel_size]
+            .chunks_exact(texel_size)
+            .map(|bytes| layout.decode(bytes))
+            .collect();
+        Level {
+            width: size.width,
+            height: size.height,
+            depth: size.depth_or_array_layers,
+            texels,
+        }
+    }
+
+    fn write(&self, data: &mut [u8], desc: &SoftTextureDescriptor, layout: TexelLayout, mip: u32) {
+        let texel_size = desc.texel_size() as usize;
+        let offset = desc.mip_level_offset(mip);
+        let bytes = &mut data[offset..offset + self.texels.len() * texel_size];
+        for (chunk, texel) in bytes.chunks_exact_mut(texel_size).zip(&self.texels) {
+            layout.encode(*texel, chunk);
+        }
+    }
+
+    fn texel(&self, x: u32, y: u32, z: u32) -> [f32; 4] {
+        self.texels[((z * self.height + y) * self.width + x) as usize]
+    }
+
+    /// Scale to a new size. Linear filtering halving a dimension averages
+    /// texel pairs, so it doubles as the box filter for mip generation.
+    fn resample(&self, width: u32, height: u32, depth: u32, filter: BlitFilter) -> Level {
+        let mut texels = Vec::with_capacity((width * height * depth) as usize);
+        for z in 0..depth {
+            let zs = taps(z, depth, self.depth, filter);
+            for y in 0..height {
+                let ys = taps(y, height, self.height, filter);
+                for x in 0..width {
+                    let xs = taps(x, width, self.width, filter);
+                    let mut texel = [0.0; 4];
+                    for &(sz, wz) in &zs {
+                        for &(sy, wy) in &ys {
+                            for &(sx, wx) in &xs {
+                                let weight = wx * wy * wz;
+                                if weight == 0.0 {
+                                    continue;
+                                }
+                                let sample = self.texel(sx, sy, sz);
+                                for (acc, value) in texel.iter_mut().zip(sample) {
+                                    *acc += value * weight;
+                                }
+                            }
+                        }
+                    }
+                    texels.push(texel);
+                }
+            }
+        }
+        Level {
+            width,
+            height,
+            depth,
+            texels,
+        }
+    }
+}
+
+/// Source texels and weights contributing to destination texel `index`
+/// along one axis.
+fn taps(index: u32, dest_len: u32, src_len: u32, filter: BlitFilter) -> [(u32, f32); 2] {
+    let center = (index as f32 + 0.5) * src_len as f32 / dest_len as f32;
+    match filter {
+        BlitFilter::Nearest => {
+            let texel = (center as u32).min(src_len - 1);
+            [(texel, 1.0), (texel, 0.0)]
+        }
+        BlitFilter::Linear => {
+            let coord = (center - 0.5).max(0.0);
+            let low = (coord as u32).min(src_len - 1);
+            let high = (low + 1).min(src_len - 1);
+            let frac = coord - low as f32;
+            [(low, 1.0 - frac), (high, frac)]
+        }
+    }
+}
+
+/// Storage of a texture on the software backend.
+struct TextureStorage {
+    data: Arc<Mutex<Vec<u8>>>,
+    desc: SoftTextureDescriptor,
+    layout: TexelLayout,
+}
+
+fn texture_storage(
+    ctx: &WebGpuContext,
+    texture_handle: u32,
+    operation: &str,
+) -> Result<TextureStorage, u32> {
+    let texture_id = *ctx
+        .textures
+        .get(&texture_handle)
+        .ok_or(super::WEBGPU_ERROR_INVALID_HANDLE)?;
+    validate_texture_alive(ctx, texture_handle, operation)?;
+    let texture = unsafe { ctx.global.texture_as_hal::<SoftApi>(texture_id) }
+        .ok_or(super::WEBGPU_ERROR_INVALID_HANDLE)?;
+    let desc = texture.desc.clone();
+    if desc.sample_count > 1 {
+        return Err(validation_error(format!(
+            "{} does not support multisampled textures",
+            operation
+        )));
+    }
+    let layout = TexelLayout::of(desc.format).ok_or_else(|| {
+        validation_error(format!(
+            "{} does not support format {:?}",
+            operation, desc.format
+        ))
+    })?;
+    Ok(TextureStorage {
+        data: texture.data.clone(),
+        desc,
+        layout,
+    })
+}
+
+/// Submit an empty batch so writes staged by `writeTexture` reach texture
+/// storage before it is read directly.
+fn flush_pending_writes(ctx: &WebGpuContext, device_handle: u32) -> Result<(), u32> {
+    let device_id = *ctx
+        .devices
+        .get(&device_handle)
+        .ok_or(super::WEBGPU_ERROR_INVALID_HANDLE)?;
+    let queue_id = *ctx
+        .queues
+        .get(&device_handle)
+        .ok_or(super::WEBGPU_ERROR_INVALID_HANDLE)?;
+    if let Err(e) = ctx.global.queue_submit(queue_id, &[]) {
+        crate::error::set_error(
+            crate::error::ErrorSource::WebGPU(crate::error::WebGPUErrorFilter::Validation),
+            super::WEBGPU_ERROR_OPERATION_FAILED,
+            format!("Submission index {}: {}", e.0, e.1),
+        );
+        return Err(super::WEBGPU_ERROR_OPERATION_FAILED);
+    }
+    let _ = ctx.global.device_poll(
+        device_id,
+        wgt::PollType::Wait {
+            submission_index: None,
+            timeout: None,
+        },
+    );
+    Ok(())
+}
+
+fn validate_mip_level(
+    storage: &TextureStorage,
+    mip_level: u32,
+    operation: &str,
+) -> Result<(), u32> {
+    if mip_level >= storage.desc.mip_level_count {
+        return Err(validation_error(format!(
+            "{} mip level {} is out of range, the texture has {} levels",
+            operation, mip_level, storage.desc.mip_level_count
+        )));
+    }
+    Ok(())
+}
+
+/// Fill mip levels 1 and up of a texture from level 0.
+pub fn generate_mipmaps(ctx_handle: u32, device_handle: u32, texture_handle: u32) -> u32 {
+    with_context_val(ctx_handle, super::WEBGPU_ERROR_INVALID_HANDLE, |ctx| {
+        let storage = match texture_storage(ctx, texture_handle, "generateMipmaps") {
+            Ok(storage) => storage,
+            Err(code) => return code,
+        };
+        if storage.desc.dimension == wgt::TextureDimension::D1 {
+            return validation_error("generateMipmaps does not support 1D textures".to_string());
+        }
+        if let Err(code) = flush_pending_writes(ctx, device_handle) {
+            return code;
+        }
+
+        let mut data = storage.data.lock().unwrap();
+        let mut level = Level::read(&data, &storage.desc, storage.layout, 0);
+        for mip in 1..storage.desc.mip_level_count {
+            let size = storage.desc.mip_level_size(mip);
+            level = level.resample(
+                size.width,
+                size.height,
+                size.depth_or_array_layers,
+                BlitFilter::Linear,
+            );
+            level.write(&mut data, &storage.desc, storage.layout, mip);
+        }
+        super::WEBGPU_SUCCESS
+    })
+}
+
+/// Copy one mip level into another, scaling to the destination size and
+/// converting between formats.
+pub fn blit_texture(ctx_handle: u32, device_handle: u32, config: BlitConfig) -> u32 {
+    with_context_val(ctx_handle, super::WEBGPU_ERROR_INVALID_HANDLE, |ctx| {
+        let result = texture_storage(ctx, config.source_texture_handle, "blitTexture source")
+            .and_then(|source| {
+                validate_mip_level(&source, config.source_mip_level, "blitTexture source")?;
+                Ok(source)
+            })
+            .and_then(|source| {
+                let dest =
+                    texture_storage(ctx, config.dest_texture_handle, "blitTexture destination")?;
+                validate_mip_level(&dest, config.dest_mip_level, "blitTexture destination")?;
+                Ok((source, dest))
+            });
+        let (source, dest) = match result {
+            Ok(storages) => storages,
+            Err(code) => return code,
+        };
+        if config.source_texture_handle == config.dest_texture_handle
+            && config.source_mip_level == config.dest_mip_level
+        {
+            return validation_error(
+                "blitTexture source and destination are the same mip level".to_string(),
+            );
+        }
+        let is_3d = |storage: &TextureStorage| storage.desc.dimension == wgt::TextureDimension::D3;
+        let dest_size = dest.desc.mip_level_size(config.dest_mip_level);
+        let source_size = source.desc.mip_level_size(config.source_mip_level);
+        if is_3d(&source) != is_3d(&dest)
+            || (!is_3d(&dest)
+                && source_size.depth_or_array_layers != dest_size.depth_or_array_layers)
+        {
+            return validation_error(
+                "blitTexture source and destination must have matching layers".to_string(),
+            );
+        }
+        if let Err(code) = flush_pending_writes(ctx, device_handle) {
+            return code;
+        }
+
+        // Decode before locking the destination, which may share storage
+        // with the source
+        let level = Level::read(
+            &source.data.lock().unwrap(),
+            &source.desc,
+            source.layout,
+            config.source_mip_level,
+        )
+        .resample(
+            dest_size.width,
+            dest_size.height,
+            dest_size.depth_or_array_layers,
+            config.filter,
+        );
+        level.write(
+            &mut dest.data.lock().unwrap(),
+            &dest.desc,
+            dest.layout,
+            config.dest_mip_level,
+        );
+        super::WEBGPU_SUCCESS
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_box_filter_and_format_round_trip() {
+        let level = Level {
+            width: 2,
+            height: 2,
+            depth: 1,
+            texels: vec![
+                [0.0, 0.0, 0.0, 1.0],
+                [1.0, 0.0, 0.0, 1.0],
+                [0.0, 1.0, 0.0, 1.0],
+                [1.0, 1.0, 1.0, 1.0],
+            ],
+        };
+        let half = level.resample(1, 1, 1, BlitFilter::Linear);
+        assert_eq!(half.texels, vec![[0.5, 0.5, 0.25, 1.0]]);
+        let nearest = level.resample(4, 2, 1, BlitFilter::Nearest);
+        assert_eq!(nearest.texel(1, 0, 0), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(nearest.texel(2, 0, 0), [1.0, 0.0, 0.0, 1.0]);
+
+        let bgra = TexelLayout::of(wgt::TextureFormat::Bgra8Unorm).unwrap();
+        let mut bytes = [0u8; 4];
+        bgra.encode([1.0, 0.5, 0.0, 1.0], &mut bytes);
+        assert_eq!(bytes, [0, 128, 255, 255]);
+
+        let srgb = TexelLayout::of(wgt::TextureFormat::Rgba8UnormSrgb).unwrap();
+        srgb.encode([0.5, 0.5, 0.5, 0.5], &mut bytes);
+        assert_eq!(bytes, [188, 188, 188, 128]);
+        let decoded = srgb.decode(&bytes);
+        assert!((decoded[0] - 0.5).abs() < 0.01);
+
+        let r16 = TexelLayout::of(wgt::TextureFormat::R16Float).unwrap();
+        let mut bytes = [0u8; 2];
+        r16.encode([0.25, 9.0, 9.0, 9.0], &mut bytes);
+        assert_eq!(r16.decode(&bytes), [0.25, 0.0, 0.0, 1.0]);
+        assert!(TexelLayout::of(wgt::TextureFormat::Depth32Float).is_none());
+    }
+}
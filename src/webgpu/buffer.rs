@@ -1,8 +1,112 @@
 //! WebGPU Buffer management
 
-use super::adapter::{with_context, with_context_val};
+use super::adapter::{with_context, with_context_val, WebGpuContext};
 use wgpu_types as wgt;
 
+/// Report a buffer or texture misuse as a WebGPU validation error.
+pub(crate) fn validation_error(msg: String) -> u32 {
+    crate::error::set_error(
+        crate::error::ErrorSource::WebGPU(crate::error::WebGPUErrorFilter::Validation),
+        super::WEBGPU_ERROR_VALIDATION,
+        msg,
+    );
+    super::WEBGPU_ERROR_VALIDATION
+}
+
+/// Validate a usage mask passed to `createBuffer` against the WebGPU rules.
+fn validate_creation_usage(usage: u32) -> Result<wgt::BufferUsages, u32> {
+    let flags = match wgt::BufferUsages::from_bits(usage) {
+        Some(f) => f,
+        None => {
+            return Err(validation_error(format!(
+                "createBuffer: usage {:#x} contains unknown flags",
+                usage
+            )))
+        }
+    };
+    if flags.is_empty() {
+        return Err(validation_error("createBuffer: usage must not be 0".into()));
+    }
+    if flags.contains(wgt::BufferUsages::MAP_READ)
+        && !(wgt::BufferUsages::MAP_READ | wgt::BufferUsages::COPY_DST).contains(flags)
+    {
+        return Err(validation_error(format!(
+            "createBuffer: MAP_READ may only be combined with COPY_DST, got {:?}",
+            flags
+        )));
+    }
+    if flags.contains(wgt::BufferUsages::MAP_WRITE)
+        && !(wgt::BufferUsages::MAP_WRITE | wgt::BufferUsages::COPY_SRC).contains(flags)
+    {
+        return Err(validation_error(format!(
+            "createBuffer: MAP_WRITE may only be combined with COPY_SRC, got {:?}",
+            flags
+        )));
+    }
+    Ok(flags)
+}
+
+/// Check that a buffer was created with every flag in `required`.
+///
+/// `operation` names the API call in the error message, e.g. "copyBufferToBuffer source".
+/// Unknown handles pass; callers report those as invalid handles themselves.
+pub(crate) fn validate_buffer_usage(
+    ctx: &WebGpuContext,
+    buffer_handle: u32,
+    required: wgt::BufferUsages,
+    operation: &str,
+) -> Result<(), u32> {
+    let actual = match ctx.buffer_usages.get(&buffer_handle) {
+        Some(u) => *u,
+        None => return Ok(()),
+    };
+    if actual.contains(required) {
+        Ok(())
+    } else {
+        Err(validation_error(format!(
+            "{} requires buffer usage {:?}, but buffer {} was created with {:?}",
+            operation, required, buffer_handle, actual
+        )))
+    }
+}
+
+/// Check that a buffer was created with at least one flag in `accepted`.
+pub(crate) fn validate_buffer_usage_any(
+    ctx: &WebGpuContext,
+    buffer_handle: u32,
+    accepted: wgt::BufferUsages,
+    operation: &str,
+) -> Result<(), u32> {
+    let actual = match ctx.buffer_usages.get(&buffer_handle) {
+        Some(u) => *u,
+        None => return Ok(()),
+    };
+    if actual.intersects(accepted) {
+        Ok(())
+    } else {
+        Err(validation_error(format!(
+            "{} requires one of buffer usages {:?}, but buffer {} was created with {:?}",
+            operation, accepted, buffer_handle, actual
+        )))
+    }
+}
+
+/// Check that a buffer has not been destroyed.
+pub(crate) fn validate_buffer_alive(
+    ctx: &WebGpuContext,
+    buffer_handle: u32,
+    operation: &str,
+) -> Result<(), u32> {
+    if ctx.destroyed_buffers.contains(&buffer_handle) {
+        Err(validation_error(format!(
+            "{} uses buffer {} after it was destroyed",
+            operation, buffer_handle
+        )))
+    } else {
+        Ok(())
+    }
+}
+
 /// Create a new buffer
 pub fn create_buffer(
     ctx_handle: u32,
@@ -17,6 +121,11 @@ pub fn create_buffer(
             None => return super::NULL_HANDLE,
         };
 
+        let usage = match validate_creation_usage(usage) {
+            Ok(u) => u,
+            Err(_) => return super::NULL_HANDLE,
+        };
+
         let mut size = size;
         if mapped_at_creation {
             size = (size + 3) & !3;
@@ -25,7 +134,7 @@ pub fn create_buffer(
         let desc = wgt::BufferDescriptor {
             label: None,
             size,
-            usage: wgt::BufferUsages::from_bits_truncate(usage),
+            usage,
             mapped_at_creation,
         };
 
@@ -44,16 +153,19 @@ pub fn create_buffer(
         ctx.next_buffer_id += 1;
         ctx.buffers.insert(handle, buffer_id);
         ctx.buffer_to_device.insert(buffer_id, device_id);
+        ctx.buffer_usages.insert(handle, usage);
 
         handle
     })
 }
 
-/// Destroy a buffer
+/// Destroy a buffer. Destroying it again is a no-op.
 pub fn destroy_buffer(ctx_handle: u32, buffer_handle: u32) -> u32 {
     with_context(ctx_handle, |ctx| {
-        if let Some(id) = ctx.buffers.remove(&buffer_handle) {
-            ctx.global.buffer_destroy(id);
+        if let Some(&id) = ctx.buffers.get(&buffer_handle) {
+            if ctx.destroyed_buffers.insert(buffer_handle) {
+                ctx.global.buffer_destroy(id);
+            }
             super::WEBGPU_SUCCESS
         } else {
             crate::error::set_error(
@@ -86,13 +198,26 @@ pub fn buffer_map_async(
             None => return super::WEBGPU_ERROR_INVALID_HANDLE,
         };
 
-        let host = if mode == 1 {
+        let (host, required, name) = if mode == 1 {
             // Read
-            wgpu_core::device::HostMap::Read
+            (
+                wgpu_core::device::HostMap::Read,
+                wgt::BufferUsages::MAP_READ,
+                "mapAsync(READ)",
+            )
         } else {
             // Write
-            wgpu_core::device::HostMap::Write
+            (
+                wgpu_core::device::HostMap::Write,
+                wgt::BufferUsages::MAP_WRITE,
+                "mapAsync(WRITE)",
+            )
         };
+        if let Err(code) = validate_buffer_alive(ctx, buffer_handle, name)
+            .and_then(|_| validate_buffer_usage(ctx, buffer_handle, required, name))
+        {
+            return code;
+        }
 
         let op = wgpu_core::resource::BufferMapOperation {
             host,
@@ -1,6 +1,9 @@
 //! WebGPU Command Encoder and Queue management
 
 use super::adapter::{with_context, with_context_val};
+use super::buffer::{validate_buffer_alive, validate_buffer_usage};
+use super::texture::{validate_texture_alive, validate_texture_view_alive};
+use super::trace::{record, record_pass, RecordedCommand};
 use std::num::NonZero;
 use wgpu_types as wgt;
 
@@ -36,6 +39,7 @@ pub fn create_command_encoder(ctx_handle: u32, device_handle: u32) -> u32 {
         let handle = ctx.next_command_encoder_id;
         ctx.next_command_encoder_id += 1;
         ctx.command_encoders.insert(handle, encoder_id);
+        ctx.encoder_commands.insert(handle, Vec::new());
 
         handle
     })
@@ -55,6 +59,10 @@ pub fn command_encoder_finish(ctx_handle: u32, encoder_handle: u32) -> u32 {
                 return super::NULL_HANDLE;
             }
         };
+        let commands = ctx
+            .encoder_commands
+            .remove(&encoder_handle)
+            .unwrap_or_default();
 
         let desc = wgt::CommandBufferDescriptor { label: None };
 
@@ -71,6 +79,7 @@ pub fn command_encoder_finish(ctx_handle: u32, encoder_handle: u32) -> u32 {
         let handle = ctx.next_command_buffer_id;
         ctx.next_command_buffer_id += 1;
         ctx.command_buffers.insert(handle, buffer_id);
+        ctx.command_buffer_commands.insert(handle, commands);
 
         handle
     })
@@ -123,6 +132,28 @@ pub fn command_encoder_copy_buffer_to_buffer(
             }
         };
 
+        if let Err(code) = validate_buffer_alive(ctx, source_handle, "copyBufferToBuffer source")
+            .and_then(|_| validate_buffer_alive(ctx, dest_handle, "copyBufferToBuffer destination"))
+            .and_then(|_| {
+                validate_buffer_usage(
+                    ctx,
+                    source_handle,
+                    wgt::BufferUsages::COPY_SRC,
+                    "copyBufferToBuffer source",
+                )
+            })
+            .and_then(|_| {
+                validate_buffer_usage(
+                    ctx,
+                    dest_handle,
+                    wgt::BufferUsages::COPY_DST,
+                    "copyBufferToBuffer destination",
+                )
+            })
+        {
+            return code;
+        }
+
         if let Err(e) = ctx.global.command_encoder_copy_buffer_to_buffer(
             encoder_id,
             source_id,
@@ -139,6 +170,18 @@ pub fn command_encoder_copy_buffer_to_buffer(
             return super::NULL_HANDLE;
         }
 
+        record(
+            ctx,
+            encoder_handle,
+            RecordedCommand::CopyBufferToBuffer {
+                source: source_handle,
+                source_offset,
+                destination: dest_handle,
+                destination_offset: dest_offset,
+                size,
+            },
+        );
+
         0 // Success
     })
 }
@@ -154,6 +197,7 @@ pub fn queue_submit(ctx_handle: u32, device_handle: u32, cb_handles: &[u32]) ->
         let mut cb_ids = Vec::with_capacity(cb_handles.len());
         for &h in cb_handles {
             if let Some(id) = ctx.command_buffers.remove(&h) {
+                ctx.command_buffer_commands.remove(&h);
                 cb_ids.push(id);
             } else {
                 return None;
@@ -198,6 +242,7 @@ pub fn queue_submit(ctx_handle: u32, device_handle: u32, cb_handles: &[u32]) ->
 
 pub struct CopyTextureToBufferConfig {
     pub source_texture_handle: u32,
+    pub source_mip_level: u32,
     pub dest_buffer_handle: u32,
     pub dest_offset: u64,
     pub dest_bytes_per_row: u32,
@@ -229,9 +274,32 @@ pub fn command_encoder_copy_texture_to_buffer(
             None => return super::WEBGPU_ERROR_INVALID_HANDLE,
         };
 
+        if let Err(code) = validate_texture_alive(
+            ctx,
+            config.source_texture_handle,
+            "copyTextureToBuffer source",
+        )
+        .and_then(|_| {
+            validate_buffer_alive(
+                ctx,
+                config.dest_buffer_handle,
+                "copyTextureToBuffer destination",
+            )
+        })
+        .and_then(|_| {
+            validate_buffer_usage(
+                ctx,
+                config.dest_buffer_handle,
+                wgt::BufferUsages::COPY_DST,
+                "copyTextureToBuffer destination",
+            )
+        }) {
+            return code;
+        }
+
         let source = wgt::TexelCopyTextureInfo {
             texture: texture_id,
-            mip_level: 0,
+            mip_level: config.source_mip_level,
             origin: wgt::Origin3d::ZERO,
             aspect: wgt::TextureAspect::All,
         };
@@ -271,6 +339,20 @@ pub fn command_encoder_copy_texture_to_buffer(
             return super::WEBGPU_ERROR_OPERATION_FAILED;
         }
 
+        record(
+            ctx,
+            encoder_handle,
+            RecordedCommand::CopyTextureToBuffer {
+                source: config.source_texture_handle,
+                mip_level: config.source_mip_level,
+                destination: config.dest_buffer_handle,
+                offset: config.dest_offset,
+                bytes_per_row: config.dest_bytes_per_row,
+                rows_per_image: config.dest_rows_per_image,
+                size: [config.size_width, config.size_height, config.size_depth],
+            },
+        );
+
         super::WEBGPU_SUCCESS
     })
 }
@@ -302,6 +384,12 @@ pub fn command_encoder_begin_render_pass(
             None => return super::NULL_HANDLE,
         };
 
+        if validate_texture_view_alive(ctx, config.view_handle, "beginRenderPass color attachment")
+            .is_err()
+        {
+            return super::NULL_HANDLE;
+        }
+
         let load = match config.load_op {
             0 => wgpu_core::command::LoadOp::Load,
             _ => wgpu_core::command::LoadOp::Clear(wgt::Color {
@@ -349,6 +437,26 @@ pub fn command_encoder_begin_render_pass(
         let handle = ctx.next_render_pass_id;
         ctx.next_render_pass_id += 1;
         ctx.render_passes.insert(handle, pass);
+        ctx.render_pass_encoders.insert(handle, encoder_handle);
+        record_pass(
+            ctx,
+            handle,
+            RecordedCommand::BeginRenderPass {
+                view: config.view_handle,
+                load_op: if config.load_op == 0 { "load" } else { "clear" },
+                store_op: if config.store_op == 0 {
+                    "store"
+                } else {
+                    "discard"
+                },
+                clear_value: [
+                    config.clear_r,
+                    config.clear_g,
+                    config.clear_b,
+                    config.clear_a,
+                ],
+            },
+        );
 
         handle
     })
@@ -376,6 +484,14 @@ pub fn render_pass_set_pipeline(ctx_handle: u32, pass_handle: u32, pipeline_hand
             return super::WEBGPU_ERROR_OPERATION_FAILED;
         }
 
+        record_pass(
+            ctx,
+            pass_handle,
+            RecordedCommand::SetPipeline {
+                pipeline: pipeline_handle,
+            },
+        );
+
         super::WEBGPU_SUCCESS
     })
 }
@@ -395,6 +511,19 @@ pub fn render_pass_set_vertex_buffer(
             None => return super::WEBGPU_ERROR_INVALID_HANDLE,
         };
 
+        if let Err(code) =
+            validate_buffer_alive(ctx, buffer_handle, "setVertexBuffer").and_then(|_| {
+                validate_buffer_usage(
+                    ctx,
+                    buffer_handle,
+                    wgt::BufferUsages::VERTEX,
+                    "setVertexBuffer",
+                )
+            })
+        {
+            return code;
+        }
+
         let pass = match ctx.render_passes.get_mut(&pass_handle) {
             Some(p) => p,
             None => return super::WEBGPU_ERROR_INVALID_HANDLE,
@@ -408,6 +537,17 @@ pub fn render_pass_set_vertex_buffer(
             NonZero::new(size),
         );
 
+        record_pass(
+            ctx,
+            pass_handle,
+            RecordedCommand::SetVertexBuffer {
+                slot,
+                buffer: buffer_handle,
+                offset,
+                size,
+            },
+        );
+
         super::WEBGPU_SUCCESS
     })
 }
@@ -427,15 +567,27 @@ pub fn render_pass_set_index_buffer(
             None => return super::WEBGPU_ERROR_INVALID_HANDLE,
         };
 
+        if let Err(code) =
+            validate_buffer_alive(ctx, buffer_handle, "setIndexBuffer").and_then(|_| {
+                validate_buffer_usage(
+                    ctx,
+                    buffer_handle,
+                    wgt::BufferUsages::INDEX,
+                    "setIndexBuffer",
+                )
+            })
+        {
+            return code;
+        }
+
         let pass = match ctx.render_passes.get_mut(&pass_handle) {
             Some(p) => p,
             None => return super::WEBGPU_ERROR_INVALID_HANDLE,
         };
 
-        let format = match format_id {
-            1 => wgt::IndexFormat::Uint16,
-            2 => wgt::IndexFormat::Uint32,
-            _ => wgt::IndexFormat::Uint16,
+        let (format, format_name) = match format_id {
+            2 => (wgt::IndexFormat::Uint32, "uint32"),
+            _ => (wgt::IndexFormat::Uint16, "uint16"),
         };
 
         let _ = ctx.global.render_pass_set_index_buffer(
@@ -446,6 +598,17 @@ pub fn render_pass_set_index_buffer(
             NonZero::new(size),
         );
 
+        record_pass(
+            ctx,
+            pass_handle,
+            RecordedCommand::SetIndexBuffer {
+                buffer: buffer_handle,
+                format: format_name,
+                offset,
+                size,
+            },
+        );
+
         super::WEBGPU_SUCCESS
     })
 }
@@ -480,6 +643,17 @@ pub fn render_pass_draw(
             return super::WEBGPU_ERROR_OPERATION_FAILED;
         }
 
+        record_pass(
+            ctx,
+            pass_handle,
+            RecordedCommand::Draw {
+                vertex_count,
+                instance_count,
+                first_vertex,
+                first_instance,
+            },
+        );
+
         super::WEBGPU_SUCCESS
     })
 }
@@ -516,6 +690,18 @@ pub fn render_pass_draw_indexed(
             return super::WEBGPU_ERROR_OPERATION_FAILED;
         }
 
+        record_pass(
+            ctx,
+            pass_handle,
+            RecordedCommand::DrawIndexed {
+                index_count,
+                instance_count,
+                first_index,
+                base_vertex,
+                first_instance,
+            },
+        );
+
         super::WEBGPU_SUCCESS
     })
 }
@@ -550,6 +736,15 @@ pub fn render_pass_set_bind_group(
             return super::WEBGPU_ERROR_OPERATION_FAILED;
         }
 
+        record_pass(
+            ctx,
+            pass_handle,
+            RecordedCommand::SetBindGroup {
+                index,
+                bind_group: bg_handle,
+            },
+        );
+
         super::WEBGPU_SUCCESS
     })
 }
@@ -576,6 +771,19 @@ pub fn render_pass_set_viewport(
             .global
             .render_pass_set_viewport(pass, x, y, w, h, min_depth, max_depth);
 
+        record_pass(
+            ctx,
+            pass_handle,
+            RecordedCommand::SetViewport {
+                x,
+                y,
+                width: w,
+                height: h,
+                min_depth,
+                max_depth,
+            },
+        );
+
         super::WEBGPU_SUCCESS
     })
 }
@@ -597,6 +805,70 @@ pub fn render_pass_set_scissor_rect(
 
         let _ = ctx.global.render_pass_set_scissor_rect(pass, x, y, w, h);
 
+        record_pass(
+            ctx,
+            pass_handle,
+            RecordedCommand::SetScissorRect {
+                x,
+                y,
+                width: w,
+                height: h,
+            },
+        );
+
+        super::WEBGPU_SUCCESS
+    })
+}
+
+/// Set the constant color used by `constant` blend factors for a pass
+pub fn render_pass_set_blend_constant(
+    ctx_handle: u32,
+    pass_handle: u32,
+    r: f64,
+    g: f64,
+    b: f64,
+    a: f64,
+) -> u32 {
+    with_context(ctx_handle, |ctx| {
+        let pass = match ctx.render_passes.get_mut(&pass_handle) {
+            Some(p) => p,
+            None => return super::WEBGPU_ERROR_INVALID_HANDLE,
+        };
+
+        let _ = ctx
+            .global
+            .render_pass_set_blend_constant(pass, wgt::Color { r, g, b, a });
+
+        record_pass(
+            ctx,
+            pass_handle,
+            RecordedCommand::SetBlendConstant {
+                color: [r, g, b, a],
+            },
+        );
+
+        super::WEBGPU_SUCCESS
+    })
+}
+
+/// Set the stencil reference value for a pass
+pub fn render_pass_set_stencil_reference(ctx_handle: u32, pass_handle: u32, reference: u32) -> u32 {
+    with_context(ctx_handle, |ctx| {
+        let pass = match ctx.render_passes.get_mut(&pass_handle) {
+            Some(p) => p,
+            None => return super::WEBGPU_ERROR_INVALID_HANDLE,
+        };
+
+        let _ = ctx
+            .global
+            .render_pass_set_stencil_reference(pass, reference);
+
+        record_pass(
+            ctx,
+            pass_handle,
+            RecordedCommand::SetStencilReference { reference },
+        );
+
         super::WEBGPU_SUCCESS
     })
 }
@@ -608,6 +880,8 @@ pub fn render_pass_end(ctx_handle: u32, pass_handle: u32) -> u32 {
             Some(p) => p,
             None => return None,
         };
+        record_pass(ctx, pass_handle, RecordedCommand::EndRenderPass);
+        ctx.render_pass_encoders.remove(&pass_handle);
         Some((ctx.global.clone(), pass))
     });
 
@@ -628,6 +902,90 @@ pub fn render_pass_end(ctx_handle: u32, pass_handle: u32) -> u32 {
     super::WEBGPU_SUCCESS
 }
 
+/// Which debug command to encode, see [`command_encoder_debug`] and
+/// [`render_pass_debug`].
+pub enum DebugCommand<'a> {
+    PushGroup(&'a str),
+    PopGroup,
+    InsertMarker(&'a str),
+}
+
+impl DebugCommand<'_> {
+    fn recorded(&self) -> RecordedCommand {
+        match self {
+            DebugCommand::PushGroup(label) => RecordedCommand::PushDebugGroup {
+                label: label.to_string(),
+            },
+            DebugCommand::PopGroup => RecordedCommand::PopDebugGroup,
+            DebugCommand::InsertMarker(label) => RecordedCommand::InsertDebugMarker {
+                label: label.to_string(),
+            },
+        }
+    }
+}
+
+/// Push or pop a debug group, or insert a debug marker, on an encoder
+pub fn command_encoder_debug(ctx_handle: u32, encoder_handle: u32, command: DebugCommand) -> u32 {
+    with_context(ctx_handle, |ctx| {
+        let encoder_id = match ctx.command_encoders.get(&encoder_handle) {
+            Some(id) => *id,
+            None => return super::WEBGPU_ERROR_INVALID_HANDLE,
+        };
+
+        let result = match command {
+            DebugCommand::PushGroup(label) => ctx
+                .global
+                .command_encoder_push_debug_group(encoder_id, label),
+            DebugCommand::PopGroup => ctx.global.command_encoder_pop_debug_group(encoder_id),
+            DebugCommand::InsertMarker(label) => ctx
+                .global
+                .command_encoder_insert_debug_marker(encoder_id, label),
+        };
+        if let Err(e) = result {
+            crate::error::set_error(
+                crate::error::ErrorSource::WebGPU(crate::error::WebGPUErrorFilter::Validation),
+                super::WEBGPU_ERROR_OPERATION_FAILED,
+                e,
+            );
+            return super::WEBGPU_ERROR_OPERATION_FAILED;
+        }
+
+        record(ctx, encoder_handle, command.recorded());
+        super::WEBGPU_SUCCESS
+    })
+}
+
+/// Push or pop a debug group, or insert a debug marker, in a render pass
+pub fn render_pass_debug(ctx_handle: u32, pass_handle: u32, command: DebugCommand) -> u32 {
+    with_context(ctx_handle, |ctx| {
+        let pass = match ctx.render_passes.get_mut(&pass_handle) {
+            Some(p) => p,
+            None => return super::WEBGPU_ERROR_INVALID_HANDLE,
+        };
+
+        let result = match command {
+            DebugCommand::PushGroup(label) => {
+                ctx.global.render_pass_push_debug_group(pass, label, 0)
+            }
+            DebugCommand::PopGroup => ctx.global.render_pass_pop_debug_group(pass),
+            DebugCommand::InsertMarker(label) => {
+                ctx.global.render_pass_insert_debug_marker(pass, label, 0)
+            }
+        };
+        if let Err(e) = result {
+            crate::error::set_error(
+                crate::error::ErrorSource::WebGPU(crate::error::WebGPUErrorFilter::Validation),
+                super::WEBGPU_ERROR_OPERATION_FAILED,
+                e,
+            );
+            return super::WEBGPU_ERROR_OPERATION_FAILED;
+        }
+
+        record_pass(ctx, pass_handle, command.recorded());
+        super::WEBGPU_SUCCESS
+    })
+}
+
 /// Write data to a buffer via the queue
 pub fn queue_write_buffer(
     ctx_handle: u32,
@@ -642,6 +1000,17 @@ pub fn queue_write_buffer(
             None => return super::WEBGPU_ERROR_INVALID_HANDLE,
         };
 
+        if let Err(code) = validate_buffer_alive(ctx, buffer_handle, "writeBuffer").and_then(|_| {
+            validate_buffer_usage(
+                ctx,
+                buffer_handle,
+                wgt::BufferUsages::COPY_DST,
+                "writeBuffer",
+            )
+        }) {
+            return code;
+        }
+
         let queue_id = match ctx.queues.get(&device_handle) {
             Some(id) => *id,
             None => return super::WEBGPU_ERROR_OPERATION_FAILED,
@@ -683,6 +1052,10 @@ pub fn queue_write_texture(
             None => return super::WEBGPU_ERROR_INVALID_HANDLE,
         };
 
+        if let Err(code) = validate_texture_alive(ctx, texture_handle, "writeTexture") {
+            return code;
+        }
+
         let queue_id = match ctx.queues.get(&device_handle) {
             Some(id) => *id,
             None => return super::WEBGPU_ERROR_OPERATION_FAILED,
@@ -7,11 +7,13 @@
 pub mod adapter;
 pub mod backend;
 pub mod bind_group;
+pub mod blit;
 pub mod buffer;
 pub mod command;
 pub mod pipeline;
 pub mod shader;
 pub mod texture;
+pub mod trace;
 
 #[cfg(test)]
 mod tests;
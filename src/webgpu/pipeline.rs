@@ -16,6 +16,9 @@ pub struct RenderPipelineConfig<'a> {
     pub depth_format: u32,
     pub depth_write_enabled: bool,
     pub depth_compare: u32,
+    /// Front face compare/fail/depth-fail/pass, the same for the back face,
+    /// then the read and write masks; empty for the default stencil state
+    pub stencil: &'a [u32],
     pub blend_enabled: bool,
     pub color_blend_src: u32,
     pub color_blend_dst: u32,
@@ -130,7 +133,7 @@ pub fn create_render_pipeline(
                 _ => wgt::TextureFormat::Depth32Float,
             };
 
-            let compare = match config.depth_compare {
+            let map_compare = |c, default| match c {
                 1 => wgt::CompareFunction::Never,
                 2 => wgt::CompareFunction::Less,
                 3 => wgt::CompareFunction::Equal,
@@ -139,14 +142,43 @@ pub fn create_render_pipeline(
                 6 => wgt::CompareFunction::NotEqual,
                 7 => wgt::CompareFunction::GreaterEqual,
                 8 => wgt::CompareFunction::Always,
-                _ => wgt::CompareFunction::Less,
+                _ => default,
+            };
+            let compare = map_compare(config.depth_compare, wgt::CompareFunction::Less);
+
+            let map_stencil_op = |o| match o {
+                1 => wgt::StencilOperation::Zero,
+                2 => wgt::StencilOperation::Replace,
+                3 => wgt::StencilOperation::Invert,
+                4 => wgt::StencilOperation::IncrementClamp,
+                5 => wgt::StencilOperation::DecrementClamp,
+                6 => wgt::StencilOperation::IncrementWrap,
+                7 => wgt::StencilOperation::DecrementWrap,
+                _ => wgt::StencilOperation::Keep,
+            };
+            let stencil = match config.stencil {
+                [face @ .., read_mask, write_mask] if face.len() == 8 => {
+                    let face_state = |f: &[u32]| wgt::StencilFaceState {
+                        compare: map_compare(f[0], wgt::CompareFunction::Always),
+                        fail_op: map_stencil_op(f[1]),
+                        depth_fail_op: map_stencil_op(f[2]),
+                        pass_op: map_stencil_op(f[3]),
+                    };
+                    wgt::StencilState {
+                        front: face_state(&face[..4]),
+                        back: face_state(&face[4..]),
+                        read_mask: *read_mask,
+                        write_mask: *write_mask,
+                    }
+                }
+                _ => wgt::StencilState::default(),
             };
 
             Some(wgt::DepthStencilState {
                 format,
                 depth_write_enabled: config.depth_write_enabled,
                 depth_compare: compare,
-                stencil: wgt::StencilState::default(),
+                stencil,
                 bias: wgt::DepthBiasState::default(),
             })
         } else {
@@ -165,6 +197,8 @@ pub fn create_render_pipeline(
                 7 => wgt::BlendFactor::OneMinusDst,
                 8 => wgt::BlendFactor::DstAlpha,
                 9 => wgt::BlendFactor::OneMinusDstAlpha,
+                10 => wgt::BlendFactor::Constant,
+                11 => wgt::BlendFactor::OneMinusConstant,
                 _ => wgt::BlendFactor::One,
             };
             let map_op = |o| match o {
@@ -255,7 +289,11 @@ pub unsafe fn create_pipeline_layout(
     bind_group_layouts_ptr: *const u32,
     bind_group_layouts_len: usize,
 ) -> u32 {
-    let bgl_handles = std::slice::from_raw_parts(bind_group_layouts_ptr, bind_group_layouts_len);
+    let bgl_handles =
+        match crate::guest_mem::read_slice(bind_group_layouts_ptr, bind_group_layouts_len) {
+            Ok(h) => h,
+            Err(_) => return super::NULL_HANDLE,
+        };
 
     with_context(ctx_handle, |ctx| {
         let device_id = match ctx.devices.get(&device_handle) {
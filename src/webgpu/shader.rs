@@ -16,9 +16,9 @@ pub unsafe fn create_shader_module(
     code_ptr: *const u8,
     code_len: usize,
 ) -> u32 {
-    let code = {
-        let slice = std::slice::from_raw_parts(code_ptr, code_len);
-        std::str::from_utf8_unchecked(slice)
+    let code = match crate::guest_mem::read_utf8(code_ptr, code_len) {
+        Ok(c) => c,
+        Err(_) => return super::NULL_HANDLE,
     };
 
     with_context(ctx_handle, |ctx| {
@@ -349,6 +349,49 @@ mod internal_tests {
         destroy_context(ctx);
     }
 
+    #[test]
+    fn test_buffer_usage_validation() {
+        use crate::webgpu::adapter::{request_adapter, request_device};
+        use crate::webgpu::buffer::{buffer_map_async, create_buffer};
+        use crate::webgpu::command::{
+            command_encoder_copy_buffer_to_buffer, create_command_encoder,
+        };
+        use crate::webgpu::WEBGPU_ERROR_VALIDATION;
+        use wgpu_types::{BufferUsages, PowerPreference};
+
+        let ctx = create_context();
+        let adapter = request_adapter(ctx, PowerPreference::LowPower);
+        let device = request_device(ctx, adapter);
+
+        // Zero usage and MAP_READ combined with anything but COPY_DST are rejected
+        assert_eq!(create_buffer(ctx, device, 64, 0, false), NULL_HANDLE);
+        let bad = (BufferUsages::MAP_READ | BufferUsages::VERTEX).bits();
+        assert_eq!(create_buffer(ctx, device, 64, bad, false), NULL_HANDLE);
+
+        let vertex = create_buffer(
+            ctx,
+            device,
+            64,
+            (BufferUsages::VERTEX | BufferUsages::COPY_DST).bits(),
+            false,
+        );
+        assert_ne!(vertex, NULL_HANDLE);
+
+        // Mapping a buffer without MAP_READ is a validation error
+        error::webgpu_push_error_scope(WebGPUErrorFilter::Validation);
+        let result = buffer_map_async(ctx, device, vertex, 1, 0, 64);
+        assert_eq!(result, WEBGPU_ERROR_VALIDATION);
+        let err = error::webgpu_pop_error_scope();
+        assert!(err.is_some(), "Usage violation should be reported");
+
+        // A buffer without COPY_SRC cannot be a copy source
+        let encoder = create_command_encoder(ctx, device);
+        let result = command_encoder_copy_buffer_to_buffer(ctx, encoder, vertex, 0, vertex, 0, 64);
+        assert_eq!(result, WEBGPU_ERROR_VALIDATION);
+
+        destroy_context(ctx);
+    }
+
     #[test]
     fn test_texture_creation() {
         use crate::webgpu::adapter::{request_adapter, request_device};
@@ -402,6 +445,67 @@ mod internal_tests {
         let result = destroy_texture(ctx, texture);
         assert_eq!(result, WEBGPU_SUCCESS);
 
+        destroy_context(ctx);
+    }
+    #[test]
+    fn test_use_after_destroy() {
+        use crate::webgpu::adapter::{request_adapter, request_device};
+        use crate::webgpu::buffer::{create_buffer, destroy_buffer};
+        use crate::webgpu::command::{
+            command_encoder_copy_buffer_to_buffer, create_command_encoder, queue_write_buffer,
+            queue_write_texture,
+        };
+        use crate::webgpu::texture::{create_texture, destroy_texture, TextureConfig};
+        use crate::webgpu::WEBGPU_ERROR_VALIDATION;
+        use wgpu_types::{BufferUsages, PowerPreference, TextureDimension, TextureUsages};
+
+        let ctx = create_context();
+        let adapter = request_adapter(ctx, PowerPreference::LowPower);
+        let device = request_device(ctx, adapter);
+
+        let usage = (BufferUsages::COPY_SRC | BufferUsages::COPY_DST).bits();
+        let source = create_buffer(ctx, device, 64, usage, false);
+        let dest = create_buffer(ctx, device, 64, usage, false);
+        assert_eq!(destroy_buffer(ctx, source), WEBGPU_SUCCESS);
+        // Destroying twice is allowed
+        assert_eq!(destroy_buffer(ctx, source), WEBGPU_SUCCESS);
+
+        error::webgpu_push_error_scope(WebGPUErrorFilter::Validation);
+        let encoder = create_command_encoder(ctx, device);
+        let result = command_encoder_copy_buffer_to_buffer(ctx, encoder, source, 0, dest, 0, 64);
+        assert_eq!(result, WEBGPU_ERROR_VALIDATION);
+        let err = error::webgpu_pop_error_scope().expect("use after destroy is reported");
+        assert!(err.message.contains("destroyed"), "{}", err.message);
+
+        assert_eq!(
+            queue_write_buffer(ctx, device, source, 0, &[0; 4]),
+            WEBGPU_ERROR_VALIDATION
+        );
+        assert_eq!(
+            queue_write_buffer(ctx, device, dest, 0, &[0; 4]),
+            WEBGPU_SUCCESS
+        );
+
+        let texture = create_texture(
+            ctx,
+            device,
+            TextureConfig {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2 as u32,
+                format: 17, // Rgba8Unorm
+                usage: (TextureUsages::COPY_SRC | TextureUsages::COPY_DST).bits(),
+            },
+        );
+        assert_eq!(destroy_texture(ctx, texture), WEBGPU_SUCCESS);
+        assert_eq!(
+            queue_write_texture(ctx, device, texture, &[0; 64], 16, 4, 4, 4, 1),
+            WEBGPU_ERROR_VALIDATION
+        );
+
         destroy_context(ctx);
     }
 }
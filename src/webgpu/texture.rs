@@ -1,8 +1,62 @@
 //! WebGPU Texture management
 
-use super::adapter::with_context;
+use super::adapter::{with_context, WebGpuContext};
+use super::backend::{SoftSampler, SoftTextureDescriptor, SoftTextureView};
+use super::buffer::validation_error;
+use crate::wasm_gl_emu::transfer::{f32_to_half, half_to_f32};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use wgpu_types as wgt;
 
+/// Check that a texture has not been destroyed.
+pub(crate) fn validate_texture_alive(
+    ctx: &WebGpuContext,
+    texture_handle: u32,
+    operation: &str,
+) -> Result<(), u32> {
+    if ctx.destroyed_textures.contains(&texture_handle) {
+        Err(validation_error(format!(
+            "{} uses texture {} after it was destroyed",
+            operation, texture_handle
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Check that the texture a view was created from has not been destroyed.
+pub(crate) fn validate_texture_view_alive(
+    ctx: &WebGpuContext,
+    view_handle: u32,
+    operation: &str,
+) -> Result<(), u32> {
+    match ctx.texture_view_textures.get(&view_handle) {
+        Some(&texture_handle) => validate_texture_alive(ctx, texture_handle, operation),
+        None => Ok(()),
+    }
+}
+
+/// Map the format codes used by the JS bindings to texture formats.
+fn texture_format(code: u32) -> Option<wgt::TextureFormat> {
+    Some(match code {
+        0 => wgt::TextureFormat::R8Unorm,
+        1 => wgt::TextureFormat::R8Snorm,
+        2 => wgt::TextureFormat::R8Uint,
+        3 => wgt::TextureFormat::R8Sint,
+        12 => wgt::TextureFormat::R16Float,
+        17 => wgt::TextureFormat::Rgba8Unorm,
+        18 => wgt::TextureFormat::Rgba8UnormSrgb,
+        19 => wgt::TextureFormat::Bgra8Unorm,
+        20 => wgt::TextureFormat::Bgra8UnormSrgb,
+        24 => wgt::TextureFormat::Rgba16Float,
+        35 => wgt::TextureFormat::R32Float,
+        38 => wgt::TextureFormat::Depth32Float,
+        39 => wgt::TextureFormat::Depth24Plus,
+        40 => wgt::TextureFormat::Depth24PlusStencil8,
+        _ => return None,
+    })
+}
+
 pub struct TextureConfig {
     pub width: u32,
     pub height: u32,
@@ -29,24 +83,7 @@ pub fn create_texture(ctx_handle: u32, device_handle: u32, config: TextureConfig
             _ => return super::NULL_HANDLE,
         };
 
-        // TODO: Map integer format to wgt::TextureFormat
-        let format = match config.format {
-            0 => wgt::TextureFormat::R8Unorm,
-            1 => wgt::TextureFormat::R8Snorm,
-            2 => wgt::TextureFormat::R8Uint,
-            3 => wgt::TextureFormat::R8Sint,
-            12 => wgt::TextureFormat::R16Float,
-            17 => wgt::TextureFormat::Rgba8Unorm,
-            18 => wgt::TextureFormat::Rgba8UnormSrgb,
-            19 => wgt::TextureFormat::Bgra8Unorm,
-            20 => wgt::TextureFormat::Bgra8UnormSrgb,
-            24 => wgt::TextureFormat::Rgba16Float,
-            35 => wgt::TextureFormat::R32Float,
-            38 => wgt::TextureFormat::Depth32Float,
-            39 => wgt::TextureFormat::Depth24Plus,
-            40 => wgt::TextureFormat::Depth24PlusStencil8,
-            _ => wgt::TextureFormat::Rgba8Unorm, // Default
-        };
+        let format = texture_format(config.format).unwrap_or(wgt::TextureFormat::Rgba8Unorm);
 
         let desc = wgt::TextureDescriptor {
             label: None,
@@ -113,10 +150,11 @@ pub fn create_texture_view(ctx_handle: u32, texture_handle: u32, config: Texture
             None => return super::NULL_HANDLE,
         };
 
+        // 0 inherits the texture format
         let format = if config.format == 0 {
             None
         } else {
-            Some(wgt::TextureFormat::Rgba8Unorm) // TODO: Map format
+            texture_format(config.format)
         };
 
         let dimension = match config.dimension {
@@ -171,6 +209,7 @@ pub fn create_texture_view(ctx_handle: u32, texture_handle: u32, config: Texture
         let handle = ctx.next_texture_view_id;
         ctx.next_texture_view_id += 1;
         ctx.texture_views.insert(handle, view_id);
+        ctx.texture_view_textures.insert(handle, texture_handle);
 
         handle
     })
@@ -255,14 +294,480 @@ pub fn create_sampler(ctx_handle: u32, device_handle: u32, config: SamplerConfig
     })
 }
 
-/// Destroy a texture
+/// Destroy a texture. Destroying it again is a no-op.
 pub fn destroy_texture(ctx_handle: u32, texture_handle: u32) -> u32 {
     with_context(ctx_handle, |ctx| {
-        if let Some(id) = ctx.textures.remove(&texture_handle) {
-            ctx.global.texture_destroy(id);
+        if let Some(&id) = ctx.textures.get(&texture_handle) {
+            if ctx.destroyed_textures.insert(texture_handle) {
+                ctx.global.texture_destroy(id);
+            }
             super::WEBGPU_SUCCESS
         } else {
             super::WEBGPU_ERROR_INVALID_HANDLE
         }
     })
 }
+
+#[derive(Debug, Clone, Copy)]
+enum Component {
+    Unorm8,
+    Float16,
+    Float32,
+}
+
+/// How the texels of a color format are stored.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TexelLayout {
+    component: Component,
+    channels: usize,
+    bgra: bool,
+    srgb: bool,
+}
+
+impl TexelLayout {
+    pub(crate) fn of(format: wgt::TextureFormat) -> Option<Self> {
+        use wgt::TextureFormat as F;
+        let (component, channels) = match format {
+            F::R8Unorm => (Component::Unorm8, 1),
+            F::Rg8Unorm => (Component::Unorm8, 2),
+            F::Rgba8Unorm | F::Rgba8UnormSrgb | F::Bgra8Unorm | F::Bgra8UnormSrgb => {
+                (Component::Unorm8, 4)
+            }
+            F::R16Float => (Component::Float16, 1),
+            F::Rg16Float => (Component::Float16, 2),
+            F::Rgba16Float => (Component::Float16, 4),
+            F::R32Float => (Component::Float32, 1),
+            F::Rg32Float => (Component::Float32, 2),
+            F::Rgba32Float => (Component::Float32, 4),
+            _ => return None,
+        };
+        Some(TexelLayout {
+            component,
+            channels,
+            bgra: matches!(format, F::Bgra8Unorm | F::Bgra8UnormSrgb),
+            srgb: format.is_srgb(),
+        })
+    }
+
+    fn component_size(&self) -> usize {
+        match self.component {
+            Component::Unorm8 => 1,
+            Component::Float16 => 2,
+            Component::Float32 => 4,
+        }
+    }
+
+    pub(crate) fn decode(&self, bytes: &[u8]) -> [f32; 4] {
+        let mut texel = [0.0, 0.0, 0.0, 1.0];
+        for (value, chunk) in texel
+            .iter_mut()
+            .zip(bytes.chunks_exact(self.component_size()))
+            .take(self.channels)
+        {
+            *value = match self.component {
+                Component::Unorm8 => chunk[0] as f32 / 255.0,
+                Component::Float16 => half_to_f32(u16::from_le_bytes([chunk[0], chunk[1]])),
+                Component::Float32 => f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]),
+            };
+        }
+        if self.bgra {
+            texel.swap(0, 2);
+        }
+        if self.srgb {
+            for value in &mut texel[..3] {
+                *value = srgb_to_linear(*value);
+            }
+        }
+        texel
+    }
+
+    pub(crate) fn encode(&self, mut texel: [f32; 4], bytes: &mut [u8]) {
+        if self.srgb {
+            for value in &mut texel[..3] {
+                *value = linear_to_srgb(*value);
+            }
+        }
+        if self.bgra {
+            texel.swap(0, 2);
+        }
+        for (chunk, value) in bytes
+            .chunks_exact_mut(self.component_size())
+            .zip(texel)
+            .take(self.channels)
+        {
+            match self.component {
+                Component::Unorm8 => chunk[0] = (value.clamp(0.0, 1.0) * 255.0).round() as u8,
+                Component::Float16 => chunk.copy_from_slice(&f32_to_half(value).to_le_bytes()),
+                Component::Float32 => chunk.copy_from_slice(&value.to_le_bytes()),
+            }
+        }
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Level of detail requested by a shader sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SampleLevel {
+    /// Implicit level. The rasterizer shades pixels one at a time and has no
+    /// screen-space derivatives, so this samples the base level.
+    Auto,
+    Exact(f32),
+    Bias(f32),
+    Gradient {
+        ddx: [f32; 2],
+        ddy: [f32; 2],
+    },
+}
+
+/// Texture view bound to a shader for the duration of a draw.
+#[derive(Debug, Clone)]
+pub(crate) struct SampledTexture {
+    /// Start of the texture storage, kept locked by the draw
+    data: *const u8,
+    len: usize,
+    desc: SoftTextureDescriptor,
+    layout: Option<TexelLayout>,
+    base_mip_level: u32,
+    mip_level_count: u32,
+    base_array_layer: u32,
+    array_layer_count: u32,
+}
+
+impl SampledTexture {
+    pub(crate) fn new(view: &SoftTextureView, data: *const u8, len: usize) -> Self {
+        let desc = view.texture_desc.clone();
+        let range = &view.desc.range;
+        let layers = match desc.dimension {
+            wgt::TextureDimension::D3 => 1,
+            _ => desc.size.depth_or_array_layers,
+        };
+        SampledTexture {
+            data,
+            len,
+            layout: TexelLayout::of(view.desc.format),
+            base_mip_level: range.base_mip_level,
+            mip_level_count: range
+                .mip_level_count
+                .unwrap_or(desc.mip_level_count.saturating_sub(range.base_mip_level))
+                .max(1),
+            base_array_layer: range.base_array_layer,
+            array_layer_count: range
+                .array_layer_count
+                .unwrap_or(layers.saturating_sub(range.base_array_layer))
+                .max(1),
+            desc,
+        }
+    }
+
+    /// Size of a mip level of the view, level 0 being its base level.
+    pub(crate) fn level_size(&self, level: u32) -> wgt::Extent3d {
+        self.desc.mip_level_size(self.base_mip_level + level)
+    }
+
+    /// Byte offset of the base level of the view in the texture storage.
+    pub(crate) fn base_offset(&self) -> usize {
+        self.desc.mip_level_offset(self.base_mip_level)
+    }
+
+    fn texel(&self, level: u32, x: u32, y: u32, z: u32) -> [f32; 4] {
+        let Some(layout) = self.layout else {
+            return [0.0, 0.0, 0.0, 1.0];
+        };
+        let mip = self.base_mip_level + level;
+        let size = self.desc.mip_level_size(mip);
+        let texel_size = self.desc.texel_size() as usize;
+        let index = ((z * size.height + y) * size.width + x) as usize;
+        let offset = self.desc.mip_level_offset(mip) + index * texel_size;
+        if offset + texel_size > self.len {
+            return [0.0, 0.0, 0.0, 1.0];
+        }
+        // SAFETY: the draw holds the storage lock while bindings are registered,
+        // and the range was checked against the storage length above
+        let bytes = unsafe { std::slice::from_raw_parts(self.data.add(offset), texel_size) };
+        layout.decode(bytes)
+    }
+}
+
+/// Sampler bound to a shader for the duration of a draw.
+#[derive(Debug, Clone)]
+pub(crate) struct SamplerState {
+    address_modes: [wgt::AddressMode; 3],
+    mag_filter: wgt::FilterMode,
+    min_filter: wgt::FilterMode,
+    mipmap_filter: wgt::MipmapFilterMode,
+    lod_min_clamp: f32,
+    lod_max_clamp: f32,
+}
+
+impl From<&SoftSampler> for SamplerState {
+    fn from(sampler: &SoftSampler) -> Self {
+        SamplerState {
+            address_modes: sampler.desc.address_modes,
+            mag_filter: sampler.desc.mag_filter,
+            min_filter: sampler.desc.min_filter,
+            mipmap_filter: sampler.desc.mipmap_filter,
+            lod_min_clamp: sampler.desc.lod_clamp.start,
+            lod_max_clamp: sampler.desc.lod_clamp.end,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum ShaderBinding {
+    Texture(SampledTexture),
+    Sampler(SamplerState),
+}
+
+thread_local! {
+    /// Textures and samplers of the draw in progress, keyed by the address of
+    /// their descriptor block in shader memory.
+    static DRAW_BINDINGS: RefCell<HashMap<u32, ShaderBinding>> = RefCell::new(HashMap::new());
+    /// Result of the last shader sample, read back by the shader.
+    static SAMPLE_RESULT: RefCell<[f32; 4]> = const { RefCell::new([0.0; 4]) };
+}
+
+/// Register a binding whose descriptor block the shaders will pass to
+/// [`shader_sample`].
+pub(crate) fn bind_for_draw(descriptor_addr: u32, binding: ShaderBinding) {
+    DRAW_BINDINGS.with(|bindings| bindings.borrow_mut().insert(descriptor_addr, binding));
+}
+
+/// Forget the bindings of a finished draw, whose storage is about to be unlocked.
+pub(crate) fn clear_draw_bindings() {
+    DRAW_BINDINGS.with(|bindings| bindings.borrow_mut().clear());
+}
+
+/// Sample a texture bound to the draw in progress. Unknown descriptors sample
+/// opaque black. Returns the address of the RGBA result, valid until the
+/// next sample.
+pub fn shader_sample(
+    texture_addr: u32,
+    sampler_addr: u32,
+    coord: [f32; 3],
+    level: SampleLevel,
+) -> *const f32 {
+    let texel = DRAW_BINDINGS.with(|bindings| {
+        let bindings = bindings.borrow();
+        match (bindings.get(&texture_addr), bindings.get(&sampler_addr)) {
+            (Some(ShaderBinding::Texture(texture)), Some(ShaderBinding::Sampler(sampler))) => {
+                sample(texture, sampler, coord, level)
+            }
+            _ => [0.0, 0.0, 0.0, 1.0],
+        }
+    });
+    SAMPLE_RESULT.with(|result| {
+        *result.borrow_mut() = texel;
+        result.as_ptr() as *const f32
+    })
+}
+
+/// Filter a texture view at normalized coordinates. `coord[2]` is the depth
+/// coordinate of 3D textures and the array layer otherwise.
+pub(crate) fn sample(
+    texture: &SampledTexture,
+    sampler: &SamplerState,
+    coord: [f32; 3],
+    level: SampleLevel,
+) -> [f32; 4] {
+    let lod = match level {
+        SampleLevel::Auto => 0.0,
+        SampleLevel::Exact(lod) | SampleLevel::Bias(lod) => lod,
+        SampleLevel::Gradient { ddx, ddy } => {
+            let base = texture.level_size(0);
+            let footprint =
+                |d: [f32; 2]| (d[0] * base.width as f32).hypot(d[1] * base.height as f32);
+            footprint(ddx).max(footprint(ddy)).log2()
+        }
+    };
+    let lod = lod.max(sampler.lod_min_clamp).min(sampler.lod_max_clamp);
+
+    // Magnifying samples the base level; minifying selects mips
+    if lod.is_nan() || lod <= 0.0 {
+        return filter_level(texture, sampler, 0, sampler.mag_filter, coord);
+    }
+    let max_level = (texture.mip_level_count - 1) as f32;
+    match sampler.mipmap_filter {
+        wgt::MipmapFilterMode::Nearest => {
+            let level = (lod + 0.5).floor().min(max_level) as u32;
+            filter_level(texture, sampler, level, sampler.min_filter, coord)
+        }
+        wgt::MipmapFilterMode::Linear => {
+            let low = lod.floor().min(max_level);
+            let high = (low + 1.0).min(max_level);
+            let frac = if high > low { lod - low } else { 0.0 };
+            let a = filter_level(texture, sampler, low as u32, sampler.min_filter, coord);
+            if frac == 0.0 {
+                return a;
+            }
+            let b = filter_level(texture, sampler, high as u32, sampler.min_filter, coord);
+            std::array::from_fn(|i| a[i] + (b[i] - a[i]) * frac)
+        }
+    }
+}
+
+fn filter_level(
+    texture: &SampledTexture,
+    sampler: &SamplerState,
+    level: u32,
+    filter: wgt::FilterMode,
+    coord: [f32; 3],
+) -> [f32; 4] {
+    let size = texture.level_size(level);
+    let modes = sampler.address_modes;
+    let xs = taps(coord[0], size.width, filter, modes[0]);
+    let ys = taps(coord[1], size.height, filter, modes[1]);
+    let zs = if texture.desc.dimension == wgt::TextureDimension::D3 {
+        taps(coord[2], size.depth_or_array_layers, filter, modes[2])
+    } else {
+        let layer = (coord[2].round().max(0.0) as u32).min(texture.array_layer_count - 1);
+        let layer = texture.base_array_layer + layer;
+        [(layer, 1.0), (layer, 0.0)]
+    };
+
+    let mut result = [0.0; 4];
+    for &(z, wz) in &zs {
+        for &(y, wy) in &ys {
+            for &(x, wx) in &xs {
+                let weight = wx * wy * wz;
+                if weight == 0.0 {
+                    continue;
+                }
+                let texel = texture.texel(level, x, y, z);
+                for (acc, value) in result.iter_mut().zip(texel) {
+                    *acc += value * weight;
+                }
+            }
+        }
+    }
+    result
+}
+
+/// Texels and weights covering a normalized coordinate along one axis.
+fn taps(coord: f32, size: u32, filter: wgt::FilterMode, mode: wgt::AddressMode) -> [(u32, f32); 2] {
+    let texel = coord * size as f32;
+    match filter {
+        wgt::FilterMode::Nearest => {
+            let index = address(texel.floor() as i32, size, mode);
+            [(index, 1.0), (index, 0.0)]
+        }
+        wgt::FilterMode::Linear => {
+            let texel = texel - 0.5;
+            let low = texel.floor();
+            let frac = texel - low;
+            [
+                (address(low as i32, size, mode), 1.0 - frac),
+                (address(low as i32 + 1, size, mode), frac),
+            ]
+        }
+    }
+}
+
+/// Apply an address mode to a texel index.
+fn address(index: i32, size: u32, mode: wgt::AddressMode) -> u32 {
+    let size = size as i32;
+    let index = match mode {
+        wgt::AddressMode::Repeat => index.rem_euclid(size),
+        wgt::AddressMode::MirrorRepeat => {
+            let index = index.rem_euclid(2 * size);
+            if index < size {
+                index
+            } else {
+                2 * size - 1 - index
+            }
+        }
+        // WebGPU exposes no border color, so clamp-to-border behaves like clamp-to-edge
+        _ => index.clamp(0, size - 1),
+    };
+    index as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sampler(filter: wgt::FilterMode, mode: wgt::AddressMode) -> SamplerState {
+        SamplerState {
+            address_modes: [mode; 3],
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_filter: wgt::MipmapFilterMode::Linear,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 32.0,
+        }
+    }
+
+    /// 2x2 R32Float texture with a 1x1 second level.
+    fn texture(data: &[f32]) -> SampledTexture {
+        let bytes: &'static [u8] = Box::leak(
+            data.iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+        );
+        SampledTexture {
+            data: bytes.as_ptr(),
+            len: bytes.len(),
+            desc: SoftTextureDescriptor {
+                size: wgt::Extent3d {
+                    width: 2,
+                    height: 2,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 2,
+                sample_count: 1,
+                dimension: wgt::TextureDimension::D2,
+                format: wgt::TextureFormat::R32Float,
+                usage: wgt::TextureUses::RESOURCE,
+            },
+            layout: TexelLayout::of(wgt::TextureFormat::R32Float),
+            base_mip_level: 0,
+            mip_level_count: 2,
+            base_array_layer: 0,
+            array_layer_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_address_modes_filters_and_mips() {
+        let texture = texture(&[0.0, 1.0, 2.0, 3.0, 8.0]);
+        let red = |sampler: &SamplerState, u: f32, v: f32, level: SampleLevel| {
+            sample(&texture, sampler, [u, v, 0.0], level)[0]
+        };
+
+        let nearest = sampler(wgt::FilterMode::Nearest, wgt::AddressMode::Repeat);
+        assert_eq!(red(&nearest, 0.75, 0.25, SampleLevel::Auto), 1.0);
+        assert_eq!(red(&nearest, 1.75, -0.75, SampleLevel::Auto), 1.0);
+
+        let clamped = sampler(wgt::FilterMode::Nearest, wgt::AddressMode::ClampToEdge);
+        assert_eq!(red(&clamped, 3.0, 0.25, SampleLevel::Auto), 1.0);
+
+        let mirrored = sampler(wgt::FilterMode::Nearest, wgt::AddressMode::MirrorRepeat);
+        assert_eq!(red(&mirrored, 1.25, 0.25, SampleLevel::Auto), 1.0);
+        assert_eq!(red(&mirrored, 1.75, 0.25, SampleLevel::Auto), 0.0);
+
+        let linear = sampler(wgt::FilterMode::Linear, wgt::AddressMode::ClampToEdge);
+        assert_eq!(red(&linear, 0.5, 0.5, SampleLevel::Auto), 1.5);
+        assert_eq!(red(&linear, 0.5, 0.5, SampleLevel::Exact(1.0)), 8.0);
+        assert_eq!(red(&linear, 0.5, 0.5, SampleLevel::Exact(0.5)), 4.75);
+        let gradient = SampleLevel::Gradient {
+            ddx: [1.0, 0.0],
+            ddy: [0.0, 1.0],
+        };
+        assert_eq!(red(&linear, 0.5, 0.5, gradient), 8.0);
+    }
+}
@@ -0,0 +1,158 @@
+//! Command recording for golden-trace testing
+//!
+//! Each command encoder keeps a log of the commands encoded on it, render pass
+//! commands included. `finish()` hands the log to the command buffer, where
+//! [`command_buffer_dump`] serializes it to JSON until the buffer is submitted.
+//! Resources appear by handle, so a trace is stable across runs as long as
+//! objects are created in the same order.
+
+use super::adapter::{with_context_val, WebGpuContext};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+thread_local! {
+    /// Keeps the last dump alive until the next one is taken.
+    static DUMP_BUFFER: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(
+    tag = "command",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum RecordedCommand {
+    PushDebugGroup {
+        label: String,
+    },
+    PopDebugGroup,
+    InsertDebugMarker {
+        label: String,
+    },
+    CopyBufferToBuffer {
+        source: u32,
+        source_offset: u64,
+        destination: u32,
+        destination_offset: u64,
+        size: u64,
+    },
+    CopyTextureToBuffer {
+        source: u32,
+        mip_level: u32,
+        destination: u32,
+        offset: u64,
+        bytes_per_row: u32,
+        rows_per_image: u32,
+        size: [u32; 3],
+    },
+    BeginRenderPass {
+        view: u32,
+        load_op: &'static str,
+        store_op: &'static str,
+        clear_value: [f64; 4],
+    },
+    SetPipeline {
+        pipeline: u32,
+    },
+    SetBindGroup {
+        index: u32,
+        bind_group: u32,
+    },
+    SetVertexBuffer {
+        slot: u32,
+        buffer: u32,
+        offset: u64,
+        size: u64,
+    },
+    SetIndexBuffer {
+        buffer: u32,
+        format: &'static str,
+        offset: u64,
+        size: u64,
+    },
+    SetViewport {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        min_depth: f32,
+        max_depth: f32,
+    },
+    SetScissorRect {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    SetBlendConstant {
+        color: [f64; 4],
+    },
+    SetStencilReference {
+        reference: u32,
+    },
+    Draw {
+        vertex_count: u32,
+        instance_count: u32,
+        first_vertex: u32,
+        first_instance: u32,
+    },
+    DrawIndexed {
+        index_count: u32,
+        instance_count: u32,
+        first_index: u32,
+        base_vertex: i32,
+        first_instance: u32,
+    },
+    EndRenderPass,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CommandBufferDump<'a> {
+    command_buffer: u32,
+    commands: &'a [RecordedCommand],
+}
+
+/// Append a command to the log of an encoder.
+pub(crate) fn record(ctx: &mut WebGpuContext, encoder_handle: u32, command: RecordedCommand) {
+    if let Some(log) = ctx.encoder_commands.get_mut(&encoder_handle) {
+        log.push(command);
+    }
+}
+
+/// Append a command to the log of the encoder a render pass was begun on.
+pub(crate) fn record_pass(ctx: &mut WebGpuContext, pass_handle: u32, command: RecordedCommand) {
+    if let Some(&encoder_handle) = ctx.render_pass_encoders.get(&pass_handle) {
+        record(ctx, encoder_handle, command);
+    }
+}
+
+/// Serialize the commands of a finished, not yet submitted command buffer
+/// as pretty-printed JSON. Returns a null-terminated string that stays valid
+/// until the next dump, or null for an unknown handle.
+pub fn command_buffer_dump(ctx_handle: u32, command_buffer_handle: u32) -> *const c_char {
+    let json = with_context_val(ctx_handle, None, |ctx| {
+        let commands = ctx.command_buffer_commands.get(&command_buffer_handle)?;
+        serde_json::to_string_pretty(&CommandBufferDump {
+            command_buffer: command_buffer_handle,
+            commands,
+        })
+        .ok()
+    });
+    let Some(json) = json else {
+        crate::error::set_error(
+            crate::error::ErrorSource::WebGPU(crate::error::WebGPUErrorFilter::Validation),
+            super::WEBGPU_ERROR_INVALID_HANDLE,
+            "Invalid command buffer handle",
+        );
+        return std::ptr::null();
+    };
+    DUMP_BUFFER.with(|buffer| {
+        let c_str = CString::new(json).unwrap_or_default();
+        let ptr = c_str.as_ptr();
+        *buffer.borrow_mut() = Some(c_str);
+        ptr
+    })
+}
@@ -102,6 +102,18 @@ export class GPUUncapturedErrorEvent extends Event {
   }
 }
 
+export class GPUDeviceLostEvent extends Event {
+  constructor(type, eventInitDict) {
+    super(type, eventInitDict);
+    this.info = eventInitDict.info;
+  }
+}
+
+// Kinds and lost reasons returned by wasm_webgpu_device_pop_event
+const DEVICE_EVENT_UNCAPTURED_ERROR = 1;
+const DEVICE_EVENT_LOST = 2;
+const DEVICE_LOST_REASONS = /** @type {const} */(['unknown', 'destroyed']);
+
 const TEXTURE_FORMAT_MAP = /** @type {const} */({
   'r8unorm': 0,
   'r8snorm': 1,
@@ -155,21 +167,24 @@ const LIMIT_NAMES = /** @type {const} */([
  */
 export class GPU {
 
-  static dispatchUncapturedError(msg) {
-    // In Node.js, Event might not be available globally or might behave differently.
-    // But we are using 'node:test' which implies Node environment.
-    // If Event is not defined, we might need a polyfill or just call the handler directly.
-
-    const error = new GPUError(msg);
-
-    for (const device of activeDevices) {
-      if (typeof device.onuncapturederror === 'function') {
-        device.onuncapturederror({ error });
-      }
-      // Also dispatch as event if supported
-      if (typeof Event !== 'undefined') {
-        const event = new GPUUncapturedErrorEvent('uncapturederror', { error });
-        device.dispatchEvent(event);
+  /**
+   * Drain the device event queue of a module, dispatching uncaptured errors
+   * to its live devices and resolving `lost` on the devices that were lost.
+   * @param {*} wasm - WebAssembly module exports.
+   * @param {WebAssembly.Memory} memory - WebAssembly linear memory.
+   */
+  static dispatchDeviceEvents(wasm, memory) {
+    for (let recordPtr; (recordPtr = wasm.wasm_webgpu_device_pop_event());) {
+      const [kind, ctxHandle, deviceHandle, code, msgPtr, msgLen] = new Uint32Array(memory.buffer, recordPtr, 6);
+      const message = new TextDecoder('utf-8').decode(new Uint8Array(memory.buffer, msgPtr, msgLen));
+
+      for (const device of [...activeDevices]) {
+        if (device.wasm !== wasm) continue;
+        if (kind === DEVICE_EVENT_UNCAPTURED_ERROR) {
+          device._dispatchUncapturedError(createGPUError(code, message));
+        } else if (kind === DEVICE_EVENT_LOST && device.ctxHandle === ctxHandle && device.deviceHandle === deviceHandle) {
+          device._dispatchLost(new GPUDeviceLostInfo(DEVICE_LOST_REASONS[code] ?? 'unknown', message));
+        }
       }
     }
   }
@@ -292,9 +307,34 @@ export class GPUDevice extends (typeof EventTarget !== 'undefined' ? EventTarget
     this.deviceHandle = deviceHandle;
     this.queue = new GPUQueue(wasmModule, wasmMemory, ctxHandle, deviceHandle);
     this._destroyed = false;
+    /** @type {Promise<GPUDeviceLostInfo>} */
+    this.lost = new Promise(resolve => { this._resolveLost = resolve; });
     activeDevices.add(this);
   }
 
+  /**
+   * @param {GPUError} error
+   */
+  _dispatchUncapturedError(error) {
+    if (typeof this.onuncapturederror === 'function') {
+      this.onuncapturederror({ error });
+    }
+    if (typeof Event !== 'undefined') {
+      this.dispatchEvent(new GPUUncapturedErrorEvent('uncapturederror', { error }));
+    }
+  }
+
+  /**
+   * @param {GPUDeviceLostInfo} info
+   */
+  _dispatchLost(info) {
+    activeDevices.delete(this);
+    this._resolveLost(info);
+    if (typeof Event !== 'undefined') {
+      this.dispatchEvent(new GPUDeviceLostEvent('lost', { info }));
+    }
+  }
+
   pushErrorScope(filter) {
     let filterCode = 0;
     if (filter === 'validation') filterCode = 0;
@@ -310,12 +350,7 @@ export class GPUDevice extends (typeof EventTarget !== 'undefined' ? EventTarget
       const ptr = this.wasm.wasm_get_webgpu_error_msg_ptr();
       const msg = readString(this.memory, ptr);
       const filter = this.wasm.wasm_get_webgpu_error_filter();
-
-      if (filter === 0) return new GPUValidationError(msg);
-      if (filter === 1) return new GPUOutOfMemoryError(msg);
-      if (filter === 2) return new GPUInternalError(msg);
-
-      return new GPUError(msg);
+      return createGPUError(filter, msg);
     }
     return null;
   }
@@ -516,10 +551,35 @@ export class GPUDevice extends (typeof EventTarget !== 'undefined' ? EventTarget
       'always': 8,
     }[depthStencil?.depthCompare || 'less'] || 2;
 
+    const compareMap = {
+      'never': 1, 'less': 2, 'equal': 3, 'less-equal': 4,
+      'greater': 5, 'not-equal': 6, 'greater-equal': 7, 'always': 8,
+    };
+    const stencilOpMap = {
+      'keep': 0, 'zero': 1, 'replace': 2, 'invert': 3,
+      'increment-clamp': 4, 'decrement-clamp': 5, 'increment-wrap': 6, 'decrement-wrap': 7,
+    };
+    const stencilFace = (face = {}) => [
+      compareMap[face.compare || 'always'],
+      stencilOpMap[face.failOp || 'keep'],
+      stencilOpMap[face.depthFailOp || 'keep'],
+      stencilOpMap[face.passOp || 'keep'],
+    ];
+    // Front face, back face, read mask, write mask
+    const stencilData = depthStencil ? [
+      ...stencilFace(depthStencil.stencilFront),
+      ...stencilFace(depthStencil.stencilBack),
+      depthStencil.stencilReadMask ?? 0xFFFFFFFF,
+      depthStencil.stencilWriteMask ?? 0xFFFFFFFF,
+    ] : [];
+    const sPtr = this.wasm.wasm_alloc(Math.max(stencilData.length, 1) * 4);
+    new Uint32Array(this.memory.buffer, sPtr, stencilData.length).set(stencilData);
+
     const blendFactorMap = {
       'zero': 0, 'one': 1, 'src': 2, 'one-minus-src': 3,
       'src-alpha': 4, 'one-minus-src-alpha': 5,
       'dst': 6, 'one-minus-dst': 7, 'dst-alpha': 8, 'one-minus-dst-alpha': 9,
+      'constant': 10, 'one-minus-constant': 11,
     };
 
     const blendOpMap = {
@@ -545,6 +605,8 @@ export class GPUDevice extends (typeof EventTarget !== 'undefined' ? EventTarget
       depthFormat,
       depthStencil?.depthWriteEnabled ? 1 : 0,
       depthCompare,
+      sPtr,
+      stencilData.length,
       blend ? 1 : 0,
       blendFactorMap[blend?.color?.srcFactor] || 0,
       blendFactorMap[blend?.color?.dstFactor] || 0,
@@ -557,6 +619,7 @@ export class GPUDevice extends (typeof EventTarget !== 'undefined' ? EventTarget
     this.wasm.wasm_free(vPtr, vBytes.length);
     this.wasm.wasm_free(fPtr, fBytes.length);
     this.wasm.wasm_free(lPtr, layoutData.length * 4);
+    this.wasm.wasm_free(sPtr, Math.max(stencilData.length, 1) * 4);
 
     if (pipelineHandle === 0) {
       // Error already captured by Rust
@@ -720,11 +783,41 @@ export class GPUDevice extends (typeof EventTarget !== 'undefined' ? EventTarget
     return new GPUSampler(this.wasm, this.memory, this.ctxHandle, handle);
   }
 
+  /**
+   * Fill mip levels 1 and up of a texture by box-filtering level 0.
+   * Non-standard: browsers use internal pipelines for this.
+   * @param {GPUTexture} texture
+   */
+  generateMipmaps(texture) {
+    this.wasm.wasm_webgpu_generate_mipmaps(this.ctxHandle, this.deviceHandle, texture.textureHandle);
+  }
+
+  /**
+   * Copy a mip level into another texture, scaling to the destination size
+   * and converting between formats. Non-standard.
+   * @param {Object} source - { texture, mipLevel }
+   * @param {Object} destination - { texture, mipLevel }
+   * @param {Object} options - { filter: 'nearest' | 'linear' }
+   */
+  blitTexture(source, destination, options = {}) {
+    this.wasm.wasm_webgpu_blit_texture(
+      this.ctxHandle,
+      this.deviceHandle,
+      source.texture.textureHandle,
+      source.mipLevel || 0,
+      destination.texture.textureHandle,
+      destination.mipLevel || 0,
+      options.filter === 'linear' ? 1 : 0
+    );
+  }
+
   /**
    * Destroy the device
    */
   destroy() {
     if (this._destroyed) return;
+    // Queues the 'destroyed' lost event, dispatched before the context goes away
+    this.wasm.wasm_webgpu_destroy_device(this.ctxHandle, this.deviceHandle);
     activeDevices.delete(this);
     if (typeof this.wasm.wasm_webgpu_destroy_context === 'function') {
       this.wasm.wasm_webgpu_destroy_context(this.ctxHandle);
@@ -1003,6 +1096,7 @@ export class GPUCommandEncoder {
       this.ctxHandle,
       this.encoderHandle,
       source.texture.textureHandle,
+      source.mipLevel || 0,
       destination.buffer.bufferHandle,
       BigInt(destination.offset || 0),
       destination.bytesPerRow || 0,
@@ -1039,6 +1133,26 @@ export class GPUCommandEncoder {
     return new GPURenderPassEncoder(this.wasm, this.memory, this.ctxHandle, passHandle);
   }
 
+  /**
+   * @param {string} groupLabel
+   */
+  pushDebugGroup(groupLabel) {
+    withString(this.wasm, this.memory, groupLabel, (ptr, len) =>
+      this.wasm.wasm_webgpu_command_encoder_push_debug_group(this.ctxHandle, this.encoderHandle, ptr, len));
+  }
+
+  popDebugGroup() {
+    this.wasm.wasm_webgpu_command_encoder_pop_debug_group(this.ctxHandle, this.encoderHandle);
+  }
+
+  /**
+   * @param {string} markerLabel
+   */
+  insertDebugMarker(markerLabel) {
+    withString(this.wasm, this.memory, markerLabel, (ptr, len) =>
+      this.wasm.wasm_webgpu_command_encoder_insert_debug_marker(this.ctxHandle, this.encoderHandle, ptr, len));
+  }
+
   /**
    * Finish encoding and create a command buffer
    * @returns {GPUCommandBuffer}
@@ -1168,12 +1282,49 @@ export class GPURenderPassEncoder {
     this.wasm.wasm_webgpu_render_pass_set_scissor_rect(this.ctxHandle, this.passHandle, x, y, width, height);
   }
 
+  /**
+   * Set the constant color used by 'constant' and 'one-minus-constant' blend factors
+   * @param {GPUColor} color - {r, g, b, a} or [r, g, b, a]
+   */
+  setBlendConstant(color) {
+    const [r, g, b, a] = Array.isArray(color) ? color : [color.r, color.g, color.b, color.a];
+    this.wasm.wasm_webgpu_render_pass_set_blend_constant(this.ctxHandle, this.passHandle, r, g, b, a);
+  }
+
+  /**
+   * Set the reference value for the stencil test
+   * @param {number} reference
+   */
+  setStencilReference(reference) {
+    this.wasm.wasm_webgpu_render_pass_set_stencil_reference(this.ctxHandle, this.passHandle, reference >>> 0);
+  }
+
   /**
    * End the render pass
    */
   end() {
     this.wasm.wasm_webgpu_render_pass_end(this.ctxHandle, this.passHandle);
   }
+
+  /**
+   * @param {string} groupLabel
+   */
+  pushDebugGroup(groupLabel) {
+    withString(this.wasm, this.memory, groupLabel, (ptr, len) =>
+      this.wasm.wasm_webgpu_render_pass_push_debug_group(this.ctxHandle, this.passHandle, ptr, len));
+  }
+
+  popDebugGroup() {
+    this.wasm.wasm_webgpu_render_pass_pop_debug_group(this.ctxHandle, this.passHandle);
+  }
+
+  /**
+   * @param {string} markerLabel
+   */
+  insertDebugMarker(markerLabel) {
+    withString(this.wasm, this.memory, markerLabel, (ptr, len) =>
+      this.wasm.wasm_webgpu_render_pass_insert_debug_marker(this.ctxHandle, this.passHandle, ptr, len));
+  }
 }
 
 export class GPUCommandBuffer {
@@ -1189,6 +1340,16 @@ export class GPUCommandBuffer {
     this.ctxHandle = ctxHandle;
     this.commandBufferHandle = commandBufferHandle;
   }
+
+  /**
+   * Non-standard: the recorded commands as JSON, for comparing against
+   * golden traces. Only available until the buffer is submitted.
+   * @returns {string | null}
+   */
+  dumpCommands() {
+    const ptr = this.wasm.wasm_webgpu_command_buffer_dump(this.ctxHandle, this.commandBufferHandle);
+    return readString(this.memory, ptr);
+  }
 }
 
 export class GPUTexture {
@@ -1252,8 +1413,8 @@ export class GPUTexture {
   }
 
   destroy() {
+    // The handle stays valid so later uses report a validation error
     this.wasm.wasm_webgpu_destroy_texture(this.ctxHandle, this.textureHandle);
-    this.textureHandle = 0;
   }
 }
 
@@ -1427,6 +1588,26 @@ function readString(memory, ptr) {
   return new TextDecoder().decode(view.subarray(ptr, end));
 }
 
+/**
+ * Copy a string into wasm memory for the duration of `fn(ptr, len)`.
+ * @template T
+ * @param {*} wasm
+ * @param {WebAssembly.Memory} memory
+ * @param {string} str
+ * @param {(ptr: number, len: number) => T} fn
+ * @returns {T}
+ */
+function withString(wasm, memory, str, fn) {
+  const bytes = new TextEncoder().encode(str);
+  const ptr = bytes.length ? wasm.wasm_alloc(bytes.length) : 0;
+  new Uint8Array(memory.buffer, ptr, bytes.length).set(bytes);
+  try {
+    return fn(ptr, bytes.length);
+  } finally {
+    if (ptr) wasm.wasm_free(ptr, bytes.length);
+  }
+}
+
 export class GPUError {
   constructor(message) {
     this.message = message;
@@ -1453,3 +1634,26 @@ export class GPUOutOfMemoryError extends GPUError {
     this.name = 'GPUOutOfMemoryError';
   }
 }
+
+/**
+ * @param {number} filter - 0: validation, 1: out-of-memory, 2: internal
+ * @param {string} message
+ * @returns {GPUError}
+ */
+function createGPUError(filter, message) {
+  if (filter === 0) return new GPUValidationError(message);
+  if (filter === 1) return new GPUOutOfMemoryError(message);
+  if (filter === 2) return new GPUInternalError(message);
+  return new GPUError(message);
+}
+
+export class GPUDeviceLostInfo {
+  /**
+   * @param {'unknown' | 'destroyed'} reason
+   * @param {string} message
+   */
+  constructor(reason, message) {
+    this.reason = reason;
+    this.message = message;
+  }
+}
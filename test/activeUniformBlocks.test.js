@@ -0,0 +1,99 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS_SRC = `#version 300 es
+layout(std140) uniform Lights {
+  vec4 color;
+  vec3 direction;
+  float intensity;
+} lights;
+layout(location = 0) in vec4 position;
+void main() { gl_Position = position; }
+`;
+
+const FS_SRC = `#version 300 es
+precision highp float;
+layout(std140) uniform Material {
+  mat4 transform;
+  float roughness;
+};
+out vec4 fragColor;
+void main() { fragColor = vec4(1.0); }
+`;
+
+test('active uniform blocks report their name, size, members and stages', async () => {
+  const gl = await webGL2();
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, VS_SRC);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, FS_SRC);
+    gl.compileShader(fs);
+    const p = gl.createProgram();
+    gl.attachShader(p, vs);
+    gl.attachShader(p, fs);
+    gl.linkProgram(p);
+    gl.uniformBlockBinding(p, 0, 3);
+
+    const block = (index) => ({
+      name: gl.getActiveUniformBlockName(p, index),
+      binding: gl.getActiveUniformBlockParameter(p, index, gl.UNIFORM_BLOCK_BINDING),
+      dataSize: gl.getActiveUniformBlockParameter(p, index, gl.UNIFORM_BLOCK_DATA_SIZE),
+      uniforms: gl.getActiveUniformBlockParameter(p, index, gl.UNIFORM_BLOCK_ACTIVE_UNIFORMS),
+      indices: Array.from(
+        gl.getActiveUniformBlockParameter(p, index, gl.UNIFORM_BLOCK_ACTIVE_UNIFORM_INDICES),
+      ).map((i) => gl.getActiveUniform(p, i).name),
+      vertex: gl.getActiveUniformBlockParameter(p, index, gl.UNIFORM_BLOCK_REFERENCED_BY_VERTEX_SHADER),
+      fragment: gl.getActiveUniformBlockParameter(p, index, gl.UNIFORM_BLOCK_REFERENCED_BY_FRAGMENT_SHADER),
+    });
+
+    const blocks = [block(0), block(1)];
+    const outOfRange = gl.getActiveUniformBlockName(p, 2);
+    const outOfRangeError = gl.getError();
+    const badParameter = gl.getActiveUniformBlockParameter(p, 0, gl.LINK_STATUS);
+
+    assert.deepEqual(
+      {
+        linked: gl.getProgramParameter(p, gl.LINK_STATUS),
+        count: gl.getProgramParameter(p, gl.ACTIVE_UNIFORM_BLOCKS),
+        materialIndex: gl.getUniformBlockIndex(p, 'Material'),
+        blocks,
+        outOfRange,
+        outOfRangeError,
+        badParameter,
+        badParameterError: gl.getError(),
+      },
+      {
+        linked: true,
+        count: 2,
+        materialIndex: 1,
+        blocks: [
+          {
+            name: 'Lights',
+            binding: 3,
+            dataSize: 32,
+            uniforms: 3,
+            indices: ['Lights.color', 'Lights.direction', 'Lights.intensity'],
+            vertex: true,
+            fragment: false,
+          },
+          {
+            name: 'Material',
+            binding: 0,
+            dataSize: 80,
+            uniforms: 2,
+            indices: ['transform', 'roughness'],
+            vertex: false,
+            fragment: true,
+          },
+        ],
+        outOfRange: null,
+        outOfRangeError: gl.INVALID_VALUE,
+        badParameter: null,
+        badParameterError: gl.INVALID_ENUM,
+      },
+    );
+  } finally { gl.destroy(); }
+});
@@ -11,7 +11,7 @@ test('Backend WASM checks (opcode presence)', async (t) => {
 
     await t.test('I32Load present for integer FS inputs', () => {
       const vs = `#version 300 es
-      layout(location=0) in ivec4 a; void main(){ gl_Position = vec4(0.0); }`;
+      layout(location=0) in ivec4 a_in; flat out ivec4 a; void main(){ a = a_in; gl_Position = vec4(0.0); }`;
       const fs = `#version 300 es
       precision highp float; flat in ivec4 a; out vec4 fragColor;
       void main(){ if (a.x == -1) fragColor = vec4(0,1,0,1); else fragColor = vec4(1,0,0,1);} `;
@@ -34,7 +34,7 @@ test('Backend WASM checks (opcode presence)', async (t) => {
 
     await t.test('F32Load not used for integer-only compare', () => {
       const vs = `#version 300 es
-      layout(location=0) in ivec4 a; void main(){ gl_Position = vec4(0.0); }`;
+      layout(location=0) in ivec4 a_in; flat out ivec4 a; void main(){ a = a_in; gl_Position = vec4(0.0); }`;
       const fs = `#version 300 es
       precision highp float; flat in ivec4 a; out vec4 fragColor;
       void main(){ if (a.x == -1) fragColor = vec4(0,1,0,1); else fragColor = vec4(1,0,0,1);} `;
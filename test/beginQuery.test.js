@@ -2,7 +2,18 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
-test('beginQuery throws not implemented', async () => {
+test('beginQuery supports TIME_ELAPSED_EXT only', async () => {
   const gl = await webGL2();
-  try { assert.throws(() => gl.beginQuery(0,1), /not implemented/); } finally { gl.destroy(); }
+  try {
+    const ext = gl.getExtension('EXT_disjoint_timer_query_webgl2');
+    const query = gl.createQuery();
+    gl.beginQuery(0x8C2F /* ANY_SAMPLES_PASSED */, query);
+    const unsupported = gl.getError();
+    gl.beginQuery(ext.TIME_ELAPSED_EXT, query);
+    const current = gl.getQuery(ext.TIME_ELAPSED_EXT, gl.CURRENT_QUERY) === query;
+    gl.beginQuery(ext.TIME_ELAPSED_EXT, gl.createQuery());
+    const nested = gl.getError();
+    gl.endQuery(ext.TIME_ELAPSED_EXT);
+    assert.deepEqual({ unsupported, current, nested }, { unsupported: 0x0500, current: true, nested: 0x0502 });
+  } finally { gl.destroy(); }
 });
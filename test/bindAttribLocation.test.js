@@ -9,3 +9,81 @@ test('bindAttribLocation does not throw', async () => {
     gl.bindAttribLocation(program, 0, 'a_position');
   } finally { gl.destroy(); }
 });
+
+const fsSource = `#version 300 es
+precision highp float;
+in vec4 v_color;
+out vec4 fragColor;
+void main() { fragColor = v_color; }`;
+
+function shader(gl, type, source) {
+  const s = gl.createShader(type);
+  gl.shaderSource(s, source);
+  gl.compileShader(s);
+  return s;
+}
+
+function program(gl, vsSource, bindings) {
+  const p = gl.createProgram();
+  gl.attachShader(p, shader(gl, gl.VERTEX_SHADER, vsSource));
+  gl.attachShader(p, shader(gl, gl.FRAGMENT_SHADER, fsSource));
+  for (const [name, index] of Object.entries(bindings)) gl.bindAttribLocation(p, index, name);
+  gl.linkProgram(p);
+  return p;
+}
+
+test('bindAttribLocation takes effect at the next link and yields to layout qualifiers', async () => {
+  const gl = await webGL2();
+  try {
+    const vsSource = `#version 300 es
+in vec4 a_pos;
+in vec4 a_color;
+layout(location = 2) in float a_size;
+out vec4 v_color;
+void main() { v_color = a_color; gl_Position = a_pos * a_size; }`;
+    const p = program(gl, vsSource, { a_size: 7 });
+    const linked = gl.getAttribLocation(p, 'a_color');
+
+    gl.bindAttribLocation(p, 5, 'a_color');
+    const beforeRelink = gl.getAttribLocation(p, 'a_color');
+    gl.linkProgram(p);
+    const afterRelink = gl.getAttribLocation(p, 'a_color');
+
+    const aliased = program(gl, vsSource, { a_pos: 3, a_color: 3 });
+    const aliasedOnQualifier = program(gl, vsSource, { a_pos: 2 });
+
+    const errors = [
+      () => gl.bindAttribLocation(p, 16, 'a_pos'),
+      () => gl.bindAttribLocation(p, 0, 'gl_Vertex'),
+      () => gl.bindAttribLocation(p, 0, 'webgl_pos'),
+      () => gl.bindAttribLocation(p, 15, 'a_pos'),
+    ].map(call => {
+      call();
+      return gl.getError();
+    });
+
+    assert.deepEqual({
+      qualifierWins: gl.getAttribLocation(p, 'a_size'),
+      unchangedUntilRelink: beforeRelink === linked,
+      afterRelink,
+      aliased: [
+        gl.getProgramParameter(aliased, gl.LINK_STATUS),
+        gl.getProgramInfoLog(aliased),
+      ],
+      aliasedOnQualifier: [
+        gl.getProgramParameter(aliasedOnQualifier, gl.LINK_STATUS),
+        gl.getProgramInfoLog(aliasedOnQualifier),
+      ],
+      errors,
+    }, {
+      qualifierWins: 2,
+      unchangedUntilRelink: true,
+      afterRelink: 5,
+      aliased: [false, "Link failed: Attributes 'a_color' and 'a_pos' are both bound to location 3"],
+      aliasedOnQualifier: [false, "Link failed: Attributes 'a_size' and 'a_pos' are both bound to location 2"],
+      errors: [gl.INVALID_VALUE, gl.INVALID_OPERATION, gl.INVALID_OPERATION, gl.NO_ERROR],
+    });
+  } finally {
+    gl.destroy();
+  }
+});
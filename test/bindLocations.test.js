@@ -0,0 +1,90 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+uniform float u_scale;
+uniform vec4 u_tint;
+out vec2 v_uv;
+out vec4 v_color;
+void main() {
+  v_uv = pos * u_scale;
+  v_color = u_tint;
+  gl_Position = vec4(pos, 0.0, 1.0);
+}`;
+
+const FS = `#version 300 es
+precision highp float;
+in vec2 v_uv;
+in vec4 v_color;
+out vec4 color;
+void main() { color = v_color + vec4(0.0 * v_uv, 0.0, 0.0); }`;
+
+test('bindUniformLocation and bindVaryingLocation pin locations at link', async () => {
+  const gl = await webGL2({ size: { width: 2, height: 2 } });
+  try {
+    const link = (bind) => {
+      const program = gl.createProgram();
+      for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+        const shader = gl.createShader(type);
+        gl.shaderSource(shader, source);
+        gl.compileShader(shader);
+        gl.attachShader(program, shader);
+      }
+      bind(program);
+      gl.linkProgram(program);
+      return program;
+    };
+
+    const program = link((p) => {
+      gl.bindUniformLocation(p, 7, 'u_tint');
+      gl.bindUniformLocation(p, 0, 'u_missing');
+      gl.bindVaryingLocation(p, 5, 'v_color');
+    });
+    gl.useProgram(program);
+    const tint = gl.getUniformLocation(program, 'u_tint');
+    gl.uniform4f(tint, 0, 1, 0, 1);
+    gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    const pixel = new Uint8Array(4);
+    gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+
+    const clash = link((p) => {
+      gl.bindVaryingLocation(p, 3, 'v_uv');
+      gl.bindVaryingLocation(p, 3, 'v_color');
+    });
+
+    const errorOf = (f) => { f(); return gl.getError(); };
+    assert.deepEqual({
+      linked: gl.getProgramParameter(program, gl.LINK_STATUS),
+      tintLocation: tint._handle,
+      scaleLocation: gl.getUniformLocation(program, 'u_scale')._handle,
+      pixel: Array.from(pixel),
+      clashLinked: gl.getProgramParameter(clash, gl.LINK_STATUS),
+      clashLog: gl.getProgramInfoLog(clash),
+      errors: {
+        uniformOutOfRange: errorOf(() => gl.bindUniformLocation(program, 64, 'u_tint')),
+        varyingOutOfRange: errorOf(() => gl.bindVaryingLocation(program, 15, 'v_uv')),
+        reservedName: errorOf(() => gl.bindUniformLocation(program, 1, 'gl_Color')),
+        noProgram: errorOf(() => gl.bindVaryingLocation(null, 1, 'v_uv')),
+      },
+    }, {
+      linked: true,
+      tintLocation: 7,
+      scaleLocation: 0,
+      pixel: [0, 255, 0, 255],
+      clashLinked: false,
+      clashLog: "Link failed: Varyings 'v_color' and 'v_uv' are both bound to location 3",
+      errors: {
+        uniformOutOfRange: gl.INVALID_VALUE,
+        varyingOutOfRange: gl.INVALID_VALUE,
+        reservedName: gl.INVALID_OPERATION,
+        noProgram: gl.INVALID_VALUE,
+      },
+    });
+  } finally { gl.destroy(); }
+});
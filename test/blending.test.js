@@ -0,0 +1,81 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+const FS = `#version 300 es
+precision highp float;
+uniform vec4 u_color;
+out vec4 color;
+void main() { color = u_color; }`;
+
+test('Blending combines fragments with the framebuffer', async () => {
+  const gl = await webGL2({ size: { width: 1, height: 1 } });
+  try {
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    gl.useProgram(program);
+    const uColor = gl.getUniformLocation(program, 'u_color');
+    gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+
+    // Draw `src` over a pixel cleared to `dst` and check the result; channels
+    // within 2 of `expected` read as expected, since 8-bit quantization may
+    // round either way
+    const assertBlend = (name, dst, src, expected, setup) => {
+      gl.disable(gl.BLEND);
+      gl.clearColor(...dst);
+      gl.clear(gl.COLOR_BUFFER_BIT);
+      gl.enable(gl.BLEND);
+      gl.blendEquation(gl.FUNC_ADD);
+      setup();
+      gl.uniform4f(uColor, ...src);
+      gl.drawArrays(gl.TRIANGLES, 0, 3);
+      const pixel = new Uint8Array(4);
+      gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+      const actual = Array.from(pixel, (value, i) => (Math.abs(value - expected[i]) <= 2 ? expected[i] : value));
+      assert.deepEqual(actual, expected, name);
+    };
+    assertBlend('alpha', [0, 0, 1, 1], [1, 0, 0, 0.5], [128, 0, 128, 191],
+      () => gl.blendFunc(gl.SRC_ALPHA, gl.ONE_MINUS_SRC_ALPHA));
+    assertBlend('additive', [0.5, 0.25, 0, 1], [0.25, 0.5, 0, 1], [191, 191, 0, 255],
+      () => gl.blendFunc(gl.ONE, gl.ONE));
+    assertBlend('constant', [0, 0, 0, 0], [1, 1, 1, 1], [128, 128, 128, 128], () => {
+      gl.blendColor(0.5, 0.5, 0.5, 0.5);
+      gl.blendFunc(gl.CONSTANT_COLOR, gl.ZERO);
+    });
+    assertBlend('minMax', [0.5, 0.25, 0, 0.5], [0.25, 0.5, 0, 0.25], [64, 64, 0, 128],
+      () => gl.blendEquationSeparate(gl.MIN, gl.MAX));
+    assertBlend('reverseSubtract', [0.5, 0.25, 0, 1], [0.25, 0.5, 0, 1], [64, 0, 0, 0], () => {
+      gl.blendFunc(gl.ONE, gl.ONE);
+      gl.blendEquation(gl.FUNC_REVERSE_SUBTRACT);
+    });
+
+    gl.blendFuncSeparate(gl.SRC_ALPHA, gl.ONE_MINUS_SRC_ALPHA, gl.ONE, gl.ZERO);
+    gl.blendEquationSeparate(gl.FUNC_ADD, gl.MAX);
+    const errorOf = (f) => { f(); return gl.getError(); };
+    assert.equal(errorOf(() => gl.blendFunc(gl.SRC_ALPHA, 0x1234)), gl.INVALID_ENUM);
+    assert.equal(errorOf(() => gl.blendEquation(gl.SRC_ALPHA)), gl.INVALID_ENUM);
+    assert.equal(errorOf(() => gl.blendFunc(gl.CONSTANT_COLOR, gl.CONSTANT_ALPHA)), gl.INVALID_OPERATION);
+
+    // Rejected calls leave the state alone
+    assert.equal(gl.getParameter(gl.BLEND_SRC_RGB), gl.SRC_ALPHA);
+    assert.equal(gl.getParameter(gl.BLEND_DST_RGB), gl.ONE_MINUS_SRC_ALPHA);
+    assert.equal(gl.getParameter(gl.BLEND_SRC_ALPHA), gl.ONE);
+    assert.equal(gl.getParameter(gl.BLEND_DST_ALPHA), gl.ZERO);
+    assert.equal(gl.getParameter(gl.BLEND_EQUATION_RGB), gl.FUNC_ADD);
+    assert.equal(gl.getParameter(gl.BLEND_EQUATION_ALPHA), gl.MAX);
+    assert.deepEqual(Array.from(gl.getParameter(gl.BLEND_COLOR)), [0.5, 0.5, 0.5, 0.5]);
+  } finally { gl.destroy(); }
+});
@@ -2,7 +2,113 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+uniform float u_z;
+void main() { gl_Position = vec4(pos, u_z, 1.0); }`;
+
+const FS = `#version 300 es
+precision highp float;
+uniform vec4 u_color;
+out vec4 color;
+void main() { color = u_color; }`;
+
 test('checkFramebufferStatus returns FRAMEBUFFER_COMPLETE', async () => {
   const gl = await webGL2();
   try { assert.equal(gl.checkFramebufferStatus(gl.FRAMEBUFFER), gl.FRAMEBUFFER_COMPLETE); } finally { gl.destroy(); }
 });
+
+test('Framebuffer completeness follows the attached images', async () => {
+  const gl = await webGL2({ size: { width: 2, height: 2 } });
+  try {
+    const renderbuffer = (format, width = 2, height = 2) => {
+      const rb = gl.createRenderbuffer();
+      gl.bindRenderbuffer(gl.RENDERBUFFER, rb);
+      if (format) gl.renderbufferStorage(gl.RENDERBUFFER, format, width, height);
+      return rb;
+    };
+    const statusOf = (attachments) => {
+      gl.bindFramebuffer(gl.FRAMEBUFFER, gl.createFramebuffer());
+      for (const [attachment, rb] of attachments) {
+        gl.framebufferRenderbuffer(gl.FRAMEBUFFER, attachment, gl.RENDERBUFFER, rb);
+      }
+      return gl.checkFramebufferStatus(gl.FRAMEBUFFER);
+    };
+    const statuses = {
+      colorAndDepth: statusOf([
+        [gl.COLOR_ATTACHMENT0, renderbuffer(gl.RGBA8)],
+        [gl.DEPTH_ATTACHMENT, renderbuffer(gl.DEPTH_COMPONENT16)],
+      ]),
+      depthStencil: statusOf([[gl.DEPTH_STENCIL_ATTACHMENT, renderbuffer(gl.DEPTH24_STENCIL8)]]),
+      none: statusOf([]),
+      noStorage: statusOf([[gl.COLOR_ATTACHMENT0, renderbuffer(null)]]),
+      depthAsColor: statusOf([[gl.COLOR_ATTACHMENT0, renderbuffer(gl.DEPTH_COMPONENT16)]]),
+      colorAsDepth: statusOf([[gl.DEPTH_ATTACHMENT, renderbuffer(gl.RGBA8)]]),
+      mismatchedSizes: statusOf([
+        [gl.COLOR_ATTACHMENT0, renderbuffer(gl.RGBA8)],
+        [gl.DEPTH_ATTACHMENT, renderbuffer(gl.DEPTH_COMPONENT16, 4, 4)],
+      ]),
+      separateDepthAndStencil: statusOf([
+        [gl.DEPTH_ATTACHMENT, renderbuffer(gl.DEPTH_COMPONENT16)],
+        [gl.STENCIL_ATTACHMENT, renderbuffer(gl.STENCIL_INDEX8)],
+      ]),
+    };
+
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    gl.useProgram(program);
+    gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    const draw = (z, color) => {
+      gl.uniform1f(gl.getUniformLocation(program, 'u_z'), z);
+      gl.uniform4f(gl.getUniformLocation(program, 'u_color'), ...color);
+      gl.drawArrays(gl.TRIANGLES, 0, 3);
+      return gl.getError();
+    };
+
+    // The framebuffer left bound has separate depth and stencil images
+    const errorOf = (f) => { f(); return gl.getError(); };
+    const incomplete = {
+      draw: draw(0, [1, 1, 1, 1]),
+      clear: errorOf(() => gl.clear(gl.COLOR_BUFFER_BIT)),
+    };
+
+    // The far draw fails the depth test against the renderbuffer
+    statusOf([
+      [gl.COLOR_ATTACHMENT0, renderbuffer(gl.RGBA8)],
+      [gl.DEPTH_ATTACHMENT, renderbuffer(gl.DEPTH_COMPONENT24)],
+    ]);
+    gl.enable(gl.DEPTH_TEST);
+    gl.clear(gl.COLOR_BUFFER_BIT | gl.DEPTH_BUFFER_BIT);
+    draw(0, [0, 1, 0, 1]);
+    draw(0.5, [1, 0, 0, 1]);
+    const pixel = new Uint8Array(4);
+    gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+
+    assert.deepEqual({ statuses, incomplete, pixel: Array.from(pixel) }, {
+      statuses: {
+        colorAndDepth: gl.FRAMEBUFFER_COMPLETE,
+        depthStencil: gl.FRAMEBUFFER_COMPLETE,
+        none: gl.FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT,
+        noStorage: gl.FRAMEBUFFER_INCOMPLETE_ATTACHMENT,
+        depthAsColor: gl.FRAMEBUFFER_INCOMPLETE_ATTACHMENT,
+        colorAsDepth: gl.FRAMEBUFFER_INCOMPLETE_ATTACHMENT,
+        mismatchedSizes: gl.FRAMEBUFFER_INCOMPLETE_DIMENSIONS,
+        separateDepthAndStencil: gl.FRAMEBUFFER_UNSUPPORTED,
+      },
+      incomplete: {
+        draw: gl.INVALID_FRAMEBUFFER_OPERATION,
+        clear: gl.INVALID_FRAMEBUFFER_OPERATION,
+      },
+      pixel: [0, 255, 0, 255],
+    });
+  } finally { gl.destroy(); }
+});
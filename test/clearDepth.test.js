@@ -2,7 +2,22 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
-test('clearDepth throws not implemented', async () => {
+test('clearDepth sets a clamped DEPTH_CLEAR_VALUE', async () => {
   const gl = await webGL2();
-  try { assert.throws(() => gl.clearDepth(1.0), /not implemented/); } finally { gl.destroy(); }
+  try {
+    const initial = gl.getParameter(gl.DEPTH_CLEAR_VALUE);
+    gl.clearDepth(0.25);
+    const quarter = gl.getParameter(gl.DEPTH_CLEAR_VALUE);
+    gl.clearDepth(2);
+    const above = gl.getParameter(gl.DEPTH_CLEAR_VALUE);
+    gl.clearDepth(-1);
+    const below = gl.getParameter(gl.DEPTH_CLEAR_VALUE);
+
+    assert.deepEqual(
+      { initial, quarter, above, below, error: gl.getError() },
+      { initial: 1, quarter: 0.25, above: 1, below: 0, error: gl.NO_ERROR }
+    );
+  } finally {
+    gl.destroy();
+  }
 });
@@ -0,0 +1,65 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('recorded command buffers replay in one call', async () => {
+  const gl = await webGL2({ size: { width: 4, height: 4 } });
+  try {
+    gl.beginCommandRecording();
+    gl.enable(gl.SCISSOR_TEST);
+    gl.scissor(0, 0, 2, 2);
+    gl.clearColor(0, 1, 0, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    gl.disable(gl.SCISSOR_TEST);
+    const commands = gl.endCommandRecording();
+
+    // Nothing ran while recording
+    const before = new Uint8Array(4);
+    gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, before);
+
+    gl.playCommandBuffer(commands);
+    const inside = new Uint8Array(4);
+    const outside = new Uint8Array(4);
+    gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, inside);
+    gl.readPixels(3, 3, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, outside);
+
+    // GL errors inside a buffer surface through getError and do not stop playback
+    gl.beginCommandRecording();
+    gl.drawArrays(0x1234, 0, 3);
+    gl.clearColor(0, 0, 1, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    gl.playCommandBuffer(gl.endCommandRecording());
+    const error = gl.getError();
+    const blue = new Uint8Array(4);
+    gl.readPixels(3, 3, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, blue);
+
+    let malformed = null;
+    try {
+      gl.playCommandBuffer(new Uint32Array([6 | (2 << 16), 0, 0]));
+    } catch (e) {
+      malformed = e.message;
+    }
+
+    assert.deepEqual({
+      words: commands.length,
+      before: Array.from(before),
+      inside: Array.from(inside),
+      outside: Array.from(outside),
+      error,
+      blue: Array.from(blue),
+      malformed: /command 0/.test(malformed),
+      restored: Object.hasOwn(gl, 'clear'),
+    }, {
+      words: 14,
+      before: [0, 0, 0, 0],
+      inside: [0, 255, 0, 255],
+      outside: [0, 0, 0, 0],
+      error: gl.INVALID_ENUM,
+      blue: [0, 0, 255, 255],
+      malformed: true,
+      restored: false,
+    });
+  } finally {
+    gl.destroy();
+  }
+});
@@ -0,0 +1,67 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2, getShaderIr } from '../index.js';
+
+test('Dynamically indexed constant arrays are read from the data segment', async () => {
+  const gl = await webGL2();
+  try {
+    gl.viewport(0, 0, 4, 1);
+
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+      layout(location = 0) in vec2 a_pos;
+      void main() { gl_Position = vec4(a_pos, 0.0, 1.0); }`);
+    gl.compileShader(vs);
+
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+      precision highp float;
+      const vec4 palette[4] = vec4[4](
+        vec4(1.0, 0.0, 0.0, 1.0),
+        vec4(0.0, 1.0, 0.0, 1.0),
+        vec4(0.0, 0.0, 1.0, 1.0),
+        vec4(1.0, 1.0, 1.0, 1.0)
+      );
+      const int shifts[4] = int[4](0, 3, 2, 1);
+      uniform int u_shift;
+      out vec4 color;
+      void main() {
+        int i = int(gl_FragCoord.x);
+        color = palette[(i + shifts[u_shift]) % 4];
+      }`);
+    gl.compileShader(fs);
+
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+
+    const draw = (shift) => {
+      gl.uniform1i(gl.getUniformLocation(program, 'u_shift'), shift);
+      gl.drawArrays(gl.TRIANGLES, 0, 3);
+      const pixels = new Uint8Array(16);
+      gl.readPixels(0, 0, 4, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+      return [0, 4, 8, 12].map((offset) => Array.from(pixels.slice(offset, offset + 4)));
+    };
+
+    const red = [255, 0, 0, 255];
+    const green = [0, 255, 0, 255];
+    const blue = [0, 0, 255, 255];
+    const white = [255, 255, 255, 255];
+    const wat = getShaderIr(gl._ctxHandle, program._handle, gl.FRAGMENT_SHADER, 'wat');
+
+    assert.deepEqual(
+      { unshifted: draw(0), shifted: draw(1), hasDataSegment: wat.includes('(data ') },
+      { unshifted: [red, green, blue, white], shifted: [white, red, green, blue], hasDataSegment: true }
+    );
+  } finally {
+    gl.destroy();
+  }
+});
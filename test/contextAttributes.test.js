@@ -0,0 +1,78 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('Context attributes configure the default framebuffer', async () => {
+  const defaults = await webGL2();
+  const gl = await webGL2({ attributes: { alpha: false, depth: false, stencil: true, preserveDrawingBuffer: true } });
+  try {
+    gl.viewport(0, 0, 1, 1);
+    gl.clearColor(0.0, 1.0, 0.0, 0.5);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    const cleared = new Uint8Array(4);
+    gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, cleared);
+
+    // Without a depth buffer the depth test always passes
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+      layout(location = 0) in vec2 a_pos;
+      void main() { gl_Position = vec4(a_pos, 0.5, 1.0); }`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+      precision highp float;
+      out vec4 color;
+      void main() { color = vec4(1.0, 0.0, 0.0, 0.25); }`);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.enable(gl.DEPTH_TEST);
+    gl.depthFunc(gl.NEVER);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    const drawn = new Uint8Array(4);
+    gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, drawn);
+
+    assert.deepEqual(
+      {
+        defaults: defaults.getContextAttributes(),
+        attributes: gl.getContextAttributes(),
+        bits: [gl.ALPHA_BITS, gl.DEPTH_BITS, gl.STENCIL_BITS, gl.SAMPLES].map((pname) => gl.getParameter(pname)),
+        cleared: Array.from(cleared),
+        drawn: Array.from(drawn),
+      },
+      {
+        defaults: {
+          alpha: true,
+          depth: true,
+          stencil: false,
+          antialias: false,
+          premultipliedAlpha: true,
+          preserveDrawingBuffer: false,
+        },
+        attributes: {
+          alpha: false,
+          depth: false,
+          stencil: true,
+          antialias: false,
+          premultipliedAlpha: true,
+          preserveDrawingBuffer: true,
+        },
+        bits: [0, 0, 8, 0],
+        cleared: [0, 255, 0, 255],
+        drawn: [255, 0, 0, 255],
+      }
+    );
+  } finally {
+    gl.destroy();
+    defaults.destroy();
+  }
+});
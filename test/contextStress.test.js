@@ -0,0 +1,35 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const BATCHES = 10;
+const BATCH_SIZE = 1000;
+
+test('Creating and destroying 10k contexts leaks nothing and never reuses a handle', async () => {
+  const probe = await webGL2({ size: { width: 1, height: 1 } });
+  const ex = probe._instance.exports;
+  probe.destroy();
+  const baseline = { contexts: ex.wasm_context_count(), allocations: ex.wasm_allocation_count() };
+
+  const handles = new Set();
+  let memoryAfterFirstBatch = 0;
+  for (let batch = 0; batch < BATCHES; batch++) {
+    for (let i = 0; i < BATCH_SIZE; i++) {
+      const gl = await webGL2({ size: { width: 1, height: 1 } });
+      handles.add(gl._ctxHandle);
+      const tex = gl.createTexture();
+      gl.bindTexture(gl.TEXTURE_2D, tex);
+      gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA8, 1, 1, 0, gl.RGBA, gl.UNSIGNED_BYTE, new Uint8Array(4));
+      // Staging memory the context never frees goes away with it
+      ex.wasm_ctx_alloc(gl._ctxHandle, 256);
+      gl.destroy();
+    }
+    if (batch === 0) memoryAfterFirstBatch = ex.memory.buffer.byteLength;
+  }
+
+  // Past 4096 contexts a slot's generations run out and it is retired
+  assert.equal(handles.size, BATCHES * BATCH_SIZE);
+  assert.equal(ex.wasm_context_count(), baseline.contexts);
+  assert.equal(ex.wasm_allocation_count(), baseline.allocations);
+  assert.equal(ex.memory.buffer.byteLength, memoryAfterFirstBatch, 'memory grew after the first batch');
+});
@@ -72,7 +72,7 @@ test('coverage module is available with coverage feature', async () => {
       wasm_register_shader: () => { return 0; },
       wasm_release_shader_index: () => {},
       wasm_sync_turbo_globals: () => {},
-      dispatch_uncaptured_error: () => {},
+      dispatch_device_events: () => {},
       ACTIVE_ATTR_PTR: new WebAssembly.Global({ value: 'i32', mutable: true }, 0),
       ACTIVE_UNIFORM_PTR: new WebAssembly.Global({ value: 'i32', mutable: true }, 0),
       ACTIVE_VARYING_PTR: new WebAssembly.Global({ value: 'i32', mutable: true }, 0),
@@ -2,7 +2,14 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
-test('createQuery throws not implemented', async () => {
+test('createQuery returns a query that is not a query until started', async () => {
   const gl = await webGL2();
-  try { assert.throws(() => gl.createQuery(), /not implemented/); } finally { gl.destroy(); }
+  try {
+    const ext = gl.getExtension('EXT_disjoint_timer_query_webgl2');
+    const query = gl.createQuery();
+    const beforeBegin = gl.isQuery(query);
+    gl.beginQuery(ext.TIME_ELAPSED_EXT, query);
+    gl.endQuery(ext.TIME_ELAPSED_EXT);
+    assert.deepEqual({ beforeBegin, afterBegin: gl.isQuery(query) }, { beforeBegin: false, afterBegin: true });
+  } finally { gl.destroy(); }
 });
@@ -0,0 +1,48 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('getProgramDebugStub honors source, breakpoint and watch options', async () => {
+  const gl = await webGL2({ debug: 'shaders' });
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, '#version 300 es\nin vec4 pos;\nvoid main() {\n  gl_Position = pos;\n}');
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, '#version 300 es\nprecision highp float;\nout vec4 color;\nvoid main() {\n  color = vec4(1.0);\n}');
+    gl.compileShader(fs);
+    const prog = gl.createProgram();
+    gl.attachShader(prog, vs);
+    gl.attachShader(prog, fs);
+    gl.linkProgram(prog);
+
+    const sourceMap = (stub) => {
+      const b64 = stub.slice(stub.indexOf('base64,') + 7);
+      return JSON.parse(Buffer.from(b64, 'base64').toString());
+    };
+    const plain = gl.getProgramDebugStub(prog, gl.VERTEX_SHADER);
+    const custom = gl.getProgramDebugStub(prog, gl.VERTEX_SHADER, {
+      embedSource: false,
+      breakpoints: [4],
+      watch: ['pos'],
+    });
+
+    assert.deepEqual({
+      plainEmbedsSource: 'sourcesContent' in sourceMap(plain),
+      customEmbedsSource: 'sourcesContent' in sourceMap(custom),
+      plainBreakpoints: plain.includes('debugger'),
+      breakpointLine: custom.includes('this?.breakpoints?.has(4)'),
+      watchLine: custom.includes('this?.watch?.("pos", 4)'),
+      linesMatch: custom.split('\n').length === plain.split('\n').length,
+    }, {
+      plainEmbedsSource: true,
+      customEmbedsSource: false,
+      plainBreakpoints: false,
+      breakpointLine: true,
+      watchLine: true,
+      linesMatch: true,
+    });
+  } finally {
+    gl.destroy();
+  }
+});
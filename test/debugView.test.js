@@ -0,0 +1,81 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const vsSource = `#version 300 es
+layout(location = 0) in vec3 position;
+void main() {
+  gl_Position = vec4(position, 1.0);
+}`;
+
+const fsSource = `#version 300 es
+precision highp float;
+out vec4 fragColor;
+void main() {
+  fragColor = vec4(0.0, 0.0, 1.0, 1.0);
+}`;
+
+function pixel(gl, x, y) {
+  const out = new Uint8Array(4);
+  gl.readPixels(x, y, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, out);
+  return Array.from(out);
+}
+
+test('debug views replace draw output', async () => {
+  const gl = await webGL2({ size: { width: 64, height: 64 } });
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, vsSource);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, fsSource);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    // A large triangle at z = 0 (depth 0.5) covering the lower-left half
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 0, 1, -1, 0, -1, 1, 0]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 3, gl.FLOAT, false, 0, 0);
+    gl.viewport(0, 0, 64, 64);
+    gl.clearColor(0, 0, 0, 1);
+
+    const draw = (mode, times = 1) => {
+      gl.clear(gl.COLOR_BUFFER_BIT);
+      gl.setDebugView(mode);
+      for (let i = 0; i < times; i++) gl.drawArrays(gl.TRIANGLES, 0, 3);
+      return { inside: pixel(gl, 16, 16), edge: pixel(gl, 0, 32) };
+    };
+
+    const none = draw('none');
+    const depth = draw('depth');
+    const overdraw = draw('overdraw', 2);
+    const wireframe = draw('wireframe');
+    gl.setDebugView('none');
+
+    let unknown = null;
+    try {
+      gl.setDebugView('normals');
+    } catch (e) {
+      unknown = e.message;
+    }
+
+    assert.deepEqual(
+      { none: none.inside, depth: depth.inside, overdraw: overdraw.inside, wireframe, unknown },
+      {
+        none: [0, 0, 255, 255],
+        depth: [128, 128, 128, 255],
+        overdraw: [64, 0, 0, 255],
+        wireframe: { inside: [0, 0, 0, 255], edge: [255, 255, 255, 255] },
+        unknown: 'unknown debug view: normals',
+      },
+    );
+  } finally {
+    gl.destroy();
+  }
+});
@@ -33,21 +33,33 @@ test('getShaderGlsl returns GLSL for compiled vertex shader', async () => {
 precision highp float;
 precision highp int;
 
+// Exports:
+//   "func_21" -> func_21
+//   "main" -> main
+
 // WASM linear memory mapped to buffer
 layout(std430, binding = 0) buffer MemoryBuffer {
     int memory[];
 };
 
+uniform int g0; // import env.ACTIVE_ATTR_PTR
+uniform int g1; // import env.ACTIVE_UNIFORM_PTR
+uniform int g2; // import env.ACTIVE_VARYING_PTR
+uniform int g3; // import env.ACTIVE_PRIVATE_PTR
+uniform int g4; // import env.ACTIVE_TEXTURE_PTR
+uniform int g5; // import env.ACTIVE_FRAME_SP
+
 void func_21() {
     int v0;
     float v1;
     int v2;
     
+    // line 2: void main() { gl_Position = vec4(0); }
     memory[(g2) >> 2] = floatBitsToInt(0.0);
     memory[((g2) + 4) >> 2] = floatBitsToInt(0.0);
     memory[((g2) + 8) >> 2] = floatBitsToInt(0.0);
     memory[((g2) + 12) >> 2] = floatBitsToInt(0.0);
-    return func0();
+    return;
 }
 void main(int p0, int p1, int p2) {
     int v3;
@@ -98,21 +110,33 @@ test('getShaderGlsl returns GLSL for compiled fragment shader', async () => {
 precision highp float;
 precision highp int;
 
+// Exports:
+//   "func_21" -> func_21
+//   "main" -> main
+
 // WASM linear memory mapped to buffer
 layout(std430, binding = 0) buffer MemoryBuffer {
     int memory[];
 };
 
+uniform int g0; // import env.ACTIVE_ATTR_PTR
+uniform int g1; // import env.ACTIVE_UNIFORM_PTR
+uniform int g2; // import env.ACTIVE_VARYING_PTR
+uniform int g3; // import env.ACTIVE_PRIVATE_PTR
+uniform int g4; // import env.ACTIVE_TEXTURE_PTR
+uniform int g5; // import env.ACTIVE_FRAME_SP
+
 void func_21() {
     int v0;
     float v1;
     int v2;
     
+    // line 2: precision mediump float; out vec4 color; void main() { color = vec4(1); }
     memory[(g3) >> 2] = floatBitsToInt(1.0);
     memory[((g3) + 4) >> 2] = floatBitsToInt(1.0);
     memory[((g3) + 8) >> 2] = floatBitsToInt(1.0);
     memory[((g3) + 12) >> 2] = floatBitsToInt(1.0);
-    return func0();
+    return;
 }
 void main(int p0, int p1) {
     int v2;
@@ -178,21 +202,33 @@ test('decompileWasmToGlsl decompiles WASM bytes directly', async () => {
 precision highp float;
 precision highp int;
 
+// Exports:
+//   "func_21" -> func_21
+//   "main" -> main
+
 // WASM linear memory mapped to buffer
 layout(std430, binding = 0) buffer MemoryBuffer {
     int memory[];
 };
 
+uniform int g0; // import env.ACTIVE_ATTR_PTR
+uniform int g1; // import env.ACTIVE_UNIFORM_PTR
+uniform int g2; // import env.ACTIVE_VARYING_PTR
+uniform int g3; // import env.ACTIVE_PRIVATE_PTR
+uniform int g4; // import env.ACTIVE_TEXTURE_PTR
+uniform int g5; // import env.ACTIVE_FRAME_SP
+
 void func_21() {
     int v0;
     float v1;
     int v2;
     
+    // line 2: void main() { gl_Position = vec4(0); }
     memory[(g2) >> 2] = floatBitsToInt(0.0);
     memory[((g2) + 4) >> 2] = floatBitsToInt(0.0);
     memory[((g2) + 8) >> 2] = floatBitsToInt(0.0);
     memory[((g2) + 12) >> 2] = floatBitsToInt(0.0);
-    return func0();
+    return;
 }
 void main(int p0, int p1, int p2) {
     int v3;
@@ -216,6 +252,31 @@ void main(int p0, int p1, int p2) {
   }
 });
 
+test('decompileWasmToGlsl applies caller-supplied symbol names', async () => {
+  const gl = await webGL2();
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, '#version 300 es\nvoid main() { gl_Position = vec4(0); }');
+    gl.compileShader(vs);
+
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, '#version 300 es\nprecision mediump float; out vec4 color; void main() { color = vec4(1); }');
+    gl.compileShader(fs);
+
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+
+    const wasmBytes = getShaderModule(gl._ctxHandle, program._handle, gl.VERTEX_SHADER);
+    const glsl = decompileWasmToGlsl(gl, wasmBytes, '# session names\nfunc 21 write_position\n');
+
+    assert.ok(glsl.includes('void write_position() {'), 'Function should use the supplied name');
+  } finally {
+    gl.destroy();
+  }
+});
+
 test('decompiled GLSL contains function definitions', async () => {
   const gl = await webGL2();
   try {
@@ -0,0 +1,100 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+function readPixel(gl) {
+  const pixel = new Uint8Array(4);
+  gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+  return Array.from(pixel);
+}
+
+test('Deleting objects that are still in use defers their destruction', async () => {
+  const gl = await webGL2({ size: { width: 1, height: 1 } });
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+      layout(location = 0) in vec2 a_pos;
+      void main() { gl_Position = vec4(a_pos, 0.0, 1.0); }`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+      precision highp float;
+      out vec4 color;
+      void main() { color = vec4(1.0, 0.0, 0.0, 1.0); }`);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    // The vertex array keeps its buffer after the buffer is deleted
+    const vao = gl.createVertexArray();
+    gl.bindVertexArray(vao);
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.bindVertexArray(null);
+    gl.deleteBuffer(buffer);
+
+    // The current program and its attached shaders outlive their deletion
+    gl.deleteShader(vs);
+    gl.deleteShader(fs);
+    gl.deleteProgram(program);
+    const deleted = {
+      program: gl.isProgram(program),
+      shader: gl.isShader(vs),
+      buffer: gl.isBuffer(buffer),
+    };
+
+    gl.bindVertexArray(vao);
+    gl.clearColor(0, 0, 0, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    const drawn = readPixel(gl);
+
+    // Framebuffers keep attachments deleted while they are not bound
+    const attachments = {};
+    for (const kind of ['texture', 'renderbuffer']) {
+      const fb = gl.createFramebuffer();
+      gl.bindFramebuffer(gl.FRAMEBUFFER, fb);
+      let image;
+      if (kind === 'texture') {
+        image = gl.createTexture();
+        gl.bindTexture(gl.TEXTURE_2D, image);
+        gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA8, 1, 1, 0, gl.RGBA, gl.UNSIGNED_BYTE, new Uint8Array([0, 0, 255, 255]));
+        gl.framebufferTexture2D(gl.FRAMEBUFFER, gl.COLOR_ATTACHMENT0, gl.TEXTURE_2D, image, 0);
+      } else {
+        image = gl.createRenderbuffer();
+        gl.bindRenderbuffer(gl.RENDERBUFFER, image);
+        gl.renderbufferStorage(gl.RENDERBUFFER, gl.RGBA8, 1, 1);
+        gl.framebufferRenderbuffer(gl.FRAMEBUFFER, gl.COLOR_ATTACHMENT0, gl.RENDERBUFFER, image);
+      }
+      gl.bindFramebuffer(gl.FRAMEBUFFER, null);
+      if (kind === 'texture') {
+        gl.deleteTexture(image);
+      } else {
+        gl.deleteRenderbuffer(image);
+      }
+
+      gl.bindFramebuffer(gl.FRAMEBUFFER, fb);
+      gl.drawArrays(gl.TRIANGLES, 0, 3);
+      attachments[kind] = readPixel(gl);
+      gl.bindFramebuffer(gl.FRAMEBUFFER, null);
+      gl.deleteFramebuffer(fb);
+    }
+
+    assert.deepEqual(
+      { deleted, drawn, attachments },
+      {
+        deleted: { program: false, shader: false, buffer: false },
+        drawn: [255, 0, 0, 255],
+        attachments: { texture: [255, 0, 0, 255], renderbuffer: [255, 0, 0, 255] },
+      }
+    );
+  } finally {
+    gl.destroy();
+  }
+});
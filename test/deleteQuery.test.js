@@ -2,7 +2,16 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
-test('deleteQuery throws not implemented', async () => {
+test('deleteQuery ends an active query and deletes it', async () => {
   const gl = await webGL2();
-  try { assert.throws(() => gl.deleteQuery(1), /not implemented/); } finally { gl.destroy(); }
+  try {
+    const ext = gl.getExtension('EXT_disjoint_timer_query_webgl2');
+    const query = gl.createQuery();
+    gl.beginQuery(ext.TIME_ELAPSED_EXT, query);
+    gl.deleteQuery(query);
+    assert.deepEqual(
+      { isQuery: gl.isQuery(query), current: gl.getQuery(ext.TIME_ELAPSED_EXT, gl.CURRENT_QUERY), error: gl.getError() },
+      { isQuery: false, current: null, error: 0 },
+    );
+  } finally { gl.destroy(); }
 });
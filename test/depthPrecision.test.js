@@ -0,0 +1,106 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const vsSource = `#version 300 es
+layout(location = 0) in float depth;
+void main() {
+    gl_Position = vec4(0.0, 0.0, depth, 1.0);
+    gl_PointSize = 4.0;
+}`;
+
+const fsSource = `#version 300 es
+precision highp float;
+uniform vec4 u_color;
+out vec4 fragColor;
+void main() {
+    fragColor = u_color;
+}`;
+
+/**
+ * Clear depth to `clearDepth`, then draw a 4x4 point at each NDC depth in
+ * `draws` (red, green, blue, ...) under `func`, and return the color left
+ * in the middle of the 4x4 context.
+ */
+async function drawPoints(options, clearDepth, func, draws) {
+  const gl = await webGL2({ size: { width: 4, height: 4 }, ...options });
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, vsSource);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, fsSource);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+    const colorLoc = gl.getUniformLocation(program, 'u_color');
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array(draws), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 1, gl.FLOAT, false, 0, 0);
+
+    gl.enable(gl.DEPTH_TEST);
+    gl.depthFunc(func);
+    gl.clearColor(0, 0, 0, 1);
+    gl.clearDepth(clearDepth);
+    gl.clear(gl.COLOR_BUFFER_BIT | gl.DEPTH_BUFFER_BIT);
+
+    const colors = [[1, 0, 0, 1], [0, 1, 0, 1], [0, 0, 1, 1]];
+    draws.forEach((_, i) => {
+      gl.uniform4fv(colorLoc, colors[i]);
+      gl.drawArrays(gl.POINTS, i, 1);
+    });
+
+    const pixel = new Uint8Array(4);
+    gl.readPixels(1, 1, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+    return { pixel: Array.from(pixel), depthBits: gl.getParameter(gl.DEPTH_BITS) };
+  } finally {
+    gl.destroy();
+  }
+}
+
+test('reversed-Z and depth buffer precision', async () => {
+  // Reversed-Z: clear to 0 and keep the fragment with the greater depth
+  const reversed = await drawPoints({}, 0, 0x0204 /* GREATER */, [-0.5, 0.5, 0.0]);
+  // The usual depth function against a 0 clear rejects everything
+  const reversedWithLess = await drawPoints({}, 0, 0x0201 /* LESS */, [-0.5, 0.5]);
+
+  // Window depths 0.25000003 and 0.25 are distinct floats but round to the
+  // same 24-bit value, so the nearer point only wins in a float buffer
+  const close = [-0.4999999403953552, -0.4999999701976776];
+  const float32 = await drawPoints({}, 1, 0x0201 /* LESS */, close);
+  const fixed24 = await drawPoints({ depthPrecision: 'fixed24' }, 1, 0x0201 /* LESS */, close);
+
+  let unknownPrecision = null;
+  try {
+    await webGL2({ depthPrecision: 'fixed16' });
+  } catch (e) {
+    unknownPrecision = /Unknown depthPrecision/.test(e.message);
+  }
+
+  assert.deepEqual(
+    {
+      reversed: reversed.pixel,
+      reversedWithLess: reversedWithLess.pixel,
+      float32: float32.pixel,
+      fixed24: fixed24.pixel,
+      float32Bits: float32.depthBits,
+      fixed24Bits: fixed24.depthBits,
+      unknownPrecision,
+    },
+    {
+      reversed: [0, 255, 0, 255],
+      reversedWithLess: [0, 0, 0, 255],
+      float32: [0, 255, 0, 255],
+      fixed24: [255, 0, 0, 255],
+      float32Bits: 24,
+      fixed24Bits: 24,
+      unknownPrecision: true,
+    }
+  );
+});
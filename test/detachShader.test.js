@@ -2,7 +2,23 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
-test('detachShader throws not implemented', async () => {
+test('detachShader removes the shader from the program', async () => {
   const gl = await webGL2();
-  try { assert.throws(() => gl.detachShader(1,1), /not implemented/); } finally { gl.destroy(); }
+  try {
+    const program = gl.createProgram();
+    const shader = gl.createShader(gl.VERTEX_SHADER);
+    gl.attachShader(program, shader);
+    const attached = gl.getProgramParameter(program, gl.ATTACHED_SHADERS);
+    gl.detachShader(program, shader);
+    const detached = gl.getProgramParameter(program, gl.ATTACHED_SHADERS);
+    let error = null;
+    try {
+      gl.detachShader(program, shader);
+    } catch (e) {
+      error = gl.getError();
+    }
+    assert.deepEqual({ attached, detached, error }, { attached: 1, detached: 0, error: gl.INVALID_OPERATION });
+  } finally {
+    gl.destroy();
+  }
 });
@@ -0,0 +1,69 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('draws to RGB565 are quantized, and dithered while DITHER is enabled', async () => {
+  const gl = await webGL2({ size: { width: 4, height: 4 } });
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+    layout(location = 0) in vec2 position;
+    void main() {
+      gl_Position = vec4(position, 0.0, 1.0);
+    }`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+    precision highp float;
+    out vec4 color;
+    void main() {
+      color = vec4(0.5, 0.5, 0.5, 1.0);
+    }`);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+
+    const fb = gl.createFramebuffer();
+    gl.bindFramebuffer(gl.FRAMEBUFFER, fb);
+    const rb = gl.createRenderbuffer();
+    gl.bindRenderbuffer(gl.RENDERBUFFER, rb);
+    gl.renderbufferStorage(gl.RENDERBUFFER, gl.RGB565, 4, 4);
+    gl.framebufferRenderbuffer(gl.FRAMEBUFFER, gl.COLOR_ATTACHMENT0, gl.RENDERBUFFER, rb);
+    gl.viewport(0, 0, 4, 4);
+
+    // Red values of the 4x4 block and how often each occurs
+    const draw = () => {
+      gl.clearColor(0, 0, 0, 1);
+      gl.clear(gl.COLOR_BUFFER_BIT);
+      gl.drawArrays(gl.TRIANGLES, 0, 3);
+      const out = new Uint8Array(4 * 16);
+      gl.readPixels(0, 0, 4, 4, gl.RGBA, gl.UNSIGNED_BYTE, out);
+      const counts = {};
+      for (let i = 0; i < out.length; i += 4) counts[out[i]] = (counts[out[i]] || 0) + 1;
+      return counts;
+    };
+
+    const enabledByDefault = gl.isEnabled(gl.DITHER);
+    const dithered = draw();
+    gl.disable(gl.DITHER);
+    const rounded = draw();
+
+    // 0.5 is 15.5 of 31 red steps: rounding gives 16 (132 after expansion to
+    // 8 bits); dithering alternates between 15 (123) and 16
+    assert.deepEqual(
+      { enabledByDefault, dithered, rounded, disabled: gl.isEnabled(gl.DITHER) },
+      { enabledByDefault: true, dithered: { 123: 8, 132: 8 }, rounded: { 132: 16 }, disabled: false },
+    );
+  } finally {
+    gl.destroy();
+  }
+});
@@ -0,0 +1,139 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+const FS = `#version 300 es
+precision highp float;
+out vec4 color;
+void main() { color = vec4(1.0, 0.0, 0.0, 1.0); }`;
+
+test('drawElements decodes every index type and shades each index once', async () => {
+  // Each vertex shader invocation costs 1ns, so timer queries count them
+  const gl = await webGL2({ size: { width: 4, height: 4 }, gpuCostModel: { vertexNs: 1 } });
+  try {
+    const ext = gl.getExtension('EXT_disjoint_timer_query_webgl2');
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    const quad = gl.createVertexArray();
+    gl.bindVertexArray(quad);
+    gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 1, -1, -1, 1, 1, 1]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.bindBuffer(gl.ELEMENT_ARRAY_BUFFER, gl.createBuffer());
+
+    const indices = [0, 1, 2, 2, 1, 3];
+    const draw = (Indices, type) => {
+      gl.clearColor(0, 0, 0, 0);
+      gl.clear(gl.COLOR_BUFFER_BIT);
+      gl.bufferData(gl.ELEMENT_ARRAY_BUFFER, new Indices(indices), gl.STATIC_DRAW);
+      const query = gl.createQuery();
+      gl.beginQuery(ext.TIME_ELAPSED_EXT, query);
+      gl.drawElements(gl.TRIANGLES, indices.length, type, 0);
+      gl.endQuery(ext.TIME_ELAPSED_EXT);
+      const pixels = new Uint8Array(4 * 4 * 4);
+      gl.readPixels(0, 0, 4, 4, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+      return {
+        vertexInvocations: gl.getQueryParameter(query, gl.QUERY_RESULT),
+        covered: pixels.every((value, i) => value === [255, 0, 0, 255][i % 4]),
+        error: gl.getError(),
+      };
+    };
+    const decoded = {
+      u8: draw(Uint8Array, gl.UNSIGNED_BYTE),
+      u16: draw(Uint16Array, gl.UNSIGNED_SHORT),
+      u32: draw(Uint32Array, gl.UNSIGNED_INT),
+    };
+
+    const errorOf = (f) => { f(); return gl.getError(); };
+    const errors = {
+      badType: errorOf(() => gl.drawElements(gl.TRIANGLES, 6, gl.FLOAT, 0)),
+      negativeCount: errorOf(() => gl.drawElements(gl.TRIANGLES, -3, gl.UNSIGNED_INT, 0)),
+      misaligned: errorOf(() => gl.drawElements(gl.TRIANGLES, 3, gl.UNSIGNED_INT, 2)),
+      pastEnd: errorOf(() => gl.drawElements(gl.TRIANGLES, 6, gl.UNSIGNED_INT, 4)),
+      noElementBuffer: errorOf(() => {
+        gl.bindVertexArray(gl.createVertexArray());
+        gl.drawElements(gl.TRIANGLES, 3, gl.UNSIGNED_INT, 0);
+      }),
+    };
+
+    assert.deepEqual({ decoded, errors }, {
+      decoded: {
+        u8: { vertexInvocations: 4, covered: true, error: gl.NO_ERROR },
+        u16: { vertexInvocations: 4, covered: true, error: gl.NO_ERROR },
+        u32: { vertexInvocations: 4, covered: true, error: gl.NO_ERROR },
+      },
+      errors: {
+        badType: gl.INVALID_ENUM,
+        negativeCount: gl.INVALID_VALUE,
+        misaligned: gl.INVALID_OPERATION,
+        pastEnd: gl.INVALID_OPERATION,
+        noElementBuffer: gl.INVALID_OPERATION,
+      },
+    });
+  } finally { gl.destroy(); }
+});
+
+test('drawElements restarts strips at the largest index of the index type', async () => {
+  const gl = await webGL2({ size: { width: 4, height: 4 }, gpuCostModel: { vertexNs: 1 } });
+  try {
+    const ext = gl.getExtension('EXT_disjoint_timer_query_webgl2');
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    // Two quads, one over the left column of pixels and one over the right
+    gl.bindVertexArray(gl.createVertexArray());
+    gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([
+      -1, -1, -0.5, -1, -1, 1, -0.5, 1,
+      0.5, -1, 1, -1, 0.5, 1, 1, 1,
+    ]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.bindBuffer(gl.ELEMENT_ARRAY_BUFFER, gl.createBuffer());
+
+    for (const [Indices, type, restart] of [
+      [Uint8Array, gl.UNSIGNED_BYTE, 0xFF],
+      [Uint16Array, gl.UNSIGNED_SHORT, 0xFFFF],
+      [Uint32Array, gl.UNSIGNED_INT, 0xFFFFFFFF],
+    ]) {
+      gl.clearColor(0, 0, 0, 0);
+      gl.clear(gl.COLOR_BUFFER_BIT);
+      gl.bufferData(gl.ELEMENT_ARRAY_BUFFER, new Indices([0, 1, 2, 3, restart, 4, 5, 6, 7]), gl.STATIC_DRAW);
+      const query = gl.createQuery();
+      gl.beginQuery(ext.TIME_ELAPSED_EXT, query);
+      gl.drawElements(gl.TRIANGLE_STRIP, 9, type, 0);
+      gl.endQuery(ext.TIME_ELAPSED_EXT);
+      assert.equal(gl.getError(), gl.NO_ERROR);
+      // The restart index is not a vertex and isn't shaded
+      assert.equal(gl.getQueryParameter(query, gl.QUERY_RESULT), 8);
+
+      // No triangle bridges the two quads
+      const pixels = new Uint8Array(4 * 4 * 4);
+      gl.readPixels(0, 0, 4, 4, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+      for (let y = 0; y < 4; y++) {
+        const row = Array.from(pixels.subarray(y * 16, y * 16 + 16));
+        assert.deepEqual(row, [255, 0, 0, 255, 0, 0, 0, 0, 0, 0, 0, 0, 255, 0, 0, 255], `row ${y} of ${Indices.name}`);
+      }
+    }
+  } finally { gl.destroy(); }
+});
@@ -0,0 +1,66 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('drawPacket binds, uploads uniforms and draws in one call', async () => {
+  const gl = await webGL2({ size: { width: 8, height: 8 } });
+  try {
+    const buf = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buf);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, '#version 300 es\nin vec2 pos;\nvoid main() { gl_Position = vec4(pos, 0.0, 1.0); }');
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, '#version 300 es\nprecision highp float;\nuniform vec4 u_color;\nout vec4 color;\nvoid main() { color = u_color; }');
+    gl.compileShader(fs);
+    const prog = gl.createProgram();
+    gl.attachShader(prog, vs);
+    gl.attachShader(prog, fs);
+    gl.linkProgram(prog);
+    const pos = gl.getAttribLocation(prog, 'pos');
+    const color = gl.getUniformLocation(prog, 'u_color');
+
+    const pack = (program, rgba) => {
+      const bits = new Uint32Array(new Float32Array(rgba).buffer);
+      return new Uint32Array([
+        program, 1, 1, gl.TRIANGLES, 0, 3, 0, 1,
+        pos, buf._handle, 2, gl.FLOAT, 0, 0, 0,
+        color._handle, 4, ...bits,
+      ]);
+    };
+    const pixel = () => {
+      const out = new Uint8Array(4);
+      gl.readPixels(4, 4, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, out);
+      return Array.from(out);
+    };
+
+    gl.drawPacket(pack(prog._handle, [1, 0, 0, 1]));
+    const red = pixel();
+    // Program 0 keeps the current program
+    gl.drawPacket(pack(0, [0, 0, 1, 1]));
+    const blue = pixel();
+
+    let malformed = null;
+    try {
+      gl.drawPacket(new Uint32Array([0, 1, 0]));
+    } catch (e) {
+      malformed = e.message;
+    }
+
+    assert.deepEqual({
+      red,
+      blue,
+      malformed: /truncated header/.test(malformed),
+      error: gl.getError(),
+    }, {
+      red: [255, 0, 0, 255],
+      blue: [0, 0, 255, 255],
+      malformed: true,
+      error: gl.NO_ERROR,
+    });
+  } finally {
+    gl.destroy();
+  }
+});
@@ -2,7 +2,18 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
-test('endQuery throws not implemented', async () => {
+test('endQuery makes the result available and fails without an active query', async () => {
   const gl = await webGL2();
-  try { assert.throws(() => gl.endQuery(0), /not implemented/); } finally { gl.destroy(); }
+  try {
+    const ext = gl.getExtension('EXT_disjoint_timer_query_webgl2');
+    gl.endQuery(ext.TIME_ELAPSED_EXT);
+    const withoutActive = gl.getError();
+    const query = gl.createQuery();
+    gl.beginQuery(ext.TIME_ELAPSED_EXT, query);
+    gl.endQuery(ext.TIME_ELAPSED_EXT);
+    assert.deepEqual(
+      { withoutActive, available: gl.getQueryParameter(query, gl.QUERY_RESULT_AVAILABLE) },
+      { withoutActive: 0x0502, available: true },
+    );
+  } finally { gl.destroy(); }
 });
@@ -0,0 +1,63 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { createWebGL2Cube, createWebGPUCube } from '../../examples/spinning_cube.js';
+
+const WIDTH = 64;
+const HEIGHT = 48;
+const CHECKER_COLORS = ['255,215,0,255', '100,149,237,255'];
+
+/** Summarize a frame: which texels were written and where the cube landed. */
+function summarize(image) {
+  const pixel = (x, y) => Array.from(image.subarray((y * WIDTH + x) * 4, (y * WIDTH + x) * 4 + 4)).join();
+  let covered = 0;
+  let checkerOnly = true;
+  for (let y = 0; y < HEIGHT; y++) {
+    for (let x = 0; x < WIDTH; x++) {
+      const color = pixel(x, y);
+      if (color === '0,0,0,0') continue;
+      covered++;
+      checkerOnly &&= CHECKER_COLORS.includes(color);
+    }
+  }
+  return {
+    corner: pixel(0, 0),
+    centerIsChecker: CHECKER_COLORS.includes(pixel(WIDTH / 2, HEIGHT / 2)),
+    checkerOnly,
+    covered,
+  };
+}
+
+for (const [name, create] of [['WebGL2', createWebGL2Cube], ['WebGPU', createWebGPUCube]]) {
+  test(`spinning cube example renders textured frames on the ${name} path`, async () => {
+    const cube = await create({ width: WIDTH, height: HEIGHT });
+    const first = await cube.render(0);
+    const later = await cube.render(1250);
+    cube.destroy();
+
+    const summary = summarize(first);
+    assert.deepEqual(
+      {
+        corner: summary.corner,
+        centerIsChecker: summary.centerIsChecker,
+        checkerOnly: summary.checkerOnly,
+        plausibleCoverage: summary.covered > (WIDTH * HEIGHT) / 8 && summary.covered < (WIDTH * HEIGHT) / 2,
+        animates: first.some((value, i) => value !== later[i]),
+      },
+      { corner: '0,0,0,0', centerIsChecker: true, checkerOnly: true, plausibleCoverage: true, animates: true }
+    );
+  });
+}
+
+test('spinning cube example covers the same pixels on both paths', async () => {
+  const coverage = [];
+  for (const create of [createWebGL2Cube, createWebGPUCube]) {
+    const cube = await create({ width: WIDTH, height: HEIGHT });
+    const image = await cube.render(600);
+    cube.destroy();
+    coverage.push(Array.from({ length: WIDTH * HEIGHT }, (_, i) => image[i * 4 + 3] !== 0));
+  }
+  const mismatched = coverage[0].filter((covered, i) => covered !== coverage[1][i]).length;
+
+  // Allow for rasterization rule differences along the silhouette
+  assert.deepEqual({ closeMatch: mismatched <= WIDTH + HEIGHT }, { closeMatch: true });
+});
@@ -0,0 +1,120 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const vsSource = `#version 300 es
+layout(location = 0) in vec2 position;
+void main() {
+    gl_Position = vec4(position, 0.0, 1.0);
+}`;
+
+const fsSource = `#version 300 es
+precision highp float;
+out vec4 fragColor;
+void main() {
+    fragColor = vec4(0.0, 1.0, 0.0, 1.0);
+}`;
+
+function createProgram(gl) {
+  const vs = gl.createShader(gl.VERTEX_SHADER);
+  gl.shaderSource(vs, vsSource);
+  gl.compileShader(vs);
+  const fs = gl.createShader(gl.FRAGMENT_SHADER);
+  gl.shaderSource(fs, fsSource);
+  gl.compileShader(fs);
+  const program = gl.createProgram();
+  gl.attachShader(program, vs);
+  gl.attachShader(program, fs);
+  gl.linkProgram(program);
+  return program;
+}
+
+test('explainLastDraw says why a draw left no trace', async () => {
+  const gl = await webGL2({ size: { width: 8, height: 8 } });
+  try {
+    const beforeDraw = gl.explainLastDraw();
+
+    // No program in use
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    const noProgram = gl.explainLastDraw();
+
+    gl.useProgram(createProgram(gl));
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    // A counter-clockwise triangle covering the lower-left half
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 1, -1, -1, 1]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    const drawn = gl.explainLastDraw();
+
+    // Reading past the end of the buffer
+    gl.drawArrays(gl.TRIANGLES, 0, 6);
+    const shortBuffer = gl.explainLastDraw();
+
+    // Culling the only triangle
+    gl.enable(gl.CULL_FACE);
+    gl.cullFace(gl.FRONT);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    const culled = gl.explainLastDraw();
+    gl.disable(gl.CULL_FACE);
+
+    // A 0 depth clear under LESS rejects every fragment
+    gl.enable(gl.DEPTH_TEST);
+    gl.clearDepth(0);
+    gl.clear(gl.DEPTH_BUFFER_BIT);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    const depthFailed = gl.explainLastDraw();
+    gl.disable(gl.DEPTH_TEST);
+
+    gl.enable(gl.BLEND);
+    gl.blendFunc(gl.ZERO, gl.ONE);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    const blended = gl.explainLastDraw();
+
+    assert.deepEqual({
+      beforeDraw,
+      noProgram: noProgram.error !== null && noProgram.issues[0].startsWith('the draw was rejected'),
+      drawn: {
+        call: drawn.call,
+        mode: drawn.mode,
+        attributes: drawn.attributes.map(a => [a.name, a.enabled, a.bufferBytes, a.neededBytes]),
+        primitives: drawn.primitives,
+        shaded: drawn.shadedFragments > 0,
+        issues: drawn.issues,
+      },
+      shortBuffer: shortBuffer.issues.includes("attribute 'position' (location 0) reads 48 bytes but its buffer holds 24"),
+      culled: {
+        culled: culled.primitives.culled,
+        issues: culled.issues,
+      },
+      depthFailed: {
+        failed: depthFailed.depthTest.failed > 0,
+        shaded: depthFailed.shadedFragments,
+        issue: depthFailed.issues.some(issue => issue.includes('failed the depth test (depthFunc LESS, clearDepth 0)')),
+      },
+      blended: blended.issues,
+    }, {
+      beforeDraw: null,
+      noProgram: true,
+      drawn: {
+        call: 'drawArrays',
+        mode: 'TRIANGLES',
+        attributes: [['position', true, 24, 24]],
+        primitives: { assembled: 1, clipped: 0, culled: 0, empty: 0 },
+        shaded: true,
+        issues: [],
+      },
+      shortBuffer: true,
+      culled: {
+        culled: 1,
+        issues: ['every primitive in view was culled (cullFace FRONT, frontFace CCW)'],
+      },
+      depthFailed: { failed: true, shaded: 0, issue: true },
+      blended: ['blending with ZERO, ONE keeps the destination color'],
+    });
+  } finally {
+    gl.destroy();
+  }
+});
@@ -0,0 +1,38 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('framebufferHash tracks framebuffer contents and size', async () => {
+  const gl = await webGL2({ size: { width: 4, height: 4 } });
+  const other = await webGL2({ size: { width: 4, height: 4 } });
+  const small = await webGL2({ size: { width: 2, height: 2 } });
+  try {
+    for (const ctx of [gl, other, small]) {
+      ctx.clearColor(0, 0.5, 1, 1);
+      ctx.clear(ctx.COLOR_BUFFER_BIT);
+    }
+    const blue = gl.framebufferHash();
+    const sameContents = other.framebufferHash();
+    const smaller = small.framebufferHash();
+
+    gl.clearColor(1, 0, 0, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    const red = gl.framebufferHash();
+
+    assert.deepEqual({
+      format: /^[0-9a-f]{16}$/.test(blue),
+      sameContentsMatch: sameContents === blue,
+      sizeMatters: smaller !== blue,
+      contentsMatter: red !== blue,
+    }, {
+      format: true,
+      sameContentsMatch: true,
+      sizeMatters: true,
+      contentsMatter: true,
+    });
+  } finally {
+    gl.destroy();
+    other.destroy();
+    small.destroy();
+  }
+});
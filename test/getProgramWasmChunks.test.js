@@ -0,0 +1,71 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const vsSource = `#version 300 es
+layout(location = 0) in vec2 position;
+void main() {
+  gl_Position = vec4(position, 0.0, 1.0);
+}`;
+
+const fsSource = `#version 300 es
+precision highp float;
+out vec4 color;
+void main() {
+  color = vec4(0.25, 0.5, 0.75, 1.0);
+}`;
+
+function link(gl) {
+  const vs = gl.createShader(gl.VERTEX_SHADER);
+  gl.shaderSource(vs, vsSource);
+  gl.compileShader(vs);
+  const fs = gl.createShader(gl.FRAGMENT_SHADER);
+  gl.shaderSource(fs, fsSource);
+  gl.compileShader(fs);
+  const program = gl.createProgram();
+  gl.attachShader(program, vs);
+  gl.attachShader(program, fs);
+  gl.linkProgram(program);
+  return program;
+}
+
+test('program WASM reads in chunks and carries a stable content hash', async () => {
+  const gl = await webGL2();
+  try {
+    const program = link(gl);
+    const whole = gl.getProgramWasm(program, gl.FRAGMENT_SHADER);
+    const chunked = gl.getProgramWasm(program, gl.FRAGMENT_SHADER, { chunkSize: 7 });
+    const hash = gl.getProgramWasmHash(program, gl.FRAGMENT_SHADER);
+
+    gl.linkProgram(program);
+    const relinkedHash = gl.getProgramWasmHash(program, gl.FRAGMENT_SHADER);
+    const otherHash = gl.getProgramWasmHash(link(gl), gl.FRAGMENT_SHADER);
+
+    const unlinked = gl.createProgram();
+
+    assert.deepEqual(
+      {
+        magic: Array.from(whole.subarray(0, 4)),
+        chunkedMatches: chunked.length === whole.length && chunked.every((b, i) => b === whole[i]),
+        hashFormat: /^[0-9a-f]{16}$/.test(hash),
+        relinkedSame: relinkedHash === hash,
+        otherProgramSame: otherHash === hash,
+        stagesDiffer: gl.getProgramWasmHash(program, gl.VERTEX_SHADER) !== hash,
+        unlinkedWasm: gl.getProgramWasm(unlinked, gl.FRAGMENT_SHADER),
+        unlinkedHash: gl.getProgramWasmHash(unlinked, gl.FRAGMENT_SHADER),
+      },
+      {
+        magic: [0x00, 0x61, 0x73, 0x6d],
+        chunkedMatches: true,
+        hashFormat: true,
+        relinkedSame: true,
+        otherProgramSame: true,
+        stagesDiffer: true,
+        unlinkedWasm: null,
+        unlinkedHash: null,
+      },
+    );
+  } finally {
+    gl.destroy();
+  }
+});
@@ -2,7 +2,18 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
-test('getQueryParameter throws not implemented', async () => {
+test('getQueryParameter returns null for a query that was never started', async () => {
   const gl = await webGL2();
-  try { assert.throws(() => gl.getQueryParameter(1,0), /not implemented/); } finally { gl.destroy(); }
+  try {
+    const ext = gl.getExtension('EXT_disjoint_timer_query_webgl2');
+    const query = gl.createQuery();
+    const unstarted = gl.getQueryParameter(query, gl.QUERY_RESULT);
+    const error = gl.getError();
+    gl.beginQuery(ext.TIME_ELAPSED_EXT, query);
+    gl.endQuery(ext.TIME_ELAPSED_EXT);
+    assert.deepEqual(
+      { unstarted, error, result: gl.getQueryParameter(query, gl.QUERY_RESULT) },
+      { unstarted: null, error: 0x0502, result: 0 },
+    );
+  } finally { gl.destroy(); }
 });
@@ -2,13 +2,70 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
-test('getTransformFeedbackVarying returns varying info', async () => {
+test('getTransformFeedbackVarying reports the linked varyings with their types', async () => {
   const gl = await webGL2();
   try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+    layout(location = 0) in vec4 position;
+    out vec3 v_normal;
+    flat out int v_id;
+    void main() {
+      v_normal = position.xyz;
+      v_id = gl_VertexID;
+      gl_Position = position;
+    }`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+    precision highp float;
+    in vec3 v_normal;
+    flat in int v_id;
+    out vec4 color;
+    void main() {
+      color = vec4(v_normal, float(v_id));
+    }`);
+    gl.compileShader(fs);
     const p = gl.createProgram();
-    gl.transformFeedbackVaryings(p, ['a'], gl.INTERLEAVED_ATTRIBS);
-    const v = gl.getTransformFeedbackVarying(p, 0);
-    assert.strictEqual(v.name, 'a');
-    assert.strictEqual(v.size, 1);
+    gl.attachShader(p, vs);
+    gl.attachShader(p, fs);
+    gl.transformFeedbackVaryings(p, ['gl_Position', 'v_normal', 'v_id'], gl.SEPARATE_ATTRIBS);
+
+    // Nothing is linked yet
+    const beforeLink = gl.getTransformFeedbackVarying(p, 0);
+    const beforeLinkError = gl.getError();
+
+    gl.linkProgram(p);
+    const varyings = [0, 1, 2].map((i) => {
+      const { name, size, type } = gl.getTransformFeedbackVarying(p, i);
+      return { name, size, type };
+    });
+
+    assert.deepEqual(
+      {
+        beforeLink,
+        beforeLinkError,
+        linked: gl.getProgramParameter(p, gl.LINK_STATUS),
+        count: gl.getProgramParameter(p, gl.TRANSFORM_FEEDBACK_VARYINGS),
+        mode: gl.getProgramParameter(p, gl.TRANSFORM_FEEDBACK_BUFFER_MODE),
+        varyings,
+        outOfRange: gl.getTransformFeedbackVarying(p, 3),
+        error: gl.getError(),
+      },
+      {
+        beforeLink: null,
+        beforeLinkError: gl.INVALID_VALUE,
+        linked: true,
+        count: 3,
+        mode: gl.SEPARATE_ATTRIBS,
+        varyings: [
+          { name: 'gl_Position', size: 1, type: gl.FLOAT_VEC4 },
+          { name: 'v_normal', size: 1, type: gl.FLOAT_VEC3 },
+          { name: 'v_id', size: 1, type: gl.INT },
+        ],
+        outOfRange: null,
+        error: gl.INVALID_VALUE,
+      },
+    );
   } finally { gl.destroy(); }
 });
@@ -0,0 +1,66 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const vsSource = `attribute vec2 position;
+varying vec2 uv;
+void main() {
+    uv = position * 0.5 + 0.5;
+    gl_Position = vec4(position, 0.0, 1.0);
+}`;
+
+const fsSource = `precision mediump float;
+uniform sampler2D tex;
+varying vec2 uv;
+void main() {
+    gl_FragColor = texture2D(tex, uv);
+}`;
+
+/** Compile the WebGL1 shaders, draw a textured triangle over the 2x2 context and read one pixel. */
+async function render(options) {
+  const gl = await webGL2({ size: { width: 2, height: 2 }, ...options });
+  try {
+    const logs = [];
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, vsSource], [gl.FRAGMENT_SHADER, fsSource]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      logs.push(gl.getShaderParameter(shader, gl.COMPILE_STATUS) ? gl.getShaderInfoLog(shader) : 'failed');
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    if (!gl.getProgramParameter(program, gl.LINK_STATUS)) return { logs, pixel: null };
+    gl.useProgram(program);
+
+    const texture = gl.createTexture();
+    gl.bindTexture(gl.TEXTURE_2D, texture);
+    gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, 1, 1, 0, gl.RGBA, gl.UNSIGNED_BYTE, new Uint8Array([0, 255, 0, 255]));
+    gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, gl.NEAREST);
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    const location = gl.getAttribLocation(program, 'position');
+    gl.enableVertexAttribArray(location);
+    gl.vertexAttribPointer(location, 2, gl.FLOAT, false, 0, 0);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+
+    const pixel = new Uint8Array(4);
+    gl.readPixels(1, 1, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+    return { logs, pixel: Array.from(pixel) };
+  } finally {
+    gl.destroy();
+  }
+}
+
+test('glsl100Compat compiles GLSL ES 1.00 shaders in a WebGL2 context', async () => {
+  const strict = await render({});
+  const compat = await render({ glsl100Compat: true });
+  const warning = 'Shader compiled successfully\nWARNING: GLSL ES 1.00 shader translated to GLSL ES 3.00; declare #version 300 es to compile it as written';
+
+  assert.deepEqual({ strict, compat }, {
+    strict: { logs: ['failed', 'failed'], pixel: null },
+    compat: { logs: [warning, warning], pixel: [0, 255, 0, 255] },
+  });
+});
@@ -0,0 +1,91 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+const FS = `#version 300 es
+precision highp float;
+out vec4 color;
+void main() { color = vec4(1.0); }`;
+
+test('Timer queries measure draws on the virtual GPU timeline', async () => {
+  const gl = await webGL2({
+    size: { width: 4, height: 4 },
+    gpuCostModel: { drawNs: 1000, vertexNs: 10, fragmentNs: 1 },
+  });
+  try {
+    const ext = gl.getExtension('EXT_disjoint_timer_query_webgl2');
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    const buf = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buf);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+
+    const start = gl.createQuery();
+    const end = gl.createQuery();
+    const elapsed = gl.createQuery();
+    ext.queryCounterEXT(start, ext.TIMESTAMP_EXT);
+    gl.beginQuery(ext.TIME_ELAPSED_EXT, elapsed);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    gl.endQuery(ext.TIME_ELAPSED_EXT);
+    ext.queryCounterEXT(end, ext.TIMESTAMP_EXT);
+
+    const startNs = gl.getQueryParameter(start, gl.QUERY_RESULT);
+    const endNs = gl.getQueryParameter(end, gl.QUERY_RESULT);
+    assert.deepEqual(
+      {
+        // Two draws of 3 vertices covering 16 fragments each
+        elapsed: gl.getQueryParameter(elapsed, gl.QUERY_RESULT),
+        timestamps: endNs - startNs,
+        timestampParameter: gl.getParameter(ext.TIMESTAMP_EXT),
+        disjoint: gl.getParameter(ext.GPU_DISJOINT_EXT),
+        counterBits: gl.getQuery(ext.TIMESTAMP_EXT, ext.QUERY_COUNTER_BITS_EXT),
+      },
+      { elapsed: 2092, timestamps: 2092, timestampParameter: endNs, disjoint: false, counterBits: 64 },
+    );
+  } finally { gl.destroy(); }
+});
+
+test('Timer queries follow the host clock with gpuClock cpu', async () => {
+  const gl = await webGL2({ size: { width: 4, height: 4 }, gpuClock: 'cpu' });
+  try {
+    const ext = gl.getExtension('EXT_disjoint_timer_query_webgl2');
+    const elapsed = gl.createQuery();
+    gl.beginQuery(ext.TIME_ELAPSED_EXT, elapsed);
+    const wallStart = performance.now();
+    while (performance.now() - wallStart < 5) { /* spin */ }
+    gl.endQuery(ext.TIME_ELAPSED_EXT);
+    const elapsedNs = gl.getQueryParameter(elapsed, gl.QUERY_RESULT);
+
+    const before = gl.getParameter(ext.TIMESTAMP_EXT);
+    gl.setGpuClock('virtual');
+    const frozen = gl.getParameter(ext.TIMESTAMP_EXT);
+    let unknown = null;
+    try { gl.setGpuClock('wall'); } catch (e) { unknown = e.message; }
+
+    assert.deepEqual(
+      {
+        measuredSpin: elapsedNs >= 5e6,
+        monotonic: frozen >= before,
+        frozen: gl.getParameter(ext.TIMESTAMP_EXT) === frozen,
+        unknown,
+        error: gl.getError(),
+      },
+      { measuredSpin: true, monotonic: true, frozen: true, unknown: "Unknown GPU clock 'wall'", error: gl.NO_ERROR },
+    );
+  } finally { gl.destroy(); }
+});
@@ -0,0 +1,43 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const ERR_INVALID_ARGS = 3;
+
+test('bufferData rejects a source range past the end of linear memory', async () => {
+  const gl = await webGL2();
+  try {
+    const ex = gl._instance.exports;
+    gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+    const end = ex.memory.buffer.byteLength;
+    const code = ex.wasm_ctx_buffer_data(gl._ctxHandle, gl.ARRAY_BUFFER, end - 8, 64, gl.STATIC_DRAW);
+    assert.strictEqual(code, ERR_INVALID_ARGS);
+  } finally {
+    gl.destroy();
+  }
+});
+
+test('shaderSource rejects a null pointer with non-zero length', async () => {
+  const gl = await webGL2();
+  try {
+    const ex = gl._instance.exports;
+    const shader = gl.createShader(gl.VERTEX_SHADER);
+    const code = ex.wasm_ctx_shader_source(gl._ctxHandle, shader._handle, 0, 16);
+    assert.strictEqual(code, ERR_INVALID_ARGS);
+  } finally {
+    gl.destroy();
+  }
+});
+
+test('drawBuffers rejects a misaligned pointer', async () => {
+  const gl = await webGL2();
+  try {
+    const ex = gl._instance.exports;
+    const ptr = ex.wasm_alloc(16);
+    const code = ex.wasm_ctx_draw_buffers(gl._ctxHandle, ptr + 1, 1);
+    ex.wasm_free(ptr);
+    assert.strictEqual(code, ERR_INVALID_ARGS);
+  } finally {
+    gl.destroy();
+  }
+});
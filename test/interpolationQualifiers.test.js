@@ -0,0 +1,73 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const vsSource = `#version 300 es
+#extension GL_NV_shader_noperspective_interpolation : require
+layout(location = 0) in vec4 position;
+layout(location = 1) in float value;
+smooth out float vSmooth;
+noperspective out float vLinear;
+void main() {
+    vSmooth = value;
+    vLinear = value;
+    gl_Position = position;
+}`;
+
+const fsSource = `#version 300 es
+#extension GL_NV_shader_noperspective_interpolation : require
+precision highp float;
+smooth in float vSmooth;
+noperspective in float vLinear;
+out vec4 color;
+void main() {
+    color = vec4(vSmooth, vLinear, 0.0, 1.0);
+}`;
+
+test('smooth varyings are perspective-correct and noperspective ones are not', async () => {
+  const gl = await webGL2({ size: { width: 64, height: 64 } });
+  try {
+    const compile = (type, source) => {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      return shader;
+    };
+
+    const withoutExtension = compile(gl.FRAGMENT_SHADER, fsSource.replace(/^#extension.*$/m, ''));
+    const compiledWithoutExtension = gl.getShaderParameter(withoutExtension, gl.COMPILE_STATUS);
+
+    const program = gl.createProgram();
+    gl.attachShader(program, compile(gl.VERTEX_SHADER, vsSource));
+    gl.attachShader(program, compile(gl.FRAGMENT_SHADER, fsSource));
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    // Covers the viewport; the second vertex has w = 4, so perspective-correct
+    // and screen-space interpolation of `value` diverge
+    const vertices = new Float32Array([
+      -1, -1, 0, 1, 0,
+      12, -4, 0, 4, 1,
+      -1, 3, 0, 1, 0,
+    ]);
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, vertices, gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 4, gl.FLOAT, false, 20, 0);
+    gl.enableVertexAttribArray(1);
+    gl.vertexAttribPointer(1, 1, gl.FLOAT, false, 20, 16);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+
+    const pixel = new Uint8Array(4);
+    gl.readPixels(32, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+
+    // Screen-space weight of the second vertex is ~0.254 at this pixel;
+    // dividing by w gives ~0.078 once perspective-corrected
+    const near = (actual, expected) => Math.abs(actual - expected) <= 1;
+    assert.deepStrictEqual(
+      [near(pixel[0], 20), near(pixel[1], 65), compiledWithoutExtension],
+      [true, true, false]
+    );
+  } finally { gl.destroy(); }
+});
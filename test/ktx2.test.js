@@ -0,0 +1,55 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const IDENTIFIER = [0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A];
+
+// KTX2 file with `levels` (base first), stored smallest first
+function ktx2(vkFormat, width, height, scheme, levels) {
+  const headerBytes = 80 + levels.length * 24;
+  const out = new Uint8Array(headerBytes + levels.reduce((n, l) => n + l.length, 0));
+  const view = new DataView(out.buffer);
+  out.set(IDENTIFIER);
+  [vkFormat, 1, width, height, 0, 0, 1, levels.length, scheme]
+    .forEach((word, i) => view.setUint32(12 + i * 4, word, true));
+  let offset = headerBytes;
+  for (let level = levels.length - 1; level >= 0; level--) {
+    const entry = 80 + level * 24;
+    view.setBigUint64(entry, BigInt(offset), true);
+    view.setBigUint64(entry + 8, BigInt(levels[level].length), true);
+    view.setBigUint64(entry + 16, BigInt(levels[level].length), true);
+    out.set(levels[level], offset);
+    offset += levels[level].length;
+  }
+  return out;
+}
+
+test('texImageKTX2 uploads uncompressed levels and rejects Basis', async () => {
+  const gl = await webGL2({ size: { width: 2, height: 2 } });
+  try {
+    const base = new Uint8Array([255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 255, 255, 255, 255, 255, 255]);
+    const tex = gl.createTexture();
+    gl.bindTexture(gl.TEXTURE_2D, tex);
+    gl.texImageKTX2(gl.TEXTURE_2D, ktx2(37, 2, 2, 0, [base, new Uint8Array([9, 9, 9, 255])]));
+
+    const fb = gl.createFramebuffer();
+    gl.bindFramebuffer(gl.FRAMEBUFFER, fb);
+    gl.framebufferTexture2D(gl.FRAMEBUFFER, gl.COLOR_ATTACHMENT0, gl.TEXTURE_2D, tex, 0);
+    const pixels = new Uint8Array(16);
+    gl.readPixels(0, 0, 2, 2, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+
+    let basisError = null;
+    try {
+      gl.texImageKTX2(gl.TEXTURE_2D, ktx2(0, 2, 2, 1, [base]));
+    } catch (e) {
+      basisError = /Basis Universal/.test(e.message);
+    }
+
+    assert.deepEqual({ pixels: Array.from(pixels), basisError }, {
+      pixels: Array.from(base),
+      basisError: true,
+    });
+  } finally {
+    gl.destroy();
+  }
+});
@@ -0,0 +1,108 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VARYINGS_VS = `#version 300 es
+layout(location = 0) in vec4 pos;
+out vec4 v_a;
+out vec4 v_b;
+out vec4 v_c;
+void main() { v_a = pos; v_b = pos; v_c = pos; gl_Position = pos; }`;
+
+const VARYINGS_FS = `#version 300 es
+precision highp float;
+in vec4 v_a;
+in vec4 v_b;
+in vec4 v_c;
+out vec4 color;
+void main() { color = v_a + v_b + v_c; }`;
+
+const UNIFORMS_VS = `#version 300 es
+layout(location = 0) in vec4 pos;
+uniform mat4 u_matrix;
+uniform vec4 u_offset;
+void main() { gl_Position = u_matrix * pos + u_offset; }`;
+
+const UNIFORMS_FS = `#version 300 es
+precision highp float;
+out vec4 color;
+void main() { color = vec4(1.0); }`;
+
+test('Contexts report and enforce lowered implementation limits', async () => {
+  const gl = await webGL2({
+    size: { width: 1, height: 1 },
+    limits: { maxTextureSize: 64, maxVertexAttribs: 4, maxVaryingVectors: 2, maxUniformVectors: 4, maxDrawBuffers: 2 },
+  });
+  try {
+    const link = (vs, fs) => {
+      const program = gl.createProgram();
+      for (const [type, source] of [[gl.VERTEX_SHADER, vs], [gl.FRAGMENT_SHADER, fs]]) {
+        const shader = gl.createShader(type);
+        gl.shaderSource(shader, source);
+        gl.compileShader(shader);
+        gl.attachShader(program, shader);
+      }
+      gl.linkProgram(program);
+      return gl.getProgramInfoLog(program);
+    };
+
+    const parameters = Object.fromEntries([
+      'MAX_TEXTURE_SIZE', 'MAX_RENDERBUFFER_SIZE', 'MAX_VERTEX_ATTRIBS', 'MAX_VARYING_VECTORS',
+      'MAX_VARYING_COMPONENTS', 'MAX_VERTEX_UNIFORM_VECTORS', 'MAX_FRAGMENT_UNIFORM_VECTORS',
+      'MAX_DRAW_BUFFERS', 'MAX_COLOR_ATTACHMENTS',
+    ].map((name) => [name, gl.getParameter(gl[name])]));
+
+    const errorOf = (f) => { f(); return gl.getError(); };
+    gl.bindTexture(gl.TEXTURE_2D, gl.createTexture());
+    gl.bindRenderbuffer(gl.RENDERBUFFER, gl.createRenderbuffer());
+    gl.bindFramebuffer(gl.FRAMEBUFFER, gl.createFramebuffer());
+    const errors = {
+      textureAtLimit: errorOf(() => gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA8, 64, 64, 0, gl.RGBA, gl.UNSIGNED_BYTE, null)),
+      textureTooLarge: errorOf(() => gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA8, 65, 1, 0, gl.RGBA, gl.UNSIGNED_BYTE, null)),
+      renderbufferTooLarge: errorOf(() => gl.renderbufferStorage(gl.RENDERBUFFER, gl.RGBA8, 1, 128)),
+      attribOutOfRange: errorOf(() => gl.enableVertexAttribArray(4)),
+      tooManyDrawBuffers: errorOf(() => gl.drawBuffers([gl.COLOR_ATTACHMENT0, gl.COLOR_ATTACHMENT1, gl.NONE])),
+      colorAttachmentOutOfRange: errorOf(() => gl.framebufferRenderbuffer(
+        gl.FRAMEBUFFER, gl.COLOR_ATTACHMENT2, gl.RENDERBUFFER, null)),
+    };
+
+    const logs = {
+      varyings: link(VARYINGS_VS, VARYINGS_FS).startsWith('Link failed: Too many varyings'),
+      uniforms: link(UNIFORMS_VS, UNIFORMS_FS),
+    };
+
+    let rejected = null;
+    try {
+      await webGL2({ size: { width: 1, height: 1 }, limits: { maxDrawBuffers: 64 } });
+    } catch (e) {
+      rejected = e.message;
+    }
+
+    assert.deepEqual({ parameters, errors, logs, rejected }, {
+      parameters: {
+        MAX_TEXTURE_SIZE: 64,
+        MAX_RENDERBUFFER_SIZE: 64,
+        MAX_VERTEX_ATTRIBS: 4,
+        MAX_VARYING_VECTORS: 2,
+        MAX_VARYING_COMPONENTS: 8,
+        MAX_VERTEX_UNIFORM_VECTORS: 4,
+        MAX_FRAGMENT_UNIFORM_VECTORS: 4,
+        MAX_DRAW_BUFFERS: 2,
+        MAX_COLOR_ATTACHMENTS: 2,
+      },
+      errors: {
+        textureAtLimit: gl.NO_ERROR,
+        textureTooLarge: gl.INVALID_VALUE,
+        renderbufferTooLarge: gl.INVALID_VALUE,
+        attribOutOfRange: gl.INVALID_VALUE,
+        tooManyDrawBuffers: gl.INVALID_VALUE,
+        colorAttachmentOutOfRange: gl.INVALID_ENUM,
+      },
+      logs: {
+        varyings: true,
+        uniforms: 'Link failed: 5 uniform vectors exceed MAX_VERTEX_UNIFORM_VECTORS (4)',
+      },
+      rejected: 'Failed to create context: MAX_DRAW_BUFFERS of 64 exceeds the supported 8',
+    });
+  } finally { gl.destroy(); }
+});
@@ -0,0 +1,81 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const vsSource = `#version 300 es
+layout(location = 0) in vec2 position;
+void main() {
+  gl_Position = vec4(position, 0.0, 1.0);
+}`;
+
+const fsSource = `#version 300 es
+#extension GL_WEBGL2_noise : enable
+precision highp float;
+out vec4 color;
+void main() {
+  vec2 p = gl_FragCoord.xy;
+  color = vec4(hash(p), noise(p * 0.37), snoise(p * 0.21) * 0.5 + 0.5, snoise(vec2(0.0)) + 1.0);
+}`;
+
+async function render(fs) {
+  const gl = await webGL2({ size: { width: 8, height: 8 } });
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, vsSource);
+    gl.compileShader(vs);
+    const shader = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(shader, fs);
+    gl.compileShader(shader);
+    if (!gl.getShaderParameter(shader, gl.COMPILE_STATUS)) {
+      return { compiled: false };
+    }
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, shader);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.viewport(0, 0, 8, 8);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+
+    const pixels = new Uint8Array(8 * 8 * 4);
+    gl.readPixels(0, 0, 8, 8, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+    return { compiled: true, pixels: Array.from(pixels) };
+  } finally {
+    gl.destroy();
+  }
+}
+
+test('noise builtins are injected on request and render deterministically', async () => {
+  const first = await render(fsSource);
+  const second = await render(fsSource);
+  const withoutDirective = await render(fsSource.replace('#extension GL_WEBGL2_noise : enable\n', ''));
+
+  const channel = (c) => new Set(first.pixels.filter((_, i) => i % 4 === c));
+
+  assert.deepEqual(
+    {
+      compiled: first.compiled,
+      deterministic: first.pixels.every((v, i) => v === second.pixels[i]),
+      hashVaries: channel(0).size > 1,
+      noiseVaries: channel(1).size > 1,
+      simplexVaries: channel(2).size > 1,
+      simplexAtOrigin: [...channel(3)],
+      withoutDirective,
+    },
+    {
+      compiled: true,
+      deterministic: true,
+      hashVaries: true,
+      noiseVaries: true,
+      simplexVaries: true,
+      simplexAtOrigin: [255],
+      withoutDirective: { compiled: false },
+    },
+  );
+});
@@ -45,8 +45,11 @@ test('output_layout_offsets: verify vertex attribute and fragment varying offset
     });
 
     await t.test('Fragment varying offset (location 1)', () => {
+      // v_color is packed after v_other, into location 1
       const vs = `#version 300 es
-      void main() { gl_Position = vec4(0.0); }`;
+      out vec4 v_other;
+      out vec4 v_color;
+      void main() { v_other = vec4(0.0); v_color = vec4(1.0); gl_Position = vec4(0.0); }`;
       const fs = `#version 300 es
       precision highp float;
       layout(location = 1) in vec4 v_color;
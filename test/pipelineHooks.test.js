@@ -0,0 +1,75 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+out float v_x;
+void main() { v_x = pos.x; gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+const FS = `#version 300 es
+precision highp float;
+in float v_x;
+out vec4 color;
+void main() { color = vec4(1.0); }`;
+
+test('Pipeline hooks see vertices and fragments and may discard them', async () => {
+  const gl = await webGL2({ size: { width: 2, height: 2 } });
+  try {
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.bindVaryingLocation(program, 0, 'v_x');
+    gl.linkProgram(program);
+    gl.useProgram(program);
+    gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    const drawAndRead = () => {
+      gl.clearColor(0, 0, 0, 0);
+      gl.clear(gl.COLOR_BUFFER_BIT);
+      gl.drawArrays(gl.TRIANGLES, 0, 3);
+      const pixels = new Uint8Array(16);
+      gl.readPixels(0, 0, 2, 2, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+      return Array.from({ length: 4 }, (_, i) => pixels[i * 4]);
+    };
+
+    // Discard the left column and record what the hooks saw
+    const vertices = [];
+    const fragments = [];
+    gl.setPipelineHooks({
+      vertex: ({ vertexId, position }) => { vertices.push([vertexId, ...position]); },
+      fragment: ({ x, y, depth, frontFacing, varyings }) => {
+        fragments.push({ x, y, depth, frontFacing, v_x: varyings[0] });
+        return x >= 1;
+      },
+    });
+    const discarded = drawAndRead();
+    fragments.sort((a, b) => a.y - b.y || a.x - b.x);
+
+    gl.setPipelineHooks({ vertex: ({ vertexId }) => vertexId !== 1 });
+    const culled = drawAndRead();
+
+    gl.setPipelineHooks(null);
+    const removed = drawAndRead();
+
+    assert.deepEqual({ vertices, fragments, discarded, culled, removed, error: gl.getError() }, {
+      vertices: [[0, -1, -1, 0, 1], [1, 3, -1, 0, 1], [2, -1, 3, 0, 1]],
+      fragments: [
+        { x: 0, y: 0, depth: 0.5, frontFacing: true, v_x: -0.5 },
+        { x: 1, y: 0, depth: 0.5, frontFacing: true, v_x: 0.5 },
+        { x: 0, y: 1, depth: 0.5, frontFacing: true, v_x: -0.5 },
+        { x: 1, y: 1, depth: 0.5, frontFacing: true, v_x: 0.5 },
+      ],
+      discarded: [0, 255, 0, 255],
+      culled: [0, 0, 0, 0],
+      removed: [255, 255, 255, 255],
+      error: gl.NO_ERROR,
+    });
+  } finally { gl.destroy(); }
+});
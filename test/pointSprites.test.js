@@ -0,0 +1,67 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('points rasterize as gl_PointSize squares with gl_PointCoord', async () => {
+  const gl = await webGL2({ size: { width: 8, height: 8 } });
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+    void main() {
+      gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+      gl_PointSize = 4.0;
+    }`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+    precision highp float;
+    out vec4 color;
+    void main() {
+      color = vec4(gl_PointCoord, 0.0, 1.0);
+    }`);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    gl.viewport(0, 0, 8, 8);
+    gl.clearColor(0, 0, 0, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    gl.drawArrays(gl.POINTS, 0, 1);
+
+    const pixel = (x, y) => {
+      const out = new Uint8Array(4);
+      gl.readPixels(x, y, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, out);
+      return Array.from(out);
+    };
+
+    // The sprite covers pixels 2..5; window y runs up, so t = 0 is row 5
+    assert.deepEqual(
+      {
+        linked: gl.getProgramParameter(program, gl.LINK_STATUS),
+        upperLeft: pixel(2, 5),
+        lowerRight: pixel(5, 2),
+        outside: [pixel(1, 4), pixel(6, 4), pixel(4, 1), pixel(4, 6)],
+        range: Array.from(gl.getParameter(gl.ALIASED_POINT_SIZE_RANGE)),
+        error: gl.getError(),
+      },
+      {
+        linked: true,
+        upperLeft: [31, 31, 0, 255],
+        lowerRight: [223, 223, 0, 255],
+        outside: [
+          [0, 0, 0, 255],
+          [0, 0, 0, 255],
+          [0, 0, 0, 255],
+          [0, 0, 0, 255],
+        ],
+        range: [1, 1024],
+        error: gl.NO_ERROR,
+      },
+    );
+  } finally {
+    gl.destroy();
+  }
+});
@@ -0,0 +1,44 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('present premultiplies unpremultiplied drawing buffers and uploads premultiply on request', async () => {
+  const frames = {};
+  for (const premultipliedAlpha of [true, false]) {
+    const gl = await webGL2({ size: { width: 1, height: 1 }, attributes: { premultipliedAlpha } });
+    try {
+      gl.clearColor(1.0, 0.0, 0.0, 0.2);
+      gl.clear(gl.COLOR_BUFFER_BIT);
+      frames[premultipliedAlpha ? 'premultiplied' : 'unpremultiplied'] = Array.from(gl.present());
+    } finally {
+      gl.destroy();
+    }
+  }
+
+  const gl = await webGL2({ size: { width: 1, height: 1 } });
+  const uploads = {};
+  try {
+    const fb = gl.createFramebuffer();
+    for (const premultiply of [false, true]) {
+      gl.pixelStorei(gl.UNPACK_PREMULTIPLY_ALPHA_WEBGL, premultiply);
+      const tex = gl.createTexture();
+      gl.bindTexture(gl.TEXTURE_2D, tex);
+      gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, 1, 1, 0, gl.RGBA, gl.UNSIGNED_BYTE, new Uint8Array([200, 100, 0, 51]));
+      gl.bindFramebuffer(gl.FRAMEBUFFER, fb);
+      gl.framebufferTexture2D(gl.FRAMEBUFFER, gl.COLOR_ATTACHMENT0, gl.TEXTURE_2D, tex, 0);
+      const pixel = new Uint8Array(4);
+      gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+      uploads[premultiply ? 'premultiplied' : 'straight'] = Array.from(pixel);
+    }
+  } finally {
+    gl.destroy();
+  }
+
+  assert.deepEqual(
+    { frames, uploads },
+    {
+      frames: { premultiplied: [255, 0, 0, 51], unpremultiplied: [51, 0, 0, 51] },
+      uploads: { straight: [200, 100, 0, 51], premultiplied: [40, 20, 0, 51] },
+    }
+  );
+});
@@ -0,0 +1,28 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('present clears the drawing buffer unless preserveDrawingBuffer is set', async () => {
+  const frames = {};
+  for (const preserveDrawingBuffer of [false, true]) {
+    const gl = await webGL2({ size: { width: 1, height: 1 }, attributes: { preserveDrawingBuffer } });
+    try {
+      gl.clearColor(0.0, 0.0, 1.0, 1.0);
+      gl.clear(gl.COLOR_BUFFER_BIT);
+
+      // The implicit clear ignores the scissor test and color mask
+      gl.enable(gl.SCISSOR_TEST);
+      gl.scissor(0, 0, 0, 0);
+      gl.colorMask(false, false, false, false);
+      gl.present();
+
+      const pixel = new Uint8Array(4);
+      gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+      frames[preserveDrawingBuffer ? 'preserved' : 'cleared'] = Array.from(pixel);
+    } finally {
+      gl.destroy();
+    }
+  }
+
+  assert.deepEqual(frames, { cleared: [0, 0, 0, 0], preserved: [0, 0, 255, 255] });
+});
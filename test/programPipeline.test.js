@@ -0,0 +1,106 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const vs = `#version 300 es
+uniform float u_red;
+out vec4 v_color;
+void main() {
+    v_color = vec4(u_red, 0.5, 0.0, 1.0);
+    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+    gl_PointSize = 1.0;
+}`;
+
+const fs = `#version 300 es
+precision highp float;
+uniform float u_blue;
+in vec4 v_color;
+out vec4 color;
+void main() {
+    color = vec4(v_color.rg, u_blue, v_color.a);
+}`;
+
+test('separable programs are combined by a program pipeline', async () => {
+  const gl = await webGL2({ size: { width: 64, height: 64 } });
+  try {
+    const compile = (type, source) => {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      return shader;
+    };
+    const linkStage = (type, source, separable = true) => {
+      const program = gl.createProgram();
+      gl.programParameteri(program, gl.PROGRAM_SEPARABLE, separable ? 1 : 0);
+      gl.attachShader(program, compile(type, source));
+      gl.linkProgram(program);
+      return program;
+    };
+
+    const vsProgram = linkStage(gl.VERTEX_SHADER, vs);
+    const fsProgram = linkStage(gl.FRAGMENT_SHADER, fs);
+    gl.programUniform1f(vsProgram, gl.getUniformLocation(vsProgram, 'u_red'), 1.0);
+    gl.programUniform1f(fsProgram, gl.getUniformLocation(fsProgram, 'u_blue'), 0.25);
+
+    const pipeline = gl.createProgramPipeline();
+    gl.bindProgramPipeline(pipeline);
+    gl.useProgramStages(pipeline, gl.VERTEX_SHADER_BIT, vsProgram);
+    gl.useProgramStages(pipeline, gl.FRAGMENT_SHADER_BIT, fsProgram);
+    gl.useProgram(null);
+    gl.drawArrays(gl.POINTS, 0, 1);
+    const pixel = new Uint8Array(4);
+    gl.readPixels(32, 32, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+    const expected = [255, 128, 64, 255];
+    const pixelMatches = expected.every((value, i) => Math.abs(pixel[i] - value) <= 1);
+    const drawError = gl.getError();
+
+    // A non-separable program cannot provide pipeline stages
+    gl.useProgramStages(pipeline, gl.ALL_SHADER_BITS, linkStage(gl.VERTEX_SHADER, vs, false));
+    const notSeparableError = gl.getError();
+
+    // Mismatched varying types fail validation at draw time
+    const mismatched = gl.createProgramPipeline();
+    gl.useProgramStages(mismatched, gl.VERTEX_SHADER_BIT, vsProgram);
+    gl.useProgramStages(
+      mismatched,
+      gl.FRAGMENT_SHADER_BIT,
+      linkStage(gl.FRAGMENT_SHADER, fs.replace('in vec4 v_color;', 'in vec3 v_color;').replace('v_color.a', '1.0'))
+    );
+    gl.bindProgramPipeline(mismatched);
+    gl.drawArrays(gl.POINTS, 0, 1);
+    const mismatchError = gl.getError();
+
+    const vertexOnly = gl.createProgramPipeline();
+    gl.useProgramStages(vertexOnly, gl.VERTEX_SHADER_BIT, vsProgram);
+    gl.validateProgramPipeline(vertexOnly);
+
+    assert.deepEqual(
+      {
+        separable: gl.getProgramParameter(fsProgram, gl.PROGRAM_SEPARABLE),
+        pixel: pixelMatches ? expected : Array.from(pixel),
+        drawError,
+        binding: gl.getParameter(gl.PROGRAM_PIPELINE_BINDING) === mismatched,
+        fragmentStage: gl.getProgramPipelineParameter(pipeline, gl.FRAGMENT_SHADER) === fsProgram._handle,
+        notSeparableError,
+        mismatchError,
+        mismatchLog: /type mismatch/.test(gl.getProgramPipelineInfoLog(mismatched)),
+        vertexOnlyValid: gl.getProgramPipelineParameter(vertexOnly, gl.VALIDATE_STATUS),
+        vertexOnlyLog: /no fragment program/.test(gl.getProgramPipelineInfoLog(vertexOnly)),
+      },
+      {
+        separable: true,
+        pixel: expected,
+        drawError: gl.NO_ERROR,
+        binding: true,
+        fragmentStage: true,
+        notSeparableError: gl.INVALID_OPERATION,
+        mismatchError: gl.INVALID_OPERATION,
+        mismatchLog: true,
+        vertexOnlyValid: false,
+        vertexOnlyLog: true,
+      }
+    );
+  } finally {
+    gl.destroy();
+  }
+});
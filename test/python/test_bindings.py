@@ -0,0 +1,72 @@
+"""Draws through the Python bindings.
+
+Needs the extension installed (`maturin develop`) and `webgl2.wasm` built at
+the repository root (`npm run build-release`); run with
+`python -m unittest discover test/python`.
+"""
+
+import os
+import unittest
+
+import webgl2
+
+WASM = os.path.join(os.path.dirname(__file__), "..", "..", "webgl2.wasm")
+
+VS = """#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }"""
+
+FS = """#version 300 es
+precision highp float;
+uniform vec4 tint;
+out vec4 color;
+void main() { color = tint; }"""
+
+
+def pixel(pixels, width, x, y):
+    at = (y * width + x) * 4
+    return list(pixels[at:at + 4])
+
+
+class BindingsTest(unittest.TestCase):
+    def setUp(self):
+        self.gl = webgl2.Context(4, 4, wasm_path=WASM)
+
+    def test_draw_reads_back_the_uniform_color(self):
+        gl = self.gl
+        program = gl.program(VS, FS)
+        gl.use_program(program)
+        gl.attribute(0, gl.buffer([-1, -1, 3, -1, -1, 3]), 2)
+        gl.uniform(program, "tint", [1.0, 0.0, 1.0, 1.0])
+        gl.clear()
+        gl.draw_arrays(webgl2.Context.TRIANGLES, 0, 3)
+
+        pixels = gl.read_pixels()
+        self.assertEqual(len(pixels), 4 * 4 * 4)
+        for y in range(4):
+            for x in range(4):
+                self.assertEqual(pixel(pixels, 4, x, y), [255, 0, 255, 255], (x, y))
+        self.assertEqual(gl.get_error(), 0)
+
+    def test_draw_covers_only_the_triangle(self):
+        gl = self.gl
+        program = gl.program(VS, FS)
+        gl.use_program(program)
+        # Lower-left half of the viewport
+        gl.attribute(0, gl.buffer([-1, -1, 1, -1, -1, 1]), 2)
+        gl.uniform(program, "tint", [0.0, 1.0, 0.0, 1.0])
+        gl.clear(0.0, 0.0, 1.0, 1.0)
+        gl.draw_arrays(webgl2.Context.TRIANGLES, 0, 3)
+
+        # Rows are bottom row first
+        pixels = gl.read_pixels()
+        self.assertEqual(pixel(pixels, 4, 0, 0), [0, 255, 0, 255])
+        self.assertEqual(pixel(pixels, 4, 3, 3), [0, 0, 255, 255])
+
+    def test_compile_errors_raise_with_the_info_log(self):
+        with self.assertRaisesRegex(ValueError, "shader failed to compile"):
+            self.gl.program(VS, FS.replace("color = tint;", "color = missing;"))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,81 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+/**
+ * Draw a white rectangle over NDC x in [-1, -0.3], y in [-1, 0] on a 4x4
+ * context and return which pixels it covered, row by row as `readPixels`
+ * returns them, plus the single pixel read at (0, 0).
+ */
+async function drawCorner(options) {
+  const gl = await webGL2({ size: { width: 4, height: 4 }, ...options });
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+layout(location = 0) in vec2 position;
+void main() { gl_Position = vec4(position, 0.0, 1.0); }`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+precision highp float;
+out vec4 color;
+void main() { color = vec4(1.0); }`);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([
+      -1, -1, -0.3, -1, -0.3, 0,
+      -1, -1, -0.3, 0, -1, 0,
+    ]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.clearColor(0, 0, 0, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    gl.drawArrays(gl.TRIANGLES, 0, 6);
+
+    const pixels = new Uint8Array(4 * 4 * 4);
+    gl.readPixels(0, 0, 4, 4, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+    const rows = [];
+    for (let y = 0; y < 4; y++) {
+      let row = '';
+      for (let x = 0; x < 4; x++) row += pixels[(y * 4 + x) * 4] ? '#' : '.';
+      rows.push(row);
+    }
+    const corner = new Uint8Array(4);
+    gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, corner);
+    return { rows, corner: corner[0] };
+  } finally {
+    gl.destroy();
+  }
+}
+
+test('window coordinates and readPixels origin', async () => {
+  // The rectangle spans window x in [0, 1.4] and y in [0, 2]: pixel centers
+  // are at half-integers, so it covers column 0 of rows 0 and 1
+  const bottomLeft = await drawCorner({});
+  const topLeft = await drawCorner({ readOrigin: 'top-left' });
+
+  let unknownOrigin = null;
+  try {
+    await webGL2({ readOrigin: 'center' });
+  } catch (e) {
+    unknownOrigin = /Unknown readOrigin/.test(e.message);
+  }
+
+  assert.deepEqual(
+    { bottomLeft, topLeft, unknownOrigin },
+    {
+      // Bottom row first, as in WebGL
+      bottomLeft: { rows: ['#...', '#...', '....', '....'], corner: 255 },
+      // Top row first, as in image files
+      topLeft: { rows: ['....', '....', '#...', '#...'], corner: 0 },
+      unknownOrigin: true,
+    }
+  );
+});
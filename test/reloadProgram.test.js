@@ -0,0 +1,68 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('reloadProgram swaps shader code in place and keeps uniform values', async () => {
+  const gl = await webGL2();
+  try {
+    const vsSrc = `#version 300 es
+void main() {
+    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+    gl_PointSize = 100.0;
+}`;
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, vsSrc);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+precision highp float;
+uniform float uGreen;
+out vec4 color;
+void main() {
+    color = vec4(0.0, uGreen, 0.0, 1.0);
+}`);
+    gl.compileShader(fs);
+    const prog = gl.createProgram();
+    gl.attachShader(prog, vs);
+    gl.attachShader(prog, fs);
+    gl.linkProgram(prog);
+    gl.useProgram(prog);
+    gl.uniform1f(gl.getUniformLocation(prog, 'uGreen'), 1.0);
+
+    const draw = () => {
+      gl.clearColor(0.0, 0.0, 0.0, 0.0);
+      gl.clear(gl.COLOR_BUFFER_BIT);
+      gl.drawArrays(gl.POINTS, 0, 1);
+      const pixel = new Uint8Array(4);
+      gl.readPixels(320, 240, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+      return Array.from(pixel);
+    };
+
+    const before = draw();
+    // uRed takes uGreen's old location, so the interface changes
+    const unchanged = gl.reloadProgram(prog, vsSrc, `#version 300 es
+precision highp float;
+uniform float uRed;
+uniform float uGreen;
+out vec4 color;
+void main() {
+    color = vec4(uRed, uGreen, 1.0, 1.0);
+}`);
+    const reloaded = draw();
+
+    let rejected = false;
+    try {
+      gl.reloadProgram(prog, vsSrc, 'not glsl');
+    } catch (e) {
+      rejected = true;
+    }
+    const afterError = draw();
+
+    assert.deepStrictEqual(
+      [before, unchanged, reloaded, rejected, afterError],
+      [[0, 255, 0, 255], false, [0, 255, 255, 255], true, [0, 255, 255, 255]]
+    );
+  } finally {
+    gl.destroy();
+  }
+});
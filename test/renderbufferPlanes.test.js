@@ -0,0 +1,100 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+const FS = `#version 300 es
+precision highp float;
+out vec4 color;
+void main() { color = vec4(1.0); }`;
+
+test('Draws update depth and stencil renderbuffer planes', async () => {
+  const gl = await webGL2({ size: { width: 2, height: 2 } });
+  try {
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    gl.useProgram(program);
+    gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+    // The left column of the 2x2 framebuffer at depth 0.5, then all of it
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([
+      -1, -1, 0, -1, -1, 1, -1, 1, 0, -1, 0, 1,
+      -1, -1, 3, -1, -1, 3,
+    ]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    const drawLeft = () => gl.drawArrays(gl.TRIANGLES, 0, 6);
+    const drawAll = () => gl.drawArrays(gl.TRIANGLES, 6, 3);
+
+    const renderbuffer = (format) => {
+      const rb = gl.createRenderbuffer();
+      gl.bindRenderbuffer(gl.RENDERBUFFER, rb);
+      gl.renderbufferStorage(gl.RENDERBUFFER, format, 2, 2);
+      return rb;
+    };
+    const framebuffer = (attachments) => {
+      gl.bindFramebuffer(gl.FRAMEBUFFER, gl.createFramebuffer());
+      for (const [attachment, rb] of attachments) {
+        gl.framebufferRenderbuffer(gl.FRAMEBUFFER, attachment, gl.RENDERBUFFER, rb);
+      }
+    };
+    const planes = (rb) => {
+      const { internalFormat, depth, stencil } = gl.getRenderbufferPixels(rb);
+      return {
+        internalFormat,
+        depth: Array.from(depth, (d) => Math.round(d * 1000) / 1000),
+        stencil: Array.from(stencil),
+      };
+    };
+    gl.enable(gl.DEPTH_TEST);
+    gl.enable(gl.STENCIL_TEST);
+
+    // No color attachment: the draw covers the depth buffer
+    const depthRb = renderbuffer(gl.DEPTH_COMPONENT16);
+    framebuffer([[gl.DEPTH_ATTACHMENT, depthRb]]);
+    drawLeft();
+    const depthOnly = planes(depthRb);
+
+    // The second draw only passes where the first didn't write 5
+    const stencilRb = renderbuffer(gl.STENCIL_INDEX8);
+    framebuffer([
+      [gl.COLOR_ATTACHMENT0, renderbuffer(gl.RGBA8)],
+      [gl.STENCIL_ATTACHMENT, stencilRb],
+    ]);
+    gl.stencilFunc(gl.ALWAYS, 5, 0xFF);
+    gl.stencilOp(gl.KEEP, gl.KEEP, gl.REPLACE);
+    drawLeft();
+    gl.stencilFunc(gl.NOTEQUAL, 5, 0xFF);
+    gl.stencilOp(gl.KEEP, gl.KEEP, gl.INCR);
+    drawAll();
+    const stencilOnly = planes(stencilRb);
+
+    const depthStencilRb = renderbuffer(gl.DEPTH_STENCIL);
+    framebuffer([
+      [gl.COLOR_ATTACHMENT0, renderbuffer(gl.RGBA8)],
+      [gl.DEPTH_STENCIL_ATTACHMENT, depthStencilRb],
+    ]);
+    gl.stencilFunc(gl.ALWAYS, 3, 0xFF);
+    gl.stencilOp(gl.KEEP, gl.KEEP, gl.REPLACE);
+    drawLeft();
+    const depthStencil = planes(depthStencilRb);
+    gl.clear(gl.DEPTH_BUFFER_BIT | gl.STENCIL_BUFFER_BIT);
+    const cleared = planes(depthStencilRb);
+
+    assert.deepEqual({ depthOnly, stencilOnly, depthStencil, cleared, error: gl.getError() }, {
+      depthOnly: { internalFormat: gl.DEPTH_COMPONENT16, depth: [0.5, 1, 0.5, 1], stencil: [] },
+      stencilOnly: { internalFormat: gl.STENCIL_INDEX8, depth: [], stencil: [5, 1, 5, 1] },
+      depthStencil: { internalFormat: gl.DEPTH24_STENCIL8, depth: [0.5, 1, 0.5, 1], stencil: [3, 0, 3, 0] },
+      cleared: { internalFormat: gl.DEPTH24_STENCIL8, depth: [1, 1, 1, 1], stencil: [0, 0, 0, 0] },
+      error: gl.NO_ERROR,
+    });
+  } finally { gl.destroy(); }
+});
@@ -0,0 +1,81 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('resize to 4K reallocates the drawing buffer and follows the viewport', async () => {
+  const gl = await webGL2();
+  try {
+    gl.resize(3840, 2160);
+    const viewport = Array.from(gl.getParameter(gl.VIEWPORT));
+    const scissor = Array.from(gl.getParameter(gl.SCISSOR_BOX));
+
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+layout(location = 0) in vec2 position;
+void main() { gl_Position = vec4(position, 0.0, 1.0); }`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+precision highp float;
+out vec4 color;
+void main() { color = vec4(0.0, 1.0, 0.0, 1.0); }`);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    // A small triangle in the top-right corner, which only reaches the last
+    // pixel if the viewport spans the whole 4K buffer
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([0.99, 0.99, 1.0, 0.99, 1.0, 1.0]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.enable(gl.DEPTH_TEST);
+    gl.clearColor(0, 0, 1, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT | gl.DEPTH_BUFFER_BIT);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+
+    const corner = new Uint8Array(4);
+    gl.readPixels(3839, 2158, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, corner);
+    const origin = new Uint8Array(4);
+    gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, origin);
+
+    // A viewport the application set is kept
+    gl.viewport(0, 0, 100, 100);
+    gl.resize(1920, 1080);
+    const customViewport = Array.from(gl.getParameter(gl.VIEWPORT));
+
+    let tooLarge = null;
+    try {
+      gl.resize(16385, 16);
+    } catch (e) {
+      tooLarge = /exceeds/.test(e.message);
+    }
+
+    assert.deepEqual(
+      {
+        viewport,
+        scissor,
+        corner: Array.from(corner),
+        origin: Array.from(origin),
+        customViewport,
+        tooLarge,
+        size: [gl.drawingBufferWidth, gl.drawingBufferHeight],
+      },
+      {
+        viewport: [0, 0, 3840, 2160],
+        scissor: [0, 0, 3840, 2160],
+        corner: [0, 255, 0, 255],
+        origin: [0, 0, 255, 255],
+        customViewport: [0, 0, 100, 100],
+        tooLarge: true,
+        size: [1920, 1080],
+      }
+    );
+  } finally {
+    gl.destroy();
+  }
+});
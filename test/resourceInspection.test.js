@@ -0,0 +1,64 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('textures and buffers can be read without binding them', async () => {
+  const gl = await webGL2({ size: { width: 1, height: 1 } });
+  try {
+    const texels = new Uint8Array(2 * 2 * 4).map((_, i) => i * 10);
+    const texture = gl.createTexture();
+    gl.bindTexture(gl.TEXTURE_2D, texture);
+    gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA8, 2, 2, 0, gl.RGBA, gl.UNSIGNED_BYTE, texels);
+    const floats = new Float32Array([0.25, -1, 1e6, 0]);
+    const floatTexture = gl.createTexture();
+    gl.bindTexture(gl.TEXTURE_2D, floatTexture);
+    gl.texImage2D(gl.TEXTURE_2D, 0, gl.R32F, 4, 1, 0, gl.RED, gl.FLOAT, floats);
+    gl.bindTexture(gl.TEXTURE_2D, null);
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Uint8Array([1, 2, 3, 4, 5, 6]), gl.STATIC_DRAW);
+    gl.bindBuffer(gl.ARRAY_BUFFER, null);
+
+    const rgba = gl.getTexturePixels(texture);
+    const r32f = gl.getTexturePixels(floatTexture);
+    const errors = [
+      () => gl.getTexturePixels(texture, 1),
+      () => gl.getTexturePixels(texture, 0, 1),
+      () => gl.getBufferData(buffer, 4, 4),
+    ].map(read => {
+      try {
+        read();
+        return null;
+      } catch (e) {
+        return e.message.replace(/^.*failed: /, '');
+      }
+    });
+
+    assert.deepEqual({
+      rgba: { ...rgba, data: Array.from(rgba.data) },
+      r32f: {
+        size: [r32f.width, r32f.height],
+        internalFormat: r32f.internalFormat,
+        values: Array.from(new Float32Array(r32f.data.buffer)),
+      },
+      whole: Array.from(gl.getBufferData(buffer)),
+      range: Array.from(gl.getBufferData(buffer, 2, 3)),
+      tail: Array.from(gl.getBufferData(buffer, 4)),
+      errors,
+    }, {
+      rgba: { width: 2, height: 2, internalFormat: gl.RGBA8, data: Array.from(texels) },
+      r32f: { size: [4, 1], internalFormat: gl.R32F, values: Array.from(floats) },
+      whole: [1, 2, 3, 4, 5, 6],
+      range: [3, 4, 5],
+      tail: [5, 6],
+      errors: [
+        `texture ${texture._handle} has no level 1`,
+        `level 0 of texture ${texture._handle} has no layer 1`,
+        `range 4..8 is outside buffer ${buffer._handle} of 6 bytes`,
+      ],
+    });
+  } finally {
+    gl.destroy();
+  }
+});
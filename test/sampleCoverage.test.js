@@ -2,7 +2,71 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
-test('sampleCoverage throws not implemented', async () => {
-  const gl = await webGL2();
-  try { assert.throws(() => gl.sampleCoverage(1.0, false), /not implemented/); } finally { gl.destroy(); }
+test('sample coverage state round-trips and leaves single-sampled draws untouched', async () => {
+  const gl = await webGL2({ size: { width: 8, height: 8 } });
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+    layout(location = 0) in vec2 position;
+    void main() {
+      gl_Position = vec4(position, 0.0, 1.0);
+    }`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+    precision highp float;
+    out vec4 color;
+    void main() {
+      color = vec4(1.0, 0.0, 0.0, 0.0);
+    }`);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.viewport(0, 0, 8, 8);
+
+    gl.enable(gl.SAMPLE_ALPHA_TO_COVERAGE);
+    gl.enable(gl.SAMPLE_COVERAGE);
+    gl.sampleCoverage(1.5, true);
+    const state = {
+      alphaToCoverage: gl.isEnabled(gl.SAMPLE_ALPHA_TO_COVERAGE),
+      coverage: gl.isEnabled(gl.SAMPLE_COVERAGE),
+      value: gl.getParameter(gl.SAMPLE_COVERAGE_VALUE),
+      invert: gl.getParameter(gl.SAMPLE_COVERAGE_INVERT),
+      sampleBuffers: gl.getParameter(gl.SAMPLE_BUFFERS),
+    };
+
+    gl.clearColor(0, 0, 0, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    const out = new Uint8Array(4);
+    gl.readPixels(4, 4, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, out);
+
+    gl.disable(gl.SAMPLE_ALPHA_TO_COVERAGE);
+
+    assert.deepEqual(
+      {
+        state,
+        pixel: Array.from(out),
+        disabled: gl.isEnabled(gl.SAMPLE_ALPHA_TO_COVERAGE),
+        error: gl.getError(),
+      },
+      {
+        state: { alphaToCoverage: true, coverage: true, value: 1, invert: true, sampleBuffers: 0 },
+        pixel: [255, 0, 0, 0],
+        disabled: false,
+        error: gl.NO_ERROR,
+      },
+    );
+  } finally {
+    gl.destroy();
+  }
 });
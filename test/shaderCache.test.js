@@ -0,0 +1,79 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2, setShaderCache } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+const FS = `#version 300 es
+precision highp float;
+uniform vec4 u_color;
+out vec4 color;
+void main() { color = u_color; }`;
+
+function memoryCache() {
+  const entries = new Map();
+  const stats = { puts: 0, hits: new Set() };
+  return {
+    stats,
+    get(key) {
+      const bytes = entries.get(key);
+      if (bytes) stats.hits.add(key);
+      return bytes;
+    },
+    put(key, bytes) {
+      stats.puts++;
+      entries.set(key, bytes);
+    },
+  };
+}
+
+async function drawWithNewContext(shaderCache, rgba) {
+  const gl = await webGL2({ size: { width: 1, height: 1 }, shaderCache });
+  try {
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    gl.useProgram(program);
+    gl.uniform4f(gl.getUniformLocation(program, 'u_color'), ...rgba);
+
+    const buf = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buf);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+
+    const pixel = new Uint8Array(4);
+    gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+    return Array.from(pixel);
+  } finally {
+    gl.destroy();
+  }
+}
+
+test('Linking stores compiled shaders in the shader cache and reuses them', async () => {
+  const cache = memoryCache();
+  try {
+    const first = await drawWithNewContext(cache, [1, 0, 0, 1]);
+    const afterFirst = { puts: cache.stats.puts, hits: cache.stats.hits.size };
+    const second = await drawWithNewContext(cache, [0, 0, 1, 1]);
+    assert.deepEqual(
+      { first, afterFirst, second, afterSecond: { puts: cache.stats.puts, hits: cache.stats.hits.size } },
+      {
+        first: [255, 0, 0, 255],
+        afterFirst: { puts: 2, hits: 0 },
+        second: [0, 0, 255, 255],
+        afterSecond: { puts: 2, hits: 2 },
+      },
+    );
+  } finally {
+    setShaderCache(null);
+  }
+});
@@ -0,0 +1,52 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('getProgramCoverage reports per-line fragment shader execution counts', async () => {
+  const gl = await webGL2({ coverage: true });
+  try {
+    const buf = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buf);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([0.0, 0.0, 0.0, 1.0]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 4, gl.FLOAT, false, 0, 0);
+
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+layout(location = 0) in vec4 pos;
+void main() {
+    gl_Position = pos;
+}`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+precision highp float;
+out vec4 color;
+void main() {
+    if (gl_FragCoord.x < 0.0) {
+        color = vec4(0.0);
+    } else {
+        color = vec4(1.0);
+    }
+}`);
+    gl.compileShader(fs);
+    const prog = gl.createProgram();
+    gl.attachShader(prog, vs);
+    gl.attachShader(prog, fs);
+    gl.linkProgram(prog);
+    gl.useProgram(prog);
+
+    gl.drawArrays(gl.POINTS, 0, 1);
+    const afterDraw = gl.getProgramCoverage(prog, gl.FRAGMENT_SHADER);
+    gl.resetProgramCoverage(prog);
+    const afterReset = gl.getProgramCoverage(prog, gl.FRAGMENT_SHADER);
+
+    // Line 6 is the untaken branch, line 8 the taken one
+    assert.deepStrictEqual(
+      [afterDraw.get(6), afterDraw.get(8) > 0, afterReset.get(8)],
+      [0, true, 0]
+    );
+  } finally {
+    gl.destroy();
+  }
+});
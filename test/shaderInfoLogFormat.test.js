@@ -0,0 +1,19 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('getShaderInfoLog reports errors in GLSL compiler format', async () => {
+  const gl = await webGL2();
+  try {
+    const shader = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(shader, `#version 300 es
+precision highp float;
+out vec4 color;
+void main() {
+    color = foo;
+}`);
+    gl.compileShader(shader);
+    const log = gl.getShaderInfoLog(shader);
+    assert.match(log, /^ERROR: 0:5: 'foo' : undeclared identifier$/m);
+  } finally { gl.destroy(); }
+});
@@ -0,0 +1,52 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2, getShaderIr, getShaderWat } from '../index.js';
+
+test('getShaderIr returns the Naga IR and WAT of each program stage', async () => {
+  const gl = await webGL2();
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, '#version 300 es\nvoid main() { gl_Position = vec4(0); }');
+    gl.compileShader(vs);
+
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, '#version 300 es\nprecision mediump float; out vec4 color; void main() { color = vec4(1); }');
+    gl.compileShader(fs);
+
+    const unlinked = gl.createProgram();
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+
+    const vsIr = getShaderIr(gl._ctxHandle, program._handle, gl.VERTEX_SHADER);
+    const fsIr = getShaderIr(gl._ctxHandle, program._handle, gl.FRAGMENT_SHADER, 'naga');
+    const fsWat = getShaderIr(gl._ctxHandle, program._handle, gl.FRAGMENT_SHADER, 'wat');
+
+    let badFormat = null;
+    try {
+      getShaderIr(gl._ctxHandle, program._handle, gl.FRAGMENT_SHADER, 'spirv');
+    } catch (e) {
+      badFormat = e.message;
+    }
+
+    assert.deepEqual(
+      {
+        vsIsNaga: vsIr.startsWith('Module {') && vsIr.includes('stage: Vertex'),
+        fsIsNaga: fsIr.startsWith('Module {') && fsIr.includes('stage: Fragment'),
+        watMatches: fsWat.startsWith('(module') && fsWat === getShaderWat(gl._ctxHandle, program._handle, gl.FRAGMENT_SHADER),
+        unlinked: getShaderIr(gl._ctxHandle, unlinked._handle, gl.VERTEX_SHADER),
+        badFormat,
+      },
+      {
+        vsIsNaga: true,
+        fsIsNaga: true,
+        watMatches: true,
+        unlinked: null,
+        badFormat: 'Unknown IR format: spirv',
+      }
+    );
+  } finally {
+    gl.destroy();
+  }
+});
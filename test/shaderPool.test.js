@@ -0,0 +1,84 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = '#version 300 es\nin vec2 pos;\nvoid main() { gl_Position = vec4(pos, 0.0, 1.0); }';
+const FS = '#version 300 es\nprecision highp float;\nout vec4 color;\nvoid main() { color = vec4(1.0, 0.0, 0.0, 1.0); }';
+
+function link(gl) {
+  const prog = gl.createProgram();
+  for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+    const shader = gl.createShader(type);
+    gl.shaderSource(shader, source);
+    gl.compileShader(shader);
+    gl.attachShader(prog, shader);
+  }
+  gl.linkProgram(prog);
+  return prog;
+}
+
+function drawPixel(gl, prog) {
+  gl.useProgram(prog);
+  gl.clearColor(0, 0, 0, 1);
+  gl.clear(gl.COLOR_BUFFER_BIT);
+  gl.drawArrays(gl.TRIANGLES, 0, 3);
+  const px = new Uint8Array(4);
+  gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, px);
+  return Array.from(px);
+}
+
+test('programs linked from identical shaders share pooled instances', async () => {
+  const gl = await webGL2({ size: { width: 1, height: 1 } });
+  try {
+    const buf = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buf);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+
+    const first = link(gl);
+    const afterFirst = gl.getShaderPoolStats();
+    const second = link(gl);
+    const afterSecond = gl.getShaderPoolStats();
+    gl.clearShaderPool();
+    const third = link(gl);
+
+    assert.deepEqual({
+      afterFirst,
+      afterSecond,
+      afterClear: gl.getShaderPoolStats(),
+      shared: second._fsInstance === first._fsInstance,
+      freshAfterClear: third._fsInstance !== first._fsInstance,
+      pixels: [drawPixel(gl, first), drawPixel(gl, second), drawPixel(gl, third)],
+    }, {
+      afterFirst: { hits: 0, misses: 2, size: 2 },
+      afterSecond: { hits: 2, misses: 2, size: 2 },
+      afterClear: { hits: 2, misses: 4, size: 2 },
+      shared: true,
+      freshAfterClear: true,
+      pixels: [[255, 0, 0, 255], [255, 0, 0, 255], [255, 0, 0, 255]],
+    });
+  } finally {
+    gl.destroy();
+  }
+});
+
+test('modules with a data section are instantiated afresh', async () => {
+  const gl = await webGL2({ size: { width: 1, height: 1 } });
+  try {
+    // A memory and one active segment writing 0x2a at address 0
+    const wasm = new Uint8Array([
+      0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00,
+      0x05, 0x03, 0x01, 0x00, 0x01,
+      0x0b, 0x07, 0x01, 0x00, 0x41, 0x00, 0x0b, 0x01, 0x2a,
+    ]);
+    const before = gl.getShaderPoolStats();
+    const first = gl._shaderInstance(wasm, {});
+    const second = gl._shaderInstance(wasm, {});
+
+    assert.notEqual(second, first);
+    assert.deepEqual(gl.getShaderPoolStats(), before);
+  } finally {
+    gl.destroy();
+  }
+});
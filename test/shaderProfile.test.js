@@ -0,0 +1,70 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('getProgramProfile attributes executed instructions to fragment shader lines', async () => {
+  const gl = await webGL2({ profile: true });
+  try {
+    const buf = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buf);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([0.0, 0.0, 0.0, 1.0]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 4, gl.FLOAT, false, 0, 0);
+
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+layout(location = 0) in vec4 pos;
+void main() {
+    gl_Position = pos;
+}`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+precision highp float;
+out vec4 color;
+void main() {
+    float acc = 0.0;
+    for (int i = 0; i < 16; i++) {
+        acc += sin(float(i));
+    }
+    color = vec4(acc);
+}`);
+    gl.compileShader(fs);
+    const prog = gl.createProgram();
+    gl.attachShader(prog, vs);
+    gl.attachShader(prog, fs);
+    gl.linkProgram(prog);
+    gl.useProgram(prog);
+
+    gl.resetProgramCoverage(prog);
+    gl.drawArrays(gl.POINTS, 0, 1);
+    const profile = gl.getProgramProfile(prog, gl.FRAGMENT_SHADER);
+    const listing = gl.getProgramProfileListing(prog, gl.FRAGMENT_SHADER).split('\n');
+
+    const byLine = new Map(profile.lines.map((l) => [l.line, l]));
+    const hottest = profile.lines.reduce((a, b) => (b.instructions > a.instructions ? b : a));
+    const percentSum = profile.lines.reduce((sum, l) => sum + l.percent, 0);
+
+    // Listing row 0 is the header, row N is source line N
+    assert.deepStrictEqual(
+      {
+        hottestLine: hottest.line,
+        loopBodyRuns: byLine.get(7).executions,
+        storeRuns: byLine.get(9).executions,
+        percentInRange: percentSum > 0 && percentSum <= 100.0001,
+        listingHeader: listing[0].trim().split(/\s+/),
+        listingLine7: listing[7].endsWith('        acc += sin(float(i));') && /^\s*\d+\.\d\d%/.test(listing[7]),
+      },
+      {
+        hottestLine: 7,
+        loopBodyRuns: 16,
+        storeRuns: 1,
+        percentInRange: true,
+        listingHeader: ['Percent', 'Instructions', 'Line', 'Source'],
+        listingLine7: true,
+      }
+    );
+  } finally {
+    gl.destroy();
+  }
+});
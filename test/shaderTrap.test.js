@@ -0,0 +1,64 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+const FS = `#version 300 es
+precision highp float;
+uniform int u_divisor;
+out vec4 color;
+void main() {
+  color = vec4(float(12 / u_divisor) / 4.0, 0.0, 0.0, 1.0);
+}`;
+
+test('Shader traps are reported with their GLSL line and the context recovers', async () => {
+  // Divide by zero, then draw again with a valid divisor
+  const trapAndRecover = async (debug) => {
+    const gl = await webGL2({ size: { width: 1, height: 1 }, debug });
+    try {
+      const program = gl.createProgram();
+      for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+        const shader = gl.createShader(type);
+        gl.shaderSource(shader, source);
+        gl.compileShader(shader);
+        gl.attachShader(program, shader);
+      }
+      gl.linkProgram(program);
+      gl.useProgram(program);
+      gl.bindBuffer(gl.ARRAY_BUFFER, gl.createBuffer());
+      gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+      gl.enableVertexAttribArray(0);
+      gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+      const divisor = gl.getUniformLocation(program, 'u_divisor');
+
+      gl.uniform1i(divisor, 0);
+      gl.drawArrays(gl.TRIANGLES, 0, 3);
+      const error = gl.getError();
+      const log = gl.getProgramInfoLog(program).split('\n').pop()
+        .replace(`program_${program._handle}_`, 'program_N_')
+        .replace(/0x[0-9a-f]+/, '0x…');
+
+      gl.uniform1i(divisor, 3);
+      gl.drawArrays(gl.TRIANGLES, 0, 3);
+      const pixel = new Uint8Array(4);
+      gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+      return { error, log, recovered: { error: gl.getError(), pixel: Array.from(pixel) } };
+    } finally { gl.destroy(); }
+  };
+
+  assert.deepEqual({ debug: await trapAndRecover('shaders'), release: await trapAndRecover(false) }, {
+    debug: {
+      error: 0x0502, // INVALID_OPERATION
+      log: 'Shader trap in fragment shader: divide by zero at program_N_fs.glsl:6',
+      recovered: { error: 0, pixel: [255, 0, 0, 255] },
+    },
+    release: {
+      error: 0x0502,
+      log: 'Shader trap in fragment shader: divide by zero at offset 0x… (no debug info)',
+      recovered: { error: 0, pixel: [255, 0, 0, 255] },
+    },
+  });
+});
@@ -0,0 +1,151 @@
+// @ts-check
+
+// Golden compiler output for the shaders in `corpus/`: each `<name>.vert` /
+// `<name>.frag` pair is linked and the Naga IR and WAT of both stages are
+// compared with `golden/<name>.{vs,fs}.{naga,wat}`. A change in backend output
+// fails with a line diff of the affected file.
+//
+// After an intentional codegen change, regenerate the goldens and review the
+// diff as part of the change (see docs/1.9-wat-testing.md):
+//
+//     UPDATE_SNAPSHOTS=1 node --test test/snapshots/corpus.test.js
+//
+// A shader without goldens fails until they are generated this way.
+
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import fs from 'node:fs';
+import path from 'node:path';
+import { fileURLToPath } from 'node:url';
+import { webGL2, getShaderIr } from '../../index.js';
+
+const here = path.dirname(fileURLToPath(import.meta.url));
+const corpusDir = path.join(here, 'corpus');
+const goldenDir = path.join(here, 'golden');
+const update = process.env.UPDATE_SNAPSHOTS === '1';
+
+const names = fs
+  .readdirSync(corpusDir)
+  .filter((f) => f.endsWith('.vert'))
+  .map((f) => f.slice(0, -'.vert'.length))
+  .sort();
+
+/** Link a corpus pair and return the compiler output of both stages. */
+async function compilerOutput(name) {
+  const gl = await webGL2();
+  try {
+    const program = gl.createProgram();
+    for (const [type, ext] of [[gl.VERTEX_SHADER, 'vert'], [gl.FRAGMENT_SHADER, 'frag']]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, fs.readFileSync(path.join(corpusDir, `${name}.${ext}`), 'utf8'));
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    if (!gl.getProgramParameter(program, gl.LINK_STATUS)) {
+      return { linkError: gl.getProgramInfoLog(program) };
+    }
+    const output = {};
+    for (const [stage, type] of [['vs', gl.VERTEX_SHADER], ['fs', gl.FRAGMENT_SHADER]]) {
+      for (const format of ['naga', 'wat']) {
+        output[`${name}.${stage}.${format}`] = getShaderIr(gl._ctxHandle, program._handle, type, format);
+      }
+    }
+    return output;
+  } finally {
+    gl.destroy();
+  }
+}
+
+/**
+ * Line diff of `expected` against `actual`: removed lines start with `-`,
+ * added ones with `+`, and runs of unchanged lines are cut down to the
+ * `context` lines around each change.
+ */
+function lineDiff(expected, actual, context = 3) {
+  const a = expected.split('\n');
+  const b = actual.split('\n');
+  // Only the region between the common prefix and suffix needs aligning
+  let start = 0;
+  while (start < a.length && start < b.length && a[start] === b[start]) start++;
+  let endA = a.length;
+  let endB = b.length;
+  while (endA > start && endB > start && a[endA - 1] === b[endB - 1]) {
+    endA--;
+    endB--;
+  }
+
+  // Longest common subsequence of the changed region, from the back
+  const n = endA - start;
+  const m = endB - start;
+  const lcs = Array.from({ length: n + 1 }, () => new Uint32Array(m + 1));
+  for (let i = n - 1; i >= 0; i--) {
+    for (let j = m - 1; j >= 0; j--) {
+      lcs[i][j] = a[start + i] === b[start + j]
+        ? lcs[i + 1][j + 1] + 1
+        : Math.max(lcs[i + 1][j], lcs[i][j + 1]);
+    }
+  }
+  const rows = a.slice(0, start).map((line) => [' ', line]);
+  let i = 0;
+  let j = 0;
+  while (i < n || j < m) {
+    if (i < n && j < m && a[start + i] === b[start + j]) {
+      rows.push([' ', a[start + i]]);
+      i++;
+      j++;
+    } else if (i < n && (j === m || lcs[i + 1][j] >= lcs[i][j + 1])) {
+      rows.push(['-', a[start + i]]);
+      i++;
+    } else {
+      rows.push(['+', b[start + j]]);
+      j++;
+    }
+  }
+  rows.push(...a.slice(endA).map((line) => [' ', line]));
+
+  const near = (k) => rows
+    .slice(Math.max(0, k - context), k + context + 1)
+    .some(([mark]) => mark !== ' ');
+  const out = [];
+  let line = 0;
+  let skipped = false;
+  rows.forEach(([mark, text], k) => {
+    if (mark !== '+') line++;
+    if (!near(k)) {
+      skipped = true;
+      return;
+    }
+    if (skipped || out.length === 0) out.push(`@@ line ${line} @@`);
+    skipped = false;
+    out.push(`${mark} ${text}`);
+  });
+  return out.join('\n');
+}
+
+for (const name of names) {
+  test(`corpus snapshot: ${name}`, async () => {
+    const actual = await compilerOutput(name);
+    if (actual.linkError !== undefined) {
+      assert.fail(`${name} failed to link: ${actual.linkError}`);
+    }
+
+    if (update) {
+      fs.mkdirSync(goldenDir, { recursive: true });
+      for (const [file, text] of Object.entries(actual)) {
+        fs.writeFileSync(path.join(goldenDir, file), text);
+      }
+      return;
+    }
+    for (const [file, text] of Object.entries(actual)) {
+      const golden = path.join(goldenDir, file);
+      if (!fs.existsSync(golden)) {
+        assert.fail(`${file} has no golden output; run with UPDATE_SNAPSHOTS=1 to generate it`);
+      }
+      const expected = fs.readFileSync(golden, 'utf8');
+      if (text !== expected) {
+        assert.fail(`${file} differs from its golden output:\n${lineDiff(expected, text)}`);
+      }
+    }
+  });
+}
@@ -0,0 +1,33 @@
+#version 300 es
+precision highp float;
+uniform int u_steps;
+in vec2 v_pos;
+out vec4 color;
+
+float steps(vec2 c) {
+    vec2 z = vec2(0.0);
+    for (int i = 0; i < 32; i++) {
+        if (i >= u_steps || dot(z, z) > 4.0) {
+            return float(i);
+        }
+        z = vec2(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+    }
+    return 32.0;
+}
+
+void main() {
+    float n = steps(v_pos);
+    switch (int(n) % 3) {
+    case 0:
+        color = vec4(n / 32.0, 0.0, 0.0, 1.0);
+        break;
+    case 1:
+        color = vec4(0.0, n / 32.0, 0.0, 1.0);
+        break;
+    default:
+        if (n < 1.0) {
+            discard;
+        }
+        color = vec4(0.0, 0.0, n / 32.0, 1.0);
+    }
+}
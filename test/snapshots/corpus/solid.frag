@@ -0,0 +1,6 @@
+#version 300 es
+precision highp float;
+out vec4 color;
+void main() {
+    color = vec4(1.0, 0.5, 0.25, 1.0);
+}
@@ -0,0 +1,11 @@
+#version 300 es
+precision highp float;
+uniform sampler2D u_albedo;
+uniform sampler2D u_mask;
+in vec2 v_uv;
+out vec4 color;
+void main() {
+    vec4 albedo = texture(u_albedo, v_uv);
+    float mask = texelFetch(u_mask, ivec2(v_uv * 4.0), 0).r;
+    color = mix(vec4(0.0), albedo, mask);
+}
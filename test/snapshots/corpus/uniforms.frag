@@ -0,0 +1,10 @@
+#version 300 es
+precision highp float;
+uniform vec3 u_light_dir;
+uniform vec4 u_color;
+in vec3 v_normal;
+out vec4 color;
+void main() {
+    float diffuse = max(dot(normalize(v_normal), -u_light_dir), 0.0);
+    color = vec4(u_color.rgb * diffuse, u_color.a);
+}
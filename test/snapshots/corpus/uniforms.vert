@@ -0,0 +1,10 @@
+#version 300 es
+layout(location = 0) in vec4 position;
+uniform mat4 u_model;
+uniform mat4 u_view_projection;
+uniform mat3 u_normal_matrix;
+out vec3 v_normal;
+void main() {
+    v_normal = u_normal_matrix * normalize(position.xyz);
+    gl_Position = u_view_projection * u_model * position;
+}
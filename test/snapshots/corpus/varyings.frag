@@ -0,0 +1,8 @@
+#version 300 es
+precision highp float;
+in vec2 v_uv;
+flat in int v_id;
+out vec4 color;
+void main() {
+    color = vec4(v_uv, float(v_id) / 255.0, 1.0);
+}
@@ -0,0 +1,11 @@
+#version 300 es
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec2 uv;
+layout(location = 2) in ivec2 id;
+out vec2 v_uv;
+flat out int v_id;
+void main() {
+    v_uv = uv;
+    v_id = id.x + id.y;
+    gl_Position = vec4(position, 1.0);
+}
@@ -21,3 +21,28 @@ test('texImage2D uploads pixel data and can be read back', async () => {
     gl.destroy();
   }
 });
+
+test('texImage2D reads source rows with the unpack layout and widens RGB', async () => {
+  const gl = await webGL2();
+  try {
+    const tex = gl.createTexture();
+    gl.bindTexture(gl.TEXTURE_2D, tex);
+    // Two 3-pixel RGB rows of 9 bytes, each padded to the default 4-byte alignment
+    const rows = new Uint8Array([
+      1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 0, 0,
+      10, 11, 12, 13, 14, 15, 16, 17, 18, 0, 0, 0,
+    ]);
+    gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGB, 3, 2, 0, gl.RGB, gl.UNSIGNED_BYTE, rows);
+    assert.equal(gl.getError(), gl.NO_ERROR);
+    assert.deepEqual(Array.from(gl.getTexturePixels(tex).data), [
+      1, 2, 3, 255, 4, 5, 6, 255, 7, 8, 9, 255,
+      10, 11, 12, 255, 13, 14, 15, 255, 16, 17, 18, 255,
+    ]);
+
+    // Without the padding the second row is cut short
+    gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGB, 3, 2, 0, gl.RGB, gl.UNSIGNED_BYTE, rows.subarray(0, 20));
+    assert.equal(gl.getError(), gl.INVALID_OPERATION);
+  } finally {
+    gl.destroy();
+  }
+});
@@ -0,0 +1,81 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('aliased textures sample host memory without re-uploading', async () => {
+  const gl = await webGL2({ size: { width: 2, height: 2 } });
+  const ex = gl._instance.exports;
+  const ptr = ex.wasm_ctx_alloc(gl._ctxHandle, 4);
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+    layout(location = 0) in vec2 position;
+    void main() {
+      gl_Position = vec4(position, 0.0, 1.0);
+    }`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+    precision highp float;
+    uniform sampler2D tex;
+    out vec4 color;
+    void main() {
+      color = texture(tex, vec2(0.5));
+    }`);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.viewport(0, 0, 2, 2);
+
+    const texture = gl.createTexture();
+    gl.bindTexture(gl.TEXTURE_2D, texture);
+    gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, gl.NEAREST);
+    gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MAG_FILTER, gl.NEAREST);
+
+    const region = () => new Uint8Array(ex.memory.buffer, ptr, 4);
+    const frame = (rgba) => {
+      if (rgba) region().set(rgba);
+      gl.drawArrays(gl.TRIANGLES, 0, 3);
+      const out = new Uint8Array(4);
+      gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, out);
+      return Array.from(out);
+    };
+
+    region().set([255, 0, 0, 255]);
+    gl.texImage2DAlias(gl.TEXTURE_2D, 0, gl.RGBA8, 1, 1, ptr, 4);
+    const first = frame();
+    const second = frame([0, 0, 255, 255]);
+
+    // After invalidation the texture keeps the last contents
+    gl.invalidateTextureAlias(gl.TEXTURE_2D, 0);
+    const detached = frame([0, 255, 0, 255]);
+    gl.invalidateTextureAlias(gl.TEXTURE_2D, 0);
+    const notAliased = gl.getError();
+
+    gl.texImage2DAlias(gl.TEXTURE_2D, 0, gl.RGBA8, 2, 2, ptr, 4);
+    const tooSmall = gl.getError();
+
+    assert.deepEqual(
+      { first, second, detached, notAliased, tooSmall },
+      {
+        first: [255, 0, 0, 255],
+        second: [0, 0, 255, 255],
+        detached: [0, 0, 255, 255],
+        notAliased: gl.INVALID_OPERATION,
+        tooSmall: gl.INVALID_VALUE,
+      },
+    );
+  } finally {
+    ex.wasm_free(ptr);
+    gl.destroy();
+  }
+});
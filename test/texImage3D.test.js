@@ -39,3 +39,23 @@ test('texImage3D validation', async () => {
     gl.destroy();
   }
 });
+
+test('texImage3D reads each image with the unpack layout', async () => {
+  const gl = await webGL2();
+  try {
+    const tex = gl.createTexture();
+    gl.bindTexture(gl.TEXTURE_2D_ARRAY, tex);
+    // 1x1 layers picked out of 2-pixel wide R32F rows, one row per layer
+    gl.pixelStorei(gl.UNPACK_ROW_LENGTH, 2);
+    gl.pixelStorei(gl.UNPACK_SKIP_PIXELS, 1);
+    const rows = new Float32Array([0, 1.5, 0, 2.5]);
+    gl.texImage3D(gl.TEXTURE_2D_ARRAY, 0, gl.R32F, 1, 1, 2, 0, gl.RED, gl.FLOAT, rows);
+    assert.equal(gl.getError(), gl.NO_ERROR);
+
+    const layer = (i) => Array.from(new Float32Array(gl.getTexturePixels(tex, 0, i).data.buffer));
+    assert.deepEqual(layer(0), [1.5]);
+    assert.deepEqual(layer(1), [2.5]);
+  } finally {
+    gl.destroy();
+  }
+});
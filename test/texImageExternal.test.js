@@ -0,0 +1,81 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+const FS = `#version 300 es
+precision highp float;
+uniform sampler2D u_tex;
+uniform vec2 u_size;
+out vec4 color;
+void main() { color = texture(u_tex, gl_FragCoord.xy / u_size); }`;
+
+test('texImageExternal imports RGBA, RGB and YUV host frames', async () => {
+  const gl = await webGL2({ size: { width: 2, height: 2 } });
+  try {
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    gl.useProgram(program);
+    gl.uniform2f(gl.getUniformLocation(program, 'u_size'), 2, 2);
+
+    const buf = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buf);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+
+    const tex = gl.createTexture();
+    gl.bindTexture(gl.TEXTURE_2D, tex);
+    gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, gl.NEAREST);
+    gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MAG_FILTER, gl.NEAREST);
+
+    const draw = () => {
+      gl.drawArrays(gl.TRIANGLES, 0, 3);
+      const pixels = new Uint8Array(16);
+      gl.readPixels(0, 0, 2, 2, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+      return Array.from(pixels);
+    };
+
+    gl.texImageExternal(2, 2, gl.RGBA, new Uint8Array([
+      10, 20, 30, 255, 40, 50, 60, 255,
+      70, 80, 90, 255, 100, 110, 120, 255,
+    ]));
+    const rgba = draw();
+
+    gl.texImageExternal(2, 2, gl.RGB, new Uint8Array([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]));
+    const rgb = draw();
+
+    // Gray levels: Y 16 is black and Y 235 is white in limited range
+    gl.texImageExternal(2, 2, gl.EXTERNAL_FORMAT_I420, new Uint8Array([16, 235, 126, 235, 128, 128]));
+    const i420 = draw().filter((_, i) => i % 4 === 0);
+
+    // A 1x1 frame replaces the 2x2 storage; Y/U/V of pure red
+    gl.texImageExternal(1, 1, gl.EXTERNAL_FORMAT_NV12, new Uint8Array([81, 90, 240]));
+    const nv12 = draw().slice(0, 4);
+
+    gl.texImageExternal(1, 1, 0x1234, new Uint8Array(4));
+    const badFormat = gl.getError();
+    gl.texImageExternal(2, 2, gl.RGBA, new Uint8Array(4));
+    const shortFrame = gl.getError();
+
+    assert.deepEqual({ rgba, rgb, i420, nv12, badFormat, shortFrame }, {
+      rgba: [10, 20, 30, 255, 40, 50, 60, 255, 70, 80, 90, 255, 100, 110, 120, 255],
+      rgb: [1, 2, 3, 255, 4, 5, 6, 255, 7, 8, 9, 255, 10, 11, 12, 255],
+      i420: [0, 255, 128, 255],
+      nv12: [255, 0, 0, 255],
+      badFormat: gl.INVALID_ENUM,
+      shortFrame: gl.INVALID_VALUE,
+    });
+  } finally {
+    gl.destroy();
+  }
+});
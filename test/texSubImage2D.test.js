@@ -0,0 +1,55 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('texSubImage2D updates a sub-rectangle and validates it', async () => {
+  const gl = await webGL2();
+  try {
+    const tex = gl.createTexture();
+    gl.bindTexture(gl.TEXTURE_2D, tex);
+    gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, 4, 2, 0, gl.RGBA, gl.UNSIGNED_BYTE, new Uint8Array(32));
+    const fb = gl.createFramebuffer();
+    gl.bindFramebuffer(gl.FRAMEBUFFER, fb);
+    gl.framebufferTexture2D(gl.FRAMEBUFFER, gl.COLOR_ATTACHMENT0, gl.TEXTURE_2D, tex, 0);
+    const reds = () => {
+      const out = new Uint8Array(32);
+      gl.readPixels(0, 0, 4, 2, gl.RGBA, gl.UNSIGNED_BYTE, out);
+      return Array.from({ length: 8 }, (_, i) => out[i * 4]);
+    };
+    const rgba = (...values) => new Uint8Array(values.flatMap((v) => [v, 0, 0, 255]));
+    const errorOf = (f) => { f(); return gl.getError(); };
+
+    gl.texSubImage2D(gl.TEXTURE_2D, 0, 1, 1, 2, 1, gl.RGBA, gl.UNSIGNED_BYTE, rgba(10, 20));
+    const tight = reds();
+
+    // A 1x2 region picked out of a 4x2 source image
+    gl.pixelStorei(gl.UNPACK_ROW_LENGTH, 4);
+    gl.pixelStorei(gl.UNPACK_SKIP_PIXELS, 2);
+    gl.texSubImage2D(gl.TEXTURE_2D, 0, 3, 0, 1, 2, gl.RGBA, gl.UNSIGNED_BYTE,
+      rgba(1, 2, 30, 4, 5, 6, 40, 8));
+    gl.pixelStorei(gl.UNPACK_ROW_LENGTH, 0);
+    gl.pixelStorei(gl.UNPACK_SKIP_PIXELS, 0);
+    const fromAtlas = reds();
+
+    const errors = {
+      outOfBounds: errorOf(() => gl.texSubImage2D(gl.TEXTURE_2D, 0, 3, 0, 2, 1, gl.RGBA, gl.UNSIGNED_BYTE, rgba(1, 2))),
+      negativeOffset: errorOf(() => gl.texSubImage2D(gl.TEXTURE_2D, 0, -1, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, rgba(1))),
+      missingLevel: errorOf(() => gl.texSubImage2D(gl.TEXTURE_2D, 1, 0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, rgba(1))),
+      shortData: errorOf(() => gl.texSubImage2D(gl.TEXTURE_2D, 0, 0, 0, 2, 2, gl.RGBA, gl.UNSIGNED_BYTE, rgba(1, 2, 3))),
+      badAlignment: errorOf(() => gl.pixelStorei(gl.UNPACK_ALIGNMENT, 3)),
+    };
+
+    assert.deepEqual({ tight, fromAtlas, errors, unchanged: reds() }, {
+      tight: [0, 0, 0, 0, 0, 10, 20, 0],
+      fromAtlas: [0, 0, 0, 30, 0, 10, 20, 40],
+      errors: {
+        outOfBounds: gl.INVALID_VALUE,
+        negativeOffset: gl.INVALID_VALUE,
+        missingLevel: gl.INVALID_OPERATION,
+        shortData: gl.INVALID_OPERATION,
+        badAlignment: gl.INVALID_VALUE,
+      },
+      unchanged: [0, 0, 0, 30, 0, 10, 20, 40],
+    });
+  } finally { gl.destroy(); }
+});
@@ -0,0 +1,228 @@
+// Bitmap text for rendered test output (frame numbers, labels in golden images)
+
+// 5x7 glyphs, one row per entry from the top, bit 4 is the leftmost column
+const GLYPHS = {
+  ' ': [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+  '0': [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+  '1': [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+  '2': [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+  '3': [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+  '4': [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+  '5': [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+  '6': [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+  '7': [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+  '8': [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+  '9': [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+  'A': [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+  'B': [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+  'C': [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+  'D': [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+  'E': [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+  'F': [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+  'G': [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+  'H': [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+  'I': [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+  'J': [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+  'K': [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+  'L': [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+  'M': [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+  'N': [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+  'O': [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+  'P': [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+  'Q': [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+  'R': [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+  'S': [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+  'T': [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+  'U': [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+  'V': [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+  'W': [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+  'X': [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+  'Y': [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+  'Z': [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+  '.': [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+  ':': [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+  '-': [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+  '_': [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+  '/': [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+  '=': [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+  '#': [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+  '?': [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+};
+
+export const GLYPH_WIDTH = 5;
+export const GLYPH_HEIGHT = 7;
+
+// Atlas cells leave one empty column and row so NEAREST sampling at glyph
+// edges never picks up a neighbour
+const CELL_WIDTH = GLYPH_WIDTH + 1;
+const CELL_HEIGHT = GLYPH_HEIGHT + 1;
+const ATLAS_COLUMNS = 16;
+
+/**
+ * Rasterizes the font into RGBA8 pixels: opaque white where a glyph is set,
+ * transparent elsewhere. Row 0 of `pixels` is the top row of the glyphs.
+ * @returns {{ width: number, height: number, pixels: Uint8Array, cells: Map<string, { x: number, y: number }> }}
+ */
+export function fontAtlasPixels() {
+  const chars = Object.keys(GLYPHS);
+  const width = ATLAS_COLUMNS * CELL_WIDTH;
+  const height = Math.ceil(chars.length / ATLAS_COLUMNS) * CELL_HEIGHT;
+  const pixels = new Uint8Array(width * height * 4);
+  const cells = new Map();
+  chars.forEach((ch, i) => {
+    const x = (i % ATLAS_COLUMNS) * CELL_WIDTH;
+    const y = Math.floor(i / ATLAS_COLUMNS) * CELL_HEIGHT;
+    cells.set(ch, { x, y });
+    GLYPHS[ch].forEach((bits, row) => {
+      for (let col = 0; col < GLYPH_WIDTH; col++) {
+        const p = ((y + row) * width + x + col) * 4;
+        if (bits & (0x10 >> col)) pixels.fill(255, p, p + 4);
+      }
+    });
+  });
+  return { width, height, pixels, cells };
+}
+
+/**
+ * Uploads the font atlas as a NEAREST-filtered RGBA8 texture.
+ * @param {WebGL2RenderingContext} gl
+ */
+export function createFontAtlas(gl) {
+  const atlas = fontAtlasPixels();
+  const texture = gl.createTexture();
+  gl.bindTexture(gl.TEXTURE_2D, texture);
+  gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA, atlas.width, atlas.height, 0, gl.RGBA, gl.UNSIGNED_BYTE, atlas.pixels);
+  gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, gl.NEAREST);
+  gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MAG_FILTER, gl.NEAREST);
+  gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_S, gl.CLAMP_TO_EDGE);
+  gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_T, gl.CLAMP_TO_EDGE);
+  return { texture, ...atlas };
+}
+
+/**
+ * Builds one textured quad per character: interleaved clip-space position and
+ * atlas UV (4 floats per vertex) and triangle indices. Lowercase letters use
+ * the uppercase glyphs; characters without a glyph render as '?'.
+ * @param {{ width: number, height: number, cells: Map<string, { x: number, y: number }> }} atlas
+ * @param {string} text
+ * @param {{ x: number, y: number, scale?: number, viewportWidth: number, viewportHeight: number }} options
+ *   `x`, `y`: window coordinates (origin bottom-left) of the text's top-left corner
+ * @returns {{ vertices: Float32Array, indices: Uint16Array }}
+ */
+export function buildTextGeometry(atlas, text, { x, y, scale = 1, viewportWidth, viewportHeight }) {
+  const vertices = new Float32Array(text.length * 16);
+  const indices = new Uint16Array(text.length * 6);
+  const clipX = (px) => (px / viewportWidth) * 2 - 1;
+  const clipY = (py) => (py / viewportHeight) * 2 - 1;
+  [...text].forEach((raw, i) => {
+    const ch = raw.toUpperCase();
+    const cell = atlas.cells.get(ch) ?? atlas.cells.get('?');
+    const left = x + i * CELL_WIDTH * scale;
+    const right = left + GLYPH_WIDTH * scale;
+    const top = y;
+    const bottom = y - GLYPH_HEIGHT * scale;
+    const u0 = cell.x / atlas.width;
+    const u1 = (cell.x + GLYPH_WIDTH) / atlas.width;
+    // Atlas row 0 is the glyphs' top row
+    const v0 = cell.y / atlas.height;
+    const v1 = (cell.y + GLYPH_HEIGHT) / atlas.height;
+    vertices.set([
+      clipX(left), clipY(top), u0, v0,
+      clipX(right), clipY(top), u1, v0,
+      clipX(right), clipY(bottom), u1, v1,
+      clipX(left), clipY(bottom), u0, v1,
+    ], i * 16);
+    const base = i * 4;
+    indices.set([base, base + 1, base + 2, base, base + 2, base + 3], i * 6);
+  });
+  return { vertices, indices };
+}
+
+/**
+ * Uploads the geometry of `text` into new vertex and index buffers.
+ * @param {WebGL2RenderingContext} gl
+ */
+export function createTextBuffers(gl, atlas, text, options) {
+  const { vertices, indices } = buildTextGeometry(atlas, text, options);
+  const vertexBuffer = gl.createBuffer();
+  gl.bindBuffer(gl.ARRAY_BUFFER, vertexBuffer);
+  gl.bufferData(gl.ARRAY_BUFFER, vertices, gl.STATIC_DRAW);
+  const indexBuffer = gl.createBuffer();
+  gl.bindBuffer(gl.ELEMENT_ARRAY_BUFFER, indexBuffer);
+  gl.bufferData(gl.ELEMENT_ARRAY_BUFFER, indices, gl.STATIC_DRAW);
+  return { vertexBuffer, indexBuffer, indexCount: indices.length };
+}
+
+const TEXT_VS = `#version 300 es
+layout(location = 0) in vec2 a_position;
+layout(location = 1) in vec2 a_uv;
+out vec2 v_uv;
+void main() {
+  v_uv = a_uv;
+  gl_Position = vec4(a_position, 0.0, 1.0);
+}`;
+
+const TEXT_FS = `#version 300 es
+precision highp float;
+uniform sampler2D u_atlas;
+uniform vec4 u_color;
+in vec2 v_uv;
+out vec4 color;
+void main() {
+  if (texture(u_atlas, v_uv).a < 0.5) discard;
+  color = u_color;
+}`;
+
+const textPrograms = new WeakMap();
+
+function textProgram(gl) {
+  let entry = textPrograms.get(gl);
+  if (!entry) {
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, TEXT_VS], [gl.FRAGMENT_SHADER, TEXT_FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    if (!gl.getProgramParameter(program, gl.LINK_STATUS)) {
+      throw new Error(`text program: ${gl.getProgramInfoLog(program)}`);
+    }
+    entry = { program, atlas: createFontAtlas(gl) };
+    textPrograms.set(gl, entry);
+  }
+  return entry;
+}
+
+/**
+ * Draws `text` into the current framebuffer with texture unit 0, leaving the
+ * text program and atlas texture bound. Pixels outside the glyphs
+ * are discarded, so the text overlays whatever is already drawn.
+ * @param {WebGL2RenderingContext} gl
+ * @param {string} text
+ * @param {{ x: number, y: number, scale?: number, color?: number[] }} options
+ *   `x`, `y`: window coordinates of the top-left corner; `color` is RGBA in 0..1
+ */
+export function drawText(gl, text, { x, y, scale = 1, color = [1, 1, 1, 1] }) {
+  const { program, atlas } = textProgram(gl);
+  const [, , viewportWidth, viewportHeight] = gl.getParameter(gl.VIEWPORT);
+  gl.useProgram(program);
+  gl.activeTexture(gl.TEXTURE0);
+  gl.bindTexture(gl.TEXTURE_2D, atlas.texture);
+  gl.uniform1i(gl.getUniformLocation(program, 'u_atlas'), 0);
+  gl.uniform4f(gl.getUniformLocation(program, 'u_color'), color[0], color[1], color[2], color[3]);
+
+  const { vertexBuffer, indexBuffer, indexCount } = createTextBuffers(gl, atlas, text, {
+    x, y, scale, viewportWidth, viewportHeight,
+  });
+  gl.bindBuffer(gl.ARRAY_BUFFER, vertexBuffer);
+  gl.enableVertexAttribArray(0);
+  gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 16, 0);
+  gl.enableVertexAttribArray(1);
+  gl.vertexAttribPointer(1, 2, gl.FLOAT, false, 16, 8);
+  gl.bindBuffer(gl.ELEMENT_ARRAY_BUFFER, indexBuffer);
+  gl.drawElements(gl.TRIANGLES, indexCount, gl.UNSIGNED_SHORT, 0);
+  gl.deleteBuffer(vertexBuffer);
+  gl.deleteBuffer(indexBuffer);
+}
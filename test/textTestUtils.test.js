@@ -0,0 +1,49 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+import { drawText, fontAtlasPixels, buildTextGeometry } from './textTestUtils.js';
+
+test('drawText renders glyphs from the bitmap font atlas', async () => {
+  const gl = await webGL2({ size: { width: 8, height: 8 } });
+  try {
+    gl.viewport(0, 0, 8, 8);
+    gl.clearColor(0, 0, 0, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    drawText(gl, '1', { x: 1, y: 8, color: [1, 0, 0, 1] });
+
+    const pixels = new Uint8Array(8 * 8 * 4);
+    gl.readPixels(0, 0, 8, 8, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+    // Rows from the top, '#' where the glyph was drawn
+    const rows = [];
+    for (let y = 7; y >= 0; y--) {
+      let row = '';
+      for (let x = 0; x < 8; x++) row += pixels[(y * 8 + x) * 4] > 127 ? '#' : '.';
+      rows.push(row);
+    }
+
+    const geometry = buildTextGeometry(fontAtlasPixels(), 'Hi?', {
+      x: 0, y: 7, viewportWidth: 8, viewportHeight: 8,
+    });
+
+    assert.deepEqual({
+      rows,
+      vertexFloats: geometry.vertices.length,
+      indices: Array.from(geometry.indices.slice(6, 12)),
+    }, {
+      rows: [
+        '...#....',
+        '..##....',
+        '...#....',
+        '...#....',
+        '...#....',
+        '...#....',
+        '..###...',
+        '........',
+      ],
+      vertexFloats: 48,
+      indices: [4, 5, 6, 4, 6, 7],
+    });
+  } finally {
+    gl.destroy();
+  }
+});
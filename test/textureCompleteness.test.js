@@ -0,0 +1,103 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+async function samplingContext(textureValidation, fsSource) {
+  const gl = await webGL2({ size: { width: 1, height: 1 }, textureValidation });
+  const program = gl.createProgram();
+  for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, fsSource]]) {
+    const shader = gl.createShader(type);
+    gl.shaderSource(shader, source);
+    gl.compileShader(shader);
+    gl.attachShader(program, shader);
+  }
+  gl.linkProgram(program);
+  gl.useProgram(program);
+
+  const buf = gl.createBuffer();
+  gl.bindBuffer(gl.ARRAY_BUFFER, buf);
+  gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+  gl.enableVertexAttribArray(0);
+  gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+
+  // Level 0 only, with the default NEAREST_MIPMAP_LINEAR minification filter
+  const tex = gl.createTexture();
+  gl.bindTexture(gl.TEXTURE_2D, tex);
+  gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA8, 2, 2, 0, gl.RGBA, gl.UNSIGNED_BYTE, new Uint8Array(16).fill(200));
+  return gl;
+}
+
+function drawPixel(gl) {
+  gl.drawArrays(gl.TRIANGLES, 0, 3);
+  const pixel = new Uint8Array(4);
+  gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+  return Array.from(pixel);
+}
+
+function drawError(gl) {
+  try {
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+    return null;
+  } catch (e) {
+    return { error: gl.getError(), mentionsSampler: /u_tex/.test(e.message) };
+  }
+}
+
+const FS = `#version 300 es
+precision highp float;
+uniform sampler2D u_tex;
+out vec4 color;
+void main() { color = texture(u_tex, vec2(0.5)); }`;
+
+const FS_CONFLICT = `#version 300 es
+precision highp float;
+uniform sampler2D u_tex;
+uniform highp sampler3D u_volume;
+out vec4 color;
+void main() { color = texture(u_tex, vec2(0.5)) + texture(u_volume, vec3(0.5)); }`;
+
+test('Draws validate sampler texture units by texture validation level', async () => {
+  const results = {};
+
+  const lenient = await samplingContext('lenient', FS);
+  try {
+    results.lenient = drawPixel(lenient);
+  } finally {
+    lenient.destroy();
+  }
+
+  const spec = await samplingContext('spec', FS);
+  try {
+    results.specIncomplete = drawPixel(spec);
+    spec.texParameteri(spec.TEXTURE_2D, spec.TEXTURE_MIN_FILTER, spec.NEAREST);
+    results.specComplete = drawPixel(spec);
+  } finally {
+    spec.destroy();
+  }
+
+  const strict = await samplingContext('strict', FS);
+  try {
+    results.strict = drawError(strict);
+  } finally {
+    strict.destroy();
+  }
+
+  const conflict = await samplingContext('spec', FS_CONFLICT);
+  try {
+    results.conflict = drawError(conflict);
+  } finally {
+    conflict.destroy();
+  }
+
+  assert.deepEqual(results, {
+    lenient: [200, 200, 200, 200],
+    specIncomplete: [0, 0, 0, 255],
+    specComplete: [200, 200, 200, 200],
+    strict: { error: 0x0502, mentionsSampler: true },
+    conflict: { error: 0x0502, mentionsSampler: true },
+  });
+});
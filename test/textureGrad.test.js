@@ -0,0 +1,79 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+// Red channel sampled by `expr`, drawn over a 1x1 canvas
+function samplingShader(expr) {
+  return `#version 300 es
+precision highp float;
+uniform sampler2D u_tex;
+out vec4 color;
+void main() { color = vec4(${expr}.r, 0.0, 0.0, 1.0); }`;
+}
+
+function drawRed(gl, expr) {
+  const program = gl.createProgram();
+  for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, samplingShader(expr)]]) {
+    const shader = gl.createShader(type);
+    gl.shaderSource(shader, source);
+    gl.compileShader(shader);
+    gl.attachShader(program, shader);
+  }
+  gl.linkProgram(program);
+  gl.useProgram(program);
+  gl.drawArrays(gl.TRIANGLES, 0, 3);
+  const pixel = new Uint8Array(4);
+  gl.readPixels(0, 0, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+  return pixel[0];
+}
+
+test('Explicit levels of detail, gradients and texel offsets', async () => {
+  const gl = await webGL2({ size: { width: 1, height: 1 } });
+  try {
+    const buf = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buf);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+
+    // Left column black, right column red: at u = 0.5 nearest picks red and
+    // linear averages the two
+    const tex = gl.createTexture();
+    gl.bindTexture(gl.TEXTURE_2D, tex);
+    gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGBA8, 2, 2, 0, gl.RGBA, gl.UNSIGNED_BYTE,
+      new Uint8Array([0, 0, 0, 255, 200, 0, 0, 255, 0, 0, 0, 255, 200, 0, 0, 255]));
+    gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MIN_FILTER, gl.LINEAR);
+    gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_MAG_FILTER, gl.NEAREST);
+    gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_S, gl.CLAMP_TO_EDGE);
+    gl.texParameteri(gl.TEXTURE_2D, gl.TEXTURE_WRAP_T, gl.CLAMP_TO_EDGE);
+
+    const results = {
+      lodMagnified: drawRed(gl, 'textureLod(u_tex, vec2(0.5), 0.0)'),
+      lodMinified: drawRed(gl, 'textureLod(u_tex, vec2(0.5), 1.0)'),
+      gradMagnified: drawRed(gl, 'textureGrad(u_tex, vec2(0.5), vec2(0.1, 0.0), vec2(0.0, 0.1))'),
+      gradMinified: drawRed(gl, 'textureGrad(u_tex, vec2(0.5), vec2(1.0, 0.0), vec2(0.0, 1.0))'),
+      noOffset: drawRed(gl, 'texture(u_tex, vec2(0.25, 0.5))'),
+      offset: drawRed(gl, 'textureOffset(u_tex, vec2(0.25, 0.5), ivec2(1, 0))'),
+      lodOffset: drawRed(gl, 'textureLodOffset(u_tex, vec2(0.25, 0.5), 0.0, ivec2(1, 0))'),
+    };
+    gl.texParameterf(gl.TEXTURE_2D, gl.TEXTURE_LOD_BIAS, 2.0);
+    results.biased = drawRed(gl, 'textureLod(u_tex, vec2(0.5), 0.0)');
+
+    assert.deepEqual(results, {
+      lodMagnified: 200,
+      lodMinified: 100,
+      gradMagnified: 200,
+      gradMinified: 100,
+      noOffset: 0,
+      offset: 200,
+      lodOffset: 200,
+      biased: 100,
+    });
+  } finally {
+    gl.destroy();
+  }
+});
@@ -0,0 +1,89 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2, startTrace, replayTrace, readDrawFramebuffer, hashPixels } from '../index.js';
+
+function drawScene(gl) {
+  const vs = gl.createShader(gl.VERTEX_SHADER);
+  gl.shaderSource(vs, `#version 300 es
+  layout(location = 0) in vec2 position;
+  void main() {
+    gl_Position = vec4(position, 0.0, 1.0);
+  }`);
+  gl.compileShader(vs);
+  const fs = gl.createShader(gl.FRAGMENT_SHADER);
+  gl.shaderSource(fs, `#version 300 es
+  precision highp float;
+  uniform vec4 tint;
+  out vec4 color;
+  void main() {
+    color = tint;
+  }`);
+  gl.compileShader(fs);
+  const program = gl.createProgram();
+  gl.attachShader(program, vs);
+  gl.attachShader(program, fs);
+  gl.linkProgram(program);
+  gl.useProgram(program);
+
+  const buffer = gl.createBuffer();
+  gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+  gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 1, -1, -1, 1]), gl.STATIC_DRAW);
+  gl.enableVertexAttribArray(0);
+  gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+  gl.viewport(0, 0, 8, 8);
+
+  gl.clearColor(0, 0, 0, 1);
+  gl.clear(gl.COLOR_BUFFER_BIT);
+  gl.uniform4fv(gl.getUniformLocation(program, 'tint'), new Float32Array([1, 0.5, 0, 1]));
+  gl.drawArrays(gl.TRIANGLES, 0, 3);
+}
+
+async function replayHashes(trace) {
+  const gl = await webGL2({ size: { width: trace.width, height: trace.height } });
+  try {
+    const hashes = [];
+    replayTrace(gl, trace, {
+      onDraw: (call) => hashes.push([trace.calls[call].method, hashPixels(readDrawFramebuffer(gl).pixels)]),
+    });
+    return hashes;
+  } finally {
+    gl.destroy();
+  }
+}
+
+test('traces replay to the same framebuffers and expose changed draws', async () => {
+  const gl = await webGL2({ size: { width: 8, height: 8 } });
+  let trace;
+  let original;
+  try {
+    const recorder = startTrace(gl);
+    drawScene(recorder.context);
+    trace = JSON.parse(JSON.stringify(recorder.stop()));
+    original = hashPixels(readDrawFramebuffer(gl).pixels);
+  } finally {
+    gl.destroy();
+  }
+
+  const replayed = await replayHashes(trace);
+  const tinted = structuredClone(trace);
+  const uniformCall = tinted.calls.find((call) => call.method === 'uniform4fv');
+  uniformCall.args[1] = { typed: 'Float32Array', base64: Buffer.from(new Float32Array([0, 1, 0, 1]).buffer).toString('base64') };
+  const changed = await replayHashes(tinted);
+
+  assert.deepEqual(
+    {
+      size: [trace.width, trace.height],
+      methods: replayed.map(([method]) => method),
+      lastMatchesLive: replayed[replayed.length - 1][1] === original,
+      clearSame: changed[0][1] === replayed[0][1],
+      drawDiffers: changed[1][1] !== replayed[1][1],
+    },
+    {
+      size: [8, 8],
+      methods: ['clear', 'drawArrays'],
+      lastMatchesLive: true,
+      clearSame: true,
+      drawDiffers: true,
+    },
+  );
+});
@@ -2,13 +2,63 @@ import test from 'node:test';
 import assert from 'node:assert/strict';
 import { webGL2 } from '../index.js';
 
-test('transformFeedbackVaryings stores varyings on program', async () => {
+const VS_SRC = `#version 300 es
+layout(location = 0) in vec4 position;
+out vec4 v_color;
+void main() {
+  v_color = position;
+  gl_Position = position;
+}`;
+
+const FS_SRC = `#version 300 es
+precision highp float;
+in vec4 v_color;
+out vec4 color;
+void main() { color = v_color; }`;
+
+test('transformFeedbackVaryings takes effect at link and rejects unknown varyings', async () => {
   const gl = await webGL2();
   try {
-    const p = gl.createProgram();
-    gl.transformFeedbackVaryings(p, ['a','b'], gl.INTERLEAVED_ATTRIBS);
-    // No exception, and getTransformFeedbackVarying should return the names
-    const v0 = gl.getTransformFeedbackVarying(p, 0);
-    assert.strictEqual(v0.name, 'a');
+    const link = (varyings) => {
+      const vs = gl.createShader(gl.VERTEX_SHADER);
+      gl.shaderSource(vs, VS_SRC);
+      gl.compileShader(vs);
+      const fs = gl.createShader(gl.FRAGMENT_SHADER);
+      gl.shaderSource(fs, FS_SRC);
+      gl.compileShader(fs);
+      const p = gl.createProgram();
+      gl.attachShader(p, vs);
+      gl.attachShader(p, fs);
+      gl.transformFeedbackVaryings(p, varyings, gl.INTERLEAVED_ATTRIBS);
+      gl.linkProgram(p);
+      return p;
+    };
+
+    const p = link(['v_color']);
+    // Changing the varyings after linking leaves the linked set alone
+    gl.transformFeedbackVaryings(p, ['gl_Position', 'v_color'], gl.INTERLEAVED_ATTRIBS);
+    const afterChange = gl.getProgramParameter(p, gl.TRANSFORM_FEEDBACK_VARYINGS);
+    gl.linkProgram(p);
+
+    const bad = link(['v_missing']);
+
+    assert.deepEqual(
+      {
+        afterChange,
+        afterRelink: gl.getProgramParameter(p, gl.TRANSFORM_FEEDBACK_VARYINGS),
+        mode: gl.getProgramParameter(p, gl.TRANSFORM_FEEDBACK_BUFFER_MODE),
+        first: gl.getTransformFeedbackVarying(p, 0).name,
+        badLinked: gl.getProgramParameter(bad, gl.LINK_STATUS),
+        badLog: gl.getProgramInfoLog(bad),
+      },
+      {
+        afterChange: 1,
+        afterRelink: 2,
+        mode: gl.INTERLEAVED_ATTRIBS,
+        first: 'gl_Position',
+        badLinked: false,
+        badLog: "Link failed: transform feedback varying 'v_missing' is not written by the vertex shader",
+      },
+    );
   } finally { gl.destroy(); }
 });
@@ -0,0 +1,41 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('uniform setters skip unchanged values and count them', async () => {
+  const gl = await webGL2();
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, '#version 300 es\nuniform mat4 u_mvp;\nin vec4 pos;\nvoid main() { gl_Position = u_mvp * pos; }');
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, '#version 300 es\nprecision highp float;\nuniform vec4 u_color;\nout vec4 color;\nvoid main() { color = u_color; }');
+    gl.compileShader(fs);
+    const prog = gl.createProgram();
+    gl.attachShader(prog, vs);
+    gl.attachShader(prog, fs);
+    gl.linkProgram(prog);
+    gl.useProgram(prog);
+    const mvp = gl.getUniformLocation(prog, 'u_mvp');
+    const color = gl.getUniformLocation(prog, 'u_color');
+
+    const start = gl.getUniformStats();
+    const identity = new Float32Array([1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1]);
+    for (let frame = 0; frame < 3; frame++) {
+      gl.uniformMatrix4fv(mvp, false, identity);
+      gl.uniform4f(color, 1, 0, 0, 1);
+    }
+    gl.uniform4f(color, 0, 1, 0, 1);
+    const end = gl.getUniformStats();
+
+    assert.deepEqual({
+      uploads: end.uploads - start.uploads,
+      skipped: end.skipped - start.skipped,
+    }, {
+      uploads: 3,
+      skipped: 4,
+    });
+  } finally {
+    gl.destroy();
+  }
+});
@@ -0,0 +1,57 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+void main() { gl_Position = vec4(pos, 0.0, 1.0); }`;
+
+const FS = `#version 300 es
+precision highp float;
+uniform sampler2D u_image;
+uniform highp sampler3D u_volume;
+out vec4 color;
+void main() { color = texture(u_image, vec2(0.5)) + texture(u_volume, vec3(0.5)); }`;
+
+test('validateProgram reports link state and sampler unit conflicts', async () => {
+  const gl = await webGL2();
+  try {
+    const program = gl.createProgram();
+    gl.validateProgram(program);
+    const unlinked = gl.getProgramParameter(program, gl.VALIDATE_STATUS);
+
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    // Both samplers default to texture unit 0
+    gl.validateProgram(program);
+    const conflict = {
+      status: gl.getProgramParameter(program, gl.VALIDATE_STATUS),
+      log: gl.getProgramInfoLog(program),
+    };
+
+    gl.uniform1i(gl.getUniformLocation(program, 'u_volume'), 1);
+    gl.validateProgram(program);
+    const valid = gl.getProgramParameter(program, gl.VALIDATE_STATUS);
+
+    assert.deepEqual(
+      { unlinked, conflict, valid },
+      {
+        unlinked: false,
+        conflict: {
+          status: false,
+          log: "samplers 'u_image' and 'u_volume' of different types use texture unit 0",
+        },
+        valid: true,
+      }
+    );
+  } finally {
+    gl.destroy();
+  }
+});
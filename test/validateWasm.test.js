@@ -0,0 +1,45 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+const VS = `#version 300 es
+layout(location = 0) in vec2 pos;
+out vec2 v_uv;
+void main() {
+  v_uv = pos * 0.5 + 0.5;
+  gl_Position = vec4(pos, 0.0, 1.0);
+}`;
+
+const FS = `#version 300 es
+precision highp float;
+uniform sampler2D u_tex;
+uniform int u_steps;
+in vec2 v_uv;
+out vec4 color;
+void main() {
+  vec4 sum = vec4(0.0);
+  for (int i = 0; i < u_steps; i++) {
+    sum += texture(u_tex, v_uv + vec2(float(i) * 0.01, 0.0));
+  }
+  color = sum / float(max(u_steps, 1)) + vec4(uint(gl_FragCoord.x) % 2u);
+}`;
+
+test('validateWasm validates generated shader modules at link time', async () => {
+  const gl = await webGL2({ validateWasm: true });
+  try {
+    const program = gl.createProgram();
+    for (const [type, source] of [[gl.VERTEX_SHADER, VS], [gl.FRAGMENT_SHADER, FS]]) {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      gl.attachShader(program, shader);
+    }
+    gl.linkProgram(program);
+    assert.deepEqual(
+      { linked: gl.getProgramParameter(program, gl.LINK_STATUS), log: gl.getProgramInfoLog(program) },
+      { linked: true, log: 'Program linked successfully.' },
+    );
+  } finally {
+    gl.destroy();
+  }
+});
@@ -0,0 +1,93 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+// c and d share location 0, a and b share location 1
+const packedVs = `#version 300 es
+out vec2 a;
+out vec2 b;
+out vec3 c;
+out float d;
+void main() {
+    a = vec2(0.9, 0.25);
+    b = vec2(0.5, 0.9);
+    c = vec3(0.9, 0.9, 0.75);
+    d = 1.0;
+    gl_Position = vec4(0.0, 0.0, 0.0, 1.0);
+    gl_PointSize = 1.0;
+}`;
+
+const packedFs = `#version 300 es
+precision highp float;
+in vec2 a;
+in vec2 b;
+in vec3 c;
+in float d;
+out vec4 color;
+void main() {
+    color = vec4(a.y, b.x, c.z, d);
+}`;
+
+const emptyFs = `#version 300 es
+precision highp float;
+out vec4 color;
+void main() {
+    color = vec4(1.0);
+}`;
+
+// One vec4 more than MAX_VARYING_VECTORS
+const tooManyVs = `#version 300 es
+${Array.from({ length: 16 }, (_, i) => `out vec4 v${i};`).join('\n')}
+void main() {
+${Array.from({ length: 16 }, (_, i) => `    v${i} = vec4(${i}.0);`).join('\n')}
+    gl_Position = vec4(0.0);
+}`;
+
+test('varyings are packed by component and checked at link time', async () => {
+  const gl = await webGL2({ size: { width: 64, height: 64 } });
+  try {
+    const compile = (type, source) => {
+      const shader = gl.createShader(type);
+      gl.shaderSource(shader, source);
+      gl.compileShader(shader);
+      return shader;
+    };
+    const link = (vs, fs) => {
+      const program = gl.createProgram();
+      gl.attachShader(program, compile(gl.VERTEX_SHADER, vs));
+      gl.attachShader(program, compile(gl.FRAGMENT_SHADER, fs));
+      gl.linkProgram(program);
+      return program;
+    };
+    const linkLog = (vs, fs) => {
+      const program = link(vs, fs);
+      return gl.getProgramParameter(program, gl.LINK_STATUS) ? '' : gl.getProgramInfoLog(program);
+    };
+
+    gl.useProgram(link(packedVs, packedFs));
+    gl.drawArrays(gl.POINTS, 0, 1);
+    const pixel = new Uint8Array(4);
+    gl.readPixels(32, 32, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixel);
+    const expected = [64, 128, 191, 255];
+    const pixelMatches = expected.every((value, i) => Math.abs(pixel[i] - value) <= 1);
+
+    assert.deepEqual(
+      {
+        maxVaryingVectors: gl.getParameter(gl.MAX_VARYING_VECTORS),
+        pixel: pixelMatches ? expected : Array.from(pixel),
+        tooMany: /MAX_VARYING_VECTORS/.test(linkLog(tooManyVs, emptyFs)),
+        typeMismatch: /type mismatch/.test(linkLog(packedVs, packedFs.replace('in vec3 c;', 'in vec2 c;').replace('c.z', 'c.y'))),
+        undeclared: /not declared in the vertex shader/.test(linkLog(packedVs, packedFs.replace('in float d;', 'in float e;').replace(', d)', ', e)'))),
+      },
+      {
+        maxVaryingVectors: 15,
+        pixel: expected,
+        tooMany: true,
+        typeMismatch: true,
+        undeclared: true,
+      }
+    );
+  } finally {
+    gl.destroy();
+  }
+});
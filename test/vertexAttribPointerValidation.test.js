@@ -0,0 +1,48 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('vertexAttribPointer validates stride, size, type and alignment', async () => {
+  const gl = await webGL2();
+  try {
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+
+    const errorFor = (call) => {
+      call();
+      return gl.getError();
+    };
+    const errors = [
+      errorFor(() => gl.vertexAttribPointer(0, 4, gl.FLOAT, false, 16, 8)),
+      errorFor(() => gl.vertexAttribPointer(0, 4, gl.FLOAT, false, 256, 0)),
+      errorFor(() => gl.vertexAttribPointer(0, 5, gl.FLOAT, false, 0, 0)),
+      errorFor(() => gl.vertexAttribPointer(0, 4, gl.FLOAT, false, 0, -4)),
+      errorFor(() => gl.vertexAttribPointer(0, 4, gl.RGBA, false, 0, 0)),
+      errorFor(() => gl.vertexAttribPointer(0, 3, gl.INT_2_10_10_10_REV, true, 0, 0)),
+      errorFor(() => gl.vertexAttribPointer(0, 4, gl.INT_2_10_10_10_REV, true, 4, 0)),
+      errorFor(() => gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 2)),
+      errorFor(() => gl.vertexAttribPointer(0, 2, gl.SHORT, true, 6, 0)),
+      errorFor(() => gl.vertexAttribPointer(0, 2, gl.SHORT, true, 7, 0)),
+      errorFor(() => gl.vertexAttribIPointer(0, 2, gl.FLOAT, 0, 0)),
+      errorFor(() => {
+        gl.bindBuffer(gl.ARRAY_BUFFER, null);
+        gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 4);
+      }),
+    ];
+
+    assert.deepStrictEqual(errors, [
+      gl.NO_ERROR,
+      gl.INVALID_VALUE,
+      gl.INVALID_VALUE,
+      gl.INVALID_VALUE,
+      gl.INVALID_ENUM,
+      gl.INVALID_OPERATION,
+      gl.NO_ERROR,
+      gl.INVALID_OPERATION,
+      gl.NO_ERROR,
+      gl.INVALID_OPERATION,
+      gl.INVALID_ENUM,
+      gl.INVALID_OPERATION,
+    ]);
+  } finally { gl.destroy(); }
+});
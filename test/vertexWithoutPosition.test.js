@@ -0,0 +1,51 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('vertex shader without gl_Position links with a warning and draws at the origin', async () => {
+  const gl = await webGL2({ size: { width: 9, height: 9 } });
+  try {
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `#version 300 es
+    void main() {
+      gl_PointSize = 3.0;
+    }`);
+    gl.compileShader(vs);
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 300 es
+    precision highp float;
+    out vec4 color;
+    void main() {
+      color = vec4(0.0, 1.0, 0.0, 1.0);
+    }`);
+    gl.compileShader(fs);
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    gl.viewport(0, 0, 9, 9);
+    gl.clearColor(0, 0, 0, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    gl.drawArrays(gl.POINTS, 0, 1);
+
+    const pixel = (x, y) => {
+      const out = new Uint8Array(4);
+      gl.readPixels(x, y, 1, 1, gl.RGBA, gl.UNSIGNED_BYTE, out);
+      return Array.from(out);
+    };
+
+    assert.deepEqual(
+      {
+        linked: gl.getProgramParameter(program, gl.LINK_STATUS),
+        warns: gl.getProgramInfoLog(program).includes('does not write gl_Position'),
+        center: pixel(4, 4),
+        corner: pixel(0, 0),
+      },
+      { linked: true, warns: true, center: [0, 255, 0, 255], corner: [0, 0, 0, 255] },
+    );
+  } finally {
+    gl.destroy();
+  }
+});
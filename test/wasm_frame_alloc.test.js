@@ -0,0 +1,70 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('wasm_frame_alloc returns distinct 8-byte aligned pointers', async () => {
+  const gl = await webGL2();
+  try {
+    const ex = gl._instance.exports;
+    const a = ex.wasm_frame_alloc(13);
+    const b = ex.wasm_frame_alloc(24);
+    assert.deepStrictEqual(
+      { nonZero: a !== 0 && b !== 0, aligned: a % 8 === 0 && b % 8 === 0, disjoint: b >= a + 13 },
+      { nonZero: true, aligned: true, disjoint: true }
+    );
+  } finally {
+    resetFrameArena(gl);
+    gl.destroy();
+  }
+});
+
+test('wasm_frame_reset rewinds the arena to its first slot', async () => {
+  const gl = await webGL2();
+  try {
+    const ex = gl._instance.exports;
+    ex.wasm_frame_reset();
+    const first = ex.wasm_frame_alloc(64);
+    ex.wasm_frame_alloc(64);
+    ex.wasm_frame_reset();
+    const again = ex.wasm_frame_alloc(64);
+    assert.strictEqual(again, first);
+  } finally {
+    resetFrameArena(gl);
+    gl.destroy();
+  }
+});
+
+test('wasm_frame_alloc serves requests larger than a chunk', async () => {
+  const gl = await webGL2();
+  try {
+    const ex = gl._instance.exports;
+    const ptr = ex.wasm_frame_alloc(256 * 1024);
+    const mem = new Uint8Array(ex.memory.buffer);
+    mem[ptr + 256 * 1024 - 1] = 0xab;
+    assert.strictEqual(mem[ptr + 256 * 1024 - 1], 0xab);
+  } finally {
+    resetFrameArena(gl);
+    gl.destroy();
+  }
+});
+
+test('staging calls rewind the arena before they return', async () => {
+  const gl = await webGL2();
+  try {
+    const ex = gl._instance.exports;
+    ex.wasm_frame_reset();
+    const first = ex.wasm_frame_alloc(64);
+    ex.wasm_frame_reset();
+    for (let i = 0; i < 10000; i++) {
+      gl.drawBuffers([gl.BACK]);
+    }
+    assert.strictEqual(ex.wasm_frame_alloc(64), first);
+  } finally {
+    resetFrameArena(gl);
+    gl.destroy();
+  }
+});
+
+function resetFrameArena(gl) {
+  gl._instance.exports.wasm_frame_reset();
+}
@@ -9,7 +9,7 @@ import path from 'path';
 test('WASM opcodes: fragment integer varying reads', async (t) => {
   const gl = await webGL2({ debug: 'shaders' });
   try {
-    const vs = `#version 300 es\nlayout(location=0) in ivec4 a; void main(){ gl_Position = vec4(0.0); }`;
+    const vs = `#version 300 es\nlayout(location=0) in ivec4 a_in; flat out ivec4 a; void main(){ a = a_in; gl_Position = vec4(0.0); }`;
     const fs = `#version 300 es\nprecision highp float; flat in ivec4 a; out vec4 fragColor; void main(){ if (a.x == -1) fragColor = vec4(0,1,0,1); else fragColor = vec4(1,0,0,1);} `;
 
     const s_vs = gl.createShader(gl.VERTEX_SHADER); gl.shaderSource(s_vs, vs); gl.compileShader(s_vs); assert.ok(gl.getShaderParameter(s_vs, gl.COMPILE_STATUS));
@@ -0,0 +1,82 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGL2 } from '../index.js';
+
+test('WebGL1 contexts render GLSL ES 1.00 shaders with WebGL1 extensions', async () => {
+  const gl = await webGL2({ webgl1: true });
+  try {
+    gl.viewport(0, 0, 2, 1);
+
+    const vs = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(vs, `
+      attribute vec2 a_pos;
+      varying float v_x;
+      void main() {
+        v_x = a_pos.x;
+        gl_Position = vec4(a_pos, 0.0, 1.0);
+      }`);
+    gl.compileShader(vs);
+
+    const fs = gl.createShader(gl.FRAGMENT_SHADER);
+    gl.shaderSource(fs, `#version 100
+      #extension GL_OES_standard_derivatives : enable
+      precision mediump float;
+      varying float v_x;
+      void main() {
+        gl_FragColor = v_x < 0.0 ? vec4(1.0, 0.0, 0.0, 1.0) : vec4(0.0, 0.0, 1.0, 1.0);
+      }`);
+    gl.compileShader(fs);
+
+    const program = gl.createProgram();
+    gl.attachShader(program, vs);
+    gl.attachShader(program, fs);
+    gl.bindAttribLocation(program, 0, 'a_pos');
+    gl.linkProgram(program);
+    gl.useProgram(program);
+
+    const ext = gl.getExtension('OES_vertex_array_object');
+    const vao = ext.createVertexArrayOES();
+    ext.bindVertexArrayOES(vao);
+
+    const buffer = gl.createBuffer();
+    gl.bindBuffer(gl.ARRAY_BUFFER, buffer);
+    gl.bufferData(gl.ARRAY_BUFFER, new Float32Array([-1, -1, 3, -1, -1, 3]), gl.STATIC_DRAW);
+    gl.enableVertexAttribArray(0);
+    gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0);
+    gl.drawArrays(gl.TRIANGLES, 0, 3);
+
+    const pixels = new Uint8Array(8);
+    gl.readPixels(0, 0, 2, 1, gl.RGBA, gl.UNSIGNED_BYTE, pixels);
+
+    const glsl300 = gl.createShader(gl.VERTEX_SHADER);
+    gl.shaderSource(glsl300, '#version 300 es\nvoid main() { gl_Position = vec4(0.0); }');
+    gl.compileShader(glsl300);
+
+    assert.deepEqual(
+      {
+        compiled: [gl.getShaderParameter(vs, gl.COMPILE_STATUS), gl.getShaderParameter(fs, gl.COMPILE_STATUS)],
+        linked: gl.getProgramParameter(program, gl.LINK_STATUS),
+        pixels: Array.from(pixels),
+        isVertexArray: ext.isVertexArrayOES(vao),
+        createVertexArray: gl.createVertexArray,
+        glsl300Compiled: gl.getShaderParameter(glsl300, gl.COMPILE_STATUS),
+        version: gl.getParameter(gl.VERSION),
+        shadingLanguageVersion: gl.getParameter(gl.SHADING_LANGUAGE_VERSION),
+        derivatives: gl.getSupportedExtensions().includes('OES_standard_derivatives'),
+      },
+      {
+        compiled: [true, true],
+        linked: true,
+        pixels: [255, 0, 0, 255, 0, 0, 255, 255],
+        isVertexArray: true,
+        createVertexArray: undefined,
+        glsl300Compiled: false,
+        version: 'WebGL 1.0 (OpenGL ES 2.0)',
+        shadingLanguageVersion: 'WebGL GLSL ES 1.0',
+        derivatives: true,
+      }
+    );
+  } finally {
+    gl.destroy();
+  }
+});
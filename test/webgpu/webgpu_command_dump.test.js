@@ -0,0 +1,48 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGPU, GPUBufferUsage } from '../../index.js';
+
+test('WebGPU command buffer dump records debug groups and copies', async () => {
+  const gpu = await webGPU();
+  const adapter = await gpu.requestAdapter();
+  const device = await adapter.requestDevice();
+
+  const usage = GPUBufferUsage.COPY_SRC | GPUBufferUsage.COPY_DST;
+  const source = device.createBuffer({ size: 16, usage });
+  const destination = device.createBuffer({ size: 16, usage });
+
+  const encoder = device.createCommandEncoder();
+  encoder.pushDebugGroup('upload');
+  encoder.insertDebugMarker('copy vertices');
+  encoder.copyBufferToBuffer(source, 0, destination, 4, 8);
+  encoder.popDebugGroup();
+  const commandBuffer = encoder.finish();
+
+  const dump = JSON.parse(commandBuffer.dumpCommands());
+  device.queue.submit([commandBuffer]);
+  const afterSubmit = commandBuffer.dumpCommands();
+  device.destroy();
+
+  assert.deepEqual(
+    { dump, afterSubmit },
+    {
+      dump: {
+        commandBuffer: commandBuffer.commandBufferHandle,
+        commands: [
+          { command: 'pushDebugGroup', label: 'upload' },
+          { command: 'insertDebugMarker', label: 'copy vertices' },
+          {
+            command: 'copyBufferToBuffer',
+            source: source.bufferHandle,
+            sourceOffset: 0,
+            destination: destination.bufferHandle,
+            destinationOffset: 4,
+            size: 8,
+          },
+          { command: 'popDebugGroup' },
+        ],
+      },
+      afterSubmit: null,
+    }
+  );
+});
@@ -0,0 +1,43 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGPU } from '../../index.js';
+import { GPUBufferUsage, GPUValidationError } from '../../src/webgpu_context.js';
+
+test('WebGPU device dispatches uncapturederror and lost events', async () => {
+  const gpu = await webGPU();
+  const adapter = await gpu.requestAdapter();
+  const device = await adapter.requestDevice();
+
+  const uncaptured = [];
+  device.addEventListener('uncapturederror', (e) => uncaptured.push(e.error));
+  let lostEvent = null;
+  device.addEventListener('lost', (e) => { lostEvent = e.info; });
+
+  // Invalid usage combination, raised outside any error scope
+  device.createBuffer({ size: 4, usage: GPUBufferUsage.MAP_READ | GPUBufferUsage.VERTEX });
+
+  device.destroy();
+  const lost = await device.lost;
+
+  // No longer delivered to the destroyed device
+  const other = await (await gpu.requestAdapter()).requestDevice();
+  other.createBuffer({ size: 4, usage: GPUBufferUsage.MAP_READ | GPUBufferUsage.VERTEX });
+  other.destroy();
+
+  assert.deepEqual(
+    {
+      uncapturedCount: uncaptured.length,
+      isValidationError: uncaptured[0] instanceof GPUValidationError,
+      lostReason: lost.reason,
+      lostMessage: lost.message.length > 0,
+      lostEventMatches: lostEvent === lost,
+    },
+    {
+      uncapturedCount: 1,
+      isValidationError: true,
+      lostReason: 'destroyed',
+      lostMessage: true,
+      lostEventMatches: true,
+    }
+  );
+});
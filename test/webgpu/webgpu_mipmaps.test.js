@@ -0,0 +1,67 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGPU, GPUBufferUsage, GPUMapMode, GPUTextureUsage } from '../../index.js';
+import { GPUValidationError } from '../../src/webgpu_context.js';
+
+test('WebGPU device generates mip chains and blits between formats', async () => {
+  const gpu = await webGPU();
+  const adapter = await gpu.requestAdapter();
+  const device = await adapter.requestDevice();
+
+  const usage = GPUTextureUsage.COPY_SRC | GPUTextureUsage.COPY_DST | GPUTextureUsage.TEXTURE_BINDING;
+  const texture = device.createTexture({ size: [4, 4], format: 'rgba8unorm', mipLevelCount: 3, usage });
+
+  // Even rows black, odd rows (200, 100, 40): every 2x2 block averages the same
+  const pixels = new Uint8Array(4 * 4 * 4);
+  for (let y = 0; y < 4; y++) {
+    for (let x = 0; x < 4; x++) {
+      pixels.set(y % 2 ? [200, 100, 40, 255] : [0, 0, 0, 255], (y * 4 + x) * 4);
+    }
+  }
+  device.queue.writeTexture({ texture }, pixels, { bytesPerRow: 16 }, [4, 4]);
+  device.generateMipmaps(texture);
+
+  const float = device.createTexture({ size: [2, 2], format: 'r32float', usage });
+  device.blitTexture({ texture, mipLevel: 2 }, { texture: float }, { filter: 'linear' });
+
+  const readback = device.createBuffer({ size: 768, usage: GPUBufferUsage.COPY_DST | GPUBufferUsage.MAP_READ });
+  const encoder = device.createCommandEncoder();
+  encoder.copyTextureToBuffer({ texture, mipLevel: 1 }, { buffer: readback, bytesPerRow: 256 }, [2, 2]);
+  encoder.copyTextureToBuffer({ texture, mipLevel: 2 }, { buffer: readback, offset: 512 }, [1, 1]);
+  encoder.copyTextureToBuffer({ texture: float }, { buffer: readback, offset: 640 }, [1, 1]);
+  device.queue.submit([encoder.finish()]);
+  await readback.mapAsync(GPUMapMode.READ);
+  const bytes = readback.getMappedRange();
+  const level1 = [0, 4, 256, 260].map((offset) => Array.from(bytes.slice(offset, offset + 4)));
+  const level2 = Array.from(bytes.slice(512, 516));
+  const red = new Float32Array(bytes.slice(640, 644).buffer)[0];
+  readback.unmap();
+
+  // Depth formats have no color texels to filter
+  const depth = device.createTexture({
+    size: [4, 4],
+    format: 'depth32float',
+    mipLevelCount: 2,
+    usage: GPUTextureUsage.RENDER_ATTACHMENT,
+  });
+  device.pushErrorScope('validation');
+  device.generateMipmaps(depth);
+  const depthError = await device.popErrorScope();
+
+  device.destroy();
+
+  assert.deepEqual(
+    {
+      level1,
+      level2,
+      red: Math.round(red * 255),
+      depthError: depthError instanceof GPUValidationError,
+    },
+    {
+      level1: Array(4).fill([100, 50, 20, 255]),
+      level2: [100, 50, 20, 255],
+      red: 100,
+      depthError: true,
+    }
+  );
+});
@@ -0,0 +1,96 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGPU, GPUBufferUsage, GPUMapMode, GPUTextureUsage } from '../../index.js';
+
+test('WebGPU render passes honor viewport and blend constant commands', async () => {
+  const gpu = await webGPU();
+  const adapter = await gpu.requestAdapter();
+  const device = await adapter.requestDevice();
+
+  const module = device.createShaderModule({
+    code: `
+      @vertex
+      fn vs_main(@location(0) pos: vec2<f32>) -> @builtin(position) vec4<f32> {
+        return vec4<f32>(pos, 0.0, 1.0);
+      }
+
+      @fragment
+      fn fs_main() -> @location(0) vec4<f32> {
+        return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+      }
+    `,
+  });
+  // Writes the blend constant, whatever the fragment shader outputs
+  const pipeline = device.createRenderPipeline({
+    layout: 'auto',
+    vertex: {
+      module,
+      entryPoint: 'vs_main',
+      buffers: [{ arrayStride: 8, attributes: [{ format: 'float32x2', offset: 0, shaderLocation: 0 }] }],
+    },
+    fragment: {
+      module,
+      entryPoint: 'fs_main',
+      targets: [{
+        format: 'rgba8unorm',
+        blend: {
+          color: { srcFactor: 'constant', dstFactor: 'zero', operation: 'add' },
+          alpha: { srcFactor: 'constant', dstFactor: 'zero', operation: 'add' },
+        },
+      }],
+    },
+  });
+
+  const vertexData = new Float32Array([-1, -1, 1, -1, -1, 1, -1, 1, 1, -1, 1, 1]);
+  const vertexBuffer = device.createBuffer({
+    size: vertexData.byteLength,
+    usage: GPUBufferUsage.VERTEX | GPUBufferUsage.COPY_DST,
+  });
+  device.queue.writeBuffer(vertexBuffer, 0, vertexData);
+
+  const target = device.createTexture({
+    size: [4, 1],
+    format: 'rgba8unorm',
+    usage: GPUTextureUsage.RENDER_ATTACHMENT | GPUTextureUsage.COPY_SRC,
+  });
+  const readback = device.createBuffer({ size: 16, usage: GPUBufferUsage.COPY_DST | GPUBufferUsage.MAP_READ });
+
+  const encoder = device.createCommandEncoder();
+  const pass = encoder.beginRenderPass({
+    colorAttachments: [{
+      view: target.createView(),
+      clearValue: { r: 0, g: 0, b: 0, a: 0 },
+      loadOp: 'clear',
+      storeOp: 'store',
+    }],
+  });
+  pass.setPipeline(pipeline);
+  pass.setVertexBuffer(0, vertexBuffer);
+  pass.setViewport(0, 0, 2, 1, 0, 1);
+  pass.setBlendConstant({ r: 1, g: 0, b: 1, a: 1 });
+  pass.setStencilReference(3);
+  pass.draw(6);
+  pass.end();
+  encoder.copyTextureToBuffer({ texture: target }, { buffer: readback, bytesPerRow: 256 }, [4, 1]);
+  const commandBuffer = encoder.finish();
+  const stateCommands = JSON.parse(commandBuffer.dumpCommands()).commands
+    .filter(({ command }) => command === 'setBlendConstant' || command === 'setStencilReference');
+  device.queue.submit([commandBuffer]);
+
+  await readback.mapAsync(GPUMapMode.READ);
+  const bytes = readback.getMappedRange();
+  const pixels = [0, 4, 8, 12].map((offset) => Array.from(bytes.slice(offset, offset + 4)));
+  readback.unmap();
+  device.destroy();
+
+  assert.deepEqual(
+    { pixels, stateCommands },
+    {
+      pixels: [[255, 0, 255, 255], [255, 0, 255, 255], [0, 0, 0, 0], [0, 0, 0, 0]],
+      stateCommands: [
+        { command: 'setBlendConstant', color: [1, 0, 1, 1] },
+        { command: 'setStencilReference', reference: 3 },
+      ],
+    }
+  );
+});
@@ -0,0 +1,122 @@
+import test from 'node:test';
+import assert from 'node:assert/strict';
+import { webGPU, GPUBufferUsage, GPUMapMode, GPUTextureUsage } from '../../index.js';
+
+test('WebGPU fragment shaders sample with sampler address modes and mip levels', async () => {
+  const gpu = await webGPU();
+  const adapter = await gpu.requestAdapter();
+  const device = await adapter.requestDevice();
+
+  // Pixel 0: explicit level 0, pixel 1: explicit level 2, pixel 2: mirror-repeat,
+  // pixel 3: implicit level
+  const module = device.createShaderModule({
+    code: `
+      @group(0) @binding(0) var t_tex: texture_2d<f32>;
+      @group(0) @binding(1) var s_tex: sampler;
+
+      @vertex
+      fn vs_main(@location(0) pos: vec2<f32>) -> @builtin(position) vec4<f32> {
+        return vec4<f32>(pos, 0.0, 1.0);
+      }
+
+      @fragment
+      fn fs_main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+        let implicit = textureSample(t_tex, s_tex, vec2<f32>(0.9, 0.5));
+        if (pos.x < 1.0) {
+          return textureSampleLevel(t_tex, s_tex, vec2<f32>(0.1, 0.5), 0.0);
+        }
+        if (pos.x < 2.0) {
+          return textureSampleLevel(t_tex, s_tex, vec2<f32>(0.1, 0.5), 2.0);
+        }
+        if (pos.x < 3.0) {
+          return textureSampleLevel(t_tex, s_tex, vec2<f32>(1.9, 0.5), 0.0);
+        }
+        return implicit;
+      }
+    `,
+  });
+  const pipeline = device.createRenderPipeline({
+    layout: 'auto',
+    vertex: {
+      module,
+      entryPoint: 'vs_main',
+      buffers: [{ arrayStride: 8, attributes: [{ format: 'float32x2', offset: 0, shaderLocation: 0 }] }],
+    },
+    fragment: { module, entryPoint: 'fs_main', targets: [{ format: 'rgba8unorm' }] },
+  });
+
+  // Left half red, right half blue; levels 1 and 2 come from generateMipmaps
+  const texture = device.createTexture({
+    size: [4, 4],
+    format: 'rgba8unorm',
+    mipLevelCount: 3,
+    usage: GPUTextureUsage.TEXTURE_BINDING | GPUTextureUsage.COPY_DST,
+  });
+  const pixels = new Uint8Array(4 * 4 * 4);
+  for (let i = 0; i < 16; i++) {
+    pixels.set(i % 4 < 2 ? [200, 0, 0, 255] : [0, 0, 200, 255], i * 4);
+  }
+  device.queue.writeTexture({ texture }, pixels, { bytesPerRow: 16 }, [4, 4]);
+  device.generateMipmaps(texture);
+
+  const sampler = device.createSampler({
+    addressModeU: 'mirror-repeat',
+    magFilter: 'nearest',
+    minFilter: 'nearest',
+    mipmapFilter: 'nearest',
+  });
+  const bindGroup = device.createBindGroup({
+    layout: pipeline.getBindGroupLayout(0),
+    entries: [
+      { binding: 0, resource: texture.createView() },
+      { binding: 1, resource: sampler },
+    ],
+  });
+
+  const vertexData = new Float32Array([-1, -1, 1, -1, -1, 1, -1, 1, 1, -1, 1, 1]);
+  const vertexBuffer = device.createBuffer({
+    size: vertexData.byteLength,
+    usage: GPUBufferUsage.VERTEX | GPUBufferUsage.COPY_DST,
+  });
+  device.queue.writeBuffer(vertexBuffer, 0, vertexData);
+
+  const target = device.createTexture({
+    size: [4, 1],
+    format: 'rgba8unorm',
+    usage: GPUTextureUsage.RENDER_ATTACHMENT | GPUTextureUsage.COPY_SRC,
+  });
+  const readback = device.createBuffer({ size: 16, usage: GPUBufferUsage.COPY_DST | GPUBufferUsage.MAP_READ });
+
+  const encoder = device.createCommandEncoder();
+  const pass = encoder.beginRenderPass({
+    colorAttachments: [{
+      view: target.createView(),
+      clearValue: { r: 0, g: 0, b: 0, a: 0 },
+      loadOp: 'clear',
+      storeOp: 'store',
+    }],
+  });
+  pass.setPipeline(pipeline);
+  pass.setVertexBuffer(0, vertexBuffer);
+  pass.setBindGroup(0, bindGroup);
+  pass.draw(6);
+  pass.end();
+  encoder.copyTextureToBuffer({ texture: target }, { buffer: readback, bytesPerRow: 256 }, [4, 1]);
+  device.queue.submit([encoder.finish()]);
+
+  await readback.mapAsync(GPUMapMode.READ);
+  const bytes = readback.getMappedRange();
+  const [level0, level2, mirrored, implicit] = [0, 4, 8, 12].map((offset) => Array.from(bytes.slice(offset, offset + 4)));
+  readback.unmap();
+  device.destroy();
+
+  assert.deepEqual(
+    { level0, level2, mirrored, implicit },
+    {
+      level0: [200, 0, 0, 255],
+      level2: [100, 0, 100, 255],
+      mirrored: [200, 0, 0, 255],
+      implicit: [0, 0, 200, 255],
+    }
+  );
+});
@@ -0,0 +1,129 @@
+//! Allocations made by draw calls once their scratch buffers are sized.
+//!
+//! This is its own test binary because counting needs a `#[global_allocator]`,
+//! which would otherwise count for every unit test in the library.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use webgl2::bench::{link_program, CANONICAL_SHADERS};
+use webgl2::gl_constants::{GL_BACK, GL_CCW, GL_NO_ERROR, GL_TRIANGLES};
+use webgl2::naga_wasm_backend::output_layout::VARYING_BUFFER_WORDS;
+use webgl2::wasm_gl_emu::rasterizer::{BlendState, ColorMaskState, DepthState, StencilState};
+use webgl2::wasm_gl_emu::{
+    DebugView, GpuKernel, OwnedFramebuffer, ProcessedVertex, RasterPipeline, Rasterizer,
+    RenderState, ShaderMemoryLayout,
+};
+use webgl2::webgl2_context::{
+    create_context_with_flags, ctx_draw_arrays, ctx_get_error, ctx_use_program, destroy_context,
+};
+
+/// Counts the allocations made by each thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(|count| count.get());
+    f();
+    ALLOCATIONS.with(|count| count.get()) - before
+}
+
+#[test]
+fn test_draw_allocations_do_not_grow_with_triangles() {
+    // Natively the shaders don't run, so this covers vertex fetch, assembly
+    // and clipping of every vertex through the context's pooled buffers
+    let ctx = create_context_with_flags(0, 64, 64);
+    let program = link_program(ctx, &CANONICAL_SHADERS[0]).unwrap();
+    ctx_use_program(ctx, program);
+    let draw = |triangles: i32| {
+        allocations_during(|| {
+            assert_eq!(ctx_draw_arrays(ctx, GL_TRIANGLES, 0, triangles * 3), 0);
+        })
+    };
+
+    // The first draw sizes the scratch for 10k triangles
+    draw(10_000);
+    let one = draw(1);
+    let many = draw(10_000);
+    let error = ctx_get_error(ctx);
+    destroy_context(ctx);
+
+    assert_eq!(error, GL_NO_ERROR);
+    assert_eq!(many, one);
+}
+
+#[test]
+fn test_fragments_reuse_scratch_buffers() {
+    // 10k triangles' worth of fragments allocate nothing once the fragment
+    // scratch has been sized
+    let rasterizer = Rasterizer::default();
+    rasterizer.reserve_scratch(1);
+    let mut kernel = GpuKernel::new();
+    let mut owned_fb = OwnedFramebuffer::new(&mut kernel, 64, 64);
+    let mut fb = owned_fb.as_framebuffer(&mut kernel);
+    // Depth only: there is no fragment shader to read colors from natively
+    fb.color_attachments.clear();
+    let state = RenderState {
+        ctx_handle: 0,
+        memory: ShaderMemoryLayout::default(),
+        viewport: (0, 0, 64, 64),
+        scissor: (0, 0, 64, 64),
+        scissor_enabled: false,
+        uniform_data: &[],
+        prepare_textures: None,
+        blend: BlendState::default(),
+        color_mask: ColorMaskState::default(),
+        depth: DepthState::default(),
+        stencil: StencilState::default(),
+        cull_face_enabled: false,
+        cull_face_mode: GL_BACK,
+        front_face: GL_CCW,
+        debug_view: DebugView::None,
+        dither: false,
+    };
+    let pipeline = RasterPipeline::new();
+    let vertex = |x: f32, y: f32| ProcessedVertex {
+        position: [x, y, 0.0, 1.0],
+        varyings: vec![0.5f32.to_bits(); VARYING_BUFFER_WORDS],
+    };
+    let triangles: Vec<[ProcessedVertex; 3]> = (0..10_000)
+        .map(|i| {
+            let x = (i % 16) as f32 / 8.0 - 1.0;
+            let y = (i / 16 % 16) as f32 / 8.0 - 1.0;
+            [vertex(x, y), vertex(x + 0.125, y), vertex(x, y + 0.125)]
+        })
+        .collect();
+    let allocations = allocations_during(|| {
+        for [v0, v1, v2] in &triangles {
+            rasterizer.rasterize_triangle(&mut fb, v0, v1, v2, &pipeline, &state, &[]);
+        }
+    });
+    assert_eq!(allocations, 0);
+    assert!(rasterizer.take_invocations().fragments > 0);
+}
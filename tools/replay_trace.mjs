@@ -0,0 +1,48 @@
+// Trace replay for `webgl2 diff-trace`.
+//
+// Reads `{ trace, module, dump, out }` as JSON on stdin: the trace file to
+// replay and the index.js of the build to replay it with. Prints
+// `{ draws: [{ call, method, hash }] }`, hashing the draw framebuffer after
+// every draw or clear. With `dump` set to a call index, the replay stops
+// after that call and writes the draw framebuffer to `out` as a PNG.
+
+import fs from 'node:fs';
+import { pathToFileURL } from 'node:url';
+import { PNG } from 'pngjs';
+import { replayTrace, readDrawFramebuffer, hashPixels } from '../src/webgl2_trace.js';
+
+async function readStdin() {
+  const chunks = [];
+  for await (const chunk of process.stdin) chunks.push(chunk);
+  return JSON.parse(Buffer.concat(chunks).toString('utf8'));
+}
+
+function writePng(path, { width, height, pixels }) {
+  const png = new PNG({ width, height });
+  // readPixels rows go bottom-up
+  for (let y = 0; y < height; y++) {
+    const row = pixels.subarray((height - 1 - y) * width * 4, (height - y) * width * 4);
+    png.data.set(row, y * width * 4);
+  }
+  fs.writeFileSync(path, PNG.sync.write(png));
+}
+
+const { trace: tracePath, module, dump = null, out = null } = await readStdin();
+const trace = JSON.parse(fs.readFileSync(tracePath, 'utf8'));
+const { webGL2 } = await import(pathToFileURL(module).href);
+const gl = await webGL2({ size: { width: trace.width, height: trace.height } });
+try {
+  const draws = [];
+  replayTrace(gl, trace, {
+    until: dump ?? Infinity,
+    onDraw: (call) => {
+      if (dump === null) {
+        draws.push({ call, method: trace.calls[call].method, hash: hashPixels(readDrawFramebuffer(gl).pixels) });
+      }
+    },
+  });
+  if (dump !== null) writePng(out, readDrawFramebuffer(gl));
+  process.stdout.write(JSON.stringify({ draws }));
+} finally {
+  gl.destroy();
+}